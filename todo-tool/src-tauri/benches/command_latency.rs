@@ -0,0 +1,13 @@
+//! Lookup, snapshot and common command latency with 20k tasks:
+//! `cargo bench --no-default-features --features bench --bench command_latency`
+
+const TASKS: usize = 20_000;
+const ROUNDS: usize = 200;
+
+fn main() {
+    let root = tempfile::tempdir().expect("temp data dir");
+    println!("{TASKS} tasks, mean of {ROUNDS} calls each");
+    for (name, elapsed) in todo_tool_lib::bench::command_latency(root.path(), TASKS, ROUNDS) {
+        println!("{name}: {elapsed:?}");
+    }
+}
@@ -0,0 +1,180 @@
+use std::path::Path;
+
+use crate::models::{Task, Timestamp};
+use crate::storage::{append_jsonl, compact_jsonl, read_jsonl, StorageError};
+
+const ACTIVITY_LOG_FILE: &str = "activity.jsonl";
+const ACTIVITY_LOG_LIMIT: usize = 5_000;
+
+/// Fields every save touches or that follow from others; they never count as an edit.
+const UNTRACKED_FIELDS: [&str; 4] = ["updated_at", "completed", "completed_at", "progress"];
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ActivityKind {
+    Created,
+    /// Top-level `Task` fields the edit changed, e.g. `["due_at", "notes"]`.
+    Edited {
+        fields: Vec<String>,
+    },
+    Completed,
+    Reopened,
+    Snoozed {
+        until: Timestamp,
+    },
+}
+
+/// One line of the timeline; `title` is the task's title at the time.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ActivityEntry {
+    pub at: Timestamp,
+    pub task_id: String,
+    pub title: String,
+    #[serde(flatten)]
+    pub kind: ActivityKind,
+}
+
+impl ActivityEntry {
+    pub fn new(task: &Task, kind: ActivityKind, at: Timestamp) -> Self {
+        Self {
+            at,
+            task_id: task.id.clone(),
+            title: task.title.clone(),
+            kind,
+        }
+    }
+}
+
+/// Top-level fields that differ between two versions of a task, sorted by name.
+pub fn changed_fields(previous: &Task, next: &Task) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(before)), Ok(serde_json::Value::Object(after))) =
+        (serde_json::to_value(previous), serde_json::to_value(next))
+    else {
+        return Vec::new();
+    };
+    let mut fields: Vec<String> = after
+        .iter()
+        .filter(|(key, _)| !UNTRACKED_FIELDS.contains(&key.as_str()))
+        .filter(|(key, value)| before.get(*key) != Some(*value))
+        .map(|(key, _)| key.clone())
+        .collect();
+    fields.sort();
+    fields
+}
+
+/// What saving `next` over `previous` did: completed or reopened, then the other edits.
+pub fn edit_activity(previous: &Task, next: &Task, at: Timestamp) -> Vec<ActivityEntry> {
+    let mut entries = Vec::new();
+    match (previous.completed, next.completed) {
+        (false, true) => entries.push(ActivityEntry::new(next, ActivityKind::Completed, at)),
+        (true, false) => entries.push(ActivityEntry::new(next, ActivityKind::Reopened, at)),
+        _ => {}
+    }
+    let fields = changed_fields(previous, next);
+    if !fields.is_empty() {
+        entries.push(ActivityEntry::new(
+            next,
+            ActivityKind::Edited { fields },
+            at,
+        ));
+    }
+    entries
+}
+
+/// Appends to `activity.jsonl`; nightly maintenance trims it to `ACTIVITY_LOG_LIMIT` entries.
+pub fn append_activity(root: &Path, entries: &[ActivityEntry]) -> Result<(), StorageError> {
+    append_jsonl(&root.join(ACTIVITY_LOG_FILE), entries)
+}
+
+/// Keeps the newest `ACTIVITY_LOG_LIMIT` entries; returns how many lines were dropped.
+pub fn compact_activity(root: &Path) -> Result<usize, StorageError> {
+    compact_jsonl::<ActivityEntry>(&root.join(ACTIVITY_LOG_FILE), ACTIVITY_LOG_LIMIT)
+}
+
+/// Newest first, optionally only one task's; unparsable lines are skipped.
+pub fn read_activity(
+    root: &Path,
+    task_id: Option<&str>,
+    limit: usize,
+) -> Result<Vec<ActivityEntry>, StorageError> {
+    let entries: Vec<ActivityEntry> = read_jsonl(&root.join(ACTIVITY_LOG_FILE))?;
+    Ok(entries
+        .into_iter()
+        .rev()
+        .filter(|entry| task_id.is_none_or(|id| entry.task_id == id))
+        .take(limit)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+    use std::fs;
+
+    fn task(title: &str, completed: bool) -> Task {
+        test_support::task("a").title(title).completed(completed)
+    }
+
+    #[test]
+    fn edits_list_changed_fields_and_completion_flips() {
+        let before = task("draft", false);
+        let mut after = task("final", true);
+        after.completed_at = Some(5);
+        after.updated_at = 5;
+        after.due_at = Some(100);
+        let entries = edit_activity(&before, &after, 5);
+        assert_eq!(entries[0].kind, ActivityKind::Completed);
+        assert_eq!(
+            entries[1].kind,
+            ActivityKind::Edited {
+                fields: vec!["due_at".to_string(), "title".to_string()]
+            }
+        );
+        assert_eq!(entries[1].title, "final");
+
+        let reopened = edit_activity(&after, &task("final", false), 6);
+        assert_eq!(reopened.len(), 2);
+        assert_eq!(reopened[0].kind, ActivityKind::Reopened);
+        assert!(edit_activity(&before, &before, 7).is_empty());
+    }
+
+    #[test]
+    fn log_reads_back_newest_first_per_task() {
+        let root = tempfile::tempdir().unwrap();
+        let path = root.path();
+        assert!(read_activity(path, None, 10).unwrap().is_empty());
+        let a = task("a", false);
+        let mut b = task("b", false);
+        b.id = "b".to_string();
+        append_activity(
+            path,
+            &[
+                ActivityEntry::new(&a, ActivityKind::Created, 1),
+                ActivityEntry::new(&b, ActivityKind::Created, 2),
+                ActivityEntry::new(&a, ActivityKind::Snoozed { until: 60 }, 3),
+            ],
+        )
+        .unwrap();
+        fs::write(
+            path.join(ACTIVITY_LOG_FILE),
+            fs::read_to_string(path.join(ACTIVITY_LOG_FILE)).unwrap() + "oops\n",
+        )
+        .unwrap();
+
+        let mine: Vec<Timestamp> = read_activity(path, Some("a"), 10)
+            .unwrap()
+            .iter()
+            .map(|entry| entry.at)
+            .collect();
+        assert_eq!(mine, [3, 1]);
+        let recent = read_activity(path, None, 2).unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[1].task_id, "b");
+        let line = fs::read_to_string(path.join(ACTIVITY_LOG_FILE)).unwrap();
+        assert!(line.contains(r#""kind":"snoozed","until":60"#));
+
+        assert_eq!(compact_activity(path).unwrap(), 1);
+        assert_eq!(read_activity(path, None, 10).unwrap().len(), 3);
+    }
+}
@@ -1,7 +1,11 @@
 use chrono::{Local, TimeZone};
 use serde::{Deserialize, Serialize};
 
-use crate::models::{Project, ReminderKind, RepeatRule, Settings, Task, Timestamp};
+use crate::comments::{latest_comments, single_line};
+use crate::models::{Project, ReminderKind, RepeatRule, Settings, Tag, Task, Timestamp};
+use crate::tags::tag_details;
+
+pub mod providers;
 
 // Legacy placeholders (v1/v2/v3 prompt style).
 const PLACEHOLDER_NOW_LEGACY: &str = "{{mustdo_now}}";
@@ -173,7 +177,12 @@ fn build_project_list_block(projects: &[Project]) -> String {
     serde_json::to_string(&list).unwrap_or_else(|_| "[]".to_string())
 }
 
-fn build_open_tasks_block(tasks: &[Task], projects: &[Project]) -> String {
+fn build_open_tasks_block(
+    tasks: &[Task],
+    projects: &[Project],
+    tag_defs: &[Tag],
+    include_comments: bool,
+) -> String {
     use std::collections::HashMap;
 
     let project_name_by_id: HashMap<&str, &str> = projects
@@ -183,21 +192,29 @@ fn build_open_tasks_block(tasks: &[Task], projects: &[Project]) -> String {
 
     let mut open: Vec<&Task> = tasks.iter().filter(|t| !t.completed).collect();
     // Helpful ordering: due soon + important first.
-    open.sort_by_key(|t| (t.due_at, !t.important, t.created_at));
+    open.sort_by_key(|t| (t.due_sort_key(), !t.important, t.created_at));
 
     let mut out = String::new();
     out.push('[');
 
     let mut first = true;
     for task in open {
-        let entry = serde_json::json!({
+        let mut entry = serde_json::json!({
           "project_id": task.project_id,
           "project_name": project_name_by_id.get(task.project_id.as_str()).copied().unwrap_or(""),
           "title": task.title,
-          "due_at": format_local(task.due_at),
+          "due_at": task.due_at.map(format_local),
           "important": task.important,
-          "tags": task.tags,
+          // Name, `parent/child` path and color, so the model sees how tags are organized.
+          "tags": tag_details(&task.tags, tag_defs),
         });
+        if include_comments && !task.comments.is_empty() {
+            let comments: Vec<String> = latest_comments(task)
+                .iter()
+                .map(|c| format!("{} {}", format_local(c.created_at), single_line(c)))
+                .collect();
+            entry["comments"] = serde_json::json!(comments);
+        }
         let line = match serde_json::to_string(&entry) {
             Ok(s) => s,
             Err(_) => continue,
@@ -249,6 +266,7 @@ pub fn build_prompt(
     now: Timestamp,
     projects: &[Project],
     tasks: &[Task],
+    tag_defs: &[Tag],
 ) -> (String, String) {
     let system = [
         "你是 MustDo（必做清单）里的任务理解与基础数据补充助手。",
@@ -337,7 +355,8 @@ pub fn build_prompt(
 
     let now_string = format_local(now);
     let project_list = build_project_list_block(projects);
-    let open_tasks = build_open_tasks_block(tasks, projects);
+    let open_tasks =
+        build_open_tasks_block(tasks, projects, tag_defs, settings.ai_include_comments);
     let selected_reminder = build_user_selected_reminder_block(input, now);
     let selected_repeat = build_user_selected_repeat_block(input);
 
@@ -504,73 +523,89 @@ fn plan_from_value(value: serde_json::Value) -> Result<AiPlan, String> {
     })
 }
 
+/// Sends one system + user exchange to `settings.ai_provider` and returns the reply text;
+/// `api_key` is `None` for providers that need none.
 #[cfg(all(feature = "app", not(test)))]
-pub async fn plan_with_deepseek(
+pub async fn complete_with_provider(
     settings: &Settings,
-    input: &AiPlanRequest,
-    projects: &[Project],
-    tasks: &[Task],
-) -> Result<AiPlan, String> {
+    api_key: Option<&str>,
+    system: &str,
+    user: &str,
+) -> Result<String, String> {
     use std::time::Duration;
 
-    let api_key = settings.deepseek_api_key.trim();
-    if api_key.is_empty() {
-        return Err("missing deepseek api key".to_string());
+    let provider = providers::provider(settings.ai_provider);
+    let name = provider.name();
+    let api_key = api_key.map(str::trim).filter(|key| !key.is_empty());
+    if provider.api_key_secret().is_some() && api_key.is_none() {
+        return Err(format!("missing {name} api key"));
     }
 
-    let now = chrono::Utc::now().timestamp();
-    let (system, user) = build_prompt(settings, input, now, projects, tasks);
-
     let model = settings.ai_model.trim();
     let model = if model.is_empty() {
-        "deepseek-chat"
+        provider.default_model()
     } else {
         model
     };
-
-    let payload = serde_json::json!({
-        "model": model,
-        "temperature": 0.2,
-        "max_tokens": 1200,
-        "stream": false,
-        "messages": [
-          { "role": "system", "content": system },
-          { "role": "user", "content": user }
-        ]
-    });
+    let base_url = providers::base_url(provider, &settings.ai_base_url);
+    let request = provider.chat_request(&base_url, model, api_key, system, user);
 
     let client = reqwest::Client::builder()
         .timeout(Duration::from_secs(45))
         .build()
         .map_err(|err| format!("failed to build http client: {err}"))?;
 
-    let resp = client
-        .post("https://api.deepseek.com/v1/chat/completions")
-        .bearer_auth(api_key)
-        .json(&payload)
+    let mut builder = client.post(&request.url).json(&request.body);
+    for (header, value) in &request.headers {
+        builder = builder.header(*header, value);
+    }
+    let resp = builder
         .send()
         .await
-        .map_err(|err| format!("deepseek request failed: {err}"))?;
+        .map_err(|err| format!("{name} request failed: {err}"))?;
 
     let status = resp.status();
     let text = resp
         .text()
         .await
-        .map_err(|err| format!("failed to read deepseek response: {err}"))?;
+        .map_err(|err| format!("failed to read {name} response: {err}"))?;
 
     if !status.is_success() {
-        return Err(format!("deepseek http {status}: {text}"));
+        return Err(format!("{name} http {status}: {text}"));
     }
 
     let value: serde_json::Value =
-        serde_json::from_str(&text).map_err(|err| format!("invalid deepseek json: {err}"))?;
+        serde_json::from_str(&text).map_err(|err| format!("invalid {name} json: {err}"))?;
 
-    let content = value["choices"][0]["message"]["content"]
-        .as_str()
-        .unwrap_or("")
-        .trim();
+    Ok(provider.reply_text(&value).unwrap_or_default())
+}
 
-    parse_plan_from_text(content)
+#[cfg(all(feature = "app", not(test)))]
+pub async fn plan_with_provider(
+    settings: &Settings,
+    api_key: Option<&str>,
+    input: &AiPlanRequest,
+    now: i64,
+    projects: &[Project],
+    tasks: &[Task],
+    tag_defs: &[Tag],
+) -> Result<AiPlan, String> {
+    let (system, user) = build_prompt(settings, input, now, projects, tasks, tag_defs);
+    let content = complete_with_provider(settings, api_key, &system, &user).await?;
+    parse_plan_from_text(content.trim())
+}
+
+/// The JSON object in a model reply: the whole (possibly fenced) text, else its first `{...}`.
+pub fn reply_json(text: &str) -> Option<serde_json::Value> {
+    let trimmed = text.trim();
+    let candidate = strip_fenced_code_block(trimmed).unwrap_or(trimmed);
+    serde_json::from_str::<serde_json::Value>(candidate)
+        .ok()
+        .filter(serde_json::Value::is_object)
+        .or_else(|| {
+            extract_first_json_object(candidate)
+                .and_then(|extracted| serde_json::from_str(extracted).ok())
+        })
 }
 
 fn sanitize_plan(mut plan: AiPlan) -> AiPlan {
@@ -659,6 +694,7 @@ fn extract_first_json_object(text: &str) -> Option<&str> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::test_support::task;
 
     #[test]
     fn build_prompt_supports_legacy_placeholders() {
@@ -679,7 +715,7 @@ mod tests {
             reminder_offset_minutes: 10,
         };
 
-        let (_system, user) = build_prompt(&settings, &req, 1700000000, &[], &[]);
+        let (_system, user) = build_prompt(&settings, &req, 1700000000, &[], &[], &[]);
         assert!(user.contains("CUSTOM"));
         assert!(user.contains("买牛奶 #生活"));
         assert!(user.contains("due_at_unix: 123"));
@@ -689,6 +725,40 @@ mod tests {
         assert!(user.contains("\"生活\""));
     }
 
+    #[test]
+    fn open_tasks_block_includes_latest_comments_only_when_opted_in() {
+        let mut task = task("t").title("report").due(1);
+        task.comments = (1..=4)
+            .map(|n| crate::models::Comment {
+                id: n.to_string(),
+                text: format!("update {n}"),
+                created_at: n,
+            })
+            .collect();
+        let tasks = vec![task];
+
+        let without = build_open_tasks_block(&tasks, &[], &[], false);
+        assert!(!without.contains("comments"));
+
+        let with = build_open_tasks_block(&tasks, &[], &[], true);
+        assert!(with.contains("\"comments\""));
+        assert!(!with.contains("update 1"));
+        assert!(with.contains("update 2") && with.contains("update 4"));
+    }
+
+    #[test]
+    fn open_tasks_block_describes_tags_with_their_definitions() {
+        let task = task("t").title("report").tags(&["client"]);
+        let defs: Vec<Tag> = serde_json::from_str(
+            r##"[{"id":"w","name":"work","color":"#336699"},{"id":"c","name":"client","color":"#ff0000","parent_id":"w"}]"##,
+        )
+        .unwrap();
+        let block = build_open_tasks_block(&[task], &[], &defs, false);
+        assert!(block.contains(
+            r##""tags":[{"color":"#ff0000","name":"client","parent":"work","path":"work/client"}]"##
+        ));
+    }
+
     #[test]
     fn build_prompt_supports_new_placeholders() {
         let mut settings = Settings::default();
@@ -716,7 +786,7 @@ mod tests {
             reminder_offset_minutes: 10,
         };
 
-        let (_system, user) = build_prompt(&settings, &req, 1700000000, &[], &[]);
+        let (_system, user) = build_prompt(&settings, &req, 1700000000, &[], &[], &[]);
 
         // Placeholders should not leak to the final prompt.
         assert!(!user.contains("{{Now}}"));
@@ -0,0 +1,292 @@
+use serde_json::{json, Value};
+
+use crate::models::AiProviderKind;
+use crate::secrets;
+
+const TEMPERATURE: f64 = 0.2;
+const MAX_TOKENS: u32 = 1200;
+
+/// One HTTP call to a chat API, built without touching the network so it can be tested.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatRequest {
+    pub url: String,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: Value,
+}
+
+/// A chat API the prompt/parse pipeline can run against: `build_prompt` output goes in through
+/// `chat_request`, and `reply_text` hands the answer to `parse_plan_from_text`.
+pub trait AiProvider: Sync {
+    /// Name used in error messages and logs.
+    fn name(&self) -> &'static str;
+    /// Endpoint root used when `settings.ai_base_url` is empty.
+    fn default_base_url(&self) -> &'static str;
+    /// Model used when `settings.ai_model` is empty.
+    fn default_model(&self) -> &'static str;
+    /// Secret holding the API key; `None` for providers that need none.
+    fn api_key_secret(&self) -> Option<&'static str>;
+    fn chat_request(
+        &self,
+        base_url: &str,
+        model: &str,
+        api_key: Option<&str>,
+        system: &str,
+        user: &str,
+    ) -> ChatRequest;
+    /// The assistant's text out of a successful response body.
+    fn reply_text(&self, response: &Value) -> Option<String>;
+}
+
+/// DeepSeek, OpenAI and the many gateways that copy OpenAI's `/chat/completions`.
+pub struct OpenAiCompatible {
+    name: &'static str,
+    base_url: &'static str,
+    model: &'static str,
+    secret: &'static str,
+}
+
+impl AiProvider for OpenAiCompatible {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn default_base_url(&self) -> &'static str {
+        self.base_url
+    }
+
+    fn default_model(&self) -> &'static str {
+        self.model
+    }
+
+    fn api_key_secret(&self) -> Option<&'static str> {
+        Some(self.secret)
+    }
+
+    fn chat_request(
+        &self,
+        base_url: &str,
+        model: &str,
+        api_key: Option<&str>,
+        system: &str,
+        user: &str,
+    ) -> ChatRequest {
+        ChatRequest {
+            url: format!("{base_url}/chat/completions"),
+            headers: api_key
+                .map(|key| ("Authorization", format!("Bearer {key}")))
+                .into_iter()
+                .collect(),
+            body: json!({
+                "model": model,
+                "temperature": TEMPERATURE,
+                "max_tokens": MAX_TOKENS,
+                "stream": false,
+                "messages": [
+                  { "role": "system", "content": system },
+                  { "role": "user", "content": user }
+                ]
+            }),
+        }
+    }
+
+    fn reply_text(&self, response: &Value) -> Option<String> {
+        response["choices"][0]["message"]["content"]
+            .as_str()
+            .map(str::to_string)
+    }
+}
+
+/// Anthropic's Messages API: the system prompt is a top-level field, not a message.
+pub struct Anthropic;
+
+impl AiProvider for Anthropic {
+    fn name(&self) -> &'static str {
+        "anthropic"
+    }
+
+    fn default_base_url(&self) -> &'static str {
+        "https://api.anthropic.com/v1"
+    }
+
+    fn default_model(&self) -> &'static str {
+        "claude-3-5-haiku-latest"
+    }
+
+    fn api_key_secret(&self) -> Option<&'static str> {
+        Some(secrets::ANTHROPIC_API_KEY)
+    }
+
+    fn chat_request(
+        &self,
+        base_url: &str,
+        model: &str,
+        api_key: Option<&str>,
+        system: &str,
+        user: &str,
+    ) -> ChatRequest {
+        let mut headers = vec![("anthropic-version", "2023-06-01".to_string())];
+        if let Some(key) = api_key {
+            headers.push(("x-api-key", key.to_string()));
+        }
+        ChatRequest {
+            url: format!("{base_url}/messages"),
+            headers,
+            body: json!({
+                "model": model,
+                "temperature": TEMPERATURE,
+                "max_tokens": MAX_TOKENS,
+                "system": system,
+                "messages": [{ "role": "user", "content": user }]
+            }),
+        }
+    }
+
+    fn reply_text(&self, response: &Value) -> Option<String> {
+        let blocks = response["content"].as_array()?;
+        let text: String = blocks
+            .iter()
+            .filter(|block| block["type"] == "text")
+            .filter_map(|block| block["text"].as_str())
+            .collect();
+        Some(text)
+    }
+}
+
+/// A local Ollama server (`/api/chat`, non-streaming).
+pub struct Ollama;
+
+impl AiProvider for Ollama {
+    fn name(&self) -> &'static str {
+        "ollama"
+    }
+
+    fn default_base_url(&self) -> &'static str {
+        "http://localhost:11434"
+    }
+
+    fn default_model(&self) -> &'static str {
+        "llama3.1"
+    }
+
+    fn api_key_secret(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn chat_request(
+        &self,
+        base_url: &str,
+        model: &str,
+        _api_key: Option<&str>,
+        system: &str,
+        user: &str,
+    ) -> ChatRequest {
+        ChatRequest {
+            url: format!("{base_url}/api/chat"),
+            headers: Vec::new(),
+            body: json!({
+                "model": model,
+                "stream": false,
+                "format": "json",
+                "options": { "temperature": TEMPERATURE, "num_predict": MAX_TOKENS },
+                "messages": [
+                  { "role": "system", "content": system },
+                  { "role": "user", "content": user }
+                ]
+            }),
+        }
+    }
+
+    fn reply_text(&self, response: &Value) -> Option<String> {
+        response["message"]["content"].as_str().map(str::to_string)
+    }
+}
+
+static DEEPSEEK: OpenAiCompatible = OpenAiCompatible {
+    name: "deepseek",
+    base_url: "https://api.deepseek.com/v1",
+    model: "deepseek-chat",
+    secret: secrets::DEEPSEEK_API_KEY,
+};
+
+static OPENAI: OpenAiCompatible = OpenAiCompatible {
+    name: "openai",
+    base_url: "https://api.openai.com/v1",
+    model: "gpt-4o-mini",
+    secret: secrets::OPENAI_API_KEY,
+};
+
+pub fn provider(kind: AiProviderKind) -> &'static dyn AiProvider {
+    match kind {
+        AiProviderKind::Deepseek => &DEEPSEEK,
+        AiProviderKind::OpenAi => &OPENAI,
+        AiProviderKind::Anthropic => &Anthropic,
+        AiProviderKind::Ollama => &Ollama,
+    }
+}
+
+/// `configured` without trailing slashes, or the provider's default when blank.
+pub fn base_url(provider: &dyn AiProvider, configured: &str) -> String {
+    let configured = configured.trim().trim_end_matches('/');
+    if configured.is_empty() {
+        provider.default_base_url().to_string()
+    } else {
+        configured.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn openai_compatible_requests_use_bearer_auth_and_chat_completions() {
+        let deepseek = provider(AiProviderKind::Deepseek);
+        let url = base_url(deepseek, "");
+        let request = deepseek.chat_request(&url, "deepseek-chat", Some("sk"), "sys", "hi");
+        assert_eq!(request.url, "https://api.deepseek.com/v1/chat/completions");
+        assert_eq!(
+            request.headers,
+            vec![("Authorization", "Bearer sk".to_string())]
+        );
+        assert_eq!(request.body["messages"][0]["content"], "sys");
+
+        let openai = provider(AiProviderKind::OpenAi);
+        let url = base_url(openai, " https://gateway.local/v1/ ");
+        let request = openai.chat_request(&url, "gpt", Some("k"), "sys", "hi");
+        assert_eq!(request.url, "https://gateway.local/v1/chat/completions");
+        let reply = json!({ "choices": [{ "message": { "content": "{}" } }] });
+        assert_eq!(openai.reply_text(&reply).as_deref(), Some("{}"));
+    }
+
+    #[test]
+    fn anthropic_and_ollama_shape_requests_and_replies_their_own_way() {
+        let anthropic = provider(AiProviderKind::Anthropic);
+        let request = anthropic.chat_request(
+            anthropic.default_base_url(),
+            "claude",
+            Some("key"),
+            "sys",
+            "hi",
+        );
+        assert_eq!(request.url, "https://api.anthropic.com/v1/messages");
+        assert!(request.headers.contains(&("x-api-key", "key".to_string())));
+        assert_eq!(request.body["system"], "sys");
+        assert_eq!(request.body["messages"].as_array().unwrap().len(), 1);
+        let reply = json!({ "content": [
+            { "type": "text", "text": "{\"title\":" },
+            { "type": "text", "text": "\"x\"}" }
+        ] });
+        assert_eq!(
+            anthropic.reply_text(&reply).as_deref(),
+            Some("{\"title\":\"x\"}")
+        );
+
+        let ollama = provider(AiProviderKind::Ollama);
+        assert_eq!(ollama.api_key_secret(), None);
+        let request = ollama.chat_request(ollama.default_base_url(), "llama", None, "sys", "hi");
+        assert_eq!(request.url, "http://localhost:11434/api/chat");
+        assert!(request.headers.is_empty());
+        let reply = json!({ "message": { "role": "assistant", "content": "ok" } });
+        assert_eq!(ollama.reply_text(&reply).as_deref(), Some("ok"));
+    }
+}
@@ -0,0 +1,330 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Datelike, Local, Timelike};
+
+use crate::storage::{Storage, StorageError};
+
+/// Fixed per-entry cost of a stored ZIP entry: local header (30) + central directory record (46),
+/// plus the name twice.
+const ENTRY_OVERHEAD: u64 = 30 + 46;
+const END_OF_CENTRAL_DIRECTORY: u64 = 22;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ArchiveEstimate {
+    pub files: usize,
+    /// Size of the resulting archive; entries are stored uncompressed, so this is exact.
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ArchiveProgress {
+    pub item: String,
+    pub done: usize,
+    pub total: usize,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+fn io_error(message: String) -> StorageError {
+    StorageError::Io(std::io::Error::other(message))
+}
+
+fn source_size(storage: &Storage, path: &Path) -> Result<u64, StorageError> {
+    match fs::metadata(path) {
+        Ok(meta) => Ok(meta.len()),
+        // Guest mode keeps everything in memory.
+        Err(_) => Ok(storage.read_bytes(path)?.len() as u64),
+    }
+}
+
+pub fn estimate_archive(
+    storage: &Storage,
+    sources: &[(String, PathBuf)],
+) -> Result<ArchiveEstimate, StorageError> {
+    let mut bytes = END_OF_CENTRAL_DIRECTORY;
+    for (name, path) in sources {
+        bytes += ENTRY_OVERHEAD + 2 * name.len() as u64 + source_size(storage, path)?;
+    }
+    Ok(ArchiveEstimate {
+        files: sources.len(),
+        bytes,
+    })
+}
+
+pub fn archive_file_name(now: DateTime<Local>) -> String {
+    format!("mustdo-backups-{}.zip", now.format("%Y%m%d-%H%M%S"))
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// MS-DOS (time, date) as stored in ZIP headers; years before 1980 clamp to 1980.
+fn dos_datetime(now: DateTime<Local>) -> (u16, u16) {
+    let time = (now.hour() << 11) | (now.minute() << 5) | (now.second() / 2);
+    let year = (now.year() - 1980).clamp(0, 127) as u32;
+    let date = (year << 9) | (now.month() << 5) | now.day();
+    (time as u16, date as u16)
+}
+
+struct CentralEntry {
+    name: String,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+// Bit 11: names are UTF-8.
+const FLAGS: u16 = 0x0800;
+const VERSION: u16 = 20;
+
+fn write_local_header(
+    out: &mut impl Write,
+    entry: &CentralEntry,
+    (time, date): (u16, u16),
+) -> std::io::Result<()> {
+    out.write_all(&0x0403_4b50u32.to_le_bytes())?;
+    out.write_all(&VERSION.to_le_bytes())?;
+    out.write_all(&FLAGS.to_le_bytes())?;
+    out.write_all(&0u16.to_le_bytes())?; // stored
+    out.write_all(&time.to_le_bytes())?;
+    out.write_all(&date.to_le_bytes())?;
+    out.write_all(&entry.crc.to_le_bytes())?;
+    out.write_all(&entry.size.to_le_bytes())?;
+    out.write_all(&entry.size.to_le_bytes())?;
+    out.write_all(&(entry.name.len() as u16).to_le_bytes())?;
+    out.write_all(&0u16.to_le_bytes())?;
+    out.write_all(entry.name.as_bytes())
+}
+
+fn write_central_directory(
+    out: &mut impl Write,
+    entries: &[CentralEntry],
+    (time, date): (u16, u16),
+    offset: u32,
+) -> std::io::Result<()> {
+    let mut size = 0u32;
+    for entry in entries {
+        out.write_all(&0x0201_4b50u32.to_le_bytes())?;
+        out.write_all(&VERSION.to_le_bytes())?;
+        out.write_all(&VERSION.to_le_bytes())?;
+        out.write_all(&FLAGS.to_le_bytes())?;
+        out.write_all(&0u16.to_le_bytes())?;
+        out.write_all(&time.to_le_bytes())?;
+        out.write_all(&date.to_le_bytes())?;
+        out.write_all(&entry.crc.to_le_bytes())?;
+        out.write_all(&entry.size.to_le_bytes())?;
+        out.write_all(&entry.size.to_le_bytes())?;
+        out.write_all(&(entry.name.len() as u16).to_le_bytes())?;
+        // Extra field, comment, disk number, internal and external attributes.
+        out.write_all(&[0u8; 12])?;
+        out.write_all(&entry.offset.to_le_bytes())?;
+        out.write_all(entry.name.as_bytes())?;
+        size += 46 + entry.name.len() as u32;
+    }
+    out.write_all(&0x0605_4b50u32.to_le_bytes())?;
+    out.write_all(&[0u8; 4])?;
+    out.write_all(&(entries.len() as u16).to_le_bytes())?;
+    out.write_all(&(entries.len() as u16).to_le_bytes())?;
+    out.write_all(&size.to_le_bytes())?;
+    out.write_all(&offset.to_le_bytes())?;
+    out.write_all(&0u16.to_le_bytes())
+}
+
+fn write_entries(
+    storage: &Storage,
+    sources: &[(String, PathBuf)],
+    file: File,
+    stamp: (u16, u16),
+    bytes_total: u64,
+    progress: &mut impl FnMut(ArchiveProgress),
+) -> Result<(), StorageError> {
+    let mut out = BufWriter::new(file);
+    let mut entries: Vec<CentralEntry> = Vec::with_capacity(sources.len());
+    let mut offset = 0u64;
+    for (index, (name, path)) in sources.iter().enumerate() {
+        let bytes = storage.read_bytes(path)?;
+        let entry = CentralEntry {
+            name: name.clone(),
+            crc: crc32(&bytes),
+            size: bytes.len() as u32,
+            offset: offset as u32,
+        };
+        write_local_header(&mut out, &entry, stamp)?;
+        out.write_all(&bytes)?;
+        offset += 30 + name.len() as u64 + bytes.len() as u64;
+        entries.push(entry);
+        progress(ArchiveProgress {
+            item: name.clone(),
+            done: index + 1,
+            total: sources.len(),
+            bytes_done: offset,
+            bytes_total,
+        });
+    }
+    write_central_directory(&mut out, &entries, stamp, offset as u32)?;
+    out.into_inner()
+        .map_err(|err| io_error(format!("flush archive failed: {err}")))?
+        .sync_all()?;
+    Ok(())
+}
+
+/// Writes `sources` into an uncompressed ZIP at `dest` (via a temp file, so a failed run never
+/// leaves a truncated archive behind). Backups are small JSON files; ZIP is only the container.
+pub fn write_archive(
+    storage: &Storage,
+    sources: &[(String, PathBuf)],
+    dest: &Path,
+    now: DateTime<Local>,
+    mut progress: impl FnMut(ArchiveProgress),
+) -> Result<ArchiveEstimate, StorageError> {
+    let estimate = estimate_archive(storage, sources)?;
+    if estimate.bytes > u64::from(u32::MAX) || sources.len() > usize::from(u16::MAX) {
+        return Err(io_error(format!(
+            "archive too large: {} files, {} bytes",
+            estimate.files, estimate.bytes
+        )));
+    }
+    let tmp = dest.with_extension("zip.tmp");
+    let result = File::create(&tmp)
+        .map_err(StorageError::from)
+        .and_then(|file| {
+            write_entries(
+                storage,
+                sources,
+                file,
+                dos_datetime(now),
+                estimate.bytes,
+                &mut progress,
+            )
+        })
+        .and_then(|_| fs::rename(&tmp, dest).map_err(StorageError::from));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result.map(|_| estimate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn u16_at(bytes: &[u8], at: usize) -> u16 {
+        u16::from_le_bytes([bytes[at], bytes[at + 1]])
+    }
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    /// Walks the central directory and returns (name, contents) per entry, checking CRCs.
+    fn read_zip(bytes: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let eocd = bytes.len() - 22;
+        assert_eq!(u32_at(bytes, eocd), 0x0605_4b50);
+        let count = usize::from(u16_at(bytes, eocd + 10));
+        let mut at = u32_at(bytes, eocd + 16) as usize;
+        let mut out = Vec::new();
+        for _ in 0..count {
+            assert_eq!(u32_at(bytes, at), 0x0201_4b50);
+            let crc = u32_at(bytes, at + 16);
+            let size = u32_at(bytes, at + 24) as usize;
+            let name_len = usize::from(u16_at(bytes, at + 28));
+            let local = u32_at(bytes, at + 42) as usize;
+            let name = String::from_utf8(bytes[at + 46..at + 46 + name_len].to_vec()).unwrap();
+            assert_eq!(u32_at(bytes, local), 0x0403_4b50);
+            let data_at = local + 30 + usize::from(u16_at(bytes, local + 26));
+            let data = bytes[data_at..data_at + size].to_vec();
+            assert_eq!(crc32(&data), crc);
+            out.push((name, data));
+            at += 46 + name_len;
+        }
+        out
+    }
+
+    #[test]
+    fn crc32_and_dos_time_match_reference_values() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+        let now = Local.with_ymd_and_hms(2026, 3, 10, 14, 30, 59).unwrap();
+        assert_eq!(
+            dos_datetime(now),
+            ((14 << 11) | (30 << 5) | 29, (46 << 9) | (3 << 5) | 10)
+        );
+        assert_eq!(archive_file_name(now), "mustdo-backups-20260310-143059.zip");
+    }
+
+    #[test]
+    fn archive_contains_data_settings_and_every_backup() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = Storage::new(root.path().to_path_buf());
+        fs::write(root.path().join("data.json"), b"{\"tasks\":[]}").unwrap();
+        fs::write(root.path().join("settings.json"), b"{}").unwrap();
+        fs::write(root.path().join("secrets.json"), b"{\"smtp\":\"pw\"}").unwrap();
+        fs::create_dir(root.path().join("backups")).unwrap();
+        fs::write(root.path().join("backups/data-1.json"), b"one").unwrap();
+        fs::write(root.path().join("backups/data-2.json"), b"two").unwrap();
+
+        let sources = storage.archive_sources().unwrap();
+        let estimate = estimate_archive(&storage, &sources).unwrap();
+        assert_eq!(estimate.files, 4);
+
+        let out = tempfile::tempdir().unwrap();
+        let dest = out.path().join("all.zip");
+        let mut seen = Vec::new();
+        let now = Local.with_ymd_and_hms(2026, 3, 10, 9, 0, 0).unwrap();
+        let written = write_archive(&storage, &sources, &dest, now, |p| seen.push(p)).unwrap();
+        assert_eq!(written, estimate);
+
+        let bytes = fs::read(&dest).unwrap();
+        assert_eq!(bytes.len() as u64, estimate.bytes);
+        let mut entries = read_zip(&bytes);
+        entries.sort();
+        let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "backups/data-1.json",
+                "backups/data-2.json",
+                "data.json",
+                "settings.json"
+            ]
+        );
+        assert_eq!(entries[0].1, b"one");
+        assert!(!out.path().join("all.zip.tmp").exists());
+
+        assert_eq!(seen.len(), 4);
+        assert_eq!((seen[3].done, seen[3].total), (4, 4));
+        assert_eq!(seen[3].bytes_total, estimate.bytes);
+        assert!(seen.windows(2).all(|w| w[0].bytes_done < w[1].bytes_done));
+    }
+
+    #[test]
+    fn failed_archive_leaves_no_file_behind() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = Storage::new(root.path().to_path_buf());
+        let sources = vec![("data.json".to_string(), root.path().join("missing.json"))];
+        let out = tempfile::tempdir().unwrap();
+        let dest = out.path().join("all.zip");
+        let now = Local.with_ymd_and_hms(2026, 3, 10, 9, 0, 0).unwrap();
+        assert!(write_archive(&storage, &sources, &dest, now, |_| {}).is_err());
+        assert!(fs::read_dir(out.path()).unwrap().next().is_none());
+
+        // No backups directory yet: just the data files.
+        fs::write(root.path().join("data.json"), b"{}").unwrap();
+        assert_eq!(storage.archive_sources().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,276 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::models::{Attachment, Task, TasksFile, Timestamp};
+use crate::storage::{Storage, StorageError};
+
+/// Larger files are refused; attachments are copied into every full backup archive.
+pub const MAX_ATTACHMENT_BYTES: u64 = 100 * 1024 * 1024;
+pub const MAX_ATTACHMENTS_PER_TASK: usize = 20;
+/// Longest piece of a task id or file name kept in a stored file name.
+const MAX_NAME_PART: usize = 64;
+
+/// `raw` reduced to characters that are safe in a file name on every platform.
+fn file_name_part(raw: &str) -> String {
+    let part: String = raw
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_NAME_PART)
+        .collect();
+    let part = part.trim_matches('.');
+    if part.is_empty() {
+        "file".to_string()
+    } else {
+        part.to_string()
+    }
+}
+
+/// Checks that `source` can be attached to `task` and returns its size.
+pub fn check_source(task: &Task, source: &Path) -> Result<u64, String> {
+    if task.attachments.len() >= MAX_ATTACHMENTS_PER_TASK {
+        return Err(format!(
+            "a task can have at most {MAX_ATTACHMENTS_PER_TASK} attachments"
+        ));
+    }
+    let meta = std::fs::metadata(source).map_err(|err| format!("cannot read file: {err}"))?;
+    if !meta.is_file() {
+        return Err("attachment must be a file".to_string());
+    }
+    if meta.len() > MAX_ATTACHMENT_BYTES {
+        return Err(format!(
+            "attachment is larger than {} MB",
+            MAX_ATTACHMENT_BYTES / 1024 / 1024
+        ));
+    }
+    Ok(meta.len())
+}
+
+/// A new attachment record for `source`. The id is unique within `task` (`<now>`, then
+/// `<now>-2`...) and skips stored names for which `taken` is true.
+pub fn new_attachment(
+    task: &Task,
+    source: &Path,
+    size: u64,
+    now: Timestamp,
+    taken: impl Fn(&str) -> bool,
+) -> Attachment {
+    let name = source
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "file".to_string());
+    let stored = |id: &str| {
+        format!(
+            "{}-{}-{}",
+            file_name_part(&task.id),
+            id,
+            file_name_part(&name)
+        )
+    };
+    let free = |id: &str| !task.attachments.iter().any(|a| a.id == id) && !taken(&stored(id));
+    let base = now.to_string();
+    let id = if free(&base) {
+        base
+    } else {
+        (2..)
+            .map(|n| format!("{base}-{n}"))
+            .find(|id| free(id))
+            .expect("unbounded suffix search")
+    };
+    Attachment {
+        file: stored(&id),
+        id,
+        name,
+        size,
+        added_at: now,
+    }
+}
+
+fn referenced_files(data: &TasksFile, out: &mut BTreeSet<String>) {
+    let trashed = data.trash.iter().map(|entry| &entry.task);
+    for task in data.tasks.iter().chain(trashed) {
+        out.extend(task.attachments.iter().map(|a| a.file.clone()));
+    }
+}
+
+/// Deletes files in `attachments/` that neither `data` (tasks and trash), the yearly task
+/// archives in `archive/` nor any backup in `backups/` refers to (leftovers of removed
+/// attachments, purged tasks and interrupted copies). Files a backup still uses are kept so
+/// restoring it brings the attachments back, and archived tasks keep theirs for
+/// `unarchive_task`. Returns the removed names.
+pub fn collect_garbage(storage: &Storage, data: &TasksFile) -> Result<Vec<String>, StorageError> {
+    let mut keep = BTreeSet::new();
+    referenced_files(data, &mut keep);
+    for year in storage.task_archive_years()? {
+        match storage.load_task_archive(year) {
+            Ok(tasks) => keep.extend(
+                tasks
+                    .iter()
+                    .flat_map(|task| task.attachments.iter().map(|a| a.file.clone())),
+            ),
+            Err(err) => {
+                log::warn!(
+                    "attachments: gc skipped, unreadable task archive year={year} err={err}"
+                );
+                return Ok(Vec::new());
+            }
+        }
+    }
+    for (name, ..) in storage.list_backups()? {
+        match storage.read_backup(&name) {
+            Ok(backup) => referenced_files(&backup, &mut keep),
+            Err(err) => {
+                // Without knowing what the backup references nothing can be proven unused.
+                log::warn!("attachments: gc skipped, unreadable backup name={name} err={err}");
+                return Ok(Vec::new());
+            }
+        }
+    }
+    let mut removed = Vec::new();
+    for file in storage.list_attachments()? {
+        if keep.contains(&file) {
+            continue;
+        }
+        storage.remove_attachment(&file)?;
+        removed.push(file);
+    }
+    if !removed.is_empty() {
+        log::info!(
+            "attachments: removed orphaned files count={}",
+            removed.len()
+        );
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_support, TrashEntry};
+    use std::fs;
+
+    fn task(id: &str) -> Task {
+        test_support::task(id).title("t")
+    }
+
+    #[test]
+    fn new_attachment_builds_unique_safe_names() {
+        let mut owner = task("a/b c");
+        let first = new_attachment(
+            &owner,
+            Path::new("/tmp/Quarterly report (v2).pdf"),
+            3,
+            100,
+            |_| false,
+        );
+        assert_eq!(first.id, "100");
+        assert_eq!(first.name, "Quarterly report (v2).pdf");
+        assert_eq!(first.file, "a_b_c-100-Quarterly_report__v2_.pdf");
+        owner.attachments.push(first.clone());
+
+        let second = new_attachment(&owner, Path::new("/tmp/x.txt"), 1, 100, |_| false);
+        assert_eq!(second.id, "100-2");
+        let third = new_attachment(&owner, Path::new("/tmp/y"), 1, 200, |file| {
+            file == "a_b_c-200-y"
+        });
+        assert_eq!(third.id, "200-2");
+        assert_eq!(file_name_part(".."), "file");
+    }
+
+    #[test]
+    fn check_source_rejects_directories_missing_files_and_full_tasks() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("a.txt");
+        fs::write(&file, b"hello").unwrap();
+        let mut owner = task("t");
+        assert_eq!(check_source(&owner, &file), Ok(5));
+        assert!(check_source(&owner, dir.path()).is_err());
+        assert!(check_source(&owner, &dir.path().join("missing")).is_err());
+        for n in 0..MAX_ATTACHMENTS_PER_TASK {
+            owner
+                .attachments
+                .push(new_attachment(&owner, &file, 5, n as i64, |_| false));
+        }
+        assert!(check_source(&owner, &file).unwrap_err().contains("at most"));
+    }
+
+    #[test]
+    fn collect_garbage_keeps_files_used_by_tasks_or_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Storage::new(dir.path().to_path_buf());
+        storage.ensure_dirs().unwrap();
+        let source = dir.path().join("source.txt");
+        fs::write(&source, b"data").unwrap();
+
+        let mut live = task("live");
+        let mut trashed = task("trashed");
+        let mut backed_up = task("old");
+        for (owner, at) in [(&mut live, 1), (&mut trashed, 3), (&mut backed_up, 2)] {
+            let attachment = new_attachment(owner, &source, 4, at, |_| false);
+            assert_eq!(
+                storage.store_attachment(&source, &attachment.file).unwrap(),
+                4
+            );
+            owner.attachments.push(attachment);
+        }
+        storage.store_attachment(&source, "orphan.txt").unwrap();
+        let backup = TasksFile {
+            schema_version: 1,
+            tasks: vec![backed_up.clone()],
+            projects: Vec::new(),
+            trash: Vec::new(),
+            tags: Vec::new(),
+        };
+        fs::write(
+            dir.path().join("backups/data-1.json"),
+            serde_json::to_vec(&backup).unwrap(),
+        )
+        .unwrap();
+
+        let data = TasksFile {
+            schema_version: 1,
+            tasks: vec![live.clone()],
+            projects: Vec::new(),
+            trash: vec![TrashEntry {
+                task: trashed.clone(),
+                deleted_at: 3,
+            }],
+            tags: Vec::new(),
+        };
+        let removed = collect_garbage(&storage, &data).unwrap();
+        assert_eq!(removed, vec!["orphan.txt".to_string()]);
+        let mut left = storage.list_attachments().unwrap();
+        left.sort();
+        let mut expected = vec![
+            live.attachments[0].file.clone(),
+            trashed.attachments[0].file.clone(),
+            backed_up.attachments[0].file.clone(),
+        ];
+        expected.sort();
+        assert_eq!(left, expected);
+
+        // Archived tasks keep their files too, once every backup has rotated away.
+        let mut archived = task("archived");
+        let attachment = new_attachment(&archived, &source, 4, 5, |_| false);
+        storage.store_attachment(&source, &attachment.file).unwrap();
+        archived.attachments.push(attachment);
+        storage
+            .save_task_archive(2024, &[archived.clone()])
+            .unwrap();
+        fs::remove_file(dir.path().join("backups/data-1.json")).unwrap();
+        let removed = collect_garbage(&storage, &data).unwrap();
+        assert_eq!(removed, vec![backed_up.attachments[0].file.clone()]);
+        assert!(storage.attachment_exists(&archived.attachments[0].file));
+
+        // An unreadable backup makes every file potentially referenced.
+        storage.store_attachment(&source, "orphan.txt").unwrap();
+        fs::write(dir.path().join("backups/data-2.json"), b"{").unwrap();
+        assert!(collect_garbage(&storage, &backup).unwrap().is_empty());
+        assert!(storage.attachment_exists("orphan.txt"));
+    }
+}
@@ -0,0 +1,304 @@
+use crate::infer::normalize_tag;
+use crate::models::{
+    AutomationAction, AutomationRule, AutomationTrigger, ExportFormat, Project, ReminderKind,
+    ReminderSpec, Task, Timestamp, MAX_REMINDER_LEAD_MINUTES,
+};
+use crate::tags::tag_key;
+
+const NAME_MAX_CHARS: usize = 80;
+
+/// Trims the id and name, normalizes tags (a leading `#` is ignored) and clamps reminder leads.
+/// A rule needs at least one action, and every project it moves tasks to must exist.
+pub fn normalize_automation_rule(
+    rule: &mut AutomationRule,
+    projects: &[Project],
+) -> Result<(), String> {
+    rule.id = rule.id.trim().to_string();
+    rule.name = rule.name.trim().to_string();
+    if rule.id.is_empty() {
+        return Err("automation rule id is required".to_string());
+    }
+    if rule.name.is_empty() {
+        return Err("automation rule name is required".to_string());
+    }
+    let chars = rule.name.chars().count();
+    if chars > NAME_MAX_CHARS {
+        return Err(format!(
+            "automation rule name too long: {chars} > {NAME_MAX_CHARS} characters"
+        ));
+    }
+
+    if let AutomationTrigger::TagAdded { tag } = &mut rule.trigger {
+        *tag = normalize_tag(tag).ok_or("trigger tag is required")?;
+    }
+    if rule.actions.is_empty() {
+        return Err("automation rule needs at least one action".to_string());
+    }
+    for action in &mut rule.actions {
+        match action {
+            AutomationAction::MoveToProject { project_id } => {
+                *project_id = project_id.trim().to_string();
+                if !projects.iter().any(|project| project.id == *project_id) {
+                    return Err(format!("project not found: {project_id}"));
+                }
+            }
+            AutomationAction::AddTag { tag } => {
+                *tag = normalize_tag(tag).ok_or("action tag is required")?;
+            }
+            AutomationAction::SetReminder { minutes_before } => {
+                *minutes_before = (*minutes_before).min(MAX_REMINDER_LEAD_MINUTES);
+            }
+            AutomationAction::RunExport { .. } => {}
+        }
+    }
+    Ok(())
+}
+
+fn has_tag(task: &Task, key: &str) -> bool {
+    task.tags
+        .iter()
+        .any(|tag| tag_key(tag).as_deref() == Some(key))
+}
+
+/// The enabled rules that saving `next` over `previous` (`None` for a new task) sets off, in
+/// rule order.
+pub fn rules_for_change<'a>(
+    rules: &'a [AutomationRule],
+    previous: Option<&Task>,
+    next: &Task,
+) -> Vec<&'a AutomationRule> {
+    rules
+        .iter()
+        .filter(|rule| rule.enabled)
+        .filter(|rule| match &rule.trigger {
+            AutomationTrigger::TaskCompleted => {
+                next.completed && !previous.is_some_and(|previous| previous.completed)
+            }
+            AutomationTrigger::TagAdded { tag } => tag_key(tag).is_some_and(|key| {
+                has_tag(next, &key) && !previous.is_some_and(|previous| has_tag(previous, &key))
+            }),
+            AutomationTrigger::TaskOverdue { .. } => false,
+        })
+        .collect()
+}
+
+/// Overdue rules ready to fire as (rule id, task id), each remembered in `overdue_fired` so it
+/// fires once per due time. Entries for tasks that were completed, deleted or rescheduled are
+/// forgotten. The flag tells whether any rule changed and settings need saving.
+pub fn take_overdue(
+    rules: &mut [AutomationRule],
+    tasks: &[Task],
+    now: Timestamp,
+) -> (Vec<(String, String)>, bool) {
+    let mut fired = Vec::new();
+    let mut changed = false;
+    for rule in rules.iter_mut() {
+        let AutomationTrigger::TaskOverdue { minutes } = rule.trigger else {
+            continue;
+        };
+        let before = rule.overdue_fired.len();
+        rule.overdue_fired.retain(|task_id, due_at| {
+            tasks
+                .iter()
+                .any(|task| task.id == *task_id && !task.completed && task.due_at == Some(*due_at))
+        });
+        changed |= rule.overdue_fired.len() != before;
+        if !rule.enabled {
+            continue;
+        }
+        for task in tasks {
+            let Some(due_at) = task.due_at else {
+                continue;
+            };
+            if !task.is_overdue(now, i64::from(minutes))
+                || rule.overdue_fired.get(&task.id) == Some(&due_at)
+            {
+                continue;
+            }
+            rule.overdue_fired.insert(task.id.clone(), due_at);
+            fired.push((rule.id.clone(), task.id.clone()));
+            changed = true;
+        }
+    }
+    (fired, changed)
+}
+
+/// Runs the task actions against `task`; returns whether it changed.
+pub fn apply_actions(task: &mut Task, actions: &[AutomationAction], now: Timestamp) -> bool {
+    let mut changed = false;
+    for action in actions {
+        match action {
+            AutomationAction::MoveToProject { project_id } => {
+                if task.project_id != *project_id {
+                    task.project_id = project_id.clone();
+                    changed = true;
+                }
+            }
+            AutomationAction::AddTag { tag } => {
+                if tag_key(tag).is_some_and(|key| !has_tag(task, &key)) {
+                    task.tags.push(tag.clone());
+                    changed = true;
+                }
+            }
+            AutomationAction::SetReminder { minutes_before } => {
+                let spec = ReminderSpec {
+                    minutes_before: *minutes_before,
+                };
+                if task.due_at.is_none()
+                    || (task.reminder.specs.contains(&spec)
+                        && task.reminder.kind != ReminderKind::None)
+                {
+                    continue;
+                }
+                task.reminder.specs.push(spec);
+                task.reminder.fold_remind_at(task.due_at);
+                if task.reminder.kind == ReminderKind::None {
+                    task.reminder.kind = ReminderKind::Normal;
+                }
+                changed = true;
+            }
+            AutomationAction::RunExport { .. } => {}
+        }
+    }
+    if changed {
+        task.updated_at = now;
+    }
+    changed
+}
+
+pub fn export_formats(actions: &[AutomationAction]) -> impl Iterator<Item = ExportFormat> + '_ {
+    actions.iter().filter_map(|action| match action {
+        AutomationAction::RunExport { format } => Some(*format),
+        _ => None,
+    })
+}
+
+/// Points "move to project" actions at `to` once `from` is deleted.
+pub fn remap_project(rules: &mut [AutomationRule], from: &str, to: &str) -> bool {
+    let mut changed = false;
+    for action in rules.iter_mut().flat_map(|rule| rule.actions.iter_mut()) {
+        if let AutomationAction::MoveToProject { project_id } = action {
+            if project_id == from {
+                *project_id = to.to_string();
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+
+    fn task(id: &str, tags: &[&str]) -> Task {
+        test_support::task(id).tags(tags).due(1_000)
+    }
+
+    fn rule(id: &str, trigger: AutomationTrigger) -> AutomationRule {
+        AutomationRule {
+            id: id.to_string(),
+            name: id.to_string(),
+            enabled: true,
+            trigger,
+            actions: vec![AutomationAction::AddTag {
+                tag: "done".to_string(),
+            }],
+            overdue_fired: Default::default(),
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn completion_and_new_tags_set_off_matching_rules() {
+        let rules = vec![
+            rule("done", AutomationTrigger::TaskCompleted),
+            rule(
+                "urgent",
+                AutomationTrigger::TagAdded {
+                    tag: "urgent".to_string(),
+                },
+            ),
+        ];
+        let ids = |previous: Option<&Task>, next: &Task| -> Vec<String> {
+            rules_for_change(&rules, previous, next)
+                .iter()
+                .map(|rule| rule.id.clone())
+                .collect()
+        };
+        let open = task("a", &[]);
+        let mut tagged = task("a", &["#Urgent"]);
+        assert_eq!(ids(None, &tagged), ["urgent"]);
+        assert_eq!(ids(Some(&open), &tagged), ["urgent"]);
+        assert!(ids(Some(&tagged), &tagged).is_empty());
+
+        tagged.completed = true;
+        assert_eq!(ids(Some(&task("a", &["urgent"])), &tagged), ["done"]);
+        assert!(ids(Some(&tagged), &tagged).is_empty());
+    }
+
+    #[test]
+    fn overdue_rules_fire_once_per_due_time() {
+        let mut rules = vec![rule("late", AutomationTrigger::TaskOverdue { minutes: 10 })];
+        let mut tasks = vec![task("a", &[]), task("b", &[])];
+        tasks[1].due_at = None;
+
+        assert_eq!(take_overdue(&mut rules, &tasks, 1_500), (vec![], false));
+        let (fired, changed) = take_overdue(&mut rules, &tasks, 1_700);
+        assert_eq!(fired, [("late".to_string(), "a".to_string())]);
+        assert!(changed);
+        assert_eq!(take_overdue(&mut rules, &tasks, 1_800), (vec![], false));
+
+        tasks[0].due_at = Some(1_100);
+        let (fired, _) = take_overdue(&mut rules, &tasks, 1_800);
+        assert_eq!(fired.len(), 1);
+        tasks[0].completed = true;
+        assert_eq!(take_overdue(&mut rules, &tasks, 1_900), (vec![], true));
+        assert!(rules[0].overdue_fired.is_empty());
+    }
+
+    #[test]
+    fn actions_change_the_task_once() {
+        let actions = vec![
+            AutomationAction::MoveToProject {
+                project_id: "work".to_string(),
+            },
+            AutomationAction::AddTag {
+                tag: "review".to_string(),
+            },
+            AutomationAction::SetReminder { minutes_before: 15 },
+            AutomationAction::RunExport {
+                format: ExportFormat::Csv,
+            },
+        ];
+        let mut target = task("a", &["Review"]);
+        assert!(apply_actions(&mut target, &actions, 50));
+        assert_eq!(target.project_id, "work");
+        assert_eq!(target.tags, ["Review"]);
+        assert_eq!(target.reminder.kind, ReminderKind::Normal);
+        assert_eq!(target.reminder.specs, [ReminderSpec { minutes_before: 15 }]);
+        assert_eq!(target.updated_at, 50);
+        assert!(!apply_actions(&mut target, &actions, 60));
+        assert_eq!(
+            export_formats(&actions).collect::<Vec<_>>(),
+            [ExportFormat::Csv]
+        );
+
+        let mut rule = rule("r", AutomationTrigger::TaskCompleted);
+        rule.actions = actions;
+        assert!(normalize_automation_rule(&mut rule, &[]).is_err());
+        assert!(remap_project(
+            std::slice::from_mut(&mut rule),
+            "work",
+            "inbox"
+        ));
+        assert_eq!(
+            rule.actions[0],
+            AutomationAction::MoveToProject {
+                project_id: "inbox".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,399 @@
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveTime, TimeZone, Timelike, Weekday};
+
+use crate::models::{DateFormat, Settings, Timestamp, WeekStart};
+
+fn first_weekday(start: WeekStart) -> Weekday {
+    match start {
+        WeekStart::Monday => Weekday::Mon,
+        WeekStart::Sunday => Weekday::Sun,
+        WeekStart::Saturday => Weekday::Sat,
+    }
+}
+
+/// First day of the week containing `date`. Weekly backups and date buckets compare these
+/// instead of ISO weeks so Sunday/Saturday-start users get consistent weeks.
+pub fn week_start(date: NaiveDate, start: WeekStart) -> NaiveDate {
+    date.week(first_weekday(start)).first_day()
+}
+
+fn date_pattern(format: DateFormat) -> &'static str {
+    match format {
+        DateFormat::Ymd => "%Y-%m-%d",
+        DateFormat::Mdy => "%m/%d/%Y",
+        DateFormat::Dmy => "%d/%m/%Y",
+    }
+}
+
+/// Local date + `HH:MM`; falls back to the raw timestamp when it is out of range.
+pub fn format_datetime(ts: Timestamp, format: DateFormat) -> String {
+    Local
+        .timestamp_opt(ts, 0)
+        .single()
+        .map(|dt| {
+            dt.format(&format!("{} %H:%M", date_pattern(format)))
+                .to_string()
+        })
+        .unwrap_or_else(|| ts.to_string())
+}
+
+pub fn format_datetime_seconds(dt: chrono::DateTime<Local>, format: DateFormat) -> String {
+    dt.format(&format!("{} %H:%M:%S", date_pattern(format)))
+        .to_string()
+}
+
+/// Language backend-rendered dates are written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateLocale {
+    Zh,
+    En,
+}
+
+impl DateLocale {
+    /// `settings.language` when it names a language, otherwise the system locale.
+    pub fn resolve(language: &str, system_locale: &str) -> Self {
+        match language.trim().to_lowercase().as_str() {
+            "zh" => DateLocale::Zh,
+            "en" => DateLocale::En,
+            _ if system_locale.to_lowercase().starts_with("zh") => DateLocale::Zh,
+            _ => DateLocale::En,
+        }
+    }
+}
+
+const ZH_WEEKDAYS: [&str; 7] = ["周一", "周二", "周三", "周四", "周五", "周六", "周日"];
+
+/// One place for dates shown to people (emails, outbound payloads, the UI via `format_dates`).
+/// Exports stay on [`format_datetime`]: relative words would go stale in a file.
+#[derive(Debug, Clone, Copy)]
+pub struct DateFormatter {
+    pub format: DateFormat,
+    pub locale: DateLocale,
+    pub now: DateTime<Local>,
+}
+
+impl DateFormatter {
+    pub fn new(settings: &Settings, system_locale: &str, now: DateTime<Local>) -> Self {
+        Self {
+            format: settings.date_format,
+            locale: DateLocale::resolve(&settings.language, system_locale),
+            now,
+        }
+    }
+
+    pub fn absolute(&self, ts: Timestamp) -> String {
+        format_datetime(ts, self.format)
+    }
+
+    fn time(&self, dt: DateTime<Local>) -> String {
+        match self.locale {
+            DateLocale::Zh => dt.format("%H:%M").to_string(),
+            DateLocale::En => {
+                let (pm, hour) = dt.hour12();
+                let suffix = if pm { "pm" } else { "am" };
+                match dt.minute() {
+                    0 => format!("{hour}{suffix}"),
+                    minute => format!("{hour}:{minute:02}{suffix}"),
+                }
+            }
+        }
+    }
+
+    /// "明天 14:00" / "Tue 2pm": yesterday/today/tomorrow and the coming week by name, the rest
+    /// of this year by month and day, anything further away with the configured pattern.
+    pub fn natural(&self, ts: Timestamp) -> String {
+        let Some(dt) = Local.timestamp_opt(ts, 0).single() else {
+            return self.absolute(ts);
+        };
+        let date = dt.date_naive();
+        let days = (date - self.now.date_naive()).num_days();
+        let day = match (self.locale, days) {
+            (DateLocale::Zh, -1) => "昨天".to_string(),
+            (DateLocale::Zh, 0) => "今天".to_string(),
+            (DateLocale::Zh, 1) => "明天".to_string(),
+            (DateLocale::En, -1) => "Yesterday".to_string(),
+            (DateLocale::En, 0) => "Today".to_string(),
+            (DateLocale::En, 1) => "Tomorrow".to_string(),
+            (DateLocale::Zh, 2..=6) => {
+                ZH_WEEKDAYS[date.weekday().num_days_from_monday() as usize].to_string()
+            }
+            (DateLocale::En, 2..=6) => date.format("%a").to_string(),
+            _ if date.year() != self.now.year() => return self.absolute(ts),
+            (DateLocale::Zh, _) => format!("{}月{}日", date.month(), date.day()),
+            (DateLocale::En, _) if self.format == DateFormat::Dmy => {
+                date.format("%-d %b").to_string()
+            }
+            (DateLocale::En, _) => date.format("%b %-d").to_string(),
+        };
+        format!("{day} {}", self.time(dt))
+    }
+}
+
+/// The user's working day (`settings.work_day_start` .. `work_day_end`, local time).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl WorkHours {
+    pub fn from_settings(settings: &Settings) -> Result<Self, String> {
+        let parse = |raw: &str| {
+            NaiveTime::parse_from_str(raw.trim(), "%H:%M")
+                .map_err(|_| format!("invalid work hours: {raw} (expected HH:MM)"))
+        };
+        let hours = WorkHours {
+            start: parse(&settings.work_day_start)?,
+            end: parse(&settings.work_day_end)?,
+        };
+        if hours.start >= hours.end {
+            return Err(format!(
+                "invalid work hours: {} must be before {}",
+                settings.work_day_start.trim(),
+                settings.work_day_end.trim()
+            ));
+        }
+        Ok(hours)
+    }
+
+    /// Working minutes left today: the whole day before it starts, none once it has ended.
+    pub fn remaining_minutes(&self, now: DateTime<Local>) -> i64 {
+        let time = now.time();
+        if time >= self.end {
+            0
+        } else if time < self.start {
+            (self.end - self.start).num_minutes()
+        } else {
+            (self.end - time).num_minutes()
+        }
+    }
+}
+
+/// Checks the work-hour settings and rewrites them as `HH:MM` (e.g. "9:00" -> "09:00").
+pub fn normalize_work_hours(settings: &mut Settings) -> Result<(), String> {
+    let hours = WorkHours::from_settings(settings)?;
+    settings.work_day_start = hours.start.format("%H:%M").to_string();
+    settings.work_day_end = hours.end.format("%H:%M").to_string();
+    Ok(())
+}
+
+/// Reminder quiet hours (`settings.quiet_hours_start` .. `quiet_hours_end`, local time); the
+/// range wraps past midnight when it ends earlier than it starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    pub fn from_settings(settings: &Settings) -> Result<Self, String> {
+        let parse = |raw: &str| {
+            NaiveTime::parse_from_str(raw.trim(), "%H:%M")
+                .map_err(|_| format!("invalid quiet hours: {raw} (expected HH:MM)"))
+        };
+        let hours = QuietHours {
+            start: parse(&settings.quiet_hours_start)?,
+            end: parse(&settings.quiet_hours_end)?,
+        };
+        if hours.start == hours.end {
+            return Err("invalid quiet hours: start and end must differ".to_string());
+        }
+        Ok(hours)
+    }
+
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start < self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+/// Checks the quiet-hour settings and rewrites them as `HH:MM`.
+pub fn normalize_quiet_hours(settings: &mut Settings) -> Result<(), String> {
+    let hours = QuietHours::from_settings(settings)?;
+    settings.quiet_hours_start = hours.start.format("%H:%M").to_string();
+    settings.quiet_hours_end = hours.end.format("%H:%M").to_string();
+    Ok(())
+}
+
+/// Whether normal reminders wait at `now`: DND is on, or quiet hours are enabled and `now` is
+/// inside them. Quiet hours that do not parse hold nothing.
+pub fn reminders_held(settings: &Settings, now: DateTime<Local>) -> bool {
+    settings.dnd_enabled
+        || (settings.quiet_hours_enabled
+            && QuietHours::from_settings(settings).is_ok_and(|hours| hours.contains(now.time())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn week_start_respects_configured_first_day() {
+        // 2026-03-10 is a Tuesday.
+        let tuesday = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        assert_eq!(
+            week_start(tuesday, WeekStart::Monday),
+            NaiveDate::from_ymd_opt(2026, 3, 9).unwrap()
+        );
+        assert_eq!(
+            week_start(tuesday, WeekStart::Sunday),
+            NaiveDate::from_ymd_opt(2026, 3, 8).unwrap()
+        );
+        assert_eq!(
+            week_start(tuesday, WeekStart::Saturday),
+            NaiveDate::from_ymd_opt(2026, 3, 7).unwrap()
+        );
+
+        // A Sunday opens a new week for Sunday-start users only.
+        let sunday = NaiveDate::from_ymd_opt(2026, 3, 15).unwrap();
+        assert_eq!(week_start(sunday, WeekStart::Sunday), sunday);
+        assert_eq!(
+            week_start(sunday, WeekStart::Monday),
+            NaiveDate::from_ymd_opt(2026, 3, 9).unwrap()
+        );
+    }
+
+    #[test]
+    fn work_hours_are_validated_and_count_remaining_minutes() {
+        let mut settings = Settings {
+            work_day_start: " 9:30".to_string(),
+            ..Settings::default()
+        };
+        normalize_work_hours(&mut settings).unwrap();
+        assert_eq!(settings.work_day_start, "09:30");
+        let hours = WorkHours::from_settings(&settings).unwrap();
+        let at = |h, m| Local.with_ymd_and_hms(2026, 3, 18, h, m, 0).unwrap();
+        assert_eq!(hours.remaining_minutes(at(7, 0)), 510);
+        assert_eq!(hours.remaining_minutes(at(16, 45)), 75);
+        assert_eq!(hours.remaining_minutes(at(18, 0)), 0);
+        assert_eq!(hours.remaining_minutes(at(23, 0)), 0);
+
+        settings.work_day_end = "09:00".to_string();
+        assert!(normalize_work_hours(&mut settings).is_err());
+        settings.work_day_end = "late".to_string();
+        assert!(WorkHours::from_settings(&settings).is_err());
+    }
+
+    #[test]
+    fn quiet_hours_wrap_past_midnight_and_dnd_holds_all_day() {
+        let mut settings = Settings {
+            quiet_hours_start: "23:00".to_string(),
+            quiet_hours_end: " 8:00".to_string(),
+            ..Settings::default()
+        };
+        normalize_quiet_hours(&mut settings).unwrap();
+        assert_eq!(settings.quiet_hours_end, "08:00");
+        let at = |h, m| Local.with_ymd_and_hms(2026, 3, 18, h, m, 0).unwrap();
+
+        // Disabled by default.
+        assert!(!reminders_held(&settings, at(23, 30)));
+        settings.quiet_hours_enabled = true;
+        assert!(reminders_held(&settings, at(23, 30)));
+        assert!(reminders_held(&settings, at(7, 59)));
+        assert!(!reminders_held(&settings, at(8, 0)));
+        assert!(!reminders_held(&settings, at(22, 59)));
+
+        settings.quiet_hours_start = "12:00".to_string();
+        settings.quiet_hours_end = "13:00".to_string();
+        assert!(reminders_held(&settings, at(12, 30)));
+        assert!(!reminders_held(&settings, at(23, 30)));
+
+        settings.dnd_enabled = true;
+        assert!(reminders_held(&settings, at(15, 0)));
+
+        settings.quiet_hours_end = "12:00".to_string();
+        assert!(normalize_quiet_hours(&mut settings).is_err());
+        settings.quiet_hours_end = "noon".to_string();
+        assert!(QuietHours::from_settings(&settings).is_err());
+    }
+
+    fn formatter(locale: DateLocale, format: DateFormat) -> DateFormatter {
+        DateFormatter {
+            format,
+            locale,
+            // A Wednesday.
+            now: Local.with_ymd_and_hms(2026, 3, 18, 10, 0, 0).unwrap(),
+        }
+    }
+
+    fn at(month: u32, day: u32, hour: u32, minute: u32) -> Timestamp {
+        Local
+            .with_ymd_and_hms(2026, month, day, hour, minute, 0)
+            .unwrap()
+            .timestamp()
+    }
+
+    #[test]
+    fn date_locale_follows_setting_then_system_locale() {
+        assert_eq!(DateLocale::resolve("zh", "en-US"), DateLocale::Zh);
+        assert_eq!(DateLocale::resolve(" EN ", "zh-CN"), DateLocale::En);
+        assert_eq!(DateLocale::resolve("auto", "zh-CN"), DateLocale::Zh);
+        assert_eq!(DateLocale::resolve("auto", ""), DateLocale::En);
+
+        let settings = Settings {
+            language: "zh".to_string(),
+            date_format: DateFormat::Dmy,
+            ..Settings::default()
+        };
+        let now = Local::now();
+        let dates = DateFormatter::new(&settings, "en-US", now);
+        assert_eq!(dates.locale, DateLocale::Zh);
+        assert_eq!(dates.format, DateFormat::Dmy);
+    }
+
+    #[test]
+    fn natural_dates_in_chinese() {
+        let zh = formatter(DateLocale::Zh, DateFormat::Ymd);
+        assert_eq!(zh.natural(at(3, 18, 14, 0)), "今天 14:00");
+        assert_eq!(zh.natural(at(3, 19, 9, 30)), "明天 09:30");
+        assert_eq!(zh.natural(at(3, 17, 23, 5)), "昨天 23:05");
+        assert_eq!(zh.natural(at(3, 24, 8, 0)), "周二 08:00");
+        assert_eq!(zh.natural(at(3, 25, 8, 0)), "3月25日 08:00");
+        assert_eq!(zh.natural(at(1, 2, 8, 0)), "1月2日 08:00");
+        let next_year = Local
+            .with_ymd_and_hms(2027, 1, 2, 8, 0, 0)
+            .unwrap()
+            .timestamp();
+        assert_eq!(zh.natural(next_year), "2027-01-02 08:00");
+        assert_eq!(zh.natural(i64::MAX), i64::MAX.to_string());
+    }
+
+    #[test]
+    fn natural_dates_in_english() {
+        let en = formatter(DateLocale::En, DateFormat::Mdy);
+        assert_eq!(en.natural(at(3, 18, 14, 0)), "Today 2pm");
+        assert_eq!(en.natural(at(3, 19, 14, 30)), "Tomorrow 2:30pm");
+        assert_eq!(en.natural(at(3, 17, 0, 0)), "Yesterday 12am");
+        assert_eq!(en.natural(at(3, 24, 12, 5)), "Tue 12:05pm");
+        assert_eq!(en.natural(at(4, 1, 9, 0)), "Apr 1 9am");
+        assert_eq!(
+            formatter(DateLocale::En, DateFormat::Dmy).natural(at(4, 1, 9, 0)),
+            "1 Apr 9am"
+        );
+        let last_year = Local
+            .with_ymd_and_hms(2025, 12, 31, 9, 0, 0)
+            .unwrap()
+            .timestamp();
+        assert_eq!(en.natural(last_year), "12/31/2025 09:00");
+        assert_eq!(en.absolute(at(3, 18, 14, 0)), "03/18/2026 14:00");
+    }
+
+    #[test]
+    fn format_datetime_uses_configured_pattern() {
+        let dt = Local.with_ymd_and_hms(2026, 3, 4, 9, 5, 7).unwrap();
+        let ts = dt.timestamp();
+        assert_eq!(format_datetime(ts, DateFormat::Ymd), "2026-03-04 09:05");
+        assert_eq!(format_datetime(ts, DateFormat::Mdy), "03/04/2026 09:05");
+        assert_eq!(format_datetime(ts, DateFormat::Dmy), "04/03/2026 09:05");
+        assert_eq!(
+            format_datetime(i64::MAX, DateFormat::Ymd),
+            i64::MAX.to_string()
+        );
+        assert_eq!(
+            format_datetime_seconds(dt, DateFormat::Dmy),
+            "04/03/2026 09:05:07"
+        );
+    }
+}
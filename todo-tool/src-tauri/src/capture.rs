@@ -0,0 +1,173 @@
+use chrono::{DateTime, Duration, Local, LocalResult, NaiveTime, TimeZone};
+
+use crate::models::{NoDatePolicy, Settings, Timestamp};
+
+/// Rounding steps the quick-capture settings accept; all divide an hour evenly.
+pub const ROUNDING_CHOICES: [u32; 6] = [0, 5, 10, 15, 30, 60];
+
+pub fn parse_due_time(raw: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(raw.trim(), "%H:%M")
+        .map_err(|_| format!("invalid default due time: {raw} (expected HH:MM)"))
+}
+
+/// Checks and normalizes the quick-capture fields of `settings` (e.g. "9:05" -> "09:05").
+pub fn normalize_capture_settings(settings: &mut Settings) -> Result<(), String> {
+    let time = parse_due_time(&settings.quick_default_due_time)?;
+    settings.quick_default_due_time = time.format("%H:%M").to_string();
+    if !ROUNDING_CHOICES.contains(&settings.quick_due_rounding_minutes) {
+        return Err(format!(
+            "invalid due rounding: {} (expected one of {ROUNDING_CHOICES:?})",
+            settings.quick_due_rounding_minutes
+        ));
+    }
+    Ok(())
+}
+
+/// Snaps `ts` to the nearest `minutes` boundary of local wall-clock time (halves round up).
+pub fn round_due_at(ts: Timestamp, minutes: u32) -> Timestamp {
+    if minutes == 0 {
+        return ts;
+    }
+    let Some(local) = Local.timestamp_opt(ts, 0).single() else {
+        return ts;
+    };
+    let step = i64::from(minutes) * 60;
+    let wall = local.naive_local().and_utc().timestamp();
+    let rounded = (wall + step / 2).div_euclid(step) * step;
+    ts + (rounded - wall)
+}
+
+/// Due time for a task captured without a date, following the quick-capture settings.
+/// The Someday policy leaves such tasks undated, so here it only matters for tasks later taken
+/// out of the backlog, which land on today's default like the Today policy.
+pub fn default_due_at(now: DateTime<Local>, settings: &Settings) -> Timestamp {
+    let time = parse_due_time(&settings.quick_default_due_time)
+        .unwrap_or_else(|_| NaiveTime::from_hms_opt(18, 0, 0).expect("valid time"));
+    let at = |days: i64| {
+        let naive = (now.date_naive() + Duration::days(days)).and_time(time);
+        match Local.from_local_datetime(&naive) {
+            LocalResult::Single(value) | LocalResult::Ambiguous(value, _) => value.timestamp(),
+            // Skipped by a DST jump: the wall-clock time an hour later exists.
+            LocalResult::None => Local
+                .from_local_datetime(&(naive + Duration::hours(1)))
+                .earliest()
+                .map_or(now.timestamp(), |value| value.timestamp()),
+        }
+    };
+    match settings.quick_no_date_policy {
+        NoDatePolicy::Today | NoDatePolicy::Someday => {
+            let today = at(0);
+            if today < now.timestamp() {
+                at(1)
+            } else {
+                today
+            }
+        }
+        NoDatePolicy::Tomorrow => at(1),
+    }
+}
+
+/// What quick capture stores: the default for `None`, otherwise the parsed time rounded.
+/// Under the Someday policy an undated capture stays undated.
+pub fn resolve_capture_due(
+    parsed: Option<Timestamp>,
+    now: DateTime<Local>,
+    settings: &Settings,
+) -> Option<Timestamp> {
+    match parsed {
+        Some(ts) => Some(round_due_at(ts, settings.quick_due_rounding_minutes)),
+        None if settings.quick_no_date_policy == NoDatePolicy::Someday => None,
+        None => Some(default_due_at(now, settings)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(hour: u32, minute: u32, second: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2026, 3, 10, hour, minute, second)
+            .unwrap()
+    }
+
+    #[test]
+    fn normalize_capture_settings_validates_time_and_rounding() {
+        let mut settings = Settings {
+            quick_default_due_time: " 9:05 ".to_string(),
+            quick_due_rounding_minutes: 15,
+            ..Settings::default()
+        };
+        assert!(normalize_capture_settings(&mut settings).is_ok());
+        assert_eq!(settings.quick_default_due_time, "09:05");
+
+        settings.quick_default_due_time = "25:00".to_string();
+        assert!(normalize_capture_settings(&mut settings)
+            .unwrap_err()
+            .contains("HH:MM"));
+        settings.quick_default_due_time = "18:00".to_string();
+        settings.quick_due_rounding_minutes = 7;
+        assert!(normalize_capture_settings(&mut settings)
+            .unwrap_err()
+            .contains("rounding"));
+    }
+
+    #[test]
+    fn round_due_at_snaps_to_local_boundaries() {
+        let ts = local(9, 7, 30).timestamp();
+        assert_eq!(round_due_at(ts, 0), ts);
+        assert_eq!(round_due_at(ts, 5), local(9, 10, 0).timestamp());
+        assert_eq!(round_due_at(ts, 15), local(9, 15, 0).timestamp());
+        assert_eq!(
+            round_due_at(local(9, 7, 29).timestamp(), 15),
+            local(9, 0, 0).timestamp()
+        );
+        assert_eq!(
+            round_due_at(local(9, 52, 30).timestamp(), 15),
+            local(10, 0, 0).timestamp()
+        );
+        assert_eq!(round_due_at(i64::MAX, 15), i64::MAX);
+    }
+
+    #[test]
+    fn default_due_at_follows_policy_and_time() {
+        let mut settings = Settings::default();
+        let morning = local(9, 0, 0);
+        assert_eq!(
+            default_due_at(morning, &settings),
+            local(18, 0, 0).timestamp()
+        );
+        // Past the default time, "today" rolls over to tomorrow.
+        let evening = local(19, 0, 0);
+        let tomorrow_18 = (local(18, 0, 0) + Duration::days(1)).timestamp();
+        assert_eq!(default_due_at(evening, &settings), tomorrow_18);
+
+        settings.quick_no_date_policy = NoDatePolicy::Tomorrow;
+        assert_eq!(default_due_at(morning, &settings), tomorrow_18);
+
+        settings.quick_default_due_time = "bogus".to_string();
+        assert_eq!(default_due_at(morning, &settings), tomorrow_18);
+
+        settings.quick_no_date_policy = NoDatePolicy::Someday;
+        assert_eq!(default_due_at(evening, &settings), tomorrow_18);
+        assert_eq!(resolve_capture_due(None, evening, &settings), None);
+    }
+
+    #[test]
+    fn resolve_capture_due_rounds_parsed_times_only() {
+        let settings = Settings {
+            quick_due_rounding_minutes: 30,
+            quick_default_due_time: "17:20".to_string(),
+            ..Settings::default()
+        };
+        let now = local(8, 0, 0);
+        assert_eq!(
+            resolve_capture_due(Some(local(10, 44, 0).timestamp()), now, &settings),
+            Some(local(10, 30, 0).timestamp())
+        );
+        assert_eq!(
+            resolve_capture_due(None, now, &settings),
+            Some(local(17, 20, 0).timestamp())
+        );
+    }
+}
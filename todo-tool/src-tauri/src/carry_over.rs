@@ -0,0 +1,235 @@
+use std::path::Path;
+
+use chrono::{DateTime, Duration, Local, LocalResult, NaiveDate, TimeZone};
+
+use crate::models::{RepeatRule, Task, Timestamp};
+use crate::repeat::task_timezone;
+use crate::storage::{append_jsonl, compact_jsonl, read_jsonl, StorageError};
+
+const CARRY_OVER_LOG_FILE: &str = "carry_over.jsonl";
+const CARRY_OVER_LOG_LIMIT: usize = 500;
+
+/// One task moved forward by the nightly carry-over.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CarryOverEntry {
+    pub at: Timestamp,
+    pub task_id: String,
+    pub title: String,
+    pub from_due: Timestamp,
+    pub to_due: Timestamp,
+    /// The task's `carry_over_count` after this move.
+    pub count: u32,
+}
+
+/// True once per local day, from midnight on; the first run after enabling also counts.
+pub fn carry_over_due(last_run: Option<Timestamp>, now: DateTime<Local>) -> bool {
+    let Some(last_run) = last_run else {
+        return true;
+    };
+    Local
+        .timestamp_opt(last_run, 0)
+        .single()
+        .map(|last| last.date_naive() < now.date_naive())
+        .unwrap_or(true)
+}
+
+/// Oldest local day whose unfinished tasks are still carried: the day of the previous run, or
+/// yesterday when the job never ran. Long-overdue tasks are not dragged along on first use.
+pub fn carry_over_window_start(last_run: Option<Timestamp>, now: DateTime<Local>) -> NaiveDate {
+    let yesterday = now.date_naive() - Duration::days(1);
+    last_run
+        .and_then(|last| Local.timestamp_opt(last, 0).single())
+        .map(|last| last.date_naive().min(yesterday))
+        .unwrap_or(yesterday)
+}
+
+/// New due time for `task` if it is carried today: same wall-clock time, today's date, both in
+/// the task's `timezone` when it has one (the window is in local days, like the runs).
+///
+/// Only open, dated, one-off tasks due before today (but not before `window_start`) qualify;
+/// Someday tasks and tasks that opted out are skipped. Repeating tasks follow their own cycle.
+pub fn carry_over_target(
+    task: &Task,
+    window_start: NaiveDate,
+    now: DateTime<Local>,
+) -> Option<Timestamp> {
+    if task.completed
+        || task.someday
+        || task.skip_carry_over
+        || !matches!(task.repeat, RepeatRule::None)
+    {
+        return None;
+    }
+    let due_at = task.due_at?;
+    if Local.timestamp_opt(due_at, 0).single()?.date_naive() < window_start {
+        return None;
+    }
+    match task_timezone(task) {
+        Some(tz) => moved_to_today(due_at, &now.with_timezone(&tz)),
+        None => moved_to_today(due_at, &now),
+    }
+}
+
+/// `due_at` at the same wall-clock time on `now`'s date in `now`'s zone; `None` unless it was
+/// due on an earlier day there.
+fn moved_to_today<Tz: TimeZone>(due_at: Timestamp, now: &DateTime<Tz>) -> Option<Timestamp> {
+    let zone = now.timezone();
+    let due = zone.timestamp_opt(due_at, 0).single()?;
+    let today = now.date_naive();
+    if due.date_naive() >= today {
+        return None;
+    }
+    let naive = today.and_time(due.time());
+    match zone.from_local_datetime(&naive) {
+        LocalResult::Single(value) | LocalResult::Ambiguous(value, _) => Some(value.timestamp()),
+        // Skipped by a DST jump: keep the same absolute distance from midnight instead.
+        LocalResult::None => zone
+            .from_local_datetime(&(naive + Duration::hours(1)))
+            .earliest()
+            .map(|value| value.timestamp()),
+    }
+}
+
+/// Appends the entries of one run to `carry_over.jsonl`.
+pub fn append_carry_over_log(root: &Path, entries: &[CarryOverEntry]) -> Result<(), StorageError> {
+    append_jsonl(&root.join(CARRY_OVER_LOG_FILE), entries)
+}
+
+/// Keeps the newest `CARRY_OVER_LOG_LIMIT` entries; returns how many lines were dropped.
+pub fn compact_carry_over_log(root: &Path) -> Result<usize, StorageError> {
+    compact_jsonl::<CarryOverEntry>(&root.join(CARRY_OVER_LOG_FILE), CARRY_OVER_LOG_LIMIT)
+}
+
+/// Newest first; unparsable lines are skipped.
+pub fn read_carry_over_log(root: &Path, limit: usize) -> Result<Vec<CarryOverEntry>, StorageError> {
+    let entries = read_jsonl(&root.join(CARRY_OVER_LOG_FILE))?;
+    Ok(entries.into_iter().rev().take(limit).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+    use chrono::Timelike;
+    use std::fs;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2026, 3, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn task(id: &str, due_at: Option<Timestamp>) -> Task {
+        test_support::task(id).due(due_at)
+    }
+
+    #[test]
+    fn carry_over_runs_once_per_local_day() {
+        assert!(carry_over_due(None, at(10, 0, 0)));
+        let ran = at(10, 0, 5).timestamp();
+        assert!(!carry_over_due(Some(ran), at(10, 23, 59)));
+        assert!(carry_over_due(Some(ran), at(11, 0, 0)));
+        assert!(carry_over_due(Some(i64::MAX), at(11, 0, 0)));
+    }
+
+    #[test]
+    fn carry_over_target_keeps_time_and_respects_window_and_opt_outs() {
+        let now = at(11, 0, 0);
+        let window = carry_over_window_start(None, now);
+        assert_eq!(window, at(10, 0, 0).date_naive());
+
+        let yesterday = task("y", Some(at(10, 14, 30).timestamp()));
+        assert_eq!(
+            carry_over_target(&yesterday, window, now),
+            Some(at(11, 14, 30).timestamp())
+        );
+        // Older than the window, due today, undated: untouched.
+        let old = task("o", Some(at(8, 9, 0).timestamp()));
+        assert_eq!(carry_over_target(&old, window, now), None);
+        let today = task("t", Some(at(11, 9, 0).timestamp()));
+        assert_eq!(carry_over_target(&today, window, now), None);
+        assert_eq!(carry_over_target(&task("u", None), window, now), None);
+
+        // A missed run widens the window back to the previous run's day.
+        let window = carry_over_window_start(Some(at(8, 0, 1).timestamp()), now);
+        assert!(carry_over_target(&old, window, now).is_some());
+
+        for skip in [
+            |t: &mut Task| t.completed = true,
+            |t: &mut Task| t.someday = true,
+            |t: &mut Task| t.skip_carry_over = true,
+            |t: &mut Task| {
+                t.repeat = RepeatRule::Daily {
+                    workday_only: false,
+                }
+            },
+        ] {
+            let mut skipped = yesterday.clone();
+            skip(&mut skipped);
+            assert_eq!(carry_over_target(&skipped, window, now), None);
+        }
+    }
+
+    #[test]
+    fn carry_over_target_uses_the_task_timezone() {
+        let now = at(11, 12, 0);
+        let window = carry_over_window_start(Some(at(5, 12, 0).timestamp()), now);
+        // Far from most local zones, so its days and the local ones rarely line up.
+        let tz: chrono_tz::Tz = "Pacific/Kiritimati".parse().unwrap();
+        let today = now.with_timezone(&tz).date_naive();
+        let zoned = |date: NaiveDate, hour: u32| {
+            let mut task = task(
+                "z",
+                Some(
+                    tz.from_local_datetime(&date.and_hms_opt(hour, 30, 0).unwrap())
+                        .unwrap()
+                        .timestamp(),
+                ),
+            );
+            task.timezone = Some(tz.name().to_string());
+            task
+        };
+
+        let yesterday = zoned(today - Duration::days(1), 14);
+        let moved = carry_over_target(&yesterday, window, now).unwrap();
+        let moved = tz.timestamp_opt(moved, 0).unwrap();
+        assert_eq!((moved.date_naive(), moved.hour()), (today, 14));
+        // Early today there is not overdue yet, whatever the local date says.
+        assert_eq!(carry_over_target(&zoned(today, 0), window, now), None);
+    }
+
+    #[test]
+    fn carry_over_log_appends_trims_and_reads_newest_first() {
+        let root = tempfile::tempdir().unwrap();
+        assert!(read_carry_over_log(root.path(), 10).unwrap().is_empty());
+        append_carry_over_log(root.path(), &[]).unwrap();
+        assert!(!root.path().join(CARRY_OVER_LOG_FILE).exists());
+
+        let entries: Vec<CarryOverEntry> = (0..CARRY_OVER_LOG_LIMIT as i64 + 3)
+            .map(|n| CarryOverEntry {
+                at: n,
+                task_id: format!("t{n}"),
+                title: "t".to_string(),
+                from_due: n,
+                to_due: n + 86_400,
+                count: 1,
+            })
+            .collect();
+        append_carry_over_log(root.path(), &entries).unwrap();
+        fs::write(
+            root.path().join(CARRY_OVER_LOG_FILE),
+            fs::read_to_string(root.path().join(CARRY_OVER_LOG_FILE)).unwrap() + "garbage\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            read_carry_over_log(root.path(), usize::MAX).unwrap().len(),
+            CARRY_OVER_LOG_LIMIT + 3
+        );
+        assert_eq!(compact_carry_over_log(root.path()).unwrap(), 4);
+        let all = read_carry_over_log(root.path(), usize::MAX).unwrap();
+        assert_eq!(all.len(), CARRY_OVER_LOG_LIMIT);
+        assert_eq!(all[0].at, CARRY_OVER_LOG_LIMIT as i64 + 2);
+        assert_eq!(read_carry_over_log(root.path(), 2).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,103 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+use chrono::{DateTime, Local, Utc};
+
+use crate::models::Timestamp;
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+
+    fn now_local(&self) -> DateTime<Local> {
+        self.now().with_timezone(&Local)
+    }
+
+    fn timestamp(&self) -> Timestamp {
+        self.now().timestamp()
+    }
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Always returns the same instant; for deterministic tests of time-dependent logic.
+#[cfg(test)]
+pub struct FixedClock(pub Timestamp);
+
+#[cfg(test)]
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.0, 0).unwrap_or_else(Utc::now)
+    }
+}
+
+/// The clock the app runs on: a base clock plus an offset that `advance_clock` (debug builds
+/// only) moves forward, so reminders, repeats and backups can be exercised without waiting.
+///
+/// Clones share the offset, so the scheduler and commands always agree on "now".
+#[derive(Clone)]
+pub struct AppClock {
+    base: Arc<dyn Clock>,
+    offset_secs: Arc<AtomicI64>,
+}
+
+impl AppClock {
+    pub fn system() -> Self {
+        Self::new(Arc::new(SystemClock))
+    }
+
+    pub fn new(base: Arc<dyn Clock>) -> Self {
+        Self {
+            base,
+            offset_secs: Arc::new(AtomicI64::new(0)),
+        }
+    }
+
+    /// Moves the clock forward (or back, for negative values); returns the new total offset.
+    pub fn advance(&self, seconds: i64) -> i64 {
+        self.offset_secs.fetch_add(seconds, Ordering::SeqCst) + seconds
+    }
+
+    pub fn offset_secs(&self) -> i64 {
+        self.offset_secs.load(Ordering::SeqCst)
+    }
+}
+
+impl std::fmt::Debug for AppClock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppClock")
+            .field("offset_secs", &self.offset_secs())
+            .finish()
+    }
+}
+
+impl Clock for AppClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.base.now() + chrono::Duration::seconds(self.offset_secs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn app_clock_applies_shared_offset() {
+        let clock = AppClock::new(Arc::new(FixedClock(1_000)));
+        assert_eq!(clock.timestamp(), 1_000);
+        let shared = clock.clone();
+        assert_eq!(shared.advance(3_600), 3_600);
+        assert_eq!(clock.advance(-600), 3_000);
+        assert_eq!(clock.timestamp(), 4_000);
+        assert_eq!(shared.offset_secs(), 3_000);
+        assert_eq!(clock.now_local().timestamp(), 4_000);
+
+        let system = AppClock::system();
+        assert!((system.timestamp() - Utc::now().timestamp()).abs() <= 5);
+    }
+}
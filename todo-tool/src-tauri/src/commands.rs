@@ -1,21 +1,125 @@
-use chrono::{Datelike, Local, TimeZone, Utc};
+use chrono::{Datelike, Local, TimeZone};
 use std::fs;
-use std::path::Path;
 use std::path::PathBuf;
+use std::path::{Component, Path};
+use std::sync::Arc;
+use std::time::Instant;
 
+use crate::activity::{append_activity, edit_activity, read_activity, ActivityEntry, ActivityKind};
 #[cfg(all(feature = "app", not(test)))]
 use crate::ai::{AiPlan, AiPlanRequest};
+use crate::archive::{
+    archive_file_name, estimate_archive, write_archive, ArchiveEstimate, ArchiveProgress,
+};
+use crate::attachments::{check_source, new_attachment};
+use crate::automation::{
+    apply_actions, export_formats, normalize_automation_rule, remap_project, rules_for_change,
+    take_overdue,
+};
+use crate::calendar::{format_datetime, format_datetime_seconds, week_start, DateFormatter};
+use crate::calendar::{normalize_quiet_hours, normalize_work_hours};
+use crate::capture::{default_due_at, normalize_capture_settings, resolve_capture_due};
+use crate::carry_over::{read_carry_over_log, CarryOverEntry};
+use crate::clock::Clock;
+use crate::comments::{normalize_comment_text, single_line};
+use crate::contexts::{context_summaries, normalize_context, ContextSummary};
+use crate::daily_summary::normalize_daily_summary_time;
+use crate::data_dir::{
+    copy_data_dir, remove_copied, remove_old_entries, validate_target, write_pointer,
+    DataDirProgress,
+};
+use crate::delegation::{normalize_waiting_on, waiting_for_groups, WaitingForGroup};
+use crate::dependencies::{
+    unblock_chain as build_unblock_chain, validate_blocked_by, UnblockChain,
+};
+use crate::digest::{read_reminder_audit, ReminderAuditEntry};
+use crate::dry_run::{diff_snapshots, ChangeSet, Outcome};
+use crate::duplicates::{find_duplicates, CreateOutcome};
+#[cfg(all(feature = "app", not(test)))]
+use crate::email::{append_delivery_log, send_email, DeliveryKind};
+use crate::email::{read_delivery_log, DeliveryLogEntry, EmailMessage, SmtpConfig};
 use crate::events::StatePayload;
+use crate::events::{events_manifest as build_events_manifest, EventsManifest};
 #[cfg(all(feature = "app", not(test)))]
-use crate::events::EVENT_STATE_UPDATED;
-use crate::models::{BackupSchedule, Project, ReminderKind, RepeatRule, Settings, Task};
-use crate::repeat::next_due_timestamp;
+use crate::events::{
+    EVENT_BACKUP_ARCHIVE_PROGRESS, EVENT_DATA_DIR_PROGRESS, EVENT_FOCUS_FINISHED,
+    EVENT_MARKDOWN_SYNC, EVENT_REMINDER, EVENT_STATE_DELTA, EVENT_STATE_UPDATED,
+    EVENT_WRITE_STATUS,
+};
+use crate::export_filter::ExportFilter;
+use crate::flair::{flair_title, normalize_flair, normalize_project_flair};
+use crate::focus::{append_focus_log, focus_minutes, read_focus_log, FocusSession, FocusStatus};
+use crate::i18n::{locale_for, localized_projects, tr, Text};
+use crate::importers::{
+    parse_external, parse_markdown, ExternalImport, ExternalImportReport, MarkdownHeadings,
+};
+use crate::infer::{infer_task, normalize_tag, TaskDraft};
+use crate::maintenance::{run_maintenance, MaintenanceReport};
+use crate::md_sync::{baseline_path, plan_sync, sync_file_path, task_marker, MarkdownSyncReport};
+use crate::merge::{plan_merge, preview_merge, MergePlan, MergePreview};
+use crate::models::{
+    Attachment, AutomationRule, AutomationTrigger, BackupSchedule, Comment, ExportFormat,
+    NoDatePolicy, Project, QuadrantDefinition, ReminderKind, RepeatRule, Settings, SmartList, Tag,
+    Task, TasksFile, Timestamp, TrashEntry, ViewPrefs, WeekStart,
+};
+use crate::note_history::{read_note_history, record_note_versions, replaced_notes, NoteVersion};
+use crate::obsidian::{export_daily_notes, ObsidianExportReport};
+use crate::outbound::OutboundEvent;
+use crate::profiles::{
+    add_profile, load_profile_data, load_registry, profile_root, save_registry, Profile,
+    ProfileRegistry,
+};
+use crate::project_tree::{build_tree, validate_parent, ProjectNode};
+use crate::quadrant::{apply_quadrant, normalize_quadrant_config, quadrant_name};
+use crate::query::{self as task_query, TaskPage, TaskQuerySpec};
+use crate::recovery::{load_tasks_or_recover, RecoveryStatus};
+use crate::repeat::{
+    build_next_repeat_task, next_repeat_due, normalize_timezone, repeat_has_ended,
+};
+use crate::search::{self as task_search, SearchHit, DEFAULT_SEARCH_LIMIT, MAX_SEARCH_LIMIT};
+use crate::shortcuts::{
+    app_conflicts, check_conflicts, normalize_shortcuts, reserved_system_shortcut,
+    shortcut_bindings, top_focus_task_id, ShortcutAction, ShortcutCheck, ShortcutConflict,
+};
+use crate::smart_lists::{evaluate as evaluate_smart_list_tasks, normalize_smart_list};
+use crate::snooze::{snooze_until, NotificationAction, SnoozePreset};
+use crate::sounds::{list_sounds, load_sound, normalize_sound_id, SoundClip, SoundEntry};
 use crate::state::AppState;
-use crate::storage::{Storage, StorageError};
+use crate::stats::{
+    burndown, default_statistics_range, focus_stats, statistics, Burndown, BurndownRange,
+    FocusStats, Statistics,
+};
+use crate::steps::normalize_step_parents;
+use crate::storage::{
+    validate_backup_directory, BackupPolicy, Storage, StorageError, MAX_BACKUP_RETENTION,
+};
+use crate::suggest::{suggest_next, Suggestion, DEFAULT_SUGGESTION_LIMIT, MAX_SUGGESTION_LIMIT};
+use crate::sync::{
+    caldav_config, CalDavConfig, CalDavSyncState, RemoteOutcome, RemoteTodo, SyncPlan, SyncReport,
+    MAX_SYNC_INTERVAL_MINUTES, MIN_SYNC_INTERVAL_MINUTES,
+};
+use crate::tags::{
+    normalize_tag_definition, remove_definition, suggest_tags as suggest_tag_stats, tag_details,
+    tag_key, tag_stats, TagStat, DEFAULT_SUGGEST_LIMIT,
+};
+use crate::task_archive;
+use crate::time_tracking::{
+    report_time as build_time_report, tracked_seconds, TimeReportGroup, TimeReportRow,
+};
+use crate::undo::{UndoStatus, UndoSummary};
+use crate::weekly_review::{
+    parse_review, review_path, review_prompt, ReviewPrompt, SavedWeeklyReview,
+};
+use crate::write_queue::WriteStatus;
+use crate::year_review::{render_html, render_markdown, year_review, YearReview};
 
+#[cfg(all(feature = "app", not(test)))]
+use crate::logging::LogSpan;
 #[cfg(all(feature = "app", not(test)))]
 use crate::tray::update_tray_count;
 #[cfg(all(feature = "app", not(test)))]
+use crate::windows::show_reminder_window;
+#[cfg(all(feature = "app", not(test)))]
 use crate::windows::show_settings_window as show_settings_window_impl;
 #[cfg(all(feature = "app", not(test)))]
 use tauri::{AppHandle, Emitter, Manager, Runtime, State};
@@ -30,12 +134,44 @@ pub struct CommandResult<T> {
 }
 
 trait CommandCtx {
+    /// Active profile's data directory (see `profiles`).
     fn app_data_dir(&self) -> Result<PathBuf, StorageError>;
+    /// Data root after `data_dir` env/pointer resolution; holds the profile registry.
+    fn data_root(&self) -> Result<PathBuf, StorageError> {
+        self.app_data_dir()
+    }
+    /// OS app data dir; keeps logs and the data-dir pointer even after relocation.
+    fn default_app_data_dir(&self) -> Result<PathBuf, StorageError> {
+        self.app_data_dir()
+    }
+    fn emit_data_dir_progress(&self, _progress: &DataDirProgress) {}
+    fn emit_archive_progress(&self, _progress: &ArchiveProgress) {}
+    /// Tells the windows that a background write failed, or succeeded again after failing.
+    fn emit_write_status(&self, _status: &WriteStatus) {}
     fn emit_state_updated(&self, payload: StatePayload);
+    /// Tells the windows what a command changed. The app sends a `state_delta`; by default the
+    /// full state goes out instead.
+    fn emit_state_changes(&self, state: &AppState) {
+        self.emit_state_updated(state.state_payload());
+    }
+    /// Hands the event to the configured outbound sinks (MQTT); never blocks the command.
+    fn dispatch_event(&self, _event: OutboundEvent) {}
+    /// Same path the scheduler uses when reminders fire: event (sound/notification/overlay in the
+    /// UI) plus the reminder window for forced reminders.
+    fn fire_reminder(&self, tasks: &[Task]);
     fn update_tray_count(&self, tasks: &[Task], settings: &Settings);
     fn shortcut_unregister_all(&self);
     fn shortcut_validate(&self, shortcut: &str) -> Result<(), String>;
     fn shortcut_register(&self, shortcut: &str) -> Result<(), String>;
+    /// Whether the OS would let us register `shortcut` right now. Shortcuts this app already
+    /// holds count as free; the app-level conflict check covers them.
+    fn shortcut_probe(&self, _shortcut: &str) -> Result<(), String> {
+        Ok(())
+    }
+    /// Whether `persist` may queue the disk write (see `write_queue`) instead of writing.
+    fn write_behind(&self) -> bool {
+        false
+    }
 
     // Test seam: `serde_json::to_vec_pretty` is effectively infallible for our TasksFile
     // schema. For 100% coverage (and to keep the error-handling path tested), unit tests can
@@ -61,20 +197,76 @@ fn err<T>(message: &str) -> CommandResult<T> {
     }
 }
 
+/// Saves the in-memory state and tells the UI. With write-behind (the app) the disk write is
+/// only queued and happens on the writer thread, which reports failures through
+/// [`flush_write_behind_impl`]; while the last write is failing, commands write here again so
+/// they return the error. Otherwise (tests, tools) it is always written here. Use
+/// [`persist_now`] where the files must be current before going on.
 fn persist(ctx: &impl CommandCtx, state: &AppState) -> Result<(), StorageError> {
     let root = ctx.app_data_dir().map_err(|err| {
         log::error!("persist: app_data_dir failed: {err}");
         err
     })?;
-    let storage = Storage::new(root.clone());
-    storage.ensure_dirs().map_err(|err| {
-        log::error!(
-            "persist: ensure_dirs failed root={} err={err}",
-            root.display()
-        );
+    let should_backup = mark_auto_backup(state);
+    if ctx.write_behind() {
+        state
+            .write_queue()
+            .schedule(root, should_backup, Instant::now());
+        if state.write_queue().has_failed() {
+            flush_writes(state)?;
+            ctx.emit_write_status(&state.write_queue().status());
+        }
+        notify_state_saved(ctx, state);
+        return Ok(());
+    }
+    write_state(&root, state, should_backup)?;
+    notify_state_saved(ctx, state);
+    Ok(())
+}
+
+/// `persist` that always writes before returning, taking along anything still queued.
+fn persist_now(ctx: &impl CommandCtx, state: &AppState) -> Result<(), StorageError> {
+    let root = ctx.app_data_dir().map_err(|err| {
+        log::error!("persist: app_data_dir failed: {err}");
         err
     })?;
-    let now = Utc::now().timestamp();
+    let should_backup = mark_auto_backup(state);
+    state
+        .write_queue()
+        .schedule(root, should_backup, Instant::now());
+    flush_writes(state)?;
+    notify_state_saved(ctx, state);
+    Ok(())
+}
+
+/// Writes whatever `persist` has queued; `Ok(false)` when nothing was owed.
+pub(crate) fn flush_writes(state: &AppState) -> Result<bool, StorageError> {
+    state
+        .write_queue()
+        .run(|write| write_state(&write.root, state, write.with_backup))
+}
+
+/// Writer-thread step: writes what is due and tells the UI when that fails, and again once a
+/// write succeeds after a failure, so nobody assumes edits are on disk when they are not.
+fn flush_write_behind_impl(ctx: &impl CommandCtx, state: &AppState) {
+    let failing = state.write_queue().has_failed();
+    match flush_writes(state) {
+        Ok(_) if !failing => return,
+        Ok(_) => log::info!("write-behind: write succeeded again"),
+        Err(err) => log::error!("write-behind: write failed, will retry err={err}"),
+    }
+    ctx.emit_write_status(&state.write_queue().status());
+}
+
+/// Hook for the write-behind thread in `lib.rs`.
+#[cfg(all(feature = "app", not(test)))]
+pub fn flush_write_behind(app: &AppHandle, state: &AppState) {
+    flush_write_behind_impl(&TauriCommandCtx { app }, state);
+}
+
+/// Stamps `last_backup_at` when an auto backup is due; the backup itself is taken by the write.
+fn mark_auto_backup(state: &AppState) -> bool {
+    let now = state.clock().timestamp();
     let mut settings = state.settings();
     let should_backup = should_auto_backup(&settings, now);
     if should_backup {
@@ -84,52 +276,57 @@ fn persist(ctx: &impl CommandCtx, state: &AppState) -> Result<(), StorageError>
             settings.last_backup_at
         );
         settings.last_backup_at = Some(now);
-        state.update_settings(settings.clone());
+        state.update_settings(settings);
     }
+    should_backup
+}
 
+fn write_state(root: &Path, state: &AppState, with_backup: bool) -> Result<(), StorageError> {
+    let storage = Storage::new(root.to_path_buf())
+        .with_backup_policy(BackupPolicy::from_settings(&state.settings()));
+    storage.ensure_dirs().map_err(|err| {
+        log::error!(
+            "persist: ensure_dirs failed root={} err={err}",
+            root.display()
+        );
+        err
+    })?;
+    // Tasks and settings land together, so a crash cannot pair new data with old settings.
     let tasks_file = state.tasks_file();
     storage
-        .save_tasks(&tasks_file, should_backup)
+        .save_state(&tasks_file, &state.settings_file(), with_backup)
         .map_err(|err| {
             log::error!(
-                "persist: save_tasks failed root={} with_backup={} err={err}",
+                "persist: save_state failed root={} with_backup={} err={err}",
                 root.display(),
-                should_backup
+                with_backup
             );
             err
         })?;
-
-    let settings_file = state.settings_file();
-    storage.save_settings(&settings_file).map_err(|err| {
-        log::error!(
-            "persist: save_settings failed root={} err={err}",
-            root.display()
-        );
-        err
-    })?;
-    // Snapshot once so tray updates + events always reflect a consistent view.
-    let snapshot = state.snapshot();
-    ctx.update_tray_count(&snapshot.tasks, &snapshot.settings);
-    ctx.emit_state_updated(StatePayload {
-        tasks: snapshot.tasks,
-        projects: snapshot.projects,
-        settings: snapshot.settings,
-    });
     log::debug!(
         "persist: ok root={} tasks={} projects={} with_backup={}",
         root.display(),
         tasks_file.tasks.len(),
         tasks_file.projects.len(),
-        should_backup
+        with_backup
     );
     Ok(())
 }
 
+fn notify_state_saved(ctx: &impl CommandCtx, state: &AppState) {
+    // Snapshot once so tray updates + events always reflect a consistent view.
+    let snapshot = state.snapshot();
+    ctx.update_tray_count(&snapshot.tasks, &snapshot.settings);
+    ctx.emit_state_changes(state);
+}
+
 fn should_auto_backup(settings: &Settings, now: i64) -> bool {
     match settings.backup_schedule {
         BackupSchedule::None => false,
         BackupSchedule::Daily => is_new_day(settings.last_backup_at, now),
-        BackupSchedule::Weekly => is_new_week(settings.last_backup_at, now),
+        BackupSchedule::Weekly => {
+            is_new_week(settings.last_backup_at, now, settings.week_starts_on)
+        }
         BackupSchedule::Monthly => is_new_month(settings.last_backup_at, now),
     }
 }
@@ -151,13 +348,17 @@ fn is_new_day(last: Option<i64>, now: i64) -> bool {
     }
 }
 
-fn is_new_week(last: Option<i64>, now: i64) -> bool {
+fn is_new_week(last: Option<i64>, now: i64, start: WeekStart) -> bool {
     match last {
         None => true,
         Some(ts) => {
-            let last_date = Local.timestamp_opt(ts, 0).single().map(|dt| dt.iso_week());
-            let now_date = Local.timestamp_opt(now, 0).single().map(|dt| dt.iso_week());
-            last_date != now_date
+            let week_of = |ts: i64| {
+                Local
+                    .timestamp_opt(ts, 0)
+                    .single()
+                    .map(|dt| week_start(dt.date_naive(), start))
+            };
+            week_of(ts) != week_of(now)
         }
     }
 }
@@ -187,18 +388,71 @@ struct TauriCommandCtx<'a, R: Runtime> {
 #[cfg(all(feature = "app", not(test)))]
 impl<R: Runtime> CommandCtx for TauriCommandCtx<'_, R> {
     fn app_data_dir(&self) -> Result<PathBuf, StorageError> {
+        crate::profiles::app_profile_root(self.app)
+            .map_err(|err| StorageError::Io(std::io::Error::other(err.to_string())))
+    }
+
+    fn data_root(&self) -> Result<PathBuf, StorageError> {
+        crate::data_dir::app_data_root(self.app)
+            .map_err(|err| StorageError::Io(std::io::Error::other(err.to_string())))
+    }
+
+    fn default_app_data_dir(&self) -> Result<PathBuf, StorageError> {
         self.app
             .path()
             .app_data_dir()
             .map_err(|err| StorageError::Io(std::io::Error::other(err.to_string())))
     }
 
+    fn emit_data_dir_progress(&self, progress: &DataDirProgress) {
+        if let Err(err) = self.app.emit(EVENT_DATA_DIR_PROGRESS, progress) {
+            log::warn!("emit data_dir_progress failed: {err}");
+        }
+    }
+
+    fn emit_archive_progress(&self, progress: &ArchiveProgress) {
+        if let Err(err) = self.app.emit(EVENT_BACKUP_ARCHIVE_PROGRESS, progress) {
+            log::warn!("emit backup_archive_progress failed: {err}");
+        }
+    }
+
+    fn emit_write_status(&self, status: &WriteStatus) {
+        if let Err(err) = self.app.emit(EVENT_WRITE_STATUS, status) {
+            log::warn!("emit write_status failed: {err}");
+        }
+    }
+
     fn emit_state_updated(&self, payload: StatePayload) {
         if let Err(err) = self.app.emit(EVENT_STATE_UPDATED, payload) {
             log::warn!("emit state_updated failed: {err}");
         }
     }
 
+    fn emit_state_changes(&self, state: &AppState) {
+        let Some(delta) = state.take_delta() else {
+            return;
+        };
+        if let Err(err) = self.app.emit(EVENT_STATE_DELTA, delta) {
+            log::warn!("emit state_delta failed: {err}");
+        }
+    }
+
+    fn dispatch_event(&self, event: OutboundEvent) {
+        crate::outbound::spawn_dispatch(self.app, event);
+    }
+
+    fn fire_reminder(&self, tasks: &[Task]) {
+        if let Err(err) = self.app.emit(EVENT_REMINDER, tasks) {
+            log::warn!("emit reminder_fired failed: {err}");
+        }
+        if tasks
+            .iter()
+            .any(|task| task.reminder.kind == ReminderKind::Forced)
+        {
+            show_reminder_window(self.app);
+        }
+    }
+
     fn update_tray_count(&self, tasks: &[Task], settings: &Settings) {
         update_tray_count(self.app, tasks, settings);
     }
@@ -232,6 +486,30 @@ impl<R: Runtime> CommandCtx for TauriCommandCtx<'_, R> {
             .register(parsed)
             .map_err(|e| e.to_string())
     }
+
+    fn write_behind(&self) -> bool {
+        true
+    }
+
+    fn shortcut_probe(&self, shortcut: &str) -> Result<(), String> {
+        let parsed = shortcut
+            .trim()
+            .parse::<Shortcut>()
+            .map_err(|e| e.to_string())?;
+        let global_shortcut = self.app.global_shortcut();
+        if global_shortcut.is_registered(parsed) {
+            return Ok(());
+        }
+        // Windows refuses combinations another application holds; other platforms accept
+        // most registrations, so this only catches what the OS reports.
+        global_shortcut
+            .register(parsed)
+            .map_err(|e| e.to_string())?;
+        if let Err(err) = global_shortcut.unregister(parsed) {
+            log::warn!("shortcut probe: failed to unregister {shortcut}: {err}");
+        }
+        Ok(())
+    }
 }
 
 fn load_state_impl(ctx: &impl CommandCtx, state: &AppState) -> CommandResult<StatePayload> {
@@ -247,31 +525,16 @@ fn load_state_impl(ctx: &impl CommandCtx, state: &AppState) -> CommandResult<Sta
         log::error!("cmd=load_state ensure_dirs failed: {error}");
         return err(&format!("storage error: {error}"));
     }
-    let tasks_file = match storage.load_tasks() {
-        Ok(file) => file,
-        Err(err) => {
-            match &err {
-                StorageError::Io(io_err) if io_err.kind() == std::io::ErrorKind::NotFound => {
-                    log::info!(
-                        "cmd=load_state data.json missing path={} -> defaults",
-                        data_path.display()
-                    );
-                }
-                _ => {
-                    log::warn!(
-                        "cmd=load_state failed to load data.json path={} -> defaults err={}",
-                        data_path.display(),
-                        err
-                    );
-                }
-            }
-            crate::models::TasksFile {
-                schema_version: 1,
-                tasks: Vec::new(),
-                projects: Vec::new(),
-            }
-        }
-    };
+    let (tasks_file, recovery) = load_tasks_or_recover(&storage, state.clock().now_local());
+    if let Some(status) = recovery {
+        log::warn!(
+            "cmd=load_state data.json recovered path={} quarantined_as={:?} recovered_from={:?}",
+            data_path.display(),
+            status.quarantined_as,
+            status.recovered_from
+        );
+        state.set_recovery_status(Some(status));
+    }
     let settings = match storage.load_settings() {
         Ok(file) => file.settings,
         Err(err) => {
@@ -294,24 +557,26 @@ fn load_state_impl(ctx: &impl CommandCtx, state: &AppState) -> CommandResult<Sta
         }
     };
 
+    // The data directory decides the engine; the setting only mirrors it (and requests changes).
+    let mut settings = settings;
+    settings.storage_engine = storage.engine();
     state.replace_projects(tasks_file.projects);
     state.replace_tasks(tasks_file.tasks);
+    state.replace_trash(tasks_file.trash);
+    state.replace_tags(tasks_file.tags);
     state.update_settings(settings);
-    let snapshot = state.snapshot();
+    let payload = state.state_payload();
     log::info!(
-        "cmd=load_state ok tasks={} projects={} theme={} language={} close_behavior={:?} backup_schedule={:?}",
-        snapshot.tasks.len(),
-        snapshot.projects.len(),
-        snapshot.settings.theme,
-        snapshot.settings.language,
-        snapshot.settings.close_behavior,
-        snapshot.settings.backup_schedule
-    );
-    ok(StatePayload {
-        tasks: snapshot.tasks,
-        projects: snapshot.projects,
-        settings: snapshot.settings,
-    })
+        "cmd=load_state ok tasks={} projects={} theme={} language={} close_behavior={:?} backup_schedule={:?} seq={}",
+        payload.tasks.len(),
+        payload.projects.len(),
+        payload.settings.theme,
+        payload.settings.language,
+        payload.settings.close_behavior,
+        payload.settings.backup_schedule,
+        payload.seq
+    );
+    ok(payload)
 }
 
 fn create_project_impl(
@@ -328,6 +593,18 @@ fn create_project_impl(
     if project.name.is_empty() {
         return err("project name is required");
     }
+    project.notification_sound = match normalize_sound_id(project.notification_sound.take()) {
+        Ok(sound) => sound,
+        Err(error) => return err(&error),
+    };
+    if let Err(error) = normalize_project_flair(&mut project) {
+        return err(&error);
+    }
+    project.parent_id =
+        match validate_parent(&state.projects(), &project.id, project.parent_id.take()) {
+            Ok(parent_id) => parent_id,
+            Err(error) => return err(&error),
+        };
     if state
         .projects()
         .iter()
@@ -336,7 +613,7 @@ fn create_project_impl(
         return err("project already exists");
     }
 
-    let now = Utc::now();
+    let now = state.clock().now();
     if project.created_at == 0 {
         project.created_at = now.timestamp();
     }
@@ -378,13 +655,25 @@ fn update_project_impl(
     if project.name.is_empty() {
         return err("project name is required");
     }
+    project.notification_sound = match normalize_sound_id(project.notification_sound.take()) {
+        Ok(sound) => sound,
+        Err(error) => return err(&error),
+    };
+    if let Err(error) = normalize_project_flair(&mut project) {
+        return err(&error);
+    }
+    project.parent_id =
+        match validate_parent(&state.projects(), &project.id, project.parent_id.take()) {
+            Ok(parent_id) => parent_id,
+            Err(error) => return err(&error),
+        };
 
     let existing = match state.projects().into_iter().find(|p| p.id == project.id) {
         Some(project) => project,
         None => return err("project not found"),
     };
 
-    let now = Utc::now();
+    let now = state.clock().now();
     if project.created_at == 0 {
         project.created_at = existing.created_at;
     }
@@ -397,6 +686,9 @@ fn update_project_impl(
         // Inbox is a built-in project: keep its name stable (UI can localize it).
         project.name = existing.name.clone();
     }
+    // Muting is owned by mute_project / unmute_project.
+    project.reminders_muted = existing.reminders_muted;
+    project.muted_until = existing.muted_until;
     project.updated_at = now.timestamp();
 
     log::info!(
@@ -425,7 +717,7 @@ fn swap_project_sort_order_impl(
     first_id: String,
     second_id: String,
 ) -> CommandResult<bool> {
-    let now = Utc::now().timestamp();
+    let now = state.clock().timestamp();
     if !state.swap_project_sort_order(&first_id, &second_id, now) {
         return err("project not found");
     }
@@ -463,15 +755,12 @@ fn delete_project_impl(
     }
 
     // Best-effort: move tasks to inbox so we never leave dangling project references.
-    let now = Utc::now().timestamp();
-    let mut tasks_to_move = Vec::new();
-    for task in state.tasks() {
-        if task.project_id == project_id {
-            let mut next = task.clone();
-            next.project_id = "inbox".to_string();
-            next.updated_at = now;
-            tasks_to_move.push(next);
-        }
+    let before = state.tasks_file();
+    let now = state.clock().timestamp();
+    let mut tasks_to_move = state.project_tasks(&project_id);
+    for task in &mut tasks_to_move {
+        task.project_id = "inbox".to_string();
+        task.updated_at = now;
     }
     let moved_count = tasks_to_move.len();
     for task in tasks_to_move {
@@ -485,6 +774,23 @@ fn delete_project_impl(
         now
     );
     state.remove_project(&project_id);
+    state.record_undo("delete_project", &before);
+    let mut settings = state.settings();
+    let mut settings_changed = settings.view_prefs.remove(&project_id).is_some();
+    // Smart lists follow the tasks into the inbox.
+    for list in &mut settings.smart_lists {
+        if let Some(at) = list.project_ids.iter().position(|id| *id == project_id) {
+            list.project_ids.remove(at);
+            if !list.project_ids.iter().any(|id| id == "inbox") {
+                list.project_ids.insert(at, "inbox".to_string());
+            }
+            settings_changed = true;
+        }
+    }
+    settings_changed |= remap_project(&mut settings.automation_rules, &project_id, "inbox");
+    if settings_changed {
+        state.update_settings(settings);
+    }
     if let Err(error) = persist(ctx, state) {
         log::error!(
             "cmd=delete_project persist failed id={} err={error}",
@@ -495,2349 +801,11092 @@ fn delete_project_impl(
     ok(true)
 }
 
-fn create_task_impl(ctx: &impl CommandCtx, state: &AppState, task: Task) -> CommandResult<Task> {
-    let mut task = task;
-    let original_project_id = task.project_id.clone();
-    if task.sort_order == 0 {
-        task.sort_order = task.created_at * 1000;
+/// The projects nested into folders, for the sidebar.
+fn project_tree_impl(state: &AppState) -> CommandResult<Vec<ProjectNode>> {
+    ok(build_tree(&state.projects(), &state.shared_tasks()))
+}
+
+fn get_view_prefs_impl(state: &AppState, project_id: String) -> CommandResult<ViewPrefs> {
+    let project_id = project_id.trim().to_string();
+    if !state.projects().iter().any(|p| p.id == project_id) {
+        return err("project not found");
     }
-    if !state
-        .projects()
-        .iter()
-        .any(|project| project.id == task.project_id)
-    {
-        log::warn!(
-            "cmd=create_task unknown project_id; using inbox task_id={} original_project_id={}",
-            task.id,
-            original_project_id
+    ok(state
+        .settings()
+        .view_prefs
+        .get(&project_id)
+        .cloned()
+        .unwrap_or_default())
+}
+
+fn set_view_prefs_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    project_id: String,
+    prefs: ViewPrefs,
+) -> CommandResult<ViewPrefs> {
+    let project_id = project_id.trim().to_string();
+    if !state.projects().iter().any(|p| p.id == project_id) {
+        return err("project not found");
+    }
+
+    let previous = state.settings();
+    let mut settings = previous.clone();
+    // Defaults are implied, so only non-default prefs are stored to keep settings.json small.
+    if prefs == ViewPrefs::default() {
+        settings.view_prefs.remove(&project_id);
+    } else {
+        settings
+            .view_prefs
+            .insert(project_id.clone(), prefs.clone());
+    }
+    state.update_settings(settings);
+    if let Err(error) = persist(ctx, state) {
+        state.update_settings(previous);
+        log::error!(
+            "cmd=set_view_prefs persist failed project_id={} err={error}",
+            project_id
         );
-        task.project_id = "inbox".to_string();
+        return err(&format!("storage error: {error:?}"));
     }
     log::info!(
-        "cmd=create_task id={} project_id={} due_at={} important={} quadrant={} reminder_kind={:?} repeat={:?}",
-        task.id,
-        task.project_id,
-        task.due_at,
-        task.important,
-        task.quadrant,
-        task.reminder.kind,
-        task.repeat
+        "cmd=set_view_prefs ok project_id={} sort={:?} group_by={:?} show_completed={}",
+        project_id,
+        prefs.sort,
+        prefs.group_by,
+        prefs.show_completed
     );
-    state.add_task(task.clone());
+    ok(prefs)
+}
+
+fn list_smart_lists_impl(state: &AppState) -> CommandResult<Vec<SmartList>> {
+    ok(state.settings().smart_lists)
+}
+
+/// Replaces `settings.smart_lists` and saves; the previous lists come back if saving fails.
+fn save_smart_lists(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    command: &str,
+    edit: impl FnOnce(&mut Vec<SmartList>),
+) -> Result<(), String> {
+    let previous = state.settings();
+    let mut settings = previous.clone();
+    edit(&mut settings.smart_lists);
+    state.update_settings(settings);
     if let Err(error) = persist(ctx, state) {
-        log::error!("cmd=create_task persist failed id={} err={error}", task.id);
-        return err(&format!("storage error: {error:?}"));
+        state.update_settings(previous);
+        log::error!("cmd={command} persist failed err={error}");
+        return Err(format!("storage error: {error:?}"));
     }
-    ok(task)
+    Ok(())
 }
 
-fn update_task_impl(ctx: &impl CommandCtx, state: &AppState, task: Task) -> CommandResult<Task> {
-    let mut task = task;
-    let original_project_id = task.project_id.clone();
-    if task.sort_order == 0 {
-        task.sort_order = task.created_at * 1000;
+fn create_smart_list_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    mut list: SmartList,
+) -> CommandResult<SmartList> {
+    if let Err(message) = normalize_smart_list(&mut list, &state.projects()) {
+        log::warn!("cmd=create_smart_list rejected err={message}");
+        return err(&message);
     }
-    if !state
-        .projects()
+    if state
+        .settings()
+        .smart_lists
         .iter()
-        .any(|project| project.id == task.project_id)
+        .any(|existing| existing.id == list.id)
     {
-        log::warn!(
-            "cmd=update_task unknown project_id; using inbox task_id={} original_project_id={}",
-            task.id,
-            original_project_id
-        );
-        task.project_id = "inbox".to_string();
+        return err("smart list already exists");
+    }
+    let now = state.clock().timestamp();
+    list.created_at = now;
+    list.updated_at = now;
+    let created = list.clone();
+    if let Err(message) =
+        save_smart_lists(ctx, state, "create_smart_list", |lists| lists.push(list))
+    {
+        return err(&message);
     }
     log::info!(
-        "cmd=update_task id={} project_id={} due_at={} important={} quadrant={} reminder_kind={:?} repeat={:?}",
-        task.id,
-        task.project_id,
-        task.due_at,
-        task.important,
-        task.quadrant,
-        task.reminder.kind,
-        task.repeat
+        "cmd=create_smart_list ok id={} due={:?} projects={} tags={}",
+        created.id,
+        created.due,
+        created.project_ids.len(),
+        created.tags.len()
     );
-    state.update_task(task.clone());
-    if let Err(error) = persist(ctx, state) {
-        log::error!("cmd=update_task persist failed id={} err={error}", task.id);
-        return err(&format!("storage error: {error:?}"));
-    }
-    ok(task)
+    ok(created)
 }
 
-fn bulk_update_tasks_impl(
+fn update_smart_list_impl(
     ctx: &impl CommandCtx,
     state: &AppState,
-    tasks: Vec<Task>,
-) -> CommandResult<bool> {
-    let projects = state.projects();
-    let total = tasks.len();
-    let mut remapped_projects = 0usize;
-    for mut task in tasks {
-        if task.sort_order == 0 {
-            task.sort_order = task.created_at * 1000;
-        }
-        if !projects.iter().any(|project| project.id == task.project_id) {
-            remapped_projects += 1;
-            task.project_id = "inbox".to_string();
+    mut list: SmartList,
+) -> CommandResult<SmartList> {
+    if let Err(message) = normalize_smart_list(&mut list, &state.projects()) {
+        log::warn!("cmd=update_smart_list rejected err={message}");
+        return err(&message);
+    }
+    let Some(existing) = state
+        .settings()
+        .smart_lists
+        .into_iter()
+        .find(|existing| existing.id == list.id)
+    else {
+        return err("smart list not found");
+    };
+    list.created_at = existing.created_at;
+    list.updated_at = state.clock().timestamp();
+    let updated = list.clone();
+    if let Err(message) = save_smart_lists(ctx, state, "update_smart_list", |lists| {
+        if let Some(slot) = lists.iter_mut().find(|slot| slot.id == list.id) {
+            *slot = list;
         }
-        state.update_task(task);
+    }) {
+        return err(&message);
     }
     log::info!(
-        "cmd=bulk_update_tasks count={} remapped_projects={}",
-        total,
-        remapped_projects
+        "cmd=update_smart_list ok id={} due={:?} projects={} tags={}",
+        updated.id,
+        updated.due,
+        updated.project_ids.len(),
+        updated.tags.len()
     );
-    if let Err(error) = persist(ctx, state) {
-        log::error!("cmd=bulk_update_tasks persist failed err={error}");
-        return err(&format!("storage error: {error:?}"));
-    }
-    ok(true)
+    ok(updated)
 }
 
-fn swap_sort_order_impl(
+fn delete_smart_list_impl(
     ctx: &impl CommandCtx,
     state: &AppState,
-    first_id: String,
-    second_id: String,
+    list_id: String,
 ) -> CommandResult<bool> {
-    let now = Utc::now().timestamp();
-    if !state.swap_sort_order(&first_id, &second_id, now) {
-        return err("task not found");
+    let list_id = list_id.trim().to_string();
+    if !state
+        .settings()
+        .smart_lists
+        .iter()
+        .any(|list| list.id == list_id)
+    {
+        return err("smart list not found");
     }
-    log::info!(
-        "cmd=swap_sort_order ok first_id={} second_id={} at={}",
-        first_id,
-        second_id,
-        now
-    );
-    if let Err(error) = persist(ctx, state) {
-        log::error!(
-            "cmd=swap_sort_order persist failed first_id={} second_id={} err={error}",
-            first_id,
-            second_id
-        );
-        return err(&format!("storage error: {error:?}"));
+    if let Err(message) = save_smart_lists(ctx, state, "delete_smart_list", |lists| {
+        lists.retain(|list| list.id != list_id)
+    }) {
+        return err(&message);
     }
+    log::info!("cmd=delete_smart_list ok id={list_id}");
     ok(true)
 }
 
-fn build_next_repeat_task(completed: &Task, next_due: i64) -> Task {
-    let now = Utc::now();
-    let mut next = completed.clone();
-    next.id = format!("{}-{}", completed.id, now.timestamp());
-    next.completed = false;
-    next.completed_at = None;
-    next.created_at = now.timestamp();
-    next.updated_at = now.timestamp();
-    next.sort_order = now.timestamp_millis();
-    next.due_at = next_due;
-    next.reminder.last_fired_at = None;
-    next.reminder.forced_dismissed = false;
-    next.reminder.snoozed_until = None;
-    next.reminder.repeat_fired_count = 0;
-
-    // Preserve the reminder offset semantics across repeat instances.
-    // (Otherwise a copied `remind_at` in the past would trigger immediately on the next cycle.)
-    if next.reminder.kind == ReminderKind::None {
-        next.reminder.remind_at = None;
-    } else {
-        let old_default_target = if completed.reminder.kind == ReminderKind::Normal {
-            completed.due_at - 10 * 60
-        } else {
-            completed.due_at
-        };
-        let old_target = completed.reminder.remind_at.unwrap_or(old_default_target);
-        let offset = (completed.due_at - old_target).max(0);
-        next.reminder.remind_at = Some(next_due - offset);
-    }
+fn evaluate_smart_list_impl(state: &AppState, list_id: String) -> CommandResult<Vec<Task>> {
+    let list_id = list_id.trim();
+    let snapshot = state.snapshot();
+    let Some(list) = snapshot
+        .settings
+        .smart_lists
+        .iter()
+        .find(|list| list.id == list_id)
+    else {
+        return err("smart list not found");
+    };
+    let tasks = evaluate_smart_list_tasks(
+        list,
+        &snapshot.tasks,
+        &snapshot.projects,
+        state.clock().now_local(),
+        &snapshot.settings,
+    );
+    log::debug!(
+        "cmd=evaluate_smart_list ok id={} tasks={}",
+        list.id,
+        tasks.len()
+    );
+    ok(tasks)
+}
 
-    next
+fn list_automation_rules_impl(state: &AppState) -> CommandResult<Vec<AutomationRule>> {
+    ok(state.settings().automation_rules)
 }
 
-fn complete_task_impl(
+/// Replaces `settings.automation_rules` and saves; the previous rules come back if saving fails.
+fn save_automation_rules(
     ctx: &impl CommandCtx,
     state: &AppState,
-    task_id: String,
-) -> CommandResult<Task> {
-    let completed = match state.complete_task(&task_id) {
-        Some(task) => task,
-        None => {
-            log::warn!("cmd=complete_task task not found id={}", task_id);
-            return err("task not found");
-        }
-    };
-
-    if let RepeatRule::None = completed.repeat {
-        log::info!("cmd=complete_task id={} repeat=none", completed.id);
-        if let Err(error) = persist(ctx, state) {
-            log::error!(
-                "cmd=complete_task persist failed id={} err={error}",
-                completed.id
-            );
-            return err(&format!("storage error: {error:?}"));
-        }
-        return ok(completed);
+    command: &str,
+    edit: impl FnOnce(&mut Vec<AutomationRule>),
+) -> Result<(), String> {
+    let previous = state.settings();
+    let mut settings = previous.clone();
+    edit(&mut settings.automation_rules);
+    state.update_settings(settings);
+    if let Err(error) = persist(ctx, state) {
+        state.update_settings(previous);
+        log::error!("cmd={command} persist failed err={error}");
+        return Err(format!("storage error: {error:?}"));
     }
+    Ok(())
+}
 
-    let next_due = next_due_timestamp(completed.due_at, &completed.repeat);
-    let next = build_next_repeat_task(&completed, next_due);
-
+fn create_automation_rule_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    mut rule: AutomationRule,
+) -> CommandResult<AutomationRule> {
+    if let Err(message) = normalize_automation_rule(&mut rule, &state.projects()) {
+        log::warn!("cmd=create_automation_rule rejected err={message}");
+        return err(&message);
+    }
+    if state
+        .settings()
+        .automation_rules
+        .iter()
+        .any(|existing| existing.id == rule.id)
+    {
+        return err("automation rule already exists");
+    }
+    let now = state.clock().timestamp();
+    rule.overdue_fired.clear();
+    rule.created_at = now;
+    rule.updated_at = now;
+    let created = rule.clone();
+    if let Err(message) = save_automation_rules(ctx, state, "create_automation_rule", |rules| {
+        rules.push(rule)
+    }) {
+        return err(&message);
+    }
     log::info!(
-        "cmd=complete_task id={} repeat={:?} next_id={} next_due={}",
-        completed.id,
-        completed.repeat,
-        next.id,
-        next_due
+        "cmd=create_automation_rule ok id={} trigger={:?} actions={}",
+        created.id,
+        created.trigger,
+        created.actions.len()
     );
-    state.add_task(next.clone());
-
-    if let Err(error) = persist(ctx, state) {
-        log::error!(
-            "cmd=complete_task persist failed id={} err={error}",
-            completed.id
-        );
-        return err(&format!("storage error: {error:?}"));
-    }
-
-    ok(next)
+    ok(created)
 }
 
-fn bulk_complete_tasks_impl(
+fn update_automation_rule_impl(
     ctx: &impl CommandCtx,
     state: &AppState,
-    task_ids: Vec<String>,
-) -> CommandResult<bool> {
-    let total = task_ids.len();
-    let mut completed_count = 0usize;
-    let mut repeated_created = 0usize;
-    for task_id in task_ids {
-        let completed = match state.complete_task(&task_id) {
-            Some(task) => task,
-            None => continue,
-        };
-        completed_count += 1;
-
-        if let RepeatRule::None = completed.repeat {
-            continue;
+    mut rule: AutomationRule,
+) -> CommandResult<AutomationRule> {
+    if let Err(message) = normalize_automation_rule(&mut rule, &state.projects()) {
+        log::warn!("cmd=update_automation_rule rejected err={message}");
+        return err(&message);
+    }
+    let Some(existing) = state
+        .settings()
+        .automation_rules
+        .into_iter()
+        .find(|existing| existing.id == rule.id)
+    else {
+        return err("automation rule not found");
+    };
+    // Tasks already handled stay handled unless the trigger itself changed.
+    rule.overdue_fired = if rule.trigger == existing.trigger {
+        existing.overdue_fired
+    } else {
+        Default::default()
+    };
+    rule.created_at = existing.created_at;
+    rule.updated_at = state.clock().timestamp();
+    let updated = rule.clone();
+    if let Err(message) = save_automation_rules(ctx, state, "update_automation_rule", |rules| {
+        if let Some(slot) = rules.iter_mut().find(|slot| slot.id == rule.id) {
+            *slot = rule;
         }
-
-        let next_due = next_due_timestamp(completed.due_at, &completed.repeat);
-        let next = build_next_repeat_task(&completed, next_due);
-        state.add_task(next);
-        repeated_created += 1;
+    }) {
+        return err(&message);
     }
-
     log::info!(
-        "cmd=bulk_complete_tasks requested={} completed={} repeated_created={}",
-        total,
-        completed_count,
-        repeated_created
+        "cmd=update_automation_rule ok id={} enabled={} trigger={:?} actions={}",
+        updated.id,
+        updated.enabled,
+        updated.trigger,
+        updated.actions.len()
     );
-    if let Err(error) = persist(ctx, state) {
-        log::error!("cmd=bulk_complete_tasks persist failed err={error}");
-        return err(&format!("storage error: {error:?}"));
+    ok(updated)
+}
+
+fn delete_automation_rule_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    rule_id: String,
+) -> CommandResult<bool> {
+    let rule_id = rule_id.trim().to_string();
+    if !state
+        .settings()
+        .automation_rules
+        .iter()
+        .any(|rule| rule.id == rule_id)
+    {
+        return err("automation rule not found");
+    }
+    if let Err(message) = save_automation_rules(ctx, state, "delete_automation_rule", |rules| {
+        rules.retain(|rule| rule.id != rule_id)
+    }) {
+        return err(&message);
     }
+    log::info!("cmd=delete_automation_rule ok id={rule_id}");
     ok(true)
 }
 
-fn update_settings_impl(
+/// Runs each fired (rule, task id) pair: task actions first, saved together, then exports (each
+/// format once). Actions never set off further rules. Returns whether any task changed.
+fn apply_automation_rules(
     ctx: &impl CommandCtx,
     state: &AppState,
-    mut settings: Settings,
-) -> CommandResult<Settings> {
-    let previous = state.settings();
-    let previous_shortcut = previous.shortcut.trim().to_string();
-    let next_shortcut = settings.shortcut.trim().to_string();
-    let previous_language = previous.language.trim().to_lowercase();
-    let next_language = settings.language.trim().to_lowercase();
-    let shortcut_requested_change = previous_shortcut != next_shortcut;
-
-    // Normalize user input so tests/production behave the same and the persisted config is stable.
-    settings.shortcut = next_shortcut.clone();
-    settings.language = match next_language.as_str() {
-        "auto" | "zh" | "en" => next_language.clone(),
-        _ => {
-            log::warn!(
-                "cmd=update_settings invalid language; using default requested={}",
-                next_language
-            );
-            Settings::default().language
+    fired: Vec<(AutomationRule, String)>,
+) -> bool {
+    if fired.is_empty() {
+        return false;
+    }
+    let now = state.clock().timestamp();
+    let mut activity = Vec::new();
+    let mut exports: Vec<ExportFormat> = Vec::new();
+    for (rule, task_id) in &fired {
+        if let Some(mut task) = state.task(task_id) {
+            let previous = task.clone();
+            if apply_actions(&mut task, &rule.actions, now) {
+                activity.extend(edit_activity(&previous, &task, now));
+                state.update_task(task);
+            }
         }
-    };
+        for format in export_formats(&rule.actions) {
+            if !exports.contains(&format) {
+                exports.push(format);
+            }
+        }
+        log::info!("automation: rule fired id={} task_id={task_id}", rule.id);
+    }
+    let changed = !activity.is_empty();
+    if changed {
+        if let Err(error) = persist(ctx, state) {
+            log::error!("automation: persist failed err={error}");
+        }
+        record_activity(ctx, activity);
+    }
+    for format in exports {
+        let filter = ExportFilter::default();
+        let result = match format {
+            ExportFormat::Json => export_tasks_json_impl(ctx, state, filter),
+            ExportFormat::Csv => export_tasks_csv_impl(ctx, state, filter),
+            ExportFormat::Markdown => export_tasks_markdown_impl(ctx, state, filter),
+            ExportFormat::Ics => export_tasks_ics_impl(ctx, state, filter),
+        };
+        if let Some(error) = result.error {
+            log::warn!("automation: export failed format={format:?} err={error}");
+        }
+    }
+    changed
+}
 
-    // AI settings: keep API key stable.
-    settings.deepseek_api_key = settings.deepseek_api_key.trim().to_string();
-    settings.ai_model = settings.ai_model.trim().to_string();
-    if settings.ai_model.is_empty() {
-        settings.ai_model = Settings::default().ai_model;
+/// Runs the rules that saved task changes (before, after; no before for new tasks) set off.
+/// Best effort: the changes themselves are already saved. Returns whether any task changed.
+fn run_automations(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    changes: &[(Option<Task>, Task)],
+) -> bool {
+    let rules = state.settings().automation_rules;
+    if rules.is_empty() {
+        return false;
     }
+    let fired = changes
+        .iter()
+        .flat_map(|(previous, next)| {
+            rules_for_change(&rules, previous.as_ref(), next)
+                .into_iter()
+                .map(|rule| (rule.clone(), next.id.clone()))
+        })
+        .collect();
+    apply_automation_rules(ctx, state, fired)
+}
 
-    log::info!(
-        "cmd=update_settings start theme={} language={} close_behavior={:?} minimize_behavior={:?} backup_schedule={:?} update_behavior={:?} repeat_interval_sec={} repeat_max_times={} shortcut_change={}",
-        settings.theme,
-        settings.language,
-        settings.close_behavior,
-        settings.minimize_behavior,
-        settings.backup_schedule,
-        settings.update_behavior,
-        settings.reminder_repeat_interval_sec,
-        settings.reminder_repeat_max_times,
-        shortcut_requested_change
+/// Fires the overdue rules whose tasks crossed their threshold; returns how many fired.
+fn run_overdue_automations_impl(ctx: &impl CommandCtx, state: &AppState) -> usize {
+    let mut settings = state.settings();
+    if !settings
+        .automation_rules
+        .iter()
+        .any(|rule| matches!(rule.trigger, AutomationTrigger::TaskOverdue { .. }))
+    {
+        return 0;
+    }
+    let (fired, changed) = take_overdue(
+        &mut settings.automation_rules,
+        &state.shared_tasks(),
+        state.clock().timestamp(),
     );
-
-    // Shortcut validation/registration is the #1 place users can lock themselves out:
-    // if we unregister the old one and fail to register the new one, the app becomes unreachable
-    // from the keyboard. So we validate + register with a rollback path and only then persist.
-    let mut shortcut_changed = false;
-    if shortcut_requested_change {
-        shortcut_changed = true;
-        if let Err(parse_err) = ctx.shortcut_validate(&next_shortcut) {
-            log::warn!(
-                "cmd=update_settings invalid shortcut requested={} err={}",
-                next_shortcut,
-                parse_err
-            );
-            return err(&format!("invalid shortcut: {parse_err}"));
+    if !changed {
+        return 0;
+    }
+    let fired: Vec<(AutomationRule, String)> = fired
+        .into_iter()
+        .filter_map(|(rule_id, task_id)| {
+            let rule = settings
+                .automation_rules
+                .iter()
+                .find(|rule| rule.id == rule_id)?;
+            Some((rule.clone(), task_id))
+        })
+        .collect();
+    let count = fired.len();
+    state.update_settings(settings);
+    if !apply_automation_rules(ctx, state, fired) {
+        // Only the fired bookkeeping changed.
+        if let Err(error) = persist(ctx, state) {
+            log::error!("automation: persist failed err={error}");
         }
+    }
+    count
+}
 
-        ctx.shortcut_unregister_all();
-        if let Err(register_err) = ctx.shortcut_register(&next_shortcut) {
-            // Best-effort restore the previous shortcut so the user can still summon the quick window.
-            let _ = ctx.shortcut_register(&previous_shortcut);
-            log::error!(
-                "cmd=update_settings failed to register shortcut requested={} err={}",
-                next_shortcut,
-                register_err
-            );
-            return err(&format!("failed to register shortcut: {register_err}"));
-        }
-        log::info!(
-            "cmd=update_settings shortcut updated old={} new={}",
-            previous_shortcut,
-            next_shortcut
-        );
+/// Scheduler hook for saving what a tick changed. It takes the same path as the commands
+/// (queued behind their writes, tasks and settings in one `save_state`), so the two never
+/// overwrite each other's newer data.
+#[cfg(all(feature = "app", not(test)))]
+pub fn persist_scheduler_changes(app: &AppHandle, state: &AppState) {
+    let ctx = TauriCommandCtx { app };
+    if let Err(err) = persist(&ctx, state) {
+        log::error!("scheduler: persist failed err={err}");
     }
+}
 
-    // Currently language is a UI concern (and must never brick the app). We normalize above and
-    // keep persistence best-effort only through the usual `persist()` path.
-    let _language_changed = previous_language != settings.language;
+/// Scheduler hook for overdue automation rules.
+#[cfg(all(feature = "app", not(test)))]
+pub fn run_overdue_automations(app: &AppHandle, state: &AppState) {
+    let ctx = TauriCommandCtx { app };
+    let fired = run_overdue_automations_impl(&ctx, state);
+    if fired > 0 {
+        log::info!("scheduler: overdue automations fired count={fired}");
+    }
+}
 
-    state.update_settings(settings.clone());
+fn list_tags_impl(state: &AppState) -> CommandResult<Vec<TagStat>> {
+    ok(tag_stats(&state.shared_tasks(), &state.tags()))
+}
+
+fn list_tag_definitions_impl(state: &AppState) -> CommandResult<Vec<Tag>> {
+    ok(state.tags())
+}
+
+/// Saves tag definitions changed by `edit`; the previous ones come back if saving fails.
+fn save_tag_definitions(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    command: &str,
+    edit: impl FnOnce(&mut Vec<Tag>),
+) -> Result<(), String> {
+    let before = state.tasks_file();
+    let mut tags = before.tags.clone();
+    edit(&mut tags);
+    state.replace_tags(tags);
     if let Err(error) = persist(ctx, state) {
-        // Roll back in-memory settings to keep the running app consistent.
-        state.update_settings(previous.clone());
-        if shortcut_changed {
-            ctx.shortcut_unregister_all();
-            let _ = ctx.shortcut_register(&previous_shortcut);
-        }
-        log::error!("cmd=update_settings persist failed err={error}");
-        return err(&format!("storage error: {error:?}"));
+        state.replace_tags(before.tags);
+        log::error!("cmd={command} persist failed err={error}");
+        return Err(format!("storage error: {error:?}"));
     }
+    state.record_undo(command, &before);
+    Ok(())
+}
 
+fn create_tag_impl(ctx: &impl CommandCtx, state: &AppState, mut tag: Tag) -> CommandResult<Tag> {
+    let defs = state.tags();
+    if let Err(message) = normalize_tag_definition(&mut tag, &defs) {
+        log::warn!("cmd=create_tag rejected err={message}");
+        return err(&message);
+    }
+    if defs.iter().any(|existing| existing.id == tag.id) {
+        return err("tag already exists");
+    }
+    let now = state.clock().timestamp();
+    tag.created_at = now;
+    tag.updated_at = now;
+    let created = tag.clone();
+    if let Err(message) = save_tag_definitions(ctx, state, "create_tag", |defs| defs.push(tag)) {
+        return err(&message);
+    }
     log::info!(
-        "cmd=update_settings ok theme={} language={} close_behavior={:?} minimize_behavior={:?} backup_schedule={:?} update_behavior={:?}",
-        settings.theme,
-        settings.language,
-        settings.close_behavior,
-        settings.minimize_behavior,
-        settings.backup_schedule,
-        settings.update_behavior
+        "cmd=create_tag ok id={} color={:?} parent={:?}",
+        created.id,
+        created.color,
+        created.parent_id
     );
-    ok(settings)
+    ok(created)
 }
 
-fn snooze_task_impl(
-    ctx: &impl CommandCtx,
-    state: &AppState,
-    task_id: String,
-    until: i64,
-) -> CommandResult<bool> {
-    log::info!("cmd=snooze_task start task_id={} until={}", task_id, until);
-    let mut tasks = state.tasks();
-    let mut found = false;
-    if let Some(task) = tasks.iter_mut().find(|t| t.id == task_id) {
-        found = true;
-        task.reminder.snoozed_until = Some(until);
-        task.reminder.last_fired_at = Some(Utc::now().timestamp());
-        state.update_task(task.clone());
+/// Saves a definition's color and parent; a new name is applied to every task carrying the
+/// old one in the same undoable step.
+fn update_tag_impl(ctx: &impl CommandCtx, state: &AppState, mut tag: Tag) -> CommandResult<Tag> {
+    let defs = state.tags();
+    if let Err(message) = normalize_tag_definition(&mut tag, &defs) {
+        log::warn!("cmd=update_tag rejected err={message}");
+        return err(&message);
     }
-    if !found {
-        log::warn!("cmd=snooze_task task not found task_id={}", task_id);
+    let Some(existing) = defs.into_iter().find(|existing| existing.id == tag.id) else {
+        return err("tag not found");
+    };
+    let now = state.clock().timestamp();
+    tag.created_at = existing.created_at;
+    tag.updated_at = now;
+    let before = state.tasks_file();
+    let renamed = existing.name != tag.name;
+    if renamed {
+        let old_key = tag_key(&existing.name).unwrap_or_default();
+        state.retag_tasks(&[old_key], &tag.name, now);
     }
+    let mut defs = state.tags();
+    if let Some(slot) = defs.iter_mut().find(|slot| slot.id == tag.id) {
+        *slot = tag.clone();
+    }
+    state.replace_tags(defs);
     if let Err(error) = persist(ctx, state) {
-        log::error!(
-            "cmd=snooze_task persist failed task_id={} err={error}",
-            task_id
-        );
+        state.replace_tasks(before.tasks);
+        state.replace_tags(before.tags);
+        log::error!("cmd=update_tag persist failed err={error}");
         return err(&format!("storage error: {error:?}"));
     }
+    state.record_undo("update_tag", &before);
+    log::info!(
+        "cmd=update_tag ok id={} renamed={renamed} color={:?} parent={:?}",
+        tag.id,
+        tag.color,
+        tag.parent_id
+    );
+    ok(tag)
+}
+
+/// Drops a definition (its children move up a level); tasks keep the tag name.
+fn delete_tag_impl(ctx: &impl CommandCtx, state: &AppState, tag_id: String) -> CommandResult<bool> {
+    let tag_id = tag_id.trim().to_string();
+    if !state.tags().iter().any(|def| def.id == tag_id) {
+        return err("tag not found");
+    }
+    if let Err(message) = save_tag_definitions(ctx, state, "delete_tag", |defs| {
+        remove_definition(defs, &tag_id);
+    }) {
+        return err(&message);
+    }
+    log::info!("cmd=delete_tag ok id={tag_id}");
     ok(true)
 }
 
-fn dismiss_forced_impl(
+fn suggest_tags_impl(
+    state: &AppState,
+    prefix: Option<String>,
+    limit: Option<usize>,
+) -> CommandResult<Vec<TagStat>> {
+    ok(suggest_tag_stats(
+        &state.shared_tasks(),
+        &state.tags(),
+        prefix.as_deref().unwrap_or(""),
+        limit.unwrap_or(DEFAULT_SUGGEST_LIMIT),
+    ))
+}
+
+/// Rewrites `sources` to `target` on every task as one undoable action; returns how many
+/// tasks changed.
+fn retag_impl(
     ctx: &impl CommandCtx,
     state: &AppState,
-    task_id: String,
-) -> CommandResult<bool> {
-    log::info!("cmd=dismiss_forced start task_id={}", task_id);
-    let mut tasks = state.tasks();
-    let mut found = false;
-    if let Some(task) = tasks.iter_mut().find(|t| t.id == task_id) {
-        found = true;
-        task.reminder.forced_dismissed = true;
-        task.reminder.last_fired_at = Some(Utc::now().timestamp());
-        state.update_task(task.clone());
+    command: &str,
+    sources: &[String],
+    target: &str,
+) -> CommandResult<usize> {
+    let Some(target) = normalize_tag(target) else {
+        return err("tag name is required");
+    };
+    let mut keys: Vec<String> = Vec::new();
+    for source in sources {
+        match tag_key(source) {
+            Some(key) if !keys.contains(&key) => keys.push(key),
+            Some(_) => {}
+            None => return err("tag name is required"),
+        }
     }
-    if !found {
-        log::warn!("cmd=dismiss_forced task not found task_id={}", task_id);
+    if keys.is_empty() {
+        return err("tag name is required");
+    }
+    let in_use = tag_stats(&state.shared_tasks(), &state.tags());
+    if let Some(missing) = keys.iter().find(|key| {
+        !in_use
+            .iter()
+            .any(|stat| tag_key(&stat.tag).as_ref() == Some(*key))
+    }) {
+        log::warn!("cmd={command} rejected tag={missing}");
+        return err(&format!("tag not found: {missing}"));
     }
+    let before = state.tasks_file();
+    let changed = state.retag_tasks(&keys, &target, state.clock().timestamp());
+    state.record_undo(command, &before);
     if let Err(error) = persist(ctx, state) {
-        log::error!(
-            "cmd=dismiss_forced persist failed task_id={} err={error}",
-            task_id
-        );
+        log::error!("cmd={command} persist failed err={error}");
         return err(&format!("storage error: {error:?}"));
     }
-    ok(true)
+    log::info!(
+        "cmd={command} ok sources={} target={target} tasks={}",
+        keys.len(),
+        changed.len()
+    );
+    ok(changed.len())
 }
 
-fn delete_task_impl(
+fn rename_tag_impl(
     ctx: &impl CommandCtx,
     state: &AppState,
-    task_id: String,
-) -> CommandResult<bool> {
-    log::info!("cmd=delete_task task_id={}", task_id);
-    state.remove_task(&task_id);
-    if let Err(error) = persist(ctx, state) {
-        log::error!(
-            "cmd=delete_task persist failed task_id={} err={error}",
-            task_id
-        );
-        return err(&format!("storage error: {error:?}"));
-    }
-    ok(true)
+    old: String,
+    new: String,
+) -> CommandResult<usize> {
+    retag_impl(ctx, state, "rename_tag", &[old], &new)
 }
 
-fn delete_tasks_impl(
+fn merge_tags_impl(
     ctx: &impl CommandCtx,
     state: &AppState,
-    task_ids: Vec<String>,
-) -> CommandResult<bool> {
-    log::info!("cmd=delete_tasks count={}", task_ids.len());
-    state.remove_tasks(&task_ids);
-    if let Err(error) = persist(ctx, state) {
-        log::error!("cmd=delete_tasks persist failed err={error}");
-        return err(&format!("storage error: {error:?}"));
-    }
-    ok(true)
-}
-
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn load_state(app: AppHandle, state: State<AppState>) -> CommandResult<StatePayload> {
-    let ctx = TauriCommandCtx { app: &app };
-    load_state_impl(&ctx, state.inner())
-}
-
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn create_project(
-    app: AppHandle,
-    state: State<AppState>,
-    project: Project,
-) -> CommandResult<Project> {
-    let ctx = TauriCommandCtx { app: &app };
-    create_project_impl(&ctx, state.inner(), project)
+    sources: Vec<String>,
+    target: String,
+) -> CommandResult<usize> {
+    retag_impl(ctx, state, "merge_tags", &sources, &target)
 }
 
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn update_project(
-    app: AppHandle,
-    state: State<AppState>,
-    project: Project,
-) -> CommandResult<Project> {
-    let ctx = TauriCommandCtx { app: &app };
-    update_project_impl(&ctx, state.inner(), project)
+fn create_task_impl(ctx: &impl CommandCtx, state: &AppState, task: Task) -> CommandResult<Task> {
+    match prepare_new_task(state, task) {
+        Ok(task) => insert_new_task(ctx, state, task),
+        Err(message) => err(&message),
+    }
 }
 
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn swap_project_sort_order(
-    app: AppHandle,
-    state: State<AppState>,
-    first_id: String,
-    second_id: String,
-) -> CommandResult<bool> {
-    let ctx = TauriCommandCtx { app: &app };
-    swap_project_sort_order_impl(&ctx, state.inner(), first_id, second_id)
+/// `create_task` with `check_duplicates`: an open task with the same title in the same project
+/// on the same day (see `duplicates`) stops the creation and is returned instead, so the UI can
+/// offer to merge or to create anyway (calling again without the check).
+fn create_task_checked_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task: Task,
+    check_duplicates: bool,
+) -> CommandResult<CreateOutcome> {
+    let result = if check_duplicates {
+        let task = match prepare_new_task(state, task) {
+            Ok(task) => task,
+            Err(message) => return err(&message),
+        };
+        let duplicates = find_duplicates(&state.shared_tasks(), &task);
+        if !duplicates.is_empty() {
+            log::info!(
+                "cmd=create_task held back id={} duplicates={}",
+                task.id,
+                duplicates.len()
+            );
+            return ok(CreateOutcome::Duplicates { duplicates });
+        }
+        insert_new_task(ctx, state, task)
+    } else {
+        create_task_impl(ctx, state, task)
+    };
+    CommandResult {
+        ok: result.ok,
+        data: result
+            .data
+            .map(|task| CreateOutcome::Created(Box::new(task))),
+        error: result.error,
+    }
 }
 
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn delete_project(
-    app: AppHandle,
-    state: State<AppState>,
-    project_id: String,
-) -> CommandResult<bool> {
-    let ctx = TauriCommandCtx { app: &app };
-    delete_project_impl(&ctx, state.inner(), project_id)
+/// Validates and fills in a task about to be created: default due date or someday, known
+/// project, normalized fields, quadrant.
+fn prepare_new_task(state: &AppState, task: Task) -> Result<Task, String> {
+    let mut task = task;
+    if let Err(message) = normalize_flair(&mut task).and_then(|()| normalize_timezone(&mut task)) {
+        log::warn!("cmd=create_task rejected id={} err={message}", task.id);
+        return Err(message);
+    }
+    if let Err(message) = validate_blocked_by(&mut task, &state.shared_tasks()) {
+        log::warn!("cmd=create_task rejected id={} err={message}", task.id);
+        return Err(message);
+    }
+    let original_project_id = task.project_id.clone();
+    if task.sort_order == 0 {
+        task.sort_order = task.created_at * 1000;
+    }
+    if task.due_at.is_none() && !task.someday {
+        let settings = state.settings();
+        if settings.quick_no_date_policy == NoDatePolicy::Someday {
+            task.someday = true;
+        } else {
+            task.due_at = Some(default_due_at(state.clock().now_local(), &settings));
+        }
+        log::info!(
+            "cmd=create_task no due date; applied capture policy task_id={} due_at={:?} someday={}",
+            task.id,
+            task.due_at,
+            task.someday
+        );
+    }
+    if !state
+        .projects()
+        .iter()
+        .any(|project| project.id == task.project_id)
+    {
+        log::warn!(
+            "cmd=create_task unknown project_id; using inbox task_id={} original_project_id={}",
+            task.id,
+            original_project_id
+        );
+        task.project_id = "inbox".to_string();
+    }
+    normalize_waiting_on(&mut task, None, state.clock().timestamp());
+    normalize_context(&mut task);
+    normalize_step_parents(&mut task.steps);
+    task.reminder.fold_remind_at(task.due_at);
+    task.refresh_progress();
+    apply_quadrant(
+        &mut task,
+        &state.settings().quadrant_config,
+        state.clock().now_local(),
+    );
+    Ok(task)
 }
 
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn create_task(app: AppHandle, state: State<AppState>, task: Task) -> CommandResult<Task> {
-    let ctx = TauriCommandCtx { app: &app };
-    create_task_impl(&ctx, state.inner(), task)
+fn insert_new_task(ctx: &impl CommandCtx, state: &AppState, task: Task) -> CommandResult<Task> {
+    log::info!(
+        "cmd=create_task id={} project_id={} due_at={:?} important={} quadrant={} reminder_kind={:?} repeat={:?}",
+        task.id,
+        task.project_id,
+        task.due_at,
+        task.important,
+        task.quadrant,
+        task.reminder.kind,
+        task.repeat
+    );
+    state.add_task(task.clone());
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=create_task persist failed id={} err={error}", task.id);
+        return err(&format!("storage error: {error:?}"));
+    }
+    record_activity(
+        ctx,
+        vec![ActivityEntry::new(
+            &task,
+            ActivityKind::Created,
+            state.clock().timestamp(),
+        )],
+    );
+    ctx.dispatch_event(OutboundEvent::TaskCreated(task.clone()));
+    if run_automations(ctx, state, &[(None, task.clone())]) {
+        return ok(state.task(&task.id).unwrap_or(task));
+    }
+    ok(task)
 }
 
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub async fn ai_plan_task(
-    state: State<'_, AppState>,
-    request: AiPlanRequest,
-) -> Result<AiPlan, String> {
-    let snapshot = state.inner().snapshot();
-    let settings = &snapshot.settings;
-    if !settings.ai_enabled {
-        return Err("ai is disabled (settings.ai_enabled=false)".to_string());
+fn update_task_impl(ctx: &impl CommandCtx, state: &AppState, task: Task) -> CommandResult<Task> {
+    let mut task = task;
+    if let Err(message) = normalize_flair(&mut task).and_then(|()| normalize_timezone(&mut task)) {
+        log::warn!("cmd=update_task rejected id={} err={message}", task.id);
+        return err(&message);
     }
-    if settings.deepseek_api_key.trim().is_empty() {
-        return Err("deepseek api key missing (settings.deepseek_api_key)".to_string());
+    if let Err(message) = validate_blocked_by(&mut task, &state.shared_tasks()) {
+        log::warn!("cmd=update_task rejected id={} err={message}", task.id);
+        return err(&message);
     }
-    if settings.ai_model.trim().is_empty() {
-        return Err("ai model missing (settings.ai_model)".to_string());
+    let original_project_id = task.project_id.clone();
+    if task.sort_order == 0 {
+        task.sort_order = task.created_at * 1000;
     }
-
+    if !state
+        .projects()
+        .iter()
+        .any(|project| project.id == task.project_id)
+    {
+        log::warn!(
+            "cmd=update_task unknown project_id; using inbox task_id={} original_project_id={}",
+            task.id,
+            original_project_id
+        );
+        task.project_id = "inbox".to_string();
+    }
+    let previous = state.task(&task.id);
+    normalize_waiting_on(&mut task, previous.as_ref(), state.clock().timestamp());
+    normalize_context(&mut task);
+    normalize_step_parents(&mut task.steps);
+    task.reminder.fold_remind_at(task.due_at);
+    task.refresh_progress();
+    apply_quadrant(
+        &mut task,
+        &state.settings().quadrant_config,
+        state.clock().now_local(),
+    );
     log::info!(
-        "cmd=ai_plan_task start due_at={} important={} reminder_kind={:?} repeat={:?} raw_len={} title_len={} tags={}",
-        request.due_at,
-        request.important,
-        request.reminder_kind,
-        request.repeat,
-        request.raw_input.len(),
-        request.title.len(),
-        request.tags.len()
+        "cmd=update_task id={} project_id={} due_at={:?} important={} quadrant={} reminder_kind={:?} repeat={:?}",
+        task.id,
+        task.project_id,
+        task.due_at,
+        task.important,
+        task.quadrant,
+        task.reminder.kind,
+        task.repeat
     );
-
-    match crate::ai::plan_with_deepseek(settings, &request, &snapshot.projects, &snapshot.tasks)
-        .await
+    state.update_task(task.clone());
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=update_task persist failed id={} err={error}", task.id);
+        return err(&format!("storage error: {error:?}"));
+    }
+    if let Some(notes) = previous
+        .as_ref()
+        .and_then(|previous| replaced_notes(previous, &task))
     {
-        Ok(plan) => Ok(plan),
-        Err(message) => {
-            log::warn!("cmd=ai_plan_task failed err={}", message);
-            Err(message)
-        }
+        record_note_history(ctx, state, vec![(task.id.clone(), notes)]);
+    }
+    if let Some(previous) = &previous {
+        record_activity(
+            ctx,
+            edit_activity(previous, &task, state.clock().timestamp()),
+        );
+    }
+    let newly_completed = task.completed && !previous.as_ref().is_some_and(|prev| prev.completed);
+    ctx.dispatch_event(if newly_completed {
+        OutboundEvent::TaskCompleted(task.clone())
+    } else {
+        OutboundEvent::TaskUpdated(task.clone())
+    });
+    if run_automations(ctx, state, &[(previous, task.clone())]) {
+        return ok(state.task(&task.id).unwrap_or(task));
     }
+    ok(task)
 }
 
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn update_task(app: AppHandle, state: State<AppState>, task: Task) -> CommandResult<Task> {
-    let ctx = TauriCommandCtx { app: &app };
-    update_task_impl(&ctx, state.inner(), task)
+/// Normalizes and writes a batch of edited tasks; returns how many were moved to the inbox
+/// because their project no longer exists.
+fn apply_bulk_update(state: &AppState, mut tasks: Vec<Task>) -> Result<usize, String> {
+    let existing = state.tasks();
+    // Dependencies are checked against the data as it will be after the batch, so two edits
+    // cannot close a cycle between them.
+    let mut merged = existing.clone();
+    for task in &tasks {
+        if let Some(slot) = merged.iter_mut().find(|t| t.id == task.id) {
+            *slot = task.clone();
+        }
+    }
+    // Validate the whole batch first so a bad entry does not leave it half applied.
+    for task in &mut tasks {
+        if let Err(message) = normalize_flair(task)
+            .and_then(|()| normalize_timezone(task))
+            .and_then(|()| validate_blocked_by(task, &merged))
+        {
+            log::warn!(
+                "cmd=bulk_update_tasks rejected id={} err={message}",
+                task.id
+            );
+            return Err(message);
+        }
+    }
+    let projects = state.projects();
+    let quadrant_config = state.settings().quadrant_config;
+    let local_now = state.clock().now_local();
+    let now = local_now.timestamp();
+    let total = tasks.len();
+    let mut remapped_projects = 0usize;
+    for mut task in tasks {
+        if task.sort_order == 0 {
+            task.sort_order = task.created_at * 1000;
+        }
+        if !projects.iter().any(|project| project.id == task.project_id) {
+            remapped_projects += 1;
+            task.project_id = "inbox".to_string();
+        }
+        let previous = existing.iter().find(|t| t.id == task.id);
+        normalize_waiting_on(&mut task, previous, now);
+        normalize_context(&mut task);
+        normalize_step_parents(&mut task.steps);
+        task.reminder.fold_remind_at(task.due_at);
+        task.refresh_progress();
+        apply_quadrant(&mut task, &quadrant_config, local_now);
+        state.update_task(task);
+    }
+    log::info!(
+        "cmd=bulk_update_tasks count={} remapped_projects={}",
+        total,
+        remapped_projects
+    );
+    Ok(remapped_projects)
 }
 
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn bulk_update_tasks(
-    app: AppHandle,
-    state: State<AppState>,
+fn bulk_update_tasks_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
     tasks: Vec<Task>,
 ) -> CommandResult<bool> {
-    let ctx = TauriCommandCtx { app: &app };
-    bulk_update_tasks_impl(&ctx, state.inner(), tasks)
+    let existing = state.shared_tasks();
+    let previous: Vec<Task> = tasks
+        .iter()
+        .filter_map(|task| existing.iter().find(|t| t.id == task.id).cloned())
+        .collect();
+    let replaced: Vec<(String, String)> = tasks
+        .iter()
+        .filter_map(|task| {
+            let previous = previous.iter().find(|t| t.id == task.id)?;
+            Some((task.id.clone(), replaced_notes(previous, task)?))
+        })
+        .collect();
+    if let Err(message) = apply_bulk_update(state, tasks) {
+        return err(&message);
+    }
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=bulk_update_tasks persist failed err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    record_note_history(ctx, state, replaced);
+    let now = state.clock().timestamp();
+    let activity = previous
+        .iter()
+        .filter_map(|previous| Some(edit_activity(previous, &state.task(&previous.id)?, now)))
+        .flatten()
+        .collect();
+    record_activity(ctx, activity);
+    let changes: Vec<(Option<Task>, Task)> = previous
+        .into_iter()
+        .filter_map(|previous| {
+            let saved = state.task(&previous.id)?;
+            Some((Some(previous), saved))
+        })
+        .collect();
+    run_automations(ctx, state, &changes);
+    ok(true)
 }
 
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn swap_sort_order(
-    app: AppHandle,
-    state: State<AppState>,
-    first_id: String,
-    second_id: String,
-) -> CommandResult<bool> {
-    let ctx = TauriCommandCtx { app: &app };
-    swap_sort_order_impl(&ctx, state.inner(), first_id, second_id)
-}
-
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn complete_task(
-    app: AppHandle,
-    state: State<AppState>,
-    task_id: String,
-) -> CommandResult<Task> {
-    let ctx = TauriCommandCtx { app: &app };
-    complete_task_impl(&ctx, state.inner(), task_id)
-}
-
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn bulk_complete_tasks(
-    app: AppHandle,
-    state: State<AppState>,
-    task_ids: Vec<String>,
-) -> CommandResult<bool> {
-    let ctx = TauriCommandCtx { app: &app };
-    bulk_complete_tasks_impl(&ctx, state.inner(), task_ids)
-}
-
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn update_settings(
-    app: AppHandle,
-    state: State<AppState>,
-    settings: Settings,
-) -> CommandResult<Settings> {
-    let ctx = TauriCommandCtx { app: &app };
-    update_settings_impl(&ctx, state.inner(), settings)
-}
-
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub async fn show_settings_window(app: AppHandle) -> CommandResult<bool> {
-    log::info!("cmd=show_settings_window");
-    // Creating a new window via Wry must happen off the main event-loop thread, otherwise
-    // tauri-runtime-wry's channel-based dispatcher can deadlock. Async commands run on the
-    // async runtime, so we can safely spawn a blocking task here.
-    let join = tauri::async_runtime::spawn_blocking(move || show_settings_window_impl(&app));
-    match join.await {
-        Ok(Ok(())) => ok(true),
-        Ok(Err(message)) => {
-            log::error!("cmd=show_settings_window failed: {message}");
-            err(&message)
-        }
-        Err(join_err) => {
-            let message = format!("cmd=show_settings_window join failed: {join_err}");
-            log::error!("{message}");
-            err(&message)
+/// Keeps the notes an edit overwrote in `note_history.jsonl`. Best effort: the edit itself is
+/// already saved, so a failure is only logged.
+fn record_note_history(ctx: &impl CommandCtx, state: &AppState, replaced: Vec<(String, String)>) {
+    if replaced.is_empty() {
+        return;
+    }
+    let root = match ctx.app_data_dir() {
+        Ok(root) => root,
+        Err(error) => {
+            log::error!("note_history: app_data_dir failed: {error}");
+            return;
         }
+    };
+    match record_note_versions(&root, &replaced, state.clock().timestamp()) {
+        Ok(added) => log::debug!("note_history: recorded versions={}", added.len()),
+        Err(error) => log::error!("note_history: write failed err={error}"),
     }
 }
 
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn frontend_log(level: String, message: String, context: Option<serde_json::Value>) -> bool {
-    const MAX_CHARS: usize = 4000;
-
-    let lvl = level.trim().to_lowercase();
-    let trimmed = message.trim();
-
-    let mut msg: String = trimmed.chars().take(MAX_CHARS).collect();
-    if trimmed.chars().count() > MAX_CHARS {
-        msg.push_str("...");
+/// Appends to the task timeline in `activity.jsonl`. Best effort, like the note history.
+fn record_activity(ctx: &impl CommandCtx, entries: Vec<ActivityEntry>) {
+    if entries.is_empty() {
+        return;
     }
-
-    let ctx = context
-        .and_then(|v| serde_json::to_string(&v).ok())
-        .unwrap_or_default();
-
-    match lvl.as_str() {
-        "error" => log::error!("frontend_log: {msg} ctx={ctx}"),
-        "warn" | "warning" => log::warn!("frontend_log: {msg} ctx={ctx}"),
-        "debug" => log::debug!("frontend_log: {msg} ctx={ctx}"),
-        "trace" => log::trace!("frontend_log: {msg} ctx={ctx}"),
-        _ => log::info!("frontend_log: {msg} ctx={ctx}"),
+    let root = match ctx.app_data_dir() {
+        Ok(root) => root,
+        Err(error) => {
+            log::error!("activity: app_data_dir failed: {error}");
+            return;
+        }
+    };
+    match append_activity(&root, &entries) {
+        Ok(()) => log::debug!("activity: recorded entries={}", entries.len()),
+        Err(error) => log::error!("activity: write failed err={error}"),
     }
-
-    true
 }
 
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn set_shortcut_capture_active(state: State<AppState>, active: bool) -> CommandResult<bool> {
-    log::info!("cmd=set_shortcut_capture_active active={}", active);
-    state.set_shortcut_capture_active(active);
-    ok(true)
-}
-
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn snooze_task(
-    app: AppHandle,
-    state: State<AppState>,
+fn get_note_history_impl(
+    ctx: &impl CommandCtx,
     task_id: String,
-    until: i64,
-) -> CommandResult<bool> {
-    let ctx = TauriCommandCtx { app: &app };
-    snooze_task_impl(&ctx, state.inner(), task_id, until)
+) -> CommandResult<Vec<NoteVersion>> {
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    match read_note_history(&root, task_id.trim()) {
+        Ok(versions) => {
+            log::info!(
+                "cmd=get_note_history ok task_id={} versions={}",
+                task_id.trim(),
+                versions.len()
+            );
+            ok(versions)
+        }
+        Err(error) => {
+            log::error!("cmd=get_note_history failed err={error}");
+            err(&format!("storage error: {error:?}"))
+        }
+    }
 }
 
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn dismiss_forced(
-    app: AppHandle,
-    state: State<AppState>,
+/// Puts a saved version back into the task's notes through `update_task`, so the notes being
+/// replaced become a new version themselves.
+fn restore_note_version_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
     task_id: String,
-) -> CommandResult<bool> {
-    let ctx = TauriCommandCtx { app: &app };
-    dismiss_forced_impl(&ctx, state.inner(), task_id)
-}
-
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn delete_task(app: AppHandle, state: State<AppState>, task_id: String) -> CommandResult<bool> {
-    let ctx = TauriCommandCtx { app: &app };
-    delete_task_impl(&ctx, state.inner(), task_id)
-}
-
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn delete_tasks(
-    app: AppHandle,
-    state: State<AppState>,
-    task_ids: Vec<String>,
-) -> CommandResult<bool> {
-    let ctx = TauriCommandCtx { app: &app };
-    delete_tasks_impl(&ctx, state.inner(), task_ids)
-}
-
-#[derive(Debug, serde::Serialize)]
-pub struct BackupEntry {
-    pub name: String,
-    pub modified_at: i64,
-}
-
-fn list_backups_impl(ctx: &impl CommandCtx) -> CommandResult<Vec<BackupEntry>> {
-    log::info!("cmd=list_backups start");
+    version: u32,
+) -> CommandResult<Task> {
+    let task_id = task_id.trim().to_string();
+    let Some(mut task) = state.task(&task_id) else {
+        return err("task not found");
+    };
     let root = match ctx.app_data_dir() {
         Ok(path) => path,
         Err(e) => return err(&format!("app_data_dir error: {e}")),
     };
-    let storage = Storage::new(root);
-
-    // If the backup directory does not exist yet, create it and return an empty list.
-    let list = match storage.list_backups() {
-        Ok(list) => list,
-        Err(StorageError::Io(io)) if io.kind() == std::io::ErrorKind::NotFound => {
-            if let Err(error) = storage.ensure_dirs() {
-                log::error!("cmd=list_backups ensure_dirs failed err={error}");
-                return err(&format!("storage error: {error:?}"));
-            }
-            log::info!("cmd=list_backups backup dir missing; created");
-            Vec::new()
-        }
+    let history = match read_note_history(&root, &task_id) {
+        Ok(history) => history,
         Err(error) => {
-            log::error!("cmd=list_backups list failed err={error}");
+            log::error!("cmd=restore_note_version failed err={error}");
             return err(&format!("storage error: {error:?}"));
         }
     };
-
-    let entries: Vec<BackupEntry> = list
-        .into_iter()
-        .map(|(name, modified_at)| BackupEntry { name, modified_at })
-        .collect();
-    log::info!("cmd=list_backups ok count={}", entries.len());
-    ok(entries)
+    let Some(saved) = history.into_iter().find(|entry| entry.version == version) else {
+        return err("note version not found");
+    };
+    log::info!("cmd=restore_note_version task_id={task_id} version={version}");
+    task.notes = Some(saved.notes);
+    task.updated_at = state.clock().timestamp();
+    update_task_impl(ctx, state, task)
 }
 
-fn delete_backup_impl(ctx: &impl CommandCtx, filename: String) -> CommandResult<bool> {
-    log::info!("cmd=delete_backup start filename={}", filename);
+/// Timeline entries shown when `limit` is not given.
+const DEFAULT_ACTIVITY_LIMIT: usize = 50;
+
+fn read_activity_command(
+    ctx: &impl CommandCtx,
+    command: &str,
+    task_id: Option<&str>,
+    limit: Option<usize>,
+) -> CommandResult<Vec<ActivityEntry>> {
     let root = match ctx.app_data_dir() {
         Ok(path) => path,
         Err(e) => return err(&format!("app_data_dir error: {e}")),
     };
-    let storage = Storage::new(root);
-    if let Err(error) = storage.ensure_dirs() {
-        log::error!(
-            "cmd=delete_backup ensure_dirs failed filename={} err={error}",
-            filename
-        );
-        return err(&format!("storage error: {error:?}"));
-    }
-    if let Err(error) = storage.delete_backup(&filename) {
-        log::error!("cmd=delete_backup failed filename={} err={error}", filename);
-        return err(&format!("storage error: {error:?}"));
+    match read_activity(&root, task_id, limit.unwrap_or(DEFAULT_ACTIVITY_LIMIT)) {
+        Ok(entries) => {
+            log::info!("cmd={command} ok count={}", entries.len());
+            ok(entries)
+        }
+        Err(error) => {
+            log::error!("cmd={command} failed err={error}");
+            err(&format!("storage error: {error:?}"))
+        }
     }
-    log::info!("cmd=delete_backup ok filename={}", filename);
-    ok(true)
 }
 
-fn create_backup_impl(ctx: &impl CommandCtx, state: &AppState) -> CommandResult<bool> {
-    log::info!("cmd=create_backup start");
-    let root = match ctx.app_data_dir() {
-        Ok(path) => path,
-        Err(e) => return err(&format!("app_data_dir error: {e}")),
-    };
-    let storage = Storage::new(root);
-    if let Err(error) = storage.ensure_dirs() {
-        log::error!("cmd=create_backup ensure_dirs failed err={error}");
-        return err(&format!("storage error: {error:?}"));
+fn get_task_activity_impl(
+    ctx: &impl CommandCtx,
+    task_id: String,
+    limit: Option<usize>,
+) -> CommandResult<Vec<ActivityEntry>> {
+    read_activity_command(ctx, "get_task_activity", Some(task_id.trim()), limit)
+}
+
+fn get_recent_activity_impl(
+    ctx: &impl CommandCtx,
+    limit: Option<usize>,
+) -> CommandResult<Vec<ActivityEntry>> {
+    read_activity_command(ctx, "get_recent_activity", None, limit)
+}
+
+/// Runs `apply` against a detached copy of the state and reports what it changed; nothing is
+/// persisted or emitted.
+fn preview_changes(
+    state: &AppState,
+    apply: impl FnOnce(&AppState) -> Result<(), String>,
+) -> CommandResult<ChangeSet> {
+    let scratch = state.detached();
+    if let Err(message) = apply(&scratch) {
+        return err(&message);
     }
-    let tasks_file = state.tasks_file();
-    log::info!(
-        "cmd=create_backup saving tasks with backup tasks={} projects={}",
-        tasks_file.tasks.len(),
-        tasks_file.projects.len()
-    );
-    if let Err(error) = storage.save_tasks(&tasks_file, true) {
-        log::error!("cmd=create_backup save_tasks failed err={error}");
-        return err(&format!("storage error: {error:?}"));
+    ok(diff_snapshots(&state.snapshot(), &scratch.snapshot()))
+}
+
+/// Dispatch for commands that take `dry_run`: the preview when set, otherwise the real command.
+fn with_dry_run<T>(
+    dry_run: Option<bool>,
+    preview: impl FnOnce() -> CommandResult<ChangeSet>,
+    apply: impl FnOnce() -> CommandResult<T>,
+) -> CommandResult<Outcome<T>> {
+    if dry_run.unwrap_or(false) {
+        let result = preview();
+        return CommandResult {
+            ok: result.ok,
+            data: result.data.map(Outcome::DryRun),
+            error: result.error,
+        };
     }
-    let now = Utc::now().timestamp();
-    let mut settings = state.settings();
-    settings.last_backup_at = Some(now);
-    state.update_settings(settings.clone());
-    if let Err(error) = storage.save_settings(&state.settings_file()) {
-        log::error!("cmd=create_backup save_settings failed err={error}");
-        return err(&format!("storage error: {error:?}"));
+    let result = apply();
+    CommandResult {
+        ok: result.ok,
+        data: result.data.map(Outcome::Applied),
+        error: result.error,
     }
-    log::info!("cmd=create_backup ok last_backup_at={now}");
-    ok(true)
 }
 
-fn restore_backup_impl(
+fn bulk_update_tasks_dry_run_impl(state: &AppState, tasks: Vec<Task>) -> CommandResult<ChangeSet> {
+    let result = preview_changes(state, |scratch| {
+        apply_bulk_update(scratch, tasks).map(|_| ())
+    });
+    log::info!("cmd=bulk_update_tasks dry_run ok={}", result.ok);
+    result
+}
+
+fn swap_sort_order_impl(
     ctx: &impl CommandCtx,
     state: &AppState,
-    filename: String,
-) -> CommandResult<Vec<Task>> {
-    log::info!("cmd=restore_backup start filename={}", filename);
-    let root = match ctx.app_data_dir() {
-        Ok(path) => path,
-        Err(e) => return err(&format!("app_data_dir error: {e}")),
-    };
-    let storage = Storage::new(root);
-    if let Err(error) = storage.ensure_dirs() {
-        log::error!(
-            "cmd=restore_backup ensure_dirs failed filename={} err={error}",
-            filename
-        );
-        return err(&format!("storage error: {error:?}"));
+    first_id: String,
+    second_id: String,
+) -> CommandResult<bool> {
+    let now = state.clock().timestamp();
+    if !state.swap_sort_order(&first_id, &second_id, now) {
+        return err("task not found");
     }
-    let data = match storage.restore_backup(&filename) {
-        Ok(data) => data,
-        Err(error) => {
-            log::error!(
-                "cmd=restore_backup failed filename={} err={error}",
-                filename
-            );
-            return err(&format!("storage error: {error:?}"));
-        }
-    };
     log::info!(
-        "cmd=restore_backup loaded filename={} tasks={} projects={}",
-        filename,
-        data.tasks.len(),
-        data.projects.len()
+        "cmd=swap_sort_order ok first_id={} second_id={} at={}",
+        first_id,
+        second_id,
+        now
     );
-    state.replace_projects(data.projects.clone());
-    state.replace_tasks(data.tasks.clone());
-    ctx.update_tray_count(&state.tasks(), &state.settings());
-    let payload = StatePayload {
-        tasks: state.tasks(),
-        projects: state.projects(),
-        settings: state.settings(),
-    };
-    ctx.emit_state_updated(payload);
-    log::info!("cmd=restore_backup ok filename={}", filename);
-    ok(data.tasks)
+    if let Err(error) = persist(ctx, state) {
+        log::error!(
+            "cmd=swap_sort_order persist failed first_id={} second_id={} err={error}",
+            first_id,
+            second_id
+        );
+        return err(&format!("storage error: {error:?}"));
+    }
+    ok(true)
 }
 
-fn import_backup_impl(
+fn set_task_pinned_impl(
     ctx: &impl CommandCtx,
     state: &AppState,
-    path: String,
-) -> CommandResult<Vec<Task>> {
-    log::info!("cmd=import_backup start path={}", path);
-    let root = match ctx.app_data_dir() {
-        Ok(path) => path,
-        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    task_id: String,
+    pinned: bool,
+) -> CommandResult<Task> {
+    let cmd = if pinned { "pin_task" } else { "unpin_task" };
+    let now = state.clock().timestamp();
+    let Some(task) = state.set_task_pinned(&task_id, pinned, now) else {
+        log::warn!("cmd={cmd} task not found id={task_id}");
+        return err("task not found");
     };
-    let storage = Storage::new(root);
-    if let Err(error) = storage.ensure_dirs() {
-        log::error!(
-            "cmd=import_backup ensure_dirs failed path={} err={error}",
-            path
-        );
+    log::info!("cmd={cmd} ok id={task_id}");
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd={cmd} persist failed id={task_id} err={error}");
         return err(&format!("storage error: {error:?}"));
     }
-    let data = match storage.restore_from_path(std::path::Path::new(&path)) {
-        Ok(data) => data,
-        Err(error) => {
-            log::error!("cmd=import_backup failed path={} err={error}", path);
-            return err(&format!("storage error: {error:?}"));
-        }
-    };
-    log::info!(
-        "cmd=import_backup loaded path={} tasks={} projects={}",
-        path,
-        data.tasks.len(),
-        data.projects.len()
-    );
-    state.replace_projects(data.projects.clone());
-    state.replace_tasks(data.tasks.clone());
-    ctx.update_tray_count(&state.tasks(), &state.settings());
-    let payload = StatePayload {
-        tasks: state.tasks(),
-        projects: state.projects(),
-        settings: state.settings(),
+    ok(task)
+}
+
+/// Holds back reminders for every task in the project, optionally until `until`.
+fn mute_project_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    project_id: String,
+    until: Option<Timestamp>,
+) -> CommandResult<Project> {
+    let now = state.clock().timestamp();
+    if until.is_some_and(|until| until <= now) {
+        return err("mute end must be in the future");
+    }
+    let Some(project) = state.set_project_muted(&project_id, true, until, now) else {
+        log::warn!("cmd=mute_project project not found id={project_id}");
+        return err("project not found");
     };
-    ctx.emit_state_updated(payload);
-    log::info!("cmd=import_backup ok path={}", path);
-    ok(data.tasks)
+    log::info!("cmd=mute_project ok id={project_id} until={until:?}");
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=mute_project persist failed id={project_id} err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    ok(project)
 }
 
-fn export_default_path(root: &Path, ext: &str) -> PathBuf {
-    let exports_dir = root.join("exports");
-    let stamp = Local::now().format("%Y%m%d-%H%M%S").to_string();
-    exports_dir.join(format!("mustdo-{stamp}.{ext}"))
+fn unmute_project_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    project_id: String,
+) -> CommandResult<Project> {
+    let now = state.clock().timestamp();
+    let Some(project) = state.set_project_muted(&project_id, false, None, now) else {
+        log::warn!("cmd=unmute_project project not found id={project_id}");
+        return err("project not found");
+    };
+    log::info!("cmd=unmute_project ok id={project_id}");
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=unmute_project persist failed id={project_id} err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    ok(project)
 }
 
-#[cfg_attr(coverage, inline(never))]
-fn write_atomic_bytes(path: &Path, bytes: &[u8]) -> Result<(), StorageError> {
-    let tmp = path.with_extension("tmp");
-    fs::create_dir_all(
-        path.parent()
-            .ok_or_else(|| StorageError::Io(std::io::Error::other("invalid export path")))?,
-    )?;
-    fs::write(&tmp, bytes)?;
-    fs::rename(tmp, path)?;
-    Ok(())
+fn move_to_someday_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_id: String,
+) -> CommandResult<Task> {
+    let now = state.clock().timestamp();
+    let Some(task) = state.set_task_someday(&task_id, None, now) else {
+        log::warn!("cmd=move_to_someday task not found id={task_id}");
+        return err("task not found");
+    };
+    log::info!("cmd=move_to_someday ok id={task_id}");
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=move_to_someday persist failed id={task_id} err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    ok(task)
 }
 
-fn export_tasks_json_impl(ctx: &dyn CommandCtx, state: &AppState) -> CommandResult<String> {
-    log::info!("cmd=export_tasks_json start");
-    let root = match ctx.app_data_dir() {
-        Ok(path) => path,
-        Err(e) => return err(&format!("app_data_dir error: {e}")),
+/// Takes a task out of the backlog; without `due_at` it gets the quick-capture default due time.
+fn move_from_someday_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_id: String,
+    due_at: Option<i64>,
+) -> CommandResult<Task> {
+    let now = state.clock().now_local();
+    let due_at = due_at.unwrap_or_else(|| default_due_at(now, &state.settings()));
+    let Some(mut task) = state.set_task_someday(&task_id, Some(due_at), now.timestamp()) else {
+        log::warn!("cmd=move_from_someday task not found id={task_id}");
+        return err("task not found");
     };
+    let quadrant_config = state.settings().quadrant_config;
+    if quadrant_config.auto_classify {
+        apply_quadrant(&mut task, &quadrant_config, now);
+        state.update_task(task.clone());
+    }
+    log::info!("cmd=move_from_someday ok id={task_id} due_at={due_at}");
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=move_from_someday persist failed id={task_id} err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    ok(task)
+}
 
-    let path = export_default_path(&root, "json");
-    let data = state.tasks_file();
-    struct ForcedJsonError;
+fn add_comment_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_id: String,
+    text: String,
+) -> CommandResult<Comment> {
+    let text = match normalize_comment_text(&text) {
+        Ok(text) => text,
+        Err(message) => {
+            log::warn!("cmd=add_comment rejected task_id={task_id} err={message}");
+            return err(&message);
+        }
+    };
+    let now = state.clock().timestamp();
+    let Some(comment) = state.add_task_comment(&task_id, text, now) else {
+        log::warn!("cmd=add_comment task not found task_id={task_id}");
+        return err("task not found");
+    };
+    log::info!("cmd=add_comment ok task_id={task_id} id={}", comment.id);
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=add_comment persist failed task_id={task_id} err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    ok(comment)
+}
 
-    impl serde::Serialize for ForcedJsonError {
-        fn serialize<S>(&self, _serializer: S) -> Result<S::Ok, S::Error>
-        where
-            S: serde::Serializer,
-        {
-            Err(<S::Error as serde::ser::Error>::custom(
-                "forced json serialization error",
-            ))
+fn delete_comment_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_id: String,
+    comment_id: String,
+) -> CommandResult<bool> {
+    let now = state.clock().timestamp();
+    match state.delete_task_comment(&task_id, &comment_id, now) {
+        None => {
+            log::warn!("cmd=delete_comment task not found task_id={task_id}");
+            return err("task not found");
+        }
+        Some(false) => {
+            log::warn!("cmd=delete_comment comment not found task_id={task_id} id={comment_id}");
+            return err("comment not found");
         }
+        Some(true) => {}
+    }
+    log::info!("cmd=delete_comment ok task_id={task_id} id={comment_id}");
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=delete_comment persist failed task_id={task_id} err={error}");
+        return err(&format!("storage error: {error:?}"));
     }
+    ok(true)
+}
 
-    let json = match if ctx.force_json_serialize_error() {
-        // `TasksFile` is expected to be always serializable. This branch exists solely for tests.
-        serde_json::to_vec_pretty(&ForcedJsonError)
-    } else {
-        serde_json::to_vec_pretty(&data)
-    } {
-        Ok(bytes) => bytes,
-        Err(e) => {
-            log::error!("cmd=export_tasks_json json serialize failed err={e}");
-            return err(&format!("json error: {e}"));
+/// Turns a step (and the steps nested under it) into a task of its own; returns the new task.
+fn promote_step_to_task_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_id: String,
+    step_id: String,
+) -> CommandResult<Task> {
+    let before = state.tasks_file();
+    let (task, promoted) = match state.promote_task_step(
+        &task_id,
+        &step_id,
+        state.clock().timestamp(),
+    ) {
+        Ok(tasks) => tasks,
+        Err(message) => {
+            log::warn!(
+                    "cmd=promote_step_to_task rejected task_id={task_id} step_id={step_id} err={message}"
+                );
+            return err(&message);
         }
     };
+    state.record_undo("promote_step_to_task", &before);
+    log::info!(
+        "cmd=promote_step_to_task ok task_id={task_id} step_id={step_id} new_id={} steps={}",
+        promoted.id,
+        promoted.steps.len()
+    );
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=promote_step_to_task persist failed task_id={task_id} err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    ctx.dispatch_event(OutboundEvent::TaskUpdated(task));
+    ctx.dispatch_event(OutboundEvent::TaskCreated(promoted.clone()));
+    ok(promoted)
+}
 
-    if let Err(error) = write_atomic_bytes(&path, &json) {
-        log::error!(
-            "cmd=export_tasks_json write failed path={} err={error}",
-            path.display()
-        );
-        return err(&format!("export error: {error:?}"));
+/// Folds `task_id` into `target_task_id` as a step (nested under `parent_step_id` when given),
+/// bringing its steps along; returns the updated target.
+fn convert_task_to_step_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_id: String,
+    target_task_id: String,
+    parent_step_id: Option<String>,
+) -> CommandResult<Task> {
+    let before = state.tasks_file();
+    let target = match state.convert_task_to_steps(
+        &task_id,
+        &target_task_id,
+        parent_step_id.as_deref(),
+        state.clock().timestamp(),
+    ) {
+        Ok(target) => target,
+        Err(message) => {
+            log::warn!(
+                "cmd=convert_task_to_step rejected task_id={task_id} target={target_task_id} err={message}"
+            );
+            return err(&message);
+        }
+    };
+    state.record_undo("convert_task_to_step", &before);
+    log::info!(
+        "cmd=convert_task_to_step ok task_id={task_id} target={target_task_id} steps={}",
+        target.steps.len()
+    );
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=convert_task_to_step persist failed task_id={task_id} err={error}");
+        return err(&format!("storage error: {error:?}"));
     }
+    ctx.dispatch_event(OutboundEvent::TaskDeleted {
+        task_ids: vec![task_id],
+    });
+    ctx.dispatch_event(OutboundEvent::TaskUpdated(target.clone()));
+    ok(target)
+}
 
+/// Starts tracking time on `task_id`; a timer running on another task is stopped first.
+fn start_timer_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_id: String,
+) -> CommandResult<Task> {
+    match state.task(&task_id) {
+        None => {
+            log::warn!("cmd=start_timer task not found id={task_id}");
+            return err("task not found");
+        }
+        Some(task) if task.completed => return err("task is already completed"),
+        Some(_) => {}
+    }
+    let now = state.clock().timestamp();
+    let Some((task, stopped)) = state.start_task_timer(&task_id, now) else {
+        return err("task not found");
+    };
     log::info!(
-        "cmd=export_tasks_json ok path={} tasks={} projects={}",
-        path.display(),
-        data.tasks.len(),
-        data.projects.len()
+        "cmd=start_timer ok id={task_id} stopped={}",
+        stopped
+            .iter()
+            .map(|task| task.id.as_str())
+            .collect::<Vec<_>>()
+            .join(",")
     );
-    ok(path.to_string_lossy().to_string())
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=start_timer persist failed id={task_id} err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    ok(task)
 }
 
-fn csv_escape(value: &str) -> String {
-    // Minimal CSV escaping: wrap in quotes and double any existing quotes.
-    let escaped = value.replace('"', "\"\"");
-    format!("\"{escaped}\"")
+/// Stops the running timer (only `task_id`'s when given); returns the tasks that were stopped.
+fn stop_timer_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_id: Option<String>,
+) -> CommandResult<Vec<Task>> {
+    let now = state.clock().timestamp();
+    let stopped = state.stop_task_timers(task_id.as_deref(), now);
+    if stopped.is_empty() {
+        return ok(stopped);
+    }
+    log::info!("cmd=stop_timer ok count={}", stopped.len());
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=stop_timer persist failed err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    ok(stopped)
 }
 
-fn export_tasks_csv_impl(ctx: &impl CommandCtx, state: &AppState) -> CommandResult<String> {
-    log::info!("cmd=export_tasks_csv start");
-    let root = match ctx.app_data_dir() {
-        Ok(path) => path,
-        Err(e) => return err(&format!("app_data_dir error: {e}")),
+fn report_time_impl(
+    state: &AppState,
+    group: Option<TimeReportGroup>,
+    from: Option<Timestamp>,
+    to: Option<Timestamp>,
+) -> CommandResult<Vec<TimeReportRow>> {
+    let group = group.unwrap_or_default();
+    let rows = build_time_report(
+        &state.shared_tasks(),
+        group,
+        from,
+        to,
+        state.clock().timestamp(),
+    );
+    log::info!("cmd=report_time ok group={group:?} rows={}", rows.len());
+    ok(rows)
+}
+
+fn unblock_chain_impl(state: &AppState, task_id: String) -> CommandResult<UnblockChain> {
+    let Some(chain) = build_unblock_chain(&state.shared_tasks(), &task_id) else {
+        log::warn!("cmd=unblock_chain task not found task_id={task_id}");
+        return err("task not found");
     };
+    log::info!(
+        "cmd=unblock_chain ok task_id={task_id} nodes={} actionable={}",
+        chain.nodes.len(),
+        chain.actionable.len()
+    );
+    ok(chain)
+}
 
-    let path = export_default_path(&root, "csv");
-    let tasks = state.tasks();
-    let tasks_len = tasks.len();
-
-    let mut out = String::new();
-    out.push_str("id,project_id,title,due_at,important,completed,quadrant,tags,notes,steps\n");
-    for task in tasks {
-        let tags = task.tags.join(";");
-        let notes = task.notes.unwrap_or_default().replace("\r\n", "\n");
-        let steps = task
-            .steps
-            .iter()
-            .map(|s| {
-                if s.completed {
-                    format!("[x] {}", s.title)
-                } else {
-                    format!("[ ] {}", s.title)
-                }
-            })
-            .collect::<Vec<_>>()
-            .join(" | ");
-
-        out.push_str(&csv_escape(&task.id));
-        out.push(',');
-        out.push_str(&csv_escape(&task.project_id));
-        out.push(',');
-        out.push_str(&csv_escape(&task.title));
-        out.push(',');
-        out.push_str(&task.due_at.to_string());
-        out.push(',');
-        out.push_str(if task.important { "true" } else { "false" });
-        out.push(',');
-        out.push_str(if task.completed { "true" } else { "false" });
-        out.push(',');
-        out.push_str(&task.quadrant.to_string());
-        out.push(',');
-        out.push_str(&csv_escape(&tags));
-        out.push(',');
-        out.push_str(&csv_escape(&notes));
-        out.push(',');
-        out.push_str(&csv_escape(&steps));
-        out.push('\n');
+fn add_attachment_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_id: String,
+    path: String,
+) -> CommandResult<Attachment> {
+    let Some(task) = state.task(&task_id) else {
+        log::warn!("cmd=add_attachment task not found task_id={task_id}");
+        return err("task not found");
+    };
+    let source = Path::new(&path);
+    let size = match check_source(&task, source) {
+        Ok(size) => size,
+        Err(message) => {
+            log::warn!("cmd=add_attachment rejected task_id={task_id} err={message}");
+            return err(&message);
+        }
+    };
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    let storage = Storage::new(root);
+    let now = state.clock().timestamp();
+    let attachment = new_attachment(&task, source, size, now, |file| {
+        storage.attachment_exists(file)
+    });
+    if let Err(error) = storage.store_attachment(source, &attachment.file) {
+        log::error!("cmd=add_attachment copy failed task_id={task_id} err={error}");
+        return err(&format!("storage error: {error:?}"));
     }
-
-    if let Err(error) = write_atomic_bytes(&path, out.as_bytes()) {
-        log::error!(
-            "cmd=export_tasks_csv write failed path={} err={error}",
-            path.display()
-        );
-        return err(&format!("export error: {error:?}"));
+    if !state.add_task_attachment(&task_id, attachment.clone(), now) {
+        // Deleted while the file was copied; the copy is collected on the next start.
+        return err("task not found");
     }
-
     log::info!(
-        "cmd=export_tasks_csv ok path={} tasks={}",
-        path.display(),
-        tasks_len
+        "cmd=add_attachment ok task_id={task_id} id={} size={}",
+        attachment.id,
+        attachment.size
     );
-    ok(path.to_string_lossy().to_string())
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=add_attachment persist failed task_id={task_id} err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    ok(attachment)
+}
+
+/// Drops the reference only; the file goes with the startup garbage collection once no backup
+/// needs it either.
+fn remove_attachment_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_id: String,
+    attachment_id: String,
+) -> CommandResult<bool> {
+    let now = state.clock().timestamp();
+    match state.remove_task_attachment(&task_id, &attachment_id, now) {
+        None => {
+            log::warn!("cmd=remove_attachment task not found task_id={task_id}");
+            return err("task not found");
+        }
+        Some(false) => {
+            log::warn!(
+                "cmd=remove_attachment attachment not found task_id={task_id} id={attachment_id}"
+            );
+            return err("attachment not found");
+        }
+        Some(true) => {}
+    }
+    log::info!("cmd=remove_attachment ok task_id={task_id} id={attachment_id}");
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=remove_attachment persist failed task_id={task_id} err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    ok(true)
 }
 
-fn export_tasks_markdown_impl(ctx: &impl CommandCtx, state: &AppState) -> CommandResult<String> {
-    log::info!("cmd=export_tasks_markdown start");
+/// Path of the stored copy, for the OS to open.
+fn attachment_file_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_id: String,
+    attachment_id: String,
+) -> CommandResult<PathBuf> {
+    let Some(task) = state.task(&task_id) else {
+        return err("task not found");
+    };
+    let Some(attachment) = task.attachments.iter().find(|a| a.id == attachment_id) else {
+        return err("attachment not found");
+    };
+    if crate::storage::memory_backend_enabled() {
+        return err("attachments cannot be opened in guest mode");
+    }
     let root = match ctx.app_data_dir() {
         Ok(path) => path,
         Err(e) => return err(&format!("app_data_dir error: {e}")),
     };
-
-    let path = export_default_path(&root, "md");
-    let now = Local::now();
-    let now_ts = now.timestamp();
-    let today = now.date_naive();
-
-    let mut overdue: Vec<Task> = Vec::new();
-    let mut today_list: Vec<Task> = Vec::new();
-    let mut future: Vec<Task> = Vec::new();
-    let mut done: Vec<Task> = Vec::new();
-
-    for task in state.tasks() {
-        if task.completed {
-            done.push(task);
-            continue;
-        }
-        if task.due_at < now_ts {
-            overdue.push(task);
-            continue;
-        }
-        let due = Local.timestamp_opt(task.due_at, 0).single();
-        if let Some(due_time) = due {
-            if due_time.date_naive() == today {
-                today_list.push(task);
-                continue;
-            }
+    match Storage::new(root).attachment_path(&attachment.file) {
+        Ok(path) if path.is_file() => ok(path),
+        Ok(_) => {
+            log::warn!(
+                "cmd=open_attachment file missing task_id={task_id} file={}",
+                attachment.file
+            );
+            err("attachment file is missing")
         }
-        future.push(task);
+        Err(error) => err(&format!("storage error: {error:?}")),
     }
+}
 
-    overdue.sort_by_key(|t| t.due_at);
-    today_list.sort_by_key(|t| t.due_at);
-    future.sort_by_key(|t| t.due_at);
-    done.sort_by_key(|t| t.due_at);
-
-    let fmt_due = |ts: i64| {
-        Local
-            .timestamp_opt(ts, 0)
-            .single()
-            .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
-            .unwrap_or_else(|| ts.to_string())
+fn complete_task_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_id: String,
+) -> CommandResult<Task> {
+    let previous = state.task(&task_id);
+    let completed = match state.complete_task(&task_id) {
+        Some(task) => task,
+        None => {
+            log::warn!("cmd=complete_task task not found id={}", task_id);
+            return err("task not found");
+        }
     };
 
-    let mut out = String::new();
-    out.push_str("# MustDo Export\n\n");
-    out.push_str(&format!(
-        "Generated at: {}\n\n",
-        now.format("%Y-%m-%d %H:%M:%S")
-    ));
-
-    let mut write_section = |title: &str, tasks: &[Task], checked: bool| {
-        out.push_str(&format!("## {title}\n\n"));
-        if tasks.is_empty() {
-            out.push_str("_Empty_\n\n");
-            return;
+    let next_due = match completed.repeat {
+        RepeatRule::None => None,
+        _ => Some(next_repeat_due(&completed, state.clock().timestamp()))
+            .filter(|next_due| !repeat_has_ended(&completed, *next_due)),
+    };
+    let Some(next_due) = next_due else {
+        log::info!(
+            "cmd=complete_task id={} repeat={:?} next=none",
+            completed.id,
+            completed.repeat
+        );
+        if let Err(error) = persist(ctx, state) {
+            log::error!(
+                "cmd=complete_task persist failed id={} err={error}",
+                completed.id
+            );
+            return err(&format!("storage error: {error:?}"));
         }
-        for task in tasks {
-            let box_mark = if checked { "x" } else { " " };
-            out.push_str(&format!(
-                "- [{box_mark}] {} (due: {})\n",
-                task.title,
-                fmt_due(task.due_at)
-            ));
-            if !task.tags.is_empty() {
-                let tags = task
-                    .tags
-                    .iter()
-                    .map(|t| format!("#{t}"))
-                    .collect::<Vec<_>>()
-                    .join(" ");
-                out.push_str(&format!("  - tags: {tags}\n"));
-            }
-            if let Some(notes) = &task.notes {
-                let notes = notes.replace("\r\n", "\n").replace('\n', " ");
-                if !notes.trim().is_empty() {
-                    out.push_str(&format!("  - notes: {notes}\n"));
-                }
-            }
-            if !task.steps.is_empty() {
-                out.push_str("  - steps:\n");
-                for step in &task.steps {
-                    let s_mark = if step.completed { "x" } else { " " };
-                    out.push_str(&format!("    - [{s_mark}] {}\n", step.title));
-                }
-            }
+        record_activity(
+            ctx,
+            vec![ActivityEntry::new(
+                &completed,
+                ActivityKind::Completed,
+                state.clock().timestamp(),
+            )],
+        );
+        ctx.dispatch_event(OutboundEvent::TaskCompleted(completed.clone()));
+        if run_automations(ctx, state, &[(previous, completed.clone())]) {
+            return ok(state.task(&completed.id).unwrap_or(completed));
         }
-        out.push('\n');
+        return ok(completed);
     };
 
-    write_section("Overdue", &overdue, false);
-    write_section("Due today", &today_list, false);
-    write_section("Future", &future, false);
-    write_section("Completed", &done, true);
+    let (next, spawned) = state.spawn_repeat_instance(build_next_repeat_task(
+        &completed,
+        next_due,
+        &state.settings().reminder_lead_minutes,
+        state.clock().now(),
+    ));
 
-    if let Err(error) = write_atomic_bytes(&path, out.as_bytes()) {
+    log::info!(
+        "cmd=complete_task id={} repeat={:?} from_completion={} next_id={} next_due={} spawned={}",
+        completed.id,
+        completed.repeat,
+        completed.repeat_from_completion,
+        next.id,
+        next_due,
+        spawned
+    );
+
+    if let Err(error) = persist(ctx, state) {
         log::error!(
-            "cmd=export_tasks_markdown write failed path={} err={error}",
-            path.display()
+            "cmd=complete_task persist failed id={} err={error}",
+            completed.id
         );
-        return err(&format!("export error: {error:?}"));
+        return err(&format!("storage error: {error:?}"));
     }
 
-    log::info!(
-        "cmd=export_tasks_markdown ok path={} overdue={} today={} future={} done={}",
-        path.display(),
-        overdue.len(),
-        today_list.len(),
-        future.len(),
-        done.len()
+    record_activity(
+        ctx,
+        vec![ActivityEntry::new(
+            &completed,
+            ActivityKind::Completed,
+            state.clock().timestamp(),
+        )],
     );
-    ok(path.to_string_lossy().to_string())
+    ctx.dispatch_event(OutboundEvent::TaskCompleted(completed.clone()));
+    run_automations(ctx, state, &[(previous, completed)]);
+    ok(next)
 }
 
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn list_backups(app: AppHandle) -> CommandResult<Vec<BackupEntry>> {
-    let ctx = TauriCommandCtx { app: &app };
-    list_backups_impl(&ctx)
-}
+fn bulk_complete_tasks_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_ids: Vec<String>,
+) -> CommandResult<bool> {
+    let total = task_ids.len();
+    let before = state.tasks_file();
+    let mut completed_tasks = Vec::new();
+    let mut repeated_created = 0usize;
+    let lead_times = state.settings().reminder_lead_minutes;
+    let mut changes = Vec::new();
+    for task_id in task_ids {
+        let previous = state.task(&task_id);
+        let completed = match state.complete_task(&task_id) {
+            Some(task) => task,
+            None => continue,
+        };
+        completed_tasks.push(completed.clone());
+        changes.push((previous, completed.clone()));
 
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn delete_backup(app: AppHandle, filename: String) -> CommandResult<bool> {
-    let ctx = TauriCommandCtx { app: &app };
-    delete_backup_impl(&ctx, filename)
-}
+        if let RepeatRule::None = completed.repeat {
+            continue;
+        }
 
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn create_backup(app: AppHandle, state: State<AppState>) -> CommandResult<bool> {
-    let ctx = TauriCommandCtx { app: &app };
-    create_backup_impl(&ctx, state.inner())
+        let next_due = next_repeat_due(&completed, state.clock().timestamp());
+        if repeat_has_ended(&completed, next_due) {
+            continue;
+        }
+        let (_, spawned) = state.spawn_repeat_instance(build_next_repeat_task(
+            &completed,
+            next_due,
+            &lead_times,
+            state.clock().now(),
+        ));
+        if spawned {
+            repeated_created += 1;
+        }
+    }
+
+    log::info!(
+        "cmd=bulk_complete_tasks requested={} completed={} repeated_created={}",
+        total,
+        completed_tasks.len(),
+        repeated_created
+    );
+    state.record_undo("bulk_complete_tasks", &before);
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=bulk_complete_tasks persist failed err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    let now = state.clock().timestamp();
+    record_activity(
+        ctx,
+        completed_tasks
+            .iter()
+            .map(|task| ActivityEntry::new(task, ActivityKind::Completed, now))
+            .collect(),
+    );
+    for task in completed_tasks {
+        ctx.dispatch_event(OutboundEvent::TaskCompleted(task));
+    }
+    run_automations(ctx, state, &changes);
+    ok(true)
 }
 
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn restore_backup(
-    app: AppHandle,
-    state: State<AppState>,
-    filename: String,
-) -> CommandResult<Vec<Task>> {
-    let ctx = TauriCommandCtx { app: &app };
-    restore_backup_impl(&ctx, state.inner(), filename)
+/// Registers every binding, stopping at the first failure.
+fn register_shortcuts(
+    ctx: &impl CommandCtx,
+    bindings: &[(ShortcutAction, String)],
+) -> Result<(), String> {
+    for (action, shortcut) in bindings {
+        ctx.shortcut_register(shortcut)
+            .map_err(|e| format!("{}: {e}", action.setting_name()))?;
+    }
+    Ok(())
 }
 
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn import_backup(
-    app: AppHandle,
-    state: State<AppState>,
-    path: String,
-) -> CommandResult<Vec<Task>> {
-    let ctx = TauriCommandCtx { app: &app };
-    import_backup_impl(&ctx, state.inner(), path)
-}
-
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn export_tasks_json(app: AppHandle, state: State<AppState>) -> CommandResult<String> {
-    let ctx = TauriCommandCtx { app: &app };
-    export_tasks_json_impl(&ctx, state.inner())
-}
+/// Checks a shortcut before it is saved: parse errors, other actions of this app bound to it
+/// (`action` names the settings field being edited, whose own binding is ignored), well-known
+/// system shortcuts and, where the OS reports it, combinations already taken elsewhere.
+fn validate_shortcut_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    shortcut: String,
+    action: Option<String>,
+) -> CommandResult<ShortcutCheck> {
+    let except = match action
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        None => None,
+        Some(name) => match ShortcutAction::from_setting_name(name) {
+            Some(action) => Some(action),
+            None => return err(&format!("unknown shortcut action: {name}")),
+        },
+    };
+    let shortcut = shortcut.trim().to_string();
+    if let Err(parse_err) = ctx.shortcut_validate(&shortcut) {
+        log::info!("cmd=validate_shortcut invalid shortcut={shortcut} err={parse_err}");
+        return ok(ShortcutCheck {
+            shortcut,
+            valid: false,
+            error: Some(parse_err),
+            conflicts: Vec::new(),
+        });
+    }
 
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn export_tasks_csv(app: AppHandle, state: State<AppState>) -> CommandResult<String> {
-    let ctx = TauriCommandCtx { app: &app };
-    export_tasks_csv_impl(&ctx, state.inner())
+    let mut conflicts: Vec<ShortcutConflict> = app_conflicts(&state.settings(), &shortcut, except)
+        .into_iter()
+        .map(|action| ShortcutConflict::App {
+            action: action.setting_name().to_string(),
+        })
+        .collect();
+    if let Some(purpose) = reserved_system_shortcut(&shortcut, std::env::consts::OS) {
+        conflicts.push(ShortcutConflict::System {
+            reason: format!("reserved by the system ({purpose})"),
+        });
+    } else if let Err(probe_err) = ctx.shortcut_probe(&shortcut) {
+        conflicts.push(ShortcutConflict::System {
+            reason: format!("already in use: {probe_err}"),
+        });
+    }
+    log::info!(
+        "cmd=validate_shortcut shortcut={shortcut} conflicts={}",
+        conflicts.len()
+    );
+    ok(ShortcutCheck {
+        shortcut,
+        valid: conflicts.is_empty(),
+        error: None,
+        conflicts,
+    })
 }
 
-#[cfg(all(feature = "app", not(test)))]
-#[tauri::command]
-pub fn export_tasks_markdown(app: AppHandle, state: State<AppState>) -> CommandResult<String> {
-    let ctx = TauriCommandCtx { app: &app };
-    export_tasks_markdown_impl(&ctx, state.inner())
-}
+fn update_settings_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    mut settings: Settings,
+) -> CommandResult<Settings> {
+    let previous = state.settings();
+    let previous_bindings = shortcut_bindings(&previous);
+    let previous_language = previous.language.trim().to_lowercase();
+    let next_language = settings.language.trim().to_lowercase();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::models::Step;
-    use crate::models::{ReminderConfig, ReminderKind, RepeatRule, Task};
-    use std::fs;
-    use std::sync::Mutex;
+    // Normalize user input so tests/production behave the same and the persisted config is stable.
+    normalize_shortcuts(&mut settings);
+    let next_bindings = shortcut_bindings(&settings);
+    let shortcut_requested_change = previous_bindings != next_bindings;
+    settings.language = match next_language.as_str() {
+        "auto" | "zh" | "en" => next_language.clone(),
+        _ => {
+            log::warn!(
+                "cmd=update_settings invalid language; using default requested={}",
+                next_language
+            );
+            Settings::default().language
+        }
+    };
 
-    fn is_io(err: &StorageError) -> bool {
-        matches!(err, StorageError::Io(_))
+    // Smart lists and automation rules are owned by their own commands (see
+    // `create_smart_list`, `create_automation_rule`).
+    settings.smart_lists = previous.smart_lists.clone();
+    settings.automation_rules = previous.automation_rules.clone();
+    // The API key itself lives in secrets (see `set_secret`); never take it from the client.
+    settings.deepseek_api_key = String::new();
+    settings.ai_api_key_set = previous.ai_api_key_set;
+    if settings.ai_provider != previous.ai_provider {
+        let stored = ctx
+            .app_data_dir()
+            .and_then(|root| crate::secrets::ai_api_key_stored(&root, settings.ai_provider));
+        settings.ai_api_key_set = stored.unwrap_or_else(|error| {
+            log::warn!("cmd=update_settings could not read ai key err={error}");
+            false
+        });
     }
-
-    struct TestCtx {
-        root: tempfile::TempDir,
-        app_data_dir_error: Option<String>,
-        app_data_dir_override: Option<PathBuf>,
-        emitted: Mutex<Vec<StatePayload>>,
-        tray_updates: Mutex<usize>,
-        shortcut_unregistered: Mutex<usize>,
-        shortcut_registered: Mutex<usize>,
-        shortcut_register_error: Mutex<Option<String>>,
+    settings.ai_base_url = settings.ai_base_url.trim().to_string();
+    settings.ai_model = settings.ai_model.trim().to_string();
+    if settings.ai_model.is_empty() {
+        settings.ai_model = crate::ai::providers::provider(settings.ai_provider)
+            .default_model()
+            .to_string();
     }
-
-    impl TestCtx {
-        fn new() -> Self {
-            Self {
-                root: tempfile::tempdir().unwrap(),
-                app_data_dir_error: None,
-                app_data_dir_override: None,
-                emitted: Mutex::new(Vec::new()),
-                tray_updates: Mutex::new(0),
-                shortcut_unregistered: Mutex::new(0),
-                shortcut_registered: Mutex::new(0),
-                shortcut_register_error: Mutex::new(None),
-            }
-        }
-
-        fn with_app_data_dir_error(message: &str) -> Self {
-            let mut ctx = Self::new();
-            ctx.app_data_dir_error = Some(message.to_string());
-            ctx
+    settings.overdue_grace_minutes = settings.overdue_grace_minutes.max(0);
+    settings.caldav_url = settings.caldav_url.trim().to_string();
+    settings.caldav_username = settings.caldav_username.trim().to_string();
+    settings.caldav_sync_interval_minutes = settings
+        .caldav_sync_interval_minutes
+        .clamp(MIN_SYNC_INTERVAL_MINUTES, MAX_SYNC_INTERVAL_MINUTES);
+    settings.reminder_lead_minutes.clamp();
+    settings.backup_retention = settings.backup_retention.clamp(1, MAX_BACKUP_RETENTION);
+    settings.backup_directory = settings.backup_directory.trim().to_string();
+    if let Err(message) = normalize_quadrant_config(&mut settings.quadrant_config) {
+        log::warn!("cmd=update_settings invalid quadrant config err={message}");
+        return err(&format!("invalid quadrant config: {message}"));
+    }
+    if let Err(message) = normalize_capture_settings(&mut settings) {
+        log::warn!("cmd=update_settings invalid quick capture settings err={message}");
+        return err(&message);
+    }
+    if let Err(message) = normalize_work_hours(&mut settings) {
+        log::warn!("cmd=update_settings invalid work hours err={message}");
+        return err(&message);
+    }
+    if let Err(message) = normalize_quiet_hours(&mut settings) {
+        log::warn!("cmd=update_settings invalid quiet hours err={message}");
+        return err(&message);
+    }
+    if let Err(message) = normalize_daily_summary_time(&mut settings) {
+        log::warn!("cmd=update_settings invalid daily summary time err={message}");
+        return err(&message);
+    }
+    if !settings.backup_directory.is_empty()
+        && settings.backup_directory != previous.backup_directory
+    {
+        let root = match ctx.app_data_dir() {
+            Ok(path) => path,
+            Err(e) => return err(&format!("app_data_dir error: {e}")),
+        };
+        if let Err(message) =
+            validate_backup_directory(Path::new(&settings.backup_directory), &root)
+        {
+            log::warn!("cmd=update_settings invalid backup directory err={message}");
+            return err(&message);
         }
-
-        fn root_path(&self) -> &std::path::Path {
-            self.root.path()
+    }
+    if settings.storage_engine != previous.storage_engine {
+        let root = match ctx.app_data_dir() {
+            Ok(path) => path,
+            Err(e) => return err(&format!("app_data_dir error: {e}")),
+        };
+        let mut storage =
+            Storage::new(root).with_backup_policy(BackupPolicy::from_settings(&settings));
+        // Queued writes go to the old engine first, so the migration copies current data.
+        if let Err(error) = flush_writes(state)
+            .and_then(|_| storage.ensure_dirs())
+            .and_then(|_| storage.migrate_to(settings.storage_engine))
+        {
+            log::error!(
+                "cmd=update_settings storage migration failed from={:?} to={:?} err={error}",
+                previous.storage_engine,
+                settings.storage_engine
+            );
+            return err(&format!("storage error: {error}"));
         }
+        // Mirror the data directory, which is what later loads go by.
+        settings.storage_engine = storage.engine();
+    }
 
-        fn set_app_data_dir_override(&mut self, path: PathBuf) {
-            self.app_data_dir_override = Some(path);
-        }
+    log::info!(
+        "cmd=update_settings start theme={} language={} close_behavior={:?} minimize_behavior={:?} backup_schedule={:?} update_behavior={:?} repeat_interval_sec={} repeat_max_times={} shortcut_change={}",
+        settings.theme,
+        settings.language,
+        settings.close_behavior,
+        settings.minimize_behavior,
+        settings.backup_schedule,
+        settings.update_behavior,
+        settings.reminder_repeat_interval_sec,
+        settings.reminder_repeat_max_times,
+        shortcut_requested_change
+    );
 
-        fn set_shortcut_register_error(&self, message: Option<&str>) {
-            *self.shortcut_register_error.lock().unwrap() = message.map(|s| s.to_string());
+    // Shortcut validation/registration is the #1 place users can lock themselves out:
+    // if we unregister the old one and fail to register the new one, the app becomes unreachable
+    // from the keyboard. So we validate + register with a rollback path and only then persist.
+    let mut shortcut_changed = false;
+    if shortcut_requested_change {
+        shortcut_changed = true;
+        // The quick window binding is required; the blank ones are simply not in the list.
+        if let Err(parse_err) = ctx.shortcut_validate(&settings.shortcut) {
+            log::warn!(
+                "cmd=update_settings invalid shortcut requested={} err={}",
+                settings.shortcut,
+                parse_err
+            );
+            return err(&format!("invalid shortcut: {parse_err}"));
         }
-    }
-
-    impl CommandCtx for TestCtx {
-        fn app_data_dir(&self) -> Result<PathBuf, StorageError> {
-            if let Some(message) = &self.app_data_dir_error {
-                return Err(StorageError::Io(std::io::Error::other(message.clone())));
+        for (action, shortcut) in &next_bindings {
+            if let Err(parse_err) = ctx.shortcut_validate(shortcut) {
+                log::warn!(
+                    "cmd=update_settings invalid shortcut action={} requested={} err={}",
+                    action.setting_name(),
+                    shortcut,
+                    parse_err
+                );
+                return err(&format!(
+                    "invalid shortcut for {}: {parse_err}",
+                    action.setting_name()
+                ));
             }
-            if let Some(path) = &self.app_data_dir_override {
-                return Ok(path.clone());
-            }
-            Ok(self.root.path().to_path_buf())
-        }
-
-        fn emit_state_updated(&self, payload: StatePayload) {
-            self.emitted.lock().unwrap().push(payload);
         }
-
-        fn update_tray_count(&self, _tasks: &[Task], _settings: &Settings) {
-            *self.tray_updates.lock().unwrap() += 1;
+        if let Err(conflict) = check_conflicts(&next_bindings) {
+            log::warn!("cmd=update_settings {conflict}");
+            return err(&conflict);
         }
 
-        fn shortcut_unregister_all(&self) {
-            *self.shortcut_unregistered.lock().unwrap() += 1;
+        ctx.shortcut_unregister_all();
+        if let Err(register_err) = register_shortcuts(ctx, &next_bindings) {
+            // Best-effort restore the previous shortcuts so the user can still summon the quick window.
+            ctx.shortcut_unregister_all();
+            let _ = register_shortcuts(ctx, &previous_bindings);
+            log::error!(
+                "cmd=update_settings failed to register shortcut requested={} err={}",
+                settings.shortcut,
+                register_err
+            );
+            return err(&format!("failed to register shortcut: {register_err}"));
         }
+        log::info!(
+            "cmd=update_settings shortcuts updated old={:?} new={:?}",
+            previous_bindings,
+            next_bindings
+        );
+    }
 
-        fn shortcut_validate(&self, shortcut: &str) -> Result<(), String> {
-            let shortcut = shortcut.trim();
-            if shortcut.is_empty() {
-                return Err("empty shortcut".to_string());
-            }
-
-            // A lightweight validator for unit tests. Production builds validate using the
-            // real Tauri shortcut parser (see `TauriCommandCtx`).
-            if shortcut.starts_with("CommandOrControl+Shift+")
-                && shortcut.len() > "CommandOrControl+Shift+".len()
-            {
-                return Ok(());
-            }
-
-            Err("parse error".to_string())
-        }
+    // Currently language is a UI concern (and must never brick the app). We normalize above and
+    // keep persistence best-effort only through the usual `persist()` path.
+    let _language_changed = previous_language != settings.language;
 
-        fn shortcut_register(&self, shortcut: &str) -> Result<(), String> {
-            self.shortcut_validate(shortcut)?;
-            *self.shortcut_registered.lock().unwrap() += 1;
-            if let Some(message) = self.shortcut_register_error.lock().unwrap().clone() {
-                return Err(message);
-            }
-            Ok(())
+    state.update_settings(settings.clone());
+    if let Err(error) = persist(ctx, state) {
+        // Roll back in-memory settings to keep the running app consistent.
+        state.update_settings(previous.clone());
+        if shortcut_changed {
+            ctx.shortcut_unregister_all();
+            let _ = register_shortcuts(ctx, &previous_bindings);
         }
+        log::error!("cmd=update_settings persist failed err={error}");
+        return err(&format!("storage error: {error:?}"));
     }
 
-    struct ForceJsonErrorCtx {
-        inner: TestCtx,
+    log::info!(
+        "cmd=update_settings ok theme={} language={} close_behavior={:?} minimize_behavior={:?} backup_schedule={:?} update_behavior={:?}",
+        settings.theme,
+        settings.language,
+        settings.close_behavior,
+        settings.minimize_behavior,
+        settings.backup_schedule,
+        settings.update_behavior
+    );
+    ok(settings)
+}
+
+fn snooze_task_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_id: String,
+    until: i64,
+) -> CommandResult<bool> {
+    log::info!("cmd=snooze_task start task_id={} until={}", task_id, until);
+    let mut tasks = state.tasks();
+    let mut snoozed = None;
+    if let Some(task) = tasks.iter_mut().find(|t| t.id == task_id) {
+        task.reminder.snoozed_until = Some(until);
+        task.reminder.last_fired_at = Some(state.clock().timestamp());
+        task.reminder.ignored_count = 0;
+        state.update_task(task.clone());
+        snoozed = Some(task.clone());
+    }
+    if snoozed.is_none() {
+        log::warn!("cmd=snooze_task task not found task_id={}", task_id);
+    }
+    if let Err(error) = persist(ctx, state) {
+        log::error!(
+            "cmd=snooze_task persist failed task_id={} err={error}",
+            task_id
+        );
+        return err(&format!("storage error: {error:?}"));
+    }
+    if let Some(task) = snoozed {
+        record_activity(
+            ctx,
+            vec![ActivityEntry::new(
+                &task,
+                ActivityKind::Snoozed { until },
+                state.clock().timestamp(),
+            )],
+        );
     }
+    ok(true)
+}
 
-    impl ForceJsonErrorCtx {
-        fn new() -> Self {
-            Self {
-                inner: TestCtx::new(),
-            }
-        }
+/// Turns do-not-disturb on or off; normal reminders that came due meanwhile fire once it is off.
+fn set_do_not_disturb_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    enabled: bool,
+) -> CommandResult<Settings> {
+    let mut settings = state.settings();
+    settings.dnd_enabled = enabled;
+    state.update_settings(settings);
+    log::info!("cmd=set_do_not_disturb enabled={enabled}");
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=set_do_not_disturb persist failed err={error}");
+        return err(&format!("storage error: {error:?}"));
     }
+    ok(state.settings())
+}
 
-    impl CommandCtx for ForceJsonErrorCtx {
-        fn app_data_dir(&self) -> Result<PathBuf, StorageError> {
-            self.inner.app_data_dir()
-        }
+/// Tray menu hook: flips `settings.dnd_enabled`.
+#[cfg(all(feature = "app", not(test)))]
+pub fn toggle_do_not_disturb(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let ctx = TauriCommandCtx { app };
+    set_do_not_disturb_impl(&ctx, state.inner(), !state.settings().dnd_enabled);
+}
 
-        fn emit_state_updated(&self, payload: StatePayload) {
-            self.inner.emit_state_updated(payload);
-        }
+/// `snooze_task` with the time worked out here from a named preset; returns that time.
+fn snooze_task_preset_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_id: String,
+    preset: SnoozePreset,
+) -> CommandResult<Timestamp> {
+    let until = snooze_until(preset, state.clock().now_local(), &state.settings());
+    log::info!("cmd=snooze_task_preset task_id={task_id} preset={preset:?} until={until}");
+    let result = snooze_task_impl(ctx, state, task_id, until);
+    match result.error {
+        Some(error) => err(&error),
+        None => ok(until),
+    }
+}
 
-        fn update_tray_count(&self, tasks: &[Task], settings: &Settings) {
-            self.inner.update_tray_count(tasks, settings);
+/// A button pressed on the native reminder notification, handled like the same action taken in
+/// the app so no window has to come forward.
+fn notification_action_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_id: String,
+    action: NotificationAction,
+) -> CommandResult<bool> {
+    log::info!("cmd=notification_action task_id={task_id} action={action:?}");
+    let error = match action {
+        NotificationAction::Complete => complete_task_impl(ctx, state, task_id).error,
+        NotificationAction::SnoozeTenMinutes => {
+            snooze_task_preset_impl(ctx, state, task_id, SnoozePreset::InTenMinutes).error
         }
+    };
+    match error {
+        Some(error) => err(&error),
+        None => ok(true),
+    }
+}
 
-        fn shortcut_unregister_all(&self) {
-            self.inner.shortcut_unregister_all();
-        }
+/// Fires a task's reminder right now for preview. Nothing is written: `last_fired_at`, snooze and
+/// dismissal state stay untouched, so the real reminder still fires at its time.
+fn test_fire_reminder_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_id: String,
+) -> CommandResult<Task> {
+    let Some(task) = state.task(&task_id) else {
+        log::warn!("cmd=test_fire_reminder task not found id={task_id}");
+        return err("task not found");
+    };
+    if task.reminder.kind == ReminderKind::None {
+        return err("task has no reminder");
+    }
+    log::info!(
+        "cmd=test_fire_reminder ok id={} kind={:?}",
+        task.id,
+        task.reminder.kind
+    );
+    ctx.fire_reminder(std::slice::from_ref(&task));
+    ok(task)
+}
 
-        fn shortcut_validate(&self, shortcut: &str) -> Result<(), String> {
-            self.inner.shortcut_validate(shortcut)
-        }
+fn dismiss_forced_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_id: String,
+) -> CommandResult<bool> {
+    log::info!("cmd=dismiss_forced start task_id={}", task_id);
+    let mut tasks = state.tasks();
+    let mut found = false;
+    if let Some(task) = tasks.iter_mut().find(|t| t.id == task_id) {
+        found = true;
+        task.reminder.forced_dismissed = true;
+        task.reminder.last_fired_at = Some(state.clock().timestamp());
+        state.update_task(task.clone());
+    }
+    if !found {
+        log::warn!("cmd=dismiss_forced task not found task_id={}", task_id);
+    }
+    if let Err(error) = persist(ctx, state) {
+        log::error!(
+            "cmd=dismiss_forced persist failed task_id={} err={error}",
+            task_id
+        );
+        return err(&format!("storage error: {error:?}"));
+    }
+    ok(true)
+}
 
-        fn shortcut_register(&self, shortcut: &str) -> Result<(), String> {
-            self.inner.shortcut_register(shortcut)
-        }
+/// Moves `task_ids` to the trash; the delete commands and their dry run share it so the
+/// preview cannot drift from what a delete does. Returns how many were found.
+fn apply_delete(state: &AppState, task_ids: &[String]) -> usize {
+    state.move_to_trash(task_ids, state.clock().timestamp())
+}
 
-        fn force_json_serialize_error(&self) -> bool {
-            true
+fn delete_task_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_id: String,
+) -> CommandResult<bool> {
+    log::info!("cmd=delete_task task_id={}", task_id);
+    let before = state.tasks_file();
+    apply_delete(state, std::slice::from_ref(&task_id));
+    state.record_undo("delete_task", &before);
+    if let Err(error) = persist(ctx, state) {
+        log::error!(
+            "cmd=delete_task persist failed task_id={} err={error}",
+            task_id
+        );
+        return err(&format!("storage error: {error:?}"));
+    }
+    ctx.dispatch_event(OutboundEvent::TaskDeleted {
+        task_ids: vec![task_id],
+    });
+    ok(true)
+}
+
+fn delete_tasks_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_ids: Vec<String>,
+) -> CommandResult<bool> {
+    log::info!("cmd=delete_tasks count={}", task_ids.len());
+    let before = state.tasks_file();
+    apply_delete(state, &task_ids);
+    state.record_undo("delete_tasks", &before);
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=delete_tasks persist failed err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    ctx.dispatch_event(OutboundEvent::TaskDeleted { task_ids });
+    ok(true)
+}
+
+fn undo_last_action_impl(ctx: &impl CommandCtx, state: &AppState) -> CommandResult<UndoSummary> {
+    let Some(undone) = state.undo_last() else {
+        return err("nothing to undo");
+    };
+    log::info!(
+        "cmd=undo_last_action action={} tasks={} projects={}",
+        undone.action,
+        undone.tasks,
+        undone.projects
+    );
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=undo_last_action persist failed err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    ok(undone)
+}
+
+fn redo_last_action_impl(ctx: &impl CommandCtx, state: &AppState) -> CommandResult<UndoSummary> {
+    let Some(redone) = state.redo_last() else {
+        return err("nothing to redo");
+    };
+    log::info!(
+        "cmd=redo_last_action action={} tasks={} projects={}",
+        redone.action,
+        redone.tasks,
+        redone.projects
+    );
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=redo_last_action persist failed err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    ok(redone)
+}
+
+fn get_undo_status_impl(state: &AppState) -> CommandResult<UndoStatus> {
+    ok(state.undo_status())
+}
+
+/// Newest deletions first.
+fn list_trash_impl(state: &AppState) -> CommandResult<Vec<TrashEntry>> {
+    let mut trash = state.trash();
+    trash.sort_by_key(|entry| std::cmp::Reverse(entry.deleted_at));
+    log::info!("cmd=list_trash ok count={}", trash.len());
+    ok(trash)
+}
+
+fn restore_from_trash_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_ids: Vec<String>,
+) -> CommandResult<Vec<Task>> {
+    let before = state.tasks_file();
+    let restored = state.restore_from_trash(&task_ids, state.clock().timestamp());
+    log::info!(
+        "cmd=restore_from_trash requested={} restored={}",
+        task_ids.len(),
+        restored.len()
+    );
+    if restored.is_empty() {
+        return err("task not found in trash");
+    }
+    state.record_undo("restore_from_trash", &before);
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=restore_from_trash persist failed err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    for task in &restored {
+        ctx.dispatch_event(OutboundEvent::TaskCreated(task.clone()));
+    }
+    ok(restored)
+}
+
+/// Permanent; not recorded for undo. `task_ids = None` empties the whole trash.
+fn empty_trash_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_ids: Option<Vec<String>>,
+) -> CommandResult<usize> {
+    let removed = state.empty_trash(task_ids.as_deref());
+    log::info!(
+        "cmd=empty_trash selected={:?} removed={}",
+        task_ids.as_ref().map(Vec::len),
+        removed
+    );
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=empty_trash persist failed err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    ok(removed)
+}
+
+/// Archived tasks of one year, or of all years, most recently completed first.
+fn list_archived_impl(ctx: &impl CommandCtx, year: Option<i32>) -> CommandResult<Vec<Task>> {
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    match task_archive::list_archived(&root, year) {
+        Ok(tasks) => {
+            log::info!("cmd=list_archived ok year={year:?} count={}", tasks.len());
+            ok(tasks)
+        }
+        Err(error) => {
+            log::error!("cmd=list_archived failed err={error}");
+            err(&format!("storage error: {error:?}"))
         }
     }
+}
 
-    fn make_task(id: &str, due_at: i64) -> Task {
-        Task {
-            id: id.to_string(),
-            project_id: "inbox".to_string(),
-            title: format!("task-{id}"),
-            due_at,
-            important: false,
-            completed: false,
-            completed_at: None,
-            created_at: 1,
-            updated_at: 1,
-            sort_order: 0,
-            quadrant: 1,
-            notes: None,
-            steps: Vec::new(),
-            tags: Vec::new(),
-            sample_tag: None,
-            reminder: ReminderConfig {
-                kind: ReminderKind::Normal,
-                ..ReminderConfig::default()
-            },
-            repeat: RepeatRule::None,
+fn search_archive_impl(
+    ctx: &impl CommandCtx,
+    query: String,
+    limit: Option<usize>,
+) -> CommandResult<Vec<SearchHit>> {
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    let limit = limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_SEARCH_LIMIT);
+    match task_archive::search_archive(&root, &query, limit) {
+        Ok(hits) => {
+            log::info!(
+                "cmd=search_archive ok query_len={} hits={}",
+                query.chars().count(),
+                hits.len()
+            );
+            ok(hits)
+        }
+        Err(error) => {
+            log::error!("cmd=search_archive failed err={error}");
+            err(&format!("storage error: {error:?}"))
         }
     }
+}
 
-    fn make_state(tasks: Vec<Task>) -> AppState {
-        AppState::new(tasks, Vec::new(), Settings::default())
+/// Brings an archived task back to the end of the task list. Not recorded for undo: undoing
+/// would drop the task while its archive copy is already gone.
+fn unarchive_task_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    task_id: String,
+) -> CommandResult<Task> {
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    let (year, task) = match task_archive::find_archived(&root, &task_id) {
+        Ok(Some(found)) => found,
+        Ok(None) => return err("task not found in archive"),
+        Err(error) => {
+            log::error!("cmd=unarchive_task read failed err={error}");
+            return err(&format!("storage error: {error:?}"));
+        }
+    };
+    let Some(restored) = state.restore_archived(task, state.clock().timestamp()) else {
+        return err("task id already in use");
+    };
+    // data.json is written before the archive copy goes, so a crash in between keeps both.
+    if let Err(error) = persist_now(ctx, state) {
+        log::error!("cmd=unarchive_task persist failed err={error}");
+        return err(&format!("storage error: {error:?}"));
     }
+    if let Err(error) = task_archive::drop_archived(&root, year, &task_id) {
+        log::warn!("cmd=unarchive_task archive copy kept year={year} err={error}");
+    }
+    log::info!("cmd=unarchive_task ok id={task_id} year={year}");
+    ctx.dispatch_event(OutboundEvent::TaskCreated(restored.clone()));
+    ok(restored)
+}
 
-    #[test]
-    fn ok_and_err_helpers_construct_expected_shape() {
-        let r = ok(123);
-        assert!(r.ok);
-        assert_eq!(r.data, Some(123));
-        assert_eq!(r.error, None);
+fn delete_tasks_dry_run_impl(state: &AppState, task_ids: Vec<String>) -> CommandResult<ChangeSet> {
+    log::info!("cmd=delete_tasks dry_run count={}", task_ids.len());
+    preview_changes(state, |scratch| {
+        apply_delete(scratch, &task_ids);
+        Ok(())
+    })
+}
 
-        let r: CommandResult<i32> = err("nope");
-        assert!(!r.ok);
-        assert_eq!(r.data, None);
-        assert_eq!(r.error, Some("nope".to_string()));
+fn query_tasks_impl(state: &AppState, spec: TaskQuerySpec) -> CommandResult<TaskPage> {
+    let snapshot = state.snapshot();
+    let page = task_query::query_tasks(
+        &snapshot.tasks,
+        &snapshot.projects,
+        &spec,
+        state.clock().now_local(),
+        &snapshot.settings,
+    );
+    log::info!(
+        "cmd=query_tasks ok sort={:?} descending={} group_by={:?} offset={} limit={:?} groups={} tasks={} total={}",
+        spec.sort,
+        spec.descending,
+        spec.group_by,
+        spec.offset,
+        spec.limit,
+        page.groups.len(),
+        page.groups.iter().map(|group| group.tasks.len()).sum::<usize>(),
+        page.total
+    );
+    ok(page)
+}
+
+/// Ranked full-text search over titles, tags, step titles and notes (see `search`).
+fn search_tasks_impl(
+    state: &AppState,
+    query: String,
+    include_completed: Option<bool>,
+    limit: Option<usize>,
+) -> CommandResult<Vec<SearchHit>> {
+    let limit = limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_SEARCH_LIMIT);
+    let hits = task_search::search_tasks(
+        &state.shared_tasks(),
+        &query,
+        include_completed.unwrap_or(false),
+        limit,
+    );
+    log::info!(
+        "cmd=search_tasks ok query_len={} limit={} hits={}",
+        query.chars().count(),
+        limit,
+        hits.len()
+    );
+    ok(hits)
+}
+
+fn list_waiting_for_impl(state: &AppState) -> CommandResult<Vec<WaitingForGroup>> {
+    let now = state.clock().timestamp();
+    let groups = waiting_for_groups(&state.shared_tasks(), now);
+    log::info!(
+        "cmd=list_waiting_for ok people={} tasks={}",
+        groups.len(),
+        groups.iter().map(|group| group.tasks.len()).sum::<usize>()
+    );
+    ok(groups)
+}
+
+fn list_someday_tasks_impl(state: &AppState) -> CommandResult<Vec<Task>> {
+    let tasks = task_query::someday_tasks(&state.shared_tasks());
+    log::info!("cmd=list_someday_tasks ok tasks={}", tasks.len());
+    ok(tasks)
+}
+
+fn list_scheduled_tasks_impl(state: &AppState) -> CommandResult<Vec<Task>> {
+    let tasks = task_query::scheduled_tasks(&state.shared_tasks(), state.clock().timestamp());
+    log::info!("cmd=list_scheduled_tasks ok tasks={}", tasks.len());
+    ok(tasks)
+}
+
+/// `None` in the result means the capture stays undated (Someday policy).
+fn resolve_quick_due_impl(state: &AppState, due_at: Option<i64>) -> CommandResult<Option<i64>> {
+    let settings = state.settings();
+    let resolved = resolve_capture_due(due_at, state.clock().now_local(), &settings);
+    log::info!("cmd=resolve_quick_due parsed={due_at:?} resolved={resolved:?}");
+    ok(resolved)
+}
+
+/// Live tasks plus those archived since `from_year`, for the history views below.
+fn history_tasks(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    from_year: i32,
+) -> Result<Arc<Vec<Task>>, String> {
+    let root = ctx
+        .app_data_dir()
+        .map_err(|e| format!("app_data_dir error: {e}"))?;
+    task_archive::tasks_with_archive(&root, state, from_year).map_err(|error| {
+        log::error!("history: archive read failed from_year={from_year} err={error}");
+        format!("storage error: {error:?}")
+    })
+}
+
+fn get_burndown_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    project_id: Option<String>,
+    range: Option<BurndownRange>,
+) -> CommandResult<Burndown> {
+    if let Some(id) = project_id.as_deref() {
+        if !state.projects().iter().any(|project| project.id == id) {
+            return err("project not found");
+        }
     }
+    let range = range.unwrap_or_default();
+    let now = state.clock().now_local();
+    let first_day = now.date_naive() - chrono::Duration::days(range.days() - 1);
+    let tasks = match history_tasks(ctx, state, first_day.year()) {
+        Ok(tasks) => tasks,
+        Err(message) => return err(&message),
+    };
+    let report = burndown(&tasks, project_id.as_deref(), range, now);
+    log::info!(
+        "cmd=get_burndown ok project_id={:?} range={:?} remaining={} forecast_days={:?}",
+        report.project_id,
+        report.range,
+        report.remaining,
+        report.forecast_days
+    );
+    ok(report)
+}
 
-    #[test]
-    fn test_ctx_shortcut_register_propagates_validation_error() {
-        let ctx = TestCtx::new();
-        let err = ctx
-            .shortcut_register("bad-shortcut")
-            .expect_err("should fail shortcut_validate");
-        assert_eq!(err, "parse error");
+fn get_statistics_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    from: Option<Timestamp>,
+    to: Option<Timestamp>,
+) -> CommandResult<Statistics> {
+    let (default_from, default_to) = default_statistics_range(state.clock().now_local());
+    let from = from.unwrap_or(default_from);
+    let to = to.unwrap_or(default_to);
+    if from >= to {
+        log::warn!("cmd=get_statistics rejected from={from} to={to}");
+        return err("invalid range: from must be before to");
+    }
+    let tasks = match history_tasks(ctx, state, task_archive::local_year(from)) {
+        Ok(tasks) => tasks,
+        Err(message) => return err(&message),
+    };
+    let report = statistics(&tasks, &state.projects(), from, to);
+    log::info!(
+        "cmd=get_statistics ok from={from} to={to} completed={} created={} projects={}",
+        report.completed,
+        report.created,
+        report.projects.len()
+    );
+    ok(report)
+}
+
+/// Timer and focus time over `[from, to)`, by default the same 30 days as `get_statistics`.
+fn get_focus_stats_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    from: Option<Timestamp>,
+    to: Option<Timestamp>,
+) -> CommandResult<FocusStats> {
+    let (default_from, default_to) = default_statistics_range(state.clock().now_local());
+    let from = from.unwrap_or(default_from);
+    let to = to.unwrap_or(default_to);
+    if from >= to {
+        log::warn!("cmd=get_focus_stats rejected from={from} to={to}");
+        return err("invalid range: from must be before to");
+    }
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    let sessions = match read_focus_log(&root, None, usize::MAX) {
+        Ok(sessions) => sessions,
+        Err(error) => {
+            log::error!("cmd=get_focus_stats failed err={error}");
+            return err(&format!("storage error: {error:?}"));
+        }
+    };
+    let tasks = match history_tasks(ctx, state, task_archive::local_year(from)) {
+        Ok(tasks) => tasks,
+        Err(message) => return err(&message),
+    };
+    let report = focus_stats(&tasks, &sessions, from, to, state.clock().timestamp());
+    log::info!(
+        "cmd=get_focus_stats ok from={from} to={to} tracked_seconds={} focus_sessions={}",
+        report.tracked_seconds,
+        report.focus_sessions
+    );
+    ok(report)
+}
+
+fn suggest_next_task_impl(
+    state: &AppState,
+    limit: Option<usize>,
+) -> CommandResult<Vec<Suggestion>> {
+    let limit = limit
+        .unwrap_or(DEFAULT_SUGGESTION_LIMIT)
+        .clamp(1, MAX_SUGGESTION_LIMIT);
+    let suggestions = suggest_next(
+        &state.shared_tasks(),
+        &state.settings(),
+        state.clock().now_local(),
+        limit,
+    );
+    log::info!(
+        "cmd=suggest_next_task ok limit={} top={:?}",
+        limit,
+        suggestions.first().map(|s| (&s.task.id, s.score))
+    );
+    ok(suggestions)
+}
+
+/// Draft task from pasted text using the rule-based parser; nothing is created.
+fn infer_task_from_text_impl(state: &AppState, text: String) -> CommandResult<TaskDraft> {
+    if text.trim().is_empty() {
+        return err("text is empty");
+    }
+    let draft = infer_task(&text, state.clock().now_local(), &state.settings());
+    log::info!(
+        "cmd=infer_task_from_text ok len={} due={} steps={} tags={}",
+        text.len(),
+        draft.due_at.is_some(),
+        draft.steps.len(),
+        draft.tags.len()
+    );
+    ok(draft)
+}
+
+fn events_manifest_impl() -> CommandResult<EventsManifest> {
+    let manifest = build_events_manifest();
+    log::info!("cmd=events_manifest events={}", manifest.events.len());
+    ok(manifest)
+}
+
+/// Set when data.json was corrupt at load; the UI uses it to explain the recovery and point
+/// at the remaining backups.
+fn get_recovery_status_impl(state: &AppState) -> CommandResult<Option<RecoveryStatus>> {
+    let status = state.recovery_status();
+    log::info!(
+        "cmd=get_recovery_status recovered={} from={:?}",
+        status.is_some(),
+        status.as_ref().and_then(|s| s.recovered_from.as_ref())
+    );
+    ok(status)
+}
+
+/// Natural labels ("明天 14:00", "Tue 2pm") for the UI, formatted the same way as emails and
+/// outbound payloads.
+fn format_dates_impl(state: &AppState, timestamps: Vec<Timestamp>) -> CommandResult<Vec<String>> {
+    let dates = DateFormatter::new(
+        &state.settings(),
+        &sys_locale::get_locale().unwrap_or_default(),
+        state.clock().now_local(),
+    );
+    log::info!(
+        "cmd=format_dates ok count={} locale={:?}",
+        timestamps.len(),
+        dates.locale
+    );
+    ok(timestamps.into_iter().map(|ts| dates.natural(ts)).collect())
+}
+
+fn list_contexts_impl(state: &AppState) -> CommandResult<Vec<ContextSummary>> {
+    let summaries = context_summaries(&state.shared_tasks());
+    log::info!("cmd=list_contexts ok contexts={}", summaries.len());
+    ok(summaries)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn load_state(app: AppHandle, state: State<AppState>) -> CommandResult<StatePayload> {
+    let _span = LogSpan::command("load_state");
+    let ctx = TauriCommandCtx { app: &app };
+    load_state_impl(&ctx, state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn create_project(
+    app: AppHandle,
+    state: State<AppState>,
+    project: Project,
+) -> CommandResult<Project> {
+    let _span = LogSpan::command("create_project");
+    let ctx = TauriCommandCtx { app: &app };
+    create_project_impl(&ctx, state.inner(), project)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn update_project(
+    app: AppHandle,
+    state: State<AppState>,
+    project: Project,
+) -> CommandResult<Project> {
+    let _span = LogSpan::command("update_project");
+    let ctx = TauriCommandCtx { app: &app };
+    update_project_impl(&ctx, state.inner(), project)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn swap_project_sort_order(
+    app: AppHandle,
+    state: State<AppState>,
+    first_id: String,
+    second_id: String,
+) -> CommandResult<bool> {
+    let _span = LogSpan::command("swap_project_sort_order");
+    let ctx = TauriCommandCtx { app: &app };
+    swap_project_sort_order_impl(&ctx, state.inner(), first_id, second_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn project_tree(state: State<AppState>) -> CommandResult<Vec<ProjectNode>> {
+    let _span = LogSpan::command("project_tree");
+    project_tree_impl(state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn list_smart_lists(state: State<AppState>) -> CommandResult<Vec<SmartList>> {
+    let _span = LogSpan::command("list_smart_lists");
+    list_smart_lists_impl(state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn create_smart_list(
+    app: AppHandle,
+    state: State<AppState>,
+    list: SmartList,
+) -> CommandResult<SmartList> {
+    let _span = LogSpan::command("create_smart_list");
+    let ctx = TauriCommandCtx { app: &app };
+    create_smart_list_impl(&ctx, state.inner(), list)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn update_smart_list(
+    app: AppHandle,
+    state: State<AppState>,
+    list: SmartList,
+) -> CommandResult<SmartList> {
+    let _span = LogSpan::command("update_smart_list");
+    let ctx = TauriCommandCtx { app: &app };
+    update_smart_list_impl(&ctx, state.inner(), list)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn delete_smart_list(
+    app: AppHandle,
+    state: State<AppState>,
+    list_id: String,
+) -> CommandResult<bool> {
+    let _span = LogSpan::command("delete_smart_list");
+    let ctx = TauriCommandCtx { app: &app };
+    delete_smart_list_impl(&ctx, state.inner(), list_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn evaluate_smart_list(state: State<AppState>, list_id: String) -> CommandResult<Vec<Task>> {
+    let _span = LogSpan::command("evaluate_smart_list");
+    evaluate_smart_list_impl(state.inner(), list_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn list_automation_rules(state: State<AppState>) -> CommandResult<Vec<AutomationRule>> {
+    let _span = LogSpan::command("list_automation_rules");
+    list_automation_rules_impl(state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn create_automation_rule(
+    app: AppHandle,
+    state: State<AppState>,
+    rule: AutomationRule,
+) -> CommandResult<AutomationRule> {
+    let _span = LogSpan::command("create_automation_rule");
+    let ctx = TauriCommandCtx { app: &app };
+    create_automation_rule_impl(&ctx, state.inner(), rule)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn update_automation_rule(
+    app: AppHandle,
+    state: State<AppState>,
+    rule: AutomationRule,
+) -> CommandResult<AutomationRule> {
+    let _span = LogSpan::command("update_automation_rule");
+    let ctx = TauriCommandCtx { app: &app };
+    update_automation_rule_impl(&ctx, state.inner(), rule)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn delete_automation_rule(
+    app: AppHandle,
+    state: State<AppState>,
+    rule_id: String,
+) -> CommandResult<bool> {
+    let _span = LogSpan::command("delete_automation_rule");
+    let ctx = TauriCommandCtx { app: &app };
+    delete_automation_rule_impl(&ctx, state.inner(), rule_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn list_tags(state: State<AppState>) -> CommandResult<Vec<TagStat>> {
+    let _span = LogSpan::command("list_tags");
+    list_tags_impl(state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn list_tag_definitions(state: State<AppState>) -> CommandResult<Vec<Tag>> {
+    let _span = LogSpan::command("list_tag_definitions");
+    list_tag_definitions_impl(state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn create_tag(app: AppHandle, state: State<AppState>, tag: Tag) -> CommandResult<Tag> {
+    let _span = LogSpan::command("create_tag");
+    let ctx = TauriCommandCtx { app: &app };
+    create_tag_impl(&ctx, state.inner(), tag)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn update_tag(app: AppHandle, state: State<AppState>, tag: Tag) -> CommandResult<Tag> {
+    let _span = LogSpan::command("update_tag");
+    let ctx = TauriCommandCtx { app: &app };
+    update_tag_impl(&ctx, state.inner(), tag)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn delete_tag(app: AppHandle, state: State<AppState>, tag_id: String) -> CommandResult<bool> {
+    let _span = LogSpan::command("delete_tag");
+    let ctx = TauriCommandCtx { app: &app };
+    delete_tag_impl(&ctx, state.inner(), tag_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn suggest_tags(
+    state: State<AppState>,
+    prefix: Option<String>,
+    limit: Option<usize>,
+) -> CommandResult<Vec<TagStat>> {
+    let _span = LogSpan::command("suggest_tags");
+    suggest_tags_impl(state.inner(), prefix, limit)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn rename_tag(
+    app: AppHandle,
+    state: State<AppState>,
+    old: String,
+    new: String,
+) -> CommandResult<usize> {
+    let _span = LogSpan::command("rename_tag");
+    let ctx = TauriCommandCtx { app: &app };
+    rename_tag_impl(&ctx, state.inner(), old, new)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn merge_tags(
+    app: AppHandle,
+    state: State<AppState>,
+    sources: Vec<String>,
+    target: String,
+) -> CommandResult<usize> {
+    let _span = LogSpan::command("merge_tags");
+    let ctx = TauriCommandCtx { app: &app };
+    merge_tags_impl(&ctx, state.inner(), sources, target)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn get_view_prefs(state: State<AppState>, project_id: String) -> CommandResult<ViewPrefs> {
+    let _span = LogSpan::command("get_view_prefs");
+    get_view_prefs_impl(state.inner(), project_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn set_view_prefs(
+    app: AppHandle,
+    state: State<AppState>,
+    project_id: String,
+    prefs: ViewPrefs,
+) -> CommandResult<ViewPrefs> {
+    let _span = LogSpan::command("set_view_prefs");
+    let ctx = TauriCommandCtx { app: &app };
+    set_view_prefs_impl(&ctx, state.inner(), project_id, prefs)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn delete_project(
+    app: AppHandle,
+    state: State<AppState>,
+    project_id: String,
+) -> CommandResult<bool> {
+    let _span = LogSpan::command("delete_project");
+    let ctx = TauriCommandCtx { app: &app };
+    delete_project_impl(&ctx, state.inner(), project_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn create_task(
+    app: AppHandle,
+    state: State<AppState>,
+    task: Task,
+    check_duplicates: Option<bool>,
+) -> CommandResult<CreateOutcome> {
+    let _span = LogSpan::command("create_task");
+    let ctx = TauriCommandCtx { app: &app };
+    create_task_checked_impl(&ctx, state.inner(), task, check_duplicates.unwrap_or(false))
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub async fn ai_plan_task(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    request: AiPlanRequest,
+) -> Result<AiPlan, String> {
+    let snapshot = state.inner().snapshot();
+    let settings = &snapshot.settings;
+    if !settings.ai_enabled {
+        return Err("ai is disabled (settings.ai_enabled=false)".to_string());
+    }
+    let api_key = ai_api_key(&TauriCommandCtx { app: &app }, settings)?;
+    if settings.ai_model.trim().is_empty() {
+        return Err("ai model missing (settings.ai_model)".to_string());
+    }
+
+    log::info!(
+        "cmd=ai_plan_task start provider={:?} due_at={} important={} reminder_kind={:?} repeat={:?} raw_len={} title_len={} tags={}",
+        settings.ai_provider,
+        request.due_at,
+        request.important,
+        request.reminder_kind,
+        request.repeat,
+        request.raw_input.len(),
+        request.title.len(),
+        request.tags.len()
+    );
+
+    let now = state.clock().timestamp();
+    match crate::ai::plan_with_provider(
+        settings,
+        api_key.as_deref(),
+        &request,
+        now,
+        &snapshot.projects,
+        &snapshot.tasks,
+        &state.tags(),
+    )
+    .await
+    {
+        Ok(plan) => Ok(plan),
+        Err(message) => {
+            log::warn!("cmd=ai_plan_task failed err={}", message);
+            Err(message)
+        }
+    }
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub async fn ai_weekly_review(app: AppHandle) -> CommandResult<SavedWeeklyReview> {
+    let state = app.state::<AppState>().inner().clone();
+    let ctx = TauriCommandCtx { app: &app };
+    let (api_key, prompt) = match prepare_weekly_review_impl(&ctx, &state) {
+        Ok(prepared) => prepared,
+        Err(message) => {
+            log::warn!("cmd=ai_weekly_review rejected err={message}");
+            return err(&message);
+        }
+    };
+    log::info!(
+        "cmd=ai_weekly_review start provider={:?} completed={} overdue={}",
+        state.settings().ai_provider,
+        prompt.completed_count,
+        prompt.overdue_count
+    );
+    let reply = match crate::ai::complete_with_provider(
+        &state.settings(),
+        api_key.as_deref(),
+        &prompt.system,
+        &prompt.user,
+    )
+    .await
+    {
+        Ok(reply) => reply,
+        Err(message) => {
+            log::warn!("cmd=ai_weekly_review failed err={message}");
+            return err(&message);
+        }
+    };
+    finish_weekly_review_impl(&ctx, &state, &prompt, &reply)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn update_task(app: AppHandle, state: State<AppState>, task: Task) -> CommandResult<Task> {
+    let _span = LogSpan::command("update_task");
+    let ctx = TauriCommandCtx { app: &app };
+    update_task_impl(&ctx, state.inner(), task)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn bulk_update_tasks(
+    app: AppHandle,
+    state: State<AppState>,
+    tasks: Vec<Task>,
+    dry_run: Option<bool>,
+) -> CommandResult<Outcome<bool>> {
+    let _span = LogSpan::command("bulk_update_tasks");
+    let ctx = TauriCommandCtx { app: &app };
+    with_dry_run(
+        dry_run,
+        || bulk_update_tasks_dry_run_impl(state.inner(), tasks.clone()),
+        || bulk_update_tasks_impl(&ctx, state.inner(), tasks.clone()),
+    )
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn swap_sort_order(
+    app: AppHandle,
+    state: State<AppState>,
+    first_id: String,
+    second_id: String,
+) -> CommandResult<bool> {
+    let _span = LogSpan::command("swap_sort_order");
+    let ctx = TauriCommandCtx { app: &app };
+    swap_sort_order_impl(&ctx, state.inner(), first_id, second_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn pin_task(app: AppHandle, state: State<AppState>, task_id: String) -> CommandResult<Task> {
+    let _span = LogSpan::command("pin_task");
+    let ctx = TauriCommandCtx { app: &app };
+    set_task_pinned_impl(&ctx, state.inner(), task_id, true)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn unpin_task(app: AppHandle, state: State<AppState>, task_id: String) -> CommandResult<Task> {
+    let _span = LogSpan::command("unpin_task");
+    let ctx = TauriCommandCtx { app: &app };
+    set_task_pinned_impl(&ctx, state.inner(), task_id, false)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn mute_project(
+    app: AppHandle,
+    state: State<AppState>,
+    project_id: String,
+    until: Option<Timestamp>,
+) -> CommandResult<Project> {
+    let _span = LogSpan::command("mute_project");
+    let ctx = TauriCommandCtx { app: &app };
+    mute_project_impl(&ctx, state.inner(), project_id, until)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn unmute_project(
+    app: AppHandle,
+    state: State<AppState>,
+    project_id: String,
+) -> CommandResult<Project> {
+    let _span = LogSpan::command("unmute_project");
+    let ctx = TauriCommandCtx { app: &app };
+    unmute_project_impl(&ctx, state.inner(), project_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn add_comment(
+    app: AppHandle,
+    state: State<AppState>,
+    task_id: String,
+    text: String,
+) -> CommandResult<Comment> {
+    let _span = LogSpan::command("add_comment");
+    let ctx = TauriCommandCtx { app: &app };
+    add_comment_impl(&ctx, state.inner(), task_id, text)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn delete_comment(
+    app: AppHandle,
+    state: State<AppState>,
+    task_id: String,
+    comment_id: String,
+) -> CommandResult<bool> {
+    let _span = LogSpan::command("delete_comment");
+    let ctx = TauriCommandCtx { app: &app };
+    delete_comment_impl(&ctx, state.inner(), task_id, comment_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn promote_step_to_task(
+    app: AppHandle,
+    state: State<AppState>,
+    task_id: String,
+    step_id: String,
+) -> CommandResult<Task> {
+    let _span = LogSpan::command("promote_step_to_task");
+    let ctx = TauriCommandCtx { app: &app };
+    promote_step_to_task_impl(&ctx, state.inner(), task_id, step_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn convert_task_to_step(
+    app: AppHandle,
+    state: State<AppState>,
+    task_id: String,
+    target_task_id: String,
+    parent_step_id: Option<String>,
+) -> CommandResult<Task> {
+    let _span = LogSpan::command("convert_task_to_step");
+    let ctx = TauriCommandCtx { app: &app };
+    convert_task_to_step_impl(&ctx, state.inner(), task_id, target_task_id, parent_step_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn start_timer(app: AppHandle, state: State<AppState>, task_id: String) -> CommandResult<Task> {
+    let _span = LogSpan::command("start_timer");
+    let ctx = TauriCommandCtx { app: &app };
+    start_timer_impl(&ctx, state.inner(), task_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn stop_timer(
+    app: AppHandle,
+    state: State<AppState>,
+    task_id: Option<String>,
+) -> CommandResult<Vec<Task>> {
+    let _span = LogSpan::command("stop_timer");
+    let ctx = TauriCommandCtx { app: &app };
+    stop_timer_impl(&ctx, state.inner(), task_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn report_time(
+    state: State<AppState>,
+    group: Option<TimeReportGroup>,
+    from: Option<Timestamp>,
+    to: Option<Timestamp>,
+) -> CommandResult<Vec<TimeReportRow>> {
+    let _span = LogSpan::command("report_time");
+    report_time_impl(state.inner(), group, from, to)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn unblock_chain(state: State<AppState>, task_id: String) -> CommandResult<UnblockChain> {
+    let _span = LogSpan::command("unblock_chain");
+    unblock_chain_impl(state.inner(), task_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn add_attachment(
+    app: AppHandle,
+    state: State<AppState>,
+    task_id: String,
+    path: String,
+) -> CommandResult<Attachment> {
+    let _span = LogSpan::command("add_attachment");
+    let ctx = TauriCommandCtx { app: &app };
+    add_attachment_impl(&ctx, state.inner(), task_id, path)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn remove_attachment(
+    app: AppHandle,
+    state: State<AppState>,
+    task_id: String,
+    attachment_id: String,
+) -> CommandResult<bool> {
+    let _span = LogSpan::command("remove_attachment");
+    let ctx = TauriCommandCtx { app: &app };
+    remove_attachment_impl(&ctx, state.inner(), task_id, attachment_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn open_attachment(
+    app: AppHandle,
+    state: State<AppState>,
+    task_id: String,
+    attachment_id: String,
+) -> CommandResult<bool> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let _span = LogSpan::command("open_attachment");
+    let ctx = TauriCommandCtx { app: &app };
+    let result = attachment_file_impl(&ctx, state.inner(), task_id, attachment_id);
+    let Some(path) = result.data else {
+        return err(result.error.as_deref().unwrap_or("attachment not found"));
+    };
+    match app.opener().open_path(path.to_string_lossy(), None::<&str>) {
+        Ok(()) => ok(true),
+        Err(error) => {
+            log::error!("cmd=open_attachment open failed err={error}");
+            err(&format!("open error: {error}"))
+        }
+    }
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn complete_task(
+    app: AppHandle,
+    state: State<AppState>,
+    task_id: String,
+) -> CommandResult<Task> {
+    let _span = LogSpan::command("complete_task");
+    let ctx = TauriCommandCtx { app: &app };
+    complete_task_impl(&ctx, state.inner(), task_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn bulk_complete_tasks(
+    app: AppHandle,
+    state: State<AppState>,
+    task_ids: Vec<String>,
+) -> CommandResult<bool> {
+    let _span = LogSpan::command("bulk_complete_tasks");
+    let ctx = TauriCommandCtx { app: &app };
+    bulk_complete_tasks_impl(&ctx, state.inner(), task_ids)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn update_settings(
+    app: AppHandle,
+    state: State<AppState>,
+    settings: Settings,
+) -> CommandResult<Settings> {
+    let _span = LogSpan::command("update_settings");
+    let ctx = TauriCommandCtx { app: &app };
+    update_settings_impl(&ctx, state.inner(), settings)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub async fn show_settings_window(app: AppHandle) -> CommandResult<bool> {
+    log::info!("cmd=show_settings_window");
+    // Creating a new window via Wry must happen off the main event-loop thread, otherwise
+    // tauri-runtime-wry's channel-based dispatcher can deadlock. Async commands run on the
+    // async runtime, so we can safely spawn a blocking task here.
+    let join = tauri::async_runtime::spawn_blocking(move || show_settings_window_impl(&app));
+    match join.await {
+        Ok(Ok(())) => ok(true),
+        Ok(Err(message)) => {
+            log::error!("cmd=show_settings_window failed: {message}");
+            err(&message)
+        }
+        Err(join_err) => {
+            let message = format!("cmd=show_settings_window join failed: {join_err}");
+            log::error!("{message}");
+            err(&message)
+        }
+    }
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn frontend_log(level: String, message: String, context: Option<serde_json::Value>) -> bool {
+    let _span = LogSpan::command("frontend_log");
+    const MAX_CHARS: usize = 4000;
+
+    let lvl = level.trim().to_lowercase();
+    let trimmed = message.trim();
+
+    let mut msg: String = trimmed.chars().take(MAX_CHARS).collect();
+    if trimmed.chars().count() > MAX_CHARS {
+        msg.push_str("...");
+    }
+
+    let ctx = context
+        .and_then(|v| serde_json::to_string(&v).ok())
+        .unwrap_or_default();
+
+    match lvl.as_str() {
+        "error" => log::error!("frontend_log: {msg} ctx={ctx}"),
+        "warn" | "warning" => log::warn!("frontend_log: {msg} ctx={ctx}"),
+        "debug" => log::debug!("frontend_log: {msg} ctx={ctx}"),
+        "trace" => log::trace!("frontend_log: {msg} ctx={ctx}"),
+        _ => log::info!("frontend_log: {msg} ctx={ctx}"),
+    }
+
+    true
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn validate_shortcut(
+    app: AppHandle,
+    state: State<AppState>,
+    shortcut: String,
+    action: Option<String>,
+) -> CommandResult<ShortcutCheck> {
+    let _span = LogSpan::command("validate_shortcut");
+    let ctx = TauriCommandCtx { app: &app };
+    validate_shortcut_impl(&ctx, state.inner(), shortcut, action)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn set_shortcut_capture_active(state: State<AppState>, active: bool) -> CommandResult<bool> {
+    let _span = LogSpan::command("set_shortcut_capture_active");
+    log::info!("cmd=set_shortcut_capture_active active={}", active);
+    state.set_shortcut_capture_active(active);
+    ok(true)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn snooze_task(
+    app: AppHandle,
+    state: State<AppState>,
+    task_id: String,
+    until: i64,
+) -> CommandResult<bool> {
+    let _span = LogSpan::command("snooze_task");
+    let ctx = TauriCommandCtx { app: &app };
+    snooze_task_impl(&ctx, state.inner(), task_id, until)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn set_do_not_disturb(
+    app: AppHandle,
+    state: State<AppState>,
+    enabled: bool,
+) -> CommandResult<Settings> {
+    let _span = LogSpan::command("set_do_not_disturb");
+    let ctx = TauriCommandCtx { app: &app };
+    set_do_not_disturb_impl(&ctx, state.inner(), enabled)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn snooze_task_preset(
+    app: AppHandle,
+    state: State<AppState>,
+    task_id: String,
+    preset: SnoozePreset,
+) -> CommandResult<Timestamp> {
+    let _span = LogSpan::command("snooze_task_preset");
+    let ctx = TauriCommandCtx { app: &app };
+    snooze_task_preset_impl(&ctx, state.inner(), task_id, preset)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn notification_action(
+    app: AppHandle,
+    state: State<AppState>,
+    task_id: String,
+    action: NotificationAction,
+) -> CommandResult<bool> {
+    let _span = LogSpan::command("notification_action");
+    let ctx = TauriCommandCtx { app: &app };
+    notification_action_impl(&ctx, state.inner(), task_id, action)
+}
+
+/// The "complete top focus task" global shortcut: completes the first open task of today's
+/// focus list. `None` when there is nothing to complete.
+fn complete_focus_task_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    today: chrono::NaiveDate,
+) -> CommandResult<Option<Task>> {
+    let Some(task_id) = top_focus_task_id(&state.settings(), &state.shared_tasks(), today) else {
+        log::info!("cmd=complete_focus_task skipped: no open focus task");
+        return ok(None);
+    };
+    log::info!("cmd=complete_focus_task id={task_id}");
+    let res = complete_task_impl(ctx, state, task_id);
+    CommandResult {
+        ok: res.ok,
+        data: res.ok.then_some(res.data),
+        error: res.error,
+    }
+}
+
+/// Called from the global shortcut handler rather than the frontend.
+#[cfg(all(feature = "app", not(test)))]
+pub fn complete_focus_task<R: Runtime>(app: &AppHandle<R>) {
+    let _span = LogSpan::command("complete_focus_task");
+    let state = app.state::<AppState>();
+    let ctx = TauriCommandCtx { app };
+    let today = state.clock().now_local().date_naive();
+    let res = complete_focus_task_impl(&ctx, state.inner(), today);
+    if let Some(error) = res.error {
+        log::warn!("cmd=complete_focus_task failed err={error}");
+    }
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn dismiss_forced(
+    app: AppHandle,
+    state: State<AppState>,
+    task_id: String,
+) -> CommandResult<bool> {
+    let _span = LogSpan::command("dismiss_forced");
+    let ctx = TauriCommandCtx { app: &app };
+    dismiss_forced_impl(&ctx, state.inner(), task_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn delete_task(app: AppHandle, state: State<AppState>, task_id: String) -> CommandResult<bool> {
+    let _span = LogSpan::command("delete_task");
+    let ctx = TauriCommandCtx { app: &app };
+    delete_task_impl(&ctx, state.inner(), task_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn delete_tasks(
+    app: AppHandle,
+    state: State<AppState>,
+    task_ids: Vec<String>,
+    dry_run: Option<bool>,
+) -> CommandResult<Outcome<bool>> {
+    let _span = LogSpan::command("delete_tasks");
+    let ctx = TauriCommandCtx { app: &app };
+    with_dry_run(
+        dry_run,
+        || delete_tasks_dry_run_impl(state.inner(), task_ids.clone()),
+        || delete_tasks_impl(&ctx, state.inner(), task_ids.clone()),
+    )
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn list_trash(state: State<AppState>) -> CommandResult<Vec<TrashEntry>> {
+    let _span = LogSpan::command("list_trash");
+    list_trash_impl(state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn restore_from_trash(
+    app: AppHandle,
+    state: State<AppState>,
+    task_ids: Vec<String>,
+) -> CommandResult<Vec<Task>> {
+    let _span = LogSpan::command("restore_from_trash");
+    let ctx = TauriCommandCtx { app: &app };
+    restore_from_trash_impl(&ctx, state.inner(), task_ids)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn empty_trash(
+    app: AppHandle,
+    state: State<AppState>,
+    task_ids: Option<Vec<String>>,
+) -> CommandResult<usize> {
+    let _span = LogSpan::command("empty_trash");
+    let ctx = TauriCommandCtx { app: &app };
+    empty_trash_impl(&ctx, state.inner(), task_ids)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn list_archived(app: AppHandle, year: Option<i32>) -> CommandResult<Vec<Task>> {
+    let _span = LogSpan::command("list_archived");
+    let ctx = TauriCommandCtx { app: &app };
+    list_archived_impl(&ctx, year)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn search_archive(
+    app: AppHandle,
+    query: String,
+    limit: Option<usize>,
+) -> CommandResult<Vec<SearchHit>> {
+    let _span = LogSpan::command("search_archive");
+    let ctx = TauriCommandCtx { app: &app };
+    search_archive_impl(&ctx, query, limit)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn unarchive_task(
+    app: AppHandle,
+    state: State<AppState>,
+    task_id: String,
+) -> CommandResult<Task> {
+    let _span = LogSpan::command("unarchive_task");
+    let ctx = TauriCommandCtx { app: &app };
+    unarchive_task_impl(&ctx, state.inner(), task_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn query_tasks(state: State<AppState>, spec: TaskQuerySpec) -> CommandResult<TaskPage> {
+    let _span = LogSpan::command("query_tasks");
+    query_tasks_impl(state.inner(), spec)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn list_waiting_for(state: State<AppState>) -> CommandResult<Vec<WaitingForGroup>> {
+    let _span = LogSpan::command("list_waiting_for");
+    list_waiting_for_impl(state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn list_someday_tasks(state: State<AppState>) -> CommandResult<Vec<Task>> {
+    let _span = LogSpan::command("list_someday_tasks");
+    list_someday_tasks_impl(state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn list_scheduled_tasks(state: State<AppState>) -> CommandResult<Vec<Task>> {
+    let _span = LogSpan::command("list_scheduled_tasks");
+    list_scheduled_tasks_impl(state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn move_to_someday(
+    app: AppHandle,
+    state: State<AppState>,
+    task_id: String,
+) -> CommandResult<Task> {
+    let _span = LogSpan::command("move_to_someday");
+    let ctx = TauriCommandCtx { app: &app };
+    move_to_someday_impl(&ctx, state.inner(), task_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn move_from_someday(
+    app: AppHandle,
+    state: State<AppState>,
+    task_id: String,
+    due_at: Option<i64>,
+) -> CommandResult<Task> {
+    let _span = LogSpan::command("move_from_someday");
+    let ctx = TauriCommandCtx { app: &app };
+    move_from_someday_impl(&ctx, state.inner(), task_id, due_at)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn resolve_quick_due(
+    state: State<AppState>,
+    due_at: Option<i64>,
+) -> CommandResult<Option<i64>> {
+    let _span = LogSpan::command("resolve_quick_due");
+    resolve_quick_due_impl(state.inner(), due_at)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn get_burndown(
+    app: AppHandle,
+    state: State<AppState>,
+    project_id: Option<String>,
+    range: Option<BurndownRange>,
+) -> CommandResult<Burndown> {
+    let _span = LogSpan::command("get_burndown");
+    let ctx = TauriCommandCtx { app: &app };
+    get_burndown_impl(&ctx, state.inner(), project_id, range)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn get_statistics(
+    app: AppHandle,
+    state: State<AppState>,
+    from: Option<Timestamp>,
+    to: Option<Timestamp>,
+) -> CommandResult<Statistics> {
+    let _span = LogSpan::command("get_statistics");
+    let ctx = TauriCommandCtx { app: &app };
+    get_statistics_impl(&ctx, state.inner(), from, to)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn get_focus_stats(
+    app: AppHandle,
+    state: State<AppState>,
+    from: Option<Timestamp>,
+    to: Option<Timestamp>,
+) -> CommandResult<FocusStats> {
+    let _span = LogSpan::command("get_focus_stats");
+    let ctx = TauriCommandCtx { app: &app };
+    get_focus_stats_impl(&ctx, state.inner(), from, to)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn infer_task_from_text(state: State<AppState>, text: String) -> CommandResult<TaskDraft> {
+    let _span = LogSpan::command("infer_task_from_text");
+    infer_task_from_text_impl(state.inner(), text)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn events_manifest() -> CommandResult<EventsManifest> {
+    let _span = LogSpan::command("events_manifest");
+    events_manifest_impl()
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn get_recovery_status(state: State<AppState>) -> CommandResult<Option<RecoveryStatus>> {
+    let _span = LogSpan::command("get_recovery_status");
+    get_recovery_status_impl(state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn search_tasks(
+    state: State<AppState>,
+    query: String,
+    include_completed: Option<bool>,
+    limit: Option<usize>,
+) -> CommandResult<Vec<SearchHit>> {
+    let _span = LogSpan::command("search_tasks");
+    search_tasks_impl(state.inner(), query, include_completed, limit)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn undo_last_action(app: AppHandle, state: State<AppState>) -> CommandResult<UndoSummary> {
+    let _span = LogSpan::command("undo_last_action");
+    let ctx = TauriCommandCtx { app: &app };
+    undo_last_action_impl(&ctx, state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn redo_last_action(app: AppHandle, state: State<AppState>) -> CommandResult<UndoSummary> {
+    let _span = LogSpan::command("redo_last_action");
+    let ctx = TauriCommandCtx { app: &app };
+    redo_last_action_impl(&ctx, state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn get_undo_status(state: State<AppState>) -> CommandResult<UndoStatus> {
+    let _span = LogSpan::command("get_undo_status");
+    get_undo_status_impl(state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn suggest_next_task(
+    state: State<AppState>,
+    limit: Option<usize>,
+) -> CommandResult<Vec<Suggestion>> {
+    let _span = LogSpan::command("suggest_next_task");
+    suggest_next_task_impl(state.inner(), limit)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn format_dates(
+    state: State<AppState>,
+    timestamps: Vec<Timestamp>,
+) -> CommandResult<Vec<String>> {
+    let _span = LogSpan::command("format_dates");
+    format_dates_impl(state.inner(), timestamps)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn list_contexts(state: State<AppState>) -> CommandResult<Vec<ContextSummary>> {
+    let _span = LogSpan::command("list_contexts");
+    list_contexts_impl(state.inner())
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct BackupEntry {
+    pub name: String,
+    pub modified_at: i64,
+    /// Bytes on disk; `*.json.gz` backups are compressed.
+    pub size: u64,
+}
+
+fn set_smtp_password_impl(ctx: &impl CommandCtx, password: String) -> CommandResult<bool> {
+    log::info!("cmd=set_smtp_password clear={}", password.is_empty());
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    if let Err(error) = fs::create_dir_all(&root) {
+        return err(&format!("storage error: {error:?}"));
+    }
+    match crate::secrets::set_secret(&root, crate::secrets::SMTP_PASSWORD, &password) {
+        Ok(()) => ok(true),
+        Err(error) => {
+            log::error!("cmd=set_smtp_password failed err={error}");
+            err(&format!("storage error: {error:?}"))
+        }
+    }
+}
+
+fn set_mqtt_password_impl(ctx: &impl CommandCtx, password: String) -> CommandResult<bool> {
+    log::info!("cmd=set_mqtt_password clear={}", password.is_empty());
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    if let Err(error) = fs::create_dir_all(&root) {
+        return err(&format!("storage error: {error:?}"));
+    }
+    match crate::secrets::set_secret(&root, crate::secrets::MQTT_PASSWORD, &password) {
+        Ok(()) => ok(true),
+        Err(error) => {
+            log::error!("cmd=set_mqtt_password failed err={error}");
+            err(&format!("storage error: {error:?}"))
+        }
+    }
+}
+
+fn set_secret_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    name: String,
+    value: String,
+) -> CommandResult<Settings> {
+    log::info!("cmd=set_secret name={} clear={}", name, value.is_empty());
+    if !crate::secrets::SECRET_NAMES.contains(&name.as_str()) {
+        log::warn!("cmd=set_secret rejected unknown name={name}");
+        return err(&format!("unknown secret: {name}"));
+    }
+    let is_api_key = name.ends_with("_api_key");
+    let value = if is_api_key {
+        value.trim().to_string()
+    } else {
+        value
+    };
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    if let Err(error) = fs::create_dir_all(&root) {
+        return err(&format!("storage error: {error:?}"));
+    }
+    if let Err(error) = crate::secrets::set_secret(&root, &name, &value) {
+        log::error!("cmd=set_secret failed name={name} err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+
+    let mut settings = state.settings();
+    let key_set = match crate::secrets::ai_api_key_stored(&root, settings.ai_provider) {
+        Ok(stored) => stored,
+        Err(error) => return err(&format!("storage error: {error:?}")),
+    };
+    if settings.ai_api_key_set != key_set {
+        let previous = settings.clone();
+        settings.ai_api_key_set = key_set;
+        state.update_settings(settings.clone());
+        if let Err(error) = persist(ctx, state) {
+            state.update_settings(previous);
+            log::error!("cmd=set_secret persist failed err={error}");
+            return err(&format!("storage error: {error:?}"));
+        }
+    }
+    ok(settings)
+}
+
+fn clear_secret_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    name: String,
+) -> CommandResult<Settings> {
+    set_secret_impl(ctx, state, name, String::new())
+}
+
+fn list_carry_over_log_impl(
+    ctx: &impl CommandCtx,
+    limit: Option<usize>,
+) -> CommandResult<Vec<CarryOverEntry>> {
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    match read_carry_over_log(&root, limit.unwrap_or(50)) {
+        Ok(entries) => {
+            log::info!("cmd=list_carry_over_log ok count={}", entries.len());
+            ok(entries)
+        }
+        Err(error) => {
+            log::error!("cmd=list_carry_over_log failed err={error}");
+            err(&format!("storage error: {error:?}"))
+        }
+    }
+}
+
+fn start_focus_session_impl(
+    state: &AppState,
+    task_id: String,
+    minutes: Option<u32>,
+) -> CommandResult<FocusStatus> {
+    let minutes = match focus_minutes(minutes) {
+        Ok(minutes) => minutes,
+        Err(message) => return err(&message),
+    };
+    match state.task(&task_id) {
+        None => return err("task not found"),
+        Some(task) if task.completed => return err("task is already completed"),
+        Some(_) => {}
+    }
+    match state
+        .focus()
+        .start(&task_id, minutes, state.clock().timestamp())
+    {
+        Ok(status) => {
+            log::info!("cmd=start_focus_session ok id={task_id} minutes={minutes}");
+            ok(status)
+        }
+        Err(message) => {
+            log::warn!("cmd=start_focus_session rejected id={task_id} err={message}");
+            err(&message)
+        }
+    }
+}
+
+fn pause_focus_impl(state: &AppState) -> CommandResult<FocusStatus> {
+    match state.focus().pause(state.clock().timestamp()) {
+        Ok(status) => {
+            log::info!(
+                "cmd=pause_focus ok id={} focused_seconds={}",
+                status.task_id,
+                status.focused_seconds
+            );
+            ok(status)
+        }
+        Err(message) => err(&message),
+    }
+}
+
+fn resume_focus_impl(state: &AppState) -> CommandResult<FocusStatus> {
+    match state.focus().resume(state.clock().timestamp()) {
+        Ok(status) => {
+            log::info!(
+                "cmd=resume_focus ok id={} remaining_seconds={}",
+                status.task_id,
+                status.remaining_seconds
+            );
+            ok(status)
+        }
+        Err(message) => err(&message),
+    }
+}
+
+fn get_focus_status_impl(state: &AppState) -> CommandResult<Option<FocusStatus>> {
+    ok(state.focus().status(state.clock().timestamp()))
+}
+
+/// Ends the running session early and records it; `None` when nothing was running.
+fn stop_focus_impl(ctx: &impl CommandCtx, state: &AppState) -> CommandResult<Option<FocusSession>> {
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    let Some(session) = state.focus().stop(state.clock().timestamp()) else {
+        return ok(None);
+    };
+    if let Err(error) = append_focus_log(&root, &session) {
+        log::error!("cmd=stop_focus log write failed err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    log::info!(
+        "cmd=stop_focus ok id={} focused_seconds={}",
+        session.task_id,
+        session.focused_seconds
+    );
+    ok(Some(session))
+}
+
+fn list_focus_sessions_impl(
+    ctx: &impl CommandCtx,
+    task_id: Option<String>,
+    limit: Option<usize>,
+) -> CommandResult<Vec<FocusSession>> {
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    match read_focus_log(&root, task_id.as_deref(), limit.unwrap_or(50)) {
+        Ok(sessions) => {
+            log::info!("cmd=list_focus_sessions ok count={}", sessions.len());
+            ok(sessions)
+        }
+        Err(error) => {
+            log::error!("cmd=list_focus_sessions failed err={error}");
+            err(&format!("storage error: {error:?}"))
+        }
+    }
+}
+
+/// A session that ran its full length: recorded like a stopped one, and with
+/// `focus_end_forced_reminder` the task is shown in the forced reminder overlay (the task's
+/// own reminder state is left alone, as with `test_fire_reminder`).
+fn record_finished_focus(ctx: &impl CommandCtx, state: &AppState, session: &FocusSession) {
+    match ctx.app_data_dir() {
+        Ok(root) => {
+            if let Err(error) = append_focus_log(&root, session) {
+                log::error!("focus: log write failed err={error}");
+            }
+        }
+        Err(error) => log::error!("focus: app_data_dir failed: {error}"),
+    }
+    log::info!(
+        "focus: session finished id={} minutes={}",
+        session.task_id,
+        session.minutes
+    );
+    if !state.settings().focus_end_forced_reminder {
+        return;
+    }
+    let Some(mut task) = state.task(&session.task_id) else {
+        return;
+    };
+    task.reminder.kind = ReminderKind::Forced;
+    task.reminder.forced_dismissed = false;
+    ctx.fire_reminder(std::slice::from_ref(&task));
+}
+
+/// Scheduler hook for a focus session whose time ran out.
+#[cfg(all(feature = "app", not(test)))]
+pub fn finish_focus_session(app: &AppHandle, state: &AppState, session: FocusSession) {
+    let ctx = TauriCommandCtx { app };
+    record_finished_focus(&ctx, state, &session);
+    if let Err(error) = app.emit(EVENT_FOCUS_FINISHED, session) {
+        log::warn!("focus: emit focus_finished failed: {error}");
+    }
+}
+
+fn list_reminder_audit_impl(
+    ctx: &impl CommandCtx,
+    limit: Option<usize>,
+) -> CommandResult<Vec<ReminderAuditEntry>> {
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    match read_reminder_audit(&root, limit.unwrap_or(100)) {
+        Ok(entries) => {
+            log::info!("cmd=list_reminder_audit ok count={}", entries.len());
+            ok(entries)
+        }
+        Err(error) => {
+            log::error!("cmd=list_reminder_audit failed err={error}");
+            err(&format!("storage error: {error:?}"))
+        }
+    }
+}
+
+/// Bundled tones plus the user's files in `sounds/`, for the project sound picker.
+fn list_sounds_impl(ctx: &impl CommandCtx) -> CommandResult<Vec<SoundEntry>> {
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    let sounds = list_sounds(&root);
+    log::info!("cmd=list_sounds count={}", sounds.len());
+    ok(sounds)
+}
+
+/// Loads a sound for playback: the Settings preview, and forced reminders of projects that
+/// picked one.
+fn preview_sound_impl(ctx: &impl CommandCtx, sound_id: String) -> CommandResult<SoundClip> {
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    match load_sound(&root, sound_id.trim()) {
+        Ok(clip) => {
+            log::info!(
+                "cmd=preview_sound id={} bytes={}",
+                clip.id,
+                clip.bytes.len()
+            );
+            ok(clip)
+        }
+        Err(error) => {
+            log::warn!("cmd=preview_sound failed id={sound_id} err={error}");
+            err(&error)
+        }
+    }
+}
+
+fn list_email_deliveries_impl(
+    ctx: &impl CommandCtx,
+    limit: Option<usize>,
+) -> CommandResult<Vec<DeliveryLogEntry>> {
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    match read_delivery_log(&root, limit.unwrap_or(50)) {
+        Ok(entries) => {
+            log::info!("cmd=list_email_deliveries ok count={}", entries.len());
+            ok(entries)
+        }
+        Err(error) => {
+            log::error!("cmd=list_email_deliveries failed err={error}");
+            err(&format!("storage error: {error:?}"))
+        }
+    }
+}
+
+/// Resolves everything `send_test_email` needs before going async: the delivery log root, the
+/// validated SMTP config (settings + stored password) and the message itself.
+fn set_caldav_password_impl(ctx: &impl CommandCtx, password: String) -> CommandResult<bool> {
+    log::info!("cmd=set_caldav_password clear={}", password.is_empty());
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    if let Err(error) = fs::create_dir_all(&root) {
+        return err(&format!("storage error: {error:?}"));
+    }
+    match crate::secrets::set_secret(&root, crate::secrets::CALDAV_PASSWORD, &password) {
+        Ok(()) => ok(true),
+        Err(error) => {
+            log::error!("cmd=set_caldav_password failed err={error}");
+            err(&format!("storage error: {error:?}"))
+        }
+    }
+}
+
+/// Everything a sync run needs before it goes to the network.
+/// The selected provider's key from secrets; `None` for providers without one.
+fn ai_api_key(ctx: &impl CommandCtx, settings: &Settings) -> Result<Option<String>, String> {
+    let Some(name) = crate::ai::providers::provider(settings.ai_provider).api_key_secret() else {
+        return Ok(None);
+    };
+    let root = ctx
+        .app_data_dir()
+        .map_err(|e| format!("app_data_dir error: {e}"))?;
+    match crate::secrets::get_secret(&root, name) {
+        Ok(Some(key)) => Ok(Some(key)),
+        Ok(None) => Err(format!("ai api key missing (set_secret {name})")),
+        Err(error) => Err(format!("storage error: {error:?}")),
+    }
+}
+
+/// Everything a weekly review needs before it goes to the AI provider.
+fn prepare_weekly_review_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+) -> Result<(Option<String>, ReviewPrompt), String> {
+    let snapshot = state.snapshot();
+    let settings = &snapshot.settings;
+    if !settings.ai_enabled {
+        return Err("ai is disabled (settings.ai_enabled=false)".to_string());
+    }
+    let api_key = ai_api_key(ctx, settings)?;
+    let root = ctx
+        .app_data_dir()
+        .map_err(|e| format!("app_data_dir error: {e}"))?;
+    // Best effort like the log itself: a review without focus time beats no review.
+    let sessions = read_focus_log(&root, None, usize::MAX).unwrap_or_else(|error| {
+        log::warn!("cmd=ai_weekly_review focus log unreadable err={error}");
+        Vec::new()
+    });
+    let prompt = review_prompt(
+        &snapshot.tasks,
+        &sessions,
+        &localized_projects(&snapshot.projects, locale_for(settings)),
+        &settings.language,
+        &sys_locale::get_locale().unwrap_or_default(),
+        state.clock().timestamp(),
+    );
+    Ok((api_key, prompt))
+}
+
+/// Parses the provider's reply and stores it under `reviews/`.
+fn finish_weekly_review_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    prompt: &ReviewPrompt,
+    reply: &str,
+) -> CommandResult<SavedWeeklyReview> {
+    let review = match parse_review(prompt, reply, state.clock().timestamp()) {
+        Ok(review) => review,
+        Err(message) => {
+            log::warn!("cmd=ai_weekly_review bad reply err={message}");
+            return err(&message);
+        }
+    };
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    let path = review_path(&root, &review);
+    let json = match serde_json::to_vec_pretty(&review) {
+        Ok(json) => json,
+        Err(error) => return err(&format!("json error: {error}")),
+    };
+    if let Err(error) = write_atomic_bytes(&path, &json) {
+        log::error!("cmd=ai_weekly_review write failed err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    log::info!(
+        "cmd=ai_weekly_review ok completed={} overdue={} path={}",
+        review.completed_count,
+        review.overdue_count,
+        path.display()
+    );
+    ok(SavedWeeklyReview {
+        path: path.to_string_lossy().to_string(),
+        review,
+    })
+}
+
+fn prepare_caldav_sync_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+) -> Result<(CalDavConfig, CalDavSyncState), String> {
+    let root = ctx
+        .app_data_dir()
+        .map_err(|e| format!("app_data_dir error: {e}"))?;
+    let password = crate::secrets::get_secret(&root, crate::secrets::CALDAV_PASSWORD)
+        .map_err(|error| format!("storage error: {error:?}"))?;
+    let config = caldav_config(&state.settings(), password)?;
+    let previous = Storage::new(root)
+        .load_caldav_sync()
+        .map_err(|error| format!("storage error: {error:?}"))?;
+    Ok((config, previous))
+}
+
+/// Applies the pulled side of `plan` and records the new sync state. Pulls and local deletes
+/// are skipped for tasks edited while the run was on the network; the next run pushes them.
+fn finish_caldav_sync_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    config: &CalDavConfig,
+    previous: &CalDavSyncState,
+    remote: &[RemoteTodo],
+    plan: &SyncPlan,
+    outcome: RemoteOutcome,
+) -> CommandResult<SyncReport> {
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    let current: std::collections::HashMap<String, i64> = state
+        .tasks()
+        .into_iter()
+        .map(|task| (task.id, task.updated_at))
+        .collect();
+    let mut applied = std::collections::HashSet::new();
+    for (task, seen) in &plan.pull {
+        match (current.get(&task.id), seen) {
+            (None, None) => state.add_task(task.clone()),
+            (Some(at), Some(seen)) if at == seen => state.update_task(task.clone()),
+            _ => {
+                log::info!("cmd=sync_now skip pull id={} changed during sync", task.id);
+                continue;
+            }
+        }
+        applied.insert(task.id.clone());
+    }
+    let mut deleted_local = 0;
+    for (task_id, seen) in &plan.delete_local {
+        if current.get(task_id) == Some(seen) {
+            state.remove_task(task_id);
+            deleted_local += 1;
+        }
+    }
+
+    let next = crate::sync::next_sync_state(
+        &config.url,
+        plan,
+        &applied,
+        &outcome.failed,
+        remote,
+        previous,
+    );
+    if let Err(error) = Storage::new(root).save_caldav_sync(&next) {
+        log::error!("cmd=sync_now state write failed err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    let mut settings = state.settings();
+    settings.last_caldav_sync_at = Some(state.clock().timestamp());
+    state.update_settings(settings);
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=sync_now persist failed err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+
+    let report = SyncReport {
+        pushed: plan
+            .push
+            .iter()
+            .filter(|item| !outcome.failed.contains(&item.task.id))
+            .count(),
+        pulled: applied.len(),
+        deleted_local,
+        deleted_remote: plan
+            .delete_remote
+            .iter()
+            .filter(|item| !outcome.failed.contains(&item.uid))
+            .count(),
+        conflicts: plan.conflicts,
+        errors: outcome.errors,
+    };
+    log::info!(
+        "cmd=sync_now ok pushed={} pulled={} deleted_local={} deleted_remote={} conflicts={} errors={}",
+        report.pushed,
+        report.pulled,
+        report.deleted_local,
+        report.deleted_remote,
+        report.conflicts,
+        report.errors.len()
+    );
+    ok(report)
+}
+
+/// One full CalDAV round trip; shared by `sync_now` and the scheduler.
+#[cfg(all(feature = "app", not(test)))]
+pub async fn run_caldav_sync(app: &AppHandle, state: &AppState) -> CommandResult<SyncReport> {
+    let Some(_guard) = crate::sync::SyncGuard::acquire() else {
+        return err("sync already running");
+    };
+    let ctx = TauriCommandCtx { app };
+    let (config, previous) = match prepare_caldav_sync_impl(&ctx, state) {
+        Ok(prepared) => prepared,
+        Err(message) => {
+            log::warn!("cmd=sync_now invalid config err={message}");
+            return err(&message);
+        }
+    };
+    log::info!("cmd=sync_now start url={}", config.url);
+    let remote = match crate::sync::fetch_remote(&config).await {
+        Ok(remote) => remote,
+        Err(message) => {
+            log::warn!("cmd=sync_now list failed err={message}");
+            return err(&message);
+        }
+    };
+    let plan = crate::sync::plan_sync(&config.url, &state.shared_tasks(), &remote, &previous);
+    let now = state.clock().timestamp();
+    let mut outcome = RemoteOutcome::default();
+    for item in &plan.push {
+        if let Err(message) = crate::sync::push_todo(&config, item, now).await {
+            log::warn!("cmd=sync_now push failed id={} err={message}", item.task.id);
+            outcome.fail(&item.task.id, message);
+        }
+    }
+    for target in &plan.delete_remote {
+        if let Err(message) = crate::sync::delete_todo(&config, target).await {
+            log::warn!("cmd=sync_now delete failed id={} err={message}", target.uid);
+            outcome.fail(&target.uid, message);
+        }
+    }
+    finish_caldav_sync_impl(&ctx, state, &config, &previous, &remote, &plan, outcome)
+}
+
+fn prepare_test_email_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+) -> Result<(PathBuf, SmtpConfig, EmailMessage), String> {
+    let root = ctx
+        .app_data_dir()
+        .map_err(|e| format!("app_data_dir error: {e}"))?;
+    let password = crate::secrets::get_secret(&root, crate::secrets::SMTP_PASSWORD)
+        .map_err(|error| format!("storage error: {error:?}"))?;
+    let settings = state.settings();
+    let config = crate::email::smtp_config(&settings, password)?;
+    let dates = DateFormatter::new(
+        &settings,
+        &sys_locale::get_locale().unwrap_or_default(),
+        state.clock().now_local(),
+    );
+    let message = crate::email::build_test_email(&settings, &dates);
+    Ok((root, config, message))
+}
+
+fn list_backups_impl(ctx: &impl CommandCtx) -> CommandResult<Vec<BackupEntry>> {
+    log::info!("cmd=list_backups start");
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    let storage = Storage::new(root);
+
+    // If the backup directory does not exist yet, create it and return an empty list.
+    let list = match storage.list_backups() {
+        Ok(list) => list,
+        Err(StorageError::Io(io)) if io.kind() == std::io::ErrorKind::NotFound => {
+            if let Err(error) = storage.ensure_dirs() {
+                log::error!("cmd=list_backups ensure_dirs failed err={error}");
+                return err(&format!("storage error: {error:?}"));
+            }
+            log::info!("cmd=list_backups backup dir missing; created");
+            Vec::new()
+        }
+        Err(error) => {
+            log::error!("cmd=list_backups list failed err={error}");
+            return err(&format!("storage error: {error:?}"));
+        }
+    };
+
+    let entries: Vec<BackupEntry> = list
+        .into_iter()
+        .map(|(name, modified_at, size)| BackupEntry {
+            name,
+            modified_at,
+            size,
+        })
+        .collect();
+    log::info!("cmd=list_backups ok count={}", entries.len());
+    ok(entries)
+}
+
+fn estimate_backup_archive_impl(ctx: &impl CommandCtx) -> CommandResult<ArchiveEstimate> {
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    let storage = Storage::new(root);
+    match storage
+        .archive_sources()
+        .and_then(|sources| estimate_archive(&storage, &sources))
+    {
+        Ok(estimate) => {
+            log::info!(
+                "cmd=estimate_backup_archive ok files={} bytes={}",
+                estimate.files,
+                estimate.bytes
+            );
+            ok(estimate)
+        }
+        Err(error) => {
+            log::error!("cmd=estimate_backup_archive failed err={error}");
+            err(&format!("storage error: {error:?}"))
+        }
+    }
+}
+
+/// Bundles data, settings and every backup into a dated ZIP inside the directory `path`, for
+/// manual offsite storage. Returns the archive path.
+fn export_all_backups_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    path: String,
+) -> CommandResult<String> {
+    let dir = PathBuf::from(path.trim());
+    log::info!("cmd=export_all_backups start dir={}", dir.display());
+    if !dir.is_absolute() || !dir.is_dir() {
+        log::warn!("cmd=export_all_backups invalid dir={}", dir.display());
+        return err("destination must be an existing absolute directory");
+    }
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    // Flush in-memory state first so the archive holds the current data.
+    if let Err(error) = persist_now(ctx, state) {
+        log::error!("cmd=export_all_backups persist failed err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    let storage = Storage::new(root);
+    let now = state.clock().now_local();
+    let dest = dir.join(archive_file_name(now));
+    let result = storage.archive_sources().and_then(|sources| {
+        write_archive(&storage, &sources, &dest, now, |progress| {
+            ctx.emit_archive_progress(&progress)
+        })
+    });
+    match result {
+        Ok(estimate) => {
+            log::info!(
+                "cmd=export_all_backups ok path={} files={} bytes={}",
+                dest.display(),
+                estimate.files,
+                estimate.bytes
+            );
+            ok(dest.display().to_string())
+        }
+        Err(error) => {
+            log::error!("cmd=export_all_backups failed err={error}");
+            err(&format!("export error: {error:?}"))
+        }
+    }
+}
+
+fn delete_backup_impl(ctx: &impl CommandCtx, filename: String) -> CommandResult<bool> {
+    log::info!("cmd=delete_backup start filename={}", filename);
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    let storage = Storage::new(root);
+    if let Err(error) = storage.ensure_dirs() {
+        log::error!(
+            "cmd=delete_backup ensure_dirs failed filename={} err={error}",
+            filename
+        );
+        return err(&format!("storage error: {error:?}"));
+    }
+    if let Err(error) = storage.delete_backup(&filename) {
+        log::error!("cmd=delete_backup failed filename={} err={error}", filename);
+        return err(&format!("storage error: {error:?}"));
+    }
+    log::info!("cmd=delete_backup ok filename={}", filename);
+    ok(true)
+}
+
+/// Writes any change still waiting in the write-behind queue; `true` when something was
+/// written. Tests and the frontend call this before reading the files directly.
+fn flush_storage_impl(state: &AppState) -> CommandResult<bool> {
+    match flush_writes(state) {
+        Ok(written) => {
+            log::info!("cmd=flush_storage ok written={written}");
+            ok(written)
+        }
+        Err(error) => {
+            log::error!("cmd=flush_storage failed err={error}");
+            err(&format!("storage error: {error:?}"))
+        }
+    }
+}
+
+/// Whether edits are still waiting for the disk and why the last write failed, for windows
+/// that open after a `write_status` event was sent.
+fn get_write_status_impl(state: &AppState) -> CommandResult<WriteStatus> {
+    let status = state.write_queue().status();
+    log::info!(
+        "cmd=get_write_status ok pending={} failed={}",
+        status.pending,
+        status.last_error.is_some()
+    );
+    ok(status)
+}
+
+fn create_backup_impl(ctx: &impl CommandCtx, state: &AppState) -> CommandResult<bool> {
+    log::info!("cmd=create_backup start");
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    let storage =
+        Storage::new(root).with_backup_policy(BackupPolicy::from_settings(&state.settings()));
+    if let Err(error) = storage.ensure_dirs() {
+        log::error!("cmd=create_backup ensure_dirs failed err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    // The backup copies data.json as it is on disk, so queued changes are written first.
+    if let Err(error) = flush_writes(state) {
+        log::error!("cmd=create_backup flush failed err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    let tasks_file = state.tasks_file();
+    log::info!(
+        "cmd=create_backup saving tasks with backup tasks={} projects={}",
+        tasks_file.tasks.len(),
+        tasks_file.projects.len()
+    );
+    let now = state.clock().timestamp();
+    let previous = state.settings();
+    let mut settings = previous.clone();
+    settings.last_backup_at = Some(now);
+    state.update_settings(settings);
+    if let Err(error) = storage.save_state(&tasks_file, &state.settings_file(), true) {
+        log::error!("cmd=create_backup save_state failed err={error}");
+        state.update_settings(previous);
+        return err(&format!("storage error: {error:?}"));
+    }
+    log::info!("cmd=create_backup ok last_backup_at={now}");
+    ok(true)
+}
+
+fn run_maintenance_now_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+) -> CommandResult<MaintenanceReport> {
+    log::info!("cmd=run_maintenance_now start");
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    let report = run_maintenance(&root, state, state.clock().timestamp());
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=run_maintenance_now persist failed err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    ok(report)
+}
+
+/// Moves the data set to `path` and points future boots at it.
+///
+/// Order matters for safety: copy everything, switch the pointer, then delete the old copies.
+/// A failure before the switch removes the partial copy so the old location stays authoritative.
+fn set_data_directory_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    path: String,
+) -> CommandResult<String> {
+    let target = PathBuf::from(path.trim());
+    log::info!("cmd=set_data_directory start target={}", target.display());
+    let (default_root, current) = match (ctx.default_app_data_dir(), ctx.data_root()) {
+        (Ok(default_root), Ok(current)) => (default_root, current),
+        (Err(e), _) | (_, Err(e)) => return err(&format!("app_data_dir error: {e}")),
+    };
+    if let Err(message) = validate_target(&current, &target) {
+        log::warn!("cmd=set_data_directory invalid target err={message}");
+        return err(&message);
+    }
+    // Flush in-memory state first so the copy is complete.
+    if let Err(error) = persist_now(ctx, state) {
+        log::error!("cmd=set_data_directory persist failed err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    let copied = match copy_data_dir(&current, &target, |progress| {
+        ctx.emit_data_dir_progress(&progress)
+    }) {
+        Ok(copied) => copied,
+        Err(error) => {
+            log::error!("cmd=set_data_directory copy failed err={error}");
+            return err(&format!("storage error: {error:?}"));
+        }
+    };
+    if let Err(error) = write_pointer(&default_root, &target) {
+        log::error!("cmd=set_data_directory pointer write failed err={error}");
+        remove_copied(&target, &copied, false);
+        return err(&format!("storage error: {error:?}"));
+    }
+    remove_old_entries(&current, &copied);
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=set_data_directory persist to new location failed err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    log::info!(
+        "cmd=set_data_directory ok from={} to={} items={}",
+        current.display(),
+        target.display(),
+        copied.len()
+    );
+    ok(target.display().to_string())
+}
+
+fn list_profiles_impl(ctx: &impl CommandCtx) -> CommandResult<ProfileRegistry> {
+    match ctx.data_root() {
+        Ok(root) => ok(load_registry(&root)),
+        Err(e) => err(&format!("app_data_dir error: {e}")),
+    }
+}
+
+fn create_profile_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    name: String,
+) -> CommandResult<Profile> {
+    let root = match ctx.data_root() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    let mut registry = load_registry(&root);
+    let profile = match add_profile(&mut registry, &name, state.clock().timestamp()) {
+        Ok(profile) => profile,
+        Err(message) => {
+            log::warn!("cmd=create_profile invalid err={message}");
+            return err(&message);
+        }
+    };
+    if let Err(error) = save_registry(&root, &registry) {
+        log::error!("cmd=create_profile save failed err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    log::info!("cmd=create_profile ok id={}", profile.id);
+    ok(profile)
+}
+
+/// Flushes the current profile, then loads `name` into the running state.
+///
+/// The scheduler, tray and email fallback only ever see `AppState` and the active profile root,
+/// so nothing from the previous profile leaks once the swap is done.
+fn switch_profile_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    name: String,
+) -> CommandResult<StatePayload> {
+    let root = match ctx.data_root() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    let mut registry = load_registry(&root);
+    let Some(profile) = registry.find(&name).cloned() else {
+        return err(&format!("profile not found: {}", name.trim()));
+    };
+    log::info!(
+        "cmd=switch_profile start from={} to={}",
+        registry.active,
+        profile.id
+    );
+    if profile.id != registry.active {
+        if let Err(error) = persist_now(ctx, state) {
+            log::error!("cmd=switch_profile persist failed err={error}");
+            return err(&format!("storage error: {error:?}"));
+        }
+        let (tasks_file, settings_file) = match load_profile_data(&profile_root(&root, &profile.id))
+        {
+            Ok(data) => data,
+            Err(error) => {
+                log::error!(
+                    "cmd=switch_profile load failed id={} err={error}",
+                    profile.id
+                );
+                return err(&format!("storage error: {error:?}"));
+            }
+        };
+        let previous_bindings = shortcut_bindings(&state.settings());
+        registry.active = profile.id.clone();
+        if let Err(error) = save_registry(&root, &registry) {
+            log::error!("cmd=switch_profile save registry failed err={error}");
+            return err(&format!("storage error: {error:?}"));
+        }
+        let mut settings = settings_file.settings;
+        normalize_shortcuts(&mut settings);
+        let bindings = shortcut_bindings(&settings);
+        if bindings != previous_bindings {
+            ctx.shortcut_unregister_all();
+            let registered =
+                check_conflicts(&bindings).and_then(|()| register_shortcuts(ctx, &bindings));
+            if let Err(register_err) = registered {
+                // Keep the app reachable from the keyboard with the previous bindings.
+                log::warn!(
+                    "cmd=switch_profile shortcut register failed shortcut={} err={register_err}",
+                    settings.shortcut
+                );
+                ctx.shortcut_unregister_all();
+                let _ = register_shortcuts(ctx, &previous_bindings);
+                let previous = state.settings();
+                settings.shortcut = previous.shortcut;
+                settings.shortcut_quick_add = previous.shortcut_quick_add;
+                settings.shortcut_toggle_main = previous.shortcut_toggle_main;
+                settings.shortcut_complete_focus = previous.shortcut_complete_focus;
+            }
+        }
+        state.replace_data(tasks_file, settings);
+        if let Err(error) = persist(ctx, state) {
+            log::error!("cmd=switch_profile persist new profile failed err={error}");
+            return err(&format!("storage error: {error:?}"));
+        }
+    }
+    let payload = state.state_payload();
+    log::info!(
+        "cmd=switch_profile ok id={} tasks={}",
+        profile.id,
+        payload.tasks.len()
+    );
+    ok(payload)
+}
+
+/// Debug-build helper: moves the app clock forward so reminders/repeats/backups that depend on
+/// "now" can be checked without waiting. The scheduler picks the new time up on its next tick.
+fn advance_clock_impl(ctx: &impl CommandCtx, state: &AppState, seconds: i64) -> CommandResult<i64> {
+    if !cfg!(debug_assertions) {
+        return err("advance_clock is only available in debug builds");
+    }
+    if seconds <= 0 {
+        return err("seconds must be positive");
+    }
+    let offset = state.clock().advance(seconds);
+    let now = state.clock().timestamp();
+    log::info!("cmd=advance_clock ok seconds={seconds} offset={offset} now={now}");
+    // Views bucket tasks by "now"; push a refresh so they follow the new time.
+    let payload = state.state_payload();
+    ctx.update_tray_count(&payload.tasks, &payload.settings);
+    ctx.emit_state_updated(payload);
+    ok(now)
+}
+
+fn restore_backup_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    filename: String,
+) -> CommandResult<Vec<Task>> {
+    log::info!("cmd=restore_backup start filename={}", filename);
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    let storage = Storage::new(root);
+    if let Err(error) = storage.ensure_dirs() {
+        log::error!(
+            "cmd=restore_backup ensure_dirs failed filename={} err={error}",
+            filename
+        );
+        return err(&format!("storage error: {error:?}"));
+    }
+    let data = match storage.restore_backup(&filename) {
+        Ok(data) => data,
+        Err(error) => {
+            log::error!(
+                "cmd=restore_backup failed filename={} err={error}",
+                filename
+            );
+            return err(&format!("storage error: {error:?}"));
+        }
+    };
+    log::info!(
+        "cmd=restore_backup loaded filename={} tasks={} projects={}",
+        filename,
+        data.tasks.len(),
+        data.projects.len()
+    );
+    state.replace_projects(data.projects.clone());
+    state.replace_tasks(data.tasks.clone());
+    state.replace_trash(data.trash.clone());
+    state.replace_tags(data.tags.clone());
+    ctx.update_tray_count(&state.shared_tasks(), &state.settings());
+    ctx.emit_state_updated(state.state_payload());
+    log::info!("cmd=restore_backup ok filename={}", filename);
+    ok(data.tasks)
+}
+
+/// What restoring `data` would change, read without touching data.json.
+fn restore_preview(
+    state: &AppState,
+    cmd: &str,
+    data: Result<TasksFile, StorageError>,
+) -> CommandResult<ChangeSet> {
+    let data = match data {
+        Ok(data) => data,
+        Err(error) => {
+            log::error!("cmd={cmd} dry_run failed err={error}");
+            return err(&format!("storage error: {error:?}"));
+        }
+    };
+    log::info!(
+        "cmd={cmd} dry_run tasks={} projects={}",
+        data.tasks.len(),
+        data.projects.len()
+    );
+    preview_changes(state, |scratch| {
+        scratch.replace_projects(data.projects);
+        scratch.replace_tasks(data.tasks);
+        Ok(())
+    })
+}
+
+/// Reads `backups/<filename>` for the merge commands.
+fn read_backup_for_merge(
+    ctx: &impl CommandCtx,
+    cmd: &str,
+    filename: &str,
+) -> Result<TasksFile, String> {
+    let root = ctx
+        .app_data_dir()
+        .map_err(|e| format!("app_data_dir error: {e}"))?;
+    Storage::new(root).read_backup(filename).map_err(|error| {
+        log::error!("cmd={cmd} read failed filename={} err={error}", filename);
+        format!("storage error: {error:?}")
+    })
+}
+
+/// Backup tasks/projects that differ from the current data, for picking what to merge back.
+fn preview_backup_merge_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    filename: String,
+) -> CommandResult<MergePreview> {
+    let backup = match read_backup_for_merge(ctx, "preview_backup_merge", &filename) {
+        Ok(backup) => backup,
+        Err(message) => return err(&message),
+    };
+    let preview = preview_merge(&backup, &state.shared_tasks(), &state.projects());
+    log::info!(
+        "cmd=preview_backup_merge ok filename={} tasks={} projects={}",
+        filename,
+        preview.tasks.len(),
+        preview.projects.len()
+    );
+    ok(preview)
+}
+
+fn plan_backup_merge(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    filename: &str,
+    task_ids: &[String],
+    project_ids: &[String],
+) -> Result<MergePlan, String> {
+    let backup = read_backup_for_merge(ctx, "merge_backup", filename)?;
+    plan_merge(
+        &backup,
+        &state.shared_tasks(),
+        &state.projects(),
+        task_ids,
+        project_ids,
+    )
+}
+
+/// Writes the planned records over (or next to) the current ones; they count as edited now.
+/// A restored task that sits in the trash leaves it, so the id is not in both places.
+fn apply_merge_plan(target: &AppState, plan: &MergePlan, now: i64) {
+    let projects = target.projects();
+    for project in &plan.projects {
+        let mut project = project.clone();
+        project.updated_at = now;
+        if projects.iter().any(|current| current.id == project.id) {
+            target.update_project(project);
+        } else {
+            target.add_project(project);
+        }
+    }
+    let tasks = target.tasks();
+    for task in &plan.tasks {
+        let mut task = task.clone();
+        task.updated_at = now;
+        if tasks.iter().any(|current| current.id == task.id) {
+            target.update_task(task);
+        } else {
+            target.add_task(task);
+        }
+    }
+    let mut trash = target.trash();
+    trash.retain(|entry| !plan.tasks.iter().any(|task| task.id == entry.task.id));
+    target.replace_trash(trash);
+}
+
+fn merge_backup_dry_run_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    filename: String,
+    task_ids: Vec<String>,
+    project_ids: Vec<String>,
+) -> CommandResult<ChangeSet> {
+    let plan = match plan_backup_merge(ctx, state, &filename, &task_ids, &project_ids) {
+        Ok(plan) => plan,
+        Err(message) => return err(&message),
+    };
+    let now = state.clock().timestamp();
+    preview_changes(state, |scratch| {
+        apply_merge_plan(scratch, &plan, now);
+        Ok(())
+    })
+}
+
+/// Restores only the selected tasks/projects from a backup, keeping everything else as it
+/// is. Ids present on both sides keep whichever copy was updated later.
+fn merge_backup_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    filename: String,
+    task_ids: Vec<String>,
+    project_ids: Vec<String>,
+) -> CommandResult<MergePlan> {
+    log::info!(
+        "cmd=merge_backup start filename={} tasks={} projects={}",
+        filename,
+        task_ids.len(),
+        project_ids.len()
+    );
+    let plan = match plan_backup_merge(ctx, state, &filename, &task_ids, &project_ids) {
+        Ok(plan) => plan,
+        Err(message) => {
+            log::warn!(
+                "cmd=merge_backup rejected filename={} err={message}",
+                filename
+            );
+            return err(&message);
+        }
+    };
+    let before = state.tasks_file();
+    apply_merge_plan(state, &plan, state.clock().timestamp());
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd=merge_backup persist failed err={error}");
+        state.replace_projects(before.projects);
+        state.replace_tasks(before.tasks);
+        state.replace_trash(before.trash);
+        return err(&format!("storage error: {error:?}"));
+    }
+    state.record_undo("merge_backup", &before);
+    log::info!(
+        "cmd=merge_backup ok filename={} tasks={} projects={} kept_current={}",
+        filename,
+        plan.tasks.len(),
+        plan.projects.len(),
+        plan.kept_current.len()
+    );
+    ok(plan)
+}
+
+fn restore_backup_dry_run_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    filename: String,
+) -> CommandResult<ChangeSet> {
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    restore_preview(
+        state,
+        "restore_backup",
+        Storage::new(root).read_backup(&filename),
+    )
+}
+
+fn import_backup_dry_run_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    path: String,
+) -> CommandResult<ChangeSet> {
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    restore_preview(
+        state,
+        "import_backup",
+        Storage::new(root).read_external(Path::new(&path)),
+    )
+}
+
+fn import_backup_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    path: String,
+) -> CommandResult<Vec<Task>> {
+    log::info!("cmd=import_backup start path={}", path);
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    let storage = Storage::new(root);
+    if let Err(error) = storage.ensure_dirs() {
+        log::error!(
+            "cmd=import_backup ensure_dirs failed path={} err={error}",
+            path
+        );
+        return err(&format!("storage error: {error:?}"));
+    }
+    let data = match storage.restore_from_path(std::path::Path::new(&path)) {
+        Ok(data) => data,
+        Err(error) => {
+            log::error!("cmd=import_backup failed path={} err={error}", path);
+            return err(&format!("storage error: {error:?}"));
+        }
+    };
+    log::info!(
+        "cmd=import_backup loaded path={} tasks={} projects={}",
+        path,
+        data.tasks.len(),
+        data.projects.len()
+    );
+    state.replace_projects(data.projects.clone());
+    state.replace_tasks(data.tasks.clone());
+    state.replace_trash(data.trash.clone());
+    state.replace_tags(data.tags.clone());
+    ctx.update_tray_count(&state.shared_tasks(), &state.settings());
+    ctx.emit_state_updated(state.state_payload());
+    log::info!("cmd=import_backup ok path={}", path);
+    ok(data.tasks)
+}
+
+/// Adds the tasks/projects of a Todoist CSV or TickTick JSON export to the current data; with
+/// `dry_run` only reports what would be added.
+fn import_external_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    path: String,
+    dry_run: bool,
+) -> CommandResult<ExternalImportReport> {
+    log::info!(
+        "cmd=import_external start path={} dry_run={}",
+        path,
+        dry_run
+    );
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(error) => {
+            log::error!("cmd=import_external read failed path={} err={error}", path);
+            return err(&format!("read error: {error}"));
+        }
+    };
+    let import = match parse_external(
+        Path::new(&path),
+        &content,
+        &state.projects(),
+        state.clock().now_local(),
+        &state.settings(),
+    ) {
+        Ok(import) => import,
+        Err(message) => {
+            log::warn!("cmd=import_external rejected path={} err={message}", path);
+            return err(&message);
+        }
+    };
+    add_external_import(ctx, state, "import_external", &path, import, dry_run)
+}
+
+/// Turns `- [ ]` / `- [x]` lists into tasks, nested items into steps; `source` is a Markdown
+/// file path or the pasted text itself.
+fn import_markdown_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    source: String,
+    headings: MarkdownHeadings,
+    dry_run: bool,
+) -> CommandResult<ExternalImportReport> {
+    let path = Path::new(source.trim());
+    let is_file = !source.contains('\n') && path.is_file();
+    let label = if is_file {
+        path.display().to_string()
+    } else {
+        format!("<text {} bytes>", source.len())
+    };
+    log::info!(
+        "cmd=import_markdown start source={} headings={:?} dry_run={}",
+        label,
+        headings,
+        dry_run
+    );
+    let text = if is_file {
+        match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) => {
+                log::error!(
+                    "cmd=import_markdown read failed source={} err={error}",
+                    label
+                );
+                return err(&format!("read error: {error}"));
+            }
+        }
+    } else {
+        source
+    };
+    let import = parse_markdown(
+        &text,
+        headings,
+        &state.projects(),
+        state.clock().now_local(),
+    );
+    if import.tasks.is_empty() {
+        log::warn!(
+            "cmd=import_markdown rejected source={} no checkboxes",
+            label
+        );
+        return err("no checkbox items found: expected lines like \"- [ ] task\"");
+    }
+    add_external_import(ctx, state, "import_markdown", &label, import, dry_run)
+}
+
+/// Appends a parsed import (skipping task ids already present), or with `dry_run` only reports
+/// what it would add.
+fn add_external_import(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    cmd: &str,
+    source: &str,
+    import: ExternalImport,
+    dry_run: bool,
+) -> CommandResult<ExternalImportReport> {
+    let existing: std::collections::HashSet<String> =
+        state.tasks().into_iter().map(|task| task.id).collect();
+    let mut warnings = import.warnings;
+    let quadrants = state.settings().quadrant_config;
+    let now = state.clock().now_local();
+    let tasks: Vec<Task> = import
+        .tasks
+        .into_iter()
+        .filter(|task| {
+            let fresh = !existing.contains(&task.id);
+            if !fresh {
+                warnings.push(format!("{}: already imported, skipped", task.title));
+            }
+            fresh
+        })
+        .map(|mut task| {
+            task.refresh_progress();
+            apply_quadrant(&mut task, &quadrants, now);
+            task
+        })
+        .collect();
+    let projects = import.projects;
+    let apply = |target: &AppState| {
+        for project in &projects {
+            target.add_project(project.clone());
+        }
+        for task in &tasks {
+            target.add_task(task.clone());
+        }
+    };
+    let format = import.format;
+    let report = |changes: ChangeSet, warnings: Vec<String>| ExternalImportReport {
+        format,
+        dry_run,
+        changes,
+        warnings,
+    };
+
+    if dry_run {
+        let result = preview_changes(state, |scratch| {
+            apply(scratch);
+            Ok(())
+        });
+        log::info!(
+            "cmd={cmd} dry_run tasks={} projects={} warnings={}",
+            tasks.len(),
+            projects.len(),
+            warnings.len()
+        );
+        return match result.data {
+            Some(changes) => ok(report(changes, warnings)),
+            None => err(result.error.as_deref().unwrap_or("preview failed")),
+        };
+    }
+
+    let before = state.snapshot();
+    apply(state);
+    if let Err(error) = persist(ctx, state) {
+        log::error!("cmd={cmd} persist failed source={} err={error}", source);
+        return err(&format!("storage error: {error:?}"));
+    }
+    log::info!(
+        "cmd={cmd} ok source={} tasks={} projects={} warnings={}",
+        source,
+        tasks.len(),
+        projects.len(),
+        warnings.len()
+    );
+    ok(report(diff_snapshots(&before, &state.snapshot()), warnings))
+}
+
+fn export_default_path(root: &Path, ext: &str) -> PathBuf {
+    let exports_dir = root.join("exports");
+    let stamp = Local::now().format("%Y%m%d-%H%M%S").to_string();
+    exports_dir.join(format!("mustdo-{stamp}.{ext}"))
+}
+
+#[cfg_attr(coverage, inline(never))]
+fn write_atomic_bytes(path: &Path, bytes: &[u8]) -> Result<(), StorageError> {
+    let tmp = path.with_extension("tmp");
+    fs::create_dir_all(
+        path.parent()
+            .ok_or_else(|| StorageError::Io(std::io::Error::other("invalid export path")))?,
+    )?;
+    fs::write(&tmp, bytes)?;
+    fs::rename(tmp, path)?;
+    Ok(())
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct ExportContent {
+    pub format: ExportFormat,
+    pub mime_type: String,
+    pub content: String,
+}
+
+fn render_tasks_json(
+    ctx: &dyn CommandCtx,
+    data: &TasksFile,
+    quadrants: &[QuadrantDefinition],
+) -> Result<Vec<u8>, serde_json::Error> {
+    // Same layout as data.json (so it can be imported back) plus the quadrant definitions.
+    #[derive(serde::Serialize)]
+    struct JsonExport<'a> {
+        #[serde(flatten)]
+        data: &'a TasksFile,
+        quadrants: &'a [QuadrantDefinition],
+    }
+
+    struct ForcedJsonError;
+
+    impl serde::Serialize for ForcedJsonError {
+        fn serialize<S>(&self, _serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            Err(<S::Error as serde::ser::Error>::custom(
+                "forced json serialization error",
+            ))
+        }
+    }
+
+    if ctx.force_json_serialize_error() {
+        // `TasksFile` is expected to be always serializable. This branch exists solely for tests.
+        serde_json::to_vec_pretty(&ForcedJsonError)
+    } else {
+        serde_json::to_vec_pretty(&JsonExport { data, quadrants })
+    }
+}
+
+fn export_tasks_json_impl(
+    ctx: &dyn CommandCtx,
+    state: &AppState,
+    filter: ExportFilter,
+) -> CommandResult<String> {
+    log::info!("cmd=export_tasks_json start filter={filter:?}");
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+
+    let path = export_default_path(&root, "json");
+    let data = filter.data(state.tasks_file());
+    let quadrants = state.settings().quadrant_config.quadrants;
+    let json = match render_tasks_json(ctx, &data, &quadrants) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            log::error!("cmd=export_tasks_json json serialize failed err={e}");
+            return err(&format!("json error: {e}"));
+        }
+    };
+
+    if let Err(error) = write_atomic_bytes(&path, &json) {
+        log::error!(
+            "cmd=export_tasks_json write failed path={} err={error}",
+            path.display()
+        );
+        return err(&format!("export error: {error:?}"));
+    }
+
+    log::info!(
+        "cmd=export_tasks_json ok path={} tasks={} projects={}",
+        path.display(),
+        data.tasks.len(),
+        data.projects.len()
+    );
+    ok(path.to_string_lossy().to_string())
+}
+
+fn csv_escape(value: &str) -> String {
+    // Minimal CSV escaping: wrap in quotes and double any existing quotes.
+    let escaped = value.replace('"', "\"\"");
+    format!("\"{escaped}\"")
+}
+
+fn render_tasks_csv(
+    tasks: Vec<Task>,
+    projects: &[Project],
+    tag_defs: &[Tag],
+    settings: &Settings,
+    now: Timestamp,
+) -> String {
+    let quadrant_config = &settings.quadrant_config;
+    let date_format = settings.date_format;
+    let mut out = String::new();
+    out.push_str(
+        "id,project_id,title,due_at,start_at,important,completed,quadrant,quadrant_name,tags,notes,steps,comments,color,emoji,tracked_minutes,time_entries,project_color,project_icon,tag_paths,tag_colors\n",
+    );
+    for task in tasks {
+        let tags = task.tags.join(";");
+        // Aligned with `tags`: one path and one color (possibly empty) per tag.
+        let details = tag_details(&task.tags, tag_defs);
+        let tag_paths = details
+            .iter()
+            .map(|detail| detail.path.as_str())
+            .collect::<Vec<_>>()
+            .join(";");
+        let tag_colors = details
+            .iter()
+            .map(|detail| detail.color.as_deref().unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join(";");
+        let project = projects.iter().find(|p| p.id == task.project_id);
+        let tracked_minutes = tracked_seconds(&task, now) / 60;
+        let notes = task.notes.unwrap_or_default().replace("\r\n", "\n");
+        let steps = task
+            .steps
+            .iter()
+            .map(|s| {
+                if s.completed {
+                    format!("[x] {}", s.title)
+                } else {
+                    format!("[ ] {}", s.title)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" | ");
+        let comments = task
+            .comments
+            .iter()
+            .map(|c| {
+                format!(
+                    "{} {}",
+                    format_datetime(c.created_at, date_format),
+                    single_line(c)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" | ");
+        let time_entries = task
+            .time_entries
+            .iter()
+            .map(|entry| {
+                let end = entry
+                    .ended_at
+                    .map(|at| format_datetime(at, date_format))
+                    .unwrap_or_default();
+                format!("{} - {end}", format_datetime(entry.started_at, date_format))
+            })
+            .collect::<Vec<_>>()
+            .join(" | ");
+
+        out.push_str(&csv_escape(&task.id));
+        out.push(',');
+        out.push_str(&csv_escape(&task.project_id));
+        out.push(',');
+        out.push_str(&csv_escape(&task.title));
+        out.push(',');
+        if let Some(due_at) = task.due_at {
+            out.push_str(&due_at.to_string());
+        }
+        out.push(',');
+        if let Some(start_at) = task.start_at {
+            out.push_str(&start_at.to_string());
+        }
+        out.push(',');
+        out.push_str(if task.important { "true" } else { "false" });
+        out.push(',');
+        out.push_str(if task.completed { "true" } else { "false" });
+        out.push(',');
+        out.push_str(&task.quadrant.to_string());
+        out.push(',');
+        out.push_str(&csv_escape(&quadrant_name(quadrant_config, task.quadrant)));
+        out.push(',');
+        out.push_str(&csv_escape(&tags));
+        out.push(',');
+        out.push_str(&csv_escape(&notes));
+        out.push(',');
+        out.push_str(&csv_escape(&steps));
+        out.push(',');
+        out.push_str(&csv_escape(&comments));
+        out.push(',');
+        out.push_str(&csv_escape(task.color.as_deref().unwrap_or_default()));
+        out.push(',');
+        out.push_str(&csv_escape(task.emoji.as_deref().unwrap_or_default()));
+        out.push(',');
+        out.push_str(&tracked_minutes.to_string());
+        out.push(',');
+        out.push_str(&csv_escape(&time_entries));
+        out.push(',');
+        out.push_str(&csv_escape(
+            project.and_then(|p| p.color.as_deref()).unwrap_or_default(),
+        ));
+        out.push(',');
+        out.push_str(&csv_escape(
+            project.and_then(|p| p.icon.as_deref()).unwrap_or_default(),
+        ));
+        out.push(',');
+        out.push_str(&csv_escape(&tag_paths));
+        out.push(',');
+        out.push_str(&csv_escape(&tag_colors));
+        out.push('\n');
+    }
+    out
+}
+
+fn export_tasks_csv_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    filter: ExportFilter,
+) -> CommandResult<String> {
+    log::info!("cmd=export_tasks_csv start filter={filter:?}");
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+
+    let path = export_default_path(&root, "csv");
+    let tasks = filter.tasks(state.tasks());
+    let tasks_len = tasks.len();
+    let out = render_tasks_csv(
+        tasks,
+        &state.projects(),
+        &state.tags(),
+        &state.settings(),
+        state.clock().timestamp(),
+    );
+
+    if let Err(error) = write_atomic_bytes(&path, out.as_bytes()) {
+        log::error!(
+            "cmd=export_tasks_csv write failed path={} err={error}",
+            path.display()
+        );
+        return err(&format!("export error: {error:?}"));
+    }
+
+    log::info!(
+        "cmd=export_tasks_csv ok path={} tasks={}",
+        path.display(),
+        tasks_len
+    );
+    ok(path.to_string_lossy().to_string())
+}
+
+/// Markdown grouped by Overdue/Due today/Future/Completed; also returns the section sizes.
+/// Headings and labels follow `settings.language`. `with_ids` appends a task marker to each
+/// checkbox line so the file can be synced back.
+fn render_tasks_markdown(
+    state: &AppState,
+    filter: &ExportFilter,
+    with_ids: bool,
+) -> (String, [usize; 5]) {
+    let settings = state.settings();
+    let locale = locale_for(&settings);
+    let quadrant_config = settings.quadrant_config;
+    let date_format = settings.date_format;
+    let grace_minutes = settings.overdue_grace_minutes;
+    let tag_defs = state.tags();
+    let text = |key| tr(locale, key);
+    let now = state.clock().now_local();
+    let now_ts = now.timestamp();
+    let today = now.date_naive();
+
+    let mut overdue: Vec<Task> = Vec::new();
+    let mut today_list: Vec<Task> = Vec::new();
+    let mut future: Vec<Task> = Vec::new();
+    let mut someday: Vec<Task> = Vec::new();
+    let mut done: Vec<Task> = Vec::new();
+
+    for task in filter.tasks(state.tasks()) {
+        if task.completed {
+            done.push(task);
+            continue;
+        }
+        if task.someday {
+            someday.push(task);
+            continue;
+        }
+        if task.is_overdue(now_ts, grace_minutes) {
+            overdue.push(task);
+            continue;
+        }
+        // Tasks still inside the grace period stay in "Today" even if due before midnight.
+        // Undated tasks fall through to "Future".
+        let due = task
+            .due_at
+            .and_then(|due_at| Local.timestamp_opt(due_at, 0).single());
+        if let Some(due_time) = due {
+            if due_time.date_naive() <= today {
+                today_list.push(task);
+                continue;
+            }
+        }
+        future.push(task);
+    }
+
+    overdue.sort_by_key(Task::due_sort_key);
+    today_list.sort_by_key(Task::due_sort_key);
+    future.sort_by_key(Task::due_sort_key);
+    someday.sort_by_key(|t| t.sort_order);
+    done.sort_by_key(Task::due_sort_key);
+
+    let fmt_due = |ts: i64| format_datetime(ts, date_format);
+
+    let mut out = String::new();
+    out.push_str(&format!("# {}\n\n", text(Text::ExportTitle)));
+    out.push_str(&format!(
+        "{}: {}\n\n",
+        text(Text::GeneratedAt),
+        format_datetime_seconds(now, date_format)
+    ));
+
+    let mut write_section = |title: Text, tasks: &[Task], checked: bool| {
+        out.push_str(&format!("## {}\n\n", text(title)));
+        if tasks.is_empty() {
+            out.push_str(&format!("_{}_\n\n", text(Text::Empty)));
+            return;
+        }
+        for task in tasks {
+            let box_mark = if checked { "x" } else { " " };
+            let title = flair_title(task);
+            out.push_str(&format!("- [{box_mark}] {title}"));
+            if let Some(due_at) = task.due_at {
+                out.push_str(&format!(" ({}: {})", text(Text::Due), fmt_due(due_at)));
+            }
+            if with_ids {
+                out.push(' ');
+                out.push_str(&task_marker(&task.id));
+            }
+            out.push('\n');
+            out.push_str(&format!(
+                "  - {}: {}\n",
+                text(Text::Quadrant),
+                quadrant_name(&quadrant_config, task.quadrant)
+            ));
+            if let Some(start_at) = task.start_at {
+                out.push_str(&format!(
+                    "  - {}: {}\n",
+                    text(Text::Starts),
+                    fmt_due(start_at)
+                ));
+            }
+            if let Some(color) = &task.color {
+                out.push_str(&format!("  - {}: {color}\n", text(Text::Color)));
+            }
+            if !task.tags.is_empty() {
+                // Nested tags are written as paths (`#work/client`).
+                let tags = tag_details(&task.tags, &tag_defs)
+                    .iter()
+                    .map(|detail| format!("#{}", detail.path))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                out.push_str(&format!("  - {}: {tags}\n", text(Text::Tags)));
+            }
+            if let Some(notes) = &task.notes {
+                let notes = notes.replace("\r\n", "\n").replace('\n', " ");
+                if !notes.trim().is_empty() {
+                    out.push_str(&format!("  - {}: {notes}\n", text(Text::Notes)));
+                }
+            }
+            if !task.steps.is_empty() {
+                out.push_str(&format!("  - {}:\n", text(Text::Steps)));
+                for step in &task.steps {
+                    let s_mark = if step.completed { "x" } else { " " };
+                    out.push_str(&format!("    - [{s_mark}] {}\n", step.title));
+                }
+            }
+            if !task.comments.is_empty() {
+                out.push_str(&format!("  - {}:\n", text(Text::Comments)));
+                for comment in &task.comments {
+                    out.push_str(&format!(
+                        "    - {}: {}\n",
+                        fmt_due(comment.created_at),
+                        single_line(comment)
+                    ));
+                }
+            }
+        }
+        out.push('\n');
+    };
+
+    write_section(Text::Overdue, &overdue, false);
+    write_section(Text::DueToday, &today_list, false);
+    write_section(Text::Future, &future, false);
+    write_section(Text::Someday, &someday, false);
+    write_section(Text::Completed, &done, true);
+
+    let counts = [
+        overdue.len(),
+        today_list.len(),
+        future.len(),
+        someday.len(),
+        done.len(),
+    ];
+    (out, counts)
+}
+
+fn export_tasks_markdown_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    filter: ExportFilter,
+) -> CommandResult<String> {
+    log::info!("cmd=export_tasks_markdown start filter={filter:?}");
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+
+    let path = export_default_path(&root, "md");
+    let (out, [overdue, today, future, someday, done]) =
+        render_tasks_markdown(state, &filter, false);
+
+    if let Err(error) = write_atomic_bytes(&path, out.as_bytes()) {
+        log::error!(
+            "cmd=export_tasks_markdown write failed path={} err={error}",
+            path.display()
+        );
+        return err(&format!("export error: {error:?}"));
+    }
+
+    log::info!(
+        "cmd=export_tasks_markdown ok path={} overdue={overdue} today={today} future={future} someday={someday} done={done}",
+        path.display(),
+    );
+    ok(path.to_string_lossy().to_string())
+}
+
+fn render_tasks_ics(state: &AppState, filter: &ExportFilter) -> String {
+    crate::ics::tasks_to_calendar(
+        &filter.tasks(state.tasks()),
+        &state.settings().reminder_lead_minutes,
+        state.clock().timestamp(),
+    )
+}
+
+/// Writes every (matching) task as a VTODO into `exports/mustdo-<time>.ics` for calendar apps.
+fn export_tasks_ics_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    filter: ExportFilter,
+) -> CommandResult<String> {
+    log::info!("cmd=export_tasks_ics start filter={filter:?}");
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+
+    let path = export_default_path(&root, "ics");
+    let out = render_tasks_ics(state, &filter);
+
+    if let Err(error) = write_atomic_bytes(&path, out.as_bytes()) {
+        log::error!(
+            "cmd=export_tasks_ics write failed path={} err={error}",
+            path.display()
+        );
+        return err(&format!("export error: {error:?}"));
+    }
+
+    log::info!(
+        "cmd=export_tasks_ics ok path={} todos={}",
+        path.display(),
+        out.matches("BEGIN:VTODO").count()
+    );
+    ok(path.to_string_lossy().to_string())
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct YearReviewExport {
+    pub markdown_path: String,
+    pub html_path: String,
+    pub review: YearReview,
+}
+
+/// Writes `exports/mustdo-year-review-<year>.md` and `.html`, replacing earlier runs for the
+/// same year, and returns the numbers so the UI can show them without opening a file.
+fn export_year_review_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    year: i32,
+) -> CommandResult<YearReviewExport> {
+    log::info!("cmd=export_year_review start year={year}");
+    if !(1970..=9999).contains(&year) {
+        return err("invalid year");
+    }
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    let tasks = match history_tasks(ctx, state, year) {
+        Ok(tasks) => tasks,
+        Err(message) => return err(&message),
+    };
+    let snapshot = state.snapshot();
+    let projects = localized_projects(&snapshot.projects, locale_for(&snapshot.settings));
+    let review = year_review(&tasks, &projects, year, snapshot.settings.week_starts_on);
+    let base = root
+        .join("exports")
+        .join(format!("mustdo-year-review-{year}"));
+    let markdown_path = base.with_extension("md");
+    let html_path = base.with_extension("html");
+    let written = write_atomic_bytes(&markdown_path, render_markdown(&review).as_bytes())
+        .and_then(|_| write_atomic_bytes(&html_path, render_html(&review).as_bytes()));
+    if let Err(error) = written {
+        log::error!("cmd=export_year_review write failed err={error}");
+        return err(&format!("export error: {error:?}"));
+    }
+    log::info!(
+        "cmd=export_year_review ok year={year} completed={} path={}",
+        review.total_completed,
+        markdown_path.display()
+    );
+    ok(YearReviewExport {
+        markdown_path: markdown_path.to_string_lossy().to_string(),
+        html_path: html_path.to_string_lossy().to_string(),
+        review,
+    })
+}
+
+/// Writes `exports/mustdo-sync.md` (the Markdown export plus task markers) and its baseline.
+/// Re-exporting replaces any pending edits in the file.
+fn export_markdown_sync_impl(ctx: &impl CommandCtx, state: &AppState) -> CommandResult<String> {
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    let (out, _) = render_tasks_markdown(state, &ExportFilter::default(), true);
+    let path = sync_file_path(&root);
+    // Baseline first: the watcher must never see a fresh file next to a stale baseline.
+    let written = write_atomic_bytes(&baseline_path(&root), out.as_bytes())
+        .and_then(|_| write_atomic_bytes(&path, out.as_bytes()));
+    if let Err(error) = written {
+        log::error!("cmd=export_markdown_sync write failed err={error}");
+        return err(&format!("export error: {error:?}"));
+    }
+    log::info!("cmd=export_markdown_sync ok path={}", path.display());
+    ok(path.to_string_lossy().to_string())
+}
+
+/// Writes tasks into per-day notes under `obsidian_daily_folder` of the vault at `vault_path`,
+/// touching only the managed block in each note. The vault is remembered in settings.
+fn export_to_obsidian_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+    vault_path: String,
+) -> CommandResult<ObsidianExportReport> {
+    let vault = PathBuf::from(vault_path.trim());
+    log::info!("cmd=export_to_obsidian start vault={}", vault.display());
+    if !vault.is_absolute() || !vault.is_dir() {
+        log::warn!("cmd=export_to_obsidian invalid vault={}", vault.display());
+        return err("vault must be an existing absolute directory");
+    }
+    let mut settings = state.settings();
+    let folder = PathBuf::from(settings.obsidian_daily_folder.trim());
+    if folder
+        .components()
+        .any(|part| !matches!(part, Component::Normal(_)))
+    {
+        return err("daily notes folder must be a relative path inside the vault");
+    }
+    let tasks = state.tasks();
+    let report = match export_daily_notes(&vault.join(folder), &tasks, state.clock().now_local()) {
+        Ok(report) => report,
+        Err(error) => {
+            log::error!("cmd=export_to_obsidian failed err={error}");
+            return err(&format!("export error: {error:?}"));
+        }
+    };
+    let vault = vault.display().to_string();
+    if settings.obsidian_vault_path != vault {
+        settings.obsidian_vault_path = vault;
+        state.update_settings(settings);
+        if let Err(error) = persist(ctx, state) {
+            log::error!("cmd=export_to_obsidian persist failed err={error}");
+            return err(&format!("storage error: {error:?}"));
+        }
+    }
+    log::info!(
+        "cmd=export_to_obsidian ok written={} cleared={} tasks={}",
+        report.written.len(),
+        report.cleared.len(),
+        report.tasks
+    );
+    ok(report)
+}
+
+fn read_sync_file(path: &Path, missing: &str) -> Result<String, String> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(content),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Err(missing.to_string()),
+        Err(error) => Err(format!("storage error: {error}")),
+    }
+}
+
+/// Applies checkbox edits made to `exports/mustdo-sync.md` since the last export or sync and
+/// reports the edits it could not apply. The edited file becomes the new baseline.
+fn sync_markdown_export_impl(
+    ctx: &impl CommandCtx,
+    state: &AppState,
+) -> CommandResult<MarkdownSyncReport> {
+    let root = match ctx.app_data_dir() {
+        Ok(path) => path,
+        Err(e) => return err(&format!("app_data_dir error: {e}")),
+    };
+    let files =
+        read_sync_file(&sync_file_path(&root), "markdown sync file not found").and_then(|edited| {
+            read_sync_file(
+                &baseline_path(&root),
+                "markdown sync baseline missing; export again",
+            )
+            .map(|baseline| (edited, baseline))
+        });
+    let (edited, baseline) = match files {
+        Ok(files) => files,
+        Err(message) => {
+            log::warn!("cmd=sync_markdown_export read failed err={message}");
+            return err(&message);
+        }
+    };
+    if edited == baseline {
+        return ok(MarkdownSyncReport::default());
+    }
+
+    let report = plan_sync(&baseline, &edited, &state.shared_tasks());
+    for task_id in &report.completed {
+        let result = complete_task_impl(ctx, state, task_id.clone());
+        if !result.ok {
+            // Baseline untouched: the next sync retries, and tasks completed so far are no-ops.
+            let message = result.error.unwrap_or_default();
+            log::error!("cmd=sync_markdown_export complete failed id={task_id} err={message}");
+            return err(&message);
+        }
+    }
+    if let Err(error) = write_atomic_bytes(&baseline_path(&root), edited.as_bytes()) {
+        log::error!("cmd=sync_markdown_export baseline write failed err={error}");
+        return err(&format!("storage error: {error:?}"));
+    }
+    log::info!(
+        "cmd=sync_markdown_export ok completed={} conflicts={}",
+        report.completed.len(),
+        report.conflicts.len()
+    );
+    ok(report)
+}
+
+/// Scheduler hook for `markdown_sync_enabled`: syncs and tells the UI what happened.
+#[cfg(all(feature = "app", not(test)))]
+pub fn run_markdown_sync(app: &AppHandle, state: &AppState) {
+    let ctx = TauriCommandCtx { app };
+    let result = sync_markdown_export_impl(&ctx, state);
+    match result.data {
+        Some(report) if !report.is_empty() => {
+            if let Err(error) = app.emit(EVENT_MARKDOWN_SYNC, report) {
+                log::warn!("emit markdown_sync failed: {error}");
+            }
+        }
+        Some(_) => {}
+        None => log::warn!(
+            "scheduler: markdown sync failed err={}",
+            result.error.unwrap_or_default()
+        ),
+    }
+}
+
+/// Same content as the file exporters, returned inline so automations need no shared filesystem.
+fn export_tasks_content_impl(
+    ctx: &dyn CommandCtx,
+    state: &AppState,
+    format: ExportFormat,
+    filter: ExportFilter,
+) -> CommandResult<ExportContent> {
+    let content = match format {
+        ExportFormat::Json => {
+            let data = filter.data(state.tasks_file());
+            let quadrants = state.settings().quadrant_config.quadrants;
+            match render_tasks_json(ctx, &data, &quadrants) {
+                Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+                Err(e) => {
+                    log::error!("cmd=export_tasks_content json serialize failed err={e}");
+                    return err(&format!("json error: {e}"));
+                }
+            }
+        }
+        ExportFormat::Csv => render_tasks_csv(
+            filter.tasks(state.tasks()),
+            &state.projects(),
+            &state.tags(),
+            &state.settings(),
+            state.clock().timestamp(),
+        ),
+        ExportFormat::Markdown => render_tasks_markdown(state, &filter, false).0,
+        ExportFormat::Ics => render_tasks_ics(state, &filter),
+    };
+    log::info!(
+        "cmd=export_tasks_content ok format={format:?} filter={filter:?} bytes={}",
+        content.len()
+    );
+    ok(ExportContent {
+        format,
+        mime_type: format.mime_type().to_string(),
+        content,
+    })
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn set_smtp_password(app: AppHandle, password: String) -> CommandResult<bool> {
+    let _span = LogSpan::command("set_smtp_password");
+    let ctx = TauriCommandCtx { app: &app };
+    set_smtp_password_impl(&ctx, password)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn set_mqtt_password(app: AppHandle, password: String) -> CommandResult<bool> {
+    let _span = LogSpan::command("set_mqtt_password");
+    let ctx = TauriCommandCtx { app: &app };
+    set_mqtt_password_impl(&ctx, password)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn set_secret(
+    app: AppHandle,
+    state: State<AppState>,
+    name: String,
+    value: String,
+) -> CommandResult<Settings> {
+    let _span = LogSpan::command("set_secret");
+    let ctx = TauriCommandCtx { app: &app };
+    set_secret_impl(&ctx, state.inner(), name, value)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn clear_secret(
+    app: AppHandle,
+    state: State<AppState>,
+    name: String,
+) -> CommandResult<Settings> {
+    let _span = LogSpan::command("clear_secret");
+    let ctx = TauriCommandCtx { app: &app };
+    clear_secret_impl(&ctx, state.inner(), name)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn set_caldav_password(app: AppHandle, password: String) -> CommandResult<bool> {
+    let _span = LogSpan::command("set_caldav_password");
+    let ctx = TauriCommandCtx { app: &app };
+    set_caldav_password_impl(&ctx, password)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub async fn sync_now(app: AppHandle) -> CommandResult<SyncReport> {
+    let state = app.state::<AppState>().inner().clone();
+    run_caldav_sync(&app, &state).await
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn start_focus_session(
+    state: State<AppState>,
+    task_id: String,
+    minutes: Option<u32>,
+) -> CommandResult<FocusStatus> {
+    let _span = LogSpan::command("start_focus_session");
+    start_focus_session_impl(state.inner(), task_id, minutes)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn pause_focus(state: State<AppState>) -> CommandResult<FocusStatus> {
+    let _span = LogSpan::command("pause_focus");
+    pause_focus_impl(state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn resume_focus(state: State<AppState>) -> CommandResult<FocusStatus> {
+    let _span = LogSpan::command("resume_focus");
+    resume_focus_impl(state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn stop_focus(app: AppHandle, state: State<AppState>) -> CommandResult<Option<FocusSession>> {
+    let _span = LogSpan::command("stop_focus");
+    let ctx = TauriCommandCtx { app: &app };
+    stop_focus_impl(&ctx, state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn get_focus_status(state: State<AppState>) -> CommandResult<Option<FocusStatus>> {
+    let _span = LogSpan::command("get_focus_status");
+    get_focus_status_impl(state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn list_focus_sessions(
+    app: AppHandle,
+    task_id: Option<String>,
+    limit: Option<usize>,
+) -> CommandResult<Vec<FocusSession>> {
+    let _span = LogSpan::command("list_focus_sessions");
+    let ctx = TauriCommandCtx { app: &app };
+    list_focus_sessions_impl(&ctx, task_id, limit)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn get_note_history(app: AppHandle, task_id: String) -> CommandResult<Vec<NoteVersion>> {
+    let _span = LogSpan::command("get_note_history");
+    let ctx = TauriCommandCtx { app: &app };
+    get_note_history_impl(&ctx, task_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn restore_note_version(
+    app: AppHandle,
+    state: State<AppState>,
+    task_id: String,
+    version: u32,
+) -> CommandResult<Task> {
+    let _span = LogSpan::command("restore_note_version");
+    let ctx = TauriCommandCtx { app: &app };
+    restore_note_version_impl(&ctx, state.inner(), task_id, version)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn get_task_activity(
+    app: AppHandle,
+    task_id: String,
+    limit: Option<usize>,
+) -> CommandResult<Vec<ActivityEntry>> {
+    let _span = LogSpan::command("get_task_activity");
+    let ctx = TauriCommandCtx { app: &app };
+    get_task_activity_impl(&ctx, task_id, limit)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn get_recent_activity(
+    app: AppHandle,
+    limit: Option<usize>,
+) -> CommandResult<Vec<ActivityEntry>> {
+    let _span = LogSpan::command("get_recent_activity");
+    let ctx = TauriCommandCtx { app: &app };
+    get_recent_activity_impl(&ctx, limit)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn list_carry_over_log(
+    app: AppHandle,
+    limit: Option<usize>,
+) -> CommandResult<Vec<CarryOverEntry>> {
+    let _span = LogSpan::command("list_carry_over_log");
+    let ctx = TauriCommandCtx { app: &app };
+    list_carry_over_log_impl(&ctx, limit)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn list_sounds(app: AppHandle) -> CommandResult<Vec<SoundEntry>> {
+    let _span = LogSpan::command("list_sounds");
+    let ctx = TauriCommandCtx { app: &app };
+    list_sounds_impl(&ctx)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn preview_sound(app: AppHandle, sound_id: String) -> CommandResult<SoundClip> {
+    let _span = LogSpan::command("preview_sound");
+    let ctx = TauriCommandCtx { app: &app };
+    preview_sound_impl(&ctx, sound_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn list_reminder_audit(
+    app: AppHandle,
+    limit: Option<usize>,
+) -> CommandResult<Vec<ReminderAuditEntry>> {
+    let _span = LogSpan::command("list_reminder_audit");
+    let ctx = TauriCommandCtx { app: &app };
+    list_reminder_audit_impl(&ctx, limit)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn list_email_deliveries(
+    app: AppHandle,
+    limit: Option<usize>,
+) -> CommandResult<Vec<DeliveryLogEntry>> {
+    let _span = LogSpan::command("list_email_deliveries");
+    let ctx = TauriCommandCtx { app: &app };
+    list_email_deliveries_impl(&ctx, limit)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub async fn send_test_email(app: AppHandle) -> CommandResult<bool> {
+    let prepared = {
+        let ctx = TauriCommandCtx { app: &app };
+        let state = app.state::<AppState>();
+        prepare_test_email_impl(&ctx, state.inner())
+    };
+    let (root, config, message) = match prepared {
+        Ok(prepared) => prepared,
+        Err(message) => {
+            log::warn!("cmd=send_test_email invalid config err={message}");
+            return err(&message);
+        }
+    };
+    log::info!("cmd=send_test_email start host={}", config.host);
+    let result = send_email(&config, &message).await;
+    let entry = DeliveryLogEntry {
+        at: app.state::<AppState>().clock().timestamp(),
+        kind: DeliveryKind::Test,
+        to: message.to.clone(),
+        subject: message.subject.clone(),
+        task_ids: Vec::new(),
+        ok: result.is_ok(),
+        error: result.as_ref().err().cloned(),
+    };
+    if let Err(error) = append_delivery_log(&root, &entry) {
+        log::warn!("cmd=send_test_email failed to write delivery log err={error}");
+    }
+    match result {
+        Ok(()) => ok(true),
+        Err(message) => {
+            log::warn!("cmd=send_test_email failed err={message}");
+            err(&message)
+        }
+    }
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn list_backups(app: AppHandle) -> CommandResult<Vec<BackupEntry>> {
+    let _span = LogSpan::command("list_backups");
+    let ctx = TauriCommandCtx { app: &app };
+    list_backups_impl(&ctx)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn delete_backup(app: AppHandle, filename: String) -> CommandResult<bool> {
+    let _span = LogSpan::command("delete_backup");
+    let ctx = TauriCommandCtx { app: &app };
+    delete_backup_impl(&ctx, filename)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn create_backup(app: AppHandle, state: State<AppState>) -> CommandResult<bool> {
+    let _span = LogSpan::command("create_backup");
+    let ctx = TauriCommandCtx { app: &app };
+    create_backup_impl(&ctx, state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn estimate_backup_archive(app: AppHandle) -> CommandResult<ArchiveEstimate> {
+    let _span = LogSpan::command("estimate_backup_archive");
+    let ctx = TauriCommandCtx { app: &app };
+    estimate_backup_archive_impl(&ctx)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn export_all_backups(
+    app: AppHandle,
+    state: State<AppState>,
+    path: String,
+) -> CommandResult<String> {
+    let _span = LogSpan::command("export_all_backups");
+    let ctx = TauriCommandCtx { app: &app };
+    export_all_backups_impl(&ctx, state.inner(), path)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn set_data_directory(
+    app: AppHandle,
+    state: State<AppState>,
+    path: String,
+) -> CommandResult<String> {
+    let _span = LogSpan::command("set_data_directory");
+    if crate::data_dir::env_override().is_some() {
+        return err("data directory is fixed by the MUSTDO_DATA_DIR environment variable");
+    }
+    if crate::storage::memory_backend_enabled() {
+        return err("data directory cannot be changed in guest mode");
+    }
+    let ctx = TauriCommandCtx { app: &app };
+    set_data_directory_impl(&ctx, state.inner(), path)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn test_fire_reminder(
+    app: AppHandle,
+    state: State<AppState>,
+    task_id: String,
+) -> CommandResult<Task> {
+    let _span = LogSpan::command("test_fire_reminder");
+    let ctx = TauriCommandCtx { app: &app };
+    test_fire_reminder_impl(&ctx, state.inner(), task_id)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn advance_clock(app: AppHandle, state: State<AppState>, seconds: i64) -> CommandResult<i64> {
+    let _span = LogSpan::command("advance_clock");
+    let ctx = TauriCommandCtx { app: &app };
+    advance_clock_impl(&ctx, state.inner(), seconds)
+}
+
+/// Guest/demo mode: data lives in memory only (see `storage::use_memory_backend`).
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn is_guest_mode() -> CommandResult<bool> {
+    let _span = LogSpan::command("is_guest_mode");
+    ok(crate::storage::memory_backend_enabled())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn list_profiles(app: AppHandle) -> CommandResult<ProfileRegistry> {
+    let _span = LogSpan::command("list_profiles");
+    let ctx = TauriCommandCtx { app: &app };
+    list_profiles_impl(&ctx)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn create_profile(
+    app: AppHandle,
+    state: State<AppState>,
+    name: String,
+) -> CommandResult<Profile> {
+    let _span = LogSpan::command("create_profile");
+    let ctx = TauriCommandCtx { app: &app };
+    create_profile_impl(&ctx, &state, state.inner(), name)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn switch_profile(
+    app: AppHandle,
+    state: State<AppState>,
+    name: String,
+) -> CommandResult<StatePayload> {
+    let _span = LogSpan::command("switch_profile");
+    let ctx = TauriCommandCtx { app: &app };
+    switch_profile_impl(&ctx, state.inner(), name)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn run_maintenance_now(
+    app: AppHandle,
+    state: State<AppState>,
+) -> CommandResult<MaintenanceReport> {
+    let _span = LogSpan::command("run_maintenance_now");
+    let ctx = TauriCommandCtx { app: &app };
+    run_maintenance_now_impl(&ctx, state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn restore_backup(
+    app: AppHandle,
+    state: State<AppState>,
+    filename: String,
+    dry_run: Option<bool>,
+) -> CommandResult<Outcome<Vec<Task>>> {
+    let _span = LogSpan::command("restore_backup");
+    let ctx = TauriCommandCtx { app: &app };
+    with_dry_run(
+        dry_run,
+        || restore_backup_dry_run_impl(&ctx, state.inner(), filename.clone()),
+        || restore_backup_impl(&ctx, state.inner(), filename.clone()),
+    )
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn flush_storage(state: State<AppState>) -> CommandResult<bool> {
+    let _span = LogSpan::command("flush_storage");
+    flush_storage_impl(state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn get_write_status(state: State<AppState>) -> CommandResult<WriteStatus> {
+    let _span = LogSpan::command("get_write_status");
+    get_write_status_impl(state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn preview_backup_merge(
+    app: AppHandle,
+    state: State<AppState>,
+    filename: String,
+) -> CommandResult<MergePreview> {
+    let _span = LogSpan::command("preview_backup_merge");
+    let ctx = TauriCommandCtx { app: &app };
+    preview_backup_merge_impl(&ctx, state.inner(), filename)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn merge_backup(
+    app: AppHandle,
+    state: State<AppState>,
+    filename: String,
+    task_ids: Vec<String>,
+    project_ids: Option<Vec<String>>,
+    dry_run: Option<bool>,
+) -> CommandResult<Outcome<MergePlan>> {
+    let _span = LogSpan::command("merge_backup");
+    let ctx = TauriCommandCtx { app: &app };
+    let project_ids = project_ids.unwrap_or_default();
+    with_dry_run(
+        dry_run,
+        || {
+            merge_backup_dry_run_impl(
+                &ctx,
+                state.inner(),
+                filename.clone(),
+                task_ids.clone(),
+                project_ids.clone(),
+            )
+        },
+        || {
+            merge_backup_impl(
+                &ctx,
+                state.inner(),
+                filename.clone(),
+                task_ids.clone(),
+                project_ids.clone(),
+            )
+        },
+    )
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn import_backup(
+    app: AppHandle,
+    state: State<AppState>,
+    path: String,
+    dry_run: Option<bool>,
+) -> CommandResult<Outcome<Vec<Task>>> {
+    let _span = LogSpan::command("import_backup");
+    let ctx = TauriCommandCtx { app: &app };
+    with_dry_run(
+        dry_run,
+        || import_backup_dry_run_impl(&ctx, state.inner(), path.clone()),
+        || import_backup_impl(&ctx, state.inner(), path.clone()),
+    )
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn import_external(
+    app: AppHandle,
+    state: State<AppState>,
+    path: String,
+    dry_run: Option<bool>,
+) -> CommandResult<ExternalImportReport> {
+    let _span = LogSpan::command("import_external");
+    let ctx = TauriCommandCtx { app: &app };
+    import_external_impl(&ctx, state.inner(), path, dry_run.unwrap_or(false))
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn import_markdown(
+    app: AppHandle,
+    state: State<AppState>,
+    source: String,
+    headings: Option<MarkdownHeadings>,
+    dry_run: Option<bool>,
+) -> CommandResult<ExternalImportReport> {
+    let _span = LogSpan::command("import_markdown");
+    let ctx = TauriCommandCtx { app: &app };
+    import_markdown_impl(
+        &ctx,
+        state.inner(),
+        source,
+        headings.unwrap_or_default(),
+        dry_run.unwrap_or(false),
+    )
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn export_tasks_json(
+    app: AppHandle,
+    state: State<AppState>,
+    filter: Option<ExportFilter>,
+) -> CommandResult<String> {
+    let _span = LogSpan::command("export_tasks_json");
+    let ctx = TauriCommandCtx { app: &app };
+    export_tasks_json_impl(&ctx, state.inner(), filter.unwrap_or_default())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn export_tasks_content(
+    app: AppHandle,
+    state: State<AppState>,
+    format: ExportFormat,
+    filter: Option<ExportFilter>,
+) -> CommandResult<ExportContent> {
+    let _span = LogSpan::command("export_tasks_content");
+    let ctx = TauriCommandCtx { app: &app };
+    export_tasks_content_impl(&ctx, state.inner(), format, filter.unwrap_or_default())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn export_tasks_csv(
+    app: AppHandle,
+    state: State<AppState>,
+    filter: Option<ExportFilter>,
+) -> CommandResult<String> {
+    let _span = LogSpan::command("export_tasks_csv");
+    let ctx = TauriCommandCtx { app: &app };
+    export_tasks_csv_impl(&ctx, state.inner(), filter.unwrap_or_default())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn export_tasks_markdown(
+    app: AppHandle,
+    state: State<AppState>,
+    filter: Option<ExportFilter>,
+) -> CommandResult<String> {
+    let _span = LogSpan::command("export_tasks_markdown");
+    let ctx = TauriCommandCtx { app: &app };
+    export_tasks_markdown_impl(&ctx, state.inner(), filter.unwrap_or_default())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn export_tasks_ics(
+    app: AppHandle,
+    state: State<AppState>,
+    filter: Option<ExportFilter>,
+) -> CommandResult<String> {
+    let _span = LogSpan::command("export_tasks_ics");
+    let ctx = TauriCommandCtx { app: &app };
+    export_tasks_ics_impl(&ctx, state.inner(), filter.unwrap_or_default())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn export_year_review(
+    app: AppHandle,
+    state: State<AppState>,
+    year: i32,
+) -> CommandResult<YearReviewExport> {
+    let _span = LogSpan::command("export_year_review");
+    let ctx = TauriCommandCtx { app: &app };
+    export_year_review_impl(&ctx, state.inner(), year)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn export_markdown_sync(app: AppHandle, state: State<AppState>) -> CommandResult<String> {
+    let _span = LogSpan::command("export_markdown_sync");
+    let ctx = TauriCommandCtx { app: &app };
+    export_markdown_sync_impl(&ctx, state.inner())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn export_to_obsidian(
+    app: AppHandle,
+    state: State<AppState>,
+    vault_path: String,
+) -> CommandResult<ObsidianExportReport> {
+    let _span = LogSpan::command("export_to_obsidian");
+    let ctx = TauriCommandCtx { app: &app };
+    export_to_obsidian_impl(&ctx, state.inner(), vault_path)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+#[tauri::command]
+pub fn sync_markdown_export(
+    app: AppHandle,
+    state: State<AppState>,
+) -> CommandResult<MarkdownSyncReport> {
+    let _span = LogSpan::command("sync_markdown_export");
+    let ctx = TauriCommandCtx { app: &app };
+    sync_markdown_export_impl(&ctx, state.inner())
+}
+
+#[cfg(feature = "bench")]
+pub mod bench;
+
+#[cfg(test)]
+mod scenarios;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::StateDelta;
+    use crate::md_sync::SyncConflictKind;
+    use crate::merge::MergeStatus;
+    use crate::models::{AiProviderKind, Step};
+    use crate::models::{ReminderConfig, ReminderKind, RepeatRule, Task};
+    use chrono::Utc;
+    use std::fs;
+    use std::sync::Mutex;
+
+    fn is_io(err: &StorageError) -> bool {
+        matches!(err, StorageError::Io(_))
+    }
+
+    struct TestCtx {
+        root: tempfile::TempDir,
+        app_data_dir_error: Option<String>,
+        app_data_dir_override: Option<PathBuf>,
+        emitted: Mutex<Vec<StatePayload>>,
+        data_dir_progress: Mutex<Vec<DataDirProgress>>,
+        archive_progress: Mutex<Vec<ArchiveProgress>>,
+        fired: Mutex<Vec<Task>>,
+        dispatched: Mutex<Vec<OutboundEvent>>,
+        tray_updates: Mutex<usize>,
+        shortcut_unregistered: Mutex<usize>,
+        shortcut_registered: Mutex<usize>,
+        shortcut_register_error: Mutex<Option<String>>,
+        write_behind: bool,
+        // Emit `state_delta`s like the app instead of full payloads.
+        emit_deltas: bool,
+        deltas: Mutex<Vec<StateDelta>>,
+        write_statuses: Mutex<Vec<WriteStatus>>,
+    }
+
+    impl TestCtx {
+        fn new() -> Self {
+            Self {
+                root: tempfile::tempdir().unwrap(),
+                app_data_dir_error: None,
+                app_data_dir_override: None,
+                emitted: Mutex::new(Vec::new()),
+                data_dir_progress: Mutex::new(Vec::new()),
+                archive_progress: Mutex::new(Vec::new()),
+                fired: Mutex::new(Vec::new()),
+                dispatched: Mutex::new(Vec::new()),
+                tray_updates: Mutex::new(0),
+                shortcut_unregistered: Mutex::new(0),
+                shortcut_registered: Mutex::new(0),
+                shortcut_register_error: Mutex::new(None),
+                write_behind: false,
+                emit_deltas: false,
+                deltas: Mutex::new(Vec::new()),
+                write_statuses: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn with_app_data_dir_error(message: &str) -> Self {
+            let mut ctx = Self::new();
+            ctx.app_data_dir_error = Some(message.to_string());
+            ctx
+        }
+
+        fn root_path(&self) -> &std::path::Path {
+            self.root.path()
+        }
+
+        fn set_app_data_dir_override(&mut self, path: PathBuf) {
+            self.app_data_dir_override = Some(path);
+        }
+
+        fn set_shortcut_register_error(&self, message: Option<&str>) {
+            *self.shortcut_register_error.lock().unwrap() = message.map(|s| s.to_string());
+        }
+    }
+
+    impl CommandCtx for TestCtx {
+        fn app_data_dir(&self) -> Result<PathBuf, StorageError> {
+            if let Some(message) = &self.app_data_dir_error {
+                return Err(StorageError::Io(std::io::Error::other(message.clone())));
+            }
+            if let Some(path) = &self.app_data_dir_override {
+                return Ok(path.clone());
+            }
+            Ok(crate::profiles::active_profile_root(&self.data_root()?))
+        }
+
+        fn data_root(&self) -> Result<PathBuf, StorageError> {
+            if let Some(message) = &self.app_data_dir_error {
+                return Err(StorageError::Io(std::io::Error::other(message.clone())));
+            }
+            Ok(crate::data_dir::resolve_data_dir(self.root.path(), None))
+        }
+
+        fn default_app_data_dir(&self) -> Result<PathBuf, StorageError> {
+            if let Some(message) = &self.app_data_dir_error {
+                return Err(StorageError::Io(std::io::Error::other(message.clone())));
+            }
+            Ok(self.root.path().to_path_buf())
+        }
+
+        fn emit_data_dir_progress(&self, progress: &DataDirProgress) {
+            self.data_dir_progress
+                .lock()
+                .unwrap()
+                .push(progress.clone());
+        }
+
+        fn emit_archive_progress(&self, progress: &ArchiveProgress) {
+            self.archive_progress.lock().unwrap().push(progress.clone());
+        }
+
+        fn emit_write_status(&self, status: &WriteStatus) {
+            self.write_statuses.lock().unwrap().push(status.clone());
+        }
+
+        fn emit_state_updated(&self, payload: StatePayload) {
+            self.emitted.lock().unwrap().push(payload);
+        }
+
+        fn emit_state_changes(&self, state: &AppState) {
+            if !self.emit_deltas {
+                return self.emit_state_updated(state.state_payload());
+            }
+            self.deltas.lock().unwrap().extend(state.take_delta());
+        }
+
+        fn write_behind(&self) -> bool {
+            self.write_behind
+        }
+
+        fn dispatch_event(&self, event: OutboundEvent) {
+            self.dispatched.lock().unwrap().push(event);
+        }
+
+        fn fire_reminder(&self, tasks: &[Task]) {
+            self.fired.lock().unwrap().extend(tasks.iter().cloned());
+        }
+
+        fn update_tray_count(&self, _tasks: &[Task], _settings: &Settings) {
+            *self.tray_updates.lock().unwrap() += 1;
+        }
+
+        fn shortcut_unregister_all(&self) {
+            *self.shortcut_unregistered.lock().unwrap() += 1;
+        }
+
+        fn shortcut_validate(&self, shortcut: &str) -> Result<(), String> {
+            let shortcut = shortcut.trim();
+            if shortcut.is_empty() {
+                return Err("empty shortcut".to_string());
+            }
+
+            // A lightweight validator for unit tests. Production builds validate using the
+            // real Tauri shortcut parser (see `TauriCommandCtx`).
+            if shortcut.starts_with("CommandOrControl+Shift+")
+                && shortcut.len() > "CommandOrControl+Shift+".len()
+            {
+                return Ok(());
+            }
+
+            Err("parse error".to_string())
+        }
+
+        fn shortcut_probe(&self, shortcut: &str) -> Result<(), String> {
+            self.shortcut_validate(shortcut)?;
+            match self.shortcut_register_error.lock().unwrap().clone() {
+                Some(message) => Err(message),
+                None => Ok(()),
+            }
+        }
+
+        fn shortcut_register(&self, shortcut: &str) -> Result<(), String> {
+            self.shortcut_validate(shortcut)?;
+            *self.shortcut_registered.lock().unwrap() += 1;
+            if let Some(message) = self.shortcut_register_error.lock().unwrap().clone() {
+                return Err(message);
+            }
+            Ok(())
+        }
+    }
+
+    struct ForceJsonErrorCtx {
+        inner: TestCtx,
+    }
+
+    impl ForceJsonErrorCtx {
+        fn new() -> Self {
+            Self {
+                inner: TestCtx::new(),
+            }
+        }
+    }
+
+    impl CommandCtx for ForceJsonErrorCtx {
+        fn app_data_dir(&self) -> Result<PathBuf, StorageError> {
+            self.inner.app_data_dir()
+        }
+
+        fn emit_state_updated(&self, payload: StatePayload) {
+            self.inner.emit_state_updated(payload);
+        }
+
+        fn fire_reminder(&self, tasks: &[Task]) {
+            self.inner.fire_reminder(tasks);
+        }
+
+        fn update_tray_count(&self, tasks: &[Task], settings: &Settings) {
+            self.inner.update_tray_count(tasks, settings);
+        }
+
+        fn shortcut_unregister_all(&self) {
+            self.inner.shortcut_unregister_all();
+        }
+
+        fn shortcut_validate(&self, shortcut: &str) -> Result<(), String> {
+            self.inner.shortcut_validate(shortcut)
+        }
+
+        fn shortcut_register(&self, shortcut: &str) -> Result<(), String> {
+            self.inner.shortcut_register(shortcut)
+        }
+
+        fn force_json_serialize_error(&self) -> bool {
+            true
+        }
+    }
+
+    fn make_task(id: &str, due_at: i64) -> Task {
+        Task {
+            id: id.to_string(),
+            project_id: "inbox".to_string(),
+            title: format!("task-{id}"),
+            due_at: Some(due_at),
+            important: false,
+            completed: false,
+            completed_at: None,
+            created_at: 1,
+            updated_at: 1,
+            sort_order: 0,
+            quadrant: 1,
+            notes: None,
+            steps: Vec::new(),
+            tags: Vec::new(),
+            sample_tag: None,
+            reminder: ReminderConfig {
+                kind: ReminderKind::Normal,
+                ..ReminderConfig::default()
+            },
+            repeat: RepeatRule::None,
+            waiting_on: None,
+            waiting_since: None,
+            series_id: None,
+            pinned: false,
+            context: None,
+            comments: Vec::new(),
+            progress: Default::default(),
+            spawn_ahead: 0,
+            repeat_from_completion: false,
+            end_after_occurrences: None,
+            end_by_date: None,
+            occurrence_index: 0,
+            timezone: None,
+            escalate_after: None,
+            start_at: None,
+            someday: false,
+            carry_over_count: 0,
+            skip_carry_over: false,
+            color: None,
+            emoji: None,
+            estimate_minutes: None,
+            attachments: Vec::new(),
+            time_entries: Vec::new(),
+            blocked_by: Vec::new(),
+        }
+    }
+
+    fn make_state(tasks: Vec<Task>) -> AppState {
+        AppState::new(tasks, Vec::new(), Settings::default())
+    }
+
+    #[test]
+    fn ok_and_err_helpers_construct_expected_shape() {
+        let r = ok(123);
+        assert!(r.ok);
+        assert_eq!(r.data, Some(123));
+        assert_eq!(r.error, None);
+
+        let r: CommandResult<i32> = err("nope");
+        assert!(!r.ok);
+        assert_eq!(r.data, None);
+        assert_eq!(r.error, Some("nope".to_string()));
+    }
+
+    #[test]
+    fn test_ctx_shortcut_register_propagates_validation_error() {
+        let ctx = TestCtx::new();
+        let err = ctx
+            .shortcut_register("bad-shortcut")
+            .expect_err("should fail shortcut_validate");
+        assert_eq!(err, "parse error");
+    }
+
+    #[test]
+    fn auto_backup_predicates_cover_all_schedules() {
+        let now = Local
+            .with_ymd_and_hms(2024, 1, 2, 12, 0, 0)
+            .single()
+            .unwrap()
+            .timestamp();
+        let yesterday = Local
+            .with_ymd_and_hms(2024, 1, 1, 12, 0, 0)
+            .single()
+            .unwrap()
+            .timestamp();
+
+        let mut settings = Settings::default();
+        settings.backup_schedule = BackupSchedule::None;
+        settings.last_backup_at = None;
+        assert!(!should_auto_backup(&settings, now));
+
+        settings.backup_schedule = BackupSchedule::Daily;
+        settings.last_backup_at = None;
+        assert!(should_auto_backup(&settings, now));
+        settings.last_backup_at = Some(yesterday);
+        assert!(should_auto_backup(&settings, now));
+        settings.last_backup_at = Some(now);
+        assert!(!should_auto_backup(&settings, now));
+
+        settings.backup_schedule = BackupSchedule::Weekly;
+        settings.last_backup_at = None;
+        assert!(should_auto_backup(&settings, now));
+
+        let week_start = Local
+            .with_ymd_and_hms(2024, 1, 1, 12, 0, 0)
+            .single()
+            .unwrap()
+            .timestamp();
+        let same_week = Local
+            .with_ymd_and_hms(2024, 1, 2, 12, 0, 0)
+            .single()
+            .unwrap()
+            .timestamp();
+        let next_week = Local
+            .with_ymd_and_hms(2024, 1, 8, 12, 0, 0)
+            .single()
+            .unwrap()
+            .timestamp();
+        settings.last_backup_at = Some(week_start);
+        assert!(!should_auto_backup(&settings, same_week));
+        assert!(should_auto_backup(&settings, next_week));
+
+        // 2024-01-07 is a Sunday: still the Monday-start week, but a new Sunday-start week.
+        let sunday = Local
+            .with_ymd_and_hms(2024, 1, 7, 12, 0, 0)
+            .single()
+            .unwrap()
+            .timestamp();
+        assert!(!should_auto_backup(&settings, sunday));
+        settings.week_starts_on = WeekStart::Sunday;
+        assert!(should_auto_backup(&settings, sunday));
+        settings.week_starts_on = WeekStart::Monday;
+
+        settings.backup_schedule = BackupSchedule::Monthly;
+        settings.last_backup_at = None;
+        assert!(should_auto_backup(&settings, now));
+
+        let month_start = Local
+            .with_ymd_and_hms(2024, 1, 15, 12, 0, 0)
+            .single()
+            .unwrap()
+            .timestamp();
+        let same_month = Local
+            .with_ymd_and_hms(2024, 1, 20, 12, 0, 0)
+            .single()
+            .unwrap()
+            .timestamp();
+        let next_month = Local
+            .with_ymd_and_hms(2024, 2, 1, 12, 0, 0)
+            .single()
+            .unwrap()
+            .timestamp();
+        settings.last_backup_at = Some(month_start);
+        assert!(!should_auto_backup(&settings, same_month));
+        assert!(should_auto_backup(&settings, next_month));
+    }
+
+    #[test]
+    fn persist_success_and_error_paths() {
+        let ctx = TestCtx::new();
+        let state = AppState::new(vec![make_task("a", 1000)], Vec::new(), Settings::default());
+
+        persist(&ctx, &state).unwrap();
+        assert!(ctx.root_path().join("backups").is_dir());
+        assert!(ctx.root_path().join("data.json").is_file());
+        assert!(ctx.root_path().join("settings.json").is_file());
+        assert_eq!(ctx.emitted.lock().unwrap().len(), 1);
+        assert_eq!(*ctx.tray_updates.lock().unwrap(), 1);
+
+        let bad_ctx = TestCtx::with_app_data_dir_error("nope");
+        assert!(persist(&bad_ctx, &state).is_err());
+
+        let ctx2 = TestCtx::new();
+        fs::write(ctx2.root_path().join("backups"), b"x").unwrap();
+        assert!(persist(&ctx2, &state).is_err());
+
+        let ctx3 = TestCtx::new();
+        fs::create_dir_all(ctx3.root_path().join("data.json")).unwrap();
+        assert!(persist(&ctx3, &state).is_err());
+
+        let ctx4 = TestCtx::new();
+        fs::create_dir_all(ctx4.root_path().join("settings.json")).unwrap();
+        assert!(persist(&ctx4, &state).is_err());
+    }
+
+    #[test]
+    fn infer_task_from_text_uses_the_state_clock_and_settings() {
+        use crate::clock::{AppClock, FixedClock};
+
+        let now = Local.with_ymd_and_hms(2026, 3, 18, 10, 0, 0).unwrap();
+        let state = AppState::with_clock(
+            Vec::new(),
+            Vec::new(),
+            Settings {
+                quick_default_due_time: "08:45".to_string(),
+                ..Settings::default()
+            },
+            AppClock::new(std::sync::Arc::new(FixedClock(now.timestamp()))),
+        );
+        let draft = infer_task_from_text_impl(
+            &state,
+            "Water plants tomorrow
+- balcony"
+                .to_string(),
+        )
+        .data
+        .unwrap();
+        assert_eq!(draft.title, "Water plants");
+        assert_eq!(draft.steps, ["balcony"]);
+        assert_eq!(
+            draft.due_at,
+            Some(
+                Local
+                    .with_ymd_and_hms(2026, 3, 19, 8, 45, 0)
+                    .unwrap()
+                    .timestamp()
+            )
+        );
+        assert!(!infer_task_from_text_impl(&state, " \n ".to_string()).ok);
+    }
+
+    #[test]
+    fn events_manifest_describes_the_emitted_events() {
+        let manifest = events_manifest_impl().data.unwrap();
+        assert!(manifest
+            .events
+            .iter()
+            .any(|event| event.name == crate::events::EVENT_STATE_UPDATED && event.version == 1));
+    }
+
+    #[test]
+    fn load_state_recovers_corrupt_data_and_reports_it() {
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
+        assert_eq!(get_recovery_status_impl(&state).data.unwrap(), None);
+
+        let saved = AppState::new(
+            vec![make_task("kept", 1000)],
+            Vec::new(),
+            Settings::default(),
+        );
+        fs::create_dir_all(ctx.root_path().join("backups")).unwrap();
+        fs::write(
+            ctx.root_path().join("backups/data-2026-03-01.json"),
+            serde_json::to_string(&saved.tasks_file()).unwrap(),
+        )
+        .unwrap();
+        fs::write(ctx.root_path().join("data.json"), "{oops").unwrap();
+
+        let payload = load_state_impl(&ctx, &state).data.unwrap();
+        assert_eq!(payload.tasks[0].id, "kept");
+        let status = get_recovery_status_impl(&state).data.unwrap().unwrap();
+        assert_eq!(
+            status.recovered_from.as_deref(),
+            Some("data-2026-03-01.json")
+        );
+        let quarantined = status.quarantined_as.unwrap();
+        assert!(ctx.root_path().join(quarantined).is_file());
+
+        // A clean reload keeps the status for the prompt instead of clearing it.
+        assert!(load_state_impl(&ctx, &state).ok);
+        assert!(get_recovery_status_impl(&state).data.unwrap().is_some());
+    }
+
+    #[test]
+    fn create_task_with_duplicate_check_holds_back_a_repeat_of_an_open_task() {
+        let ctx = TestCtx::new();
+        let state = make_state(vec![make_task("milk", 10_000)]);
+        let mut again = make_task("milk-2", 10_000);
+        again.title = "TASK-MILK!".into();
+
+        let res = create_task_checked_impl(&ctx, &state, again.clone(), true);
+        match res.data.unwrap() {
+            CreateOutcome::Duplicates { duplicates } => assert_eq!(duplicates[0].id, "milk"),
+            CreateOutcome::Created(_) => panic!("expected duplicates"),
+        }
+        assert_eq!(state.tasks().len(), 1);
+        let json = serde_json::to_value(
+            create_task_checked_impl(&ctx, &state, again.clone(), true)
+                .data
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(json["duplicates"][0]["id"], "milk");
+
+        // "Create anyway" calls again without the check; the payload is the plain task.
+        let res = create_task_checked_impl(&ctx, &state, again, false);
+        let json = serde_json::to_value(res.data.unwrap()).unwrap();
+        assert_eq!(json["id"], "milk-2");
+        assert_eq!(state.tasks().len(), 2);
+
+        let mut invalid = make_task("bad", 10_000);
+        invalid.timezone = Some("Not/AZone".into());
+        assert!(!create_task_checked_impl(&ctx, &state, invalid, true).ok);
+    }
+
+    #[test]
+    fn load_state_and_task_mutation_commands_cover_success_and_error_paths() {
+        let state = make_state(Vec::new());
+
+        // app_data_dir error path.
+        let bad_ctx = TestCtx::with_app_data_dir_error("nope");
+        let res = load_state_impl(&bad_ctx, &state);
+        assert!(!res.ok);
+
+        // ensure_dirs error path.
+        let ctx2 = TestCtx::new();
+        fs::write(ctx2.root_path().join("backups"), b"x").unwrap();
+        let res = load_state_impl(&ctx2, &state);
+        assert!(!res.ok);
+
+        // success path (missing files => defaults).
+        let ctx3 = TestCtx::new();
+        let res = load_state_impl(&ctx3, &state);
+        assert!(res.ok);
+        let payload = res.data.unwrap();
+        assert!(payload.tasks.is_empty());
+        assert_eq!(payload.settings.shortcut, Settings::default().shortcut);
+        assert_eq!(state.settings().shortcut, Settings::default().shortcut);
+
+        // create_task fills sort_order when missing.
+        let mut t = make_task("a", 1000);
+        t.created_at = 2;
+        let res = create_task_impl(&ctx3, &state, t);
+        assert!(res.ok);
+        let created_task = res.data.unwrap();
+        assert_eq!(created_task.sort_order, 2000);
+
+        // create_task keeps an explicit sort_order as-is.
+        let ctx_sort = TestCtx::new();
+        let state_sort = make_state(Vec::new());
+        let mut t2 = make_task("b", 1000);
+        t2.sort_order = 123;
+        let res = create_task_impl(&ctx_sort, &state_sort, t2);
+        assert!(res.ok);
+        assert_eq!(res.data.unwrap().sort_order, 123);
+
+        // create_task persist failure path.
+        let ctx_fail = TestCtx::new();
+        fs::write(ctx_fail.root_path().join("backups"), b"x").unwrap();
+        let state_fail = make_state(Vec::new());
+        let res_fail = create_task_impl(&ctx_fail, &state_fail, make_task("x", 1));
+        assert!(!res_fail.ok);
+
+        // update_task updates by id and fills sort_order when zero.
+        let mut updated = created_task.clone();
+        updated.title = "updated".into();
+        updated.sort_order = 0;
+        let res = update_task_impl(&ctx3, &state, updated);
+        assert!(res.ok);
+        assert_eq!(state.tasks().len(), 1);
+        assert_eq!(state.tasks()[0].title, "updated");
+        assert_ne!(state.tasks()[0].sort_order, 0);
+
+        // update_task persist failure path.
+        let update_ctx_fail = TestCtx::with_app_data_dir_error("nope");
+        let state_update_fail = make_state(vec![state.tasks()[0].clone()]);
+        let mut updated_fail = state_update_fail.tasks()[0].clone();
+        updated_fail.title = "should-fail".into();
+        let res = update_task_impl(&update_ctx_fail, &state_update_fail, updated_fail);
+        assert!(!res.ok);
+
+        // swap_sort_order not-found path.
+        let res = swap_sort_order_impl(&ctx3, &state, "a".into(), "missing".into());
+        assert!(!res.ok);
+        assert_eq!(res.error, Some("task not found".to_string()));
+
+        // swap_sort_order success path.
+        let mut b = make_task("b", 1000);
+        b.sort_order = 999;
+        state.add_task(b);
+        let res = swap_sort_order_impl(&ctx3, &state, "a".into(), "b".into());
+        assert!(res.ok);
+        let tasks = state.tasks();
+        let a = tasks.iter().find(|t| t.id == "a").unwrap();
+        let b = tasks.iter().find(|t| t.id == "b").unwrap();
+        assert_eq!(a.sort_order, 999);
+        assert_eq!(b.sort_order, 2000);
+
+        // swap_sort_order persist failure path.
+        let swap_ctx_fail = TestCtx::with_app_data_dir_error("nope");
+        let state_swap_fail = make_state(vec![
+            tasks.iter().find(|t| t.id == "a").unwrap().clone(),
+            tasks.iter().find(|t| t.id == "b").unwrap().clone(),
+        ]);
+        let res = swap_sort_order_impl(&swap_ctx_fail, &state_swap_fail, "a".into(), "b".into());
+        assert!(!res.ok);
+    }
+
+    #[test]
+    fn complete_task_covers_not_found_non_repeat_repeat_and_persist_error() {
+        let ctx = TestCtx::new();
+
+        // Not found.
+        let state = make_state(Vec::new());
+        let res = complete_task_impl(&ctx, &state, "missing".into());
+        assert!(!res.ok);
+
+        // RepeatRule::None returns completed task.
+        let state = make_state(vec![make_task("a", 1000)]);
+        let res = complete_task_impl(&ctx, &state, "a".into());
+        assert!(res.ok);
+        assert!(res.data.unwrap().completed);
+
+        // RepeatRule != None creates a new task instance.
+        let mut task = make_task("r", 1000);
+        task.repeat = RepeatRule::Daily {
+            workday_only: false,
+        };
+        let state = make_state(vec![task]);
+        let res = complete_task_impl(&ctx, &state, "r".into());
+        assert!(res.ok);
+        let next = res.data.unwrap();
+        assert!(!next.completed);
+        assert!(next.id.starts_with("r-"));
+        assert!(state.tasks().len() >= 2);
+
+        // Completion-based repeats count from today, not from the long-past due date.
+        let mut task = make_task("c", 1000);
+        task.repeat = RepeatRule::EveryNDays { n: 2 };
+        task.repeat_from_completion = true;
+        let state = make_state(vec![task]);
+        let next = complete_task_impl(&ctx, &state, "c".into()).data.unwrap();
+        assert!(next.repeat_from_completion);
+        assert!(next.due_at.unwrap() > state.clock().timestamp());
+
+        // The last occurrence of a counted series completes without spawning another.
+        let mut task = make_task("last", 1000);
+        task.repeat = RepeatRule::EveryNDays { n: 1 };
+        task.end_after_occurrences = Some(2);
+        task.occurrence_index = 1;
+        let state = make_state(vec![task]);
+        let done = complete_task_impl(&ctx, &state, "last".into())
+            .data
+            .unwrap();
+        assert!(done.completed);
+        assert_eq!(state.tasks().len(), 1);
+
+        // Persist error path.
+        let ctx_fail = TestCtx::new();
+        fs::write(ctx_fail.root_path().join("backups"), b"x").unwrap();
+        let state_fail = make_state(vec![make_task("x", 1)]);
+        let res = complete_task_impl(&ctx_fail, &state_fail, "x".into());
+        assert!(!res.ok);
+
+        // Persist error path when RepeatRule != None (covers the second persist callsite).
+        let ctx_fail_repeat = TestCtx::new();
+        fs::write(ctx_fail_repeat.root_path().join("backups"), b"x").unwrap();
+        let mut repeat_task = make_task("y", 1000);
+        repeat_task.repeat = RepeatRule::Daily {
+            workday_only: false,
+        };
+        let state_fail_repeat = make_state(vec![repeat_task]);
+        let res = complete_task_impl(&ctx_fail_repeat, &state_fail_repeat, "y".into());
+        assert!(!res.ok);
+    }
+
+    #[test]
+    fn pin_and_unpin_task_persist_and_report_missing_tasks() {
+        let ctx = TestCtx::new();
+        let state = make_state(vec![make_task("a", 1000)]);
+
+        let res = set_task_pinned_impl(&ctx, &state, "a".into(), true);
+        assert!(res.ok);
+        assert!(res.data.unwrap().pinned);
+        let saved = Storage::new(ctx.root_path().to_path_buf())
+            .load_tasks()
+            .unwrap();
+        assert!(saved.tasks[0].pinned);
+
+        assert!(
+            !set_task_pinned_impl(&ctx, &state, "a".into(), false)
+                .data
+                .unwrap()
+                .pinned
+        );
+        let res = set_task_pinned_impl(&ctx, &state, "missing".into(), true);
+        assert_eq!(res.error.as_deref(), Some("task not found"));
+
+        let ctx_fail = TestCtx::new();
+        fs::write(ctx_fail.root_path().join("backups"), b"x").unwrap();
+        assert!(!set_task_pinned_impl(&ctx_fail, &state, "a".into(), true).ok);
+    }
+
+    #[test]
+    fn mute_and_unmute_project_persist_and_survive_project_edits() {
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
+        let now = state.clock().timestamp();
+
+        let res = mute_project_impl(&ctx, &state, "inbox".into(), Some(now + 3600));
+        let muted = res.data.unwrap();
+        assert!(muted.reminders_muted_at(now));
+        assert_eq!(muted.muted_until, Some(now + 3600));
+        let saved = Storage::new(ctx.root_path().to_path_buf())
+            .load_tasks()
+            .unwrap();
+        assert!(saved.projects[0].reminders_muted);
+
+        // Renames and other edits go through update_project and must not drop the mute.
+        let mut edited = muted.clone();
+        edited.reminders_muted = false;
+        edited.muted_until = None;
+        let kept = update_project_impl(&ctx, &state, edited).data.unwrap();
+        assert!(kept.reminders_muted);
+        assert_eq!(kept.muted_until, Some(now + 3600));
+
+        let res = unmute_project_impl(&ctx, &state, "inbox".into());
+        let unmuted = res.data.unwrap();
+        assert!(!unmuted.reminders_muted);
+        assert_eq!(unmuted.muted_until, None);
+
+        assert_eq!(
+            mute_project_impl(&ctx, &state, "inbox".into(), Some(now - 1))
+                .error
+                .as_deref(),
+            Some("mute end must be in the future")
+        );
+        assert_eq!(
+            mute_project_impl(&ctx, &state, "missing".into(), None)
+                .error
+                .as_deref(),
+            Some("project not found")
+        );
+        assert!(!unmute_project_impl(&ctx, &state, "missing".into()).ok);
+
+        let ctx_fail = TestCtx::new();
+        fs::write(ctx_fail.root_path().join("backups"), b"x").unwrap();
+        assert!(!mute_project_impl(&ctx_fail, &state, "inbox".into(), None).ok);
+        assert!(!unmute_project_impl(&ctx_fail, &state, "inbox".into()).ok);
+    }
+
+    #[test]
+    fn dependencies_reject_cycles_and_report_the_unblock_chain() {
+        let ctx = TestCtx::new();
+        let state = make_state(vec![make_task("a", 1000), make_task("b", 1000)]);
+
+        let c = Task {
+            blocked_by: vec!["a".to_string(), "gone".to_string()],
+            ..make_task("c", 1000)
+        };
+        let c = create_task_impl(&ctx, &state, c).data.unwrap();
+        assert_eq!(c.blocked_by, vec!["a"]);
+
+        let a = Task {
+            blocked_by: vec!["c".to_string()],
+            ..make_task("a", 1000)
+        };
+        assert_eq!(
+            update_task_impl(&ctx, &state, a).error,
+            Some("dependency cycle: a -> c -> a".to_string())
+        );
+        // Two edits in one batch cannot close a cycle either.
+        let batch = vec![
+            Task {
+                blocked_by: vec!["b".to_string()],
+                ..make_task("a", 1000)
+            },
+            Task {
+                blocked_by: vec!["c".to_string()],
+                ..make_task("b", 1000)
+            },
+        ];
+        assert!(!bulk_update_tasks_impl(&ctx, &state, batch).ok);
+        assert!(state
+            .tasks()
+            .iter()
+            .all(|t| t.id == "c" || t.blocked_by.is_empty()));
+
+        let a = Task {
+            blocked_by: vec!["b".to_string()],
+            ..make_task("a", 1000)
+        };
+        update_task_impl(&ctx, &state, a).data.unwrap();
+        let chain = unblock_chain_impl(&state, "c".into()).data.unwrap();
+        let order: Vec<_> = chain.nodes.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(order, vec!["b", "a", "c"]);
+        assert_eq!(chain.actionable, vec!["b"]);
+        assert_eq!(
+            unblock_chain_impl(&state, "missing".into()).error,
+            Some("task not found".to_string())
+        );
+    }
+
+    #[test]
+    fn steps_are_promoted_to_tasks_and_tasks_converted_back_into_steps() {
+        let ctx = TestCtx::new();
+        let step = |id: &str, parent: Option<&str>| crate::models::Step {
+            id: id.to_string(),
+            title: id.to_uppercase(),
+            completed: false,
+            created_at: 1,
+            completed_at: None,
+            parent_id: parent.map(str::to_string),
+        };
+        let mut a = make_task("a", 1000);
+        a.steps = vec![step("s1", None), step("s2", Some("s1")), step("s3", None)];
+        let state = make_state(vec![a, make_task("b", 2000)]);
+
+        assert_eq!(
+            promote_step_to_task_impl(&ctx, &state, "a".into(), "nope".into()).error,
+            Some("step not found".to_string())
+        );
+        let promoted = promote_step_to_task_impl(&ctx, &state, "a".into(), "s1".into())
+            .data
+            .unwrap();
+        assert_eq!(
+            (promoted.id.as_str(), promoted.title.as_str()),
+            ("a-s1", "S1")
+        );
+        assert_eq!(promoted.due_at, Some(1000));
+        assert_eq!(promoted.steps.len(), 1);
+        assert_eq!(promoted.steps[0].parent_id, None);
+        let tasks = state.tasks();
+        assert_eq!(tasks.len(), 3);
+        assert_eq!(tasks[0].steps.len(), 1);
+
+        assert!(!convert_task_to_step_impl(&ctx, &state, "b".into(), "b".into(), None).ok);
+        assert_eq!(
+            convert_task_to_step_impl(&ctx, &state, "b".into(), "a".into(), Some("x".into())).error,
+            Some("step not found".to_string())
+        );
+        let target = convert_task_to_step_impl(&ctx, &state, "a-s1".into(), "b".into(), None)
+            .data
+            .unwrap();
+        let nested: Vec<_> = target
+            .steps
+            .iter()
+            .map(|s| (s.id.as_str(), s.parent_id.as_deref()))
+            .collect();
+        assert_eq!(nested, vec![("a-s1", None), ("a-s1-s2", Some("a-s1"))]);
+        assert_eq!(target.progress.steps_total, 2);
+        assert!(state.tasks().iter().all(|t| t.id != "a-s1"));
+
+        let saved = Storage::new(ctx.root_path().to_path_buf())
+            .load_tasks()
+            .unwrap();
+        assert_eq!(saved.tasks.len(), 2);
+
+        undo_last_action_impl(&ctx, &state).data.unwrap();
+        assert!(state.tasks().iter().any(|t| t.id == "a-s1"));
+        undo_last_action_impl(&ctx, &state).data.unwrap();
+        assert_eq!(state.tasks().len(), 2);
+        assert_eq!(state.tasks()[0].steps.len(), 3);
+    }
+
+    #[test]
+    fn timers_track_one_task_at_a_time_and_feed_reports_and_csv() {
+        use crate::clock::{AppClock, FixedClock};
+
+        let ctx = TestCtx::new();
+        let mut b = make_task("b", 1000);
+        b.tags = vec!["deep".to_string()];
+        let state = AppState::with_clock(
+            vec![make_task("a", 1000), b],
+            Vec::new(),
+            Settings::default(),
+            AppClock::new(std::sync::Arc::new(FixedClock(10_000))),
+        );
+        assert!(!start_timer_impl(&ctx, &state, "missing".into()).ok);
+        assert!(stop_timer_impl(&ctx, &state, None).data.unwrap().is_empty());
+
+        let a = start_timer_impl(&ctx, &state, "a".into()).data.unwrap();
+        assert_eq!(a.time_entries.len(), 1);
+        state.clock().advance(600);
+        // Starting b stops a.
+        start_timer_impl(&ctx, &state, "b".into()).data.unwrap();
+        state.clock().advance(300);
+        let rows = report_time_impl(&state, Some(TimeReportGroup::Tag), None, None)
+            .data
+            .unwrap();
+        let rows: Vec<_> = rows.iter().map(|r| (r.key.as_str(), r.seconds)).collect();
+        assert_eq!(rows, vec![("", 600), ("deep", 300)]);
+
+        let saved = Storage::new(ctx.root_path().to_path_buf())
+            .load_tasks()
+            .unwrap();
+        let saved_a = saved.tasks.iter().find(|t| t.id == "a").unwrap();
+        assert_eq!(saved_a.time_entries[0].ended_at, Some(10_600));
+
+        let csv =
+            export_tasks_content_impl(&ctx, &state, ExportFormat::Csv, ExportFilter::default())
+                .data
+                .unwrap()
+                .content;
+        assert!(csv.contains(",10,\""));
+        assert!(csv.contains(",5,\""));
+
+        let stopped = stop_timer_impl(&ctx, &state, Some("a".into()))
+            .data
+            .unwrap();
+        assert!(stopped.is_empty());
+        let stopped = stop_timer_impl(&ctx, &state, None).data.unwrap();
+        assert_eq!(stopped.len(), 1);
+        assert_eq!(stopped[0].time_entries[0].ended_at, Some(10_900));
+
+        // Completing a task stops its timer.
+        start_timer_impl(&ctx, &state, "a".into()).data.unwrap();
+        state.clock().advance(60);
+        let done = state.complete_task("a").unwrap();
+        assert_eq!(done.time_entries[1].ended_at, Some(10_960));
+        assert!(!start_timer_impl(&ctx, &state, "a".into()).ok);
+    }
+
+    #[test]
+    fn attachments_are_copied_referenced_and_removed() {
+        let ctx = TestCtx::new();
+        let state = make_state(vec![make_task("a", 1)]);
+        let source = ctx.root_path().join("notes.txt");
+        fs::write(&source, b"hello").unwrap();
+        let source = source.to_string_lossy().to_string();
+
+        assert_eq!(
+            add_attachment_impl(&ctx, &state, "missing".into(), source.clone()).error,
+            Some("task not found".to_string())
+        );
+        let not_file = ctx.root_path().to_string_lossy().to_string();
+        assert!(!add_attachment_impl(&ctx, &state, "a".into(), not_file).ok);
+
+        let attachment = add_attachment_impl(&ctx, &state, "a".into(), source.clone())
+            .data
+            .unwrap();
+        assert_eq!(attachment.name, "notes.txt");
+        assert_eq!(attachment.size, 5);
+        let stored = ctx.root_path().join("attachments").join(&attachment.file);
+        assert_eq!(fs::read(&stored).unwrap(), b"hello");
+        let saved = Storage::new(ctx.root_path().to_path_buf())
+            .load_tasks()
+            .unwrap();
+        assert_eq!(saved.tasks[0].attachments, vec![attachment.clone()]);
+
+        let opened = attachment_file_impl(&ctx, &state, "a".into(), attachment.id.clone());
+        assert_eq!(opened.data, Some(stored.clone()));
+        assert!(!attachment_file_impl(&ctx, &state, "a".into(), "nope".into()).ok);
+
+        // A second copy of the same file in the same second gets its own id and stored name.
+        let again = add_attachment_impl(&ctx, &state, "a".into(), source.clone())
+            .data
+            .unwrap();
+        assert_ne!(again.file, attachment.file);
+
+        assert_eq!(
+            remove_attachment_impl(&ctx, &state, "a".into(), "nope".into()).error,
+            Some("attachment not found".to_string())
+        );
+        assert!(!remove_attachment_impl(&ctx, &state, "x".into(), attachment.id.clone()).ok);
+        assert_eq!(
+            remove_attachment_impl(&ctx, &state, "a".into(), attachment.id.clone()).data,
+            Some(true)
+        );
+        assert_eq!(state.tasks()[0].attachments, vec![again]);
+        // The file stays until garbage collection; opening the removed entry fails.
+        assert!(stored.exists());
+        assert!(!attachment_file_impl(&ctx, &state, "a".into(), attachment.id).ok);
+
+        fs::remove_file(&stored).unwrap();
+        let archive = Storage::new(ctx.root_path().to_path_buf())
+            .archive_sources()
+            .unwrap();
+        assert!(archive
+            .iter()
+            .any(|(name, _)| name.starts_with("attachments/")));
+
+        let bad_ctx = TestCtx::with_app_data_dir_error("nope");
+        assert!(!add_attachment_impl(&bad_ctx, &state, "a".into(), source).ok);
+    }
+
+    #[test]
+    fn add_and_delete_comment_persist_and_show_up_in_exports() {
+        let ctx = TestCtx::new();
+        let state = make_state(vec![make_task("a", 1000)]);
+
+        let res = add_comment_impl(
+            &ctx,
+            &state,
+            "a".into(),
+            "  called vendor\r\nno answer ".into(),
+        );
+        let comment = res.data.unwrap();
+        assert_eq!(comment.text, "called vendor\nno answer");
+        let saved = Storage::new(ctx.root_path().to_path_buf())
+            .load_tasks()
+            .unwrap();
+        assert_eq!(saved.tasks[0].comments, vec![comment.clone()]);
+        assert!(!ctx.emitted.lock().unwrap().is_empty());
+
+        let md = export_tasks_content_impl(
+            &ctx,
+            &state,
+            ExportFormat::Markdown,
+            ExportFilter::default(),
+        )
+        .data
+        .unwrap();
+        assert!(md.content.contains("  - comments:\n"));
+        assert!(md.content.contains(": called vendor no answer\n"));
+        let csv =
+            export_tasks_content_impl(&ctx, &state, ExportFormat::Csv, ExportFilter::default())
+                .data
+                .unwrap();
+        assert!(csv.content.lines().next().unwrap().ends_with(
+            ",comments,color,emoji,tracked_minutes,time_entries,project_color,project_icon,tag_paths,tag_colors"
+        ));
+        assert!(csv
+            .content
+            .contains(" called vendor no answer\",\"\",\"\",0,\"\",\"\",\"\",\"\",\"\"\n"));
+
+        let res = add_comment_impl(&ctx, &state, "a".into(), " ".into());
+        assert_eq!(res.error.as_deref(), Some("comment is empty"));
+        let res = add_comment_impl(&ctx, &state, "missing".into(), "x".into());
+        assert_eq!(res.error.as_deref(), Some("task not found"));
+        let res = delete_comment_impl(&ctx, &state, "a".into(), "nope".into());
+        assert_eq!(res.error.as_deref(), Some("comment not found"));
+        let res = delete_comment_impl(&ctx, &state, "missing".into(), comment.id.clone());
+        assert_eq!(res.error.as_deref(), Some("task not found"));
+
+        let ctx_fail = TestCtx::new();
+        fs::write(ctx_fail.root_path().join("backups"), b"x").unwrap();
+        assert!(!add_comment_impl(&ctx_fail, &state, "a".into(), "x".into()).ok);
+        assert!(!delete_comment_impl(&ctx_fail, &state, "a".into(), comment.id.clone()).ok);
+    }
+
+    #[test]
+    fn complete_task_retry_after_persist_failure_does_not_duplicate_next_instance() {
+        let ctx = TestCtx::new();
+        fs::write(ctx.root_path().join("backups"), b"x").unwrap();
+        let mut task = make_task("r", 1000);
+        task.repeat = RepeatRule::Daily {
+            workday_only: false,
+        };
+        let state = make_state(vec![task]);
+
+        // First attempt spawns the next instance in memory but fails to persist.
+        assert!(!complete_task_impl(&ctx, &state, "r".into()).ok);
+        assert_eq!(state.tasks().len(), 2);
+
+        // The user retries once storage is healthy again: same next instance, nothing new added.
+        fs::remove_file(ctx.root_path().join("backups")).unwrap();
+        let res = complete_task_impl(&ctx, &state, "r".into());
+        assert!(res.ok);
+        let next = res.data.unwrap();
+        let tasks = state.tasks();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(next.series_id.as_deref(), Some("r"));
+        assert_eq!(tasks.iter().filter(|t| !t.completed).count(), 1);
+
+        // Bulk completion of the same task does not spawn another copy either.
+        assert!(bulk_complete_tasks_impl(&ctx, &state, vec!["r".into()]).ok);
+        assert_eq!(state.tasks().len(), 2);
+
+        // Completing the spawned instance continues the series with a later due date.
+        let res = complete_task_impl(&ctx, &state, next.id.clone());
+        let third = res.data.unwrap();
+        assert_eq!(third.series_id.as_deref(), Some("r"));
+        assert!(third.due_at > next.due_at);
+        assert_eq!(state.tasks().len(), 3);
+    }
+
+    #[test]
+    fn update_settings_validates_shortcuts_registers_and_rolls_back() {
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
+
+        // Shortcut unchanged => no registration.
+        let mut settings = state.settings();
+        settings.theme = "dark".into();
+        let res = update_settings_impl(&ctx, &state, settings.clone());
+        assert!(res.ok);
+        assert_eq!(*ctx.shortcut_unregistered.lock().unwrap(), 0);
+        assert_eq!(*ctx.shortcut_registered.lock().unwrap(), 0);
+
+        // Invalid shortcut.
+        let mut invalid = settings.clone();
+        invalid.shortcut = "not-a-shortcut".into();
+        let res = update_settings_impl(&ctx, &state, invalid);
+        assert!(!res.ok);
+
+        // Shortcut changed => register.
+        let mut changed = settings.clone();
+        changed.shortcut = "CommandOrControl+Shift+Y".into();
+        let res = update_settings_impl(&ctx, &state, changed.clone());
+        assert!(res.ok);
+        assert!(state.settings().shortcut.ends_with('Y'));
+        assert_eq!(*ctx.shortcut_unregistered.lock().unwrap(), 1);
+        assert_eq!(*ctx.shortcut_registered.lock().unwrap(), 1);
+
+        // Register failure => best-effort restore previous shortcut.
+        ctx.set_shortcut_register_error(Some("boom"));
+        let mut changed2 = settings.clone();
+        changed2.shortcut = "CommandOrControl+Shift+Z".into();
+        let prev_shortcut = state.settings().shortcut;
+        let res = update_settings_impl(&ctx, &state, changed2);
+        assert!(!res.ok);
+        assert_eq!(state.settings().shortcut, prev_shortcut);
+        ctx.set_shortcut_register_error(None);
+
+        // Persist failure => rollback both in-memory settings and shortcut.
+        // Replace settings.json with a directory so `save_settings` fails reliably.
+        let settings_path = ctx.root_path().join("settings.json");
+        let _ = fs::remove_file(&settings_path);
+        fs::create_dir_all(&settings_path).unwrap();
+        let before = state.settings().shortcut;
+        let mut changed3 = settings;
+        changed3.shortcut = "CommandOrControl+Shift+T".into();
+        let res = update_settings_impl(&ctx, &state, changed3);
+        assert!(!res.ok);
+        assert_eq!(state.settings().shortcut, before);
+        assert!(*ctx.shortcut_unregistered.lock().unwrap() >= 2);
+        assert!(*ctx.shortcut_registered.lock().unwrap() >= 2);
+
+        // Persist failure with shortcut unchanged should not attempt shortcut rollback logic.
+        // This covers the `shortcut_changed == false` rollback branch in the persist error path.
+        let ctx_no_change = TestCtx::new();
+        let state_no_change = make_state(Vec::new());
+        let settings_path = ctx_no_change.root_path().join("settings.json");
+        let _ = fs::remove_file(&settings_path);
+        fs::create_dir_all(&settings_path).unwrap();
+        let before = state_no_change.settings();
+        let mut settings_no_change = before.clone();
+        settings_no_change.theme = "light".into();
+        let res = update_settings_impl(&ctx_no_change, &state_no_change, settings_no_change);
+        assert!(!res.ok);
+        assert_eq!(state_no_change.settings().shortcut, before.shortcut);
+        assert_eq!(state_no_change.settings().theme, before.theme);
+        assert_eq!(*ctx_no_change.shortcut_unregistered.lock().unwrap(), 0);
+        assert_eq!(*ctx_no_change.shortcut_registered.lock().unwrap(), 0);
+    }
+
+    #[test]
+    fn update_settings_registers_every_action_shortcut_and_rejects_conflicts() {
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
+
+        let mut settings = state.settings();
+        settings.shortcut_quick_add = " CommandOrControl+Shift+A ".into();
+        settings.shortcut_toggle_main = "CommandOrControl+Shift+M".into();
+        let res = update_settings_impl(&ctx, &state, settings.clone());
+        assert!(res.ok);
+        assert_eq!(
+            state.settings().shortcut_quick_add,
+            "CommandOrControl+Shift+A"
+        );
+        assert_eq!(*ctx.shortcut_unregistered.lock().unwrap(), 1);
+        assert_eq!(*ctx.shortcut_registered.lock().unwrap(), 3);
+
+        // Two actions on one shortcut => rejected before anything is unregistered.
+        let mut conflicting = state.settings();
+        conflicting.shortcut_complete_focus = "CommandOrControl+Shift+M".into();
+        let res = update_settings_impl(&ctx, &state, conflicting);
+        assert!(res
+            .error
+            .as_deref()
+            .unwrap_or_default()
+            .contains("shortcut conflict"));
+        assert_eq!(*ctx.shortcut_unregistered.lock().unwrap(), 1);
+        assert_eq!(state.settings().shortcut_complete_focus, "");
+
+        let mut invalid = state.settings();
+        invalid.shortcut_complete_focus = "nope".into();
+        let res = update_settings_impl(&ctx, &state, invalid);
+        assert!(res
+            .error
+            .as_deref()
+            .unwrap_or_default()
+            .contains("shortcut_complete_focus"));
+    }
+
+    #[test]
+    fn validate_shortcut_reports_parse_errors_and_conflicts() {
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
+        let mut settings = state.settings();
+        settings.shortcut_toggle_main = "CommandOrControl+Shift+M".into();
+        state.update_settings(settings);
+
+        let res = validate_shortcut_impl(&ctx, &state, "nope".into(), None);
+        let check = res.data.unwrap();
+        assert!(!check.valid && check.error.is_some() && check.conflicts.is_empty());
+
+        // The quick window's own binding is free for itself, taken for other actions.
+        let default = Settings::default().shortcut;
+        let own = validate_shortcut_impl(&ctx, &state, default.clone(), Some("shortcut".into()));
+        assert!(own.data.unwrap().valid);
+        let other = validate_shortcut_impl(
+            &ctx,
+            &state,
+            format!(" {default} "),
+            Some("shortcut_quick_add".into()),
+        );
+        let check = other.data.unwrap();
+        assert_eq!(check.shortcut, default);
+        assert_eq!(
+            check.conflicts,
+            vec![ShortcutConflict::App {
+                action: "shortcut".into()
+            }]
+        );
+
+        // Taken by another application, as reported by the OS.
+        ctx.set_shortcut_register_error(Some("HotKey already registered"));
+        let check = validate_shortcut_impl(&ctx, &state, "CommandOrControl+Shift+Q".into(), None)
+            .data
+            .unwrap();
+        assert!(!check.valid);
+        assert!(matches!(
+            &check.conflicts[..],
+            [ShortcutConflict::System { reason }] if reason.contains("already registered")
+        ));
+        assert_eq!(*ctx.shortcut_registered.lock().unwrap(), 0);
+
+        let res = validate_shortcut_impl(&ctx, &state, default, Some("bogus".into()));
+        assert!(!res.ok);
+    }
+
+    #[test]
+    fn complete_focus_task_completes_the_first_open_focus_task() {
+        let ctx = TestCtx::new();
+        let state = make_state(vec![
+            make_task("a", 4_000_000_000),
+            make_task("b", 4_000_000_000),
+        ]);
+        let today = chrono::NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let mut settings = state.settings();
+        settings.today_focus_ids = vec!["b".into(), "a".into()];
+        settings.today_focus_date = Some("2026-03-10".into());
+        state.update_settings(settings);
+
+        let res = complete_focus_task_impl(&ctx, &state, today);
+        assert_eq!(res.data.flatten().map(|task| task.id).as_deref(), Some("b"));
+        assert!(state
+            .tasks()
+            .iter()
+            .any(|task| task.id == "b" && task.completed));
+
+        // Next press moves down the list; an old list completes nothing.
+        let res = complete_focus_task_impl(&ctx, &state, today);
+        assert_eq!(res.data.flatten().map(|task| task.id).as_deref(), Some("a"));
+        let res = complete_focus_task_impl(&ctx, &state, today);
+        assert!(res.ok && res.data.flatten().is_none());
+    }
+
+    #[test]
+    fn update_settings_rejects_empty_shortcut_without_side_effects() {
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
+
+        let mut settings = state.settings();
+        settings.shortcut = "   ".into();
+
+        let res = update_settings_impl(&ctx, &state, settings);
+        assert!(!res.ok);
+        assert!(res
+            .error
+            .as_deref()
+            .unwrap_or_default()
+            .contains("empty shortcut"));
+
+        // Validation happens before any shortcut unregister/register calls.
+        assert_eq!(*ctx.shortcut_unregistered.lock().unwrap(), 0);
+        assert_eq!(*ctx.shortcut_registered.lock().unwrap(), 0);
+        assert_eq!(state.settings().shortcut, Settings::default().shortcut);
+    }
+
+    #[test]
+    fn update_settings_normalizes_unknown_language_to_default() {
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
+
+        let mut settings = state.settings();
+        settings.language = "fr".into();
+
+        let res = update_settings_impl(&ctx, &state, settings);
+        assert!(res.ok);
+        assert_eq!(state.settings().language, Settings::default().language);
+    }
+
+    #[test]
+    fn quick_capture_defaults_apply_to_undated_tasks_and_parsed_times() {
+        use crate::clock::{AppClock, FixedClock};
+
+        let ctx = TestCtx::new();
+        let morning = Local
+            .with_ymd_and_hms(2026, 3, 10, 9, 0, 0)
+            .unwrap()
+            .timestamp();
+        let state = AppState::with_clock(
+            Vec::new(),
+            Vec::new(),
+            Settings::default(),
+            AppClock::new(std::sync::Arc::new(FixedClock(morning))),
+        );
+        let at = |hour: u32, minute: u32| {
+            Local
+                .with_ymd_and_hms(2026, 3, 10, hour, minute, 0)
+                .unwrap()
+                .timestamp()
+        };
+
+        let mut settings = state.settings();
+        settings.quick_default_due_time = "17:30".into();
+        settings.quick_due_rounding_minutes = 15;
+        assert!(update_settings_impl(&ctx, &state, settings.clone()).ok);
+
+        let undated = Task {
+            due_at: None,
+            ..make_task("undated", 0)
+        };
+        let created = create_task_impl(&ctx, &state, undated).data.unwrap();
+        assert_eq!(created.due_at, Some(at(17, 30)));
+        // Explicit due dates are stored as given.
+        let created = create_task_impl(&ctx, &state, make_task("dated", at(10, 7)))
+            .data
+            .unwrap();
+        assert_eq!(created.due_at, Some(at(10, 7)));
+
+        assert_eq!(
+            resolve_quick_due_impl(&state, Some(at(10, 7))).data,
+            Some(Some(at(10, 0)))
+        );
+        assert_eq!(
+            resolve_quick_due_impl(&state, None).data,
+            Some(Some(at(17, 30)))
+        );
+
+        settings.quick_due_rounding_minutes = 20;
+        let res = update_settings_impl(&ctx, &state, settings);
+        assert!(res.error.unwrap().contains("invalid due rounding"));
+        assert_eq!(state.settings().quick_due_rounding_minutes, 15);
+    }
+
+    #[test]
+    fn someday_backlog_commands_move_list_and_capture() {
+        use crate::clock::{AppClock, FixedClock};
+
+        let ctx = TestCtx::new();
+        let at = |day: u32, hour: u32| {
+            Local
+                .with_ymd_and_hms(2026, 3, day, hour, 0, 0)
+                .unwrap()
+                .timestamp()
+        };
+        let mut fired = make_task("a", at(9, 9));
+        fired.reminder.kind = ReminderKind::Normal;
+        fired.reminder.last_fired_at = Some(at(9, 9));
+        let state = AppState::with_clock(
+            vec![fired, make_task("b", at(11, 9))],
+            Vec::new(),
+            Settings::default(),
+            AppClock::new(std::sync::Arc::new(FixedClock(at(10, 9)))),
+        );
+
+        let parked = move_to_someday_impl(&ctx, &state, "a".into()).data.unwrap();
+        assert!(parked.someday);
+        assert!(!parked.is_overdue(at(10, 9), 0));
+        let listed = list_someday_tasks_impl(&state).data.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, "a");
+        let saved = Storage::new(ctx.root_path().to_path_buf())
+            .load_tasks()
+            .unwrap();
+        assert!(saved.tasks.iter().any(|t| t.id == "a" && t.someday));
+
+        // Without a date the task lands on today's default due time; the old reminder is reset.
+        let back = move_from_someday_impl(&ctx, &state, "a".into(), None)
+            .data
+            .unwrap();
+        assert!(!back.someday);
+        assert_eq!(back.due_at, Some(at(10, 18)));
+        assert_eq!(back.reminder.last_fired_at, None);
+        assert!(list_someday_tasks_impl(&state).data.unwrap().is_empty());
+
+        move_to_someday_impl(&ctx, &state, "b".into());
+        let back = move_from_someday_impl(&ctx, &state, "b".into(), Some(at(12, 8)))
+            .data
+            .unwrap();
+        assert_eq!(back.due_at, Some(at(12, 8)));
+
+        let res = move_to_someday_impl(&ctx, &state, "missing".into());
+        assert_eq!(res.error.as_deref(), Some("task not found"));
+        let res = move_from_someday_impl(&ctx, &state, "missing".into(), None);
+        assert_eq!(res.error.as_deref(), Some("task not found"));
+
+        // The Someday capture policy files undated tasks straight into the backlog.
+        let mut settings = state.settings();
+        settings.quick_no_date_policy = NoDatePolicy::Someday;
+        assert!(update_settings_impl(&ctx, &state, settings).ok);
+        let undated = Task {
+            due_at: None,
+            ..make_task("undated", 0)
+        };
+        let created = create_task_impl(&ctx, &state, undated).data.unwrap();
+        assert!(created.someday);
+        assert_eq!(created.due_at, None);
+        assert_eq!(resolve_quick_due_impl(&state, None).data, Some(None));
+        assert_eq!(
+            move_from_someday_impl(&ctx, &state, "undated".into(), None)
+                .data
+                .unwrap()
+                .due_at,
+            Some(at(10, 18))
+        );
+
+        let (markdown, counts) = render_tasks_markdown(&state, &ExportFilter::default(), false);
+        assert!(markdown.contains("## Someday\n\n_Empty_"));
+        assert_eq!(counts[3], 0);
+        move_to_someday_impl(&ctx, &state, "b".into());
+        let (markdown, counts) = render_tasks_markdown(&state, &ExportFilter::default(), false);
+        assert!(markdown.contains("## Someday\n\n- [ ] task-b"));
+        assert_eq!(counts[3], 1);
+
+        let ctx_fail = TestCtx::new();
+        fs::write(ctx_fail.root_path().join("backups"), b"x").unwrap();
+        assert!(!move_to_someday_impl(&ctx_fail, &state, "a".into()).ok);
+        assert!(!move_from_someday_impl(&ctx_fail, &state, "b".into(), None).ok);
+    }
+
+    #[test]
+    fn update_settings_validates_quadrant_config_and_auto_classifies_tasks() {
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
+
+        let mut invalid = state.settings();
+        invalid.quadrant_config.quadrants[0].color = "nope".into();
+        let res = update_settings_impl(&ctx, &state, invalid);
+        assert!(res.error.unwrap().contains("invalid quadrant config"));
+        assert_eq!(
+            state.settings().quadrant_config.quadrants[0].color,
+            "#C94D37"
+        );
+
+        let mut settings = state.settings();
+        settings.quadrant_config.auto_classify = true;
+        settings.quadrant_config.quadrants[0].name = " Now ".into();
+        assert!(update_settings_impl(&ctx, &state, settings).ok);
+        assert_eq!(state.settings().quadrant_config.quadrants[0].name, "Now");
+
+        // Overdue + important lands in quadrant 1 regardless of the submitted value.
+        let mut task = make_task("q", 1000);
+        task.important = true;
+        task.quadrant = 4;
+        let created = create_task_impl(&ctx, &state, task).data.unwrap();
+        assert_eq!(created.quadrant, 1);
+
+        let mut edited = created.clone();
+        edited.important = false;
+        let updated = update_task_impl(&ctx, &state, edited).data.unwrap();
+        assert_eq!(updated.quadrant, 3);
+    }
+
+    #[test]
+    fn snooze_dismiss_and_delete_cover_found_not_found_and_persist_error() {
+        let ctx = TestCtx::new();
+        let mut ignored = make_task("a", 1000);
+        ignored.reminder.ignored_count = 2;
+        let state = make_state(vec![ignored, make_task("b", 2000)]);
+
+        // snooze_task: found + not found.
+        let res = snooze_task_impl(&ctx, &state, "a".into(), 1234);
+        assert!(res.ok);
+        let a = state.tasks().into_iter().find(|t| t.id == "a").unwrap();
+        assert_eq!(a.reminder.snoozed_until, Some(1234));
+        assert!(a.reminder.last_fired_at.is_some());
+        assert_eq!(a.reminder.ignored_count, 0);
+
+        let res = snooze_task_impl(&ctx, &state, "missing".into(), 1);
+        assert!(res.ok);
+
+        // set_do_not_disturb: stored in settings and persisted.
+        assert!(
+            set_do_not_disturb_impl(&ctx, &state, true)
+                .data
+                .unwrap()
+                .dnd_enabled
+        );
+        assert!(state.settings().dnd_enabled);
+
+        // snooze_task_preset: the time comes from the preset and is returned.
+        let until = snooze_task_preset_impl(&ctx, &state, "b".into(), SnoozePreset::InOneHour)
+            .data
+            .unwrap();
+        assert_eq!(until, state.clock().timestamp() + 3600);
+        let b = state.tasks().into_iter().find(|t| t.id == "b").unwrap();
+        assert_eq!(b.reminder.snoozed_until, Some(until));
+
+        // notification_action: snooze and complete go through the same paths; a missing task
+        // can not be completed.
+        let now = state.clock().timestamp();
+        assert!(
+            notification_action_impl(
+                &ctx,
+                &state,
+                "b".into(),
+                NotificationAction::SnoozeTenMinutes
+            )
+            .ok
+        );
+        let b = state.tasks().into_iter().find(|t| t.id == "b").unwrap();
+        assert_eq!(b.reminder.snoozed_until, Some(now + 600));
+        assert!(
+            notification_action_impl(&ctx, &state, "b".into(), NotificationAction::Complete).ok
+        );
+        assert!(state.tasks().iter().any(|t| t.id == "b" && t.completed));
+        assert_eq!(
+            notification_action_impl(&ctx, &state, "missing".into(), NotificationAction::Complete)
+                .error
+                .as_deref(),
+            Some("task not found")
+        );
+
+        // dismiss_forced: found + not found.
+        let res = dismiss_forced_impl(&ctx, &state, "a".into());
+        assert!(res.ok);
+        let a = state.tasks().into_iter().find(|t| t.id == "a").unwrap();
+        assert!(a.reminder.forced_dismissed);
+
+        let res = dismiss_forced_impl(&ctx, &state, "missing".into());
+        assert!(res.ok);
+
+        // delete_task + delete_tasks.
+        let res = delete_task_impl(&ctx, &state, "a".into());
+        assert!(res.ok);
+        assert!(state.tasks().iter().all(|t| t.id != "a"));
+
+        let res = delete_tasks_impl(&ctx, &state, vec!["b".into(), "missing".into()]);
+        assert!(res.ok);
+        assert!(state.tasks().is_empty());
+
+        // Persist error path: make backups a file so ensure_dirs fails.
+        let ctx_fail = TestCtx::new();
+        fs::write(ctx_fail.root_path().join("backups"), b"x").unwrap();
+        let state_fail = make_state(vec![make_task("x", 1)]);
+        assert!(!snooze_task_impl(&ctx_fail, &state_fail, "x".into(), 1).ok);
+        assert!(
+            !snooze_task_preset_impl(&ctx_fail, &state_fail, "x".into(), SnoozePreset::NextMonday)
+                .ok
+        );
+        assert!(!dismiss_forced_impl(&ctx_fail, &state_fail, "x".into()).ok);
+        assert!(!set_do_not_disturb_impl(&ctx_fail, &state_fail, true).ok);
+        assert!(!delete_task_impl(&ctx_fail, &state_fail, "x".into()).ok);
+        assert!(!delete_tasks_impl(&ctx_fail, &state_fail, vec!["x".into()]).ok);
+    }
+
+    #[test]
+    fn deleted_tasks_go_to_trash_and_can_be_restored_or_emptied() {
+        let ctx = TestCtx::new();
+        let state = make_state(vec![
+            make_task("a", 1000),
+            make_task("b", 1000),
+            make_task("c", 1000),
+        ]);
+        assert!(delete_task_impl(&ctx, &state, "a".into()).ok);
+        assert!(delete_tasks_impl(&ctx, &state, vec!["b".into(), "c".into()]).ok);
+        assert!(state.tasks().is_empty());
+        let trash = list_trash_impl(&state).data.unwrap();
+        assert_eq!(trash.len(), 3);
+        let saved = Storage::new(ctx.root_path().to_path_buf())
+            .load_tasks()
+            .unwrap();
+        assert_eq!(saved.trash.len(), 3);
+
+        // Undoing the delete takes the task back out of the trash.
+        assert!(undo_last_action_impl(&ctx, &state).ok);
+        assert_eq!(state.trash().len(), 1);
+        assert_eq!(state.tasks().len(), 2);
+
+        let restored = restore_from_trash_impl(&ctx, &state, vec!["a".into()]);
+        assert_eq!(restored.data.unwrap()[0].id, "a");
+        assert!(state.trash().is_empty());
+        assert!(!restore_from_trash_impl(&ctx, &state, vec!["a".into()]).ok);
+
+        assert!(delete_tasks_impl(&ctx, &state, vec!["a".into(), "b".into()]).ok);
+        assert_eq!(
+            empty_trash_impl(&ctx, &state, Some(vec!["a".into()])).data,
+            Some(1)
+        );
+        assert_eq!(empty_trash_impl(&ctx, &state, None).data, Some(1));
+        assert!(list_trash_impl(&state).data.unwrap().is_empty());
+
+        let ctx_fail = TestCtx::new();
+        fs::write(ctx_fail.root_path().join("backups"), b"x").unwrap();
+        assert!(!empty_trash_impl(&ctx_fail, &state, None).ok);
+        assert!(delete_task_impl(&ctx, &state, "c".into()).ok);
+        assert!(!restore_from_trash_impl(&ctx_fail, &state, vec!["c".into()]).ok);
+    }
+
+    #[test]
+    fn maintenance_archives_old_completed_tasks_that_can_be_searched_and_unarchived() {
+        let ctx = TestCtx::new();
+        let mut old = make_task("old", 1000);
+        old.completed = true;
+        old.completed_at = Some(1000);
+        old.updated_at = 1000;
+        let state = make_state(vec![old, make_task("open", 1000)]);
+
+        let report = run_maintenance_now_impl(&ctx, &state).data.unwrap();
+        assert_eq!(report.archived_tasks, 1);
+        assert_eq!(state.tasks().len(), 1);
+        let saved = Storage::new(ctx.root_path().to_path_buf())
+            .load_tasks()
+            .unwrap();
+        assert_eq!(saved.tasks.len(), 1);
+        let archived = list_archived_impl(&ctx, None).data.unwrap();
+        assert_eq!(archived[0].id, "old");
+        let hits = search_archive_impl(&ctx, "task-old".into(), None)
+            .data
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+
+        let restored = unarchive_task_impl(&ctx, &state, "old".into())
+            .data
+            .unwrap();
+        assert!(restored.completed && restored.updated_at > 1000);
+        assert!(state.task("old").is_some());
+        assert!(list_archived_impl(&ctx, None).data.unwrap().is_empty());
+        assert!(!unarchive_task_impl(&ctx, &state, "old".into()).ok);
+        // Brought back counts as changed, so the next run leaves it alone.
+        let report = run_maintenance_now_impl(&ctx, &state).data.unwrap();
+        assert_eq!(report.archived_tasks, 0);
+
+        let bad = TestCtx::with_app_data_dir_error("nope");
+        assert!(!list_archived_impl(&bad, None).ok);
+        assert!(!search_archive_impl(&bad, "x".into(), None).ok);
+        assert!(!unarchive_task_impl(&bad, &state, "old".into()).ok);
+    }
+
+    #[test]
+    fn archived_tasks_still_count_in_statistics_focus_time_and_year_review() {
+        use crate::clock::{AppClock, FixedClock};
+
+        let ctx = TestCtx::new();
+        let now = Local
+            .with_ymd_and_hms(2026, 3, 10, 9, 0, 0)
+            .unwrap()
+            .timestamp();
+        let done_at = now - 100 * 86_400;
+        let done = Task {
+            completed: true,
+            completed_at: Some(done_at),
+            updated_at: done_at,
+            time_entries: vec![crate::models::TimeEntry {
+                started_at: done_at - 600,
+                ended_at: Some(done_at),
+            }],
+            created_at: done_at - 3600,
+            ..make_task("done", done_at)
+        };
+        let state = AppState::with_clock(
+            vec![done, make_task("open", now)],
+            Vec::new(),
+            Settings::default(),
+            AppClock::new(std::sync::Arc::new(FixedClock(now))),
+        );
+        let from = Some(now - 200 * 86_400);
+
+        let report = run_maintenance_now_impl(&ctx, &state).data.unwrap();
+        assert_eq!(report.archived_tasks, 1);
+        assert!(state.task("done").is_none());
+
+        let stats = get_statistics_impl(&ctx, &state, from, None).data.unwrap();
+        assert_eq!((stats.created, stats.completed), (1, 1));
+        assert_eq!(stats.completion_rate, Some(0.5));
+        let focus = get_focus_stats_impl(&ctx, &state, from, None).data.unwrap();
+        assert_eq!(focus.tracked_seconds, 600);
+        let review = export_year_review_impl(&ctx, &state, 2025).data.unwrap();
+        assert_eq!(review.review.total_completed, 1);
+        // Ranges that start after the archived year leave its file alone.
+        let stats = get_statistics_impl(&ctx, &state, None, None).data.unwrap();
+        assert_eq!(stats.completed, 0);
+
+        let bad = TestCtx::with_app_data_dir_error("nope");
+        assert!(!get_statistics_impl(&bad, &state, from, None).ok);
+        assert!(!get_burndown_impl(&bad, &state, None, None).ok);
+    }
+
+    #[test]
+    fn backup_commands_list_create_restore_and_import_cover_paths() {
+        // list_backups app_data_dir error.
+        let bad_ctx = TestCtx::with_app_data_dir_error("nope");
+        assert!(!list_backups_impl(&bad_ctx).ok);
+
+        // list_backups NotFound => ensure_dirs + empty list.
+        let ctx = TestCtx::new();
+        let res = list_backups_impl(&ctx);
+        assert!(res.ok);
+        assert!(res.data.unwrap().is_empty());
+
+        // list_backups NotFound + ensure_dirs failure => error.
+        let mut ctx_not_dir = TestCtx::new();
+        let root_file = ctx_not_dir.root_path().join("not-a-dir");
+        fs::write(&root_file, b"x").unwrap();
+        ctx_not_dir.set_app_data_dir_override(root_file);
+        assert!(!list_backups_impl(&ctx_not_dir).ok);
+
+        // list_backups invalid backups path => error.
+        let ctx2 = TestCtx::new();
+        fs::write(ctx2.root_path().join("backups"), b"x").unwrap();
+        assert!(!list_backups_impl(&ctx2).ok);
+
+        // list_backups Ok path with at least one entry.
+        let ctx3 = TestCtx::new();
+        fs::create_dir_all(ctx3.root_path().join("backups")).unwrap();
+        fs::write(
+            ctx3.root_path().join("backups").join("data-test.json"),
+            b"{}",
+        )
+        .unwrap();
+        let res = list_backups_impl(&ctx3);
+        assert!(res.ok);
+        assert!(res
+            .data
+            .unwrap()
+            .iter()
+            .any(|entry| entry.name == "data-test.json"));
+
+        // delete_backup covers error + success.
+        let bad_ctx = TestCtx::with_app_data_dir_error("nope");
+        assert!(!delete_backup_impl(&bad_ctx, "data-test.json".into()).ok);
+
+        let ctx_del_fail = TestCtx::new();
+        fs::write(ctx_del_fail.root_path().join("backups"), b"x").unwrap();
+        assert!(!delete_backup_impl(&ctx_del_fail, "data-test.json".into()).ok);
+
+        let ctx_del_invalid = TestCtx::new();
+        fs::create_dir_all(ctx_del_invalid.root_path().join("backups")).unwrap();
+        assert!(!delete_backup_impl(&ctx_del_invalid, "../data-test.json".into()).ok);
+
+        let ctx_del_ok = TestCtx::new();
+        fs::create_dir_all(ctx_del_ok.root_path().join("backups")).unwrap();
+        fs::write(
+            ctx_del_ok
+                .root_path()
+                .join("backups")
+                .join("data-test.json"),
+            b"{}",
+        )
+        .unwrap();
+        let res = delete_backup_impl(&ctx_del_ok, "data-test.json".into());
+        assert!(res.ok);
+
+        // create_backup covers error branches + success.
+        let state = make_state(vec![make_task("a", 1000)]);
+
+        let bad_ctx = TestCtx::with_app_data_dir_error("nope");
+        assert!(!create_backup_impl(&bad_ctx, &state).ok);
+
+        let ctx4 = TestCtx::new();
+        fs::write(ctx4.root_path().join("backups"), b"x").unwrap();
+        assert!(!create_backup_impl(&ctx4, &state).ok);
+
+        let ctx5 = TestCtx::new();
+        fs::create_dir_all(ctx5.root_path().join("data.json")).unwrap();
+        assert!(!create_backup_impl(&ctx5, &state).ok);
+
+        let ctx6 = TestCtx::new();
+        fs::create_dir_all(ctx6.root_path().join("settings.json")).unwrap();
+        assert!(!create_backup_impl(&ctx6, &state).ok);
+
+        let ctx7 = TestCtx::new();
+        let state2 = make_state(vec![make_task("x", 1000)]);
+        let res = create_backup_impl(&ctx7, &state2);
+        assert!(res.ok);
+        assert!(state2.settings().last_backup_at.is_some());
+
+        // restore/import: app_data_dir error + ensure_dirs error.
+        let state_any = make_state(Vec::new());
+        let bad_ctx = TestCtx::with_app_data_dir_error("nope");
+        assert!(!restore_backup_impl(&bad_ctx, &state_any, "anything.json".into()).ok);
+        assert!(!import_backup_impl(&bad_ctx, &state_any, "anything.json".into()).ok);
+
+        let mut ctx_not_dir = TestCtx::new();
+        let root_file = ctx_not_dir.root_path().join("not-a-dir");
+        fs::write(&root_file, b"x").unwrap();
+        ctx_not_dir.set_app_data_dir_override(root_file);
+        assert!(!restore_backup_impl(&ctx_not_dir, &state_any, "anything.json".into()).ok);
+        assert!(!import_backup_impl(&ctx_not_dir, &state_any, "anything.json".into()).ok);
+
+        // restore_backup: error + success.
+        let ctx_restore = TestCtx::new();
+        let state_restore_src = make_state(vec![make_task("r", 1000)]);
+        persist(&ctx_restore, &state_restore_src).unwrap();
+
+        let storage = Storage::new(ctx_restore.root_path().to_path_buf());
+        storage.ensure_dirs().unwrap();
+        storage
+            .create_backup(&ctx_restore.root_path().join("data.json"))
+            .unwrap();
+        let backup_name = storage.list_backups().unwrap()[0].0.clone();
+
+        let state_restore_dst = make_state(Vec::new());
+        assert!(!restore_backup_impl(&ctx_restore, &state_restore_dst, "missing.json".into()).ok);
+        let res = restore_backup_impl(&ctx_restore, &state_restore_dst, backup_name);
+        assert!(res.ok);
+        assert_eq!(state_restore_dst.tasks().len(), 1);
+        assert!(!ctx_restore.emitted.lock().unwrap().is_empty());
+
+        // import_backup: error + success.
+        let external = ctx_restore.root_path().join("external.json");
+        fs::write(
+            &external,
+            serde_json::to_string_pretty(&state_restore_src.tasks_file()).unwrap(),
+        )
+        .unwrap();
+        let state_import_dst = make_state(Vec::new());
+        let res = import_backup_impl(
+            &ctx_restore,
+            &state_import_dst,
+            external.to_string_lossy().to_string(),
+        );
+        assert!(res.ok);
+        assert_eq!(state_import_dst.tasks().len(), 1);
+        assert!(!import_backup_impl(&ctx_restore, &state_import_dst, "no-such-file".into()).ok);
+    }
+
+    #[test]
+    fn import_external_previews_then_appends_and_skips_reimports() {
+        let ctx = TestCtx::new();
+        let state = make_state(vec![make_task("keep", 1)]);
+        let path = ctx.root_path().join("ticktick.json");
+        fs::write(
+            &path,
+            r#"{"projects":[{"id":"p1","name":"Work"}],
+                "tasks":[{"id":"a","projectId":"p1","title":"Report","priority":5},
+                         {"id":"b","projectId":"p1","parentId":"a","title":"Send"}]}"#,
+        )
+        .unwrap();
+        let path = path.to_string_lossy().to_string();
+
+        let preview = import_external_impl(&ctx, &state, path.clone(), true);
+        let preview = preview.data.unwrap();
+        assert!(preview.dry_run);
+        assert_eq!(preview.changes.created.len(), 1);
+        assert_eq!(preview.changes.created[0].progress.steps_total, 1);
+        assert_eq!(preview.changes.projects_created.len(), 1);
+        assert_eq!(state.tasks().len(), 1);
+        assert!(ctx.emitted.lock().unwrap().is_empty());
+
+        let applied = import_external_impl(&ctx, &state, path.clone(), false)
+            .data
+            .unwrap();
+        assert!(!applied.dry_run);
+        assert_eq!(
+            applied.format,
+            crate::importers::ExternalFormat::TickTickJson
+        );
+        assert_eq!(applied.changes.created.len(), 1);
+        assert_eq!(state.tasks().len(), 2);
+        assert!(state.projects().iter().any(|p| p.name == "Work"));
+        assert_eq!(ctx.emitted.lock().unwrap().len(), 1);
+        let saved = Storage::new(ctx.root_path().to_path_buf())
+            .load_tasks()
+            .unwrap();
+        assert!(saved.tasks.iter().any(|task| task.id == "ticktick-a"));
+
+        let again = import_external_impl(&ctx, &state, path, false)
+            .data
+            .unwrap();
+        assert!(again.changes.is_empty());
+        assert!(again
+            .warnings
+            .iter()
+            .any(|w| w.contains("already imported")));
+        assert_eq!(state.tasks().len(), 2);
+
+        let missing = ctx.root_path().join("missing.csv");
+        let res = import_external_impl(&ctx, &state, missing.to_string_lossy().to_string(), true);
+        assert!(!res.ok);
+        let unknown = ctx.root_path().join("notes.txt");
+        fs::write(&unknown, "hello").unwrap();
+        let res = import_external_impl(&ctx, &state, unknown.to_string_lossy().to_string(), false);
+        assert!(res.error.unwrap().contains("unrecognized export"));
+
+        let bad_ctx = TestCtx::with_app_data_dir_error("nope");
+        let csv = ctx.root_path().join("Home.csv");
+        fs::write(
+            &csv,
+            "TYPE,CONTENT,DESCRIPTION,PRIORITY,INDENT\ntask,Water,,1,1\n",
+        )
+        .unwrap();
+        let res = import_external_impl(&bad_ctx, &state, csv.to_string_lossy().to_string(), false);
+        assert!(!res.ok);
+    }
+
+    #[test]
+    fn import_markdown_reads_pasted_text_or_a_file() {
+        let ctx = TestCtx::new();
+        let state = make_state(vec![make_task("keep", 1)]);
+        let notes = "## Launch\n- [ ] Write notes\n  - [ ] Ask legal\n- [x] Book demo\n";
+
+        let preview =
+            import_markdown_impl(&ctx, &state, notes.to_string(), MarkdownHeadings::Tag, true)
+                .data
+                .unwrap();
+        assert!(preview.dry_run);
+        assert_eq!(preview.changes.created.len(), 2);
+        assert_eq!(preview.changes.created[0].tags, vec!["launch"]);
+        assert_eq!(preview.changes.created[0].progress.steps_total, 1);
+        assert_eq!(state.tasks().len(), 1);
+
+        let path = ctx.root_path().join("meeting.md");
+        fs::write(&path, notes).unwrap();
+        let applied = import_markdown_impl(
+            &ctx,
+            &state,
+            path.to_string_lossy().to_string(),
+            MarkdownHeadings::Project,
+            false,
+        )
+        .data
+        .unwrap();
+        assert_eq!(applied.changes.created.len(), 2);
+        assert_eq!(applied.changes.projects_created.len(), 1);
+        assert_eq!(applied.changes.projects_created[0].name, "Launch");
+        assert_eq!(state.tasks().len(), 3);
+        assert_eq!(ctx.emitted.lock().unwrap().len(), 1);
+
+        let res = import_markdown_impl(
+            &ctx,
+            &state,
+            "just some prose".to_string(),
+            MarkdownHeadings::Project,
+            false,
+        );
+        assert!(res.error.unwrap().contains("no checkbox items"));
+    }
+
+    #[test]
+    fn export_commands_write_files_and_return_paths() {
+        let ctx = TestCtx::new();
+        let state = make_state(vec![make_task("a", 123)]);
+
+        let json = export_tasks_json_impl(&ctx, &state, ExportFilter::default());
+        assert!(json.ok);
+        let json_path = json.data.unwrap();
+        assert!(std::path::Path::new(&json_path).exists());
+        let json_text = std::fs::read_to_string(&json_path).unwrap();
+        assert!(json_text.contains("\"tasks\""));
+        let exported: serde_json::Value = serde_json::from_str(&json_text).unwrap();
+        assert_eq!(exported["quadrants"][0]["name"], "Do First");
+        // The export stays importable as a plain TasksFile.
+        let reimported: TasksFile = serde_json::from_str(&json_text).unwrap();
+        assert_eq!(reimported.tasks.len(), 1);
+
+        let csv = export_tasks_csv_impl(&ctx, &state, ExportFilter::default());
+        assert!(csv.ok);
+        let csv_path = csv.data.unwrap();
+        assert!(std::path::Path::new(&csv_path).exists());
+        let csv_text = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(csv_text
+            .lines()
+            .next()
+            .unwrap()
+            .contains("id,project_id,title,due_at"));
+        assert!(csv_text.contains(",1,\"Do First\","));
+
+        let md = export_tasks_markdown_impl(&ctx, &state, ExportFilter::default());
+        assert!(md.ok);
+        let md_path = md.data.unwrap();
+        assert!(std::path::Path::new(&md_path).exists());
+        let md_text = std::fs::read_to_string(&md_path).unwrap();
+        assert!(md_text.contains("# MustDo Export"));
+        assert!(md_text.contains("## Overdue"));
+        assert!(md_text.contains("  - quadrant: Do First\n"));
+    }
+
+    #[test]
+    fn export_tasks_content_matches_file_exports_without_writing() {
+        let ctx = TestCtx::new();
+        let state = make_state(vec![make_task("a", 123)]);
+
+        let json =
+            export_tasks_content_impl(&ctx, &state, ExportFormat::Json, ExportFilter::default())
+                .data
+                .unwrap();
+        assert_eq!(json.mime_type, "application/json");
+        let path = export_tasks_json_impl(&ctx, &state, ExportFilter::default())
+            .data
+            .unwrap();
+        assert_eq!(json.content, fs::read_to_string(path).unwrap());
+
+        let csv =
+            export_tasks_content_impl(&ctx, &state, ExportFormat::Csv, ExportFilter::default())
+                .data
+                .unwrap();
+        assert_eq!(csv.format, ExportFormat::Csv);
+        assert!(csv
+            .content
+            .starts_with("id,project_id,title,due_at,start_at,"));
+        assert!(csv.content.contains("\"task-a\""));
+
+        let md = export_tasks_content_impl(
+            &ctx,
+            &state,
+            ExportFormat::Markdown,
+            ExportFilter::default(),
+        )
+        .data
+        .unwrap();
+        assert_eq!(md.mime_type, "text/markdown");
+        assert!(md.content.contains("# MustDo Export"));
+
+        // Only the explicit file export above touched exports/.
+        let exported = fs::read_dir(ctx.root_path().join("exports"))
+            .unwrap()
+            .count();
+        assert_eq!(exported, 1);
+
+        let forced = ForceJsonErrorCtx::new();
+        let res =
+            export_tasks_content_impl(&forced, &state, ExportFormat::Json, ExportFilter::default());
+        assert!(res.error.unwrap().starts_with("json error"));
+        assert_eq!(
+            serde_json::from_str::<ExportFormat>("\"markdown\"").unwrap(),
+            ExportFormat::Markdown
+        );
+    }
+
+    #[test]
+    fn markdown_export_follows_the_language_setting() {
+        let ctx = TestCtx::new();
+        let state = make_state(vec![make_task("a", 123)]);
+        let render = |language: &str| {
+            let mut settings = state.settings();
+            settings.language = language.to_string();
+            state.update_settings(settings);
+            export_tasks_content_impl(
+                &ctx,
+                &state,
+                ExportFormat::Markdown,
+                ExportFilter::default(),
+            )
+            .data
+            .unwrap()
+            .content
+        };
+
+        let zh = render("zh");
+        assert!(zh.starts_with("# MustDo 导出\n"));
+        assert!(zh.contains("## 已逾期\n"));
+        assert!(zh.contains("## 今天到期\n\n_无_"));
+        assert!(zh.contains("(截止: "));
+        let en = render("en");
+        assert!(en.contains("## Overdue\n"));
+        assert!(en.contains("(due: "));
+    }
+
+    #[test]
+    fn exports_apply_the_optional_filter() {
+        let ctx = TestCtx::new();
+        let mut work = make_task("work", 123);
+        work.project_id = "p1".to_string();
+        let project = Project {
+            id: "p1".to_string(),
+            name: "Work".to_string(),
+            pinned: false,
+            sort_order: 1,
+            created_at: 1,
+            updated_at: 1,
+            sample_tag: None,
+            reminders_muted: false,
+            muted_until: None,
+            notification_sound: None,
+            color: None,
+            icon: None,
+            parent_id: None,
+        };
+        let state = AppState::new(
+            vec![work, make_task("home", 123)],
+            vec![project],
+            Settings::default(),
+        );
+        let filter: ExportFilter = serde_json::from_str(r#"{"project_id":"p1"}"#).unwrap();
+
+        let path = export_tasks_json_impl(&ctx, &state, filter.clone())
+            .data
+            .unwrap();
+        let data: TasksFile = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(data.tasks.len(), 1);
+        assert_eq!(data.projects.len(), 1);
+
+        let path = export_tasks_csv_impl(&ctx, &state, filter.clone())
+            .data
+            .unwrap();
+        let csv = fs::read_to_string(path).unwrap();
+        assert!(csv.contains("\"task-work\"") && !csv.contains("\"task-home\""));
+
+        let path = export_tasks_markdown_impl(&ctx, &state, filter.clone())
+            .data
+            .unwrap();
+        let md = fs::read_to_string(path).unwrap();
+        assert!(md.contains("task-work") && !md.contains("task-home"));
+
+        let content = export_tasks_content_impl(&ctx, &state, ExportFormat::Csv, filter)
+            .data
+            .unwrap();
+        assert_eq!(content.content.lines().count(), 2);
+    }
+
+    #[test]
+    fn export_tasks_ics_writes_a_calendar_of_matching_tasks() {
+        let ctx = TestCtx::new();
+        let mut weekly = make_task("weekly", 1_700_000_000);
+        weekly.repeat = RepeatRule::Weekly { days: vec![1] };
+        let mut done = make_task("done", 1_700_000_000);
+        done.completed = true;
+        done.completed_at = Some(1_700_000_100);
+        let state = make_state(vec![weekly, done]);
+
+        let path = export_tasks_ics_impl(&ctx, &state, ExportFilter::default())
+            .data
+            .unwrap();
+        assert!(path.ends_with(".ics"));
+        let ics = fs::read_to_string(path).unwrap();
+        assert_eq!(ics.matches("BEGIN:VTODO").count(), 2);
+        assert!(ics.contains("RRULE:FREQ=WEEKLY;BYDAY=MO"));
+
+        let open: ExportFilter = serde_json::from_str(r#"{"status":"open"}"#).unwrap();
+        let content = export_tasks_content_impl(&ctx, &state, ExportFormat::Ics, open)
+            .data
+            .unwrap();
+        assert_eq!(content.mime_type, "text/calendar");
+        assert_eq!(content.content.matches("BEGIN:VTODO").count(), 1);
+        assert!(content.content.contains("UID:weekly"));
+    }
+
+    #[test]
+    fn export_markdown_keeps_tasks_inside_overdue_grace_in_today() {
+        let ctx = TestCtx::new();
+        let now_ts = Local::now().timestamp();
+        let state = make_state(vec![
+            make_task("just-started", now_ts - 5 * 60),
+            make_task("late", now_ts - 60 * 60),
+        ]);
+        let mut settings = state.settings();
+        settings.overdue_grace_minutes = 15;
+        state.update_settings(settings);
+
+        let md_path = export_tasks_markdown_impl(&ctx, &state, ExportFilter::default())
+            .data
+            .unwrap();
+        let md_text = std::fs::read_to_string(md_path).unwrap();
+        let overdue_start = md_text.find("## Overdue").unwrap();
+        let today_start = md_text.find("## Due today").unwrap();
+        let overdue_section = &md_text[overdue_start..today_start];
+        assert!(overdue_section.contains("task-late"));
+        assert!(!overdue_section.contains("task-just-started"));
+        assert!(md_text[today_start..].contains("task-just-started"));
+    }
+
+    #[test]
+    fn export_commands_fail_when_app_data_dir_is_not_a_directory() {
+        let mut ctx = TestCtx::new();
+        let file_root = ctx.root_path().join("not-a-dir");
+        std::fs::write(&file_root, b"x").unwrap();
+        ctx.set_app_data_dir_override(file_root);
+
+        let state = make_state(vec![make_task("a", 123)]);
+        let res = export_tasks_json_impl(&ctx, &state, ExportFilter::default());
+        assert!(!res.ok);
+    }
+
+    #[test]
+    fn write_atomic_bytes_covers_invalid_path_and_io_error_branches() {
+        // Invalid export path: no parent => ok_or_else branch.
+        let err = write_atomic_bytes(std::path::Path::new(""), b"x")
+            .expect_err("empty path should be rejected");
+        assert!(format!("{err}").contains("invalid export path"));
+        assert!(is_io(&err));
+
+        // Ensure both branches of `is_io` are exercised for coverage.
+        let json_err: StorageError = serde_json::from_str::<serde_json::Value>("oops")
+            .unwrap_err()
+            .into();
+        assert!(!is_io(&json_err));
+
+        // Force fs::write to fail by making the temp path a directory.
+        let root = tempfile::tempdir().unwrap();
+        let dest = root.path().join("out.json");
+        let tmp = dest.with_extension("tmp");
+        std::fs::create_dir_all(&tmp).unwrap();
+        let err = write_atomic_bytes(&dest, b"x").expect_err("writing to a directory should fail");
+        assert!(is_io(&err));
+
+        // Force fs::rename to fail by making the destination a directory.
+        let dest_dir = root.path().join("dest");
+        std::fs::create_dir_all(&dest_dir).unwrap();
+        let err =
+            write_atomic_bytes(&dest_dir, b"x").expect_err("renaming onto a directory should fail");
+        assert!(is_io(&err));
+    }
+
+    #[test]
+    fn bulk_update_tasks_updates_multiple_tasks_and_persists_once() {
+        let ctx = TestCtx::new();
+        let state = make_state(vec![make_task("a", 100), make_task("b", 200)]);
+
+        let mut a = make_task("a", 555);
+        a.quadrant = 3;
+        a.sort_order = 999;
+        let mut b = make_task("b", 666);
+        b.quadrant = 2;
+
+        let res = bulk_update_tasks_impl(&ctx, &state, vec![a, b]);
+        assert!(res.ok);
+
+        let tasks = state.tasks();
+        assert_eq!(tasks.len(), 2);
+        let a = tasks.iter().find(|t| t.id == "a").unwrap();
+        let b = tasks.iter().find(|t| t.id == "b").unwrap();
+        assert_eq!(a.due_at, Some(555));
+        assert_eq!(a.quadrant, 3);
+        assert_eq!(b.due_at, Some(666));
+        assert_eq!(b.quadrant, 2);
+
+        // One persist => one state_updated emission.
+        assert_eq!(ctx.emitted.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn dry_runs_report_changes_without_mutating_or_persisting() {
+        let ctx = TestCtx::new();
+        let state = make_state(vec![make_task("a", 100), make_task("b", 200)]);
+        let before = state.tasks();
+
+        let changes = delete_tasks_dry_run_impl(&state, vec!["a".to_string(), "zz".to_string()])
+            .data
+            .unwrap();
+        assert_eq!(changes.deleted.len(), 1);
+        assert_eq!(changes.deleted[0].id, "a");
+
+        let mut edited = make_task("b", 900);
+        edited.title = "renamed".to_string();
+        let changes = bulk_update_tasks_dry_run_impl(&state, vec![edited.clone()])
+            .data
+            .unwrap();
+        assert_eq!(changes.updated.len(), 1);
+        assert_eq!(changes.updated[0].fields, ["due_at", "title"]);
+        let mut bad = edited;
+        bad.color = Some("nope".to_string());
+        assert!(!bulk_update_tasks_dry_run_impl(&state, vec![bad]).ok);
+
+        // A backup holding only a new task: restoring would swap `a`/`b` for `c`.
+        let backup = make_state(vec![make_task("c", 300)]);
+        fs::create_dir_all(ctx.root_path().join("backups")).unwrap();
+        let backup_path = ctx.root_path().join("backups").join("data-test.json");
+        fs::write(
+            &backup_path,
+            serde_json::to_vec(&backup.tasks_file()).unwrap(),
+        )
+        .unwrap();
+        let changes = restore_backup_dry_run_impl(&ctx, &state, "data-test.json".to_string())
+            .data
+            .unwrap();
+        assert_eq!(changes.created.len(), 1);
+        assert_eq!(changes.deleted.len(), 2);
+        let imported = import_backup_dry_run_impl(&ctx, &state, backup_path.display().to_string());
+        assert_eq!(imported.data.unwrap().created[0].id, "c");
+        assert!(!restore_backup_dry_run_impl(&ctx, &state, "missing.json".to_string()).ok);
+        let bad_ctx = TestCtx::with_app_data_dir_error("nope");
+        assert!(!restore_backup_dry_run_impl(&bad_ctx, &state, "data-test.json".to_string()).ok);
+        assert!(!import_backup_dry_run_impl(&bad_ctx, &state, "x".to_string()).ok);
+
+        assert_eq!(
+            serde_json::to_value(state.tasks()).unwrap(),
+            serde_json::to_value(before).unwrap()
+        );
+        assert!(ctx.emitted.lock().unwrap().is_empty());
+        assert!(!ctx.root_path().join("data.json").exists());
+    }
+
+    #[test]
+    fn delete_dry_run_previews_exactly_what_delete_tasks_removes() {
+        let ctx = TestCtx::new();
+        let state = make_state(vec![make_task("a", 100), make_task("b", 200)]);
+        let ids = vec!["a".to_string(), "zz".to_string()];
+        let preview = delete_tasks_dry_run_impl(&state, ids.clone()).data.unwrap();
+        assert!(delete_tasks_impl(&ctx, &state, ids).ok);
+        let remaining: Vec<String> = state.tasks().iter().map(|t| t.id.clone()).collect();
+        let deleted: Vec<&str> = preview.deleted.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(deleted, ["a"]);
+        assert_eq!(remaining, ["b"]);
+    }
+
+    #[test]
+    fn with_dry_run_dispatches_to_preview_or_command() {
+        let state = make_state(vec![make_task("a", 100)]);
+        let preview = || delete_tasks_dry_run_impl(&state, vec!["a".to_string()]);
+        match with_dry_run(Some(true), preview, || ok(true)).data {
+            Some(Outcome::DryRun(changes)) => assert_eq!(changes.deleted.len(), 1),
+            other => panic!("unexpected {other:?}"),
+        }
+        let applied = with_dry_run(None, preview, || ok(true));
+        assert!(matches!(applied.data, Some(Outcome::Applied(true))));
+        let failed = with_dry_run(Some(false), preview, || err::<bool>("boom"));
+        assert_eq!(failed.error.as_deref(), Some("boom"));
+        assert_eq!(state.tasks().len(), 1);
+    }
+
+    #[test]
+    fn bulk_complete_tasks_marks_completed_and_spawns_next_for_repeat() {
+        let ctx = TestCtx::new();
+        let mut repeating = make_task("r", 1000);
+        repeating.repeat = RepeatRule::Daily {
+            workday_only: false,
+        };
+
+        let state = make_state(vec![make_task("a", 100), repeating.clone()]);
+        let res = bulk_complete_tasks_impl(&ctx, &state, vec!["a".to_string(), "r".to_string()]);
+        assert!(res.ok);
+
+        let tasks = state.tasks();
+        let a = tasks.iter().find(|t| t.id == "a").unwrap();
+        let r_done = tasks.iter().find(|t| t.id == "r").unwrap();
+        assert!(a.completed);
+        assert!(r_done.completed);
+
+        // A repeat task should spawn the next instance.
+        let expected_next_due =
+            crate::repeat::next_due_timestamp(repeating.due_at.unwrap(), &repeating.repeat);
+        let r_next = tasks
+            .iter()
+            .find(|t| t.id.starts_with("r-"))
+            .expect("next repeat task should exist");
+        assert!(!r_next.completed);
+        assert_eq!(r_next.due_at, Some(expected_next_due));
+
+        // One persist => one state_updated emission.
+        assert_eq!(ctx.emitted.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn project_commands_cover_create_update_swap_and_delete_paths() {
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
+
+        let project = Project {
+            id: "p1".to_string(),
+            name: "Project 1".to_string(),
+            pinned: false,
+            sort_order: 0,
+            created_at: 0,
+            updated_at: 0,
+            sample_tag: None,
+            reminders_muted: false,
+            muted_until: None,
+            notification_sound: None,
+            color: None,
+            icon: None,
+            parent_id: None,
+        };
+
+        let res = create_project_impl(&ctx, &state, project.clone());
+        assert!(res.ok);
+        assert!(state.projects().iter().any(|p| p.id == "p1"));
+
+        // Also cover the branches where created_at/sort_order are already set.
+        let preset = Project {
+            id: "p2".to_string(),
+            name: "Project 2".to_string(),
+            pinned: false,
+            sort_order: 42,
+            created_at: 123,
+            updated_at: 0,
+            sample_tag: None,
+            reminders_muted: false,
+            muted_until: None,
+            notification_sound: None,
+            color: None,
+            icon: None,
+            parent_id: None,
+        };
+        let res = create_project_impl(&ctx, &state, preset.clone());
+        assert!(res.ok);
+        let created = res.data.unwrap();
+        assert_eq!(created.id, "p2");
+        assert_eq!(created.created_at, 123);
+        assert_eq!(created.sort_order, 42);
+
+        let mut updated = project.clone();
+        updated.name = "Updated".to_string();
+        let res = update_project_impl(&ctx, &state, updated);
+        assert!(res.ok);
+        assert_eq!(
+            state.projects().iter().find(|p| p.id == "p1").unwrap().name,
+            "Updated"
+        );
+
+        let inbox_before = state
+            .projects()
+            .into_iter()
+            .find(|p| p.id == "inbox")
+            .unwrap();
+        let p1_before = state.projects().into_iter().find(|p| p.id == "p1").unwrap();
+
+        let res = swap_project_sort_order_impl(&ctx, &state, "inbox".into(), "p1".into());
+        assert!(res.ok);
+
+        let inbox_after = state
+            .projects()
+            .into_iter()
+            .find(|p| p.id == "inbox")
+            .unwrap();
+        let p1_after = state.projects().into_iter().find(|p| p.id == "p1").unwrap();
+        assert_eq!(inbox_after.sort_order, p1_before.sort_order);
+        assert_eq!(p1_after.sort_order, inbox_before.sort_order);
+
+        // update_project keeps inbox pinned.
+        let mut inbox = inbox_after.clone();
+        inbox.pinned = false;
+        inbox.name = "Renamed".to_string();
+        let res = update_project_impl(&ctx, &state, inbox);
+        assert!(res.ok);
+        assert!(
+            state
+                .projects()
+                .iter()
+                .find(|p| p.id == "inbox")
+                .unwrap()
+                .pinned
+        );
+        assert_eq!(
+            state
+                .projects()
+                .iter()
+                .find(|p| p.id == "inbox")
+                .unwrap()
+                .name,
+            inbox_after.name
+        );
+
+        // delete_project moves tasks to inbox first.
+        let mut task = make_task("x", 123);
+        task.project_id = "p1".to_string();
+        let res = create_task_impl(&ctx, &state, task);
+        assert!(res.ok);
+        assert_eq!(
+            state
+                .tasks()
+                .iter()
+                .find(|t| t.id == "x")
+                .unwrap()
+                .project_id,
+            "p1"
+        );
+
+        // Include a task that does not belong to the deleted project so both branches of the
+        // project_id check are exercised.
+        let res = create_task_impl(&ctx, &state, make_task("y", 456));
+        assert!(res.ok);
+        assert_eq!(
+            state
+                .tasks()
+                .iter()
+                .find(|t| t.id == "y")
+                .unwrap()
+                .project_id,
+            "inbox"
+        );
+
+        let res = delete_project_impl(&ctx, &state, "p1".to_string());
+        assert!(res.ok);
+        assert!(!state.projects().iter().any(|p| p.id == "p1"));
+        assert_eq!(
+            state
+                .tasks()
+                .iter()
+                .find(|t| t.id == "x")
+                .unwrap()
+                .project_id,
+            "inbox"
+        );
+        assert_eq!(
+            state
+                .tasks()
+                .iter()
+                .find(|t| t.id == "y")
+                .unwrap()
+                .project_id,
+            "inbox"
+        );
+
+        let res = delete_project_impl(&ctx, &state, "inbox".to_string());
+        assert!(!res.ok);
+    }
+
+    #[test]
+    fn project_commands_cover_validation_and_persist_error_paths() {
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
+
+        let base = Project {
+            id: "p1".to_string(),
+            name: "Project 1".to_string(),
+            pinned: false,
+            sort_order: 0,
+            created_at: 0,
+            updated_at: 0,
+            sample_tag: None,
+            reminders_muted: false,
+            muted_until: None,
+            notification_sound: None,
+            color: None,
+            icon: None,
+            parent_id: None,
+        };
+
+        // create_project validations.
+        let mut missing_id = base.clone();
+        missing_id.id = "   ".to_string();
+        let res = create_project_impl(&ctx, &state, missing_id);
+        assert!(!res.ok);
+
+        let mut missing_name = base.clone();
+        missing_name.name = "   ".to_string();
+        let res = create_project_impl(&ctx, &state, missing_name);
+        assert!(!res.ok);
+
+        // create_project duplicate check.
+        assert!(create_project_impl(&ctx, &state, base.clone()).ok);
+        let res = create_project_impl(&ctx, &state, base.clone());
+        assert!(!res.ok);
+
+        // create_project persist error.
+        let ctx_fail = TestCtx::new();
+        fs::write(ctx_fail.root_path().join("backups"), b"x").unwrap();
+        let state_fail = make_state(Vec::new());
+        let mut p2 = base.clone();
+        p2.id = "p2".to_string();
+        let res = create_project_impl(&ctx_fail, &state_fail, p2);
+        assert!(!res.ok);
+
+        // update_project validations and not-found.
+        let mut empty_id = base.clone();
+        empty_id.id = "   ".to_string();
+        let res = update_project_impl(&ctx, &state, empty_id);
+        assert!(!res.ok);
+
+        let mut empty_name = base.clone();
+        empty_name.name = "   ".to_string();
+        let res = update_project_impl(&ctx, &state, empty_name);
+        assert!(!res.ok);
+
+        let mut missing = base.clone();
+        missing.id = "missing".to_string();
+        let res = update_project_impl(&ctx, &state, missing);
+        assert!(!res.ok);
+
+        // notification_sound: blank clears, unknown ids are refused on create and update.
+        let mut chime = base.clone();
+        chime.notification_sound = Some(" chime ".to_string());
+        let res = update_project_impl(&ctx, &state, chime.clone());
+        assert_eq!(
+            res.data.unwrap().notification_sound.as_deref(),
+            Some("chime")
+        );
+        chime.notification_sound = Some("".to_string());
+        let res = update_project_impl(&ctx, &state, chime.clone());
+        assert_eq!(res.data.unwrap().notification_sound, None);
+        chime.notification_sound = Some("trumpet".to_string());
+        let res = update_project_impl(&ctx, &state, chime.clone());
+        assert_eq!(res.error.as_deref(), Some("unknown sound: trumpet"));
+        chime.id = "p9".to_string();
+        assert!(!create_project_impl(&ctx, &state, chime).ok);
+
+        // update_project persist error.
+        let ctx_fail2 = TestCtx::new();
+        fs::write(ctx_fail2.root_path().join("backups"), b"x").unwrap();
+        let state_fail2 = make_state(Vec::new());
+        state_fail2.add_project(base.clone());
+        let mut updated = base.clone();
+        updated.name = "Updated".to_string();
+        let res = update_project_impl(&ctx_fail2, &state_fail2, updated);
+        assert!(!res.ok);
+
+        // swap_project_sort_order errors.
+        let res = swap_project_sort_order_impl(&ctx, &state, "inbox".into(), "missing".into());
+        assert!(!res.ok);
+
+        let ctx_fail3 = TestCtx::new();
+        fs::write(ctx_fail3.root_path().join("backups"), b"x").unwrap();
+        let state_fail3 = make_state(Vec::new());
+        state_fail3.add_project(base.clone());
+        let res =
+            swap_project_sort_order_impl(&ctx_fail3, &state_fail3, "inbox".into(), "p1".into());
+        assert!(!res.ok);
+
+        // delete_project errors.
+        let res = delete_project_impl(&ctx, &state, "   ".to_string());
+        assert!(!res.ok);
+        let res = delete_project_impl(&ctx, &state, "missing".to_string());
+        assert!(!res.ok);
+
+        let ctx_fail4 = TestCtx::new();
+        fs::write(ctx_fail4.root_path().join("backups"), b"x").unwrap();
+        let state_fail4 = make_state(Vec::new());
+        state_fail4.add_project(base.clone());
+        let res = delete_project_impl(&ctx_fail4, &state_fail4, "p1".to_string());
+        assert!(!res.ok);
+    }
+
+    #[test]
+    fn query_tasks_returns_grouped_results_in_manual_order() {
+        use crate::models::TaskGroupBy;
+
+        let state = make_state(vec![make_task("b", 2000), make_task("a", 1000)]);
+        let mut tagged = make_task("c", 3000);
+        tagged.tags = vec!["work".to_string()];
+        tagged.sort_order = 1;
+        state.add_task(tagged);
+
+        let spec: TaskQuerySpec =
+            serde_json::from_value(serde_json::json!({ "group_by": "tag" })).unwrap();
+        assert_eq!(spec.group_by, TaskGroupBy::Tag);
+        let groups = query_tasks_impl(&state, spec).data.unwrap().groups;
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].key, "work");
+        let untagged: Vec<&str> = groups[1].tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(untagged, vec!["a", "b"]);
     }
 
     #[test]
-    fn auto_backup_predicates_cover_all_schedules() {
-        let now = Local
-            .with_ymd_and_hms(2024, 1, 2, 12, 0, 0)
-            .single()
-            .unwrap()
-            .timestamp();
-        let yesterday = Local
-            .with_ymd_and_hms(2024, 1, 1, 12, 0, 0)
-            .single()
-            .unwrap()
-            .timestamp();
+    fn view_prefs_are_stored_per_project_and_dropped_with_the_project() {
+        use crate::models::{TaskGroupBy, TaskSortKey};
 
-        let mut settings = Settings::default();
-        settings.backup_schedule = BackupSchedule::None;
-        settings.last_backup_at = None;
-        assert!(!should_auto_backup(&settings, now));
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
+        let project = Project {
+            id: "p1".to_string(),
+            name: "Project 1".to_string(),
+            pinned: false,
+            sort_order: 0,
+            created_at: 0,
+            updated_at: 0,
+            sample_tag: None,
+            reminders_muted: false,
+            muted_until: None,
+            notification_sound: None,
+            color: None,
+            icon: None,
+            parent_id: None,
+        };
+        assert!(create_project_impl(&ctx, &state, project).ok);
 
-        settings.backup_schedule = BackupSchedule::Daily;
-        settings.last_backup_at = None;
-        assert!(should_auto_backup(&settings, now));
-        settings.last_backup_at = Some(yesterday);
-        assert!(should_auto_backup(&settings, now));
-        settings.last_backup_at = Some(now);
-        assert!(!should_auto_backup(&settings, now));
+        assert_eq!(
+            get_view_prefs_impl(&state, "p1".into()).data.unwrap(),
+            ViewPrefs::default()
+        );
+        let prefs = ViewPrefs {
+            sort: TaskSortKey::Due,
+            group_by: TaskGroupBy::Tag,
+            show_completed: true,
+        };
+        assert!(set_view_prefs_impl(&ctx, &state, " p1 ".into(), prefs.clone()).ok);
+        assert_eq!(
+            get_view_prefs_impl(&state, "p1".into()).data.unwrap(),
+            prefs
+        );
+        assert_eq!(state.settings().view_prefs.len(), 1);
 
-        settings.backup_schedule = BackupSchedule::Weekly;
-        settings.last_backup_at = None;
-        assert!(should_auto_backup(&settings, now));
+        // Resetting to defaults removes the entry instead of storing defaults.
+        assert!(set_view_prefs_impl(&ctx, &state, "p1".into(), ViewPrefs::default()).ok);
+        assert!(state.settings().view_prefs.is_empty());
 
-        let week_start = Local
-            .with_ymd_and_hms(2024, 1, 1, 12, 0, 0)
-            .single()
-            .unwrap()
-            .timestamp();
-        let same_week = Local
-            .with_ymd_and_hms(2024, 1, 2, 12, 0, 0)
-            .single()
-            .unwrap()
-            .timestamp();
-        let next_week = Local
-            .with_ymd_and_hms(2024, 1, 8, 12, 0, 0)
-            .single()
-            .unwrap()
-            .timestamp();
-        settings.last_backup_at = Some(week_start);
-        assert!(!should_auto_backup(&settings, same_week));
-        assert!(should_auto_backup(&settings, next_week));
+        assert!(set_view_prefs_impl(&ctx, &state, "p1".into(), prefs.clone()).ok);
+        assert!(delete_project_impl(&ctx, &state, "p1".into()).ok);
+        assert!(state.settings().view_prefs.is_empty());
 
-        settings.backup_schedule = BackupSchedule::Monthly;
-        settings.last_backup_at = None;
-        assert!(should_auto_backup(&settings, now));
+        assert!(!get_view_prefs_impl(&state, "missing".into()).ok);
+        assert!(!set_view_prefs_impl(&ctx, &state, "missing".into(), prefs.clone()).ok);
 
-        let month_start = Local
-            .with_ymd_and_hms(2024, 1, 15, 12, 0, 0)
-            .single()
-            .unwrap()
-            .timestamp();
-        let same_month = Local
-            .with_ymd_and_hms(2024, 1, 20, 12, 0, 0)
-            .single()
-            .unwrap()
-            .timestamp();
-        let next_month = Local
-            .with_ymd_and_hms(2024, 2, 1, 12, 0, 0)
-            .single()
+        // Persist failures roll the in-memory settings back.
+        let failing = TestCtx::new();
+        fs::write(failing.root_path().join("backups"), b"x").unwrap();
+        let res = set_view_prefs_impl(&failing, &state, "inbox".into(), prefs);
+        assert!(res.error.unwrap().contains("storage error"));
+        assert!(state.settings().view_prefs.is_empty());
+    }
+
+    #[test]
+    fn smart_lists_are_saved_evaluated_and_follow_deleted_projects() {
+        use crate::models::DueFilter;
+
+        let ctx = TestCtx::new();
+        let mut waiting = make_task("waiting", 10_000);
+        waiting.waiting_on = Some("Ann".to_string());
+        let state = make_state(vec![make_task("plain", 10_000), waiting]);
+        let mut work = state.projects()[0].clone();
+        work.id = "work".to_string();
+        work.name = "Work".to_string();
+        assert!(create_project_impl(&ctx, &state, work).ok);
+
+        let list: SmartList = serde_json::from_value(serde_json::json!({
+            "id": " waiting ",
+            "name": "Waiting",
+            "waiting_only": true,
+            "project_ids": ["work"],
+            "due": { "kind": "any" }
+        }))
+        .unwrap();
+        let created = create_smart_list_impl(&ctx, &state, list.clone())
+            .data
+            .unwrap();
+        assert_eq!(created.id, "waiting");
+        assert_eq!(
+            create_smart_list_impl(&ctx, &state, list).error.as_deref(),
+            Some("smart list already exists")
+        );
+        assert!(evaluate_smart_list_impl(&state, "waiting".into())
+            .data
             .unwrap()
-            .timestamp();
-        settings.last_backup_at = Some(month_start);
-        assert!(!should_auto_backup(&settings, same_month));
-        assert!(should_auto_backup(&settings, next_month));
+            .is_empty());
+
+        // Deleting the project moves its tasks and its place in smart lists to the inbox.
+        assert!(delete_project_impl(&ctx, &state, "work".into()).ok);
+        let lists = list_smart_lists_impl(&state).data.unwrap();
+        assert_eq!(lists[0].project_ids, ["inbox"]);
+        let found = evaluate_smart_list_impl(&state, "waiting".into())
+            .data
+            .unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, "waiting");
+
+        // update_settings from a window does not touch the lists.
+        let mut settings = state.settings();
+        settings.smart_lists.clear();
+        assert!(update_settings_impl(&ctx, &state, settings).ok);
+        assert_eq!(state.settings().smart_lists.len(), 1);
+
+        let mut next = created;
+        next.waiting_only = false;
+        next.project_ids.clear();
+        next.due = DueFilter::NoDate;
+        let updated = update_smart_list_impl(&ctx, &state, next.clone())
+            .data
+            .unwrap();
+        assert_eq!(updated.due, DueFilter::NoDate);
+        next.id = "missing".to_string();
+        assert!(!update_smart_list_impl(&ctx, &state, next).ok);
+
+        let failing = TestCtx::new();
+        fs::write(failing.root_path().join("backups"), b"x").unwrap();
+        let res = delete_smart_list_impl(&failing, &state, "waiting".into());
+        assert!(res.error.unwrap().contains("storage error"));
+        assert_eq!(state.settings().smart_lists.len(), 1);
+        assert!(delete_smart_list_impl(&ctx, &state, "waiting".into()).ok);
+        assert!(!evaluate_smart_list_impl(&state, "waiting".into()).ok);
     }
 
     #[test]
-    fn persist_success_and_error_paths() {
+    fn tags_are_listed_renamed_and_merged_in_one_undoable_step() {
         let ctx = TestCtx::new();
-        let state = AppState::new(vec![make_task("a", 1000)], Vec::new(), Settings::default());
+        let mut a = make_task("a", 10_000);
+        a.tags = vec!["Job".to_string(), "home".to_string()];
+        let mut b = make_task("b", 10_000);
+        b.tags = vec!["office".to_string(), "work".to_string()];
+        let state = make_state(vec![a, b, make_task("c", 10_000)]);
+
+        let tags = list_tags_impl(&state).data.unwrap();
+        assert_eq!(tags.len(), 4);
+        let suggested = suggest_tags_impl(&state, Some("#o".into()), None)
+            .data
+            .unwrap();
+        assert_eq!(suggested[0].tag, "office");
 
-        persist(&ctx, &state).unwrap();
-        assert!(ctx.root_path().join("backups").is_dir());
+        assert_eq!(
+            rename_tag_impl(&ctx, &state, "job".into(), "#Career".into()).data,
+            Some(1)
+        );
+        assert_eq!(state.tasks()[0].tags, ["career", "home"]);
+        assert_eq!(
+            rename_tag_impl(&ctx, &state, "gone".into(), "x".into())
+                .error
+                .as_deref(),
+            Some("tag not found: gone")
+        );
+        assert!(!rename_tag_impl(&ctx, &state, "home".into(), " # ".into()).ok);
+
+        let merged = merge_tags_impl(
+            &ctx,
+            &state,
+            vec!["career".into(), "office".into()],
+            "work".into(),
+        );
+        assert_eq!(merged.data, Some(2));
+        let tasks = state.tasks();
+        assert_eq!(tasks[0].tags, ["work", "home"]);
+        assert_eq!(tasks[1].tags, ["work"]);
         assert!(ctx.root_path().join("data.json").is_file());
-        assert!(ctx.root_path().join("settings.json").is_file());
-        assert_eq!(ctx.emitted.lock().unwrap().len(), 1);
-        assert_eq!(*ctx.tray_updates.lock().unwrap(), 1);
 
-        let bad_ctx = TestCtx::with_app_data_dir_error("nope");
-        assert!(persist(&bad_ctx, &state).is_err());
+        // The whole merge comes back with a single undo.
+        undo_last_action_impl(&ctx, &state).data.unwrap();
+        let tasks = state.tasks();
+        assert_eq!(tasks[0].tags, ["career", "home"]);
+        assert_eq!(tasks[1].tags, ["office", "work"]);
+    }
 
-        let ctx2 = TestCtx::new();
-        fs::write(ctx2.root_path().join("backups"), b"x").unwrap();
-        assert!(persist(&ctx2, &state).is_err());
+    #[test]
+    fn tag_definitions_carry_colors_and_nesting_into_exports() {
+        let ctx = TestCtx::new();
+        let mut a = make_task("a", 10_000);
+        a.tags = vec!["client".to_string()];
+        let state = make_state(vec![a]);
+        let tag = |id: &str, name: &str, parent_id: Option<&str>| Tag {
+            id: id.to_string(),
+            name: name.to_string(),
+            color: Some("#369".to_string()),
+            parent_id: parent_id.map(str::to_string),
+            created_at: 0,
+            updated_at: 0,
+        };
 
-        let ctx3 = TestCtx::new();
-        fs::create_dir_all(ctx3.root_path().join("data.json")).unwrap();
-        assert!(persist(&ctx3, &state).is_err());
+        let work = create_tag_impl(&ctx, &state, tag("w", "#Work", None))
+            .data
+            .unwrap();
+        assert_eq!(
+            (work.name.as_str(), work.color.as_deref()),
+            ("work", Some("#336699"))
+        );
+        assert!(create_tag_impl(&ctx, &state, tag("c", "client", Some("w"))).ok);
+        assert_eq!(
+            create_tag_impl(&ctx, &state, tag("x", "WORK", None))
+                .error
+                .as_deref(),
+            Some("tag already exists: work")
+        );
+        let stats = list_tags_impl(&state).data.unwrap();
+        assert_eq!(stats[0].parent_id.as_deref(), Some("w"));
 
-        let ctx4 = TestCtx::new();
-        fs::create_dir_all(ctx4.root_path().join("settings.json")).unwrap();
-        assert!(persist(&ctx4, &state).is_err());
+        // Renaming a definition renames the tag on its tasks too.
+        let renamed = update_tag_impl(&ctx, &state, tag("c", "customer", Some("w")))
+            .data
+            .unwrap();
+        assert_eq!(renamed.name, "customer");
+        assert_eq!(state.tasks()[0].tags, ["customer"]);
+        let saved: TasksFile =
+            serde_json::from_slice(&fs::read(ctx.root_path().join("data.json")).unwrap()).unwrap();
+        assert_eq!(saved.tags.len(), 2);
+
+        let csv =
+            export_tasks_content_impl(&ctx, &state, ExportFormat::Csv, ExportFilter::default())
+                .data
+                .unwrap();
+        assert!(csv
+            .content
+            .lines()
+            .nth(1)
+            .unwrap()
+            .ends_with(",\"work/customer\",\"#336699\""));
+        let (markdown, _) = render_tasks_markdown(&state, &ExportFilter::default(), false);
+        assert!(markdown.contains(": #work/customer\n"));
+
+        undo_last_action_impl(&ctx, &state).data.unwrap();
+        assert_eq!(state.tasks()[0].tags, ["client"]);
+        assert_eq!(state.tags()[1].name, "client");
+
+        assert!(delete_tag_impl(&ctx, &state, "w".into()).ok);
+        assert_eq!(list_tag_definitions_impl(&state).data.unwrap().len(), 1);
+        assert_eq!(state.tags()[0].parent_id, None);
+        assert!(!delete_tag_impl(&ctx, &state, "w".into()).ok);
     }
 
     #[test]
-    fn load_state_and_task_mutation_commands_cover_success_and_error_paths() {
-        let state = make_state(Vec::new());
+    fn overwritten_notes_are_kept_and_can_be_restored() {
+        let ctx = TestCtx::new();
+        let mut task = make_task("a", 10_000);
+        task.notes = Some("draft by hand".to_string());
+        let state = make_state(vec![task.clone(), make_task("b", 10_000)]);
+
+        task.notes = Some("filled by AI".to_string());
+        assert!(update_task_impl(&ctx, &state, task.clone()).ok);
+        // Edits that leave the notes alone add nothing.
+        task.title = "renamed".to_string();
+        assert!(update_task_impl(&ctx, &state, task.clone()).ok);
+        let mut other = state.task("b").unwrap();
+        other.notes = Some("b notes".to_string());
+        assert!(bulk_update_tasks_impl(&ctx, &state, vec![other.clone()]).ok);
+        other.notes = None;
+        assert!(bulk_update_tasks_impl(&ctx, &state, vec![other]).ok);
+
+        let history = get_note_history_impl(&ctx, "a".into()).data.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(
+            (history[0].version, history[0].notes.as_str()),
+            (1, "draft by hand")
+        );
+        assert_eq!(
+            get_note_history_impl(&ctx, "b".into()).data.unwrap().len(),
+            1
+        );
 
-        // app_data_dir error path.
-        let bad_ctx = TestCtx::with_app_data_dir_error("nope");
-        let res = load_state_impl(&bad_ctx, &state);
-        assert!(!res.ok);
+        let restored = restore_note_version_impl(&ctx, &state, "a".into(), 1)
+            .data
+            .unwrap();
+        assert_eq!(restored.notes.as_deref(), Some("draft by hand"));
+        assert_eq!(state.task("a").unwrap().title, "renamed");
+        // The notes that were replaced by the restore are a version too.
+        let history = get_note_history_impl(&ctx, "a".into()).data.unwrap();
+        assert_eq!(history[0].notes, "filled by AI");
 
-        // ensure_dirs error path.
-        let ctx2 = TestCtx::new();
-        fs::write(ctx2.root_path().join("backups"), b"x").unwrap();
-        let res = load_state_impl(&ctx2, &state);
-        assert!(!res.ok);
+        assert_eq!(
+            restore_note_version_impl(&ctx, &state, "a".into(), 9)
+                .error
+                .as_deref(),
+            Some("note version not found")
+        );
+        assert!(!restore_note_version_impl(&ctx, &state, "missing".into(), 1).ok);
+    }
 
-        // success path (missing files => defaults).
-        let ctx3 = TestCtx::new();
-        let res = load_state_impl(&ctx3, &state);
-        assert!(res.ok);
-        let payload = res.data.unwrap();
-        assert!(payload.tasks.is_empty());
-        assert_eq!(payload.settings.shortcut, Settings::default().shortcut);
-        assert_eq!(state.settings().shortcut, Settings::default().shortcut);
+    #[test]
+    fn automation_rules_run_on_task_changes_and_overdue_ticks() {
+        use crate::models::{AutomationAction, ReminderSpec};
 
-        // create_task fills sort_order when missing.
-        let mut t = make_task("a", 1000);
-        t.created_at = 2;
-        let res = create_task_impl(&ctx3, &state, t);
-        assert!(res.ok);
-        let created_task = res.data.unwrap();
-        assert_eq!(created_task.sort_order, 2000);
+        let ctx = TestCtx::new();
+        let state = make_state(vec![make_task("a", 10_000), make_task("b", 10_000)]);
+        let mut done = state.projects()[0].clone();
+        done.id = "done".to_string();
+        done.name = "Done".to_string();
+        assert!(create_project_impl(&ctx, &state, done).ok);
+
+        let rule = |id: &str, trigger: serde_json::Value, actions: serde_json::Value| {
+            serde_json::from_value::<AutomationRule>(serde_json::json!({
+                "id": id,
+                "name": id,
+                "trigger": trigger,
+                "actions": actions
+            }))
+            .unwrap()
+        };
+        let archive = rule(
+            " archive ",
+            serde_json::json!({ "kind": "task_completed" }),
+            serde_json::json!([
+                { "kind": "move_to_project", "project_id": "done" },
+                { "kind": "run_export", "format": "csv" }
+            ]),
+        );
+        let created = create_automation_rule_impl(&ctx, &state, archive.clone())
+            .data
+            .unwrap();
+        assert_eq!(created.id, "archive");
+        assert_eq!(
+            create_automation_rule_impl(&ctx, &state, archive)
+                .error
+                .as_deref(),
+            Some("automation rule already exists")
+        );
+        assert_eq!(
+            create_automation_rule_impl(
+                &ctx,
+                &state,
+                rule(
+                    "bad",
+                    serde_json::json!({ "kind": "task_completed" }),
+                    serde_json::json!([{ "kind": "move_to_project", "project_id": "nope" }]),
+                ),
+            )
+            .error
+            .as_deref(),
+            Some("project not found: nope")
+        );
+        assert!(
+            create_automation_rule_impl(
+                &ctx,
+                &state,
+                rule(
+                    "urgent",
+                    serde_json::json!({ "kind": "tag_added", "tag": "#Urgent" }),
+                    serde_json::json!([{ "kind": "set_reminder", "minutes_before": 30 }]),
+                ),
+            )
+            .ok
+        );
+        assert!(
+            create_automation_rule_impl(
+                &ctx,
+                &state,
+                rule(
+                    "late",
+                    serde_json::json!({ "kind": "task_overdue", "minutes": 60 }),
+                    serde_json::json!([{ "kind": "add_tag", "tag": "late" }]),
+                ),
+            )
+            .ok
+        );
 
-        // create_task keeps an explicit sort_order as-is.
-        let ctx_sort = TestCtx::new();
-        let state_sort = make_state(Vec::new());
-        let mut t2 = make_task("b", 1000);
-        t2.sort_order = 123;
-        let res = create_task_impl(&ctx_sort, &state_sort, t2);
-        assert!(res.ok);
-        assert_eq!(res.data.unwrap().sort_order, 123);
+        // Adding the tag sets a reminder; the rule's own change fires nothing further.
+        let mut task = state.task("a").unwrap();
+        task.tags.push("urgent".to_string());
+        let saved = update_task_impl(&ctx, &state, task).data.unwrap();
+        assert_eq!(saved.reminder.specs, [ReminderSpec { minutes_before: 30 }]);
+        assert_eq!(saved.reminder.kind, ReminderKind::Normal);
 
-        // create_task persist failure path.
-        let ctx_fail = TestCtx::new();
-        fs::write(ctx_fail.root_path().join("backups"), b"x").unwrap();
-        let state_fail = make_state(Vec::new());
-        let res_fail = create_task_impl(&ctx_fail, &state_fail, make_task("x", 1));
-        assert!(!res_fail.ok);
+        let completed = complete_task_impl(&ctx, &state, "a".into()).data.unwrap();
+        assert_eq!(completed.project_id, "done");
+        assert_eq!(state.task("b").unwrap().project_id, "inbox");
+        assert_eq!(
+            fs::read_dir(ctx.root_path().join("exports"))
+                .unwrap()
+                .count(),
+            1
+        );
 
-        // update_task updates by id and fills sort_order when zero.
-        let mut updated = created_task.clone();
-        updated.title = "updated".into();
-        updated.sort_order = 0;
-        let res = update_task_impl(&ctx3, &state, updated);
-        assert!(res.ok);
-        assert_eq!(state.tasks().len(), 1);
-        assert_eq!(state.tasks()[0].title, "updated");
-        assert_ne!(state.tasks()[0].sort_order, 0);
+        // "b" is due at 10_000; the clock in tests sits well past that.
+        assert_eq!(run_overdue_automations_impl(&ctx, &state), 1);
+        assert_eq!(state.task("b").unwrap().tags, ["late"]);
+        assert_eq!(run_overdue_automations_impl(&ctx, &state), 0);
+        assert!(state.settings().automation_rules[2]
+            .overdue_fired
+            .contains_key("b"));
+
+        // Disabled rules and rules kept out of update_settings.
+        let mut disabled = created;
+        disabled.enabled = false;
+        assert!(update_automation_rule_impl(&ctx, &state, disabled).ok);
+        assert!(bulk_complete_tasks_impl(&ctx, &state, vec!["b".into()]).ok);
+        assert_eq!(state.task("b").unwrap().project_id, "inbox");
+        let mut settings = state.settings();
+        settings.automation_rules.clear();
+        assert!(update_settings_impl(&ctx, &state, settings).ok);
+        assert_eq!(list_automation_rules_impl(&state).data.unwrap().len(), 3);
 
-        // update_task persist failure path.
-        let update_ctx_fail = TestCtx::with_app_data_dir_error("nope");
-        let state_update_fail = make_state(vec![state.tasks()[0].clone()]);
-        let mut updated_fail = state_update_fail.tasks()[0].clone();
-        updated_fail.title = "should-fail".into();
-        let res = update_task_impl(&update_ctx_fail, &state_update_fail, updated_fail);
-        assert!(!res.ok);
+        assert!(delete_project_impl(&ctx, &state, "done".into()).ok);
+        assert_eq!(
+            state.settings().automation_rules[0].actions[0],
+            AutomationAction::MoveToProject {
+                project_id: "inbox".to_string()
+            }
+        );
+        assert!(delete_automation_rule_impl(&ctx, &state, "late".into()).ok);
+        assert!(!delete_automation_rule_impl(&ctx, &state, "late".into()).ok);
+    }
 
-        // swap_sort_order not-found path.
-        let res = swap_sort_order_impl(&ctx3, &state, "a".into(), "missing".into());
-        assert!(!res.ok);
-        assert_eq!(res.error, Some("task not found".to_string()));
+    #[test]
+    fn task_changes_show_up_in_the_activity_timeline() {
+        let ctx = TestCtx::new();
+        let state = make_state(vec![make_task("b", 10_000)]);
+        let task = create_task_impl(&ctx, &state, make_task("a", 10_000))
+            .data
+            .unwrap();
 
-        // swap_sort_order success path.
-        let mut b = make_task("b", 1000);
-        b.sort_order = 999;
-        state.add_task(b);
-        let res = swap_sort_order_impl(&ctx3, &state, "a".into(), "b".into());
-        assert!(res.ok);
-        let tasks = state.tasks();
-        let a = tasks.iter().find(|t| t.id == "a").unwrap();
-        let b = tasks.iter().find(|t| t.id == "b").unwrap();
-        assert_eq!(a.sort_order, 999);
-        assert_eq!(b.sort_order, 2000);
+        let mut edited = state.task(&task.id).unwrap();
+        edited.title = "renamed".to_string();
+        edited.due_at = Some(20_000);
+        assert!(update_task_impl(&ctx, &state, edited).ok);
+        assert!(snooze_task_impl(&ctx, &state, "b".into(), 1_234).ok);
+        assert!(complete_task_impl(&ctx, &state, task.id.clone()).ok);
+        let mut reopened = state.task(&task.id).unwrap();
+        reopened.completed = false;
+        reopened.completed_at = None;
+        assert!(bulk_update_tasks_impl(&ctx, &state, vec![reopened]).ok);
+
+        let kinds: Vec<ActivityKind> = get_task_activity_impl(&ctx, task.id.clone(), None)
+            .data
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ActivityKind::Reopened,
+                ActivityKind::Completed,
+                ActivityKind::Edited {
+                    fields: vec!["due_at".to_string(), "title".to_string()]
+                },
+                ActivityKind::Created,
+            ]
+        );
 
-        // swap_sort_order persist failure path.
-        let swap_ctx_fail = TestCtx::with_app_data_dir_error("nope");
-        let state_swap_fail = make_state(vec![
-            tasks.iter().find(|t| t.id == "a").unwrap().clone(),
-            tasks.iter().find(|t| t.id == "b").unwrap().clone(),
-        ]);
-        let res = swap_sort_order_impl(&swap_ctx_fail, &state_swap_fail, "a".into(), "b".into());
-        assert!(!res.ok);
+        let recent = get_recent_activity_impl(&ctx, Some(2)).data.unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[1].kind, ActivityKind::Completed);
+        assert_eq!(recent[1].title, "renamed");
+        assert!(get_task_activity_impl(&ctx, "b".into(), None)
+            .data
+            .unwrap()
+            .iter()
+            .any(|entry| entry.kind == ActivityKind::Snoozed { until: 1_234 }));
+        assert!(get_task_activity_impl(&ctx, "missing".into(), None)
+            .data
+            .unwrap()
+            .is_empty());
     }
 
     #[test]
-    fn complete_task_covers_not_found_non_repeat_repeat_and_persist_error() {
+    fn task_commands_normalize_invalid_project_ids_and_cover_persist_errors() {
         let ctx = TestCtx::new();
-
-        // Not found.
         let state = make_state(Vec::new());
-        let res = complete_task_impl(&ctx, &state, "missing".into());
-        assert!(!res.ok);
 
-        // RepeatRule::None returns completed task.
-        let state = make_state(vec![make_task("a", 1000)]);
-        let res = complete_task_impl(&ctx, &state, "a".into());
+        let mut t = make_task("invalid-proj", 1000);
+        t.project_id = "missing".to_string();
+        let res = create_task_impl(&ctx, &state, t);
         assert!(res.ok);
-        assert!(res.data.unwrap().completed);
+        assert_eq!(res.data.as_ref().unwrap().project_id, "inbox");
 
-        // RepeatRule != None creates a new task instance.
-        let mut task = make_task("r", 1000);
-        task.repeat = RepeatRule::Daily {
-            workday_only: false,
-        };
-        let state = make_state(vec![task]);
-        let res = complete_task_impl(&ctx, &state, "r".into());
+        let mut edited = res.data.unwrap();
+        edited.title = "edited".to_string();
+        edited.project_id = "missing2".to_string();
+        let res = update_task_impl(&ctx, &state, edited);
         assert!(res.ok);
-        let next = res.data.unwrap();
-        assert!(!next.completed);
-        assert!(next.id.starts_with("r-"));
-        assert!(state.tasks().len() >= 2);
+        assert_eq!(res.data.as_ref().unwrap().project_id, "inbox");
 
-        // Persist error path.
+        // bulk_update normalizes invalid project ids and hits persist error branch.
         let ctx_fail = TestCtx::new();
         fs::write(ctx_fail.root_path().join("backups"), b"x").unwrap();
-        let state_fail = make_state(vec![make_task("x", 1)]);
-        let res = complete_task_impl(&ctx_fail, &state_fail, "x".into());
-        assert!(!res.ok);
-
-        // Persist error path when RepeatRule != None (covers the second persist callsite).
-        let ctx_fail_repeat = TestCtx::new();
-        fs::write(ctx_fail_repeat.root_path().join("backups"), b"x").unwrap();
-        let mut repeat_task = make_task("y", 1000);
-        repeat_task.repeat = RepeatRule::Daily {
-            workday_only: false,
-        };
-        let state_fail_repeat = make_state(vec![repeat_task]);
-        let res = complete_task_impl(&ctx_fail_repeat, &state_fail_repeat, "y".into());
+        let state_fail = make_state(vec![make_task("bu1", 123)]);
+        let mut update = make_task("bu1", 456);
+        update.project_id = "missing".to_string();
+        update.created_at = 2;
+        update.sort_order = 0;
+        let res = bulk_update_tasks_impl(&ctx_fail, &state_fail, vec![update]);
         assert!(!res.ok);
+        assert_eq!(
+            state_fail
+                .tasks()
+                .into_iter()
+                .find(|t| t.id == "bu1")
+                .unwrap()
+                .project_id,
+            "inbox"
+        );
     }
 
     #[test]
-    fn update_settings_validates_shortcuts_registers_and_rolls_back() {
+    fn waiting_on_is_normalized_on_save_and_listed_by_person() {
         let ctx = TestCtx::new();
         let state = make_state(Vec::new());
 
-        // Shortcut unchanged => no registration.
-        let mut settings = state.settings();
-        settings.theme = "dark".into();
-        let res = update_settings_impl(&ctx, &state, settings.clone());
-        assert!(res.ok);
-        assert_eq!(*ctx.shortcut_unregistered.lock().unwrap(), 0);
-        assert_eq!(*ctx.shortcut_registered.lock().unwrap(), 0);
+        let mut delegated = make_task("d", 1000);
+        delegated.waiting_on = Some("  Alice ".to_string());
+        let created = create_task_impl(&ctx, &state, delegated).data.unwrap();
+        assert_eq!(created.waiting_on.as_deref(), Some("Alice"));
+        let since = created.waiting_since.expect("waiting_since should be set");
 
-        // Invalid shortcut.
-        let mut invalid = settings.clone();
-        invalid.shortcut = "not-a-shortcut".into();
-        let res = update_settings_impl(&ctx, &state, invalid);
-        assert!(!res.ok);
+        // Editing the title keeps the delegation start time.
+        let mut edited = created.clone();
+        edited.title = "edited".to_string();
+        edited.waiting_since = None;
+        let res = update_task_impl(&ctx, &state, edited);
+        assert_eq!(res.data.unwrap().waiting_since, Some(since));
+
+        assert!(create_task_impl(&ctx, &state, make_task("plain", 2000)).ok);
+        let groups = list_waiting_for_impl(&state).data.unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].person, "Alice");
+        assert_eq!(groups[0].tasks.len(), 1);
+        assert_eq!(groups[0].tasks[0].task.id, "d");
+
+        // Clearing the person drops the task from the list.
+        let mut cleared = state.tasks().into_iter().find(|t| t.id == "d").unwrap();
+        cleared.waiting_on = Some(" ".to_string());
+        assert!(bulk_update_tasks_impl(&ctx, &state, vec![cleared]).ok);
+        let task = state.tasks().into_iter().find(|t| t.id == "d").unwrap();
+        assert_eq!(task.waiting_on, None);
+        assert_eq!(task.waiting_since, None);
+        assert!(list_waiting_for_impl(&state).data.unwrap().is_empty());
+    }
 
-        // Shortcut changed => register.
-        let mut changed = settings.clone();
-        changed.shortcut = "CommandOrControl+Shift+Y".into();
-        let res = update_settings_impl(&ctx, &state, changed.clone());
-        assert!(res.ok);
-        assert!(state.settings().shortcut.ends_with('Y'));
-        assert_eq!(*ctx.shortcut_unregistered.lock().unwrap(), 1);
-        assert_eq!(*ctx.shortcut_registered.lock().unwrap(), 1);
+    #[test]
+    fn context_is_normalized_on_save_and_aggregated() {
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
 
-        // Register failure => best-effort restore previous shortcut.
-        ctx.set_shortcut_register_error(Some("boom"));
-        let mut changed2 = settings.clone();
-        changed2.shortcut = "CommandOrControl+Shift+Z".into();
-        let prev_shortcut = state.settings().shortcut;
-        let res = update_settings_impl(&ctx, &state, changed2);
-        assert!(!res.ok);
-        assert_eq!(state.settings().shortcut, prev_shortcut);
-        ctx.set_shortcut_register_error(None);
+        let mut direct = make_task("call", 1000);
+        direct.context = Some(" Phone ".to_string());
+        let created = create_task_impl(&ctx, &state, direct).data.unwrap();
+        assert_eq!(created.context.as_deref(), Some("@phone"));
+
+        let mut tagged = make_task("shop", 2000);
+        tagged.tags = vec!["@Errand".to_string(), "home".to_string()];
+        let created = create_task_impl(&ctx, &state, tagged).data.unwrap();
+        assert_eq!(created.context.as_deref(), Some("@errand"));
+        assert_eq!(created.tags, vec!["home"]);
+
+        let mut moved = created.clone();
+        moved.context = Some("@phone".to_string());
+        assert!(update_task_impl(&ctx, &state, moved).ok);
+
+        let summaries = list_contexts_impl(&state).data.unwrap();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].context, "@phone");
+        assert_eq!(summaries[0].open_count, 2);
+
+        let spec: TaskQuerySpec =
+            serde_json::from_value(serde_json::json!({ "filter": { "context": "PHONE" } }))
+                .unwrap();
+        let groups = query_tasks_impl(&state, spec).data.unwrap().groups;
+        assert_eq!(groups[0].tasks.len(), 2);
+    }
 
-        // Persist failure => rollback both in-memory settings and shortcut.
-        // Replace settings.json with a directory so `save_settings` fails reliably.
-        let settings_path = ctx.root_path().join("settings.json");
-        let _ = fs::remove_file(&settings_path);
-        fs::create_dir_all(&settings_path).unwrap();
-        let before = state.settings().shortcut;
-        let mut changed3 = settings;
-        changed3.shortcut = "CommandOrControl+Shift+T".into();
-        let res = update_settings_impl(&ctx, &state, changed3);
-        assert!(!res.ok);
-        assert_eq!(state.settings().shortcut, before);
-        assert!(*ctx.shortcut_unregistered.lock().unwrap() >= 2);
-        assert!(*ctx.shortcut_registered.lock().unwrap() >= 2);
+    #[test]
+    fn task_progress_is_recomputed_on_every_save_path() {
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
+        let step = |id: &str, completed: bool| crate::models::Step {
+            id: id.to_string(),
+            title: id.to_string(),
+            completed,
+            created_at: 1,
+            completed_at: None,
+            parent_id: None,
+        };
 
-        // Persist failure with shortcut unchanged should not attempt shortcut rollback logic.
-        // This covers the `shortcut_changed == false` rollback branch in the persist error path.
-        let ctx_no_change = TestCtx::new();
-        let state_no_change = make_state(Vec::new());
-        let settings_path = ctx_no_change.root_path().join("settings.json");
-        let _ = fs::remove_file(&settings_path);
-        fs::create_dir_all(&settings_path).unwrap();
-        let before = state_no_change.settings();
-        let mut settings_no_change = before.clone();
-        settings_no_change.theme = "light".into();
-        let res = update_settings_impl(&ctx_no_change, &state_no_change, settings_no_change);
-        assert!(!res.ok);
-        assert_eq!(state_no_change.settings().shortcut, before.shortcut);
-        assert_eq!(state_no_change.settings().theme, before.theme);
-        assert_eq!(*ctx_no_change.shortcut_unregistered.lock().unwrap(), 0);
-        assert_eq!(*ctx_no_change.shortcut_registered.lock().unwrap(), 0);
+        let mut task = make_task("p", 1000);
+        task.steps = vec![step("a", true), step("b", false)];
+        let created = create_task_impl(&ctx, &state, task).data.unwrap();
+        assert_eq!(created.progress.percent_complete, 50);
+
+        let mut edited = created.clone();
+        edited.steps.push(step("c", true));
+        let updated = update_task_impl(&ctx, &state, edited).data.unwrap();
+        assert_eq!(updated.progress.steps_completed, 2);
+        assert_eq!(updated.progress.steps_total, 3);
+
+        let mut bulk = updated.clone();
+        bulk.steps.truncate(1);
+        assert!(bulk_update_tasks_impl(&ctx, &state, vec![bulk]).ok);
+        assert_eq!(state.tasks()[0].progress.percent_complete, 100);
+        let last = ctx.emitted.lock().unwrap().last().cloned().unwrap();
+        assert_eq!(last.tasks[0].progress.steps_total, 1);
+
+        // Reloaded data (restore/import/profile switch) is normalized the same way.
+        let saved = Storage::new(ctx.root_path().to_path_buf())
+            .load_tasks()
+            .unwrap();
+        let reloaded = make_state(saved.tasks);
+        assert_eq!(reloaded.tasks()[0].progress.steps_completed, 1);
     }
 
     #[test]
-    fn update_settings_rejects_empty_shortcut_without_side_effects() {
+    fn set_data_directory_moves_data_and_redirects_future_writes() {
+        let ctx = TestCtx::new();
+        let state = make_state(vec![make_task("a", 1000)]);
+        assert!(persist(&ctx, &state).is_ok());
+        let target_root = tempfile::tempdir().unwrap();
+        let target = target_root.path().join("synced");
+
+        let res = set_data_directory_impl(&ctx, &state, target.display().to_string());
+        assert!(res.ok, "{:?}", res.error);
+        assert_eq!(ctx.app_data_dir().unwrap(), target);
+        assert!(target.join("data.json").exists());
+        assert!(target.join("backups").is_dir());
+        assert!(!ctx.root_path().join("data.json").exists());
+        assert!(!ctx.data_dir_progress.lock().unwrap().is_empty());
+
+        // Later writes land in the new location.
+        assert!(create_task_impl(&ctx, &state, make_task("b", 2000)).ok);
+        let saved = Storage::new(target.clone()).load_tasks().unwrap();
+        assert_eq!(saved.tasks.len(), 2);
+
+        // Moving back to the default location removes the pointer again.
+        let res = set_data_directory_impl(&ctx, &state, ctx.root_path().display().to_string());
+        assert!(res.ok, "{:?}", res.error);
+        assert_eq!(ctx.app_data_dir().unwrap(), ctx.root_path());
+        assert!(ctx.root_path().join("data.json").exists());
+        assert!(!target.join("data.json").exists());
+    }
+
+    #[test]
+    fn set_data_directory_validates_and_rolls_back() {
         let ctx = TestCtx::new();
         let state = make_state(Vec::new());
 
-        let mut settings = state.settings();
-        settings.shortcut = "   ".into();
+        let res = set_data_directory_impl(&ctx, &state, "relative/dir".into());
+        assert_eq!(
+            res.error.as_deref(),
+            Some("data directory must be an absolute path")
+        );
+        let bad = TestCtx::with_app_data_dir_error("nope");
+        assert!(!set_data_directory_impl(&bad, &state, "/tmp/x".into()).ok);
 
-        let res = update_settings_impl(&ctx, &state, settings);
+        // Pointer write fails (a directory occupies its path): the copy is removed again.
+        fs::create_dir(ctx.root_path().join("data_dir.json")).unwrap();
+        let target_root = tempfile::tempdir().unwrap();
+        let res = set_data_directory_impl(&ctx, &state, target_root.path().display().to_string());
         assert!(!res.ok);
-        assert!(res
-            .error
-            .as_deref()
-            .unwrap_or_default()
-            .contains("empty shortcut"));
+        assert!(!target_root.path().join("data.json").exists());
+        assert!(ctx.root_path().join("data.json").exists());
+        assert_eq!(ctx.app_data_dir().unwrap(), ctx.root_path());
 
-        // Validation happens before any shortcut unregister/register calls.
-        assert_eq!(*ctx.shortcut_unregistered.lock().unwrap(), 0);
-        assert_eq!(*ctx.shortcut_registered.lock().unwrap(), 0);
-        assert_eq!(state.settings().shortcut, Settings::default().shortcut);
+        // Persist failure before copying leaves everything in place.
+        let ctx_fail = TestCtx::new();
+        fs::write(ctx_fail.root_path().join("backups"), b"x").unwrap();
+        let res =
+            set_data_directory_impl(&ctx_fail, &state, target_root.path().display().to_string());
+        assert!(!res.ok);
     }
 
     #[test]
-    fn update_settings_normalizes_unknown_language_to_default() {
+    fn profiles_keep_data_sets_separate() {
+        use crate::clock::{AppClock, FixedClock};
+
         let ctx = TestCtx::new();
+        let state = AppState::with_clock(
+            vec![make_task("personal", 1000)],
+            Vec::new(),
+            Settings::default(),
+            AppClock::new(std::sync::Arc::new(FixedClock(1_700_000_000))),
+        );
+        assert!(persist(&ctx, &state).is_ok());
+
+        let listed = list_profiles_impl(&ctx).data.unwrap();
+        assert_eq!(listed.active, "default");
+        let work = create_profile_impl(&ctx, &state, "Work".into())
+            .data
+            .unwrap();
+        assert_eq!((work.id.as_str(), work.created_at), ("work", 1_700_000_000));
+        assert!(!create_profile_impl(&ctx, &state, " work ".into()).ok);
+
+        let payload = switch_profile_impl(&ctx, &state, "Work".into())
+            .data
+            .unwrap();
+        assert!(payload.tasks.is_empty());
+        let work_root = ctx.root_path().join("profiles").join("work");
+        assert_eq!(ctx.app_data_dir().unwrap(), work_root);
+        assert!(work_root.join("data.json").exists());
+        assert_eq!(list_profiles_impl(&ctx).data.unwrap().active, "work");
+
+        assert!(create_task_impl(&ctx, &state, make_task("client", 2000)).ok);
+        let default_saved = Storage::new(ctx.root_path().to_path_buf())
+            .load_tasks()
+            .unwrap();
+        let ids: Vec<_> = default_saved.tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["personal"]);
+
+        // Switching back restores the first data set; switching to the active one is a no-op.
+        let payload = switch_profile_impl(&ctx, &state, "default".into())
+            .data
+            .unwrap();
+        let ids: Vec<_> = payload.tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["personal"]);
+        assert!(switch_profile_impl(&ctx, &state, "Default".into()).ok);
+        let emitted = ctx.emitted.lock().unwrap().len();
+        assert!(emitted > 0);
+
+        assert_eq!(
+            switch_profile_impl(&ctx, &state, "nope".into())
+                .error
+                .as_deref(),
+            Some("profile not found: nope")
+        );
+    }
+
+    #[test]
+    fn profile_commands_report_errors() {
+        let bad = TestCtx::with_app_data_dir_error("boom");
         let state = make_state(Vec::new());
+        assert!(!list_profiles_impl(&bad).ok);
+        assert!(!create_profile_impl(&bad, &state, "Work".into()).ok);
+        assert!(!switch_profile_impl(&bad, &state, "Work".into()).ok);
 
-        let mut settings = state.settings();
-        settings.language = "fr".into();
+        let ctx = TestCtx::new();
+        assert!(!create_profile_impl(&ctx, &state, "  ".into()).ok);
+        assert!(create_profile_impl(&ctx, &state, "Work".into()).ok);
 
-        let res = update_settings_impl(&ctx, &state, settings);
-        assert!(res.ok);
-        assert_eq!(state.settings().language, Settings::default().language);
+        // A corrupt data file in the target profile aborts the switch before anything changes.
+        let work_root = ctx.root_path().join("profiles").join("work");
+        fs::create_dir_all(&work_root).unwrap();
+        fs::write(work_root.join("data.json"), "{broken").unwrap();
+        assert!(!switch_profile_impl(&ctx, &state, "Work".into()).ok);
+        assert_eq!(list_profiles_impl(&ctx).data.unwrap().active, "default");
+
+        // Persist failure of the current profile also aborts.
+        let ctx_fail = TestCtx::new();
+        assert!(create_profile_impl(&ctx_fail, &state, "Work".into()).ok);
+        fs::write(ctx_fail.root_path().join("backups"), b"x").unwrap();
+        assert!(!switch_profile_impl(&ctx_fail, &state, "Work".into()).ok);
+
+        // Registry cannot be written (a directory occupies its path).
+        let ctx_registry = TestCtx::new();
+        fs::create_dir(ctx_registry.root_path().join("profiles.json")).unwrap();
+        assert!(!create_profile_impl(&ctx_registry, &state, "Work".into()).ok);
     }
 
     #[test]
-    fn snooze_dismiss_and_delete_cover_found_not_found_and_persist_error() {
+    fn test_fire_reminder_fires_without_touching_reminder_state() {
         let ctx = TestCtx::new();
-        let state = make_state(vec![make_task("a", 1000), make_task("b", 2000)]);
+        let mut forced = make_task("f", 5_000);
+        forced.reminder.kind = ReminderKind::Forced;
+        let mut silent = make_task("s", 5_000);
+        silent.reminder.kind = ReminderKind::None;
+        let state = make_state(vec![forced.clone(), silent]);
+
+        let res = test_fire_reminder_impl(&ctx, &state, "f".into());
+        assert_eq!(res.data.map(|t| t.id), Some("f".to_string()));
+        let fired = ctx.fired.lock().unwrap().clone();
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].reminder.kind, ReminderKind::Forced);
+        let after = state.tasks().into_iter().find(|t| t.id == "f").unwrap();
+        assert_eq!(after.reminder.last_fired_at, forced.reminder.last_fired_at);
+        assert!(!after.reminder.forced_dismissed);
+        assert!(ctx.emitted.lock().unwrap().is_empty());
+        assert!(!ctx.root_path().join("data.json").exists());
 
-        // snooze_task: found + not found.
-        let res = snooze_task_impl(&ctx, &state, "a".into(), 1234);
-        assert!(res.ok);
-        let a = state.tasks().into_iter().find(|t| t.id == "a").unwrap();
-        assert_eq!(a.reminder.snoozed_until, Some(1234));
-        assert!(a.reminder.last_fired_at.is_some());
+        assert_eq!(
+            test_fire_reminder_impl(&ctx, &state, "s".into())
+                .error
+                .as_deref(),
+            Some("task has no reminder")
+        );
+        assert!(!test_fire_reminder_impl(&ctx, &state, "missing".into()).ok);
+        assert_eq!(ctx.fired.lock().unwrap().len(), 1);
+    }
 
-        let res = snooze_task_impl(&ctx, &state, "missing".into(), 1);
-        assert!(res.ok);
+    #[test]
+    fn advance_clock_drives_command_timestamps_and_repeats() {
+        use crate::clock::{AppClock, FixedClock};
+        let ctx = TestCtx::new();
+        let mut task = make_task("a", 5_000);
+        task.repeat = RepeatRule::Daily {
+            workday_only: false,
+        };
+        let state = AppState::with_clock(
+            vec![task],
+            Vec::new(),
+            Settings::default(),
+            AppClock::new(std::sync::Arc::new(FixedClock(1_000))),
+        );
 
-        // dismiss_forced: found + not found.
-        let res = dismiss_forced_impl(&ctx, &state, "a".into());
-        assert!(res.ok);
-        let a = state.tasks().into_iter().find(|t| t.id == "a").unwrap();
-        assert!(a.reminder.forced_dismissed);
+        assert!(!advance_clock_impl(&ctx, &state, 0).ok);
+        assert!(!advance_clock_impl(&ctx, &state, -5).ok);
+        assert_eq!(advance_clock_impl(&ctx, &state, 3_600).data, Some(4_600));
+        assert_eq!(*ctx.tray_updates.lock().unwrap(), 1);
+        assert_eq!(ctx.emitted.lock().unwrap().len(), 1);
 
-        let res = dismiss_forced_impl(&ctx, &state, "missing".into());
-        assert!(res.ok);
+        let next = complete_task_impl(&ctx, &state, "a".into()).data.unwrap();
+        let tasks = state.tasks();
+        let done = tasks.iter().find(|t| t.id == "a").unwrap();
+        assert_eq!(done.completed_at, Some(4_600));
+        assert_eq!(next.id, "a-4600");
+        assert_eq!(next.due_at, Some(5_000 + 86_400));
+    }
 
-        // delete_task + delete_tasks.
-        let res = delete_task_impl(&ctx, &state, "a".into());
+    #[test]
+    fn run_maintenance_now_records_last_run_and_persists() {
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
+        let res = run_maintenance_now_impl(&ctx, &state);
         assert!(res.ok);
-        assert!(state.tasks().iter().all(|t| t.id != "a"));
+        let report = res.data.unwrap();
+        let saved = Storage::new(ctx.root_path().to_path_buf())
+            .load_settings()
+            .unwrap();
+        assert_eq!(saved.settings.last_maintenance_at, Some(report.ran_at));
 
-        let res = delete_tasks_impl(&ctx, &state, vec!["b".into(), "missing".into()]);
-        assert!(res.ok);
-        assert!(state.tasks().is_empty());
+        let bad = TestCtx::with_app_data_dir_error("nope");
+        assert!(!run_maintenance_now_impl(&bad, &state).ok);
 
-        // Persist error path: make backups a file so ensure_dirs fails.
         let ctx_fail = TestCtx::new();
         fs::write(ctx_fail.root_path().join("backups"), b"x").unwrap();
-        let state_fail = make_state(vec![make_task("x", 1)]);
-        assert!(!snooze_task_impl(&ctx_fail, &state_fail, "x".into(), 1).ok);
-        assert!(!dismiss_forced_impl(&ctx_fail, &state_fail, "x".into()).ok);
-        assert!(!delete_task_impl(&ctx_fail, &state_fail, "x".into()).ok);
-        assert!(!delete_tasks_impl(&ctx_fail, &state_fail, vec!["x".into()]).ok);
+        assert!(!run_maintenance_now_impl(&ctx_fail, &state).ok);
+    }
+
+    #[test]
+    fn smtp_password_and_test_email_preparation() {
+        let ctx = TestCtx::new();
+        let settings = Settings {
+            smtp_host: "smtp.example.com".to_string(),
+            smtp_username: "me".to_string(),
+            email_from: "me@example.com".to_string(),
+            email_to: "me@example.com".to_string(),
+            language: "en".to_string(),
+            ..Settings::default()
+        };
+        let state = AppState::new(Vec::new(), Vec::new(), settings);
+
+        let missing = prepare_test_email_impl(&ctx, &state).unwrap_err();
+        assert!(missing.contains("password"));
+
+        assert!(set_smtp_password_impl(&ctx, "pw".to_string()).ok);
+        let (root, config, message) = prepare_test_email_impl(&ctx, &state).unwrap();
+        assert_eq!(root, ctx.root_path());
+        assert_eq!(config.password.as_deref(), Some("pw"));
+        assert_eq!(message.subject, "MustDo test email");
+        // The password never lands in settings.json.
+        assert!(!state.settings_file().settings.smtp_host.is_empty());
+        assert!(!serde_json::to_string(&state.settings_file())
+            .unwrap()
+            .contains("\"pw\""));
+
+        assert!(set_smtp_password_impl(&ctx, String::new()).ok);
+        assert!(prepare_test_email_impl(&ctx, &state).is_err());
+
+        let failing = TestCtx::with_app_data_dir_error("boom");
+        assert!(!set_smtp_password_impl(&failing, "pw".to_string()).ok);
+        assert!(prepare_test_email_impl(&failing, &state)
+            .unwrap_err()
+            .contains("app_data_dir"));
+        assert!(!list_email_deliveries_impl(&failing, None).ok);
     }
 
     #[test]
-    fn backup_commands_list_create_restore_and_import_cover_paths() {
-        // list_backups app_data_dir error.
-        let bad_ctx = TestCtx::with_app_data_dir_error("nope");
-        assert!(!list_backups_impl(&bad_ctx).ok);
-
-        // list_backups NotFound => ensure_dirs + empty list.
+    fn set_smtp_password_and_deliveries_report_storage_errors() {
         let ctx = TestCtx::new();
-        let res = list_backups_impl(&ctx);
-        assert!(res.ok);
-        assert!(res.data.unwrap().is_empty());
+        fs::create_dir(ctx.root_path().join("secrets.json")).unwrap();
+        let res = set_smtp_password_impl(&ctx, "pw".to_string());
+        assert!(res.error.unwrap().contains("storage error"));
+        let state = make_state(Vec::new());
+        assert!(prepare_test_email_impl(&ctx, &state)
+            .unwrap_err()
+            .contains("storage error"));
 
-        // list_backups NotFound + ensure_dirs failure => error.
-        let mut ctx_not_dir = TestCtx::new();
-        let root_file = ctx_not_dir.root_path().join("not-a-dir");
-        fs::write(&root_file, b"x").unwrap();
-        ctx_not_dir.set_app_data_dir_override(root_file);
-        assert!(!list_backups_impl(&ctx_not_dir).ok);
+        let mut file_root = TestCtx::new();
+        let blocker = file_root.root_path().join("blocker");
+        fs::write(&blocker, "x").unwrap();
+        file_root.set_app_data_dir_override(blocker.join("nested"));
+        assert!(!set_smtp_password_impl(&file_root, "pw".to_string()).ok);
 
-        // list_backups invalid backups path => error.
-        let ctx2 = TestCtx::new();
-        fs::write(ctx2.root_path().join("backups"), b"x").unwrap();
-        assert!(!list_backups_impl(&ctx2).ok);
+        assert!(list_email_deliveries_impl(&ctx, None)
+            .data
+            .unwrap()
+            .is_empty());
+        fs::create_dir(ctx.root_path().join("email_deliveries.jsonl")).unwrap();
+        assert!(!list_email_deliveries_impl(&ctx, Some(5)).ok);
+    }
 
-        // list_backups Ok path with at least one entry.
-        let ctx3 = TestCtx::new();
-        fs::create_dir_all(ctx3.root_path().join("backups")).unwrap();
-        fs::write(
-            ctx3.root_path().join("backups").join("data-test.json"),
-            b"{}",
-        )
-        .unwrap();
-        let res = list_backups_impl(&ctx3);
-        assert!(res.ok);
-        assert!(res
+    #[test]
+    fn task_commands_dispatch_outbound_events_after_persisting() {
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
+        let task = create_task_impl(&ctx, &state, make_task("a", 1000))
             .data
+            .unwrap();
+        let mut edited = task.clone();
+        edited.title = "renamed".to_string();
+        assert!(update_task_impl(&ctx, &state, edited.clone()).ok);
+        edited.completed = true;
+        assert!(update_task_impl(&ctx, &state, edited).ok);
+        assert!(create_task_impl(&ctx, &state, make_task("b", 1000)).ok);
+        assert!(complete_task_impl(&ctx, &state, "b".into()).ok);
+        assert!(create_task_impl(&ctx, &state, make_task("c", 1000)).ok);
+        assert!(bulk_complete_tasks_impl(&ctx, &state, vec!["c".into(), "x".into()]).ok);
+        assert!(delete_task_impl(&ctx, &state, "a".into()).ok);
+        assert!(delete_tasks_impl(&ctx, &state, vec!["b".into(), "c".into()]).ok);
+
+        let topics: Vec<&str> = ctx
+            .dispatched
+            .lock()
             .unwrap()
             .iter()
-            .any(|entry| entry.name == "data-test.json"));
+            .map(|event| event.topic())
+            .collect();
+        assert_eq!(
+            topics,
+            vec![
+                "task/created",
+                "task/updated",
+                "task/completed",
+                "task/created",
+                "task/completed",
+                "task/created",
+                "task/completed",
+                "task/deleted",
+                "task/deleted",
+            ]
+        );
+
+        // Nothing goes out when the change was not saved.
+        let ctx_fail = TestCtx::new();
+        fs::write(ctx_fail.root_path().join("backups"), b"x").unwrap();
+        assert!(!create_task_impl(&ctx_fail, &state, make_task("d", 1000)).ok);
+        assert!(!delete_task_impl(&ctx_fail, &state, "d".into()).ok);
+        assert!(ctx_fail.dispatched.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn set_mqtt_password_stores_secret_and_reports_errors() {
+        let ctx = TestCtx::new();
+        assert!(set_mqtt_password_impl(&ctx, "pw".to_string()).ok);
+        let root = ctx.app_data_dir().unwrap();
+        assert_eq!(
+            crate::secrets::get_secret(&root, crate::secrets::MQTT_PASSWORD).unwrap(),
+            Some("pw".to_string())
+        );
 
-        // delete_backup covers error + success.
         let bad_ctx = TestCtx::with_app_data_dir_error("nope");
-        assert!(!delete_backup_impl(&bad_ctx, "data-test.json".into()).ok);
+        assert!(!set_mqtt_password_impl(&bad_ctx, "pw".to_string()).ok);
+        let dir_ctx = TestCtx::new();
+        fs::create_dir(dir_ctx.root_path().join("secrets.json")).unwrap();
+        assert!(!set_mqtt_password_impl(&dir_ctx, "pw".to_string()).ok);
+    }
 
-        let ctx_del_fail = TestCtx::new();
-        fs::write(ctx_del_fail.root_path().join("backups"), b"x").unwrap();
-        assert!(!delete_backup_impl(&ctx_del_fail, "data-test.json".into()).ok);
+    #[test]
+    fn set_and_clear_secret_track_the_api_key_outside_settings() {
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
+        let root = ctx.app_data_dir().unwrap();
 
-        let ctx_del_invalid = TestCtx::new();
-        fs::create_dir_all(ctx_del_invalid.root_path().join("backups")).unwrap();
-        assert!(!delete_backup_impl(&ctx_del_invalid, "../data-test.json".into()).ok);
+        let res = set_secret_impl(&ctx, &state, "deepseek_api_key".into(), " sk-1 ".into());
+        assert!(res.data.unwrap().ai_api_key_set);
+        assert_eq!(
+            crate::secrets::get_secret(&root, crate::secrets::DEEPSEEK_API_KEY).unwrap(),
+            Some("sk-1".to_string())
+        );
+        let saved = fs::read_to_string(root.join("settings.json")).unwrap();
+        assert!(!saved.contains("sk-1"));
+
+        // A client echoing settings back cannot overwrite the key or the flag.
+        let echoed = Settings {
+            deepseek_api_key: "leaked".into(),
+            ai_api_key_set: false,
+            ..state.settings()
+        };
+        assert!(update_settings_impl(&ctx, &state, echoed).ok);
+        assert!(state.settings().ai_api_key_set);
+        assert!(state.settings().deepseek_api_key.is_empty());
 
-        let ctx_del_ok = TestCtx::new();
-        fs::create_dir_all(ctx_del_ok.root_path().join("backups")).unwrap();
-        fs::write(
-            ctx_del_ok
-                .root_path()
-                .join("backups")
-                .join("data-test.json"),
-            b"{}",
-        )
-        .unwrap();
-        let res = delete_backup_impl(&ctx_del_ok, "data-test.json".into());
-        assert!(res.ok);
+        assert!(!set_secret_impl(&ctx, &state, "theme".into(), "x".into()).ok);
+        let res = clear_secret_impl(&ctx, &state, "deepseek_api_key".into());
+        assert!(!res.data.unwrap().ai_api_key_set);
+        assert_eq!(
+            crate::secrets::get_secret(&root, crate::secrets::DEEPSEEK_API_KEY).unwrap(),
+            None
+        );
 
-        // create_backup covers error branches + success.
-        let state = make_state(vec![make_task("a", 1000)]);
+        // The flag follows the selected provider; Ollama needs no key.
+        let switch = |provider| Settings {
+            ai_provider: provider,
+            ai_model: String::new(),
+            ai_base_url: " http://box:11434/ ".into(),
+            ..state.settings()
+        };
+        let ollama = update_settings_impl(&ctx, &state, switch(AiProviderKind::Ollama));
+        let ollama = ollama.data.unwrap();
+        assert!(ollama.ai_api_key_set);
+        assert_eq!(ollama.ai_model, "llama3.1");
+        assert_eq!(ollama.ai_base_url, "http://box:11434/");
+        let openai = update_settings_impl(&ctx, &state, switch(AiProviderKind::OpenAi));
+        assert!(!openai.data.unwrap().ai_api_key_set);
+        let res = set_secret_impl(&ctx, &state, "openai_api_key".into(), "sk-2".into());
+        assert!(res.data.unwrap().ai_api_key_set);
+    }
 
-        let bad_ctx = TestCtx::with_app_data_dir_error("nope");
-        assert!(!create_backup_impl(&bad_ctx, &state).ok);
+    #[test]
+    fn weekly_review_needs_ai_and_a_key_then_saves_the_reply() {
+        let ctx = TestCtx::new();
+        let now = chrono::Local::now().timestamp();
+        let state = make_state(vec![Task {
+            completed: true,
+            completed_at: Some(now - 3600),
+            ..make_task("done", 1000)
+        }]);
+        assert!(prepare_weekly_review_impl(&ctx, &state).is_err());
+
+        state.update_settings(Settings {
+            ai_enabled: true,
+            ..state.settings()
+        });
+        let missing = prepare_weekly_review_impl(&ctx, &state).unwrap_err();
+        assert!(missing.contains("deepseek_api_key"));
 
-        let ctx4 = TestCtx::new();
-        fs::write(ctx4.root_path().join("backups"), b"x").unwrap();
-        assert!(!create_backup_impl(&ctx4, &state).ok);
+        state.update_settings(Settings {
+            ai_provider: AiProviderKind::Ollama,
+            ..state.settings()
+        });
+        let (api_key, prompt) = prepare_weekly_review_impl(&ctx, &state).unwrap();
+        assert_eq!(api_key, None);
+        assert_eq!(prompt.completed_count, 1);
+
+        assert!(!finish_weekly_review_impl(&ctx, &state, &prompt, "sorry").ok);
+        let reply =
+            r#"{"summary":"ok","wins":["shipped"],"slipped":[],"next_week_focus":["rest"]}"#;
+        let saved = finish_weekly_review_impl(&ctx, &state, &prompt, reply)
+            .data
+            .unwrap();
+        assert_eq!(saved.review.wins, vec!["shipped"]);
+        let stored: crate::weekly_review::WeeklyReview =
+            serde_json::from_str(&fs::read_to_string(&saved.path).unwrap()).unwrap();
+        assert_eq!(stored, saved.review);
+        assert!(saved.path.contains("reviews"));
+    }
 
-        let ctx5 = TestCtx::new();
-        fs::create_dir_all(ctx5.root_path().join("data.json")).unwrap();
-        assert!(!create_backup_impl(&ctx5, &state).ok);
+    #[test]
+    fn caldav_sync_applies_pulls_records_state_and_skips_tasks_edited_meanwhile() {
+        use crate::sync::{parse_vtodo, plan_sync, task_to_ics};
 
-        let ctx6 = TestCtx::new();
-        fs::create_dir_all(ctx6.root_path().join("settings.json")).unwrap();
-        assert!(!create_backup_impl(&ctx6, &state).ok);
+        let ctx = TestCtx::new();
+        let state = make_state(vec![
+            make_task("keep", 1000),
+            make_task("edited", 1000),
+            make_task("raced", 1000),
+            make_task("local", 1000),
+        ]);
+        assert!(prepare_caldav_sync_impl(&ctx, &state)
+            .unwrap_err()
+            .contains("caldav url"));
+        assert!(set_caldav_password_impl(&ctx, "pw".to_string()).ok);
+        let mut settings = state.settings();
+        settings.caldav_url = " https://dav.example.com/cal ".to_string();
+        settings.caldav_username = "me".to_string();
+        settings.caldav_sync_interval_minutes = 1;
+        let settings = update_settings_impl(&ctx, &state, settings).data.unwrap();
+        assert_eq!(settings.caldav_url, "https://dav.example.com/cal");
+        assert_eq!(settings.caldav_sync_interval_minutes, 5);
+        let (config, previous) = prepare_caldav_sync_impl(&ctx, &state).unwrap();
+        assert_eq!(config.password.as_deref(), Some("pw"));
+        assert_eq!(previous, CalDavSyncState::default());
+
+        let remote_of = |id: &str, title: &str, updated_at: i64| {
+            let mut task = make_task(id, 1000);
+            task.title = title.to_string();
+            task.updated_at = updated_at;
+            RemoteTodo {
+                href: format!("/cal/{id}.ics"),
+                etag: None,
+                todo: parse_vtodo(&task_to_ics(&task, 0)).unwrap(),
+            }
+        };
+        let remote = vec![
+            remote_of("keep", "task-keep", 1),
+            remote_of("edited", "Remote title", 50),
+            remote_of("raced", "Remote raced", 50),
+            remote_of("fresh", "From the server", 60),
+        ];
+        let plan = plan_sync(&config.url, &state.tasks(), &remote, &previous);
+        assert_eq!(plan.pull.len(), 3);
+        assert_eq!(plan.push.len(), 1);
 
-        let ctx7 = TestCtx::new();
-        let state2 = make_state(vec![make_task("x", 1000)]);
-        let res = create_backup_impl(&ctx7, &state2);
-        assert!(res.ok);
-        assert!(state2.settings().last_backup_at.is_some());
+        // Edited locally while the run was talking to the server.
+        let mut raced = state.tasks().into_iter().find(|t| t.id == "raced").unwrap();
+        raced.updated_at = 99;
+        state.update_task(raced);
+        let mut outcome = RemoteOutcome::default();
+        outcome.fail("local", "caldav PUT local.ics http 507".to_string());
 
-        // restore/import: app_data_dir error + ensure_dirs error.
-        let state_any = make_state(Vec::new());
         let bad_ctx = TestCtx::with_app_data_dir_error("nope");
-        assert!(!restore_backup_impl(&bad_ctx, &state_any, "anything.json".into()).ok);
-        assert!(!import_backup_impl(&bad_ctx, &state_any, "anything.json".into()).ok);
-
-        let mut ctx_not_dir = TestCtx::new();
-        let root_file = ctx_not_dir.root_path().join("not-a-dir");
-        fs::write(&root_file, b"x").unwrap();
-        ctx_not_dir.set_app_data_dir_override(root_file);
-        assert!(!restore_backup_impl(&ctx_not_dir, &state_any, "anything.json".into()).ok);
-        assert!(!import_backup_impl(&ctx_not_dir, &state_any, "anything.json".into()).ok);
+        let res = finish_caldav_sync_impl(
+            &bad_ctx,
+            &state,
+            &config,
+            &previous,
+            &remote,
+            &plan,
+            RemoteOutcome::default(),
+        );
+        assert!(res.error.unwrap().contains("app_data_dir"));
 
-        // restore_backup: error + success.
-        let ctx_restore = TestCtx::new();
-        let state_restore_src = make_state(vec![make_task("r", 1000)]);
-        persist(&ctx_restore, &state_restore_src).unwrap();
+        let emitted = ctx.emitted.lock().unwrap().len();
+        let report =
+            finish_caldav_sync_impl(&ctx, &state, &config, &previous, &remote, &plan, outcome)
+                .data
+                .unwrap();
+        assert_eq!((report.pushed, report.pulled, report.conflicts), (0, 2, 0));
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(ctx.emitted.lock().unwrap().len(), emitted + 1);
 
-        let storage = Storage::new(ctx_restore.root_path().to_path_buf());
-        storage.ensure_dirs().unwrap();
-        storage
-            .create_backup(&ctx_restore.root_path().join("data.json"))
+        let tasks = state.tasks();
+        let title_of = |id: &str| tasks.iter().find(|t| t.id == id).unwrap().title.clone();
+        assert_eq!(title_of("edited"), "Remote title");
+        assert_eq!(title_of("raced"), "task-raced");
+        assert_eq!(title_of("fresh"), "From the server");
+        assert_eq!(
+            state.settings().last_caldav_sync_at,
+            Some(state.clock().timestamp())
+        );
+        let saved = Storage::new(ctx.root_path().to_path_buf())
+            .load_caldav_sync()
             .unwrap();
-        let backup_name = storage.list_backups().unwrap()[0].0.clone();
-
-        let state_restore_dst = make_state(Vec::new());
-        assert!(!restore_backup_impl(&ctx_restore, &state_restore_dst, "missing.json".into()).ok);
-        let res = restore_backup_impl(&ctx_restore, &state_restore_dst, backup_name);
-        assert!(res.ok);
-        assert_eq!(state_restore_dst.tasks().len(), 1);
-        assert!(!ctx_restore.emitted.lock().unwrap().is_empty());
-
-        // import_backup: error + success.
-        let external = ctx_restore.root_path().join("external.json");
-        fs::write(
-            &external,
-            serde_json::to_string_pretty(&state_restore_src.tasks_file()).unwrap(),
-        )
-        .unwrap();
-        let state_import_dst = make_state(Vec::new());
-        let res = import_backup_impl(
-            &ctx_restore,
-            &state_import_dst,
-            external.to_string_lossy().to_string(),
+        assert_eq!(saved.url, "https://dav.example.com/cal/");
+        assert_eq!(
+            saved.items.keys().collect::<Vec<_>>(),
+            vec!["edited", "fresh", "keep"]
         );
-        assert!(res.ok);
-        assert_eq!(state_import_dst.tasks().len(), 1);
-        assert!(!import_backup_impl(&ctx_restore, &state_import_dst, "no-such-file".into()).ok);
     }
 
     #[test]
-    fn export_commands_write_files_and_return_paths() {
-        let ctx = TestCtx::new();
-        let state = make_state(vec![make_task("a", 123)]);
+    fn search_tasks_ranks_hits_and_clamps_limit() {
+        let mut notes = make_task("notes", 1000);
+        notes.notes = Some("ask about the invoice".to_string());
+        let mut title = make_task("title", 2000);
+        title.title = "Invoice for March".to_string();
+        let mut done = make_task("done", 3000);
+        done.title = "Invoice sent".to_string();
+        done.completed = true;
+        let state = make_state(vec![notes, title, done, make_task("other", 4000)]);
 
-        let json = export_tasks_json_impl(&ctx, &state);
-        assert!(json.ok);
-        let json_path = json.data.unwrap();
-        assert!(std::path::Path::new(&json_path).exists());
-        let json_text = std::fs::read_to_string(&json_path).unwrap();
-        assert!(json_text.contains("\"tasks\""));
+        let hits = search_tasks_impl(&state, "invoice".to_string(), None, None)
+            .data
+            .unwrap();
+        let ids: Vec<_> = hits.iter().map(|hit| hit.task.id.as_str()).collect();
+        assert_eq!(ids, vec!["title", "notes"]);
 
-        let csv = export_tasks_csv_impl(&ctx, &state);
-        assert!(csv.ok);
-        let csv_path = csv.data.unwrap();
-        assert!(std::path::Path::new(&csv_path).exists());
-        let csv_text = std::fs::read_to_string(&csv_path).unwrap();
-        assert!(csv_text
-            .lines()
-            .next()
+        let hits = search_tasks_impl(&state, "invoice".to_string(), Some(true), Some(0))
+            .data
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(search_tasks_impl(&state, " ".to_string(), None, None)
+            .data
             .unwrap()
-            .contains("id,project_id,title,due_at"));
-
-        let md = export_tasks_markdown_impl(&ctx, &state);
-        assert!(md.ok);
-        let md_path = md.data.unwrap();
-        assert!(std::path::Path::new(&md_path).exists());
-        let md_text = std::fs::read_to_string(&md_path).unwrap();
-        assert!(md_text.contains("# MustDo Export"));
-        assert!(md_text.contains("## Overdue"));
+            .is_empty());
     }
 
     #[test]
-    fn export_commands_fail_when_app_data_dir_is_not_a_directory() {
-        let mut ctx = TestCtx::new();
-        let file_root = ctx.root_path().join("not-a-dir");
-        std::fs::write(&file_root, b"x").unwrap();
-        ctx.set_app_data_dir_override(file_root);
+    fn undo_last_action_restores_deleted_project_and_redo_reapplies() {
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
+        let err_of = |res: CommandResult<UndoSummary>| res.error.unwrap();
+        assert_eq!(
+            err_of(undo_last_action_impl(&ctx, &state)),
+            "nothing to undo"
+        );
+        assert_eq!(
+            err_of(redo_last_action_impl(&ctx, &state)),
+            "nothing to redo"
+        );
 
-        let state = make_state(vec![make_task("a", 123)]);
-        let res = export_tasks_json_impl(&ctx, &state);
+        let project = Project {
+            id: "p1".to_string(),
+            name: "Project 1".to_string(),
+            pinned: false,
+            sort_order: 0,
+            created_at: 0,
+            updated_at: 0,
+            sample_tag: None,
+            reminders_muted: false,
+            muted_until: None,
+            notification_sound: None,
+            color: None,
+            icon: None,
+            parent_id: None,
+        };
+        assert!(create_project_impl(&ctx, &state, project).ok);
+        let mut task = make_task("x", 123);
+        task.project_id = "p1".to_string();
+        assert!(create_task_impl(&ctx, &state, task).ok);
+        assert!(create_task_impl(&ctx, &state, make_task("y", 456)).ok);
+        assert!(delete_project_impl(&ctx, &state, "p1".to_string()).ok);
+        assert!(delete_task_impl(&ctx, &state, "y".to_string()).ok);
+        let status = get_undo_status_impl(&state).data.unwrap();
+        assert_eq!(status.undo.unwrap().action, "delete_task");
+
+        let emitted = ctx.emitted.lock().unwrap().len();
+        let undone = undo_last_action_impl(&ctx, &state).data.unwrap();
+        assert_eq!((undone.action.as_str(), undone.tasks), ("delete_task", 1));
+        assert!(ctx.emitted.lock().unwrap().len() > emitted);
+        let undone = undo_last_action_impl(&ctx, &state).data.unwrap();
+        assert_eq!(
+            (undone.action.as_str(), undone.tasks, undone.projects),
+            ("delete_project", 1, 1)
+        );
+        let saved = Storage::new(ctx.root_path().to_path_buf())
+            .load_tasks()
+            .unwrap();
+        assert!(saved.projects.iter().any(|p| p.id == "p1"));
+        let ids: Vec<_> = saved.tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["x", "y"]);
+        assert_eq!(saved.tasks[0].project_id, "p1");
+
+        let redone = redo_last_action_impl(&ctx, &state).data.unwrap();
+        assert_eq!(redone.action, "delete_project");
+        assert!(!state.projects().iter().any(|p| p.id == "p1"));
+        assert_eq!(state.tasks()[0].project_id, "inbox");
+        let status = get_undo_status_impl(&state).data.unwrap();
+        assert_eq!(status.redo.unwrap().action, "delete_task");
+
+        // A fresh action drops the redo branch; a failed write still reports the error.
+        assert!(bulk_complete_tasks_impl(&ctx, &state, vec!["x".to_string()]).ok);
+        assert!(get_undo_status_impl(&state).data.unwrap().redo.is_none());
+        let bad_ctx = TestCtx::with_app_data_dir_error("nope");
+        let res = undo_last_action_impl(&bad_ctx, &state);
         assert!(!res.ok);
+        assert!(!state.tasks()[0].completed);
     }
 
     #[test]
-    fn write_atomic_bytes_covers_invalid_path_and_io_error_branches() {
-        // Invalid export path: no parent => ok_or_else branch.
-        let err = write_atomic_bytes(std::path::Path::new(""), b"x")
-            .expect_err("empty path should be rejected");
-        assert!(format!("{err}").contains("invalid export path"));
-        assert!(is_io(&err));
+    fn suggest_next_task_ranks_open_tasks_and_clamps_limit() {
+        use crate::clock::{AppClock, FixedClock};
+        let now = 1_700_000_000;
+        let mut done = make_task("done", now - 60);
+        done.completed = true;
+        let mut important = make_task("important", now + 30 * 24 * 60 * 60);
+        important.important = true;
+        let state = AppState::with_clock(
+            vec![
+                make_task("overdue", now - 60 * 60),
+                done,
+                important,
+                make_task("later", now + 30 * 24 * 60 * 60),
+            ],
+            Vec::new(),
+            Settings::default(),
+            AppClock::new(std::sync::Arc::new(FixedClock(now))),
+        );
 
-        // Ensure both branches of `is_io` are exercised for coverage.
-        let json_err: StorageError = serde_json::from_str::<serde_json::Value>("oops")
-            .unwrap_err()
-            .into();
-        assert!(!is_io(&json_err));
+        let top = suggest_next_task_impl(&state, None).data.unwrap();
+        let ids: Vec<&str> = top.iter().map(|s| s.task.id.as_str()).collect();
+        assert_eq!(ids, ["overdue", "important", "later"]);
+        assert!(!top[0].reasons.is_empty());
+        assert_eq!(
+            suggest_next_task_impl(&state, Some(0)).data.unwrap().len(),
+            1
+        );
+        assert_eq!(
+            suggest_next_task_impl(&state, Some(99)).data.unwrap().len(),
+            3
+        );
+    }
 
-        // Force fs::write to fail by making the temp path a directory.
-        let root = tempfile::tempdir().unwrap();
-        let dest = root.path().join("out.json");
-        let tmp = dest.with_extension("tmp");
-        std::fs::create_dir_all(&tmp).unwrap();
-        let err = write_atomic_bytes(&dest, b"x").expect_err("writing to a directory should fail");
-        assert!(is_io(&err));
+    #[test]
+    fn update_settings_normalizes_and_validates_work_hours() {
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
+        let mut settings = state.settings();
+        settings.work_day_start = "8:00".to_string();
+        let saved = update_settings_impl(&ctx, &state, settings.clone())
+            .data
+            .unwrap();
+        assert_eq!(saved.work_day_start, "08:00");
 
-        // Force fs::rename to fail by making the destination a directory.
-        let dest_dir = root.path().join("dest");
-        std::fs::create_dir_all(&dest_dir).unwrap();
-        let err =
-            write_atomic_bytes(&dest_dir, b"x").expect_err("renaming onto a directory should fail");
-        assert!(is_io(&err));
+        settings.work_day_end = "07:00".to_string();
+        assert!(!update_settings_impl(&ctx, &state, settings).ok);
+        assert_eq!(state.settings().work_day_end, "18:00");
     }
 
     #[test]
-    fn bulk_update_tasks_updates_multiple_tasks_and_persists_once() {
+    fn update_settings_normalizes_and_validates_daily_summary_time() {
         let ctx = TestCtx::new();
-        let state = make_state(vec![make_task("a", 100), make_task("b", 200)]);
+        let state = make_state(Vec::new());
+        let mut settings = state.settings();
+        settings.daily_summary_enabled = true;
+        settings.daily_summary_time = "7:30".to_string();
+        let saved = update_settings_impl(&ctx, &state, settings.clone())
+            .data
+            .unwrap();
+        assert_eq!(saved.daily_summary_time, "07:30");
 
-        let mut a = make_task("a", 555);
-        a.quadrant = 3;
-        a.sort_order = 999;
-        let mut b = make_task("b", 666);
-        b.quadrant = 2;
+        settings.daily_summary_time = "evening".to_string();
+        let res = update_settings_impl(&ctx, &state, settings);
+        assert!(res.error.unwrap().contains("daily summary time"));
+        assert_eq!(state.settings().daily_summary_time, "07:30");
+    }
 
-        let res = bulk_update_tasks_impl(&ctx, &state, vec![a, b]);
-        assert!(res.ok);
+    #[test]
+    fn update_settings_switches_storage_engine_once_and_load_state_follows_the_data_dir() {
+        use crate::models::StorageEngine;
 
-        let tasks = state.tasks();
-        assert_eq!(tasks.len(), 2);
-        let a = tasks.iter().find(|t| t.id == "a").unwrap();
-        let b = tasks.iter().find(|t| t.id == "b").unwrap();
-        assert_eq!(a.due_at, 555);
-        assert_eq!(a.quadrant, 3);
-        assert_eq!(b.due_at, 666);
-        assert_eq!(b.quadrant, 2);
+        let ctx = TestCtx::new();
+        let state = make_state(vec![make_task("a", 1000)]);
+        persist(&ctx, &state).unwrap();
+        let root = ctx.app_data_dir().unwrap();
 
-        // One persist => one state_updated emission.
-        assert_eq!(ctx.emitted.lock().unwrap().len(), 1);
+        let mut settings = state.settings();
+        settings.storage_engine = StorageEngine::Sqlite;
+        let saved = update_settings_impl(&ctx, &state, settings).data.unwrap();
+        assert_eq!(saved.storage_engine, StorageEngine::Sqlite);
+        assert!(root.join("data.sqlite").is_file());
+        assert!(!root.join("data.json").exists());
+
+        // Later writes go to the database; a fresh load reads them back.
+        let fresh = make_state(Vec::new());
+        create_task_impl(&ctx, &state, make_task("b", 2000));
+        let loaded = load_state_impl(&ctx, &fresh).data.unwrap();
+        assert_eq!(loaded.tasks.len(), 2);
+        assert_eq!(loaded.settings.storage_engine, StorageEngine::Sqlite);
+
+        // A hand-edited settings file cannot point at the wrong engine.
+        let mut stale = state.settings();
+        stale.storage_engine = StorageEngine::Json;
+        Storage::new(root.clone())
+            .save_settings(&crate::models::SettingsFile {
+                schema_version: 1,
+                settings: stale,
+            })
+            .unwrap();
+        let loaded = load_state_impl(&ctx, &fresh).data.unwrap();
+        assert_eq!(loaded.settings.storage_engine, StorageEngine::Sqlite);
+
+        let failing = TestCtx::with_app_data_dir_error("disk gone");
+        let mut settings = state.settings();
+        settings.storage_engine = StorageEngine::Json;
+        let result = update_settings_impl(&failing, &state, settings);
+        assert!(result.error.unwrap().contains("app_data_dir error"));
+        assert_eq!(state.settings().storage_engine, StorageEngine::Sqlite);
     }
 
     #[test]
-    fn bulk_complete_tasks_marks_completed_and_spawns_next_for_repeat() {
+    fn manual_backups_also_go_to_the_backup_directory_when_it_is_available() {
         let ctx = TestCtx::new();
-        let mut repeating = make_task("r", 1000);
-        repeating.repeat = RepeatRule::Daily {
-            workday_only: false,
-        };
+        let state = make_state(vec![make_task("a", 1000)]);
+        persist(&ctx, &state).unwrap();
+        let root = ctx.app_data_dir().unwrap();
+        let synced = tempfile::tempdir().unwrap();
+
+        for bad in [
+            "relative/dir".to_string(),
+            root.join("backups").display().to_string(),
+        ] {
+            let mut settings = state.settings();
+            settings.backup_directory = bad;
+            assert!(!update_settings_impl(&ctx, &state, settings).ok);
+        }
+        let mut settings = state.settings();
+        settings.backup_directory = format!(" {} ", synced.path().display());
+        let saved = update_settings_impl(&ctx, &state, settings).data.unwrap();
+        assert_eq!(saved.backup_directory, synced.path().display().to_string());
 
-        let state = make_state(vec![make_task("a", 100), repeating.clone()]);
-        let res = bulk_complete_tasks_impl(&ctx, &state, vec!["a".to_string(), "r".to_string()]);
-        assert!(res.ok);
+        assert!(create_backup_impl(&ctx, &state).ok);
+        let names = |dir: &Path| {
+            fs::read_dir(dir)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+        };
+        let mirrored = names(synced.path());
+        assert_eq!(mirrored.len(), 1);
+        assert_eq!(names(&root.join("backups")), mirrored);
+
+        // The folder went away (e.g. an unmounted drive): the backup still lands locally.
+        let gone = synced.path().to_path_buf();
+        drop(synced);
+        assert!(create_backup_impl(&ctx, &state).ok);
+        assert_eq!(names(&root.join("backups")).len(), 2);
+        assert!(!gone.exists());
+    }
 
-        let tasks = state.tasks();
-        let a = tasks.iter().find(|t| t.id == "a").unwrap();
-        let r_done = tasks.iter().find(|t| t.id == "r").unwrap();
-        assert!(a.completed);
-        assert!(r_done.completed);
+    #[test]
+    fn write_behind_defers_the_disk_write_until_flushed_or_backed_up() {
+        let mut ctx = TestCtx::new();
+        ctx.write_behind = true;
+        let state = make_state(vec![make_task("a", 1000)]);
+        let data_path = ctx.app_data_dir().unwrap().join("data.json");
+        persist(&ctx, &state).unwrap();
+        assert!(!data_path.exists());
+        assert_eq!(ctx.emitted.lock().unwrap().len(), 1);
+        assert!(state.write_queue().is_pending());
 
-        // A repeat task should spawn the next instance.
-        let expected_next_due = next_due_timestamp(repeating.due_at, &repeating.repeat);
-        let r_next = tasks
-            .iter()
-            .find(|t| t.id.starts_with("r-"))
-            .expect("next repeat task should exist");
-        assert!(!r_next.completed);
-        assert_eq!(r_next.due_at, expected_next_due);
+        assert_eq!(flush_storage_impl(&state).data, Some(true));
+        assert_eq!(flush_storage_impl(&state).data, Some(false));
+        let stored = Storage::new(ctx.app_data_dir().unwrap());
+        assert_eq!(stored.load_tasks().unwrap().tasks.len(), 1);
 
-        // One persist => one state_updated emission.
-        assert_eq!(ctx.emitted.lock().unwrap().len(), 1);
+        // Backups copy the file on disk, so queued changes are written before one is taken.
+        state.add_task(make_task("b", 1000));
+        persist(&ctx, &state).unwrap();
+        assert!(create_backup_impl(&ctx, &state).ok);
+        assert!(!state.write_queue().is_pending());
+        let newest = stored.list_backups().unwrap()[0].0.clone();
+        assert_eq!(stored.read_backup(&newest).unwrap().tasks.len(), 2);
+
+        // persist_now writes at once even with write-behind on.
+        state.add_task(make_task("c", 1000));
+        persist_now(&ctx, &state).unwrap();
+        assert_eq!(stored.load_tasks().unwrap().tasks.len(), 3);
     }
 
     #[test]
-    fn project_commands_cover_create_update_swap_and_delete_paths() {
-        let ctx = TestCtx::new();
-        let state = make_state(Vec::new());
-
-        let project = Project {
-            id: "p1".to_string(),
-            name: "Project 1".to_string(),
-            pinned: false,
-            sort_order: 0,
-            created_at: 0,
-            updated_at: 0,
-            sample_tag: None,
-        };
+    fn failed_background_writes_are_reported_and_make_commands_fail() {
+        let mut ctx = TestCtx::new();
+        ctx.write_behind = true;
+        let blocker = ctx.root_path().join("not-a-dir");
+        fs::write(&blocker, b"x").unwrap();
+        ctx.set_app_data_dir_override(blocker.clone());
+        let state = make_state(vec![make_task("a", 1000)]);
 
-        let res = create_project_impl(&ctx, &state, project.clone());
-        assert!(res.ok);
-        assert!(state.projects().iter().any(|p| p.id == "p1"));
+        // A quiet writer-thread pass reports nothing.
+        flush_write_behind_impl(&ctx, &state);
+        assert!(ctx.write_statuses.lock().unwrap().is_empty());
 
-        // Also cover the branches where created_at/sort_order are already set.
-        let preset = Project {
-            id: "p2".to_string(),
-            name: "Project 2".to_string(),
-            pinned: false,
-            sort_order: 42,
-            created_at: 123,
-            updated_at: 0,
-            sample_tag: None,
-        };
-        let res = create_project_impl(&ctx, &state, preset.clone());
-        assert!(res.ok);
-        let created = res.data.unwrap();
-        assert_eq!(created.id, "p2");
-        assert_eq!(created.created_at, 123);
-        assert_eq!(created.sort_order, 42);
+        persist(&ctx, &state).unwrap();
+        flush_write_behind_impl(&ctx, &state);
+        let status = get_write_status_impl(&state).data.unwrap();
+        assert!(status.pending);
+        assert!(status.last_error.is_some());
+        assert_eq!(*ctx.write_statuses.lock().unwrap(), vec![status]);
+
+        // While writes fail, commands write themselves and return the error.
+        let mut task = state.task("a").unwrap();
+        task.title = "edited".to_string();
+        assert!(!update_task_impl(&ctx, &state, task).ok);
+
+        // Once the disk is back, the next pass writes and says so.
+        fs::remove_file(&blocker).unwrap();
+        flush_write_behind_impl(&ctx, &state);
+        assert_eq!(
+            ctx.write_statuses.lock().unwrap().last(),
+            Some(&WriteStatus::default())
+        );
+        assert_eq!(
+            get_write_status_impl(&state).data,
+            Some(WriteStatus::default())
+        );
+        assert_eq!(Storage::new(blocker).load_tasks().unwrap().tasks.len(), 1);
+    }
 
-        let mut updated = project.clone();
-        updated.name = "Updated".to_string();
-        let res = update_project_impl(&ctx, &state, updated);
-        assert!(res.ok);
+    #[test]
+    fn app_emits_only_the_changed_tasks_as_numbered_deltas() {
+        let mut ctx = TestCtx::new();
+        ctx.write_behind = true;
+        ctx.emit_deltas = true;
+        let state = make_state(vec![make_task("a", 1000), make_task("b", 2000)]);
+        let mut b = state.task("b").unwrap();
+        b.title = "renamed".to_string();
+        assert!(update_task_impl(&ctx, &state, b).ok);
+        assert!(delete_task_impl(&ctx, &state, "a".into()).ok);
+
+        let deltas = ctx.deltas.lock().unwrap();
+        assert!(ctx.emitted.lock().unwrap().is_empty());
+        assert_eq!(deltas.len(), 2);
+        assert_eq!(deltas[0].seq, 1);
+        assert_eq!(deltas[0].tasks_upserted.len(), 1);
+        assert_eq!(deltas[0].tasks_upserted[0].title, "renamed");
         assert_eq!(
-            state.projects().iter().find(|p| p.id == "p1").unwrap().name,
-            "Updated"
+            (deltas[1].seq, deltas[1].tasks_removed.clone()),
+            (2, vec!["a".to_string()])
         );
+        assert_eq!(state.project_tasks("inbox").len(), 1);
+        assert_eq!(state.state_payload().seq, 2);
+    }
 
-        let inbox_before = state
-            .projects()
+    /// Command latency with a large list. Not part of the normal run:
+    /// `cargo test --no-default-features --lib --release bench_ -- --ignored --nocapture`.
+    #[test]
+    fn merge_backup_restores_only_the_selected_records_and_can_be_undone() {
+        let ctx = TestCtx::new();
+        let mut edited = make_task("edited", 1000);
+        edited.updated_at = 100;
+        let state = make_state(vec![make_task("deleted", 1000), edited]);
+        persist(&ctx, &state).unwrap();
+        assert!(create_backup_impl(&ctx, &state).ok);
+        let filename = list_backups_impl(&ctx).data.unwrap()[0].name.clone();
+
+        assert!(delete_task_impl(&ctx, &state, "deleted".into()).ok);
+        let mut edited = state
+            .tasks()
             .into_iter()
-            .find(|p| p.id == "inbox")
+            .find(|task| task.id == "edited")
             .unwrap();
-        let p1_before = state.projects().into_iter().find(|p| p.id == "p1").unwrap();
+        edited.title = "newer title".to_string();
+        assert!(update_task_impl(&ctx, &state, edited).ok);
 
-        let res = swap_project_sort_order_impl(&ctx, &state, "inbox".into(), "p1".into());
-        assert!(res.ok);
+        let preview = preview_backup_merge_impl(&ctx, &state, filename.clone())
+            .data
+            .unwrap();
+        let rows: Vec<(&str, MergeStatus)> = preview
+            .tasks
+            .iter()
+            .map(|row| (row.task.id.as_str(), row.status))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("deleted", MergeStatus::Missing),
+                ("edited", MergeStatus::CurrentNewer)
+            ]
+        );
 
-        let inbox_after = state
-            .projects()
-            .into_iter()
-            .find(|p| p.id == "inbox")
+        let ids = vec!["deleted".to_string(), "edited".to_string()];
+        let changes =
+            merge_backup_dry_run_impl(&ctx, &state, filename.clone(), ids.clone(), Vec::new())
+                .data
+                .unwrap();
+        assert_eq!(changes.created.len(), 1);
+        assert!(changes.updated.is_empty());
+        assert!(state.tasks().iter().all(|task| task.id != "deleted"));
+
+        let plan = merge_backup_impl(&ctx, &state, filename.clone(), ids, Vec::new())
+            .data
             .unwrap();
-        let p1_after = state.projects().into_iter().find(|p| p.id == "p1").unwrap();
-        assert_eq!(inbox_after.sort_order, p1_before.sort_order);
-        assert_eq!(p1_after.sort_order, inbox_before.sort_order);
+        assert_eq!(plan.kept_current, vec!["edited"]);
+        let tasks = state.tasks();
+        assert!(tasks.iter().any(|task| task.id == "deleted"));
+        assert!(tasks.iter().any(|task| task.title == "newer title"));
+        assert!(state.trash().is_empty());
+
+        assert!(!merge_backup_impl(&ctx, &state, filename, vec!["nope".into()], Vec::new()).ok);
+        assert!(undo_last_action_impl(&ctx, &state).ok);
+        assert!(state.tasks().iter().all(|task| task.id != "deleted"));
+        assert_eq!(state.trash().len(), 1);
+    }
 
-        // update_project keeps inbox pinned.
-        let mut inbox = inbox_after.clone();
-        inbox.pinned = false;
-        inbox.name = "Renamed".to_string();
-        let res = update_project_impl(&ctx, &state, inbox);
-        assert!(res.ok);
-        assert!(
-            state
-                .projects()
-                .iter()
-                .find(|p| p.id == "inbox")
-                .unwrap()
-                .pinned
+    #[test]
+    fn get_burndown_filters_by_project_and_rejects_unknown_ids() {
+        let ctx = TestCtx::new();
+        let mut done = make_task("done", 1000);
+        done.completed = true;
+        done.completed_at = Some(2000);
+        let state = make_state(vec![done, make_task("open", 1000)]);
+
+        let report = get_burndown_impl(&ctx, &state, Some("inbox".to_string()), None)
+            .data
+            .unwrap();
+        assert_eq!(report.range, BurndownRange::Month);
+        assert_eq!(report.points.len(), 30);
+        assert_eq!(report.remaining, 1);
+
+        let week = get_burndown_impl(&ctx, &state, None, Some(BurndownRange::Week))
+            .data
+            .unwrap();
+        assert_eq!(week.points.len(), 7);
+        assert!(!get_burndown_impl(&ctx, &state, Some("missing".to_string()), None).ok);
+    }
+
+    #[test]
+    fn get_statistics_defaults_to_the_last_thirty_days_and_checks_the_range() {
+        use crate::clock::{AppClock, FixedClock};
+
+        let ctx = TestCtx::new();
+        let now = Local.with_ymd_and_hms(2026, 3, 10, 9, 0, 0).unwrap();
+        let done = Task {
+            completed: true,
+            completed_at: Some(now.timestamp() - 3600),
+            ..make_task("done", now.timestamp() - 7200)
+        };
+        let state = AppState::with_clock(
+            vec![done, make_task("old", 1000)],
+            Vec::new(),
+            Settings::default(),
+            AppClock::new(std::sync::Arc::new(FixedClock(now.timestamp()))),
         );
+        let report = get_statistics_impl(&ctx, &state, None, None).data.unwrap();
         assert_eq!(
-            state
-                .projects()
-                .iter()
-                .find(|p| p.id == "inbox")
+            report.from,
+            Local
+                .with_ymd_and_hms(2026, 2, 9, 0, 0, 0)
                 .unwrap()
-                .name,
-            inbox_after.name
+                .timestamp()
         );
+        assert_eq!(report.completed, 1);
+        assert_eq!(report.completion_rate, Some(1.0));
+        assert_eq!(report.current_streak, 1);
 
-        // delete_project moves tasks to inbox first.
-        let mut task = make_task("x", 123);
-        task.project_id = "p1".to_string();
-        let res = create_task_impl(&ctx, &state, task);
-        assert!(res.ok);
-        assert_eq!(
-            state
-                .tasks()
-                .iter()
-                .find(|t| t.id == "x")
-                .unwrap()
-                .project_id,
-            "p1"
+        assert!(!get_statistics_impl(&ctx, &state, Some(10), Some(10)).ok);
+        let past = get_statistics_impl(&ctx, &state, Some(0), Some(2000))
+            .data
+            .unwrap();
+        assert_eq!((past.completed, past.completion_rate), (0, Some(0.0)));
+    }
+
+    #[test]
+    fn get_focus_stats_reads_the_focus_log_and_checks_the_range() {
+        use crate::clock::{AppClock, FixedClock};
+
+        let ctx = TestCtx::new();
+        let now = Local
+            .with_ymd_and_hms(2026, 3, 10, 9, 0, 0)
+            .unwrap()
+            .timestamp();
+        let state = AppState::with_clock(
+            vec![Task {
+                estimate_minutes: Some(20),
+                time_entries: vec![crate::models::TimeEntry {
+                    started_at: now - 7200,
+                    ended_at: Some(now - 6600),
+                }],
+                ..make_task("a", now)
+            }],
+            Vec::new(),
+            Settings::default(),
+            AppClock::new(std::sync::Arc::new(FixedClock(now))),
         );
+        let session = FocusSession {
+            task_id: "a".to_string(),
+            started_at: now - 3600,
+            ended_at: now - 2100,
+            minutes: 25,
+            focused_seconds: 1500,
+            finished: true,
+        };
+        append_focus_log(ctx.root_path(), &session).unwrap();
+
+        let report = get_focus_stats_impl(&ctx, &state, None, None).data.unwrap();
+        assert_eq!((report.tracked_seconds, report.focus_seconds), (600, 1500));
+        assert_eq!(report.projects[0].key, "inbox");
+        assert_eq!(report.estimates[0].actual_minutes, 35);
+        let later = get_focus_stats_impl(&ctx, &state, Some(now - 3000), Some(now)).data;
+        assert_eq!(later.unwrap().focus_sessions, 0);
+        assert!(!get_focus_stats_impl(&ctx, &state, Some(now), Some(now)).ok);
+    }
 
-        // Include a task that does not belong to the deleted project so both branches of the
-        // project_id check are exercised.
-        let res = create_task_impl(&ctx, &state, make_task("y", 456));
-        assert!(res.ok);
-        assert_eq!(
-            state
-                .tasks()
-                .iter()
-                .find(|t| t.id == "y")
-                .unwrap()
-                .project_id,
-            "inbox"
+    #[test]
+    fn format_dates_uses_settings_language_and_clock() {
+        use crate::clock::{AppClock, FixedClock};
+
+        let now = Local.with_ymd_and_hms(2026, 3, 10, 9, 0, 0).unwrap();
+        let settings = Settings {
+            language: "zh".to_string(),
+            ..Settings::default()
+        };
+        let state = AppState::with_clock(
+            Vec::new(),
+            Vec::new(),
+            settings.clone(),
+            AppClock::new(std::sync::Arc::new(FixedClock(now.timestamp()))),
         );
+        let tomorrow = Local
+            .with_ymd_and_hms(2026, 3, 11, 14, 0, 0)
+            .unwrap()
+            .timestamp();
+        let labels = format_dates_impl(&state, vec![tomorrow]).data.unwrap();
+        assert_eq!(labels, vec!["明天 14:00".to_string()]);
 
-        let res = delete_project_impl(&ctx, &state, "p1".to_string());
-        assert!(res.ok);
-        assert!(!state.projects().iter().any(|p| p.id == "p1"));
+        state.update_settings(Settings {
+            language: "en".to_string(),
+            ..settings
+        });
+        let labels = format_dates_impl(&state, vec![tomorrow, now.timestamp()])
+            .data
+            .unwrap();
         assert_eq!(
-            state
-                .tasks()
-                .iter()
-                .find(|t| t.id == "x")
-                .unwrap()
-                .project_id,
-            "inbox"
+            labels,
+            vec!["Tomorrow 2pm".to_string(), "Today 9am".to_string()]
         );
+    }
+
+    #[test]
+    fn list_carry_over_log_reads_entries_and_reports_errors() {
+        let ctx = TestCtx::new();
+        assert!(list_carry_over_log_impl(&ctx, None)
+            .data
+            .unwrap()
+            .is_empty());
+        let entry = CarryOverEntry {
+            at: 10,
+            task_id: "a".to_string(),
+            title: "a".to_string(),
+            from_due: 1,
+            to_due: 86_401,
+            count: 2,
+        };
+        crate::carry_over::append_carry_over_log(ctx.root_path(), std::slice::from_ref(&entry))
+            .unwrap();
         assert_eq!(
-            state
-                .tasks()
-                .iter()
-                .find(|t| t.id == "y")
-                .unwrap()
-                .project_id,
-            "inbox"
+            list_carry_over_log_impl(&ctx, Some(5)).data.unwrap(),
+            vec![entry]
         );
 
-        let res = delete_project_impl(&ctx, &state, "inbox".to_string());
-        assert!(!res.ok);
+        let bad_ctx = TestCtx::with_app_data_dir_error("nope");
+        assert!(!list_carry_over_log_impl(&bad_ctx, None).ok);
+        let dir_ctx = TestCtx::new();
+        fs::create_dir(dir_ctx.root_path().join("carry_over.jsonl")).unwrap();
+        assert!(!list_carry_over_log_impl(&dir_ctx, None).ok);
     }
 
     #[test]
-    fn project_commands_cover_validation_and_persist_error_paths() {
+    fn focus_commands_time_a_session_and_record_it() {
+        use crate::clock::{AppClock, FixedClock};
+
         let ctx = TestCtx::new();
-        let state = make_state(Vec::new());
+        let mut done = make_task("done", 1000);
+        done.completed = true;
+        let state = AppState::with_clock(
+            vec![make_task("a", 1000), done],
+            Vec::new(),
+            Settings::default(),
+            AppClock::new(std::sync::Arc::new(FixedClock(10_000))),
+        );
+        assert_eq!(get_focus_status_impl(&state).data.unwrap(), None);
+        assert!(!start_focus_session_impl(&state, "missing".into(), None).ok);
+        assert!(!start_focus_session_impl(&state, "done".into(), None).ok);
+        assert!(!start_focus_session_impl(&state, "a".into(), Some(0)).ok);
+        assert!(!pause_focus_impl(&state).ok);
 
-        let base = Project {
-            id: "p1".to_string(),
-            name: "Project 1".to_string(),
-            pinned: false,
-            sort_order: 0,
-            created_at: 0,
-            updated_at: 0,
-            sample_tag: None,
-        };
+        let status = start_focus_session_impl(&state, "a".into(), None)
+            .data
+            .unwrap();
+        assert_eq!((status.minutes, status.remaining_seconds), (25, 25 * 60));
+        state.clock().advance(300);
+        assert!(pause_focus_impl(&state).data.unwrap().paused);
+        state.clock().advance(600);
+        let resumed = resume_focus_impl(&state).data.unwrap();
+        assert_eq!(resumed.focused_seconds, 300);
+        state.clock().advance(60);
 
-        // create_project validations.
-        let mut missing_id = base.clone();
-        missing_id.id = "   ".to_string();
-        let res = create_project_impl(&ctx, &state, missing_id);
-        assert!(!res.ok);
+        let bad_ctx = TestCtx::with_app_data_dir_error("nope");
+        assert!(!stop_focus_impl(&bad_ctx, &state).ok);
+        let session = stop_focus_impl(&ctx, &state).data.unwrap().unwrap();
+        assert_eq!((session.focused_seconds, session.finished), (360, false));
+        assert_eq!(stop_focus_impl(&ctx, &state).data.unwrap(), None);
 
-        let mut missing_name = base.clone();
-        missing_name.name = "   ".to_string();
-        let res = create_project_impl(&ctx, &state, missing_name);
-        assert!(!res.ok);
+        // A session that runs out is recorded too, and can raise the forced overlay.
+        let mut settings = state.settings();
+        settings.focus_end_forced_reminder = true;
+        state.update_settings(settings);
+        start_focus_session_impl(&state, "a".into(), Some(1))
+            .data
+            .unwrap();
+        state.clock().advance(60);
+        let Some(crate::focus::FocusTick::Finished(finished)) =
+            state.focus().tick(state.clock().timestamp())
+        else {
+            panic!("expected the session to finish");
+        };
+        record_finished_focus(&ctx, &state, &finished);
+        let fired = ctx.fired.lock().unwrap().clone();
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].reminder.kind, ReminderKind::Forced);
+        assert_eq!(
+            state.tasks()[0].reminder.kind,
+            make_task("a", 1000).reminder.kind
+        );
 
-        // create_project duplicate check.
-        assert!(create_project_impl(&ctx, &state, base.clone()).ok);
-        let res = create_project_impl(&ctx, &state, base.clone());
-        assert!(!res.ok);
+        let sessions = list_focus_sessions_impl(&ctx, Some("a".into()), None)
+            .data
+            .unwrap();
+        assert_eq!(sessions, vec![finished, session]);
+        assert!(list_focus_sessions_impl(&ctx, Some("other".into()), None)
+            .data
+            .unwrap()
+            .is_empty());
+        assert!(!list_focus_sessions_impl(&bad_ctx, None, None).ok);
+    }
 
-        // create_project persist error.
-        let ctx_fail = TestCtx::new();
-        fs::write(ctx_fail.root_path().join("backups"), b"x").unwrap();
-        let state_fail = make_state(Vec::new());
-        let mut p2 = base.clone();
-        p2.id = "p2".to_string();
-        let res = create_project_impl(&ctx_fail, &state_fail, p2);
-        assert!(!res.ok);
+    #[test]
+    fn export_all_backups_writes_dated_archive_with_progress() {
+        let ctx = TestCtx::new();
+        let state = make_state(vec![make_task("a", 100)]);
+        // Saving with a backup leaves one file in backups/.
+        persist(&ctx, &state).unwrap();
+        let root = ctx.app_data_dir().unwrap();
+        Storage::new(root.clone())
+            .save_tasks(&state.tasks_file(), true)
+            .unwrap();
 
-        // update_project validations and not-found.
-        let mut empty_id = base.clone();
-        empty_id.id = "   ".to_string();
-        let res = update_project_impl(&ctx, &state, empty_id);
-        assert!(!res.ok);
+        let estimate = estimate_backup_archive_impl(&ctx).data.unwrap();
+        assert!(estimate.files >= 2);
 
-        let mut empty_name = base.clone();
-        empty_name.name = "   ".to_string();
-        let res = update_project_impl(&ctx, &state, empty_name);
-        assert!(!res.ok);
+        let out = tempfile::tempdir().unwrap();
+        let path = export_all_backups_impl(&ctx, &state, out.path().display().to_string())
+            .data
+            .unwrap();
+        assert!(path.ends_with(".zip"));
+        assert!(path.contains("mustdo-backups-"));
+        assert_eq!(fs::metadata(&path).unwrap().len(), estimate.bytes);
+        let progress = ctx.archive_progress.lock().unwrap();
+        assert_eq!(progress.len(), estimate.files);
+        assert_eq!(progress.last().unwrap().bytes_total, estimate.bytes);
+    }
 
-        let mut missing = base.clone();
-        missing.id = "missing".to_string();
-        let res = update_project_impl(&ctx, &state, missing);
-        assert!(!res.ok);
+    #[test]
+    fn export_all_backups_rejects_bad_destinations_and_reports_errors() {
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
+        assert!(!export_all_backups_impl(&ctx, &state, "relative/dir".to_string()).ok);
+        let missing = ctx.root_path().join("missing");
+        assert!(!export_all_backups_impl(&ctx, &state, missing.display().to_string()).ok);
 
-        // update_project persist error.
-        let ctx_fail2 = TestCtx::new();
-        fs::write(ctx_fail2.root_path().join("backups"), b"x").unwrap();
-        let state_fail2 = make_state(Vec::new());
-        state_fail2.add_project(base.clone());
-        let mut updated = base.clone();
-        updated.name = "Updated".to_string();
-        let res = update_project_impl(&ctx_fail2, &state_fail2, updated);
-        assert!(!res.ok);
+        let out = tempfile::tempdir().unwrap();
+        let bad_ctx = TestCtx::with_app_data_dir_error("nope");
+        assert!(!export_all_backups_impl(&bad_ctx, &state, out.path().display().to_string()).ok);
+        assert!(!estimate_backup_archive_impl(&bad_ctx).ok);
 
-        // swap_project_sort_order errors.
-        let res = swap_project_sort_order_impl(&ctx, &state, "inbox".into(), "missing".into());
-        assert!(!res.ok);
+        let blocked = TestCtx::new();
+        fs::write(blocked.root_path().join("backups"), b"x").unwrap();
+        assert!(!export_all_backups_impl(&blocked, &state, out.path().display().to_string()).ok);
+        assert!(fs::read_dir(out.path()).unwrap().next().is_none());
+    }
 
-        let ctx_fail3 = TestCtx::new();
-        fs::write(ctx_fail3.root_path().join("backups"), b"x").unwrap();
-        let state_fail3 = make_state(Vec::new());
-        state_fail3.add_project(base.clone());
-        let res =
-            swap_project_sort_order_impl(&ctx_fail3, &state_fail3, "inbox".into(), "p1".into());
-        assert!(!res.ok);
+    #[test]
+    fn export_year_review_writes_markdown_and_html() {
+        let ctx = TestCtx::new();
+        let mut done = make_task("a", 100);
+        done.completed = true;
+        done.completed_at = Some(
+            Local
+                .with_ymd_and_hms(2025, 6, 1, 12, 0, 0)
+                .unwrap()
+                .timestamp(),
+        );
+        let state = make_state(vec![done, make_task("b", 100)]);
+
+        let export = export_year_review_impl(&ctx, &state, 2025).data.unwrap();
+        assert_eq!(export.review.total_completed, 1);
+        assert_eq!(export.review.months[5].completed, 1);
+        assert!(export.markdown_path.ends_with("mustdo-year-review-2025.md"));
+        let md = fs::read_to_string(&export.markdown_path).unwrap();
+        assert!(md.starts_with("# 2025 in Review"));
+        let html = fs::read_to_string(&export.html_path).unwrap();
+        assert!(html.contains("<h1>2025 in Review</h1>"));
+        // Re-running the same year replaces the files rather than piling up copies.
+        assert!(export_year_review_impl(&ctx, &state, 2025).ok);
+        let exports = fs::read_dir(ctx.root_path().join("exports"))
+            .unwrap()
+            .count();
+        assert_eq!(exports, 2);
 
-        // delete_project errors.
-        let res = delete_project_impl(&ctx, &state, "   ".to_string());
-        assert!(!res.ok);
-        let res = delete_project_impl(&ctx, &state, "missing".to_string());
-        assert!(!res.ok);
+        assert!(!export_year_review_impl(&ctx, &state, 0).ok);
+        let bad_ctx = TestCtx::with_app_data_dir_error("nope");
+        assert!(!export_year_review_impl(&bad_ctx, &state, 2025).ok);
+        let blocked = TestCtx::new();
+        fs::write(blocked.root_path().join("exports"), b"x").unwrap();
+        assert!(!export_year_review_impl(&blocked, &state, 2025).ok);
+    }
 
-        let ctx_fail4 = TestCtx::new();
-        fs::write(ctx_fail4.root_path().join("backups"), b"x").unwrap();
-        let state_fail4 = make_state(Vec::new());
-        state_fail4.add_project(base.clone());
-        let res = delete_project_impl(&ctx_fail4, &state_fail4, "p1".to_string());
-        assert!(!res.ok);
+    #[test]
+    fn export_to_obsidian_writes_daily_notes_and_remembers_the_vault() {
+        let ctx = TestCtx::new();
+        let state = make_state(vec![make_task("a", Utc::now().timestamp())]);
+        let vault = tempfile::tempdir().unwrap();
+        let vault_path = vault.path().display().to_string();
+
+        let report = export_to_obsidian_impl(&ctx, &state, format!(" {vault_path} "))
+            .data
+            .unwrap();
+        assert_eq!(report.tasks, 1);
+        assert_eq!(report.written.len(), 1);
+        let note = vault.path().join("Daily").join(&report.written[0]);
+        assert!(fs::read_to_string(note)
+            .unwrap()
+            .contains("- [ ] task-a 📅 "));
+        assert_eq!(state.settings().obsidian_vault_path, vault_path);
+        assert_eq!(ctx.emitted.lock().unwrap().len(), 1);
     }
 
     #[test]
-    fn task_commands_normalize_invalid_project_ids_and_cover_persist_errors() {
+    fn export_to_obsidian_rejects_bad_vaults_and_folders() {
         let ctx = TestCtx::new();
         let state = make_state(Vec::new());
+        assert!(!export_to_obsidian_impl(&ctx, &state, "relative/vault".to_string()).ok);
+        let missing = ctx.root_path().join("missing");
+        assert!(!export_to_obsidian_impl(&ctx, &state, missing.display().to_string()).ok);
+
+        let vault = tempfile::tempdir().unwrap();
+        for folder in ["../outside", "/abs"] {
+            state.update_settings(Settings {
+                obsidian_daily_folder: folder.to_string(),
+                ..state.settings()
+            });
+            let res = export_to_obsidian_impl(&ctx, &state, vault.path().display().to_string());
+            assert!(!res.ok);
+        }
+        assert_eq!(state.settings().obsidian_vault_path, "");
+        assert!(!ctx.root_path().join("outside").exists());
+    }
 
-        let mut t = make_task("invalid-proj", 1000);
-        t.project_id = "missing".to_string();
-        let res = create_task_impl(&ctx, &state, t);
-        assert!(res.ok);
-        assert_eq!(res.data.as_ref().unwrap().project_id, "inbox");
+    #[test]
+    fn list_reminder_audit_reads_entries_and_reports_errors() {
+        let ctx = TestCtx::new();
+        assert!(list_reminder_audit_impl(&ctx, None)
+            .data
+            .unwrap()
+            .is_empty());
+        let tasks = vec![make_task("a", 100), make_task("b", 100)];
+        crate::digest::append_reminder_audit(
+            ctx.root_path(),
+            &crate::digest::audit_entries(&tasks, 100, true),
+        )
+        .unwrap();
+        let entries = list_reminder_audit_impl(&ctx, Some(1)).data.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].task_id, "b");
+        assert!(entries[0].grouped);
 
-        let mut edited = res.data.unwrap();
-        edited.title = "edited".to_string();
-        edited.project_id = "missing2".to_string();
-        let res = update_task_impl(&ctx, &state, edited);
-        assert!(res.ok);
-        assert_eq!(res.data.as_ref().unwrap().project_id, "inbox");
+        let bad_ctx = TestCtx::with_app_data_dir_error("nope");
+        assert!(!list_reminder_audit_impl(&bad_ctx, None).ok);
+        let dir_ctx = TestCtx::new();
+        fs::create_dir(dir_ctx.root_path().join("reminder_audit.jsonl")).unwrap();
+        assert!(!list_reminder_audit_impl(&dir_ctx, None).ok);
+    }
 
-        // bulk_update normalizes invalid project ids and hits persist error branch.
-        let ctx_fail = TestCtx::new();
-        fs::write(ctx_fail.root_path().join("backups"), b"x").unwrap();
-        let state_fail = make_state(vec![make_task("bu1", 123)]);
-        let mut update = make_task("bu1", 456);
-        update.project_id = "missing".to_string();
-        update.created_at = 2;
-        update.sort_order = 0;
-        let res = bulk_update_tasks_impl(&ctx_fail, &state_fail, vec![update]);
-        assert!(!res.ok);
+    #[test]
+    fn sound_commands_list_and_load_sounds() {
+        let ctx = TestCtx::new();
+        let sounds = ctx.root_path().join("sounds");
+        fs::create_dir_all(&sounds).unwrap();
+        fs::write(sounds.join("ding.ogg"), b"ogg").unwrap();
+        let listed = list_sounds_impl(&ctx).data.unwrap();
+        assert_eq!(listed.last().unwrap().id, "user:ding.ogg");
+        assert!(!listed.last().unwrap().bundled);
+
+        let clip = preview_sound_impl(&ctx, " user:ding.ogg ".into())
+            .data
+            .unwrap();
+        assert_eq!(clip.bytes, b"ogg");
         assert_eq!(
-            state_fail
-                .tasks()
-                .into_iter()
-                .find(|t| t.id == "bu1")
-                .unwrap()
-                .project_id,
-            "inbox"
+            preview_sound_impl(&ctx, "bell".into()).data.unwrap().tone,
+            Some("bell".to_string())
         );
+        assert!(!preview_sound_impl(&ctx, "user:gone.wav".into()).ok);
+
+        let bad_ctx = TestCtx::with_app_data_dir_error("nope");
+        assert!(!list_sounds_impl(&bad_ctx).ok);
+        assert!(!preview_sound_impl(&bad_ctx, "beep".into()).ok);
     }
 
     #[test]
@@ -2845,14 +11894,55 @@ mod tests {
         let mut none = make_task("none", 1000);
         none.reminder.kind = ReminderKind::None;
         none.reminder.remind_at = Some(900);
-        let next = build_next_repeat_task(&none, 2000);
+        none.start_at = Some(400);
+        let next = build_next_repeat_task(&none, 2000, &Default::default(), Utc::now());
         assert_eq!(next.reminder.remind_at, None);
+        // The start date keeps its lead over the due date.
+        assert_eq!(next.start_at, Some(1400));
 
         let mut forced = make_task("forced", 1000);
         forced.reminder.kind = ReminderKind::Forced;
         forced.reminder.remind_at = None;
-        let next = build_next_repeat_task(&forced, 3000);
+        let next = build_next_repeat_task(&forced, 3000, &Default::default(), Utc::now());
         assert_eq!(next.reminder.remind_at, Some(3000));
+        assert_eq!(next.reminder.kind, ReminderKind::Forced);
+
+        // An escalated reminder goes back to Normal with a clean ignore count.
+        forced.reminder.escalated = true;
+        forced.reminder.ignored_count = 4;
+        let next = build_next_repeat_task(&forced, 3000, &Default::default(), Utc::now());
+        assert_eq!(next.reminder.kind, ReminderKind::Normal);
+        assert!(!next.reminder.escalated);
+        assert_eq!(next.reminder.ignored_count, 0);
+
+        // Without an explicit remind_at the configured lead time is carried to the next instance.
+        let leads = crate::models::ReminderLeadTimes {
+            normal: 30,
+            forced: 5,
+        };
+        let next = build_next_repeat_task(&forced, 3000, &leads, Utc::now());
+        assert_eq!(next.reminder.remind_at, Some(3000 - 5 * 60));
+
+        // Relative specs move with the due time on their own.
+        forced.reminder.specs = vec![crate::models::ReminderSpec { minutes_before: 1 }];
+        let next = build_next_repeat_task(&forced, 3000, &leads, Utc::now());
+        assert_eq!(next.reminder.remind_at, None);
+        assert_eq!(next.reminder.targets(next.due_at, &leads), vec![3000 - 60]);
+    }
+
+    #[test]
+    fn update_settings_clamps_reminder_lead_times() {
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
+        let mut settings = state.settings();
+        settings.reminder_lead_minutes.normal = 1_000_000;
+        settings.reminder_lead_minutes.forced = 15;
+        let saved = update_settings_impl(&ctx, &state, settings).data.unwrap();
+        assert_eq!(
+            saved.reminder_lead_minutes.normal,
+            crate::models::MAX_REMINDER_LEAD_MINUTES
+        );
+        assert_eq!(state.settings().reminder_lead_minutes.forced, 15);
     }
 
     #[test]
@@ -2882,17 +11972,17 @@ mod tests {
         let state = make_state(Vec::new());
 
         let bad = TestCtx::with_app_data_dir_error("nope");
-        let res = export_tasks_json_impl(&bad, &state);
+        let res = export_tasks_json_impl(&bad, &state, ExportFilter::default());
         assert!(!res.ok);
 
         // success path hits default `force_json_serialize_error` implementation (returns false).
         let ok_ctx = TestCtx::new();
-        let res = export_tasks_json_impl(&ok_ctx, &state);
+        let res = export_tasks_json_impl(&ok_ctx, &state, ExportFilter::default());
         assert!(res.ok);
 
         // forced serialization error path.
         let err_ctx = ForceJsonErrorCtx::new();
-        let res = export_tasks_json_impl(&err_ctx, &state);
+        let res = export_tasks_json_impl(&err_ctx, &state, ExportFilter::default());
         assert!(!res.ok);
     }
 
@@ -2903,6 +11993,7 @@ mod tests {
             tasks: Vec::new(),
             projects: Vec::new(),
             settings: Settings::default(),
+            seq: 0,
         });
         ctx.update_tray_count(&[], &Settings::default());
         ctx.shortcut_unregister_all();
@@ -2910,6 +12001,73 @@ mod tests {
         assert!(ctx.shortcut_register("CommandOrControl+Shift+P").is_ok());
     }
 
+    #[test]
+    fn markdown_sync_completes_checked_tasks_and_reports_conflicts() {
+        let ctx = TestCtx::new();
+        let state = make_state(vec![make_task("a", 100), make_task("b", 200)]);
+        assert!(!sync_markdown_export_impl(&ctx, &state).ok);
+
+        let path = export_markdown_sync_impl(&ctx, &state).data.unwrap();
+        let exported = fs::read_to_string(&path).unwrap();
+        assert!(exported.contains("- [ ] task-a (due: "));
+        assert!(exported.contains(" <!-- mustdo:a -->\n"));
+        assert!(
+            !render_tasks_markdown(&state, &ExportFilter::default(), false)
+                .0
+                .contains("mustdo:")
+        );
+        // Nothing edited yet.
+        assert!(sync_markdown_export_impl(&ctx, &state)
+            .data
+            .unwrap()
+            .is_empty());
+
+        let edited = exported.replace("- [ ] task-a", "- [x] task-a") + "- [ ] brand new\n";
+        fs::write(&path, &edited).unwrap();
+        let report = sync_markdown_export_impl(&ctx, &state).data.unwrap();
+        assert_eq!(report.completed, ["a"]);
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].kind, SyncConflictKind::Added);
+        assert!(state.tasks().iter().any(|t| t.id == "a" && t.completed));
+        assert!(!ctx.emitted.lock().unwrap().is_empty());
+
+        // The edited file is now the baseline, so a second pass is a no-op.
+        assert!(sync_markdown_export_impl(&ctx, &state)
+            .data
+            .unwrap()
+            .is_empty());
+        assert_eq!(fs::read_to_string(&path).unwrap(), edited);
+    }
+
+    #[test]
+    fn markdown_sync_errors_leave_the_baseline_for_a_retry() {
+        let ctx = TestCtx::new();
+        let state = make_state(vec![make_task("a", 100)]);
+        let path = export_markdown_sync_impl(&ctx, &state).data.unwrap();
+        let root = ctx.app_data_dir().unwrap();
+        let edited = fs::read_to_string(&path)
+            .unwrap()
+            .replace("- [ ] task-a", "- [x] task-a");
+        fs::write(&path, &edited).unwrap();
+
+        // Persisting the completion fails: data.json is a directory.
+        fs::remove_file(root.join("data.json")).ok();
+        fs::create_dir_all(root.join("data.json")).unwrap();
+        assert!(!sync_markdown_export_impl(&ctx, &state).ok);
+        assert_ne!(
+            fs::read_to_string(crate::md_sync::baseline_path(&root)).unwrap(),
+            edited
+        );
+
+        fs::remove_file(crate::md_sync::baseline_path(&root)).unwrap();
+        let result = sync_markdown_export_impl(&ctx, &state);
+        assert!(result.error.unwrap().contains("baseline missing"));
+
+        let bad = TestCtx::with_app_data_dir_error("nope");
+        assert!(!export_markdown_sync_impl(&bad, &state).ok);
+        assert!(!sync_markdown_export_impl(&bad, &state).ok);
+    }
+
     #[test]
     fn export_tasks_csv_and_markdown_cover_error_and_formatting_branches() {
         let now = Local::now();
@@ -2941,6 +12099,7 @@ mod tests {
                 completed: true,
                 created_at: 1,
                 completed_at: Some(1),
+                parent_id: None,
             },
             Step {
                 id: "s2".to_string(),
@@ -2948,6 +12107,7 @@ mod tests {
                 completed: false,
                 created_at: 1,
                 completed_at: None,
+                parent_id: None,
             },
         ];
 
@@ -2966,13 +12126,218 @@ mod tests {
 
         // app_data_dir error paths.
         let bad = TestCtx::with_app_data_dir_error("nope");
-        assert!(!export_tasks_csv_impl(&bad, &state).ok);
-        assert!(!export_tasks_markdown_impl(&bad, &state).ok);
+        assert!(!export_tasks_csv_impl(&bad, &state, ExportFilter::default()).ok);
+        assert!(!export_tasks_markdown_impl(&bad, &state, ExportFilter::default()).ok);
 
         // Force write_atomic_bytes to fail by making `exports/` a file.
         let ctx = TestCtx::new();
         fs::write(ctx.root_path().join("exports"), b"x").unwrap();
-        assert!(!export_tasks_csv_impl(&ctx, &state).ok);
-        assert!(!export_tasks_markdown_impl(&ctx, &state).ok);
+        assert!(!export_tasks_csv_impl(&ctx, &state, ExportFilter::default()).ok);
+        assert!(!export_tasks_markdown_impl(&ctx, &state, ExportFilter::default()).ok);
+    }
+
+    #[test]
+    fn task_flair_is_validated_and_exported() {
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
+        let mut task = make_task("f", 0);
+        task.due_at = None;
+        task.someday = true;
+        task.title = "launch".to_string();
+        task.color = Some("#F80".to_string());
+        task.emoji = Some(" 🚀 ".to_string());
+        let created = create_task_impl(&ctx, &state, task.clone()).data.unwrap();
+        assert_eq!(created.color.as_deref(), Some("#ff8800"));
+        assert_eq!(created.emoji.as_deref(), Some("🚀"));
+
+        let csv = render_tasks_csv(
+            state.tasks(),
+            &state.projects(),
+            &state.tags(),
+            &state.settings(),
+            0,
+        );
+        assert!(
+            csv.starts_with("id,")
+                && csv.lines().next().unwrap().ends_with(
+                    ",color,emoji,tracked_minutes,time_entries,project_color,project_icon,tag_paths,tag_colors"
+                )
+        );
+        assert!(csv.contains(",\"#ff8800\",\"🚀\",0,\"\",\"\",\"\",\"\",\"\"\n"));
+        let (markdown, _) = render_tasks_markdown(&state, &ExportFilter::default(), false);
+        assert!(markdown.contains("- [ ] 🚀 launch\n"));
+        assert!(markdown.contains("  - color: #ff8800\n"));
+
+        let mut bad = created.clone();
+        bad.color = Some("orange".to_string());
+        let res = update_task_impl(&ctx, &state, bad.clone());
+        assert!(!res.ok);
+        assert!(res.error.unwrap().contains("invalid color"));
+        assert_eq!(state.tasks()[0].color.as_deref(), Some("#ff8800"));
+
+        bad.color = None;
+        bad.emoji = Some("rocket".to_string());
+        assert!(!bulk_update_tasks_impl(&ctx, &state, vec![created.clone(), bad]).ok);
+        let mut other = make_task("g", 0);
+        other.emoji = Some("x".to_string());
+        assert!(!create_task_impl(&ctx, &state, other).ok);
+        assert_eq!(state.tasks().len(), 1);
+
+        let mut cleared = created;
+        cleared.color = Some(String::new());
+        cleared.emoji = None;
+        let updated = update_task_impl(&ctx, &state, cleared).data.unwrap();
+        assert_eq!(updated.color, None);
+    }
+
+    #[test]
+    fn project_color_and_icon_are_validated_and_exported() {
+        let ctx = TestCtx::new();
+        let state = make_state(vec![make_task("t", 0)]);
+        let mut inbox = state.projects()[0].clone();
+        inbox.color = Some("#0AF".to_string());
+        inbox.icon = Some(" 📥 ".to_string());
+        let updated = update_project_impl(&ctx, &state, inbox.clone())
+            .data
+            .unwrap();
+        assert_eq!(updated.color.as_deref(), Some("#00aaff"));
+        assert_eq!(updated.icon.as_deref(), Some("📥"));
+
+        let csv = render_tasks_csv(
+            state.tasks(),
+            &state.projects(),
+            &state.tags(),
+            &state.settings(),
+            0,
+        );
+        assert!(csv
+            .lines()
+            .nth(1)
+            .unwrap()
+            .ends_with(",\"#00aaff\",\"📥\",\"\",\"\""));
+
+        inbox.icon = Some("inbox tray".to_string());
+        let res = update_project_impl(&ctx, &state, inbox.clone());
+        assert_eq!(res.error.as_deref(), Some("invalid icon: inbox tray"));
+        assert_eq!(state.projects()[0].icon.as_deref(), Some("📥"));
+
+        let mut work = inbox;
+        work.id = "work".to_string();
+        work.name = "Work".to_string();
+        work.icon = Some("Star".to_string());
+        work.color = Some("blue".to_string());
+        assert!(!create_project_impl(&ctx, &state, work.clone()).ok);
+        work.color = None;
+        let created = create_project_impl(&ctx, &state, work).data.unwrap();
+        assert_eq!(created.icon.as_deref(), Some("star"));
+    }
+
+    #[test]
+    fn nested_projects_reject_cycles_and_lift_children_on_delete() {
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
+        let mut folder = state.projects()[0].clone();
+        folder.pinned = false;
+        let mut project = |id: &str, parent_id: Option<&str>| -> CommandResult<Project> {
+            folder.id = id.to_string();
+            folder.name = id.to_string();
+            folder.parent_id = parent_id.map(str::to_string);
+            create_project_impl(&ctx, &state, folder.clone())
+        };
+        assert!(project("work", None).ok);
+        assert!(project("client", Some(" work ")).ok);
+        assert!(project("site", Some("client")).ok);
+        assert_eq!(
+            project("x", Some("gone")).error.as_deref(),
+            Some("parent project not found: gone")
+        );
+
+        let mut work = state
+            .projects()
+            .into_iter()
+            .find(|p| p.id == "work")
+            .unwrap();
+        work.parent_id = Some("site".to_string());
+        let res = update_project_impl(&ctx, &state, work);
+        assert_eq!(
+            res.error.as_deref(),
+            Some("project cannot be nested under itself")
+        );
+
+        let tree = project_tree_impl(&state).data.unwrap();
+        let work = tree.iter().find(|node| node.project.id == "work").unwrap();
+        assert_eq!(work.children[0].project.id, "client");
+        assert_eq!(work.children[0].children[0].project.id, "site");
+
+        assert!(delete_project_impl(&ctx, &state, "client".into()).ok);
+        let site = state
+            .projects()
+            .into_iter()
+            .find(|p| p.id == "site")
+            .unwrap();
+        assert_eq!(site.parent_id.as_deref(), Some("work"));
+        assert!(undo_last_action_impl(&ctx, &state).ok);
+        let site = state
+            .projects()
+            .into_iter()
+            .find(|p| p.id == "site")
+            .unwrap();
+        assert_eq!(site.parent_id.as_deref(), Some("client"));
+    }
+
+    #[test]
+    fn task_timezones_are_validated_on_save() {
+        let ctx = TestCtx::new();
+        let state = make_state(Vec::new());
+        let mut task = make_task("tz", 1000);
+        task.timezone = Some("Asia/Tokyo ".to_string());
+        let created = create_task_impl(&ctx, &state, task).data.unwrap();
+        assert_eq!(created.timezone.as_deref(), Some("Asia/Tokyo"));
+
+        let mut bad = created.clone();
+        bad.timezone = Some("Tokyo".to_string());
+        let res = update_task_impl(&ctx, &state, bad.clone());
+        assert!(res.error.unwrap().contains("invalid timezone"));
+        assert!(!bulk_update_tasks_impl(&ctx, &state, vec![bad]).ok);
+        assert_eq!(state.tasks()[0].timezone.as_deref(), Some("Asia/Tokyo"));
+    }
+
+    #[test]
+    fn undated_tasks_export_without_due_and_repeat_from_completion() {
+        let ctx = TestCtx::new();
+        let mut undated = make_task("u", 0);
+        undated.due_at = None;
+        undated.title = "call plumber".to_string();
+        let mut repeating = make_task("r", 0);
+        repeating.due_at = None;
+        repeating.repeat = RepeatRule::Daily {
+            workday_only: false,
+        };
+        let state = make_state(vec![undated, repeating]);
+
+        let csv = render_tasks_csv(
+            state.tasks(),
+            &state.projects(),
+            &state.tags(),
+            &state.settings(),
+            0,
+        );
+        assert!(csv.contains("\n\"u\",\"inbox\",\"call plumber\",,,false,"));
+        let (markdown, [_, _, future, _, _]) =
+            render_tasks_markdown(&state, &ExportFilter::default(), false);
+        assert_eq!(future, 2);
+        assert!(markdown.contains("- [ ] call plumber\n"));
+
+        let next = complete_task_impl(&ctx, &state, "r".into()).data.unwrap();
+        let done_at = state
+            .tasks()
+            .into_iter()
+            .find(|t| t.id == "r")
+            .and_then(|t| t.completed_at)
+            .unwrap();
+        assert_eq!(
+            next.due_at,
+            Some(crate::repeat::next_due_timestamp(done_at, &next.repeat))
+        );
     }
 }
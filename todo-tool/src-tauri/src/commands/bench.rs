@@ -0,0 +1,92 @@
+//! Command latency on a large task list, driven by `benches/command_latency.rs`. It lives in the
+//! library because `CommandCtx` and the `*_impl` commands are crate-private; the bench target only
+//! picks the sizes and prints the numbers.
+
+use super::*;
+use crate::importers::blank_task;
+use std::time::Duration;
+
+/// A `CommandCtx` that behaves like the app (write-behind, state deltas) with nothing listening.
+struct BenchCtx {
+    root: PathBuf,
+}
+
+impl CommandCtx for BenchCtx {
+    fn app_data_dir(&self) -> Result<PathBuf, StorageError> {
+        Ok(self.root.clone())
+    }
+
+    fn emit_state_updated(&self, _payload: StatePayload) {}
+
+    fn emit_state_changes(&self, state: &AppState) {
+        state.take_delta();
+    }
+
+    fn write_behind(&self) -> bool {
+        true
+    }
+
+    fn fire_reminder(&self, _tasks: &[Task]) {}
+
+    fn update_tray_count(&self, _tasks: &[Task], _settings: &Settings) {}
+
+    fn shortcut_unregister_all(&self) {}
+
+    fn shortcut_validate(&self, _shortcut: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn shortcut_register(&self, _shortcut: &str) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Runs lookup, snapshot and the common task commands `rounds` times each against `tasks`
+/// tasks saved under `root`, and returns the mean time per call. The last row is the one
+/// flush that writes everything the commands queued.
+pub fn command_latency(root: &Path, tasks: usize, rounds: usize) -> Vec<(&'static str, Duration)> {
+    let ctx = BenchCtx {
+        root: root.to_path_buf(),
+    };
+    let state = AppState::new(
+        (0..tasks)
+            .map(|i| Task {
+                due_at: Some(1000 + i as i64),
+                ..blank_task(format!("t{i}"), "inbox", "task", 1, i)
+            })
+            .collect(),
+        Vec::new(),
+        Settings::default(),
+    );
+    let id = |round: usize| format!("t{}", round * 97 % tasks);
+    let mut rows = Vec::new();
+    let mut measure = |name: &'static str, run: &mut dyn FnMut(usize)| {
+        let start = Instant::now();
+        for round in 0..rounds {
+            run(round);
+        }
+        rows.push((name, start.elapsed() / rounds as u32));
+    };
+
+    measure("lookup", &mut |round| {
+        assert!(state.task(&id(round)).is_some());
+    });
+    measure("snapshot", &mut |_| {
+        assert_eq!(state.snapshot().tasks.len(), tasks);
+    });
+    measure("update_task", &mut |round| {
+        let mut task = state.task(&id(round)).unwrap();
+        task.title = format!("edited {round}");
+        assert!(update_task_impl(&ctx, &state, task).ok);
+    });
+    measure("pin_task", &mut |round| {
+        assert!(set_task_pinned_impl(&ctx, &state, id(round), round % 2 == 0).ok);
+    });
+    measure("complete_task", &mut |round| {
+        assert!(complete_task_impl(&ctx, &state, id(round)).ok);
+    });
+    let start = Instant::now();
+    assert_eq!(flush_storage_impl(&state).data, Some(true));
+    rows.push(("flush (one write of data.json)", start.elapsed()));
+    rows
+}
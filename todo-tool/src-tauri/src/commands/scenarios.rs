@@ -0,0 +1,565 @@
+//! Scenario harness: replays the recorded command sequences in `testcases/scenarios/*.json`
+//! against the real `*_impl` functions, a temp data dir and a scripted `CommandCtx`.
+//!
+//! A scenario file looks like:
+//!
+//! ```json
+//! {
+//!   "description": "...",
+//!   "now": "2026-03-18T09:00:00",
+//!   "settings": { "backup_schedule": "none" },
+//!   "steps": [
+//!     { "command": "create_task", "args": { "task": { "id": "a", "title": "A" } }, "bind": "a" },
+//!     { "advance": 3600 },
+//!     { "command": "complete_task", "args": { "task_id": "a" }, "data": { "id": "$any" } },
+//!     { "state": { "tasks": [{ "id": "a", "completed": true }, { "title": "A" }] } },
+//!     { "disk": { "tasks": [{}, {}] } }
+//!   ]
+//! }
+//! ```
+//!
+//! Within a step the parts run in this order: `advance` (clock seconds), `fail_storage`
+//! (true makes every data-dir lookup fail until set back to false), `command` with its
+//! `ok`/`error`/`data`/`events` checks, then the `state` and `disk` snapshots.
+//!
+//! Expectations are subsets: objects only check the keys they list, arrays must have the same
+//! length and are compared in order, and `"$any"` matches anything. In args and expectations
+//! `"$now"` / `"$now+N"` / `"$now-N"` become the clock timestamp, `"${name.path}"` reads a value
+//! bound earlier with `"bind"`, and `{"$from": "task:<id>" | "project:<id>" | "settings", ...}`
+//! starts from the current record and overrides the listed keys. `create_task` fills the
+//! bookkeeping fields (`created_at`, `notes`, ...) so files only spell out what matters.
+
+use super::*;
+use crate::clock::{AppClock, FixedClock};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+const SCENARIO_DIR: &str = "testcases/scenarios";
+const STORAGE_FAILURE: &str = "scripted storage failure";
+
+#[derive(Default)]
+struct EmitCounts {
+    state_updated: usize,
+    reminders: usize,
+    outbound: usize,
+}
+
+struct ScriptedCtx {
+    root: tempfile::TempDir,
+    fail_storage: Mutex<bool>,
+    counts: Mutex<EmitCounts>,
+}
+
+impl ScriptedCtx {
+    fn new() -> Self {
+        Self {
+            root: tempfile::tempdir().unwrap(),
+            fail_storage: Mutex::new(false),
+            counts: Mutex::new(EmitCounts::default()),
+        }
+    }
+
+    fn take_counts(&self) -> EmitCounts {
+        std::mem::take(&mut *self.counts.lock().unwrap())
+    }
+}
+
+impl CommandCtx for ScriptedCtx {
+    fn app_data_dir(&self) -> Result<PathBuf, StorageError> {
+        if *self.fail_storage.lock().unwrap() {
+            return Err(StorageError::Io(std::io::Error::other(STORAGE_FAILURE)));
+        }
+        Ok(self.root.path().to_path_buf())
+    }
+
+    fn emit_state_updated(&self, _payload: StatePayload) {
+        self.counts.lock().unwrap().state_updated += 1;
+    }
+
+    fn dispatch_event(&self, _event: OutboundEvent) {
+        self.counts.lock().unwrap().outbound += 1;
+    }
+
+    fn fire_reminder(&self, tasks: &[Task]) {
+        self.counts.lock().unwrap().reminders += tasks.len();
+    }
+
+    fn update_tray_count(&self, _tasks: &[Task], _settings: &Settings) {}
+
+    fn shortcut_unregister_all(&self) {}
+
+    fn shortcut_validate(&self, _shortcut: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn shortcut_register(&self, _shortcut: &str) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Scenario {
+    description: String,
+    /// Local wall-clock start, `YYYY-MM-DDTHH:MM:SS`.
+    now: String,
+    /// Overrides on top of `Settings::default()` before the first step.
+    #[serde(default)]
+    settings: Option<Value>,
+    steps: Vec<ScenarioStep>,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ScenarioStep {
+    advance: Option<i64>,
+    fail_storage: Option<bool>,
+    command: Option<String>,
+    args: Value,
+    /// Expected `CommandResult.ok`; a command step expects success unless told otherwise.
+    ok: Option<bool>,
+    /// Substring of `CommandResult.error`.
+    error: Option<String>,
+    data: Option<Value>,
+    events: Option<ExpectedEvents>,
+    bind: Option<String>,
+    state: Option<Value>,
+    disk: Option<Value>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExpectedEvents {
+    #[serde(default)]
+    state_updated: Option<usize>,
+    #[serde(default)]
+    reminders: Option<usize>,
+    #[serde(default)]
+    outbound: Option<usize>,
+}
+
+struct Runner {
+    ctx: ScriptedCtx,
+    state: AppState,
+    vars: BTreeMap<String, Value>,
+}
+
+impl Runner {
+    fn new(scenario: &Scenario) -> Result<Self, String> {
+        let start = chrono::NaiveDateTime::parse_from_str(&scenario.now, "%Y-%m-%dT%H:%M:%S")
+            .map_err(|e| format!("bad `now` {:?}: {e}", scenario.now))?;
+        let start = Local
+            .from_local_datetime(&start)
+            .single()
+            .ok_or_else(|| format!("ambiguous `now` {:?}", scenario.now))?
+            .timestamp();
+        let mut settings = serde_json::to_value(Settings::default()).unwrap();
+        if let Some(overrides) = &scenario.settings {
+            merge(&mut settings, overrides);
+        }
+        let settings: Settings =
+            serde_json::from_value(settings).map_err(|e| format!("bad `settings`: {e}"))?;
+        let clock = AppClock::new(Arc::new(FixedClock(start)));
+        Ok(Self {
+            ctx: ScriptedCtx::new(),
+            state: AppState::with_clock(Vec::new(), Vec::new(), settings, clock),
+            vars: BTreeMap::new(),
+        })
+    }
+
+    fn run_step(&mut self, step: &ScenarioStep) -> Result<(), String> {
+        if let Some(seconds) = step.advance {
+            self.state.clock().advance(seconds);
+        }
+        if let Some(fail) = step.fail_storage {
+            *self.ctx.fail_storage.lock().unwrap() = fail;
+        }
+        if let Some(command) = &step.command {
+            self.run_command(command, step)?;
+        }
+        if let Some(expected) = &step.state {
+            let actual = json!({
+                "tasks": self.state.tasks(),
+                "projects": self.state.projects(),
+                "settings": self.state.settings(),
+            });
+            let expected = self.resolve(expected)?;
+            matches(&expected, &actual, "state")?;
+        }
+        if let Some(expected) = &step.disk {
+            let root = self.ctx.root.path().to_path_buf();
+            let file = Storage::new(root)
+                .load_tasks()
+                .map_err(|e| format!("disk: load failed: {e}"))?;
+            let expected = self.resolve(expected)?;
+            matches(&expected, &serde_json::to_value(file).unwrap(), "disk")?;
+        }
+        Ok(())
+    }
+
+    fn run_command(&mut self, command: &str, step: &ScenarioStep) -> Result<(), String> {
+        let mut args = self.resolve(&step.args)?;
+        if command == "create_task" {
+            if let Some(task) = args.get_mut("task") {
+                fill_new_task(task, self.state.clock().timestamp());
+            }
+        }
+        self.ctx.take_counts();
+        let result = dispatch(&self.ctx, &self.state, command, &args)?;
+        let counts = self.ctx.take_counts();
+
+        let ok = result["ok"].as_bool().unwrap_or(false);
+        let expect_ok = step.ok.unwrap_or(step.error.is_none());
+        if ok != expect_ok {
+            return Err(format!("expected ok={expect_ok}, got {result}"));
+        }
+        if let Some(needle) = &step.error {
+            let error = result["error"].as_str().unwrap_or_default();
+            if !error.contains(needle.as_str()) {
+                return Err(format!("error {error:?} does not contain {needle:?}"));
+            }
+        }
+        if let Some(expected) = &step.data {
+            let expected = self.resolve(expected)?;
+            matches(&expected, &result["data"], "data")?;
+        }
+        if let Some(events) = &step.events {
+            let checks = [
+                ("state_updated", events.state_updated, counts.state_updated),
+                ("reminders", events.reminders, counts.reminders),
+                ("outbound", events.outbound, counts.outbound),
+            ];
+            for (name, expected, actual) in checks {
+                if expected.is_some_and(|expected| expected != actual) {
+                    return Err(format!(
+                        "events.{name}: expected {expected:?}, got {actual}"
+                    ));
+                }
+            }
+        }
+        if let Some(name) = &step.bind {
+            self.vars.insert(name.clone(), result["data"].clone());
+        }
+        Ok(())
+    }
+
+    /// Expands `$now`, `${var.path}` and `$from` records; everything else passes through.
+    fn resolve(&self, value: &Value) -> Result<Value, String> {
+        match value {
+            Value::String(text) => self.resolve_text(text),
+            Value::Array(items) => items
+                .iter()
+                .map(|item| self.resolve(item))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::Array),
+            Value::Object(fields) => {
+                let mut out = match fields.get("$from") {
+                    Some(Value::String(source)) => self.current(source)?,
+                    Some(other) => return Err(format!("$from must be a string, got {other}")),
+                    None => Value::Object(Map::new()),
+                };
+                for (key, field) in fields {
+                    if key != "$from" {
+                        out[key] = self.resolve(field)?;
+                    }
+                }
+                Ok(out)
+            }
+            other => Ok(other.clone()),
+        }
+    }
+
+    fn resolve_text(&self, text: &str) -> Result<Value, String> {
+        if let Some(offset) = text.strip_prefix("$now") {
+            let now = self.state.clock().timestamp();
+            let offset = match offset {
+                "" => 0,
+                _ => offset
+                    .strip_prefix('+')
+                    .unwrap_or(offset)
+                    .parse::<i64>()
+                    .map_err(|_| format!("bad clock expression {text:?}"))?,
+            };
+            return Ok(json!(now + offset));
+        }
+        let Some(path) = text
+            .strip_prefix("${")
+            .and_then(|rest| rest.strip_suffix('}'))
+        else {
+            return Ok(Value::String(text.to_string()));
+        };
+        let mut parts = path.split('.');
+        let name = parts.next().unwrap_or_default();
+        let mut value = self
+            .vars
+            .get(name)
+            .ok_or_else(|| format!("unbound variable {name:?}"))?;
+        for part in parts {
+            value = match part.parse::<usize>() {
+                Ok(index) => value.get(index),
+                Err(_) => value.get(part),
+            }
+            .ok_or_else(|| format!("{text}: no `{part}`"))?;
+        }
+        Ok(value.clone())
+    }
+
+    fn current(&self, source: &str) -> Result<Value, String> {
+        let found = match source.split_once(':') {
+            Some(("task", id)) => self
+                .state
+                .tasks()
+                .into_iter()
+                .find(|task| task.id == id)
+                .map(|task| serde_json::to_value(task).unwrap()),
+            Some(("project", id)) => self
+                .state
+                .projects()
+                .into_iter()
+                .find(|project| project.id == id)
+                .map(|project| serde_json::to_value(project).unwrap()),
+            None if source == "settings" => {
+                Some(serde_json::to_value(self.state.settings()).unwrap())
+            }
+            _ => return Err(format!("unknown $from source {source:?}")),
+        };
+        found.ok_or_else(|| format!("$from {source:?}: not found"))
+    }
+}
+
+fn fill_new_task(task: &mut Value, now: i64) {
+    let Some(fields) = task.as_object_mut() else {
+        return;
+    };
+    for (key, default) in [
+        ("project_id", json!("inbox")),
+        ("completed_at", Value::Null),
+        ("notes", Value::Null),
+        ("created_at", json!(now)),
+        ("updated_at", json!(now)),
+        ("sort_order", json!(now * 1000)),
+    ] {
+        fields.entry(key).or_insert(default);
+    }
+}
+
+/// Shallow-by-key, deep-by-object overlay used for the scenario's settings overrides.
+fn merge(base: &mut Value, overrides: &Value) {
+    match (base, overrides) {
+        (Value::Object(base), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                merge(base.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+        (base, value) => *base = value.clone(),
+    }
+}
+
+fn matches(expected: &Value, actual: &Value, path: &str) -> Result<(), String> {
+    match expected {
+        Value::String(text) if text == "$any" => Ok(()),
+        Value::Object(fields) => {
+            for (key, field) in fields {
+                matches(
+                    field,
+                    actual.get(key).unwrap_or(&Value::Null),
+                    &format!("{path}.{key}"),
+                )?;
+            }
+            Ok(())
+        }
+        Value::Array(items) => {
+            let Some(actual_items) = actual.as_array() else {
+                return Err(format!("{path}: expected an array, got {actual}"));
+            };
+            if items.len() != actual_items.len() {
+                return Err(format!(
+                    "{path}: expected {} items, got {}: {actual}",
+                    items.len(),
+                    actual_items.len()
+                ));
+            }
+            for (index, (item, actual)) in items.iter().zip(actual_items).enumerate() {
+                matches(item, actual, &format!("{path}.{index}"))?;
+            }
+            Ok(())
+        }
+        _ if expected == actual => Ok(()),
+        _ => Err(format!("{path}: expected {expected}, got {actual}")),
+    }
+}
+
+fn arg<T: DeserializeOwned>(args: &Value, name: &str) -> Result<T, String> {
+    serde_json::from_value(args.get(name).cloned().unwrap_or(Value::Null))
+        .map_err(|e| format!("arg `{name}`: {e}"))
+}
+
+fn value<T: Serialize>(result: CommandResult<T>) -> Result<Value, String> {
+    serde_json::to_value(result).map_err(|e| format!("result does not serialize: {e}"))
+}
+
+/// Command name + JSON args -> the impl the Tauri wrapper of the same name calls. Extend this
+/// table when a scenario needs a command that is not listed yet.
+fn dispatch(
+    ctx: &ScriptedCtx,
+    state: &AppState,
+    command: &str,
+    args: &Value,
+) -> Result<Value, String> {
+    match command {
+        "load_state" => value(load_state_impl(ctx, state)),
+        "create_project" => value(create_project_impl(ctx, state, arg(args, "project")?)),
+        "update_project" => value(update_project_impl(ctx, state, arg(args, "project")?)),
+        "delete_project" => value(delete_project_impl(ctx, state, arg(args, "project_id")?)),
+        "mute_project" => value(mute_project_impl(
+            ctx,
+            state,
+            arg(args, "project_id")?,
+            arg(args, "until")?,
+        )),
+        "unmute_project" => value(unmute_project_impl(ctx, state, arg(args, "project_id")?)),
+        "create_task" => value(create_task_checked_impl(
+            ctx,
+            state,
+            arg(args, "task")?,
+            arg::<Option<bool>>(args, "check_duplicates")?.unwrap_or(false),
+        )),
+        "update_task" => value(update_task_impl(ctx, state, arg(args, "task")?)),
+        "bulk_update_tasks" => value(bulk_update_tasks_impl(ctx, state, arg(args, "tasks")?)),
+        "bulk_update_tasks_dry_run" => {
+            value(bulk_update_tasks_dry_run_impl(state, arg(args, "tasks")?))
+        }
+        "set_task_pinned" => value(set_task_pinned_impl(
+            ctx,
+            state,
+            arg(args, "task_id")?,
+            arg(args, "pinned")?,
+        )),
+        "move_to_someday" => value(move_to_someday_impl(ctx, state, arg(args, "task_id")?)),
+        "move_from_someday" => value(move_from_someday_impl(
+            ctx,
+            state,
+            arg(args, "task_id")?,
+            arg(args, "due_at")?,
+        )),
+        "add_comment" => value(add_comment_impl(
+            ctx,
+            state,
+            arg(args, "task_id")?,
+            arg(args, "text")?,
+        )),
+        "complete_task" => value(complete_task_impl(ctx, state, arg(args, "task_id")?)),
+        "bulk_complete_tasks" => {
+            value(bulk_complete_tasks_impl(ctx, state, arg(args, "task_ids")?))
+        }
+        "snooze_task" => value(snooze_task_impl(
+            ctx,
+            state,
+            arg(args, "task_id")?,
+            arg(args, "until")?,
+        )),
+        "test_fire_reminder" => value(test_fire_reminder_impl(ctx, state, arg(args, "task_id")?)),
+        "delete_task" => value(delete_task_impl(ctx, state, arg(args, "task_id")?)),
+        "delete_tasks" => value(delete_tasks_impl(ctx, state, arg(args, "task_ids")?)),
+        "delete_tasks_dry_run" => value(delete_tasks_dry_run_impl(state, arg(args, "task_ids")?)),
+        "undo_last_action" => value(undo_last_action_impl(ctx, state)),
+        "redo_last_action" => value(redo_last_action_impl(ctx, state)),
+        "get_undo_status" => value(get_undo_status_impl(state)),
+        "update_settings" => value(update_settings_impl(ctx, state, arg(args, "settings")?)),
+        "query_tasks" => value(query_tasks_impl(state, arg(args, "spec")?)),
+        "create_backup" => value(create_backup_impl(ctx, state)),
+        "list_backups" => value(list_backups_impl(ctx)),
+        "restore_backup" => value(restore_backup_impl(ctx, state, arg(args, "filename")?)),
+        "restore_backup_dry_run" => value(restore_backup_dry_run_impl(
+            ctx,
+            state,
+            arg(args, "filename")?,
+        )),
+        "advance_clock" => value(advance_clock_impl(ctx, state, arg(args, "seconds")?)),
+        other => Err(format!("unknown command `{other}`")),
+    }
+}
+
+fn run_scenario(scenario: &Scenario) -> Result<(), String> {
+    let mut runner = Runner::new(scenario)?;
+    for (index, step) in scenario.steps.iter().enumerate() {
+        runner.run_step(step).map_err(|message| {
+            let label = step.command.as_deref().unwrap_or("-");
+            format!("step {index} ({label}): {message}")
+        })?;
+    }
+    Ok(())
+}
+
+fn scenario_files() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(SCENARIO_DIR);
+    let mut files: Vec<PathBuf> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn recorded_scenarios_replay_cleanly() {
+    let files = scenario_files();
+    assert!(!files.is_empty(), "no scenarios under {SCENARIO_DIR}");
+    let mut failures = Vec::new();
+    for path in &files {
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        let outcome = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| serde_json::from_str::<Scenario>(&text).map_err(|e| e.to_string()))
+            .and_then(|scenario| {
+                run_scenario(&scenario).map_err(|e| format!("{}: {e}", scenario.description))
+            });
+        if let Err(message) = outcome {
+            failures.push(format!("{name}: {message}"));
+        }
+    }
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+#[test]
+fn mismatches_name_the_first_differing_path() {
+    let actual = json!({"tasks": [{"id": "a", "tags": ["x"]}], "extra": 1});
+    assert!(matches(&json!({"tasks": [{"id": "$any"}]}), &actual, "state").is_ok());
+    assert_eq!(
+        matches(&json!({"tasks": [{"tags": ["y"]}]}), &actual, "state").unwrap_err(),
+        "state.tasks.0.tags.0: expected \"y\", got \"x\""
+    );
+    assert!(matches(&json!({"tasks": []}), &actual, "state")
+        .unwrap_err()
+        .starts_with("state.tasks: expected 0 items, got 1"));
+    assert!(matches(&json!({"missing": 1}), &actual, "state").is_err());
+}
+
+#[test]
+fn steps_fail_on_unexpected_results() {
+    let scenario: Scenario = serde_json::from_value(json!({
+        "description": "harness self-check",
+        "now": "2026-03-18T09:00:00",
+        "steps": [
+            { "command": "complete_task", "args": { "task_id": "ghost" } }
+        ]
+    }))
+    .unwrap();
+    let message = run_scenario(&scenario).unwrap_err();
+    assert!(message.starts_with("step 0 (complete_task): expected ok=true"));
+
+    let scenario: Scenario = serde_json::from_value(json!({
+        "description": "unknown command",
+        "now": "2026-03-18T09:00:00",
+        "steps": [{ "command": "no_such_command" }]
+    }))
+    .unwrap();
+    assert_eq!(
+        run_scenario(&scenario).unwrap_err(),
+        "step 0 (no_such_command): unknown command `no_such_command`"
+    );
+}
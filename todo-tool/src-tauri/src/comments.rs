@@ -0,0 +1,112 @@
+use crate::models::{Comment, Task, Timestamp};
+
+const MAX_COMMENT_CHARS: usize = 2_000;
+/// How many of a task's newest comments go into the AI context when opted in.
+pub const AI_CONTEXT_COMMENTS: usize = 3;
+
+/// Trims and unifies line endings; empty or oversized comments are rejected.
+pub fn normalize_comment_text(raw: &str) -> Result<String, String> {
+    let text = raw.replace("\r\n", "\n").trim().to_string();
+    if text.is_empty() {
+        return Err("comment is empty".to_string());
+    }
+    if text.chars().count() > MAX_COMMENT_CHARS {
+        return Err(format!(
+            "comment is longer than {MAX_COMMENT_CHARS} characters"
+        ));
+    }
+    Ok(text)
+}
+
+/// Builds a comment whose id is unique within `task` (`<created_at>`, then `<created_at>-2`...).
+pub fn new_comment(task: &Task, text: String, now: Timestamp) -> Comment {
+    let taken = |id: &str| task.comments.iter().any(|c| c.id == id);
+    let base = now.to_string();
+    let id = if taken(&base) {
+        (2..)
+            .map(|n| format!("{base}-{n}"))
+            .find(|id| !taken(id))
+            .expect("unbounded suffix search")
+    } else {
+        base
+    };
+    Comment {
+        id,
+        text,
+        created_at: now,
+    }
+}
+
+/// Comment text on one line, for list-style exports.
+pub fn single_line(comment: &Comment) -> String {
+    comment
+        .text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The newest `AI_CONTEXT_COMMENTS` comments, oldest first.
+pub fn latest_comments(task: &Task) -> &[Comment] {
+    let skip = task.comments.len().saturating_sub(AI_CONTEXT_COMMENTS);
+    &task.comments[skip..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+
+    fn make_task(comments: Vec<Comment>) -> Task {
+        Task {
+            sort_order: 1,
+            comments,
+            ..test_support::task("t").due(1)
+        }
+    }
+
+    #[test]
+    fn normalize_comment_text_trims_and_validates() {
+        assert_eq!(
+            normalize_comment_text("  sent draft\r\nwaiting on review \n").unwrap(),
+            "sent draft\nwaiting on review"
+        );
+        assert!(normalize_comment_text(" \r\n ")
+            .unwrap_err()
+            .contains("empty"));
+        assert!(normalize_comment_text(&"x".repeat(2_001)).is_err());
+        assert!(normalize_comment_text(&"x".repeat(2_000)).is_ok());
+    }
+
+    #[test]
+    fn new_comment_ids_are_unique_within_the_task() {
+        let mut task = make_task(Vec::new());
+        let first = new_comment(&task, "a".to_string(), 100);
+        assert_eq!(first.id, "100");
+        task.comments.push(first);
+        let second = new_comment(&task, "b".to_string(), 100);
+        assert_eq!(second.id, "100-2");
+        task.comments.push(second);
+        assert_eq!(new_comment(&task, "c".to_string(), 100).id, "100-3");
+        assert_eq!(new_comment(&task, "d".to_string(), 101).id, "101");
+    }
+
+    #[test]
+    fn latest_comments_and_single_line() {
+        let comments: Vec<Comment> = (1..=5)
+            .map(|n| Comment {
+                id: n.to_string(),
+                text: format!("step {n}\n  done"),
+                created_at: n,
+            })
+            .collect();
+        let task = make_task(comments);
+        let ids: Vec<&str> = latest_comments(&task)
+            .iter()
+            .map(|c| c.id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["3", "4", "5"]);
+        assert_eq!(single_line(&task.comments[0]), "step 1 done");
+        assert!(latest_comments(&make_task(Vec::new())).is_empty());
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+
+use crate::models::Task;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ContextSummary {
+    pub context: String,
+    pub open_count: usize,
+    pub total_count: usize,
+}
+
+/// Canonical form: trimmed, lowercase, single leading `@` ("Phone" and "@phone" are the same).
+pub fn normalize_context_label(raw: &str) -> Option<String> {
+    let name = raw.trim().trim_start_matches('@').trim().to_lowercase();
+    if name.is_empty() {
+        return None;
+    }
+    Some(format!("@{name}"))
+}
+
+/// Normalizes `context`, or lifts the first `@tag` into it when no context was set.
+///
+/// The lifted tag is removed from `tags` so the context has a single source of truth.
+pub fn normalize_context(task: &mut Task) {
+    task.context = task.context.as_deref().and_then(normalize_context_label);
+    if task.context.is_some() {
+        return;
+    }
+    let Some(index) = task.tags.iter().position(|tag| tag.trim().starts_with('@')) else {
+        return;
+    };
+    let tag = task.tags.remove(index);
+    task.context = normalize_context_label(&tag);
+}
+
+/// Contexts in use, busiest (most open tasks) first; ties sort by name.
+pub fn context_summaries(tasks: &[Task]) -> Vec<ContextSummary> {
+    let mut counts: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    for task in tasks {
+        let Some(context) = task.context.as_deref().and_then(normalize_context_label) else {
+            continue;
+        };
+        let entry = counts.entry(context).or_default();
+        entry.1 += 1;
+        if !task.completed {
+            entry.0 += 1;
+        }
+    }
+    let mut summaries: Vec<ContextSummary> = counts
+        .into_iter()
+        .map(|(context, (open_count, total_count))| ContextSummary {
+            context,
+            open_count,
+            total_count,
+        })
+        .collect();
+    summaries.sort_by(|a, b| {
+        b.open_count
+            .cmp(&a.open_count)
+            .then_with(|| a.context.cmp(&b.context))
+    });
+    summaries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+
+    fn make_task(id: &str, context: Option<&str>, tags: &[&str], completed: bool) -> Task {
+        Task {
+            sort_order: 1,
+            context: context.map(str::to_string),
+            ..test_support::task(id)
+                .due(1)
+                .tags(tags)
+                .completed(completed)
+        }
+    }
+
+    #[test]
+    fn normalize_context_label_adds_prefix_and_lowercases() {
+        assert_eq!(
+            normalize_context_label(" Phone "),
+            Some("@phone".to_string())
+        );
+        assert_eq!(
+            normalize_context_label("@Deep-Work"),
+            Some("@deep-work".to_string())
+        );
+        assert_eq!(
+            normalize_context_label("@@errand"),
+            Some("@errand".to_string())
+        );
+        assert_eq!(normalize_context_label(" @ "), None);
+        assert_eq!(normalize_context_label(""), None);
+    }
+
+    #[test]
+    fn normalize_context_prefers_explicit_value_then_lifts_tag() {
+        let mut explicit = make_task("a", Some("Phone"), &["@errand", "home"], false);
+        normalize_context(&mut explicit);
+        assert_eq!(explicit.context.as_deref(), Some("@phone"));
+        assert_eq!(explicit.tags, vec!["@errand", "home"]);
+
+        let mut from_tag = make_task("b", Some("  "), &["home", "@Errand", "@phone"], false);
+        normalize_context(&mut from_tag);
+        assert_eq!(from_tag.context.as_deref(), Some("@errand"));
+        assert_eq!(from_tag.tags, vec!["home", "@phone"]);
+
+        let mut none = make_task("c", None, &["home"], false);
+        normalize_context(&mut none);
+        assert_eq!(none.context, None);
+        assert_eq!(none.tags, vec!["home"]);
+    }
+
+    #[test]
+    fn context_summaries_count_open_and_total_per_context() {
+        let tasks = vec![
+            make_task("a", Some("@phone"), &[], false),
+            make_task("b", Some("@Phone"), &[], true),
+            make_task("c", Some("@errand"), &[], false),
+            make_task("d", Some("@errand"), &[], false),
+            make_task("e", Some("@deep"), &[], true),
+            make_task("f", None, &[], false),
+        ];
+        let summaries = context_summaries(&tasks);
+        let rows: Vec<(&str, usize, usize)> = summaries
+            .iter()
+            .map(|s| (s.context.as_str(), s.open_count, s.total_count))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![("@errand", 2, 2), ("@phone", 1, 2), ("@deep", 0, 1)]
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use chrono::{DateTime, Local, NaiveTime, TimeZone};
+
+use crate::models::{Settings, Task, Timestamp};
+use crate::state::AppState;
+
+/// Counts for the end-of-day summary, all relative to the local day it was built on.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct DailySummary {
+    /// Local date as `YYYY-MM-DD`.
+    pub date: String,
+    pub generated_at: Timestamp,
+    /// Tasks completed today.
+    pub completed: usize,
+    /// Open tasks past their due time (after `overdue_grace_minutes`).
+    pub overdue: usize,
+    /// Open tasks due later today.
+    pub remaining: usize,
+}
+
+impl DailySummary {
+    pub fn is_empty(&self) -> bool {
+        self.completed == 0 && self.overdue == 0 && self.remaining == 0
+    }
+}
+
+pub fn parse_summary_time(raw: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(raw.trim(), "%H:%M")
+        .map_err(|_| format!("invalid daily summary time: {raw} (expected HH:MM)"))
+}
+
+/// Checks `daily_summary_time` and rewrites it as `HH:MM` (e.g. "9:05" -> "09:05").
+pub fn normalize_daily_summary_time(settings: &mut Settings) -> Result<(), String> {
+    let at = parse_summary_time(&settings.daily_summary_time)?;
+    settings.daily_summary_time = at.format("%H:%M").to_string();
+    Ok(())
+}
+
+/// True once per local day, from `at` on. A machine that was asleep at that time catches up
+/// on the first tick after waking, but not after midnight: the summary is about that day.
+pub fn summary_due(last_run: Option<Timestamp>, at: NaiveTime, now: DateTime<Local>) -> bool {
+    if now.time() < at {
+        return false;
+    }
+    let Some(last_run) = last_run else {
+        return true;
+    };
+    Local
+        .timestamp_opt(last_run, 0)
+        .single()
+        .map(|last| last.date_naive() != now.date_naive())
+        .unwrap_or(true)
+}
+
+pub fn build_summary(tasks: &[Task], now: DateTime<Local>, grace_minutes: i64) -> DailySummary {
+    let today = now.date_naive();
+    let now_ts = now.timestamp();
+    let local_date = |ts: Timestamp| {
+        Local
+            .timestamp_opt(ts, 0)
+            .single()
+            .map(|dt| dt.date_naive())
+    };
+    let mut summary = DailySummary {
+        date: today.format("%Y-%m-%d").to_string(),
+        generated_at: now_ts,
+        completed: 0,
+        overdue: 0,
+        remaining: 0,
+    };
+    for task in tasks {
+        if task.completed {
+            if task.completed_at.and_then(local_date) == Some(today) {
+                summary.completed += 1;
+            }
+        } else if task.is_overdue(now_ts, grace_minutes) {
+            summary.overdue += 1;
+        } else if !task.someday && task.due_at.and_then(local_date) == Some(today) {
+            summary.remaining += 1;
+        }
+    }
+    summary
+}
+
+/// The summary for today when it is enabled and due, recording the run in
+/// `last_daily_summary_at`. Only in-memory state is changed here; callers persist settings.
+pub fn take_due_summary(state: &AppState, now: DateTime<Local>) -> Option<DailySummary> {
+    let mut settings = state.settings();
+    if !settings.daily_summary_enabled {
+        return None;
+    }
+    let at = match parse_summary_time(&settings.daily_summary_time) {
+        Ok(at) => at,
+        Err(err) => {
+            log::warn!("daily summary: skipped err={err}");
+            return None;
+        }
+    };
+    if !summary_due(settings.last_daily_summary_at, at, now) {
+        return None;
+    }
+    let summary = build_summary(&state.shared_tasks(), now, settings.overdue_grace_minutes);
+    settings.last_daily_summary_at = Some(now.timestamp());
+    state.update_settings(settings);
+    log::info!(
+        "daily summary: built date={} completed={} overdue={} remaining={}",
+        summary.date,
+        summary.completed,
+        summary.overdue,
+        summary.remaining
+    );
+    Some(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+
+    fn local(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2026, 3, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn task(id: &str, due: Option<DateTime<Local>>, done: Option<DateTime<Local>>) -> Task {
+        test_support::task(id)
+            .due(due.map(|dt| dt.timestamp()))
+            .done_at(done.map(|dt| dt.timestamp()))
+    }
+
+    #[test]
+    fn summary_is_due_once_per_day_from_the_configured_time() {
+        let at = parse_summary_time("18:00").unwrap();
+        assert!(!summary_due(None, at, local(10, 17, 59)));
+        assert!(summary_due(None, at, local(10, 18, 0)));
+        // Catches up later the same evening, but only once.
+        assert!(summary_due(
+            Some(local(9, 18, 0).timestamp()),
+            at,
+            local(10, 23, 30)
+        ));
+        assert!(!summary_due(
+            Some(local(10, 18, 0).timestamp()),
+            at,
+            local(10, 23, 30)
+        ));
+        // A missed evening is not sent the next morning.
+        assert!(!summary_due(
+            Some(local(8, 18, 0).timestamp()),
+            at,
+            local(10, 8, 0)
+        ));
+
+        assert!(parse_summary_time("25:00").is_err());
+        let mut settings = Settings {
+            daily_summary_time: " 9:05".to_string(),
+            ..Settings::default()
+        };
+        normalize_daily_summary_time(&mut settings).unwrap();
+        assert_eq!(settings.daily_summary_time, "09:05");
+    }
+
+    #[test]
+    fn build_summary_counts_completed_overdue_and_remaining_for_today() {
+        let now = local(10, 18, 0);
+        let mut someday = task("someday", Some(local(10, 20, 0)), None);
+        someday.someday = true;
+        let tasks = vec![
+            task("done-today", Some(local(9, 9, 0)), Some(local(10, 9, 0))),
+            task("done-yesterday", None, Some(local(9, 9, 0))),
+            task("late", Some(local(10, 17, 0)), None),
+            task("in-grace", Some(local(10, 17, 55)), None),
+            task("tonight", Some(local(10, 21, 0)), None),
+            task("tomorrow", Some(local(11, 9, 0)), None),
+            task("undated", None, None),
+            someday,
+        ];
+        let summary = build_summary(&tasks, now, 10);
+        assert_eq!(summary.date, "2026-03-10");
+        assert_eq!(summary.generated_at, now.timestamp());
+        assert_eq!(
+            (summary.completed, summary.overdue, summary.remaining),
+            (1, 1, 2)
+        );
+        assert!(!summary.is_empty());
+        assert!(build_summary(&[], now, 0).is_empty());
+    }
+
+    #[test]
+    fn take_due_summary_records_the_run() {
+        let settings = Settings {
+            daily_summary_enabled: true,
+            ..Settings::default()
+        };
+        let state = AppState::new(Vec::new(), Vec::new(), settings);
+        assert!(take_due_summary(&state, local(10, 12, 0)).is_none());
+        let summary = take_due_summary(&state, local(10, 18, 30)).unwrap();
+        assert!(summary.is_empty());
+        assert_eq!(
+            state.settings().last_daily_summary_at,
+            Some(local(10, 18, 30).timestamp())
+        );
+        assert!(take_due_summary(&state, local(10, 19, 0)).is_none());
+
+        let mut settings = state.settings();
+        settings.daily_summary_enabled = false;
+        state.update_settings(settings);
+        assert!(take_due_summary(&state, local(11, 18, 30)).is_none());
+    }
+}
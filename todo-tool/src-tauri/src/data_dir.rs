@@ -0,0 +1,491 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::storage::StorageError;
+
+/// Overrides every other setting when non-empty; handy for portable installs and tests.
+pub const DATA_DIR_ENV: &str = "MUSTDO_DATA_DIR";
+/// Guest/demo mode: `--guest` on the command line or a truthy `MUSTDO_GUEST`.
+pub const GUEST_FLAG: &str = "--guest";
+pub const GUEST_ENV: &str = "MUSTDO_GUEST";
+/// Lives in the OS app data dir (never moves) and points at the relocated data directory.
+const POINTER_FILE: &str = "data_dir.json";
+
+/// Everything that belongs to one data set. Logs and the pointer file stay in the OS dir.
+/// A module that writes a new file or directory into the data dir adds it here (see the
+/// `every_written_entry_is_migrated` test).
+const DATA_ENTRIES: [&str; 19] = [
+    "data.json",
+    "data.sqlite",
+    "settings.json",
+    "secrets.json",
+    "email_deliveries.jsonl",
+    "reminder_audit.jsonl",
+    "carry_over.jsonl",
+    "focus_sessions.jsonl",
+    "note_history.jsonl",
+    "activity.jsonl",
+    "caldav_sync.json",
+    "backups",
+    "attachments",
+    "archive",
+    "exports",
+    "sounds",
+    "reviews",
+    "profiles.json",
+    "profiles",
+];
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct DataDirPointer {
+    path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct DataDirProgress {
+    pub item: String,
+    pub done: usize,
+    pub total: usize,
+}
+
+pub fn read_pointer(default_root: &Path) -> Option<PathBuf> {
+    let raw = fs::read_to_string(default_root.join(POINTER_FILE)).ok()?;
+    match serde_json::from_str::<DataDirPointer>(&raw) {
+        Ok(pointer) if pointer.path.is_absolute() => Some(pointer.path),
+        Ok(pointer) => {
+            log::warn!(
+                "data_dir: ignoring relative pointer path={}",
+                pointer.path.display()
+            );
+            None
+        }
+        Err(err) => {
+            log::warn!("data_dir: ignoring unreadable pointer err={err}");
+            None
+        }
+    }
+}
+
+/// Points the app at `target`; pointing back at `default_root` removes the pointer file.
+pub fn write_pointer(default_root: &Path, target: &Path) -> Result<(), StorageError> {
+    let path = default_root.join(POINTER_FILE);
+    if target == default_root {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        return Ok(());
+    }
+    fs::create_dir_all(default_root)?;
+    let pointer = DataDirPointer {
+        path: target.to_path_buf(),
+    };
+    fs::write(path, serde_json::to_vec_pretty(&pointer)?)?;
+    Ok(())
+}
+
+/// Non-empty value of `DATA_DIR_ENV`, if set.
+pub fn env_override() -> Option<String> {
+    std::env::var(DATA_DIR_ENV)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+}
+
+/// Boot-time resolution: env override, then the pointer file, then the OS default.
+pub fn resolve_data_dir(default_root: &Path, env_override: Option<String>) -> PathBuf {
+    if let Some(dir) = env_override
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+    {
+        return PathBuf::from(dir);
+    }
+    read_pointer(default_root).unwrap_or_else(|| default_root.to_path_buf())
+}
+
+pub fn guest_mode_requested(
+    mut args: impl Iterator<Item = String>,
+    env_value: Option<String>,
+) -> bool {
+    let env_on = env_value
+        .map(|value| value.trim().to_lowercase())
+        .is_some_and(|value| matches!(value.as_str(), "1" | "true" | "yes" | "on"));
+    env_on || args.any(|arg| arg == GUEST_FLAG)
+}
+
+/// Throwaway root for guest mode. Data goes to the in-memory storage backend; this directory
+/// only catches the few files written outside `Storage` (delivery log, registry, exports).
+pub fn guest_root() -> PathBuf {
+    std::env::temp_dir().join(format!("mustdo-guest-{}", std::process::id()))
+}
+
+/// Data directory for a running app: the OS app data dir redirected by env/pointer.
+#[cfg(all(feature = "app", not(test)))]
+pub fn app_data_root<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<PathBuf> {
+    use tauri::Manager;
+    if crate::storage::memory_backend_enabled() {
+        return Ok(guest_root());
+    }
+    let default_root = app.path().app_data_dir()?;
+    Ok(resolve_data_dir(&default_root, env_override()))
+}
+
+pub fn validate_target(current: &Path, target: &Path) -> Result<(), String> {
+    if target.as_os_str().is_empty() {
+        return Err("data directory path is empty".to_string());
+    }
+    if !target.is_absolute() {
+        return Err("data directory must be an absolute path".to_string());
+    }
+    if target == current {
+        return Err("data directory is already in use".to_string());
+    }
+    if target.starts_with(current) {
+        return Err("data directory cannot be inside the current one".to_string());
+    }
+    if target.exists() && !target.is_dir() {
+        return Err("data directory path is a file".to_string());
+    }
+    if ["data.json", "data.sqlite", "settings.json"]
+        .iter()
+        .any(|name| target.join(name).exists())
+    {
+        return Err("target already contains MustDo data".to_string());
+    }
+    Ok(())
+}
+
+fn copy_entry(from: &Path, to: &Path) -> std::io::Result<()> {
+    if from.is_dir() {
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_entry(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        return Ok(());
+    }
+    fs::copy(from, to).map(|_| ())
+}
+
+fn remove_entry(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Removes what `copy_data_dir` placed in `target` (best effort; used for rollback).
+pub fn remove_copied(target: &Path, copied: &[String], created_target: bool) {
+    for name in copied.iter().rev() {
+        if let Err(err) = remove_entry(&target.join(name)) {
+            log::warn!("data_dir: rollback failed item={name} err={err}");
+        }
+    }
+    if created_target {
+        let _ = fs::remove_dir(target);
+    }
+}
+
+/// Copies every data entry from `from` to `to`, reporting progress per entry.
+///
+/// On failure everything copied so far is removed again and `to` is left as it was found.
+/// Returns the names that were copied so the caller can roll back a later step too.
+pub fn copy_data_dir(
+    from: &Path,
+    to: &Path,
+    mut progress: impl FnMut(DataDirProgress),
+) -> Result<Vec<String>, StorageError> {
+    let created_target = !to.exists();
+    fs::create_dir_all(to)?;
+    let present: Vec<&str> = DATA_ENTRIES
+        .iter()
+        .copied()
+        .filter(|name| from.join(name).exists())
+        .collect();
+    let mut copied: Vec<String> = Vec::new();
+    for (index, name) in present.iter().enumerate() {
+        if let Err(err) = copy_entry(&from.join(name), &to.join(name)) {
+            log::error!("data_dir: copy failed item={name} err={err}");
+            // The failing entry may be partially written; include it in the rollback.
+            copied.push(name.to_string());
+            remove_copied(to, &copied, created_target);
+            return Err(err.into());
+        }
+        copied.push(name.to_string());
+        progress(DataDirProgress {
+            item: name.to_string(),
+            done: index + 1,
+            total: present.len(),
+        });
+    }
+    Ok(copied)
+}
+
+/// Deletes the old copies once the new location is active; failures are only logged.
+pub fn remove_old_entries(root: &Path, names: &[String]) {
+    for name in names {
+        if let Err(err) = remove_entry(&root.join(name)) {
+            log::warn!(
+                "data_dir: failed to remove old item={name} root={} err={err}",
+                root.display()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_prefers_env_then_pointer_then_default() {
+        let default_root = tempfile::tempdir().unwrap();
+        let root = default_root.path();
+        let target = tempfile::tempdir().unwrap();
+
+        assert_eq!(env_override(), None);
+        assert_eq!(resolve_data_dir(root, env_override()), root);
+        write_pointer(root, target.path()).unwrap();
+        assert_eq!(resolve_data_dir(root, None), target.path());
+        assert_eq!(
+            resolve_data_dir(root, Some(" /env/dir ".to_string())),
+            PathBuf::from("/env/dir")
+        );
+        assert_eq!(
+            resolve_data_dir(root, Some("  ".to_string())),
+            target.path()
+        );
+
+        // Pointing back at the default removes the pointer.
+        write_pointer(root, root).unwrap();
+        assert!(!root.join(POINTER_FILE).exists());
+        write_pointer(root, root).unwrap();
+
+        // Garbage or relative pointers are ignored.
+        fs::write(root.join(POINTER_FILE), "nope").unwrap();
+        assert_eq!(resolve_data_dir(root, None), root);
+        fs::write(root.join(POINTER_FILE), r#"{"path":"relative/dir"}"#).unwrap();
+        assert_eq!(resolve_data_dir(root, None), root);
+    }
+
+    #[test]
+    fn guest_mode_from_flag_or_env() {
+        let args = |list: &[&str]| {
+            list.iter()
+                .map(|arg| arg.to_string())
+                .collect::<Vec<_>>()
+                .into_iter()
+        };
+        assert!(!guest_mode_requested(
+            args(&["mustdo"]),
+            std::env::var(GUEST_ENV).ok()
+        ));
+        assert!(guest_mode_requested(args(&["mustdo", "--guest"]), None));
+        assert!(guest_mode_requested(
+            args(&["mustdo"]),
+            Some(" TRUE ".into())
+        ));
+        assert!(!guest_mode_requested(args(&["mustdo"]), Some("0".into())));
+        assert!(!guest_mode_requested(args(&["mustdo", "--guests"]), None));
+        assert!(guest_root().starts_with(std::env::temp_dir()));
+    }
+
+    #[test]
+    fn validate_target_rejects_unsafe_destinations() {
+        let current = tempfile::tempdir().unwrap();
+        let other = tempfile::tempdir().unwrap();
+        let cur = current.path();
+
+        let cases = [
+            (PathBuf::new(), "empty"),
+            (PathBuf::from("relative"), "absolute"),
+            (cur.to_path_buf(), "already in use"),
+            (cur.join("nested"), "inside"),
+        ];
+        for (target, expected) in cases {
+            let message = validate_target(cur, &target).unwrap_err();
+            assert!(message.contains(expected), "{message}");
+        }
+
+        let file = other.path().join("file");
+        fs::write(&file, b"x").unwrap();
+        assert!(validate_target(cur, &file).unwrap_err().contains("file"));
+        fs::write(other.path().join("data.json"), b"{}").unwrap();
+        assert!(validate_target(cur, other.path())
+            .unwrap_err()
+            .contains("already contains"));
+
+        assert!(validate_target(cur, &other.path().join("fresh")).is_ok());
+    }
+
+    #[test]
+    fn copy_data_dir_copies_present_entries_and_reports_progress() {
+        let from = tempfile::tempdir().unwrap();
+        fs::write(from.path().join("data.json"), b"{}").unwrap();
+        fs::write(from.path().join("settings.json"), b"{}").unwrap();
+        fs::create_dir_all(from.path().join("backups/nested")).unwrap();
+        fs::write(from.path().join("backups/nested/b.json"), b"1").unwrap();
+        fs::write(from.path().join("unrelated.txt"), b"x").unwrap();
+        let to_root = tempfile::tempdir().unwrap();
+        let to = to_root.path().join("new");
+
+        let mut events = Vec::new();
+        let copied = copy_data_dir(from.path(), &to, |p| events.push(p)).unwrap();
+        assert_eq!(copied, vec!["data.json", "settings.json", "backups"]);
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[2].done, 3);
+        assert_eq!(events[2].total, 3);
+        assert!(to.join("backups/nested/b.json").exists());
+        assert!(!to.join("unrelated.txt").exists());
+
+        remove_old_entries(from.path(), &copied);
+        assert!(!from.path().join("data.json").exists());
+        assert!(!from.path().join("backups").exists());
+        assert!(from.path().join("unrelated.txt").exists());
+    }
+
+    #[test]
+    fn every_written_entry_is_migrated() {
+        use crate::models::{ReminderKind, SettingsFile, TasksFile};
+        use crate::storage::Storage;
+
+        let from = tempfile::tempdir().unwrap();
+        let root = from.path();
+        let storage = Storage::new(root.to_path_buf());
+        storage.ensure_dirs().unwrap();
+        storage
+            .save_tasks(
+                &TasksFile {
+                    schema_version: 1,
+                    tasks: Vec::new(),
+                    projects: Vec::new(),
+                    trash: Vec::new(),
+                    tags: Vec::new(),
+                },
+                true,
+            )
+            .unwrap();
+        storage
+            .save_settings(&SettingsFile {
+                schema_version: 1,
+                settings: Default::default(),
+            })
+            .unwrap();
+        crate::secrets::set_secret(root, "smtp_password", "x").unwrap();
+        storage.save_caldav_sync(&Default::default()).unwrap();
+        let task = crate::models::test_support::task("a");
+        storage
+            .save_task_archive(2024, std::slice::from_ref(&task))
+            .unwrap();
+        let source = root.join("unrelated.txt");
+        fs::write(&source, b"x").unwrap();
+        storage.store_attachment(&source, "a-1-x.txt").unwrap();
+        fs::create_dir_all(crate::sounds::sounds_dir(root)).unwrap();
+        fs::create_dir_all(crate::weekly_review::reviews_dir(root)).unwrap();
+        let sync_file = crate::md_sync::sync_file_path(root);
+        fs::create_dir_all(sync_file.parent().unwrap()).unwrap();
+        fs::write(sync_file, b"").unwrap();
+        crate::profiles::save_registry(root, &Default::default()).unwrap();
+        fs::create_dir_all(crate::profiles::profile_root(root, "work")).unwrap();
+
+        crate::email::append_delivery_log(
+            root,
+            &crate::email::DeliveryLogEntry {
+                at: 1,
+                kind: crate::email::DeliveryKind::Reminder,
+                to: "a@b.c".to_string(),
+                subject: "s".to_string(),
+                task_ids: Vec::new(),
+                ok: true,
+                error: None,
+            },
+        )
+        .unwrap();
+        crate::digest::append_reminder_audit(
+            root,
+            &[crate::digest::ReminderAuditEntry {
+                at: 1,
+                task_id: "a".to_string(),
+                title: "a".to_string(),
+                kind: ReminderKind::Normal,
+                grouped: false,
+            }],
+        )
+        .unwrap();
+        crate::carry_over::append_carry_over_log(
+            root,
+            &[crate::carry_over::CarryOverEntry {
+                at: 1,
+                task_id: "a".to_string(),
+                title: "a".to_string(),
+                from_due: 1,
+                to_due: 2,
+                count: 1,
+            }],
+        )
+        .unwrap();
+        crate::focus::append_focus_log(
+            root,
+            &crate::focus::FocusSession {
+                task_id: "a".to_string(),
+                started_at: 1,
+                ended_at: 2,
+                minutes: 25,
+                focused_seconds: 1,
+                finished: false,
+            },
+        )
+        .unwrap();
+        crate::note_history::record_note_versions(root, &[("a".into(), "old".into())], 1).unwrap();
+        crate::activity::append_activity(
+            root,
+            &[crate::activity::ActivityEntry::new(
+                &task,
+                crate::activity::ActivityKind::Created,
+                1,
+            )],
+        )
+        .unwrap();
+        fs::remove_file(&source).unwrap();
+
+        let mut written: Vec<String> = fs::read_dir(root)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        written.sort();
+        for name in &written {
+            assert!(
+                DATA_ENTRIES.contains(&name.as_str()),
+                "{name} is not migrated"
+            );
+        }
+        let to_root = tempfile::tempdir().unwrap();
+        let to = to_root.path().join("new");
+        let mut copied = copy_data_dir(root, &to, |_| {}).unwrap();
+        copied.sort();
+        assert_eq!(copied, written);
+        assert_eq!(
+            copied.len(),
+            DATA_ENTRIES.len() - 1,
+            "data.sqlite is engine-only"
+        );
+    }
+
+    #[test]
+    fn copy_data_dir_rolls_back_on_failure() {
+        let from = tempfile::tempdir().unwrap();
+        fs::write(from.path().join("data.json"), b"{}").unwrap();
+        fs::write(from.path().join("settings.json"), b"{}").unwrap();
+        let to_root = tempfile::tempdir().unwrap();
+        let to = to_root.path().join("new");
+        fs::create_dir_all(&to).unwrap();
+        // A directory where settings.json should go makes the second copy fail.
+        fs::create_dir(to.join("settings.json")).unwrap();
+
+        let result = copy_data_dir(from.path(), &to, |_| {});
+        assert!(result.is_err());
+        assert!(!to.join("data.json").exists());
+        // The target existed beforehand, so it is kept.
+        assert!(to.exists());
+        assert!(from.path().join("data.json").exists());
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use crate::models::{Task, Timestamp};
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WaitingForEntry {
+    pub task: Task,
+    pub waiting_since: Timestamp,
+    pub pending_secs: i64,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WaitingForGroup {
+    pub person: String,
+    pub oldest_pending_secs: i64,
+    pub tasks: Vec<WaitingForEntry>,
+}
+
+fn person_key(person: &str) -> String {
+    person.trim().to_lowercase()
+}
+
+/// Trims `waiting_on` and keeps `waiting_since` consistent with it.
+///
+/// The delegation clock only restarts when the task is handed to a different person; edits that
+/// keep the same person (case-insensitive) preserve the original start time.
+pub fn normalize_waiting_on(task: &mut Task, previous: Option<&Task>, now: Timestamp) {
+    task.waiting_on = task
+        .waiting_on
+        .take()
+        .map(|person| person.trim().to_string())
+        .filter(|person| !person.is_empty());
+
+    let Some(person) = task.waiting_on.as_deref() else {
+        task.waiting_since = None;
+        return;
+    };
+
+    task.waiting_since = match previous {
+        Some(previous) => {
+            let same_person = previous
+                .waiting_on
+                .as_deref()
+                .is_some_and(|prev| person_key(prev) == person_key(person));
+            if same_person {
+                previous.waiting_since.or(task.waiting_since).or(Some(now))
+            } else {
+                Some(now)
+            }
+        }
+        None => task.waiting_since.or(Some(now)),
+    };
+}
+
+/// Groups open delegated tasks by person, longest-waiting people first.
+pub fn waiting_for_groups(tasks: &[Task], now: Timestamp) -> Vec<WaitingForGroup> {
+    let mut order: Vec<String> = Vec::new();
+    let mut groups: HashMap<String, WaitingForGroup> = HashMap::new();
+
+    for task in tasks.iter().filter(|task| !task.completed) {
+        let Some(person) = task
+            .waiting_on
+            .as_deref()
+            .map(str::trim)
+            .filter(|person| !person.is_empty())
+        else {
+            continue;
+        };
+        let waiting_since = task.waiting_since.unwrap_or(task.created_at);
+        let pending_secs = now.saturating_sub(waiting_since).max(0);
+
+        let key = person_key(person);
+        let group = groups.entry(key.clone()).or_insert_with(|| {
+            order.push(key);
+            WaitingForGroup {
+                person: person.to_string(),
+                oldest_pending_secs: 0,
+                tasks: Vec::new(),
+            }
+        });
+        group.oldest_pending_secs = group.oldest_pending_secs.max(pending_secs);
+        group.tasks.push(WaitingForEntry {
+            task: task.clone(),
+            waiting_since,
+            pending_secs,
+        });
+    }
+
+    let mut out: Vec<WaitingForGroup> = order
+        .into_iter()
+        .filter_map(|key| groups.remove(&key))
+        .collect();
+    for group in &mut out {
+        group
+            .tasks
+            .sort_by_key(|entry| (entry.waiting_since, entry.task.due_at));
+    }
+    out.sort_by(|a, b| {
+        b.oldest_pending_secs
+            .cmp(&a.oldest_pending_secs)
+            .then_with(|| a.person.to_lowercase().cmp(&b.person.to_lowercase()))
+    });
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+
+    fn make_task(id: &str, waiting_on: Option<&str>, waiting_since: Option<i64>) -> Task {
+        Task {
+            sort_order: 1,
+            waiting_on: waiting_on.map(|s| s.to_string()),
+            waiting_since,
+            ..test_support::task(id)
+                .title(&format!("task-{id}"))
+                .due(1000)
+                .created(10)
+        }
+    }
+
+    #[test]
+    fn normalize_waiting_on_trims_and_tracks_since() {
+        let mut blank = make_task("a", Some("   "), Some(5));
+        normalize_waiting_on(&mut blank, None, 100);
+        assert_eq!(blank.waiting_on, None);
+        assert_eq!(blank.waiting_since, None);
+
+        let mut created = make_task("a", Some(" Alice "), None);
+        normalize_waiting_on(&mut created, None, 100);
+        assert_eq!(created.waiting_on.as_deref(), Some("Alice"));
+        assert_eq!(created.waiting_since, Some(100));
+
+        // Same person (case-insensitive) keeps the original start time.
+        let mut edited = make_task("a", Some("alice"), None);
+        normalize_waiting_on(&mut edited, Some(&created), 200);
+        assert_eq!(edited.waiting_since, Some(100));
+
+        // Handing over to someone else restarts the clock.
+        let mut handed = make_task("a", Some("bob@example.com"), Some(100));
+        normalize_waiting_on(&mut handed, Some(&created), 300);
+        assert_eq!(handed.waiting_since, Some(300));
+    }
+
+    #[test]
+    fn waiting_for_groups_groups_by_person_and_orders_by_pending_time() {
+        let mut done = make_task("done", Some("Alice"), Some(0));
+        done.completed = true;
+        let tasks = vec![
+            make_task("a1", Some("Alice"), Some(900)),
+            make_task("b1", Some("Bob"), Some(500)),
+            make_task("a2", Some("alice "), Some(800)),
+            make_task("none", None, None),
+            // Missing waiting_since falls back to created_at.
+            make_task("c1", Some("Carol"), None),
+            done,
+        ];
+
+        let groups = waiting_for_groups(&tasks, 1000);
+        let people: Vec<&str> = groups.iter().map(|g| g.person.as_str()).collect();
+        assert_eq!(people, vec!["Carol", "Bob", "Alice"]);
+
+        let alice = &groups[2];
+        assert_eq!(alice.oldest_pending_secs, 200);
+        let ids: Vec<&str> = alice.tasks.iter().map(|e| e.task.id.as_str()).collect();
+        assert_eq!(ids, vec!["a2", "a1"]);
+        assert_eq!(groups[0].tasks[0].pending_secs, 990);
+    }
+}
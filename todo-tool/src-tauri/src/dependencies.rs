@@ -0,0 +1,279 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::models::Task;
+
+/// Trims and de-duplicates `task.blocked_by`, dropping ids no stored task has. Rejects the task
+/// itself and any cycle through `tasks` (the stored tasks; a stored copy of `task` is ignored).
+pub fn validate_blocked_by(task: &mut Task, tasks: &[Task]) -> Result<(), String> {
+    let mut ids: Vec<String> = Vec::new();
+    for raw in &task.blocked_by {
+        let id = raw.trim();
+        if id == task.id {
+            return Err("a task cannot be blocked by itself".to_string());
+        }
+        if id.is_empty() || ids.iter().any(|seen| seen == id) {
+            continue;
+        }
+        if tasks.iter().any(|t| t.id == id) {
+            ids.push(id.to_string());
+        }
+    }
+    task.blocked_by = ids;
+    if let Some(cycle) = find_cycle(task, tasks) {
+        return Err(format!("dependency cycle: {}", cycle.join(" -> ")));
+    }
+    Ok(())
+}
+
+/// `task -> ... -> task` when `task`'s new blockers lead back to it.
+fn find_cycle(task: &Task, tasks: &[Task]) -> Option<Vec<String>> {
+    let mut edges: HashMap<&str, &[String]> = tasks
+        .iter()
+        .map(|t| (t.id.as_str(), t.blocked_by.as_slice()))
+        .collect();
+    edges.insert(task.id.as_str(), task.blocked_by.as_slice());
+
+    fn walk<'a>(
+        id: &'a str,
+        target: &str,
+        edges: &HashMap<&'a str, &'a [String]>,
+        visited: &mut HashSet<&'a str>,
+        path: &mut Vec<&'a str>,
+    ) -> bool {
+        for next in edges.get(id).copied().unwrap_or_default() {
+            path.push(next);
+            if next == target {
+                return true;
+            }
+            if visited.insert(next) && walk(next, target, edges, visited, path) {
+                return true;
+            }
+            path.pop();
+        }
+        false
+    }
+
+    let mut path = vec![task.id.as_str()];
+    walk(&task.id, &task.id, &edges, &mut HashSet::new(), &mut path)
+        .then(|| path.into_iter().map(str::to_string).collect())
+}
+
+/// Ids of open tasks waiting on at least one open task.
+pub fn blocked_task_ids(tasks: &[Task]) -> HashSet<String> {
+    let open: HashSet<&str> = tasks
+        .iter()
+        .filter(|t| !t.completed)
+        .map(|t| t.id.as_str())
+        .collect();
+    tasks
+        .iter()
+        .filter(|t| !t.completed && t.blocked_by.iter().any(|id| open.contains(id.as_str())))
+        .map(|t| t.id.clone())
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct DependencyNode {
+    pub id: String,
+    pub title: String,
+    pub completed: bool,
+    /// Still waiting on an open task of its own.
+    pub blocked: bool,
+    /// Fewest dependency hops from the queried task (0 for the task itself).
+    pub depth: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct DependencyEdge {
+    /// The blocking task.
+    pub from: String,
+    /// The task waiting on it.
+    pub to: String,
+}
+
+/// Everything a task is (transitively) waiting on.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct UnblockChain {
+    pub task_id: String,
+    /// Blockers before the tasks they block, ending with the queried task: completing the open
+    /// ones in this order unblocks each of the next.
+    pub nodes: Vec<DependencyNode>,
+    pub edges: Vec<DependencyEdge>,
+    /// Open blockers that can be worked on right now.
+    pub actionable: Vec<String>,
+}
+
+/// The dependency graph behind `task_id`; `None` when there is no such task.
+pub fn unblock_chain(tasks: &[Task], task_id: &str) -> Option<UnblockChain> {
+    let by_id: HashMap<&str, &Task> = tasks.iter().map(|t| (t.id.as_str(), t)).collect();
+    by_id.get(task_id)?;
+    fn blockers<'a>(task: &'a Task, by_id: &HashMap<&str, &Task>) -> Vec<&'a str> {
+        task.blocked_by
+            .iter()
+            .map(String::as_str)
+            .filter(|id| by_id.contains_key(id))
+            .collect()
+    }
+
+    // Breadth-first for the depths.
+    let mut depths: HashMap<&str, usize> = HashMap::from([(task_id, 0)]);
+    let mut queue = vec![task_id];
+    while !queue.is_empty() {
+        let mut next = Vec::new();
+        for id in queue {
+            let depth = depths[id];
+            for blocker in blockers(by_id[id], &by_id) {
+                if !depths.contains_key(blocker) {
+                    depths.insert(blocker, depth + 1);
+                    next.push(blocker);
+                }
+            }
+        }
+        queue = next;
+    }
+
+    // Post-order walk for the blockers-first order; `placed` also guards legacy cycles.
+    fn place<'a>(
+        id: &'a str,
+        by_id: &HashMap<&'a str, &'a Task>,
+        placed: &mut HashSet<&'a str>,
+        order: &mut Vec<&'a str>,
+    ) {
+        if !placed.insert(id) {
+            return;
+        }
+        for blocker in &by_id[id].blocked_by {
+            if let Some((blocker, _)) = by_id.get_key_value(blocker.as_str()) {
+                place(blocker, by_id, placed, order);
+            }
+        }
+        order.push(id);
+    }
+    let mut order = Vec::new();
+    place(task_id, &by_id, &mut HashSet::new(), &mut order);
+
+    let is_blocked = |task: &Task| {
+        !task.completed
+            && blockers(task, &by_id)
+                .into_iter()
+                .any(|id| !by_id[id].completed)
+    };
+    let nodes: Vec<DependencyNode> = order
+        .iter()
+        .map(|id| {
+            let task = by_id[id];
+            DependencyNode {
+                id: task.id.clone(),
+                title: task.title.clone(),
+                completed: task.completed,
+                blocked: is_blocked(task),
+                depth: depths[id],
+            }
+        })
+        .collect();
+    let edges = order
+        .iter()
+        .flat_map(|id| {
+            blockers(by_id[id], &by_id)
+                .into_iter()
+                .map(move |blocker| DependencyEdge {
+                    from: blocker.to_string(),
+                    to: id.to_string(),
+                })
+        })
+        .collect();
+    let actionable = nodes
+        .iter()
+        .filter(|node| node.id != task_id && !node.completed && !node.blocked)
+        .map(|node| node.id.clone())
+        .collect();
+    Some(UnblockChain {
+        task_id: task_id.to_string(),
+        nodes,
+        edges,
+        actionable,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+
+    fn task(id: &str, blocked_by: &[&str], completed: bool) -> Task {
+        let mut task = test_support::task(id)
+            .title(&id.to_uppercase())
+            .completed(completed);
+        task.blocked_by = blocked_by.iter().map(|id| id.to_string()).collect();
+        task
+    }
+
+    #[test]
+    fn validation_cleans_ids_and_rejects_self_and_cycles() {
+        let stored = vec![task("a", &["b"], false), task("b", &[], false)];
+        let mut c = task("c", &[" a ", "a", "gone", ""], false);
+        validate_blocked_by(&mut c, &stored).unwrap();
+        assert_eq!(c.blocked_by, vec!["a"]);
+
+        let mut own = task("c", &["c"], false);
+        assert!(validate_blocked_by(&mut own, &stored).is_err());
+
+        // b -> a -> b
+        let mut b = task("b", &["a"], false);
+        assert_eq!(
+            validate_blocked_by(&mut b, &stored).unwrap_err(),
+            "dependency cycle: b -> a -> b"
+        );
+        // Replacing a's own blockers is checked against the new list, not the stored one.
+        let mut a = task("a", &[], false);
+        validate_blocked_by(&mut a, &stored).unwrap();
+    }
+
+    #[test]
+    fn blocked_ids_ignore_completed_and_missing_blockers() {
+        let tasks = vec![
+            task("a", &["b"], false),
+            task("b", &[], false),
+            task("c", &["d"], false),
+            task("d", &[], true),
+            task("e", &["gone"], false),
+            task("f", &["b"], true),
+        ];
+        let blocked = blocked_task_ids(&tasks);
+        assert_eq!(blocked, HashSet::from(["a".to_string()]));
+    }
+
+    #[test]
+    fn unblock_chain_orders_blockers_first() {
+        // goal <- (design, review); review <- design; design <- research (done)
+        let tasks = vec![
+            task("goal", &["review", "design"], false),
+            task("review", &["design"], false),
+            task("design", &["research", "gone"], false),
+            task("research", &[], true),
+            task("other", &[], false),
+        ];
+        let chain = unblock_chain(&tasks, "goal").unwrap();
+        let order: Vec<_> = chain
+            .nodes
+            .iter()
+            .map(|n| (n.id.as_str(), n.depth, n.blocked))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                ("research", 2, false),
+                ("design", 1, false),
+                ("review", 1, true),
+                ("goal", 0, true),
+            ]
+        );
+        assert_eq!(chain.edges.len(), 4);
+        assert!(chain.edges.contains(&DependencyEdge {
+            from: "design".to_string(),
+            to: "review".to_string(),
+        }));
+        assert_eq!(chain.actionable, vec!["design"]);
+        assert!(unblock_chain(&tasks, "missing").is_none());
+    }
+}
@@ -0,0 +1,156 @@
+use std::path::Path;
+
+use crate::models::{ReminderKind, Task, Timestamp};
+use crate::storage::{append_jsonl, compact_jsonl, read_jsonl, StorageError};
+
+const REMINDER_AUDIT_FILE: &str = "reminder_audit.jsonl";
+const REMINDER_AUDIT_LIMIT: usize = 1000;
+
+/// Reminders that fired in the same tick, delivered as one notification/overlay.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ReminderDigest {
+    pub fired_at: Timestamp,
+    /// True when any task is a forced reminder; the overlay window is shown for the whole digest.
+    pub forced: bool,
+    pub tasks: Vec<Task>,
+}
+
+/// One reminder firing, recorded per task even when it was delivered inside a digest.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ReminderAuditEntry {
+    pub at: Timestamp,
+    pub task_id: String,
+    pub title: String,
+    pub kind: ReminderKind,
+    pub grouped: bool,
+}
+
+pub fn should_group(count: usize, threshold: u32) -> bool {
+    threshold > 0 && count >= threshold as usize
+}
+
+pub fn build_digest(tasks: Vec<Task>, fired_at: Timestamp) -> ReminderDigest {
+    ReminderDigest {
+        fired_at,
+        forced: tasks
+            .iter()
+            .any(|task| task.reminder.kind == ReminderKind::Forced),
+        tasks,
+    }
+}
+
+pub fn audit_entries(tasks: &[Task], at: Timestamp, grouped: bool) -> Vec<ReminderAuditEntry> {
+    tasks
+        .iter()
+        .map(|task| ReminderAuditEntry {
+            at,
+            task_id: task.id.clone(),
+            title: task.title.clone(),
+            kind: task.reminder.kind.clone(),
+            grouped,
+        })
+        .collect()
+}
+
+/// Appends one tick's firings to `reminder_audit.jsonl`.
+pub fn append_reminder_audit(
+    root: &Path,
+    entries: &[ReminderAuditEntry],
+) -> Result<(), StorageError> {
+    append_jsonl(&root.join(REMINDER_AUDIT_FILE), entries)
+}
+
+/// Keeps the newest `REMINDER_AUDIT_LIMIT` entries; returns how many lines were dropped.
+pub fn compact_reminder_audit(root: &Path) -> Result<usize, StorageError> {
+    compact_jsonl::<ReminderAuditEntry>(&root.join(REMINDER_AUDIT_FILE), REMINDER_AUDIT_LIMIT)
+}
+
+/// Newest first; unparsable lines are skipped.
+pub fn read_reminder_audit(
+    root: &Path,
+    limit: usize,
+) -> Result<Vec<ReminderAuditEntry>, StorageError> {
+    let entries = read_jsonl(&root.join(REMINDER_AUDIT_FILE))?;
+    Ok(entries.into_iter().rev().take(limit).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+    use std::fs;
+
+    fn task(id: &str, kind: ReminderKind) -> Task {
+        let mut task = test_support::task(id);
+        task.reminder.kind = kind;
+        task
+    }
+
+    #[test]
+    fn grouping_follows_threshold() {
+        assert!(!should_group(15, 0));
+        assert!(!should_group(4, 5));
+        assert!(should_group(5, 5));
+        assert!(should_group(1, 1));
+
+        let digest = build_digest(
+            vec![
+                task("a", ReminderKind::Normal),
+                task("b", ReminderKind::Forced),
+            ],
+            42,
+        );
+        assert!(digest.forced);
+        assert_eq!(digest.fired_at, 42);
+        assert!(!build_digest(vec![task("a", ReminderKind::Normal)], 1).forced);
+    }
+
+    #[test]
+    fn audit_log_keeps_one_entry_per_task_newest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let tasks = vec![
+            task("a", ReminderKind::Normal),
+            task("b", ReminderKind::Forced),
+        ];
+        append_reminder_audit(dir.path(), &audit_entries(&tasks, 10, true)).unwrap();
+        append_reminder_audit(dir.path(), &audit_entries(&tasks[..1], 20, false)).unwrap();
+        append_reminder_audit(dir.path(), &[]).unwrap();
+        fs::write(
+            dir.path().join(REMINDER_AUDIT_FILE),
+            format!(
+                "{}not json\n",
+                fs::read_to_string(dir.path().join(REMINDER_AUDIT_FILE)).unwrap()
+            ),
+        )
+        .unwrap();
+
+        let entries = read_reminder_audit(dir.path(), 10).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!((entries[0].at, entries[0].grouped), (20, false));
+        assert_eq!(entries[1].task_id, "b");
+        assert_eq!(entries[1].kind, ReminderKind::Forced);
+        assert!(entries[2].grouped);
+        assert_eq!(read_reminder_audit(dir.path(), 1).unwrap().len(), 1);
+
+        let empty = tempfile::tempdir().unwrap();
+        assert!(read_reminder_audit(empty.path(), 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn audit_log_is_trimmed_to_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let tasks: Vec<Task> = (0..REMINDER_AUDIT_LIMIT + 5)
+            .map(|i| task(&format!("t{i}"), ReminderKind::Normal))
+            .collect();
+        append_reminder_audit(dir.path(), &audit_entries(&tasks, 1, true)).unwrap();
+        assert_eq!(
+            read_reminder_audit(dir.path(), usize::MAX).unwrap().len(),
+            REMINDER_AUDIT_LIMIT + 5
+        );
+        assert_eq!(compact_reminder_audit(dir.path()).unwrap(), 5);
+        assert_eq!(compact_reminder_audit(dir.path()).unwrap(), 0);
+        let entries = read_reminder_audit(dir.path(), usize::MAX).unwrap();
+        assert_eq!(entries.len(), REMINDER_AUDIT_LIMIT);
+        assert_eq!(entries[0].task_id, format!("t{}", REMINDER_AUDIT_LIMIT + 4));
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::models::{Project, Task};
+use crate::state::AppStateSnapshot;
+
+/// Bookkeeping fields that change on every write and would drown out the real differences.
+const IGNORED_FIELDS: [&str; 1] = ["updated_at"];
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TaskChange {
+    pub before: Task,
+    pub after: Task,
+    /// Top-level `Task` fields that differ, in field-name order.
+    pub fields: Vec<String>,
+}
+
+/// What a command would change, as computed on a detached copy of the state.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct ChangeSet {
+    pub created: Vec<Task>,
+    pub updated: Vec<TaskChange>,
+    pub deleted: Vec<Task>,
+    pub projects_created: Vec<Project>,
+    pub projects_updated: Vec<Project>,
+    pub projects_deleted: Vec<Project>,
+}
+
+impl ChangeSet {
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty()
+            && self.updated.is_empty()
+            && self.deleted.is_empty()
+            && self.projects_created.is_empty()
+            && self.projects_updated.is_empty()
+            && self.projects_deleted.is_empty()
+    }
+}
+
+/// Result of a command that accepts `dry_run`: the usual data, or the would-be changes.
+/// Untagged so callers that never pass `dry_run` see the same payload as before.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(untagged)]
+pub enum Outcome<T> {
+    Applied(T),
+    DryRun(ChangeSet),
+}
+
+pub fn changed_fields(before: &Task, after: &Task) -> Vec<String> {
+    let as_map = |task: &Task| match serde_json::to_value(task) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    let before = as_map(before);
+    let after = as_map(after);
+    after
+        .iter()
+        .filter(|(key, value)| {
+            !IGNORED_FIELDS.contains(&key.as_str()) && before.get(key.as_str()) != Some(value)
+        })
+        .map(|(key, _)| key.clone())
+        .collect()
+}
+
+pub fn same_project(before: &Project, after: &Project) -> bool {
+    let mut before = before.clone();
+    before.updated_at = after.updated_at;
+    serde_json::to_value(&before).ok() == serde_json::to_value(after).ok()
+}
+
+pub fn diff_snapshots(before: &AppStateSnapshot, after: &AppStateSnapshot) -> ChangeSet {
+    let mut changes = ChangeSet::default();
+
+    let old_tasks: BTreeMap<&str, &Task> = before
+        .tasks
+        .iter()
+        .map(|task| (task.id.as_str(), task))
+        .collect();
+    let new_ids: BTreeSet<&str> = after.tasks.iter().map(|task| task.id.as_str()).collect();
+    for task in after.tasks.iter() {
+        match old_tasks.get(task.id.as_str()) {
+            None => changes.created.push(task.clone()),
+            Some(old) => {
+                let fields = changed_fields(old, task);
+                if !fields.is_empty() {
+                    changes.updated.push(TaskChange {
+                        before: (*old).clone(),
+                        after: task.clone(),
+                        fields,
+                    });
+                }
+            }
+        }
+    }
+    changes.deleted = before
+        .tasks
+        .iter()
+        .filter(|task| !new_ids.contains(task.id.as_str()))
+        .cloned()
+        .collect();
+
+    let old_projects: BTreeMap<&str, &Project> = before
+        .projects
+        .iter()
+        .map(|project| (project.id.as_str(), project))
+        .collect();
+    for project in &after.projects {
+        match old_projects.get(project.id.as_str()) {
+            None => changes.projects_created.push(project.clone()),
+            Some(old) if !same_project(old, project) => {
+                changes.projects_updated.push(project.clone())
+            }
+            Some(_) => {}
+        }
+    }
+    changes.projects_deleted = before
+        .projects
+        .iter()
+        .filter(|project| !after.projects.iter().any(|p| p.id == project.id))
+        .cloned()
+        .collect();
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::task;
+    use crate::models::Settings;
+
+    fn project(id: &str, name: &str) -> Project {
+        serde_json::from_str(&format!(
+            r#"{{"id":"{id}","name":"{name}","pinned":false,"sort_order":0,"created_at":1,"updated_at":1}}"#
+        ))
+        .unwrap()
+    }
+
+    fn snapshot(tasks: Vec<Task>, projects: Vec<Project>) -> AppStateSnapshot {
+        AppStateSnapshot {
+            tasks: std::sync::Arc::new(tasks),
+            projects,
+            settings: Settings::default(),
+        }
+    }
+
+    #[test]
+    fn diff_lists_created_updated_and_deleted_tasks_and_projects() {
+        let mut edited = task("b");
+        edited.title = "renamed".to_string();
+        edited.important = true;
+        edited.updated_at = 99;
+        let mut touched = task("c");
+        touched.updated_at = 99;
+
+        let before = snapshot(
+            vec![task("a"), task("b"), task("c")],
+            vec![project("inbox", "Inbox"), project("old", "Old")],
+        );
+        let after = snapshot(
+            vec![edited, touched, task("d")],
+            vec![project("inbox", "Box"), project("new", "New")],
+        );
+        let changes = diff_snapshots(&before, &after);
+        assert_eq!(changes.created.len(), 1);
+        assert_eq!(changes.created[0].id, "d");
+        assert_eq!(changes.deleted.len(), 1);
+        assert_eq!(changes.deleted[0].id, "a");
+        // Only `updated_at` moved on `c`, so it is not a change.
+        assert_eq!(changes.updated.len(), 1);
+        assert_eq!(changes.updated[0].fields, ["important", "title"]);
+        assert_eq!(changes.updated[0].before.title, "b");
+        assert_eq!(changes.projects_created[0].id, "new");
+        assert_eq!(changes.projects_updated[0].name, "Box");
+        assert_eq!(changes.projects_deleted[0].id, "old");
+        assert!(!changes.is_empty());
+        assert!(diff_snapshots(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn outcome_serializes_like_the_plain_result() {
+        assert_eq!(
+            serde_json::to_value(Outcome::<bool>::Applied(true)).unwrap(),
+            serde_json::json!(true)
+        );
+        let dry = serde_json::to_value(Outcome::<bool>::DryRun(ChangeSet::default())).unwrap();
+        assert_eq!(dry["created"], serde_json::json!([]));
+    }
+}
@@ -0,0 +1,117 @@
+use chrono::{Local, NaiveDate, TimeZone};
+
+use crate::models::Task;
+
+/// What `create_task` returns when asked to `check_duplicates`. Untagged so the created task
+/// reads exactly as before; a held-back creation is `{ "duplicates": [...] }`.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(untagged)]
+pub enum CreateOutcome {
+    Created(Box<Task>),
+    Duplicates { duplicates: Vec<Task> },
+}
+
+/// Lowercased, inner whitespace collapsed, punctuation trimmed from both ends: "Buy milk!" and
+/// " buy  MILK" are the same title.
+pub fn normalize_title(title: &str) -> String {
+    title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c: char| c.is_ascii_punctuation() || c.is_whitespace())
+        .to_lowercase()
+}
+
+/// Local due day; undated and someday tasks share the `None` day.
+fn due_day(task: &Task) -> Option<NaiveDate> {
+    if task.someday {
+        return None;
+    }
+    let due_at = task.due_at?;
+    Local
+        .timestamp_opt(due_at, 0)
+        .single()
+        .map(|at| at.date_naive())
+}
+
+/// Open tasks that look like `candidate` again: same normalized title, same project, due the
+/// same day. Completed tasks never count.
+pub fn find_duplicates(tasks: &[Task], candidate: &Task) -> Vec<Task> {
+    let title = normalize_title(&candidate.title);
+    if title.is_empty() {
+        return Vec::new();
+    }
+    let day = due_day(candidate);
+    tasks
+        .iter()
+        .filter(|task| {
+            !task.completed
+                && task.id != candidate.id
+                && task.project_id == candidate.project_id
+                && due_day(task) == day
+                && normalize_title(&task.title) == title
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+
+    fn task(id: &str, project_id: &str, title: &str, due_at: Option<i64>) -> Task {
+        test_support::task(id)
+            .project(project_id)
+            .title(title)
+            .due(due_at)
+    }
+
+    fn at(day: u32, hour: u32) -> Option<i64> {
+        Some(
+            Local
+                .with_ymd_and_hms(2026, 4, day, hour, 0, 0)
+                .unwrap()
+                .timestamp(),
+        )
+    }
+
+    #[test]
+    fn titles_match_ignoring_case_spacing_and_end_punctuation() {
+        assert_eq!(normalize_title("  Buy   MILK! "), "buy milk");
+        assert_eq!(normalize_title("...call mom?"), "call mom");
+        assert_eq!(normalize_title("写周报。"), "写周报。");
+        assert_eq!(normalize_title(" !? "), "");
+    }
+
+    #[test]
+    fn only_open_tasks_in_the_same_project_and_day_are_duplicates() {
+        let mut done = task("done", "inbox", "Buy milk", at(10, 9));
+        done.completed = true;
+        let mut someday = task("someday", "inbox", "Call mom", at(12, 9));
+        someday.someday = true;
+        let tasks = vec![
+            task("same", "inbox", "buy milk", at(10, 18)),
+            task("other-day", "inbox", "Buy milk", at(11, 9)),
+            task("other-project", "work", "Buy milk", at(10, 9)),
+            done,
+            task("undated", "inbox", "Call mom", None),
+            someday,
+        ];
+
+        let ids = |candidate: &Task| -> Vec<String> {
+            find_duplicates(&tasks, candidate)
+                .into_iter()
+                .map(|task| task.id)
+                .collect()
+        };
+        assert_eq!(ids(&task("new", "inbox", "Buy milk!", at(10, 7))), ["same"]);
+        assert_eq!(
+            ids(&task("new", "inbox", "call mom", None)),
+            ["undated", "someday"]
+        );
+        assert!(ids(&task("new", "inbox", "Buy bread", at(10, 7))).is_empty());
+        assert!(ids(&task("same", "inbox", "Buy milk", at(10, 7))).is_empty());
+        assert!(ids(&task("new", "inbox", "!!", at(10, 7))).is_empty());
+    }
+}
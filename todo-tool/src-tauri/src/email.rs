@@ -0,0 +1,466 @@
+use std::path::Path;
+
+use crate::calendar::{DateFormatter, DateLocale};
+use crate::models::{ReminderKind, Settings, SmtpSecurity, Task, Timestamp};
+use crate::storage::{append_jsonl, compact_jsonl, read_jsonl, StorageError};
+
+const DELIVERY_LOG_FILE: &str = "email_deliveries.jsonl";
+const DELIVERY_LOG_LIMIT: usize = 200;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    pub username: String,
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmailMessage {
+    pub from: String,
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryKind {
+    Reminder,
+    Test,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DeliveryLogEntry {
+    pub at: Timestamp,
+    pub kind: DeliveryKind,
+    pub to: String,
+    pub subject: String,
+    #[serde(default)]
+    pub task_ids: Vec<String>,
+    pub ok: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Validates the SMTP-related settings and pairs them with the stored password.
+pub fn smtp_config(settings: &Settings, password: Option<String>) -> Result<SmtpConfig, String> {
+    let host = settings.smtp_host.trim();
+    if host.is_empty() {
+        return Err("smtp host missing (settings.smtp_host)".to_string());
+    }
+    if settings.smtp_port == 0 {
+        return Err("smtp port invalid (settings.smtp_port)".to_string());
+    }
+    if !looks_like_address(&settings.email_from) {
+        return Err("sender address invalid (settings.email_from)".to_string());
+    }
+    if !looks_like_address(&settings.email_to) {
+        return Err("recipient address invalid (settings.email_to)".to_string());
+    }
+    let username = settings.smtp_username.trim().to_string();
+    if !username.is_empty() && password.is_none() {
+        return Err("smtp password missing (secret smtp_password)".to_string());
+    }
+    Ok(SmtpConfig {
+        host: host.to_string(),
+        port: settings.smtp_port,
+        security: settings.smtp_security,
+        username,
+        password,
+    })
+}
+
+fn looks_like_address(value: &str) -> bool {
+    let value = value.trim();
+    match value.split_once('@') {
+        Some((local, domain)) => !local.is_empty() && !domain.is_empty() && !value.contains(' '),
+        None => false,
+    }
+}
+
+/// Tasks whose reminder already fired but still needs the email fallback.
+///
+/// A firing is considered unacknowledged while the task stays open, is not snoozed past `now`
+/// and (for forced reminders) was not dismissed. When the app runs `headless` (no visible
+/// window) the email goes out immediately; otherwise only after `email_unacked_minutes`.
+pub fn email_reminder_candidates(
+    tasks: &[Task],
+    settings: &Settings,
+    now: Timestamp,
+    headless: bool,
+) -> Vec<Task> {
+    if !settings.email_reminder_enabled {
+        return Vec::new();
+    }
+    let headless = headless && settings.email_when_headless;
+    let wait_secs = settings.email_unacked_minutes.saturating_mul(60);
+    if !headless && wait_secs <= 0 {
+        return Vec::new();
+    }
+
+    tasks
+        .iter()
+        .filter(|task| !task.completed && !task.someday)
+        .filter(|task| {
+            let reminder = &task.reminder;
+            if reminder.kind == ReminderKind::None {
+                return false;
+            }
+            if reminder.kind == ReminderKind::Forced && reminder.forced_dismissed {
+                return false;
+            }
+            if reminder.snoozed_until.is_some_and(|until| until > now) {
+                return false;
+            }
+            let Some(fired_at) = reminder.last_fired_at else {
+                return false;
+            };
+            if reminder.email_sent_at.is_some_and(|sent| sent >= fired_at) {
+                return false;
+            }
+            headless || now.saturating_sub(fired_at) >= wait_secs
+        })
+        .cloned()
+        .collect()
+}
+
+/// Emails follow `settings.language`; "auto" falls back to the system locale.
+/// Language follows `dates.locale`; due times read naturally ("明天 14:00", "Tomorrow 2pm").
+pub fn build_reminder_email(
+    settings: &Settings,
+    tasks: &[Task],
+    dates: &DateFormatter,
+) -> EmailMessage {
+    let zh = dates.locale == DateLocale::Zh;
+    let subject = match (zh, tasks) {
+        (true, [task]) => format!("MustDo 提醒：{}", task.title),
+        (true, _) => format!("MustDo 提醒：{} 项待办未处理", tasks.len()),
+        (false, [task]) => format!("MustDo reminder: {}", task.title),
+        (false, _) => format!("MustDo reminder: {} tasks need attention", tasks.len()),
+    };
+
+    let mut body = String::new();
+    body.push_str(if zh {
+        "以下提醒仍未处理：\n\n"
+    } else {
+        "These reminders have not been handled yet:\n\n"
+    });
+    for task in tasks {
+        let marker = if task.important { "!" } else { "-" };
+        let due_label = if zh { "截止" } else { "due" };
+        match task.due_at {
+            Some(due_at) => body.push_str(&format!(
+                "{marker} {} ({due_label} {})\n",
+                task.title,
+                dates.natural(due_at)
+            )),
+            None => body.push_str(&format!("{marker} {}\n", task.title)),
+        }
+    }
+    body.push_str(if zh {
+        "\n打开 MustDo 完成或稍后提醒。\n"
+    } else {
+        "\nOpen MustDo to complete or snooze them.\n"
+    });
+
+    EmailMessage {
+        from: settings.email_from.trim().to_string(),
+        to: settings.email_to.trim().to_string(),
+        subject,
+        body,
+    }
+}
+
+pub fn build_test_email(settings: &Settings, dates: &DateFormatter) -> EmailMessage {
+    let now = dates.now.timestamp();
+    let (subject, body) = if dates.locale == DateLocale::Zh {
+        (
+            "MustDo 测试邮件".to_string(),
+            format!("邮件提醒配置可用（发送时间 {}）。\n", dates.absolute(now)),
+        )
+    } else {
+        (
+            "MustDo test email".to_string(),
+            format!(
+                "Email reminders are configured correctly (sent at {}).\n",
+                dates.absolute(now)
+            ),
+        )
+    };
+    EmailMessage {
+        from: settings.email_from.trim().to_string(),
+        to: settings.email_to.trim().to_string(),
+        subject,
+        body,
+    }
+}
+
+/// Appends one delivery attempt to `email_deliveries.jsonl`.
+pub fn append_delivery_log(root: &Path, entry: &DeliveryLogEntry) -> Result<(), StorageError> {
+    append_jsonl(&root.join(DELIVERY_LOG_FILE), std::slice::from_ref(entry))?;
+    log::info!(
+        "email: delivery logged kind={:?} ok={} tasks={}",
+        entry.kind,
+        entry.ok,
+        entry.task_ids.len()
+    );
+    Ok(())
+}
+
+/// Newest first. Lines that fail to parse are skipped rather than failing the whole log.
+pub fn read_delivery_log(root: &Path, limit: usize) -> Result<Vec<DeliveryLogEntry>, StorageError> {
+    let entries = read_jsonl(&root.join(DELIVERY_LOG_FILE))?;
+    Ok(entries.into_iter().rev().take(limit).collect())
+}
+
+/// Drops unparsable lines and keeps the newest `DELIVERY_LOG_LIMIT` entries; returns how many
+/// lines were dropped.
+pub fn compact_delivery_log(root: &Path) -> Result<usize, StorageError> {
+    compact_jsonl::<DeliveryLogEntry>(&root.join(DELIVERY_LOG_FILE), DELIVERY_LOG_LIMIT)
+}
+
+#[cfg(all(feature = "app", not(test)))]
+pub async fn send_email(config: &SmtpConfig, message: &EmailMessage) -> Result<(), String> {
+    use lettre::transport::smtp::authentication::Credentials;
+    use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+    let email = Message::builder()
+        .from(
+            message
+                .from
+                .parse()
+                .map_err(|err| format!("invalid from: {err}"))?,
+        )
+        .to(message
+            .to
+            .parse()
+            .map_err(|err| format!("invalid to: {err}"))?)
+        .subject(message.subject.clone())
+        .body(message.body.clone())
+        .map_err(|err| format!("build email failed: {err}"))?;
+
+    let builder = match config.security {
+        SmtpSecurity::StartTls => {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
+        }
+        SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host),
+        SmtpSecurity::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+            &config.host,
+        )),
+    }
+    .map_err(|err| format!("smtp setup failed: {err}"))?;
+
+    let mut builder = builder
+        .port(config.port)
+        .timeout(Some(std::time::Duration::from_secs(20)));
+    if !config.username.is_empty() {
+        builder = builder.credentials(Credentials::new(
+            config.username.clone(),
+            config.password.clone().unwrap_or_default(),
+        ));
+    }
+
+    builder
+        .build()
+        .send(email)
+        .await
+        .map(|_| ())
+        .map_err(|err| format!("smtp send failed: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_support, ReminderConfig};
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+
+    fn make_task(id: &str, kind: ReminderKind, last_fired_at: Option<i64>) -> Task {
+        Task {
+            sort_order: 1,
+            reminder: ReminderConfig {
+                kind,
+                last_fired_at,
+                ..ReminderConfig::default()
+            },
+            ..test_support::task(id)
+                .title(&format!("task-{id}"))
+                .due(1_700_000_000)
+        }
+    }
+
+    fn email_settings() -> Settings {
+        Settings {
+            email_reminder_enabled: true,
+            smtp_host: "smtp.example.com".to_string(),
+            smtp_username: "me".to_string(),
+            email_from: "me@example.com".to_string(),
+            email_to: "me@example.com".to_string(),
+            ..Settings::default()
+        }
+    }
+
+    #[test]
+    fn smtp_config_validates_required_fields() {
+        let settings = email_settings();
+        let config = smtp_config(&settings, Some("pw".to_string())).unwrap();
+        assert_eq!(config.host, "smtp.example.com");
+        assert_eq!(config.port, 587);
+        assert_eq!(config.security, SmtpSecurity::StartTls);
+
+        assert!(smtp_config(&settings, None)
+            .unwrap_err()
+            .contains("password"));
+
+        let mut anonymous = settings.clone();
+        anonymous.smtp_username = " ".to_string();
+        assert!(smtp_config(&anonymous, None).is_ok());
+
+        let mut bad = settings.clone();
+        bad.smtp_host = String::new();
+        assert!(smtp_config(&bad, None).unwrap_err().contains("smtp_host"));
+        let mut bad = settings.clone();
+        bad.smtp_port = 0;
+        assert!(smtp_config(&bad, None).unwrap_err().contains("smtp_port"));
+        let mut bad = settings.clone();
+        bad.email_from = "nobody".to_string();
+        assert!(smtp_config(&bad, None).unwrap_err().contains("email_from"));
+        let mut bad = settings;
+        bad.email_to = "a b@example.com".to_string();
+        assert!(smtp_config(&bad, None).unwrap_err().contains("email_to"));
+    }
+
+    #[test]
+    fn email_reminder_candidates_respect_ack_state_and_wait_time() {
+        let settings = email_settings();
+        let now = 10_000;
+        let fired = now - 20 * 60;
+
+        let mut done = make_task("done", ReminderKind::Normal, Some(fired));
+        done.completed = true;
+        let mut snoozed = make_task("snoozed", ReminderKind::Normal, Some(fired));
+        snoozed.reminder.snoozed_until = Some(now + 60);
+        let mut dismissed = make_task("dismissed", ReminderKind::Forced, Some(fired));
+        dismissed.reminder.forced_dismissed = true;
+        let mut emailed = make_task("emailed", ReminderKind::Normal, Some(fired));
+        emailed.reminder.email_sent_at = Some(fired);
+        let tasks = vec![
+            make_task("due", ReminderKind::Forced, Some(fired)),
+            make_task("recent", ReminderKind::Normal, Some(now - 60)),
+            make_task("never", ReminderKind::Normal, None),
+            make_task("off", ReminderKind::None, Some(fired)),
+            done,
+            snoozed,
+            dismissed,
+            emailed,
+        ];
+
+        let ids = |tasks: Vec<Task>| tasks.into_iter().map(|t| t.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(email_reminder_candidates(&tasks, &settings, now, false)),
+            vec!["due"]
+        );
+        // Headless delivery does not wait for the unacknowledged window.
+        assert_eq!(
+            ids(email_reminder_candidates(&tasks, &settings, now, true)),
+            vec!["due", "recent"]
+        );
+
+        let mut no_wait = settings.clone();
+        no_wait.email_unacked_minutes = 0;
+        assert!(email_reminder_candidates(&tasks, &no_wait, now, false).is_empty());
+        no_wait.email_when_headless = false;
+        assert!(email_reminder_candidates(&tasks, &no_wait, now, true).is_empty());
+
+        let mut disabled = settings;
+        disabled.email_reminder_enabled = false;
+        assert!(email_reminder_candidates(&tasks, &disabled, now, true).is_empty());
+    }
+
+    fn dates(locale: DateLocale) -> DateFormatter {
+        use chrono::TimeZone;
+        DateFormatter {
+            format: crate::models::DateFormat::Ymd,
+            locale,
+            now: chrono::Local.timestamp_opt(1_700_000_000, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn build_emails_list_tasks_in_both_languages() {
+        let settings = email_settings();
+        let mut important = make_task("a", ReminderKind::Normal, None);
+        important.important = true;
+        let other = make_task("b", ReminderKind::Normal, None);
+        let en = dates(DateLocale::En);
+        let zh = dates(DateLocale::Zh);
+
+        let single = build_reminder_email(&settings, std::slice::from_ref(&important), &en);
+        assert_eq!(single.subject, "MustDo reminder: task-a");
+        assert_eq!(single.to, "me@example.com");
+        assert!(single.body.contains("! task-a (due Today "));
+
+        let multi = build_reminder_email(&settings, &[important.clone(), other.clone()], &zh);
+        assert_eq!(multi.subject, "MustDo 提醒：2 项待办未处理");
+        assert!(multi.body.contains("- task-b (截止 今天 "));
+        let single_zh = build_reminder_email(&settings, &[other], &zh);
+        assert_eq!(single_zh.subject, "MustDo 提醒：task-b");
+        let multi_en = build_reminder_email(&settings, &[important.clone(), important], &en);
+        assert!(multi_en.subject.contains("2 tasks"));
+
+        assert_eq!(
+            build_test_email(&settings, &en).subject,
+            "MustDo test email"
+        );
+        assert!(build_test_email(&settings, &zh).body.contains("邮件提醒"));
+    }
+
+    #[test]
+    fn delivery_log_appends_trims_and_reads_newest_first() {
+        let root = tempfile::tempdir().unwrap();
+        assert!(read_delivery_log(root.path(), 10).unwrap().is_empty());
+
+        let entry = |at: i64| DeliveryLogEntry {
+            at,
+            kind: DeliveryKind::Reminder,
+            to: "me@example.com".to_string(),
+            subject: "s".to_string(),
+            task_ids: vec!["a".to_string()],
+            ok: at % 2 == 0,
+            error: None,
+        };
+        for at in 0..(DELIVERY_LOG_LIMIT as i64 + 5) {
+            append_delivery_log(root.path(), &entry(at)).unwrap();
+        }
+        // Garbage lines are ignored.
+        let path = root.path().join(DELIVERY_LOG_FILE);
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file, "not json").unwrap();
+
+        let all = read_delivery_log(root.path(), usize::MAX).unwrap();
+        assert_eq!(all.len(), DELIVERY_LOG_LIMIT + 5);
+        assert_eq!(all[0].at, DELIVERY_LOG_LIMIT as i64 + 4);
+        assert_eq!(read_delivery_log(root.path(), 3).unwrap().len(), 3);
+
+        // A directory in place of the log file surfaces as an io error.
+        let broken = tempfile::tempdir().unwrap();
+        fs::create_dir(broken.path().join(DELIVERY_LOG_FILE)).unwrap();
+        assert!(append_delivery_log(broken.path(), &entry(1)).is_err());
+        assert!(read_delivery_log(broken.path(), 1).is_err());
+
+        // Compaction drops the garbage line and the oldest entries once, then is a no-op.
+        assert_eq!(compact_delivery_log(root.path()).unwrap(), 6);
+        assert_eq!(compact_delivery_log(root.path()).unwrap(), 0);
+        let compacted = fs::read_to_string(&path).unwrap();
+        assert_eq!(compacted.lines().count(), DELIVERY_LOG_LIMIT);
+        let oldest = read_delivery_log(root.path(), usize::MAX).unwrap();
+        assert_eq!(oldest.last().unwrap().at, 5);
+        assert_eq!(
+            compact_delivery_log(tempfile::tempdir().unwrap().path()).unwrap(),
+            0
+        );
+        assert!(compact_delivery_log(broken.path()).is_err());
+    }
+}
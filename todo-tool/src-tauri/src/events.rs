@@ -1,7 +1,26 @@
 use crate::models::{Project, Settings, Task};
 
 pub const EVENT_REMINDER: &str = "reminder_fired";
+/// Sent instead of `EVENT_REMINDER` when a tick fires at least `reminder_digest_threshold` tasks.
+pub const EVENT_REMINDER_DIGEST: &str = "reminder_digest";
+/// Normal reminders raised to Forced in one tick after being ignored too often.
+pub const EVENT_REMINDER_ESCALATED: &str = "reminder_escalated";
 pub const EVENT_STATE_UPDATED: &str = "state_updated";
+/// What changed since the previous delta (see `state_delta`).
+pub const EVENT_STATE_DELTA: &str = "state_delta";
+/// Open tasks whose `start_at` was reached since the previous scheduler tick.
+pub const EVENT_TASK_STARTED: &str = "task_started";
+pub const EVENT_DATA_DIR_PROGRESS: &str = "data_dir_progress";
+pub const EVENT_BACKUP_ARCHIVE_PROGRESS: &str = "backup_archive_progress";
+/// Completions and conflicts applied from an edited `mustdo-sync.md`.
+pub const EVENT_MARKDOWN_SYNC: &str = "markdown_sync";
+/// Completed/overdue/remaining counts, sent once a day at `daily_summary_time`.
+pub const EVENT_DAILY_SUMMARY: &str = "daily_summary";
+/// Every second while a focus session runs, then once when it ends on its own.
+pub const EVENT_FOCUS_TICK: &str = "focus_tick";
+pub const EVENT_FOCUS_FINISHED: &str = "focus_finished";
+/// A write-behind write failed, or succeeded again after failing.
+pub const EVENT_WRITE_STATUS: &str = "write_status";
 // Tauri v2 event names must be [A-Za-z0-9-/:_]. Avoid dots.
 pub const EVENT_NAVIGATE: &str = "mustdo:navigate";
 
@@ -10,6 +29,18 @@ pub struct StatePayload {
     pub tasks: Vec<Task>,
     pub projects: Vec<Project>,
     pub settings: Settings,
+    /// Latest `state_delta` already included; deltas continue from `seq + 1`.
+    pub seq: u64,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct StateDelta {
+    pub seq: u64,
+    pub tasks_upserted: Vec<Task>,
+    pub tasks_removed: Vec<String>,
+    /// The whole project list, only when some project changed.
+    pub projects_changed: Option<Vec<Project>>,
+    pub settings_changed: Option<Settings>,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -17,6 +48,257 @@ pub struct NavigatePayload {
     pub hash: String,
 }
 
+/// Bumped when the manifest format itself changes, not when an event's payload does.
+pub const EVENTS_MANIFEST_VERSION: u32 = 1;
+
+/// One field of a payload schema. `ty` uses TypeScript notation; `Task`, `Project` and
+/// `Settings` are the same shapes `load_state` returns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct FieldSpec {
+    pub name: &'static str,
+    #[serde(rename = "type")]
+    pub ty: &'static str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct SchemaSpec {
+    pub name: &'static str,
+    pub fields: &'static [FieldSpec],
+}
+
+/// `version` starts at 1 and is bumped whenever the payload changes incompatibly: a field is
+/// removed, renamed or changes type. Adding a field keeps the version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct EventSpec {
+    pub name: &'static str,
+    pub version: u32,
+    pub payload: &'static str,
+    pub description: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct EventsManifest {
+    pub manifest_version: u32,
+    pub events: Vec<EventSpec>,
+    pub schemas: Vec<SchemaSpec>,
+}
+
+const fn field(name: &'static str, ty: &'static str) -> FieldSpec {
+    FieldSpec { name, ty }
+}
+
+pub const EVENTS: &[EventSpec] = &[
+    EventSpec {
+        name: EVENT_STATE_UPDATED,
+        version: 1,
+        payload: "StatePayload",
+        description: "Full state after restores, imports and clock changes; windows replace \
+                      what they hold.",
+    },
+    EventSpec {
+        name: EVENT_STATE_DELTA,
+        version: 1,
+        payload: "StateDelta",
+        description: "Tasks, projects and settings changed by a command, numbered by seq; on a \
+                      gap in seq reload with load_state.",
+    },
+    EventSpec {
+        name: EVENT_REMINDER,
+        version: 1,
+        payload: "Task[]",
+        description: "Tasks whose reminder fired in one scheduler tick.",
+    },
+    EventSpec {
+        name: EVENT_REMINDER_ESCALATED,
+        version: 1,
+        payload: "Task[]",
+        description: "Normal reminders ignored escalate_after times that now fire as Forced; sent \
+                      just before the reminder event that carries them.",
+    },
+    EventSpec {
+        name: EVENT_TASK_STARTED,
+        version: 1,
+        payload: "Task[]",
+        description: "Tasks whose start_at was reached in one scheduler tick; they now show up in \
+                      the regular lists.",
+    },
+    EventSpec {
+        name: EVENT_REMINDER_DIGEST,
+        version: 1,
+        payload: "ReminderDigest",
+        description:
+            "Replaces reminder_fired when a tick fires reminder_digest_threshold tasks or more.",
+    },
+    EventSpec {
+        name: EVENT_DATA_DIR_PROGRESS,
+        version: 1,
+        payload: "DataDirProgress",
+        description: "Per-file progress while the data directory is moved.",
+    },
+    EventSpec {
+        name: EVENT_BACKUP_ARCHIVE_PROGRESS,
+        version: 1,
+        payload: "ArchiveProgress",
+        description: "Per-file progress while export_all_backups writes its ZIP.",
+    },
+    EventSpec {
+        name: EVENT_MARKDOWN_SYNC,
+        version: 1,
+        payload: "MarkdownSyncReport",
+        description: "Completions and conflicts applied from an edited mustdo-sync.md.",
+    },
+    EventSpec {
+        name: EVENT_DAILY_SUMMARY,
+        version: 1,
+        payload: "DailySummary",
+        description: "End-of-day counts, sent once a day at daily_summary_time when enabled.",
+    },
+    EventSpec {
+        name: EVENT_FOCUS_TICK,
+        version: 1,
+        payload: "FocusStatus",
+        description: "Once per second while a focus session is running (not while paused).",
+    },
+    EventSpec {
+        name: EVENT_FOCUS_FINISHED,
+        version: 1,
+        payload: "FocusSession",
+        description: "A focus session ran its full length; stopped sessions do not send it.",
+    },
+    EventSpec {
+        name: EVENT_WRITE_STATUS,
+        version: 1,
+        payload: "WriteStatus",
+        description:
+            "A background write of data and settings failed, or worked again after failing.",
+    },
+    EventSpec {
+        name: EVENT_NAVIGATE,
+        version: 1,
+        payload: "NavigatePayload",
+        description: "Asks the main window to switch to a route, e.g. from the tray menu.",
+    },
+];
+
+pub const SCHEMAS: &[SchemaSpec] = &[
+    SchemaSpec {
+        name: "StatePayload",
+        fields: &[
+            field("tasks", "Task[]"),
+            field("projects", "Project[]"),
+            field("settings", "Settings"),
+            field("seq", "number"),
+        ],
+    },
+    SchemaSpec {
+        name: "StateDelta",
+        fields: &[
+            field("seq", "number"),
+            field("tasks_upserted", "Task[]"),
+            field("tasks_removed", "string[]"),
+            field("projects_changed", "Project[] | null"),
+            field("settings_changed", "Settings | null"),
+        ],
+    },
+    SchemaSpec {
+        name: "ReminderDigest",
+        fields: &[
+            field("fired_at", "number"),
+            field("forced", "boolean"),
+            field("tasks", "Task[]"),
+        ],
+    },
+    SchemaSpec {
+        name: "DataDirProgress",
+        fields: &[
+            field("item", "string"),
+            field("done", "number"),
+            field("total", "number"),
+        ],
+    },
+    SchemaSpec {
+        name: "ArchiveProgress",
+        fields: &[
+            field("item", "string"),
+            field("done", "number"),
+            field("total", "number"),
+            field("bytes_done", "number"),
+            field("bytes_total", "number"),
+        ],
+    },
+    SchemaSpec {
+        name: "MarkdownSyncReport",
+        fields: &[
+            field("completed", "string[]"),
+            field("conflicts", "SyncConflict[]"),
+        ],
+    },
+    SchemaSpec {
+        name: "SyncConflict",
+        fields: &[
+            field(
+                "kind",
+                "\"added\" | \"removed\" | \"duplicated\" | \"unknown_task\" | \"text_changed\" | \"unchecked\" | \"task_missing\"",
+            ),
+            field("line", "number | null"),
+            field("task_id", "string | null"),
+            field("text", "string"),
+        ],
+    },
+    SchemaSpec {
+        name: "DailySummary",
+        fields: &[
+            field("date", "string"),
+            field("generated_at", "number"),
+            field("completed", "number"),
+            field("overdue", "number"),
+            field("remaining", "number"),
+        ],
+    },
+    SchemaSpec {
+        name: "FocusStatus",
+        fields: &[
+            field("task_id", "string"),
+            field("minutes", "number"),
+            field("started_at", "number"),
+            field("focused_seconds", "number"),
+            field("remaining_seconds", "number"),
+            field("paused", "boolean"),
+        ],
+    },
+    SchemaSpec {
+        name: "FocusSession",
+        fields: &[
+            field("task_id", "string"),
+            field("started_at", "number"),
+            field("ended_at", "number"),
+            field("minutes", "number"),
+            field("focused_seconds", "number"),
+            field("finished", "boolean"),
+        ],
+    },
+    SchemaSpec {
+        name: "WriteStatus",
+        fields: &[
+            field("pending", "boolean"),
+            field("last_error", "string | null"),
+        ],
+    },
+    SchemaSpec {
+        name: "NavigatePayload",
+        fields: &[field("hash", "string")],
+    },
+];
+
+/// The event contract for tooling and plugins, so they do not have to read this file.
+pub fn events_manifest() -> EventsManifest {
+    EventsManifest {
+        manifest_version: EVENTS_MANIFEST_VERSION,
+        events: EVENTS.to_vec(),
+        schemas: SCHEMAS.to_vec(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -27,7 +309,7 @@ mod tests {
             id: id.to_string(),
             project_id: "inbox".to_string(),
             title: format!("task-{id}"),
-            due_at: 1,
+            due_at: Some(1),
             important: false,
             completed: false,
             completed_at: None,
@@ -41,19 +323,53 @@ mod tests {
             sample_tag: None,
             reminder: ReminderConfig::default(),
             repeat: RepeatRule::None,
+            waiting_on: None,
+            waiting_since: None,
+            series_id: None,
+            pinned: false,
+            context: None,
+            comments: Vec::new(),
+            progress: Default::default(),
+            spawn_ahead: 0,
+            repeat_from_completion: false,
+            end_after_occurrences: None,
+            end_by_date: None,
+            occurrence_index: 0,
+            timezone: None,
+            escalate_after: None,
+            start_at: None,
+            someday: false,
+            carry_over_count: 0,
+            skip_carry_over: false,
+            color: None,
+            emoji: None,
+            estimate_minutes: None,
+            attachments: Vec::new(),
+            time_entries: Vec::new(),
+            blocked_by: Vec::new(),
         }
     }
 
     #[test]
     fn event_constants_and_payload_are_usable_and_serializable() {
         assert_eq!(EVENT_REMINDER, "reminder_fired");
+        assert_eq!(EVENT_REMINDER_DIGEST, "reminder_digest");
         assert_eq!(EVENT_STATE_UPDATED, "state_updated");
+        assert_eq!(EVENT_STATE_DELTA, "state_delta");
+        assert_eq!(EVENT_DATA_DIR_PROGRESS, "data_dir_progress");
+        assert_eq!(EVENT_BACKUP_ARCHIVE_PROGRESS, "backup_archive_progress");
+        assert_eq!(EVENT_MARKDOWN_SYNC, "markdown_sync");
+        assert_eq!(EVENT_DAILY_SUMMARY, "daily_summary");
+        assert_eq!(EVENT_FOCUS_TICK, "focus_tick");
+        assert_eq!(EVENT_FOCUS_FINISHED, "focus_finished");
+        assert_eq!(EVENT_WRITE_STATUS, "write_status");
         assert_eq!(EVENT_NAVIGATE, "mustdo:navigate");
 
         let payload = StatePayload {
             tasks: vec![make_task("a")],
             projects: Vec::new(),
             settings: Settings::default(),
+            seq: 0,
         };
         let value = serde_json::to_value(payload).unwrap();
         assert!(value.get("tasks").is_some());
@@ -65,4 +381,141 @@ mod tests {
         let value = serde_json::to_value(nav).unwrap();
         assert_eq!(value.get("hash").and_then(|v| v.as_str()), Some("#/main"));
     }
+
+    fn keys(value: serde_json::Value) -> Vec<String> {
+        let mut keys: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    fn schema_keys(name: &str) -> Vec<String> {
+        let schema = SCHEMAS.iter().find(|schema| schema.name == name).unwrap();
+        let mut keys: Vec<String> = schema.fields.iter().map(|f| f.name.to_string()).collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn manifest_schemas_match_the_serialized_payloads() {
+        let samples = [
+            (
+                "StatePayload",
+                serde_json::to_value(StatePayload {
+                    tasks: Vec::new(),
+                    projects: Vec::new(),
+                    settings: Settings::default(),
+                    seq: 0,
+                })
+                .unwrap(),
+            ),
+            (
+                "StateDelta",
+                serde_json::to_value(StateDelta::default()).unwrap(),
+            ),
+            (
+                "ReminderDigest",
+                serde_json::to_value(crate::digest::build_digest(vec![make_task("a")], 5)).unwrap(),
+            ),
+            (
+                "DataDirProgress",
+                serde_json::to_value(crate::data_dir::DataDirProgress {
+                    item: "data.json".to_string(),
+                    done: 1,
+                    total: 2,
+                })
+                .unwrap(),
+            ),
+            (
+                "ArchiveProgress",
+                serde_json::to_value(crate::archive::ArchiveProgress {
+                    item: "data.json".to_string(),
+                    done: 1,
+                    total: 2,
+                    bytes_done: 3,
+                    bytes_total: 4,
+                })
+                .unwrap(),
+            ),
+            (
+                "MarkdownSyncReport",
+                serde_json::to_value(crate::md_sync::MarkdownSyncReport::default()).unwrap(),
+            ),
+            (
+                "SyncConflict",
+                serde_json::to_value(crate::md_sync::SyncConflict {
+                    kind: crate::md_sync::SyncConflictKind::TaskMissing,
+                    line: Some(1),
+                    task_id: None,
+                    text: String::new(),
+                })
+                .unwrap(),
+            ),
+            (
+                "DailySummary",
+                serde_json::to_value(crate::daily_summary::build_summary(
+                    &[],
+                    chrono::Local::now(),
+                    0,
+                ))
+                .unwrap(),
+            ),
+            (
+                "FocusStatus",
+                serde_json::to_value(
+                    crate::focus::FocusTimer::default()
+                        .start("a", 25, 0)
+                        .unwrap(),
+                )
+                .unwrap(),
+            ),
+            (
+                "FocusSession",
+                serde_json::to_value({
+                    let mut timer = crate::focus::FocusTimer::default();
+                    timer.start("a", 25, 0).unwrap();
+                    timer.stop(1).unwrap()
+                })
+                .unwrap(),
+            ),
+            (
+                "WriteStatus",
+                serde_json::to_value(crate::write_queue::WriteStatus::default()).unwrap(),
+            ),
+            (
+                "NavigatePayload",
+                serde_json::to_value(NavigatePayload {
+                    hash: String::new(),
+                })
+                .unwrap(),
+            ),
+        ];
+        assert_eq!(samples.len(), SCHEMAS.len());
+        // A failure here means a payload changed: update the schema, and bump the event's
+        // version if the change removes, renames or retypes a field.
+        for (name, value) in samples {
+            assert_eq!(keys(value), schema_keys(name), "{name}");
+        }
+    }
+
+    #[test]
+    fn manifest_lists_every_event_once_with_a_known_payload() {
+        let manifest = events_manifest();
+        assert_eq!(manifest.manifest_version, EVENTS_MANIFEST_VERSION);
+        let mut names: Vec<&str> = manifest.events.iter().map(|event| event.name).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), 14);
+        for event in &manifest.events {
+            assert!(event.version >= 1);
+            let payload = event.payload.trim_end_matches("[]");
+            assert!(
+                payload == "Task" || SCHEMAS.iter().any(|schema| schema.name == payload),
+                "{}",
+                event.name
+            );
+        }
+        let value = serde_json::to_value(&manifest).unwrap();
+        assert_eq!(value["schemas"][0]["fields"][0]["type"], "Task[]");
+        assert_eq!(value["events"][0]["name"], "state_updated");
+    }
 }
@@ -0,0 +1,216 @@
+use std::collections::HashSet;
+
+use crate::models::{Task, TasksFile, Timestamp};
+use crate::tags::tag_key;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportStatus {
+    #[default]
+    All,
+    Open,
+    Completed,
+}
+
+/// Optional narrowing for the task exporters; the default exports everything.
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+pub struct ExportFilter {
+    pub project_id: Option<String>,
+    /// Case-insensitive; a leading `#` is ignored.
+    pub tag: Option<String>,
+    pub status: ExportStatus,
+    /// Range over `completed_at` for completed tasks and `due_at` for open ones; `from` is
+    /// inclusive, `to` exclusive. Tasks without that timestamp are left out once either is set.
+    pub from: Option<Timestamp>,
+    pub to: Option<Timestamp>,
+}
+
+impl ExportFilter {
+    pub fn is_empty(&self) -> bool {
+        self.project_id.is_none()
+            && self.tag.is_none()
+            && self.status == ExportStatus::All
+            && self.from.is_none()
+            && self.to.is_none()
+    }
+
+    pub fn matches(&self, task: &Task) -> bool {
+        match self.status {
+            ExportStatus::All => {}
+            ExportStatus::Open if task.completed => return false,
+            ExportStatus::Completed if !task.completed => return false,
+            ExportStatus::Open | ExportStatus::Completed => {}
+        }
+        if let Some(project_id) = self.project_id.as_deref() {
+            if task.project_id != project_id {
+                return false;
+            }
+        }
+        if let Some(tag) = self.tag.as_deref().and_then(tag_key) {
+            if !task
+                .tags
+                .iter()
+                .any(|t| tag_key(t).as_deref() == Some(&tag))
+            {
+                return false;
+            }
+        }
+        if self.from.is_some() || self.to.is_some() {
+            let at = if task.completed {
+                task.completed_at
+            } else {
+                task.due_at
+            };
+            let Some(at) = at else {
+                return false;
+            };
+            if self.from.is_some_and(|from| at < from) || self.to.is_some_and(|to| at >= to) {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub fn tasks(&self, tasks: Vec<Task>) -> Vec<Task> {
+        tasks
+            .into_iter()
+            .filter(|task| self.matches(task))
+            .collect()
+    }
+
+    /// The matching tasks with the projects they (or `project_id`) refer to, so the result can
+    /// still be imported. The trash is left out of filtered exports.
+    pub fn data(&self, data: TasksFile) -> TasksFile {
+        if self.is_empty() {
+            return data;
+        }
+        let tasks = self.tasks(data.tasks);
+        let used: HashSet<&str> = tasks
+            .iter()
+            .map(|task| task.project_id.as_str())
+            .chain(self.project_id.as_deref())
+            .collect();
+        let projects = data
+            .projects
+            .into_iter()
+            .filter(|project| used.contains(project.id.as_str()))
+            .collect();
+        TasksFile {
+            schema_version: data.schema_version,
+            tasks,
+            projects,
+            trash: Vec::new(),
+            tags: data.tags,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_support, Project};
+
+    fn task(id: &str, project: &str, tags: &[&str], due: Option<i64>, done: Option<i64>) -> Task {
+        test_support::task(id)
+            .project(project)
+            .tags(tags)
+            .due(due)
+            .done_at(done)
+    }
+
+    fn ids(tasks: &[Task]) -> Vec<&str> {
+        tasks.iter().map(|task| task.id.as_str()).collect()
+    }
+
+    fn sample() -> Vec<Task> {
+        vec![
+            task("open-work", "work", &["Client"], Some(150), None),
+            task("done-work", "work", &[], Some(50), Some(120)),
+            task("done-home", "home", &["client"], None, Some(300)),
+            task("undated", "home", &[], None, None),
+        ]
+    }
+
+    #[test]
+    fn filters_combine_project_tag_status_and_range() {
+        assert!(ExportFilter::default().is_empty());
+        assert_eq!(ExportFilter::default().tasks(sample()).len(), 4);
+
+        let work = ExportFilter {
+            project_id: Some("work".to_string()),
+            ..ExportFilter::default()
+        };
+        assert_eq!(ids(&work.tasks(sample())), vec!["open-work", "done-work"]);
+
+        let tagged = ExportFilter {
+            tag: Some("#CLIENT".to_string()),
+            ..ExportFilter::default()
+        };
+        assert_eq!(ids(&tagged.tasks(sample())), vec!["open-work", "done-home"]);
+
+        let open = ExportFilter {
+            status: ExportStatus::Open,
+            ..ExportFilter::default()
+        };
+        assert_eq!(ids(&open.tasks(sample())), vec!["open-work", "undated"]);
+
+        // Completed tasks are ranged by completion time, not by their old due date.
+        let last_month_done = ExportFilter {
+            status: ExportStatus::Completed,
+            from: Some(100),
+            to: Some(300),
+            ..ExportFilter::default()
+        };
+        assert_eq!(ids(&last_month_done.tasks(sample())), vec!["done-work"]);
+
+        let ranged = ExportFilter {
+            from: Some(100),
+            ..ExportFilter::default()
+        };
+        assert_eq!(
+            ids(&ranged.tasks(sample())),
+            vec!["open-work", "done-work", "done-home"]
+        );
+    }
+
+    #[test]
+    fn data_keeps_referenced_projects_and_drops_trash() {
+        let project = |id: &str| Project {
+            id: id.to_string(),
+            name: id.to_string(),
+            pinned: false,
+            sort_order: 0,
+            created_at: 0,
+            updated_at: 0,
+            sample_tag: None,
+            reminders_muted: false,
+            muted_until: None,
+            notification_sound: None,
+            color: None,
+            icon: None,
+            parent_id: None,
+        };
+        let data = TasksFile {
+            schema_version: 1,
+            tasks: sample(),
+            projects: vec![project("inbox"), project("work"), project("home")],
+            trash: vec![crate::models::TrashEntry {
+                task: task("gone", "work", &[], None, None),
+                deleted_at: 1,
+            }],
+            tags: Vec::new(),
+        };
+        assert_eq!(ExportFilter::default().data(data.clone()).trash.len(), 1);
+
+        let filter = ExportFilter {
+            tag: Some("client".to_string()),
+            ..ExportFilter::default()
+        };
+        let filtered = filter.data(data);
+        assert_eq!(filtered.tasks.len(), 2);
+        let projects: Vec<_> = filtered.projects.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(projects, vec!["work", "home"]);
+        assert!(filtered.trash.is_empty());
+    }
+}
@@ -0,0 +1,191 @@
+use crate::models::{Project, Task};
+
+/// Long enough for ZWJ sequences such as family emoji; short enough to rule out sentences.
+const EMOJI_MAX_CHARS: usize = 8;
+
+/// Built-in project icons the UI draws itself (see `Icons` in the frontend); anything else must
+/// be an emoji.
+pub const PROJECT_ICONS: [&str; 12] = [
+    "star", "calendar", "bell", "list", "grid", "pin", "clock", "settings", "check", "repeat",
+    "search", "filter",
+];
+
+/// Accepts `#rgb` or `#rrggbb` (the `#` is optional) and returns the lowercase `#rrggbb` form;
+/// blank clears the color.
+pub fn normalize_color(raw: &str) -> Result<Option<String>, String> {
+    let value = raw.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("invalid color: {value}"));
+    }
+    let hex = hex.to_ascii_lowercase();
+    match hex.len() {
+        3 => Ok(Some(format!(
+            "#{}",
+            hex.chars().flat_map(|c| [c, c]).collect::<String>()
+        ))),
+        6 => Ok(Some(format!("#{hex}"))),
+        _ => Err(format!("invalid color: {value}")),
+    }
+}
+
+/// One emoji (possibly a multi-codepoint sequence); blank clears it. Plain text is rejected so
+/// the field cannot turn into a second title.
+pub fn normalize_emoji(raw: &str) -> Result<Option<String>, String> {
+    let value = raw.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    let chars = value.chars().count();
+    if chars > EMOJI_MAX_CHARS {
+        return Err(format!(
+            "emoji too long: {chars} > {EMOJI_MAX_CHARS} characters"
+        ));
+    }
+    if value
+        .chars()
+        .any(|c| c.is_whitespace() || c.is_control() || c.is_alphabetic())
+        || value.is_ascii()
+    {
+        return Err(format!("invalid emoji: {value}"));
+    }
+    Ok(Some(value.to_string()))
+}
+
+/// Validates and normalizes `color` and `emoji` in place; the task is left untouched on error.
+pub fn normalize_flair(task: &mut Task) -> Result<(), String> {
+    let color = match task.color.as_deref() {
+        Some(raw) => normalize_color(raw)?,
+        None => None,
+    };
+    let emoji = match task.emoji.as_deref() {
+        Some(raw) => normalize_emoji(raw)?,
+        None => None,
+    };
+    task.color = color;
+    task.emoji = emoji;
+    Ok(())
+}
+
+/// A project icon: one of `PROJECT_ICONS` (case-insensitive) or an emoji; blank clears it.
+pub fn normalize_icon(raw: &str) -> Result<Option<String>, String> {
+    let value = raw.trim();
+    let id = value.to_ascii_lowercase();
+    if PROJECT_ICONS.contains(&id.as_str()) {
+        return Ok(Some(id));
+    }
+    normalize_emoji(value).map_err(|_| format!("invalid icon: {value}"))
+}
+
+/// Validates and normalizes a project's `color` and `icon` in place; untouched on error.
+pub fn normalize_project_flair(project: &mut Project) -> Result<(), String> {
+    let color = match project.color.as_deref() {
+        Some(raw) => normalize_color(raw)?,
+        None => None,
+    };
+    let icon = match project.icon.as_deref() {
+        Some(raw) => normalize_icon(raw)?,
+        None => None,
+    };
+    project.color = color;
+    project.icon = icon;
+    Ok(())
+}
+
+/// Project name for plain-text surfaces (tray menu): an emoji icon goes in front, built-in icon
+/// ids only render in the UI.
+pub fn project_label(project: &Project) -> String {
+    match project.icon.as_deref() {
+        Some(icon) if !PROJECT_ICONS.contains(&icon) => format!("{icon} {}", project.name),
+        _ => project.name.clone(),
+    }
+}
+
+/// `title` with the emoji in front, for plain-text surfaces (exports, menus).
+pub fn flair_title(task: &Task) -> String {
+    match &task.emoji {
+        Some(emoji) => format!("{emoji} {}", task.title),
+        None => task.title.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::task;
+
+    #[test]
+    fn colors_are_validated_and_normalized() {
+        assert_eq!(
+            normalize_color("#FF8800").unwrap().as_deref(),
+            Some("#ff8800")
+        );
+        assert_eq!(
+            normalize_color(" f80 ").unwrap().as_deref(),
+            Some("#ff8800")
+        );
+        assert_eq!(normalize_color("  ").unwrap(), None);
+        for bad in ["#ff88", "#ggg", "red", "#ff8800aa", "##fff"] {
+            assert!(normalize_color(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn emoji_accepts_sequences_and_rejects_text() {
+        for ok in ["🚀", " 🔥 ", "👍🏽", "🇨🇳", "👨‍👩‍👧‍👦", "❤️"]
+        {
+            assert_eq!(normalize_emoji(ok).unwrap().as_deref(), Some(ok.trim()));
+        }
+        assert_eq!(normalize_emoji("").unwrap(), None);
+        for bad in ["a", ":)", "🚀 go", "中", "🚀🚀🚀🚀🚀🚀🚀🚀🚀"] {
+            assert!(normalize_emoji(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn normalize_flair_is_all_or_nothing() {
+        let mut task = task("a").title("Ship");
+        task.color = Some("#ABC".to_string());
+        task.emoji = Some("🚀".to_string());
+        normalize_flair(&mut task).unwrap();
+        assert_eq!(task.color.as_deref(), Some("#aabbcc"));
+        assert_eq!(flair_title(&task), "🚀 Ship");
+
+        task.color = Some("#123".to_string());
+        task.emoji = Some("nope".to_string());
+        assert!(normalize_flair(&mut task).is_err());
+        assert_eq!(task.color.as_deref(), Some("#123"));
+
+        task.emoji = None;
+        assert_eq!(flair_title(&task), "Ship");
+    }
+
+    #[test]
+    fn project_icons_are_built_in_ids_or_emoji() {
+        assert_eq!(normalize_icon(" Star ").unwrap().as_deref(), Some("star"));
+        assert_eq!(normalize_icon("📚").unwrap().as_deref(), Some("📚"));
+        assert_eq!(normalize_icon("").unwrap(), None);
+        for bad in ["rocket", "a", "📚 books"] {
+            assert!(
+                normalize_icon(bad).unwrap_err().contains("invalid icon"),
+                "{bad}"
+            );
+        }
+
+        let mut project: Project =
+            serde_json::from_str(r#"{"id":"w","name":"Work","color":"0af","icon":"📚"}"#).unwrap();
+        normalize_project_flair(&mut project).unwrap();
+        assert_eq!(project.color.as_deref(), Some("#00aaff"));
+        assert_eq!(project_label(&project), "📚 Work");
+
+        project.icon = Some("pin".to_string());
+        assert_eq!(project_label(&project), "Work");
+        project.icon = Some("book".to_string());
+        project.color = Some("#fff".to_string());
+        assert!(normalize_project_flair(&mut project).is_err());
+        assert_eq!(project.color.as_deref(), Some("#fff"));
+    }
+}
@@ -0,0 +1,292 @@
+use std::path::Path;
+
+use crate::models::Timestamp;
+use crate::storage::{append_jsonl, compact_jsonl, read_jsonl, StorageError};
+
+pub const DEFAULT_FOCUS_MINUTES: u32 = 25;
+pub const MAX_FOCUS_MINUTES: u32 = 240;
+const FOCUS_LOG_FILE: &str = "focus_sessions.jsonl";
+const FOCUS_LOG_LIMIT: usize = 2000;
+
+/// The session being timed. Runtime-only: quitting the app drops it without a record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ActiveSession {
+    task_id: String,
+    minutes: u32,
+    started_at: Timestamp,
+    /// Seconds focused before the current run, summed over earlier pauses.
+    focused_before: i64,
+    /// Start of the current run; `None` while paused.
+    resumed_at: Option<Timestamp>,
+}
+
+impl ActiveSession {
+    fn focused_seconds(&self, now: Timestamp) -> i64 {
+        let running = self
+            .resumed_at
+            .map(|at| now.saturating_sub(at).max(0))
+            .unwrap_or(0);
+        self.focused_before
+            .saturating_add(running)
+            .min(self.planned_seconds())
+    }
+
+    fn planned_seconds(&self) -> i64 {
+        i64::from(self.minutes) * 60
+    }
+
+    fn status(&self, now: Timestamp) -> FocusStatus {
+        let focused_seconds = self.focused_seconds(now);
+        FocusStatus {
+            task_id: self.task_id.clone(),
+            minutes: self.minutes,
+            started_at: self.started_at,
+            focused_seconds,
+            remaining_seconds: self.planned_seconds() - focused_seconds,
+            paused: self.resumed_at.is_none(),
+        }
+    }
+
+    fn record(&self, now: Timestamp) -> FocusSession {
+        let focused_seconds = self.focused_seconds(now);
+        FocusSession {
+            task_id: self.task_id.clone(),
+            started_at: self.started_at,
+            ended_at: now,
+            minutes: self.minutes,
+            focused_seconds,
+            finished: focused_seconds >= self.planned_seconds(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct FocusStatus {
+    pub task_id: String,
+    pub minutes: u32,
+    pub started_at: Timestamp,
+    pub focused_seconds: i64,
+    pub remaining_seconds: i64,
+    pub paused: bool,
+}
+
+/// One ended session, as kept in `focus_sessions.jsonl`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FocusSession {
+    pub task_id: String,
+    pub started_at: Timestamp,
+    pub ended_at: Timestamp,
+    pub minutes: u32,
+    /// Time actually focused; pauses are not counted.
+    pub focused_seconds: i64,
+    /// Ran for the full `minutes` rather than being stopped early.
+    pub finished: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FocusTick {
+    Running(FocusStatus),
+    Finished(FocusSession),
+}
+
+/// At most one focus session at a time, across all tasks.
+#[derive(Debug, Default)]
+pub struct FocusTimer {
+    active: Option<ActiveSession>,
+}
+
+impl FocusTimer {
+    pub fn status(&self, now: Timestamp) -> Option<FocusStatus> {
+        self.active.as_ref().map(|session| session.status(now))
+    }
+
+    pub fn start(
+        &mut self,
+        task_id: &str,
+        minutes: u32,
+        now: Timestamp,
+    ) -> Result<FocusStatus, String> {
+        if let Some(active) = &self.active {
+            return Err(format!(
+                "a focus session is already running for task {}",
+                active.task_id
+            ));
+        }
+        let session = ActiveSession {
+            task_id: task_id.to_string(),
+            minutes,
+            started_at: now,
+            focused_before: 0,
+            resumed_at: Some(now),
+        };
+        let status = session.status(now);
+        self.active = Some(session);
+        Ok(status)
+    }
+
+    pub fn pause(&mut self, now: Timestamp) -> Result<FocusStatus, String> {
+        let session = self.active.as_mut().ok_or("no focus session running")?;
+        if session.resumed_at.is_some() {
+            session.focused_before = session.focused_seconds(now);
+            session.resumed_at = None;
+        }
+        Ok(session.status(now))
+    }
+
+    pub fn resume(&mut self, now: Timestamp) -> Result<FocusStatus, String> {
+        let session = self.active.as_mut().ok_or("no focus session running")?;
+        if session.resumed_at.is_none() {
+            session.resumed_at = Some(now);
+        }
+        Ok(session.status(now))
+    }
+
+    /// Ends the session early; `None` when nothing was running.
+    pub fn stop(&mut self, now: Timestamp) -> Option<FocusSession> {
+        self.active.take().map(|session| session.record(now))
+    }
+
+    /// Called every scheduler tick: the status of a running session, or the finished record
+    /// once its time is up. Nothing while idle or paused.
+    pub fn tick(&mut self, now: Timestamp) -> Option<FocusTick> {
+        let session = self.active.as_ref()?;
+        session.resumed_at?;
+        let status = session.status(now);
+        if status.remaining_seconds > 0 {
+            return Some(FocusTick::Running(status));
+        }
+        let record = session.record(now);
+        self.active = None;
+        Some(FocusTick::Finished(record))
+    }
+}
+
+/// Session length for `start_focus_session`: 25 minutes unless given, 1..=240.
+pub fn focus_minutes(requested: Option<u32>) -> Result<u32, String> {
+    match requested.unwrap_or(DEFAULT_FOCUS_MINUTES) {
+        minutes @ 1..=MAX_FOCUS_MINUTES => Ok(minutes),
+        minutes => Err(format!(
+            "invalid focus length: {minutes} (expected 1-{MAX_FOCUS_MINUTES} minutes)"
+        )),
+    }
+}
+
+/// Appends an ended session to `focus_sessions.jsonl`.
+pub fn append_focus_log(root: &Path, session: &FocusSession) -> Result<(), StorageError> {
+    append_jsonl(&root.join(FOCUS_LOG_FILE), std::slice::from_ref(session))
+}
+
+/// Keeps the newest `FOCUS_LOG_LIMIT` sessions; returns how many lines were dropped.
+pub fn compact_focus_log(root: &Path) -> Result<usize, StorageError> {
+    compact_jsonl::<FocusSession>(&root.join(FOCUS_LOG_FILE), FOCUS_LOG_LIMIT)
+}
+
+/// Newest first, optionally for one task only; unparsable lines are skipped.
+pub fn read_focus_log(
+    root: &Path,
+    task_id: Option<&str>,
+    limit: usize,
+) -> Result<Vec<FocusSession>, StorageError> {
+    let sessions: Vec<FocusSession> = read_jsonl(&root.join(FOCUS_LOG_FILE))?;
+    Ok(sessions
+        .into_iter()
+        .rev()
+        .filter(|session| task_id.is_none_or(|id| session.task_id == id))
+        .take(limit)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Write;
+
+    #[test]
+    fn timer_counts_focused_time_across_pauses_and_finishes_once() {
+        let mut timer = FocusTimer::default();
+        assert!(timer.tick(0).is_none());
+        assert!(timer.pause(0).is_err());
+
+        let status = timer.start("a", 1, 100).unwrap();
+        assert_eq!((status.remaining_seconds, status.paused), (60, false));
+        assert!(timer.start("b", 5, 101).unwrap_err().contains("task a"));
+
+        assert_eq!(
+            timer.tick(120),
+            Some(FocusTick::Running(timer.status(120).unwrap()))
+        );
+        let paused = timer.pause(130).unwrap();
+        assert_eq!((paused.focused_seconds, paused.paused), (30, true));
+        // Paused sessions neither tick nor finish.
+        assert!(timer.tick(1000).is_none());
+        assert_eq!(timer.pause(1000).unwrap().focused_seconds, 30);
+
+        assert_eq!(timer.resume(1000).unwrap().remaining_seconds, 30);
+        assert!(matches!(timer.tick(1029), Some(FocusTick::Running(_))));
+        let Some(FocusTick::Finished(record)) = timer.tick(1035) else {
+            panic!("expected the session to finish");
+        };
+        assert_eq!(
+            record,
+            FocusSession {
+                task_id: "a".to_string(),
+                started_at: 100,
+                ended_at: 1035,
+                minutes: 1,
+                focused_seconds: 60,
+                finished: true,
+            }
+        );
+        assert!(timer.status(1036).is_none());
+    }
+
+    #[test]
+    fn stop_records_an_unfinished_session() {
+        let mut timer = FocusTimer::default();
+        assert!(timer.stop(0).is_none());
+        timer.start("a", 25, 0).unwrap();
+        let record = timer.stop(600).unwrap();
+        assert_eq!((record.focused_seconds, record.finished), (600, false));
+        assert!(timer.status(601).is_none());
+
+        assert_eq!(focus_minutes(None), Ok(DEFAULT_FOCUS_MINUTES));
+        assert_eq!(focus_minutes(Some(50)), Ok(50));
+        assert!(focus_minutes(Some(0)).is_err());
+        assert!(focus_minutes(Some(MAX_FOCUS_MINUTES + 1)).is_err());
+    }
+
+    #[test]
+    fn focus_log_filters_by_task_newest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(read_focus_log(dir.path(), None, 10).unwrap().is_empty());
+        for (task_id, at) in [("a", 1), ("b", 2), ("a", 3)] {
+            let session = FocusSession {
+                task_id: task_id.to_string(),
+                started_at: at,
+                ended_at: at + 1,
+                minutes: 25,
+                focused_seconds: 1,
+                finished: false,
+            };
+            append_focus_log(dir.path(), &session).unwrap();
+        }
+        fs::OpenOptions::new()
+            .append(true)
+            .open(dir.path().join(FOCUS_LOG_FILE))
+            .unwrap()
+            .write_all(b"not json\n")
+            .unwrap();
+
+        let all = read_focus_log(dir.path(), None, 10).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(compact_focus_log(dir.path()).unwrap(), 1);
+        let a: Vec<_> = read_focus_log(dir.path(), Some("a"), 10)
+            .unwrap()
+            .into_iter()
+            .map(|s| s.started_at)
+            .collect();
+        assert_eq!(a, vec![3, 1]);
+        assert_eq!(read_focus_log(dir.path(), Some("a"), 1).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,125 @@
+use crate::calendar::DateLocale;
+use crate::models::{Project, Settings};
+
+/// The inbox keeps this stored name until the user renames it; it is shown localized.
+const INBOX_DEFAULT_NAME: &str = "Inbox";
+
+/// Fixed strings the backend writes for people: export headings and labels, the tray menu.
+/// Emails build their sentences in `email.rs` with the same locale.
+// The tray menu entries are only built in the app, not in `--no-default-features` builds.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Text {
+    ExportTitle,
+    GeneratedAt,
+    Overdue,
+    DueToday,
+    Future,
+    Someday,
+    Completed,
+    Empty,
+    Due,
+    Quadrant,
+    Starts,
+    Color,
+    Tags,
+    Notes,
+    Steps,
+    Comments,
+    Inbox,
+    TrayShowQuick,
+    TrayShowMain,
+    TraySettings,
+    TrayDoNotDisturb,
+    TrayProjects,
+    TrayQuit,
+    TrayPending,
+}
+
+/// `settings.language`, or the system locale when it is "auto".
+pub fn locale_for(settings: &Settings) -> DateLocale {
+    DateLocale::resolve(
+        &settings.language,
+        &sys_locale::get_locale().unwrap_or_default(),
+    )
+}
+
+pub fn tr(locale: DateLocale, text: Text) -> &'static str {
+    let (zh, en) = match text {
+        Text::ExportTitle => ("MustDo 导出", "MustDo Export"),
+        Text::GeneratedAt => ("生成时间", "Generated at"),
+        Text::Overdue => ("已逾期", "Overdue"),
+        Text::DueToday => ("今天到期", "Due today"),
+        Text::Future => ("以后", "Future"),
+        Text::Someday => ("将来某天", "Someday"),
+        Text::Completed => ("已完成", "Completed"),
+        Text::Empty => ("无", "Empty"),
+        Text::Due => ("截止", "due"),
+        Text::Quadrant => ("象限", "quadrant"),
+        Text::Starts => ("开始", "starts"),
+        Text::Color => ("颜色", "color"),
+        Text::Tags => ("标签", "tags"),
+        Text::Notes => ("备注", "notes"),
+        Text::Steps => ("步骤", "steps"),
+        Text::Comments => ("评论", "comments"),
+        Text::Inbox => ("收件箱", "Inbox"),
+        Text::TrayShowQuick => ("打开快捷窗口", "Open quick window"),
+        Text::TrayShowMain => ("打开主界面", "Open main window"),
+        Text::TraySettings => ("设置", "Settings"),
+        Text::TrayDoNotDisturb => ("勿扰模式", "Do not disturb"),
+        Text::TrayProjects => ("项目", "Projects"),
+        Text::TrayQuit => ("退出", "Quit"),
+        Text::TrayPending => ("待办", "Pending"),
+    };
+    match locale {
+        DateLocale::Zh => zh,
+        DateLocale::En => en,
+    }
+}
+
+/// `projects` as shown in exports: the inbox under its localized name unless the user renamed
+/// it.
+pub fn localized_projects(projects: &[Project], locale: DateLocale) -> Vec<Project> {
+    projects
+        .iter()
+        .map(|project| {
+            let mut project = project.clone();
+            if project.id == "inbox" && project.name == INBOX_DEFAULT_NAME {
+                project.name = tr(locale, Text::Inbox).to_string();
+            }
+            project
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strings_and_inbox_name_follow_the_locale() {
+        assert_eq!(tr(DateLocale::Zh, Text::Overdue), "已逾期");
+        assert_eq!(tr(DateLocale::En, Text::DueToday), "Due today");
+
+        let projects: Vec<Project> = serde_json::from_value(serde_json::json!([
+            { "id": "inbox", "name": "Inbox" },
+            { "id": "work", "name": "Inbox" }
+        ]))
+        .unwrap();
+        let names = |locale| {
+            localized_projects(&projects, locale)
+                .into_iter()
+                .map(|project| project.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(DateLocale::Zh), vec!["收件箱", "Inbox"]);
+        assert_eq!(names(DateLocale::En), vec!["Inbox", "Inbox"]);
+
+        let mut renamed = projects.clone();
+        renamed[0].name = "Eingang".to_string();
+        assert_eq!(
+            localized_projects(&renamed, DateLocale::Zh)[0].name,
+            "Eingang"
+        );
+    }
+}
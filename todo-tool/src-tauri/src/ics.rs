@@ -0,0 +1,336 @@
+//! RFC 5545 writing shared by CalDAV sync (one VTODO per resource) and the `.ics` export
+//! (`export_tasks_ics`: every task in one calendar, with recurrence and alarms), plus the
+//! date-time reading both sync and `RRULE` import need.
+
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+use crate::models::{ReminderKind, ReminderLeadTimes, Task, Timestamp};
+use crate::repeat::task_timezone;
+use crate::rrule::task_rrule;
+
+const EXPORT_PRODID: &str = "-//MustDo//Task export//EN";
+
+pub fn escape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Content lines are folded at 75 octets without splitting a UTF-8 sequence (RFC 5545 3.1).
+pub fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+pub fn format_utc(ts: Timestamp) -> String {
+    Utc.timestamp_opt(ts, 0)
+        .single()
+        .unwrap_or_default()
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+/// Floating local time: calendars expand `RRULE` days in the time zone of `DTSTART`, so a
+/// repeating task keeps its local weekday/month day wherever the reader is.
+pub fn format_local(ts: Timestamp) -> String {
+    Local
+        .timestamp_opt(ts, 0)
+        .single()
+        .unwrap_or_default()
+        .format("%Y%m%dT%H%M%S")
+        .to_string()
+}
+
+/// Wall-clock time of `ts` in `tz`, for a `;TZID=` property.
+fn format_zoned(ts: Timestamp, tz: chrono_tz::Tz) -> String {
+    tz.timestamp_opt(ts, 0)
+        .single()
+        .map(|at| at.format("%Y%m%dT%H%M%S").to_string())
+        .unwrap_or_default()
+}
+
+/// Value of the property parameter `name`, unquoted.
+fn param<'a>(params: &'a str, name: &str) -> Option<&'a str> {
+    params.split(';').find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| value.trim().trim_matches('"'))
+    })
+}
+
+/// `20260318T090000Z` is UTC, a `TZID` naming an IANA zone is read in that zone, other floating
+/// or `TZID` times as local time, and a bare date as the end of that local day.
+pub fn parse_time(params: &str, value: &str) -> Option<Timestamp> {
+    let value = value.trim();
+    if param(params, "VALUE").is_some_and(|kind| kind.eq_ignore_ascii_case("DATE"))
+        || value.len() == 8
+    {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        let end_of_day = date.and_hms_opt(23, 59, 0)?;
+        return Local
+            .from_local_datetime(&end_of_day)
+            .earliest()
+            .map(|dt| dt.timestamp());
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(naive.and_utc().timestamp());
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    // Custom `VTIMEZONE` ids are not looked up.
+    if let Some(tz) = param(params, "TZID").and_then(|name| name.parse::<chrono_tz::Tz>().ok()) {
+        return tz
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|dt| dt.timestamp());
+    }
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| dt.timestamp())
+}
+
+/// The VTODO properties of `task` (without `BEGIN`/`END`). A dated repeating task also gets its
+/// `RRULE`, end condition included. A task with a `timezone` writes `DUE` (and a matching
+/// `DTSTART`) with that `TZID`, a repeating one without it as floating local time, any other
+/// in UTC. A recurrence needs a start to expand from, so undated tasks go out without one.
+pub fn push_todo_properties(out: &mut String, task: &Task, now: Timestamp) {
+    let zone = task_timezone(task);
+    let rrule = task.due_at.and_then(|_| task_rrule(task, zone.is_none()));
+    push_line(out, &format!("UID:{}", task.id));
+    push_line(out, &format!("DTSTAMP:{}", format_utc(now)));
+    push_line(out, &format!("CREATED:{}", format_utc(task.created_at)));
+    push_line(
+        out,
+        &format!("LAST-MODIFIED:{}", format_utc(task.updated_at)),
+    );
+    push_line(out, &format!("SUMMARY:{}", escape_text(&task.title)));
+    if let Some(notes) = task.notes.as_deref() {
+        push_line(out, &format!("DESCRIPTION:{}", escape_text(notes)));
+    }
+    if let Some(due) = task.due_at {
+        match zone {
+            Some(tz) => {
+                let at = format_zoned(due, tz);
+                push_line(out, &format!("DTSTART;TZID={}:{at}", tz.name()));
+                push_line(out, &format!("DUE;TZID={}:{at}", tz.name()));
+            }
+            None if rrule.is_some() => {
+                push_line(out, &format!("DTSTART:{}", format_local(due)));
+                push_line(out, &format!("DUE:{}", format_local(due)));
+            }
+            None => push_line(out, &format!("DUE:{}", format_utc(due))),
+        }
+    }
+    if task.important {
+        push_line(out, "PRIORITY:1");
+    }
+    if !task.tags.is_empty() {
+        let tags: Vec<String> = task.tags.iter().map(|tag| escape_text(tag)).collect();
+        push_line(out, &format!("CATEGORIES:{}", tags.join(",")));
+    }
+    if task.completed {
+        push_line(out, "STATUS:COMPLETED");
+        if let Some(at) = task.completed_at {
+            push_line(out, &format!("COMPLETED:{}", format_utc(at)));
+        }
+    } else {
+        push_line(out, "STATUS:NEEDS-ACTION");
+    }
+    if let Some(rrule) = rrule {
+        push_line(out, &format!("RRULE:{rrule}"));
+    }
+}
+
+fn relative_trigger(minutes: u32) -> String {
+    if minutes == 0 {
+        "TRIGGER;RELATED=END:PT0S".to_string()
+    } else {
+        format!("TRIGGER;RELATED=END:-PT{minutes}M")
+    }
+}
+
+/// `TRIGGER` values for the task's reminders: an explicit `remind_at` is absolute, specs and the
+/// default lead time are relative to `DUE` so they follow every occurrence. Empty without one.
+fn alarm_triggers(task: &Task, lead_times: &ReminderLeadTimes) -> Vec<String> {
+    let reminder = &task.reminder;
+    if task.completed || reminder.kind == ReminderKind::None {
+        return Vec::new();
+    }
+    let mut triggers = Vec::new();
+    if task.due_at.is_some() {
+        triggers.extend(
+            reminder
+                .specs
+                .iter()
+                .map(|spec| relative_trigger(spec.minutes_before)),
+        );
+    }
+    if let Some(at) = reminder.remind_at {
+        triggers.push(format!("TRIGGER;VALUE=DATE-TIME:{}", format_utc(at)));
+    }
+    if triggers.is_empty() && task.due_at.is_some() {
+        triggers.push(relative_trigger(lead_times.minutes(&reminder.kind)));
+    }
+    triggers
+}
+
+/// One VCALENDAR with a VTODO per task; repeating tasks carry their `RRULE` and reminders
+/// become a display `VALARM`.
+pub fn tasks_to_calendar(tasks: &[Task], lead_times: &ReminderLeadTimes, now: Timestamp) -> String {
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, &format!("PRODID:{EXPORT_PRODID}"));
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    for task in tasks {
+        push_line(&mut out, "BEGIN:VTODO");
+        push_todo_properties(&mut out, task, now);
+        for trigger in alarm_triggers(task, lead_times) {
+            push_line(&mut out, "BEGIN:VALARM");
+            push_line(&mut out, "ACTION:DISPLAY");
+            push_line(
+                &mut out,
+                &format!("DESCRIPTION:{}", escape_text(&task.title)),
+            );
+            push_line(&mut out, &trigger);
+            push_line(&mut out, "END:VALARM");
+        }
+        push_line(&mut out, "END:VTODO");
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_support, ReminderConfig, ReminderSpec, RepeatRule};
+
+    fn task(id: &str, due_at: Option<Timestamp>) -> Task {
+        test_support::task(id)
+            .title(&format!("Task {id}"))
+            .due(due_at)
+            .updated(2)
+    }
+
+    fn unfolded(ics: &str) -> String {
+        ics.replace("\r\n ", "")
+    }
+
+    #[test]
+    fn push_line_folds_long_lines_without_splitting_characters() {
+        let mut out = String::new();
+        push_line(&mut out, &format!("SUMMARY:{}", "é".repeat(60)));
+        for line in out.split("\r\n").filter(|line| !line.is_empty()) {
+            assert!(line.len() <= 75);
+        }
+        assert_eq!(unfolded(&out), format!("SUMMARY:{}\r\n", "é".repeat(60)));
+        assert_eq!(escape_text("a,b;c\\d\r\ne"), "a\\,b\\;c\\\\d\\ne");
+    }
+
+    #[test]
+    fn tasks_with_a_timezone_are_written_and_read_in_that_zone() {
+        let tz: chrono_tz::Tz = "Asia/Tokyo".parse().unwrap();
+        let due = tz
+            .with_ymd_and_hms(2026, 3, 16, 9, 30, 0)
+            .unwrap()
+            .timestamp();
+        let mut zoned = task("zoned", Some(due));
+        zoned.timezone = Some("Asia/Tokyo".to_string());
+        zoned.repeat = RepeatRule::Weekly { days: vec![1] };
+        zoned.end_by_date = Some(due + 7 * 86_400);
+
+        let mut out = String::new();
+        push_todo_properties(&mut out, &zoned, 100);
+        assert!(out.contains(
+            "DTSTART;TZID=Asia/Tokyo:20260316T093000\r\nDUE;TZID=Asia/Tokyo:20260316T093000\r\n"
+        ));
+        // UNTIL goes out in UTC next to a zoned DTSTART.
+        assert!(out.contains("RRULE:FREQ=WEEKLY;BYDAY=MO;UNTIL=20260323T003000Z\r\n"));
+        zoned.repeat = RepeatRule::None;
+        let mut out = String::new();
+        push_todo_properties(&mut out, &zoned, 100);
+        assert!(out.contains("DUE;TZID=Asia/Tokyo:20260316T093000\r\n"));
+
+        assert_eq!(parse_time("TZID=Asia/Tokyo", "20260316T093000"), Some(due));
+        assert_eq!(
+            parse_time("VALUE=DATE-TIME;TZID=\"Asia/Tokyo\"", "20260316T093000"),
+            Some(due)
+        );
+    }
+
+    #[test]
+    fn calendar_holds_one_vtodo_per_task_with_rrule_and_alarms() {
+        let due = Local
+            .with_ymd_and_hms(2026, 3, 16, 9, 30, 0)
+            .unwrap()
+            .timestamp();
+        let mut weekly = task("weekly", Some(due));
+        weekly.repeat = RepeatRule::Weekly { days: vec![1, 3] };
+        weekly.end_after_occurrences = Some(10);
+        weekly.occurrence_index = 4;
+        weekly.reminder = ReminderConfig {
+            kind: ReminderKind::Normal,
+            ..ReminderConfig::default()
+        };
+        let mut explicit = task("explicit", Some(due));
+        explicit.reminder = ReminderConfig {
+            kind: ReminderKind::Forced,
+            remind_at: Some(0),
+            specs: vec![
+                ReminderSpec {
+                    minutes_before: 1_440,
+                },
+                ReminderSpec { minutes_before: 0 },
+            ],
+            ..ReminderConfig::default()
+        };
+        let mut done = task("done", Some(due));
+        done.completed = true;
+        done.completed_at = Some(due);
+        done.reminder.kind = ReminderKind::Normal;
+        let mut undated = task("undated", None);
+        undated.repeat = RepeatRule::Daily {
+            workday_only: false,
+        };
+
+        let ics = unfolded(&tasks_to_calendar(
+            &[weekly, explicit, done, undated],
+            &ReminderLeadTimes::default(),
+            100,
+        ));
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VTODO").count(), 4);
+        assert_eq!(ics.matches("BEGIN:VALARM").count(), 4);
+        assert!(ics.contains(
+            "DTSTART:20260316T093000\r\nDUE:20260316T093000\r\nSTATUS:NEEDS-ACTION\r\n\
+             RRULE:FREQ=WEEKLY;BYDAY=MO,WE;COUNT=6\r\n"
+        ));
+        assert!(ics.contains("TRIGGER;RELATED=END:-PT10M"));
+        assert!(ics.contains("TRIGGER;VALUE=DATE-TIME:19700101T000000Z"));
+        assert!(ics.contains("TRIGGER;RELATED=END:-PT1440M"));
+        assert!(ics.contains("TRIGGER;RELATED=END:PT0S"));
+        assert!(ics.contains(&format!("DUE:{}", format_utc(due))));
+        assert_eq!(ics.matches("RRULE:").count(), 1);
+    }
+}
@@ -0,0 +1,1036 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, NaiveTime, TimeZone};
+use serde_json::Value;
+
+use crate::comments::{new_comment, normalize_comment_text};
+use crate::dry_run::ChangeSet;
+use crate::infer::{infer_task, normalize_tag};
+use crate::models::{Project, ReminderConfig, RepeatRule, Settings, Step, Task, Timestamp};
+use crate::rrule::rrule_to_repeat_rule;
+
+/// Dates parsed from free text below this confidence are dropped with a warning.
+const MIN_DATE_CONFIDENCE: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExternalFormat {
+    /// One project per file: `TYPE,CONTENT,DESCRIPTION,PRIORITY,INDENT,...,DATE,...`.
+    TodoistCsv,
+    /// `{ "projects": [...], "tasks": [...] }` (or the `projectProfiles` / `syncTaskBean` shape).
+    TickTickJson,
+    /// `- [ ]` / `- [x]` checkbox lists, e.g. pasted meeting notes.
+    Markdown,
+}
+
+/// What the headings above a Markdown checkbox list turn into.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarkdownHeadings {
+    /// The nearest heading names the task's project (matched by name, created otherwise).
+    #[default]
+    Project,
+    /// Every enclosing heading becomes a tag; the tasks go to the inbox.
+    Tag,
+}
+
+/// Projects and tasks read from another app's export, with fresh ids, not yet in the state.
+#[derive(Debug, Clone)]
+pub struct ExternalImport {
+    pub format: ExternalFormat,
+    /// Only projects that do not exist yet; existing ones are matched by name.
+    pub projects: Vec<Project>,
+    pub tasks: Vec<Task>,
+    /// Rows or fields that were skipped or only partly kept.
+    pub warnings: Vec<String>,
+}
+
+/// What `import_external` did (or, with `dry_run`, would do).
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ExternalImportReport {
+    pub format: ExternalFormat,
+    pub dry_run: bool,
+    pub changes: ChangeSet,
+    pub warnings: Vec<String>,
+}
+
+/// Which exporter wrote `content` (`.md` files are read as checkbox lists); `None` when it is
+/// none of the formats.
+pub fn detect_format(path: &Path, content: &str) -> Option<ExternalFormat> {
+    let content = content.trim_start_matches('\u{feff}');
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase());
+    if matches!(extension.as_deref(), Some("md" | "markdown")) {
+        return Some(ExternalFormat::Markdown);
+    }
+    if extension.as_deref() != Some("json") {
+        let header = content
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_ascii_uppercase();
+        let columns: Vec<&str> = header.split(',').map(str::trim).collect();
+        if ["TYPE", "CONTENT", "PRIORITY", "INDENT"]
+            .iter()
+            .all(|column| columns.contains(column))
+        {
+            return Some(ExternalFormat::TodoistCsv);
+        }
+    }
+    let value: Value = serde_json::from_str(content).ok()?;
+    tick_tick_tasks(&value)
+        .filter(|tasks| tasks.iter().all(|task| task.get("title").is_some()))
+        .map(|_| ExternalFormat::TickTickJson)
+}
+
+/// Parses `content` into tasks and projects to append to the current data.
+pub fn parse_external(
+    path: &Path,
+    content: &str,
+    existing: &[Project],
+    now: DateTime<Local>,
+    settings: &Settings,
+) -> Result<ExternalImport, String> {
+    let content = content.trim_start_matches('\u{feff}');
+    match detect_format(path, content) {
+        Some(ExternalFormat::TodoistCsv) => {
+            Ok(parse_todoist(path, content, existing, now, settings))
+        }
+        Some(ExternalFormat::TickTickJson) => {
+            let value: Value = serde_json::from_str(content).map_err(|err| err.to_string())?;
+            Ok(parse_tick_tick(&value, existing, now))
+        }
+        Some(ExternalFormat::Markdown) => Ok(parse_markdown(
+            content,
+            MarkdownHeadings::default(),
+            existing,
+            now,
+        )),
+        None => Err(
+            "unrecognized export: expected a Todoist CSV, TickTick JSON backup or Markdown file"
+                .to_string(),
+        ),
+    }
+}
+
+/// RFC 4180 records; quoted fields may contain commas, `""` and line breaks.
+pub fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => quoted = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows.retain(|row| row.iter().any(|field| !field.trim().is_empty()));
+    rows
+}
+
+pub fn blank_task(id: String, project_id: &str, title: &str, now: Timestamp, index: usize) -> Task {
+    Task {
+        id,
+        project_id: project_id.to_string(),
+        title: title.trim().to_string(),
+        due_at: None,
+        important: false,
+        completed: false,
+        completed_at: None,
+        created_at: now,
+        updated_at: now,
+        sort_order: now * 1000 + index as i64,
+        quadrant: 1,
+        notes: None,
+        steps: Vec::new(),
+        tags: Vec::new(),
+        sample_tag: None,
+        reminder: ReminderConfig::default(),
+        repeat: RepeatRule::None,
+        waiting_on: None,
+        waiting_since: None,
+        series_id: None,
+        pinned: false,
+        context: None,
+        comments: Vec::new(),
+        progress: Default::default(),
+        spawn_ahead: 0,
+        repeat_from_completion: false,
+        end_after_occurrences: None,
+        end_by_date: None,
+        occurrence_index: 0,
+        timezone: None,
+        escalate_after: None,
+        start_at: None,
+        someday: false,
+        carry_over_count: 0,
+        skip_carry_over: false,
+        color: None,
+        emoji: None,
+        estimate_minutes: None,
+        attachments: Vec::new(),
+        time_entries: Vec::new(),
+        blocked_by: Vec::new(),
+    }
+}
+
+fn new_step(task: &Task, title: &str, completed: bool, now: Timestamp) -> Step {
+    Step {
+        id: format!("{}-s{}", task.id, task.steps.len() + 1),
+        title: title.trim().to_string(),
+        completed,
+        created_at: now,
+        completed_at: completed.then_some(now),
+        parent_id: None,
+    }
+}
+
+/// Existing project with the same (case-insensitive) name, else a new one queued in `created`.
+fn resolve_project(
+    name: &str,
+    id: String,
+    existing: &[Project],
+    created: &mut Vec<Project>,
+    now: Timestamp,
+) -> String {
+    let name = name.trim();
+    if name.is_empty() || name.eq_ignore_ascii_case("inbox") {
+        return "inbox".to_string();
+    }
+    if let Some(project) = existing
+        .iter()
+        .chain(created.iter())
+        .find(|project| project.name.trim().to_lowercase() == name.to_lowercase())
+    {
+        return project.id.clone();
+    }
+    created.push(Project {
+        id: id.clone(),
+        name: name.to_string(),
+        pinned: false,
+        sort_order: now * 1000 + created.len() as i64,
+        created_at: now,
+        updated_at: now,
+        sample_tag: None,
+        reminders_muted: false,
+        muted_until: None,
+        notification_sound: None,
+        color: None,
+        icon: None,
+        parent_id: None,
+    });
+    id
+}
+
+/// Todoist's natural-language recurrences that have an exact `RepeatRule`.
+fn todoist_recurrence(date: &str) -> Option<RepeatRule> {
+    let lower = date.trim().to_lowercase();
+    let rest = lower
+        .strip_prefix("every! ")
+        .or_else(|| lower.strip_prefix("every "))?;
+    let rest = rest.split(" at ").next().unwrap_or(rest).trim();
+    let rule = match rest {
+        "day" | "days" => RepeatRule::Daily {
+            workday_only: false,
+        },
+        "weekday" | "workday" => RepeatRule::Daily { workday_only: true },
+        "week" => RepeatRule::Weekly { days: Vec::new() },
+        "month" => RepeatRule::Monthly { day: 0 },
+        "year" => RepeatRule::Yearly { month: 0, day: 0 },
+        _ => return None,
+    };
+    Some(rule)
+}
+
+/// Fills the placeholders of a monthly/yearly rule from the first due date (an empty weekly rule
+/// already repeats every 7 days).
+fn anchor_rule(rule: RepeatRule, due_at: Option<Timestamp>) -> RepeatRule {
+    use chrono::Datelike;
+    let Some(due) = due_at.and_then(|ts| Local.timestamp_opt(ts, 0).single()) else {
+        return rule;
+    };
+    match rule {
+        RepeatRule::Monthly { day: 0 } => RepeatRule::Monthly {
+            day: due.day() as u8,
+        },
+        RepeatRule::Yearly { month: 0, .. } => RepeatRule::Yearly {
+            month: due.month() as u8,
+            day: due.day() as u8,
+        },
+        rule => rule,
+    }
+}
+
+fn parse_todoist(
+    path: &Path,
+    content: &str,
+    existing: &[Project],
+    now: DateTime<Local>,
+    settings: &Settings,
+) -> ExternalImport {
+    let stamp = now.timestamp();
+    let mut import = ExternalImport {
+        format: ExternalFormat::TodoistCsv,
+        projects: Vec::new(),
+        tasks: Vec::new(),
+        warnings: Vec::new(),
+    };
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let project_id = resolve_project(
+        &name,
+        format!("todoist-{stamp}"),
+        existing,
+        &mut import.projects,
+        stamp,
+    );
+
+    let mut rows = parse_csv(content).into_iter();
+    let header: Vec<String> = rows
+        .next()
+        .unwrap_or_default()
+        .iter()
+        .map(|column| column.trim().to_ascii_uppercase())
+        .collect();
+    let column = |name: &str| header.iter().position(|column| column == name);
+    let (kind_at, content_at, description_at, priority_at, indent_at, date_at) = (
+        column("TYPE"),
+        column("CONTENT"),
+        column("DESCRIPTION"),
+        column("PRIORITY"),
+        column("INDENT"),
+        column("DATE"),
+    );
+    let mut section: Option<String> = None;
+    for (line, row) in rows.enumerate() {
+        let line = line + 2;
+        let get = |at: Option<usize>| {
+            at.and_then(|at| row.get(at))
+                .map(|value| value.trim())
+                .unwrap_or_default()
+        };
+        let text = get(content_at);
+        match get(kind_at).to_ascii_lowercase().as_str() {
+            "task" => {}
+            "section" => {
+                section = normalize_tag(text);
+                continue;
+            }
+            "note" => {
+                let Some(task) = import.tasks.last_mut() else {
+                    import
+                        .warnings
+                        .push(format!("line {line}: comment without a task skipped"));
+                    continue;
+                };
+                if let Ok(text) = normalize_comment_text(text) {
+                    let comment = new_comment(task, text, stamp);
+                    task.comments.push(comment);
+                }
+                continue;
+            }
+            _ => continue,
+        }
+        if text.is_empty() {
+            import
+                .warnings
+                .push(format!("line {line}: task without content skipped"));
+            continue;
+        }
+        let indent: u32 = get(indent_at).parse().unwrap_or(1);
+        if indent > 1 {
+            match import.tasks.last_mut() {
+                Some(parent) => {
+                    let step = new_step(parent, text, false, stamp);
+                    parent.steps.push(step);
+                }
+                None => import
+                    .warnings
+                    .push(format!("line {line}: sub-task without a parent skipped")),
+            }
+            continue;
+        }
+
+        let mut task = blank_task(
+            format!("todoist-{stamp}-{}", import.tasks.len() + 1),
+            &project_id,
+            text,
+            stamp,
+            import.tasks.len(),
+        );
+        // Todoist writes p1 (highest) as 4.
+        task.important = matches!(get(priority_at), "4" | "3");
+        let description = get(description_at);
+        if !description.is_empty() {
+            task.notes = Some(description.to_string());
+        }
+        if let Some(tag) = &section {
+            task.tags.push(tag.clone());
+        }
+        let date = get(date_at);
+        if !date.is_empty() {
+            let draft = infer_task(date, now, settings);
+            if draft.confidence.due_at >= MIN_DATE_CONFIDENCE {
+                task.due_at = draft.due_at;
+            }
+            match todoist_recurrence(date) {
+                Some(rule) => task.repeat = anchor_rule(rule, task.due_at),
+                None if date.to_lowercase().starts_with("every") => import.warnings.push(format!(
+                    "line {line}: recurrence \"{date}\" not supported; imported as a one-off task"
+                )),
+                None => {}
+            }
+            if task.due_at.is_none() && task.repeat == RepeatRule::None {
+                import
+                    .warnings
+                    .push(format!("line {line}: date \"{date}\" not understood"));
+            }
+        }
+        import.tasks.push(task);
+    }
+    import
+}
+
+fn tick_tick_tasks(value: &Value) -> Option<&Vec<Value>> {
+    value
+        .get("tasks")
+        .or_else(|| value.pointer("/syncTaskBean/update"))
+        .and_then(Value::as_array)
+}
+
+fn text_field<'a>(value: &'a Value, key: &str) -> &'a str {
+    value.get(key).and_then(Value::as_str).unwrap_or_default()
+}
+
+/// TickTick writes `2024-05-01T10:00:00.000+0000`; RFC 3339 is accepted too.
+fn tick_tick_time(value: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f%z")
+        .or_else(|_| DateTime::parse_from_rfc3339(value))
+        .ok()
+}
+
+/// All-day dates are stored as midnight in the user's zone; like CalDAV imports they become the
+/// end of that local day.
+fn tick_tick_due(task: &Value) -> Option<Timestamp> {
+    let due = tick_tick_time(text_field(task, "dueDate"))?;
+    if !task
+        .get("isAllDay")
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+    {
+        return Some(due.timestamp());
+    }
+    // No tz database here: the local zone stands in for the exporter's `timeZone`.
+    let date = due.with_timezone(&Local).date_naive();
+    let end_of_day = NaiveDateTime::new(date, NaiveTime::from_hms_opt(23, 59, 0)?);
+    Local
+        .from_local_datetime(&end_of_day)
+        .earliest()
+        .map(|dt| dt.timestamp())
+}
+
+fn parse_tick_tick(value: &Value, existing: &[Project], now: DateTime<Local>) -> ExternalImport {
+    let stamp = now.timestamp();
+    let mut import = ExternalImport {
+        format: ExternalFormat::TickTickJson,
+        projects: Vec::new(),
+        tasks: Vec::new(),
+        warnings: Vec::new(),
+    };
+    let mut project_ids: BTreeMap<String, String> = BTreeMap::new();
+    let projects = value
+        .get("projects")
+        .or_else(|| value.get("projectProfiles"))
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    for project in &projects {
+        let id = text_field(project, "id");
+        if id.is_empty() {
+            continue;
+        }
+        let local = resolve_project(
+            text_field(project, "name"),
+            format!("ticktick-{id}"),
+            existing,
+            &mut import.projects,
+            stamp,
+        );
+        project_ids.insert(id.to_string(), local);
+    }
+
+    let tasks = tick_tick_tasks(value).cloned().unwrap_or_default();
+    let mut by_source: BTreeMap<String, usize> = BTreeMap::new();
+    let mut children = Vec::new();
+    for raw in &tasks {
+        let source_id = text_field(raw, "id");
+        let title = text_field(raw, "title");
+        if title.trim().is_empty() {
+            import
+                .warnings
+                .push(format!("task {source_id}: no title, skipped"));
+            continue;
+        }
+        if !text_field(raw, "parentId").is_empty() {
+            children.push(raw);
+            continue;
+        }
+        let source_project = text_field(raw, "projectId");
+        let project_id = match project_ids.get(source_project) {
+            Some(id) => id.clone(),
+            None if source_project.starts_with("inbox") || source_project.is_empty() => {
+                "inbox".to_string()
+            }
+            None => {
+                import.warnings.push(format!(
+                    "task {source_id}: unknown project {source_project}, imported into Inbox"
+                ));
+                "inbox".to_string()
+            }
+        };
+        let id = if source_id.is_empty() {
+            format!("ticktick-{stamp}-{}", import.tasks.len() + 1)
+        } else {
+            format!("ticktick-{source_id}")
+        };
+        let mut task = blank_task(id, &project_id, title, stamp, import.tasks.len());
+        // 5 = high, 3 = medium, 1 = low, 0 = none.
+        task.important = raw.get("priority").and_then(Value::as_i64).unwrap_or(0) >= 3;
+        task.due_at = tick_tick_due(raw);
+        if let Some(created) = tick_tick_time(text_field(raw, "createdTime")) {
+            task.created_at = created.timestamp();
+        }
+        if raw.get("status").and_then(Value::as_i64) == Some(2) {
+            task.completed = true;
+            task.completed_at = Some(
+                tick_tick_time(text_field(raw, "completedTime"))
+                    .map_or(stamp, |done| done.timestamp()),
+            );
+        }
+        let notes = [text_field(raw, "content"), text_field(raw, "desc")]
+            .iter()
+            .map(|text| text.trim())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        if !notes.is_empty() {
+            task.notes = Some(notes);
+        }
+        for tag in raw
+            .get("tags")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            if let Some(tag) = tag.as_str().and_then(normalize_tag) {
+                if !task.tags.contains(&tag) {
+                    task.tags.push(tag);
+                }
+            }
+        }
+        for item in raw
+            .get("items")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let title = text_field(item, "title");
+            if !title.trim().is_empty() {
+                let completed = item.get("status").and_then(Value::as_i64).unwrap_or(0) != 0;
+                let step = new_step(&task, title, completed, stamp);
+                task.steps.push(step);
+            }
+        }
+        let repeat = text_field(raw, "repeatFlag");
+        if !repeat.is_empty() {
+            let dtstart = task
+                .due_at
+                .and_then(|ts| Local.timestamp_opt(ts, 0).single())
+                .unwrap_or(now)
+                .date_naive();
+            match rrule_to_repeat_rule(repeat, dtstart) {
+                Ok(rule) => {
+                    task.repeat = rule.rule;
+                    task.end_after_occurrences = rule.count;
+                    task.end_by_date = rule.until;
+                    for note in rule.lossy {
+                        import
+                            .warnings
+                            .push(format!("task {source_id}: repeat rule {note}"));
+                    }
+                }
+                Err(error) => import.warnings.push(format!(
+                    "task {source_id}: repeat rule not supported ({error}); imported as a one-off task"
+                )),
+            }
+        }
+        by_source.insert(source_id.to_string(), import.tasks.len());
+        import.tasks.push(task);
+    }
+
+    // Sub-tasks become steps of their parent; one level deep is all a step can hold.
+    for raw in children {
+        let parent = text_field(raw, "parentId");
+        let completed = raw.get("status").and_then(Value::as_i64) == Some(2);
+        match by_source.get(parent) {
+            Some(&index) => {
+                let task = &mut import.tasks[index];
+                let step = new_step(task, text_field(raw, "title"), completed, stamp);
+                task.steps.push(step);
+            }
+            None => import.warnings.push(format!(
+                "task {}: parent {parent} not found, skipped",
+                text_field(raw, "id")
+            )),
+        }
+    }
+    import
+}
+
+/// `# Title` (up to six `#`, closing `#`s dropped) as its level and text.
+fn markdown_heading(line: &str) -> Option<(usize, &str)> {
+    let body = line.trim_start();
+    if line.len() - body.len() > 3 {
+        return None;
+    }
+    let level = body.len() - body.trim_start_matches('#').len();
+    let rest = &body[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with([' ', '\t'])) {
+        return None;
+    }
+    Some((level, rest.trim().trim_end_matches('#').trim()))
+}
+
+/// A list item as its indent width (a tab counts as four), checkbox state (`None` for a plain
+/// bullet) and text.
+fn markdown_item(line: &str) -> Option<(usize, Option<bool>, &str)> {
+    let body = line.trim_start();
+    let indent = line[..line.len() - body.len()]
+        .chars()
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum();
+    let rest = match body.strip_prefix(['-', '*', '+']) {
+        Some(rest) => rest,
+        None => {
+            let digits = body.len() - body.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            if digits == 0 {
+                return None;
+            }
+            body[digits..].strip_prefix(['.', ')'])?
+        }
+    };
+    if !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let rest = rest.trim_start();
+    let checked = match rest.get(..3) {
+        Some("[ ]") => Some(false),
+        Some("[x]" | "[X]") => Some(true),
+        _ => None,
+    };
+    let text = if checked.is_some() { &rest[3..] } else { rest };
+    Some((indent, checked, text.trim()))
+}
+
+/// Top-level checkboxes become tasks; anything indented under one (checkbox or plain bullet)
+/// becomes its step. Other lines, including top-level plain bullets and fenced code, are
+/// skipped.
+pub fn parse_markdown(
+    text: &str,
+    headings: MarkdownHeadings,
+    existing: &[Project],
+    now: DateTime<Local>,
+) -> ExternalImport {
+    let stamp = now.timestamp();
+    let mut import = ExternalImport {
+        format: ExternalFormat::Markdown,
+        projects: Vec::new(),
+        tasks: Vec::new(),
+        warnings: Vec::new(),
+    };
+    // Headings still open at this point, outermost first.
+    let mut outline: Vec<(usize, String)> = Vec::new();
+    // Indent of the last task's checkbox while its nested items may follow.
+    let mut task_indent: Option<usize> = None;
+    let mut fenced = false;
+    for (line, raw) in text.trim_start_matches('\u{feff}').lines().enumerate() {
+        let line = line + 1;
+        if raw.trim_start().starts_with("```") || raw.trim_start().starts_with("~~~") {
+            fenced = !fenced;
+            continue;
+        }
+        if fenced {
+            continue;
+        }
+        if let Some((level, title)) = markdown_heading(raw) {
+            outline.retain(|(open, _)| *open < level);
+            outline.push((level, title.to_string()));
+            task_indent = None;
+            continue;
+        }
+        let Some((indent, checked, title)) = markdown_item(raw) else {
+            continue;
+        };
+        if let (Some(parent), Some(task)) = (task_indent, import.tasks.last_mut()) {
+            if indent > parent {
+                if !title.is_empty() {
+                    let step = new_step(task, title, checked == Some(true), stamp);
+                    task.steps.push(step);
+                }
+                continue;
+            }
+        }
+        task_indent = None;
+        let Some(completed) = checked else {
+            continue;
+        };
+        if title.is_empty() {
+            import
+                .warnings
+                .push(format!("line {line}: checkbox without text skipped"));
+            continue;
+        }
+        let project_id = match (headings, outline.last()) {
+            (MarkdownHeadings::Project, Some((_, heading))) => resolve_project(
+                heading,
+                format!("markdown-{stamp}-p{}", import.projects.len() + 1),
+                existing,
+                &mut import.projects,
+                stamp,
+            ),
+            _ => "inbox".to_string(),
+        };
+        let mut task = blank_task(
+            format!("markdown-{stamp}-{}", import.tasks.len() + 1),
+            &project_id,
+            title,
+            stamp,
+            import.tasks.len(),
+        );
+        if completed {
+            task.completed = true;
+            task.completed_at = Some(stamp);
+        }
+        if headings == MarkdownHeadings::Tag {
+            for tag in outline
+                .iter()
+                .filter_map(|(_, heading)| normalize_tag(heading))
+            {
+                if !task.tags.contains(&tag) {
+                    task.tags.push(tag);
+                }
+            }
+        }
+        task_indent = Some(indent);
+        import.tasks.push(task);
+    }
+    import
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, Timelike};
+
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap()
+    }
+
+    fn project(id: &str, name: &str) -> Project {
+        Project {
+            id: id.to_string(),
+            name: name.to_string(),
+            pinned: false,
+            sort_order: 0,
+            created_at: 0,
+            updated_at: 0,
+            sample_tag: None,
+            reminders_muted: false,
+            muted_until: None,
+            notification_sound: None,
+            color: None,
+            icon: None,
+            parent_id: None,
+        }
+    }
+
+    const TODOIST: &str = "\u{feff}TYPE,CONTENT,DESCRIPTION,PRIORITY,INDENT,AUTHOR,RESPONSIBLE,DATE,DATE_LANG,TIMEZONE\n\
+section,Errands,,,,,,,,\n\
+task,\"Buy milk, eggs\",\"2 litres\nsemi-skimmed\",4,1,Ann (1),,2024-05-03,en,Europe/Berlin\n\
+task,Check receipt,,1,2,Ann (1),,,en,\n\
+note,\"Ask for \"\"oat\"\" too\",,,,Ann (1),,,,\n\
+\n\
+task,Water plants,,1,1,Ann (1),,every day,en,\n\
+task,Stretch,,2,1,Ann (1),,every other full moon,en,\n";
+
+    #[test]
+    fn parse_csv_handles_quotes_commas_and_line_breaks() {
+        let rows = parse_csv("a,\"b,c\",\"d\"\"e\"\r\n\"multi\nline\",,x\n\n");
+        assert_eq!(
+            rows,
+            vec![
+                vec!["a".to_string(), "b,c".to_string(), "d\"e".to_string()],
+                vec!["multi\nline".to_string(), String::new(), "x".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn detect_format_recognizes_both_exports() {
+        assert_eq!(
+            detect_format(Path::new("Work.csv"), TODOIST),
+            Some(ExternalFormat::TodoistCsv)
+        );
+        assert_eq!(
+            detect_format(
+                Path::new("backup.json"),
+                r#"{"projects":[],"tasks":[{"id":"1","title":"x"}]}"#
+            ),
+            Some(ExternalFormat::TickTickJson)
+        );
+        assert_eq!(
+            detect_format(
+                Path::new("backup.json"),
+                r#"{"syncTaskBean":{"update":[{"title":"x"}]}}"#
+            ),
+            Some(ExternalFormat::TickTickJson)
+        );
+        assert_eq!(
+            detect_format(Path::new("data.json"), r#"{"tasks":[{"id":"1"}]}"#),
+            None
+        );
+        assert_eq!(detect_format(Path::new("notes.csv"), "a,b,c\n1,2,3"), None);
+        let result = parse_external(
+            Path::new("x.txt"),
+            "hello",
+            &[],
+            now(),
+            &Settings::default(),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn todoist_rows_map_to_tasks_steps_comments_and_a_project() {
+        let import = parse_external(
+            Path::new("/tmp/Home.csv"),
+            TODOIST,
+            &[],
+            now(),
+            &Settings::default(),
+        )
+        .unwrap();
+        assert_eq!(import.format, ExternalFormat::TodoistCsv);
+        assert_eq!(import.projects.len(), 1);
+        assert_eq!(import.projects[0].name, "Home");
+        let project_id = import.projects[0].id.clone();
+        assert_eq!(import.tasks.len(), 3);
+        assert!(import
+            .tasks
+            .iter()
+            .all(|task| task.project_id == project_id));
+
+        let milk = &import.tasks[0];
+        assert_eq!(milk.title, "Buy milk, eggs");
+        assert!(milk.important);
+        assert_eq!(milk.notes.as_deref(), Some("2 litres\nsemi-skimmed"));
+        assert_eq!(milk.tags, vec!["errands".to_string()]);
+        let due = Local.timestamp_opt(milk.due_at.unwrap(), 0).unwrap();
+        assert_eq!((due.month(), due.day()), (5, 3));
+        assert_eq!(milk.steps.len(), 1);
+        assert_eq!(milk.steps[0].title, "Check receipt");
+        assert_eq!(milk.comments.len(), 1);
+        assert_eq!(milk.comments[0].text, "Ask for \"oat\" too");
+
+        let plants = &import.tasks[1];
+        assert!(!plants.important);
+        assert_eq!(
+            plants.repeat,
+            RepeatRule::Daily {
+                workday_only: false
+            }
+        );
+        assert!(import.tasks[2].due_at.is_none());
+        assert_eq!(import.tasks[2].repeat, RepeatRule::None);
+        assert!(import
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("every other full moon")));
+    }
+
+    #[test]
+    fn todoist_reuses_projects_with_the_same_name() {
+        let existing = vec![project("p-home", "home")];
+        let import = parse_external(
+            Path::new("Home.csv"),
+            TODOIST,
+            &existing,
+            now(),
+            &Settings::default(),
+        )
+        .unwrap();
+        assert!(import.projects.is_empty());
+        assert!(import.tasks.iter().all(|task| task.project_id == "p-home"));
+    }
+
+    #[test]
+    fn tick_tick_backup_maps_projects_checklists_subtasks_and_status() {
+        let json = r##"{
+            "projects": [{"id": "p1", "name": "Work"}, {"id": "p2", "name": "Inbox"}],
+            "tasks": [
+                {"id": "a", "projectId": "p1", "title": "Report", "content": "Q2 numbers",
+                 "priority": 5, "status": 0, "tags": ["Finance", "#q2"],
+                 "dueDate": "2024-05-02T08:30:00.000+0000", "isAllDay": false,
+                 "items": [{"title": "Draft", "status": 1}, {"title": "Review", "status": 0}],
+                 "repeatFlag": "RRULE:FREQ=WEEKLY;BYDAY=TH;COUNT=4"},
+                {"id": "b", "projectId": "inbox123", "title": "Done thing", "priority": 1,
+                 "status": 2, "completedTime": "2024-04-30T10:00:00.000+0000"},
+                {"id": "c", "projectId": "p1", "parentId": "a", "title": "Send", "status": 2},
+                {"id": "d", "projectId": "p1", "parentId": "zzz", "title": "Orphan"},
+                {"id": "e", "projectId": "p9", "title": "Lost", "dueDate": "2024-05-05T22:00:00.000+0000", "isAllDay": true},
+                {"id": "f", "projectId": "p1", "title": "  "}
+            ]
+        }"##;
+        let import = parse_external(
+            Path::new("ticktick.json"),
+            json,
+            &[],
+            now(),
+            &Settings::default(),
+        )
+        .unwrap();
+        assert_eq!(import.format, ExternalFormat::TickTickJson);
+        assert_eq!(import.projects.len(), 1);
+        assert_eq!(import.projects[0].id, "ticktick-p1");
+        assert_eq!(import.tasks.len(), 3);
+
+        let report = &import.tasks[0];
+        assert_eq!(report.id, "ticktick-a");
+        assert_eq!(report.project_id, "ticktick-p1");
+        assert!(report.important);
+        assert_eq!(report.notes.as_deref(), Some("Q2 numbers"));
+        assert_eq!(report.tags, vec!["finance".to_string(), "q2".to_string()]);
+        assert_eq!(
+            report.due_at,
+            Some(
+                DateTime::parse_from_rfc3339("2024-05-02T08:30:00Z")
+                    .unwrap()
+                    .timestamp()
+            )
+        );
+        let steps: Vec<(&str, bool)> = report
+            .steps
+            .iter()
+            .map(|step| (step.title.as_str(), step.completed))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![("Draft", true), ("Review", false), ("Send", true)]
+        );
+        assert!(matches!(report.repeat, RepeatRule::Weekly { .. }));
+        assert_eq!(report.end_after_occurrences, Some(4));
+
+        let done = &import.tasks[1];
+        assert_eq!(done.project_id, "inbox");
+        assert!(!done.important);
+        assert!(done.completed);
+        assert_eq!(
+            done.completed_at,
+            Some(
+                DateTime::parse_from_rfc3339("2024-04-30T10:00:00Z")
+                    .unwrap()
+                    .timestamp()
+            )
+        );
+
+        let lost = &import.tasks[2];
+        assert_eq!(lost.project_id, "inbox");
+        let due = Local.timestamp_opt(lost.due_at.unwrap(), 0).unwrap();
+        assert_eq!((due.hour(), due.minute()), (23, 59));
+
+        assert_eq!(import.warnings.len(), 3);
+    }
+
+    #[test]
+    fn markdown_checkboxes_become_tasks_with_steps_under_their_headings() {
+        let notes = [
+            "# Weekly sync",
+            "Attendees: Ann, Bo",
+            "- plain note, not a task",
+            "## Work",
+            "- [ ] Send the report",
+            "  - [x] Draft numbers",
+            "    - Ask Bo",
+            "* [X] Book the room",
+            "1. [ ]   ",
+            "```",
+            "- [ ] inside code",
+            "```",
+            "## Home ##",
+            "\t- [ ] not nested under anything",
+        ]
+        .join("\n");
+        let notes = notes.as_str();
+        let existing = vec![project("p-work", "work")];
+        let import = parse_markdown(notes, MarkdownHeadings::Project, &existing, now());
+        assert_eq!(import.format, ExternalFormat::Markdown);
+        let titles: Vec<&str> = import.tasks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            vec![
+                "Send the report",
+                "Book the room",
+                "not nested under anything"
+            ]
+        );
+        let report = &import.tasks[0];
+        assert_eq!(report.project_id, "p-work");
+        assert_eq!(
+            report
+                .steps
+                .iter()
+                .map(|step| (step.title.as_str(), step.completed))
+                .collect::<Vec<_>>(),
+            vec![("Draft numbers", true), ("Ask Bo", false)]
+        );
+        assert!(import.tasks[1].completed);
+        assert_eq!(import.projects.len(), 1);
+        assert_eq!(import.projects[0].name, "Home");
+        assert_eq!(import.tasks[2].project_id, import.projects[0].id);
+        assert_eq!(import.warnings.len(), 1);
+        assert!(import.warnings[0].starts_with("line 9:"));
+
+        let tagged = parse_markdown(notes, MarkdownHeadings::Tag, &existing, now());
+        assert!(tagged.projects.is_empty());
+        assert_eq!(tagged.tasks[0].project_id, "inbox");
+        assert_eq!(tagged.tasks[0].tags, vec!["Weekly sync", "work"]);
+        assert_eq!(tagged.tasks[2].tags, vec!["Weekly sync", "home"]);
+    }
+}
@@ -0,0 +1,1061 @@
+use std::ops::Range;
+
+use chrono::{DateTime, Datelike, Duration, Local, LocalResult, NaiveDate, NaiveTime, TimeZone};
+
+use crate::calendar::week_start;
+use crate::capture::parse_due_time;
+use crate::models::{Settings, Timestamp, WeekStart};
+
+/// Longer first lines (typically a pasted paragraph) are cut here and kept in full in the notes.
+const MAX_TITLE_CHARS: usize = 120;
+/// Hour and minute.
+type ClockTime = (u32, u32);
+/// A time mentioned without a day ("tonight", "今晚").
+const EVENING: ClockTime = (20, 0);
+const MORNING: ClockTime = (9, 0);
+
+/// 0.0 means the field was not found in the text; values near 1.0 come from explicit markers
+/// (ISO dates, `#tags`, bullet lists), lower ones from guesses like a weekday in the body.
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize)]
+pub struct DraftConfidence {
+    pub title: f32,
+    pub due_at: f32,
+    pub important: f32,
+    pub tags: f32,
+    pub steps: f32,
+}
+
+/// A task suggestion for the composer; nothing is saved until the user submits it.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct TaskDraft {
+    pub title: String,
+    pub due_at: Option<Timestamp>,
+    pub important: bool,
+    pub tags: Vec<String>,
+    pub steps: Vec<String>,
+    pub notes: Option<String>,
+    pub confidence: DraftConfidence,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct DateHit {
+    range: Range<usize>,
+    date: NaiveDate,
+    /// Implied time of day, e.g. "tonight".
+    time: Option<NaiveTime>,
+    confidence: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct TimeHit {
+    range: Range<usize>,
+    time: NaiveTime,
+}
+
+fn hm(hour: u32, minute: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, minute, 0).expect("valid time")
+}
+
+/// ASCII digits starting at byte `at` (at most `max`), with their value and end offset.
+fn digits_at(text: &str, at: usize, max: usize) -> Option<(u32, usize)> {
+    let len = text[at..]
+        .bytes()
+        .take(max + 1)
+        .take_while(u8::is_ascii_digit)
+        .count();
+    if len == 0 || len > max {
+        return None;
+    }
+    Some((text[at..at + len].parse().ok()?, at + len))
+}
+
+fn starts_number(text: &str, at: usize) -> bool {
+    text.as_bytes().get(at).is_some_and(u8::is_ascii_digit)
+        && (at == 0 || !text.as_bytes()[at - 1].is_ascii_digit())
+}
+
+fn upcoming(today: NaiveDate, month: u32, day: u32) -> Option<NaiveDate> {
+    let this_year = NaiveDate::from_ymd_opt(today.year(), month, day);
+    match this_year {
+        Some(date) if date >= today => Some(date),
+        _ => NaiveDate::from_ymd_opt(today.year() + 1, month, day),
+    }
+}
+
+/// Weekday number 1 (Mon) ..= 7 (Sun) to a date: `next_week` picks that day in the following
+/// week, otherwise the nearest one from today on.
+fn weekday_date(today: NaiveDate, weekday: u32, next_week: bool, start: WeekStart) -> NaiveDate {
+    let today_num = today.weekday().number_from_monday();
+    if next_week {
+        let next_start = week_start(today, start) + Duration::days(7);
+        let offset = (7 + weekday - next_start.weekday().number_from_monday()) % 7;
+        next_start + Duration::days(i64::from(offset))
+    } else {
+        today + Duration::days(i64::from((7 + weekday - today_num) % 7))
+    }
+}
+
+fn iso_dates(text: &str, out: &mut Vec<DateHit>) {
+    for at in 0..text.len() {
+        if !starts_number(text, at) {
+            continue;
+        }
+        let Some((year, end)) = digits_at(text, at, 4).filter(|(_, end)| end - at == 4) else {
+            continue;
+        };
+        let Some(sep) = text[end..]
+            .chars()
+            .next()
+            .filter(|c| matches!(c, '-' | '/' | '.'))
+        else {
+            continue;
+        };
+        let Some((month, end)) = digits_at(text, end + 1, 2) else {
+            continue;
+        };
+        if !text[end..].starts_with(sep) {
+            continue;
+        }
+        let Some((day, end)) = digits_at(text, end + 1, 2) else {
+            continue;
+        };
+        if let Some(date) = NaiveDate::from_ymd_opt(year as i32, month, day) {
+            out.push(DateHit {
+                range: at..end,
+                date,
+                time: None,
+                confidence: 0.95,
+            });
+        }
+    }
+}
+
+const ZH_WEEKDAYS: [(char, u32); 8] = [
+    ('一', 1),
+    ('二', 2),
+    ('三', 3),
+    ('四', 4),
+    ('五', 5),
+    ('六', 6),
+    ('日', 7),
+    ('天', 7),
+];
+
+fn zh_dates(text: &str, today: NaiveDate, start: WeekStart, out: &mut Vec<DateHit>) {
+    // "5月1日" / "2026年5月1号".
+    for at in 0..text.len() {
+        if !starts_number(text, at) {
+            continue;
+        }
+        let (year, month_at) = match digits_at(text, at, 4) {
+            Some((year, end)) if end - at == 4 && text[end..].starts_with('年') => {
+                (Some(year as i32), end + '年'.len_utf8())
+            }
+            _ => (None, at),
+        };
+        let Some((month, end)) = digits_at(text, month_at, 2) else {
+            continue;
+        };
+        if !text[end..].starts_with('月') {
+            continue;
+        }
+        let Some((day, end)) = digits_at(text, end + '月'.len_utf8(), 2) else {
+            continue;
+        };
+        let Some(suffix) = text[end..]
+            .chars()
+            .next()
+            .filter(|c| matches!(c, '日' | '号'))
+        else {
+            continue;
+        };
+        let date = match year {
+            Some(year) => NaiveDate::from_ymd_opt(year, month, day),
+            None => upcoming(today, month, day),
+        };
+        if let Some(date) = date {
+            out.push(DateHit {
+                range: at..end + suffix.len_utf8(),
+                date,
+                time: None,
+                confidence: 0.9,
+            });
+        }
+    }
+
+    const RELATIVE: [(&str, i64, Option<ClockTime>); 9] = [
+        ("大后天", 3, None),
+        ("后天", 2, None),
+        ("明天", 1, None),
+        ("明日", 1, None),
+        ("明早", 1, Some(MORNING)),
+        ("明晚", 1, Some(EVENING)),
+        ("今天", 0, None),
+        ("今日", 0, None),
+        ("今晚", 0, Some(EVENING)),
+    ];
+    for (word, days, time) in RELATIVE {
+        for (at, _) in text.match_indices(word) {
+            // "后天" inside "大后天" is already covered.
+            if word == "后天" && text[..at].ends_with('大') {
+                continue;
+            }
+            out.push(DateHit {
+                range: at..at + word.len(),
+                date: today + Duration::days(days),
+                time: time.map(|(h, m)| hm(h, m)),
+                confidence: 0.85,
+            });
+        }
+    }
+
+    for prefix in ["下周", "下星期", "下礼拜", "周", "星期", "礼拜"] {
+        for (at, _) in text.match_indices(prefix) {
+            let next_week = prefix.starts_with('下');
+            if !next_week && text[..at].ends_with('下') {
+                continue;
+            }
+            let after = at + prefix.len();
+            let weekday = text[after..]
+                .chars()
+                .next()
+                .and_then(|c| ZH_WEEKDAYS.iter().find(|(zh, _)| *zh == c));
+            match weekday {
+                Some((c, weekday)) => out.push(DateHit {
+                    range: at..after + c.len_utf8(),
+                    date: weekday_date(today, *weekday, next_week, start),
+                    time: None,
+                    confidence: 0.85,
+                }),
+                None if next_week => out.push(DateHit {
+                    range: at..after,
+                    date: week_start(today, start) + Duration::days(7),
+                    time: None,
+                    confidence: 0.6,
+                }),
+                None => {}
+            }
+        }
+    }
+
+    // "3天后".
+    for (at, _) in text.match_indices("天后") {
+        let digits = text[..at]
+            .bytes()
+            .rev()
+            .take_while(u8::is_ascii_digit)
+            .count();
+        if digits == 0 || digits > 3 || text[..at].ends_with("后天") {
+            continue;
+        }
+        if let Ok(days) = text[at - digits..at].parse::<i64>() {
+            out.push(DateHit {
+                range: at - digits..at + "天后".len(),
+                date: today + Duration::days(days),
+                time: None,
+                confidence: 0.8,
+            });
+        }
+    }
+}
+
+fn zh_times(text: &str, out: &mut Vec<TimeHit>) {
+    const PERIODS: [(&str, bool); 8] = [
+        ("上午", false),
+        ("早上", false),
+        ("凌晨", false),
+        ("中午", true),
+        ("下午", true),
+        ("傍晚", true),
+        ("晚上", true),
+        ("今晚", true),
+    ];
+    for at in 0..text.len() {
+        if !starts_number(text, at) {
+            continue;
+        }
+        let Some((hour, end)) = digits_at(text, at, 2) else {
+            continue;
+        };
+        let Some(unit) = ["点", "时"]
+            .into_iter()
+            .find(|unit| text[end..].starts_with(unit))
+        else {
+            continue;
+        };
+        let mut end = end + unit.len();
+        let mut minute = 0;
+        if text[end..].starts_with('半') {
+            minute = 30;
+            end += '半'.len_utf8();
+        } else if let Some((value, after)) = digits_at(text, end, 2) {
+            minute = value;
+            end = after;
+            if text[end..].starts_with('分') {
+                end += '分'.len_utf8();
+            }
+        }
+        let mut start = at;
+        let mut hour = hour;
+        if let Some((period, pm)) = PERIODS.iter().find(|(p, _)| text[..at].ends_with(p)) {
+            // "今晚" also names the day; leave it for the date detector.
+            if *period != "今晚" {
+                start = at - period.len();
+            }
+            if *pm && hour < 12 && !(*period == "中午" && hour >= 11) {
+                hour += 12;
+            }
+        }
+        if let Some(time) = NaiveTime::from_hms_opt(hour, minute, 0) {
+            out.push(TimeHit {
+                range: start..end,
+                time,
+            });
+        }
+    }
+}
+
+const EN_MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const EN_WEEKDAYS: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+fn month_number(word: &str) -> Option<u32> {
+    let index = EN_MONTHS.iter().position(|m| word.starts_with(m))?;
+    let full = [
+        "january",
+        "february",
+        "march",
+        "april",
+        "may",
+        "june",
+        "july",
+        "august",
+        "september",
+        "october",
+        "november",
+        "december",
+    ][index];
+    (word == EN_MONTHS[index] || word == full || (index == 8 && word == "sept"))
+        .then_some(index as u32 + 1)
+}
+
+fn weekday_number(word: &str, allow_short: bool) -> Option<u32> {
+    EN_WEEKDAYS
+        .iter()
+        .position(|full| {
+            word == *full || (allow_short && word.len() >= 3 && full.starts_with(word))
+        })
+        .map(|index| index as u32 + 1)
+}
+
+/// "1", "1st", "22nd" as a day of month.
+fn day_number(word: &str) -> Option<u32> {
+    let digits: String = word.chars().take_while(char::is_ascii_digit).collect();
+    let suffix = &word[digits.len()..];
+    if digits.is_empty() || !matches!(suffix, "" | "st" | "nd" | "rd" | "th") {
+        return None;
+    }
+    digits.parse().ok().filter(|day| (1..=31).contains(day))
+}
+
+/// Words of ASCII letters/digits (plus `:` for times) with their byte ranges in `lower`.
+fn words(lower: &str) -> Vec<(Range<usize>, &str)> {
+    let mut out = Vec::new();
+    let mut start = None;
+    for (at, c) in lower
+        .char_indices()
+        .chain(std::iter::once((lower.len(), ' ')))
+    {
+        let word_char = c.is_ascii_alphanumeric() || c == ':';
+        match (start, word_char) {
+            (None, true) => start = Some(at),
+            (Some(from), false) => {
+                out.push((from..at, &lower[from..at]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+fn en_dates(lower: &str, today: NaiveDate, start: WeekStart, out: &mut Vec<DateHit>) {
+    let words = words(lower);
+    let word = |i: usize| words.get(i).map(|(_, w)| *w);
+    for (i, (range, w)) in words.iter().enumerate() {
+        let prev = i.checked_sub(1).and_then(word);
+        let hit =
+            |range: Range<usize>, date: NaiveDate, time: Option<NaiveTime>, confidence| DateHit {
+                range,
+                date,
+                time,
+                confidence,
+            };
+        match *w {
+            "today" => out.push(hit(range.clone(), today, None, 0.85)),
+            "tonight" => out.push(hit(
+                range.clone(),
+                today,
+                Some(hm(EVENING.0, EVENING.1)),
+                0.85,
+            )),
+            "tomorrow" | "tmrw" | "tmr" => {
+                if prev == Some("after") && i >= 2 && word(i - 2) == Some("day") {
+                    let first = if i >= 3 && word(i - 3) == Some("the") {
+                        i - 3
+                    } else {
+                        i - 2
+                    };
+                    let from = words[first].0.start;
+                    out.push(hit(from..range.end, today + Duration::days(2), None, 0.85));
+                } else {
+                    out.push(hit(range.clone(), today + Duration::days(1), None, 0.85));
+                }
+            }
+            "week" if prev == Some("next") => {
+                let from = words[i - 1].0.start;
+                let date = week_start(today, start) + Duration::days(7);
+                out.push(hit(from..range.end, date, None, 0.6));
+            }
+            "in" => {
+                let (Some(count), Some(unit)) = (word(i + 1), word(i + 2)) else {
+                    continue;
+                };
+                let Ok(count) = count.parse::<i64>() else {
+                    continue;
+                };
+                let days = match unit {
+                    "day" | "days" => count,
+                    "week" | "weeks" => count * 7,
+                    _ => continue,
+                };
+                if (1..=365).contains(&days) {
+                    let end = words[i + 2].0.end;
+                    out.push(hit(
+                        range.start..end,
+                        today + Duration::days(days),
+                        None,
+                        0.8,
+                    ));
+                }
+            }
+            _ => {}
+        }
+
+        // Short forms ("fri") only count after a word that introduces a day: "sat" and "wed"
+        // are ordinary words otherwise.
+        let introduced = matches!(prev, Some("on" | "by" | "next" | "this" | "due" | "before"));
+        if let Some(weekday) = weekday_number(w, introduced) {
+            let next_week = prev == Some("next");
+            let from = if matches!(prev, Some("next" | "this")) {
+                words[i - 1].0.start
+            } else {
+                range.start
+            };
+            let date = weekday_date(today, weekday, next_week, start);
+            out.push(hit(from..range.end, date, None, 0.8));
+        }
+
+        // "May 1st" / "1 May"; a month name alone is too ambiguous ("may").
+        if let Some(month) = month_number(w) {
+            let date_after = word(i + 1)
+                .and_then(day_number)
+                .map(|day| (day, words[i + 1].0.end));
+            let date_before = prev
+                .and_then(day_number)
+                .map(|day| (day, words[i - 1].0.start));
+            if let Some((day, end)) = date_after {
+                if let Some(date) = upcoming(today, month, day) {
+                    out.push(hit(range.start..end, date, None, 0.85));
+                }
+            } else if let Some((day, from)) = date_before {
+                if let Some(date) = upcoming(today, month, day) {
+                    out.push(hit(from..range.end, date, None, 0.85));
+                }
+            }
+        }
+    }
+}
+
+fn en_times(lower: &str, out: &mut Vec<TimeHit>) {
+    let words = words(lower);
+    for (i, (range, w)) in words.iter().enumerate() {
+        if *w == "noon" {
+            out.push(TimeHit {
+                range: range.clone(),
+                time: hm(12, 0),
+            });
+            continue;
+        }
+        let (clock, mut meridiem) = match w.strip_suffix("am").or_else(|| w.strip_suffix("pm")) {
+            Some(rest) => (rest, Some(w.ends_with("pm"))),
+            None => (*w, None),
+        };
+        let mut end = range.end;
+        if meridiem.is_none() {
+            if let Some((next_range, next)) = words.get(i + 1) {
+                if matches!(*next, "am" | "pm") && next_range.start == range.end + 1 {
+                    meridiem = Some(*next == "pm");
+                    end = next_range.end;
+                }
+            }
+        }
+        let (hour, minute) = match clock.split_once(':') {
+            Some((h, m)) if m.len() == 2 => (h.parse::<u32>(), m.parse::<u32>()),
+            Some(_) => continue,
+            // A bare number is only a time with am/pm ("3pm").
+            None if meridiem.is_some() => (clock.parse::<u32>(), Ok(0)),
+            None => continue,
+        };
+        let (Ok(mut hour), Ok(minute)) = (hour, minute) else {
+            continue;
+        };
+        match meridiem {
+            Some(_) if !(1..=12).contains(&hour) => continue,
+            Some(true) if hour < 12 => hour += 12,
+            Some(false) if hour == 12 => hour = 0,
+            _ => {}
+        }
+        if let Some(time) = NaiveTime::from_hms_opt(hour, minute, 0) {
+            out.push(TimeHit {
+                range: range.start..end,
+                time,
+            });
+        }
+    }
+}
+
+/// Date and time mentioned in `line`, earliest in the line first. Overlapping detector hits
+/// keep the first (so "明晚8点" is one date plus one time, not two dates).
+fn scan(line: &str, today: NaiveDate, start: WeekStart) -> (Vec<DateHit>, Vec<TimeHit>) {
+    // ASCII lowercasing keeps byte offsets, so ranges apply to the original line.
+    let lower = line.to_ascii_lowercase();
+    let mut dates = Vec::new();
+    iso_dates(&lower, &mut dates);
+    zh_dates(&lower, today, start, &mut dates);
+    en_dates(&lower, today, start, &mut dates);
+    let mut times = Vec::new();
+    en_times(&lower, &mut times);
+    zh_times(&lower, &mut times);
+
+    dates.sort_by_key(|hit| (hit.range.start, std::cmp::Reverse(hit.range.end)));
+    let mut kept: Vec<DateHit> = Vec::new();
+    for hit in dates {
+        if kept.iter().all(|k| hit.range.start >= k.range.end) {
+            kept.push(hit);
+        }
+    }
+    times.sort_by_key(|hit| (hit.range.start, std::cmp::Reverse(hit.range.end)));
+    let mut kept_times: Vec<TimeHit> = Vec::new();
+    for hit in times {
+        let clear = kept_times.iter().all(|k| hit.range.start >= k.range.end)
+            && kept
+                .iter()
+                .all(|d| hit.range.start >= d.range.end || hit.range.end <= d.range.start);
+        if clear {
+            kept_times.push(hit);
+        }
+    }
+    (kept, kept_times)
+}
+
+fn to_timestamp(date: NaiveDate, time: NaiveTime) -> Option<Timestamp> {
+    match Local.from_local_datetime(&date.and_time(time)) {
+        LocalResult::Single(value) | LocalResult::Ambiguous(value, _) => Some(value.timestamp()),
+        LocalResult::None => Local
+            .from_local_datetime(&(date.and_time(time) + Duration::hours(1)))
+            .earliest()
+            .map(|value| value.timestamp()),
+    }
+}
+
+struct DueGuess {
+    due_at: Timestamp,
+    confidence: f32,
+    /// Byte ranges to cut from the line the guess came from.
+    ranges: Vec<Range<usize>>,
+}
+
+fn guess_due(line: &str, now: DateTime<Local>, settings: &Settings) -> Option<DueGuess> {
+    let today = now.date_naive();
+    let (dates, times) = scan(line, today, settings.week_starts_on);
+    let date = dates.first();
+    let time = times.first();
+    let default_time = parse_due_time(&settings.quick_default_due_time).unwrap_or(hm(18, 0));
+    let (due_at, confidence) = match (date, time) {
+        (Some(date), Some(time)) => (to_timestamp(date.date, time.time)?, date.confidence),
+        (Some(date), None) => {
+            let implied = date.time.unwrap_or(default_time);
+            // No time given: the default time is a convention, not something the text said.
+            let penalty = if date.time.is_some() { 0.0 } else { 0.1 };
+            (to_timestamp(date.date, implied)?, date.confidence - penalty)
+        }
+        (None, Some(time)) => {
+            let mut at = to_timestamp(today, time.time)?;
+            if at <= now.timestamp() {
+                at = to_timestamp(today + Duration::days(1), time.time)?;
+            }
+            (at, 0.7)
+        }
+        (None, None) => return None,
+    };
+    let ranges = date
+        .map(|d| d.range.clone())
+        .into_iter()
+        .chain(time.map(|t| t.range.clone()))
+        .collect();
+    Some(DueGuess {
+        due_at,
+        confidence,
+        ranges,
+    })
+}
+
+/// Words that only tie a date to the sentence ("by Friday", "截止5月1日") and go with it.
+const CONNECTORS: [&str; 6] = ["by", "on", "at", "due", "before", "截止"];
+
+/// Removes `ranges` and the connector right before each, then tidies spacing and punctuation.
+fn cut(line: &str, ranges: &[Range<usize>]) -> String {
+    let mut ranges = ranges.to_vec();
+    ranges.sort_by_key(|range| range.start);
+    let mut out = String::new();
+    let mut pos = 0;
+    for range in ranges {
+        let mut kept = line[pos..range.start].trim_end();
+        let lower = kept.to_ascii_lowercase();
+        for word in CONNECTORS {
+            let Some(head) = lower.strip_suffix(word) else {
+                continue;
+            };
+            // ASCII connectors must be whole words ("Bob" does not end in "by").
+            if !word.is_ascii() || head.is_empty() || head.ends_with(char::is_whitespace) {
+                kept = &kept[..head.len()];
+                break;
+            }
+        }
+        out.push_str(kept);
+        out.push(' ');
+        pos = range.end;
+    }
+    out.push_str(&line[pos..]);
+    out.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c: char| {
+            matches!(c, ',' | '，' | '、' | '-' | ':' | '：') || c.is_whitespace()
+        })
+        .to_string()
+}
+
+/// Same rules as the composer's `normalizeTag` so pasted and typed tags match.
+pub fn normalize_tag(raw: &str) -> Option<String> {
+    const PUNCT: &str = ",.;:，。！？、（）()[]{}<>《》\"'“”";
+    let value = raw.trim();
+    let value = value.strip_prefix('#').unwrap_or(value).trim();
+    let value = value.trim_matches(|c: char| c.is_whitespace() || PUNCT.contains(c));
+    if value.is_empty() {
+        return None;
+    }
+    let value = if value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        value.to_ascii_lowercase()
+    } else {
+        value.to_string()
+    };
+    // JS slices by UTF-16 units; characters outside the BMP are rare enough in tags to ignore.
+    Some(value.chars().take(32).collect())
+}
+
+/// Splits `#tags` out of `line`; returns the remaining text.
+fn take_tags(line: &str, tags: &mut Vec<String>) -> String {
+    let mut rest = Vec::new();
+    for token in line.split_whitespace() {
+        if token.len() > 1 && token.starts_with('#') {
+            if let Some(tag) = normalize_tag(token) {
+                if !tags.contains(&tag) {
+                    tags.push(tag);
+                }
+                continue;
+            }
+        }
+        rest.push(token);
+    }
+    rest.join(" ")
+}
+
+const IMPORTANT_WORDS: [&str; 4] = ["urgent", "asap", "important", "critical"];
+const IMPORTANT_ZH: [&str; 4] = ["紧急", "重要", "尽快", "加急"];
+
+fn mentions_importance(line: &str) -> bool {
+    let lower = line.to_ascii_lowercase();
+    IMPORTANT_ZH.iter().any(|word| lower.contains(word))
+        || lower
+            .split_whitespace()
+            .any(|token| token == "!" || token.starts_with("!!"))
+        || words(&lower)
+            .iter()
+            .any(|(_, word)| IMPORTANT_WORDS.contains(word))
+}
+
+/// Text after a bullet marker (`-`, `*`, `•`, `1.`, `1)`, optionally `[ ]`/`[x]`), if any.
+fn bullet_text(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    let rest = if let Some(rest) = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .or_else(|| line.strip_prefix("+ "))
+        .or_else(|| line.strip_prefix("• "))
+        .or_else(|| line.strip_prefix('•'))
+    {
+        rest
+    } else {
+        let digits = line.bytes().take_while(u8::is_ascii_digit).count();
+        let after = line.get(digits..)?;
+        if digits == 0 || digits > 3 {
+            return None;
+        }
+        after
+            .strip_prefix(". ")
+            .or_else(|| after.strip_prefix(") "))
+            .or_else(|| after.strip_prefix("、"))?
+    };
+    let rest = rest.trim_start();
+    let rest = ["[ ] ", "[x] ", "[X] "]
+        .iter()
+        .find_map(|box_| rest.strip_prefix(box_))
+        .unwrap_or(rest);
+    let rest = rest.trim();
+    (!rest.is_empty()).then_some(rest)
+}
+
+/// Mail headers that say nothing about the task.
+fn is_header(line: &str) -> bool {
+    let lower = line.to_ascii_lowercase();
+    [
+        "from:",
+        "to:",
+        "cc:",
+        "sent:",
+        "date:",
+        "发件人：",
+        "收件人：",
+        "抄送：",
+        "时间：",
+    ]
+    .iter()
+    .any(|header| lower.starts_with(header))
+}
+
+fn strip_subject(line: &str) -> (&str, bool) {
+    let lower = line.to_ascii_lowercase();
+    for prefix in ["subject:", "主题：", "主题:"] {
+        if lower.starts_with(prefix) {
+            return (line[prefix.len()..].trim(), true);
+        }
+    }
+    (line, false)
+}
+
+fn strip_reply_prefixes(mut title: &str) -> &str {
+    loop {
+        let lower = title.to_ascii_lowercase();
+        let Some(prefix) = ["re:", "fw:", "fwd:", "回复：", "转发：", "回复:", "转发:"]
+            .iter()
+            .find(|prefix| lower.starts_with(*prefix))
+        else {
+            return title;
+        };
+        title = title[prefix.len()..].trim_start();
+    }
+}
+
+/// Rule-based draft from free text (no AI call): the first meaningful line (or a `Subject:`
+/// header) becomes the title, bullet lines become steps, other lines become notes, and a date
+/// and time are looked for in the title first, then in the rest of the text.
+pub fn infer_task(text: &str, now: DateTime<Local>, settings: &Settings) -> TaskDraft {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !is_header(line))
+        .collect();
+    let mut draft = TaskDraft::default();
+    if lines.is_empty() {
+        return draft;
+    }
+
+    let subject = lines.iter().position(|line| strip_subject(line).1);
+    let title_index = subject.unwrap_or(0);
+    let (raw_title, from_subject) = strip_subject(lines[title_index]);
+    let title_is_bullet = bullet_text(raw_title).is_some();
+    let raw_title = bullet_text(raw_title).unwrap_or(raw_title);
+    let raw_title = strip_reply_prefixes(raw_title);
+
+    let mut body_tags = Vec::new();
+    let mut notes = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        if index == title_index {
+            continue;
+        }
+        match bullet_text(line) {
+            Some(step) => draft.steps.push(take_tags(step, &mut body_tags)),
+            None => notes.push(*line),
+        }
+    }
+
+    let mut title_line = take_tags(raw_title, &mut draft.tags);
+    let title_tags = draft.tags.len();
+    for tag in body_tags {
+        if !draft.tags.contains(&tag) {
+            draft.tags.push(tag);
+        }
+    }
+    draft.confidence.tags = match (title_tags, draft.tags.len()) {
+        (_, 0) => 0.0,
+        (t, all) if t == all => 0.95,
+        _ => 0.7,
+    };
+
+    if let Some(guess) = guess_due(&title_line, now, settings) {
+        let cleaned = cut(&title_line, &guess.ranges);
+        if !cleaned.is_empty() {
+            title_line = cleaned;
+        }
+        draft.due_at = Some(guess.due_at);
+        draft.confidence.due_at = guess.confidence;
+    } else {
+        // A date somewhere in the body is likelier to be context ("as discussed on Monday").
+        let body = notes
+            .iter()
+            .copied()
+            .chain(draft.steps.iter().map(String::as_str));
+        if let Some(guess) = body
+            .filter_map(|line| guess_due(line, now, settings))
+            .next()
+        {
+            draft.due_at = Some(guess.due_at);
+            draft.confidence.due_at = guess.confidence - 0.2;
+        }
+    }
+
+    if mentions_importance(&title_line) {
+        draft.important = true;
+        draft.confidence.important = 0.9;
+        title_line = title_line
+            .split_whitespace()
+            .filter(|token| !(*token == "!" || token.starts_with("!!")))
+            .collect::<Vec<_>>()
+            .join(" ");
+    } else if notes.iter().any(|line| mentions_importance(line)) {
+        draft.important = true;
+        draft.confidence.important = 0.6;
+    }
+
+    let mut title_confidence = match (from_subject, title_is_bullet) {
+        (true, _) => 0.85,
+        (false, true) => 0.5,
+        (false, false) => 0.9,
+    };
+    if title_line.chars().count() > MAX_TITLE_CHARS {
+        notes.insert(0, raw_title);
+        title_line = title_line.chars().take(MAX_TITLE_CHARS).collect::<String>();
+        title_line = format!("{}…", title_line.trim_end());
+        title_confidence = 0.5;
+    }
+    draft.title = title_line;
+    draft.confidence.title = if draft.title.is_empty() {
+        0.0
+    } else {
+        title_confidence
+    };
+    draft.confidence.steps = if draft.steps.is_empty() { 0.0 } else { 0.9 };
+    draft.notes = (!notes.is_empty()).then(|| notes.join("\n"));
+    draft
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Wednesday, 2026-03-18 10:00 local.
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 3, 18, 10, 0, 0).unwrap()
+    }
+
+    fn at(month: u32, day: u32, hour: u32, minute: u32) -> Timestamp {
+        Local
+            .with_ymd_and_hms(2026, month, day, hour, minute, 0)
+            .unwrap()
+            .timestamp()
+    }
+
+    fn infer(text: &str) -> TaskDraft {
+        infer_task(text, now(), &Settings::default())
+    }
+
+    #[test]
+    fn english_dates_and_times_are_found_and_cut_from_the_title() {
+        let cases = [
+            ("Send report by Friday", "Send report", at(3, 20, 18, 0)),
+            ("Call Bob tomorrow at 3pm", "Call Bob", at(3, 19, 15, 0)),
+            ("Dentist next Monday 9:30", "Dentist", at(3, 23, 9, 30)),
+            ("Pay rent on May 1st", "Pay rent", at(5, 1, 18, 0)),
+            (
+                "Renew passport 2026-04-02 14:00",
+                "Renew passport",
+                at(4, 2, 14, 0),
+            ),
+            ("Book flights in 2 weeks", "Book flights", at(4, 1, 18, 0)),
+            ("Movie tonight", "Movie", at(3, 18, 20, 0)),
+            ("Standup 9am", "Standup", at(3, 19, 9, 0)),
+            (
+                "Plan trip the day after tomorrow",
+                "Plan trip",
+                at(3, 20, 18, 0),
+            ),
+            ("Lunch 12 pm", "Lunch", at(3, 18, 12, 0)),
+        ];
+        for (text, title, due) in cases {
+            let draft = infer(text);
+            assert_eq!(draft.title, title, "{text}");
+            assert_eq!(draft.due_at, Some(due), "{text}");
+            assert!(draft.confidence.due_at > 0.5, "{text}");
+        }
+    }
+
+    #[test]
+    fn chinese_dates_and_times_are_found() {
+        let cases = [
+            ("明天下午3点开会", "开会", at(3, 19, 15, 0)),
+            ("周五交周报", "交周报", at(3, 20, 18, 0)),
+            ("下周一 10:30 面试", "面试", at(3, 23, 10, 30)),
+            ("5月1日交房租", "交房租", at(5, 1, 18, 0)),
+            ("今晚健身", "健身", at(3, 18, 20, 0)),
+            ("大后天上午9点半体检", "体检", at(3, 21, 9, 30)),
+            ("3天后复查", "复查", at(3, 21, 18, 0)),
+            ("截止2026年4月10号提交材料", "提交材料", at(4, 10, 18, 0)),
+        ];
+        for (text, title, due) in cases {
+            let draft = infer(text);
+            assert_eq!(draft.title, title, "{text}");
+            assert_eq!(draft.due_at, Some(due), "{text}");
+        }
+    }
+
+    #[test]
+    fn ambiguous_words_are_not_dates() {
+        for text in [
+            "May I borrow the car",
+            "Sat down with the team",
+            "Version 2.0 notes",
+        ] {
+            let draft = infer(text);
+            assert_eq!(draft.due_at, None, "{text}");
+            assert_eq!(draft.title, text);
+            assert_eq!(draft.confidence.due_at, 0.0);
+        }
+        // A time that already passed today means tomorrow.
+        assert_eq!(infer("Sync 8:00").due_at, Some(at(3, 19, 8, 0)));
+        // A month/day already behind us is next year's.
+        let draft = infer("Birthday Jan 5");
+        assert_eq!(
+            draft.due_at,
+            Some(
+                Local
+                    .with_ymd_and_hms(2027, 1, 5, 18, 0, 0)
+                    .unwrap()
+                    .timestamp()
+            )
+        );
+    }
+
+    #[test]
+    fn bullets_become_steps_and_other_lines_notes() {
+        let draft = infer(
+            "Prepare launch #work !!\n\n- write blog post #marketing\n* [x] update changelog\n2) tweet\nRemember the screenshots.",
+        );
+        assert_eq!(draft.title, "Prepare launch");
+        assert!(draft.important);
+        assert_eq!(draft.confidence.important, 0.9);
+        assert_eq!(draft.tags, ["work", "marketing"]);
+        assert_eq!(draft.confidence.tags, 0.7);
+        assert_eq!(
+            draft.steps,
+            ["write blog post", "update changelog", "tweet"]
+        );
+        assert_eq!(draft.confidence.steps, 0.9);
+        assert_eq!(draft.notes.as_deref(), Some("Remember the screenshots."));
+        assert_eq!(draft.due_at, None);
+    }
+
+    #[test]
+    fn email_snippets_use_the_subject_and_look_for_dates_in_the_body() {
+        let draft = infer(
+            "From: Alice <alice@example.com>\nTo: me\nSubject: Re: Fwd: Q2 budget review\n\nHi, could you send the numbers by Friday? It's urgent.\nThanks",
+        );
+        assert_eq!(draft.title, "Q2 budget review");
+        assert_eq!(draft.confidence.title, 0.85);
+        assert_eq!(draft.due_at, Some(at(3, 20, 18, 0)));
+        assert!((draft.confidence.due_at - 0.5).abs() < 1e-6);
+        assert!(draft.important);
+        assert_eq!(draft.confidence.important, 0.6);
+        assert_eq!(
+            draft.notes.as_deref(),
+            Some("Hi, could you send the numbers by Friday? It's urgent.\nThanks")
+        );
+    }
+
+    #[test]
+    fn list_only_and_long_text_lower_the_title_confidence() {
+        let draft = infer("- milk\n- eggs");
+        assert_eq!(draft.title, "milk");
+        assert_eq!(draft.steps, ["eggs"]);
+        assert_eq!(draft.confidence.title, 0.5);
+
+        let long = "word ".repeat(40);
+        let draft = infer(&long);
+        assert!(draft.title.ends_with('…'));
+        assert_eq!(draft.title.chars().count(), MAX_TITLE_CHARS);
+        assert_eq!(draft.notes.as_deref(), Some(long.trim()));
+        assert_eq!(draft.confidence.title, 0.5);
+
+        assert_eq!(infer("  \n\t").confidence, DraftConfidence::default());
+    }
+
+    #[test]
+    fn tags_follow_the_composer_rules() {
+        assert_eq!(normalize_tag("#Work").as_deref(), Some("work"));
+        assert_eq!(normalize_tag("#「工作」，").as_deref(), Some("「工作」"));
+        assert_eq!(normalize_tag("#..."), None);
+        assert_eq!(
+            normalize_tag(&format!("#{}", "a".repeat(40)))
+                .unwrap()
+                .len(),
+            32
+        );
+    }
+
+    #[test]
+    fn settings_shape_defaults_and_weeks() {
+        let settings = Settings {
+            quick_default_due_time: "09:15".to_string(),
+            week_starts_on: WeekStart::Sunday,
+            ..Settings::default()
+        };
+        // With Sunday weeks, "next week" starts on Sunday the 22nd.
+        let draft = infer_task("Sprint planning next week", now(), &settings);
+        assert_eq!(draft.due_at, Some(at(3, 22, 9, 15)));
+        assert_eq!(draft.title, "Sprint planning");
+        assert!((draft.confidence.due_at - 0.5).abs() < 1e-6);
+    }
+}
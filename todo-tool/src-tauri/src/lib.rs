@@ -1,20 +1,81 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
+mod activity;
 mod ai;
+mod archive;
+mod attachments;
+mod automation;
+mod calendar;
+mod capture;
+mod carry_over;
+mod clock;
 mod commands;
+mod comments;
+mod contexts;
+mod daily_summary;
+mod data_dir;
+mod delegation;
+mod dependencies;
+mod digest;
+mod dry_run;
+mod duplicates;
+mod email;
 mod events;
-#[cfg(all(feature = "app", not(test)))]
+mod export_filter;
+mod flair;
+mod focus;
+mod i18n;
+mod ics;
+mod importers;
+mod infer;
 mod logging;
+mod maintenance;
+mod md_sync;
+mod merge;
+mod migrations;
 mod models;
+mod mqtt;
+mod note_history;
+mod obsidian;
+mod outbound;
+mod profiles;
+mod project_tree;
+mod quadrant;
+mod query;
+mod recovery;
 mod repeat;
+mod rrule;
 mod scheduler;
+mod search;
+mod secrets;
+mod shortcuts;
+mod smart_lists;
+mod snooze;
+mod sounds;
 mod state;
+mod state_delta;
+mod stats;
+mod steps;
 mod storage;
+mod suggest;
+mod sync;
+mod tags;
+mod task_archive;
+mod task_index;
+mod time_tracking;
+mod trash;
 mod tray;
+mod undo;
+mod weekly_review;
 #[cfg(all(feature = "app", not(test)))]
 mod windows;
+mod write_queue;
+mod year_review;
+
+#[cfg(feature = "bench")]
+pub use commands::bench;
 
 #[cfg(all(feature = "app", not(test)))]
-use tauri::{Manager, WebviewWindowBuilder, WindowEvent};
+use tauri::{Emitter, Manager, WebviewWindowBuilder, WindowEvent};
 #[cfg(all(feature = "app", not(test)))]
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
 
@@ -23,6 +84,8 @@ use crate::commands::*;
 #[cfg(all(feature = "app", not(test)))]
 use crate::scheduler::start_scheduler;
 #[cfg(all(feature = "app", not(test)))]
+use crate::shortcuts::{shortcut_bindings, ShortcutAction};
+#[cfg(all(feature = "app", not(test)))]
 use crate::state::AppState;
 #[cfg(all(feature = "app", not(test)))]
 use crate::storage::Storage;
@@ -33,6 +96,63 @@ use crate::tray::update_tray_count;
 #[cfg(all(feature = "app", not(test)))]
 use crate::windows::{hide_quick_window, hide_settings_window};
 
+#[cfg(all(feature = "app", not(test)))]
+fn show_and_focus(app: &tauri::AppHandle, label: &str) -> Option<tauri::WebviewWindow> {
+    let Some(window) = app.get_webview_window(label) else {
+        log::warn!("shortcut: {label} window missing");
+        return None;
+    };
+    if let Err(err) = window.unminimize() {
+        log::warn!("shortcut: failed to unminimize {label} window: {err}");
+    }
+    if let Err(err) = window.show() {
+        log::warn!("shortcut: failed to show {label} window: {err}");
+    }
+    if let Err(err) = window.set_focus() {
+        log::warn!("shortcut: failed to focus {label} window: {err}");
+    }
+    Some(window)
+}
+
+/// Runs the action bound to a pressed global shortcut (see `shortcuts::ShortcutAction`).
+#[cfg(all(feature = "app", not(test)))]
+fn run_shortcut_action(app: &tauri::AppHandle, action: ShortcutAction) {
+    log::info!("shortcut: action={}", action.setting_name());
+    match action {
+        ShortcutAction::ShowQuick => {
+            show_and_focus(app, "quick");
+        }
+        ShortcutAction::QuickAdd => {
+            if let Some(window) = show_and_focus(app, "quick") {
+                // The quick window focuses its add input on this route.
+                if let Err(err) = window.emit(
+                    crate::events::EVENT_NAVIGATE,
+                    crate::events::NavigatePayload {
+                        hash: "#/quick/add".to_string(),
+                    },
+                ) {
+                    log::warn!("shortcut: failed to emit navigate event: {err}");
+                }
+            }
+        }
+        ShortcutAction::ToggleMain => {
+            let visible = app
+                .get_webview_window("main")
+                .is_some_and(|window| window.is_visible().unwrap_or(false));
+            if visible {
+                if let Some(window) = app.get_webview_window("main") {
+                    if let Err(err) = window.hide() {
+                        log::warn!("shortcut: failed to hide main window: {err}");
+                    }
+                }
+            } else {
+                show_and_focus(app, "main");
+            }
+        }
+        ShortcutAction::CompleteFocus => complete_focus_task(app),
+    }
+}
+
 #[cfg_attr(all(mobile, feature = "app"), tauri::mobile_entry_point)]
 #[cfg(all(feature = "app", not(test)))]
 pub fn run() {
@@ -42,7 +162,7 @@ pub fn run() {
         .plugin(tauri_plugin_notification::init())
         .plugin(
             tauri_plugin_global_shortcut::Builder::new()
-                .with_handler(|app, _shortcut, event| {
+                .with_handler(|app, shortcut, event| {
                     if event.state() == tauri_plugin_global_shortcut::ShortcutState::Pressed {
                         // When the user is recording a shortcut in Settings, ignore the global
                         // shortcut handler so we don't accidentally pop up the quick window.
@@ -50,19 +170,17 @@ pub fn run() {
                         if state.is_shortcut_capture_active() {
                             return;
                         }
-                        if let Some(window) = app.get_webview_window("quick") {
-                            if let Err(err) = window.unminimize() {
-                                log::warn!("shortcut: failed to unminimize quick window: {err}");
-                            }
-                            if let Err(err) = window.show() {
-                                log::warn!("shortcut: failed to show quick window: {err}");
-                            }
-                            if let Err(err) = window.set_focus() {
-                                log::warn!("shortcut: failed to focus quick window: {err}");
-                            }
-                        } else {
-                            log::warn!("shortcut: quick window missing");
-                        }
+                        // Only configured bindings are registered, so an unmatched press can
+                        // only be the quick window one spelled differently.
+                        let action = shortcut_bindings(&state.settings())
+                            .into_iter()
+                            .find(|(_, binding)| {
+                                binding
+                                    .parse::<Shortcut>()
+                                    .is_ok_and(|parsed| parsed.id() == shortcut.id())
+                            })
+                            .map_or(ShortcutAction::ShowQuick, |(action, _)| action);
+                        run_shortcut_action(app, action);
                     }
                 })
                 .build(),
@@ -104,11 +222,38 @@ pub fn run() {
                 app_data_dir.display()
             );
 
-            let storage = Storage::new(app_data_dir.clone());
+            let guest = crate::data_dir::guest_mode_requested(
+                std::env::args(),
+                std::env::var(crate::data_dir::GUEST_ENV).ok(),
+            );
+            let data_dir = if guest {
+                crate::storage::use_memory_backend();
+                let root = crate::data_dir::guest_root();
+                let _ = std::fs::remove_dir_all(&root);
+                std::fs::create_dir_all(&root)?;
+                log::info!(
+                    "boot: guest mode (in-memory storage, nothing persisted) scratch={}",
+                    root.display()
+                );
+                root
+            } else {
+                crate::data_dir::resolve_data_dir(&app_data_dir, crate::data_dir::env_override())
+            };
+            if !guest && data_dir != app_data_dir {
+                log::info!("boot: data directory relocated to {}", data_dir.display());
+            }
+            let profile_id = crate::profiles::load_registry(&data_dir).active;
+            let data_dir = crate::profiles::profile_root(&data_dir, &profile_id);
+            log::info!(
+                "boot: profile id={} root={}",
+                profile_id,
+                data_dir.display()
+            );
+            let storage = Storage::new(data_dir.clone());
             storage.ensure_dirs().map_err(|err| {
                 log::error!(
                     "boot: ensure_dirs failed root={} err={} elapsed_ms={}",
-                    app_data_dir.display(),
+                    data_dir.display(),
                     err,
                     boot.elapsed().as_millis()
                 );
@@ -116,59 +261,48 @@ pub fn run() {
             })?;
             log::info!(
                 "boot: ensure_dirs ok root={} elapsed_ms={}",
-                app_data_dir.display(),
+                data_dir.display(),
                 boot.elapsed().as_millis()
             );
 
-            let data_path = app_data_dir.join("data.json");
-            let tasks_file = match storage.load_tasks() {
-                Ok(file) => {
-                    log::info!(
-                        "boot: loaded data.json schema_version={} tasks={} projects={} elapsed_ms={}",
-                        file.schema_version,
-                        file.tasks.len(),
-                        file.projects.len(),
-                        boot.elapsed().as_millis()
-                    );
-                    file
-                }
-                Err(err) => {
-                    match &err {
-                        crate::storage::StorageError::Io(io_err)
-                            if io_err.kind() == std::io::ErrorKind::NotFound =>
-                        {
-                            log::info!(
-                                "boot: data.json missing path={} -> defaults elapsed_ms={}",
-                                data_path.display(),
-                                boot.elapsed().as_millis()
-                            );
-                        }
-                        _ => {
-                            log::warn!(
-                                "boot: failed to load data.json path={} -> defaults err={} elapsed_ms={}",
-                                data_path.display(),
-                                err,
-                                boot.elapsed().as_millis()
-                            );
-                        }
-                    }
-                    crate::models::TasksFile {
-                        schema_version: 1,
-                        tasks: Vec::new(),
-                        projects: Vec::new(),
-                    }
-                }
-            };
+            let data_path = data_dir.join("data.json");
+            // A corrupt data.json is quarantined and recovered from the newest readable backup
+            // instead of being silently replaced by defaults on the next persist.
+            let (tasks_file, recovery) =
+                crate::recovery::load_tasks_or_recover(&storage, chrono::Local::now());
+            match &recovery {
+                None => log::info!(
+                    "boot: loaded data.json path={} schema_version={} tasks={} projects={} elapsed_ms={}",
+                    data_path.display(),
+                    tasks_file.schema_version,
+                    tasks_file.tasks.len(),
+                    tasks_file.projects.len(),
+                    boot.elapsed().as_millis()
+                ),
+                Some(status) => log::warn!(
+                    "boot: data.json recovered path={} quarantined_as={:?} recovered_from={:?} tasks={} elapsed_ms={}",
+                    data_path.display(),
+                    status.quarantined_as,
+                    status.recovered_from,
+                    tasks_file.tasks.len(),
+                    boot.elapsed().as_millis()
+                ),
+            }
+            if let Err(err) = crate::attachments::collect_garbage(&storage, &tasks_file) {
+                log::warn!("boot: attachment cleanup failed err={err}");
+            }
             let tasks = tasks_file.tasks;
             let projects = tasks_file.projects;
+            let trash = tasks_file.trash;
+            let tag_defs = tasks_file.tags;
 
-            let settings_path = app_data_dir.join("settings.json");
+            let settings_path = data_dir.join("settings.json");
             let mut settings_missing = false;
             let settings_file = match storage.load_settings() {
                 Ok(file) => {
                     let settings = &file.settings;
                     log::info!(
-                        "boot: loaded settings.json schema_version={} theme={} language={} close_behavior={:?} minimize_behavior={:?} backup_schedule={:?} update_behavior={:?} shortcut={} ai_enabled={} deepseek_key_present={} elapsed_ms={}",
+                        "boot: loaded settings.json schema_version={} theme={} language={} close_behavior={:?} minimize_behavior={:?} backup_schedule={:?} update_behavior={:?} shortcut={} ai_enabled={} ai_provider={:?} ai_key_stored={} elapsed_ms={}",
                         file.schema_version,
                         settings.theme,
                         settings.language,
@@ -178,7 +312,8 @@ pub fn run() {
                         settings.update_behavior,
                         settings.shortcut,
                         settings.ai_enabled,
-                        !settings.deepseek_api_key.trim().is_empty(),
+                        settings.ai_provider,
+                        settings.ai_api_key_set,
                         boot.elapsed().as_millis()
                     );
                     file
@@ -219,11 +354,14 @@ pub fn run() {
                 // First run: ensure settings.json is created so future loads are deterministic.
                 settings_dirty = true;
             }
+            if settings.storage_engine != storage.engine() {
+                // The data directory decides the engine; keep the setting in step with it.
+                settings.storage_engine = storage.engine();
+                settings_dirty = true;
+            }
             let original_shortcut = settings.shortcut.clone();
             let original_language = settings.language.clone();
-            let trimmed_shortcut = settings.shortcut.trim().to_string();
-            if trimmed_shortcut != settings.shortcut {
-                settings.shortcut = trimmed_shortcut;
+            if crate::shortcuts::normalize_shortcuts(&mut settings) {
                 settings_dirty = true;
             }
 
@@ -250,13 +388,15 @@ pub fn run() {
                 settings_dirty = true;
             }
             if settings.ai_model.is_empty() {
-                settings.ai_model = crate::models::Settings::default().ai_model;
+                settings.ai_model = crate::ai::providers::provider(settings.ai_provider)
+                    .default_model()
+                    .to_string();
                 settings_dirty = true;
             }
-            let trimmed_deepseek_key = settings.deepseek_api_key.trim().to_string();
-            if trimmed_deepseek_key != settings.deepseek_api_key {
-                settings.deepseek_api_key = trimmed_deepseek_key;
-                settings_dirty = true;
+            // Credentials belong in the keychain/secrets, not settings.json.
+            match crate::secrets::migrate_plaintext_secrets(&data_dir, &mut settings) {
+                Ok(changed) => settings_dirty |= changed,
+                Err(err) => log::error!("boot: secrets migration failed err={err}"),
             }
 
             if settings.migrate_ai_prompt_if_legacy_default() {
@@ -299,6 +439,38 @@ pub fn run() {
                     }
                 }
             };
+            // The per-action bindings are optional: one that no longer parses or clashes with
+            // an earlier binding is unbound rather than blocking boot.
+            let mut action_shortcuts: Vec<(ShortcutAction, Shortcut)> = Vec::new();
+            for (action, binding) in shortcut_bindings(&settings) {
+                if action == ShortcutAction::ShowQuick {
+                    continue;
+                }
+                let parsed = binding.parse::<Shortcut>().ok().filter(|parsed| {
+                    shortcut.is_none_or(|main| main.id() != parsed.id())
+                        && action_shortcuts
+                            .iter()
+                            .all(|(_, other)| other.id() != parsed.id())
+                });
+                match parsed {
+                    Some(parsed) => action_shortcuts.push((action, parsed)),
+                    None => {
+                        log::warn!(
+                            "boot: unbinding invalid or conflicting shortcut action={} shortcut={binding}",
+                            action.setting_name()
+                        );
+                        match action {
+                            ShortcutAction::QuickAdd => settings.shortcut_quick_add.clear(),
+                            ShortcutAction::ToggleMain => settings.shortcut_toggle_main.clear(),
+                            ShortcutAction::CompleteFocus => {
+                                settings.shortcut_complete_focus.clear()
+                            }
+                            ShortcutAction::ShowQuick => {}
+                        }
+                        settings_dirty = true;
+                    }
+                }
+            }
 
             log::info!(
                 "loaded state tasks={} projects={} theme={} language={} close_behavior={:?} backup_schedule={:?}",
@@ -311,6 +483,9 @@ pub fn run() {
             );
 
             let state = AppState::new(tasks, projects, settings);
+            state.replace_trash(trash);
+            state.replace_tags(tag_defs);
+            state.set_recovery_status(recovery);
             app.manage(state.clone());
 
             // Create the main window programmatically so we can enable transparency on non-macOS
@@ -374,12 +549,12 @@ pub fn run() {
             }
 
             log::info!("boot: init tray elapsed_ms={}", boot.elapsed().as_millis());
-            init_tray(app, &state.settings()).map_err(|err| {
+            init_tray(app, &state.settings(), &state.projects()).map_err(|err| {
                 log::error!("boot: init tray failed: {err}");
                 err
             })?;
             log::info!("boot: tray ready elapsed_ms={}", boot.elapsed().as_millis());
-            update_tray_count(app.handle(), &state.tasks(), &state.settings());
+            update_tray_count(app.handle(), &state.shared_tasks(), &state.settings());
 
             if let Some(shortcut) = shortcut {
                 match app.handle().global_shortcut().register(shortcut) {
@@ -395,6 +570,14 @@ pub fn run() {
                     }
                 }
             }
+            for (action, shortcut) in action_shortcuts {
+                if let Err(err) = app.handle().global_shortcut().register(shortcut) {
+                    log::warn!(
+                        "failed to register global shortcut action={}: {err}",
+                        action.setting_name()
+                    );
+                }
+            }
 
             if settings_dirty {
                 if let Err(err) = storage.save_settings(&state.settings_file()) {
@@ -404,6 +587,16 @@ pub fn run() {
                 }
             }
             start_scheduler(app.handle().clone(), state.clone());
+            // Writes what `persist` queued once edits go quiet (see `write_queue`).
+            let writer = state.clone();
+            let writer_app = app.handle().clone();
+            std::thread::Builder::new()
+                .name("write-behind".into())
+                .spawn(move || loop {
+                    writer.write_queue().wait_until_due();
+                    let _span = crate::logging::LogSpan::background("write_behind");
+                    flush_write_behind(&writer_app, &writer);
+                })?;
             log::info!(
                 "boot: setup completed elapsed_ms={}",
                 boot.elapsed().as_millis()
@@ -460,28 +653,147 @@ pub fn run() {
             delete_project,
             create_task,
             ai_plan_task,
+            ai_weekly_review,
             update_task,
             bulk_update_tasks,
             swap_sort_order,
+            pin_task,
+            unpin_task,
+            mute_project,
+            unmute_project,
+            add_comment,
+            delete_comment,
+            promote_step_to_task,
+            convert_task_to_step,
+            start_timer,
+            stop_timer,
+            report_time,
+            unblock_chain,
+            add_attachment,
+            remove_attachment,
+            open_attachment,
             complete_task,
             bulk_complete_tasks,
             update_settings,
             show_settings_window,
             frontend_log,
             snooze_task,
+            snooze_task_preset,
+            notification_action,
+            set_do_not_disturb,
             dismiss_forced,
             delete_task,
             delete_tasks,
+            list_trash,
+            restore_from_trash,
+            empty_trash,
+            list_archived,
+            search_archive,
+            unarchive_task,
             list_backups,
+            estimate_backup_archive,
+            export_all_backups,
             delete_backup,
             create_backup,
             restore_backup,
+            flush_storage,
+            get_write_status,
+            preview_backup_merge,
+            merge_backup,
             import_backup,
+            import_external,
+            import_markdown,
             export_tasks_json,
+            export_tasks_content,
             export_tasks_csv,
             export_tasks_markdown,
+            export_tasks_ics,
+            export_markdown_sync,
+            export_year_review,
+            export_to_obsidian,
+            sync_markdown_export,
             set_shortcut_capture_active,
+            validate_shortcut,
+            query_tasks,
+            list_waiting_for,
+            list_contexts,
+            get_burndown,
+            get_statistics,
+            get_focus_stats,
+            search_tasks,
+            undo_last_action,
+            redo_last_action,
+            get_undo_status,
+            suggest_next_task,
+            get_recovery_status,
+            events_manifest,
+            infer_task_from_text,
+            format_dates,
+            resolve_quick_due,
+            list_someday_tasks,
+            list_scheduled_tasks,
+            move_to_someday,
+            move_from_someday,
+            project_tree,
+            list_smart_lists,
+            create_smart_list,
+            update_smart_list,
+            delete_smart_list,
+            evaluate_smart_list,
+            list_automation_rules,
+            create_automation_rule,
+            update_automation_rule,
+            delete_automation_rule,
+            list_tags,
+            suggest_tags,
+            rename_tag,
+            merge_tags,
+            list_tag_definitions,
+            create_tag,
+            update_tag,
+            delete_tag,
+            get_note_history,
+            restore_note_version,
+            get_task_activity,
+            get_recent_activity,
+            get_view_prefs,
+            set_view_prefs,
+            set_smtp_password,
+            set_mqtt_password,
+            set_caldav_password,
+            set_secret,
+            clear_secret,
+            sync_now,
+            send_test_email,
+            list_email_deliveries,
+            list_carry_over_log,
+            start_focus_session,
+            pause_focus,
+            resume_focus,
+            stop_focus,
+            get_focus_status,
+            list_focus_sessions,
+            list_reminder_audit,
+            list_sounds,
+            preview_sound,
+            run_maintenance_now,
+            set_data_directory,
+            list_profiles,
+            create_profile,
+            switch_profile,
+            is_guest_mode,
+            advance_clock,
+            test_fire_reminder,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Nothing queued by write-behind may be lost on quit.
+                match flush_writes(app.state::<AppState>().inner()) {
+                    Ok(written) => log::info!("exit: storage flushed written={written}"),
+                    Err(err) => log::error!("exit: storage flush failed err={err}"),
+                }
+            }
+        });
 }
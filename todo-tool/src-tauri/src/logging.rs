@@ -1,10 +1,160 @@
+use std::cell::RefCell;
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::Instant;
 
 pub const LOG_FILE_BASENAME: &str = "mustdo";
 pub const LOG_FILE_SUFFIX: &str = "log";
 pub const LOG_ROTATE_SIZE_BYTES: u64 = 100 * 1024 * 1024;
 pub const LOG_ROTATE_KEEP_FILES: usize = 30;
 
+/// `MUSTDO_LOG_FORMAT=json` switches the log files to one JSON object per line.
+pub const LOG_FORMAT_ENV: &str = "MUSTDO_LOG_FORMAT";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl LogFormat {
+    pub fn parse(value: Option<&str>) -> Self {
+        match value.map(|value| value.trim().to_ascii_lowercase()) {
+            Some(value) if value == "json" => LogFormat::Json,
+            _ => LogFormat::Text,
+        }
+    }
+}
+
+/// Who a log line belongs to: the invoked command (or background job) and its correlation id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogContext {
+    pub command: String,
+    pub correlation_id: String,
+    /// Only set on the span's closing line.
+    pub duration_ms: Option<u128>,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<LogContext>> = const { RefCell::new(None) };
+}
+
+/// Unique per process run: launch time in ms plus a counter.
+fn next_correlation_id() -> String {
+    static LAUNCH: OnceLock<u128> = OnceLock::new();
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let launch = LAUNCH.get_or_init(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|dur| dur.as_millis())
+            .unwrap_or(0)
+    });
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed) + 1;
+    format!("{launch:x}-{n}")
+}
+
+pub fn current_context() -> Option<LogContext> {
+    CURRENT.with(|current| current.borrow().clone())
+}
+
+/// Tags every log line on this thread with a fresh correlation id until dropped, so persist and
+/// storage logs can be traced back to the command that caused them. Only for synchronous code:
+/// the context is thread-local and does not follow an `.await`.
+pub struct LogSpan {
+    started: Instant,
+    previous: Option<LogContext>,
+    log_finish: bool,
+}
+
+impl LogSpan {
+    /// Span for an invoked command; logs the duration when it ends.
+    pub fn command(name: &str) -> Self {
+        Self::enter(name, true)
+    }
+
+    /// Span for a background pass (scheduler tick); ends silently so idle ticks stay quiet.
+    pub fn background(name: &str) -> Self {
+        Self::enter(name, false)
+    }
+
+    fn enter(name: &str, log_finish: bool) -> Self {
+        let context = LogContext {
+            command: name.to_string(),
+            correlation_id: next_correlation_id(),
+            duration_ms: None,
+        };
+        let previous = CURRENT.with(|current| current.borrow_mut().replace(context));
+        Self {
+            started: Instant::now(),
+            previous,
+            log_finish,
+        }
+    }
+}
+
+impl Drop for LogSpan {
+    fn drop(&mut self) {
+        if self.log_finish {
+            let duration_ms = self.started.elapsed().as_millis();
+            let command = CURRENT.with(|current| {
+                let mut current = current.borrow_mut();
+                let context = current.as_mut()?;
+                context.duration_ms = Some(duration_ms);
+                Some(context.command.clone())
+            });
+            if let Some(command) = command {
+                log::info!("cmd={command} done duration_ms={duration_ms}");
+            }
+        }
+        let previous = self.previous.take();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+/// One log record as a JSON line; context fields are omitted outside a span.
+pub fn json_line(
+    timestamp: &str,
+    level: log::Level,
+    module: Option<&str>,
+    message: &str,
+    context: Option<&LogContext>,
+) -> String {
+    let mut line = serde_json::Map::new();
+    line.insert("ts".into(), timestamp.into());
+    line.insert("level".into(), level.as_str().into());
+    line.insert("module".into(), module.unwrap_or_default().into());
+    if let Some(context) = context {
+        line.insert("command".into(), context.command.as_str().into());
+        line.insert(
+            "correlation_id".into(),
+            context.correlation_id.as_str().into(),
+        );
+        if let Some(duration_ms) = context.duration_ms {
+            line.insert("duration_ms".into(), (duration_ms as u64).into());
+        }
+    }
+    line.insert("msg".into(), message.into());
+    serde_json::Value::Object(line).to_string()
+}
+
+#[cfg(all(feature = "app", not(test)))]
+fn json_format(
+    w: &mut dyn std::io::Write,
+    now: &mut flexi_logger::DeferredNow,
+    record: &log::Record,
+) -> std::io::Result<()> {
+    let timestamp = now.now().format("%Y-%m-%dT%H:%M:%S%.3f%:z").to_string();
+    let line = json_line(
+        &timestamp,
+        record.level(),
+        record.module_path(),
+        &record.args().to_string(),
+        current_context().as_ref(),
+    );
+    w.write_all(line.as_bytes())
+}
+
 /// Returns the directory that contains user-facing app data (data.json/settings.json/backups/)
 /// and, by design, the log files.
 pub fn log_directory(app_data_dir: &Path) -> &Path {
@@ -36,6 +186,12 @@ pub fn init_logging(app_data_dir: &Path) -> Result<(), flexi_logger::FlexiLogger
         })
         .unwrap_or_else(|| default_spec.to_string());
 
+    let format = LogFormat::parse(std::env::var(LOG_FORMAT_ENV).ok().as_deref());
+    let file_format: flexi_logger::FormatFunction = match format {
+        LogFormat::Json => json_format,
+        LogFormat::Text => detailed_format,
+    };
+
     Logger::try_with_str(spec)?
         .log_to_file(
             FileSpec::default()
@@ -44,7 +200,7 @@ pub fn init_logging(app_data_dir: &Path) -> Result<(), flexi_logger::FlexiLogger
                 .suffix(LOG_FILE_SUFFIX),
         )
         .write_mode(WriteMode::BufferAndFlush)
-        .format_for_files(detailed_format)
+        .format_for_files(file_format)
         .rotate(
             Criterion::Size(LOG_ROTATE_SIZE_BYTES),
             Naming::Numbers,
@@ -61,8 +217,9 @@ pub fn init_logging(app_data_dir: &Path) -> Result<(), flexi_logger::FlexiLogger
     install_panic_hook();
 
     log::info!(
-        "logger initialized dir={} rotate_size_bytes={} keep_files={}",
+        "logger initialized dir={} format={:?} rotate_size_bytes={} keep_files={}",
         log_directory(app_data_dir).display(),
+        format,
         LOG_ROTATE_SIZE_BYTES,
         LOG_ROTATE_KEEP_FILES
     );
@@ -90,3 +247,81 @@ fn install_panic_hook() {
         default_hook(info);
     }));
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_files_live_in_the_data_directory() {
+        let root = Path::new("/tmp/mustdo");
+        assert_eq!(log_directory(root), root);
+        assert_eq!(
+            format!("{LOG_FILE_BASENAME}.{LOG_FILE_SUFFIX}"),
+            "mustdo.log"
+        );
+        assert_eq!(LOG_ROTATE_SIZE_BYTES, 100 * 1024 * 1024);
+        assert_eq!(LOG_ROTATE_KEEP_FILES, 30);
+    }
+
+    #[test]
+    fn log_format_is_text_unless_json_is_requested() {
+        assert_eq!(LOG_FORMAT_ENV, "MUSTDO_LOG_FORMAT");
+        assert_eq!(LogFormat::parse(None), LogFormat::Text);
+        assert_eq!(LogFormat::parse(Some("")), LogFormat::Text);
+        assert_eq!(LogFormat::parse(Some(" JSON ")), LogFormat::Json);
+        assert_eq!(LogFormat::parse(Some("yaml")), LogFormat::Text);
+    }
+
+    #[test]
+    fn spans_nest_and_restore_the_outer_context() {
+        assert_eq!(current_context(), None);
+        let outer = LogSpan::command("create_task");
+        let outer_ctx = current_context().unwrap();
+        assert_eq!(outer_ctx.command, "create_task");
+        assert_eq!(outer_ctx.duration_ms, None);
+        {
+            let _inner = LogSpan::background("scheduler");
+            let inner_ctx = current_context().unwrap();
+            assert_eq!(inner_ctx.command, "scheduler");
+            assert_ne!(inner_ctx.correlation_id, outer_ctx.correlation_id);
+        }
+        assert_eq!(current_context(), Some(outer_ctx));
+        drop(outer);
+        assert_eq!(current_context(), None);
+
+        // Other threads never see this thread's span.
+        let _span = LogSpan::command("persist");
+        assert_eq!(std::thread::spawn(current_context).join().unwrap(), None);
+    }
+
+    #[test]
+    fn json_line_carries_context_fields() {
+        let context = LogContext {
+            command: "update_task".to_string(),
+            correlation_id: "abc-1".to_string(),
+            duration_ms: Some(12),
+        };
+        let line = json_line(
+            "2026-03-18T09:30:00.000+08:00",
+            log::Level::Info,
+            Some("todo_tool_lib::commands"),
+            "cmd=update_task \"quoted\"\nnext",
+            Some(&context),
+        );
+        assert!(!line.contains('\n'));
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["level"], "INFO");
+        assert_eq!(value["module"], "todo_tool_lib::commands");
+        assert_eq!(value["command"], "update_task");
+        assert_eq!(value["correlation_id"], "abc-1");
+        assert_eq!(value["duration_ms"], 12);
+        assert_eq!(value["msg"], "cmd=update_task \"quoted\"\nnext");
+
+        let bare: serde_json::Value =
+            serde_json::from_str(&json_line("t", log::Level::Warn, None, "x", None)).unwrap();
+        assert!(bare.get("command").is_none());
+        assert!(bare.get("duration_ms").is_none());
+        assert_eq!(bare["module"], "");
+    }
+}
@@ -0,0 +1,189 @@
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::{DateTime, Local, TimeZone, Timelike};
+
+use crate::activity::compact_activity;
+use crate::carry_over::compact_carry_over_log;
+use crate::digest::compact_reminder_audit;
+use crate::email::compact_delivery_log;
+use crate::focus::compact_focus_log;
+use crate::models::Timestamp;
+use crate::note_history::compact_note_history;
+use crate::state::AppState;
+use crate::storage::{Storage, StorageError};
+use crate::task_archive::archive_completed;
+
+/// Local hour after which the nightly job may run (it runs at most once per calendar day).
+const MAINTENANCE_HOUR: u32 = 3;
+const STALE_TEMP_AGE: Duration = Duration::from_secs(60 * 60);
+
+type CompactLog = fn(&Path) -> Result<usize, StorageError>;
+
+/// Every side log in the data root. Appends never trim them; this job does.
+const SIDE_LOGS: [(&str, CompactLog); 6] = [
+    ("email delivery log", compact_delivery_log),
+    ("reminder audit", compact_reminder_audit),
+    ("focus log", compact_focus_log),
+    ("carry-over log", compact_carry_over_log),
+    ("note history", compact_note_history),
+    ("activity log", compact_activity),
+];
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct MaintenanceReport {
+    pub ran_at: Timestamp,
+    pub removed_temp_files: usize,
+    pub compacted_log_lines: usize,
+    pub pruned_view_prefs: usize,
+    /// Completed tasks moved to the yearly archive files (`archive_after_days`).
+    pub archived_tasks: usize,
+    /// Steps that failed; the remaining steps still run.
+    pub errors: Vec<String>,
+}
+
+/// True once per local day, from `MAINTENANCE_HOUR` on. A machine that was asleep at that
+/// hour catches up on the first tick after waking.
+pub fn maintenance_due(last_run: Option<Timestamp>, now: DateTime<Local>) -> bool {
+    if now.hour() < MAINTENANCE_HOUR {
+        return false;
+    }
+    let Some(last_run) = last_run else {
+        return true;
+    };
+    Local
+        .timestamp_opt(last_run, 0)
+        .single()
+        .map(|last| last.date_naive() != now.date_naive())
+        .unwrap_or(true)
+}
+
+/// Runs every cleanup step against `root` and records `last_maintenance_at` in settings.
+///
+/// Only in-memory state is changed here, apart from the archive files; callers persist the
+/// tasks and settings afterwards.
+pub fn run_maintenance(root: &Path, state: &AppState, now: Timestamp) -> MaintenanceReport {
+    let mut report = MaintenanceReport {
+        ran_at: now,
+        ..MaintenanceReport::default()
+    };
+
+    match Storage::new(root.to_path_buf()).remove_stale_temp_files(STALE_TEMP_AGE) {
+        Ok(count) => report.removed_temp_files = count,
+        Err(err) => report.errors.push(format!("temp files: {err}")),
+    }
+    for (name, compact) in SIDE_LOGS {
+        match compact(root) {
+            Ok(count) => report.compacted_log_lines += count,
+            Err(err) => report.errors.push(format!("{name}: {err}")),
+        }
+    }
+    match archive_completed(root, state, now) {
+        Ok(count) => report.archived_tasks = count,
+        Err(err) => report.errors.push(format!("task archive: {err}")),
+    }
+
+    let snapshot = state.snapshot();
+    let mut settings = snapshot.settings;
+    let before = settings.view_prefs.len();
+    settings
+        .view_prefs
+        .retain(|project_id, _| snapshot.projects.iter().any(|p| &p.id == project_id));
+    report.pruned_view_prefs = before - settings.view_prefs.len();
+    settings.last_maintenance_at = Some(now);
+    state.update_settings(settings);
+
+    log::info!(
+        "maintenance: done temp_files={} log_lines={} view_prefs={} archived={} errors={}",
+        report.removed_temp_files,
+        report.compacted_log_lines,
+        report.pruned_view_prefs,
+        report.archived_tasks,
+        report.errors.len()
+    );
+    for error in &report.errors {
+        log::warn!("maintenance: step failed err={error}");
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Project, Settings, ViewPrefs};
+    use std::fs;
+
+    fn at(hour: u32, day: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap()
+    }
+
+    fn project(id: &str) -> Project {
+        Project {
+            id: id.to_string(),
+            name: id.to_string(),
+            pinned: false,
+            sort_order: 0,
+            created_at: 0,
+            updated_at: 0,
+            sample_tag: None,
+            reminders_muted: false,
+            muted_until: None,
+            notification_sound: None,
+            color: None,
+            icon: None,
+            parent_id: None,
+        }
+    }
+
+    #[test]
+    fn maintenance_due_once_per_day_after_the_nightly_hour() {
+        assert!(!maintenance_due(None, at(2, 10)));
+        assert!(maintenance_due(None, at(3, 10)));
+        let ran = at(4, 10).timestamp();
+        assert!(!maintenance_due(Some(ran), at(23, 10)));
+        assert!(!maintenance_due(Some(ran), at(1, 11)));
+        assert!(maintenance_due(Some(ran), at(9, 11)));
+        assert!(maintenance_due(Some(i64::MAX), at(9, 11)));
+    }
+
+    #[test]
+    fn run_maintenance_cleans_files_prunes_prefs_and_records_run() {
+        let root = tempfile::tempdir().unwrap();
+        let mut settings = Settings::default();
+        settings
+            .view_prefs
+            .insert("kept".to_string(), ViewPrefs::default());
+        settings
+            .view_prefs
+            .insert("gone".to_string(), ViewPrefs::default());
+        let state = AppState::new(Vec::new(), vec![project("kept")], settings);
+        let side_logs = [
+            "email_deliveries.jsonl",
+            "reminder_audit.jsonl",
+            "focus_sessions.jsonl",
+            "carry_over.jsonl",
+            "note_history.jsonl",
+            "activity.jsonl",
+        ];
+        for log in side_logs {
+            fs::write(root.path().join(log), "garbage\n").unwrap();
+        }
+
+        let report = run_maintenance(root.path(), &state, 1234);
+        assert_eq!(report.ran_at, 1234);
+        assert_eq!(report.compacted_log_lines, side_logs.len());
+        assert_eq!(report.pruned_view_prefs, 1);
+        assert!(report.errors.is_empty());
+        let settings = state.settings();
+        assert_eq!(settings.last_maintenance_at, Some(1234));
+        assert!(settings.view_prefs.contains_key("kept"));
+
+        // Failing steps are reported without aborting the run.
+        let broken = tempfile::tempdir().unwrap();
+        fs::create_dir(broken.path().join("email_deliveries.jsonl")).unwrap();
+        let report = run_maintenance(broken.path(), &state, 2000);
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.errors[0].starts_with("email delivery log"));
+        assert_eq!(state.settings().last_maintenance_at, Some(2000));
+    }
+}
@@ -0,0 +1,326 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::models::Task;
+
+/// The designated export the watcher reads back, inside `exports/`.
+pub const SYNC_FILE: &str = "mustdo-sync.md";
+/// Copy of the sync file as last written or reconciled; edits are diffed against it.
+const SYNC_BASELINE_FILE: &str = "mustdo-sync.base.md";
+const MARKER_PREFIX: &str = "<!-- mustdo:";
+const MARKER_SUFFIX: &str = "-->";
+
+pub fn sync_file_path(root: &Path) -> PathBuf {
+    root.join("exports").join(SYNC_FILE)
+}
+
+pub fn baseline_path(root: &Path) -> PathBuf {
+    root.join("exports").join(SYNC_BASELINE_FILE)
+}
+
+/// Cheap change check for the watcher; `None` when the file is missing.
+pub fn sync_file_modified(root: &Path) -> Option<SystemTime> {
+    fs::metadata(sync_file_path(root))
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// Trailing marker that ties an exported checkbox line to its task.
+pub fn task_marker(id: &str) -> String {
+    format!("{MARKER_PREFIX}{id} {MARKER_SUFFIX}")
+}
+
+/// A top-level `- [ ]` / `- [x]` line; step lines are indented and never match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyncLine {
+    /// 1-based line number.
+    pub line: usize,
+    pub checked: bool,
+    /// Text between the checkbox and the marker (title plus the due suffix).
+    pub text: String,
+    pub id: Option<String>,
+}
+
+fn parse_line(line: &str) -> Option<(bool, String, Option<String>)> {
+    let rest = line.strip_prefix("- [")?;
+    let (checked, rest) = match rest.get(..2)? {
+        " ]" => (false, &rest[2..]),
+        "x]" | "X]" => (true, &rest[2..]),
+        _ => return None,
+    };
+    let rest = rest.trim_end();
+    let (text, id) = match rest.rfind(MARKER_PREFIX) {
+        Some(start) => {
+            let id = rest[start + MARKER_PREFIX.len()..]
+                .strip_suffix(MARKER_SUFFIX)
+                .map(str::trim)
+                .filter(|id| !id.is_empty() && !id.contains(char::is_whitespace));
+            match id {
+                Some(id) => (&rest[..start], Some(id.to_string())),
+                None => (rest, None),
+            }
+        }
+        None => (rest, None),
+    };
+    Some((checked, text.trim().to_string(), id))
+}
+
+pub fn parse_sync_markdown(content: &str) -> Vec<SyncLine> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            parse_line(line).map(|(checked, text, id)| SyncLine {
+                line: index + 1,
+                checked,
+                text,
+                id,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncConflictKind {
+    /// A checkbox line without a task marker; new tasks are not created from the file.
+    Added,
+    /// A task line from the export is gone.
+    Removed,
+    /// The same marker appears more than once.
+    Duplicated,
+    /// The marker does not belong to this export.
+    UnknownTask,
+    /// The line text was edited; titles are not synced back.
+    TextChanged,
+    /// A completed task was unchecked; reopening is not synced back.
+    Unchecked,
+    /// Checked in the file, but the task no longer exists.
+    TaskMissing,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct SyncConflict {
+    pub kind: SyncConflictKind,
+    /// Line in the edited file; `None` for removed lines.
+    pub line: Option<usize>,
+    pub task_id: Option<String>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct MarkdownSyncReport {
+    /// Tasks checked off in the file that were still open, in file order.
+    pub completed: Vec<String>,
+    pub conflicts: Vec<SyncConflict>,
+}
+
+impl MarkdownSyncReport {
+    pub fn is_empty(&self) -> bool {
+        self.completed.is_empty() && self.conflicts.is_empty()
+    }
+}
+
+/// Diffs the edited file against the baseline. Only unchecked -> checked toggles turn into
+/// completions; everything else that changed is reported, never applied.
+pub fn plan_sync(baseline: &str, edited: &str, tasks: &[Task]) -> MarkdownSyncReport {
+    let base_lines = parse_sync_markdown(baseline);
+    // First occurrence per marker, matching how the edited file is read.
+    let mut base: BTreeMap<&str, &SyncLine> = BTreeMap::new();
+    let mut base_counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for line in &base_lines {
+        if let Some(id) = line.id.as_deref() {
+            base.entry(id).or_insert(line);
+            *base_counts.entry(id).or_default() += 1;
+        }
+    }
+    let base_unmarked: BTreeSet<(bool, &str)> = base_lines
+        .iter()
+        .filter(|line| line.id.is_none())
+        .map(|line| (line.checked, line.text.as_str()))
+        .collect();
+
+    let mut plan = MarkdownSyncReport::default();
+    let mut seen: BTreeMap<String, usize> = BTreeMap::new();
+    let conflict = |kind, line: &SyncLine| SyncConflict {
+        kind,
+        line: Some(line.line),
+        task_id: line.id.clone(),
+        text: line.text.clone(),
+    };
+
+    for line in parse_sync_markdown(edited) {
+        let Some(id) = line.id.as_deref() else {
+            // Already reported when it first appeared.
+            if !base_unmarked.contains(&(line.checked, line.text.as_str())) {
+                plan.conflicts
+                    .push(conflict(SyncConflictKind::Added, &line));
+            }
+            continue;
+        };
+        let count = seen.entry(id.to_string()).or_default();
+        *count += 1;
+        if *count > 1 {
+            // Copies already present in the baseline were reported before.
+            if *count > base_counts.get(id).copied().unwrap_or(0) {
+                plan.conflicts
+                    .push(conflict(SyncConflictKind::Duplicated, &line));
+            }
+            continue;
+        }
+        let Some(before) = base.get(id) else {
+            plan.conflicts
+                .push(conflict(SyncConflictKind::UnknownTask, &line));
+            continue;
+        };
+        if before.text != line.text {
+            plan.conflicts
+                .push(conflict(SyncConflictKind::TextChanged, &line));
+        }
+        match (before.checked, line.checked) {
+            (false, true) => match tasks.iter().find(|task| task.id == id) {
+                Some(task) if !task.completed => plan.completed.push(id.to_string()),
+                Some(_) => {}
+                None => plan
+                    .conflicts
+                    .push(conflict(SyncConflictKind::TaskMissing, &line)),
+            },
+            (true, false) => plan
+                .conflicts
+                .push(conflict(SyncConflictKind::Unchecked, &line)),
+            _ => {}
+        }
+    }
+
+    let mut removed: Vec<&SyncLine> = base
+        .iter()
+        .filter(|(id, _)| !seen.contains_key(**id))
+        .map(|(_, line)| *line)
+        .collect();
+    removed.sort_by_key(|line| line.line);
+    for line in removed {
+        plan.conflicts.push(SyncConflict {
+            kind: SyncConflictKind::Removed,
+            line: None,
+            task_id: line.id.clone(),
+            text: line.text.clone(),
+        });
+    }
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+
+    fn task(id: &str, completed: bool) -> Task {
+        test_support::task(id).completed(completed)
+    }
+
+    const BASE: &str = "# MustDo Export\n\n## Due today\n\n\
+- [ ] Call mom (due: 2026-03-18 18:00) <!-- mustdo:a -->\n\
+  - steps:\n    - [ ] dial <!-- mustdo:zzz -->\n\
+- [ ] Pay rent <!-- mustdo:b -->\n\
+- [ ] Water plants <!-- mustdo:c -->\n\
+\n## Completed\n\n\
+- [x] Ship it <!-- mustdo:d -->\n";
+
+    #[test]
+    fn parses_top_level_checkboxes_with_markers() {
+        let lines = parse_sync_markdown(BASE);
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[0],
+            SyncLine {
+                line: 5,
+                checked: false,
+                text: "Call mom (due: 2026-03-18 18:00)".to_string(),
+                id: Some("a".to_string()),
+            }
+        );
+        assert!(lines[3].checked);
+        assert_eq!(
+            parse_sync_markdown("- [X] done\n- [?] nope\n- [ ] bad <!-- mustdo: -->\n"),
+            [
+                SyncLine {
+                    line: 1,
+                    checked: true,
+                    text: "done".to_string(),
+                    id: None
+                },
+                SyncLine {
+                    line: 3,
+                    checked: false,
+                    text: "bad <!-- mustdo: -->".to_string(),
+                    id: None
+                },
+            ]
+        );
+        assert_eq!(task_marker("a"), "<!-- mustdo:a -->");
+    }
+
+    #[test]
+    fn checkbox_toggles_become_completions() {
+        let edited = BASE
+            .replace("- [ ] Call mom", "- [x] Call mom")
+            .replace("- [ ] Pay rent", "- [X] Pay rent");
+        let tasks = [
+            task("a", false),
+            task("b", true),
+            task("c", false),
+            task("d", true),
+        ];
+        let plan = plan_sync(BASE, &edited, &tasks);
+        // `b` was already completed in the app: nothing to do and nothing to report.
+        assert_eq!(plan.completed, ["a"]);
+        assert!(plan.conflicts.is_empty());
+        assert_eq!(plan_sync(BASE, BASE, &tasks), MarkdownSyncReport::default());
+    }
+
+    #[test]
+    fn structural_edits_are_reported_not_applied() {
+        let edited = BASE
+            .replace("- [ ] Pay rent <!-- mustdo:b -->\n", "")
+            .replace("Water plants", "Water all plants")
+            .replace("- [x] Ship it", "- [ ] Ship it")
+            .replace("- [ ] Call mom", "- [x] Call mom")
+            + "- [ ] New idea\n- [x] Ship it <!-- mustdo:d -->\n- [ ] Stray <!-- mustdo:zz -->\n";
+        let plan = plan_sync(BASE, &edited, &[task("c", false)]);
+        assert!(plan.completed.is_empty());
+        let kinds: Vec<_> = plan
+            .conflicts
+            .iter()
+            .map(|c| (c.kind, c.task_id.as_deref(), c.line))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (SyncConflictKind::TaskMissing, Some("a"), Some(5)),
+                (SyncConflictKind::TextChanged, Some("c"), Some(8)),
+                (SyncConflictKind::Unchecked, Some("d"), Some(12)),
+                (SyncConflictKind::Added, None, Some(13)),
+                (SyncConflictKind::Duplicated, Some("d"), Some(14)),
+                (SyncConflictKind::UnknownTask, Some("zz"), Some(15)),
+                (SyncConflictKind::Removed, Some("b"), None),
+            ]
+        );
+
+        assert!(!plan.is_empty());
+
+        // Once reconciled the edited file is the new baseline, so nothing is reported twice.
+        assert_eq!(plan_sync(&edited, &edited, &[]).conflicts, []);
+    }
+
+    #[test]
+    fn sync_file_modified_tracks_the_designated_export() {
+        let root = tempfile::tempdir().unwrap();
+        assert_eq!(sync_file_modified(root.path()), None);
+        fs::create_dir_all(root.path().join("exports")).unwrap();
+        fs::write(sync_file_path(root.path()), BASE).unwrap();
+        assert!(sync_file_modified(root.path()).is_some());
+        assert_ne!(sync_file_path(root.path()), baseline_path(root.path()));
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::dry_run::{changed_fields, same_project};
+use crate::models::{Project, Task, TasksFile, Timestamp};
+
+/// How a backup record compares with the current record that has the same id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeStatus {
+    /// Only in the backup (deleted or moved to the trash since).
+    Missing,
+    /// Both have it and the backup's copy was updated later; restoring it replaces the current
+    /// one.
+    BackupNewer,
+    /// Both have it and the current copy is at least as recent; restoring keeps it.
+    CurrentNewer,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MergeTask {
+    pub task: Task,
+    pub current: Option<Task>,
+    pub status: MergeStatus,
+    /// Top-level fields that differ from the current copy; empty when it is missing.
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MergeProject {
+    pub project: Project,
+    pub current: Option<Project>,
+    pub status: MergeStatus,
+}
+
+/// Backup records that differ from the current data, i.e. what a merge could bring back.
+/// Records only in the current data are not listed: a merge never deletes.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct MergePreview {
+    pub tasks: Vec<MergeTask>,
+    pub projects: Vec<MergeProject>,
+}
+
+/// The records to write for a merge: new or replacing ones, by id.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct MergePlan {
+    pub tasks: Vec<Task>,
+    pub projects: Vec<Project>,
+    /// Selected ids whose current copy is newer and was kept.
+    pub kept_current: Vec<String>,
+}
+
+fn status(current: Option<Timestamp>, backup: Timestamp) -> MergeStatus {
+    match current {
+        None => MergeStatus::Missing,
+        Some(current) if backup > current => MergeStatus::BackupNewer,
+        Some(_) => MergeStatus::CurrentNewer,
+    }
+}
+
+pub fn preview_merge(backup: &TasksFile, tasks: &[Task], projects: &[Project]) -> MergePreview {
+    let current_tasks: HashMap<&str, &Task> =
+        tasks.iter().map(|task| (task.id.as_str(), task)).collect();
+    let current_projects: HashMap<&str, &Project> = projects
+        .iter()
+        .map(|project| (project.id.as_str(), project))
+        .collect();
+    let mut preview = MergePreview::default();
+    for task in &backup.tasks {
+        let current = current_tasks.get(task.id.as_str()).copied();
+        let fields = current.map_or_else(Vec::new, |current| changed_fields(current, task));
+        if current.is_some() && fields.is_empty() {
+            continue;
+        }
+        preview.tasks.push(MergeTask {
+            task: task.clone(),
+            current: current.cloned(),
+            status: status(current.map(|current| current.updated_at), task.updated_at),
+            fields,
+        });
+    }
+    for project in &backup.projects {
+        let current = current_projects.get(project.id.as_str()).copied();
+        if current.is_some_and(|current| same_project(current, project)) {
+            continue;
+        }
+        preview.projects.push(MergeProject {
+            project: project.clone(),
+            current: current.cloned(),
+            status: status(
+                current.map(|current| current.updated_at),
+                project.updated_at,
+            ),
+        });
+    }
+    preview
+}
+
+/// Picks the selected backup records to write back. Where both sides have an id the one
+/// updated later wins. A restored task whose project exists in neither the current data nor
+/// the selection brings its backup project along, or lands in the inbox without one.
+pub fn plan_merge(
+    backup: &TasksFile,
+    tasks: &[Task],
+    projects: &[Project],
+    task_ids: &[String],
+    project_ids: &[String],
+) -> Result<MergePlan, String> {
+    let mut plan = MergePlan::default();
+    let find_project = |id: &str| backup.projects.iter().find(|project| project.id == id);
+    for id in project_ids.iter().collect::<BTreeSet<_>>() {
+        let project = find_project(id).ok_or_else(|| format!("project not in backup: {id}"))?;
+        let current = projects.iter().find(|current| current.id == *id);
+        match status(
+            current.map(|current| current.updated_at),
+            project.updated_at,
+        ) {
+            MergeStatus::CurrentNewer => plan.kept_current.push(id.clone()),
+            _ => plan.projects.push(project.clone()),
+        }
+    }
+    for id in task_ids.iter().collect::<BTreeSet<_>>() {
+        let task = backup
+            .tasks
+            .iter()
+            .find(|task| task.id == *id)
+            .ok_or_else(|| format!("task not in backup: {id}"))?;
+        let current = tasks.iter().find(|current| current.id == *id);
+        if status(current.map(|current| current.updated_at), task.updated_at)
+            == MergeStatus::CurrentNewer
+        {
+            plan.kept_current.push(id.clone());
+            continue;
+        }
+        let mut task = task.clone();
+        let known = |id: &str| {
+            projects.iter().any(|project| project.id == id)
+                || plan.projects.iter().any(|project| project.id == id)
+        };
+        if !known(&task.project_id) {
+            match find_project(&task.project_id) {
+                Some(project) => plan.projects.push(project.clone()),
+                None => task.project_id = "inbox".to_string(),
+            }
+        }
+        plan.tasks.push(task);
+    }
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+
+    fn task(id: &str, project_id: &str, updated_at: Timestamp) -> Task {
+        test_support::task(id)
+            .project(project_id)
+            .updated(updated_at)
+    }
+
+    fn project(id: &str, updated_at: Timestamp) -> Project {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": id,
+            "updated_at": updated_at
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn preview_lists_missing_and_changed_records_with_the_newer_side() {
+        let mut older = task("older", "inbox", 5);
+        older.title = "backup title".to_string();
+        let mut newer = task("newer", "inbox", 50);
+        newer.important = true;
+        let backup = TasksFile {
+            schema_version: 1,
+            tasks: vec![
+                task("gone", "work", 10),
+                older,
+                newer,
+                task("same", "inbox", 1),
+            ],
+            projects: vec![project("inbox", 1), project("work", 10)],
+            trash: Vec::new(),
+            tags: Vec::new(),
+        };
+        let tasks = vec![
+            task("older", "inbox", 20),
+            task("newer", "inbox", 20),
+            task("same", "inbox", 9),
+            task("extra", "inbox", 1),
+        ];
+        let projects = vec![project("inbox", 1)];
+
+        let preview = preview_merge(&backup, &tasks, &projects);
+        let rows: Vec<(&str, MergeStatus)> = preview
+            .tasks
+            .iter()
+            .map(|row| (row.task.id.as_str(), row.status))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("gone", MergeStatus::Missing),
+                ("older", MergeStatus::CurrentNewer),
+                ("newer", MergeStatus::BackupNewer),
+            ]
+        );
+        assert_eq!(preview.tasks[1].fields, vec!["title"]);
+        assert_eq!(preview.projects.len(), 1);
+        assert_eq!(preview.projects[0].project.id, "work");
+
+        let plan = plan_merge(
+            &backup,
+            &tasks,
+            &projects,
+            &["gone".into(), "older".into(), "newer".into()],
+            &[],
+        )
+        .unwrap();
+        let ids: Vec<&str> = plan.tasks.iter().map(|task| task.id.as_str()).collect();
+        assert_eq!(ids, vec!["gone", "newer"]);
+        assert_eq!(plan.kept_current, vec!["older"]);
+        // "gone" lived in a project that no longer exists; it comes back with it.
+        assert_eq!(plan.projects.len(), 1);
+        assert_eq!(plan.projects[0].id, "work");
+
+        assert!(plan_merge(&backup, &tasks, &projects, &["nope".into()], &[]).is_err());
+        let mut orphan = backup.clone();
+        orphan.projects.retain(|project| project.id != "work");
+        let plan = plan_merge(&orphan, &tasks, &projects, &["gone".into()], &[]).unwrap();
+        assert_eq!(plan.tasks[0].project_id, "inbox");
+    }
+}
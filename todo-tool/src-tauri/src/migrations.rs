@@ -0,0 +1,378 @@
+use chrono::{Datelike, Local, TimeZone};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::models::{ReminderConfig, ReminderKind, RepeatRule, Step, Task, TasksFile, Timestamp};
+
+/// Files written before `schema_version` existed (or with an explicit 0).
+const LEGACY_SCHEMA_VERSION: u32 = 0;
+const MIGRATED_SCHEMA_VERSION: u32 = 1;
+
+/// v0 data.json: either `{ "tasks": [...] }` without a version, or a bare task array.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum LegacyTasksFileV0 {
+    Wrapped {
+        #[serde(default)]
+        tasks: Vec<LegacyTaskV0>,
+    },
+    Bare(Vec<LegacyTaskV0>),
+}
+
+#[derive(Debug, Deserialize)]
+struct LegacyTaskV0 {
+    id: String,
+    title: String,
+    #[serde(default)]
+    project_id: Option<String>,
+    #[serde(default, alias = "due")]
+    due_at: Option<Timestamp>,
+    #[serde(default)]
+    important: bool,
+    #[serde(default, alias = "done")]
+    completed: bool,
+    #[serde(default, alias = "done_at")]
+    completed_at: Option<Timestamp>,
+    #[serde(default)]
+    created_at: Option<Timestamp>,
+    #[serde(default)]
+    updated_at: Option<Timestamp>,
+    #[serde(default)]
+    sort_order: Option<Timestamp>,
+    #[serde(default)]
+    quadrant: Option<u8>,
+    #[serde(default, alias = "note")]
+    notes: Option<String>,
+    #[serde(default)]
+    steps: Vec<LegacyStepV0>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    reminder: Option<LegacyReminderV0>,
+    /// Early builds stored the reminder time next to the task instead of inside `reminder`.
+    #[serde(default)]
+    remind_at: Option<Timestamp>,
+    #[serde(default)]
+    repeat: Option<LegacyRepeatV0>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LegacyStepV0 {
+    id: String,
+    title: String,
+    #[serde(default, alias = "done")]
+    completed: bool,
+    #[serde(default)]
+    created_at: Option<Timestamp>,
+    #[serde(default)]
+    completed_at: Option<Timestamp>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum LegacyReminderV0 {
+    Kind(String),
+    Config {
+        #[serde(default, alias = "type")]
+        kind: Option<String>,
+        #[serde(default)]
+        remind_at: Option<Timestamp>,
+        #[serde(default)]
+        snoozed_until: Option<Timestamp>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum LegacyRepeatV0 {
+    Name(String),
+    Rule {
+        #[serde(alias = "kind")]
+        r#type: String,
+        #[serde(default)]
+        workday_only: bool,
+        #[serde(default)]
+        days: Vec<u8>,
+        #[serde(default)]
+        day: Option<u8>,
+        #[serde(default)]
+        month: Option<u8>,
+    },
+}
+
+fn legacy_key(value: &str) -> String {
+    value.trim().to_lowercase().replace(['-', ' '], "_")
+}
+
+fn legacy_reminder_kind(task_id: &str, value: &str) -> ReminderKind {
+    match legacy_key(value).as_str() {
+        "" | "none" | "off" => ReminderKind::None,
+        "normal" | "notify" | "notification" => ReminderKind::Normal,
+        "forced" | "force" | "strong" => ReminderKind::Forced,
+        other => {
+            log::warn!("migration v0: task={task_id} unknown reminder kind={other}, disabled");
+            ReminderKind::None
+        }
+    }
+}
+
+fn legacy_reminder(task_id: &str, legacy: &LegacyTaskV0) -> ReminderConfig {
+    let (kind, remind_at, snoozed_until) = match &legacy.reminder {
+        None => (None, None, None),
+        Some(LegacyReminderV0::Kind(kind)) => (Some(kind.as_str()), None, None),
+        Some(LegacyReminderV0::Config {
+            kind,
+            remind_at,
+            snoozed_until,
+        }) => (kind.as_deref(), *remind_at, *snoozed_until),
+    };
+    let remind_at = remind_at.or(legacy.remind_at);
+    let kind = match kind {
+        Some(kind) => legacy_reminder_kind(task_id, kind),
+        // A bare reminder time without a kind was always a normal notification.
+        None if remind_at.is_some() => ReminderKind::Normal,
+        None => ReminderKind::None,
+    };
+    ReminderConfig {
+        kind,
+        remind_at,
+        snoozed_until,
+        ..ReminderConfig::default()
+    }
+}
+
+fn legacy_repeat(task_id: &str, repeat: &LegacyRepeatV0, due_at: Timestamp) -> RepeatRule {
+    let (name, workday_only, days, day, month) = match repeat {
+        LegacyRepeatV0::Name(name) => (name.as_str(), false, Vec::new(), None, None),
+        LegacyRepeatV0::Rule {
+            r#type,
+            workday_only,
+            days,
+            day,
+            month,
+        } => (r#type.as_str(), *workday_only, days.clone(), *day, *month),
+    };
+    let due = Local.timestamp_opt(due_at, 0).single();
+    let due_day = due.map(|d| d.day() as u8).unwrap_or(1);
+    let due_month = due.map(|d| d.month() as u8).unwrap_or(1);
+    match legacy_key(name).as_str() {
+        "" | "none" | "never" => RepeatRule::None,
+        "daily" | "day" => RepeatRule::Daily { workday_only },
+        "workday" | "workdays" | "weekdays" => RepeatRule::Daily { workday_only: true },
+        "weekly" | "week" => RepeatRule::Weekly { days },
+        "monthly" | "month" => RepeatRule::Monthly {
+            day: day.unwrap_or(due_day),
+        },
+        "yearly" | "year" | "annually" => RepeatRule::Yearly {
+            month: month.unwrap_or(due_month),
+            day: day.unwrap_or(due_day),
+        },
+        other => {
+            log::warn!("migration v0: task={task_id} unknown repeat type={other}, dropped");
+            RepeatRule::None
+        }
+    }
+}
+
+fn migrate_task_v0(legacy: LegacyTaskV0) -> Task {
+    let created_at = legacy
+        .created_at
+        .or(legacy.updated_at)
+        .or(legacy.due_at)
+        .unwrap_or(0);
+    // Legacy files without a due date really meant "undated"; repeats still need an anchor.
+    let due_at = legacy.due_at;
+    let completed_at = match (legacy.completed, legacy.completed_at) {
+        (true, None) => legacy.updated_at.or(Some(created_at)),
+        (completed, at) => at.filter(|_| completed),
+    };
+    let reminder = legacy_reminder(&legacy.id, &legacy);
+    let repeat = legacy
+        .repeat
+        .as_ref()
+        .map(|repeat| legacy_repeat(&legacy.id, repeat, due_at.unwrap_or(created_at)))
+        .unwrap_or_default();
+    let steps = legacy
+        .steps
+        .into_iter()
+        .map(|step| Step {
+            id: step.id,
+            title: step.title,
+            completed: step.completed,
+            created_at: step.created_at.unwrap_or(created_at),
+            completed_at: step.completed_at.filter(|_| step.completed),
+            parent_id: None,
+        })
+        .collect();
+
+    Task {
+        id: legacy.id,
+        project_id: legacy
+            .project_id
+            .filter(|id| !id.trim().is_empty())
+            .unwrap_or_else(|| "inbox".to_string()),
+        title: legacy.title,
+        due_at,
+        important: legacy.important,
+        completed: legacy.completed,
+        completed_at,
+        created_at,
+        updated_at: legacy.updated_at.unwrap_or(created_at),
+        // 0 lets AppState::new fill the usual `created_at * 1000` ordering.
+        sort_order: legacy.sort_order.unwrap_or(0),
+        quadrant: legacy.quadrant.filter(|q| (1..=4).contains(q)).unwrap_or(1),
+        notes: legacy.notes.filter(|notes| !notes.trim().is_empty()),
+        steps,
+        tags: legacy.tags,
+        sample_tag: None,
+        reminder,
+        repeat,
+        waiting_on: None,
+        waiting_since: None,
+        series_id: None,
+        pinned: false,
+        context: None,
+        comments: Vec::new(),
+        progress: Default::default(),
+        spawn_ahead: 0,
+        repeat_from_completion: false,
+        end_after_occurrences: None,
+        end_by_date: None,
+        occurrence_index: 0,
+        timezone: None,
+        escalate_after: None,
+        start_at: None,
+        someday: false,
+        carry_over_count: 0,
+        skip_carry_over: false,
+        color: None,
+        emoji: None,
+        estimate_minutes: None,
+        attachments: Vec::new(),
+        time_entries: Vec::new(),
+        blocked_by: Vec::new(),
+    }
+}
+
+fn schema_version(value: &Value) -> u32 {
+    value
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .map(|v| v as u32)
+        .unwrap_or(LEGACY_SCHEMA_VERSION)
+}
+
+/// Parses data.json contents of any known schema version into the current `TasksFile`.
+///
+/// Versioned files go straight through serde; v0 files are read with the tolerant legacy
+/// structs above so renamed fields and old enum spellings are mapped instead of rejected.
+pub fn parse_tasks_file(raw: &str) -> Result<TasksFile, serde_json::Error> {
+    let value: Value = serde_json::from_str(raw)?;
+    let version = schema_version(&value);
+    if version != LEGACY_SCHEMA_VERSION {
+        return serde_json::from_value(value);
+    }
+
+    let legacy: LegacyTasksFileV0 = serde_json::from_value(value)?;
+    let tasks = match legacy {
+        LegacyTasksFileV0::Wrapped { tasks } | LegacyTasksFileV0::Bare(tasks) => tasks,
+    };
+    log::info!("migration v0: upgrading data.json tasks={}", tasks.len());
+    Ok(TasksFile {
+        schema_version: MIGRATED_SCHEMA_VERSION,
+        tasks: tasks.into_iter().map(migrate_task_v0).collect(),
+        projects: Vec::new(),
+        trash: Vec::new(),
+        tags: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WRAPPED_V0: &str = include_str!("../testcases/fixtures/legacy_v0_data.json");
+    const BARE_V0: &str = include_str!("../testcases/fixtures/legacy_v0_bare_array.json");
+
+    fn find<'a>(file: &'a TasksFile, id: &str) -> &'a Task {
+        file.tasks.iter().find(|t| t.id == id).unwrap()
+    }
+
+    #[test]
+    fn parse_tasks_file_migrates_wrapped_v0_fixture() {
+        let file = parse_tasks_file(WRAPPED_V0).unwrap();
+        assert_eq!(file.schema_version, MIGRATED_SCHEMA_VERSION);
+        assert_eq!(file.tasks.len(), 3);
+        assert!(file.projects.is_empty());
+
+        let forced = find(&file, "legacy-1");
+        assert_eq!(forced.project_id, "inbox");
+        assert_eq!(forced.due_at, Some(1_700_003_600));
+        assert!(forced.completed);
+        assert_eq!(forced.completed_at, Some(1_700_000_500));
+        assert_eq!(forced.notes.as_deref(), Some("old note field"));
+        assert_eq!(forced.reminder.kind, ReminderKind::Forced);
+        assert_eq!(forced.reminder.remind_at, Some(1_700_003_000));
+        assert_eq!(forced.repeat, RepeatRule::Daily { workday_only: true });
+        assert_eq!(forced.steps.len(), 1);
+        assert!(forced.steps[0].completed);
+        assert_eq!(forced.steps[0].created_at, 1_700_000_000);
+
+        let flat = find(&file, "legacy-2");
+        assert_eq!(flat.reminder.kind, ReminderKind::Normal);
+        assert_eq!(flat.reminder.remind_at, Some(1_700_100_000));
+        assert_eq!(flat.repeat, RepeatRule::Weekly { days: vec![1, 3] });
+        assert_eq!(flat.quadrant, 1);
+        assert_eq!(flat.updated_at, flat.created_at);
+        assert_eq!(flat.sort_order, 0);
+
+        // Unknown variants are dropped explicitly (and logged) rather than failing the load.
+        let unknown = find(&file, "legacy-3");
+        assert_eq!(unknown.reminder.kind, ReminderKind::None);
+        assert_eq!(unknown.repeat, RepeatRule::None);
+        assert_eq!(unknown.project_id, "work");
+        assert_eq!(unknown.due_at, None);
+        assert_eq!(unknown.completed_at, None);
+    }
+
+    #[test]
+    fn parse_tasks_file_migrates_bare_array_fixture() {
+        let file = parse_tasks_file(BARE_V0).unwrap();
+        assert_eq!(file.schema_version, MIGRATED_SCHEMA_VERSION);
+        assert_eq!(file.tasks.len(), 2);
+
+        let monthly = find(&file, "bare-1");
+        let due_day = Local
+            .timestamp_opt(monthly.due_at.unwrap(), 0)
+            .unwrap()
+            .day() as u8;
+        assert_eq!(monthly.repeat, RepeatRule::Monthly { day: due_day });
+        assert!(monthly.important);
+        // Completed without a timestamp falls back to the last update.
+        assert_eq!(monthly.completed_at, Some(1_690_000_100));
+
+        let yearly = find(&file, "bare-2");
+        assert_eq!(yearly.repeat, RepeatRule::Yearly { month: 2, day: 29 });
+        assert_eq!(yearly.reminder.kind, ReminderKind::Normal);
+        assert_eq!(yearly.quadrant, 1);
+    }
+
+    #[test]
+    fn parse_tasks_file_passes_current_versions_through() {
+        let raw = r#"{"schema_version":1,"tasks":[],"projects":[{"id":"p","name":"P"}]}"#;
+        let file = parse_tasks_file(raw).unwrap();
+        assert_eq!(file.schema_version, 1);
+        assert_eq!(file.projects.len(), 1);
+
+        // Versioned files keep strict parsing: legacy spellings are not silently accepted.
+        let strict = r#"{"schema_version":1,"tasks":[{"id":"a","title":"a","due":1}]}"#;
+        assert!(parse_tasks_file(strict).is_err());
+    }
+
+    #[test]
+    fn parse_tasks_file_rejects_malformed_legacy_data() {
+        assert!(parse_tasks_file("oops").is_err());
+        assert!(parse_tasks_file(r#"{"tasks":[{"id":"x"}]}"#).is_err());
+        assert!(parse_tasks_file(r#""just a string""#).is_err());
+    }
+}
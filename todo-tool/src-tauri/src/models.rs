@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 pub type Timestamp = i64;
@@ -19,6 +21,20 @@ pub struct ReminderConfig {
     pub forced_dismissed: bool,
     pub last_fired_at: Option<Timestamp>,
     pub repeat_fired_count: i64,
+    /// Firing time (`last_fired_at`) that the email fallback already covered.
+    pub email_sent_at: Option<Timestamp>,
+    /// Reminders relative to the due time, each firing on its own; kept sorted, earliest first.
+    pub specs: Vec<ReminderSpec>,
+    /// Normal firings since the user last snoozed; drives escalation to Forced.
+    pub ignored_count: u32,
+    /// `kind` was raised from Normal to Forced by escalation; the next repeat goes back to Normal.
+    pub escalated: bool,
+}
+
+/// One reminder relative to the task's due time (e.g. a day before, an hour before, at due).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ReminderSpec {
+    pub minutes_before: u32,
 }
 
 impl Default for ReminderConfig {
@@ -30,8 +46,118 @@ impl Default for ReminderConfig {
             forced_dismissed: false,
             last_fired_at: None,
             repeat_fired_count: 0,
+            email_sent_at: None,
+            specs: Vec::new(),
+            ignored_count: 0,
+            escalated: false,
+        }
+    }
+}
+
+impl ReminderConfig {
+    /// Moves an explicit `remind_at` a whole number of minutes before the due time into `specs`
+    /// (how single reminders were stored), and sorts and de-duplicates them. Undated tasks and
+    /// other explicit times keep `remind_at`.
+    pub fn fold_remind_at(&mut self, due_at: Option<Timestamp>) {
+        if let (Some(at), Some(due)) = (self.remind_at, due_at) {
+            if at <= due && (due - at) % 60 == 0 {
+                let minutes = ((due - at) / 60).min(i64::from(MAX_REMINDER_LEAD_MINUTES));
+                self.specs.push(ReminderSpec {
+                    minutes_before: minutes as u32,
+                });
+                self.remind_at = None;
+            }
+        }
+        for spec in &mut self.specs {
+            spec.minutes_before = spec.minutes_before.min(MAX_REMINDER_LEAD_MINUTES);
+        }
+        self.specs.sort_unstable_by(|a, b| b.cmp(a));
+        self.specs.dedup();
+    }
+
+    /// Every time this reminder fires at, ascending: each spec (dated tasks only) and an
+    /// explicit `remind_at`, or the kind's default lead time when there are neither.
+    pub fn targets(
+        &self,
+        due_at: Option<Timestamp>,
+        lead_times: &ReminderLeadTimes,
+    ) -> Vec<Timestamp> {
+        let mut targets: Vec<Timestamp> = due_at
+            .map(|due| {
+                self.specs
+                    .iter()
+                    .map(|spec| due.saturating_sub(i64::from(spec.minutes_before) * 60))
+                    .collect()
+            })
+            .unwrap_or_default();
+        targets.extend(self.remind_at);
+        if targets.is_empty() {
+            targets.extend(due_at.map(|due| lead_times.default_target(&self.kind, due)));
+        }
+        targets.sort_unstable();
+        targets.dedup();
+        targets
+    }
+
+    /// The time the scheduler is working towards at `now`: a snooze wins, otherwise the latest
+    /// target already reached, else the next one. `None` when nothing is scheduled.
+    pub fn current_target(
+        &self,
+        due_at: Option<Timestamp>,
+        lead_times: &ReminderLeadTimes,
+        now: Timestamp,
+    ) -> Option<Timestamp> {
+        if self.snoozed_until.is_some() {
+            return self.snoozed_until;
+        }
+        let targets = self.targets(due_at, lead_times);
+        targets
+            .iter()
+            .rev()
+            .find(|at| **at <= now)
+            .or(targets.first())
+            .copied()
+    }
+}
+
+/// Upper bound for a reminder lead time (one week).
+pub const MAX_REMINDER_LEAD_MINUTES: u32 = 7 * 24 * 60;
+
+/// Minutes before `due_at` a reminder fires when it has no explicit `remind_at`, per kind.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+pub struct ReminderLeadTimes {
+    pub normal: u32,
+    pub forced: u32,
+}
+
+impl Default for ReminderLeadTimes {
+    fn default() -> Self {
+        Self {
+            normal: 10,
+            forced: 0,
+        }
+    }
+}
+
+impl ReminderLeadTimes {
+    pub fn minutes(&self, kind: &ReminderKind) -> u32 {
+        match kind {
+            ReminderKind::None => 0,
+            ReminderKind::Normal => self.normal,
+            ReminderKind::Forced => self.forced,
         }
     }
+
+    /// When a `kind` reminder on a task due at `due_at` fires by default.
+    pub fn default_target(&self, kind: &ReminderKind, due_at: Timestamp) -> Timestamp {
+        due_at.saturating_sub(i64::from(self.minutes(kind)) * 60)
+    }
+
+    pub fn clamp(&mut self) {
+        self.normal = self.normal.min(MAX_REMINDER_LEAD_MINUTES);
+        self.forced = self.forced.min(MAX_REMINDER_LEAD_MINUTES);
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
@@ -52,6 +178,21 @@ pub enum RepeatRule {
         month: u8,
         day: u8,
     },
+    /// Every `n` days.
+    EveryNDays {
+        n: u16,
+    },
+    /// Every `n` weeks on `days` (1 = Monday ..= 7), counted in Monday-based weeks; the due
+    /// date's weekday when `days` is empty.
+    EveryNWeeks {
+        n: u16,
+        days: Vec<u8>,
+    },
+    /// Every `n` months on `day`, clamped to the month's length.
+    EveryNMonths {
+        n: u16,
+        day: u8,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,6 +203,65 @@ pub struct Step {
     pub completed: bool,
     pub created_at: Timestamp,
     pub completed_at: Option<Timestamp>,
+    /// The step this one is nested under; `None` for a top-level step.
+    #[serde(default)]
+    pub parent_id: Option<String>,
+}
+
+/// Checklist roll-up derived from `steps`, so list views can draw progress without the steps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct TaskProgress {
+    pub steps_completed: usize,
+    pub steps_total: usize,
+    /// Rounded down; 0 when the task has no steps.
+    pub percent_complete: u8,
+}
+
+impl TaskProgress {
+    pub fn from_steps(steps: &[Step]) -> Self {
+        let steps_total = steps.len();
+        let steps_completed = steps.iter().filter(|step| step.completed).count();
+        let percent_complete = (steps_completed * 100)
+            .checked_div(steps_total)
+            .map_or(0, |percent| percent as u8);
+        Self {
+            steps_completed,
+            steps_total,
+            percent_complete,
+        }
+    }
+}
+
+/// Timestamped progress entry; unlike `notes`, comments are appended, never rewritten.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Comment {
+    pub id: String,
+    pub text: String,
+    pub created_at: Timestamp,
+}
+
+/// A file copied into the data directory's `attachments/` folder (see `attachments`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Attachment {
+    pub id: String,
+    /// Original file name, for display.
+    pub name: String,
+    /// Name of the copy inside `attachments/`; unique across tasks.
+    pub file: String,
+    pub size: u64,
+    pub added_at: Timestamp,
+}
+
+/// One stretch of tracked time on a task.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct TimeEntry {
+    pub started_at: Timestamp,
+    /// `None` while the timer is running.
+    pub ended_at: Option<Timestamp>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -79,6 +279,44 @@ pub struct Project {
     pub updated_at: Timestamp,
     #[serde(default)]
     pub sample_tag: Option<String>,
+    /// Reminders of this project's tasks are held back while muted.
+    #[serde(default)]
+    pub reminders_muted: bool,
+    /// Mute ends on its own at this time; `None` mutes until `unmute_project`.
+    #[serde(default)]
+    pub muted_until: Option<Timestamp>,
+    /// Sound for this project's forced reminders (a `sounds::list_sounds` id); `None` plays the
+    /// default.
+    #[serde(default)]
+    pub notification_sound: Option<String>,
+    /// `#rrggbb` accent for the sidebar and menus (see `flair::normalize_color`).
+    #[serde(default)]
+    pub color: Option<String>,
+    /// A built-in icon id (`flair::PROJECT_ICONS`) or an emoji.
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Folder this project is nested in; `None` is top-level (see `project_tree`).
+    #[serde(default)]
+    pub parent_id: Option<String>,
+}
+
+/// Metadata for a tag. Tasks still carry tag names; a tag matches the definition whose name
+/// has the same `tags::tag_key`. Tags without a definition have no color or parent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct Tag {
+    pub id: String,
+    pub name: String,
+    /// `#rrggbb` (see `flair::normalize_color`).
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Id of the tag this one is nested under; `None` is top-level.
+    #[serde(default)]
+    pub parent_id: Option<String>,
+    #[serde(default)]
+    pub created_at: Timestamp,
+    #[serde(default)]
+    pub updated_at: Timestamp,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -88,7 +326,9 @@ pub struct Task {
     #[serde(default = "default_project_id")]
     pub project_id: String,
     pub title: String,
-    pub due_at: Timestamp,
+    /// `None` for undated tasks; older files always carry a number, which still reads as-is.
+    #[serde(default)]
+    pub due_at: Option<Timestamp>,
     #[serde(default)]
     pub important: bool,
     #[serde(default)]
@@ -111,6 +351,116 @@ pub struct Task {
     pub reminder: ReminderConfig,
     #[serde(default)]
     pub repeat: RepeatRule,
+    #[serde(default)]
+    pub waiting_on: Option<String>,
+    /// When the task was handed to `waiting_on`; maintained by the command layer.
+    #[serde(default)]
+    pub waiting_since: Option<Timestamp>,
+    /// Id of the task that started this repeat series; shared by every spawned instance.
+    #[serde(default)]
+    pub series_id: Option<String>,
+    /// Sticks the task to the top of its list; independent of `important`.
+    #[serde(default)]
+    pub pinned: bool,
+    /// GTD context such as `@phone`; stored normalized (lowercase, leading `@`).
+    #[serde(default)]
+    pub context: Option<String>,
+    /// Someday/Maybe backlog: any `due_at` is kept but ignored, and the task is left out of overdue
+    /// logic, reminders and the regular lists (see `list_someday_tasks`).
+    #[serde(default)]
+    pub someday: bool,
+    /// How many times the nightly carry-over has moved this task to a new day.
+    #[serde(default)]
+    pub carry_over_count: u32,
+    /// Per-task opt-out from the nightly carry-over.
+    #[serde(default)]
+    pub skip_carry_over: bool,
+    /// Repeating tasks only: how many future occurrences the scheduler keeps created in advance
+    /// (0 = spawn the next one on completion).
+    #[serde(default)]
+    pub spawn_ahead: u8,
+    /// Repeating tasks only: count the next occurrence from the day the task was completed
+    /// instead of from its due date ("3 days after I last did it").
+    #[serde(default)]
+    pub repeat_from_completion: bool,
+    /// Repeating tasks only: the series stops once this many occurrences exist.
+    #[serde(default)]
+    pub end_after_occurrences: Option<u32>,
+    /// Repeating tasks only: no occurrence is created that would be due after this time.
+    #[serde(default)]
+    pub end_by_date: Option<Timestamp>,
+    /// 0-based position in the repeat series; tasks saved before it was tracked count as first.
+    #[serde(default)]
+    pub occurrence_index: u32,
+    /// IANA zone (e.g. `Europe/Berlin`) repeats are computed in, so the due time of day holds
+    /// across travel and DST; `None` uses the system zone. Validated by the command layer.
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Overrides `settings.reminder_escalate_after` for this task; `Some(0)` never escalates.
+    #[serde(default)]
+    pub escalate_after: Option<u32>,
+    /// The task stays out of the regular lists and today counts until this time; `None` means
+    /// it is active right away. Independent of `due_at`.
+    #[serde(default)]
+    pub start_at: Option<Timestamp>,
+    /// Hex color such as `#ff8800`; validated and lowercased by the command layer.
+    #[serde(default)]
+    pub color: Option<String>,
+    /// A single emoji shown in front of the title; validated by the command layer.
+    #[serde(default)]
+    pub emoji: Option<String>,
+    /// Rough effort in minutes; lets suggestions prefer work that fits the rest of the day.
+    #[serde(default)]
+    pub estimate_minutes: Option<u32>,
+    /// Progress log, oldest first.
+    #[serde(default)]
+    pub comments: Vec<Comment>,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    /// Tracked work, oldest first; at most the last entry is still running (see `time_tracking`).
+    #[serde(default)]
+    pub time_entries: Vec<TimeEntry>,
+    /// Ids of tasks that must be completed first; reminders wait until they are (see
+    /// `dependencies`). Ids of deleted tasks no longer block.
+    #[serde(default)]
+    pub blocked_by: Vec<String>,
+    /// Derived from `steps` whenever the task is written; client-sent values are ignored.
+    #[serde(default, skip_deserializing)]
+    pub progress: TaskProgress,
+}
+
+impl Project {
+    /// Whether reminders are held back at `now`; a passed `muted_until` ends the mute.
+    pub fn reminders_muted_at(&self, now: Timestamp) -> bool {
+        self.reminders_muted && self.muted_until.is_none_or(|until| now < until)
+    }
+}
+
+impl Task {
+    pub fn refresh_progress(&mut self) {
+        self.progress = TaskProgress::from_steps(&self.steps);
+    }
+
+    /// Open, dated, not in the Someday backlog and past `due_at` by more than the grace period
+    /// (negative grace counts as 0).
+    pub fn is_overdue(&self, now: Timestamp, grace_minutes: i64) -> bool {
+        let grace_secs = grace_minutes.max(0).saturating_mul(60);
+        !self.completed
+            && !self.someday
+            && self
+                .due_at
+                .is_some_and(|due| due.saturating_add(grace_secs) < now)
+    }
+
+    /// Open and waiting for a `start_at` that is still ahead.
+    pub fn is_scheduled(&self, now: Timestamp) -> bool {
+        !self.completed && self.start_at.is_some_and(|start| start > now)
+    }
+
+    /// Sort key for "by due date" orderings: undated tasks go after every dated one.
+    pub fn due_sort_key(&self) -> Timestamp {
+        self.due_at.unwrap_or(Timestamp::MAX)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -137,6 +487,20 @@ pub enum UpdateBehavior {
     Disabled,
 }
 
+/// Which chat API `ai_plan_task` talks to; see `ai::providers`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AiProviderKind {
+    #[default]
+    Deepseek,
+    /// OpenAI or any endpoint speaking its chat completions API.
+    #[serde(rename = "openai")]
+    OpenAi,
+    Anthropic,
+    /// A local Ollama server; needs no API key.
+    Ollama,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum UiRadius {
@@ -168,7 +532,15 @@ pub enum UiShadow {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct Settings {
+    /// Shows the quick window; the one binding that cannot be blank.
     pub shortcut: String,
+    /// Per-action global shortcuts (see `shortcuts::ShortcutAction`); blank means unbound.
+    #[serde(default)]
+    pub shortcut_quick_add: String,
+    #[serde(default)]
+    pub shortcut_toggle_main: String,
+    #[serde(default)]
+    pub shortcut_complete_focus: String,
     pub theme: String,
     #[serde(default)]
     pub ui_radius: UiRadius,
@@ -180,12 +552,25 @@ pub struct Settings {
     pub language: String,
     #[serde(default)]
     pub ai_enabled: bool,
-    #[serde(default)]
+    /// Legacy plaintext key: only read so boot can move it into secrets, never written back.
+    #[serde(default, skip_serializing)]
     pub deepseek_api_key: String,
+    #[serde(default)]
+    pub ai_provider: AiProviderKind,
+    /// Overrides the provider's endpoint (proxies, self-hosted gateways); empty uses the default.
+    #[serde(default)]
+    pub ai_base_url: String,
+    /// Whether the selected provider has its API key stored in secrets (always true for
+    /// providers without one). Maintained by the backend; client values are ignored.
+    #[serde(default)]
+    pub ai_api_key_set: bool,
     #[serde(default = "default_ai_model")]
     pub ai_model: String,
     #[serde(default = "default_ai_prompt")]
     pub ai_prompt: String,
+    /// Opt-in: include each open task's latest comments in the AI prompt context.
+    #[serde(default)]
+    pub ai_include_comments: bool,
     #[serde(default)]
     pub update_behavior: UpdateBehavior,
     pub sound_enabled: bool,
@@ -202,10 +587,32 @@ pub struct Settings {
     pub quick_tab: String,
     #[serde(default = "default_quick_sort")]
     pub quick_sort: String,
+    /// Local "HH:MM" used when quick capture gets no time.
+    #[serde(default = "default_quick_due_time")]
+    pub quick_default_due_time: String,
+    /// Parsed due times snap to this many minutes (0 = keep as typed).
+    #[serde(default)]
+    pub quick_due_rounding_minutes: u32,
+    #[serde(default)]
+    pub quick_no_date_policy: NoDatePolicy,
     #[serde(default = "default_forced_color")]
     pub forced_reminder_color: String,
     #[serde(default)]
     pub backup_schedule: BackupSchedule,
+    /// How many backups `backups/` keeps (1..=100); the oldest go after each new one.
+    #[serde(default = "default_backup_retention")]
+    pub backup_retention: u32,
+    /// New backups are gzip-compressed (`*.json.gz`); existing ones are left as they are.
+    #[serde(default)]
+    pub backup_compress: bool,
+    /// Extra folder (e.g. Dropbox/OneDrive) that also receives every backup; empty = none.
+    /// When it is unavailable backups still land in `backups/`.
+    #[serde(default)]
+    pub backup_directory: String,
+    /// Where tasks/projects live. Changing it migrates the data once (see `Storage::migrate_to`);
+    /// on load it reflects the file actually found in the data directory.
+    #[serde(default)]
+    pub storage_engine: StorageEngine,
     #[serde(default)]
     pub last_backup_at: Option<Timestamp>,
     #[serde(default)]
@@ -216,12 +623,137 @@ pub struct Settings {
     pub reminder_repeat_interval_sec: i64,
     #[serde(default = "default_reminder_repeat_max_times")]
     pub reminder_repeat_max_times: i64,
+    /// When at least this many reminders fire in one tick they are shown as a single digest
+    /// instead of one notification/overlay each. 0 never groups.
+    #[serde(default = "default_reminder_digest_threshold")]
+    pub reminder_digest_threshold: u32,
+    /// A Normal reminder that fired this many times without being snoozed or completed fires as
+    /// Forced from then on. 0 never escalates.
+    #[serde(default)]
+    pub reminder_escalate_after: u32,
+    #[serde(default)]
+    pub reminder_lead_minutes: ReminderLeadTimes,
+    #[serde(default)]
+    pub email_reminder_enabled: bool,
+    #[serde(default)]
+    pub smtp_host: String,
+    #[serde(default = "default_smtp_port")]
+    pub smtp_port: u16,
+    #[serde(default)]
+    pub smtp_security: SmtpSecurity,
+    #[serde(default)]
+    pub smtp_username: String,
+    #[serde(default)]
+    pub email_from: String,
+    #[serde(default)]
+    pub email_to: String,
+    #[serde(default = "default_email_unacked_minutes")]
+    pub email_unacked_minutes: i64,
+    #[serde(default = "default_email_when_headless")]
+    pub email_when_headless: bool,
+    #[serde(default)]
+    pub quadrant_config: QuadrantConfig,
+    /// Per-project list preferences keyed by project id.
+    #[serde(default)]
+    pub view_prefs: BTreeMap<String, ViewPrefs>,
+    /// Saved filters in sidebar order; changed only through the smart list commands.
+    #[serde(default)]
+    pub smart_lists: Vec<SmartList>,
+    /// If-this-then-that rules in the order they run; changed only through the automation
+    /// rule commands.
+    #[serde(default)]
+    pub automation_rules: Vec<AutomationRule>,
+    #[serde(default)]
+    pub week_starts_on: WeekStart,
+    #[serde(default)]
+    pub date_format: DateFormat,
+    /// Minutes past `due_at` before an open task counts as overdue.
+    #[serde(default)]
+    pub overdue_grace_minutes: i64,
+    /// Working day as local `HH:MM`; used to judge how much of today is left.
+    #[serde(default = "default_work_day_start")]
+    pub work_day_start: String,
+    #[serde(default = "default_work_day_end")]
+    pub work_day_end: String,
+    /// Holds normal reminders until DND is turned off; forced reminders still fire.
+    #[serde(default)]
+    pub dnd_enabled: bool,
+    /// Holds normal reminders between `quiet_hours_start` and `quiet_hours_end` (local `HH:MM`,
+    /// may wrap past midnight); they fire together once the quiet hours end.
+    #[serde(default)]
+    pub quiet_hours_enabled: bool,
+    #[serde(default = "default_quiet_hours_start")]
+    pub quiet_hours_start: String,
+    #[serde(default = "default_quiet_hours_end")]
+    pub quiet_hours_end: String,
+    #[serde(default)]
+    pub last_maintenance_at: Option<Timestamp>,
+    /// At local midnight, move unfinished tasks due the previous day to today.
+    #[serde(default)]
+    pub carry_over_enabled: bool,
+    #[serde(default)]
+    pub last_carry_over_at: Option<Timestamp>,
+    /// Once a day at `daily_summary_time` (local `HH:MM`), report completed/overdue/remaining
+    /// counts as a notification.
+    #[serde(default)]
+    pub daily_summary_enabled: bool,
+    #[serde(default = "default_daily_summary_time")]
+    pub daily_summary_time: String,
+    #[serde(default)]
+    pub last_daily_summary_at: Option<Timestamp>,
+    /// Show the forced reminder overlay for the task when a focus session runs out.
+    #[serde(default)]
+    pub focus_end_forced_reminder: bool,
+    /// Watch `exports/mustdo-sync.md` and complete tasks checked off in it.
+    #[serde(default)]
+    pub markdown_sync_enabled: bool,
+    /// Vault last used by the Obsidian export; daily notes go to `obsidian_daily_folder` in it.
+    #[serde(default)]
+    pub obsidian_vault_path: String,
+    #[serde(default = "default_obsidian_daily_folder")]
+    pub obsidian_daily_folder: String,
+    /// Publishes task events and reminder firings to an MQTT broker (password in secrets).
+    #[serde(default)]
+    pub mqtt_enabled: bool,
+    #[serde(default)]
+    pub mqtt_host: String,
+    #[serde(default = "default_mqtt_port")]
+    pub mqtt_port: u16,
+    #[serde(default)]
+    pub mqtt_tls: bool,
+    #[serde(default)]
+    pub mqtt_username: String,
+    #[serde(default = "default_mqtt_topic_prefix")]
+    pub mqtt_topic_prefix: String,
+    /// Two-way sync with a CalDAV task collection (password in secrets); `sync_now` works
+    /// whenever a URL is set, `caldav_enabled` adds the periodic run.
+    #[serde(default)]
+    pub caldav_enabled: bool,
+    #[serde(default)]
+    pub caldav_url: String,
+    #[serde(default)]
+    pub caldav_username: String,
+    #[serde(default = "default_caldav_sync_interval_minutes")]
+    pub caldav_sync_interval_minutes: u32,
+    #[serde(default)]
+    pub last_caldav_sync_at: Option<Timestamp>,
+    /// Deleted tasks stay in the trash this many days before the scheduler purges them;
+    /// 0 keeps them until the trash is emptied.
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u32,
+    /// Completed tasks move to the yearly archive files this many days after completion (see
+    /// `task_archive`); 0 keeps them in data.json.
+    #[serde(default = "default_archive_after_days")]
+    pub archive_after_days: u32,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
             shortcut: "CommandOrControl+Shift+T".to_string(),
+            shortcut_quick_add: String::new(),
+            shortcut_toggle_main: String::new(),
+            shortcut_complete_focus: String::new(),
             theme: "retro".to_string(),
             ui_radius: UiRadius::Theme,
             ui_border: UiBorder::Theme,
@@ -229,8 +761,12 @@ impl Default for Settings {
             language: default_language(),
             ai_enabled: false,
             deepseek_api_key: String::new(),
+            ai_provider: AiProviderKind::Deepseek,
+            ai_base_url: String::new(),
+            ai_api_key_set: false,
             ai_model: default_ai_model(),
             ai_prompt: default_ai_prompt(),
+            ai_include_comments: false,
             update_behavior: UpdateBehavior::NextRestart,
             sound_enabled: true,
             close_behavior: CloseBehavior::HideToTray,
@@ -240,18 +776,311 @@ impl Default for Settings {
             quick_bounds: None,
             quick_tab: default_quick_tab(),
             quick_sort: default_quick_sort(),
+            quick_default_due_time: default_quick_due_time(),
+            quick_due_rounding_minutes: 0,
+            quick_no_date_policy: NoDatePolicy::Today,
             forced_reminder_color: default_forced_color(),
             backup_schedule: BackupSchedule::Daily,
+            backup_retention: default_backup_retention(),
+            backup_compress: false,
+            backup_directory: String::new(),
+            storage_engine: StorageEngine::Json,
             last_backup_at: None,
             today_focus_ids: Vec::new(),
             today_focus_date: None,
             today_prompted_date: None,
             reminder_repeat_interval_sec: default_reminder_repeat_interval_sec(),
             reminder_repeat_max_times: default_reminder_repeat_max_times(),
+            reminder_digest_threshold: default_reminder_digest_threshold(),
+            reminder_escalate_after: 0,
+            reminder_lead_minutes: ReminderLeadTimes::default(),
+            email_reminder_enabled: false,
+            smtp_host: String::new(),
+            smtp_port: default_smtp_port(),
+            smtp_security: SmtpSecurity::StartTls,
+            smtp_username: String::new(),
+            email_from: String::new(),
+            email_to: String::new(),
+            email_unacked_minutes: default_email_unacked_minutes(),
+            email_when_headless: default_email_when_headless(),
+            quadrant_config: QuadrantConfig::default(),
+            view_prefs: BTreeMap::new(),
+            smart_lists: Vec::new(),
+            automation_rules: Vec::new(),
+            week_starts_on: WeekStart::Monday,
+            date_format: DateFormat::Ymd,
+            overdue_grace_minutes: 0,
+            work_day_start: default_work_day_start(),
+            work_day_end: default_work_day_end(),
+            dnd_enabled: false,
+            quiet_hours_enabled: false,
+            quiet_hours_start: default_quiet_hours_start(),
+            quiet_hours_end: default_quiet_hours_end(),
+            last_maintenance_at: None,
+            carry_over_enabled: false,
+            last_carry_over_at: None,
+            daily_summary_enabled: false,
+            daily_summary_time: default_daily_summary_time(),
+            last_daily_summary_at: None,
+            focus_end_forced_reminder: false,
+            markdown_sync_enabled: false,
+            obsidian_vault_path: String::new(),
+            obsidian_daily_folder: default_obsidian_daily_folder(),
+            mqtt_enabled: false,
+            mqtt_host: String::new(),
+            mqtt_port: default_mqtt_port(),
+            mqtt_tls: false,
+            mqtt_username: String::new(),
+            mqtt_topic_prefix: default_mqtt_topic_prefix(),
+            caldav_enabled: false,
+            caldav_url: String::new(),
+            caldav_username: String::new(),
+            caldav_sync_interval_minutes: default_caldav_sync_interval_minutes(),
+            last_caldav_sync_at: None,
+            trash_retention_days: default_trash_retention_days(),
+            archive_after_days: default_archive_after_days(),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    #[default]
+    StartTls,
+    Tls,
+    None,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct QuadrantDefinition {
+    pub id: u8,
+    pub name: String,
+    pub color: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case", default)]
+pub struct QuadrantConfig {
+    /// Display order; every quadrant id 1-4 appears exactly once.
+    pub quadrants: Vec<QuadrantDefinition>,
+    /// When false, `classify_quadrant` only fills in missing/invalid quadrants.
+    pub auto_classify: bool,
+    /// 0 keeps the UI rule "overdue or due today"; otherwise "due within N hours" is urgent.
+    pub urgent_within_hours: i64,
+    pub important_urgent: u8,
+    pub important_not_urgent: u8,
+    pub urgent_not_important: u8,
+    pub neither: u8,
+}
+
+impl Default for QuadrantConfig {
+    fn default() -> Self {
+        let quadrant = |id: u8, name: &str, color: &str| QuadrantDefinition {
+            id,
+            name: name.to_string(),
+            color: color.to_string(),
+        };
+        Self {
+            quadrants: vec![
+                quadrant(1, "Do First", "#C94D37"),
+                quadrant(2, "Schedule", "#D9A441"),
+                quadrant(3, "Delegate", "#3A7CA5"),
+                quadrant(4, "Eliminate", "#8A8A8A"),
+            ],
+            auto_classify: false,
+            urgent_within_hours: 0,
+            important_urgent: 1,
+            important_not_urgent: 2,
+            urgent_not_important: 3,
+            neither: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Sunday,
+    Saturday,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DateFormat {
+    /// 2026-03-10
+    #[default]
+    Ymd,
+    /// 03/10/2026
+    Mdy,
+    /// 10/03/2026
+    Dmy,
+}
+
+/// Where quick capture puts a task typed without any date.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NoDatePolicy {
+    /// Today at the default time, or tomorrow once that time has passed.
+    #[default]
+    Today,
+    Tomorrow,
+    /// Straight into the Someday backlog.
+    Someday,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskSortKey {
+    /// User-controlled `sort_order` (drag and drop).
+    #[default]
+    Manual,
+    Due,
+    Priority,
+    Created,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskGroupBy {
+    #[default]
+    None,
+    Project,
+    Tag,
+    DateBucket,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case", default)]
+pub struct ViewPrefs {
+    pub sort: TaskSortKey,
+    pub group_by: TaskGroupBy,
+    pub show_completed: bool,
+}
+
+/// Due-date part of a smart list, relative to the moment it is evaluated.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum DueFilter {
+    #[default]
+    Any,
+    Overdue,
+    /// Due by the end of today, overdue included.
+    Today,
+    /// Due from the start of today through the end of the `days`th day (today counts as one).
+    NextDays {
+        days: u32,
+    },
+    NoDate,
+}
+
+/// A saved filter shown next to the projects ("Next 7 days", "Waiting"); evaluated in Rust by
+/// `evaluate_smart_list` so every window sees the same result.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct SmartList {
+    pub id: String,
+    pub name: String,
+    /// Any of these projects; empty matches every project.
+    #[serde(default)]
+    pub project_ids: Vec<String>,
+    /// Any of these tags (case-insensitive); empty matches every task.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub important_only: bool,
+    /// Only tasks waiting on someone (`waiting_on` set).
+    #[serde(default)]
+    pub waiting_only: bool,
+    #[serde(default)]
+    pub due: DueFilter,
+    #[serde(default)]
+    pub include_completed: bool,
+    #[serde(default)]
+    pub include_someday: bool,
+    #[serde(default)]
+    pub sort: TaskSortKey,
+    #[serde(default)]
+    pub created_at: Timestamp,
+    #[serde(default)]
+    pub updated_at: Timestamp,
+}
+
+/// Formats shared by the file exporters, `export_tasks_content` and the export automation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Json,
+    Csv,
+    Markdown,
+    Ics,
+}
+
+impl ExportFormat {
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "application/json",
+            ExportFormat::Csv => "text/csv",
+            ExportFormat::Markdown => "text/markdown",
+            ExportFormat::Ics => "text/calendar",
+        }
+    }
+}
+
+/// What sets an automation rule off.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum AutomationTrigger {
+    /// A task goes from open to completed.
+    TaskCompleted,
+    /// An open task is still not done `minutes` after its due time; fires once per due time.
+    TaskOverdue { minutes: u32 },
+    /// A task gains `tag` (case-insensitive), including when it is created with it.
+    TagAdded { tag: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum AutomationAction {
+    MoveToProject {
+        project_id: String,
+    },
+    AddTag {
+        tag: String,
+    },
+    /// Adds a reminder this long before the due time; undated tasks are left alone.
+    SetReminder {
+        minutes_before: u32,
+    },
+    /// Writes all tasks to `exports/` like the export commands do.
+    RunExport {
+        format: ExportFormat,
+    },
+}
+
+/// A saved if-this-then-that rule. Changes made by its actions never set off other rules.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub struct AutomationRule {
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_rule_enabled")]
+    pub enabled: bool,
+    pub trigger: AutomationTrigger,
+    /// Run in order against the task that set the rule off.
+    pub actions: Vec<AutomationAction>,
+    /// Task id -> the `due_at` an overdue rule last fired for; kept by the backend.
+    #[serde(default)]
+    pub overdue_fired: BTreeMap<String, Timestamp>,
+    #[serde(default)]
+    pub created_at: Timestamp,
+    #[serde(default)]
+    pub updated_at: Timestamp,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum BackupSchedule {
@@ -262,6 +1091,16 @@ pub enum BackupSchedule {
     Monthly,
 }
 
+/// `json` keeps everything in data.json; `sqlite` keeps one row per task in data.sqlite so a
+/// change does not rewrite the whole list.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageEngine {
+    #[default]
+    Json,
+    Sqlite,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct WindowBounds {
@@ -307,6 +1146,34 @@ fn default_quick_sort() -> String {
     "default".to_string()
 }
 
+fn default_quick_due_time() -> String {
+    "18:00".to_string()
+}
+
+fn default_work_day_start() -> String {
+    "09:00".to_string()
+}
+
+fn default_daily_summary_time() -> String {
+    "18:00".to_string()
+}
+
+fn default_work_day_end() -> String {
+    "18:00".to_string()
+}
+
+fn default_quiet_hours_start() -> String {
+    "23:00".to_string()
+}
+
+fn default_quiet_hours_end() -> String {
+    "08:00".to_string()
+}
+
+fn default_obsidian_daily_folder() -> String {
+    "Daily".to_string()
+}
+
 fn default_language() -> String {
     "auto".to_string()
 }
@@ -475,6 +1342,51 @@ fn default_reminder_repeat_max_times() -> i64 {
     0
 }
 
+fn default_reminder_digest_threshold() -> u32 {
+    5
+}
+
+fn default_smtp_port() -> u16 {
+    587
+}
+
+fn default_email_unacked_minutes() -> i64 {
+    // 0 disables the "still not handled" email; headless delivery is controlled separately.
+    15
+}
+
+fn default_email_when_headless() -> bool {
+    true
+}
+
+fn default_rule_enabled() -> bool {
+    true
+}
+
+fn default_mqtt_port() -> u16 {
+    1883
+}
+
+fn default_caldav_sync_interval_minutes() -> u32 {
+    30
+}
+
+fn default_backup_retention() -> u32 {
+    crate::storage::DEFAULT_BACKUP_RETENTION
+}
+
+fn default_trash_retention_days() -> u32 {
+    30
+}
+
+fn default_archive_after_days() -> u32 {
+    90
+}
+
+fn default_mqtt_topic_prefix() -> String {
+    "mustdo".to_string()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub struct TasksFile {
@@ -482,6 +1394,18 @@ pub struct TasksFile {
     pub tasks: Vec<Task>,
     #[serde(default)]
     pub projects: Vec<Project>,
+    #[serde(default)]
+    pub trash: Vec<TrashEntry>,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+}
+
+/// A deleted task, kept until it is restored, purged or the trash is emptied.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub struct TrashEntry {
+    pub task: Task,
+    pub deleted_at: Timestamp,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -491,6 +1415,112 @@ pub struct SettingsFile {
     pub settings: Settings,
 }
 
+/// Task fixtures for unit tests: `task(id)` plus chained setters, so adding a field to `Task`
+/// touches one place instead of every test module.
+#[cfg(test)]
+pub mod test_support {
+    use super::*;
+
+    /// An open inbox task titled `id`, created and last updated at 1.
+    pub fn task(id: &str) -> Task {
+        Task {
+            id: id.to_string(),
+            project_id: default_project_id(),
+            title: id.to_string(),
+            due_at: None,
+            important: false,
+            completed: false,
+            completed_at: None,
+            created_at: 1,
+            updated_at: 1,
+            sort_order: 0,
+            quadrant: default_quadrant(),
+            notes: None,
+            steps: Vec::new(),
+            tags: Vec::new(),
+            sample_tag: None,
+            reminder: ReminderConfig::default(),
+            repeat: RepeatRule::None,
+            waiting_on: None,
+            waiting_since: None,
+            series_id: None,
+            pinned: false,
+            context: None,
+            someday: false,
+            carry_over_count: 0,
+            skip_carry_over: false,
+            spawn_ahead: 0,
+            repeat_from_completion: false,
+            end_after_occurrences: None,
+            end_by_date: None,
+            occurrence_index: 0,
+            timezone: None,
+            escalate_after: None,
+            start_at: None,
+            color: None,
+            emoji: None,
+            estimate_minutes: None,
+            comments: Vec::new(),
+            attachments: Vec::new(),
+            time_entries: Vec::new(),
+            blocked_by: Vec::new(),
+            progress: TaskProgress::default(),
+        }
+    }
+
+    impl Task {
+        pub fn title(mut self, title: &str) -> Self {
+            self.title = title.to_string();
+            self
+        }
+
+        pub fn project(mut self, project_id: &str) -> Self {
+            self.project_id = project_id.to_string();
+            self
+        }
+
+        pub fn due(mut self, due_at: impl Into<Option<Timestamp>>) -> Self {
+            self.due_at = due_at.into();
+            self
+        }
+
+        pub fn tags(mut self, tags: &[&str]) -> Self {
+            self.tags = tags.iter().map(|tag| tag.to_string()).collect();
+            self
+        }
+
+        pub fn notes<'a>(mut self, notes: impl Into<Option<&'a str>>) -> Self {
+            self.notes = notes.into().map(str::to_string);
+            self
+        }
+
+        /// Completed without a recorded completion time, as in files from older versions.
+        pub fn completed(mut self, completed: bool) -> Self {
+            self.completed = completed;
+            self
+        }
+
+        /// Completed at `at`; `None` leaves the task open.
+        pub fn done_at(mut self, at: impl Into<Option<Timestamp>>) -> Self {
+            self.completed_at = at.into();
+            self.completed = self.completed_at.is_some();
+            self
+        }
+
+        /// Sets both `created_at` and `updated_at`.
+        pub fn created(mut self, at: Timestamp) -> Self {
+            self.created_at = at;
+            self.updated_at = at;
+            self
+        }
+
+        pub fn updated(mut self, at: Timestamp) -> Self {
+            self.updated_at = at;
+            self
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -504,6 +1534,57 @@ mod tests {
         assert!(!config.forced_dismissed);
         assert_eq!(config.last_fired_at, None);
         assert_eq!(config.repeat_fired_count, 0);
+        assert_eq!(config.email_sent_at, None);
+        assert!(config.specs.is_empty());
+    }
+
+    #[test]
+    fn reminder_specs_fold_remind_at_and_yield_targets() {
+        let leads = ReminderLeadTimes::default();
+        let mut config = ReminderConfig {
+            kind: ReminderKind::Normal,
+            remind_at: Some(10_000 - 3_600),
+            specs: vec![
+                ReminderSpec { minutes_before: 0 },
+                ReminderSpec {
+                    minutes_before: 1_440,
+                },
+                ReminderSpec { minutes_before: 0 },
+            ],
+            ..ReminderConfig::default()
+        };
+        config.fold_remind_at(Some(10_000));
+        assert_eq!(config.remind_at, None);
+        let minutes: Vec<u32> = config.specs.iter().map(|s| s.minutes_before).collect();
+        assert_eq!(minutes, vec![1_440, 60, 0]);
+        assert_eq!(
+            config.targets(Some(100_000), &leads),
+            vec![100_000 - 86_400, 100_000 - 3_600, 100_000]
+        );
+        assert_eq!(
+            config.current_target(Some(100_000), &leads, 100_000 - 60),
+            Some(100_000 - 3_600)
+        );
+        assert_eq!(
+            config.current_target(Some(100_000), &leads, 0),
+            Some(100_000 - 86_400)
+        );
+
+        // Undated and after-due reminders stay absolute; no specs falls back to the lead time.
+        let mut undated = ReminderConfig {
+            kind: ReminderKind::Normal,
+            remind_at: Some(500),
+            ..ReminderConfig::default()
+        };
+        undated.fold_remind_at(None);
+        assert_eq!((undated.remind_at, undated.specs.len()), (Some(500), 0));
+        assert_eq!(undated.targets(Some(400), &leads), vec![500]);
+        // An offset that is not whole minutes was a clamped "as soon as possible" time.
+        undated.fold_remind_at(Some(590));
+        assert_eq!(undated.remind_at, Some(500));
+        undated.remind_at = None;
+        assert_eq!(undated.targets(Some(1_000), &leads), vec![1_000 - 600]);
+        assert!(undated.targets(None, &leads).is_empty());
     }
 
     #[test]
@@ -528,6 +1609,7 @@ mod tests {
         assert!(settings.deepseek_api_key.is_empty());
         assert_eq!(settings.ai_model, "deepseek-chat");
         assert_eq!(settings.ai_prompt, default_ai_prompt());
+        assert!(!settings.ai_include_comments);
         assert_eq!(
             serde_json::to_value(&settings.update_behavior).expect("serialize update_behavior"),
             serde_json::json!("next_restart")
@@ -546,6 +1628,9 @@ mod tests {
         assert!(settings.quick_bounds.is_none());
         assert_eq!(settings.quick_tab, "todo");
         assert_eq!(settings.quick_sort, "default");
+        assert_eq!(settings.quick_default_due_time, "18:00");
+        assert_eq!(settings.quick_due_rounding_minutes, 0);
+        assert_eq!(settings.quick_no_date_policy, NoDatePolicy::Today);
         assert_eq!(settings.forced_reminder_color, "#C94D37");
         assert_eq!(
             serde_json::to_value(&settings.backup_schedule).expect("serialize backup_schedule"),
@@ -557,6 +1642,42 @@ mod tests {
         assert_eq!(settings.today_prompted_date, None);
         assert_eq!(settings.reminder_repeat_interval_sec, 10 * 60);
         assert_eq!(settings.reminder_repeat_max_times, 0);
+        assert_eq!(settings.reminder_digest_threshold, 5);
+        assert_eq!(settings.work_day_start, "09:00");
+        assert_eq!(settings.work_day_end, "18:00");
+        assert_eq!(settings.reminder_lead_minutes, ReminderLeadTimes::default());
+        assert!(!settings.email_reminder_enabled);
+        assert!(settings.smtp_host.is_empty());
+        assert_eq!(settings.smtp_port, 587);
+        assert_eq!(settings.smtp_security, SmtpSecurity::StartTls);
+        assert!(settings.smtp_username.is_empty());
+        assert!(settings.email_from.is_empty());
+        assert!(settings.email_to.is_empty());
+        assert_eq!(settings.email_unacked_minutes, 15);
+        assert!(settings.email_when_headless);
+        assert_eq!(settings.quadrant_config, QuadrantConfig::default());
+        assert!(settings.view_prefs.is_empty());
+        assert_eq!(settings.week_starts_on, WeekStart::Monday);
+        assert_eq!(settings.date_format, DateFormat::Ymd);
+        assert_eq!(settings.overdue_grace_minutes, 0);
+        assert_eq!(settings.last_maintenance_at, None);
+        assert!(!settings.carry_over_enabled);
+        assert_eq!(settings.last_carry_over_at, None);
+        assert!(!settings.daily_summary_enabled);
+        assert_eq!(settings.daily_summary_time, "18:00");
+        assert_eq!(settings.last_daily_summary_at, None);
+        assert!(!settings.focus_end_forced_reminder);
+        assert!(!settings.markdown_sync_enabled);
+        assert!(!settings.caldav_enabled);
+        assert_eq!(settings.caldav_sync_interval_minutes, 30);
+        assert_eq!(settings.last_caldav_sync_at, None);
+        assert_eq!(settings.storage_engine, StorageEngine::Json);
+        assert_eq!(settings.obsidian_vault_path, "");
+        assert_eq!(settings.obsidian_daily_folder, "Daily");
+        assert!(!settings.mqtt_enabled);
+        assert_eq!(settings.mqtt_port, 1883);
+        assert!(!settings.mqtt_tls);
+        assert_eq!(settings.mqtt_topic_prefix, "mustdo");
     }
 
     #[test]
@@ -601,6 +1722,7 @@ mod tests {
         assert!(settings.deepseek_api_key.is_empty());
         assert_eq!(settings.ai_model, "deepseek-chat");
         assert_eq!(settings.ai_prompt, default_ai_prompt());
+        assert!(!settings.ai_include_comments);
         assert_eq!(
             serde_json::to_value(&settings.update_behavior).expect("serialize update_behavior"),
             serde_json::json!("next_restart")
@@ -610,6 +1732,9 @@ mod tests {
         assert!(settings.quick_bounds.is_none());
         assert_eq!(settings.quick_tab, "todo");
         assert_eq!(settings.quick_sort, "default");
+        assert_eq!(settings.quick_default_due_time, "18:00");
+        assert_eq!(settings.quick_due_rounding_minutes, 0);
+        assert_eq!(settings.quick_no_date_policy, NoDatePolicy::Today);
         assert_eq!(settings.forced_reminder_color, "#C94D37");
         assert_eq!(
             serde_json::to_value(&settings.backup_schedule).expect("serialize backup_schedule"),
@@ -621,6 +1746,38 @@ mod tests {
         assert_eq!(settings.today_prompted_date, None);
         assert_eq!(settings.reminder_repeat_interval_sec, 10 * 60);
         assert_eq!(settings.reminder_repeat_max_times, 0);
+        assert_eq!(settings.reminder_digest_threshold, 5);
+        assert_eq!(settings.work_day_start, "09:00");
+        assert_eq!(settings.work_day_end, "18:00");
+        assert_eq!(settings.reminder_lead_minutes, ReminderLeadTimes::default());
+        assert!(!settings.email_reminder_enabled);
+        assert!(settings.smtp_host.is_empty());
+        assert_eq!(settings.smtp_port, 587);
+        assert_eq!(settings.smtp_security, SmtpSecurity::StartTls);
+        assert!(settings.smtp_username.is_empty());
+        assert!(settings.email_from.is_empty());
+        assert!(settings.email_to.is_empty());
+        assert_eq!(settings.email_unacked_minutes, 15);
+        assert!(settings.email_when_headless);
+        assert_eq!(settings.quadrant_config, QuadrantConfig::default());
+        assert!(settings.view_prefs.is_empty());
+        assert_eq!(settings.week_starts_on, WeekStart::Monday);
+        assert_eq!(settings.date_format, DateFormat::Ymd);
+        assert_eq!(settings.overdue_grace_minutes, 0);
+        assert_eq!(settings.last_maintenance_at, None);
+        assert!(!settings.carry_over_enabled);
+        assert_eq!(settings.last_carry_over_at, None);
+        assert!(!settings.markdown_sync_enabled);
+        assert!(!settings.caldav_enabled);
+        assert_eq!(settings.caldav_sync_interval_minutes, 30);
+        assert_eq!(settings.last_caldav_sync_at, None);
+        assert_eq!(settings.storage_engine, StorageEngine::Json);
+        assert_eq!(settings.obsidian_vault_path, "");
+        assert_eq!(settings.obsidian_daily_folder, "Daily");
+        assert!(!settings.mqtt_enabled);
+        assert_eq!(settings.mqtt_port, 1883);
+        assert!(!settings.mqtt_tls);
+        assert_eq!(settings.mqtt_topic_prefix, "mustdo");
     }
 
     #[test]
@@ -639,6 +1796,20 @@ mod tests {
         assert!(matches!(back, RepeatRule::Daily { workday_only: true }));
     }
 
+    #[test]
+    fn project_mute_defaults_off_and_expires_at_muted_until() {
+        let mut project: Project = serde_json::from_str(r#"{"id":"p","name":"P"}"#).unwrap();
+        assert!(!project.reminders_muted);
+        assert_eq!(project.muted_until, None);
+        assert!(!project.reminders_muted_at(100));
+
+        project.reminders_muted = true;
+        assert!(project.reminders_muted_at(i64::MAX));
+        project.muted_until = Some(100);
+        assert!(project.reminders_muted_at(99));
+        assert!(!project.reminders_muted_at(100));
+    }
+
     #[test]
     fn task_sort_order_defaults_to_zero_when_missing() {
         let json = r#"
@@ -695,6 +1866,97 @@ mod tests {
         assert_eq!(task.reminder.kind, ReminderKind::None);
         assert!(!task.reminder.forced_dismissed);
         assert_eq!(task.repeat, RepeatRule::None);
+        assert_eq!(task.waiting_on, None);
+        assert_eq!(task.waiting_since, None);
+        assert_eq!(task.series_id, None);
+        assert!(!task.pinned);
+        assert_eq!(task.context, None);
+        assert!(task.comments.is_empty());
+        assert_eq!(task.progress, TaskProgress::default());
+    }
+
+    #[test]
+    fn task_progress_is_derived_from_steps_and_never_read_back() {
+        let json = r#"{"id":"t","title":"t","due_at":1,"completed_at":null,"created_at":1,"updated_at":1,"notes":null,
+            "steps":[
+              {"id":"a","title":"a","completed":true,"created_at":1,"completed_at":2},
+              {"id":"b","title":"b","completed":false,"created_at":1,"completed_at":null},
+              {"id":"c","title":"c","completed":false,"created_at":1,"completed_at":null}
+            ],
+            "progress":{"steps_completed":3,"steps_total":3,"percent_complete":100}}"#;
+        let mut task: Task = serde_json::from_str(json).unwrap();
+        assert_eq!(task.progress, TaskProgress::default());
+
+        task.refresh_progress();
+        assert_eq!(
+            task.progress,
+            TaskProgress {
+                steps_completed: 1,
+                steps_total: 3,
+                percent_complete: 33,
+            }
+        );
+        let value = serde_json::to_value(&task).unwrap();
+        assert_eq!(value["progress"]["percent_complete"], 33);
+        assert_eq!(TaskProgress::from_steps(&[]).percent_complete, 0);
+    }
+
+    #[test]
+    fn task_is_overdue_respects_grace_period() {
+        let json = r#"{"id":"t","title":"t","due_at":1000,"completed_at":null,"created_at":1,"updated_at":1,"notes":null}"#;
+        let mut task: Task = serde_json::from_str(json).unwrap();
+
+        assert!(!task.is_overdue(1000, 0));
+        assert!(task.is_overdue(1001, 0));
+        assert!(!task.is_overdue(1000 + 15 * 60, 15));
+        assert!(task.is_overdue(1001 + 15 * 60, 15));
+        // Negative grace behaves like no grace; completed tasks are never overdue.
+        assert!(task.is_overdue(1001, -30));
+        task.completed = true;
+        assert!(!task.is_overdue(i64::MAX, 0));
+    }
+
+    #[test]
+    fn reminder_lead_times_default_parse_and_clamp() {
+        let leads = ReminderLeadTimes::default();
+        assert_eq!(leads.default_target(&ReminderKind::Normal, 6_000), 5_400);
+        assert_eq!(leads.default_target(&ReminderKind::Forced, 6_000), 6_000);
+        assert_eq!(leads.minutes(&ReminderKind::None), 0);
+
+        let mut leads: ReminderLeadTimes = serde_json::from_str(r#"{"forced":5}"#).unwrap();
+        assert_eq!((leads.normal, leads.forced), (10, 5));
+        assert_eq!(leads.default_target(&ReminderKind::Forced, 6_000), 5_700);
+        assert_eq!(
+            leads.default_target(&ReminderKind::Forced, i64::MIN),
+            i64::MIN
+        );
+
+        leads.normal = u32::MAX;
+        leads.clamp();
+        assert_eq!(leads.normal, MAX_REMINDER_LEAD_MINUTES);
+        assert_eq!(leads.forced, 5);
+    }
+
+    #[test]
+    fn task_due_at_is_optional_and_reads_old_files() {
+        let dated: Task = serde_json::from_str(
+            r#"{"id":"t","title":"t","due_at":1000,"completed_at":null,"created_at":1,"updated_at":1,"notes":null}"#,
+        )
+        .unwrap();
+        assert_eq!(dated.due_at, Some(1000));
+        assert_eq!(dated.due_sort_key(), 1000);
+
+        for json in [
+            r#"{"id":"u","title":"u","due_at":null,"completed_at":null,"created_at":1,"updated_at":1,"notes":null}"#,
+            r#"{"id":"u","title":"u","completed_at":null,"created_at":1,"updated_at":1,"notes":null}"#,
+        ] {
+            let undated: Task = serde_json::from_str(json).unwrap();
+            assert_eq!(undated.due_at, None);
+            assert!(!undated.is_overdue(i64::MAX, 0));
+            assert_eq!(undated.due_sort_key(), i64::MAX);
+            let value = serde_json::to_value(&undated).unwrap();
+            assert!(value["due_at"].is_null());
+        }
     }
 
     #[test]
@@ -703,7 +1965,7 @@ mod tests {
             id: "t3".to_string(),
             project_id: "inbox".to_string(),
             title: "non-default".to_string(),
-            due_at: 123,
+            due_at: Some(123),
             important: false,
             completed: false,
             completed_at: None,
@@ -722,10 +1984,38 @@ mod tests {
                 forced_dismissed: false,
                 last_fired_at: None,
                 repeat_fired_count: 0,
+                email_sent_at: None,
+                specs: Vec::new(),
+                ignored_count: 0,
+                escalated: false,
             },
             repeat: RepeatRule::Daily {
                 workday_only: false,
             },
+            waiting_on: None,
+            waiting_since: None,
+            series_id: None,
+            pinned: false,
+            context: None,
+            comments: Vec::new(),
+            progress: Default::default(),
+            spawn_ahead: 0,
+            repeat_from_completion: false,
+            end_after_occurrences: None,
+            end_by_date: None,
+            occurrence_index: 0,
+            timezone: None,
+            escalate_after: None,
+            start_at: None,
+            someday: false,
+            carry_over_count: 0,
+            skip_carry_over: false,
+            color: None,
+            emoji: None,
+            estimate_minutes: None,
+            attachments: Vec::new(),
+            time_entries: Vec::new(),
+            blocked_by: Vec::new(),
         };
 
         assert_ne!(task.reminder.kind, ReminderKind::None);
@@ -0,0 +1,334 @@
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::models::Settings;
+use crate::outbound::EventSink;
+
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+const KEEP_ALIVE_SECS: u16 = 30;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MqttConfig {
+    pub host: String,
+    pub port: u16,
+    pub tls: bool,
+    pub username: String,
+    pub password: Option<String>,
+    pub topic_prefix: String,
+    pub client_id: String,
+}
+
+/// Validates the MQTT settings and pairs them with the stored password.
+pub fn mqtt_config(settings: &Settings, password: Option<String>) -> Result<MqttConfig, String> {
+    let host = settings.mqtt_host.trim();
+    if host.is_empty() {
+        return Err("mqtt host missing (settings.mqtt_host)".to_string());
+    }
+    if settings.mqtt_port == 0 {
+        return Err("mqtt port invalid (settings.mqtt_port)".to_string());
+    }
+    let topic_prefix = settings.mqtt_topic_prefix.trim().trim_end_matches('/');
+    if topic_prefix.is_empty() || topic_prefix.contains(['+', '#']) {
+        return Err("mqtt topic prefix invalid (settings.mqtt_topic_prefix)".to_string());
+    }
+    Ok(MqttConfig {
+        host: host.to_string(),
+        port: settings.mqtt_port,
+        tls: settings.mqtt_tls,
+        username: settings.mqtt_username.trim().to_string(),
+        password,
+        topic_prefix: topic_prefix.to_string(),
+        client_id: format!("mustdo-{}", std::process::id()),
+    })
+}
+
+fn push_remaining_length(out: &mut Vec<u8>, mut len: usize) {
+    loop {
+        let mut byte = (len % 128) as u8;
+        len /= 128;
+        if len > 0 {
+            byte |= 0x80;
+        }
+        out.push(byte);
+        if len == 0 {
+            break;
+        }
+    }
+}
+
+fn push_str(out: &mut Vec<u8>, value: &str) {
+    out.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    out.extend_from_slice(value.as_bytes());
+}
+
+fn packet(header: u8, body: Vec<u8>) -> Vec<u8> {
+    let mut out = vec![header];
+    push_remaining_length(&mut out, body.len());
+    out.extend(body);
+    out
+}
+
+/// MQTT 3.1.1 CONNECT with a clean session.
+pub fn encode_connect(config: &MqttConfig) -> Vec<u8> {
+    let mut flags = 0x02;
+    if !config.username.is_empty() {
+        flags |= 0x80;
+        if config.password.is_some() {
+            flags |= 0x40;
+        }
+    }
+    let mut body = Vec::new();
+    push_str(&mut body, "MQTT");
+    body.push(4);
+    body.push(flags);
+    body.extend_from_slice(&KEEP_ALIVE_SECS.to_be_bytes());
+    push_str(&mut body, &config.client_id);
+    if !config.username.is_empty() {
+        push_str(&mut body, &config.username);
+        if let Some(password) = &config.password {
+            push_str(&mut body, password);
+        }
+    }
+    packet(0x10, body)
+}
+
+/// QoS 0 PUBLISH, not retained: reminder firings are moments, not state.
+pub fn encode_publish(topic: &str, payload: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    push_str(&mut body, topic);
+    body.extend_from_slice(payload);
+    packet(0x30, body)
+}
+
+const DISCONNECT: [u8; 2] = [0xE0, 0x00];
+
+fn check_connack(bytes: [u8; 4]) -> Result<(), String> {
+    if bytes[0] != 0x20 || bytes[1] != 0x02 {
+        return Err("mqtt: unexpected reply to CONNECT".to_string());
+    }
+    match bytes[3] {
+        0 => Ok(()),
+        4 | 5 => Err("mqtt: broker rejected the credentials".to_string()),
+        code => Err(format!("mqtt: broker refused the connection (code {code})")),
+    }
+}
+
+fn exchange(
+    stream: &mut impl ReadWrite,
+    config: &MqttConfig,
+    topic: &str,
+    payload: &[u8],
+) -> Result<(), String> {
+    stream
+        .write_all(&encode_connect(config))
+        .map_err(|err| format!("mqtt: send CONNECT failed: {err}"))?;
+    let mut connack = [0u8; 4];
+    stream
+        .read_exact(&mut connack)
+        .map_err(|err| format!("mqtt: read CONNACK failed: {err}"))?;
+    check_connack(connack)?;
+    stream
+        .write_all(&encode_publish(topic, payload))
+        .and_then(|_| stream.write_all(&DISCONNECT))
+        .and_then(|_| stream.flush())
+        .map_err(|err| format!("mqtt: publish failed: {err}"))
+}
+
+trait ReadWrite: Read + Write {}
+impl<T: Read + Write> ReadWrite for T {}
+
+#[cfg(feature = "app")]
+fn publish_tls(
+    tcp: TcpStream,
+    config: &MqttConfig,
+    topic: &str,
+    payload: &[u8],
+) -> Result<(), String> {
+    use std::sync::Arc;
+
+    let roots = rustls::RootCertStore::from_iter(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let tls_config = rustls::ClientConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .map_err(|err| format!("mqtt: tls setup failed: {err}"))?
+    .with_root_certificates(roots)
+    .with_no_client_auth();
+    let server_name = rustls::pki_types::ServerName::try_from(config.host.clone())
+        .map_err(|err| format!("mqtt: invalid tls server name: {err}"))?;
+    let connection = rustls::ClientConnection::new(Arc::new(tls_config), server_name)
+        .map_err(|err| format!("mqtt: tls setup failed: {err}"))?;
+    let mut stream = rustls::StreamOwned::new(connection, tcp);
+    exchange(&mut stream, config, topic, payload)
+}
+
+#[cfg(not(feature = "app"))]
+fn publish_tls(_: TcpStream, _: &MqttConfig, _: &str, _: &[u8]) -> Result<(), String> {
+    Err("mqtt: tls requires the desktop build".to_string())
+}
+
+/// One short-lived connection per message; events are rare enough that a persistent session
+/// (and its reconnect logic) is not worth it.
+pub fn publish(config: &MqttConfig, topic: &str, payload: &[u8]) -> Result<(), String> {
+    let addr = (config.host.as_str(), config.port)
+        .to_socket_addrs()
+        .map_err(|err| format!("mqtt: resolve {} failed: {err}", config.host))?
+        .next()
+        .ok_or_else(|| format!("mqtt: no address for {}", config.host))?;
+    let mut tcp = TcpStream::connect_timeout(&addr, IO_TIMEOUT)
+        .map_err(|err| format!("mqtt: connect failed: {err}"))?;
+    tcp.set_read_timeout(Some(IO_TIMEOUT))
+        .and_then(|_| tcp.set_write_timeout(Some(IO_TIMEOUT)))
+        .map_err(|err| format!("mqtt: socket setup failed: {err}"))?;
+    if config.tls {
+        publish_tls(tcp, config, topic, payload)
+    } else {
+        exchange(&mut tcp, config, topic, payload)
+    }
+}
+
+pub struct MqttSink {
+    pub config: MqttConfig,
+}
+
+impl EventSink for MqttSink {
+    fn name(&self) -> &'static str {
+        "mqtt"
+    }
+
+    fn deliver(&self, topic: &str, payload: &[u8]) -> Result<(), String> {
+        let topic = format!("{}/{topic}", self.config.topic_prefix);
+        publish(&self.config, &topic, payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    fn config(port: u16) -> MqttConfig {
+        let settings = Settings {
+            mqtt_host: "127.0.0.1".to_string(),
+            mqtt_port: port,
+            mqtt_topic_prefix: "home/mustdo/".to_string(),
+            ..Settings::default()
+        };
+        mqtt_config(&settings, None).unwrap()
+    }
+
+    #[test]
+    fn mqtt_config_validates_host_port_and_prefix() {
+        let settings = Settings::default();
+        assert!(mqtt_config(&settings, None).unwrap_err().contains("host"));
+
+        let mut settings = Settings {
+            mqtt_host: " broker.local ".to_string(),
+            mqtt_username: "ha".to_string(),
+            ..Settings::default()
+        };
+        let config = mqtt_config(&settings, Some("pw".to_string())).unwrap();
+        assert_eq!(config.host, "broker.local");
+        assert_eq!(config.port, 1883);
+        assert_eq!(config.topic_prefix, "mustdo");
+
+        settings.mqtt_port = 0;
+        assert!(mqtt_config(&settings, None).unwrap_err().contains("port"));
+        settings.mqtt_port = 8883;
+        for prefix in ["", "/", "home/+/x", "#"] {
+            settings.mqtt_topic_prefix = prefix.to_string();
+            assert!(mqtt_config(&settings, None).unwrap_err().contains("prefix"));
+        }
+    }
+
+    #[test]
+    fn packets_follow_mqtt_311_encoding() {
+        let mut len = Vec::new();
+        push_remaining_length(&mut len, 321);
+        assert_eq!(len, vec![0xC1, 0x02]);
+
+        let mut anonymous = config(1883);
+        anonymous.client_id = "c".to_string();
+        assert_eq!(
+            encode_connect(&anonymous),
+            vec![0x10, 13, 0, 4, b'M', b'Q', b'T', b'T', 4, 0x02, 0, 30, 0, 1, b'c']
+        );
+
+        let mut with_login = anonymous.clone();
+        with_login.username = "u".to_string();
+        with_login.password = Some("p".to_string());
+        let connect = encode_connect(&with_login);
+        assert_eq!(connect[9], 0xC2);
+        assert!(connect.ends_with(&[0, 1, b'u', 0, 1, b'p']));
+
+        assert_eq!(
+            encode_publish("a/b", b"{}"),
+            vec![0x30, 7, 0, 3, b'a', b'/', b'b', b'{', b'}']
+        );
+
+        assert!(check_connack([0x20, 0x02, 0, 0]).is_ok());
+        assert!(check_connack([0x20, 0x02, 0, 5])
+            .unwrap_err()
+            .contains("credentials"));
+        assert!(check_connack([0x20, 0x02, 0, 2])
+            .unwrap_err()
+            .contains("code 2"));
+        assert!(check_connack([0x30, 0, 0, 0]).is_err());
+    }
+
+    fn fake_broker(return_code: u8) -> (u16, std::thread::JoinHandle<Vec<u8>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = std::thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            let mut header = [0u8; 2];
+            socket.read_exact(&mut header).unwrap();
+            let mut connect = vec![0u8; usize::from(header[1])];
+            socket.read_exact(&mut connect).unwrap();
+            socket.write_all(&[0x20, 0x02, 0, return_code]).unwrap();
+            let mut rest = Vec::new();
+            let _ = socket.read_to_end(&mut rest);
+            rest
+        });
+        (port, handle)
+    }
+
+    #[test]
+    fn sink_publishes_under_the_topic_prefix() {
+        let (port, broker) = fake_broker(0);
+        let sink = MqttSink {
+            config: config(port),
+        };
+        assert_eq!(sink.name(), "mqtt");
+        sink.deliver("reminder/forced", b"{}").unwrap();
+
+        let mut expected = encode_publish("home/mustdo/reminder/forced", b"{}");
+        expected.extend_from_slice(&DISCONNECT);
+        assert_eq!(broker.join().unwrap(), expected);
+    }
+
+    #[test]
+    fn publish_reports_refused_and_failed_connections() {
+        let (port, broker) = fake_broker(5);
+        assert!(publish(&config(port), "t", b"x")
+            .unwrap_err()
+            .contains("credentials"));
+        assert!(broker.join().unwrap().is_empty());
+
+        let closed = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = closed.local_addr().unwrap().port();
+        drop(closed);
+        assert!(publish(&config(port), "t", b"x")
+            .unwrap_err()
+            .contains("connect failed"));
+
+        #[cfg(not(feature = "app"))]
+        {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let mut tls = config(listener.local_addr().unwrap().port());
+            tls.tls = true;
+            assert!(publish(&tls, "t", b"x").unwrap_err().contains("tls"));
+        }
+    }
+}
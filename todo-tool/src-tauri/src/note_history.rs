@@ -0,0 +1,196 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::models::{Task, Timestamp};
+use crate::storage::{append_jsonl, read_jsonl, write_jsonl, StorageError};
+
+const NOTE_HISTORY_FILE: &str = "note_history.jsonl";
+/// Versions kept per task; older ones are dropped when a new one is added.
+pub const NOTE_HISTORY_LIMIT: usize = 20;
+
+/// The notes a task had before an edit replaced them.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct NoteVersion {
+    pub task_id: String,
+    /// Counts up per task from 1; stays stable when older versions are dropped.
+    pub version: u32,
+    pub notes: String,
+    /// When these notes were replaced.
+    pub saved_at: Timestamp,
+}
+
+fn read_all(root: &Path) -> Result<Vec<NoteVersion>, StorageError> {
+    read_jsonl(&root.join(NOTE_HISTORY_FILE))
+}
+
+/// The newest `NOTE_HISTORY_LIMIT` versions of every task, in file order.
+fn newest_per_task(all: &[NoteVersion]) -> Vec<&NoteVersion> {
+    // Walk from the newest line back, counting versions per task.
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    let mut kept: Vec<&NoteVersion> = Vec::with_capacity(all.len());
+    for entry in all.iter().rev() {
+        let count = seen.entry(entry.task_id.as_str()).or_default();
+        *count += 1;
+        if *count <= NOTE_HISTORY_LIMIT {
+            kept.push(entry);
+        }
+    }
+    kept.reverse();
+    kept
+}
+
+/// The notes `previous` -> `next` overwrote, if the edit changed non-blank notes.
+pub fn replaced_notes(previous: &Task, next: &Task) -> Option<String> {
+    let notes = previous.notes.as_deref()?;
+    (!notes.trim().is_empty() && previous.notes != next.notes).then(|| notes.to_string())
+}
+
+/// Appends the replaced notes of every changed task to `note_history.jsonl`, then trims each
+/// of those tasks to the newest `NOTE_HISTORY_LIMIT` versions.
+pub fn record_note_versions(
+    root: &Path,
+    replaced: &[(String, String)],
+    now: Timestamp,
+) -> Result<Vec<NoteVersion>, StorageError> {
+    if replaced.is_empty() {
+        return Ok(Vec::new());
+    }
+    let existing = read_all(root)?;
+    let mut added: Vec<NoteVersion> = Vec::new();
+    for (task_id, notes) in replaced {
+        let latest = existing
+            .iter()
+            .chain(added.iter())
+            .filter(|entry| entry.task_id == *task_id)
+            .max_by_key(|entry| entry.version);
+        if latest.is_some_and(|latest| latest.notes == *notes) {
+            continue;
+        }
+        added.push(NoteVersion {
+            task_id: task_id.clone(),
+            version: latest.map_or(1, |latest| latest.version + 1),
+            notes: notes.clone(),
+            saved_at: now,
+        });
+    }
+    if added.is_empty() {
+        return Ok(added);
+    }
+
+    let path = root.join(NOTE_HISTORY_FILE);
+    append_jsonl(&path, &added)?;
+    let all: Vec<NoteVersion> = existing.into_iter().chain(added.iter().cloned()).collect();
+    let over_limit = added.iter().any(|entry| {
+        all.iter()
+            .filter(|other| other.task_id == entry.task_id)
+            .count()
+            > NOTE_HISTORY_LIMIT
+    });
+    if over_limit {
+        write_jsonl(&path, &newest_per_task(&all))?;
+    }
+    Ok(added)
+}
+
+/// Drops unparsable lines and versions past the per-task limit; returns how many lines were
+/// dropped.
+pub fn compact_note_history(root: &Path) -> Result<usize, StorageError> {
+    let path = root.join(NOTE_HISTORY_FILE);
+    if !path.exists() {
+        return Ok(0);
+    }
+    let lines = fs::read_to_string(&path)?.lines().count();
+    let all = read_all(root)?;
+    let kept = newest_per_task(&all);
+    let dropped = lines - kept.len();
+    if dropped > 0 {
+        write_jsonl(&path, &kept)?;
+    }
+    Ok(dropped)
+}
+
+/// Saved versions of one task's notes, newest first; unparsable lines are skipped.
+pub fn read_note_history(root: &Path, task_id: &str) -> Result<Vec<NoteVersion>, StorageError> {
+    let mut versions: Vec<NoteVersion> = read_all(root)?
+        .into_iter()
+        .filter(|entry| entry.task_id == task_id)
+        .collect();
+    versions.sort_by_key(|entry| std::cmp::Reverse(entry.version));
+    Ok(versions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+
+    fn replaced(task_id: &str, notes: &str) -> Vec<(String, String)> {
+        vec![(task_id.to_string(), notes.to_string())]
+    }
+
+    #[test]
+    fn versions_count_up_per_task_and_skip_repeats() {
+        let root = tempfile::tempdir().unwrap();
+        let path = root.path();
+        assert!(read_note_history(path, "a").unwrap().is_empty());
+
+        record_note_versions(path, &replaced("a", "first"), 10).unwrap();
+        record_note_versions(path, &replaced("b", "other"), 11).unwrap();
+        record_note_versions(path, &replaced("a", "second"), 12).unwrap();
+        assert!(record_note_versions(path, &replaced("a", "second"), 13)
+            .unwrap()
+            .is_empty());
+
+        let history = read_note_history(path, "a").unwrap();
+        let versions: Vec<(u32, &str)> = history
+            .iter()
+            .map(|entry| (entry.version, entry.notes.as_str()))
+            .collect();
+        assert_eq!(versions, [(2, "second"), (1, "first")]);
+        assert_eq!(read_note_history(path, "b").unwrap()[0].version, 1);
+    }
+
+    #[test]
+    fn only_the_newest_versions_of_a_task_are_kept() {
+        let root = tempfile::tempdir().unwrap();
+        let path = root.path();
+        record_note_versions(path, &replaced("b", "keep me"), 1).unwrap();
+        for n in 0..(NOTE_HISTORY_LIMIT + 5) {
+            record_note_versions(path, &replaced("a", &format!("v{n}")), n as i64).unwrap();
+        }
+        let history = read_note_history(path, "a").unwrap();
+        assert_eq!(history.len(), NOTE_HISTORY_LIMIT);
+        assert_eq!(history[0].version, NOTE_HISTORY_LIMIT as u32 + 5);
+        assert_eq!(history.last().unwrap().version, 6);
+        assert_eq!(read_note_history(path, "b").unwrap().len(), 1);
+
+        let file = path.join(NOTE_HISTORY_FILE);
+        fs::write(&file, fs::read_to_string(&file).unwrap() + "garbage\n").unwrap();
+        assert_eq!(compact_note_history(path).unwrap(), 1);
+        assert_eq!(compact_note_history(path).unwrap(), 0);
+        assert_eq!(
+            read_note_history(path, "a").unwrap().len(),
+            NOTE_HISTORY_LIMIT
+        );
+    }
+
+    #[test]
+    fn only_overwritten_non_blank_notes_count() {
+        let task = |notes: Option<&str>| test_support::task("a").notes(notes);
+        assert_eq!(
+            replaced_notes(&task(Some("old")), &task(Some("new"))).as_deref(),
+            Some("old")
+        );
+        assert_eq!(
+            replaced_notes(&task(Some("old")), &task(None)).as_deref(),
+            Some("old")
+        );
+        assert_eq!(
+            replaced_notes(&task(Some("same")), &task(Some("same"))),
+            None
+        );
+        assert_eq!(replaced_notes(&task(Some("  ")), &task(Some("new"))), None);
+        assert_eq!(replaced_notes(&task(None), &task(Some("new"))), None);
+    }
+}
@@ -0,0 +1,365 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+
+use crate::models::{RepeatRule, Task, Timestamp};
+
+/// The export owns only the text between these lines; everything else in a note is the user's.
+pub const BLOCK_START: &str = "<!-- mustdo:begin -->";
+pub const BLOCK_END: &str = "<!-- mustdo:end -->";
+/// Completed tasks stay in their daily note for this long after completion.
+const COMPLETED_KEEP_DAYS: i64 = 14;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct ObsidianExportReport {
+    /// Daily notes created or updated, as `YYYY-MM-DD.md`.
+    pub written: Vec<String>,
+    /// Notes whose managed block was removed because no task is due that day any more.
+    pub cleared: Vec<String>,
+    pub tasks: usize,
+}
+
+fn local_date(ts: Timestamp) -> Option<NaiveDate> {
+    Local
+        .timestamp_opt(ts, 0)
+        .single()
+        .map(|dt| dt.date_naive())
+}
+
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn recurrence(rule: &RepeatRule) -> Option<String> {
+    let text = match rule {
+        RepeatRule::None => return None,
+        RepeatRule::Daily { workday_only: true } => "every weekday".to_string(),
+        RepeatRule::Daily { .. } => "every day".to_string(),
+        RepeatRule::Weekly { .. } => "every week".to_string(),
+        RepeatRule::Monthly { .. } => "every month".to_string(),
+        RepeatRule::Yearly { .. } => "every year".to_string(),
+        RepeatRule::EveryNDays { n } => format!("every {} days", (*n).max(1)),
+        RepeatRule::EveryNWeeks { n, .. } => format!("every {} weeks", (*n).max(1)),
+        RepeatRule::EveryNMonths { n, .. } => format!("every {} months", (*n).max(1)),
+    };
+    Some(text)
+}
+
+/// One task in Obsidian Tasks syntax:
+/// `- [ ] title #tag ⏫ 🔁 every day 📅 2024-05-01 ✅ 2024-05-01`, steps nested below.
+pub fn task_line(task: &Task, due: NaiveDate) -> String {
+    let mut line = format!(
+        "- [{}] {}",
+        if task.completed { "x" } else { " " },
+        single_line(&task.title)
+    );
+    for tag in &task.tags {
+        let tag: String = tag.split_whitespace().collect::<Vec<_>>().join("-");
+        let tag = tag.trim_start_matches('#');
+        if !tag.is_empty() {
+            line.push_str(&format!(" #{tag}"));
+        }
+    }
+    if task.important {
+        line.push_str(" ⏫");
+    }
+    if let Some(rule) = recurrence(&task.repeat) {
+        line.push_str(&format!(" 🔁 {rule}"));
+    }
+    line.push_str(&format!(" 📅 {}", due.format("%Y-%m-%d")));
+    if let Some(done) = task
+        .completed_at
+        .filter(|_| task.completed)
+        .and_then(local_date)
+    {
+        line.push_str(&format!(" ✅ {}", done.format("%Y-%m-%d")));
+    }
+    for step in &task.steps {
+        line.push_str(&format!(
+            "\n    - [{}] {}",
+            if step.completed { "x" } else { " " },
+            single_line(&step.title)
+        ));
+    }
+    line
+}
+
+/// Dated, non-Someday tasks by local due day. Completed tasks drop out once they were finished
+/// more than [`COMPLETED_KEEP_DAYS`] ago so old history does not keep rewriting notes.
+pub fn plan_daily_notes(tasks: &[Task], now: DateTime<Local>) -> BTreeMap<NaiveDate, Vec<&Task>> {
+    let keep_after = now.timestamp() - COMPLETED_KEEP_DAYS * 24 * 60 * 60;
+    let mut days: BTreeMap<NaiveDate, Vec<&Task>> = BTreeMap::new();
+    for task in tasks {
+        if task.someday {
+            continue;
+        }
+        if task.completed && task.completed_at.unwrap_or(task.updated_at) < keep_after {
+            continue;
+        }
+        let Some(due) = task.due_at.and_then(local_date) else {
+            continue;
+        };
+        days.entry(due).or_default().push(task);
+    }
+    for list in days.values_mut() {
+        list.sort_by_key(|task| (task.completed, task.due_sort_key(), task.sort_order));
+    }
+    days
+}
+
+pub fn render_block(tasks: &[&Task], due: NaiveDate) -> String {
+    let mut block = String::from(BLOCK_START);
+    block.push('\n');
+    for task in tasks {
+        block.push_str(&task_line(task, due));
+        block.push('\n');
+    }
+    block.push_str(BLOCK_END);
+    block
+}
+
+/// Replaces the managed block in `existing`, appends it when the note has none, or removes it
+/// when `block` is `None`. Text outside the block is kept byte for byte.
+pub fn merge_block(existing: &str, block: Option<&str>) -> String {
+    let span = existing.find(BLOCK_START).and_then(|start| {
+        existing[start..]
+            .find(BLOCK_END)
+            .map(|end| (start, start + end + BLOCK_END.len()))
+    });
+    match (span, block) {
+        (Some((start, end)), Some(block)) => {
+            format!("{}{}{}", &existing[..start], block, &existing[end..])
+        }
+        (Some((start, end)), None) => {
+            let before = existing[..start].trim_end_matches('\n');
+            let after = existing[end..].trim_start_matches('\n');
+            match (before.is_empty(), after.is_empty()) {
+                (true, _) => after.to_string(),
+                (false, true) => format!("{before}\n"),
+                (false, false) => format!("{before}\n\n{after}"),
+            }
+        }
+        (None, Some(block)) if existing.trim().is_empty() => format!("{block}\n"),
+        (None, Some(block)) => {
+            format!("{}\n\n{}\n", existing.trim_end_matches('\n'), block)
+        }
+        (None, None) => existing.to_string(),
+    }
+}
+
+fn write_note(path: &Path, content: &str) -> std::io::Result<()> {
+    // Rename over the note so an interrupted write never leaves the user's note half-written.
+    let tmp = path.with_extension("md.mustdo.tmp");
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, path)
+}
+
+fn note_name(day: NaiveDate) -> String {
+    format!("{}.md", day.format("%Y-%m-%d"))
+}
+
+/// Writes one `YYYY-MM-DD.md` per day into `folder` (created if needed) and clears the managed
+/// block from notes of days that no longer have tasks. Unchanged notes are not rewritten.
+pub fn export_daily_notes(
+    folder: &Path,
+    tasks: &[Task],
+    now: DateTime<Local>,
+) -> std::io::Result<ObsidianExportReport> {
+    fs::create_dir_all(folder)?;
+    let days = plan_daily_notes(tasks, now);
+    let mut report = ObsidianExportReport {
+        tasks: days.values().map(Vec::len).sum(),
+        ..ObsidianExportReport::default()
+    };
+
+    for (day, list) in &days {
+        let path = folder.join(note_name(*day));
+        let existing = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+        let next = merge_block(&existing, Some(&render_block(list, *day)));
+        if next != existing {
+            write_note(&path, &next)?;
+            report.written.push(note_name(*day));
+        }
+    }
+
+    let mut stale: Vec<PathBuf> = Vec::new();
+    for entry in fs::read_dir(folder)?.filter_map(|entry| entry.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(day) = name
+            .strip_suffix(".md")
+            .and_then(|stem| NaiveDate::parse_from_str(stem, "%Y-%m-%d").ok())
+        else {
+            continue;
+        };
+        if !days.contains_key(&day) {
+            stale.push(entry.path());
+        }
+    }
+    stale.sort();
+    for path in stale {
+        let existing = fs::read_to_string(&path)?;
+        if !existing.contains(BLOCK_START) {
+            continue;
+        }
+        write_note(&path, &merge_block(&existing, None))?;
+        if let Some(name) = path.file_name() {
+            report.cleared.push(name.to_string_lossy().to_string());
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_support, Step};
+
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 3, 18, 9, 0, 0).unwrap()
+    }
+
+    fn at(day: u32, hour: u32) -> Timestamp {
+        Local
+            .with_ymd_and_hms(2026, 3, day, hour, 0, 0)
+            .unwrap()
+            .timestamp()
+    }
+
+    fn task(id: &str, due_at: Option<Timestamp>) -> Task {
+        test_support::task(id).due(due_at)
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, day).unwrap()
+    }
+
+    #[test]
+    fn task_lines_use_obsidian_tasks_syntax() {
+        let mut open = task("open", Some(at(18, 18)));
+        open.title = "Call\nmom".to_string();
+        open.important = true;
+        open.tags = vec!["family time".to_string(), "#home".to_string()];
+        open.repeat = RepeatRule::Daily { workday_only: true };
+        open.steps = vec![Step {
+            id: "s".to_string(),
+            title: "dial".to_string(),
+            completed: true,
+            created_at: 1,
+            completed_at: None,
+            parent_id: None,
+        }];
+        assert_eq!(
+            task_line(&open, date(18)),
+            "- [ ] Call mom #family-time #home ⏫ 🔁 every weekday 📅 2026-03-18\n    - [x] dial"
+        );
+
+        let mut done = task("done", Some(at(17, 9)));
+        done.completed = true;
+        done.completed_at = Some(at(18, 8));
+        assert_eq!(
+            task_line(&done, date(17)),
+            "- [x] done 📅 2026-03-17 ✅ 2026-03-18"
+        );
+    }
+
+    #[test]
+    fn plan_groups_by_day_and_skips_undated_someday_and_old_completions() {
+        let mut old = task("old", Some(at(1, 9)));
+        old.completed = true;
+        old.completed_at = Some(at(1, 10));
+        let mut recent = task("recent", Some(at(17, 9)));
+        recent.completed = true;
+        recent.completed_at = Some(at(17, 10));
+        let mut parked = task("parked", Some(at(18, 9)));
+        parked.someday = true;
+        let tasks = vec![
+            task("late", Some(at(18, 20))),
+            task("early", Some(at(18, 8))),
+            task("undated", None),
+            old,
+            recent,
+            parked,
+        ];
+        let days = plan_daily_notes(&tasks, now());
+        let ids: Vec<(NaiveDate, Vec<&str>)> = days
+            .iter()
+            .map(|(day, list)| (*day, list.iter().map(|t| t.id.as_str()).collect()))
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                (date(17), vec!["recent"]),
+                (date(18), vec!["early", "late"])
+            ]
+        );
+    }
+
+    #[test]
+    fn merge_keeps_user_text_around_the_managed_block() {
+        let block = render_block(&[], date(18));
+        assert_eq!(block, format!("{BLOCK_START}\n{BLOCK_END}"));
+
+        assert_eq!(merge_block("", Some("B")), "B\n");
+        assert_eq!(merge_block("# Notes\n", Some("B")), "# Notes\n\nB\n");
+        let note = format!("# Notes\n\n{BLOCK_START}\nold\n{BLOCK_END}\n\nafter\n");
+        assert_eq!(merge_block(&note, Some("NEW")), "# Notes\n\nNEW\n\nafter\n");
+        assert_eq!(merge_block(&note, None), "# Notes\n\nafter\n");
+        assert_eq!(
+            merge_block(&format!("{BLOCK_START}\nold\n{BLOCK_END}\n"), None),
+            ""
+        );
+        assert_eq!(merge_block("plain", None), "plain");
+        // An unterminated marker is user text, not a block.
+        assert_eq!(
+            merge_block(&format!("{BLOCK_START} oops"), Some("B")),
+            format!("{BLOCK_START} oops\n\nB\n")
+        );
+    }
+
+    #[test]
+    fn export_writes_updates_and_clears_daily_notes() {
+        let vault = tempfile::tempdir().unwrap();
+        let folder = vault.path().join("Daily");
+        fs::create_dir_all(&folder).unwrap();
+        fs::write(folder.join("2026-03-18.md"), "# Wednesday\nmy journal\n").unwrap();
+        fs::write(folder.join("2026-03-10.md"), "untouched\n").unwrap();
+
+        let tasks = vec![task("a", Some(at(18, 9))), task("b", Some(at(19, 9)))];
+        let report = export_daily_notes(&folder, &tasks, now()).unwrap();
+        assert_eq!(report.written, ["2026-03-18.md", "2026-03-19.md"]);
+        assert_eq!(report.tasks, 2);
+        let note = fs::read_to_string(folder.join("2026-03-18.md")).unwrap();
+        assert_eq!(
+            note,
+            format!(
+                "# Wednesday\nmy journal\n\n{BLOCK_START}\n- [ ] a 📅 2026-03-18\n{BLOCK_END}\n"
+            )
+        );
+
+        // Re-running without changes rewrites nothing.
+        let again = export_daily_notes(&folder, &tasks, now()).unwrap();
+        assert!(again.written.is_empty());
+
+        // `b` moved to another day: its old note loses the block, the user's text stays.
+        let mut moved = tasks[1].clone();
+        moved.due_at = Some(at(20, 9));
+        let report = export_daily_notes(&folder, &[tasks[0].clone(), moved], now()).unwrap();
+        assert_eq!(report.written, ["2026-03-20.md"]);
+        assert_eq!(report.cleared, ["2026-03-19.md"]);
+        assert_eq!(
+            fs::read_to_string(folder.join("2026-03-19.md")).unwrap(),
+            ""
+        );
+        assert_eq!(
+            fs::read_to_string(folder.join("2026-03-10.md")).unwrap(),
+            "untouched\n"
+        );
+        assert!(!folder.join("2026-03-18.md.mustdo.tmp").exists());
+    }
+}
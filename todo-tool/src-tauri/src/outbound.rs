@@ -0,0 +1,281 @@
+use std::path::Path;
+
+use crate::calendar::DateFormatter;
+use crate::models::{ReminderKind, Settings, Task};
+
+/// Something worth telling systems outside the app about (home automation, scripts).
+#[derive(Debug, Clone)]
+pub enum OutboundEvent {
+    TaskCreated(Task),
+    TaskUpdated(Task),
+    TaskCompleted(Task),
+    TaskDeleted { task_ids: Vec<String> },
+    ReminderFired(Vec<Task>),
+}
+
+fn task_summary(task: &Task, dates: &DateFormatter) -> serde_json::Value {
+    serde_json::json!({
+        "id": task.id,
+        "title": task.title,
+        "project_id": task.project_id,
+        "due_at": task.due_at,
+        "due_label": task.due_at.map(|due_at| dates.natural(due_at)),
+        "important": task.important,
+        "completed": task.completed,
+    })
+}
+
+fn has_forced(tasks: &[Task]) -> bool {
+    tasks
+        .iter()
+        .any(|task| task.reminder.kind == ReminderKind::Forced)
+}
+
+impl OutboundEvent {
+    /// Topic relative to the sink's own prefix. Forced firings get their own topic so a broker
+    /// rule can react to them alone.
+    pub fn topic(&self) -> &'static str {
+        match self {
+            OutboundEvent::TaskCreated(_) => "task/created",
+            OutboundEvent::TaskUpdated(_) => "task/updated",
+            OutboundEvent::TaskCompleted(_) => "task/completed",
+            OutboundEvent::TaskDeleted { .. } => "task/deleted",
+            OutboundEvent::ReminderFired(tasks) if has_forced(tasks) => "reminder/forced",
+            OutboundEvent::ReminderFired(_) => "reminder/fired",
+        }
+    }
+
+    /// `dates` supplies the event time and the localized `due_label` of each task.
+    pub fn payload(&self, dates: &DateFormatter) -> serde_json::Value {
+        let mut payload = match self {
+            OutboundEvent::TaskCreated(task)
+            | OutboundEvent::TaskUpdated(task)
+            | OutboundEvent::TaskCompleted(task) => {
+                serde_json::json!({ "task": task_summary(task, dates) })
+            }
+            OutboundEvent::TaskDeleted { task_ids } => serde_json::json!({ "task_ids": task_ids }),
+            OutboundEvent::ReminderFired(tasks) => serde_json::json!({
+                "forced": has_forced(tasks),
+                "tasks": tasks
+                    .iter()
+                    .map(|task| task_summary(task, dates))
+                    .collect::<Vec<_>>(),
+            }),
+        };
+        payload["event"] = self.topic().into();
+        payload["at"] = dates.now.timestamp().into();
+        payload
+    }
+}
+
+/// An outbound destination. Delivery is blocking; callers run it off the UI/command thread.
+pub trait EventSink: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn deliver(&self, topic: &str, payload: &[u8]) -> Result<(), String>;
+}
+
+pub fn any_sink_enabled(settings: &Settings) -> bool {
+    settings.mqtt_enabled
+}
+
+/// Sinks enabled in `settings`; misconfigured ones are logged and skipped. Built per dispatch so
+/// settings changes apply to the next event.
+pub fn configured_sinks(settings: &Settings, root: &Path) -> Vec<Box<dyn EventSink>> {
+    let mut sinks: Vec<Box<dyn EventSink>> = Vec::new();
+    if settings.mqtt_enabled {
+        let password = crate::secrets::get_secret(root, crate::secrets::MQTT_PASSWORD)
+            .unwrap_or_else(|err| {
+                log::warn!("outbound: failed to read mqtt password: {err}");
+                None
+            });
+        match crate::mqtt::mqtt_config(settings, password) {
+            Ok(config) => sinks.push(Box::new(crate::mqtt::MqttSink { config })),
+            Err(err) => log::warn!("outbound: mqtt disabled err={err}"),
+        }
+    }
+    sinks
+}
+
+/// Delivers `event` to every sink; one failing sink does not stop the others.
+pub fn dispatch(
+    sinks: &[Box<dyn EventSink>],
+    event: &OutboundEvent,
+    dates: &DateFormatter,
+) -> Vec<(&'static str, String)> {
+    let payload = event.payload(dates).to_string();
+    let mut failures = Vec::new();
+    for sink in sinks {
+        match sink.deliver(event.topic(), payload.as_bytes()) {
+            Ok(()) => log::info!(
+                "outbound: delivered sink={} topic={}",
+                sink.name(),
+                event.topic()
+            ),
+            Err(err) => failures.push((sink.name(), err)),
+        }
+    }
+    failures
+}
+
+/// Fire-and-forget dispatch for the running app.
+#[cfg(all(feature = "app", not(test)))]
+pub fn spawn_dispatch<R: tauri::Runtime>(app: &tauri::AppHandle<R>, event: OutboundEvent) {
+    use tauri::Manager;
+
+    let state = app.state::<crate::state::AppState>();
+    let settings = state.settings();
+    if !any_sink_enabled(&settings) {
+        return;
+    }
+    let dates = DateFormatter::new(
+        &settings,
+        &sys_locale::get_locale().unwrap_or_default(),
+        state.clock().now_local(),
+    );
+    let root = match crate::profiles::app_profile_root(app) {
+        Ok(path) => path,
+        Err(err) => {
+            log::error!("outbound: app_data_dir failed: {err}");
+            return;
+        }
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        let sinks = configured_sinks(&settings, &root);
+        for (sink, err) in dispatch(&sinks, &event, &dates) {
+            log::warn!(
+                "outbound: delivery failed sink={sink} topic={} err={err}",
+                event.topic()
+            );
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::DateLocale;
+    use crate::models::test_support;
+    use chrono::TimeZone;
+    use std::sync::{Arc, Mutex};
+
+    fn dates(at: i64) -> DateFormatter {
+        DateFormatter {
+            format: crate::models::DateFormat::Ymd,
+            locale: DateLocale::En,
+            now: chrono::Local.timestamp_opt(at, 0).unwrap(),
+        }
+    }
+
+    fn task(id: &str, kind: ReminderKind) -> Task {
+        let mut task = test_support::task(id).due(100_000);
+        task.reminder.kind = kind;
+        task
+    }
+
+    struct Recording {
+        fail: bool,
+        seen: Arc<Mutex<Vec<(String, serde_json::Value)>>>,
+    }
+
+    impl EventSink for Recording {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        fn deliver(&self, topic: &str, payload: &[u8]) -> Result<(), String> {
+            self.seen
+                .lock()
+                .unwrap()
+                .push((topic.to_string(), serde_json::from_slice(payload).unwrap()));
+            if self.fail {
+                Err("down".to_string())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[test]
+    fn events_map_to_topics_and_compact_payloads() {
+        let normal = task("a", ReminderKind::Normal);
+        let forced = task("b", ReminderKind::Forced);
+        assert_eq!(
+            OutboundEvent::TaskCreated(normal.clone()).topic(),
+            "task/created"
+        );
+        assert_eq!(
+            OutboundEvent::TaskUpdated(normal.clone()).topic(),
+            "task/updated"
+        );
+        assert_eq!(
+            OutboundEvent::TaskCompleted(normal.clone()).topic(),
+            "task/completed"
+        );
+        assert_eq!(
+            OutboundEvent::ReminderFired(vec![normal.clone()]).topic(),
+            "reminder/fired"
+        );
+
+        let fired = OutboundEvent::ReminderFired(vec![normal.clone(), forced]).payload(&dates(42));
+        assert_eq!(fired["event"], "reminder/forced");
+        assert_eq!(fired["at"], 42);
+        assert_eq!(fired["forced"], true);
+        assert_eq!(fired["tasks"][1]["id"], "b");
+        assert!(fired["tasks"][0].get("reminder").is_none());
+
+        let deleted = OutboundEvent::TaskDeleted {
+            task_ids: vec!["a".to_string()],
+        }
+        .payload(&dates(1));
+        assert_eq!(deleted["event"], "task/deleted");
+        assert_eq!(deleted["task_ids"][0], "a");
+        let created = OutboundEvent::TaskCreated(normal).payload(&dates(100_000));
+        assert_eq!(created["task"]["due_at"], 100_000);
+        assert!(created["task"]["due_label"]
+            .as_str()
+            .unwrap()
+            .starts_with("Today "));
+    }
+
+    #[test]
+    fn dispatch_reaches_every_sink_and_collects_failures() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sinks: Vec<Box<dyn EventSink>> = vec![
+            Box::new(Recording {
+                fail: true,
+                seen: seen.clone(),
+            }),
+            Box::new(Recording {
+                fail: false,
+                seen: seen.clone(),
+            }),
+        ];
+        let event = OutboundEvent::TaskCompleted(task("a", ReminderKind::None));
+        let failures = dispatch(&sinks, &event, &dates(7));
+        assert_eq!(failures, vec![("recording", "down".to_string())]);
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 2);
+        assert_eq!(seen[1].0, "task/completed");
+        assert_eq!(seen[1].1["task"]["id"], "a");
+        assert!(dispatch(&[], &event, &dates(7)).is_empty());
+    }
+
+    #[test]
+    fn configured_sinks_follow_settings() {
+        let root = tempfile::tempdir().unwrap();
+        let mut settings = Settings::default();
+        assert!(!any_sink_enabled(&settings));
+        assert!(configured_sinks(&settings, root.path()).is_empty());
+
+        // Enabled but missing a host: skipped rather than failing every event.
+        settings.mqtt_enabled = true;
+        assert!(any_sink_enabled(&settings));
+        assert!(configured_sinks(&settings, root.path()).is_empty());
+
+        settings.mqtt_host = "broker.local".to_string();
+        crate::secrets::set_secret(root.path(), crate::secrets::MQTT_PASSWORD, "pw").unwrap();
+        let sinks = configured_sinks(&settings, root.path());
+        assert_eq!(sinks.len(), 1);
+        assert_eq!(sinks[0].name(), "mqtt");
+    }
+}
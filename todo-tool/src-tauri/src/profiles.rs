@@ -0,0 +1,267 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::models::{Settings, SettingsFile, TasksFile, Timestamp};
+use crate::storage::{Storage, StorageError};
+
+/// Registry of profiles; lives in the data root so a relocated data directory takes it along.
+pub const PROFILES_FILE: &str = "profiles.json";
+/// Non-default profiles keep their data set in `profiles/<id>/` under the data root.
+pub const PROFILES_DIR: &str = "profiles";
+pub const DEFAULT_PROFILE_ID: &str = "default";
+const MAX_PROFILE_NAME_CHARS: usize = 40;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub created_at: Timestamp,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ProfileRegistry {
+    pub active: String,
+    pub profiles: Vec<Profile>,
+}
+
+impl Default for ProfileRegistry {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_PROFILE_ID.to_string(),
+            profiles: vec![default_profile()],
+        }
+    }
+}
+
+impl ProfileRegistry {
+    pub fn find(&self, name_or_id: &str) -> Option<&Profile> {
+        let wanted = name_or_id.trim().to_lowercase();
+        self.profiles
+            .iter()
+            .find(|p| p.id == wanted || p.name.to_lowercase() == wanted)
+    }
+}
+
+fn default_profile() -> Profile {
+    Profile {
+        id: DEFAULT_PROFILE_ID.to_string(),
+        name: "Default".to_string(),
+        created_at: 0,
+    }
+}
+
+/// Missing or unreadable registries fall back to the single default profile (the data root itself).
+pub fn load_registry(data_root: &Path) -> ProfileRegistry {
+    let Ok(raw) = fs::read_to_string(data_root.join(PROFILES_FILE)) else {
+        return ProfileRegistry::default();
+    };
+    let mut registry = match serde_json::from_str::<ProfileRegistry>(&raw) {
+        Ok(registry) => registry,
+        Err(err) => {
+            log::warn!("profiles: ignoring unreadable registry err={err}");
+            return ProfileRegistry::default();
+        }
+    };
+    if !registry.profiles.iter().any(|p| p.id == DEFAULT_PROFILE_ID) {
+        registry.profiles.insert(0, default_profile());
+    }
+    if !registry.profiles.iter().any(|p| p.id == registry.active) {
+        log::warn!(
+            "profiles: active profile missing id={} -> default",
+            registry.active
+        );
+        registry.active = DEFAULT_PROFILE_ID.to_string();
+    }
+    registry
+}
+
+pub fn save_registry(data_root: &Path, registry: &ProfileRegistry) -> Result<(), StorageError> {
+    fs::create_dir_all(data_root)?;
+    let path = data_root.join(PROFILES_FILE);
+    let tmp = data_root.join(format!("{PROFILES_FILE}.tmp"));
+    fs::write(&tmp, serde_json::to_vec_pretty(registry)?)?;
+    fs::rename(tmp, path)?;
+    Ok(())
+}
+
+pub fn profile_root(data_root: &Path, profile_id: &str) -> PathBuf {
+    if profile_id == DEFAULT_PROFILE_ID {
+        return data_root.to_path_buf();
+    }
+    data_root.join(PROFILES_DIR).join(profile_id)
+}
+
+/// Directory holding the active profile's data.json/settings.json/secrets/backups.
+pub fn active_profile_root(data_root: &Path) -> PathBuf {
+    profile_root(data_root, &load_registry(data_root).active)
+}
+
+/// Active profile directory for a running app (data directory resolution + registry).
+#[cfg(all(feature = "app", not(test)))]
+pub fn app_profile_root<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<PathBuf> {
+    Ok(active_profile_root(&crate::data_dir::app_data_root(app)?))
+}
+
+pub fn normalize_profile_name(raw: &str) -> Result<String, String> {
+    let name = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+    if name.is_empty() {
+        return Err("profile name is empty".to_string());
+    }
+    if name.chars().count() > MAX_PROFILE_NAME_CHARS {
+        return Err(format!(
+            "profile name is longer than {MAX_PROFILE_NAME_CHARS} characters"
+        ));
+    }
+    Ok(name)
+}
+
+/// Directory-safe id derived from the name ("Client A" -> "client-a"), unique in the registry.
+fn profile_id_for(registry: &ProfileRegistry, name: &str) -> String {
+    let mut slug = String::new();
+    for ch in name.to_lowercase().chars() {
+        if ch.is_alphanumeric() {
+            slug.push(ch);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    let base = if slug.is_empty() { "profile" } else { slug };
+    let taken = |id: &str| registry.profiles.iter().any(|p| p.id == id);
+    if !taken(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{base}-{n}"))
+        .find(|id| !taken(id))
+        .expect("unbounded suffix search")
+}
+
+/// Adds a profile to the registry (in memory); names are unique case-insensitively.
+pub fn add_profile(
+    registry: &mut ProfileRegistry,
+    raw_name: &str,
+    now: Timestamp,
+) -> Result<Profile, String> {
+    let name = normalize_profile_name(raw_name)?;
+    if registry
+        .profiles
+        .iter()
+        .any(|p| p.name.to_lowercase() == name.to_lowercase())
+    {
+        return Err(format!("profile already exists: {name}"));
+    }
+    let profile = Profile {
+        id: profile_id_for(registry, &name),
+        name,
+        created_at: now,
+    };
+    registry.profiles.push(profile.clone());
+    Ok(profile)
+}
+
+/// Loads a profile's data set; a profile that was never written yet starts empty.
+pub fn load_profile_data(root: &Path) -> Result<(TasksFile, SettingsFile), StorageError> {
+    let storage = Storage::new(root.to_path_buf());
+    let tasks = match storage.load_tasks() {
+        Ok(file) => file,
+        Err(StorageError::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => TasksFile {
+            schema_version: 1,
+            tasks: Vec::new(),
+            projects: Vec::new(),
+            trash: Vec::new(),
+            tags: Vec::new(),
+        },
+        Err(err) => return Err(err),
+    };
+    let settings = match storage.load_settings() {
+        Ok(file) => file,
+        Err(StorageError::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => SettingsFile {
+            schema_version: 1,
+            settings: Settings::default(),
+        },
+        Err(err) => return Err(err),
+    };
+    Ok((tasks, settings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_defaults_and_repairs() {
+        let root = tempfile::tempdir().unwrap();
+        let registry = load_registry(root.path());
+        assert_eq!(registry, ProfileRegistry::default());
+        assert_eq!(active_profile_root(root.path()), root.path());
+
+        fs::write(root.path().join(PROFILES_FILE), "nope").unwrap();
+        assert_eq!(load_registry(root.path()), ProfileRegistry::default());
+
+        // A registry without the default profile or with a dangling active id is repaired.
+        fs::write(
+            root.path().join(PROFILES_FILE),
+            r#"{"active":"gone","profiles":[{"id":"work","name":"Work"}]}"#,
+        )
+        .unwrap();
+        let registry = load_registry(root.path());
+        assert_eq!(registry.active, DEFAULT_PROFILE_ID);
+        let ids: Vec<&str> = registry.profiles.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, vec!["default", "work"]);
+    }
+
+    #[test]
+    fn add_profile_validates_names_and_derives_unique_ids() {
+        let mut registry = ProfileRegistry::default();
+        let work = add_profile(&mut registry, "  Client   A ", 10).unwrap();
+        assert_eq!(work.name, "Client A");
+        assert_eq!(work.id, "client-a");
+        assert_eq!(work.created_at, 10);
+
+        assert!(add_profile(&mut registry, "client a", 11)
+            .unwrap_err()
+            .contains("already exists"));
+        assert!(add_profile(&mut registry, "   ", 11)
+            .unwrap_err()
+            .contains("empty"));
+        assert!(add_profile(&mut registry, &"x".repeat(41), 11).is_err());
+
+        assert_eq!(
+            add_profile(&mut registry, "Client-A!", 12).unwrap().id,
+            "client-a-2"
+        );
+        assert_eq!(add_profile(&mut registry, "工作", 13).unwrap().id, "工作");
+        assert_eq!(add_profile(&mut registry, "!!!", 14).unwrap().id, "profile");
+        assert_eq!(
+            registry.find("CLIENT A").map(|p| p.id.as_str()),
+            Some("client-a")
+        );
+        assert_eq!(
+            registry.find("client-a-2").map(|p| p.name.as_str()),
+            Some("Client-A!")
+        );
+    }
+
+    #[test]
+    fn profile_roots_and_data_loading() {
+        let root = tempfile::tempdir().unwrap();
+        let mut registry = ProfileRegistry::default();
+        let work = add_profile(&mut registry, "Work", 1).unwrap();
+        registry.active = work.id.clone();
+        save_registry(root.path(), &registry).unwrap();
+
+        let expected = root.path().join(PROFILES_DIR).join("work");
+        assert_eq!(active_profile_root(root.path()), expected);
+        assert_eq!(profile_root(root.path(), DEFAULT_PROFILE_ID), root.path());
+
+        let (tasks, settings) = load_profile_data(&expected).unwrap();
+        assert!(tasks.tasks.is_empty());
+        assert_eq!(settings.settings.shortcut, Settings::default().shortcut);
+
+        fs::create_dir_all(&expected).unwrap();
+        fs::write(expected.join("data.json"), "{broken").unwrap();
+        assert!(load_profile_data(&expected).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::models::{Project, Task};
+
+const INBOX_PROJECT_ID: &str = "inbox";
+
+fn parent_of<'a>(projects: &'a [Project], id: &str) -> Option<&'a str> {
+    projects
+        .iter()
+        .find(|project| project.id == id)
+        .and_then(|project| project.parent_id.as_deref())
+}
+
+/// Clears `parent_id`s pointing at a missing project, at the project itself or around a cycle,
+/// so the projects always form a tree. The inbox stays top-level.
+pub fn normalize_project_parents(projects: &mut [Project]) {
+    let ids: HashSet<String> = projects.iter().map(|project| project.id.clone()).collect();
+    for project in projects.iter_mut() {
+        if project.id == INBOX_PROJECT_ID
+            || project
+                .parent_id
+                .as_ref()
+                .is_some_and(|parent| *parent == project.id || !ids.contains(parent))
+        {
+            project.parent_id = None;
+        }
+    }
+    for i in 0..projects.len() {
+        let id = projects[i].id.clone();
+        let mut seen = HashSet::from([id.as_str()]);
+        let mut cursor = projects[i].parent_id.as_deref();
+        let mut in_cycle = false;
+        while let Some(parent) = cursor {
+            if parent == id {
+                in_cycle = true;
+                break;
+            }
+            if !seen.insert(parent) {
+                // A cycle further up; it is broken when one of its own projects comes round.
+                break;
+            }
+            cursor = parent_of(projects, parent);
+        }
+        if in_cycle {
+            projects[i].parent_id = None;
+        }
+    }
+}
+
+/// The parent `project_id` may be moved under, as stored: trimmed, blank cleared. Refuses a
+/// missing parent, the project itself or one of its descendants, and nesting the inbox.
+pub fn validate_parent(
+    projects: &[Project],
+    project_id: &str,
+    parent_id: Option<String>,
+) -> Result<Option<String>, String> {
+    let Some(parent_id) = parent_id
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+    else {
+        return Ok(None);
+    };
+    if project_id == INBOX_PROJECT_ID {
+        return Err("inbox cannot be nested".to_string());
+    }
+    if !projects.iter().any(|project| project.id == parent_id) {
+        return Err(format!("parent project not found: {parent_id}"));
+    }
+    let mut seen = HashSet::new();
+    let mut cursor = Some(parent_id.as_str());
+    while let Some(id) = cursor {
+        if id == project_id {
+            return Err("project cannot be nested under itself".to_string());
+        }
+        if !seen.insert(id) {
+            break;
+        }
+        cursor = parent_of(projects, id);
+    }
+    Ok(Some(parent_id))
+}
+
+/// One sidebar entry of `project_tree`; `open_tasks` counts only the project's own tasks.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectNode {
+    pub project: Project,
+    pub open_tasks: usize,
+    pub children: Vec<ProjectNode>,
+}
+
+/// The projects as a forest in sidebar order (pinned first, then `sort_order`) at every level.
+pub fn build_tree(projects: &[Project], tasks: &[Task]) -> Vec<ProjectNode> {
+    let mut open: HashMap<&str, usize> = HashMap::new();
+    for task in tasks.iter().filter(|task| !task.completed) {
+        *open.entry(task.project_id.as_str()).or_default() += 1;
+    }
+    let mut ordered: Vec<&Project> = projects.iter().collect();
+    ordered.sort_by(|a, b| {
+        (!a.pinned, a.sort_order, a.created_at, &a.id).cmp(&(
+            !b.pinned,
+            b.sort_order,
+            b.created_at,
+            &b.id,
+        ))
+    });
+    let ids: HashSet<&str> = projects.iter().map(|project| project.id.as_str()).collect();
+    let mut children: HashMap<Option<&str>, Vec<&Project>> = HashMap::new();
+    for project in ordered {
+        let parent = project
+            .parent_id
+            .as_deref()
+            .filter(|parent| ids.contains(parent));
+        children.entry(parent).or_default().push(project);
+    }
+
+    fn nodes(
+        parent: Option<&str>,
+        children: &HashMap<Option<&str>, Vec<&Project>>,
+        open: &HashMap<&str, usize>,
+    ) -> Vec<ProjectNode> {
+        children
+            .get(&parent)
+            .map(|list| {
+                list.iter()
+                    .map(|project| ProjectNode {
+                        project: (*project).clone(),
+                        open_tasks: open.get(project.id.as_str()).copied().unwrap_or(0),
+                        children: nodes(Some(project.id.as_str()), children, open),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+    nodes(None, &children, &open)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+
+    fn project(id: &str, parent_id: Option<&str>, sort_order: i64) -> Project {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": id,
+            "parent_id": parent_id,
+            "sort_order": sort_order
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn parents_must_exist_and_must_not_form_a_cycle() {
+        let projects = vec![
+            project("inbox", None, 0),
+            project("work", None, 1),
+            project("client", Some("work"), 2),
+            project("site", Some("client"), 3),
+        ];
+        assert_eq!(
+            validate_parent(&projects, "home", Some(" work ".into())),
+            Ok(Some("work".to_string()))
+        );
+        assert_eq!(
+            validate_parent(&projects, "site", Some(" ".into())),
+            Ok(None)
+        );
+        assert_eq!(
+            validate_parent(&projects, "work", Some("site".into())),
+            Err("project cannot be nested under itself".to_string())
+        );
+        assert!(validate_parent(&projects, "work", Some("work".into())).is_err());
+        assert!(validate_parent(&projects, "work", Some("nope".into())).is_err());
+        assert!(validate_parent(&projects, "inbox", Some("work".into())).is_err());
+    }
+
+    #[test]
+    fn broken_parents_are_cleared_on_load() {
+        let mut projects = vec![
+            project("inbox", Some("a"), 0),
+            project("a", Some("b"), 1),
+            project("b", Some("a"), 2),
+            project("c", Some("gone"), 3),
+            project("d", Some("d"), 4),
+            project("e", Some("a"), 5),
+        ];
+        normalize_project_parents(&mut projects);
+        let parents: Vec<Option<&str>> = projects
+            .iter()
+            .map(|project| project.parent_id.as_deref())
+            .collect();
+        assert_eq!(parents, [None, None, Some("a"), None, None, Some("a")]);
+    }
+
+    #[test]
+    fn tree_nests_children_in_sidebar_order_with_open_counts() {
+        let mut pinned = project("later", None, 9);
+        pinned.pinned = true;
+        let projects = vec![
+            project("work", None, 2),
+            project("b", Some("work"), 5),
+            project("a", Some("work"), 4),
+            project("a1", Some("a"), 1),
+            pinned,
+        ];
+        let task = |project_id: &str, completed: bool| {
+            test_support::task(&format!("{project_id}-{completed}"))
+                .project(project_id)
+                .title("t")
+                .completed(completed)
+        };
+        let tree = build_tree(&projects, &[task("a", false), task("a", true)]);
+
+        let ids = |nodes: &[ProjectNode]| -> Vec<String> {
+            nodes.iter().map(|node| node.project.id.clone()).collect()
+        };
+        assert_eq!(ids(&tree), ["later", "work"]);
+        assert_eq!(ids(&tree[1].children), ["a", "b"]);
+        assert_eq!(ids(&tree[1].children[0].children), ["a1"]);
+        assert_eq!(tree[1].children[0].open_tasks, 1);
+        assert_eq!(tree[1].open_tasks, 0);
+    }
+}
@@ -0,0 +1,214 @@
+use chrono::{DateTime, Local, TimeZone};
+
+use crate::models::{QuadrantConfig, Task};
+
+fn is_valid_quadrant(id: u8) -> bool {
+    (1..=4).contains(&id)
+}
+
+fn is_hex_color(value: &str) -> bool {
+    let Some(hex) = value.strip_prefix('#') else {
+        return false;
+    };
+    matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Trims names/colors and checks the config is usable; the first problem found is reported.
+pub fn normalize_quadrant_config(config: &mut QuadrantConfig) -> Result<(), String> {
+    for quadrant in &mut config.quadrants {
+        quadrant.name = quadrant.name.trim().to_string();
+        quadrant.color = quadrant.color.trim().to_string();
+    }
+
+    let mut ids: Vec<u8> = config.quadrants.iter().map(|q| q.id).collect();
+    ids.sort_unstable();
+    if ids != [1, 2, 3, 4] {
+        return Err("quadrants must define ids 1-4 exactly once".to_string());
+    }
+    if let Some(q) = config.quadrants.iter().find(|q| q.name.is_empty()) {
+        return Err(format!("quadrant {} name is empty", q.id));
+    }
+    if let Some(q) = config.quadrants.iter().find(|q| !is_hex_color(&q.color)) {
+        return Err(format!("quadrant {} color must be #RGB or #RRGGBB", q.id));
+    }
+    if config.urgent_within_hours < 0 {
+        return Err("urgent_within_hours must be >= 0".to_string());
+    }
+    let mapping = [
+        config.important_urgent,
+        config.important_not_urgent,
+        config.urgent_not_important,
+        config.neither,
+    ];
+    if !mapping.iter().copied().all(is_valid_quadrant) {
+        return Err("quadrant mapping must point to quadrants 1-4".to_string());
+    }
+    Ok(())
+}
+
+fn is_urgent(task: &Task, config: &QuadrantConfig, now: DateTime<Local>) -> bool {
+    let Some(due_at) = task.due_at.filter(|_| !task.someday) else {
+        return false;
+    };
+    let now_ts = now.timestamp();
+    if due_at < now_ts {
+        return true;
+    }
+    if config.urgent_within_hours > 0 {
+        return due_at - now_ts <= config.urgent_within_hours.saturating_mul(3600);
+    }
+    Local
+        .timestamp_opt(due_at, 0)
+        .single()
+        .is_some_and(|due| due.date_naive() == now.date_naive())
+}
+
+/// Maps `important` + due proximity onto the configured quadrant.
+pub fn classify_quadrant(task: &Task, config: &QuadrantConfig, now: DateTime<Local>) -> u8 {
+    match (task.important, is_urgent(task, config, now)) {
+        (true, true) => config.important_urgent,
+        (true, false) => config.important_not_urgent,
+        (false, true) => config.urgent_not_important,
+        (false, false) => config.neither,
+    }
+}
+
+/// Create/update hook: always re-classifies with `auto_classify`, otherwise only repairs
+/// quadrants outside 1-4 so manual placement from the UI is kept.
+pub fn apply_quadrant(task: &mut Task, config: &QuadrantConfig, now: DateTime<Local>) {
+    if config.auto_classify || !is_valid_quadrant(task.quadrant) {
+        task.quadrant = classify_quadrant(task, config, now);
+    }
+}
+
+pub fn quadrant_name(config: &QuadrantConfig, id: u8) -> String {
+    config
+        .quadrants
+        .iter()
+        .find(|q| q.id == id)
+        .map(|q| q.name.clone())
+        .unwrap_or_else(|| format!("Q{id}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+
+    fn make_task(important: bool, due_at: i64, quadrant: u8) -> Task {
+        Task {
+            important,
+            sort_order: 1,
+            quadrant,
+            ..test_support::task("t").due(due_at)
+        }
+    }
+
+    fn noon() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn normalize_quadrant_config_trims_and_validates() {
+        let mut config = QuadrantConfig::default();
+        config.quadrants[0].name = "  Now ".to_string();
+        config.quadrants.reverse();
+        assert!(normalize_quadrant_config(&mut config).is_ok());
+        assert_eq!(config.quadrants[3].name, "Now");
+        assert_eq!(config.quadrants[0].id, 4);
+
+        type Mutation = fn(&mut QuadrantConfig);
+        let invalid: Vec<(Mutation, &str)> = vec![
+            (|c| c.quadrants.truncate(3), "ids 1-4"),
+            (|c| c.quadrants[1].id = 1, "ids 1-4"),
+            (|c| c.quadrants[2].name = " ".to_string(), "name is empty"),
+            (|c| c.quadrants[0].color = "red".to_string(), "color"),
+            (|c| c.quadrants[0].color = "#12345G".to_string(), "color"),
+            (|c| c.urgent_within_hours = -1, "urgent_within_hours"),
+            (|c| c.neither = 5, "mapping"),
+        ];
+        for (mutate, expected) in invalid {
+            let mut config = QuadrantConfig::default();
+            mutate(&mut config);
+            let message = normalize_quadrant_config(&mut config).unwrap_err();
+            assert!(message.contains(expected), "{message}");
+        }
+
+        let mut short_color = QuadrantConfig::default();
+        short_color.quadrants[0].color = "#abc".to_string();
+        assert!(normalize_quadrant_config(&mut short_color).is_ok());
+    }
+
+    #[test]
+    fn classify_quadrant_uses_importance_and_due_proximity() {
+        let now = noon();
+        let ts = now.timestamp();
+        let config = QuadrantConfig::default();
+
+        assert_eq!(
+            classify_quadrant(&make_task(true, ts - 60, 0), &config, now),
+            1
+        );
+        assert_eq!(
+            classify_quadrant(&make_task(true, ts + 3600, 0), &config, now),
+            1
+        );
+        assert_eq!(
+            classify_quadrant(&make_task(true, ts + 2 * 86400, 0), &config, now),
+            2
+        );
+        assert_eq!(
+            classify_quadrant(&make_task(false, ts + 60, 0), &config, now),
+            3
+        );
+        assert_eq!(
+            classify_quadrant(&make_task(false, ts + 2 * 86400, 0), &config, now),
+            4
+        );
+
+        // A proximity window replaces the "due today" rule, and the mapping can be remapped.
+        let custom = QuadrantConfig {
+            urgent_within_hours: 72,
+            urgent_not_important: 4,
+            ..QuadrantConfig::default()
+        };
+        assert_eq!(
+            classify_quadrant(&make_task(true, ts + 2 * 86400, 0), &custom, now),
+            1
+        );
+        assert_eq!(
+            classify_quadrant(&make_task(false, ts + 2 * 86400, 0), &custom, now),
+            4
+        );
+        assert_eq!(
+            classify_quadrant(&make_task(true, ts + 4 * 86400, 0), &custom, now),
+            2
+        );
+    }
+
+    #[test]
+    fn apply_quadrant_keeps_manual_choice_unless_auto_or_invalid() {
+        let now = noon();
+        let ts = now.timestamp();
+        let mut config = QuadrantConfig::default();
+
+        let mut manual = make_task(true, ts - 60, 4);
+        apply_quadrant(&mut manual, &config, now);
+        assert_eq!(manual.quadrant, 4);
+
+        let mut invalid = make_task(true, ts - 60, 0);
+        apply_quadrant(&mut invalid, &config, now);
+        assert_eq!(invalid.quadrant, 1);
+
+        config.auto_classify = true;
+        apply_quadrant(&mut manual, &config, now);
+        assert_eq!(manual.quadrant, 1);
+    }
+
+    #[test]
+    fn quadrant_name_falls_back_for_unknown_ids() {
+        let config = QuadrantConfig::default();
+        assert_eq!(quadrant_name(&config, 2), "Schedule");
+        assert_eq!(quadrant_name(&config, 9), "Q9");
+    }
+}
@@ -0,0 +1,882 @@
+use std::cmp::Ordering;
+
+use chrono::{DateTime, Local, TimeZone};
+
+use crate::calendar::week_start;
+use crate::contexts::normalize_context_label;
+use crate::models::{Project, Settings, Task, TaskGroupBy, TaskSortKey, Timestamp, WeekStart};
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+pub struct TaskFilter {
+    pub project_id: Option<String>,
+    /// Matches tasks carrying any of these tags (case-insensitive).
+    pub tags: Vec<String>,
+    pub include_completed: bool,
+    /// Only completed tasks (a "done" list); implies `include_completed`.
+    pub completed_only: bool,
+    pub important_only: bool,
+    pub due_after: Option<Timestamp>,
+    pub due_before: Option<Timestamp>,
+    /// Case-insensitive substring match against title and notes.
+    pub text: Option<String>,
+    /// Matches the task context; `@` is optional ("phone" matches "@phone").
+    pub context: Option<String>,
+    /// Someday/Maybe tasks are hidden from regular queries unless asked for.
+    pub include_someday: bool,
+    /// Likewise for tasks whose `start_at` is still ahead.
+    pub include_scheduled: bool,
+}
+
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(rename_all = "snake_case", default)]
+pub struct TaskQuerySpec {
+    pub filter: TaskFilter,
+    pub sort: TaskSortKey,
+    pub descending: bool,
+    pub group_by: TaskGroupBy,
+    /// Drops `steps` from the returned tasks; `progress` still carries the checklist counts.
+    pub omit_steps: bool,
+    /// Rows to skip, counted across groups in display order.
+    pub offset: usize,
+    /// Rows to return after `offset`; everything when unset.
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TaskGroup {
+    pub key: String,
+    pub label: String,
+    pub tasks: Vec<Task>,
+}
+
+/// One page of `query_tasks`: the groups with rows on it, in display order.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TaskPage {
+    pub groups: Vec<TaskGroup>,
+    /// Rows over all pages (a task listed under two tags counts twice).
+    pub total: usize,
+    pub offset: usize,
+    pub has_more: bool,
+}
+
+struct Group<'a> {
+    key: String,
+    label: String,
+    tasks: Vec<&'a Task>,
+}
+
+const DATE_BUCKETS: [&str; 9] = [
+    "overdue",
+    "today",
+    "tomorrow",
+    "this_week",
+    "next_week",
+    "later",
+    "scheduled",
+    "someday",
+    "completed",
+];
+
+fn matches_filter(task: &Task, filter: &TaskFilter, now: Timestamp) -> bool {
+    if task.completed && !(filter.include_completed || filter.completed_only) {
+        return false;
+    }
+    if filter.completed_only && !task.completed {
+        return false;
+    }
+    if task.someday && !filter.include_someday {
+        return false;
+    }
+    if task.is_scheduled(now) && !filter.include_scheduled {
+        return false;
+    }
+    if filter.important_only && !task.important {
+        return false;
+    }
+    if let Some(project_id) = filter.project_id.as_deref() {
+        if task.project_id != project_id {
+            return false;
+        }
+    }
+    if let Some(context) = filter.context.as_deref().and_then(normalize_context_label) {
+        let task_context = task.context.as_deref().and_then(normalize_context_label);
+        if task_context.as_deref() != Some(context.as_str()) {
+            return false;
+        }
+    }
+    if !filter.tags.is_empty()
+        && !task.tags.iter().any(|tag| {
+            filter
+                .tags
+                .iter()
+                .any(|want| want.eq_ignore_ascii_case(tag))
+        })
+    {
+        return false;
+    }
+    // A due-date range only ever matches dated tasks.
+    if filter
+        .due_after
+        .is_some_and(|after| task.due_at.is_none_or(|due| due < after))
+    {
+        return false;
+    }
+    if filter
+        .due_before
+        .is_some_and(|before| task.due_at.is_none_or(|due| due >= before))
+    {
+        return false;
+    }
+    if let Some(text) = filter
+        .text
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+    {
+        let needle = text.to_lowercase();
+        let in_title = task.title.to_lowercase().contains(&needle);
+        let in_notes = task
+            .notes
+            .as_deref()
+            .is_some_and(|notes| notes.to_lowercase().contains(&needle));
+        if !in_title && !in_notes {
+            return false;
+        }
+    }
+    true
+}
+
+/// Every key falls back to manual order and then id so results are stable across calls.
+fn compare_tasks(a: &Task, b: &Task, sort: TaskSortKey) -> Ordering {
+    let primary = match sort {
+        TaskSortKey::Manual => Ordering::Equal,
+        TaskSortKey::Due => a
+            .due_sort_key()
+            .cmp(&b.due_sort_key())
+            .then_with(|| b.important.cmp(&a.important)),
+        TaskSortKey::Priority => b
+            .important
+            .cmp(&a.important)
+            .then_with(|| a.quadrant.cmp(&b.quadrant))
+            .then_with(|| a.due_sort_key().cmp(&b.due_sort_key())),
+        TaskSortKey::Created => a.created_at.cmp(&b.created_at),
+    };
+    primary
+        .then_with(|| a.sort_order.cmp(&b.sort_order))
+        .then_with(|| a.id.cmp(&b.id))
+}
+
+fn date_bucket(
+    task: &Task,
+    now: DateTime<Local>,
+    start: WeekStart,
+    grace_minutes: i64,
+) -> &'static str {
+    if task.completed {
+        return "completed";
+    }
+    if task.someday {
+        return "someday";
+    }
+    if task.is_scheduled(now.timestamp()) {
+        return "scheduled";
+    }
+    if task.is_overdue(now.timestamp(), grace_minutes) {
+        return "overdue";
+    }
+    let Some(due) = task
+        .due_at
+        .and_then(|due_at| Local.timestamp_opt(due_at, 0).single())
+    else {
+        return "later";
+    };
+    let today = now.date_naive();
+    match (due.date_naive() - today).num_days() {
+        i64::MIN..=0 => return "today",
+        1 => return "tomorrow",
+        _ => {}
+    }
+    let weeks_ahead =
+        (week_start(due.date_naive(), start) - week_start(today, start)).num_days() / 7;
+    match weeks_ahead {
+        0 => "this_week",
+        1 => "next_week",
+        _ => "later",
+    }
+}
+
+fn group_tasks<'a>(
+    tasks: Vec<&'a Task>,
+    projects: &[Project],
+    group_by: TaskGroupBy,
+    now: DateTime<Local>,
+    settings: &Settings,
+) -> Vec<Group<'a>> {
+    let mut groups: Vec<Group<'a>> = Vec::new();
+    let mut push = |key: &str, label: &str, task: &'a Task| match groups
+        .iter_mut()
+        .find(|group| group.key == key)
+    {
+        Some(group) => group.tasks.push(task),
+        None => groups.push(Group {
+            key: key.to_string(),
+            label: label.to_string(),
+            tasks: vec![task],
+        }),
+    };
+
+    match group_by {
+        TaskGroupBy::None => {
+            return vec![Group {
+                key: "all".to_string(),
+                label: "all".to_string(),
+                tasks,
+            }];
+        }
+        TaskGroupBy::Project => {
+            for &task in &tasks {
+                let label = projects
+                    .iter()
+                    .find(|project| project.id == task.project_id)
+                    .map(|project| project.name.as_str())
+                    .unwrap_or(task.project_id.as_str());
+                push(&task.project_id, label, task);
+            }
+        }
+        TaskGroupBy::Tag => {
+            for &task in &tasks {
+                if task.tags.is_empty() {
+                    push("", "", task);
+                }
+                for tag in &task.tags {
+                    push(&tag.to_lowercase(), tag, task);
+                }
+            }
+        }
+        TaskGroupBy::DateBucket => {
+            for &task in &tasks {
+                let bucket = date_bucket(
+                    task,
+                    now,
+                    settings.week_starts_on,
+                    settings.overdue_grace_minutes,
+                );
+                push(bucket, bucket, task);
+            }
+        }
+    }
+
+    match group_by {
+        TaskGroupBy::Project => {
+            // Follow the sidebar: pinned projects first, then manual project order.
+            let rank = |key: &str| {
+                projects
+                    .iter()
+                    .find(|project| project.id == key)
+                    .map(|project| (0, !project.pinned, project.sort_order))
+                    .unwrap_or((1, true, 0))
+            };
+            groups.sort_by(|a, b| rank(&a.key).cmp(&rank(&b.key)).then(a.key.cmp(&b.key)));
+        }
+        // Untagged tasks ("" key) go last.
+        TaskGroupBy::Tag => groups.sort_by(|a, b| {
+            a.key
+                .is_empty()
+                .cmp(&b.key.is_empty())
+                .then_with(|| a.key.cmp(&b.key))
+        }),
+        TaskGroupBy::DateBucket => groups.sort_by_key(|group| {
+            DATE_BUCKETS
+                .iter()
+                .position(|bucket| *bucket == group.key)
+                .unwrap_or(DATE_BUCKETS.len())
+        }),
+        TaskGroupBy::None => {}
+    }
+    groups
+}
+
+/// Open Someday/Maybe tasks, pinned first, then in manual order.
+pub fn someday_tasks(tasks: &[Task]) -> Vec<Task> {
+    let mut backlog: Vec<Task> = tasks
+        .iter()
+        .filter(|task| task.someday && !task.completed)
+        .cloned()
+        .collect();
+    backlog.sort_by(|a, b| {
+        b.pinned
+            .cmp(&a.pinned)
+            .then_with(|| compare_tasks(a, b, TaskSortKey::Manual))
+    });
+    backlog
+}
+
+/// Open tasks whose `start_at` is still ahead, soonest start first.
+pub fn scheduled_tasks(tasks: &[Task], now: Timestamp) -> Vec<Task> {
+    let mut waiting: Vec<Task> = tasks
+        .iter()
+        .filter(|task| task.is_scheduled(now))
+        .cloned()
+        .collect();
+    waiting.sort_by(|a, b| {
+        a.start_at
+            .cmp(&b.start_at)
+            .then_with(|| compare_tasks(a, b, TaskSortKey::Manual))
+    });
+    waiting
+}
+
+/// Filters, sorts and groups tasks, then cuts out the page `spec.offset`/`spec.limit` asks for;
+/// only the rows on it are copied. Ordering inside each group is pinned-first, then `spec.sort`.
+/// Date buckets follow `settings.week_starts_on` and `settings.overdue_grace_minutes`. Groups
+/// without rows on the page are left out, except the single `all` group of ungrouped queries.
+pub fn query_tasks(
+    tasks: &[Task],
+    projects: &[Project],
+    spec: &TaskQuerySpec,
+    now: DateTime<Local>,
+    settings: &Settings,
+) -> TaskPage {
+    let mut matched: Vec<&Task> = tasks
+        .iter()
+        .filter(|task| matches_filter(task, &spec.filter, now.timestamp()))
+        .collect();
+    matched.sort_by(|a, b| {
+        let ord = compare_tasks(a, b, spec.sort);
+        let ord = if spec.descending { ord.reverse() } else { ord };
+        // Pinned tasks stay on top whatever the sort key or direction.
+        b.pinned.cmp(&a.pinned).then(ord)
+    });
+    let groups = group_tasks(matched, projects, spec.group_by, now, settings);
+
+    let total = groups.iter().map(|group| group.tasks.len()).sum();
+    let mut skip = spec.offset;
+    let mut take = spec.limit.unwrap_or(usize::MAX);
+    let mut page = Vec::new();
+    for group in groups {
+        let rows: Vec<Task> = group
+            .tasks
+            .iter()
+            .skip(skip)
+            .take(take)
+            .map(|&task| {
+                let mut task = task.clone();
+                if spec.omit_steps {
+                    task.steps = Vec::new();
+                }
+                task
+            })
+            .collect();
+        skip = skip.saturating_sub(group.tasks.len());
+        take -= rows.len();
+        if rows.is_empty() && spec.group_by != TaskGroupBy::None {
+            continue;
+        }
+        page.push(TaskGroup {
+            key: group.key,
+            label: group.label,
+            tasks: rows,
+        });
+    }
+    let returned: usize = page.iter().map(|group| group.tasks.len()).sum();
+    TaskPage {
+        groups: page,
+        total,
+        offset: spec.offset,
+        has_more: spec.offset.saturating_add(returned) < total,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+
+    fn make_task(id: &str, due_at: i64, sort_order: i64) -> Task {
+        Task {
+            sort_order,
+            ..test_support::task(id)
+                .title(&format!("task-{id}"))
+                .due(due_at)
+                .created(sort_order)
+                .updated(1)
+        }
+    }
+
+    fn make_project(id: &str, pinned: bool, sort_order: i64) -> Project {
+        Project {
+            id: id.to_string(),
+            name: format!("Project {id}"),
+            pinned,
+            sort_order,
+            created_at: 1,
+            updated_at: 1,
+            sample_tag: None,
+            reminders_muted: false,
+            muted_until: None,
+            notification_sound: None,
+            color: None,
+            icon: None,
+            parent_id: None,
+        }
+    }
+
+    fn noon() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap()
+    }
+
+    fn ids(group: &TaskGroup) -> Vec<&str> {
+        group.tasks.iter().map(|t| t.id.as_str()).collect()
+    }
+
+    #[test]
+    fn filter_covers_completion_importance_project_tags_range_and_text() {
+        let now = noon();
+        let mut done = make_task("done", 100, 1);
+        done.completed = true;
+        let mut important = make_task("imp", 200, 2);
+        important.important = true;
+        important.tags = vec!["Work".to_string()];
+        let mut other_project = make_task("other", 300, 3);
+        other_project.project_id = "p1".to_string();
+        let mut noted = make_task("noted", 400, 4);
+        noted.notes = Some("Call the Dentist".to_string());
+        let tasks = vec![done, important, other_project, noted];
+
+        let run = |filter: TaskFilter| {
+            let spec = TaskQuerySpec {
+                filter,
+                ..TaskQuerySpec::default()
+            };
+            let groups = query_tasks(&tasks, &[], &spec, now, &Settings::default()).groups;
+            ids(&groups[0])
+                .into_iter()
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(run(TaskFilter::default()), vec!["imp", "other", "noted"]);
+        let all = TaskFilter {
+            include_completed: true,
+            ..TaskFilter::default()
+        };
+        assert_eq!(run(all).len(), 4);
+        let completed_only = TaskFilter {
+            completed_only: true,
+            ..TaskFilter::default()
+        };
+        assert_eq!(run(completed_only), vec!["done"]);
+        let important_only = TaskFilter {
+            important_only: true,
+            ..TaskFilter::default()
+        };
+        assert_eq!(run(important_only), vec!["imp"]);
+        let project = TaskFilter {
+            project_id: Some("p1".to_string()),
+            ..TaskFilter::default()
+        };
+        assert_eq!(run(project), vec!["other"]);
+        let tags = TaskFilter {
+            tags: vec!["work".to_string()],
+            ..TaskFilter::default()
+        };
+        assert_eq!(run(tags), vec!["imp"]);
+        let range = TaskFilter {
+            due_after: Some(250),
+            due_before: Some(400),
+            ..TaskFilter::default()
+        };
+        assert_eq!(run(range), vec!["other"]);
+        let text = TaskFilter {
+            text: Some(" dentist ".to_string()),
+            ..TaskFilter::default()
+        };
+        assert_eq!(run(text), vec!["noted"]);
+        let title = TaskFilter {
+            text: Some("TASK-IMP".to_string()),
+            ..TaskFilter::default()
+        };
+        assert_eq!(run(title), vec!["imp"]);
+    }
+
+    #[test]
+    fn filter_by_context_ignores_case_and_prefix() {
+        let now = noon();
+        let mut phone = make_task("phone", 100, 1);
+        phone.context = Some("@phone".to_string());
+        let mut errand = make_task("errand", 100, 2);
+        errand.context = Some("@errand".to_string());
+        let plain = make_task("plain", 100, 3);
+        let tasks = vec![phone, errand, plain];
+
+        for needle in ["@phone", "Phone", " @PHONE "] {
+            let spec = TaskQuerySpec {
+                filter: TaskFilter {
+                    context: Some(needle.to_string()),
+                    ..TaskFilter::default()
+                },
+                ..TaskQuerySpec::default()
+            };
+            let groups = query_tasks(&tasks, &[], &spec, now, &Settings::default()).groups;
+            assert_eq!(ids(&groups[0]), vec!["phone"]);
+        }
+
+        // A blank context filter is treated as "no filter".
+        let spec = TaskQuerySpec {
+            filter: TaskFilter {
+                context: Some("  ".to_string()),
+                ..TaskFilter::default()
+            },
+            ..TaskQuerySpec::default()
+        };
+        let groups = query_tasks(&tasks, &[], &spec, now, &Settings::default()).groups;
+        assert_eq!(groups[0].tasks.len(), 3);
+    }
+
+    #[test]
+    fn omit_steps_keeps_progress_counts() {
+        let now = noon();
+        let mut task = make_task("a", 100, 1);
+        task.steps = vec![crate::models::Step {
+            id: "s".to_string(),
+            title: "s".to_string(),
+            completed: true,
+            created_at: 1,
+            completed_at: Some(2),
+            parent_id: None,
+        }];
+        task.refresh_progress();
+        let tasks = vec![task];
+
+        let full = query_tasks(
+            &tasks,
+            &[],
+            &TaskQuerySpec::default(),
+            now,
+            &Settings::default(),
+        )
+        .groups;
+        assert_eq!(full[0].tasks[0].steps.len(), 1);
+
+        let spec: TaskQuerySpec = serde_json::from_str(r#"{"omit_steps":true}"#).unwrap();
+        let lean = query_tasks(&tasks, &[], &spec, now, &Settings::default()).groups;
+        let task = &lean[0].tasks[0];
+        assert!(task.steps.is_empty());
+        assert_eq!(task.progress.steps_total, 1);
+        assert_eq!(task.progress.percent_complete, 100);
+    }
+
+    #[test]
+    fn sort_keys_order_tasks_with_manual_order_as_tiebreaker() {
+        let now = noon();
+        let mut a = make_task("a", 300, 3);
+        a.created_at = 10;
+        let mut b = make_task("b", 100, 2);
+        b.created_at = 30;
+        b.quadrant = 2;
+        let mut c = make_task("c", 100, 1);
+        c.created_at = 20;
+        c.important = true;
+        c.quadrant = 3;
+        let tasks = vec![a, b, c];
+
+        let order = |sort: TaskSortKey, descending: bool| {
+            let spec = TaskQuerySpec {
+                sort,
+                descending,
+                ..TaskQuerySpec::default()
+            };
+            let groups = query_tasks(&tasks, &[], &spec, now, &Settings::default()).groups;
+            ids(&groups[0]).join(",")
+        };
+
+        assert_eq!(order(TaskSortKey::Manual, false), "c,b,a");
+        assert_eq!(order(TaskSortKey::Due, false), "c,b,a");
+        assert_eq!(order(TaskSortKey::Priority, false), "c,a,b");
+        assert_eq!(order(TaskSortKey::Created, false), "a,c,b");
+        assert_eq!(order(TaskSortKey::Created, true), "b,c,a");
+
+        // Undated tasks sort after every dated one and never match a due-date range.
+        let mut undated = make_task("u", 0, 0);
+        undated.due_at = None;
+        let mut with_undated = tasks.clone();
+        with_undated.push(undated);
+        let spec = TaskQuerySpec {
+            sort: TaskSortKey::Due,
+            ..TaskQuerySpec::default()
+        };
+        let groups = query_tasks(&with_undated, &[], &spec, now, &Settings::default()).groups;
+        assert_eq!(ids(&groups[0]).join(","), "c,b,a,u");
+        let spec = TaskQuerySpec {
+            filter: TaskFilter {
+                due_before: Some(i64::MAX),
+                ..TaskFilter::default()
+            },
+            ..TaskQuerySpec::default()
+        };
+        let groups = query_tasks(&with_undated, &[], &spec, now, &Settings::default()).groups;
+        assert_eq!(groups[0].tasks.len(), 3);
+
+        // A pinned task leads in every sort key and direction.
+        let mut pinned = tasks.clone();
+        pinned[0].pinned = true;
+        for (sort, descending) in [
+            (TaskSortKey::Manual, false),
+            (TaskSortKey::Due, false),
+            (TaskSortKey::Created, true),
+        ] {
+            let spec = TaskQuerySpec {
+                sort,
+                descending,
+                ..TaskQuerySpec::default()
+            };
+            let groups = query_tasks(&pinned, &[], &spec, now, &Settings::default()).groups;
+            assert_eq!(ids(&groups[0])[0], "a");
+        }
+    }
+
+    #[test]
+    fn pages_cut_across_groups_in_display_order() {
+        let now = noon();
+        let projects = vec![make_project("inbox", true, 0), make_project("p1", false, 1)];
+        let mut tasks: Vec<Task> = (1..=5)
+            .map(|i| make_task(&format!("t{i}"), 100 * i, i))
+            .collect();
+        for task in &mut tasks[3..] {
+            task.project_id = "p1".to_string();
+        }
+        let page = |group_by, offset, limit| {
+            let spec = TaskQuerySpec {
+                group_by,
+                offset,
+                limit,
+                ..TaskQuerySpec::default()
+            };
+            query_tasks(&tasks, &projects, &spec, now, &Settings::default())
+        };
+
+        // inbox: t1 t2 t3 | p1: t4 t5
+        let second = page(TaskGroupBy::Project, 2, Some(2));
+        assert_eq!((second.total, second.offset, second.has_more), (5, 2, true));
+        let rows: Vec<(&str, Vec<&str>)> = second
+            .groups
+            .iter()
+            .map(|group| (group.key.as_str(), ids(group)))
+            .collect();
+        assert_eq!(rows, vec![("inbox", vec!["t3"]), ("p1", vec!["t4"])]);
+
+        let last = page(TaskGroupBy::Project, 4, Some(2));
+        assert!(!last.has_more);
+        assert_eq!(last.groups.len(), 1);
+        assert_eq!(ids(&last.groups[0]), vec!["t5"]);
+
+        // Past the end: no rows, but ungrouped queries keep their single group.
+        let beyond = page(TaskGroupBy::None, 10, Some(5));
+        assert_eq!((beyond.total, beyond.has_more), (5, false));
+        assert!(beyond.groups[0].tasks.is_empty());
+        assert!(page(TaskGroupBy::Project, 10, None).groups.is_empty());
+        assert_eq!(page(TaskGroupBy::None, 0, None).groups[0].tasks.len(), 5);
+    }
+
+    #[test]
+    fn group_by_project_tag_and_date_bucket() {
+        let now = noon();
+        let ts = now.timestamp();
+        let projects = vec![
+            make_project("inbox", true, 0),
+            make_project("p1", false, 2),
+            make_project("p2", true, 5),
+        ];
+        let mut t1 = make_task("t1", ts - 60, 1);
+        t1.tags = vec!["Home".to_string(), "work".to_string()];
+        let mut t2 = make_task("t2", ts + 3600, 2);
+        t2.project_id = "p1".to_string();
+        t2.tags = vec!["Work".to_string()];
+        let mut t3 = make_task("t3", ts + 86400, 3);
+        t3.project_id = "p2".to_string();
+        let mut t4 = make_task("t4", ts + 3 * 86400, 4);
+        t4.project_id = "ghost".to_string();
+        let t5 = make_task("t5", ts + 30 * 86400, 5);
+        let mut t6 = make_task("t6", ts, 6);
+        t6.completed = true;
+        let tasks = vec![t1, t2, t3, t4, t5, t6];
+
+        let spec = |group_by| TaskQuerySpec {
+            filter: TaskFilter {
+                include_completed: true,
+                ..TaskFilter::default()
+            },
+            group_by,
+            ..TaskQuerySpec::default()
+        };
+
+        let by_project = query_tasks(
+            &tasks,
+            &projects,
+            &spec(TaskGroupBy::Project),
+            now,
+            &Settings::default(),
+        )
+        .groups;
+        let keys: Vec<&str> = by_project.iter().map(|g| g.key.as_str()).collect();
+        assert_eq!(keys, vec!["inbox", "p2", "p1", "ghost"]);
+        assert_eq!(by_project[1].label, "Project p2");
+        assert_eq!(by_project[3].label, "ghost");
+        assert_eq!(ids(&by_project[0]), vec!["t1", "t5", "t6"]);
+
+        let by_tag = query_tasks(
+            &tasks,
+            &projects,
+            &spec(TaskGroupBy::Tag),
+            now,
+            &Settings::default(),
+        )
+        .groups;
+        let keys: Vec<&str> = by_tag.iter().map(|g| g.key.as_str()).collect();
+        assert_eq!(keys, vec!["home", "work", ""]);
+        assert_eq!(ids(&by_tag[1]), vec!["t1", "t2"]);
+        assert_eq!(by_tag[1].label, "work");
+
+        let by_date = query_tasks(
+            &tasks,
+            &projects,
+            &spec(TaskGroupBy::DateBucket),
+            now,
+            &Settings::default(),
+        )
+        .groups;
+        let keys: Vec<&str> = by_date.iter().map(|g| g.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "overdue",
+                "today",
+                "tomorrow",
+                "this_week",
+                "later",
+                "completed"
+            ]
+        );
+        let far = make_task("x", i64::MAX, 1);
+        assert_eq!(date_bucket(&far, now, WeekStart::Monday, 0), "later");
+    }
+
+    #[test]
+    fn date_bucket_waits_for_overdue_grace() {
+        let now = noon();
+        let started = make_task("m", now.timestamp() - 5 * 60, 1);
+        assert_eq!(date_bucket(&started, now, WeekStart::Monday, 0), "overdue");
+        assert_eq!(date_bucket(&started, now, WeekStart::Monday, 10), "today");
+
+        let settings = Settings {
+            overdue_grace_minutes: 10,
+            ..Settings::default()
+        };
+        let spec = TaskQuerySpec {
+            group_by: TaskGroupBy::DateBucket,
+            ..TaskQuerySpec::default()
+        };
+        let groups = query_tasks(&[started], &[], &spec, now, &settings).groups;
+        assert_eq!(groups[0].key, "today");
+    }
+
+    #[test]
+    fn date_buckets_follow_week_start() {
+        // Tuesday noon; Sunday 2026-03-15 ends a Monday-start week but opens a Sunday-start one.
+        let now = noon();
+        let sunday = Local.with_ymd_and_hms(2026, 3, 15, 9, 0, 0).unwrap();
+        let task = make_task("s", sunday.timestamp(), 1);
+        assert_eq!(date_bucket(&task, now, WeekStart::Monday, 0), "this_week");
+        assert_eq!(date_bucket(&task, now, WeekStart::Sunday, 0), "next_week");
+
+        let two_weeks = Local.with_ymd_and_hms(2026, 3, 24, 9, 0, 0).unwrap();
+        let later = make_task("l", two_weeks.timestamp(), 1);
+        assert_eq!(date_bucket(&later, now, WeekStart::Monday, 0), "later");
+    }
+
+    #[test]
+    fn someday_tasks_are_hidden_unless_requested() {
+        let now = noon();
+        let mut parked = make_task("parked", now.timestamp() - 3 * 86_400, 2);
+        parked.someday = true;
+        let mut pinned = make_task("pinned", now.timestamp(), 3);
+        pinned.someday = true;
+        pinned.pinned = true;
+        let mut done = make_task("done", now.timestamp(), 1);
+        done.someday = true;
+        done.completed = true;
+        let regular = make_task("regular", now.timestamp() + 3_600, 1);
+        let tasks = vec![parked.clone(), pinned, done, regular];
+
+        let groups = query_tasks(
+            &tasks,
+            &[],
+            &TaskQuerySpec::default(),
+            now,
+            &Settings::default(),
+        )
+        .groups;
+        let ids: Vec<&str> = groups[0].tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["regular"]);
+
+        let spec = TaskQuerySpec {
+            filter: TaskFilter {
+                include_someday: true,
+                ..TaskFilter::default()
+            },
+            group_by: TaskGroupBy::DateBucket,
+            ..TaskQuerySpec::default()
+        };
+        let groups = query_tasks(&tasks, &[], &spec, now, &Settings::default()).groups;
+        let keys: Vec<&str> = groups.iter().map(|g| g.key.as_str()).collect();
+        assert!(keys.contains(&"someday"));
+        assert!(!keys.contains(&"overdue"));
+        assert_eq!(date_bucket(&parked, now, WeekStart::Monday, 0), "someday");
+
+        let backlog: Vec<String> = someday_tasks(&tasks).into_iter().map(|t| t.id).collect();
+        assert_eq!(backlog, vec!["pinned", "parked"]);
+    }
+
+    #[test]
+    fn tasks_starting_later_are_hidden_until_their_start() {
+        let now = noon();
+        let mut later = make_task("later", now.timestamp(), 2);
+        later.start_at = Some(now.timestamp() + 86_400);
+        let mut sooner = make_task("sooner", now.timestamp(), 3);
+        sooner.start_at = Some(now.timestamp() + 3_600);
+        let mut started = make_task("started", now.timestamp(), 1);
+        started.start_at = Some(now.timestamp());
+        let tasks = vec![later.clone(), sooner, started];
+
+        let groups = query_tasks(
+            &tasks,
+            &[],
+            &TaskQuerySpec::default(),
+            now,
+            &Settings::default(),
+        )
+        .groups;
+        let ids: Vec<&str> = groups[0].tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["started"]);
+
+        let spec = TaskQuerySpec {
+            filter: TaskFilter {
+                include_scheduled: true,
+                ..TaskFilter::default()
+            },
+            group_by: TaskGroupBy::DateBucket,
+            ..TaskQuerySpec::default()
+        };
+        let groups = query_tasks(&tasks, &[], &spec, now, &Settings::default()).groups;
+        let keys: Vec<&str> = groups.iter().map(|g| g.key.as_str()).collect();
+        assert_eq!(keys, vec!["today", "scheduled"]);
+        assert_eq!(date_bucket(&later, now, WeekStart::Monday, 0), "scheduled");
+
+        let waiting: Vec<String> = scheduled_tasks(&tasks, now.timestamp())
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(waiting, vec!["sooner", "later"]);
+    }
+}
@@ -0,0 +1,203 @@
+use chrono::{DateTime, Local};
+
+use crate::models::TasksFile;
+use crate::storage::{Storage, StorageError};
+
+/// What happened when data.json could not be read at load time. Kept for the session so the
+/// UI can explain it and offer the remaining backups.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct RecoveryStatus {
+    pub detected_at: i64,
+    pub error: String,
+    /// Where the unreadable file was moved; `None` if moving it failed.
+    pub quarantined_as: Option<String>,
+    /// Backup that was loaded instead; `None` means the app started empty.
+    pub recovered_from: Option<String>,
+    /// Newer backups that were unreadable too, newest first.
+    pub skipped_backups: Vec<String>,
+}
+
+/// Parse failures and undecodable bytes mean the file itself is damaged. Other IO errors
+/// (permissions, locks) are left alone: the file may be fine once the problem goes away.
+pub fn is_corrupt(error: &StorageError) -> bool {
+    match error {
+        StorageError::Json(_) => true,
+        StorageError::Io(err) => err.kind() == std::io::ErrorKind::InvalidData,
+        StorageError::Sqlite(err) => matches!(
+            err.sqlite_error_code(),
+            Some(rusqlite::ErrorCode::DatabaseCorrupt | rusqlite::ErrorCode::NotADatabase)
+        ),
+    }
+}
+
+fn empty_tasks_file() -> TasksFile {
+    TasksFile {
+        schema_version: 1,
+        tasks: Vec::new(),
+        projects: Vec::new(),
+        trash: Vec::new(),
+        tags: Vec::new(),
+    }
+}
+
+/// Quarantines the corrupt data.json, then loads the newest backup that still parses and
+/// writes it back as data.json. Falls back to an empty data set when no backup is usable.
+pub fn recover_tasks(
+    storage: &Storage,
+    error: &StorageError,
+    now: DateTime<Local>,
+) -> (TasksFile, RecoveryStatus) {
+    let mut status = RecoveryStatus {
+        detected_at: now.timestamp(),
+        error: error.to_string(),
+        quarantined_as: None,
+        recovered_from: None,
+        skipped_backups: Vec::new(),
+    };
+    match storage.quarantine_data_file(&now.format("%Y%m%d-%H%M%S").to_string()) {
+        Ok(name) => status.quarantined_as = Some(name),
+        Err(err) => log::error!("recovery: failed to quarantine data.json err={err}"),
+    }
+
+    let backups = match storage.list_backups() {
+        Ok(backups) => backups,
+        Err(err) => {
+            log::warn!("recovery: failed to list backups err={err}");
+            Vec::new()
+        }
+    };
+    for (name, ..) in backups {
+        match storage.read_backup(&name) {
+            Ok(file) => {
+                if let Err(err) = storage.save_tasks(&file, false) {
+                    log::error!("recovery: failed to write recovered data.json err={err}");
+                }
+                log::warn!(
+                    "recovery: restored data.json from backup={} tasks={}",
+                    name,
+                    file.tasks.len()
+                );
+                status.recovered_from = Some(name);
+                return (file, status);
+            }
+            Err(err) => {
+                log::warn!("recovery: skipping unreadable backup={name} err={err}");
+                status.skipped_backups.push(name);
+            }
+        }
+    }
+    log::warn!("recovery: no usable backup; starting with empty data");
+    (empty_tasks_file(), status)
+}
+
+/// Loads data.json for boot and `load_state`. A missing file starts empty; a corrupt one goes
+/// through [`recover_tasks`] and yields a status for the UI.
+pub fn load_tasks_or_recover(
+    storage: &Storage,
+    now: DateTime<Local>,
+) -> (TasksFile, Option<RecoveryStatus>) {
+    match storage.load_tasks() {
+        Ok(file) => (file, None),
+        Err(StorageError::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => {
+            log::info!("data.json missing -> defaults");
+            (empty_tasks_file(), None)
+        }
+        Err(err) if is_corrupt(&err) => {
+            log::error!("data.json is corrupt err={err}; attempting recovery");
+            let (file, status) = recover_tasks(storage, &err, now);
+            (file, Some(status))
+        }
+        Err(err) => {
+            log::warn!("failed to load data.json -> defaults err={err}");
+            (empty_tasks_file(), None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 3, 18, 9, 30, 0).unwrap()
+    }
+
+    fn setup() -> (tempfile::TempDir, Storage) {
+        let root = tempfile::tempdir().unwrap();
+        let storage = Storage::new(root.path().to_path_buf());
+        storage.ensure_dirs().unwrap();
+        (root, storage)
+    }
+
+    const VALID: &str = r#"{"schema_version":1,"tasks":[{"id":"a","title":"Saved","completed_at":null,"created_at":1,"updated_at":1,"notes":null}],"projects":[]}"#;
+
+    #[test]
+    fn corrupt_data_is_quarantined_and_restored_from_the_newest_valid_backup() {
+        let (root, storage) = setup();
+        let backups = root.path().join("backups");
+        fs::write(backups.join("data-2026-03-16.json"), VALID).unwrap();
+        // Newer by mtime, but broken: it must be skipped.
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        fs::write(backups.join("data-2026-03-17.json"), "{broken").unwrap();
+        fs::write(root.path().join("data.json"), "{\"tasks\": [").unwrap();
+
+        let (file, status) = load_tasks_or_recover(&storage, now());
+        let status = status.expect("corruption is reported");
+        assert_eq!(file.tasks[0].title, "Saved");
+        assert_eq!(
+            status.quarantined_as.as_deref(),
+            Some("data.corrupt-20260318-093000.json")
+        );
+        assert_eq!(
+            status.recovered_from.as_deref(),
+            Some("data-2026-03-16.json")
+        );
+        assert_eq!(status.skipped_backups, ["data-2026-03-17.json"]);
+        assert!(status.error.starts_with("json error"));
+        assert_eq!(
+            fs::read_to_string(root.path().join("data.corrupt-20260318-093000.json")).unwrap(),
+            "{\"tasks\": ["
+        );
+        // data.json now holds the recovered data, so the next boot is clean.
+        let (again, status) = load_tasks_or_recover(&storage, now());
+        assert!(status.is_none());
+        assert_eq!(again.tasks.len(), 1);
+    }
+
+    #[test]
+    fn corrupt_data_without_backups_starts_empty_and_keeps_every_quarantine() {
+        let (root, storage) = setup();
+        fs::write(root.path().join("data.json"), [0xFF]).unwrap();
+        let (file, status) = load_tasks_or_recover(&storage, now());
+        let status = status.unwrap();
+        assert!(file.tasks.is_empty());
+        assert_eq!(status.recovered_from, None);
+        assert!(!root.path().join("data.json").exists());
+
+        fs::write(root.path().join("data.json"), "nope").unwrap();
+        let (_, status) = load_tasks_or_recover(&storage, now());
+        assert_eq!(
+            status.unwrap().quarantined_as.as_deref(),
+            Some("data.corrupt-20260318-093000-2.json")
+        );
+        assert!(root
+            .path()
+            .join("data.corrupt-20260318-093000.json")
+            .exists());
+    }
+
+    #[test]
+    fn missing_data_is_not_a_recovery() {
+        let (_root, storage) = setup();
+        let (file, status) = load_tasks_or_recover(&storage, now());
+        assert!(file.tasks.is_empty());
+        assert!(status.is_none());
+        assert!(!is_corrupt(&StorageError::Io(std::io::Error::from(
+            std::io::ErrorKind::PermissionDenied
+        ))));
+    }
+}
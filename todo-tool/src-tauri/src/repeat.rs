@@ -1,11 +1,47 @@
-use chrono::{Datelike, Duration, LocalResult, NaiveDate, TimeZone, Utc, Weekday};
+use std::collections::{BTreeMap, HashSet};
 
-use crate::models::RepeatRule;
+use chrono::{
+    DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday,
+};
+
+use crate::models::{ReminderKind, ReminderLeadTimes, RepeatRule, Task, Timestamp};
+
+/// Upper bound for `Task::spawn_ahead`; larger values are treated as this.
+pub const MAX_SPAWN_AHEAD: u8 = 12;
 
 pub fn next_due_timestamp(due_at: i64, repeat: &RepeatRule) -> i64 {
     next_due_timestamp_in_timezone(chrono::Local, due_at, repeat)
 }
 
+/// `task.timezone` as a zone; `None` when unset or unknown, meaning the system zone.
+pub fn task_timezone(task: &Task) -> Option<chrono_tz::Tz> {
+    task.timezone.as_deref()?.parse().ok()
+}
+
+/// Trims `task.timezone`, clearing it when blank; rejects names that are not IANA zones.
+pub fn normalize_timezone(task: &mut Task) -> Result<(), String> {
+    let Some(name) = task.timezone.as_deref().map(str::trim) else {
+        return Ok(());
+    };
+    if name.is_empty() {
+        task.timezone = None;
+        return Ok(());
+    }
+    let tz: chrono_tz::Tz = name
+        .parse()
+        .map_err(|_| format!("invalid timezone: {name}"))?;
+    task.timezone = Some(tz.name().to_string());
+    Ok(())
+}
+
+/// The occurrence of `task`'s repeat after `due_at`, computed in the task's own zone.
+pub fn next_task_due(task: &Task, due_at: Timestamp) -> Timestamp {
+    match task_timezone(task) {
+        Some(tz) => next_due_timestamp_in_timezone(tz, due_at, &task.repeat),
+        None => next_due_timestamp(due_at, &task.repeat),
+    }
+}
+
 fn next_due_timestamp_in_timezone<Tz>(tz: Tz, due_at: i64, repeat: &RepeatRule) -> i64
 where
     Tz: TimeZone,
@@ -23,22 +59,47 @@ where
         RepeatRule::None => base_date,
         RepeatRule::Daily { workday_only } => next_workday(base_date, *workday_only),
         RepeatRule::Weekly { days } => next_weekday(base_date, days),
-        RepeatRule::Monthly { day } => next_month_day(base_date, *day),
+        RepeatRule::Monthly { day } => next_month_day(base_date, 1, *day),
         RepeatRule::Yearly { month, day } => next_year_day(base_date, *month, *day),
+        RepeatRule::EveryNDays { n } => base_date + Duration::days(i64::from((*n).max(1))),
+        RepeatRule::EveryNWeeks { n, days } => next_nth_weekday(base_date, *n, days),
+        RepeatRule::EveryNMonths { n, day } => next_month_day(base_date, *n, *day),
     };
 
-    let time = base.time();
-    let next_naive = next_date.and_time(time);
+    resolve_local(&tz, next_date.and_time(base.time()), base).timestamp()
+}
 
-    let next_local = match tz.from_local_datetime(&next_naive) {
+/// `naive` in `tz`: the earlier of two ambiguous times, an hour later inside a DST gap.
+fn resolve_local<Tz>(tz: &Tz, naive: NaiveDateTime, fallback: DateTime<Tz>) -> DateTime<Tz>
+where
+    Tz: TimeZone,
+    Tz::Offset: Copy,
+{
+    match tz.from_local_datetime(&naive) {
         LocalResult::Single(value) => value,
         LocalResult::Ambiguous(value, _) => value,
         LocalResult::None => tz
-            .from_local_datetime(&(next_naive + Duration::hours(1)))
+            .from_local_datetime(&(naive + Duration::hours(1)))
             .earliest()
-            .unwrap_or(base),
-    };
-    next_local.timestamp()
+            .unwrap_or(fallback),
+    }
+}
+
+/// `due_at`'s time of day on the day `done_at` falls on.
+fn completion_anchor<Tz>(tz: Tz, due_at: Timestamp, done_at: Timestamp) -> Timestamp
+where
+    Tz: TimeZone,
+    Tz::Offset: Copy,
+{
+    match (
+        tz.timestamp_opt(due_at, 0).single(),
+        tz.timestamp_opt(done_at, 0).single(),
+    ) {
+        (Some(due), Some(done)) => {
+            resolve_local(&tz, done.date_naive().and_time(due.time()), done).timestamp()
+        }
+        _ => done_at,
+    }
 }
 
 fn next_workday(date: NaiveDate, workday_only: bool) -> NaiveDate {
@@ -70,14 +131,32 @@ fn next_weekday(date: NaiveDate, days: &[u8]) -> NaiveDate {
     date + Duration::days(7)
 }
 
-fn next_month_day(date: NaiveDate, day: u8) -> NaiveDate {
-    let mut year = date.year();
-    let mut month = date.month();
-    month += 1;
-    if month > 12 {
-        month = 1;
-        year += 1;
+/// The next listed weekday later in `date`'s Monday-based week, else the first listed weekday
+/// `n` weeks after that week's start. Without valid `days` the due date's weekday is kept.
+fn next_nth_weekday(date: NaiveDate, n: u16, days: &[u8]) -> NaiveDate {
+    let n = i64::from(n.max(1));
+    let today = date.weekday().number_from_monday() as u8;
+    let mut days: Vec<u8> = days
+        .iter()
+        .copied()
+        .filter(|day| (1..=7).contains(day))
+        .collect();
+    if days.is_empty() {
+        days.push(today);
     }
+    days.sort_unstable();
+    if let Some(later) = days.iter().find(|day| **day > today) {
+        return date + Duration::days(i64::from(later - today));
+    }
+    let week_start = date - Duration::days(i64::from(today - 1));
+    week_start + Duration::days(7 * n + i64::from(days[0] - 1))
+}
+
+/// `day` (clamped to the month's length) `months` months after `date`.
+fn next_month_day(date: NaiveDate, months: u16, day: u8) -> NaiveDate {
+    let index = date.month0() + u32::from(months.max(1));
+    let year = date.year() + (index / 12) as i32;
+    let month = index % 12 + 1;
     let last_day = last_day_of_month(year, month);
     let safe_day = std::cmp::max(1, day as u32);
     let use_day = std::cmp::min(safe_day, last_day);
@@ -101,10 +180,162 @@ fn last_day_of_month(year: i32, month: u32) -> u32 {
     last.day()
 }
 
+/// Due time of the occurrence after `completed`. Undated tasks repeat from when they were done;
+/// with `repeat_from_completion` a dated task counts from the day it was done, keeping its time
+/// of day.
+pub fn next_repeat_due(completed: &Task, now: Timestamp) -> Timestamp {
+    let done_at = completed.completed_at.unwrap_or(now);
+    let anchor = match completed.due_at {
+        Some(due) if completed.repeat_from_completion => match task_timezone(completed) {
+            Some(tz) => completion_anchor(tz, due, done_at),
+            None => completion_anchor(chrono::Local, due, done_at),
+        },
+        Some(due) => due,
+        None => done_at,
+    };
+    next_task_due(completed, anchor)
+}
+
+/// Whether `completed`'s series stops before an occurrence due at `next_due`: it already has
+/// `end_after_occurrences` occurrences, or `next_due` is past `end_by_date`.
+pub fn repeat_has_ended(completed: &Task, next_due: Timestamp) -> bool {
+    completed
+        .end_after_occurrences
+        .is_some_and(|limit| completed.occurrence_index.saturating_add(1) >= limit)
+        || completed.end_by_date.is_some_and(|end| next_due > end)
+}
+
+/// Next occurrence of `completed`'s series, due at `next_due`. Everything that belongs to one
+/// occurrence starts over: reminder state, tracked time, the checklist, comments and the
+/// carry-over count.
+pub fn build_next_repeat_task(
+    completed: &Task,
+    next_due: i64,
+    lead_times: &ReminderLeadTimes,
+    now: DateTime<Utc>,
+) -> Task {
+    let mut next = completed.clone();
+    next.id = format!("{}-{}", completed.id, now.timestamp());
+    next.series_id = Some(
+        completed
+            .series_id
+            .clone()
+            .unwrap_or_else(|| completed.id.clone()),
+    );
+    next.completed = false;
+    next.completed_at = None;
+    next.created_at = now.timestamp();
+    next.updated_at = now.timestamp();
+    next.sort_order = now.timestamp_millis();
+    next.due_at = Some(next_due);
+    // The start date keeps its distance to the due date.
+    next.start_at = match (completed.start_at, completed.due_at) {
+        (Some(start), Some(old_due)) => Some(start.saturating_add(next_due - old_due)),
+        _ => None,
+    };
+    next.occurrence_index = completed.occurrence_index.saturating_add(1);
+    next.reminder.last_fired_at = None;
+    next.reminder.forced_dismissed = false;
+    next.reminder.snoozed_until = None;
+    next.reminder.repeat_fired_count = 0;
+    next.reminder.ignored_count = 0;
+    // Escalation is per occurrence: the next one starts as the Normal reminder it was.
+    if next.reminder.escalated {
+        next.reminder.kind = ReminderKind::Normal;
+        next.reminder.escalated = false;
+    }
+    // Tracked time belongs to the occurrence it was spent on.
+    next.time_entries.clear();
+    for step in &mut next.steps {
+        step.completed = false;
+        step.completed_at = None;
+    }
+    next.comments.clear();
+    next.refresh_progress();
+    next.carry_over_count = 0;
+
+    // Preserve the reminder offset semantics across repeat instances.
+    // (Otherwise a copied `remind_at` in the past would trigger immediately on the next cycle.)
+    // An undated task has no offset to carry, so its next reminder uses the default target.
+    // Specs are relative to the due time already and move with it.
+    match completed.due_at {
+        Some(_)
+            if completed.reminder.remind_at.is_none() && !completed.reminder.specs.is_empty() =>
+        {
+            next.reminder.remind_at = None
+        }
+        Some(old_due) if next.reminder.kind != ReminderKind::None => {
+            let old_default_target = lead_times.default_target(&completed.reminder.kind, old_due);
+            let old_target = completed.reminder.remind_at.unwrap_or(old_default_target);
+            let offset = (old_due - old_target).max(0);
+            next.reminder.remind_at = Some(next_due - offset);
+        }
+        _ => next.reminder.remind_at = None,
+    }
+
+    next
+}
+
+/// Instances that have to be created so every series with `spawn_ahead > 0` has that many open
+/// occurrences after its earliest open one. The latest open occurrence is the template, so edits
+/// to it carry forward; series whose instances are all completed are left alone.
+pub fn plan_spawn_ahead(
+    tasks: &[Task],
+    lead_times: &ReminderLeadTimes,
+    now: DateTime<Utc>,
+) -> Vec<Task> {
+    let mut open_by_series: BTreeMap<&str, Vec<&Task>> = BTreeMap::new();
+    for task in tasks.iter().filter(|task| !task.completed) {
+        let series = task.series_id.as_deref().unwrap_or(&task.id);
+        open_by_series.entry(series).or_default().push(task);
+    }
+
+    let mut taken_ids: HashSet<String> = tasks.iter().map(|task| task.id.clone()).collect();
+    let mut planned = Vec::new();
+    for (series, open) in open_by_series {
+        let Some(latest) = open.iter().max_by_key(|task| task.due_at) else {
+            continue;
+        };
+        if matches!(latest.repeat, RepeatRule::None)
+            || latest.spawn_ahead == 0
+            // The next due date is only known once the current one is done.
+            || latest.repeat_from_completion
+            || latest.someday
+            || latest.due_at.is_none()
+        {
+            continue;
+        }
+        let wanted = usize::from(latest.spawn_ahead.min(MAX_SPAWN_AHEAD)) + 1;
+        let mut template = (*latest).clone();
+        for _ in open.len()..wanted {
+            let Some(due) = template.due_at else {
+                break;
+            };
+            let next_due = next_task_due(&template, due);
+            if next_due <= due || repeat_has_ended(&template, next_due) {
+                break;
+            }
+            let mut next = build_next_repeat_task(&template, next_due, lead_times, now);
+            next.id = format!("{series}-{next_due}");
+            if !taken_ids.insert(next.id.clone()) {
+                let id = (2..)
+                    .map(|n| format!("{series}-{next_due}-{n}"))
+                    .find(|id| !taken_ids.contains(id))
+                    .expect("unbounded suffix search");
+                taken_ids.insert(id.clone());
+                next.id = id;
+            }
+            planned.push(next.clone());
+            template = next;
+        }
+    }
+    planned
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::RepeatRule;
+    use crate::models::{test_support, RepeatRule};
     use chrono::Timelike as _;
 
     #[test]
@@ -239,6 +470,75 @@ mod tests {
         assert_eq!(out, expected);
     }
 
+    #[test]
+    fn interval_rules_skip_whole_periods() {
+        let tz = chrono_tz::UTC;
+        let at = |y, m, d| tz.with_ymd_and_hms(y, m, d, 9, 0, 0).unwrap().timestamp();
+        let next = |due, rule: RepeatRule| next_due_timestamp_in_timezone(tz, due, &rule);
+
+        assert_eq!(
+            next(at(2024, 2, 27), RepeatRule::EveryNDays { n: 3 }),
+            at(2024, 3, 1)
+        );
+        // n = 0 behaves like 1 instead of repeating on the same day.
+        assert_eq!(
+            next(at(2024, 2, 27), RepeatRule::EveryNDays { n: 0 }),
+            at(2024, 2, 28)
+        );
+
+        // 2024-01-01 is a Monday: Friday of the same week, then Monday two weeks later.
+        let biweekly = |due| {
+            next(
+                due,
+                RepeatRule::EveryNWeeks {
+                    n: 2,
+                    days: vec![5, 1],
+                },
+            )
+        };
+        assert_eq!(biweekly(at(2024, 1, 1)), at(2024, 1, 5));
+        assert_eq!(biweekly(at(2024, 1, 5)), at(2024, 1, 15));
+        // No valid days: keep the due date's weekday.
+        assert_eq!(
+            next(
+                at(2024, 1, 3),
+                RepeatRule::EveryNWeeks {
+                    n: 3,
+                    days: vec![0, 9]
+                }
+            ),
+            at(2024, 1, 24)
+        );
+
+        // Clamped to the month's length and rolled across years.
+        assert_eq!(
+            next(at(2023, 11, 30), RepeatRule::EveryNMonths { n: 3, day: 31 }),
+            at(2024, 2, 29)
+        );
+        assert_eq!(
+            next(at(2024, 1, 15), RepeatRule::EveryNMonths { n: 14, day: 15 }),
+            at(2025, 3, 15)
+        );
+    }
+
+    #[test]
+    fn repeat_rules_keep_their_json_shape() {
+        let old: RepeatRule = serde_json::from_str(r#"{"type":"weekly","days":[1,3]}"#).unwrap();
+        assert_eq!(old, RepeatRule::Weekly { days: vec![1, 3] });
+        let rule = RepeatRule::EveryNWeeks {
+            n: 2,
+            days: vec![2],
+        };
+        let json = serde_json::to_string(&rule).unwrap();
+        assert_eq!(json, r#"{"type":"every_n_weeks","n":2,"days":[2]}"#);
+        assert_eq!(serde_json::from_str::<RepeatRule>(&json).unwrap(), rule);
+        assert_eq!(
+            serde_json::from_str::<RepeatRule>(r#"{"type":"every_n_months","n":6,"day":1}"#)
+                .unwrap(),
+            RepeatRule::EveryNMonths { n: 6, day: 1 }
+        );
+    }
+
     #[test]
     fn out_of_range_timestamp_falls_back_to_now() {
         // This exercises the `timestamp_opt(...).single() == None` fallback closure.
@@ -345,4 +645,272 @@ mod tests {
         assert_eq!(dt.hour(), 1);
         assert_eq!(dt.minute(), 30);
     }
+
+    fn repeating_task(id: &str, due_at: i64, spawn_ahead: u8) -> Task {
+        let mut task = test_support::task(id).due(due_at);
+        task.repeat = RepeatRule::Daily {
+            workday_only: false,
+        };
+        task.spawn_ahead = spawn_ahead;
+        task
+    }
+
+    #[test]
+    fn plan_spawn_ahead_fills_each_series_up_to_the_requested_count() {
+        let now = Utc.timestamp_opt(1_000, 0).unwrap();
+        let day0 = 1_767_225_600; // 2026-01-01T00:00:00Z
+        let mut head = repeating_task("a", day0, 3);
+        head.steps = vec![crate::models::Step {
+            id: "s".to_string(),
+            title: "s".to_string(),
+            completed: true,
+            created_at: 1,
+            completed_at: Some(2),
+            parent_id: None,
+        }];
+        head.comments = vec![crate::models::Comment {
+            id: "c".to_string(),
+            text: "c".to_string(),
+            created_at: 1,
+        }];
+
+        let planned = plan_spawn_ahead(
+            std::slice::from_ref(&head),
+            &ReminderLeadTimes::default(),
+            now,
+        );
+        assert_eq!(planned.len(), 3);
+        let mut due = day0;
+        for task in &planned {
+            due = next_due_timestamp(due, &head.repeat);
+            assert_eq!(task.due_at, Some(due));
+            assert_eq!(task.id, format!("a-{due}"));
+            assert_eq!(task.series_id.as_deref(), Some("a"));
+            assert!(!task.steps[0].completed);
+            assert_eq!(task.progress.steps_completed, 0);
+            assert!(task.comments.is_empty());
+        }
+
+        // Already materialized series are left alone; a completed head still counts its
+        // open successors only.
+        let mut tasks = vec![head.clone()];
+        tasks.extend(planned.clone());
+        assert!(plan_spawn_ahead(&tasks, &ReminderLeadTimes::default(), now).is_empty());
+        tasks[0].completed = true;
+        let topped_up = plan_spawn_ahead(&tasks, &ReminderLeadTimes::default(), now);
+        assert_eq!(topped_up.len(), 1);
+        assert_eq!(
+            topped_up[0].due_at,
+            Some(next_due_timestamp(planned[2].due_at.unwrap(), &head.repeat))
+        );
+
+        // Non-repeating, spawn_ahead=0 and fully completed series are skipped; huge values cap.
+        let mut once = repeating_task("once", day0, 2);
+        once.repeat = RepeatRule::None;
+        let off = repeating_task("off", day0, 0);
+        let mut done = repeating_task("done", day0, 2);
+        done.completed = true;
+        assert!(
+            plan_spawn_ahead(&[once, off, done], &ReminderLeadTimes::default(), now).is_empty()
+        );
+        let greedy = repeating_task("g", day0, u8::MAX);
+        assert_eq!(
+            plan_spawn_ahead(&[greedy], &ReminderLeadTimes::default(), now).len(),
+            usize::from(MAX_SPAWN_AHEAD)
+        );
+    }
+
+    #[test]
+    fn completing_and_spawning_ahead_build_the_same_occurrence() {
+        let now = Utc.timestamp_opt(1_000, 0).unwrap();
+        let day0 = 1_767_225_600;
+        let mut head = repeating_task("a", day0, 1);
+        head.steps = vec![crate::models::Step {
+            id: "s".to_string(),
+            title: "s".to_string(),
+            completed: true,
+            created_at: 1,
+            completed_at: Some(2),
+            parent_id: None,
+        }];
+        head.refresh_progress();
+        head.comments = vec![crate::models::Comment {
+            id: "c".to_string(),
+            text: "c".to_string(),
+            created_at: 1,
+        }];
+        head.carry_over_count = 3;
+        let leads = ReminderLeadTimes::default();
+
+        let spawned = plan_spawn_ahead(std::slice::from_ref(&head), &leads, now).remove(0);
+        let next_due = next_due_timestamp(day0, &head.repeat);
+        let mut done = head.clone();
+        done.completed = true;
+        done.completed_at = Some(day0);
+        let completed = build_next_repeat_task(&done, next_due, &leads, now);
+        assert!(!completed.steps[0].completed && completed.steps[0].completed_at.is_none());
+        assert_eq!(completed.progress.steps_completed, 0);
+        assert!(completed.comments.is_empty());
+        assert_eq!(completed.carry_over_count, 0);
+        // Only the id scheme differs between the two paths.
+        let same = Task {
+            id: spawned.id.clone(),
+            ..completed
+        };
+        assert_eq!(
+            serde_json::to_value(&same).unwrap(),
+            serde_json::to_value(&spawned).unwrap()
+        );
+    }
+
+    #[test]
+    fn plan_spawn_ahead_avoids_id_collisions() {
+        let now = Utc.timestamp_opt(1_000, 0).unwrap();
+        let head = repeating_task("a", 1_767_225_600, 1);
+        let next_due = next_due_timestamp(head.due_at.unwrap(), &head.repeat);
+        let mut squatter = repeating_task(&format!("a-{next_due}"), 1, 0);
+        squatter.repeat = RepeatRule::None;
+        let planned = plan_spawn_ahead(&[head, squatter], &ReminderLeadTimes::default(), now);
+        assert_eq!(planned.len(), 1);
+        assert_eq!(planned[0].id, format!("a-{next_due}-2"));
+    }
+
+    #[test]
+    fn undated_repeats_anchor_on_completion_and_skip_spawn_ahead() {
+        let now = Utc.timestamp_opt(1_000, 0).unwrap();
+        let day0 = 1_767_225_600;
+        let mut undated = repeating_task("u", day0, 2);
+        undated.due_at = None;
+        undated.reminder.kind = ReminderKind::Normal;
+        undated.reminder.remind_at = Some(day0 - 60);
+        assert!(plan_spawn_ahead(
+            std::slice::from_ref(&undated),
+            &ReminderLeadTimes::default(),
+            now
+        )
+        .is_empty());
+
+        undated.completed = true;
+        undated.completed_at = Some(day0);
+        let next_due = next_repeat_due(&undated, 5);
+        assert_eq!(next_due, next_due_timestamp(day0, &undated.repeat));
+        undated.completed_at = None;
+        assert_eq!(next_repeat_due(&undated, day0), next_due);
+
+        let next = build_next_repeat_task(&undated, next_due, &ReminderLeadTimes::default(), now);
+        assert_eq!(next.due_at, Some(next_due));
+        assert_eq!(next.reminder.remind_at, None);
+    }
+
+    #[test]
+    fn completion_based_repeats_count_from_the_day_it_was_done() {
+        let tz = chrono_tz::UTC;
+        let due = tz
+            .with_ymd_and_hms(2024, 3, 1, 9, 0, 0)
+            .unwrap()
+            .timestamp();
+        let done = tz
+            .with_ymd_and_hms(2024, 3, 4, 18, 30, 0)
+            .unwrap()
+            .timestamp();
+        let anchor = completion_anchor(tz, due, done);
+        assert_eq!(
+            anchor,
+            tz.with_ymd_and_hms(2024, 3, 4, 9, 0, 0)
+                .unwrap()
+                .timestamp()
+        );
+        assert_eq!(
+            next_due_timestamp_in_timezone(tz, anchor, &RepeatRule::EveryNDays { n: 3 }),
+            tz.with_ymd_and_hms(2024, 3, 7, 9, 0, 0)
+                .unwrap()
+                .timestamp()
+        );
+
+        let mut task = repeating_task("plants", due, 2);
+        task.repeat_from_completion = true;
+        assert!(plan_spawn_ahead(
+            std::slice::from_ref(&task),
+            &ReminderLeadTimes::default(),
+            Utc.timestamp_opt(1_000, 0).unwrap()
+        )
+        .is_empty());
+        task.completed = true;
+        task.completed_at = Some(done);
+        let from_done = next_repeat_due(&task, 5);
+        assert_eq!(
+            from_done,
+            next_due_timestamp(completion_anchor(chrono::Local, due, done), &task.repeat)
+        );
+        task.repeat_from_completion = false;
+        assert_eq!(
+            next_repeat_due(&task, 5),
+            next_due_timestamp(due, &task.repeat)
+        );
+    }
+
+    #[test]
+    fn end_conditions_stop_the_series() {
+        let now = Utc.timestamp_opt(1_000, 0).unwrap();
+        let day = 86_400;
+        let day0 = 1_767_225_600;
+        let leads = ReminderLeadTimes::default();
+
+        let mut counted = repeating_task("c", day0, 5);
+        counted.end_after_occurrences = Some(3);
+        let planned = plan_spawn_ahead(std::slice::from_ref(&counted), &leads, now);
+        let indexes: Vec<u32> = planned.iter().map(|task| task.occurrence_index).collect();
+        assert_eq!(indexes, vec![1, 2]);
+        assert!(!repeat_has_ended(&planned[0], day0 + 2 * day));
+        assert!(repeat_has_ended(&planned[1], day0 + 3 * day));
+
+        let mut dated = repeating_task("d", day0, 5);
+        dated.end_by_date = Some(day0 + 2 * day);
+        let planned = plan_spawn_ahead(std::slice::from_ref(&dated), &leads, now);
+        assert_eq!(planned.len(), 2);
+        assert_eq!(planned[1].due_at, Some(day0 + 2 * day));
+
+        let open_ended = repeating_task("o", day0, 0);
+        assert!(!repeat_has_ended(&open_ended, i64::MAX));
+        let next = build_next_repeat_task(&open_ended, day0 + day, &leads, now);
+        assert_eq!(next.occurrence_index, 1);
+    }
+
+    #[test]
+    fn task_timezone_keeps_the_time_of_day_across_dst() {
+        let tz = chrono_tz::America::New_York;
+        // The night of 2024-03-10 skips an hour in New York.
+        let due = tz
+            .with_ymd_and_hms(2024, 3, 9, 9, 0, 0)
+            .unwrap()
+            .timestamp();
+        let mut task = repeating_task("t", due, 0);
+        task.timezone = Some(" America/New_York ".to_string());
+        normalize_timezone(&mut task).unwrap();
+        assert_eq!(task.timezone.as_deref(), Some("America/New_York"));
+        let next = next_task_due(&task, due);
+        assert_eq!(
+            next,
+            tz.with_ymd_and_hms(2024, 3, 10, 9, 0, 0)
+                .unwrap()
+                .timestamp()
+        );
+        assert_eq!(next - due, 23 * 3600);
+
+        task.completed = true;
+        task.completed_at = Some(next + 12 * 3600);
+        task.repeat_from_completion = true;
+        assert_eq!(
+            next_repeat_due(&task, 0),
+            tz.with_ymd_and_hms(2024, 3, 11, 9, 0, 0)
+                .unwrap()
+                .timestamp()
+        );
+
+        task.timezone = Some("Mars/Olympus".to_string());
+        assert!(normalize_timezone(&mut task).is_err());
+        task.timezone = Some("  ".to_string());
+        normalize_timezone(&mut task).unwrap();
+        assert_eq!(task.timezone, None);
+    }
 }
@@ -0,0 +1,631 @@
+//! RFC 5545 `RRULE` <-> [`RepeatRule`] conversion for calendar import/export.
+//!
+//! Export is exact for every `RepeatRule`, including the "clamp to the last day of the month"
+//! behaviour of monthly/yearly rules, and carries a task's end condition as `COUNT` / `UNTIL`.
+//! Import maps whatever the model can express and lists everything it had to drop or
+//! approximate in [`RruleImport::lossy`], so callers can surface the difference instead of
+//! silently changing an external series.
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::ics::{format_local, format_utc, parse_time};
+use crate::models::{RepeatRule, Task, Timestamp};
+
+const WEEKDAYS: [&str; 7] = ["MO", "TU", "WE", "TH", "FR", "SA", "SU"];
+const WORKDAYS: [u8; 5] = [1, 2, 3, 4, 5];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RruleImport {
+    pub rule: RepeatRule,
+    /// `COUNT`: occurrences in the series, the one at `DTSTART` included.
+    pub count: Option<u32>,
+    /// `UNTIL`: no occurrence is due after this.
+    pub until: Option<Timestamp>,
+    /// Human-readable notes for each part that could not be kept as-is; empty when lossless.
+    pub lossy: Vec<String>,
+}
+
+/// `BYMONTHDAY` (plus `BYSETPOS` when needed) matching our clamp-to-month-end semantics.
+fn month_day_parts(day: u8, shortest_month: u8) -> String {
+    if day <= shortest_month {
+        format!("BYMONTHDAY={day}")
+    } else if day >= 31 {
+        "BYMONTHDAY=-1".to_string()
+    } else {
+        format!("BYMONTHDAY={day},-1;BYSETPOS=1")
+    }
+}
+
+fn month_length(month: u8) -> (u8, u8) {
+    match month {
+        2 => (28, 29),
+        4 | 6 | 9 | 11 => (30, 30),
+        _ => (31, 31),
+    }
+}
+
+fn byday(days: &[u8]) -> String {
+    days.iter()
+        .map(|day| WEEKDAYS[usize::from(*day - 1)])
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Valid weekdays (1 = Monday .. 7 = Sunday), sorted and without duplicates.
+fn normalize_days(days: &[u8]) -> Vec<u8> {
+    let mut days: Vec<u8> = days
+        .iter()
+        .copied()
+        .filter(|day| (1..=7).contains(day))
+        .collect();
+    days.sort_unstable();
+    days.dedup();
+    days
+}
+
+/// `RRULE` value (without the `RRULE:` prefix); `None` for tasks that do not repeat.
+pub fn repeat_rule_to_rrule(rule: &RepeatRule) -> Option<String> {
+    let value = match rule {
+        RepeatRule::None => return None,
+        RepeatRule::Daily {
+            workday_only: false,
+        } => "FREQ=DAILY".to_string(),
+        RepeatRule::Daily { workday_only: true } => {
+            format!("FREQ=DAILY;BYDAY={}", byday(&WORKDAYS))
+        }
+        RepeatRule::Weekly { days } => {
+            // Invalid entries are ignored by the scheduler too, leaving a plain weekly repeat.
+            let days = normalize_days(days);
+            if days.is_empty() {
+                "FREQ=WEEKLY".to_string()
+            } else {
+                format!("FREQ=WEEKLY;BYDAY={}", byday(&days))
+            }
+        }
+        RepeatRule::Monthly { day } => {
+            format!("FREQ=MONTHLY;{}", month_day_parts((*day).clamp(1, 31), 28))
+        }
+        RepeatRule::Yearly { month, day } => {
+            let month = (*month).clamp(1, 12);
+            let (shortest, longest) = month_length(month);
+            let day = (*day).clamp(1, longest);
+            format!(
+                "FREQ=YEARLY;BYMONTH={month};{}",
+                month_day_parts(day, shortest)
+            )
+        }
+        RepeatRule::EveryNDays { n } => format!("FREQ=DAILY;INTERVAL={}", (*n).max(1)),
+        RepeatRule::EveryNWeeks { n, days } => {
+            let days = normalize_days(days);
+            let n = (*n).max(1);
+            if days.is_empty() {
+                format!("FREQ=WEEKLY;INTERVAL={n}")
+            } else {
+                // Our weeks start on Monday, which decides the skipped weeks for several days.
+                format!("FREQ=WEEKLY;INTERVAL={n};BYDAY={};WKST=MO", byday(&days))
+            }
+        }
+        RepeatRule::EveryNMonths { n, day } => format!(
+            "FREQ=MONTHLY;INTERVAL={};{}",
+            (*n).max(1),
+            month_day_parts((*day).clamp(1, 31), 28)
+        ),
+    };
+    Some(value)
+}
+
+/// [`repeat_rule_to_rrule`] plus the task's end: `COUNT` holds the occurrences left from this
+/// one on (the exported `DTSTART`), `UNTIL` its `end_by_date`. RFC 5545 allows only one of
+/// them, so a task with both keeps the count. `floating` writes `UNTIL` in local time to match
+/// a floating `DTSTART`.
+pub fn task_rrule(task: &Task, floating: bool) -> Option<String> {
+    let mut value = repeat_rule_to_rrule(&task.repeat)?;
+    if let Some(limit) = task.end_after_occurrences {
+        let left = limit.saturating_sub(task.occurrence_index).max(1);
+        value.push_str(&format!(";COUNT={left}"));
+    } else if let Some(until) = task.end_by_date {
+        let until = if floating {
+            format_local(until)
+        } else {
+            format_utc(until)
+        };
+        value.push_str(&format!(";UNTIL={until}"));
+    }
+    Some(value)
+}
+
+fn parse_byday(value: &str) -> Result<Vec<u8>, String> {
+    value
+        .split(',')
+        .map(|token| {
+            let token = token.trim().to_ascii_uppercase();
+            WEEKDAYS
+                .iter()
+                .position(|day| *day == token)
+                .map(|index| index as u8 + 1)
+                .ok_or_else(|| format!("unsupported BYDAY value: {token}"))
+        })
+        .collect()
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid {name} value: {value}"))
+}
+
+/// An external day-of-month skips months that are too short; ours falls back to the last day.
+fn plain_month_day(day: u8, shortest: u8, lossy: &mut Vec<String>) -> u8 {
+    if day > shortest {
+        lossy.push(format!(
+            "day {day} is skipped in shorter months externally; imported as the last day of those months"
+        ));
+    }
+    day
+}
+
+/// Reads `BYMONTHDAY`/`BYSETPOS` back into a clamped day, recognizing the exact forms written by
+/// [`repeat_rule_to_rrule`]. `shortest`/`longest` bound the length of the month(s) involved.
+fn parse_month_day(
+    bymonthday: &str,
+    bysetpos: Option<&str>,
+    (shortest, longest): (u8, u8),
+    lossy: &mut Vec<String>,
+) -> Result<u8, String> {
+    let values: Vec<i32> = bymonthday
+        .split(',')
+        .map(|value| parse_number("BYMONTHDAY", value))
+        .collect::<Result<_, _>>()?;
+    match (values.as_slice(), bysetpos) {
+        ([-1], None) => Ok(longest),
+        ([day, -1], Some("1")) if (1..=31).contains(day) => Ok(*day as u8),
+        ([day], None) if (1..=31).contains(day) => Ok(plain_month_day(*day as u8, shortest, lossy)),
+        ([first, ..], _) if (1..=31).contains(first) => {
+            lossy.push(format!(
+                "only the first BYMONTHDAY is kept: BYMONTHDAY={bymonthday}"
+            ));
+            if bysetpos.is_some() {
+                lossy.push("BYSETPOS is not supported and was ignored".to_string());
+            }
+            Ok(*first as u8)
+        }
+        _ => Err(format!("unsupported BYMONTHDAY value: {bymonthday}")),
+    }
+}
+
+/// Parses an `RRULE` value (the `RRULE:` prefix is optional). `dtstart` fills in the weekday /
+/// day / month an external rule leaves implicit.
+pub fn rrule_to_repeat_rule(value: &str, dtstart: NaiveDate) -> Result<RruleImport, String> {
+    let value = value.trim();
+    let value = value
+        .strip_prefix("RRULE:")
+        .or_else(|| value.strip_prefix("rrule:"))
+        .unwrap_or(value);
+
+    let mut freq = None;
+    let mut byday_value = None;
+    let mut bymonthday = None;
+    let mut bymonth = None;
+    let mut bysetpos = None;
+    let mut interval: u16 = 1;
+    let mut wkst = None;
+    let mut count = None;
+    let mut until = None;
+    let mut lossy = Vec::new();
+    for part in value.split(';').filter(|part| !part.trim().is_empty()) {
+        let (name, val) = part
+            .split_once('=')
+            .ok_or_else(|| format!("malformed RRULE part: {part}"))?;
+        let name = name.trim().to_ascii_uppercase();
+        let val = val.trim();
+        match name.as_str() {
+            "FREQ" => freq = Some(val.to_ascii_uppercase()),
+            "BYDAY" => byday_value = Some(val),
+            "BYMONTHDAY" => bymonthday = Some(val),
+            "BYMONTH" => bymonth = Some(val),
+            "BYSETPOS" => bysetpos = Some(val),
+            "INTERVAL" => interval = parse_number::<u16>("INTERVAL", val)?.max(1),
+            "COUNT" => count = Some(parse_number::<u32>("COUNT", val)?.max(1)),
+            "UNTIL" => {
+                until =
+                    Some(parse_time("", val).ok_or_else(|| format!("invalid UNTIL value: {val}"))?)
+            }
+            "WKST" => wkst = Some(val.to_ascii_uppercase()),
+            _ => lossy.push(format!("{name} is not supported and was ignored")),
+        }
+    }
+
+    let interval_note =
+        format!("INTERVAL={interval} is not supported with this rule; repeats every period");
+    let rule = match freq.as_deref() {
+        Some("DAILY") => match byday_value {
+            None if interval > 1 => RepeatRule::EveryNDays { n: interval },
+            None => RepeatRule::Daily {
+                workday_only: false,
+            },
+            Some(value) => {
+                if interval > 1 {
+                    lossy.push(interval_note.clone());
+                }
+                let days = normalize_days(&parse_byday(value)?);
+                if days == WORKDAYS {
+                    RepeatRule::Daily { workday_only: true }
+                } else {
+                    // DAILY limited by BYDAY is the same series as WEEKLY on those days.
+                    RepeatRule::Weekly { days }
+                }
+            }
+        },
+        Some("WEEKLY") => {
+            let days = match byday_value {
+                Some(value) => normalize_days(&parse_byday(value)?),
+                None => Vec::new(),
+            };
+            if interval > 1 {
+                // The week start only matters when one week holds several occurrences.
+                if days.len() > 1 && wkst.as_deref().is_some_and(|wkst| wkst != "MO") {
+                    lossy.push(format!(
+                        "WKST={} is not supported; weeks start on Monday",
+                        wkst.as_deref().unwrap_or_default()
+                    ));
+                }
+                RepeatRule::EveryNWeeks { n: interval, days }
+            } else {
+                RepeatRule::Weekly { days }
+            }
+        }
+        Some("MONTHLY") => {
+            if let Some(value) = byday_value {
+                return Err(format!("unsupported monthly BYDAY rule: {value}"));
+            }
+            let day = match bymonthday {
+                Some(value) => parse_month_day(value, bysetpos, (28, 31), &mut lossy)?,
+                None => plain_month_day(dtstart.day() as u8, 28, &mut lossy),
+            };
+            if interval > 1 {
+                RepeatRule::EveryNMonths { n: interval, day }
+            } else {
+                RepeatRule::Monthly { day }
+            }
+        }
+        Some("YEARLY") => {
+            if let Some(value) = byday_value {
+                return Err(format!("unsupported yearly BYDAY rule: {value}"));
+            }
+            if interval > 1 {
+                lossy.push(interval_note.clone());
+            }
+            let month = match bymonth {
+                Some(value) => {
+                    let months: Vec<u8> = value
+                        .split(',')
+                        .map(|month| parse_number("BYMONTH", month))
+                        .collect::<Result<_, _>>()?;
+                    if months.iter().any(|month| !(1..=12).contains(month)) {
+                        return Err(format!("invalid BYMONTH value: {value}"));
+                    }
+                    if months.len() > 1 {
+                        lossy.push(format!("only the first BYMONTH is kept: BYMONTH={value}"));
+                    }
+                    months[0]
+                }
+                None => dtstart.month() as u8,
+            };
+            let lengths = month_length(month);
+            let day = match bymonthday {
+                Some(value) => parse_month_day(value, bysetpos, lengths, &mut lossy)?,
+                None => plain_month_day(dtstart.day() as u8, lengths.0, &mut lossy),
+            };
+            RepeatRule::Yearly { month, day }
+        }
+        Some(other) => return Err(format!("unsupported FREQ: {other}")),
+        None => return Err("RRULE is missing FREQ".to_string()),
+    };
+
+    Ok(RruleImport {
+        rule,
+        count,
+        until,
+        lossy,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+    use chrono::{Local, TimeZone};
+
+    fn start() -> NaiveDate {
+        // A Wednesday.
+        NaiveDate::from_ymd_opt(2026, 3, 18).unwrap()
+    }
+
+    fn import(value: &str) -> RruleImport {
+        rrule_to_repeat_rule(value, start()).unwrap()
+    }
+
+    fn assert_round_trip(rule: RepeatRule, expected: &str) {
+        let exported = repeat_rule_to_rrule(&rule).unwrap();
+        assert_eq!(exported, expected);
+        let back = import(&exported);
+        assert_eq!(back.rule, rule, "{expected}");
+        assert!(back.lossy.is_empty(), "{expected}: {:?}", back.lossy);
+    }
+
+    #[test]
+    fn supported_rules_round_trip_losslessly() {
+        assert_eq!(repeat_rule_to_rrule(&RepeatRule::None), None);
+        assert_round_trip(
+            RepeatRule::Daily {
+                workday_only: false,
+            },
+            "FREQ=DAILY",
+        );
+        assert_round_trip(
+            RepeatRule::Daily { workday_only: true },
+            "FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR",
+        );
+        assert_round_trip(RepeatRule::Weekly { days: vec![] }, "FREQ=WEEKLY");
+        assert_round_trip(
+            RepeatRule::Weekly {
+                days: vec![1, 3, 5, 6, 7],
+            },
+            "FREQ=WEEKLY;BYDAY=MO,WE,FR,SA,SU",
+        );
+        for day in 1..=28 {
+            assert_round_trip(
+                RepeatRule::Monthly { day },
+                &format!("FREQ=MONTHLY;BYMONTHDAY={day}"),
+            );
+        }
+        assert_round_trip(
+            RepeatRule::Monthly { day: 29 },
+            "FREQ=MONTHLY;BYMONTHDAY=29,-1;BYSETPOS=1",
+        );
+        assert_round_trip(
+            RepeatRule::Monthly { day: 30 },
+            "FREQ=MONTHLY;BYMONTHDAY=30,-1;BYSETPOS=1",
+        );
+        assert_round_trip(
+            RepeatRule::Monthly { day: 31 },
+            "FREQ=MONTHLY;BYMONTHDAY=-1",
+        );
+        assert_round_trip(
+            RepeatRule::Yearly { month: 3, day: 18 },
+            "FREQ=YEARLY;BYMONTH=3;BYMONTHDAY=18",
+        );
+        assert_round_trip(
+            RepeatRule::Yearly { month: 4, day: 30 },
+            "FREQ=YEARLY;BYMONTH=4;BYMONTHDAY=30",
+        );
+        assert_round_trip(
+            RepeatRule::Yearly { month: 12, day: 31 },
+            "FREQ=YEARLY;BYMONTH=12;BYMONTHDAY=31",
+        );
+        assert_round_trip(
+            RepeatRule::Yearly { month: 2, day: 29 },
+            "FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=29,-1;BYSETPOS=1",
+        );
+    }
+
+    #[test]
+    fn interval_rules_round_trip_losslessly() {
+        assert_round_trip(RepeatRule::EveryNDays { n: 3 }, "FREQ=DAILY;INTERVAL=3");
+        assert_round_trip(
+            RepeatRule::EveryNWeeks { n: 2, days: vec![] },
+            "FREQ=WEEKLY;INTERVAL=2",
+        );
+        assert_round_trip(
+            RepeatRule::EveryNWeeks {
+                n: 2,
+                days: vec![1, 5],
+            },
+            "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,FR;WKST=MO",
+        );
+        assert_round_trip(
+            RepeatRule::EveryNMonths { n: 3, day: 31 },
+            "FREQ=MONTHLY;INTERVAL=3;BYMONTHDAY=-1",
+        );
+        // An interval of one is the plain rule again.
+        assert_eq!(
+            import("FREQ=DAILY;INTERVAL=1").rule,
+            RepeatRule::Daily {
+                workday_only: false
+            }
+        );
+    }
+
+    #[test]
+    fn export_normalizes_out_of_range_values_like_the_scheduler() {
+        assert_eq!(
+            repeat_rule_to_rrule(&RepeatRule::Weekly {
+                days: vec![5, 0, 42, 1, 5]
+            })
+            .unwrap(),
+            "FREQ=WEEKLY;BYDAY=MO,FR"
+        );
+        assert_eq!(
+            repeat_rule_to_rrule(&RepeatRule::Weekly { days: vec![9] }).unwrap(),
+            "FREQ=WEEKLY"
+        );
+        assert_eq!(
+            repeat_rule_to_rrule(&RepeatRule::Monthly { day: 0 }).unwrap(),
+            "FREQ=MONTHLY;BYMONTHDAY=1"
+        );
+        assert_eq!(
+            repeat_rule_to_rrule(&RepeatRule::Yearly { month: 4, day: 31 }).unwrap(),
+            "FREQ=YEARLY;BYMONTH=4;BYMONTHDAY=30"
+        );
+        assert_eq!(
+            repeat_rule_to_rrule(&RepeatRule::Yearly { month: 13, day: 40 }).unwrap(),
+            "FREQ=YEARLY;BYMONTH=12;BYMONTHDAY=31"
+        );
+    }
+
+    #[test]
+    fn byday_import_covers_daily_and_weekly_forms() {
+        assert_eq!(
+            import("RRULE:FREQ=WEEKLY;BYDAY=FR,MO,MO").rule,
+            RepeatRule::Weekly { days: vec![1, 5] }
+        );
+        // WEEKLY on workdays stays weekly; DAILY on a subset becomes weekly on those days.
+        assert_eq!(
+            import("FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR").rule,
+            RepeatRule::Weekly {
+                days: vec![1, 2, 3, 4, 5]
+            }
+        );
+        assert_eq!(
+            import("freq=daily;byday=sa,su").rule,
+            RepeatRule::Weekly { days: vec![6, 7] }
+        );
+        assert!(rrule_to_repeat_rule("FREQ=WEEKLY;BYDAY=1MO", start())
+            .unwrap_err()
+            .contains("BYDAY"));
+        assert!(rrule_to_repeat_rule("FREQ=MONTHLY;BYDAY=-1FR", start()).is_err());
+        assert!(rrule_to_repeat_rule("FREQ=YEARLY;BYDAY=MO", start()).is_err());
+    }
+
+    #[test]
+    fn bymonthday_import_reports_skipped_months_and_extra_days() {
+        let skipping = import("FREQ=MONTHLY;BYMONTHDAY=31");
+        assert_eq!(skipping.rule, RepeatRule::Monthly { day: 31 });
+        assert_eq!(skipping.lossy.len(), 1);
+        assert!(import("FREQ=MONTHLY;BYMONTHDAY=28").lossy.is_empty());
+        let implicit = rrule_to_repeat_rule(
+            "FREQ=MONTHLY",
+            NaiveDate::from_ymd_opt(2026, 1, 30).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(implicit.rule, RepeatRule::Monthly { day: 30 });
+        assert_eq!(implicit.lossy.len(), 1);
+
+        let several = import("FREQ=MONTHLY;BYMONTHDAY=1,15");
+        assert_eq!(several.rule, RepeatRule::Monthly { day: 1 });
+        assert_eq!(several.lossy.len(), 1);
+
+        let leap = import("FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=29");
+        assert_eq!(leap.rule, RepeatRule::Yearly { month: 2, day: 29 });
+        assert_eq!(leap.lossy.len(), 1);
+        let last = import("FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=-1");
+        assert_eq!(last.rule, RepeatRule::Yearly { month: 2, day: 29 });
+        assert!(last.lossy.is_empty());
+
+        assert!(rrule_to_repeat_rule("FREQ=MONTHLY;BYMONTHDAY=-3", start()).is_err());
+        assert!(rrule_to_repeat_rule("FREQ=MONTHLY;BYMONTHDAY=x", start()).is_err());
+        assert!(rrule_to_repeat_rule("FREQ=YEARLY;BYMONTH=13", start()).is_err());
+    }
+
+    #[test]
+    fn count_and_until_become_end_conditions() {
+        let counted = import("FREQ=DAILY;COUNT=5");
+        assert_eq!(
+            counted.rule,
+            RepeatRule::Daily {
+                workday_only: false
+            }
+        );
+        assert_eq!((counted.count, counted.until), (Some(5), None));
+        assert!(counted.lossy.is_empty());
+
+        let until = import("FREQ=WEEKLY;BYDAY=TU;UNTIL=20261231T235959Z");
+        assert_eq!(until.rule, RepeatRule::Weekly { days: vec![2] });
+        assert_eq!(until.until, Some(1_798_761_599));
+        assert!(until.lossy.is_empty());
+        // A date-only UNTIL keeps that whole local day.
+        let day = import("FREQ=DAILY;UNTIL=20261231").until.unwrap();
+        assert_eq!(
+            Local
+                .timestamp_opt(day, 0)
+                .unwrap()
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            "2026-12-31 23:59"
+        );
+        assert!(rrule_to_repeat_rule("FREQ=DAILY;UNTIL=soon", start()).is_err());
+    }
+
+    #[test]
+    fn task_end_conditions_round_trip() {
+        let due = Local
+            .with_ymd_and_hms(2026, 3, 18, 9, 0, 0)
+            .unwrap()
+            .timestamp();
+        let mut task = test_support::task("t").due(Some(due));
+        task.repeat = RepeatRule::Weekly { days: vec![3] };
+        assert_eq!(task_rrule(&task, true).unwrap(), "FREQ=WEEKLY;BYDAY=WE");
+
+        // The third of five occurrences has three left, this one included.
+        task.end_after_occurrences = Some(5);
+        task.occurrence_index = 2;
+        let counted = task_rrule(&task, true).unwrap();
+        assert_eq!(counted, "FREQ=WEEKLY;BYDAY=WE;COUNT=3");
+        assert_eq!(import(&counted).count, Some(3));
+
+        task.end_after_occurrences = None;
+        task.end_by_date = Some(due + 30 * 86_400);
+        for floating in [true, false] {
+            let dated = task_rrule(&task, floating).unwrap();
+            assert_eq!(dated.ends_with('Z'), !floating, "{dated}");
+            let back = import(&dated);
+            assert_eq!(
+                (back.rule, back.until),
+                (task.repeat.clone(), task.end_by_date)
+            );
+            assert!(back.lossy.is_empty());
+        }
+
+        // RFC 5545 allows one end; the count wins.
+        task.end_after_occurrences = Some(1);
+        assert_eq!(
+            task_rrule(&task, false).unwrap(),
+            "FREQ=WEEKLY;BYDAY=WE;COUNT=1"
+        );
+        task.repeat = RepeatRule::None;
+        assert_eq!(task_rrule(&task, false), None);
+    }
+
+    #[test]
+    fn unsupported_intervals_are_reported_as_lossy() {
+        assert!(import("FREQ=DAILY;INTERVAL=1;WKST=SU").lossy.is_empty());
+        let every_other = import("FREQ=WEEKLY;INTERVAL=2;COUNT=10;BYHOUR=9");
+        assert_eq!(
+            every_other.rule,
+            RepeatRule::EveryNWeeks { n: 2, days: vec![] }
+        );
+        assert_eq!(every_other.count, Some(10));
+        assert_eq!(every_other.lossy.len(), 1);
+        // Intervals the model has no rule for, and week starts other than ours.
+        assert_eq!(import("FREQ=YEARLY;INTERVAL=2").lossy.len(), 1);
+        assert_eq!(import("FREQ=DAILY;INTERVAL=2;BYDAY=MO,WE").lossy.len(), 1);
+        assert_eq!(
+            import("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,SU;WKST=SU")
+                .lossy
+                .len(),
+            1
+        );
+
+        assert!(rrule_to_repeat_rule("FREQ=DAILY;COUNT=many", start()).is_err());
+        assert!(rrule_to_repeat_rule("FREQ=DAILY;INTERVAL=-1", start()).is_err());
+    }
+
+    #[test]
+    fn implicit_parts_come_from_dtstart_and_bad_rules_are_rejected() {
+        assert_eq!(import("FREQ=MONTHLY").rule, RepeatRule::Monthly { day: 18 });
+        assert_eq!(
+            import("FREQ=YEARLY").rule,
+            RepeatRule::Yearly { month: 3, day: 18 }
+        );
+        assert_eq!(
+            import("FREQ=YEARLY;BYMONTHDAY=1").rule,
+            RepeatRule::Yearly { month: 3, day: 1 }
+        );
+
+        assert!(rrule_to_repeat_rule("", start()).is_err());
+        assert!(rrule_to_repeat_rule("BYDAY=MO", start()).is_err());
+        assert!(rrule_to_repeat_rule("FREQ=HOURLY", start()).is_err());
+        assert!(rrule_to_repeat_rule("FREQ", start()).is_err());
+    }
+}
@@ -1,18 +1,25 @@
+use std::collections::HashSet;
+
+use chrono::{Local, TimeZone};
+
+use crate::calendar::reminders_held;
+use crate::dependencies::blocked_task_ids;
 use crate::models::{ReminderKind, Task};
 use crate::state::AppState;
 
 #[cfg(all(feature = "app", not(test)))]
-use crate::events::{StatePayload, EVENT_REMINDER, EVENT_STATE_UPDATED};
+use crate::clock::Clock;
 #[cfg(all(feature = "app", not(test)))]
-use crate::storage::Storage;
+use crate::events::{
+    EVENT_DAILY_SUMMARY, EVENT_FOCUS_TICK, EVENT_REMINDER, EVENT_REMINDER_DIGEST,
+    EVENT_REMINDER_ESCALATED, EVENT_TASK_STARTED,
+};
 #[cfg(all(feature = "app", not(test)))]
-use crate::windows::show_reminder_window;
-#[cfg(all(feature = "app", not(test)))]
-use chrono::Utc;
+use crate::windows::{any_window_visible, show_reminder_window};
 #[cfg(all(feature = "app", not(test)))]
 use std::time::Duration;
 #[cfg(all(feature = "app", not(test)))]
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, Emitter};
 
 #[cfg(all(feature = "app", not(test)))]
 pub fn start_scheduler(app: AppHandle, state: AppState) {
@@ -26,63 +33,327 @@ pub fn start_scheduler(app: AppHandle, state: AppState) {
 
         let mut interval = tokio::time::interval(Duration::from_secs(1));
         interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        let mut markdown_sync_seen = None;
+        let mut caldav_attempted_at = None;
+        // Tasks that started while the app was closed are already active on load.
+        let mut started_checked_at = state.clock().timestamp();
         loop {
             interval.tick().await;
-            let now = Utc::now().timestamp();
-            let due_tasks = collect_due_tasks(&state, now);
+            // One correlation id per tick, shared by the reminder, persist and email logs below.
+            let _span = crate::logging::LogSpan::background("scheduler");
+            let now = state.clock().timestamp();
+            let mut due_tasks = collect_due_tasks(&state, now);
             if !due_tasks.is_empty() {
+                let escalated = escalate_ignored_reminders(&state, &mut due_tasks);
+                if !escalated.is_empty() {
+                    log::info!(
+                        "scheduler: reminder escalated count={} ids={}",
+                        escalated.len(),
+                        format_task_ids(&escalated, 10)
+                    );
+                    if let Err(err) = app.emit(EVENT_REMINDER_ESCALATED, escalated) {
+                        log::warn!("scheduler: failed to emit escalation event: {err}");
+                    }
+                }
                 let has_forced = due_tasks
                     .iter()
                     .any(|task| task.reminder.kind == ReminderKind::Forced);
+                let grouped = crate::digest::should_group(
+                    due_tasks.len(),
+                    state.settings().reminder_digest_threshold,
+                );
                 log::info!(
-                    "scheduler: reminder fired now={} count={} forced={} ids={}",
+                    "scheduler: reminder fired now={} count={} forced={} grouped={} ids={}",
                     now,
                     due_tasks.len(),
                     has_forced,
+                    grouped,
                     format_task_ids(&due_tasks, 10)
                 );
                 for task in &due_tasks {
                     state.mark_reminder_fired(task, now);
                 }
-                persist_reminder_state(&app, &state);
-                if let Err(err) = app.emit(EVENT_REMINDER, due_tasks) {
+                persist_tick_changes(&app, &state);
+                record_reminder_audit(&app, &due_tasks, now, grouped);
+                crate::outbound::spawn_dispatch(
+                    &app,
+                    crate::outbound::OutboundEvent::ReminderFired(due_tasks.clone()),
+                );
+                let emitted = if grouped {
+                    app.emit(
+                        EVENT_REMINDER_DIGEST,
+                        crate::digest::build_digest(due_tasks, now),
+                    )
+                } else {
+                    app.emit(EVENT_REMINDER, due_tasks)
+                };
+                if let Err(err) = emitted {
                     log::warn!("scheduler: failed to emit reminder event: {err}");
                 }
                 if has_forced {
                     show_reminder_window(&app);
                 }
             }
+            emit_started_tasks(&app, &state, &mut started_checked_at, now);
+            spawn_ahead_repeats(&app, &state);
+            dispatch_email_fallback(&app, &state, now);
+            run_scheduled_maintenance(&app, &state, now);
+            run_carry_over(&app, &state);
+            run_daily_summary(&app, &state);
+            crate::commands::run_overdue_automations(&app, &state);
+            tick_focus(&app, &state, now);
+            if crate::trash::purge_expired(&state, now) > 0 {
+                persist_tick_changes(&app, &state);
+            }
+            poll_markdown_sync(&app, &state, &mut markdown_sync_seen);
+            start_caldav_sync(&app, &state, now, &mut caldav_attempted_at);
+        }
+    });
+}
+
+#[cfg(all(feature = "app", not(test)))]
+fn record_reminder_audit(app: &AppHandle, tasks: &[Task], now: i64, grouped: bool) {
+    use crate::digest::{append_reminder_audit, audit_entries};
+
+    let root = match crate::profiles::app_profile_root(app) {
+        Ok(path) => path,
+        Err(err) => {
+            log::error!("scheduler: app_data_dir failed: {err}");
+            return;
+        }
+    };
+    if let Err(err) = append_reminder_audit(&root, &audit_entries(tasks, now, grouped)) {
+        log::warn!("scheduler: failed to write reminder audit: {err}");
+    }
+}
+
+#[cfg(all(feature = "app", not(test)))]
+fn spawn_ahead_repeats(app: &AppHandle, state: &AppState) {
+    let spawned = state.materialize_spawn_ahead(state.clock().now());
+    if spawned == 0 {
+        return;
+    }
+    log::info!("scheduler: spawned ahead repeat instances count={spawned}");
+    persist_tick_changes(app, state);
+}
+
+#[cfg(all(feature = "app", not(test)))]
+fn dispatch_email_fallback(app: &AppHandle, state: &AppState, now: i64) {
+    use crate::email::{
+        append_delivery_log, build_reminder_email, email_reminder_candidates, send_email,
+        smtp_config, DeliveryKind, DeliveryLogEntry,
+    };
+
+    let settings = state.settings();
+    if !settings.email_reminder_enabled {
+        return;
+    }
+    let headless = !any_window_visible(app);
+    let tasks = email_reminder_candidates(&state.shared_tasks(), &settings, now, headless);
+    if tasks.is_empty() {
+        return;
+    }
+    let root = match crate::profiles::app_profile_root(app) {
+        Ok(path) => path,
+        Err(err) => {
+            log::error!("scheduler: app_data_dir failed: {err}");
+            return;
+        }
+    };
+
+    // Mark first so a slow or failing SMTP server never causes duplicate emails for one firing.
+    let task_ids: Vec<String> = tasks.iter().map(|task| task.id.clone()).collect();
+    state.mark_reminder_emailed(&task_ids);
+    persist_tick_changes(app, state);
+
+    let dates = crate::calendar::DateFormatter::new(
+        &settings,
+        &sys_locale::get_locale().unwrap_or_default(),
+        state.clock().now_local(),
+    );
+    let message = build_reminder_email(&settings, &tasks, &dates);
+    log::info!(
+        "scheduler: email fallback now={} headless={} count={} ids={}",
+        now,
+        headless,
+        tasks.len(),
+        format_task_ids(&tasks, 10)
+    );
+    tauri::async_runtime::spawn(async move {
+        let password = crate::secrets::get_secret(&root, crate::secrets::SMTP_PASSWORD)
+            .unwrap_or_else(|err| {
+                log::warn!("scheduler: failed to read smtp password: {err}");
+                None
+            });
+        let result = match smtp_config(&settings, password) {
+            Ok(config) => send_email(&config, &message).await,
+            Err(err) => Err(err),
+        };
+        if let Err(err) = &result {
+            log::warn!("scheduler: email fallback failed err={err}");
+        }
+        let entry = DeliveryLogEntry {
+            at: now,
+            kind: DeliveryKind::Reminder,
+            to: message.to.clone(),
+            subject: message.subject.clone(),
+            task_ids,
+            ok: result.is_ok(),
+            error: result.err(),
+        };
+        if let Err(err) = append_delivery_log(&root, &entry) {
+            log::warn!("scheduler: failed to write email delivery log: {err}");
         }
     });
 }
 
 #[cfg(all(feature = "app", not(test)))]
-fn persist_reminder_state(app: &AppHandle, state: &AppState) {
-    let root = match app.path().app_data_dir() {
+fn run_scheduled_maintenance(app: &AppHandle, state: &AppState, now: i64) {
+    use crate::maintenance::{maintenance_due, run_maintenance};
+
+    if !maintenance_due(
+        state.settings().last_maintenance_at,
+        state.clock().now_local(),
+    ) {
+        return;
+    }
+    let root = match crate::profiles::app_profile_root(app) {
+        Ok(path) => path,
+        Err(err) => {
+            log::error!("scheduler: app_data_dir failed: {err}");
+            return;
+        }
+    };
+    log::info!("scheduler: nightly maintenance start now={now}");
+    run_maintenance(&root, state, now);
+    persist_tick_changes(app, state);
+}
+
+#[cfg(all(feature = "app", not(test)))]
+fn run_carry_over(app: &AppHandle, state: &AppState) {
+    use crate::carry_over::{append_carry_over_log, carry_over_due};
+
+    let settings = state.settings();
+    let now = state.clock().now_local();
+    if !settings.carry_over_enabled || !carry_over_due(settings.last_carry_over_at, now) {
+        return;
+    }
+    let root = match crate::profiles::app_profile_root(app) {
         Ok(path) => path,
         Err(err) => {
             log::error!("scheduler: app_data_dir failed: {err}");
             return;
         }
     };
-    let storage = Storage::new(root);
-    if let Err(err) = storage.ensure_dirs() {
-        log::error!("scheduler: ensure_dirs failed: {err}");
+    let entries = state.carry_over_tasks(now);
+    log::info!("scheduler: carry-over done count={}", entries.len());
+    if let Err(err) = append_carry_over_log(&root, &entries) {
+        log::warn!("scheduler: failed to write carry-over log: {err}");
+    }
+    persist_tick_changes(app, state);
+}
+
+/// The frontend turns the event into a notification; an empty day is recorded but not sent.
+#[cfg(all(feature = "app", not(test)))]
+fn run_daily_summary(app: &AppHandle, state: &AppState) {
+    let Some(summary) = crate::daily_summary::take_due_summary(state, state.clock().now_local())
+    else {
+        return;
+    };
+    persist_tick_changes(app, state);
+    if summary.is_empty() {
+        return;
+    }
+    if let Err(err) = app.emit(EVENT_DAILY_SUMMARY, summary) {
+        log::warn!("scheduler: failed to emit daily_summary: {err}");
+    }
+}
+
+#[cfg(all(feature = "app", not(test)))]
+fn emit_started_tasks(app: &AppHandle, state: &AppState, checked_at: &mut i64, now: i64) {
+    if now <= *checked_at {
+        return;
+    }
+    let started = collect_started_tasks(state, *checked_at, now);
+    *checked_at = now;
+    if started.is_empty() {
         return;
     }
-    if let Err(err) = storage.save_tasks(&state.tasks_file(), false) {
-        log::error!("scheduler: save_tasks failed: {err}");
+    log::info!(
+        "scheduler: tasks started now={} count={} ids={}",
+        now,
+        started.len(),
+        format_task_ids(&started, 10)
+    );
+    if let Err(err) = app.emit(EVENT_TASK_STARTED, started) {
+        log::warn!("scheduler: failed to emit task_started: {err}");
+    }
+}
+
+#[cfg(all(feature = "app", not(test)))]
+fn tick_focus(app: &AppHandle, state: &AppState, now: i64) {
+    use crate::focus::FocusTick;
+
+    // Release the timer before the finish hook reads state and writes the log.
+    let tick = state.focus().tick(now);
+    match tick {
+        None => {}
+        Some(FocusTick::Running(status)) => {
+            if let Err(err) = app.emit(EVENT_FOCUS_TICK, status) {
+                log::warn!("scheduler: failed to emit focus_tick: {err}");
+            }
+        }
+        Some(FocusTick::Finished(session)) => {
+            crate::commands::finish_focus_session(app, state, session);
+        }
+    }
+}
+
+/// Only a changed mtime triggers a sync; the first poll after startup always does, so edits
+/// made while the app was closed are picked up.
+#[cfg(all(feature = "app", not(test)))]
+fn poll_markdown_sync(app: &AppHandle, state: &AppState, seen: &mut Option<std::time::SystemTime>) {
+    if !state.settings().markdown_sync_enabled {
         return;
     }
-    let payload = StatePayload {
-        tasks: state.tasks(),
-        projects: state.projects(),
-        settings: state.settings(),
+    let root = match crate::profiles::app_profile_root(app) {
+        Ok(path) => path,
+        Err(err) => {
+            log::error!("scheduler: app_data_dir failed: {err}");
+            return;
+        }
     };
-    if let Err(err) = app.emit(EVENT_STATE_UPDATED, payload) {
-        log::warn!("scheduler: failed to emit state_updated: {err}");
+    let modified = crate::md_sync::sync_file_modified(&root);
+    if modified.is_none() || modified == *seen {
+        return;
     }
-    log::debug!("scheduler: persisted reminder state");
+    *seen = modified;
+    log::info!("scheduler: markdown sync file changed");
+    crate::commands::run_markdown_sync(app, state);
+}
+
+/// Runs in the background; a manual `sync_now` still in progress makes this attempt a no-op.
+#[cfg(all(feature = "app", not(test)))]
+fn start_caldav_sync(app: &AppHandle, state: &AppState, now: i64, attempted_at: &mut Option<i64>) {
+    if !crate::sync::sync_due(&state.settings(), *attempted_at, now) {
+        return;
+    }
+    *attempted_at = Some(now);
+    let app = app.clone();
+    let state = state.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = crate::commands::run_caldav_sync(&app, &state).await;
+        if let Some(error) = result.error {
+            log::warn!("scheduler: caldav sync failed err={error}");
+        }
+    });
+}
+
+/// Never writes storage directly; see `commands::persist_scheduler_changes`.
+#[cfg(all(feature = "app", not(test)))]
+fn persist_tick_changes(app: &AppHandle, state: &AppState) {
+    crate::commands::persist_scheduler_changes(app, state);
+    log::debug!("scheduler: persisted tick changes");
 }
 
 #[cfg(all(feature = "app", not(test)))]
@@ -100,14 +371,74 @@ fn format_task_ids(tasks: &[Task], limit: usize) -> String {
     out
 }
 
+/// Open tasks whose `start_at` falls in `(since, now]`.
+fn collect_started_tasks(state: &AppState, since: i64, now: i64) -> Vec<Task> {
+    let mut started: Vec<Task> = state
+        .shared_tasks()
+        .iter()
+        .filter(|task| !task.completed)
+        .filter(|task| {
+            task.start_at
+                .is_some_and(|start| since < start && start <= now)
+        })
+        .cloned()
+        .collect();
+    started.sort_by_key(|task| (task.start_at, task.sort_order));
+    started
+}
+
+/// Raises Normal reminders in `due` that were ignored `escalate_after` times (the task's own
+/// value, else `settings.reminder_escalate_after`) to Forced, here and in the state, and
+/// returns them.
+fn escalate_ignored_reminders(state: &AppState, due: &mut [Task]) -> Vec<Task> {
+    let default_after = state.settings().reminder_escalate_after;
+    let mut escalated = Vec::new();
+    for task in due.iter_mut() {
+        let after = task.escalate_after.unwrap_or(default_after);
+        if task.reminder.kind != ReminderKind::Normal
+            || after == 0
+            || task.reminder.ignored_count < after
+        {
+            continue;
+        }
+        task.reminder.kind = ReminderKind::Forced;
+        task.reminder.escalated = true;
+        task.reminder.forced_dismissed = false;
+        state.update_task(task.clone());
+        escalated.push(task.clone());
+    }
+    escalated
+}
+
 fn collect_due_tasks(state: &AppState, now: i64) -> Vec<Task> {
     let mut due = Vec::new();
     let settings = state.settings();
     let repeat_interval = settings.reminder_repeat_interval_sec.max(0);
     let repeat_max_times = settings.reminder_repeat_max_times;
-    let tasks = state.tasks();
-    for task in tasks {
-        if task.completed {
+    let lead_times = &settings.reminder_lead_minutes;
+    // DND / quiet hours hold normal reminders; they come due together once released.
+    let held = Local
+        .timestamp_opt(now, 0)
+        .single()
+        .is_some_and(|local| reminders_held(&settings, local));
+    let muted: HashSet<String> = state
+        .projects()
+        .into_iter()
+        .filter(|project| project.reminders_muted_at(now))
+        .map(|project| project.id)
+        .collect();
+    let tasks = state.shared_tasks();
+    let blocked = blocked_task_ids(&tasks);
+    for task in tasks.iter() {
+        if task.completed || task.someday {
+            continue;
+        }
+        // Reminders that came due while muted fire once the mute ends.
+        if muted.contains(&task.project_id) {
+            continue;
+        }
+        // Likewise for tasks waiting on open dependencies: they fire once unblocked.
+        if blocked.contains(&task.id) {
             continue;
         }
         let reminder = &task.reminder;
@@ -117,16 +448,14 @@ fn collect_due_tasks(state: &AppState, now: i64) -> Vec<Task> {
         if reminder.kind == ReminderKind::Forced && reminder.forced_dismissed {
             continue;
         }
+        if held && reminder.kind == ReminderKind::Normal {
+            continue;
+        }
         // At this point `reminder.kind` is Normal or Forced (None has already been skipped).
-        let default_target = if reminder.kind == ReminderKind::Normal {
-            task.due_at - 10 * 60
-        } else {
-            task.due_at
+        // Each spec is its own target; undated tasks only remind when an explicit time was set.
+        let Some(target_time) = reminder.current_target(task.due_at, lead_times, now) else {
+            continue;
         };
-        let target_time = reminder
-            .snoozed_until
-            .or(reminder.remind_at)
-            .unwrap_or(default_target);
 
         // Repeat reminders are intentionally scoped to Normal reminders.
         // Forced reminders already have a blocking overlay, and repeating the overlay tends to
@@ -165,7 +494,12 @@ fn collect_due_tasks(state: &AppState, now: i64) -> Vec<Task> {
                 target_time
             }
         } else if let Some(last) = reminder.last_fired_at {
-            last.saturating_add(effective_repeat_interval)
+            // A later spec reached since the last firing fires right away.
+            if last < target_time && target_time <= now {
+                target_time
+            } else {
+                last.saturating_add(effective_repeat_interval)
+            }
         } else {
             target_time
         };
@@ -174,13 +508,13 @@ fn collect_due_tasks(state: &AppState, now: i64) -> Vec<Task> {
             due.push(task.clone());
         }
     }
-    due.sort_by_key(|task| (!task.important, task.due_at));
+    due.sort_by_key(|task| (!task.important, task.due_sort_key()));
     due
 }
 
 #[cfg(test)]
 mod tests {
-    use super::collect_due_tasks;
+    use super::{collect_due_tasks, collect_started_tasks, escalate_ignored_reminders};
     use crate::models::{ReminderConfig, ReminderKind, RepeatRule, Task};
     use crate::state::AppState;
 
@@ -195,7 +529,7 @@ mod tests {
             id: id.to_string(),
             project_id: "inbox".to_string(),
             title: format!("task-{id}"),
-            due_at,
+            due_at: Some(due_at),
             important,
             completed,
             completed_at: None,
@@ -209,9 +543,55 @@ mod tests {
             sample_tag: None,
             reminder,
             repeat: RepeatRule::None,
+            waiting_on: None,
+            waiting_since: None,
+            series_id: None,
+            pinned: false,
+            context: None,
+            comments: Vec::new(),
+            progress: Default::default(),
+            spawn_ahead: 0,
+            repeat_from_completion: false,
+            end_after_occurrences: None,
+            end_by_date: None,
+            occurrence_index: 0,
+            timezone: None,
+            escalate_after: None,
+            start_at: None,
+            someday: false,
+            carry_over_count: 0,
+            skip_carry_over: false,
+            color: None,
+            emoji: None,
+            estimate_minutes: None,
+            attachments: Vec::new(),
+            time_entries: Vec::new(),
+            blocked_by: Vec::new(),
         }
     }
 
+    #[test]
+    fn advancing_the_app_clock_makes_reminders_due() {
+        use crate::clock::{AppClock, Clock, FixedClock};
+        use std::sync::Arc;
+
+        let reminder = ReminderConfig {
+            kind: ReminderKind::Normal,
+            remind_at: Some(10_000),
+            ..ReminderConfig::default()
+        };
+        let state = AppState::with_clock(
+            vec![task_with_reminder("a", 20_000, false, false, reminder)],
+            Vec::new(),
+            crate::models::Settings::default(),
+            AppClock::new(Arc::new(FixedClock(1_000))),
+        );
+        assert!(collect_due_tasks(&state, state.clock().timestamp()).is_empty());
+        state.clock().advance(9_000);
+        let due = collect_due_tasks(&state, state.clock().timestamp());
+        assert_eq!(due.len(), 1);
+    }
+
     #[test]
     fn collect_due_tasks_filters_and_sorts_correctly() {
         let now = 1000;
@@ -473,4 +853,288 @@ mod tests {
         let out = collect_due_tasks(&state, now);
         assert!(out.is_empty());
     }
+
+    #[test]
+    fn collect_due_tasks_skips_someday_tasks() {
+        let mut parked = task_with_reminder(
+            "parked",
+            1100,
+            false,
+            false,
+            ReminderConfig {
+                kind: ReminderKind::Normal,
+                ..ReminderConfig::default()
+            },
+        );
+        parked.someday = true;
+        assert!(!parked.is_overdue(2000, 0));
+
+        let state = AppState::new(vec![parked], Vec::new(), crate::models::Settings::default());
+        assert!(collect_due_tasks(&state, 2000).is_empty());
+    }
+
+    #[test]
+    fn collect_due_tasks_holds_back_muted_projects_until_the_mute_ends() {
+        let reminder = ReminderConfig {
+            kind: ReminderKind::Normal,
+            ..ReminderConfig::default()
+        };
+        let mut on_hold = task_with_reminder("held", 1100, false, false, reminder.clone());
+        on_hold.project_id = "hold".to_string();
+        let other = task_with_reminder("other", 1100, false, false, reminder);
+        let project: crate::models::Project =
+            serde_json::from_str(r#"{"id":"hold","name":"On hold"}"#).unwrap();
+        let state = AppState::new(
+            vec![on_hold, other],
+            vec![project],
+            crate::models::Settings::default(),
+        );
+
+        state.set_project_muted("hold", true, Some(5000), 1000);
+        let due = collect_due_tasks(&state, 2000);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].id, "other");
+        // The reminder missed while muted fires once the mute ends.
+        let due = collect_due_tasks(&state, 5000);
+        assert_eq!(due.len(), 2);
+
+        state.set_project_muted("hold", true, None, 1000);
+        assert_eq!(collect_due_tasks(&state, 99_999).len(), 1);
+        state.set_project_muted("hold", false, None, 1000);
+        assert_eq!(collect_due_tasks(&state, 99_999).len(), 2);
+    }
+
+    #[test]
+    fn collect_due_tasks_waits_for_blocking_tasks() {
+        let reminder = ReminderConfig {
+            kind: ReminderKind::Normal,
+            ..ReminderConfig::default()
+        };
+        let mut waiting = task_with_reminder("waiting", 1100, false, false, reminder.clone());
+        waiting.blocked_by = vec!["first".to_string()];
+        let first = task_with_reminder("first", 50_000, false, false, reminder);
+        let state = AppState::new(
+            vec![waiting, first],
+            Vec::new(),
+            crate::models::Settings::default(),
+        );
+        assert!(collect_due_tasks(&state, 2000).is_empty());
+
+        state.complete_task("first").unwrap();
+        let due = collect_due_tasks(&state, 2000);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].id, "waiting");
+    }
+
+    #[test]
+    fn collect_due_tasks_uses_configured_lead_times() {
+        let forced = task_with_reminder(
+            "forced",
+            2000,
+            false,
+            false,
+            ReminderConfig {
+                kind: ReminderKind::Forced,
+                ..ReminderConfig::default()
+            },
+        );
+        let normal = task_with_reminder(
+            "normal",
+            2000,
+            false,
+            false,
+            ReminderConfig {
+                kind: ReminderKind::Normal,
+                ..ReminderConfig::default()
+            },
+        );
+        let mut settings = crate::models::Settings::default();
+        settings.reminder_lead_minutes.forced = 5;
+        settings.reminder_lead_minutes.normal = 30;
+        let state = AppState::new(vec![forced, normal], Vec::new(), settings);
+
+        // Forced fires 5 minutes early (1700), normal 30 minutes early (200).
+        let ids = |now| -> Vec<String> {
+            collect_due_tasks(&state, now)
+                .into_iter()
+                .map(|task| task.id)
+                .collect()
+        };
+        assert!(ids(199).is_empty());
+        assert_eq!(ids(200), vec!["normal"]);
+        assert_eq!(ids(1700), vec!["forced", "normal"]);
+    }
+
+    #[test]
+    fn collect_due_tasks_needs_an_explicit_time_for_undated_tasks() {
+        let reminder = ReminderConfig {
+            kind: ReminderKind::Normal,
+            ..ReminderConfig::default()
+        };
+        let mut undated = task_with_reminder("u", 0, false, false, reminder.clone());
+        undated.due_at = None;
+        let mut explicit = task_with_reminder("e", 0, false, false, reminder);
+        explicit.due_at = None;
+        explicit.reminder.remind_at = Some(1500);
+
+        let state = AppState::new(
+            vec![undated, explicit],
+            Vec::new(),
+            crate::models::Settings::default(),
+        );
+        let ids: Vec<String> = collect_due_tasks(&state, 2000)
+            .into_iter()
+            .map(|task| task.id)
+            .collect();
+        assert_eq!(ids, vec!["e"]);
+    }
+
+    #[test]
+    fn collect_due_tasks_fires_each_reminder_spec_once() {
+        use crate::models::ReminderSpec;
+
+        let reminder = ReminderConfig {
+            kind: ReminderKind::Normal,
+            specs: vec![
+                ReminderSpec { minutes_before: 60 },
+                ReminderSpec { minutes_before: 0 },
+            ],
+            ..ReminderConfig::default()
+        };
+        let task = task_with_reminder("multi", 10_000, false, false, reminder);
+        let mut settings = crate::models::Settings::default();
+        settings.reminder_repeat_interval_sec = 0;
+        let state = AppState::new(vec![task.clone()], Vec::new(), settings);
+
+        assert!(collect_due_tasks(&state, 6_399).is_empty());
+        assert_eq!(collect_due_tasks(&state, 6_400).len(), 1);
+        state.mark_reminder_fired(&task, 6_400);
+        assert!(collect_due_tasks(&state, 9_999).is_empty());
+        assert_eq!(collect_due_tasks(&state, 10_000).len(), 1);
+        state.mark_reminder_fired(&task, 10_000);
+        assert!(collect_due_tasks(&state, 20_000).is_empty());
+    }
+
+    #[test]
+    fn collect_due_tasks_holds_normal_reminders_during_dnd_and_quiet_hours() {
+        use chrono::{Local, TimeZone};
+
+        let normal = ReminderConfig {
+            kind: ReminderKind::Normal,
+            remind_at: Some(0),
+            ..ReminderConfig::default()
+        };
+        let forced = ReminderConfig {
+            kind: ReminderKind::Forced,
+            ..normal.clone()
+        };
+        let mut settings = crate::models::Settings {
+            dnd_enabled: true,
+            reminder_repeat_interval_sec: 0,
+            ..Default::default()
+        };
+        let tasks = vec![
+            task_with_reminder("n1", 1_000, false, false, normal.clone()),
+            task_with_reminder("n2", 2_000, false, false, normal),
+            task_with_reminder("f", 3_000, false, false, forced),
+        ];
+        let night = Local.with_ymd_and_hms(2026, 3, 18, 23, 30, 0).unwrap();
+        let morning = Local.with_ymd_and_hms(2026, 3, 19, 8, 0, 0).unwrap();
+        let ids = |state: &AppState, at: chrono::DateTime<Local>| {
+            collect_due_tasks(state, at.timestamp())
+                .into_iter()
+                .map(|t| t.id)
+                .collect::<Vec<_>>()
+        };
+
+        let state = AppState::new(tasks.clone(), Vec::new(), settings.clone());
+        assert_eq!(ids(&state, morning), vec!["f"]);
+
+        settings.dnd_enabled = false;
+        settings.quiet_hours_enabled = true;
+        let state = AppState::new(tasks, Vec::new(), settings);
+        assert_eq!(ids(&state, night), vec!["f"]);
+        // Released together once the quiet hours end.
+        assert_eq!(ids(&state, morning), vec!["n1", "n2", "f"]);
+    }
+
+    #[test]
+    fn collect_started_tasks_reports_each_start_once() {
+        let mut first =
+            task_with_reminder("first", 50_000, false, false, ReminderConfig::default());
+        first.start_at = Some(2_000);
+        let mut second = first.clone();
+        second.id = "second".to_string();
+        second.start_at = Some(1_000);
+        let mut done = first.clone();
+        done.id = "done".to_string();
+        done.completed = true;
+        let unscheduled =
+            task_with_reminder("plain", 50_000, false, false, ReminderConfig::default());
+        let state = AppState::new(
+            vec![first, second, done, unscheduled],
+            Vec::new(),
+            Default::default(),
+        );
+
+        let ids = |tasks: Vec<Task>| tasks.into_iter().map(|t| t.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(collect_started_tasks(&state, 0, 2_000)),
+            vec!["second", "first"]
+        );
+        assert_eq!(
+            ids(collect_started_tasks(&state, 1_000, 2_000)),
+            vec!["first"]
+        );
+        assert!(collect_started_tasks(&state, 2_000, 3_000).is_empty());
+    }
+
+    #[test]
+    fn ignored_normal_reminders_escalate_to_forced() {
+        let reminder = ReminderConfig {
+            kind: ReminderKind::Normal,
+            remind_at: Some(0),
+            ..ReminderConfig::default()
+        };
+        let settings = crate::models::Settings {
+            reminder_repeat_interval_sec: 60,
+            reminder_repeat_max_times: 0,
+            reminder_escalate_after: 2,
+            ..Default::default()
+        };
+        let mut never = task_with_reminder("never", 1_000, false, false, reminder.clone());
+        never.escalate_after = Some(0);
+        let state = AppState::new(
+            vec![
+                task_with_reminder("a", 1_000, false, false, reminder),
+                never,
+            ],
+            Vec::new(),
+            settings,
+        );
+
+        let fire = |now: i64| {
+            let mut due = collect_due_tasks(&state, now);
+            let escalated = escalate_ignored_reminders(&state, &mut due);
+            for task in &due {
+                state.mark_reminder_fired(task, now);
+            }
+            escalated.into_iter().map(|t| t.id).collect::<Vec<_>>()
+        };
+        assert!(fire(100).is_empty());
+        assert!(fire(160).is_empty());
+        assert_eq!(fire(220), vec!["a"]);
+
+        let tasks = state.tasks();
+        let a = tasks.iter().find(|t| t.id == "a").unwrap();
+        assert_eq!(a.reminder.kind, ReminderKind::Forced);
+        assert!(a.reminder.escalated);
+        let never = tasks.iter().find(|t| t.id == "never").unwrap();
+        assert_eq!(never.reminder.kind, ReminderKind::Normal);
+        assert_eq!(never.reminder.ignored_count, 3);
+        // A forced reminder fires once and then waits for the overlay.
+        assert!(collect_due_tasks(&state, 280)
+            .iter()
+            .all(|t| t.id == "never"));
+    }
 }
@@ -0,0 +1,370 @@
+use crate::models::Task;
+
+pub const DEFAULT_SEARCH_LIMIT: usize = 50;
+pub const MAX_SEARCH_LIMIT: usize = 500;
+
+/// How a query term hit a field, best first: the field starts with the term, the term is a
+/// whole word of the field, or it only appears inside a word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    Substring,
+    Word,
+    Prefix,
+}
+
+impl MatchKind {
+    fn points(self) -> u32 {
+        match self {
+            MatchKind::Prefix => 30,
+            MatchKind::Word => 20,
+            MatchKind::Substring => 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchField {
+    Title,
+    Tag,
+    Step,
+    Notes,
+}
+
+impl SearchField {
+    /// Same match kind, different field: a title hit outranks a tag, step or notes hit.
+    fn weight(self) -> u32 {
+        match self {
+            SearchField::Title => 4,
+            SearchField::Tag => 3,
+            SearchField::Step => 2,
+            SearchField::Notes => 1,
+        }
+    }
+}
+
+/// `[start, end)` in UTF-16 code units of the field text, ready for `String.slice` in the UI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct MatchSpan {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct FieldMatch {
+    pub field: SearchField,
+    /// Position in `tags` / `steps`; `None` for title and notes.
+    pub index: Option<usize>,
+    pub spans: Vec<MatchSpan>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SearchHit {
+    pub task: Task,
+    pub score: u32,
+    /// Best way each query term matched, in query order.
+    pub kinds: Vec<MatchKind>,
+    pub matches: Vec<FieldMatch>,
+}
+
+/// Lowercased chars of `text`, each with the char index it came from (lowercasing may expand
+/// one char into several).
+fn fold(text: &str) -> (Vec<char>, Vec<usize>) {
+    let mut folded = Vec::with_capacity(text.len());
+    let mut origin = Vec::with_capacity(text.len());
+    for (index, c) in text.chars().enumerate() {
+        for lower in c.to_lowercase() {
+            folded.push(lower);
+            origin.push(index);
+        }
+    }
+    (folded, origin)
+}
+
+/// Query terms: whitespace separated, lowercased, `#` dropped so "#work" finds the tag.
+fn terms(query: &str) -> Vec<Vec<char>> {
+    query
+        .split_whitespace()
+        .map(|term| term.strip_prefix('#').unwrap_or(term))
+        .filter(|term| !term.is_empty())
+        .map(|term| fold(term).0)
+        .collect()
+}
+
+struct Occurrence {
+    kind: MatchKind,
+    /// Char range in the original text.
+    start: usize,
+    end: usize,
+}
+
+fn occurrences(folded: &[char], origin: &[usize], term: &[char]) -> Vec<Occurrence> {
+    if term.is_empty() || term.len() > folded.len() {
+        return Vec::new();
+    }
+    let mut found = Vec::new();
+    for start in 0..=folded.len() - term.len() {
+        let end = start + term.len();
+        if folded[start..end] != *term {
+            continue;
+        }
+        let word_start = start == 0 || !folded[start - 1].is_alphanumeric();
+        let word_end = end == folded.len() || !folded[end].is_alphanumeric();
+        let kind = if start == 0 {
+            MatchKind::Prefix
+        } else if word_start && word_end {
+            MatchKind::Word
+        } else {
+            MatchKind::Substring
+        };
+        found.push(Occurrence {
+            kind,
+            start: origin[start],
+            end: origin[end - 1] + 1,
+        });
+    }
+    found
+}
+
+/// Char ranges -> merged UTF-16 spans.
+fn utf16_spans(text: &str, mut ranges: Vec<(usize, usize)>) -> Vec<MatchSpan> {
+    ranges.sort_unstable();
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    let mut offsets = Vec::with_capacity(text.len() + 1);
+    let mut offset = 0;
+    for c in text.chars() {
+        offsets.push(offset);
+        offset += c.len_utf16();
+    }
+    offsets.push(offset);
+    merged
+        .into_iter()
+        .map(|(start, end)| MatchSpan {
+            start: offsets[start],
+            end: offsets[end],
+        })
+        .collect()
+}
+
+fn fields(task: &Task) -> Vec<(SearchField, Option<usize>, &str)> {
+    let mut fields = vec![(SearchField::Title, None, task.title.as_str())];
+    fields.extend(
+        task.tags
+            .iter()
+            .enumerate()
+            .map(|(i, tag)| (SearchField::Tag, Some(i), tag.as_str())),
+    );
+    fields.extend(
+        task.steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| (SearchField::Step, Some(i), step.title.as_str())),
+    );
+    if let Some(notes) = task.notes.as_deref() {
+        fields.push((SearchField::Notes, None, notes));
+    }
+    fields
+}
+
+/// Scores one task; `None` unless every term matches somewhere.
+fn search_task(task: &Task, terms: &[Vec<char>]) -> Option<SearchHit> {
+    let mut best: Vec<Option<(MatchKind, u32)>> = vec![None; terms.len()];
+    let mut matches = Vec::new();
+    for (field, index, text) in fields(task) {
+        let (folded, origin) = fold(text);
+        let mut ranges = Vec::new();
+        for (term_index, term) in terms.iter().enumerate() {
+            for hit in occurrences(&folded, &origin, term) {
+                let points = hit.kind.points() * field.weight();
+                if best[term_index].is_none_or(|(_, current)| points > current) {
+                    best[term_index] = Some((hit.kind, points));
+                }
+                ranges.push((hit.start, hit.end));
+            }
+        }
+        if !ranges.is_empty() {
+            matches.push(FieldMatch {
+                field,
+                index,
+                spans: utf16_spans(text, ranges),
+            });
+        }
+    }
+    let best: Vec<(MatchKind, u32)> = best.into_iter().collect::<Option<_>>()?;
+    Some(SearchHit {
+        task: task.clone(),
+        score: best.iter().map(|(_, points)| points).sum(),
+        kinds: best.iter().map(|(kind, _)| *kind).collect(),
+        matches,
+    })
+}
+
+/// Ranked matches for `query` over titles, tags, step titles and notes. Every term must match;
+/// ties go to open tasks, then the earlier due date, then the most recently updated.
+pub fn search_tasks(
+    tasks: &[Task],
+    query: &str,
+    include_completed: bool,
+    limit: usize,
+) -> Vec<SearchHit> {
+    let terms = terms(query);
+    if terms.is_empty() {
+        return Vec::new();
+    }
+    let mut hits: Vec<SearchHit> = tasks
+        .iter()
+        .filter(|task| include_completed || !task.completed)
+        .filter_map(|task| search_task(task, &terms))
+        .collect();
+    hits.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then(a.task.completed.cmp(&b.task.completed))
+            .then_with(|| match (a.task.due_at, b.task.due_at) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            })
+            .then(b.task.updated_at.cmp(&a.task.updated_at))
+            .then_with(|| a.task.id.cmp(&b.task.id))
+    });
+    hits.truncate(limit);
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_support, Step};
+
+    fn task(id: &str, title: &str) -> Task {
+        test_support::task(id).title(title)
+    }
+
+    fn ids(hits: &[SearchHit]) -> Vec<&str> {
+        hits.iter().map(|hit| hit.task.id.as_str()).collect()
+    }
+
+    #[test]
+    fn ranks_prefix_over_word_over_substring() {
+        let tasks = vec![
+            task("substring", "Update reports"),
+            task("word", "Send the report"),
+            task("prefix", "Report to Anna"),
+        ];
+        let hits = search_tasks(&tasks, "report", false, 10);
+        assert_eq!(ids(&hits), vec!["prefix", "word", "substring"]);
+        assert_eq!(hits[0].kinds, vec![MatchKind::Prefix]);
+        assert_eq!(hits[1].kinds, vec![MatchKind::Word]);
+        assert_eq!(hits[2].kinds, vec![MatchKind::Substring]);
+        assert_eq!(
+            hits[1].matches[0].spans,
+            vec![MatchSpan { start: 9, end: 15 }]
+        );
+    }
+
+    #[test]
+    fn searches_tags_steps_and_notes_and_requires_every_term() {
+        let mut a = task("a", "Groceries");
+        a.tags = vec!["home".to_string()];
+        a.steps = vec![Step {
+            id: "s".to_string(),
+            title: "Buy milk".to_string(),
+            completed: false,
+            created_at: 1,
+            completed_at: None,
+            parent_id: None,
+        }];
+        a.notes = Some("Oat MILK if possible".to_string());
+        let b = task("b", "Milk the budget");
+        let tasks = vec![a, b];
+
+        let hits = search_tasks(&tasks, "#home milk", false, 10);
+        assert_eq!(ids(&hits), vec!["a"]);
+        let fields: Vec<_> = hits[0]
+            .matches
+            .iter()
+            .map(|m| (m.field, m.index, m.spans.clone()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                (
+                    SearchField::Tag,
+                    Some(0),
+                    vec![MatchSpan { start: 0, end: 4 }]
+                ),
+                (
+                    SearchField::Step,
+                    Some(0),
+                    vec![MatchSpan { start: 4, end: 8 }]
+                ),
+                (
+                    SearchField::Notes,
+                    None,
+                    vec![MatchSpan { start: 4, end: 8 }]
+                ),
+            ]
+        );
+
+        // A title prefix beats the same word in a step.
+        assert_eq!(
+            ids(&search_tasks(&tasks, "milk", false, 10)),
+            vec!["b", "a"]
+        );
+        assert!(search_tasks(&tasks, "   ", false, 10).is_empty());
+        assert!(search_tasks(&tasks, "#", false, 10).is_empty());
+    }
+
+    #[test]
+    fn spans_are_utf16_offsets_and_merge_overlaps() {
+        let tasks = vec![task("a", "📌 写周报 weekly report")];
+        let hits = search_tasks(&tasks, "周报 week weekly", false, 10);
+        // The pin is two UTF-16 units; "week" and "weekly" overlap into one span.
+        assert_eq!(
+            hits[0].matches[0].spans,
+            vec![
+                MatchSpan { start: 4, end: 6 },
+                MatchSpan { start: 7, end: 13 }
+            ]
+        );
+        let hits = search_tasks(&[task("u", "ÜBER ärger")], "über ÄRGER", false, 10);
+        assert_eq!(
+            hits[0].matches[0].spans,
+            vec![
+                MatchSpan { start: 0, end: 4 },
+                MatchSpan { start: 5, end: 10 }
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_completed_unless_asked_and_breaks_ties_by_state_then_due() {
+        let mut done = task("done", "Call mom");
+        done.completed = true;
+        let mut later = task("later", "Call bank");
+        later.due_at = Some(2_000);
+        let mut sooner = task("sooner", "Call dentist");
+        sooner.due_at = Some(1_000);
+        let undated = task("undated", "Call plumber");
+        let tasks = vec![done, later, sooner, undated];
+
+        assert_eq!(
+            ids(&search_tasks(&tasks, "call", false, 10)),
+            vec!["sooner", "later", "undated"]
+        );
+        assert_eq!(
+            ids(&search_tasks(&tasks, "call", true, 2)),
+            vec!["sooner", "later"]
+        );
+        assert_eq!(ids(&search_tasks(&tasks, "mom", true, 10)), vec!["done"]);
+    }
+}
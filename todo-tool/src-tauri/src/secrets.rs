@@ -0,0 +1,259 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::ai::providers;
+use crate::models::{AiProviderKind, Settings};
+use crate::storage::{Storage, StorageError};
+
+/// Secret names for the AI providers' API keys (see `ai::providers`).
+pub const DEEPSEEK_API_KEY: &str = "deepseek_api_key";
+pub const OPENAI_API_KEY: &str = "openai_api_key";
+pub const ANTHROPIC_API_KEY: &str = "anthropic_api_key";
+/// Secret name for the SMTP account password used by email reminders.
+pub const SMTP_PASSWORD: &str = "smtp_password";
+/// Secret name for the MQTT broker password.
+pub const MQTT_PASSWORD: &str = "mqtt_password";
+/// Secret name for the CalDAV account password.
+pub const CALDAV_PASSWORD: &str = "caldav_password";
+/// Every name the `set_secret`/`clear_secret` commands accept.
+pub const SECRET_NAMES: [&str; 6] = [
+    DEEPSEEK_API_KEY,
+    OPENAI_API_KEY,
+    ANTHROPIC_API_KEY,
+    SMTP_PASSWORD,
+    MQTT_PASSWORD,
+    CALDAV_PASSWORD,
+];
+
+/// Not a secret: kept in `secrets.json` to name this data set's keychain entries, so relocated
+/// data directories keep their credentials and profiles never share them.
+const KEYCHAIN_ID: &str = "keychain_id";
+
+/// The OS keychain (macOS Keychain, Windows Credential Manager, Secret Service on Linux).
+#[cfg(all(
+    feature = "app",
+    not(test),
+    not(any(target_os = "android", target_os = "ios"))
+))]
+mod keychain {
+    const SERVICE: &str = "MustDo";
+
+    fn entry(account: &str) -> Result<keyring::Entry, String> {
+        keyring::Entry::new(SERVICE, account).map_err(|err| err.to_string())
+    }
+
+    pub fn get(account: &str) -> Result<Option<String>, String> {
+        match entry(account)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+
+    pub fn set(account: &str, value: &str) -> Result<(), String> {
+        entry(account)?
+            .set_password(value)
+            .map_err(|err| err.to_string())
+    }
+
+    pub fn delete(account: &str) -> Result<(), String> {
+        match entry(account)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(err) => Err(err.to_string()),
+        }
+    }
+}
+
+/// Builds without a keychain (tests, mobile) keep everything in `secrets.json`.
+#[cfg(not(all(
+    feature = "app",
+    not(test),
+    not(any(target_os = "android", target_os = "ios"))
+)))]
+mod keychain {
+    const UNAVAILABLE: &str = "no keychain in this build";
+
+    pub fn get(_account: &str) -> Result<Option<String>, String> {
+        Err(UNAVAILABLE.to_string())
+    }
+
+    pub fn set(_account: &str, _value: &str) -> Result<(), String> {
+        Err(UNAVAILABLE.to_string())
+    }
+
+    pub fn delete(_account: &str) -> Result<(), String> {
+        Err(UNAVAILABLE.to_string())
+    }
+}
+
+fn account(keychain_id: &str, name: &str) -> String {
+    format!("{keychain_id}/{name}")
+}
+
+fn new_keychain_id() -> String {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default();
+    format!("{nanos:x}-{:x}", std::process::id())
+}
+
+/// Writes `value` to the keychain, recording the data set's keychain id in `secrets` on first
+/// use. False when there is no usable keychain, so the value has to stay in `secrets.json`.
+fn store_in_keychain(secrets: &mut BTreeMap<String, String>, name: &str, value: &str) -> bool {
+    let id = secrets
+        .get(KEYCHAIN_ID)
+        .cloned()
+        .unwrap_or_else(new_keychain_id);
+    match keychain::set(&account(&id, name), value) {
+        Ok(()) => {
+            secrets.insert(KEYCHAIN_ID.to_string(), id);
+            true
+        }
+        Err(err) => {
+            log::warn!("secrets: keychain unavailable, using secrets.json name={name} err={err}");
+            false
+        }
+    }
+}
+
+/// Reads the OS keychain first and falls back to `secrets.json`, which holds credentials on
+/// systems without a keychain and ones stored before the keychain was used. Either way they never
+/// end up in settings.json, exports or backups.
+pub fn get_secret(root: &Path, name: &str) -> Result<Option<String>, StorageError> {
+    let secrets = Storage::new(root.to_path_buf()).load_secrets()?;
+    if let Some(id) = secrets.get(KEYCHAIN_ID) {
+        match keychain::get(&account(id, name)) {
+            Ok(Some(value)) if !value.is_empty() => return Ok(Some(value)),
+            Ok(_) => {}
+            Err(err) => log::warn!("secrets: keychain read failed name={name} err={err}"),
+        }
+    }
+    Ok(secrets.get(name).filter(|value| !value.is_empty()).cloned())
+}
+
+/// Stores `value` under `name` (in the keychain when there is one); an empty value removes it.
+pub fn set_secret(root: &Path, name: &str, value: &str) -> Result<(), StorageError> {
+    let storage = Storage::new(root.to_path_buf());
+    let mut secrets = storage.load_secrets()?;
+    if value.is_empty() {
+        if let Some(id) = secrets.get(KEYCHAIN_ID) {
+            if let Err(err) = keychain::delete(&account(id, name)) {
+                log::warn!("secrets: keychain delete failed name={name} err={err}");
+            }
+        }
+        secrets.remove(name);
+    } else if store_in_keychain(&mut secrets, name, value) {
+        secrets.remove(name);
+    } else {
+        secrets.insert(name.to_string(), value.to_string());
+    }
+    storage.save_secrets(&secrets)?;
+    log::info!(
+        "secrets: updated name={} stored={}",
+        name,
+        !value.is_empty()
+    );
+    Ok(())
+}
+
+/// Whether `provider` can be called: its API key is stored, or it needs none.
+pub fn ai_api_key_stored(root: &Path, provider: AiProviderKind) -> Result<bool, StorageError> {
+    match providers::provider(provider).api_key_secret() {
+        Some(name) => Ok(get_secret(root, name)?.is_some()),
+        None => Ok(true),
+    }
+}
+
+/// First-boot migration: moves a plaintext `deepseek_api_key` out of settings.json and any
+/// `secrets.json` entries into the keychain, then refreshes `ai_api_key_set`. True when
+/// `settings` changed and needs saving.
+pub fn migrate_plaintext_secrets(
+    root: &Path,
+    settings: &mut Settings,
+) -> Result<bool, StorageError> {
+    let mut changed = false;
+    let legacy_key = std::mem::take(&mut settings.deepseek_api_key);
+    let legacy_key = legacy_key.trim();
+    if !legacy_key.is_empty() {
+        set_secret(root, DEEPSEEK_API_KEY, legacy_key)?;
+        log::info!("secrets: migrated deepseek_api_key out of settings.json");
+        changed = true;
+    }
+
+    let storage = Storage::new(root.to_path_buf());
+    let mut secrets = storage.load_secrets()?;
+    let before = secrets.clone();
+    for name in SECRET_NAMES {
+        let Some(value) = secrets.get(name).cloned() else {
+            continue;
+        };
+        if !store_in_keychain(&mut secrets, name, &value) {
+            break;
+        }
+        secrets.remove(name);
+        log::info!("secrets: migrated name={name} into the keychain");
+    }
+    if secrets != before {
+        storage.save_secrets(&secrets)?;
+    }
+
+    let key_set = ai_api_key_stored(root, settings.ai_provider)?;
+    if settings.ai_api_key_set != key_set {
+        settings.ai_api_key_set = key_set;
+        changed = true;
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_get_and_clear_secret() {
+        let root = tempfile::tempdir().unwrap();
+        assert_eq!(get_secret(root.path(), SMTP_PASSWORD).unwrap(), None);
+
+        set_secret(root.path(), SMTP_PASSWORD, "pw").unwrap();
+        assert_eq!(
+            get_secret(root.path(), SMTP_PASSWORD).unwrap().as_deref(),
+            Some("pw")
+        );
+
+        set_secret(root.path(), SMTP_PASSWORD, "").unwrap();
+        assert_eq!(get_secret(root.path(), SMTP_PASSWORD).unwrap(), None);
+    }
+
+    #[test]
+    fn secret_errors_surface_storage_failures() {
+        let root = tempfile::tempdir().unwrap();
+        // A directory where secrets.json should be makes both reads and writes fail.
+        std::fs::create_dir(root.path().join("secrets.json")).unwrap();
+        assert!(get_secret(root.path(), SMTP_PASSWORD).is_err());
+        assert!(set_secret(root.path(), SMTP_PASSWORD, "pw").is_err());
+    }
+
+    #[test]
+    fn migration_moves_the_plaintext_api_key_out_of_settings() {
+        let root = tempfile::tempdir().unwrap();
+        let mut settings = Settings {
+            deepseek_api_key: " sk-1 ".to_string(),
+            ..Settings::default()
+        };
+        assert!(migrate_plaintext_secrets(root.path(), &mut settings).unwrap());
+        assert!(settings.deepseek_api_key.is_empty());
+        assert!(settings.ai_api_key_set);
+        assert_eq!(
+            get_secret(root.path(), DEEPSEEK_API_KEY)
+                .unwrap()
+                .as_deref(),
+            Some("sk-1")
+        );
+        let saved = serde_json::to_value(&settings).unwrap();
+        assert!(saved.get("deepseek_api_key").is_none());
+
+        // Nothing left to move on the next boot.
+        assert!(!migrate_plaintext_secrets(root.path(), &mut settings).unwrap());
+    }
+}
@@ -0,0 +1,327 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::models::{Settings, Task};
+
+/// What a global shortcut does. Each action has its own binding in settings; only the quick
+/// window one is required, the others are unbound while blank.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutAction {
+    /// Shows the quick window (`shortcut`).
+    ShowQuick,
+    /// Shows the quick window with the add input focused (`shortcut_quick_add`).
+    QuickAdd,
+    /// Hides the main window when visible, shows it otherwise (`shortcut_toggle_main`).
+    ToggleMain,
+    /// Completes the first open task in today's focus list (`shortcut_complete_focus`).
+    CompleteFocus,
+}
+
+impl ShortcutAction {
+    pub const ALL: [ShortcutAction; 4] = [
+        ShortcutAction::ShowQuick,
+        ShortcutAction::QuickAdd,
+        ShortcutAction::ToggleMain,
+        ShortcutAction::CompleteFocus,
+    ];
+
+    /// The settings field holding the binding; also used in error messages.
+    pub fn setting_name(self) -> &'static str {
+        match self {
+            ShortcutAction::ShowQuick => "shortcut",
+            ShortcutAction::QuickAdd => "shortcut_quick_add",
+            ShortcutAction::ToggleMain => "shortcut_toggle_main",
+            ShortcutAction::CompleteFocus => "shortcut_complete_focus",
+        }
+    }
+
+    pub fn from_setting_name(name: &str) -> Option<ShortcutAction> {
+        ShortcutAction::ALL
+            .into_iter()
+            .find(|action| action.setting_name() == name)
+    }
+
+    fn binding(self, settings: &Settings) -> &str {
+        match self {
+            ShortcutAction::ShowQuick => &settings.shortcut,
+            ShortcutAction::QuickAdd => &settings.shortcut_quick_add,
+            ShortcutAction::ToggleMain => &settings.shortcut_toggle_main,
+            ShortcutAction::CompleteFocus => &settings.shortcut_complete_focus,
+        }
+    }
+}
+
+/// Trims every binding in place so the persisted config is stable; true when one changed.
+pub fn normalize_shortcuts(settings: &mut Settings) -> bool {
+    let mut changed = false;
+    for field in [
+        &mut settings.shortcut,
+        &mut settings.shortcut_quick_add,
+        &mut settings.shortcut_toggle_main,
+        &mut settings.shortcut_complete_focus,
+    ] {
+        let trimmed = field.trim();
+        if trimmed.len() != field.len() {
+            *field = trimmed.to_string();
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// The registry: every bound action with its shortcut, in `ShortcutAction::ALL` order. Blank
+/// bindings are left out, including a blank quick window one (validation rejects that first).
+pub fn shortcut_bindings(settings: &Settings) -> Vec<(ShortcutAction, String)> {
+    ShortcutAction::ALL
+        .iter()
+        .map(|action| (*action, action.binding(settings).trim().to_string()))
+        .filter(|(_, shortcut)| !shortcut.is_empty())
+        .collect()
+}
+
+fn canonical_part(part: &str) -> String {
+    let part = part.trim().to_lowercase();
+    let alias = match part.as_str() {
+        "control" | "ctrl" => "ctrl",
+        "option" | "alt" => "alt",
+        "command" | "cmd" | "super" | "meta" => "super",
+        "commandorcontrol" | "commandorctrl" | "cmdorctrl" | "cmdorcontrol" => {
+            if cfg!(target_os = "macos") {
+                "super"
+            } else {
+                "ctrl"
+            }
+        }
+        _ => "",
+    };
+    if !alias.is_empty() {
+        return alias.to_string();
+    }
+    // `KeyT` and `T`, `Digit1` and `1` name the same key.
+    if let Some(key) = part.strip_prefix("key").filter(|key| key.len() == 1) {
+        return key.to_string();
+    }
+    if let Some(digit) = part.strip_prefix("digit").filter(|digit| digit.len() == 1) {
+        return digit.to_string();
+    }
+    part
+}
+
+/// A comparable form of `raw`: lower case, modifier aliases folded and modifiers sorted, so
+/// `Shift+CmdOrCtrl+KeyT` and `CommandOrControl+Shift+T` come out the same.
+pub fn canonical_shortcut(raw: &str) -> String {
+    let mut parts: Vec<String> = raw
+        .split('+')
+        .filter(|part| !part.trim().is_empty())
+        .map(canonical_part)
+        .collect();
+    let Some(key) = parts.pop() else {
+        return String::new();
+    };
+    parts.sort();
+    parts.dedup();
+    parts.push(key);
+    parts.join("+")
+}
+
+/// Errors when two actions are bound to the same shortcut; the global shortcut plugin would
+/// only deliver it to one of them.
+pub fn check_conflicts(bindings: &[(ShortcutAction, String)]) -> Result<(), String> {
+    for (index, (action, shortcut)) in bindings.iter().enumerate() {
+        let canonical = canonical_shortcut(shortcut);
+        if let Some((other, _)) = bindings[index + 1..]
+            .iter()
+            .find(|(_, other)| canonical_shortcut(other) == canonical)
+        {
+            return Err(format!(
+                "shortcut conflict: {} and {} both use {shortcut}",
+                action.setting_name(),
+                other.setting_name()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Why a shortcut cannot be used as is.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ShortcutConflict {
+    /// Another action of this app is bound to it (`action` is its settings field).
+    App { action: String },
+    /// The OS keeps it for itself or another application already holds it.
+    System { reason: String },
+}
+
+/// Result of `validate_shortcut`: `error` is a parse error, in which case there are no
+/// conflicts to report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ShortcutCheck {
+    pub shortcut: String,
+    pub valid: bool,
+    pub error: Option<String>,
+    pub conflicts: Vec<ShortcutConflict>,
+}
+
+/// Actions other than `except` already bound to `shortcut`, however it is spelled.
+pub fn app_conflicts(
+    settings: &Settings,
+    shortcut: &str,
+    except: Option<ShortcutAction>,
+) -> Vec<ShortcutAction> {
+    let canonical = canonical_shortcut(shortcut);
+    shortcut_bindings(settings)
+        .into_iter()
+        .filter(|(action, binding)| {
+            Some(*action) != except && canonical_shortcut(binding) == canonical
+        })
+        .map(|(action, _)| action)
+        .collect()
+}
+
+/// Well-known combinations the OS handles before any app sees them, by `std::env::consts::OS`
+/// name. Registering them either fails or silently never fires, so they are refused up front.
+const RESERVED_SHORTCUTS: &[(&str, &str, &str)] = &[
+    ("macos", "super+space", "Spotlight"),
+    ("macos", "super+tab", "app switcher"),
+    ("macos", "super+q", "quit application"),
+    ("macos", "ctrl+super+q", "lock screen"),
+    ("macos", "alt+super+escape", "force quit"),
+    ("macos", "shift+super+3", "screenshot"),
+    ("macos", "shift+super+4", "screenshot"),
+    ("macos", "shift+super+5", "screenshot"),
+    ("macos", "ctrl+space", "input source"),
+    ("windows", "alt+tab", "app switcher"),
+    ("windows", "alt+f4", "close window"),
+    ("windows", "alt+ctrl+delete", "security screen"),
+    ("windows", "ctrl+shift+escape", "task manager"),
+    ("windows", "super+d", "show desktop"),
+    ("windows", "super+e", "file explorer"),
+    ("windows", "super+l", "lock screen"),
+    ("windows", "super+r", "run dialog"),
+    ("windows", "super+tab", "task view"),
+    ("linux", "alt+tab", "app switcher"),
+    ("linux", "alt+f4", "close window"),
+    ("linux", "alt+ctrl+delete", "log out"),
+    ("linux", "alt+ctrl+t", "terminal"),
+    ("linux", "super+l", "lock screen"),
+];
+
+/// What `os` reserves `shortcut` for, if it is one of the well-known system shortcuts.
+pub fn reserved_system_shortcut(shortcut: &str, os: &str) -> Option<&'static str> {
+    let canonical = canonical_shortcut(shortcut);
+    RESERVED_SHORTCUTS
+        .iter()
+        .find(|(reserved_os, combo, _)| *reserved_os == os && *combo == canonical)
+        .map(|(_, _, purpose)| *purpose)
+}
+
+/// The task "complete top focus task" acts on: the first open task of today's focus list, in
+/// the order the user picked. `None` when the list is from another day or all done.
+pub fn top_focus_task_id(settings: &Settings, tasks: &[Task], today: NaiveDate) -> Option<String> {
+    let today = today.format("%Y-%m-%d").to_string();
+    if settings.today_focus_date.as_deref() != Some(today.as_str()) {
+        return None;
+    }
+    settings
+        .today_focus_ids
+        .iter()
+        .find(|id| tasks.iter().any(|task| task.id == **id && !task.completed))
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+
+    fn task(id: &str, completed: bool) -> Task {
+        test_support::task(id).completed(completed)
+    }
+
+    #[test]
+    fn bindings_skip_blank_actions_and_conflicts_ignore_spelling() {
+        let mut settings = Settings {
+            shortcut_quick_add: " Alt+Space ".to_string(),
+            shortcut_complete_focus: "CommandOrControl+Shift+D".to_string(),
+            ..Settings::default()
+        };
+        assert!(normalize_shortcuts(&mut settings));
+        assert!(!normalize_shortcuts(&mut settings));
+        assert_eq!(settings.shortcut_quick_add, "Alt+Space");
+        let bindings = shortcut_bindings(&settings);
+        assert_eq!(
+            bindings
+                .iter()
+                .map(|(action, _)| *action)
+                .collect::<Vec<_>>(),
+            vec![
+                ShortcutAction::ShowQuick,
+                ShortcutAction::QuickAdd,
+                ShortcutAction::CompleteFocus
+            ]
+        );
+        assert_eq!(check_conflicts(&bindings), Ok(()));
+
+        assert_eq!(
+            canonical_shortcut("Shift+CmdOrCtrl+KeyT"),
+            canonical_shortcut("CommandOrControl+Shift+T")
+        );
+        assert_ne!(canonical_shortcut("Shift+T"), canonical_shortcut("Alt+T"));
+
+        settings.shortcut_toggle_main = "shift+cmdorctrl+t".to_string();
+        let err = check_conflicts(&shortcut_bindings(&settings)).unwrap_err();
+        assert!(err.contains("shortcut and shortcut_toggle_main"), "{err}");
+    }
+
+    #[test]
+    fn app_and_system_conflicts_are_found_in_any_spelling() {
+        let settings = Settings {
+            shortcut: "Alt+Shift+T".to_string(),
+            shortcut_toggle_main: "Alt+M".to_string(),
+            ..Settings::default()
+        };
+        assert_eq!(
+            app_conflicts(&settings, "shift+alt+t", None),
+            vec![ShortcutAction::ShowQuick]
+        );
+        // Re-recording an action's own binding is not a conflict.
+        assert!(
+            app_conflicts(&settings, "Alt+Shift+T", Some(ShortcutAction::ShowQuick)).is_empty()
+        );
+        assert!(app_conflicts(&settings, "Alt+N", None).is_empty());
+        assert_eq!(
+            ShortcutAction::from_setting_name("shortcut_toggle_main"),
+            Some(ShortcutAction::ToggleMain)
+        );
+        assert_eq!(ShortcutAction::from_setting_name("nope"), None);
+
+        assert_eq!(
+            reserved_system_shortcut("Super+Space", "macos"),
+            Some("Spotlight")
+        );
+        assert_eq!(
+            reserved_system_shortcut("Control+Alt+Delete", "windows"),
+            Some("security screen")
+        );
+        assert_eq!(reserved_system_shortcut("Super+Space", "windows"), None);
+        assert_eq!(reserved_system_shortcut("Alt+Shift+T", "macos"), None);
+    }
+
+    #[test]
+    fn top_focus_task_is_the_first_open_one_from_today() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 10).unwrap();
+        let tasks = vec![task("a", true), task("b", false), task("c", false)];
+        let mut settings = Settings {
+            today_focus_ids: vec!["missing".into(), "a".into(), "c".into(), "b".into()],
+            today_focus_date: Some("2026-03-10".to_string()),
+            ..Settings::default()
+        };
+        assert_eq!(
+            top_focus_task_id(&settings, &tasks, today).as_deref(),
+            Some("c")
+        );
+        settings.today_focus_date = Some("2026-03-09".to_string());
+        assert_eq!(top_focus_task_id(&settings, &tasks, today), None);
+    }
+}
@@ -0,0 +1,228 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone};
+
+use crate::models::{DueFilter, Project, Settings, SmartList, Task, TaskGroupBy, Timestamp};
+use crate::query::{query_tasks, TaskFilter, TaskQuerySpec};
+
+const NAME_MAX_CHARS: usize = 80;
+/// "Next N days" looks at most a year ahead.
+const MAX_DAYS: u32 = 366;
+
+/// Trims the name and id, drops blank/duplicate tags and project ids (a leading `#` on a tag is
+/// ignored) and clamps `NextDays`. Every project id must exist when the list is saved.
+pub fn normalize_smart_list(list: &mut SmartList, projects: &[Project]) -> Result<(), String> {
+    list.id = list.id.trim().to_string();
+    list.name = list.name.trim().to_string();
+    if list.id.is_empty() {
+        return Err("smart list id is required".to_string());
+    }
+    if list.name.is_empty() {
+        return Err("smart list name is required".to_string());
+    }
+    let chars = list.name.chars().count();
+    if chars > NAME_MAX_CHARS {
+        return Err(format!(
+            "smart list name too long: {chars} > {NAME_MAX_CHARS} characters"
+        ));
+    }
+
+    let mut project_ids: Vec<String> = Vec::new();
+    for id in list.project_ids.iter().map(|id| id.trim()) {
+        if id.is_empty() || project_ids.iter().any(|seen| seen == id) {
+            continue;
+        }
+        if !projects.iter().any(|project| project.id == id) {
+            return Err(format!("project not found: {id}"));
+        }
+        project_ids.push(id.to_string());
+    }
+    list.project_ids = project_ids;
+
+    let mut tags: Vec<String> = Vec::new();
+    for tag in list
+        .tags
+        .iter()
+        .map(|tag| tag.trim().trim_start_matches('#'))
+    {
+        if !tag.is_empty() && !tags.iter().any(|seen| seen.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+    list.tags = tags;
+
+    if let DueFilter::NextDays { days } = &mut list.due {
+        *days = (*days).clamp(1, MAX_DAYS);
+    }
+    Ok(())
+}
+
+fn day_start(date: NaiveDate) -> Timestamp {
+    let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is valid");
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        // Midnight skipped by DST: the day starts one hour later.
+        .unwrap_or_else(|| {
+            Local
+                .from_local_datetime(&(midnight + Duration::hours(1)))
+                .earliest()
+                .expect("an hour past a DST gap is valid")
+        })
+        .timestamp()
+}
+
+fn matches_due(task: &Task, due: DueFilter, now: DateTime<Local>, grace_minutes: i64) -> bool {
+    let today = now.date_naive();
+    match due {
+        DueFilter::Any => true,
+        DueFilter::Overdue => task.is_overdue(now.timestamp(), grace_minutes),
+        DueFilter::Today => task
+            .due_at
+            .is_some_and(|due_at| due_at < day_start(today + Duration::days(1))),
+        DueFilter::NextDays { days } => {
+            let end = day_start(today + Duration::days(i64::from(days.max(1))));
+            task.due_at
+                .is_some_and(|due_at| due_at >= day_start(today) && due_at < end)
+        }
+        DueFilter::NoDate => task.due_at.is_none(),
+    }
+}
+
+/// The tasks `list` selects right now, pinned first and then in `list.sort` order, exactly as
+/// `query_tasks` would list them.
+pub fn evaluate(
+    list: &SmartList,
+    tasks: &[Task],
+    projects: &[Project],
+    now: DateTime<Local>,
+    settings: &Settings,
+) -> Vec<Task> {
+    let candidates: Vec<Task> = tasks
+        .iter()
+        .filter(|task| list.project_ids.is_empty() || list.project_ids.contains(&task.project_id))
+        .filter(|task| !list.waiting_only || task.waiting_on.is_some())
+        .filter(|task| matches_due(task, list.due, now, settings.overdue_grace_minutes))
+        .cloned()
+        .collect();
+    let spec = TaskQuerySpec {
+        filter: TaskFilter {
+            tags: list.tags.clone(),
+            include_completed: list.include_completed,
+            important_only: list.important_only,
+            include_someday: list.include_someday,
+            ..TaskFilter::default()
+        },
+        sort: list.sort,
+        group_by: TaskGroupBy::None,
+        ..TaskQuerySpec::default()
+    };
+    query_tasks(&candidates, projects, &spec, now, settings)
+        .groups
+        .into_iter()
+        .flat_map(|group| group.tasks)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_support, TaskSortKey};
+
+    fn list(due: DueFilter) -> SmartList {
+        serde_json::from_value(serde_json::json!({ "id": "l", "name": "List" }))
+            .map(|list: SmartList| SmartList {
+                due,
+                sort: TaskSortKey::Due,
+                ..list
+            })
+            .unwrap()
+    }
+
+    fn task(id: &str, due_at: Option<Timestamp>) -> Task {
+        test_support::task(id).due(due_at)
+    }
+
+    fn project(id: &str) -> Project {
+        serde_json::from_value(serde_json::json!({ "id": id, "name": id })).unwrap()
+    }
+
+    #[test]
+    fn saved_lists_are_trimmed_deduplicated_and_checked() {
+        let projects = vec![project("inbox"), project("work")];
+        let mut saved = list(DueFilter::NextDays { days: 0 });
+        saved.name = "  Next week ".to_string();
+        saved.project_ids = vec![" work".into(), "work".into(), "".into()];
+        saved.tags = vec!["#Urgent".into(), "urgent".into(), " ".into(), "home".into()];
+        normalize_smart_list(&mut saved, &projects).unwrap();
+        assert_eq!(saved.name, "Next week");
+        assert_eq!(saved.project_ids, ["work"]);
+        assert_eq!(saved.tags, ["Urgent", "home"]);
+        assert_eq!(saved.due, DueFilter::NextDays { days: 1 });
+
+        saved.project_ids = vec!["gone".into()];
+        assert_eq!(
+            normalize_smart_list(&mut saved, &projects),
+            Err("project not found: gone".to_string())
+        );
+        let mut unnamed = list(DueFilter::Any);
+        unnamed.name = " ".to_string();
+        assert!(normalize_smart_list(&mut unnamed, &projects).is_err());
+        unnamed.name = "x".repeat(81);
+        assert!(normalize_smart_list(&mut unnamed, &projects).is_err());
+    }
+
+    #[test]
+    fn due_ranges_are_relative_to_now() {
+        let now = Local.with_ymd_and_hms(2026, 4, 10, 12, 0, 0).unwrap();
+        let at = |day: u32, hour: u32| {
+            Some(
+                Local
+                    .with_ymd_and_hms(2026, 4, day, hour, 0, 0)
+                    .unwrap()
+                    .timestamp(),
+            )
+        };
+        let mut waiting = task("waiting", at(20, 9));
+        waiting.waiting_on = Some("Ann".to_string());
+        let mut done = task("done", at(10, 18));
+        done.completed = true;
+        let tasks = vec![
+            task("late", at(9, 9)),
+            task("earlier-today", at(10, 8)),
+            task("tonight", at(10, 20)),
+            task("seventh-day", at(16, 23)),
+            task("eighth-day", at(17, 0)),
+            task("undated", None),
+            waiting,
+            done,
+        ];
+        let settings = Settings::default();
+        let ids = |list: &SmartList| -> Vec<String> {
+            evaluate(list, &tasks, &[], now, &settings)
+                .into_iter()
+                .map(|task| task.id)
+                .collect()
+        };
+
+        assert_eq!(ids(&list(DueFilter::Overdue)), ["late", "earlier-today"]);
+        assert_eq!(
+            ids(&list(DueFilter::Today)),
+            ["late", "earlier-today", "tonight"]
+        );
+        assert_eq!(
+            ids(&list(DueFilter::NextDays { days: 7 })),
+            ["earlier-today", "tonight", "seventh-day"]
+        );
+        assert_eq!(ids(&list(DueFilter::NoDate)), ["undated"]);
+
+        let mut waiting_list = list(DueFilter::Any);
+        waiting_list.waiting_only = true;
+        assert_eq!(ids(&waiting_list), ["waiting"]);
+
+        let mut with_done = list(DueFilter::Today);
+        with_done.include_completed = true;
+        assert_eq!(
+            ids(&with_done),
+            ["late", "earlier-today", "done", "tonight"]
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use chrono::{DateTime, Datelike, Duration, Local, LocalResult, NaiveDate, NaiveTime, TimeZone};
+
+use crate::calendar::WorkHours;
+use crate::models::{Settings, Timestamp};
+
+/// Named snooze targets shared by every window, so "tomorrow morning" means the same time
+/// whether it is picked in the reminder overlay, a notification or the main list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnoozePreset {
+    #[serde(rename = "in_5m")]
+    InFiveMinutes,
+    #[serde(rename = "in_10m")]
+    InTenMinutes,
+    #[serde(rename = "in_15m")]
+    InFifteenMinutes,
+    #[serde(rename = "in_1h")]
+    InOneHour,
+    /// Today at `work_day_end`; an hour from now once that has passed.
+    ThisEvening,
+    /// Tomorrow at `work_day_start`.
+    TomorrowMorning,
+    /// The coming Monday (never today) at `work_day_start`.
+    NextMonday,
+}
+
+/// Buttons on the native reminder notification. The ids are the action ids the frontend
+/// registers for the reminder action type and passes back with the task id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum NotificationAction {
+    #[serde(rename = "complete")]
+    Complete,
+    #[serde(rename = "snooze_10m")]
+    SnoozeTenMinutes,
+}
+
+/// `date` at `time` in local time; a wall-clock time skipped by DST moves an hour later.
+fn local_at(date: NaiveDate, time: NaiveTime, fallback: Timestamp) -> Timestamp {
+    let naive = date.and_time(time);
+    match Local.from_local_datetime(&naive) {
+        LocalResult::Single(value) | LocalResult::Ambiguous(value, _) => value.timestamp(),
+        LocalResult::None => Local
+            .from_local_datetime(&(naive + Duration::hours(1)))
+            .earliest()
+            .map_or(fallback, |value| value.timestamp()),
+    }
+}
+
+/// When a task snoozed with `preset` at `now` reminds again. Times of day come from the work
+/// hours in `settings` (the defaults when they do not parse).
+pub fn snooze_until(preset: SnoozePreset, now: DateTime<Local>, settings: &Settings) -> Timestamp {
+    let hours = WorkHours::from_settings(settings)
+        .or_else(|_| WorkHours::from_settings(&Settings::default()))
+        .expect("default work hours are valid");
+    let now_ts = now.timestamp();
+    let in_hour = now_ts + 3600;
+    let today = now.date_naive();
+    match preset {
+        SnoozePreset::InFiveMinutes => now_ts + 5 * 60,
+        SnoozePreset::InTenMinutes => now_ts + 10 * 60,
+        SnoozePreset::InFifteenMinutes => now_ts + 15 * 60,
+        SnoozePreset::InOneHour => in_hour,
+        SnoozePreset::ThisEvening => {
+            let evening = local_at(today, hours.end, in_hour);
+            if evening > now_ts {
+                evening
+            } else {
+                in_hour
+            }
+        }
+        SnoozePreset::TomorrowMorning => local_at(today + Duration::days(1), hours.start, in_hour),
+        SnoozePreset::NextMonday => {
+            let days = 7 - i64::from(today.weekday().num_days_from_monday());
+            local_at(today + Duration::days(days), hours.start, in_hour)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        // 2026-03-10 is a Tuesday.
+        Local
+            .with_ymd_and_hms(2026, 3, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn presets_follow_the_work_hours() {
+        let settings = Settings {
+            work_day_start: "08:30".to_string(),
+            work_day_end: "17:00".to_string(),
+            ..Settings::default()
+        };
+        let now = local(10, 14, 0);
+        let until = |preset| snooze_until(preset, now, &settings);
+        assert_eq!(until(SnoozePreset::InFiveMinutes), now.timestamp() + 300);
+        assert_eq!(until(SnoozePreset::InOneHour), now.timestamp() + 3600);
+        assert_eq!(
+            until(SnoozePreset::ThisEvening),
+            local(10, 17, 0).timestamp()
+        );
+        assert_eq!(
+            until(SnoozePreset::TomorrowMorning),
+            local(11, 8, 30).timestamp()
+        );
+        assert_eq!(
+            until(SnoozePreset::NextMonday),
+            local(16, 8, 30).timestamp()
+        );
+
+        // Past the end of the day "this evening" is an hour away; on a Monday the next one is
+        // a week out.
+        let late = local(10, 20, 0);
+        assert_eq!(
+            snooze_until(SnoozePreset::ThisEvening, late, &settings),
+            late.timestamp() + 3600
+        );
+        let monday = local(16, 7, 0);
+        assert_eq!(
+            snooze_until(SnoozePreset::NextMonday, monday, &settings),
+            local(23, 8, 30).timestamp()
+        );
+    }
+
+    #[test]
+    fn presets_use_their_wire_names() {
+        let names: Vec<String> = [
+            SnoozePreset::InFiveMinutes,
+            SnoozePreset::InTenMinutes,
+            SnoozePreset::InFifteenMinutes,
+            SnoozePreset::InOneHour,
+            SnoozePreset::ThisEvening,
+            SnoozePreset::TomorrowMorning,
+            SnoozePreset::NextMonday,
+        ]
+        .iter()
+        .map(|preset| {
+            serde_json::to_value(preset)
+                .unwrap()
+                .as_str()
+                .unwrap()
+                .to_string()
+        })
+        .collect();
+        assert_eq!(
+            names,
+            vec![
+                "in_5m",
+                "in_10m",
+                "in_15m",
+                "in_1h",
+                "this_evening",
+                "tomorrow_morning",
+                "next_monday"
+            ]
+        );
+    }
+}
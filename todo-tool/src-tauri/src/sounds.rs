@@ -0,0 +1,199 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+/// Tones every install has. The frontend synthesizes them, so they need no files; "beep" is
+/// played when a project has no sound of its own or its file is gone.
+pub const BUNDLED_SOUNDS: &[&str] = &["beep", "chime", "bell", "alarm"];
+/// User sounds are files in `sounds/`, referred to as `user:<file name>`.
+const USER_PREFIX: &str = "user:";
+const USER_FORMATS: &[(&str, &str)] = &[
+    ("wav", "audio/wav"),
+    ("mp3", "audio/mpeg"),
+    ("ogg", "audio/ogg"),
+    ("m4a", "audio/mp4"),
+];
+/// Larger files are not offered; a reminder sound is a few seconds long.
+pub const MAX_SOUND_BYTES: u64 = 5 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SoundEntry {
+    pub id: String,
+    pub name: String,
+    pub bundled: bool,
+}
+
+/// A sound ready to play: a bundled tone by name, or a user file's bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SoundClip {
+    pub id: String,
+    pub tone: Option<String>,
+    pub mime: Option<String>,
+    pub bytes: Vec<u8>,
+}
+
+pub fn sounds_dir(root: &Path) -> PathBuf {
+    root.join("sounds")
+}
+
+/// The MIME type of a supported user sound file name; `None` for anything else, including
+/// names that would reach outside `sounds/`.
+fn user_mime(file: &str) -> Option<&'static str> {
+    if file.is_empty() || file.contains(['/', '\\']) || file.starts_with('.') {
+        return None;
+    }
+    let (_, ext) = file.rsplit_once('.')?;
+    USER_FORMATS
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(ext))
+        .map(|(_, mime)| *mime)
+}
+
+/// Bundled tones first, then the supported files in `sounds/` by name. A missing or unreadable
+/// folder just means no user sounds.
+pub fn list_sounds(root: &Path) -> Vec<SoundEntry> {
+    let mut sounds: Vec<SoundEntry> = BUNDLED_SOUNDS
+        .iter()
+        .map(|id| SoundEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            bundled: true,
+        })
+        .collect();
+    let mut files: Vec<String> = std::fs::read_dir(sounds_dir(root))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| {
+            entry
+                .metadata()
+                .is_ok_and(|meta| meta.is_file() && meta.len() <= MAX_SOUND_BYTES)
+        })
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|file| user_mime(file).is_some())
+        .collect();
+    files.sort();
+    sounds.extend(files.into_iter().map(|file| SoundEntry {
+        id: format!("{USER_PREFIX}{file}"),
+        name: file,
+        bundled: false,
+    }));
+    sounds
+}
+
+/// A project's sound as stored: trimmed, blank cleared. Only the form is checked; a user file
+/// that goes missing later falls back to the default sound.
+pub fn normalize_sound_id(raw: Option<String>) -> Result<Option<String>, String> {
+    let Some(id) = raw
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+    else {
+        return Ok(None);
+    };
+    let known = BUNDLED_SOUNDS.contains(&id.as_str())
+        || id
+            .strip_prefix(USER_PREFIX)
+            .is_some_and(|file| user_mime(file).is_some());
+    if known {
+        Ok(Some(id))
+    } else {
+        Err(format!("unknown sound: {id}"))
+    }
+}
+
+pub fn load_sound(root: &Path, id: &str) -> Result<SoundClip, String> {
+    if BUNDLED_SOUNDS.contains(&id) {
+        return Ok(SoundClip {
+            id: id.to_string(),
+            tone: Some(id.to_string()),
+            mime: None,
+            bytes: Vec::new(),
+        });
+    }
+    let (file, mime) = id
+        .strip_prefix(USER_PREFIX)
+        .and_then(|file| user_mime(file).map(|mime| (file, mime)))
+        .ok_or_else(|| format!("unknown sound: {id}"))?;
+    let path = sounds_dir(root).join(file);
+    let size = std::fs::metadata(&path)
+        .map_err(|_| format!("sound not found: {id}"))?
+        .len();
+    if size > MAX_SOUND_BYTES {
+        return Err(format!(
+            "sound is larger than {} MB",
+            MAX_SOUND_BYTES / 1024 / 1024
+        ));
+    }
+    let bytes = std::fs::read(&path).map_err(|err| format!("cannot read sound: {err}"))?;
+    Ok(SoundClip {
+        id: id.to_string(),
+        tone: None,
+        mime: Some(mime.to_string()),
+        bytes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_sounds_are_listed_after_the_bundled_ones_and_load_from_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let sounds = sounds_dir(dir.path());
+        std::fs::create_dir_all(&sounds).unwrap();
+        std::fs::write(sounds.join("gong.MP3"), b"mp3").unwrap();
+        std::fs::write(sounds.join("alert.wav"), b"wav").unwrap();
+        std::fs::write(sounds.join("notes.txt"), b"x").unwrap();
+
+        let ids: Vec<String> = list_sounds(dir.path())
+            .into_iter()
+            .map(|sound| sound.id)
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                "beep",
+                "chime",
+                "bell",
+                "alarm",
+                "user:alert.wav",
+                "user:gong.MP3"
+            ]
+        );
+
+        let clip = load_sound(dir.path(), "user:gong.MP3").unwrap();
+        assert_eq!(
+            (clip.mime.as_deref(), clip.bytes.as_slice()),
+            (Some("audio/mpeg"), &b"mp3"[..])
+        );
+        assert_eq!(
+            load_sound(dir.path(), "chime").unwrap().tone.as_deref(),
+            Some("chime")
+        );
+        assert!(load_sound(dir.path(), "user:missing.wav")
+            .unwrap_err()
+            .contains("not found"));
+        assert!(load_sound(dir.path(), "user:../data.json").is_err());
+        // No folder yet: only the bundled tones.
+        let empty = tempfile::tempdir().unwrap();
+        assert_eq!(list_sounds(empty.path()).len(), BUNDLED_SOUNDS.len());
+    }
+
+    #[test]
+    fn normalize_sound_id_accepts_bundled_and_user_sounds_only() {
+        assert_eq!(normalize_sound_id(None), Ok(None));
+        assert_eq!(normalize_sound_id(Some("  ".into())), Ok(None));
+        assert_eq!(
+            normalize_sound_id(Some(" bell ".into())),
+            Ok(Some("bell".to_string()))
+        );
+        assert_eq!(
+            normalize_sound_id(Some("user:ding.ogg".into())),
+            Ok(Some("user:ding.ogg".to_string()))
+        );
+        assert!(normalize_sound_id(Some("trumpet".into())).is_err());
+        assert!(normalize_sound_id(Some("user:../x.wav".into())).is_err());
+        assert!(normalize_sound_id(Some("user:ding.exe".into())).is_err());
+    }
+}
@@ -1,10 +1,28 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
 use chrono::Utc;
 
-use crate::models::{Project, Settings, SettingsFile, Task, TasksFile};
+use crate::carry_over::{carry_over_target, carry_over_window_start, CarryOverEntry};
+use crate::clock::{AppClock, Clock};
+use crate::comments::new_comment;
+use crate::events::{StateDelta, StatePayload};
+use crate::focus::FocusTimer;
+use crate::models::{
+    Attachment, Comment, Project, ReminderKind, Settings, SettingsFile, Tag, Task, TasksFile,
+    Timestamp, TrashEntry,
+};
+use crate::project_tree::normalize_project_parents;
+use crate::recovery::RecoveryStatus;
+use crate::repeat::plan_spawn_ahead;
+use crate::state_delta::DeltaTracker;
+use crate::steps::{normalize_step_parents, promote_step, task_to_steps, unique_id};
+use crate::tags::{normalize_tag_parents, retag, retag_definitions};
+use crate::task_index::TaskIndex;
+use crate::time_tracking::{is_running, start_entry, stop_entry};
+use crate::undo::{UndoEntry, UndoHistory, UndoStatus, UndoSummary};
+use crate::write_queue::WriteQueue;
 
 const SCHEMA_VERSION: u32 = 1;
 const INBOX_PROJECT_ID: &str = "inbox";
@@ -25,15 +43,22 @@ fn ensure_inbox_project(projects: &mut Vec<Project>, now: &chrono::DateTime<Utc>
         created_at: now.timestamp(),
         updated_at: now.timestamp(),
         sample_tag: None,
+        reminders_muted: false,
+        muted_until: None,
+        notification_sound: None,
+        color: None,
+        icon: None,
+        parent_id: None,
     });
 }
 
-fn normalize_projects(projects: &mut Vec<Project>) {
-    for project in projects {
+fn normalize_projects(projects: &mut [Project]) {
+    for project in projects.iter_mut() {
         if project.sort_order == 0 {
             project.sort_order = project.created_at * 1000;
         }
     }
+    normalize_project_parents(projects);
 }
 
 fn normalize_tasks(tasks: &mut Vec<Task>, projects: &[Project]) {
@@ -46,54 +71,144 @@ fn normalize_tasks(tasks: &mut Vec<Task>, projects: &[Project]) {
         if task.project_id.trim().is_empty() || !allowed.contains(task.project_id.as_str()) {
             task.project_id = INBOX_PROJECT_ID.to_string();
         }
+        normalize_step_parents(&mut task.steps);
+        task.reminder.fold_remind_at(task.due_at);
+        task.refresh_progress();
     }
 }
 
 #[derive(Clone)]
 pub struct AppState {
-    inner: Arc<Mutex<AppData>>,
+    inner: Arc<AppData>,
     // Runtime-only flag: when the user is recording a shortcut in Settings,
     // we temporarily ignore the global shortcut handler to avoid accidental triggers.
     shortcut_capture_active: Arc<AtomicBool>,
+    // Set when data.json had to be recovered on load; read by the startup recovery prompt.
+    recovery: Arc<Mutex<Option<RecoveryStatus>>>,
+    // Runtime-only: recorded destructive actions; not persisted across restarts.
+    undo: Arc<Mutex<UndoHistory>>,
+    // Runtime-only: the running focus session (see `focus`); history is in focus_sessions.jsonl.
+    focus: Arc<Mutex<FocusTimer>>,
+    // Runtime-only: the disk write `persist` owes when write-behind is on (see `write_queue`).
+    writes: Arc<WriteQueue>,
+    // Runtime-only: what the windows were last sent, for `state_delta` events.
+    deltas: Arc<Mutex<DeltaTracker>>,
+    clock: AppClock,
 }
 
 #[derive(Debug, Clone)]
 pub struct AppStateSnapshot {
-    pub tasks: Vec<Task>,
+    /// Shared with the state until its next task change, so taking a snapshot copies no tasks.
+    pub tasks: Arc<Vec<Task>>,
     pub projects: Vec<Project>,
     pub settings: Settings,
 }
 
 impl AppState {
-    fn lock_inner(&self) -> MutexGuard<'_, AppData> {
-        match self.inner.lock() {
-            Ok(guard) => guard,
-            Err(poisoned) => {
-                // Prefer to keep the app bootable if a background task panicked.
-                log::warn!("state mutex poisoned; continuing with recovered guard");
-                poisoned.into_inner()
-            }
-        }
+    fn read_projects(&self) -> RwLockReadGuard<'_, Vec<Project>> {
+        read_lock(&self.inner.projects, "projects")
+    }
+
+    fn write_projects(&self) -> RwLockWriteGuard<'_, Vec<Project>> {
+        write_lock(&self.inner.projects, "projects")
+    }
+
+    fn read_tasks(&self) -> RwLockReadGuard<'_, TaskIndex> {
+        read_lock(&self.inner.tasks, "tasks")
+    }
+
+    fn write_tasks(&self) -> RwLockWriteGuard<'_, TaskIndex> {
+        write_lock(&self.inner.tasks, "tasks")
+    }
+
+    fn read_trash(&self) -> RwLockReadGuard<'_, Vec<TrashEntry>> {
+        read_lock(&self.inner.trash, "trash")
+    }
+
+    fn write_trash(&self) -> RwLockWriteGuard<'_, Vec<TrashEntry>> {
+        write_lock(&self.inner.trash, "trash")
+    }
+
+    fn read_tags(&self) -> RwLockReadGuard<'_, Vec<Tag>> {
+        read_lock(&self.inner.tags, "tags")
+    }
+
+    fn write_tags(&self) -> RwLockWriteGuard<'_, Vec<Tag>> {
+        write_lock(&self.inner.tags, "tags")
+    }
+
+    fn read_settings(&self) -> RwLockReadGuard<'_, Settings> {
+        read_lock(&self.inner.settings, "settings")
+    }
+
+    fn write_settings(&self) -> RwLockWriteGuard<'_, Settings> {
+        write_lock(&self.inner.settings, "settings")
     }
 
     pub fn new(tasks: Vec<Task>, projects: Vec<Project>, settings: Settings) -> Self {
-        let now = Utc::now();
+        Self::with_clock(tasks, projects, settings, AppClock::system())
+    }
+
+    pub fn with_clock(
+        tasks: Vec<Task>,
+        projects: Vec<Project>,
+        settings: Settings,
+        clock: AppClock,
+    ) -> Self {
+        let now = clock.now();
         let mut tasks = tasks;
         let mut projects = projects;
 
         ensure_inbox_project(&mut projects, &now);
         normalize_projects(&mut projects);
         normalize_tasks(&mut tasks, &projects);
+        let tasks = TaskIndex::new(tasks);
+        let deltas = DeltaTracker::primed(&AppStateSnapshot {
+            tasks: tasks.shared(),
+            projects: projects.clone(),
+            settings: settings.clone(),
+        });
         Self {
-            inner: Arc::new(Mutex::new(AppData {
-                tasks,
-                projects,
-                settings,
-            })),
+            inner: Arc::new(AppData {
+                projects: RwLock::new(projects),
+                tasks: RwLock::new(tasks),
+                trash: RwLock::new(Vec::new()),
+                tags: RwLock::new(Vec::new()),
+                settings: RwLock::new(settings),
+            }),
             shortcut_capture_active: Arc::new(AtomicBool::new(false)),
+            recovery: Arc::new(Mutex::new(None)),
+            undo: Arc::new(Mutex::new(UndoHistory::default())),
+            focus: Arc::new(Mutex::new(FocusTimer::default())),
+            writes: Arc::new(WriteQueue::default()),
+            deltas: Arc::new(Mutex::new(deltas)),
+            clock,
         }
     }
 
+    /// Independent copy of the data sharing this clock; dry runs mutate the copy.
+    pub fn detached(&self) -> Self {
+        let snapshot = self.snapshot();
+        let copy = Self::with_clock(
+            Arc::unwrap_or_clone(snapshot.tasks),
+            snapshot.projects,
+            snapshot.settings,
+            self.clock.clone(),
+        );
+        copy.replace_trash(self.trash());
+        copy.replace_tags(self.tags());
+        copy
+    }
+
+    /// "Now" for everything time-dependent (commands, scheduler, backups).
+    pub fn clock(&self) -> &AppClock {
+        &self.clock
+    }
+
+    pub fn write_queue(&self) -> &WriteQueue {
+        &self.writes
+    }
+
     pub fn is_shortcut_capture_active(&self) -> bool {
         self.shortcut_capture_active.load(Ordering::Relaxed)
     }
@@ -103,123 +218,506 @@ impl AppState {
             .store(active, Ordering::Relaxed);
     }
 
+    pub fn recovery_status(&self) -> Option<RecoveryStatus> {
+        match self.recovery.lock() {
+            Ok(guard) => guard.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    pub fn set_recovery_status(&self, status: Option<RecoveryStatus>) {
+        match self.recovery.lock() {
+            Ok(mut guard) => *guard = status,
+            Err(poisoned) => *poisoned.into_inner() = status,
+        }
+    }
+
+    pub fn focus(&self) -> MutexGuard<'_, FocusTimer> {
+        self.focus
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn history(&self) -> MutexGuard<'_, UndoHistory> {
+        self.undo
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Records what `action` changed since `before` (taken with `tasks_file()` ahead of the
+    /// mutation). Nothing is recorded when the action turned out to be a no-op.
+    pub fn record_undo(&self, action: &str, before: &TasksFile) {
+        let after = self.tasks_file();
+        let entry = UndoEntry::between(action, self.clock.timestamp(), before, &after);
+        if let Some(entry) = entry {
+            self.history().push(entry);
+        }
+    }
+
+    /// Puts back the records the last recorded action touched; `None` when there is nothing
+    /// to undo.
+    pub fn undo_last(&self) -> Option<UndoSummary> {
+        let entry = self.history().pop_undo()?;
+        {
+            let mut projects = self.write_projects();
+            let mut tasks = self.write_tasks();
+            let mut trash = self.write_trash();
+            let mut tags = self.write_tags();
+            tasks.edit(|tasks| entry.revert(tasks, &mut projects, &mut trash, &mut tags));
+        }
+        let summary = entry.summary();
+        self.history().push_undone(entry);
+        Some(summary)
+    }
+
+    pub fn redo_last(&self) -> Option<UndoSummary> {
+        let entry = self.history().pop_redo()?;
+        {
+            let mut projects = self.write_projects();
+            let mut tasks = self.write_tasks();
+            let mut trash = self.write_trash();
+            let mut tags = self.write_tags();
+            tasks.edit(|tasks| entry.reapply(tasks, &mut projects, &mut trash, &mut tags));
+        }
+        let summary = entry.summary();
+        self.history().push_redone(entry);
+        Some(summary)
+    }
+
+    pub fn undo_status(&self) -> UndoStatus {
+        self.history().status()
+    }
+
+    /// Holds all three read locks at once, so no writer can slip in between collections.
     pub fn snapshot(&self) -> AppStateSnapshot {
-        let guard = self.lock_inner();
+        let projects = self.read_projects();
+        let tasks = self.read_tasks();
+        let settings = self.read_settings();
         AppStateSnapshot {
-            tasks: guard.tasks.clone(),
-            projects: guard.projects.clone(),
-            settings: guard.settings.clone(),
+            tasks: tasks.shared(),
+            projects: projects.clone(),
+            settings: settings.clone(),
+        }
+    }
+
+    fn delta_tracker(&self) -> MutexGuard<'_, DeltaTracker> {
+        self.deltas
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// The full state for loads and `state_updated`, with the latest delta number it covers.
+    pub fn state_payload(&self) -> StatePayload {
+        let tracker = self.delta_tracker();
+        let snapshot = self.snapshot();
+        StatePayload {
+            tasks: Arc::unwrap_or_clone(snapshot.tasks),
+            projects: snapshot.projects,
+            settings: snapshot.settings,
+            seq: tracker.seq(),
         }
     }
 
+    /// What changed since the previous `state_delta`; `None` when nothing did.
+    pub fn take_delta(&self) -> Option<StateDelta> {
+        let mut tracker = self.delta_tracker();
+        let projects = self.read_projects();
+        let mut tasks = self.write_tasks();
+        let settings = self.read_settings();
+        let Some(ids) = tasks.take_changed() else {
+            return tracker.diff(&AppStateSnapshot {
+                tasks: tasks.shared(),
+                projects: projects.clone(),
+                settings: settings.clone(),
+            });
+        };
+        let mut changed: Vec<(String, Option<&Task>)> = ids
+            .into_iter()
+            .map(|id| {
+                let task = tasks.get(&id);
+                (id, task)
+            })
+            .collect();
+        changed.sort_by_key(|(id, _)| tasks.position(id).unwrap_or(usize::MAX));
+        tracker.diff_changed(changed, &projects, &settings)
+    }
+
     pub fn tasks_file(&self) -> TasksFile {
-        let guard = self.lock_inner();
+        let projects = self.read_projects();
+        let tasks = self.read_tasks();
+        let trash = self.read_trash();
+        let tags = self.read_tags();
         TasksFile {
             schema_version: SCHEMA_VERSION,
-            tasks: guard.tasks.clone(),
-            projects: guard.projects.clone(),
+            tasks: tasks.to_vec(),
+            projects: projects.clone(),
+            trash: trash.clone(),
+            tags: tags.clone(),
         }
     }
 
     pub fn settings_file(&self) -> SettingsFile {
-        let guard = self.lock_inner();
         SettingsFile {
             schema_version: SCHEMA_VERSION,
-            settings: guard.settings.clone(),
+            settings: self.read_settings().clone(),
         }
     }
 
+    /// A copy of every task; read-only callers can use `shared_tasks` instead.
     pub fn tasks(&self) -> Vec<Task> {
-        let guard = self.lock_inner();
-        guard.tasks.clone()
+        self.read_tasks().to_vec()
+    }
+
+    /// Every task, shared with the state instead of copied.
+    pub fn shared_tasks(&self) -> Arc<Vec<Task>> {
+        self.read_tasks().shared()
+    }
+
+    pub fn task(&self, task_id: &str) -> Option<Task> {
+        self.read_tasks().get(task_id).cloned()
+    }
+
+    /// The project's tasks, in list order.
+    pub fn project_tasks(&self, project_id: &str) -> Vec<Task> {
+        self.read_tasks().in_project(project_id).cloned().collect()
     }
 
     pub fn projects(&self) -> Vec<Project> {
-        let guard = self.lock_inner();
-        guard.projects.clone()
+        self.read_projects().clone()
+    }
+
+    pub fn trash(&self) -> Vec<TrashEntry> {
+        self.read_trash().clone()
     }
 
     pub fn add_task(&self, task: Task) {
-        let mut guard = self.lock_inner();
-        guard.tasks.push(task);
+        self.write_tasks().push(task);
     }
 
     pub fn add_project(&self, project: Project) {
-        let mut guard = self.lock_inner();
-        guard.projects.push(project);
+        self.write_projects().push(project);
     }
 
     pub fn replace_tasks(&self, tasks: Vec<Task>) {
-        let mut guard = self.lock_inner();
+        let projects = self.read_projects();
         let mut next = tasks;
-        normalize_tasks(&mut next, &guard.projects);
-        guard.tasks = next;
+        normalize_tasks(&mut next, &projects);
+        self.write_tasks().replace(next);
+        // Recorded entries describe the data that was just replaced (load, restore).
+        self.history().clear();
+    }
+
+    pub fn replace_trash(&self, trash: Vec<TrashEntry>) {
+        *self.write_trash() = trash;
+    }
+
+    /// Tag definitions (colors and nesting); see `tags`.
+    pub fn tags(&self) -> Vec<Tag> {
+        self.read_tags().clone()
+    }
+
+    pub fn replace_tags(&self, tags: Vec<Tag>) {
+        let mut next = tags;
+        normalize_tag_parents(&mut next);
+        *self.write_tags() = next;
+    }
+
+    /// Swaps in a whole data set (used when switching profiles).
+    pub fn replace_data(&self, data: TasksFile, settings: Settings) {
+        let now = self.clock.now();
+        let mut projects = data.projects;
+        let mut tasks = data.tasks;
+        let mut tags = data.tags;
+        ensure_inbox_project(&mut projects, &now);
+        normalize_projects(&mut projects);
+        normalize_tasks(&mut tasks, &projects);
+        normalize_tag_parents(&mut tags);
+        self.history().clear();
+        let mut current_projects = self.write_projects();
+        let mut current_tasks = self.write_tasks();
+        let mut current_trash = self.write_trash();
+        let mut current_tags = self.write_tags();
+        let mut current_settings = self.write_settings();
+        *current_projects = projects;
+        current_tasks.replace(tasks);
+        *current_trash = data.trash;
+        *current_tags = tags;
+        *current_settings = settings;
     }
 
     pub fn replace_projects(&self, projects: Vec<Project>) {
-        let mut guard = self.lock_inner();
-        let now = Utc::now();
+        let now = self.clock.now();
         let mut next = projects;
         ensure_inbox_project(&mut next, &now);
         normalize_projects(&mut next);
-        guard.projects = next;
+        let mut current = self.write_projects();
+        *current = next;
 
         // Any task referencing a now-missing project is moved to inbox.
-        let projects_snapshot = guard.projects.clone();
-        normalize_tasks(&mut guard.tasks, &projects_snapshot);
+        self.write_tasks()
+            .edit(|tasks| normalize_tasks(tasks, &current));
     }
 
     pub fn update_task(&self, task: Task) {
-        let mut guard = self.lock_inner();
-        if let Some(existing) = guard.tasks.iter_mut().find(|t| t.id == task.id) {
+        let id = task.id.clone();
+        self.write_tasks().update(&id, |existing| {
             let mut next = task;
             if next.sample_tag.is_none() {
                 next.sample_tag = existing.sample_tag.clone();
             }
             *existing = next;
-        }
+        });
     }
 
     pub fn update_project(&self, project: Project) {
-        let mut guard = self.lock_inner();
-        if let Some(existing) = guard.projects.iter_mut().find(|p| p.id == project.id) {
+        let mut projects = self.write_projects();
+        if let Some(existing) = projects.iter_mut().find(|p| p.id == project.id) {
             *existing = project;
         }
     }
 
+    pub fn set_project_muted(
+        &self,
+        project_id: &str,
+        muted: bool,
+        until: Option<i64>,
+        updated_at: i64,
+    ) -> Option<Project> {
+        let mut projects = self.write_projects();
+        let project = projects.iter_mut().find(|p| p.id == project_id)?;
+        project.reminders_muted = muted;
+        project.muted_until = if muted { until } else { None };
+        project.updated_at = updated_at;
+        Some(project.clone())
+    }
+
     pub fn remove_project(&self, project_id: &str) {
-        let mut guard = self.lock_inner();
         if project_id == INBOX_PROJECT_ID {
             return;
         }
-        guard.projects.retain(|project| project.id != project_id);
-        let projects_snapshot = guard.projects.clone();
-        normalize_tasks(&mut guard.tasks, &projects_snapshot);
+        let mut projects = self.write_projects();
+        let parent_id = projects
+            .iter()
+            .find(|project| project.id == project_id)
+            .and_then(|project| project.parent_id.clone());
+        projects.retain(|project| project.id != project_id);
+        // Subprojects move up one level rather than going with their folder.
+        for project in projects.iter_mut() {
+            if project.parent_id.as_deref() == Some(project_id) {
+                project.parent_id = parent_id.clone();
+            }
+        }
+        self.write_tasks()
+            .edit(|tasks| normalize_tasks(tasks, &projects));
     }
 
-    pub fn swap_sort_order(&self, first_id: &str, second_id: &str, updated_at: i64) -> bool {
-        let mut guard = self.lock_inner();
-        let mut first_index = None;
-        let mut second_index = None;
-        for (index, task) in guard.tasks.iter().enumerate() {
-            if task.id == first_id {
-                first_index = Some(index);
-            } else if task.id == second_id {
-                second_index = Some(index);
+    pub fn set_task_pinned(&self, task_id: &str, pinned: bool, updated_at: i64) -> Option<Task> {
+        self.write_tasks().update(task_id, |task| {
+            if task.pinned != pinned {
+                task.pinned = pinned;
+                task.updated_at = updated_at;
             }
-            if first_index.is_some() && second_index.is_some() {
-                break;
+            task.clone()
+        })
+    }
+
+    /// Moves a task into (`due_at = None`) or out of (`Some(due)`) the Someday backlog.
+    ///
+    /// Leaving the backlog reschedules the task, so its reminder starts over from the new due time.
+    pub fn set_task_someday(&self, task_id: &str, due_at: Option<i64>, now: i64) -> Option<Task> {
+        self.write_tasks().update(task_id, |task| {
+            match due_at {
+                None => task.someday = true,
+                Some(due_at) => {
+                    task.someday = false;
+                    task.due_at = Some(due_at);
+                    task.reminder.remind_at = None;
+                    task.reminder.last_fired_at = None;
+                    task.reminder.snoozed_until = None;
+                    task.reminder.forced_dismissed = false;
+                    task.reminder.repeat_fired_count = 0;
+                }
+            }
+            task.updated_at = now;
+            task.clone()
+        })
+    }
+
+    pub fn add_task_comment(&self, task_id: &str, text: String, now: i64) -> Option<Comment> {
+        self.write_tasks().update(task_id, |task| {
+            let comment = new_comment(task, text, now);
+            task.comments.push(comment.clone());
+            task.updated_at = now;
+            comment
+        })
+    }
+
+    /// `None` when the task is missing; `Some(false)` when it has no such comment.
+    pub fn delete_task_comment(&self, task_id: &str, comment_id: &str, now: i64) -> Option<bool> {
+        self.write_tasks().update(task_id, |task| {
+            let before = task.comments.len();
+            task.comments.retain(|c| c.id != comment_id);
+            if task.comments.len() == before {
+                return false;
+            }
+            task.updated_at = now;
+            true
+        })
+    }
+
+    /// Splits a step (with its nested steps) out of `task_id` into a new task. Returns the
+    /// shortened task and the new one.
+    pub fn promote_task_step(
+        &self,
+        task_id: &str,
+        step_id: &str,
+        now: i64,
+    ) -> Result<(Task, Task), String> {
+        let mut tasks = self.write_tasks();
+        let trash = self.read_trash();
+        let new_id = unique_id(format!("{task_id}-{step_id}"), |id| {
+            tasks.contains(id) || trash.iter().any(|e| e.task.id == id)
+        });
+        let (task, promoted) = tasks
+            .update(task_id, |task| {
+                promote_step(task, step_id, new_id, now).map(|promoted| (task.clone(), promoted))
+            })
+            .ok_or("task not found")?
+            .ok_or("step not found")?;
+        tasks.push(promoted.clone());
+        Ok((task, promoted))
+    }
+
+    /// Removes `task_id` and appends it, with its steps, to `target_id` as nested steps
+    /// (under `parent_step_id` when given). Returns the updated target.
+    pub fn convert_task_to_steps(
+        &self,
+        task_id: &str,
+        target_id: &str,
+        parent_step_id: Option<&str>,
+        now: i64,
+    ) -> Result<Task, String> {
+        if task_id == target_id {
+            return Err("a task cannot become a step of itself".to_string());
+        }
+        let mut tasks = self.write_tasks();
+        let index = tasks.position(task_id).ok_or("task not found")?;
+        let target = tasks.get(target_id).ok_or("target task not found")?;
+        if parent_step_id.is_some_and(|id| !target.steps.iter().any(|s| s.id == id)) {
+            return Err("step not found".to_string());
+        }
+        let steps = task_to_steps(
+            &tasks[index],
+            target,
+            parent_step_id.map(str::to_string),
+            now,
+        );
+        tasks.edit(|tasks| tasks.remove(index));
+        tasks
+            .update(target_id, |target| {
+                target.steps.extend(steps);
+                target.updated_at = now;
+                target.refresh_progress();
+                target.clone()
+            })
+            .ok_or_else(|| "target task not found".to_string())
+    }
+
+    /// Starts the task's timer and stops any other running one, so time is never counted twice.
+    /// Returns the task and the other tasks that were stopped.
+    pub fn start_task_timer(&self, task_id: &str, now: i64) -> Option<(Task, Vec<Task>)> {
+        let mut tasks = self.write_tasks();
+        if !tasks.contains(task_id) {
+            return None;
+        }
+        let running: Vec<String> = tasks
+            .iter()
+            .filter(|t| t.id != task_id && is_running(t))
+            .map(|t| t.id.clone())
+            .collect();
+        let mut stopped = Vec::new();
+        for id in running {
+            if let Some(Some(task)) = tasks.update(&id, |task| {
+                stop_entry(task, now).then(|| {
+                    task.updated_at = now;
+                    task.clone()
+                })
+            }) {
+                stopped.push(task);
             }
         }
-        let (first_index, second_index) = match (first_index, second_index) {
-            (Some(first), Some(second)) => (first, second),
+        let task = tasks.update(task_id, |task| {
+            if start_entry(task, now) {
+                task.updated_at = now;
+            }
+            task.clone()
+        })?;
+        Some((task, stopped))
+    }
+
+    /// Stops every running timer, or only `task_id`'s; returns the stopped tasks.
+    pub fn stop_task_timers(&self, task_id: Option<&str>, now: i64) -> Vec<Task> {
+        let mut tasks = self.write_tasks();
+        let running: Vec<String> = tasks
+            .iter()
+            .filter(|t| task_id.is_none_or(|id| t.id == id) && is_running(t))
+            .map(|t| t.id.clone())
+            .collect();
+        let mut stopped = Vec::new();
+        for id in running {
+            if let Some(Some(task)) = tasks.update(&id, |task| {
+                stop_entry(task, now).then(|| {
+                    task.updated_at = now;
+                    task.clone()
+                })
+            }) {
+                stopped.push(task);
+            }
+        }
+        stopped
+    }
+
+    pub fn add_task_attachment(&self, task_id: &str, attachment: Attachment, now: i64) -> bool {
+        self.write_tasks()
+            .update(task_id, |task| {
+                task.attachments.push(attachment);
+                task.updated_at = now;
+            })
+            .is_some()
+    }
+
+    /// `None` when the task is missing; `Some(false)` when it has no such attachment.
+    pub fn remove_task_attachment(
+        &self,
+        task_id: &str,
+        attachment_id: &str,
+        now: i64,
+    ) -> Option<bool> {
+        self.write_tasks().update(task_id, |task| {
+            let before = task.attachments.len();
+            task.attachments.retain(|a| a.id != attachment_id);
+            if task.attachments.len() == before {
+                return false;
+            }
+            task.updated_at = now;
+            true
+        })
+    }
+
+    pub fn swap_sort_order(&self, first_id: &str, second_id: &str, updated_at: i64) -> bool {
+        let mut tasks = self.write_tasks();
+        let (first_order, second_order) = match (tasks.get(first_id), tasks.get(second_id)) {
+            (Some(first), Some(second)) if first_id != second_id => {
+                (first.sort_order, second.sort_order)
+            }
             _ => return false,
         };
-        let first_order = guard.tasks[first_index].sort_order;
-        guard.tasks[first_index].sort_order = guard.tasks[second_index].sort_order;
-        guard.tasks[second_index].sort_order = first_order;
-        guard.tasks[first_index].updated_at = updated_at;
-        guard.tasks[second_index].updated_at = updated_at;
+        for (id, sort_order) in [(first_id, second_order), (second_id, first_order)] {
+            tasks.update(id, |task| {
+                task.sort_order = sort_order;
+                task.updated_at = updated_at;
+            });
+        }
         true
     }
 
@@ -229,10 +727,10 @@ impl AppState {
         second_id: &str,
         updated_at: i64,
     ) -> bool {
-        let mut guard = self.lock_inner();
+        let mut projects = self.write_projects();
         let mut first_index = None;
         let mut second_index = None;
-        for (index, project) in guard.projects.iter().enumerate() {
+        for (index, project) in projects.iter().enumerate() {
             if project.id == first_id {
                 first_index = Some(index);
             } else if project.id == second_id {
@@ -246,73 +744,275 @@ impl AppState {
             (Some(first), Some(second)) => (first, second),
             _ => return false,
         };
-        let first_order = guard.projects[first_index].sort_order;
-        guard.projects[first_index].sort_order = guard.projects[second_index].sort_order;
-        guard.projects[second_index].sort_order = first_order;
-        guard.projects[first_index].updated_at = updated_at;
-        guard.projects[second_index].updated_at = updated_at;
+        let first_order = projects[first_index].sort_order;
+        projects[first_index].sort_order = projects[second_index].sort_order;
+        projects[second_index].sort_order = first_order;
+        projects[first_index].updated_at = updated_at;
+        projects[second_index].updated_at = updated_at;
         true
     }
 
     pub fn complete_task(&self, task_id: &str) -> Option<Task> {
-        let mut guard = self.lock_inner();
-        let now = Utc::now().timestamp();
-        let mut completed_task: Option<Task> = None;
-        if let Some(task) = guard.tasks.iter_mut().find(|t| t.id == task_id) {
+        let now = self.clock.timestamp();
+        self.write_tasks().update(task_id, |task| {
             task.completed = true;
             task.completed_at = Some(now);
             task.updated_at = now;
             task.reminder.snoozed_until = None;
             task.reminder.last_fired_at = Some(now);
-            completed_task = Some(task.clone());
+            stop_entry(task, now);
+            task.clone()
+        })
+    }
+
+    /// Adds the next repeat instance unless an open task of the same series is already due at
+    /// that time (e.g. `complete_task` retried after a failed persist). Returns the live instance
+    /// and whether it was newly added.
+    pub fn spawn_repeat_instance(&self, next: Task) -> (Task, bool) {
+        let mut tasks = self.write_tasks();
+        let existing = tasks.iter().find(|task| {
+            !task.completed
+                && task.due_at == next.due_at
+                && task.series_id.is_some()
+                && task.series_id == next.series_id
+        });
+        if let Some(existing) = existing {
+            return (existing.clone(), false);
         }
-        completed_task
+        tasks.push(next.clone());
+        (next, true)
+    }
+
+    /// Swaps the `sources` tag keys for `target` on every task and tag definition in one write;
+    /// returns the ids of the tasks that changed.
+    pub fn retag_tasks(&self, sources: &[String], target: &str, now: Timestamp) -> Vec<String> {
+        let mut changed = Vec::new();
+        let mut tasks = self.write_tasks();
+        let mut tags = self.write_tags();
+        retag_definitions(&mut tags, sources, target, now);
+        tasks.edit(|tasks| {
+            for task in tasks.iter_mut() {
+                if retag(task, sources, target) {
+                    task.updated_at = now;
+                    changed.push(task.id.clone());
+                }
+            }
+        });
+        changed
+    }
+
+    /// Moves yesterday's unfinished tasks to today and records the run in settings.
+    ///
+    /// Explicit reminder times move by the same amount as the due time, and the reminder is
+    /// re-armed so it fires again on the new day.
+    pub fn carry_over_tasks(&self, now: chrono::DateTime<chrono::Local>) -> Vec<CarryOverEntry> {
+        let mut tasks = self.write_tasks();
+        let mut settings = self.write_settings();
+        let window_start = carry_over_window_start(settings.last_carry_over_at, now);
+        let mut entries = Vec::new();
+        tasks.edit(|tasks| {
+            for task in tasks.iter_mut() {
+                let (Some(from_due), Some(to_due)) =
+                    (task.due_at, carry_over_target(task, window_start, now))
+                else {
+                    continue;
+                };
+                let shift = to_due - from_due;
+                task.due_at = Some(to_due);
+                task.carry_over_count = task.carry_over_count.saturating_add(1);
+                task.reminder.remind_at = task.reminder.remind_at.map(|at| at + shift);
+                task.reminder.last_fired_at = None;
+                task.reminder.snoozed_until = None;
+                task.reminder.forced_dismissed = false;
+                task.reminder.repeat_fired_count = 0;
+                task.updated_at = now.timestamp();
+                entries.push(CarryOverEntry {
+                    at: now.timestamp(),
+                    task_id: task.id.clone(),
+                    title: task.title.clone(),
+                    from_due,
+                    to_due,
+                    count: task.carry_over_count,
+                });
+            }
+        });
+        settings.last_carry_over_at = Some(now.timestamp());
+        entries
+    }
+
+    /// Creates the future occurrences `spawn_ahead` asks for; returns how many were added.
+    pub fn materialize_spawn_ahead(&self, now: chrono::DateTime<Utc>) -> usize {
+        let mut tasks = self.write_tasks();
+        let planned = plan_spawn_ahead(&tasks, &self.read_settings().reminder_lead_minutes, now);
+        let count = planned.len();
+        tasks.extend(planned);
+        count
     }
 
     pub fn remove_task(&self, task_id: &str) {
-        let mut guard = self.lock_inner();
-        guard.tasks.retain(|task| task.id != task_id);
+        self.write_tasks().retain(|task| task.id != task_id);
+    }
+
+    /// Moves the listed tasks into the trash; returns how many were found.
+    pub fn move_to_trash(&self, task_ids: &[String], now: i64) -> usize {
+        let ids: HashSet<&str> = task_ids.iter().map(|id| id.as_str()).collect();
+        let mut tasks = self.write_tasks();
+        let mut trash = self.write_trash();
+        let mut deleted = Vec::new();
+        tasks.retain(|task| {
+            if ids.contains(task.id.as_str()) {
+                deleted.push(task.clone());
+                return false;
+            }
+            true
+        });
+        // A task deleted again after a sync brought it back replaces its older trash entry.
+        trash.retain(|entry| !deleted.iter().any(|task| task.id == entry.task.id));
+        let count = deleted.len();
+        trash.extend(deleted.into_iter().map(|task| TrashEntry {
+            task,
+            deleted_at: now,
+        }));
+        count
     }
 
-    pub fn remove_tasks(&self, task_ids: &[String]) {
-        let mut guard = self.lock_inner();
+    /// Moves the listed trash entries back to the task list (at the end; a task whose project
+    /// is gone lands in the inbox). Entries whose id is in use again stay in the trash.
+    pub fn restore_from_trash(&self, task_ids: &[String], now: i64) -> Vec<Task> {
         let ids: HashSet<&str> = task_ids.iter().map(|id| id.as_str()).collect();
-        guard.tasks.retain(|task| !ids.contains(task.id.as_str()));
+        let projects = self.read_projects();
+        let mut tasks = self.write_tasks();
+        let mut trash = self.write_trash();
+        let (picked, kept): (Vec<TrashEntry>, Vec<TrashEntry>) =
+            std::mem::take(&mut *trash).into_iter().partition(|entry| {
+                ids.contains(entry.task.id.as_str()) && !tasks.contains(&entry.task.id)
+            });
+        *trash = kept;
+        let mut restored: Vec<Task> = picked.into_iter().map(|entry| entry.task).collect();
+        normalize_tasks(&mut restored, &projects);
+        for task in &mut restored {
+            task.updated_at = now;
+        }
+        tasks.extend(restored.iter().cloned());
+        restored
+    }
+
+    /// Permanently drops the listed trash entries, or all of them for `None`; returns how many.
+    pub fn empty_trash(&self, task_ids: Option<&[String]>) -> usize {
+        let mut trash = self.write_trash();
+        let before = trash.len();
+        match task_ids {
+            Some(task_ids) => {
+                let ids: HashSet<&str> = task_ids.iter().map(|id| id.as_str()).collect();
+                trash.retain(|entry| !ids.contains(entry.task.id.as_str()));
+            }
+            None => trash.clear(),
+        }
+        before - trash.len()
+    }
+
+    /// Drops trash entries deleted before `cutoff`; returns how many.
+    pub fn purge_trash(&self, cutoff: i64) -> usize {
+        let mut trash = self.write_trash();
+        let before = trash.len();
+        trash.retain(|entry| entry.deleted_at >= cutoff);
+        before - trash.len()
+    }
+
+    /// Drops tasks that were written to the archive; one changed since it was read (reopened,
+    /// edited) stays. Returns how many were removed.
+    pub fn remove_archived(&self, archived: &[Task]) -> usize {
+        let archived: HashMap<&str, i64> = archived
+            .iter()
+            .map(|task| (task.id.as_str(), task.updated_at))
+            .collect();
+        let mut tasks = self.write_tasks();
+        let before = tasks.len();
+        tasks.retain(|task| archived.get(task.id.as_str()) != Some(&task.updated_at));
+        before - tasks.len()
+    }
+
+    /// Puts an archived task back at the end of the list (a task whose project is gone lands in
+    /// the inbox); `None` when its id is in use again.
+    pub fn restore_archived(&self, task: Task, now: i64) -> Option<Task> {
+        let projects = self.read_projects();
+        let mut tasks = self.write_tasks();
+        if tasks.contains(&task.id) {
+            return None;
+        }
+        let mut restored = vec![task];
+        normalize_tasks(&mut restored, &projects);
+        let mut task = restored.pop()?;
+        task.updated_at = now;
+        tasks.push(task.clone());
+        Some(task)
     }
 
     pub fn mark_reminder_fired(&self, task: &Task, at: i64) {
-        let mut guard = self.lock_inner();
-        if let Some(existing) = guard.tasks.iter_mut().find(|t| t.id == task.id) {
+        self.write_tasks().update(&task.id, |existing| {
             existing.reminder.last_fired_at = Some(at);
             existing.reminder.repeat_fired_count = existing
                 .reminder
                 .repeat_fired_count
                 .max(0)
                 .saturating_add(1);
+            if existing.reminder.kind == ReminderKind::Normal {
+                existing.reminder.ignored_count = existing.reminder.ignored_count.saturating_add(1);
+            }
             if let Some(snoozed_until) = existing.reminder.snoozed_until {
                 if snoozed_until <= at {
                     existing.reminder.snoozed_until = None;
                 }
             }
+        });
+    }
+
+    /// Records that the current firing of each task was covered by the email fallback.
+    pub fn mark_reminder_emailed(&self, task_ids: &[String]) {
+        let mut tasks = self.write_tasks();
+        for task_id in task_ids {
+            tasks.update(task_id, |task| {
+                task.reminder.email_sent_at = task.reminder.last_fired_at;
+            });
         }
     }
 
     pub fn settings(&self) -> Settings {
-        let guard = self.lock_inner();
-        guard.settings.clone()
+        self.read_settings().clone()
     }
 
     pub fn update_settings(&self, settings: Settings) {
-        let mut guard = self.lock_inner();
-        guard.settings = settings;
+        *self.write_settings() = settings;
     }
 }
 
+/// Each collection has its own lock so a long read of one (export, AI context) does not stall
+/// writers of another (reminder marking, settings).
+///
+/// Lock order: projects, then tasks, then trash, then tags, then settings. Methods that need more than one take them
+/// in that order and never call back into `AppState` while holding a guard.
 #[derive(Debug)]
 struct AppData {
-    tasks: Vec<Task>,
-    projects: Vec<Project>,
-    settings: Settings,
+    projects: RwLock<Vec<Project>>,
+    tasks: RwLock<TaskIndex>,
+    trash: RwLock<Vec<TrashEntry>>,
+    tags: RwLock<Vec<Tag>>,
+    settings: RwLock<Settings>,
+}
+
+// Prefer to keep the app bootable if a background task panicked while holding a lock.
+fn read_lock<'a, T>(lock: &'a RwLock<T>, name: &str) -> RwLockReadGuard<'a, T> {
+    lock.read().unwrap_or_else(|poisoned| {
+        log::warn!("state {name} lock poisoned; continuing with recovered guard");
+        poisoned.into_inner()
+    })
+}
+
+fn write_lock<'a, T>(lock: &'a RwLock<T>, name: &str) -> RwLockWriteGuard<'a, T> {
+    lock.write().unwrap_or_else(|poisoned| {
+        log::warn!("state {name} lock poisoned; continuing with recovered guard");
+        poisoned.into_inner()
+    })
 }
 
 #[cfg(test)]
@@ -325,7 +1025,7 @@ mod tests {
             id: id.to_string(),
             project_id: "inbox".to_string(),
             title: format!("task-{id}"),
-            due_at,
+            due_at: Some(due_at),
             important: false,
             completed: false,
             completed_at: None,
@@ -342,6 +1042,30 @@ mod tests {
                 ..ReminderConfig::default()
             },
             repeat: RepeatRule::None,
+            waiting_on: None,
+            waiting_since: None,
+            series_id: None,
+            pinned: false,
+            context: None,
+            comments: Vec::new(),
+            progress: Default::default(),
+            spawn_ahead: 0,
+            repeat_from_completion: false,
+            end_after_occurrences: None,
+            end_by_date: None,
+            occurrence_index: 0,
+            timezone: None,
+            escalate_after: None,
+            start_at: None,
+            someday: false,
+            carry_over_count: 0,
+            skip_carry_over: false,
+            color: None,
+            emoji: None,
+            estimate_minutes: None,
+            attachments: Vec::new(),
+            time_entries: Vec::new(),
+            blocked_by: Vec::new(),
         }
     }
 
@@ -425,6 +1149,114 @@ mod tests {
         assert!(!state.swap_sort_order("a", "missing", 1));
     }
 
+    #[test]
+    fn set_task_pinned_updates_only_on_change() {
+        let state = AppState::new(
+            vec![make_task("a", 1, 1, 10)],
+            Vec::new(),
+            Settings::default(),
+        );
+
+        let pinned = state.set_task_pinned("a", true, 50).unwrap();
+        assert!(pinned.pinned);
+        assert_eq!(pinned.updated_at, 50);
+        // Pinning again is a no-op and keeps the previous updated_at.
+        assert_eq!(state.set_task_pinned("a", true, 60).unwrap().updated_at, 50);
+        assert!(!state.set_task_pinned("a", false, 70).unwrap().pinned);
+        assert!(state.set_task_pinned("missing", true, 80).is_none());
+    }
+
+    #[test]
+    fn task_comments_append_and_delete() {
+        let state = AppState::new(
+            vec![make_task("a", 1, 1, 10)],
+            Vec::new(),
+            Settings::default(),
+        );
+
+        let first = state.add_task_comment("a", "one".to_string(), 50).unwrap();
+        let second = state.add_task_comment("a", "two".to_string(), 50).unwrap();
+        assert_ne!(first.id, second.id);
+        let task = state.tasks().into_iter().next().unwrap();
+        assert_eq!(task.comments, vec![first.clone(), second.clone()]);
+        assert_eq!(task.updated_at, 50);
+        assert!(state
+            .add_task_comment("missing", "x".to_string(), 60)
+            .is_none());
+
+        assert_eq!(state.delete_task_comment("a", &first.id, 70), Some(true));
+        assert_eq!(state.delete_task_comment("a", &first.id, 80), Some(false));
+        assert_eq!(state.delete_task_comment("missing", &second.id, 80), None);
+        let task = state.tasks().into_iter().next().unwrap();
+        assert_eq!(task.comments, vec![second]);
+        assert_eq!(task.updated_at, 70);
+    }
+
+    #[test]
+    fn materialize_spawn_ahead_adds_instances_once() {
+        let mut head = make_task("a", 1, 1, 10);
+        head.repeat = crate::models::RepeatRule::Daily {
+            workday_only: false,
+        };
+        head.spawn_ahead = 2;
+        let state = AppState::new(vec![head], Vec::new(), Settings::default());
+        let now = Utc::now();
+
+        assert_eq!(state.materialize_spawn_ahead(now), 2);
+        assert_eq!(state.materialize_spawn_ahead(now), 0);
+        let tasks = state.tasks();
+        assert_eq!(tasks.len(), 3);
+        assert!(tasks[1..]
+            .iter()
+            .all(|t| t.series_id.as_deref() == Some("a")));
+
+        // Completing the head finds its successor already materialized.
+        let completed = state.complete_task("a").unwrap();
+        let next = crate::repeat::build_next_repeat_task(
+            &completed,
+            crate::repeat::next_due_timestamp(completed.due_at.unwrap(), &completed.repeat),
+            &Default::default(),
+            now,
+        );
+        let (existing, spawned) = state.spawn_repeat_instance(next);
+        assert!(!spawned);
+        assert_eq!(existing.id, tasks[1].id);
+        assert_eq!(state.materialize_spawn_ahead(now), 1);
+    }
+
+    #[test]
+    fn spawn_repeat_instance_skips_open_duplicates_in_same_series() {
+        let state = AppState::new(Vec::new(), Vec::new(), Settings::default());
+        let mut next = make_task("a-1", 1, 1, 10);
+        next.series_id = Some("a".to_string());
+
+        let (added, spawned) = state.spawn_repeat_instance(next.clone());
+        assert!(spawned);
+        assert_eq!(added.id, "a-1");
+
+        let mut retry = next.clone();
+        retry.id = "a-2".to_string();
+        let (existing, spawned) = state.spawn_repeat_instance(retry.clone());
+        assert!(!spawned);
+        assert_eq!(existing.id, "a-1");
+        assert_eq!(state.tasks().len(), 1);
+
+        // Different due date, another series, or a completed match all spawn normally.
+        let mut later = retry.clone();
+        later.id = "a-later".to_string();
+        later.due_at = Some(99);
+        assert!(state.spawn_repeat_instance(later).1);
+        let mut other = retry.clone();
+        other.id = "b-1".to_string();
+        other.series_id = Some("b".to_string());
+        assert!(state.spawn_repeat_instance(other).1);
+        state.complete_task("a-1");
+        let mut after_done = retry;
+        after_done.id = "a-3".to_string();
+        assert!(state.spawn_repeat_instance(after_done).1);
+        assert_eq!(state.tasks().len(), 4);
+    }
+
     #[test]
     fn complete_remove_and_mark_reminder() {
         let mut task = make_task("a", 1, 1, 10);
@@ -449,13 +1281,50 @@ mod tests {
         // mark_reminder_fired on a missing task is a no-op.
         state.mark_reminder_fired(&make_task("missing", 1, 1, 1), 1);
 
-        // remove_task and remove_tasks.
+        // remove_task.
         state.add_task(make_task("b", 1, 1, 1));
-        state.add_task(make_task("c", 1, 1, 1));
         state.remove_task("b");
         assert!(state.tasks().iter().all(|t| t.id != "b"));
-        state.remove_tasks(&vec!["a".to_string(), "c".to_string()]);
-        assert!(state.tasks().is_empty());
+    }
+
+    #[test]
+    fn trash_moves_restores_empties_and_purges_tasks() {
+        let state = AppState::new(
+            vec![
+                make_task("a", 1, 1, 10),
+                make_task("b", 2, 2, 20),
+                make_task("c", 3, 3, 30),
+            ],
+            Vec::new(),
+            Settings::default(),
+        );
+        let ids = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(state.move_to_trash(&ids(&["a", "c", "missing"]), 100), 2);
+        assert_eq!(state.tasks().len(), 1);
+        assert_eq!(state.tasks_file().trash.len(), 2);
+
+        let mut orphan = make_task("d", 4, 4, 40);
+        orphan.project_id = "gone".to_string();
+        state.add_task(orphan);
+        state.move_to_trash(&ids(&["d"]), 200);
+        let restored = state.restore_from_trash(&ids(&["a", "d"]), 300);
+        assert_eq!(restored.len(), 2);
+        assert!(restored.iter().all(|t| t.updated_at == 300));
+        assert_eq!(restored[1].project_id, "inbox");
+        let trash: Vec<_> = state.trash().into_iter().map(|e| e.task.id).collect();
+        assert_eq!(trash, vec!["c"]);
+
+        // An id that is live again is not restored over the live task.
+        state.add_task(make_task("c", 5, 5, 50));
+        assert!(state.restore_from_trash(&ids(&["c"]), 400).is_empty());
+        assert_eq!(state.trash().len(), 1);
+
+        assert_eq!(state.purge_trash(100), 0);
+        assert_eq!(state.purge_trash(101), 1);
+        state.move_to_trash(&ids(&["a", "b"]), 500);
+        assert_eq!(state.empty_trash(Some(&ids(&["a"]))), 1);
+        assert_eq!(state.empty_trash(None), 1);
+        assert!(state.trash().is_empty());
     }
 
     #[test]
@@ -527,21 +1396,85 @@ mod tests {
     }
 
     #[test]
-    fn lock_inner_recovers_from_poisoned_mutex() {
+    fn locks_recover_from_poisoning() {
         let state = AppState::new(Vec::new(), Vec::new(), Settings::default());
 
         let inner = state.inner.clone();
         let handle = std::thread::spawn(move || {
-            let _guard = inner.lock().unwrap();
-            panic!("poison the mutex while holding the lock");
+            let _tasks = inner.tasks.write().unwrap();
+            let _projects = inner.projects.read().unwrap();
+            panic!("poison the locks while holding them");
         });
         let _ = handle.join();
 
-        // The mutex is now poisoned. Calls that lock it should keep working.
+        // The tasks lock is now poisoned. Calls that take it should keep working.
+        assert!(state.inner.tasks.is_poisoned());
         assert!(state.tasks().is_empty());
+        state.add_task(make_task("a", 1, 1, 10));
+        assert_eq!(state.snapshot().tasks.len(), 1);
         assert!(state.projects().iter().any(|p| p.id == "inbox"));
     }
 
+    #[test]
+    fn collections_lock_independently() {
+        let state = AppState::new(
+            vec![make_task("a", 1, 1, 10)],
+            Vec::new(),
+            Settings::default(),
+        );
+
+        // A long reader of settings and projects (export, AI context) does not block task writes.
+        let settings = state.inner.settings.read().unwrap();
+        let projects = state.inner.projects.read().unwrap();
+        let task = state.tasks()[0].clone();
+        state.mark_reminder_fired(&task, 20);
+        state.set_task_pinned("a", true, 21);
+        assert_eq!(state.tasks()[0].reminder.last_fired_at, Some(20));
+        drop(projects);
+        drop(settings);
+
+        // Task readers do not block settings writes either.
+        let tasks = state.inner.tasks.read().unwrap();
+        state.update_settings(Settings {
+            theme: "dark".to_string(),
+            ..Settings::default()
+        });
+        assert_eq!(state.settings().theme, "dark");
+        drop(tasks);
+    }
+
+    #[test]
+    fn concurrent_multi_collection_mutators_do_not_deadlock() {
+        let state = AppState::new(Vec::new(), Vec::new(), Settings::default());
+        let handles: Vec<_> = (0..4)
+            .map(|worker| {
+                let state = state.clone();
+                std::thread::spawn(move || {
+                    for round in 0..200 {
+                        let id = format!("t{worker}-{round}");
+                        match round % 5 {
+                            0 => state.add_task(make_task(&id, 1, 1, 10)),
+                            1 => state.replace_projects(state.projects()),
+                            2 => {
+                                state.carry_over_tasks(chrono::Local::now());
+                            }
+                            3 => state.remove_project("missing"),
+                            _ => {
+                                state.materialize_spawn_ahead(Utc::now());
+                            }
+                        }
+                        let snapshot = state.snapshot();
+                        assert!(snapshot.projects.iter().any(|p| p.id == "inbox"));
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(state.tasks().len(), 4 * 40);
+    }
+
     #[test]
     fn mark_reminder_fired_clears_snoozed_until_when_due_or_past() {
         let mut task = make_task("a", 1, 1, 10);
@@ -564,6 +1497,62 @@ mod tests {
         assert_eq!(refreshed.reminder.snoozed_until, Some(200));
     }
 
+    #[test]
+    fn mark_reminder_emailed_copies_last_fired_at() {
+        let task = make_task("a", 1, 1, 10);
+        let other = make_task("b", 1, 1, 10);
+        let state = AppState::new(vec![task.clone(), other], Vec::new(), Settings::default());
+
+        state.mark_reminder_fired(&task, 100);
+        state.mark_reminder_emailed(&["a".to_string(), "missing".to_string()]);
+        let tasks = state.tasks();
+        let a = tasks.iter().find(|t| t.id == "a").unwrap();
+        let b = tasks.iter().find(|t| t.id == "b").unwrap();
+        assert_eq!(a.reminder.email_sent_at, Some(100));
+        assert_eq!(b.reminder.email_sent_at, None);
+    }
+
+    #[test]
+    fn carry_over_tasks_moves_yesterdays_open_tasks_to_today() {
+        use chrono::TimeZone;
+        let yesterday = chrono::Local
+            .with_ymd_and_hms(2026, 3, 10, 14, 0, 0)
+            .unwrap()
+            .timestamp();
+        let now = chrono::Local
+            .with_ymd_and_hms(2026, 3, 11, 0, 1, 0)
+            .unwrap();
+        let mut carried = make_task("a", 1, 1, yesterday);
+        carried.reminder.remind_at = Some(yesterday - 600);
+        carried.reminder.last_fired_at = Some(yesterday - 600);
+        let mut opted_out = make_task("b", 1, 2, yesterday);
+        opted_out.skip_carry_over = true;
+        let state = AppState::new(vec![carried, opted_out], Vec::new(), Settings::default());
+
+        let entries = state.carry_over_tasks(now);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].task_id, "a");
+        assert_eq!(entries[0].to_due - entries[0].from_due, 86_400);
+        assert_eq!(state.settings().last_carry_over_at, Some(now.timestamp()));
+
+        let tasks = state.tasks();
+        let a = tasks.iter().find(|t| t.id == "a").unwrap();
+        assert_eq!(a.due_at, Some(entries[0].to_due));
+        // Loaded as a "10 minutes before" spec, which moves with the due time.
+        assert_eq!(
+            a.reminder.targets(a.due_at, &Default::default()),
+            vec![entries[0].to_due - 600]
+        );
+        assert_eq!(a.reminder.last_fired_at, None);
+        assert_eq!(a.carry_over_count, 1);
+        let b = tasks.iter().find(|t| t.id == "b").unwrap();
+        assert_eq!(b.due_at, Some(yesterday));
+        assert_eq!(b.carry_over_count, 0);
+
+        // Same day again: the window starts today, nothing left to carry.
+        assert!(state.carry_over_tasks(now).is_empty());
+    }
+
     #[test]
     fn update_project_is_noop_when_id_is_missing() {
         let state = AppState::new(Vec::new(), Vec::new(), Settings::default());
@@ -577,10 +1566,44 @@ mod tests {
             created_at: 1,
             updated_at: 1,
             sample_tag: None,
+            reminders_muted: false,
+            muted_until: None,
+            notification_sound: None,
+            color: None,
+            icon: None,
+            parent_id: None,
         });
 
         let after = state.projects();
         assert_eq!(after.len(), before.len());
         assert!(after.iter().any(|p| p.id == "inbox"));
     }
+
+    #[test]
+    fn undo_and_redo_follow_recorded_actions_and_reset_on_replace() {
+        let state = AppState::new(
+            vec![make_task("a", 1, 1, 10), make_task("b", 2, 2, 20)],
+            Vec::new(),
+            Settings::default(),
+        );
+        assert!(state.undo_last().is_none());
+
+        let before = state.tasks_file();
+        state.remove_task("a");
+        state.record_undo("delete_task", &before);
+        assert_eq!(state.undo_status().undo.unwrap().action, "delete_task");
+
+        let undone = state.undo_last().unwrap();
+        assert_eq!((undone.action.as_str(), undone.tasks), ("delete_task", 1));
+        let ids: Vec<_> = state.tasks().into_iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+        assert!(state.undo_status().undo.is_none());
+
+        state.redo_last().unwrap();
+        assert_eq!(state.tasks().len(), 1);
+        assert!(state.redo_last().is_none());
+
+        state.replace_tasks(state.tasks());
+        assert!(state.undo_status().undo.is_none());
+    }
 }
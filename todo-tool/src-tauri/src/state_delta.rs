@@ -0,0 +1,192 @@
+//! `state_delta` events: after a change the windows get only the tasks that changed or went
+//! away (plus projects/settings when those changed) instead of the whole state. Each delta is
+//! numbered; a window that sees a gap reloads the full state with `load_state`, whose payload
+//! carries the number of the latest delta it already includes.
+
+use std::collections::HashMap;
+
+use crate::events::StateDelta;
+use crate::models::{Project, Settings, Task};
+use crate::state::AppStateSnapshot;
+
+/// What the windows were last sent, as serialized bodies so any field change shows up.
+#[derive(Debug, Default)]
+pub struct DeltaTracker {
+    seq: u64,
+    tasks: HashMap<String, String>,
+    projects: String,
+    settings: String,
+}
+
+fn body<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+impl DeltaTracker {
+    /// Starts from data the windows load on their own, so the first delta is not everything.
+    pub fn primed(snapshot: &AppStateSnapshot) -> Self {
+        let mut tracker = Self::default();
+        tracker.diff(snapshot);
+        tracker.seq = 0;
+        tracker
+    }
+
+    /// Number of the latest delta handed out.
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// Changes since the previous delta, with the next number; `None` when nothing changed.
+    pub fn diff(&mut self, snapshot: &AppStateSnapshot) -> Option<StateDelta> {
+        let mut delta = StateDelta::default();
+        let mut seen = HashMap::with_capacity(snapshot.tasks.len());
+        for task in snapshot.tasks.iter() {
+            let body = body(task);
+            if self.tasks.get(&task.id) != Some(&body) {
+                delta.tasks_upserted.push(task.clone());
+            }
+            seen.insert(task.id.clone(), body);
+        }
+        delta.tasks_removed = self
+            .tasks
+            .keys()
+            .filter(|id| !seen.contains_key(*id))
+            .cloned()
+            .collect();
+        delta.tasks_removed.sort();
+        self.tasks = seen;
+        self.finish(delta, &snapshot.projects, &snapshot.settings)
+    }
+
+    /// `diff` for when the state knows which tasks it touched: each id with its current copy,
+    /// or `None` when it is gone. Other tasks are taken to be unchanged.
+    pub fn diff_changed(
+        &mut self,
+        changed: Vec<(String, Option<&Task>)>,
+        projects: &[Project],
+        settings: &Settings,
+    ) -> Option<StateDelta> {
+        let mut delta = StateDelta::default();
+        for (id, task) in changed {
+            match task {
+                Some(task) => {
+                    let body = body(task);
+                    if self.tasks.get(&id) != Some(&body) {
+                        delta.tasks_upserted.push(task.clone());
+                        self.tasks.insert(id, body);
+                    }
+                }
+                None => {
+                    if self.tasks.remove(&id).is_some() {
+                        delta.tasks_removed.push(id);
+                    }
+                }
+            }
+        }
+        delta.tasks_removed.sort();
+        self.finish(delta, projects, settings)
+    }
+
+    fn finish(
+        &mut self,
+        mut delta: StateDelta,
+        projects: &[Project],
+        settings: &Settings,
+    ) -> Option<StateDelta> {
+        let projects_body = body(&projects);
+        if projects_body != self.projects {
+            delta.projects_changed = Some(projects.to_vec());
+            self.projects = projects_body;
+        }
+        let settings_body = body(settings);
+        if settings_body != self.settings {
+            delta.settings_changed = Some(settings.clone());
+            self.settings = settings_body;
+        }
+
+        if delta.tasks_upserted.is_empty()
+            && delta.tasks_removed.is_empty()
+            && delta.projects_changed.is_none()
+            && delta.settings_changed.is_none()
+        {
+            return None;
+        }
+        self.seq += 1;
+        delta.seq = self.seq;
+        Some(delta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_support, Project, Settings, Task};
+
+    fn task(id: &str, title: &str) -> Task {
+        test_support::task(id).title(title)
+    }
+
+    fn snapshot(tasks: Vec<Task>) -> AppStateSnapshot {
+        AppStateSnapshot {
+            tasks: std::sync::Arc::new(tasks),
+            projects: serde_json::from_value::<Vec<Project>>(serde_json::json!([
+                { "id": "inbox", "name": "Inbox" }
+            ]))
+            .unwrap(),
+            settings: Settings::default(),
+        }
+    }
+
+    #[test]
+    fn deltas_carry_only_what_changed_and_are_numbered() {
+        let mut tracker = DeltaTracker::primed(&snapshot(vec![task("a", "A"), task("b", "B")]));
+        assert_eq!(tracker.seq(), 0);
+        assert!(tracker
+            .diff(&snapshot(vec![task("a", "A"), task("b", "B")]))
+            .is_none());
+
+        let delta = tracker
+            .diff(&snapshot(vec![task("a", "A2"), task("c", "C")]))
+            .unwrap();
+        assert_eq!(delta.seq, 1);
+        let upserted: Vec<&str> = delta
+            .tasks_upserted
+            .iter()
+            .map(|task| task.id.as_str())
+            .collect();
+        assert_eq!(upserted, vec!["a", "c"]);
+        assert_eq!(delta.tasks_removed, vec!["b"]);
+        assert!(delta.projects_changed.is_none() && delta.settings_changed.is_none());
+
+        let mut changed = snapshot(vec![task("a", "A2"), task("c", "C")]);
+        changed.settings.language = "zh".to_string();
+        changed.projects[0].name = "Renamed".to_string();
+        let delta = tracker.diff(&changed).unwrap();
+        assert_eq!(delta.seq, 2);
+        assert!(delta.tasks_upserted.is_empty() && delta.tasks_removed.is_empty());
+        assert_eq!(delta.projects_changed.unwrap()[0].name, "Renamed");
+        assert_eq!(delta.settings_changed.unwrap().language, "zh");
+        assert_eq!(tracker.seq(), 2);
+    }
+
+    #[test]
+    fn app_state_numbers_its_deltas_and_reports_the_latest_in_full_payloads() {
+        let state =
+            crate::state::AppState::new(vec![task("a", "A")], Vec::new(), Settings::default());
+        assert!(state.take_delta().is_none());
+        state.add_task(task("b", "B"));
+        let delta = state.take_delta().unwrap();
+        assert_eq!(delta.seq, 1);
+        assert_eq!(delta.tasks_upserted[0].id, "b");
+        let payload = state.state_payload();
+        assert_eq!((payload.seq, payload.tasks.len()), (1, 2));
+
+        // Only the touched ids are looked at; an update that changes nothing sends nothing.
+        state.update_task(task("b", "B"));
+        assert!(state.take_delta().is_none());
+        state.remove_task("a");
+        let delta = state.take_delta().unwrap();
+        assert_eq!((delta.seq, delta.tasks_removed), (2, vec!["a".to_string()]));
+        assert!(delta.tasks_upserted.is_empty());
+    }
+}
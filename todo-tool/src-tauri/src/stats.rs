@@ -0,0 +1,667 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone};
+
+use crate::focus::FocusSession;
+use crate::models::{Project, Task, Timestamp};
+use crate::time_tracking::{
+    report_time, tracked_seconds, tracked_seconds_between, TimeReportGroup,
+};
+use crate::year_review::{longest_streak, Streak};
+
+/// Default `get_statistics` window when `from` is not given.
+pub const DEFAULT_STATISTICS_DAYS: i64 = 30;
+
+/// How many days of history a burndown covers, ending today.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BurndownRange {
+    Week,
+    #[default]
+    Month,
+    Quarter,
+}
+
+impl BurndownRange {
+    pub fn days(self) -> i64 {
+        match self {
+            BurndownRange::Week => 7,
+            BurndownRange::Month => 30,
+            BurndownRange::Quarter => 90,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct BurndownPoint {
+    /// Local date, `YYYY-MM-DD`.
+    pub date: String,
+    /// Tasks still open at the end of the day.
+    pub open: usize,
+    /// Tasks completed during the day.
+    pub completed: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Burndown {
+    pub project_id: Option<String>,
+    pub range: BurndownRange,
+    /// Oldest day first; the last point is today.
+    pub points: Vec<BurndownPoint>,
+    pub remaining: usize,
+    /// Average completions per day over the range.
+    pub completion_rate: f64,
+    /// Days until `remaining` reaches zero at `completion_rate`; `None` when nothing gets done.
+    pub forecast_days: Option<i64>,
+    pub forecast_date: Option<String>,
+}
+
+fn day_start(date: NaiveDate) -> Timestamp {
+    let midnight = date.and_hms_opt(0, 0, 0).expect("midnight is valid");
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|value| value.timestamp())
+        // Midnight skipped by DST: the day starts one hour later.
+        .unwrap_or_else(|| {
+            Local
+                .from_local_datetime(&(midnight + Duration::hours(1)))
+                .earliest()
+                .map(|value| value.timestamp())
+                .unwrap_or(Timestamp::MIN)
+        })
+}
+
+/// Completion time used for the charts; tasks completed before `completed_at` was recorded fall
+/// back to their last update.
+pub fn completed_at(task: &Task) -> Option<Timestamp> {
+    task.completed
+        .then(|| task.completed_at.unwrap_or(task.updated_at))
+}
+
+/// Local calendar day of `ts`; `None` when the timestamp is out of chrono's range.
+pub fn local_date(ts: Timestamp) -> Option<NaiveDate> {
+    Local
+        .timestamp_opt(ts, 0)
+        .single()
+        .map(|value| value.date_naive())
+}
+
+/// Daily open/completed counts for `project_id` (all projects when `None`) plus a linear
+/// forecast of when the open tasks run out. Someday tasks are not committed work and are left out.
+pub fn burndown(
+    tasks: &[Task],
+    project_id: Option<&str>,
+    range: BurndownRange,
+    now: DateTime<Local>,
+) -> Burndown {
+    let tasks: Vec<&Task> = tasks
+        .iter()
+        .filter(|task| !task.someday)
+        .filter(|task| project_id.is_none_or(|id| task.project_id == id))
+        .collect();
+    let today = now.date_naive();
+    let first = today - Duration::days(range.days() - 1);
+
+    let points: Vec<BurndownPoint> = (0..range.days())
+        .map(|offset| {
+            let date = first + Duration::days(offset);
+            let start = day_start(date);
+            let end = day_start(date + Duration::days(1));
+            let open = tasks
+                .iter()
+                .filter(|task| task.created_at < end)
+                .filter(|task| completed_at(task).is_none_or(|at| at >= end))
+                .count();
+            let completed = tasks
+                .iter()
+                .filter_map(|task| completed_at(task))
+                .filter(|at| (start..end).contains(at))
+                .count();
+            BurndownPoint {
+                date: date.format("%Y-%m-%d").to_string(),
+                open,
+                completed,
+            }
+        })
+        .collect();
+
+    let remaining = tasks.iter().filter(|task| !task.completed).count();
+    let done: usize = points.iter().map(|point| point.completed).sum();
+    let completion_rate = done as f64 / range.days() as f64;
+    let forecast_days = if remaining == 0 {
+        Some(0)
+    } else if completion_rate > 0.0 {
+        Some((remaining as f64 / completion_rate).ceil() as i64)
+    } else {
+        None
+    };
+    let forecast_date = forecast_days
+        .and_then(|days| today.checked_add_signed(Duration::try_days(days)?))
+        .map(|date| date.format("%Y-%m-%d").to_string());
+
+    Burndown {
+        project_id: project_id.map(str::to_string),
+        range,
+        points,
+        remaining,
+        completion_rate,
+        forecast_days,
+        forecast_date,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ProjectThroughput {
+    pub project_id: String,
+    /// The id again when the project has been deleted.
+    pub name: String,
+    pub created: usize,
+    pub completed: usize,
+    /// Open now, whatever the range.
+    pub open: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct WeekdayStat {
+    /// ISO weekday: 1 = Monday ... 7 = Sunday.
+    pub weekday: u32,
+    pub completed: usize,
+}
+
+/// Productivity over `[from, to)` for the dashboard.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Statistics {
+    pub from: Timestamp,
+    pub to: Timestamp,
+    pub created: usize,
+    pub completed: usize,
+    /// Share of the tasks due in the range that are completed; `None` when nothing was due.
+    pub completion_rate: Option<f64>,
+    /// Consecutive days with a completion, ending on the range's last day (or the day before,
+    /// so a streak is not lost while today is still young).
+    pub current_streak: usize,
+    pub longest_streak: Streak,
+    /// Most completions first.
+    pub projects: Vec<ProjectThroughput>,
+    /// Average minutes past `due_at` for dated tasks completed in the range; on-time ones
+    /// count as 0. `None` when none were dated.
+    pub average_delay_minutes: Option<f64>,
+    /// How many of those were completed after their due time.
+    pub completed_late: usize,
+    /// Always seven entries, Monday first.
+    pub weekdays: Vec<WeekdayStat>,
+    /// Weekdays with the most completions (several on a tie; empty without completions).
+    pub busiest_weekdays: Vec<u32>,
+}
+
+/// The last `DEFAULT_STATISTICS_DAYS` local days, today included.
+pub fn default_statistics_range(now: DateTime<Local>) -> (Timestamp, Timestamp) {
+    let today = now.date_naive();
+    (
+        day_start(today - Duration::days(DEFAULT_STATISTICS_DAYS - 1)),
+        day_start(today + Duration::days(1)),
+    )
+}
+
+fn throughput<'a>(
+    by_project: &'a mut HashMap<String, ProjectThroughput>,
+    task: &Task,
+    projects: &[Project],
+) -> &'a mut ProjectThroughput {
+    by_project
+        .entry(task.project_id.clone())
+        .or_insert_with(|| ProjectThroughput {
+            project_id: task.project_id.clone(),
+            name: projects
+                .iter()
+                .find(|project| project.id == task.project_id)
+                .map_or_else(|| task.project_id.clone(), |project| project.name.clone()),
+            created: 0,
+            completed: 0,
+            open: 0,
+        })
+}
+
+/// Aggregates completions over `[from, to)`. Someday tasks are only counted once completed:
+/// until then they are not committed work.
+pub fn statistics(
+    tasks: &[Task],
+    projects: &[Project],
+    from: Timestamp,
+    to: Timestamp,
+) -> Statistics {
+    let in_range = |ts: Timestamp| (from..to).contains(&ts);
+    let mut days: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+    let mut weekdays = [0usize; 7];
+    let mut by_project: HashMap<String, ProjectThroughput> = HashMap::new();
+    let mut created = 0;
+    let mut completed = 0;
+    let mut due = 0;
+    let mut due_done = 0;
+    let mut delays: Vec<Timestamp> = Vec::new();
+
+    for task in tasks {
+        if !task.completed && !task.someday {
+            throughput(&mut by_project, task, projects).open += 1;
+            if task.due_at.is_some_and(in_range) {
+                due += 1;
+            }
+        }
+        if in_range(task.created_at) {
+            created += 1;
+            throughput(&mut by_project, task, projects).created += 1;
+        }
+        let Some(done) = completed_at(task).filter(|at| in_range(*at)) else {
+            // Completed outside the range, but it was due inside it.
+            if task.completed && task.due_at.is_some_and(in_range) {
+                due += 1;
+                due_done += 1;
+            }
+            continue;
+        };
+        completed += 1;
+        throughput(&mut by_project, task, projects).completed += 1;
+        if task.due_at.is_some_and(in_range) {
+            due += 1;
+            due_done += 1;
+        }
+        if let Some(due_at) = task.due_at {
+            delays.push((done - due_at).max(0));
+        }
+        if let Some(date) = local_date(done) {
+            *days.entry(date).or_default() += 1;
+            weekdays[date.weekday().num_days_from_monday() as usize] += 1;
+        }
+    }
+
+    let last_day = local_date(to - 1);
+    let current_streak = last_day
+        .map(|last| {
+            let run_from = |start: NaiveDate| {
+                (0..)
+                    .map(|offset| start - Duration::days(offset))
+                    .take_while(|day| days.contains_key(day))
+                    .count()
+            };
+            match run_from(last) {
+                0 => run_from(last - Duration::days(1)),
+                run => run,
+            }
+        })
+        .unwrap_or(0);
+
+    let mut projects: Vec<ProjectThroughput> = by_project
+        .into_values()
+        .filter(|p| p.created > 0 || p.completed > 0)
+        .collect();
+    projects.sort_by(|a, b| {
+        b.completed
+            .cmp(&a.completed)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    let busiest = weekdays.iter().copied().max().unwrap_or(0);
+    Statistics {
+        from,
+        to,
+        created,
+        completed,
+        completion_rate: (due > 0).then(|| due_done as f64 / due as f64),
+        current_streak,
+        longest_streak: longest_streak(&days),
+        projects,
+        average_delay_minutes: (!delays.is_empty())
+            .then(|| delays.iter().sum::<Timestamp>() as f64 / delays.len() as f64 / 60.0),
+        completed_late: delays.iter().filter(|delay| **delay > 0).count(),
+        weekdays: (1..=7)
+            .map(|weekday| WeekdayStat {
+                weekday,
+                completed: weekdays[weekday as usize - 1],
+            })
+            .collect(),
+        busiest_weekdays: if busiest == 0 {
+            Vec::new()
+        } else {
+            (1..=7)
+                .filter(|weekday| weekdays[*weekday as usize - 1] == busiest)
+                .collect()
+        },
+    }
+}
+
+/// Time spent on one day, project or tag.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FocusTimeRow {
+    /// Local `YYYY-MM-DD`, a project id, or a tag (`""` for untagged tasks).
+    pub key: String,
+    /// From task timers (`time_entries`).
+    pub tracked_seconds: i64,
+    /// From focus sessions, pauses left out.
+    pub focus_seconds: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct EstimateActual {
+    pub task_id: String,
+    pub title: String,
+    pub estimate_minutes: u32,
+    /// Everything tracked and focused on the task so far, not only inside the range.
+    pub actual_minutes: i64,
+}
+
+/// Time tracking and focus sessions over `[from, to)`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FocusStats {
+    pub from: Timestamp,
+    pub to: Timestamp,
+    pub tracked_seconds: i64,
+    pub focus_seconds: i64,
+    /// Sessions started in the range.
+    pub focus_sessions: usize,
+    /// Of those, the ones that ran their full length.
+    pub finished_sessions: usize,
+    /// Oldest first.
+    pub days: Vec<FocusTimeRow>,
+    /// Most time first.
+    pub projects: Vec<FocusTimeRow>,
+    /// Most time first; a task with several tags counts for each of them.
+    pub tags: Vec<FocusTimeRow>,
+    /// Estimated tasks worked on in the range, furthest over their estimate first.
+    pub estimates: Vec<EstimateActual>,
+}
+
+fn add_time<'a>(rows: &'a mut BTreeMap<String, FocusTimeRow>, key: &str) -> &'a mut FocusTimeRow {
+    rows.entry(key.to_string()).or_insert_with(|| FocusTimeRow {
+        key: key.to_string(),
+        ..FocusTimeRow::default()
+    })
+}
+
+fn by_most_time(rows: BTreeMap<String, FocusTimeRow>) -> Vec<FocusTimeRow> {
+    let mut rows: Vec<FocusTimeRow> = rows.into_values().collect();
+    rows.sort_by_key(|row| std::cmp::Reverse(row.tracked_seconds + row.focus_seconds));
+    rows
+}
+
+/// Joins timer entries and focus sessions with their tasks. A session counts on the local day
+/// it started; sessions of deleted tasks still count per day but not per project or tag.
+pub fn focus_stats(
+    tasks: &[Task],
+    sessions: &[FocusSession],
+    from: Timestamp,
+    to: Timestamp,
+    now: Timestamp,
+) -> FocusStats {
+    let mut days: BTreeMap<String, FocusTimeRow> = BTreeMap::new();
+    let mut projects: BTreeMap<String, FocusTimeRow> = BTreeMap::new();
+    let mut tags: BTreeMap<String, FocusTimeRow> = BTreeMap::new();
+    for (group, rows) in [
+        (TimeReportGroup::Day, &mut days),
+        (TimeReportGroup::Project, &mut projects),
+        (TimeReportGroup::Tag, &mut tags),
+    ] {
+        for row in report_time(tasks, group, Some(from), Some(to), now) {
+            add_time(rows, &row.key).tracked_seconds += row.seconds;
+        }
+    }
+
+    let by_id: HashMap<&str, &Task> = tasks.iter().map(|task| (task.id.as_str(), task)).collect();
+    let mut focused: HashMap<&str, i64> = HashMap::new();
+    let mut worked: HashSet<&str> = HashSet::new();
+    let (mut focus_seconds, mut focus_sessions, mut finished_sessions) = (0, 0, 0);
+    for session in sessions {
+        *focused.entry(session.task_id.as_str()).or_default() += session.focused_seconds;
+        if !(from..to).contains(&session.started_at) {
+            continue;
+        }
+        worked.insert(session.task_id.as_str());
+        focus_seconds += session.focused_seconds;
+        focus_sessions += 1;
+        finished_sessions += usize::from(session.finished);
+        if let Some(date) = local_date(session.started_at) {
+            add_time(&mut days, &date.format("%Y-%m-%d").to_string()).focus_seconds +=
+                session.focused_seconds;
+        }
+        let Some(task) = by_id.get(session.task_id.as_str()) else {
+            continue;
+        };
+        add_time(&mut projects, &task.project_id).focus_seconds += session.focused_seconds;
+        if task.tags.is_empty() {
+            add_time(&mut tags, "").focus_seconds += session.focused_seconds;
+        }
+        for tag in &task.tags {
+            add_time(&mut tags, tag).focus_seconds += session.focused_seconds;
+        }
+    }
+
+    let mut estimates: Vec<EstimateActual> = tasks
+        .iter()
+        .filter(|task| {
+            worked.contains(task.id.as_str()) || tracked_seconds_between(task, from, to, now) > 0
+        })
+        .filter_map(|task| {
+            let spent =
+                tracked_seconds(task, now) + focused.get(task.id.as_str()).copied().unwrap_or(0);
+            Some(EstimateActual {
+                task_id: task.id.clone(),
+                title: task.title.clone(),
+                estimate_minutes: task.estimate_minutes?,
+                actual_minutes: spent / 60,
+            })
+        })
+        .collect();
+    estimates
+        .sort_by_key(|row| std::cmp::Reverse(row.actual_minutes - i64::from(row.estimate_minutes)));
+
+    FocusStats {
+        from,
+        to,
+        tracked_seconds: days.values().map(|row| row.tracked_seconds).sum(),
+        focus_seconds,
+        focus_sessions,
+        finished_sessions,
+        days: days.into_values().collect(),
+        projects: by_most_time(projects),
+        tags: by_most_time(tags),
+        estimates,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+
+    fn at(day: u32, hour: u32) -> Timestamp {
+        Local
+            .with_ymd_and_hms(2026, 3, day, hour, 0, 0)
+            .unwrap()
+            .timestamp()
+    }
+
+    fn task(id: &str, project_id: &str, created_at: Timestamp, done: Option<Timestamp>) -> Task {
+        test_support::task(id)
+            .project(project_id)
+            .created(created_at)
+            .done_at(done)
+    }
+
+    #[test]
+    fn burndown_counts_open_and_completed_per_day() {
+        let now = Local.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap();
+        let tasks = vec![
+            task("a", "p", at(1, 9), Some(at(8, 10))),
+            task("b", "p", at(1, 9), Some(at(9, 10))),
+            task("c", "p", at(9, 9), None),
+            task("d", "p", at(9, 9), None),
+            task("other", "q", at(1, 9), None),
+        ];
+        let mut someday = task("s", "p", at(1, 9), None);
+        someday.someday = true;
+        let mut tasks = tasks;
+        tasks.push(someday);
+
+        let report = burndown(&tasks, Some("p"), BurndownRange::Week, now);
+        assert_eq!(report.points.len(), 7);
+        assert_eq!(report.points[0].date, "2026-03-04");
+        let last_three: Vec<(usize, usize)> = report.points[4..]
+            .iter()
+            .map(|point| (point.open, point.completed))
+            .collect();
+        assert_eq!(last_three, vec![(1, 1), (2, 1), (2, 0)]);
+        assert_eq!(report.remaining, 2);
+        assert!((report.completion_rate - 2.0 / 7.0).abs() < 1e-9);
+        assert_eq!(report.forecast_days, Some(7));
+        assert_eq!(report.forecast_date.as_deref(), Some("2026-03-17"));
+
+        let all = burndown(&tasks, None, BurndownRange::Week, now);
+        assert_eq!(all.remaining, 3);
+    }
+
+    #[test]
+    fn burndown_forecast_handles_no_progress_and_finished_projects() {
+        let now = Local.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap();
+        let stalled = burndown(
+            &[task("a", "p", at(1, 9), None)],
+            Some("p"),
+            BurndownRange::Month,
+            now,
+        );
+        assert_eq!(stalled.points.len(), 30);
+        assert_eq!(stalled.forecast_days, None);
+        assert_eq!(stalled.forecast_date, None);
+
+        let mut legacy = task("b", "p", at(1, 9), None);
+        legacy.completed = true;
+        legacy.updated_at = at(10, 8);
+        let finished = burndown(&[legacy], Some("p"), BurndownRange::Quarter, now);
+        assert_eq!(finished.points.last().unwrap().completed, 1);
+        assert_eq!(finished.forecast_days, Some(0));
+        assert_eq!(finished.forecast_date.as_deref(), Some("2026-03-10"));
+    }
+
+    #[test]
+    fn statistics_cover_rates_streaks_projects_delays_and_weekdays() {
+        let late = Task {
+            due_at: Some(at(8, 9)),
+            ..task("late", "p", at(1, 9), Some(at(9, 10)))
+        };
+        let mut someday = task("someday", "p", 0, None);
+        someday.someday = true;
+        let tasks = vec![
+            Task {
+                due_at: Some(at(2, 9)),
+                ..task("on-time", "p", at(1, 9), Some(at(2, 8)))
+            },
+            late,
+            task("undated", "q", at(9, 9), Some(at(10, 10))),
+            Task {
+                due_at: Some(at(5, 9)),
+                ..task("overdue", "p", at(1, 9), None)
+            },
+            task("old-open", "q", 0, None),
+            someday,
+            // Due in the range, completed after it.
+            Task {
+                due_at: Some(at(3, 9)),
+                ..task("done-later", "p", 0, Some(at(20, 9)))
+            },
+        ];
+        let projects: Vec<Project> =
+            vec![serde_json::from_str(r#"{"id":"p","name":"Plan"}"#).unwrap()];
+        let stats = statistics(&tasks, &projects, at(1, 0), at(11, 0));
+
+        assert_eq!((stats.created, stats.completed), (4, 3));
+        assert_eq!(stats.completion_rate, Some(0.75));
+        assert_eq!(stats.current_streak, 2);
+        assert_eq!(stats.longest_streak.days, 2);
+        assert_eq!(stats.longest_streak.start.as_deref(), Some("2026-03-09"));
+        assert_eq!(stats.average_delay_minutes, Some(750.0));
+        assert_eq!(stats.completed_late, 1);
+        let throughput: Vec<_> = stats
+            .projects
+            .iter()
+            .map(|p| (p.name.as_str(), p.created, p.completed, p.open))
+            .collect();
+        assert_eq!(throughput, vec![("Plan", 3, 2, 1), ("q", 1, 1, 1)]);
+        // 2026-03-02 and 03-09 are Mondays, 03-10 a Tuesday.
+        assert_eq!(stats.weekdays.len(), 7);
+        assert_eq!(stats.weekdays[0].completed, 2);
+        assert_eq!(stats.weekdays[1].completed, 1);
+        assert_eq!(stats.busiest_weekdays, vec![1]);
+
+        let empty = statistics(&[], &[], at(1, 0), at(11, 0));
+        assert_eq!(empty.completion_rate, None);
+        assert_eq!(empty.average_delay_minutes, None);
+        assert!(empty.busiest_weekdays.is_empty());
+    }
+
+    #[test]
+    fn focus_stats_join_timers_and_sessions_with_their_tasks() {
+        let entry = |started_at, ended_at| crate::models::TimeEntry {
+            started_at,
+            ended_at,
+        };
+        let session = |task_id: &str, started_at: Timestamp, seconds: i64| FocusSession {
+            task_id: task_id.to_string(),
+            started_at,
+            ended_at: started_at + seconds,
+            minutes: 25,
+            focused_seconds: seconds,
+            finished: seconds == 1500,
+        };
+        let mut write = task("write", "p", 0, None);
+        write.tags = vec!["deep".to_string(), "client".to_string()];
+        write.estimate_minutes = Some(60);
+        // 30 minutes before the range, 60 inside it.
+        write.time_entries = vec![
+            entry(at(1, 9) - 1800, Some(at(1, 9))),
+            entry(at(2, 9), Some(at(2, 10))),
+        ];
+        let mut call = task("call", "q", 0, None);
+        call.estimate_minutes = Some(30);
+        call.time_entries = vec![entry(at(3, 9), None)];
+        let mut idle = task("idle", "q", 0, None);
+        idle.estimate_minutes = Some(10);
+        let tasks = vec![write, call, idle];
+        let sessions = vec![
+            session("write", at(2, 14), 1500),
+            session("call", at(3, 8), 600),
+            session("gone", at(3, 11), 300),
+            session("idle", at(20, 9), 1500),
+        ];
+
+        let stats = focus_stats(&tasks, &sessions, at(1, 9), at(4, 0), at(3, 9) + 900);
+        assert_eq!((stats.tracked_seconds, stats.focus_seconds), (4500, 2400));
+        assert_eq!((stats.focus_sessions, stats.finished_sessions), (3, 1));
+        let days: Vec<_> = stats
+            .days
+            .iter()
+            .map(|row| (row.key.as_str(), row.tracked_seconds, row.focus_seconds))
+            .collect();
+        assert_eq!(
+            days,
+            vec![("2026-03-02", 3600, 1500), ("2026-03-03", 900, 900)]
+        );
+        let projects: Vec<_> = stats
+            .projects
+            .iter()
+            .map(|row| (row.key.as_str(), row.tracked_seconds, row.focus_seconds))
+            .collect();
+        assert_eq!(projects, vec![("p", 3600, 1500), ("q", 900, 600)]);
+        let tags: Vec<_> = stats.tags.iter().map(|row| row.key.as_str()).collect();
+        assert_eq!(tags, vec!["client", "deep", ""]);
+        let estimates: Vec<_> = stats
+            .estimates
+            .iter()
+            .map(|row| {
+                (
+                    row.task_id.as_str(),
+                    row.estimate_minutes,
+                    row.actual_minutes,
+                )
+            })
+            .collect();
+        assert_eq!(estimates, vec![("write", 60, 115), ("call", 30, 25)]);
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::HashSet;
+
+use crate::models::{ReminderConfig, RepeatRule, Step, Task, Timestamp};
+
+fn parent_of<'a>(steps: &'a [Step], id: &str) -> Option<&'a str> {
+    steps
+        .iter()
+        .find(|step| step.id == id)
+        .and_then(|step| step.parent_id.as_deref())
+}
+
+/// Clears `parent_id`s pointing at a missing step, at the step itself or around a cycle, so
+/// the steps always form a tree.
+pub fn normalize_step_parents(steps: &mut [Step]) {
+    let ids: HashSet<String> = steps.iter().map(|step| step.id.clone()).collect();
+    for step in steps.iter_mut() {
+        if step
+            .parent_id
+            .as_ref()
+            .is_some_and(|parent| *parent == step.id || !ids.contains(parent))
+        {
+            step.parent_id = None;
+        }
+    }
+    for i in 0..steps.len() {
+        let id = steps[i].id.clone();
+        let mut seen = HashSet::from([id.as_str()]);
+        let mut cursor = steps[i].parent_id.as_deref();
+        let mut in_cycle = false;
+        while let Some(parent) = cursor {
+            if parent == id {
+                in_cycle = true;
+                break;
+            }
+            if !seen.insert(parent) {
+                // A cycle further up; it is broken when one of its own steps comes round.
+                break;
+            }
+            cursor = parent_of(steps, parent);
+        }
+        if in_cycle {
+            steps[i].parent_id = None;
+        }
+    }
+}
+
+/// `step_id` and every step nested under it, at any depth.
+fn subtree_ids(steps: &[Step], step_id: &str) -> HashSet<String> {
+    let mut ids = HashSet::from([step_id.to_string()]);
+    loop {
+        let before = ids.len();
+        for step in steps {
+            if step
+                .parent_id
+                .as_ref()
+                .is_some_and(|parent| ids.contains(parent))
+            {
+                ids.insert(step.id.clone());
+            }
+        }
+        if ids.len() == before {
+            return ids;
+        }
+    }
+}
+
+/// `base`, or `base-2`, `base-3`... whichever `taken` does not reject first.
+pub fn unique_id(base: String, taken: impl Fn(&str) -> bool) -> String {
+    if !taken(&base) {
+        return base;
+    }
+    (2..)
+        .map(|n| format!("{base}-{n}"))
+        .find(|id| !taken(id))
+        .expect("unbounded suffix search")
+}
+
+/// Moves `step_id` and its nested steps out of `task` into a new task `new_id`. The step's
+/// title and completion carry over; project, due date, tags and the other classification
+/// fields come from `task`. Reminder, repeat and history (comments, attachments, time) start
+/// empty. `None` when `task` has no such step.
+pub fn promote_step(
+    task: &mut Task,
+    step_id: &str,
+    new_id: String,
+    now: Timestamp,
+) -> Option<Task> {
+    let step = task.steps.iter().find(|step| step.id == step_id)?.clone();
+    let moved = subtree_ids(&task.steps, step_id);
+    let (taken, kept): (Vec<Step>, Vec<Step>) = task
+        .steps
+        .drain(..)
+        .partition(|step| moved.contains(&step.id));
+    task.steps = kept;
+    task.updated_at = now;
+    task.refresh_progress();
+
+    let steps = taken
+        .into_iter()
+        .filter(|child| child.id != step_id)
+        .map(|child| Step {
+            parent_id: child.parent_id.filter(|parent| parent != step_id),
+            ..child
+        })
+        .collect();
+    let mut promoted = Task {
+        id: new_id,
+        title: step.title,
+        completed: step.completed,
+        completed_at: step.completed_at,
+        created_at: now,
+        updated_at: now,
+        sort_order: now * 1000,
+        notes: None,
+        steps,
+        sample_tag: None,
+        reminder: ReminderConfig::default(),
+        repeat: RepeatRule::None,
+        waiting_on: None,
+        waiting_since: None,
+        series_id: None,
+        pinned: false,
+        carry_over_count: 0,
+        spawn_ahead: 0,
+        repeat_from_completion: false,
+        end_after_occurrences: None,
+        end_by_date: None,
+        occurrence_index: 0,
+        timezone: None,
+        escalate_after: None,
+        start_at: None,
+        estimate_minutes: None,
+        comments: Vec::new(),
+        attachments: Vec::new(),
+        time_entries: Vec::new(),
+        blocked_by: Vec::new(),
+        progress: Default::default(),
+        ..task.clone()
+    };
+    promoted.refresh_progress();
+    Some(promoted)
+}
+
+/// `task` as steps for `target`: one step titled like the task (nested under `parent_step_id`
+/// when given) with the task's own steps below it. Ids are prefixed with the task id and kept
+/// unique within `target`.
+pub fn task_to_steps(
+    task: &Task,
+    target: &Task,
+    parent_step_id: Option<String>,
+    now: Timestamp,
+) -> Vec<Step> {
+    let mut taken: HashSet<String> = target.steps.iter().map(|step| step.id.clone()).collect();
+    let mut claim = |base: String| {
+        let id = unique_id(base, |id| taken.contains(id));
+        taken.insert(id.clone());
+        id
+    };
+    let root_id = claim(task.id.clone());
+    let mut steps = vec![Step {
+        id: root_id.clone(),
+        title: task.title.clone(),
+        completed: task.completed,
+        created_at: now,
+        completed_at: task.completed_at,
+        parent_id: parent_step_id,
+    }];
+    let renamed: Vec<(String, String)> = task
+        .steps
+        .iter()
+        .map(|step| (step.id.clone(), claim(format!("{}-{}", task.id, step.id))))
+        .collect();
+    let new_id = |old: &str| {
+        renamed
+            .iter()
+            .find(|(from, _)| from == old)
+            .map(|(_, to)| to.clone())
+    };
+    for (step, (_, id)) in task.steps.iter().zip(&renamed) {
+        steps.push(Step {
+            id: id.clone(),
+            parent_id: Some(
+                step.parent_id
+                    .as_deref()
+                    .and_then(new_id)
+                    .unwrap_or_else(|| root_id.clone()),
+            ),
+            ..step.clone()
+        });
+    }
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+
+    fn step(id: &str, parent: Option<&str>) -> Step {
+        Step {
+            id: id.to_string(),
+            title: id.to_uppercase(),
+            completed: false,
+            created_at: 1,
+            completed_at: None,
+            parent_id: parent.map(str::to_string),
+        }
+    }
+
+    fn task(id: &str, steps: Vec<Step>) -> Task {
+        let mut task = test_support::task(id)
+            .project("work")
+            .due(500)
+            .tags(&["deep"])
+            .notes("parent notes");
+        task.important = true;
+        task.steps = steps;
+        task.refresh_progress();
+        task
+    }
+
+    fn parents(steps: &[Step]) -> Vec<(&str, Option<&str>)> {
+        steps
+            .iter()
+            .map(|step| (step.id.as_str(), step.parent_id.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn normalize_drops_dangling_self_and_cyclic_parents() {
+        let mut steps = vec![
+            step("a", Some("missing")),
+            step("b", Some("b")),
+            step("c", Some("d")),
+            step("d", Some("c")),
+            step("e", Some("c")),
+            step("f", Some("a")),
+        ];
+        normalize_step_parents(&mut steps);
+        assert_eq!(
+            parents(&steps),
+            vec![
+                ("a", None),
+                ("b", None),
+                ("c", None),
+                ("d", Some("c")),
+                ("e", Some("c")),
+                ("f", Some("a")),
+            ]
+        );
+    }
+
+    #[test]
+    fn promote_takes_the_subtree_and_inherits_classification() {
+        let mut parent = task(
+            "t",
+            vec![
+                step("a", None),
+                step("b", Some("a")),
+                step("c", Some("b")),
+                step("d", None),
+            ],
+        );
+        let promoted = promote_step(&mut parent, "a", "t-a".to_string(), 100).unwrap();
+        assert_eq!(parents(&parent.steps), vec![("d", None)]);
+        assert_eq!(parent.progress.steps_total, 1);
+        assert_eq!(parent.updated_at, 100);
+
+        assert_eq!(promoted.id, "t-a");
+        assert_eq!(promoted.title, "A");
+        assert_eq!(
+            (
+                promoted.project_id.as_str(),
+                promoted.due_at,
+                promoted.important
+            ),
+            ("work", Some(500), true)
+        );
+        assert_eq!(promoted.tags, vec!["deep"]);
+        assert_eq!(promoted.notes, None);
+        assert_eq!(
+            parents(&promoted.steps),
+            vec![("b", None), ("c", Some("b"))]
+        );
+        assert_eq!(promoted.progress.steps_total, 2);
+
+        assert!(promote_step(&mut parent, "a", "x".to_string(), 100).is_none());
+    }
+
+    #[test]
+    fn task_to_steps_nests_the_task_and_keeps_ids_unique() {
+        let source = task("s", vec![step("a", None), step("b", Some("a"))]);
+        let target = task("t", vec![step("x", None), step("s", None)]);
+        let steps = task_to_steps(&source, &target, Some("x".to_string()), 50);
+        assert_eq!(
+            parents(&steps),
+            vec![
+                ("s-2", Some("x")),
+                ("s-a", Some("s-2")),
+                ("s-b", Some("s-a"))
+            ]
+        );
+        assert_eq!(steps[0].title, "s");
+        assert_eq!(steps[0].created_at, 50);
+        assert_eq!(steps[2].title, "B");
+    }
+}
@@ -1,22 +1,100 @@
+use std::collections::BTreeMap;
 use std::fs::{self, File, OpenOptions};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
 
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
-use crate::models::{SettingsFile, TasksFile};
+use crate::migrations::parse_tasks_file;
+use crate::models::{Settings, SettingsFile, StorageEngine, Task, TasksFile};
+use crate::sync::CalDavSyncState;
+
+mod sqlite;
+
+use sqlite::SqliteTaskStore;
 
 const DATA_FILE: &str = "data.json";
+const SQLITE_FILE: &str = "data.sqlite";
 const SETTINGS_FILE: &str = "settings.json";
+const SECRETS_FILE: &str = "secrets.json";
+const CALDAV_SYNC_FILE: &str = "caldav_sync.json";
 const BACKUP_DIR: &str = "backups";
+const ATTACHMENTS_DIR: &str = "attachments";
+const EXPORTS_DIR: &str = "exports";
+/// Completed tasks moved out of data.json, one `tasks-<year>.json` per completion year.
+const TASK_ARCHIVE_DIR: &str = "archive";
+/// Present while a `save_state` is past its commit point; lists the files it moves into place.
+const JOURNAL_FILE: &str = "persist.journal";
+/// A document `save_state` has fully written but not yet moved over the live file.
+const PENDING_SUFFIX: &str = ".pending";
 // Keep this aligned with `todo-tool/UNFINISHED.md` (and AGENTS docs).
-const BACKUP_LIMIT: usize = 5;
+pub const DEFAULT_BACKUP_RETENTION: u32 = 5;
+pub const MAX_BACKUP_RETENTION: u32 = 100;
+/// Compressed backups are `data-<date>.json.gz`; both kinds can be listed and restored.
+const GZIP_SUFFIX: &str = ".gz";
+
+/// How new backups are written and how many `backups/` keeps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupPolicy {
+    pub retention: usize,
+    pub compress: bool,
+    /// A folder outside the data directory (e.g. synced by Dropbox/OneDrive) that gets a copy
+    /// of every new backup and keeps as many as `backups/`.
+    pub mirror_dir: Option<PathBuf>,
+}
+
+impl Default for BackupPolicy {
+    fn default() -> Self {
+        Self {
+            retention: DEFAULT_BACKUP_RETENTION as usize,
+            compress: false,
+            mirror_dir: None,
+        }
+    }
+}
+
+impl BackupPolicy {
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            retention: settings.backup_retention.clamp(1, MAX_BACKUP_RETENTION) as usize,
+            compress: settings.backup_compress,
+            mirror_dir: Some(settings.backup_directory.trim())
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from),
+        }
+    }
+}
+
+/// Checks a `backup_directory` before it is saved: an existing, writable, absolute folder
+/// outside the data directory `root` (backups there would be trimmed as the app's own).
+pub fn validate_backup_directory(dir: &Path, root: &Path) -> Result<(), String> {
+    if !dir.is_absolute() {
+        return Err("backup directory must be an absolute path".to_string());
+    }
+    if !dir.is_dir() {
+        return Err(format!("backup directory not found: {}", dir.display()));
+    }
+    let canonical = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if canonical(dir).starts_with(canonical(root)) {
+        return Err("backup directory must be outside the data directory".to_string());
+    }
+    let probe = dir.join(".mustdo-write-test");
+    fs::write(&probe, b"ok")
+        .and_then(|_| fs::remove_file(&probe))
+        .map_err(|err| format!("backup directory is not writable: {err}"))
+}
 
 #[derive(Debug)]
 pub enum StorageError {
     Io(std::io::Error),
     Json(serde_json::Error),
+    Sqlite(rusqlite::Error),
 }
 
 impl std::fmt::Display for StorageError {
@@ -24,6 +102,7 @@ impl std::fmt::Display for StorageError {
         match self {
             StorageError::Io(err) => write!(f, "io error: {err}"),
             StorageError::Json(err) => write!(f, "json error: {err}"),
+            StorageError::Sqlite(err) => write!(f, "sqlite error: {err}"),
         }
     }
 }
@@ -42,6 +121,41 @@ impl From<serde_json::Error> for StorageError {
     }
 }
 
+impl From<rusqlite::Error> for StorageError {
+    fn from(value: rusqlite::Error) -> Self {
+        StorageError::Sqlite(value)
+    }
+}
+
+/// Home of the tasks/projects document. `Storage` keeps settings, secrets and backups itself
+/// and hands the document to the store the data directory uses (see [`Storage::engine`]).
+pub trait TaskStore {
+    fn exists(&self) -> bool;
+    fn load(&self) -> Result<TasksFile, StorageError>;
+    fn save(&self, data: &TasksFile) -> Result<(), StorageError>;
+}
+
+/// data.json, rewritten atomically as a whole on every save.
+struct JsonTaskStore<'a> {
+    storage: &'a Storage,
+}
+
+impl TaskStore for JsonTaskStore<'_> {
+    fn exists(&self) -> bool {
+        self.storage.exists(&self.storage.root.join(DATA_FILE))
+    }
+
+    fn load(&self) -> Result<TasksFile, StorageError> {
+        self.storage
+            .load_tasks_file(self.storage.root.join(DATA_FILE))
+    }
+
+    fn save(&self, data: &TasksFile) -> Result<(), StorageError> {
+        self.storage
+            .write_atomic(self.storage.root.join(DATA_FILE), data)
+    }
+}
+
 trait WriteAndSync {
     fn write_all_bytes(&mut self, buf: &[u8]) -> std::io::Result<()>;
     fn sync_all(&self) -> std::io::Result<()>;
@@ -76,6 +190,23 @@ fn write_all_and_sync<W: WriteAndSync + ?Sized>(
     Ok(())
 }
 
+fn gzip(bytes: &[u8]) -> Result<Vec<u8>, StorageError> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(bytes)?;
+    Ok(encoder.finish()?)
+}
+
+/// `bytes` as text, gunzipped first when `path` is a `.gz` file.
+fn decode_text(path: &Path, bytes: Vec<u8>) -> Result<String, StorageError> {
+    let invalid = |err| StorageError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, err));
+    if !path.to_string_lossy().ends_with(GZIP_SUFFIX) {
+        return String::from_utf8(bytes).map_err(invalid);
+    }
+    let mut text = String::new();
+    GzDecoder::new(bytes.as_slice()).read_to_string(&mut text)?;
+    Ok(text)
+}
+
 fn invalid_backup_filename_error() -> StorageError {
     StorageError::Io(std::io::Error::other("invalid backup filename"))
 }
@@ -123,26 +254,355 @@ fn is_retryable_tempfile_create_error(err: &StorageError) -> bool {
                 | std::io::ErrorKind::IsADirectory
                 | std::io::ErrorKind::PermissionDenied
         ),
-        StorageError::Json(_) => false,
+        StorageError::Json(_) | StorageError::Sqlite(_) => false,
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MemoryFile {
+    bytes: Vec<u8>,
+    modified: SystemTime,
+}
+
+/// Process-local file map used by guest/demo mode; cloning shares the same files.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    files: Arc<Mutex<BTreeMap<PathBuf, MemoryFile>>>,
+}
+
+impl MemoryStore {
+    fn files(&self) -> std::sync::MutexGuard<'_, BTreeMap<PathBuf, MemoryFile>> {
+        self.files
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum StorageBackend {
+    Fs,
+    Memory(MemoryStore),
+}
+
+static MEMORY_BACKEND: OnceLock<MemoryStore> = OnceLock::new();
+
+/// Switches every `Storage::new` in this process to one shared in-memory store (guest mode).
+/// Cannot be undone; nothing written afterwards reaches the disk.
+#[cfg(all(feature = "app", not(test)))]
+pub fn use_memory_backend() {
+    let _ = MEMORY_BACKEND.set(MemoryStore::default());
+}
+
+pub fn memory_backend_enabled() -> bool {
+    MEMORY_BACKEND.get().is_some()
+}
+
+/// `save_state` and journal recovery touch the same pending files; one at a time per process.
+static TRANSACTION_LOCK: Mutex<()> = Mutex::new(());
+
+fn pending_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(PENDING_SUFFIX);
+    PathBuf::from(name)
+}
+
+fn not_found(path: &Path) -> StorageError {
+    StorageError::Io(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        format!("not found: {}", path.display()),
+    ))
+}
+
+/// Appends `entries` to a side log (a `*.jsonl` file in the data root, one JSON value per
+/// line). Appends never rewrite the file; [`compact_jsonl`] trims it during maintenance.
+pub fn append_jsonl<T: Serialize>(path: &Path, entries: &[T]) -> Result<(), StorageError> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let mut lines = String::new();
+    for entry in entries {
+        lines.push_str(&serde_json::to_string(entry)?);
+        lines.push('\n');
     }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(lines.as_bytes())?;
+    Ok(())
+}
+
+/// Side log entries, oldest first. A missing file is empty; unparsable lines are skipped.
+pub fn read_jsonl<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, StorageError> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Atomically replaces a side log with `entries`.
+pub fn write_jsonl<T: Serialize>(path: &Path, entries: &[T]) -> Result<(), StorageError> {
+    let mut content = String::new();
+    for entry in entries {
+        content.push_str(&serde_json::to_string(entry)?);
+        content.push('\n');
+    }
+    replace_side_log(path, content.into_bytes())
+}
+
+fn replace_side_log(path: &Path, bytes: Vec<u8>) -> Result<(), StorageError> {
+    // Side logs always live on disk, guest mode included.
+    let dir = path.parent().unwrap_or(Path::new("."));
+    Storage::with_backend(dir.to_path_buf(), StorageBackend::Fs)
+        .write_file(path.to_path_buf(), bytes)
+}
+
+/// Keeps the newest `limit` parsable lines of a side log, rewriting it atomically only when
+/// something is dropped. Returns the number of lines dropped.
+pub fn compact_jsonl<T: DeserializeOwned>(
+    path: &Path,
+    limit: usize,
+) -> Result<usize, StorageError> {
+    if !path.exists() {
+        return Ok(0);
+    }
+    let content = fs::read_to_string(path)?;
+    let lines: Vec<&str> = content.lines().collect();
+    let valid: Vec<&str> = lines
+        .iter()
+        .copied()
+        .filter(|line| serde_json::from_str::<T>(line).is_ok())
+        .collect();
+    let kept = &valid[valid.len().saturating_sub(limit)..];
+    let dropped = lines.len() - kept.len();
+    if dropped > 0 {
+        let body: String = kept.iter().map(|line| format!("{line}\n")).collect();
+        replace_side_log(path, body.into_bytes())?;
+    }
+    Ok(dropped)
 }
 
 pub struct Storage {
     root: PathBuf,
+    backend: StorageBackend,
+    engine: StorageEngine,
+    backup_policy: BackupPolicy,
 }
 
 impl Storage {
     pub fn new(root: PathBuf) -> Self {
-        Self { root }
+        let backend = match MEMORY_BACKEND.get() {
+            Some(store) => StorageBackend::Memory(store.clone()),
+            None => StorageBackend::Fs,
+        };
+        Self::with_backend(root, backend)
     }
 
+    /// The engine follows the data directory: a `data.sqlite` file selects SQLite, anything
+    /// else (including guest mode, which has no SQLite) uses data.json.
+    pub fn with_backend(root: PathBuf, backend: StorageBackend) -> Self {
+        let engine = match backend {
+            StorageBackend::Fs if root.join(SQLITE_FILE).is_file() => StorageEngine::Sqlite,
+            _ => StorageEngine::Json,
+        };
+        Self {
+            root,
+            backend,
+            engine,
+            backup_policy: BackupPolicy::default(),
+        }
+    }
+
+    /// Retention and compression for the backups this handle writes (the defaults otherwise).
+    pub fn with_backup_policy(mut self, policy: BackupPolicy) -> Self {
+        self.backup_policy = policy;
+        self
+    }
+
+    pub fn engine(&self) -> StorageEngine {
+        self.engine
+    }
+
+    fn task_store(&self) -> Box<dyn TaskStore + '_> {
+        self.store_for(self.engine)
+    }
+
+    fn store_for(&self, engine: StorageEngine) -> Box<dyn TaskStore + '_> {
+        match engine {
+            StorageEngine::Json => Box::new(JsonTaskStore { storage: self }),
+            StorageEngine::Sqlite => Box::new(SqliteTaskStore::new(self.root.join(SQLITE_FILE))),
+        }
+    }
+
+    /// One-time switch of the task store: copies the current data into the other engine,
+    /// keeps the old contents as a regular backup, then removes the old file. Returns the
+    /// number of tasks moved. A failed copy leaves the current engine untouched.
+    pub fn migrate_to(&mut self, target: StorageEngine) -> Result<usize, StorageError> {
+        if target == self.engine {
+            return Ok(0);
+        }
+        if target == StorageEngine::Sqlite && matches!(self.backend, StorageBackend::Memory(_)) {
+            return Err(StorageError::Io(std::io::Error::other(
+                "sqlite storage is not available in guest mode",
+            )));
+        }
+        let current = self.task_store();
+        let data = match current.load() {
+            Ok(data) => data,
+            Err(StorageError::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => TasksFile {
+                schema_version: 1,
+                tasks: Vec::new(),
+                projects: Vec::new(),
+                trash: Vec::new(),
+                tags: Vec::new(),
+            },
+            Err(err) => return Err(err),
+        };
+        let had_data = current.exists();
+        drop(current);
+        match target {
+            StorageEngine::Sqlite => {
+                // Build the database next to the live file and move it in only once complete.
+                let temp = self.root.join(SQLITE_FILE).with_extension("sqlite.tmp");
+                let _ = fs::remove_file(&temp);
+                let mut cleanup = TempPathGuard::new(temp.clone());
+                SqliteTaskStore::new(temp.clone()).save(&data)?;
+                fs::rename(&temp, self.root.join(SQLITE_FILE))?;
+                cleanup.disarm();
+            }
+            StorageEngine::Json => self.store_for(StorageEngine::Json).save(&data)?,
+        }
+        if had_data {
+            let old = match self.engine {
+                StorageEngine::Json => self.root.join(DATA_FILE),
+                StorageEngine::Sqlite => self.root.join(SQLITE_FILE),
+            };
+            self.backup_tasks()?;
+            self.remove_file(&old)?;
+        }
+        log::info!(
+            "storage: migrated {:?} -> {:?} tasks={} projects={}",
+            self.engine,
+            target,
+            data.tasks.len(),
+            data.projects.len()
+        );
+        self.engine = target;
+        Ok(data.tasks.len())
+    }
+
+    /// Also settles a `save_state` that was interrupted, so whatever is loaded next is one
+    /// consistent pair of data and settings. A failed recovery is retried by the next save.
     pub fn ensure_dirs(&self) -> Result<(), StorageError> {
-        fs::create_dir_all(self.root.join(BACKUP_DIR))?;
+        if let StorageBackend::Fs = self.backend {
+            fs::create_dir_all(self.root.join(BACKUP_DIR))?;
+        }
+        if let Err(err) = self.recover_journal() {
+            log::warn!(
+                "persist journal recovery failed root={} err={err}",
+                self.root.display()
+            );
+        }
         Ok(())
     }
 
+    fn exists(&self, path: &Path) -> bool {
+        match &self.backend {
+            StorageBackend::Fs => path.exists(),
+            StorageBackend::Memory(store) => store.files().contains_key(path),
+        }
+    }
+
+    fn read_string(&self, path: &Path) -> Result<String, StorageError> {
+        match &self.backend {
+            StorageBackend::Fs => Self::read_to_string(path),
+            StorageBackend::Memory(store) => {
+                let files = store.files();
+                let file = files.get(path).ok_or_else(|| not_found(path))?;
+                String::from_utf8(file.bytes.clone()).map_err(|err| {
+                    StorageError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+                })
+            }
+        }
+    }
+
+    fn copy_file(&self, from: &Path, to: &Path) -> Result<(), StorageError> {
+        match &self.backend {
+            StorageBackend::Fs => {
+                fs::copy(from, to)?;
+            }
+            StorageBackend::Memory(store) => {
+                let mut files = store.files();
+                let mut file = files.get(from).cloned().ok_or_else(|| not_found(from))?;
+                file.modified = SystemTime::now();
+                files.insert(to.to_path_buf(), file);
+            }
+        }
+        Ok(())
+    }
+
+    fn rename_file(&self, from: &Path, to: &Path) -> Result<(), StorageError> {
+        match &self.backend {
+            StorageBackend::Fs => fs::rename(from, to)?,
+            StorageBackend::Memory(store) => {
+                let mut files = store.files();
+                let file = files.remove(from).ok_or_else(|| not_found(from))?;
+                files.insert(to.to_path_buf(), file);
+            }
+        }
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<(), StorageError> {
+        match &self.backend {
+            StorageBackend::Fs => fs::remove_file(path)?,
+            StorageBackend::Memory(store) => {
+                store.files().remove(path).ok_or_else(|| not_found(path))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn file_size(&self, path: &Path) -> u64 {
+        match &self.backend {
+            StorageBackend::Fs => fs::metadata(path).map(|meta| meta.len()).unwrap_or(0),
+            StorageBackend::Memory(store) => store
+                .files()
+                .get(path)
+                .map_or(0, |file| file.bytes.len() as u64),
+        }
+    }
+
+    /// Files directly inside `dir` with their modification time, oldest first.
+    fn list_files(&self, dir: &Path) -> Result<Vec<(String, SystemTime)>, StorageError> {
+        let mut entries: Vec<(String, SystemTime)> = match &self.backend {
+            StorageBackend::Fs => fs::read_dir(dir)?
+                .filter_map(|entry| entry.ok())
+                .map(|entry| {
+                    let modified = entry
+                        .metadata()
+                        .and_then(|m| m.modified())
+                        .unwrap_or(SystemTime::UNIX_EPOCH);
+                    (entry.file_name().to_string_lossy().to_string(), modified)
+                })
+                .collect(),
+            StorageBackend::Memory(store) => store
+                .files()
+                .iter()
+                .filter(|(path, _)| path.parent() == Some(dir))
+                .filter_map(|(path, file)| {
+                    let name = path.file_name()?.to_string_lossy().to_string();
+                    Some((name, file.modified))
+                })
+                .collect(),
+        };
+        entries.sort_by_key(|(_, modified)| *modified);
+        Ok(entries)
+    }
+
     pub fn load_tasks(&self) -> Result<TasksFile, StorageError> {
-        self.load_json(self.root.join(DATA_FILE))
+        self.task_store().load()
     }
 
     pub fn load_settings(&self) -> Result<SettingsFile, StorageError> {
@@ -150,40 +610,265 @@ impl Storage {
     }
 
     pub fn save_tasks(&self, data: &TasksFile, with_backup: bool) -> Result<(), StorageError> {
-        if with_backup {
-            return self.write_with_backup(DATA_FILE, data);
+        let store = self.task_store();
+        if with_backup && store.exists() {
+            self.backup_tasks()?;
         }
-        self.write_atomic(self.root.join(DATA_FILE), data)
+        store.save(data)
     }
 
     pub fn save_settings(&self, data: &SettingsFile) -> Result<(), StorageError> {
+        if self.engine == StorageEngine::Sqlite {
+            // The database copy is what recovery trusts, so it must never be older.
+            SqliteTaskStore::new(self.root.join(SQLITE_FILE))
+                .save_settings(&serde_json::to_string(data)?)?;
+        }
         self.write_atomic(self.root.join(SETTINGS_FILE), data)
     }
 
-    fn load_json<T: DeserializeOwned>(&self, path: PathBuf) -> Result<T, StorageError> {
-        let mut file = File::open(&path)?;
+    /// Saves tasks and settings as one unit.
+    ///
+    /// JSON engine: both documents are first written and synced as `*.pending` files; writing
+    /// the journal is the commit point, after which they replace data.json and settings.json.
+    /// A crash before the journal leaves both old files, one after it is finished by
+    /// [`Storage::recover_journal`].
+    ///
+    /// SQLite engine: the changed rows and the settings document are written in one
+    /// transaction, which is the commit point; settings.json is then rewritten as a mirror
+    /// and restored from the database by [`Storage::recover_journal`] if that write is lost.
+    pub fn save_state(
+        &self,
+        tasks: &TasksFile,
+        settings: &SettingsFile,
+        with_backup: bool,
+    ) -> Result<(), StorageError> {
+        let _guard = TRANSACTION_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        // An earlier save cut short must not be replayed over this one later.
+        self.settle_journal()?;
+        if with_backup && self.task_store().exists() {
+            self.backup_tasks()?;
+        }
+        if self.engine == StorageEngine::Sqlite {
+            SqliteTaskStore::new(self.root.join(SQLITE_FILE))
+                .save_with_settings(tasks, &serde_json::to_string(settings)?)?;
+            return self.write_atomic(self.root.join(SETTINGS_FILE), settings);
+        }
+        self.write_atomic(pending_path(&self.root.join(DATA_FILE)), tasks)?;
+        self.write_atomic(pending_path(&self.root.join(SETTINGS_FILE)), settings)?;
+        self.write_atomic(
+            self.root.join(JOURNAL_FILE),
+            &serde_json::json!({ "files": [DATA_FILE, SETTINGS_FILE] }),
+        )?;
+        self.apply_journal()
+    }
+
+    /// Finishes a committed `save_state` or drops the pending files of one that never reached
+    /// its journal; under SQLite also brings settings.json back in line with the settings
+    /// committed to the database. Returns whether anything was left over.
+    pub fn recover_journal(&self) -> Result<bool, StorageError> {
+        let _guard = TRANSACTION_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let settled = self.settle_journal()?;
+        Ok(self.restore_settings_mirror()? || settled)
+    }
+
+    fn restore_settings_mirror(&self) -> Result<bool, StorageError> {
+        if self.engine != StorageEngine::Sqlite {
+            return Ok(false);
+        }
+        let Some(committed) = SqliteTaskStore::new(self.root.join(SQLITE_FILE)).load_settings()?
+        else {
+            return Ok(false);
+        };
+        let committed: serde_json::Value = serde_json::from_str(&committed)?;
+        let path = self.root.join(SETTINGS_FILE);
+        if self.exists(&path)
+            && self
+                .load_json::<serde_json::Value>(path.clone())
+                .is_ok_and(|mirror| mirror == committed)
+        {
+            return Ok(false);
+        }
+        log::warn!(
+            "settings.json behind the database, restoring path={}",
+            path.display()
+        );
+        self.write_atomic(path, &committed)?;
+        Ok(true)
+    }
+
+    fn settle_journal(&self) -> Result<bool, StorageError> {
+        if self.exists(&self.root.join(JOURNAL_FILE)) {
+            log::warn!(
+                "persist journal found, finishing interrupted save root={}",
+                self.root.display()
+            );
+            self.apply_journal()?;
+            return Ok(true);
+        }
+        let mut abandoned = false;
+        for file in [DATA_FILE, SETTINGS_FILE] {
+            let pending = pending_path(&self.root.join(file));
+            if self.exists(&pending) {
+                log::warn!("dropping uncommitted save path={}", pending.display());
+                self.remove_file(&pending)?;
+                abandoned = true;
+            }
+        }
+        Ok(abandoned)
+    }
+
+    /// Moves the pending documents into place, then removes the journal. Each step is
+    /// repeatable, so an interrupted run is simply run again.
+    fn apply_journal(&self) -> Result<(), StorageError> {
+        let data_pending = pending_path(&self.root.join(DATA_FILE));
+        if self.exists(&data_pending) {
+            self.rename_file(&data_pending, &self.root.join(DATA_FILE))?;
+        }
+        let settings_pending = pending_path(&self.root.join(SETTINGS_FILE));
+        if self.exists(&settings_pending) {
+            self.rename_file(&settings_pending, &self.root.join(SETTINGS_FILE))?;
+        }
+        self.remove_file(&self.root.join(JOURNAL_FILE))
+    }
+
+    /// Missing `secrets.json` is treated as "no secrets stored yet".
+    pub fn load_secrets(&self) -> Result<BTreeMap<String, String>, StorageError> {
+        let path = self.root.join(SECRETS_FILE);
+        if !self.exists(&path) {
+            return Ok(BTreeMap::new());
+        }
+        self.load_json(path)
+    }
+
+    pub fn save_secrets(&self, secrets: &BTreeMap<String, String>) -> Result<(), StorageError> {
+        self.write_atomic(self.root.join(SECRETS_FILE), secrets)
+    }
+
+    /// Missing `caldav_sync.json` means nothing has been synced yet.
+    pub fn load_caldav_sync(&self) -> Result<CalDavSyncState, StorageError> {
+        let path = self.root.join(CALDAV_SYNC_FILE);
+        if !self.exists(&path) {
+            return Ok(CalDavSyncState::default());
+        }
+        self.load_json(path)
+    }
+
+    pub fn save_caldav_sync(&self, state: &CalDavSyncState) -> Result<(), StorageError> {
+        self.write_atomic(self.root.join(CALDAV_SYNC_FILE), state)
+    }
+
+    fn task_archive_path(&self, year: i32) -> PathBuf {
+        self.root
+            .join(TASK_ARCHIVE_DIR)
+            .join(format!("tasks-{year}.json"))
+    }
+
+    /// Years that have archived tasks, oldest first.
+    pub fn task_archive_years(&self) -> Result<Vec<i32>, StorageError> {
+        let dir = self.root.join(TASK_ARCHIVE_DIR);
+        if matches!(self.backend, StorageBackend::Fs) && !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut years: Vec<i32> = self
+            .list_files(&dir)?
+            .into_iter()
+            .filter_map(|(name, _)| {
+                name.strip_prefix("tasks-")?
+                    .strip_suffix(".json")?
+                    .parse()
+                    .ok()
+            })
+            .collect();
+        years.sort_unstable();
+        Ok(years)
+    }
+
+    /// Missing `archive/tasks-<year>.json` means nothing was archived for that year.
+    pub fn load_task_archive(&self, year: i32) -> Result<Vec<Task>, StorageError> {
+        let path = self.task_archive_path(year);
+        if !self.exists(&path) {
+            return Ok(Vec::new());
+        }
+        self.load_json(path)
+    }
+
+    /// Writes a year's archive; an empty list removes the file.
+    pub fn save_task_archive(&self, year: i32, tasks: &[Task]) -> Result<(), StorageError> {
+        let path = self.task_archive_path(year);
+        if tasks.is_empty() {
+            return if self.exists(&path) {
+                self.remove_file(&path)
+            } else {
+                Ok(())
+            };
+        }
+        if let StorageBackend::Fs = self.backend {
+            fs::create_dir_all(self.root.join(TASK_ARCHIVE_DIR))?;
+        }
+        self.write_atomic(path, &tasks)
+    }
+
+    fn read_to_string(path: &Path) -> Result<String, StorageError> {
+        let mut file = File::open(path)?;
         let mut buf = String::new();
         file.read_to_string(&mut buf)?;
+        Ok(buf)
+    }
+
+    fn load_json<T: DeserializeOwned>(&self, path: PathBuf) -> Result<T, StorageError> {
+        let buf = self.read_string(&path)?;
         let parsed = serde_json::from_str(&buf)?;
         log::debug!("loaded json path={} bytes={}", path.display(), buf.len());
         Ok(parsed)
     }
 
-    fn write_with_backup<T: Serialize>(
-        &self,
-        filename: &str,
-        data: &T,
-    ) -> Result<(), StorageError> {
-        let path = self.root.join(filename);
-        if path.exists() {
-            self.create_backup(&path)?;
+    /// data.json and its backups may predate `schema_version`; route them through migrations.
+    fn load_tasks_file(&self, path: PathBuf) -> Result<TasksFile, StorageError> {
+        let buf = self.read_string(&path)?;
+        Self::parse_tasks(&path, &buf)
+    }
+
+    fn parse_tasks(path: &Path, buf: &str) -> Result<TasksFile, StorageError> {
+        let parsed = parse_tasks_file(buf)?;
+        log::debug!("loaded tasks path={} bytes={}", path.display(), buf.len());
+        Ok(parsed)
+    }
+
+    /// Backs up the stored task data. Backups are always data.json-format files, whatever the
+    /// engine, so listing, previews and restores work the same for both.
+    fn backup_tasks(&self) -> Result<(), StorageError> {
+        match self.engine {
+            StorageEngine::Json => self.create_backup(&self.root.join(DATA_FILE)),
+            StorageEngine::Sqlite => {
+                let data = self.task_store().load()?;
+                let json = serde_json::to_vec_pretty(&data)?;
+                self.write_backup(&json, Path::new(SQLITE_FILE))
+            }
         }
-        self.write_atomic(path, data)
     }
 
     fn write_atomic<T: Serialize>(&self, path: PathBuf, data: &T) -> Result<(), StorageError> {
         let json = serde_json::to_vec_pretty(data)?;
-        self.write_atomic_bytes(path, &json, create_file_writer)
+        self.write_file(path, json)
+    }
+
+    fn write_file(&self, path: PathBuf, bytes: Vec<u8>) -> Result<(), StorageError> {
+        if let StorageBackend::Memory(store) = &self.backend {
+            log::debug!("memory write path={} bytes={}", path.display(), bytes.len());
+            store.files().insert(
+                path,
+                MemoryFile {
+                    bytes,
+                    modified: SystemTime::now(),
+                },
+            );
+            return Ok(());
+        }
+        self.write_atomic_bytes(path, &bytes, create_file_writer)
     }
 
     #[cfg_attr(coverage, inline(never))]
@@ -246,52 +931,261 @@ impl Storage {
     }
 
     pub fn create_backup(&self, path: &Path) -> Result<(), StorageError> {
+        if self.backup_policy.compress {
+            let bytes = self.read_bytes(path)?;
+            return self.write_backup(&bytes, path);
+        }
         let backup_name = self.next_backup_name()?;
         let backup_path = self.root.join(BACKUP_DIR).join(&backup_name);
-        fs::copy(path, &backup_path)?;
+        self.copy_file(path, &backup_path)?;
         log::info!(
             "backup created name={} source={} dest={}",
             backup_name,
             path.display(),
             backup_path.display()
         );
-        // Trimming is best-effort: a backup file was successfully created and should not be
-        // discarded just because cleanup failed (e.g., transient FS errors).
-        if let Err(err) = self.trim_backups() {
+        self.finish_backup(&backup_name);
+        Ok(())
+    }
+
+    /// Stores `json` as the next backup, gzip-compressed when the policy asks for it.
+    fn write_backup(&self, json: &[u8], source: &Path) -> Result<(), StorageError> {
+        let backup_name = self.next_backup_name()?;
+        let backup_path = self.root.join(BACKUP_DIR).join(&backup_name);
+        let bytes = if self.backup_policy.compress {
+            gzip(json)?
+        } else {
+            json.to_vec()
+        };
+        let size = bytes.len();
+        self.write_file(backup_path, bytes)?;
+        log::info!(
+            "backup created name={} source={} bytes={}",
+            backup_name,
+            source.display(),
+            size
+        );
+        self.finish_backup(&backup_name);
+        Ok(())
+    }
+
+    /// Mirrors a new backup and trims old ones. Both are best-effort: a backup file was
+    /// successfully created and should not be discarded just because cleanup failed (e.g.,
+    /// transient FS errors) or the extra folder is unavailable.
+    fn finish_backup(&self, name: &str) {
+        if let Err(err) = self.mirror_backup(name) {
+            log::warn!("backup mirror skipped name={name} err={err}");
+        }
+        if let Err(err) = self.trim_backups(&self.root.join(BACKUP_DIR)) {
             log::warn!("backup trim failed: {err}");
         }
-        Ok(())
+    }
+
+    /// Copies `backups/<name>` to the policy's `mirror_dir`. A folder that is missing (e.g. an
+    /// unmounted drive) fails here, leaving `backups/` as the only copy.
+    fn mirror_backup(&self, name: &str) -> Result<(), StorageError> {
+        let Some(dir) = &self.backup_policy.mirror_dir else {
+            return Ok(());
+        };
+        if matches!(self.backend, StorageBackend::Memory(_)) {
+            // Guest mode writes nothing to disk.
+            return Ok(());
+        }
+        if !dir.is_dir() {
+            return Err(not_found(dir));
+        }
+        let bytes = self.read_bytes(&self.root.join(BACKUP_DIR).join(name))?;
+        self.write_atomic_bytes(dir.join(name), &bytes, create_file_writer)?;
+        log::info!("backup mirrored name={} dir={}", name, dir.display());
+        self.trim_backups(dir)
+    }
+
+    /// What a full backup archive bundles, as (archive name, path): `data.json` or
+    /// `data.sqlite`, `settings.json` and every file in `backups/`, `attachments/` and
+    /// `archive/`. Secrets never leave the data directory.
+    pub fn archive_sources(&self) -> Result<Vec<(String, PathBuf)>, StorageError> {
+        let mut sources: Vec<(String, PathBuf)> = [DATA_FILE, SQLITE_FILE, SETTINGS_FILE]
+            .into_iter()
+            .map(|name| (name.to_string(), self.root.join(name)))
+            .filter(|(_, path)| self.exists(path))
+            .collect();
+        for dir_name in [BACKUP_DIR, ATTACHMENTS_DIR, TASK_ARCHIVE_DIR] {
+            let dir = self.root.join(dir_name);
+            if matches!(self.backend, StorageBackend::Memory(_)) || dir.is_dir() {
+                for (name, _) in self.list_files(&dir)? {
+                    let path = dir.join(&name);
+                    sources.push((format!("{dir_name}/{name}"), path));
+                }
+            }
+        }
+        Ok(sources)
+    }
+
+    /// `attachments/<file>`; `file` must be a bare file name.
+    pub fn attachment_path(&self, file: &str) -> Result<PathBuf, StorageError> {
+        let name = sanitize_backup_filename(file)?;
+        Ok(self.root.join(ATTACHMENTS_DIR).join(name))
+    }
+
+    /// Copies the user's file at `source` to `attachments/<file>`; returns its size. In guest
+    /// mode the copy is kept in memory like everything else.
+    pub fn store_attachment(&self, source: &Path, file: &str) -> Result<u64, StorageError> {
+        let target = self.attachment_path(file)?;
+        if let StorageBackend::Memory(store) = &self.backend {
+            let bytes = fs::read(source)?;
+            let size = bytes.len() as u64;
+            store.files().insert(
+                target,
+                MemoryFile {
+                    bytes,
+                    modified: SystemTime::now(),
+                },
+            );
+            return Ok(size);
+        }
+        fs::create_dir_all(self.root.join(ATTACHMENTS_DIR))?;
+        let temp = target.with_extension("tmp");
+        let mut cleanup = TempPathGuard::new(temp.clone());
+        let size = fs::copy(source, &temp)?;
+        fs::rename(&temp, &target)?;
+        cleanup.disarm();
+        Ok(size)
+    }
+
+    pub fn attachment_exists(&self, file: &str) -> bool {
+        self.attachment_path(file)
+            .map(|path| self.exists(&path))
+            .unwrap_or(false)
+    }
+
+    /// File names in `attachments/`, oldest first; empty when the folder does not exist.
+    pub fn list_attachments(&self) -> Result<Vec<String>, StorageError> {
+        let dir = self.root.join(ATTACHMENTS_DIR);
+        if matches!(self.backend, StorageBackend::Fs) && !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        Ok(self
+            .list_files(&dir)?
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect())
+    }
+
+    pub fn remove_attachment(&self, file: &str) -> Result<(), StorageError> {
+        let path = self.attachment_path(file)?;
+        self.remove_file(&path)
+    }
+
+    pub fn read_bytes(&self, path: &Path) -> Result<Vec<u8>, StorageError> {
+        match &self.backend {
+            StorageBackend::Fs => Ok(fs::read(path)?),
+            StorageBackend::Memory(store) => Ok(store
+                .files()
+                .get(path)
+                .ok_or_else(|| not_found(path))?
+                .bytes
+                .clone()),
+        }
     }
 
     pub fn delete_backup(&self, filename: &str) -> Result<(), StorageError> {
         let name = sanitize_backup_filename(filename)?;
         let path = self.root.join(BACKUP_DIR).join(name);
-        fs::remove_file(path)?;
+        self.remove_file(&path)?;
         log::info!("backup deleted name={filename}");
         Ok(())
     }
 
-    pub fn list_backups(&self) -> Result<Vec<(String, i64)>, StorageError> {
-        let mut entries: Vec<_> = fs::read_dir(self.root.join(BACKUP_DIR))?
-            .filter_map(|entry| entry.ok())
-            .collect();
-        entries.sort_by_key(|entry| entry.metadata().and_then(|m| m.modified()).ok());
-        entries.reverse();
-        let mut results = Vec::new();
-        for entry in entries {
-            let name = entry.file_name().to_string_lossy().to_string();
-            let modified = entry
-                .metadata()
-                .and_then(|m| m.modified())
-                .ok()
-                .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
-                .map(|dur| dur.as_secs() as i64)
-                .unwrap_or(0);
-            results.push((name, modified));
+    /// Removes `*.tmp` / `*.tmp.<pid>.<n>` leftovers of interrupted atomic writes in the data
+    /// and exports directories. Only files older than `min_age` are touched so a write that is
+    /// in flight right now keeps its temp file.
+    pub fn remove_stale_temp_files(&self, min_age: Duration) -> Result<usize, StorageError> {
+        if let StorageBackend::Memory(_) = self.backend {
+            // Memory writes never leave temp files behind.
+            return Ok(0);
         }
+        let cutoff = SystemTime::now()
+            .checked_sub(min_age)
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        let mut removed = 0;
+        for dir in [self.root.clone(), self.root.join(EXPORTS_DIR)] {
+            if !dir.is_dir() {
+                continue;
+            }
+            for entry in fs::read_dir(&dir)?.filter_map(|entry| entry.ok()) {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if !(name.ends_with(".tmp") || name.contains(".tmp.")) {
+                    continue;
+                }
+                let Ok(meta) = entry.metadata() else {
+                    continue;
+                };
+                let stale = meta.modified().map(|at| at <= cutoff).unwrap_or(false);
+                if meta.is_file() && stale {
+                    fs::remove_file(entry.path())?;
+                    removed += 1;
+                }
+            }
+        }
+        if removed > 0 {
+            log::info!("storage: removed stale temp files count={removed}");
+        }
+        Ok(removed)
+    }
+
+    /// Backups newest first, as (file name, modified at, size in bytes).
+    pub fn list_backups(&self) -> Result<Vec<(String, i64, u64)>, StorageError> {
+        let backup_dir = self.root.join(BACKUP_DIR);
+        let mut entries = self.list_files(&backup_dir)?;
+        entries.reverse();
+        let results = entries
+            .into_iter()
+            .map(|(name, modified)| {
+                let modified = modified
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|dur| dur.as_secs() as i64)
+                    .unwrap_or(0);
+                let size = self.file_size(&backup_dir.join(&name));
+                (name, modified, size)
+            })
+            .collect();
         Ok(results)
     }
 
+    /// Parses a backup (plain or `.gz`) without touching data.json (restore previews).
+    pub fn read_backup(&self, filename: &str) -> Result<TasksFile, StorageError> {
+        let filename = sanitize_backup_filename(filename)?;
+        let path = self.root.join(BACKUP_DIR).join(filename);
+        let buf = decode_text(&path, self.read_bytes(&path)?)?;
+        Self::parse_tasks(&path, &buf)
+    }
+
+    /// Parses a user-picked file (plain or `.gz`) without touching data.json (import previews).
+    pub fn read_external(&self, source: &Path) -> Result<TasksFile, StorageError> {
+        let buf = decode_text(source, fs::read(source)?)?;
+        Self::parse_tasks(source, &buf)
+    }
+
+    /// Moves an unreadable data.json (or data.sqlite) aside as `data.corrupt-<stamp>.json`
+    /// (`.sqlite`) so the next persist cannot overwrite it. Returns the quarantine file name.
+    pub fn quarantine_data_file(&self, stamp: &str) -> Result<String, StorageError> {
+        let (file, ext) = match self.engine {
+            StorageEngine::Json => (DATA_FILE, "json"),
+            StorageEngine::Sqlite => (SQLITE_FILE, "sqlite"),
+        };
+        let source = self.root.join(file);
+        let mut name = format!("data.corrupt-{stamp}.{ext}");
+        let mut index = 2;
+        while self.exists(&self.root.join(&name)) {
+            name = format!("data.corrupt-{stamp}-{index}.{ext}");
+            index += 1;
+        }
+        self.copy_file(&source, &self.root.join(&name))?;
+        self.remove_file(&source)?;
+        log::warn!("{file} quarantined as {name}");
+        Ok(name)
+    }
+
     pub fn restore_backup(&self, filename: &str) -> Result<TasksFile, StorageError> {
         let filename = sanitize_backup_filename(filename)?;
         let path = self.root.join(BACKUP_DIR).join(filename);
@@ -300,8 +1194,8 @@ impl Storage {
             filename,
             path.display()
         );
-        let data: TasksFile = self.load_json(path)?;
-        self.write_atomic(self.root.join(DATA_FILE), &data)?;
+        let data = self.read_backup(filename)?;
+        self.task_store().save(&data)?;
         log::info!("backup restore completed name={}", filename);
         Ok(data)
     }
@@ -311,8 +1205,9 @@ impl Storage {
             "restore from external path requested path={}",
             source.display()
         );
-        let data: TasksFile = self.load_json(source.to_path_buf())?;
-        self.write_atomic(self.root.join(DATA_FILE), &data)?;
+        // The source is a user-picked file on disk, whatever backend holds the app data.
+        let data = self.read_external(source)?;
+        self.task_store().save(&data)?;
         log::info!(
             "restore from external path completed path={}",
             source.display()
@@ -320,15 +1215,17 @@ impl Storage {
         Ok(data)
     }
 
-    fn trim_backups(&self) -> Result<(), StorageError> {
-        let mut entries: Vec<_> = fs::read_dir(self.root.join(BACKUP_DIR))?
-            .filter_map(|entry| entry.ok())
-            .collect();
-        entries.sort_by_key(|entry| entry.metadata().and_then(|m| m.modified()).ok());
-        let to_remove = entries.len().saturating_sub(BACKUP_LIMIT);
-        for entry in entries.into_iter().take(to_remove) {
-            let path = entry.path();
-            if let Err(err) = fs::remove_file(&path) {
+    /// Keeps the newest `retention` backups in `dir`; other files (a mirror folder may hold
+    /// anything) are left alone.
+    fn trim_backups(&self, backup_dir: &Path) -> Result<(), StorageError> {
+        let mut entries = self.list_files(backup_dir)?;
+        entries.retain(|(name, _)| {
+            name.starts_with("data-") && (name.ends_with(".json") || name.ends_with(".json.gz"))
+        });
+        let to_remove = entries.len().saturating_sub(self.backup_policy.retention);
+        for (name, _) in entries.into_iter().take(to_remove) {
+            let path = backup_dir.join(name);
+            if let Err(err) = self.remove_file(&path) {
                 log::warn!(
                     "failed to remove old backup path={} err={}",
                     path.display(),
@@ -353,10 +1250,18 @@ impl Storage {
             } else {
                 format!("data-{date}-{index}.json")
             };
-            let path = self.root.join(BACKUP_DIR).join(&name);
-            if !path.exists() {
-                return Ok(name);
+            // Plain and compressed backups share the numbering.
+            let dir = self.root.join(BACKUP_DIR);
+            if self.exists(&dir.join(&name))
+                || self.exists(&dir.join(format!("{name}{GZIP_SUFFIX}")))
+            {
+                continue;
             }
+            return Ok(if self.backup_policy.compress {
+                format!("{name}{GZIP_SUFFIX}")
+            } else {
+                name
+            });
         }
         Err(StorageError::Io(std::io::Error::other(
             "failed to generate backup filename",
@@ -382,6 +1287,8 @@ mod tests {
             schema_version: 1,
             tasks: Vec::new(),
             projects: Vec::new(),
+            trash: Vec::new(),
+            tags: Vec::new(),
         }
     }
 
@@ -400,6 +1307,69 @@ mod tests {
         assert!(!is_retryable_tempfile_create_error(&json_err));
     }
 
+    #[test]
+    fn secrets_round_trip_and_default_to_empty() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = Storage::new(root.path().to_path_buf());
+        assert!(storage.load_secrets().unwrap().is_empty());
+
+        let mut secrets = BTreeMap::new();
+        secrets.insert("smtp_password".to_string(), "hunter2".to_string());
+        storage.save_secrets(&secrets).unwrap();
+        assert_eq!(storage.load_secrets().unwrap(), secrets);
+
+        fs::write(root.path().join(SECRETS_FILE), "oops").unwrap();
+        assert!(is_json(&storage.load_secrets().unwrap_err()));
+    }
+
+    #[test]
+    fn memory_backend_never_touches_the_disk() {
+        let root = tempfile::tempdir().unwrap();
+        let store = MemoryStore::default();
+        let storage = Storage::with_backend(
+            root.path().to_path_buf(),
+            StorageBackend::Memory(store.clone()),
+        );
+        storage.ensure_dirs().unwrap();
+        assert!(is_io(&storage.load_tasks().unwrap_err()));
+        assert!(storage.load_secrets().unwrap().is_empty());
+
+        storage.save_tasks(&sample_tasks_file(), false).unwrap();
+        storage.save_settings(&sample_settings_file()).unwrap();
+        let mut secrets = BTreeMap::new();
+        secrets.insert("k".to_string(), "v".to_string());
+        storage.save_secrets(&secrets).unwrap();
+        for _ in 0..(DEFAULT_BACKUP_RETENTION as usize + 2) {
+            storage.save_tasks(&sample_tasks_file(), true).unwrap();
+        }
+        assert_eq!(fs::read_dir(root.path()).unwrap().count(), 0);
+
+        // A second handle on the same store sees the data; backups rotate like on disk.
+        let again = Storage::with_backend(root.path().to_path_buf(), StorageBackend::Memory(store));
+        assert_eq!(again.load_tasks().unwrap().schema_version, 1);
+        assert_eq!(again.load_settings().unwrap().schema_version, 1);
+        assert_eq!(again.load_secrets().unwrap(), secrets);
+        let backups = again.list_backups().unwrap();
+        assert_eq!(backups.len(), DEFAULT_BACKUP_RETENTION as usize);
+        let (name, ..) = &backups[0];
+        assert!(again.restore_backup(name).is_ok());
+        again.delete_backup(name).unwrap();
+        assert!(is_io(&again.delete_backup(name).unwrap_err()));
+        assert_eq!(
+            again
+                .remove_stale_temp_files(Duration::from_secs(0))
+                .unwrap(),
+            0
+        );
+
+        // External restores still read the picked file from disk.
+        let source = root.path().join("import.json");
+        fs::write(&source, serde_json::to_vec(&sample_tasks_file()).unwrap()).unwrap();
+        assert!(again.restore_from_path(&source).is_ok());
+        assert!(!root.path().join(DATA_FILE).exists());
+        assert!(!memory_backend_enabled());
+    }
+
     #[test]
     fn ensure_dirs_creates_backup_dir_and_fails_on_invalid_path() {
         let root = tempfile::tempdir().unwrap();
@@ -416,6 +1386,33 @@ mod tests {
         assert!(is_io(&err));
     }
 
+    #[test]
+    fn remove_stale_temp_files_only_touches_old_temp_files() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = Storage::new(root.path().to_path_buf());
+        fs::create_dir_all(root.path().join(EXPORTS_DIR)).unwrap();
+        let old = SystemTime::now() - Duration::from_secs(7200);
+        for name in ["data.tmp", "settings.tmp.42.1", "exports/x.tmp"] {
+            let file = File::create(root.path().join(name)).unwrap();
+            file.set_modified(old).unwrap();
+        }
+        File::create(root.path().join("fresh.tmp")).unwrap();
+        File::create(root.path().join("data.json"))
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        fs::create_dir(root.path().join("dir.tmp")).unwrap();
+
+        let removed = storage
+            .remove_stale_temp_files(Duration::from_secs(3600))
+            .unwrap();
+        assert_eq!(removed, 3);
+        assert!(root.path().join("fresh.tmp").exists());
+        assert!(root.path().join("data.json").exists());
+        assert!(root.path().join("dir.tmp").exists());
+        assert!(!root.path().join("exports/x.tmp").exists());
+    }
+
     #[test]
     fn load_tasks_errors_on_missing_file_and_invalid_json() {
         let root = tempfile::tempdir().unwrap();
@@ -436,6 +1433,28 @@ mod tests {
         assert!(!is_io(&err));
     }
 
+    #[test]
+    fn load_tasks_and_restore_upgrade_legacy_v0_files() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = Storage::new(root.path().to_path_buf());
+        storage.ensure_dirs().unwrap();
+        let legacy = r#"[{"id":"a","title":"Old","due":100,"done":true,"created_at":50}]"#;
+
+        fs::write(root.path().join(DATA_FILE), legacy).unwrap();
+        let loaded = storage.load_tasks().unwrap();
+        assert_eq!(loaded.schema_version, 1);
+        assert_eq!(loaded.tasks[0].due_at, Some(100));
+        assert!(loaded.tasks[0].completed);
+
+        let source = root.path().join("legacy.json");
+        fs::write(&source, legacy).unwrap();
+        let restored = storage.restore_from_path(&source).unwrap();
+        assert_eq!(restored.tasks.len(), 1);
+        // The restored data.json is written back in the current schema.
+        let raw = fs::read_to_string(root.path().join(DATA_FILE)).unwrap();
+        assert!(raw.contains("\"schema_version\": 1"));
+    }
+
     #[test]
     fn load_tasks_errors_on_invalid_utf8_data() {
         let root = tempfile::tempdir().unwrap();
@@ -730,13 +1749,13 @@ mod tests {
         // Create an initial data file.
         storage.save_tasks(&sample_tasks_file(), false).unwrap();
 
-        // Trigger more than BACKUP_LIMIT backups; must stay trimmed.
-        for _ in 0..(BACKUP_LIMIT + 2) {
+        // Trigger more than the default retention; must stay trimmed.
+        for _ in 0..(DEFAULT_BACKUP_RETENTION as usize + 2) {
             storage.save_tasks(&sample_tasks_file(), true).unwrap();
         }
         let backups = storage.list_backups().unwrap();
-        assert!(backups.len() <= BACKUP_LIMIT);
-        assert!(backups.iter().all(|(name, _)| name.starts_with("data-")));
+        assert!(backups.len() <= DEFAULT_BACKUP_RETENTION as usize);
+        assert!(backups.iter().all(|(name, ..)| name.starts_with("data-")));
     }
 
     #[test]
@@ -757,7 +1776,7 @@ mod tests {
 
         let date = chrono::Local::now().format("%Y-%m-%d").to_string();
         let backups = storage.list_backups().unwrap();
-        let names: Vec<_> = backups.into_iter().map(|(name, _)| name).collect();
+        let names: Vec<_> = backups.into_iter().map(|(name, ..)| name).collect();
         assert!(names
             .iter()
             .any(|name| name == &format!("data-{date}.json")));
@@ -766,6 +1785,111 @@ mod tests {
             .any(|name| name == &format!("data-{date}-2.json")));
     }
 
+    #[test]
+    fn compressed_backups_follow_the_policy_and_restore() {
+        let root = tempfile::tempdir().unwrap();
+        let plain = Storage::new(root.path().to_path_buf());
+        plain.ensure_dirs().unwrap();
+        let mut tasks = sample_tasks_file();
+        tasks.projects = (0..50)
+            .map(|index| {
+                serde_json::from_value(serde_json::json!({
+                    "id": format!("p{index}"),
+                    "name": "A project with a fairly repetitive name"
+                }))
+                .unwrap()
+            })
+            .collect();
+        plain.save_tasks(&tasks, false).unwrap();
+        plain.create_backup(&root.path().join(DATA_FILE)).unwrap();
+        let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+        File::options()
+            .write(true)
+            .open(
+                root.path()
+                    .join(BACKUP_DIR)
+                    .join(format!("data-{date}.json")),
+            )
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(3600))
+            .unwrap();
+
+        let storage = Storage::new(root.path().to_path_buf()).with_backup_policy(BackupPolicy {
+            retention: 2,
+            compress: true,
+            ..BackupPolicy::default()
+        });
+        storage.save_tasks(&tasks, true).unwrap();
+        storage.save_tasks(&tasks, true).unwrap();
+
+        // The plain backup was the oldest and went; the numbering continues past it.
+        let backups = storage.list_backups().unwrap();
+        let mut names: Vec<&str> = backups.iter().map(|(name, ..)| name.as_str()).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                format!("data-{date}-2.json.gz"),
+                format!("data-{date}-3.json.gz")
+            ]
+        );
+        let plain_size = fs::metadata(root.path().join(DATA_FILE)).unwrap().len();
+        assert!(backups
+            .iter()
+            .all(|(_, _, size)| *size > 0 && *size < plain_size));
+
+        assert_eq!(storage.read_backup(names[0]).unwrap().projects.len(), 50);
+        storage.save_tasks(&sample_tasks_file(), false).unwrap();
+        let restored = storage.restore_backup(names[1]).unwrap();
+        assert_eq!(restored.projects.len(), 50);
+        assert_eq!(storage.load_tasks().unwrap().projects.len(), 50);
+        let external = root.path().join(BACKUP_DIR).join(names[1]);
+        assert_eq!(storage.read_external(&external).unwrap().projects.len(), 50);
+
+        let settings = Settings {
+            backup_retention: 0,
+            backup_compress: true,
+            ..Settings::default()
+        };
+        assert_eq!(
+            BackupPolicy::from_settings(&settings),
+            BackupPolicy {
+                retention: 1,
+                compress: true,
+                mirror_dir: None
+            }
+        );
+    }
+
+    #[test]
+    fn mirrored_backups_are_trimmed_without_touching_other_files() {
+        let root = tempfile::tempdir().unwrap();
+        let mirror = tempfile::tempdir().unwrap();
+        fs::write(mirror.path().join("notes.txt"), b"mine").unwrap();
+        let storage = Storage::new(root.path().to_path_buf()).with_backup_policy(BackupPolicy {
+            retention: 2,
+            mirror_dir: Some(mirror.path().to_path_buf()),
+            ..BackupPolicy::default()
+        });
+        storage.ensure_dirs().unwrap();
+        storage.save_tasks(&sample_tasks_file(), false).unwrap();
+        for _ in 0..4 {
+            storage.save_tasks(&sample_tasks_file(), true).unwrap();
+        }
+
+        let count = |dir: &Path| fs::read_dir(dir).unwrap().count();
+        assert_eq!(count(&root.path().join(BACKUP_DIR)), 2);
+        // Two backups plus the unrelated file.
+        assert_eq!(count(mirror.path()), 3);
+        assert!(mirror.path().join("notes.txt").is_file());
+
+        assert!(validate_backup_directory(mirror.path(), root.path()).is_ok());
+        assert!(validate_backup_directory(&root.path().join(BACKUP_DIR), root.path()).is_err());
+        assert!(validate_backup_directory(Path::new("backups"), root.path()).is_err());
+        assert!(validate_backup_directory(&mirror.path().join("missing"), root.path()).is_err());
+        assert!(!mirror.path().join(".mustdo-write-test").exists());
+    }
+
     #[test]
     fn next_backup_name_fails_when_limit_exhausted() {
         let root = tempfile::tempdir().unwrap();
@@ -933,7 +2057,9 @@ mod tests {
         let root = tempfile::tempdir().unwrap();
         File::create(root.path().join(BACKUP_DIR)).unwrap();
         let storage = Storage::new(root.path().to_path_buf());
-        let err = storage.trim_backups().expect_err("read_dir should fail");
+        let err = storage
+            .trim_backups(&root.path().join(BACKUP_DIR))
+            .expect_err("read_dir should fail");
         assert!(is_io(&err));
     }
 
@@ -946,5 +2072,175 @@ mod tests {
             .unwrap_err()
             .into();
         assert!(format!("{json_err}").contains("json error"));
+
+        let sqlite_err: StorageError = rusqlite::Error::InvalidQuery.into();
+        assert!(format!("{sqlite_err}").contains("sqlite error"));
+    }
+
+    fn tasks_with_title(title: &str) -> TasksFile {
+        let mut file = sample_tasks_file();
+        file.tasks = vec![serde_json::from_value(serde_json::json!({
+            "id": "t1",
+            "title": title,
+            "completed_at": null,
+            "created_at": 1,
+            "updated_at": 1,
+            "notes": null
+        }))
+        .unwrap()];
+        file
+    }
+
+    #[test]
+    fn save_state_commits_data_and_settings_together_and_recovers_interrupted_saves() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = Storage::new(root.path().to_path_buf());
+        storage.ensure_dirs().unwrap();
+        let settings = |language: &str| {
+            let mut file = sample_settings_file();
+            file.settings.language = language.to_string();
+            file
+        };
+        let saved = |storage: &Storage| {
+            (
+                storage.load_tasks().unwrap().tasks[0].title.clone(),
+                storage.load_settings().unwrap().settings.language,
+            )
+        };
+        storage
+            .save_state(&tasks_with_title("one"), &settings("en"), false)
+            .unwrap();
+        assert_eq!(saved(&storage), ("one".to_string(), "en".to_string()));
+        let pending_data = pending_path(&root.path().join(DATA_FILE));
+        let pending_settings = pending_path(&root.path().join(SETTINGS_FILE));
+        let journal = root.path().join(JOURNAL_FILE);
+        assert!(!pending_data.exists() && !pending_settings.exists() && !journal.exists());
+
+        // Crash before the journal: the half-written save is dropped, both files stay old.
+        storage
+            .write_atomic(pending_data.clone(), &tasks_with_title("lost"))
+            .unwrap();
+        assert!(storage.recover_journal().unwrap());
+        assert_eq!(saved(&storage), ("one".to_string(), "en".to_string()));
+        assert!(!pending_data.exists());
+
+        // Crash after the journal with only data.json moved: the settings move is finished.
+        storage
+            .write_atomic(pending_settings.clone(), &settings("zh"))
+            .unwrap();
+        storage
+            .write_atomic(root.path().join(DATA_FILE), &tasks_with_title("two"))
+            .unwrap();
+        fs::write(&journal, b"{}").unwrap();
+        storage.ensure_dirs().unwrap();
+        assert_eq!(saved(&storage), ("two".to_string(), "zh".to_string()));
+        assert!(!journal.exists() && !pending_settings.exists());
+        assert!(!storage.recover_journal().unwrap());
+
+        // SQLite: one transaction commits both, without a pending document or journal.
+        let mut sqlite = Storage::new(root.path().to_path_buf());
+        sqlite.migrate_to(StorageEngine::Sqlite).unwrap();
+        sqlite
+            .save_state(&tasks_with_title("rows"), &settings("en"), false)
+            .unwrap();
+        assert_eq!(saved(&sqlite), ("rows".to_string(), "en".to_string()));
+        assert!(!pending_data.exists() && !pending_settings.exists() && !journal.exists());
+        assert!(!sqlite.recover_journal().unwrap());
+
+        // Crash after the commit but before settings.json: the mirror is restored.
+        sqlite
+            .write_atomic(root.path().join(SETTINGS_FILE), &settings("zh"))
+            .unwrap();
+        assert!(sqlite.recover_journal().unwrap());
+        assert_eq!(saved(&sqlite).1, "en");
+        sqlite.save_settings(&settings("fr")).unwrap();
+        assert!(!sqlite.recover_journal().unwrap());
+        assert_eq!(saved(&sqlite).1, "fr");
+    }
+
+    #[test]
+    fn migrate_to_sqlite_and_back_keeps_data_and_a_backup() {
+        let root = tempfile::tempdir().unwrap();
+        let mut storage = Storage::new(root.path().to_path_buf());
+        storage.ensure_dirs().unwrap();
+        assert_eq!(storage.engine(), StorageEngine::Json);
+        storage
+            .save_tasks(&tasks_with_title("json"), false)
+            .unwrap();
+
+        assert_eq!(storage.migrate_to(StorageEngine::Sqlite).unwrap(), 1);
+        assert_eq!(storage.migrate_to(StorageEngine::Sqlite).unwrap(), 0);
+        assert!(!root.path().join(DATA_FILE).exists());
+        assert!(root.path().join(SQLITE_FILE).is_file());
+        assert!(!root.path().join("data.sqlite.tmp").exists());
+        assert_eq!(storage.list_backups().unwrap().len(), 1);
+
+        // A fresh handle picks the engine from the data directory.
+        let sqlite = Storage::new(root.path().to_path_buf());
+        assert_eq!(sqlite.engine(), StorageEngine::Sqlite);
+        assert_eq!(sqlite.load_tasks().unwrap().tasks[0].title, "json");
+
+        // Backups stay data.json-format files and restore back into the database.
+        sqlite
+            .save_tasks(&tasks_with_title("sqlite"), true)
+            .unwrap();
+        let backups = sqlite.list_backups().unwrap();
+        assert_eq!(backups.len(), 2);
+        let newest = &backups[0].0;
+        assert_eq!(sqlite.read_backup(newest).unwrap().tasks[0].title, "json");
+        sqlite.restore_backup(newest).unwrap();
+        assert_eq!(sqlite.load_tasks().unwrap().tasks[0].title, "json");
+        let names: Vec<String> = sqlite
+            .archive_sources()
+            .unwrap()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert!(names.contains(&SQLITE_FILE.to_string()));
+        assert!(!names.contains(&DATA_FILE.to_string()));
+
+        let mut storage = sqlite;
+        assert_eq!(storage.migrate_to(StorageEngine::Json).unwrap(), 1);
+        assert!(!root.path().join(SQLITE_FILE).exists());
+        assert_eq!(
+            Storage::new(root.path().to_path_buf()).engine(),
+            StorageEngine::Json
+        );
+        assert_eq!(storage.load_tasks().unwrap().tasks[0].title, "json");
+        assert_eq!(storage.list_backups().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn migrate_to_sqlite_starts_empty_without_data_and_is_refused_in_guest_mode() {
+        let root = tempfile::tempdir().unwrap();
+        let mut storage = Storage::new(root.path().to_path_buf());
+        storage.ensure_dirs().unwrap();
+        assert_eq!(storage.migrate_to(StorageEngine::Sqlite).unwrap(), 0);
+        assert!(storage.load_tasks().unwrap().tasks.is_empty());
+        assert!(storage.list_backups().unwrap().is_empty());
+
+        let mut guest = Storage::with_backend(
+            root.path().join("guest"),
+            StorageBackend::Memory(MemoryStore::default()),
+        );
+        assert!(is_io(&guest.migrate_to(StorageEngine::Sqlite).unwrap_err()));
+        assert_eq!(guest.engine(), StorageEngine::Json);
+    }
+
+    #[test]
+    fn corrupt_sqlite_file_is_quarantined_with_its_extension() {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join(SQLITE_FILE),
+            b"this is not a database file but it is long enough",
+        )
+        .unwrap();
+        let storage = Storage::new(root.path().to_path_buf());
+        assert_eq!(storage.engine(), StorageEngine::Sqlite);
+        let err = storage.load_tasks().unwrap_err();
+        assert!(crate::recovery::is_corrupt(&err));
+        let name = storage.quarantine_data_file("20260318-090000").unwrap();
+        assert_eq!(name, "data.corrupt-20260318-090000.sqlite");
+        assert!(!root.path().join(SQLITE_FILE).exists());
     }
 }
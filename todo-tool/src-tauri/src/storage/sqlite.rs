@@ -0,0 +1,316 @@
+//! SQLite task store: one row per task / project / tag, keyed by id, so a save only rewrites the
+//! rows whose JSON body actually changed instead of the whole document.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{StorageError, TaskStore};
+use crate::migrations::parse_tasks_file;
+use crate::models::TasksFile;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+CREATE TABLE IF NOT EXISTS projects (id TEXT PRIMARY KEY, body TEXT NOT NULL);
+CREATE TABLE IF NOT EXISTS tasks (id TEXT PRIMARY KEY, body TEXT NOT NULL);
+CREATE TABLE IF NOT EXISTS trash (id TEXT PRIMARY KEY, body TEXT NOT NULL);
+CREATE TABLE IF NOT EXISTS tags (id TEXT PRIMARY KEY, body TEXT NOT NULL);
+";
+
+const SETTINGS_KEY: &str = "settings";
+
+pub struct SqliteTaskStore {
+    path: PathBuf,
+}
+
+impl SqliteTaskStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    fn open(&self) -> Result<Connection, StorageError> {
+        let conn = Connection::open(&self.path)?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(conn)
+    }
+
+    /// Saves the rows and the settings document in one transaction, which makes the commit
+    /// the single point where a `save_state` either happened or did not.
+    pub fn save_with_settings(&self, data: &TasksFile, settings: &str) -> Result<(), StorageError> {
+        self.write(Some(data), Some(settings))
+    }
+
+    pub fn save_settings(&self, settings: &str) -> Result<(), StorageError> {
+        self.write(None, Some(settings))
+    }
+
+    /// The settings document of the last committed save; settings.json mirrors it.
+    pub fn load_settings(&self) -> Result<Option<String>, StorageError> {
+        if !self.path.is_file() {
+            return Ok(None);
+        }
+        Ok(self
+            .open()?
+            .query_row(
+                "SELECT value FROM meta WHERE key = ?1",
+                params![SETTINGS_KEY],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    fn write(&self, data: Option<&TasksFile>, settings: Option<&str>) -> Result<(), StorageError> {
+        let mut conn = self.open()?;
+        let tx = conn.transaction()?;
+        if let Some(settings) = settings {
+            set_meta(&tx, SETTINGS_KEY, settings)?;
+        }
+        if let Some(data) = data {
+            set_meta(&tx, "schema_version", &data.schema_version.to_string())?;
+            let tasks = sync_rows(&tx, "tasks", &data.tasks, |task| task.id.as_str())?;
+            let projects = sync_rows(&tx, "projects", &data.projects, |project| {
+                project.id.as_str()
+            })?;
+            let trash = sync_rows(&tx, "trash", &data.trash, |entry| entry.task.id.as_str())?;
+            let tags = sync_rows(&tx, "tags", &data.tags, |tag| tag.id.as_str())?;
+            log::debug!(
+                "sqlite write path={} changed_tasks={} changed_projects={} changed_trash={} changed_tags={}",
+                self.path.display(),
+                tasks,
+                projects,
+                trash,
+                tags
+            );
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+/// Writes a meta value unless it is already stored; returns the number of rows written.
+fn set_meta(tx: &rusqlite::Transaction<'_>, key: &str, value: &str) -> Result<usize, StorageError> {
+    Ok(tx.execute(
+        "INSERT INTO meta (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value WHERE value != excluded.value",
+        params![key, value],
+    )?)
+}
+
+/// Upserts `(id, body)` rows and drops ids that are gone; the order lives in one meta row
+/// (`<table>_order`). Unchanged rows are skipped by the `WHERE` clause, so SQLite only
+/// touches the pages of rows that differ.
+fn sync_rows<T: Serialize>(
+    tx: &rusqlite::Transaction<'_>,
+    table: &str,
+    rows: &[T],
+    id_of: impl Fn(&T) -> &str,
+) -> Result<usize, StorageError> {
+    let mut changed = 0;
+    let mut upsert = tx.prepare(&format!(
+        "INSERT INTO {table} (id, body) VALUES (?1, ?2)
+         ON CONFLICT(id) DO UPDATE SET body = excluded.body WHERE body != excluded.body"
+    ))?;
+    let mut ids = Vec::with_capacity(rows.len());
+    for row in rows {
+        let body = serde_json::to_string(row)?;
+        changed += upsert.execute(params![id_of(row), body])?;
+        ids.push(id_of(row));
+    }
+    let ids = serde_json::to_string(&ids)?;
+    changed += tx.execute(
+        &format!("DELETE FROM {table} WHERE id NOT IN (SELECT value FROM json_each(?1))"),
+        params![ids],
+    )?;
+    changed += set_meta(tx, &format!("{table}_order"), &ids)?;
+    Ok(changed)
+}
+
+/// Rows in the stored order; rows missing from the order (hand edits) go last, by id.
+fn read_rows(conn: &Connection, table: &str) -> Result<Vec<Value>, StorageError> {
+    let order: Option<String> = conn
+        .query_row(
+            "SELECT value FROM meta WHERE key = ?1",
+            params![format!("{table}_order")],
+            |row| row.get(0),
+        )
+        .optional()?;
+    let order: Vec<String> = match order {
+        Some(order) => serde_json::from_str(&order)?,
+        None => Vec::new(),
+    };
+    let mut stmt = conn.prepare(&format!("SELECT id, body FROM {table} ORDER BY id"))?;
+    let mut bodies: BTreeMap<String, String> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+    let mut ordered = Vec::with_capacity(bodies.len());
+    for id in order {
+        if let Some(body) = bodies.remove(&id) {
+            ordered.push(body);
+        }
+    }
+    ordered.extend(bodies.into_values());
+    ordered
+        .iter()
+        .map(|body| serde_json::from_str(body).map_err(StorageError::from))
+        .collect()
+}
+
+impl TaskStore for SqliteTaskStore {
+    fn exists(&self) -> bool {
+        self.path.is_file()
+    }
+
+    fn load(&self) -> Result<TasksFile, StorageError> {
+        if !self.exists() {
+            return Err(StorageError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("not found: {}", self.path.display()),
+            )));
+        }
+        let conn = self.open()?;
+        let version: Option<String> = conn
+            .query_row(
+                "SELECT value FROM meta WHERE key = 'schema_version'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let version = version.and_then(|v| v.parse::<u32>().ok()).unwrap_or(1);
+        // Rows hold the same JSON the document format does, so migrations apply unchanged.
+        let document = serde_json::json!({
+            "schema_version": version,
+            "tasks": read_rows(&conn, "tasks")?,
+            "projects": read_rows(&conn, "projects")?,
+            "trash": read_rows(&conn, "trash")?,
+            "tags": read_rows(&conn, "tags")?,
+        });
+        let data = parse_tasks_file(&document.to_string())?;
+        log::debug!(
+            "loaded sqlite path={} tasks={} projects={}",
+            self.path.display(),
+            data.tasks.len(),
+            data.projects.len()
+        );
+        Ok(data)
+    }
+
+    fn save(&self, data: &TasksFile) -> Result<(), StorageError> {
+        self.write(Some(data), None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_support, Project, Tag, Task, TrashEntry};
+
+    fn task(id: &str, title: &str) -> Task {
+        test_support::task(id).title(title)
+    }
+
+    fn file(tasks: Vec<Task>) -> TasksFile {
+        TasksFile {
+            schema_version: 1,
+            tasks,
+            projects: vec![Project {
+                id: "inbox".to_string(),
+                name: "Inbox".to_string(),
+                pinned: true,
+                sort_order: 0,
+                created_at: 0,
+                updated_at: 0,
+                sample_tag: None,
+                reminders_muted: false,
+                muted_until: None,
+                notification_sound: None,
+                color: None,
+                icon: None,
+                parent_id: None,
+            }],
+            trash: Vec::new(),
+            tags: Vec::new(),
+        }
+    }
+
+    fn count_changes(store: &SqliteTaskStore, data: &TasksFile) -> usize {
+        let mut conn = store.open().unwrap();
+        let tx = conn.transaction().unwrap();
+        let changed = sync_rows(&tx, "tasks", &data.tasks, |task| task.id.as_str()).unwrap();
+        tx.commit().unwrap();
+        changed
+    }
+
+    #[test]
+    fn roundtrips_in_order_and_reports_missing_file() {
+        let root = tempfile::tempdir().unwrap();
+        let store = SqliteTaskStore::new(root.path().join("data.sqlite"));
+        let err = store.load().unwrap_err();
+        assert!(
+            matches!(err, StorageError::Io(ref io) if io.kind() == std::io::ErrorKind::NotFound)
+        );
+
+        let data = file(vec![task("b", "B"), task("a", "A"), task("c", "C")]);
+        store.save(&data).unwrap();
+        let loaded = store.load().unwrap();
+        let ids: Vec<_> = loaded.tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["b", "a", "c"]);
+        assert_eq!(loaded.projects.len(), 1);
+        assert_eq!(loaded.schema_version, 1);
+
+        let mut data = loaded;
+        let deleted = data.tasks.remove(1);
+        data.trash.push(TrashEntry {
+            task: deleted,
+            deleted_at: 5,
+        });
+        data.tags.push(Tag {
+            id: "t1".to_string(),
+            name: "work".to_string(),
+            color: Some("#336699".to_string()),
+            parent_id: None,
+            created_at: 1,
+            updated_at: 1,
+        });
+        store.save(&data).unwrap();
+        let loaded = store.load().unwrap();
+        assert_eq!(loaded.tasks.len(), 2);
+        assert_eq!(loaded.trash.len(), 1);
+        assert_eq!(loaded.trash[0].task.id, "a");
+        assert_eq!(loaded.trash[0].deleted_at, 5);
+        assert_eq!(loaded.tags, data.tags);
+    }
+
+    #[test]
+    fn save_only_rewrites_changed_and_removed_rows() {
+        let root = tempfile::tempdir().unwrap();
+        let store = SqliteTaskStore::new(root.path().join("data.sqlite"));
+        let mut data = file((0..50).map(|i| task(&i.to_string(), "same")).collect());
+        assert_eq!(count_changes(&store, &data), 51);
+        assert_eq!(count_changes(&store, &data), 0);
+
+        data.tasks[7].title = "edited".to_string();
+        data.tasks.remove(20);
+        // The edited row, the deleted row and the order; the other 47 rows stay untouched.
+        assert_eq!(count_changes(&store, &data), 3);
+        let loaded = store.load().unwrap();
+        assert_eq!(loaded.tasks.len(), 49);
+        assert_eq!(loaded.tasks[7].title, "edited");
+    }
+
+    #[test]
+    fn corrupt_database_is_reported_as_sqlite_error() {
+        let root = tempfile::tempdir().unwrap();
+        let path = root.path().join("data.sqlite");
+        std::fs::write(
+            &path,
+            b"definitely not sqlite, but long enough to look like a header....",
+        )
+        .unwrap();
+        let err = SqliteTaskStore::new(path).load().unwrap_err();
+        assert!(matches!(err, StorageError::Sqlite(_)));
+        assert!(crate::recovery::is_corrupt(&err));
+    }
+}
@@ -0,0 +1,291 @@
+use chrono::{DateTime, Local};
+
+use crate::calendar::WorkHours;
+use crate::models::{Settings, Task};
+
+pub const DEFAULT_SUGGESTION_LIMIT: usize = 3;
+pub const MAX_SUGGESTION_LIMIT: usize = 10;
+
+/// Tasks at or under this estimate count as quick wins.
+const QUICK_WIN_MINUTES: u32 = 15;
+/// Untouched this long and the task starts earning staleness points.
+const STALE_AFTER_DAYS: i64 = 7;
+/// Staleness stops growing after this, so old clutter cannot outrank due work.
+const STALE_CAP_DAYS: i64 = 21;
+
+/// Why a task was suggested; the frontend turns these into localized text.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SuggestionReason {
+    Overdue {
+        minutes: i64,
+    },
+    DueSoon {
+        minutes: i64,
+    },
+    Important,
+    FitsToday {
+        estimate_minutes: u32,
+        remaining_minutes: i64,
+    },
+    QuickWin {
+        estimate_minutes: u32,
+    },
+    TooLongForToday {
+        estimate_minutes: u32,
+        remaining_minutes: i64,
+    },
+    Stale {
+        days: i64,
+    },
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Suggestion {
+    pub task: Task,
+    pub score: i64,
+    /// Strongest contribution first.
+    pub reasons: Vec<SuggestionReason>,
+}
+
+/// Done, someday and delegated tasks are not something to start on now.
+fn is_candidate(task: &Task) -> bool {
+    !task.completed && !task.someday && task.waiting_on.is_none()
+}
+
+fn urgency(
+    task: &Task,
+    now: DateTime<Local>,
+    grace_minutes: i64,
+) -> Option<(i64, SuggestionReason)> {
+    let due_at = task.due_at?;
+    let now = now.timestamp();
+    if task.is_overdue(now, grace_minutes) {
+        let minutes = (now - due_at) / 60;
+        // A little extra per overdue day, capped so one ancient task does not dominate.
+        let score = 50 + (minutes / (24 * 60)).min(10);
+        return Some((score, SuggestionReason::Overdue { minutes }));
+    }
+    let minutes = ((due_at - now) / 60).max(0);
+    let score = match minutes {
+        m if m <= 4 * 60 => 40,
+        m if m <= 24 * 60 => 30,
+        m if m <= 3 * 24 * 60 => 15,
+        _ => return None,
+    };
+    Some((score, SuggestionReason::DueSoon { minutes }))
+}
+
+fn effort(task: &Task, remaining_minutes: i64) -> Vec<(i64, SuggestionReason)> {
+    let Some(estimate_minutes) = task.estimate_minutes.filter(|value| *value > 0) else {
+        return Vec::new();
+    };
+    if i64::from(estimate_minutes) > remaining_minutes {
+        return vec![(
+            -15,
+            SuggestionReason::TooLongForToday {
+                estimate_minutes,
+                remaining_minutes,
+            },
+        )];
+    }
+    let mut out = vec![(
+        10,
+        SuggestionReason::FitsToday {
+            estimate_minutes,
+            remaining_minutes,
+        },
+    )];
+    if estimate_minutes <= QUICK_WIN_MINUTES {
+        out.push((5, SuggestionReason::QuickWin { estimate_minutes }));
+    }
+    out
+}
+
+fn staleness(task: &Task, now: DateTime<Local>) -> Option<(i64, SuggestionReason)> {
+    let days = (now.timestamp() - task.updated_at) / (24 * 60 * 60);
+    if days < STALE_AFTER_DAYS {
+        return None;
+    }
+    Some((
+        days.min(STALE_CAP_DAYS) / 2,
+        SuggestionReason::Stale { days },
+    ))
+}
+
+fn score_task(
+    task: &Task,
+    now: DateTime<Local>,
+    settings: &Settings,
+    remaining: i64,
+) -> Suggestion {
+    let mut parts: Vec<(i64, SuggestionReason)> = Vec::new();
+    parts.extend(urgency(task, now, settings.overdue_grace_minutes));
+    if task.important {
+        parts.push((25, SuggestionReason::Important));
+    }
+    parts.extend(effort(task, remaining));
+    parts.extend(staleness(task, now));
+    parts.sort_by_key(|(points, _)| std::cmp::Reverse(points.abs()));
+    Suggestion {
+        task: task.clone(),
+        score: parts.iter().map(|(points, _)| points).sum(),
+        reasons: parts.into_iter().map(|(_, reason)| reason).collect(),
+    }
+}
+
+/// Ranks open tasks by urgency, importance, fit with the remaining work day and staleness.
+/// Ties go to the earlier due date, then to the manual order. Invalid work hours fall back to
+/// the defaults rather than failing.
+pub fn suggest_next(
+    tasks: &[Task],
+    settings: &Settings,
+    now: DateTime<Local>,
+    limit: usize,
+) -> Vec<Suggestion> {
+    let hours = WorkHours::from_settings(settings).unwrap_or_else(|_| {
+        WorkHours::from_settings(&Settings::default()).expect("default work hours are valid")
+    });
+    let remaining = hours.remaining_minutes(now);
+    let mut ranked: Vec<Suggestion> = tasks
+        .iter()
+        .filter(|task| is_candidate(task))
+        .map(|task| score_task(task, now, settings, remaining))
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| {
+                a.task
+                    .due_at
+                    .unwrap_or(i64::MAX)
+                    .cmp(&b.task.due_at.unwrap_or(i64::MAX))
+            })
+            .then_with(|| a.task.sort_order.cmp(&b.task.sort_order))
+    });
+    ranked.truncate(limit);
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::models::test_support;
+
+    const HOUR: i64 = 60 * 60;
+    const DAY: i64 = 24 * HOUR;
+
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2026, 3, 18, 16, 0, 0).unwrap()
+    }
+
+    fn task(id: &str, due_in: Option<i64>) -> Task {
+        let at = now().timestamp();
+        test_support::task(id)
+            .created(at)
+            .due(due_in.map(|offset| at + offset))
+    }
+
+    fn ids(suggestions: &[Suggestion]) -> Vec<&str> {
+        suggestions.iter().map(|s| s.task.id.as_str()).collect()
+    }
+
+    #[test]
+    fn ranks_by_urgency_and_importance_with_reasons() {
+        let overdue = task("overdue", Some(-2 * DAY));
+        let soon = task("soon", Some(2 * HOUR));
+        let mut important = task("important", None);
+        important.important = true;
+        let later = task("later", Some(10 * DAY));
+
+        let out = suggest_next(
+            &[later, important, soon, overdue],
+            &Settings::default(),
+            now(),
+            10,
+        );
+        assert_eq!(ids(&out), ["overdue", "soon", "important", "later"]);
+        assert_eq!(out[0].score, 52);
+        assert_eq!(
+            out[0].reasons,
+            [SuggestionReason::Overdue {
+                minutes: 2 * 24 * 60
+            }]
+        );
+        assert_eq!(out[1].reasons, [SuggestionReason::DueSoon { minutes: 120 }]);
+        assert_eq!(out[2].reasons, [SuggestionReason::Important]);
+        assert!(out[3].reasons.is_empty());
+    }
+
+    #[test]
+    fn estimates_are_weighed_against_remaining_work_hours() {
+        // 16:00 with the day ending at 18:00 leaves 120 minutes.
+        let mut quick = task("quick", None);
+        quick.estimate_minutes = Some(10);
+        let mut fits = task("fits", None);
+        fits.estimate_minutes = Some(90);
+        let mut long = task("long", None);
+        long.estimate_minutes = Some(180);
+        let plain = task("plain", None);
+
+        let out = suggest_next(&[long, plain, fits, quick], &Settings::default(), now(), 10);
+        assert_eq!(ids(&out), ["quick", "fits", "plain", "long"]);
+        assert_eq!(
+            out[0].reasons,
+            [
+                SuggestionReason::FitsToday {
+                    estimate_minutes: 10,
+                    remaining_minutes: 120
+                },
+                SuggestionReason::QuickWin {
+                    estimate_minutes: 10
+                },
+            ]
+        );
+        assert_eq!(out[3].score, -15);
+
+        // After hours nothing fits, and bad settings fall back to the defaults.
+        let settings = Settings {
+            work_day_end: "nonsense".to_string(),
+            ..Settings::default()
+        };
+        let late = Local.with_ymd_and_hms(2026, 3, 18, 19, 0, 0).unwrap();
+        let mut small = task("small", None);
+        small.estimate_minutes = Some(5);
+        let out = suggest_next(&[small], &settings, late, 1);
+        assert!(matches!(
+            out[0].reasons[0],
+            SuggestionReason::TooLongForToday {
+                remaining_minutes: 0,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn stale_tasks_gain_capped_weight_and_inactive_tasks_are_skipped() {
+        let mut stale = task("stale", None);
+        stale.updated_at -= 10 * DAY;
+        let mut ancient = task("ancient", None);
+        ancient.updated_at -= 400 * DAY;
+        let mut done = task("done", Some(-DAY));
+        done.completed = true;
+        let mut someday = task("someday", Some(-DAY));
+        someday.someday = true;
+        let mut waiting = task("waiting", Some(-DAY));
+        waiting.waiting_on = Some("Ann".to_string());
+
+        let out = suggest_next(
+            &[stale, ancient, done, someday, waiting, task("fresh", None)],
+            &Settings::default(),
+            now(),
+            DEFAULT_SUGGESTION_LIMIT,
+        );
+        assert_eq!(ids(&out), ["ancient", "stale", "fresh"]);
+        assert_eq!(out[0].score, STALE_CAP_DAYS / 2);
+        assert_eq!(out[1].reasons, [SuggestionReason::Stale { days: 10 }]);
+        assert!(suggest_next(&[], &Settings::default(), now(), 3).is_empty());
+    }
+}
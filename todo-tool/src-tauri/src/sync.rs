@@ -0,0 +1,1118 @@
+//! CalDAV sync: tasks are mirrored as VTODO objects in one calendar collection (Nextcloud
+//! Tasks, Apple Reminders via iCloud, ...). Each run lists the collection, plans pushes and
+//! pulls per UID (= task id), and resolves edits made on both sides by `updated_at` against
+//! `LAST-MODIFIED`. `caldav_sync.json` remembers what was in sync after the last run so a
+//! record missing on one side can be told apart from one that was never synced.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use chrono::{Local, TimeZone};
+
+use crate::ics::{parse_time, push_line, push_todo_properties};
+use crate::models::{ReminderConfig, RepeatRule, Settings, Task, Timestamp};
+use crate::rrule::rrule_to_repeat_rule;
+
+const PRODID: &str = "-//MustDo//CalDAV sync//EN";
+pub const MIN_SYNC_INTERVAL_MINUTES: u32 = 5;
+pub const MAX_SYNC_INTERVAL_MINUTES: u32 = 24 * 60;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalDavConfig {
+    /// Calendar collection URL, always ending in `/`.
+    pub url: String,
+    pub username: String,
+    pub password: Option<String>,
+}
+
+/// Validates the CalDAV settings and pairs them with the stored password.
+pub fn caldav_config(
+    settings: &Settings,
+    password: Option<String>,
+) -> Result<CalDavConfig, String> {
+    let url = settings.caldav_url.trim();
+    if url.is_empty() {
+        return Err("caldav url missing (settings.caldav_url)".to_string());
+    }
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err("caldav url must start with http:// or https://".to_string());
+    }
+    let username = settings.caldav_username.trim();
+    if username.is_empty() && password.is_some() {
+        return Err("caldav username missing (settings.caldav_username)".to_string());
+    }
+    let url = if url.ends_with('/') {
+        url.to_string()
+    } else {
+        format!("{url}/")
+    };
+    Ok(CalDavConfig {
+        url,
+        username: username.to_string(),
+        password,
+    })
+}
+
+/// Whether the scheduler should start a sync at `now`. `last_attempt` (kept by the scheduler)
+/// spaces out retries while the server is unreachable, since only successful runs move
+/// `last_caldav_sync_at`.
+pub fn sync_due(settings: &Settings, last_attempt: Option<Timestamp>, now: Timestamp) -> bool {
+    if !settings.caldav_enabled || settings.caldav_url.trim().is_empty() {
+        return false;
+    }
+    let interval = i64::from(settings.caldav_sync_interval_minutes) * 60;
+    [settings.last_caldav_sync_at, last_attempt]
+        .into_iter()
+        .flatten()
+        .all(|at| now - at >= interval)
+}
+
+static SYNC_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Held for the duration of one sync so `sync_now` and the scheduler never overlap.
+pub struct SyncGuard(());
+
+impl SyncGuard {
+    pub fn acquire() -> Option<Self> {
+        SYNC_RUNNING
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| SyncGuard(()))
+    }
+}
+
+impl Drop for SyncGuard {
+    fn drop(&mut self) {
+        SYNC_RUNNING.store(false, Ordering::Release);
+    }
+}
+
+/// One record as of the end of the last successful sync.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SyncedItem {
+    pub href: String,
+    /// `updated_at` / `LAST-MODIFIED` both sides agreed on.
+    pub synced_at: Timestamp,
+}
+
+/// Contents of `caldav_sync.json`, keyed by UID.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CalDavSyncState {
+    /// Collection the items belong to; a different URL starts over with a fresh state.
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub items: BTreeMap<String, SyncedItem>,
+}
+
+/// The fields MustDo maps to and from a VTODO.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VTodo {
+    pub uid: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub due: Option<Timestamp>,
+    pub completed: bool,
+    pub completed_at: Option<Timestamp>,
+    pub important: bool,
+    pub categories: Vec<String>,
+    /// `RRULE` value as sent, end condition included.
+    pub rrule: Option<String>,
+    pub created: Option<Timestamp>,
+    pub last_modified: Timestamp,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteTodo {
+    pub href: String,
+    pub etag: Option<String>,
+    pub todo: VTodo,
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+/// A whole VCALENDAR holding `task` as its only VTODO.
+pub fn task_to_ics(task: &Task, now: Timestamp) -> String {
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, &format!("PRODID:{PRODID}"));
+    push_line(&mut out, "BEGIN:VTODO");
+    push_todo_properties(&mut out, task, now);
+    push_line(&mut out, "END:VTODO");
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+/// Splits at the first `:` outside a quoted parameter value.
+fn split_property(line: &str) -> Option<(&str, &str, &str)> {
+    let mut quoted = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ':' if !quoted => {
+                let (head, value) = (&line[..index], &line[index + 1..]);
+                let (name, params) = head.split_once(';').unwrap_or((head, ""));
+                return Some((name, params, value));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Commas split `CATEGORIES` unless escaped.
+fn split_categories(value: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                current.push(c);
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            ',' => parts.push(std::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    parts.push(current);
+    parts
+        .iter()
+        .map(|part| unescape_text(part).trim().to_string())
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+/// The first VTODO of a calendar object; `None` for events, journals or objects without a UID.
+pub fn parse_vtodo(ics: &str) -> Option<VTodo> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in ics.lines() {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        match raw.strip_prefix([' ', '\t']) {
+            Some(rest) if !lines.is_empty() => lines.last_mut()?.push_str(rest),
+            _ => lines.push(raw.to_string()),
+        }
+    }
+
+    let mut todo = VTodo {
+        uid: String::new(),
+        summary: String::new(),
+        description: None,
+        due: None,
+        completed: false,
+        completed_at: None,
+        important: false,
+        categories: Vec::new(),
+        rrule: None,
+        created: None,
+        last_modified: 0,
+    };
+    let mut dtstamp = None;
+    let mut depth = 0usize;
+    let mut inside = false;
+    for line in &lines {
+        let Some((name, params, value)) = split_property(line) else {
+            continue;
+        };
+        let name = name.to_ascii_uppercase();
+        match (name.as_str(), value.trim().to_ascii_uppercase().as_str()) {
+            ("BEGIN", "VTODO") if !inside && depth == 0 => {
+                inside = true;
+                continue;
+            }
+            ("END", "VTODO") if inside && depth == 0 => break,
+            ("BEGIN", _) if inside => depth += 1,
+            ("END", _) if inside => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        // Properties of nested components (VALARM) are not the task's.
+        if !inside || depth > 0 {
+            continue;
+        }
+        match name.as_str() {
+            "UID" => todo.uid = value.trim().to_string(),
+            "SUMMARY" => todo.summary = unescape_text(value).trim().to_string(),
+            "DESCRIPTION" => {
+                let text = unescape_text(value);
+                todo.description = Some(text).filter(|text| !text.trim().is_empty());
+            }
+            "DUE" => todo.due = parse_time(params, value),
+            "STATUS" => todo.completed = value.trim().eq_ignore_ascii_case("COMPLETED"),
+            "COMPLETED" => todo.completed_at = parse_time(params, value),
+            // RFC 5545: 1-4 is high priority.
+            "PRIORITY" => {
+                todo.important = matches!(value.trim().parse::<u8>(), Ok(1..=4));
+            }
+            "CATEGORIES" => todo.categories.extend(split_categories(value)),
+            "RRULE" => todo.rrule = Some(value.trim().to_string()),
+            "CREATED" => todo.created = parse_time(params, value),
+            "LAST-MODIFIED" => todo.last_modified = parse_time(params, value).unwrap_or(0),
+            "DTSTAMP" => dtstamp = parse_time(params, value),
+            _ => {}
+        }
+    }
+    if !inside || todo.uid.is_empty() {
+        return None;
+    }
+    if todo.completed_at.is_some() {
+        todo.completed = true;
+    }
+    if todo.last_modified == 0 {
+        todo.last_modified = dtstamp.unwrap_or(0);
+    }
+    Some(todo)
+}
+
+/// Takes over a remote recurrence and its end. The remote `DTSTART` is this task's occurrence,
+/// so `COUNT` is added to the occurrences already done here. A rule the model cannot hold
+/// leaves the local one as it was.
+fn apply_rrule(task: &mut Task, rrule: &str, last_modified: Timestamp) {
+    let Some(dtstart) = Local
+        .timestamp_opt(task.due_at.unwrap_or(last_modified), 0)
+        .single()
+    else {
+        return;
+    };
+    match rrule_to_repeat_rule(rrule, dtstart.date_naive()) {
+        Ok(import) => {
+            for note in &import.lossy {
+                log::warn!("caldav: uid={} repeat rule {note}", task.id);
+            }
+            task.repeat = import.rule;
+            task.end_after_occurrences = import
+                .count
+                .map(|count| task.occurrence_index.saturating_add(count));
+            task.end_by_date = import.until;
+        }
+        Err(error) => log::warn!(
+            "caldav: uid={} repeat rule not supported, local rule kept err={error}",
+            task.id
+        ),
+    }
+}
+
+/// Copies the remote fields onto `task`; fields CalDAV has no equivalent for stay as they are,
+/// and so does the repeat of a VTODO without `RRULE`.
+pub fn apply_vtodo(task: &mut Task, todo: &VTodo) {
+    task.title = todo.summary.clone();
+    task.notes = todo.description.clone();
+    task.due_at = todo.due;
+    task.important = todo.important;
+    task.tags = todo.categories.clone();
+    if let Some(rrule) = todo.rrule.as_deref() {
+        apply_rrule(task, rrule, todo.last_modified);
+    }
+    if task.completed != todo.completed {
+        task.completed = todo.completed;
+        task.completed_at = if todo.completed {
+            todo.completed_at.or(Some(todo.last_modified))
+        } else {
+            None
+        };
+    } else if todo.completed && todo.completed_at.is_some() {
+        task.completed_at = todo.completed_at;
+    }
+    task.updated_at = todo.last_modified;
+}
+
+/// A new inbox task for a VTODO created on the server.
+pub fn task_from_vtodo(todo: &VTodo) -> Task {
+    let created_at = todo.created.unwrap_or(todo.last_modified);
+    let mut task = Task {
+        id: todo.uid.clone(),
+        project_id: "inbox".to_string(),
+        title: String::new(),
+        due_at: None,
+        important: false,
+        completed: false,
+        completed_at: None,
+        created_at,
+        updated_at: todo.last_modified,
+        sort_order: created_at * 1000,
+        quadrant: 1,
+        notes: None,
+        steps: Vec::new(),
+        tags: Vec::new(),
+        sample_tag: None,
+        reminder: ReminderConfig::default(),
+        repeat: RepeatRule::None,
+        waiting_on: None,
+        waiting_since: None,
+        series_id: None,
+        pinned: false,
+        context: None,
+        comments: Vec::new(),
+        progress: Default::default(),
+        spawn_ahead: 0,
+        repeat_from_completion: false,
+        end_after_occurrences: None,
+        end_by_date: None,
+        occurrence_index: 0,
+        timezone: None,
+        escalate_after: None,
+        start_at: None,
+        someday: false,
+        carry_over_count: 0,
+        skip_carry_over: false,
+        color: None,
+        emoji: None,
+        estimate_minutes: None,
+        attachments: Vec::new(),
+        time_entries: Vec::new(),
+        blocked_by: Vec::new(),
+    };
+    apply_vtodo(&mut task, todo);
+    task
+}
+
+/// Absolute URL for a `href` from a multistatus response (usually an absolute path).
+pub fn resolve_href(base: &str, href: &str) -> String {
+    if href.starts_with("http://") || href.starts_with("https://") {
+        return href.to_string();
+    }
+    if let Some(path) = href.strip_prefix('/') {
+        let origin_end = base
+            .find("://")
+            .and_then(|scheme| base[scheme + 3..].find('/').map(|i| scheme + 3 + i))
+            .unwrap_or(base.len());
+        return format!("{}/{}", &base[..origin_end], path);
+    }
+    format!("{base}{href}")
+}
+
+fn unescape_xml(text: &str) -> String {
+    let trimmed = text.trim();
+    if let Some(raw) = trimmed
+        .strip_prefix("<![CDATA[")
+        .and_then(|inner| inner.strip_suffix("]]>"))
+    {
+        return raw.to_string();
+    }
+    let mut out = String::with_capacity(trimmed.len());
+    let mut rest = trimmed;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start..];
+        let Some(end) = after.find(';') else {
+            out.push_str(after);
+            return out;
+        };
+        let entity = &after[1..end];
+        let decoded = match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => out.push(c),
+            None => out.push_str(&after[..=end]),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Inner text of every `local` element, whatever namespace prefix the server picked.
+fn elements<'a>(xml: &'a str, local: &str) -> Vec<&'a str> {
+    let mut found = Vec::new();
+    let mut rest = xml;
+    while let Some(open) = rest.find('<') {
+        let tag = &rest[open + 1..];
+        let name_end = tag
+            .find(|c: char| c.is_whitespace() || c == '>' || c == '/')
+            .unwrap_or(tag.len());
+        let name = &tag[..name_end];
+        let Some(close) = tag.find('>') else {
+            break;
+        };
+        let self_closing = tag[..close].ends_with('/');
+        let local_name = name.rsplit(':').next().unwrap_or(name);
+        if local_name.eq_ignore_ascii_case(local) && !name.starts_with(['/', '?', '!']) {
+            if self_closing {
+                found.push("");
+                rest = &tag[close + 1..];
+                continue;
+            }
+            let body = &tag[close + 1..];
+            let end_tag = format!("</{name}>");
+            match body.find(&end_tag) {
+                Some(end) => {
+                    found.push(&body[..end]);
+                    rest = &body[end + end_tag.len()..];
+                }
+                None => break,
+            }
+            continue;
+        }
+        rest = &tag[close + 1..];
+    }
+    found
+}
+
+/// `href` / `getetag` / `calendar-data` of each response in a `REPORT` multistatus; responses
+/// that are not VTODOs (or failed with a 404 propstat) are skipped.
+pub fn parse_multistatus(xml: &str) -> Vec<RemoteTodo> {
+    elements(xml, "response")
+        .into_iter()
+        .filter_map(|response| {
+            let href = unescape_xml(elements(response, "href").first()?);
+            let etag = elements(response, "getetag")
+                .first()
+                .map(|etag| unescape_xml(etag))
+                .filter(|etag| !etag.is_empty());
+            let data = unescape_xml(elements(response, "calendar-data").first()?);
+            let todo = parse_vtodo(&data)?;
+            Some(RemoteTodo { href, etag, todo })
+        })
+        .collect()
+}
+
+/// Body of the `REPORT` that lists every VTODO with its data and ETag.
+pub const CALENDAR_QUERY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop><d:getetag/><c:calendar-data/></d:prop>
+  <c:filter><c:comp-filter name="VCALENDAR"><c:comp-filter name="VTODO"/></c:comp-filter></c:filter>
+</c:calendar-query>"#;
+
+#[derive(Debug, Clone)]
+pub struct PushItem {
+    pub task: Task,
+    pub href: String,
+    /// `If-Match` for updates; `None` creates with `If-None-Match: *`.
+    pub etag: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteDelete {
+    pub uid: String,
+    pub href: String,
+    pub etag: Option<String>,
+}
+
+/// What one sync run does, decided from a single listing of the collection.
+#[derive(Debug, Clone, Default)]
+pub struct SyncPlan {
+    pub push: Vec<PushItem>,
+    pub delete_remote: Vec<RemoteDelete>,
+    /// Remote versions to store locally (new tasks or updates), with the local `updated_at`
+    /// seen while planning so an edit made during the run is not overwritten.
+    pub pull: Vec<(Task, Option<Timestamp>)>,
+    pub delete_local: Vec<(String, Timestamp)>,
+    /// Records changed on both sides since the last sync; the newer `updated_at` won.
+    pub conflicts: usize,
+    /// Records already in sync, carried into the next state unchanged.
+    pub unchanged: BTreeMap<String, SyncedItem>,
+}
+
+/// Requests that failed during a run, by UID, with one message each.
+#[derive(Debug, Default)]
+pub struct RemoteOutcome {
+    pub failed: HashSet<String>,
+    pub errors: Vec<String>,
+}
+
+impl RemoteOutcome {
+    pub fn fail(&mut self, uid: &str, message: String) {
+        self.failed.insert(uid.to_string());
+        self.errors.push(message);
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct SyncReport {
+    pub pushed: usize,
+    pub pulled: usize,
+    pub deleted_local: usize,
+    pub deleted_remote: usize,
+    pub conflicts: usize,
+    /// One line per request that failed; those records are retried on the next run.
+    pub errors: Vec<String>,
+}
+
+pub fn plan_sync(
+    config_url: &str,
+    local: &[Task],
+    remote: &[RemoteTodo],
+    previous: &CalDavSyncState,
+) -> SyncPlan {
+    let empty = CalDavSyncState::default();
+    let known = if previous.url == config_url {
+        previous
+    } else {
+        &empty
+    };
+    let local_by_id: BTreeMap<&str, &Task> =
+        local.iter().map(|task| (task.id.as_str(), task)).collect();
+    let remote_by_id: BTreeMap<&str, &RemoteTodo> = remote
+        .iter()
+        .map(|item| (item.todo.uid.as_str(), item))
+        .collect();
+    let ids: BTreeSet<&str> = local_by_id
+        .keys()
+        .chain(remote_by_id.keys())
+        .copied()
+        .collect();
+
+    let mut plan = SyncPlan::default();
+    for id in ids {
+        let synced = known.items.get(id);
+        let synced_at = synced.map(|item| item.synced_at);
+        let changed_since = |at: Timestamp| synced_at.is_none_or(|synced| at > synced);
+        match (local_by_id.get(id), remote_by_id.get(id)) {
+            (Some(task), Some(item)) => {
+                let remote_at = item.todo.last_modified;
+                if task.updated_at == remote_at {
+                    plan.unchanged.insert(
+                        id.to_string(),
+                        SyncedItem {
+                            href: item.href.clone(),
+                            synced_at: remote_at,
+                        },
+                    );
+                    continue;
+                }
+                if synced.is_some() && changed_since(task.updated_at) && changed_since(remote_at) {
+                    plan.conflicts += 1;
+                }
+                if task.updated_at > remote_at {
+                    plan.push.push(PushItem {
+                        task: (*task).clone(),
+                        href: item.href.clone(),
+                        etag: item.etag.clone(),
+                    });
+                } else {
+                    let mut next = (*task).clone();
+                    apply_vtodo(&mut next, &item.todo);
+                    plan.pull.push((next, Some(task.updated_at)));
+                }
+            }
+            (Some(task), None) => match synced {
+                // Deleted on the server and not edited here since: follow the server.
+                Some(_) if !changed_since(task.updated_at) => {
+                    plan.delete_local.push((id.to_string(), task.updated_at));
+                }
+                _ => plan.push.push(PushItem {
+                    task: (*task).clone(),
+                    href: synced
+                        .map(|item| item.href.clone())
+                        .unwrap_or_else(|| format!("{}.ics", encode_path_segment(id))),
+                    etag: None,
+                }),
+            },
+            (None, Some(item)) => match synced {
+                Some(_) if !changed_since(item.todo.last_modified) => {
+                    plan.delete_remote.push(RemoteDelete {
+                        uid: id.to_string(),
+                        href: item.href.clone(),
+                        etag: item.etag.clone(),
+                    });
+                }
+                _ => plan.pull.push((task_from_vtodo(&item.todo), None)),
+            },
+            (None, None) => {}
+        }
+    }
+    plan
+}
+
+/// Task ids are uuids in practice; anything else is percent-encoded for the object name.
+fn encode_path_segment(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+/// State to remember after a run: everything in sync, minus the requests that failed.
+pub fn next_sync_state(
+    config_url: &str,
+    plan: &SyncPlan,
+    applied_pulls: &HashSet<String>,
+    failed: &HashSet<String>,
+    remote: &[RemoteTodo],
+    previous: &CalDavSyncState,
+) -> CalDavSyncState {
+    let mut items = plan.unchanged.clone();
+    for item in &plan.push {
+        if failed.contains(&item.task.id) {
+            if let Some(known) = previous.items.get(&item.task.id) {
+                items.insert(item.task.id.clone(), known.clone());
+            }
+            continue;
+        }
+        items.insert(
+            item.task.id.clone(),
+            SyncedItem {
+                href: item.href.clone(),
+                synced_at: item.task.updated_at,
+            },
+        );
+    }
+    for delete in &plan.delete_remote {
+        if failed.contains(&delete.uid) {
+            if let Some(known) = previous.items.get(&delete.uid) {
+                items.insert(delete.uid.clone(), known.clone());
+            }
+        }
+    }
+    for (task, _) in &plan.pull {
+        if !applied_pulls.contains(&task.id) {
+            continue;
+        }
+        if let Some(item) = remote.iter().find(|item| item.todo.uid == task.id) {
+            items.insert(
+                task.id.clone(),
+                SyncedItem {
+                    href: item.href.clone(),
+                    synced_at: item.todo.last_modified,
+                },
+            );
+        }
+    }
+    CalDavSyncState {
+        url: config_url.to_string(),
+        items,
+    }
+}
+
+#[cfg(all(feature = "app", not(test)))]
+fn client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .map_err(|err| format!("failed to build http client: {err}"))
+}
+
+#[cfg(all(feature = "app", not(test)))]
+fn authorized(config: &CalDavConfig, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    if config.username.is_empty() {
+        return request;
+    }
+    request.basic_auth(&config.username, config.password.as_deref())
+}
+
+/// Lists every VTODO in the collection.
+#[cfg(all(feature = "app", not(test)))]
+pub async fn fetch_remote(config: &CalDavConfig) -> Result<Vec<RemoteTodo>, String> {
+    let method = reqwest::Method::from_bytes(b"REPORT").map_err(|err| err.to_string())?;
+    let request = client()?
+        .request(method, &config.url)
+        .header("Depth", "1")
+        .header("Content-Type", "application/xml; charset=utf-8")
+        .body(CALENDAR_QUERY);
+    let resp = authorized(config, request)
+        .send()
+        .await
+        .map_err(|err| format!("caldav request failed: {err}"))?;
+    let status = resp.status();
+    let text = resp
+        .text()
+        .await
+        .map_err(|err| format!("failed to read caldav response: {err}"))?;
+    if !status.is_success() {
+        return Err(format!("caldav REPORT http {status}"));
+    }
+    Ok(parse_multistatus(&text))
+}
+
+#[cfg(all(feature = "app", not(test)))]
+pub async fn push_todo(
+    config: &CalDavConfig,
+    item: &PushItem,
+    now: Timestamp,
+) -> Result<(), String> {
+    let request = client()?
+        .put(resolve_href(&config.url, &item.href))
+        .header("Content-Type", "text/calendar; charset=utf-8")
+        .body(task_to_ics(&item.task, now));
+    let request = match item.etag.as_deref() {
+        Some(etag) => request.header("If-Match", etag),
+        None => request.header("If-None-Match", "*"),
+    };
+    let resp = authorized(config, request)
+        .send()
+        .await
+        .map_err(|err| format!("caldav PUT {} failed: {err}", item.href))?;
+    if !resp.status().is_success() {
+        return Err(format!("caldav PUT {} http {}", item.href, resp.status()));
+    }
+    Ok(())
+}
+
+#[cfg(all(feature = "app", not(test)))]
+pub async fn delete_todo(config: &CalDavConfig, target: &RemoteDelete) -> Result<(), String> {
+    let request = client()?.delete(resolve_href(&config.url, &target.href));
+    let request = match target.etag.as_deref() {
+        Some(etag) => request.header("If-Match", etag),
+        None => request,
+    };
+    let resp = authorized(config, request)
+        .send()
+        .await
+        .map_err(|err| format!("caldav DELETE {} failed: {err}", target.href))?;
+    // Already gone counts as done.
+    if !resp.status().is_success() && resp.status() != reqwest::StatusCode::NOT_FOUND {
+        return Err(format!(
+            "caldav DELETE {} http {}",
+            target.href,
+            resp.status()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(id: &str, title: &str, updated_at: Timestamp) -> Task {
+        let mut task = task_from_vtodo(&VTodo {
+            uid: id.to_string(),
+            summary: title.to_string(),
+            description: None,
+            due: None,
+            completed: false,
+            completed_at: None,
+            important: false,
+            categories: Vec::new(),
+            rrule: None,
+            created: Some(1),
+            last_modified: updated_at,
+        });
+        task.updated_at = updated_at;
+        task
+    }
+
+    fn remote(task: &Task) -> RemoteTodo {
+        RemoteTodo {
+            href: format!("/cal/{}.ics", task.id),
+            etag: Some(format!("\"{}\"", task.updated_at)),
+            todo: parse_vtodo(&task_to_ics(task, 0)).unwrap(),
+        }
+    }
+
+    fn synced(items: &[(&str, Timestamp)]) -> CalDavSyncState {
+        CalDavSyncState {
+            url: "https://dav/cal/".to_string(),
+            items: items
+                .iter()
+                .map(|(id, at)| {
+                    (
+                        id.to_string(),
+                        SyncedItem {
+                            href: format!("/cal/{id}.ics"),
+                            synced_at: *at,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn vtodo_roundtrips_and_ignores_nested_components() {
+        let mut original = task("t-1", "Call Anna; bring notes, pens", 1_700_000_100);
+        original.notes = Some("line one\nline two \\ done".to_string());
+        original.due_at = Some(1_700_003_600);
+        original.important = true;
+        original.tags = vec!["work".to_string(), "a,b".to_string()];
+        original.completed = true;
+        original.completed_at = Some(1_700_000_050);
+        original.title = format!("{} {}", original.title, "长".repeat(40));
+
+        let ics = task_to_ics(&original, 1_700_000_200);
+        assert!(ics
+            .lines()
+            .all(|line| line.trim_end_matches('\r').len() <= 75));
+        let todo = parse_vtodo(&ics).unwrap();
+        assert_eq!(todo.uid, "t-1");
+        assert_eq!(todo.summary, original.title);
+        assert_eq!(todo.description, original.notes);
+        assert_eq!(todo.due, original.due_at);
+        assert!(todo.important && todo.completed);
+        assert_eq!(todo.completed_at, Some(1_700_000_050));
+        assert_eq!(todo.categories, original.tags);
+        assert_eq!(todo.last_modified, 1_700_000_100);
+
+        let other = "BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\nUID:x\r\nSUMMARY:Outer\r\nPRIORITY:5\r\n\
+                     DTSTAMP:20260318T090000Z\r\nBEGIN:VALARM\r\nSUMMARY:Alarm\r\nEND:VALARM\r\n\
+                     DUE;VALUE=DATE:20260320\r\nEND:VTODO\r\nEND:VCALENDAR\r\n";
+        let todo = parse_vtodo(other).unwrap();
+        assert_eq!(todo.summary, "Outer");
+        assert!(!todo.important);
+        assert_eq!(todo.last_modified, 1_773_824_400);
+        let due = Local.timestamp_opt(todo.due.unwrap(), 0).unwrap();
+        assert_eq!(due.format("%Y-%m-%d %H:%M").to_string(), "2026-03-20 23:59");
+        assert!(
+            parse_vtodo("BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:e\r\nEND:VEVENT\r\n").is_none()
+        );
+    }
+
+    #[test]
+    fn repeats_and_their_end_travel_both_ways() {
+        let due = Local
+            .with_ymd_and_hms(2026, 3, 18, 9, 0, 0)
+            .unwrap()
+            .timestamp();
+        let mut original = task("r", "Standup", 10);
+        original.due_at = Some(due);
+        original.repeat = RepeatRule::Weekly { days: vec![3] };
+        original.end_by_date = Some(due + 60 * 86_400);
+
+        let todo = parse_vtodo(&task_to_ics(&original, 20)).unwrap();
+        assert_eq!(todo.due, Some(due));
+        let pulled = task_from_vtodo(&todo);
+        assert_eq!(pulled.repeat, original.repeat);
+        assert_eq!(pulled.end_by_date, original.end_by_date);
+
+        // The server counts from this occurrence; the local count keeps the ones already done.
+        original.end_by_date = None;
+        original.end_after_occurrences = Some(5);
+        original.occurrence_index = 3;
+        let todo = parse_vtodo(&task_to_ics(&original, 20)).unwrap();
+        assert_eq!(todo.rrule.as_deref(), Some("FREQ=WEEKLY;BYDAY=WE;COUNT=2"));
+        let mut local = original.clone();
+        local.end_after_occurrences = None;
+        apply_vtodo(&mut local, &todo);
+        assert_eq!(local.end_after_occurrences, Some(5));
+        assert_eq!(task_from_vtodo(&todo).end_after_occurrences, Some(2));
+
+        // Without RRULE, or with one the model cannot hold, the local repeat stays.
+        let mut plain = todo.clone();
+        plain.rrule = None;
+        apply_vtodo(&mut local, &plain);
+        assert_eq!(local.repeat, original.repeat);
+        plain.rrule = Some("FREQ=HOURLY".to_string());
+        apply_vtodo(&mut local, &plain);
+        assert_eq!(local.repeat, original.repeat);
+    }
+
+    #[test]
+    fn multistatus_parsing_handles_prefixes_entities_and_skips_non_todos() {
+        let xml = r#"<?xml version="1.0"?>
+<D:multistatus xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+  <D:response>
+    <D:href>/dav/cal/a.ics</D:href>
+    <D:propstat><D:prop>
+      <D:getetag>&quot;e1&quot;</D:getetag>
+      <C:calendar-data>BEGIN:VCALENDAR&#13;
+BEGIN:VTODO&#13;
+UID:a&#13;
+SUMMARY:Tom &amp; Jerry&#13;
+END:VTODO&#13;
+END:VCALENDAR&#13;
+</C:calendar-data>
+    </D:prop></D:propstat>
+  </D:response>
+  <response xmlns="DAV:">
+    <href>/dav/cal/b.ics</href>
+    <propstat><prop><getetag/><cal:calendar-data xmlns:cal="urn:ietf:params:xml:ns:caldav"><![CDATA[BEGIN:VCALENDAR
+BEGIN:VTODO
+UID:b
+SUMMARY:<b>
+END:VTODO
+END:VCALENDAR]]></cal:calendar-data></prop></propstat>
+  </response>
+  <D:response><D:href>/dav/cal/event.ics</D:href><D:propstat><D:prop><C:calendar-data>BEGIN:VCALENDAR
+BEGIN:VEVENT
+UID:e
+END:VEVENT
+END:VCALENDAR</C:calendar-data></D:prop></D:propstat></D:response>
+  <D:response><D:href>/dav/cal/gone.ics</D:href><D:status>HTTP/1.1 404 Not Found</D:status></D:response>
+</D:multistatus>"#;
+        let items = parse_multistatus(xml);
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].href, "/dav/cal/a.ics");
+        assert_eq!(items[0].etag.as_deref(), Some("\"e1\""));
+        assert_eq!(items[0].todo.summary, "Tom & Jerry");
+        assert_eq!(items[1].etag, None);
+        assert_eq!(items[1].todo.summary, "<b>");
+
+        let base = "https://dav.example.com/remote.php/dav/cal/";
+        assert_eq!(
+            resolve_href(base, "/remote.php/dav/cal/a.ics"),
+            "https://dav.example.com/remote.php/dav/cal/a.ics"
+        );
+        assert_eq!(resolve_href(base, "a.ics"), format!("{base}a.ics"));
+        assert_eq!(resolve_href(base, "https://x/y.ics"), "https://x/y.ics");
+        assert!(CALENDAR_QUERY.contains("VTODO"));
+    }
+
+    #[test]
+    fn plan_resolves_edits_by_updated_at_and_follows_deletions() {
+        let url = "https://dav/cal/";
+        let same = task("same", "Same", 100);
+        let local_newer = task("local-newer", "Local", 300);
+        let remote_newer = task("remote-newer", "Old", 100);
+        let mut remote_newer_there = remote_newer.clone();
+        remote_newer_there.title = "Edited remotely".to_string();
+        remote_newer_there.updated_at = 400;
+        let new_local = task("new local/1", "New here", 50);
+        let new_remote = task("new-remote", "New there", 60);
+        let gone_remote = task("gone-remote", "Deleted remotely", 100);
+        let edited_after_remote_delete = task("revived", "Edited here", 500);
+        let gone_local = task("gone-local", "Deleted here", 100);
+        let remote_edit_after_local_delete = task("kept-remote", "Edited there", 700);
+
+        let local = vec![
+            same.clone(),
+            local_newer.clone(),
+            remote_newer.clone(),
+            new_local.clone(),
+            gone_remote,
+            edited_after_remote_delete,
+        ];
+        let mut older = local_newer.clone();
+        older.updated_at = 200;
+        let server = vec![
+            remote(&same),
+            remote(&older),
+            remote(&remote_newer_there),
+            remote(&new_remote),
+            remote(&gone_local),
+            remote(&remote_edit_after_local_delete),
+        ];
+        let previous = synced(&[
+            ("same", 100),
+            ("local-newer", 100),
+            ("remote-newer", 100),
+            ("gone-remote", 100),
+            ("revived", 100),
+            ("gone-local", 100),
+            ("kept-remote", 100),
+        ]);
+        let plan = plan_sync(url, &local, &server, &previous);
+
+        let pushed: Vec<_> = plan
+            .push
+            .iter()
+            .map(|item| {
+                (
+                    item.task.id.as_str(),
+                    item.href.as_str(),
+                    item.etag.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            pushed,
+            vec![
+                ("local-newer", "/cal/local-newer.ics", Some("\"200\"")),
+                ("new local/1", "new%20local%2F1.ics", None),
+                ("revived", "/cal/revived.ics", None),
+            ]
+        );
+        let pulled: Vec<_> = plan
+            .pull
+            .iter()
+            .map(|(task, seen)| (task.id.as_str(), task.title.as_str(), *seen))
+            .collect();
+        assert_eq!(
+            pulled,
+            vec![
+                ("kept-remote", "Edited there", None),
+                ("new-remote", "New there", None),
+                ("remote-newer", "Edited remotely", Some(100)),
+            ]
+        );
+        assert_eq!(plan.delete_local, vec![("gone-remote".to_string(), 100)]);
+        let deleted: Vec<_> = plan.delete_remote.iter().map(|d| d.uid.as_str()).collect();
+        assert_eq!(deleted, vec!["gone-local"]);
+        // Both sides moved past the last sync only for local-newer.
+        assert_eq!(plan.conflicts, 1);
+        assert_eq!(plan.unchanged.keys().collect::<Vec<_>>(), vec!["same"]);
+
+        // Another collection: nothing is known, so nothing is deleted.
+        let plan = plan_sync("https://other/", &local, &server, &previous);
+        assert!(plan.delete_local.is_empty() && plan.delete_remote.is_empty());
+        assert_eq!(plan.conflicts, 0);
+    }
+
+    #[test]
+    fn next_state_keeps_failed_records_for_a_retry() {
+        let url = "https://dav/cal/";
+        let a = task("a", "A", 300);
+        let b = task("b", "B", 50);
+        let c = task("c", "C", 80);
+        let server = vec![remote(&c), remote(&task("d", "D", 100))];
+        let previous = synced(&[("a", 100), ("d", 100)]);
+        let plan = plan_sync(url, &[a, b], &server, &previous);
+        assert_eq!(plan.push.len(), 2);
+        assert_eq!(plan.delete_remote.len(), 1);
+
+        let mut outcome = RemoteOutcome::default();
+        outcome.fail("a", "http 412".to_string());
+        outcome.fail("d", "http 500".to_string());
+        let applied: HashSet<String> = ["c".to_string()].into();
+        let next = next_sync_state(url, &plan, &applied, &outcome.failed, &server, &previous);
+        assert_eq!(next.url, url);
+        let items: Vec<_> = next
+            .items
+            .iter()
+            .map(|(id, item)| (id.as_str(), item.synced_at))
+            .collect();
+        assert_eq!(items, vec![("a", 100), ("b", 50), ("c", 80), ("d", 100)]);
+    }
+
+    #[test]
+    fn config_validation_schedule_and_single_run_guard() {
+        let mut settings = Settings::default();
+        assert!(caldav_config(&settings, None).unwrap_err().contains("url"));
+        settings.caldav_url = "ftp://x".to_string();
+        assert!(caldav_config(&settings, None).is_err());
+        settings.caldav_url = " https://dav.example.com/cal ".to_string();
+        assert!(caldav_config(&settings, Some("pw".to_string()))
+            .unwrap_err()
+            .contains("username"));
+        settings.caldav_username = "me".to_string();
+        let config = caldav_config(&settings, Some("pw".to_string())).unwrap();
+        assert_eq!(config.url, "https://dav.example.com/cal/");
+
+        assert!(!sync_due(&settings, None, 10_000));
+        settings.caldav_enabled = true;
+        assert!(sync_due(&settings, None, 10_000));
+        settings.last_caldav_sync_at = Some(10_000 - 29 * 60);
+        assert!(!sync_due(&settings, None, 10_000));
+        settings.last_caldav_sync_at = Some(10_000 - 30 * 60);
+        assert!(sync_due(&settings, None, 10_000));
+        assert!(!sync_due(&settings, Some(9_000), 10_000));
+
+        let guard = SyncGuard::acquire().unwrap();
+        assert!(SyncGuard::acquire().is_none());
+        drop(guard);
+        assert!(SyncGuard::acquire().is_some());
+    }
+}
@@ -0,0 +1,456 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::flair::normalize_color;
+use crate::infer::normalize_tag;
+use crate::models::{Tag, Task, Timestamp};
+
+pub const DEFAULT_SUGGEST_LIMIT: usize = 10;
+pub const MAX_SUGGEST_LIMIT: usize = 50;
+
+/// Tags compare case-insensitively everywhere (filters, exports, smart lists).
+pub fn tag_key(raw: &str) -> Option<String> {
+    normalize_tag(raw).map(|tag| tag.to_lowercase())
+}
+
+/// The definition `name` refers to, if any.
+pub fn definition_for<'a>(defs: &'a [Tag], name: &str) -> Option<&'a Tag> {
+    let key = tag_key(name)?;
+    defs.iter()
+        .find(|def| tag_key(&def.name).as_ref() == Some(&key))
+}
+
+fn parent_of<'a>(defs: &'a [Tag], id: &str) -> Option<&'a str> {
+    defs.iter()
+        .find(|def| def.id == id)
+        .and_then(|def| def.parent_id.as_deref())
+}
+
+/// Clears `parent_id`s pointing at a missing tag, at the tag itself or around a cycle.
+pub fn normalize_tag_parents(defs: &mut [Tag]) {
+    let ids: HashSet<String> = defs.iter().map(|def| def.id.clone()).collect();
+    for def in defs.iter_mut() {
+        if def
+            .parent_id
+            .as_ref()
+            .is_some_and(|parent| *parent == def.id || !ids.contains(parent))
+        {
+            def.parent_id = None;
+        }
+    }
+    for i in 0..defs.len() {
+        let id = defs[i].id.clone();
+        let mut seen = HashSet::from([id.as_str()]);
+        let mut cursor = defs[i].parent_id.as_deref();
+        let mut in_cycle = false;
+        while let Some(parent) = cursor {
+            if parent == id {
+                in_cycle = true;
+                break;
+            }
+            if !seen.insert(parent) {
+                break;
+            }
+            cursor = parent_of(defs, parent);
+        }
+        if in_cycle {
+            defs[i].parent_id = None;
+        }
+    }
+}
+
+/// Checks a definition before it is saved next to `defs` (which may hold its old version):
+/// trims the id, normalizes the name like the composer does, checks the color, and refuses a
+/// name another definition has or a parent that is missing or nested under this tag.
+pub fn normalize_tag_definition(tag: &mut Tag, defs: &[Tag]) -> Result<(), String> {
+    tag.id = tag.id.trim().to_string();
+    if tag.id.is_empty() {
+        return Err("tag id is required".to_string());
+    }
+    tag.name = normalize_tag(&tag.name).ok_or_else(|| "tag name is required".to_string())?;
+    if let Some(other) = definition_for(defs, &tag.name).filter(|other| other.id != tag.id) {
+        return Err(format!("tag already exists: {}", other.name));
+    }
+    tag.color = match tag.color.as_deref() {
+        Some(raw) => normalize_color(raw)?,
+        None => None,
+    };
+    tag.parent_id = tag
+        .parent_id
+        .as_deref()
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string);
+    if let Some(parent_id) = tag.parent_id.as_deref() {
+        if !defs.iter().any(|def| def.id == parent_id) {
+            return Err(format!("parent tag not found: {parent_id}"));
+        }
+        let mut seen = HashSet::new();
+        let mut cursor = Some(parent_id);
+        while let Some(id) = cursor {
+            if id == tag.id {
+                return Err("tag cannot be nested under itself".to_string());
+            }
+            if !seen.insert(id) {
+                break;
+            }
+            cursor = parent_of(defs, id);
+        }
+    }
+    Ok(())
+}
+
+/// Drops the definition `tag_id`; its children move up to its parent.
+pub fn remove_definition(defs: &mut Vec<Tag>, tag_id: &str) -> Option<Tag> {
+    let position = defs.iter().position(|def| def.id == tag_id)?;
+    let removed = defs.remove(position);
+    for def in defs.iter_mut() {
+        if def.parent_id.as_deref() == Some(tag_id) {
+            def.parent_id = removed.parent_id.clone();
+        }
+    }
+    Some(removed)
+}
+
+/// Follows `retag` for the definitions: a source definition is renamed to `target`, or folded
+/// into the target's definition (children included) when there already is one.
+pub fn retag_definitions(defs: &mut Vec<Tag>, sources: &[String], target: &str, now: Timestamp) {
+    let target_key = tag_key(target);
+    let mut kept_id = definition_for(defs, target).map(|def| def.id.clone());
+    let source_ids: Vec<String> = defs
+        .iter()
+        .filter(|def| {
+            tag_key(&def.name).is_some_and(|key| sources.contains(&key))
+                && tag_key(&def.name) != target_key
+        })
+        .map(|def| def.id.clone())
+        .collect();
+    for id in source_ids {
+        match kept_id.as_deref() {
+            Some(kept) => {
+                for def in defs.iter_mut() {
+                    if def.parent_id.as_deref() == Some(id.as_str()) {
+                        def.parent_id = Some(kept.to_string());
+                    }
+                }
+                defs.retain(|def| def.id != id);
+            }
+            None => {
+                kept_id = Some(id);
+            }
+        }
+    }
+    if let Some(def) = kept_id
+        .as_deref()
+        .and_then(|kept| defs.iter_mut().find(|def| def.id == kept))
+    {
+        if def.name != target {
+            def.name = target.to_string();
+            def.updated_at = now;
+        }
+    }
+    normalize_tag_parents(defs);
+}
+
+/// A task's tag with its definition resolved, as exports and the AI prompt show it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TagDetail {
+    pub name: String,
+    /// Ancestors and the tag joined by `/`, e.g. `work/client`.
+    pub path: String,
+    pub color: Option<String>,
+    pub parent: Option<String>,
+}
+
+pub fn tag_detail(name: &str, defs: &[Tag]) -> TagDetail {
+    let Some(def) = definition_for(defs, name) else {
+        return TagDetail {
+            name: name.to_string(),
+            path: name.to_string(),
+            color: None,
+            parent: None,
+        };
+    };
+    let mut path = vec![name.to_string()];
+    let mut seen = HashSet::from([def.id.as_str()]);
+    let mut cursor = def.parent_id.as_deref();
+    while let Some(parent) = cursor
+        .and_then(|id| defs.iter().find(|def| def.id == id))
+        .filter(|parent| seen.insert(parent.id.as_str()))
+    {
+        path.push(parent.name.clone());
+        cursor = parent.parent_id.as_deref();
+    }
+    path.reverse();
+    TagDetail {
+        name: name.to_string(),
+        parent: (path.len() > 1).then(|| path[path.len() - 2].clone()),
+        path: path.join("/"),
+        color: def.color.clone(),
+    }
+}
+
+pub fn tag_details(names: &[String], defs: &[Tag]) -> Vec<TagDetail> {
+    names.iter().map(|name| tag_detail(name, defs)).collect()
+}
+
+/// One entry of `list_tags`; `tag` is the defined name, else the spelling on the most
+/// recently updated task. Defined tags nobody uses are listed with zero counts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TagStat {
+    pub tag: String,
+    pub count: usize,
+    pub open_count: usize,
+    pub last_used_at: Timestamp,
+    pub id: Option<String>,
+    pub color: Option<String>,
+    pub parent_id: Option<String>,
+}
+
+/// Every tag in use or defined, most used first and then by name.
+pub fn tag_stats(tasks: &[Task], defs: &[Tag]) -> Vec<TagStat> {
+    let mut stats: HashMap<String, TagStat> = HashMap::new();
+    for task in tasks {
+        let mut seen: Vec<String> = Vec::new();
+        for tag in &task.tags {
+            let Some(key) = tag_key(tag) else {
+                continue;
+            };
+            if seen.contains(&key) {
+                continue;
+            }
+            seen.push(key.clone());
+            let stat = stats.entry(key).or_insert_with(|| TagStat {
+                tag: tag.clone(),
+                count: 0,
+                open_count: 0,
+                last_used_at: task.updated_at,
+                id: None,
+                color: None,
+                parent_id: None,
+            });
+            stat.count += 1;
+            if !task.completed {
+                stat.open_count += 1;
+            }
+            if task.updated_at > stat.last_used_at {
+                stat.last_used_at = task.updated_at;
+                stat.tag = tag.clone();
+            }
+        }
+    }
+    for def in defs {
+        let Some(key) = tag_key(&def.name) else {
+            continue;
+        };
+        let stat = stats.entry(key).or_insert_with(|| TagStat {
+            tag: def.name.clone(),
+            count: 0,
+            open_count: 0,
+            last_used_at: def.updated_at,
+            id: None,
+            color: None,
+            parent_id: None,
+        });
+        stat.tag = def.name.clone();
+        stat.id = Some(def.id.clone());
+        stat.color = def.color.clone();
+        stat.parent_id = def.parent_id.clone();
+    }
+    let mut stats: Vec<TagStat> = stats.into_values().collect();
+    stats.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.tag.to_lowercase().cmp(&b.tag.to_lowercase()))
+    });
+    stats
+}
+
+/// Autocomplete for the tag input: tags starting with `prefix` (a leading `#` is ignored),
+/// recently used first, then by use count.
+pub fn suggest_tags(tasks: &[Task], defs: &[Tag], prefix: &str, limit: usize) -> Vec<TagStat> {
+    let prefix = prefix.trim();
+    let prefix = prefix.strip_prefix('#').unwrap_or(prefix).to_lowercase();
+    let mut matches: Vec<TagStat> = tag_stats(tasks, defs)
+        .into_iter()
+        .filter(|stat| stat.tag.to_lowercase().starts_with(&prefix))
+        .collect();
+    matches.sort_by(|a, b| {
+        b.last_used_at
+            .cmp(&a.last_used_at)
+            .then_with(|| b.count.cmp(&a.count))
+            .then_with(|| a.tag.cmp(&b.tag))
+    });
+    matches.truncate(limit.clamp(1, MAX_SUGGEST_LIMIT));
+    matches
+}
+
+/// Replaces every tag whose key is in `sources` with `target`, keeping the position of the
+/// first one and dropping the duplicates this creates. Returns whether the tags changed.
+pub fn retag(task: &mut Task, sources: &[String], target: &str) -> bool {
+    let target_key = tag_key(target);
+    let mut next: Vec<String> = Vec::with_capacity(task.tags.len());
+    let mut keys: Vec<Option<String>> = Vec::with_capacity(task.tags.len());
+    for tag in &task.tags {
+        let key = tag_key(tag);
+        let replaced = key.as_ref().is_some_and(|key| sources.contains(key));
+        let (tag, key) = if replaced {
+            (target.to_string(), target_key.clone())
+        } else {
+            (tag.clone(), key)
+        };
+        if key.is_some() && keys.contains(&key) {
+            continue;
+        }
+        next.push(tag);
+        keys.push(key);
+    }
+    if next == task.tags {
+        return false;
+    }
+    task.tags = next;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+
+    fn task(id: &str, tags: &[&str], completed: bool, updated_at: Timestamp) -> Task {
+        test_support::task(id)
+            .tags(tags)
+            .completed(completed)
+            .updated(updated_at)
+    }
+
+    #[test]
+    fn stats_count_tags_case_insensitively() {
+        let tasks = vec![
+            task("a", &["work", "Home"], false, 10),
+            task("b", &["Work", "work"], true, 20),
+            task("c", &["errands"], false, 30),
+        ];
+        let stats = tag_stats(&tasks, &[]);
+        let work = &stats[0];
+        assert_eq!(
+            (
+                work.tag.as_str(),
+                work.count,
+                work.open_count,
+                work.last_used_at
+            ),
+            ("Work", 2, 1, 20)
+        );
+        let names: Vec<&str> = stats.iter().map(|stat| stat.tag.as_str()).collect();
+        assert_eq!(names, ["Work", "errands", "Home"]);
+    }
+
+    #[test]
+    fn suggestions_match_the_prefix_and_prefer_recent_tags() {
+        let tasks = vec![
+            task("a", &["work", "weekly"], false, 10),
+            task("b", &["work"], false, 10),
+            task("c", &["web"], false, 30),
+            task("d", &["home"], false, 40),
+        ];
+        let names = |prefix: &str, limit: usize| -> Vec<String> {
+            suggest_tags(&tasks, &[], prefix, limit)
+                .into_iter()
+                .map(|stat| stat.tag)
+                .collect()
+        };
+        assert_eq!(names("#W", 10), ["web", "work", "weekly"]);
+        assert_eq!(names("", 2), ["home", "web"]);
+        assert_eq!(names("x", 10), Vec::<String>::new());
+    }
+
+    #[test]
+    fn retag_replaces_sources_in_place_without_duplicates() {
+        let sources = vec!["job".to_string(), "office".to_string()];
+        let mut t = task("a", &["home", "Job", "urgent", "office", "work"], false, 1);
+        assert!(retag(&mut t, &sources, "work"));
+        assert_eq!(t.tags, ["home", "work", "urgent"]);
+
+        let mut untouched = task("b", &["home"], false, 1);
+        assert!(!retag(&mut untouched, &sources, "work"));
+
+        // Renaming only the spelling is still a change.
+        let mut cased = task("c", &["Job"], false, 1);
+        assert!(retag(&mut cased, &["job".to_string()], "job"));
+        assert_eq!(cased.tags, ["job"]);
+    }
+
+    fn def(id: &str, name: &str, parent_id: Option<&str>) -> Tag {
+        Tag {
+            id: id.to_string(),
+            name: name.to_string(),
+            color: None,
+            parent_id: parent_id.map(str::to_string),
+            created_at: 1,
+            updated_at: 1,
+        }
+    }
+
+    #[test]
+    fn definitions_are_validated_against_the_others() {
+        let defs = vec![def("w", "work", None), def("c", "client", Some("w"))];
+        let mut tag = def(" h ", "#Home", None);
+        tag.color = Some("ABC".to_string());
+        normalize_tag_definition(&mut tag, &defs).unwrap();
+        assert_eq!((tag.id.as_str(), tag.name.as_str()), ("h", "home"));
+        assert_eq!(tag.color.as_deref(), Some("#aabbcc"));
+
+        let mut clash = def("x", "Work", None);
+        assert_eq!(
+            normalize_tag_definition(&mut clash, &defs),
+            Err("tag already exists: work".to_string())
+        );
+        let mut cycle = def("w", "work", Some("c"));
+        assert_eq!(
+            normalize_tag_definition(&mut cycle, &defs),
+            Err("tag cannot be nested under itself".to_string())
+        );
+        assert!(normalize_tag_definition(&mut def("y", "y", Some("nope")), &defs).is_err());
+        assert!(normalize_tag_definition(&mut def("y", " # ", None), &defs).is_err());
+    }
+
+    #[test]
+    fn details_resolve_colors_and_parent_paths() {
+        let mut work = def("w", "work", None);
+        work.color = Some("#336699".to_string());
+        let defs = vec![work, def("c", "client", Some("w"))];
+        let details = tag_details(&["Client".to_string(), "misc".to_string()], &defs);
+        assert_eq!(details[0].path, "work/Client");
+        assert_eq!(details[0].parent.as_deref(), Some("work"));
+        assert_eq!(details[1].path, "misc");
+        assert_eq!(tag_detail("work", &defs).color.as_deref(), Some("#336699"));
+
+        let stats = tag_stats(&[task("a", &["client"], false, 5)], &defs);
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].id.as_deref(), Some("c"));
+        assert_eq!((stats[1].tag.as_str(), stats[1].count), ("work", 0));
+    }
+
+    #[test]
+    fn retagging_renames_or_folds_definitions() {
+        let mut defs = vec![
+            def("j", "job", None),
+            def("w", "work", None),
+            def("c", "client", Some("j")),
+        ];
+        retag_definitions(&mut defs, &["job".to_string()], "work", 9);
+        let ids: Vec<&str> = defs.iter().map(|def| def.id.as_str()).collect();
+        assert_eq!(ids, ["w", "c"]);
+        assert_eq!(defs[1].parent_id.as_deref(), Some("w"));
+
+        retag_definitions(&mut defs, &["client".to_string()], "customer", 9);
+        assert_eq!(defs[1].name, "customer");
+        assert_eq!(defs[1].updated_at, 9);
+
+        let mut removed = defs.clone();
+        remove_definition(&mut removed, "w").unwrap();
+        assert_eq!(removed[0].parent_id, None);
+    }
+}
@@ -0,0 +1,291 @@
+//! Completed tasks leave data.json once they have been done for `archive_after_days`: the
+//! nightly maintenance moves them into `archive/tasks-<year>.json` (the local year they were
+//! completed in), so the live file only holds what is still in use. Archived tasks can be
+//! listed, searched and brought back one at a time. History views (statistics, burndown,
+//! focus time, year review) read the archive files too, through [`tasks_with_archive`].
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+
+use chrono::{Datelike, Local, TimeZone};
+
+use crate::models::{Task, Timestamp};
+use crate::search::{search_tasks, SearchHit};
+use crate::state::AppState;
+use crate::storage::{Storage, StorageError};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Tasks completed (and last changed) before the returned time are due for the archive;
+/// `None` when archiving is off (0 days).
+pub fn archive_cutoff(after_days: u32, now: Timestamp) -> Option<Timestamp> {
+    if after_days == 0 {
+        return None;
+    }
+    Some(now.saturating_sub(i64::from(after_days) * SECONDS_PER_DAY))
+}
+
+/// A task brought back counts as changed, so it stays in the list for another full period.
+fn archivable(task: &Task, cutoff: Timestamp) -> bool {
+    task.completed && task.completed_at.is_some_and(|at| at < cutoff) && task.updated_at < cutoff
+}
+
+/// Local year of `ts`, the key of the archive files.
+pub fn local_year(ts: Timestamp) -> i32 {
+    Local
+        .timestamp_opt(ts, 0)
+        .single()
+        .map_or(1970, |at| at.year())
+}
+
+fn archive_year(task: &Task) -> i32 {
+    local_year(task.completed_at.unwrap_or(task.updated_at))
+}
+
+/// Moves tasks due for the archive into their year's file; returns how many left the task
+/// list. The files are written before the tasks are dropped, and a task already in its file
+/// (a run that stopped before data.json was saved) is replaced rather than added twice.
+///
+/// Only in-memory state is changed here; callers persist when something was archived.
+pub fn archive_completed(
+    root: &Path,
+    state: &AppState,
+    now: Timestamp,
+) -> Result<usize, StorageError> {
+    let Some(cutoff) = archive_cutoff(state.settings().archive_after_days, now) else {
+        return Ok(0);
+    };
+    let mut by_year: BTreeMap<i32, Vec<Task>> = BTreeMap::new();
+    for task in state.shared_tasks().iter() {
+        if archivable(task, cutoff) {
+            by_year
+                .entry(archive_year(task))
+                .or_default()
+                .push(task.clone());
+        }
+    }
+    if by_year.is_empty() {
+        return Ok(0);
+    }
+
+    let storage = Storage::new(root.to_path_buf());
+    let mut archived = Vec::new();
+    for (year, tasks) in by_year {
+        let ids: HashSet<&str> = tasks.iter().map(|task| task.id.as_str()).collect();
+        let mut file = storage.load_task_archive(year)?;
+        file.retain(|existing| !ids.contains(existing.id.as_str()));
+        file.extend(tasks.iter().cloned());
+        storage.save_task_archive(year, &file)?;
+        archived.extend(tasks);
+    }
+    let removed = state.remove_archived(&archived);
+    log::info!("task_archive: archived count={removed} cutoff={cutoff}");
+    Ok(removed)
+}
+
+/// Archived tasks of `year`, or of every year, most recently completed first.
+pub fn list_archived(root: &Path, year: Option<i32>) -> Result<Vec<Task>, StorageError> {
+    let storage = Storage::new(root.to_path_buf());
+    let years = match year {
+        Some(year) => vec![year],
+        None => storage.task_archive_years()?,
+    };
+    let mut tasks = Vec::new();
+    for year in years {
+        tasks.extend(storage.load_task_archive(year)?);
+    }
+    tasks.sort_by_key(|task| std::cmp::Reverse(task.completed_at));
+    Ok(tasks)
+}
+
+/// The task list plus every task archived for `from_year` or later. Archived tasks were
+/// completed in their file's year, so this covers everything completed since `from_year`
+/// began; aggregates over a range starting then pass its year. Live copies win over archive
+/// copies of the same task, and without matching archive files the live list comes back as is.
+pub fn tasks_with_archive(
+    root: &Path,
+    state: &AppState,
+    from_year: i32,
+) -> Result<Arc<Vec<Task>>, StorageError> {
+    let live = state.shared_tasks();
+    let storage = Storage::new(root.to_path_buf());
+    let mut archived = Vec::new();
+    for year in storage.task_archive_years()? {
+        if year >= from_year {
+            archived.extend(storage.load_task_archive(year)?);
+        }
+    }
+    let ids: HashSet<&str> = live.iter().map(|task| task.id.as_str()).collect();
+    archived.retain(|task| !ids.contains(task.id.as_str()));
+    if archived.is_empty() {
+        return Ok(live);
+    }
+    let mut tasks = live.to_vec();
+    tasks.extend(archived);
+    Ok(Arc::new(tasks))
+}
+
+/// The same ranked search as `search_tasks`, over every archive file.
+pub fn search_archive(
+    root: &Path,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<SearchHit>, StorageError> {
+    let tasks = list_archived(root, None)?;
+    Ok(search_tasks(&tasks, query, true, limit))
+}
+
+/// Where an archived task lives: its year and the task itself.
+pub fn find_archived(root: &Path, task_id: &str) -> Result<Option<(i32, Task)>, StorageError> {
+    let storage = Storage::new(root.to_path_buf());
+    for year in storage.task_archive_years()?.into_iter().rev() {
+        if let Some(task) = storage
+            .load_task_archive(year)?
+            .into_iter()
+            .find(|task| task.id == task_id)
+        {
+            return Ok(Some((year, task)));
+        }
+    }
+    Ok(None)
+}
+
+/// Removes a task from its year's file, once it is safely back in the task list.
+pub fn drop_archived(root: &Path, year: i32, task_id: &str) -> Result<(), StorageError> {
+    let storage = Storage::new(root.to_path_buf());
+    let mut file = storage.load_task_archive(year)?;
+    file.retain(|task| task.id != task_id);
+    storage.save_task_archive(year, &file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_support, Settings};
+
+    const DAY: i64 = SECONDS_PER_DAY;
+
+    fn task(id: &str, title: &str, completed_at: Option<Timestamp>) -> Task {
+        test_support::task(id)
+            .title(title)
+            .done_at(completed_at)
+            .updated(completed_at.unwrap_or(1))
+    }
+
+    /// Archiving is opt-in; the tests use a 90-day period.
+    fn archiving_settings() -> Settings {
+        Settings {
+            archive_after_days: 90,
+            ..Settings::default()
+        }
+    }
+
+    fn local(year: i32, month: u32, day: u32) -> Timestamp {
+        Local
+            .with_ymd_and_hms(year, month, day, 12, 0, 0)
+            .unwrap()
+            .timestamp()
+    }
+
+    #[test]
+    fn old_completed_tasks_move_into_yearly_files_once() {
+        let root = tempfile::tempdir().unwrap();
+        let now = local(2026, 6, 1);
+        let state = AppState::new(
+            vec![
+                task("old-2025", "Tax return", Some(local(2025, 3, 1))),
+                task("old-2026", "Renew passport", Some(local(2026, 1, 10))),
+                task("recent", "Water plants", Some(now - 10 * DAY)),
+                task("open", "Write report", None),
+            ],
+            Vec::new(),
+            archiving_settings(),
+        );
+
+        assert_eq!(archive_completed(root.path(), &state, now).unwrap(), 2);
+        let left: Vec<String> = state.tasks().into_iter().map(|task| task.id).collect();
+        assert_eq!(left, vec!["recent", "open"]);
+        let storage = Storage::new(root.path().to_path_buf());
+        assert_eq!(storage.task_archive_years().unwrap(), vec![2025, 2026]);
+        assert_eq!(storage.load_task_archive(2025).unwrap()[0].id, "old-2025");
+
+        // A run that wrote the files but never saved data.json leaves no duplicates.
+        state.add_task(task("old-2025", "Tax return", Some(local(2025, 3, 1))));
+        assert_eq!(archive_completed(root.path(), &state, now).unwrap(), 1);
+        assert_eq!(storage.load_task_archive(2025).unwrap().len(), 1);
+
+        let ids: Vec<String> = list_archived(root.path(), None)
+            .unwrap()
+            .into_iter()
+            .map(|task| task.id)
+            .collect();
+        assert_eq!(ids, vec!["old-2026", "old-2025"]);
+        assert_eq!(list_archived(root.path(), Some(2024)).unwrap().len(), 0);
+        let hits = search_archive(root.path(), "passport", 10).unwrap();
+        assert_eq!(hits[0].task.id, "old-2026");
+
+        let mut settings = state.settings();
+        settings.archive_after_days = 0;
+        state.update_settings(settings);
+        assert_eq!(
+            archive_completed(root.path(), &state, now + 365 * DAY).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn history_reads_archived_tasks_from_the_requested_year_on() {
+        let root = tempfile::tempdir().unwrap();
+        let state = AppState::new(
+            vec![
+                task("2024", "A", Some(local(2024, 5, 5))),
+                task("2025", "B", Some(local(2025, 5, 5))),
+                task("open", "C", None),
+            ],
+            Vec::new(),
+            archiving_settings(),
+        );
+        assert!(Arc::ptr_eq(
+            &tasks_with_archive(root.path(), &state, 2024).unwrap(),
+            &state.shared_tasks()
+        ));
+        archive_completed(root.path(), &state, local(2026, 1, 1)).unwrap();
+        // Left over from a run that archived but never saved data.json.
+        state.add_task(task("2025", "B", Some(local(2025, 5, 5))));
+
+        let ids = |from_year| -> Vec<String> {
+            tasks_with_archive(root.path(), &state, from_year)
+                .unwrap()
+                .iter()
+                .map(|task| task.id.clone())
+                .collect()
+        };
+        assert_eq!(ids(2024), vec!["open", "2025", "2024"]);
+        assert_eq!(ids(2025), vec!["open", "2025"]);
+        assert_eq!(local_year(local(2025, 12, 31)), 2025);
+    }
+
+    #[test]
+    fn archived_tasks_can_be_found_and_dropped_from_their_file() {
+        let root = tempfile::tempdir().unwrap();
+        let state = AppState::new(
+            vec![task("a", "A", Some(local(2024, 5, 5)))],
+            Vec::new(),
+            archiving_settings(),
+        );
+        archive_completed(root.path(), &state, local(2026, 1, 1)).unwrap();
+
+        let (year, found) = find_archived(root.path(), "a").unwrap().unwrap();
+        assert_eq!((year, found.title.as_str()), (2024, "A"));
+        assert!(find_archived(root.path(), "missing").unwrap().is_none());
+
+        drop_archived(root.path(), year, "a").unwrap();
+        assert!(find_archived(root.path(), "a").unwrap().is_none());
+        // The last task of a year takes its file with it.
+        assert!(Storage::new(root.path().to_path_buf())
+            .task_archive_years()
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,234 @@
+//! The task list `AppState` keeps in memory, indexed by id and by project. Tasks stay in
+//! their stored order (data.json, undo and the UI rely on it), and the list sits behind an
+//! `Arc` so snapshots share it instead of copying every task; a write after a snapshot copies
+//! the list once (copy-on-write). It also notes which ids changed, so a `state_delta` only
+//! looks at those.
+
+use std::collections::{HashMap, HashSet};
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::models::Task;
+
+#[derive(Debug, Default)]
+pub struct TaskIndex {
+    tasks: Arc<Vec<Task>>,
+    // Position of each id; with duplicate ids (damaged data) the first one wins, like a scan.
+    by_id: HashMap<String, usize>,
+    // Positions of each project's tasks, ascending.
+    by_project: HashMap<String, Vec<usize>>,
+    // Ids touched since `take_changed`, for `state_delta`; `None` after a bulk edit.
+    changed: Option<HashSet<String>>,
+}
+
+impl TaskIndex {
+    pub fn new(tasks: Vec<Task>) -> Self {
+        let mut index = Self {
+            tasks: Arc::new(tasks),
+            changed: Some(HashSet::new()),
+            ..Self::default()
+        };
+        index.reindex();
+        index
+    }
+
+    fn reindex(&mut self) {
+        self.by_id.clear();
+        self.by_project.clear();
+        for (position, task) in self.tasks.iter().enumerate() {
+            self.by_id.entry(task.id.clone()).or_insert(position);
+            self.by_project
+                .entry(task.project_id.clone())
+                .or_default()
+                .push(position);
+        }
+    }
+
+    fn mark_changed(&mut self, id: &str) {
+        if let Some(changed) = &mut self.changed {
+            changed.insert(id.to_string());
+        }
+    }
+
+    /// Ids added, changed or removed since the last call; `None` when a bulk edit may have
+    /// touched any task.
+    pub fn take_changed(&mut self) -> Option<HashSet<String>> {
+        self.changed.replace(HashSet::new())
+    }
+
+    /// The list itself, shared rather than copied.
+    pub fn shared(&self) -> Arc<Vec<Task>> {
+        Arc::clone(&self.tasks)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Task> {
+        self.by_id.get(id).map(|&position| &self.tasks[position])
+    }
+
+    pub fn position(&self, id: &str) -> Option<usize> {
+        self.by_id.get(id).copied()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.by_id.contains_key(id)
+    }
+
+    /// The project's tasks in list order.
+    pub fn in_project<'a>(&'a self, project_id: &str) -> impl Iterator<Item = &'a Task> + 'a {
+        self.by_project
+            .get(project_id)
+            .into_iter()
+            .flatten()
+            .map(|&position| &self.tasks[position])
+    }
+
+    /// Changes one task in place; `None` when the id is unknown. Moving it to another project
+    /// (or renaming it) keeps the indices right.
+    pub fn update<R>(&mut self, id: &str, change: impl FnOnce(&mut Task) -> R) -> Option<R> {
+        let position = self.position(id)?;
+        let task = &mut Arc::make_mut(&mut self.tasks)[position];
+        let project_id = task.project_id.clone();
+        let result = change(task);
+        let renamed_to = (task.id != id).then(|| task.id.clone());
+        let moved_to = (task.project_id != project_id).then(|| task.project_id.clone());
+        self.mark_changed(id);
+        if let Some(new_id) = renamed_to {
+            self.mark_changed(&new_id);
+            self.reindex();
+        } else if let Some(moved_to) = moved_to {
+            if let Some(positions) = self.by_project.get_mut(&project_id) {
+                positions.retain(|&other| other != position);
+                if positions.is_empty() {
+                    self.by_project.remove(&project_id);
+                }
+            }
+            let positions = self.by_project.entry(moved_to).or_default();
+            let at = positions.partition_point(|&other| other < position);
+            positions.insert(at, position);
+        }
+        Some(result)
+    }
+
+    pub fn push(&mut self, task: Task) {
+        let position = self.tasks.len();
+        self.mark_changed(&task.id);
+        self.by_id.entry(task.id.clone()).or_insert(position);
+        self.by_project
+            .entry(task.project_id.clone())
+            .or_default()
+            .push(position);
+        Arc::make_mut(&mut self.tasks).push(task);
+    }
+
+    pub fn extend(&mut self, tasks: impl IntoIterator<Item = Task>) {
+        for task in tasks {
+            self.push(task);
+        }
+    }
+
+    /// Edits the whole list (removals, reorders, bulk changes), then rebuilds the indices.
+    pub fn edit<R>(&mut self, change: impl FnOnce(&mut Vec<Task>) -> R) -> R {
+        let result = change(Arc::make_mut(&mut self.tasks));
+        self.changed = None;
+        self.reindex();
+        result
+    }
+
+    pub fn retain(&mut self, mut keep: impl FnMut(&Task) -> bool) {
+        let mut removed = Vec::new();
+        Arc::make_mut(&mut self.tasks).retain(|task| {
+            let kept = keep(task);
+            if !kept {
+                removed.push(task.id.clone());
+            }
+            kept
+        });
+        if removed.is_empty() {
+            return;
+        }
+        for id in &removed {
+            self.mark_changed(id);
+        }
+        self.reindex();
+    }
+
+    pub fn replace(&mut self, tasks: Vec<Task>) {
+        self.tasks = Arc::new(tasks);
+        self.changed = None;
+        self.reindex();
+    }
+}
+
+impl Deref for TaskIndex {
+    type Target = [Task];
+
+    fn deref(&self) -> &[Task] {
+        &self.tasks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+
+    fn task(id: &str, project_id: &str) -> Task {
+        test_support::task(id).project(project_id)
+    }
+
+    fn ids<'a>(tasks: impl Iterator<Item = &'a Task>) -> Vec<&'a str> {
+        tasks.map(|task| task.id.as_str()).collect()
+    }
+
+    #[test]
+    fn lookups_follow_pushes_moves_and_removals() {
+        let mut index = TaskIndex::new(vec![task("a", "inbox"), task("b", "work")]);
+        index.push(task("c", "inbox"));
+        assert_eq!(
+            index.get("c").map(|task| task.project_id.as_str()),
+            Some("inbox")
+        );
+        assert_eq!(ids(index.in_project("inbox")), vec!["a", "c"]);
+
+        assert_eq!(
+            index.update("c", |task| task.project_id = "work".to_string()),
+            Some(())
+        );
+        assert_eq!(ids(index.in_project("work")), vec!["b", "c"]);
+        assert_eq!(ids(index.in_project("inbox")), vec!["a"]);
+        assert!(index.update("missing", |_| ()).is_none());
+
+        index.retain(|task| task.id != "a");
+        assert_eq!(index.position("c"), Some(1));
+        assert!(!index.contains("a"));
+        assert_eq!(ids(index.in_project("inbox")), Vec::<&str>::new());
+
+        index.update("b", |task| task.id = "b2".to_string());
+        assert!(index.get("b").is_none());
+        assert_eq!(index.position("b2"), Some(0));
+
+        let mut changed: Vec<String> = index.take_changed().unwrap().into_iter().collect();
+        changed.sort();
+        assert_eq!(changed, vec!["a", "b", "b2", "c"]);
+        assert_eq!(index.take_changed(), Some(HashSet::new()));
+        index.edit(|tasks| tasks.reverse());
+        assert_eq!(index.take_changed(), None);
+    }
+
+    #[test]
+    fn snapshots_share_the_list_until_the_next_write() {
+        let mut index = TaskIndex::new(vec![task("a", "inbox")]);
+        let snapshot = index.shared();
+        assert!(Arc::ptr_eq(&snapshot, &index.shared()));
+
+        index.update("a", |task| task.title = "changed".to_string());
+        assert_eq!(snapshot[0].title, "a");
+        assert_eq!(index.get("a").unwrap().title, "changed");
+        assert!(!Arc::ptr_eq(&snapshot, &index.shared()));
+
+        // Duplicate ids (damaged data) resolve to the first, as a linear scan would.
+        index.replace(vec![task("d", "inbox"), task("d", "work")]);
+        assert_eq!(index.get("d").unwrap().project_id, "inbox");
+        assert_eq!(index.len(), 2);
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::{Duration, Local, TimeZone};
+
+use crate::models::{Task, TimeEntry, Timestamp};
+
+pub fn is_running(task: &Task) -> bool {
+    task.time_entries
+        .last()
+        .is_some_and(|entry| entry.ended_at.is_none())
+}
+
+/// Starts a new entry; false when the task's timer is already running.
+pub fn start_entry(task: &mut Task, now: Timestamp) -> bool {
+    if is_running(task) {
+        return false;
+    }
+    task.time_entries.push(TimeEntry {
+        started_at: now,
+        ended_at: None,
+    });
+    true
+}
+
+/// Closes the running entry; false when there is none.
+pub fn stop_entry(task: &mut Task, now: Timestamp) -> bool {
+    match task.time_entries.last_mut() {
+        Some(entry) if entry.ended_at.is_none() => {
+            entry.ended_at = Some(now.max(entry.started_at));
+            true
+        }
+        _ => false,
+    }
+}
+
+/// `[start, end)` of `entry`, a running entry ending at `now`.
+fn span(entry: &TimeEntry, now: Timestamp) -> (Timestamp, Timestamp) {
+    let end = entry.ended_at.unwrap_or(now).max(entry.started_at);
+    (entry.started_at, end)
+}
+
+/// Total tracked seconds, counting a running entry up to `now`.
+pub fn tracked_seconds(task: &Task, now: Timestamp) -> i64 {
+    task.time_entries
+        .iter()
+        .map(|entry| {
+            let (start, end) = span(entry, now);
+            end - start
+        })
+        .sum()
+}
+
+/// Tracked seconds inside `[from, to)`, counting a running entry up to `now`.
+pub fn tracked_seconds_between(task: &Task, from: Timestamp, to: Timestamp, now: Timestamp) -> i64 {
+    task.time_entries
+        .iter()
+        .map(|entry| {
+            let (start, end) = span(entry, now);
+            (end.min(to) - start.max(from)).max(0)
+        })
+        .sum()
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeReportGroup {
+    #[default]
+    Day,
+    Project,
+    Tag,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct TimeReportRow {
+    /// Local `YYYY-MM-DD`, a project id, or a tag (`""` for untagged tasks).
+    pub key: String,
+    pub seconds: i64,
+    pub task_count: usize,
+}
+
+/// Splits `[start, end)` at local midnights.
+fn split_by_day(start: Timestamp, end: Timestamp) -> Vec<(String, i64)> {
+    let mut parts = Vec::new();
+    let mut cursor = start;
+    while cursor < end {
+        let Some(local) = Local.timestamp_opt(cursor, 0).single() else {
+            break;
+        };
+        let next_midnight = (local.date_naive() + Duration::days(1))
+            .and_hms_opt(0, 0, 0)
+            .and_then(|midnight| Local.from_local_datetime(&midnight).earliest())
+            .map(|midnight| midnight.timestamp())
+            .unwrap_or(end);
+        let part_end = next_midnight.min(end).max(cursor + 1);
+        parts.push((
+            local.date_naive().format("%Y-%m-%d").to_string(),
+            part_end - cursor,
+        ));
+        cursor = part_end;
+    }
+    parts
+}
+
+/// Tracked time inside `[from, to)` (open-ended when `None`), grouped by local day (oldest
+/// first), project or tag (most time first). A task with several tags counts for each of them.
+pub fn report_time(
+    tasks: &[Task],
+    group: TimeReportGroup,
+    from: Option<Timestamp>,
+    to: Option<Timestamp>,
+    now: Timestamp,
+) -> Vec<TimeReportRow> {
+    let mut totals: BTreeMap<String, (i64, BTreeSet<&str>)> = BTreeMap::new();
+    for task in tasks {
+        for entry in &task.time_entries {
+            let (start, end) = span(entry, now);
+            let start = from.map_or(start, |from| start.max(from));
+            let end = to.map_or(end, |to| end.min(to));
+            if start >= end {
+                continue;
+            }
+            let parts = match group {
+                TimeReportGroup::Day => split_by_day(start, end),
+                TimeReportGroup::Project => vec![(task.project_id.clone(), end - start)],
+                TimeReportGroup::Tag if task.tags.is_empty() => vec![(String::new(), end - start)],
+                TimeReportGroup::Tag => task
+                    .tags
+                    .iter()
+                    .map(|tag| (tag.clone(), end - start))
+                    .collect(),
+            };
+            for (key, seconds) in parts {
+                let total = totals.entry(key).or_default();
+                total.0 += seconds;
+                total.1.insert(task.id.as_str());
+            }
+        }
+    }
+    let mut rows: Vec<TimeReportRow> = totals
+        .into_iter()
+        .map(|(key, (seconds, tasks))| TimeReportRow {
+            key,
+            seconds,
+            task_count: tasks.len(),
+        })
+        .collect();
+    if group != TimeReportGroup::Day {
+        rows.sort_by_key(|row| std::cmp::Reverse(row.seconds));
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+
+    fn task(id: &str, project: &str, tags: &[&str], entries: &[(i64, Option<i64>)]) -> Task {
+        let mut task = test_support::task(id).project(project).tags(tags);
+        task.time_entries = entries
+            .iter()
+            .map(|&(started_at, ended_at)| TimeEntry {
+                started_at,
+                ended_at,
+            })
+            .collect();
+        task
+    }
+
+    #[test]
+    fn start_and_stop_keep_at_most_one_running_entry() {
+        let mut t = task("a", "inbox", &[], &[]);
+        assert!(!stop_entry(&mut t, 5));
+        assert!(start_entry(&mut t, 10));
+        assert!(!start_entry(&mut t, 20));
+        assert!(is_running(&t));
+        assert_eq!(tracked_seconds(&t, 70), 60);
+        assert!(stop_entry(&mut t, 100));
+        assert!(!is_running(&t));
+        assert!(start_entry(&mut t, 200));
+        assert_eq!(tracked_seconds(&t, 230), 120);
+        assert_eq!(tracked_seconds_between(&t, 50, 210, 230), 60);
+        assert_eq!(t.time_entries.len(), 2);
+    }
+
+    #[test]
+    fn report_groups_by_project_and_tag_within_the_range() {
+        let tasks = vec![
+            task(
+                "a",
+                "work",
+                &["client", "deep"],
+                &[(0, Some(100)), (200, None)],
+            ),
+            task("b", "work", &[], &[(50, Some(80))]),
+            task("c", "home", &["client"], &[(1000, Some(2000))]),
+        ];
+        let by_project = report_time(&tasks, TimeReportGroup::Project, None, None, 300);
+        let rows: Vec<_> = by_project
+            .iter()
+            .map(|r| (r.key.as_str(), r.seconds, r.task_count))
+            .collect();
+        assert_eq!(rows, vec![("home", 1000, 1), ("work", 230, 2)]);
+
+        // Clipped to [60, 250): a contributes 40 + 50, b 20, c nothing.
+        let by_tag = report_time(&tasks, TimeReportGroup::Tag, Some(60), Some(250), 300);
+        let rows: Vec<_> = by_tag.iter().map(|r| (r.key.as_str(), r.seconds)).collect();
+        assert_eq!(rows, vec![("client", 90), ("deep", 90), ("", 20)]);
+    }
+
+    #[test]
+    fn report_by_day_splits_entries_at_local_midnight() {
+        let evening = Local
+            .with_ymd_and_hms(2026, 3, 10, 23, 0, 0)
+            .unwrap()
+            .timestamp();
+        let tasks = vec![task(
+            "a",
+            "inbox",
+            &[],
+            &[(evening, Some(evening + 2 * 3600))],
+        )];
+        let rows = report_time(&tasks, TimeReportGroup::Day, None, None, evening);
+        let rows: Vec<_> = rows.iter().map(|r| (r.key.as_str(), r.seconds)).collect();
+        assert_eq!(rows, vec![("2026-03-10", 3600), ("2026-03-11", 3600)]);
+    }
+}
@@ -0,0 +1,58 @@
+use crate::models::Timestamp;
+use crate::state::AppState;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Entries deleted before the returned time are past retention; `None` when retention is
+/// off (0 days).
+pub fn purge_cutoff(retention_days: u32, now: Timestamp) -> Option<Timestamp> {
+    if retention_days == 0 {
+        return None;
+    }
+    Some(now.saturating_sub(i64::from(retention_days) * SECONDS_PER_DAY))
+}
+
+/// Drops trash entries older than `trash_retention_days`; returns how many. Only in-memory
+/// state is changed here; callers persist when something was purged.
+pub fn purge_expired(state: &AppState, now: Timestamp) -> usize {
+    let Some(cutoff) = purge_cutoff(state.settings().trash_retention_days, now) else {
+        return 0;
+    };
+    let purged = state.purge_trash(cutoff);
+    if purged > 0 {
+        log::info!("trash: purged expired entries count={purged} cutoff={cutoff}");
+    }
+    purged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{test_support, Settings};
+
+    fn task(id: &str) -> crate::models::Task {
+        test_support::task(id).title("t")
+    }
+
+    #[test]
+    fn purge_expired_respects_retention_and_zero_keeps_everything() {
+        let day = SECONDS_PER_DAY;
+        let settings = Settings {
+            trash_retention_days: 0,
+            ..Settings::default()
+        };
+        let state = AppState::new(vec![task("old"), task("new")], Vec::new(), settings);
+        state.move_to_trash(&["old".to_string()], 0);
+        state.move_to_trash(&["new".to_string()], 20 * day);
+        assert_eq!(purge_expired(&state, 100 * day), 0);
+
+        let mut settings = state.settings();
+        settings.trash_retention_days = 30;
+        state.update_settings(settings);
+        assert_eq!(purge_cutoff(30, 30 * day), Some(0));
+        assert_eq!(purge_expired(&state, 30 * day), 0);
+        assert_eq!(purge_expired(&state, 30 * day + 1), 1);
+        let left: Vec<_> = state.trash().into_iter().map(|e| e.task.id).collect();
+        assert_eq!(left, vec!["new"]);
+    }
+}
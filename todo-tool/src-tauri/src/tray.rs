@@ -1,10 +1,13 @@
 use chrono::{Local, TimeZone};
-#[cfg(all(feature = "app", not(test)))]
-use sys_locale::get_locale;
 
+use crate::calendar::DateLocale;
+use crate::flair::project_label;
+#[cfg(all(feature = "app", not(test)))]
+use crate::i18n::locale_for;
+use crate::i18n::{localized_projects, tr, Text};
 #[cfg(all(feature = "app", not(test)))]
 use crate::models::Settings;
-use crate::models::Task;
+use crate::models::{Project, Task};
 
 #[cfg(all(feature = "app", not(test)))]
 use crate::events::{NavigatePayload, EVENT_NAVIGATE};
@@ -12,92 +15,102 @@ use crate::events::{NavigatePayload, EVENT_NAVIGATE};
 use crate::windows::show_settings_window;
 #[cfg(all(feature = "app", not(test)))]
 use tauri::{
-    menu::{Menu, MenuItem},
+    image::Image,
+    menu::{CheckMenuItem, Menu, MenuItem, Submenu},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     App, AppHandle, Emitter, Manager, Runtime,
 };
 
 #[cfg(all(feature = "app", not(test)))]
 const TRAY_ID: &str = "main";
+/// Menu item ids of the projects submenu are this prefix plus the project id.
+const PROJECT_ITEM_PREFIX: &str = "project:";
+/// The projects submenu stays short; the sidebar has the full list.
+const TRAY_PROJECTS_MAX: usize = 10;
 
-#[derive(Clone, Copy)]
-enum TrayLanguage {
-    Zh,
-    En,
-}
-
-#[allow(dead_code)]
-struct TrayLabels {
-    show_quick: &'static str,
-    show_main: &'static str,
-    show_settings: &'static str,
-    quit: &'static str,
-    tooltip_prefix: &'static str,
-}
-
-#[cfg(all(feature = "app", not(test)))]
-fn resolve_tray_language(language: &str) -> TrayLanguage {
-    let normalized = language.trim().to_lowercase();
-    match normalized.as_str() {
-        "zh" => TrayLanguage::Zh,
-        "en" => TrayLanguage::En,
-        _ => detect_system_language(),
-    }
-}
-
-#[cfg(all(feature = "app", not(test)))]
-fn detect_system_language() -> TrayLanguage {
-    let locale = get_locale().unwrap_or_default().to_lowercase();
-    if locale.starts_with("zh") {
-        TrayLanguage::Zh
-    } else {
-        TrayLanguage::En
-    }
-}
-
-fn tray_labels(lang: TrayLanguage) -> TrayLabels {
-    match lang {
-        TrayLanguage::Zh => TrayLabels {
-            show_quick: "打开快捷窗口",
-            show_main: "打开主界面",
-            show_settings: "设置",
-            quit: "退出",
-            tooltip_prefix: "待办",
-        },
-        TrayLanguage::En => TrayLabels {
-            show_quick: "Open quick window",
-            show_main: "Open main window",
-            show_settings: "Settings",
-            quit: "Quit",
-            tooltip_prefix: "Pending",
-        },
-    }
-}
+const BADGE_RGBA: [u8; 4] = [220, 38, 38, 255];
+const BADGE_TEXT_RGBA: [u8; 4] = [255, 255, 255, 255];
+/// 3x5 bitmaps of 0-9 and '+', one row per byte (low three bits, leftmost pixel first).
+const GLYPHS: [[u8; 5]; 11] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+    [0b000, 0b010, 0b111, 0b010, 0b000],
+];
 
 #[cfg(all(feature = "app", not(test)))]
 fn build_tray_menu<R: Runtime, M: Manager<R>>(
     app: &M,
-    lang: TrayLanguage,
+    locale: DateLocale,
+    dnd_enabled: bool,
+    projects: &[Project],
 ) -> Result<Menu<R>, Box<dyn std::error::Error>> {
-    let labels = tray_labels(lang);
-    let show_quick = MenuItem::with_id(app, "show_quick", labels.show_quick, true, None::<&str>)?;
-    let show_main = MenuItem::with_id(app, "show_main", labels.show_main, true, None::<&str>)?;
+    let label = |text| tr(locale, text);
+    let show_quick = MenuItem::with_id(
+        app,
+        "show_quick",
+        label(Text::TrayShowQuick),
+        true,
+        None::<&str>,
+    )?;
+    let show_main = MenuItem::with_id(
+        app,
+        "show_main",
+        label(Text::TrayShowMain),
+        true,
+        None::<&str>,
+    )?;
     let show_settings = MenuItem::with_id(
         app,
         "show_settings",
-        labels.show_settings,
+        label(Text::TraySettings),
         true,
         None::<&str>,
     )?;
-    let quit = MenuItem::with_id(app, "quit", labels.quit, true, None::<&str>)?;
+    let dnd = CheckMenuItem::with_id(
+        app,
+        "toggle_dnd",
+        label(Text::TrayDoNotDisturb),
+        true,
+        dnd_enabled,
+        None::<&str>,
+    )?;
+    let project_items = tray_projects(projects, locale)
+        .into_iter()
+        .map(|(id, text)| MenuItem::with_id(app, id, text, true, None::<&str>))
+        .collect::<Result<Vec<_>, _>>()?;
+    let project_refs = project_items
+        .iter()
+        .map(|item| item as &dyn tauri::menu::IsMenuItem<R>)
+        .collect::<Vec<_>>();
+    let projects_menu = Submenu::with_items(app, label(Text::TrayProjects), true, &project_refs)?;
+    let quit = MenuItem::with_id(app, "quit", label(Text::TrayQuit), true, None::<&str>)?;
     Ok(Menu::with_items(
         app,
-        &[&show_quick, &show_main, &show_settings, &quit],
+        &[
+            &show_quick,
+            &show_main,
+            &projects_menu,
+            &show_settings,
+            &dnd,
+            &quit,
+        ],
     )?)
 }
 
 #[cfg(all(feature = "app", not(test)))]
-pub fn init_tray(app: &mut App, settings: &Settings) -> Result<(), Box<dyn std::error::Error>> {
+pub fn init_tray(
+    app: &mut App,
+    settings: &Settings,
+    projects: &[Project],
+) -> Result<(), Box<dyn std::error::Error>> {
     let icon = app.default_window_icon().cloned().ok_or_else(|| {
         std::io::Error::new(
             std::io::ErrorKind::NotFound,
@@ -105,17 +118,14 @@ pub fn init_tray(app: &mut App, settings: &Settings) -> Result<(), Box<dyn std::
         )
     })?;
 
-    let lang = resolve_tray_language(&settings.language);
+    let locale = locale_for(settings);
     log::info!(
-        "tray: init start id={} language_setting={} resolved_lang={}",
+        "tray: init start id={} language_setting={} resolved_lang={:?}",
         TRAY_ID,
         settings.language,
-        match lang {
-            TrayLanguage::Zh => "zh",
-            TrayLanguage::En => "en",
-        }
+        locale
     );
-    let menu = build_tray_menu(app, lang)?;
+    let menu = build_tray_menu(app, locale, settings.dnd_enabled, projects)?;
     log::info!("tray: menu built");
 
     let _tray = TrayIconBuilder::with_id(TRAY_ID)
@@ -128,6 +138,7 @@ pub fn init_tray(app: &mut App, settings: &Settings) -> Result<(), Box<dyn std::
 
             match id {
                 "quit" => app.exit(0),
+                "toggle_dnd" => crate::commands::toggle_do_not_disturb(app),
                 "show_quick" => {
                     if let Some(window) = app.get_webview_window("quick") {
                         if let Err(err) = window.unminimize() {
@@ -178,7 +189,11 @@ pub fn init_tray(app: &mut App, settings: &Settings) -> Result<(), Box<dyn std::
                         }
                     });
                 }
-                _ => {}
+                _ => {
+                    if let Some(project_id) = id.strip_prefix(PROJECT_ITEM_PREFIX) {
+                        show_project(app, project_id);
+                    }
+                }
             }
         })
         .on_tray_icon_event(|tray, event| {
@@ -220,10 +235,46 @@ pub fn init_tray(app: &mut App, settings: &Settings) -> Result<(), Box<dyn std::
     Ok(())
 }
 
+/// Shows the main window on a project picked from the tray's projects submenu.
+#[cfg(all(feature = "app", not(test)))]
+fn show_project<R: Runtime>(app: &AppHandle<R>, project_id: &str) {
+    let Some(window) = app.get_webview_window("main") else {
+        log::warn!("tray: main window missing (project)");
+        return;
+    };
+    if let Err(err) = window.unminimize() {
+        log::warn!("tray: failed to unminimize main window (project): {err}");
+    }
+    if let Err(err) = window.show() {
+        log::warn!("tray: failed to show main window (project): {err}");
+    }
+    if let Err(err) = window.set_focus() {
+        log::warn!("tray: failed to focus main window (project): {err}");
+    }
+    if let Err(err) = window.emit(
+        EVENT_NAVIGATE,
+        NavigatePayload {
+            hash: format!("#/main/project/{project_id}"),
+        },
+    ) {
+        log::warn!("tray: failed to emit navigate event (project): {err}");
+    }
+}
+
 #[cfg(all(feature = "app", not(test)))]
 pub fn update_tray_count<R: Runtime>(app: &AppHandle<R>, tasks: &[Task], settings: &Settings) {
-    let lang = resolve_tray_language(&settings.language);
-    let tooltip = tray_tooltip(tasks, Local::now(), lang);
+    let locale = locale_for(settings);
+    // Follow the app clock so a simulated (advanced) clock shows matching counts.
+    let now = app
+        .try_state::<crate::state::AppState>()
+        .map(|state| crate::clock::Clock::now_local(state.clock()))
+        .unwrap_or_else(Local::now);
+    let tooltip = tray_tooltip(tasks, now, locale);
+    let projects = app
+        .try_state::<crate::state::AppState>()
+        .map(|state| state.projects())
+        .unwrap_or_default();
+    let overdue = overdue_count_at(tasks, now, settings.overdue_grace_minutes);
 
     // In production we update the real tray icon. In tests we avoid touching platform tray APIs
     // (and keep coverage focused on the tooltip computation logic).
@@ -232,7 +283,8 @@ pub fn update_tray_count<R: Runtime>(app: &AppHandle<R>, tasks: &[Task], setting
             if let Err(err) = tray.set_tooltip(Some(tooltip)) {
                 log::warn!("tray: failed to update tooltip: {err}");
             }
-            match build_tray_menu(app, lang) {
+            update_tray_badge(app, &tray, overdue);
+            match build_tray_menu(app, locale, settings.dnd_enabled, &projects) {
                 Ok(menu) => {
                     if let Err(err) = tray.set_menu(Some(menu)) {
                         log::warn!("tray: failed to update menu: {err}");
@@ -246,17 +298,122 @@ pub fn update_tray_count<R: Runtime>(app: &AppHandle<R>, tasks: &[Task], setting
     }
 }
 
+/// The overdue count on the tray icon. macOS menu bar icons are small template images, so the
+/// count goes next to the icon as its title there; elsewhere it is drawn onto the app icon as a
+/// red badge, falling back to the title when the platform refuses the new image.
+#[cfg(all(feature = "app", not(test)))]
+fn update_tray_badge<R: Runtime>(
+    app: &AppHandle<R>,
+    tray: &tauri::tray::TrayIcon<R>,
+    overdue: usize,
+) {
+    let title = (overdue > 0).then(|| badge_text(overdue));
+    if cfg!(target_os = "macos") {
+        if let Err(err) = tray.set_title(title) {
+            log::warn!("tray: failed to update title: {err}");
+        }
+        return;
+    }
+    let Some(base) = app.default_window_icon() else {
+        return;
+    };
+    let mut rgba = base.rgba().to_vec();
+    draw_overdue_badge(&mut rgba, base.width(), base.height(), overdue);
+    if let Err(err) = tray.set_icon(Some(Image::new_owned(rgba, base.width(), base.height()))) {
+        log::warn!("tray: failed to update icon, showing the count as title: {err}");
+        if let Err(err) = tray.set_title(title) {
+            log::warn!("tray: failed to update title: {err}");
+        }
+    }
+}
+
+fn badge_text(count: usize) -> String {
+    if count > 9 {
+        "9+".to_string()
+    } else {
+        count.to_string()
+    }
+}
+
+/// Paints `count` as a red badge in the top-right corner of a `width`x`height` RGBA image.
+/// A count of 0 leaves the image as it is.
+fn draw_overdue_badge(rgba: &mut [u8], width: u32, height: u32, count: usize) {
+    if count == 0 || rgba.len() < (width * height * 4) as usize {
+        return;
+    }
+    let (w, h) = (width as i64, height as i64);
+    let diameter = w.min(h) * 6 / 10;
+    if diameter < 6 {
+        return;
+    }
+    let radius = diameter / 2;
+    let (cx, cy) = (w - radius - 1, radius);
+    let mut put = |x: i64, y: i64, color: [u8; 4]| {
+        if (0..w).contains(&x) && (0..h).contains(&y) {
+            let at = ((y * w + x) * 4) as usize;
+            rgba[at..at + 4].copy_from_slice(&color);
+        }
+    };
+    for y in cy - radius..=cy + radius {
+        for x in cx - radius..=cx + radius {
+            let (dx, dy) = (x - cx, y - cy);
+            if dx * dx + dy * dy <= radius * radius {
+                put(x, y, BADGE_RGBA);
+            }
+        }
+    }
+
+    let glyphs: Vec<usize> = badge_text(count)
+        .chars()
+        .map(|c| c.to_digit(10).map_or(10, |digit| digit as usize))
+        .collect();
+    let columns = glyphs.len() as i64 * 4 - 1;
+    let scale = (diameter * 7 / 10 / columns)
+        .min(diameter * 6 / 10 / 5)
+        .max(1);
+    let left = cx - columns * scale / 2;
+    let top = cy - 5 * scale / 2;
+    for (index, glyph) in glyphs.iter().enumerate() {
+        for (row, bits) in GLYPHS[*glyph].iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) == 0 {
+                    continue;
+                }
+                let x0 = left + (index as i64 * 4 + column) * scale;
+                let y0 = top + row as i64 * scale;
+                for y in y0..y0 + scale {
+                    for x in x0..x0 + scale {
+                        put(x, y, BADGE_TEXT_RGBA);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Open, started tasks past their due time plus the overdue grace period.
+fn overdue_count_at(tasks: &[Task], now: chrono::DateTime<Local>, grace_minutes: i64) -> usize {
+    let now_ts = now.timestamp();
+    tasks
+        .iter()
+        .filter(|task| !task.is_scheduled(now_ts) && task.is_overdue(now_ts, grace_minutes))
+        .count()
+}
+
 fn pending_count_at(tasks: &[Task], now: chrono::DateTime<Local>) -> usize {
     let now_ts = now.timestamp();
     let today = now.date_naive();
     tasks
         .iter()
-        .filter(|task| !task.completed)
+        .filter(|task| !task.completed && !task.someday && !task.is_scheduled(now_ts))
         .filter(|task| {
-            if task.due_at < now_ts {
+            let Some(due_at) = task.due_at else {
+                return false;
+            };
+            if due_at < now_ts {
                 return true;
             }
-            let due = Local.timestamp_opt(task.due_at, 0).single();
+            let due = Local.timestamp_opt(due_at, 0).single();
             if let Some(due_time) = due {
                 return due_time.date_naive() == today;
             }
@@ -265,10 +422,26 @@ fn pending_count_at(tasks: &[Task], now: chrono::DateTime<Local>) -> usize {
         .count()
 }
 
-fn tray_tooltip(tasks: &[Task], now: chrono::DateTime<Local>, lang: TrayLanguage) -> String {
+/// The projects submenu as `(menu id, label)`: pinned first, then in sidebar order, at most
+/// `TRAY_PROJECTS_MAX`. Labels carry the emoji icon; the inbox shows its localized name.
+fn tray_projects(projects: &[Project], locale: DateLocale) -> Vec<(String, String)> {
+    let mut projects = localized_projects(projects, locale);
+    projects.sort_by_key(|project| (!project.pinned, project.sort_order));
+    projects
+        .iter()
+        .take(TRAY_PROJECTS_MAX)
+        .map(|project| {
+            (
+                format!("{PROJECT_ITEM_PREFIX}{}", project.id),
+                project_label(project),
+            )
+        })
+        .collect()
+}
+
+fn tray_tooltip(tasks: &[Task], now: chrono::DateTime<Local>, locale: DateLocale) -> String {
     let count = pending_count_at(tasks, now);
-    let labels = tray_labels(lang);
-    format!("{}: {count}", labels.tooltip_prefix)
+    format!("{}: {count}", tr(locale, Text::TrayPending))
 }
 
 #[cfg(test)]
@@ -281,7 +454,7 @@ mod tests {
             id: id.to_string(),
             project_id: "inbox".to_string(),
             title: format!("task-{id}"),
-            due_at,
+            due_at: Some(due_at),
             important: false,
             completed,
             completed_at: None,
@@ -295,6 +468,30 @@ mod tests {
             sample_tag: None,
             reminder: ReminderConfig::default(),
             repeat: RepeatRule::None,
+            waiting_on: None,
+            waiting_since: None,
+            series_id: None,
+            pinned: false,
+            context: None,
+            comments: Vec::new(),
+            progress: Default::default(),
+            spawn_ahead: 0,
+            repeat_from_completion: false,
+            end_after_occurrences: None,
+            end_by_date: None,
+            occurrence_index: 0,
+            timezone: None,
+            escalate_after: None,
+            start_at: None,
+            someday: false,
+            carry_over_count: 0,
+            skip_carry_over: false,
+            color: None,
+            emoji: None,
+            estimate_minutes: None,
+            attachments: Vec::new(),
+            time_entries: Vec::new(),
+            blocked_by: Vec::new(),
         }
     }
 
@@ -303,7 +500,12 @@ mod tests {
         let now = Local::now();
         let now_ts = now.timestamp();
 
+        // Not started yet: kept out of the count until start_at.
+        let mut scheduled = make_task("scheduled", now_ts - 60, false);
+        scheduled.start_at = Some(now_ts + 3600);
+
         let tasks = vec![
+            scheduled,
             // Overdue (counts via due_at < now_ts).
             make_task("overdue", now_ts - 60, false),
             // Due today but in the future (counts via same-day match).
@@ -318,11 +520,81 @@ mod tests {
 
         let count = pending_count_at(&tasks, now);
         assert_eq!(count, 2);
+        assert_eq!(overdue_count_at(&tasks, now, 0), 1);
+        // One minute late is still inside a five-minute grace window.
+        assert_eq!(overdue_count_at(&tasks, now, 5), 0);
+        let mut late = tasks.clone();
+        late.push(make_task("late", now_ts - 10 * 60, false));
+        assert_eq!(overdue_count_at(&late, now, 5), 1);
 
-        let tooltip = tray_tooltip(&tasks, now, TrayLanguage::Zh);
+        let tooltip = tray_tooltip(&tasks, now, DateLocale::Zh);
         assert_eq!(tooltip, "待办: 2");
 
-        let tooltip_en = tray_tooltip(&tasks, now, TrayLanguage::En);
+        let tooltip_en = tray_tooltip(&tasks, now, DateLocale::En);
         assert_eq!(tooltip_en, "Pending: 2");
     }
+
+    #[test]
+    fn tray_projects_list_pinned_first_with_their_icons() {
+        let project = |id: &str, name: &str, pinned: bool, sort_order: i64| -> Project {
+            serde_json::from_value(serde_json::json!({
+                "id": id,
+                "name": name,
+                "pinned": pinned,
+                "sort_order": sort_order
+            }))
+            .unwrap()
+        };
+        let mut work = project("work", "Work", false, 2);
+        work.icon = Some("💼".to_string());
+        let mut home = project("home", "Home", false, 1);
+        home.icon = Some("star".to_string());
+        let projects = vec![work, home, project("inbox", "Inbox", true, 0)];
+
+        assert_eq!(
+            tray_projects(&projects, DateLocale::Zh),
+            vec![
+                ("project:inbox".to_string(), "收件箱".to_string()),
+                ("project:home".to_string(), "Home".to_string()),
+                ("project:work".to_string(), "💼 Work".to_string()),
+            ]
+        );
+        let many = (0..20)
+            .map(|i| project(&format!("p{i}"), "P", false, i))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tray_projects(&many, DateLocale::En).len(),
+            TRAY_PROJECTS_MAX
+        );
+    }
+
+    #[test]
+    fn overdue_badge_is_drawn_in_the_top_right_corner() {
+        let (width, height) = (32u32, 32u32);
+        let blank = vec![0u8; (width * height * 4) as usize];
+        let pixel = |rgba: &[u8], x: u32, y: u32| {
+            let at = ((y * width + x) * 4) as usize;
+            [rgba[at], rgba[at + 1], rgba[at + 2], rgba[at + 3]]
+        };
+
+        let mut untouched = blank.clone();
+        draw_overdue_badge(&mut untouched, width, height, 0);
+        assert_eq!(untouched, blank);
+
+        let mut one = blank.clone();
+        draw_overdue_badge(&mut one, width, height, 1);
+        // Badge edge is red, the bottom-left corner keeps the icon, and the digit is white.
+        assert_eq!(pixel(&one, 31 - 9, 1), BADGE_RGBA);
+        assert_eq!(pixel(&one, 0, 31), [0, 0, 0, 0]);
+        assert!((0..32)
+            .flat_map(|y| (0..32).map(move |x| (x, y)))
+            .any(|(x, y)| pixel(&one, x, y) == BADGE_TEXT_RGBA));
+
+        assert_eq!(badge_text(7), "7");
+        assert_eq!(badge_text(12), "9+");
+        // Images too small for a legible badge stay as they are.
+        let mut tiny = vec![0u8; 4 * 4 * 4];
+        draw_overdue_badge(&mut tiny, 4, 4, 3);
+        assert!(tiny.iter().all(|byte| *byte == 0));
+    }
 }
@@ -0,0 +1,346 @@
+//! Undo/redo for destructive task and project mutations. An entry keeps only the records the
+//! action touched, each with its position before and after, so undoing a delete puts the task
+//! back where it was without rewinding unrelated edits made since. The trash and the tag
+//! definitions are tracked the same way, so undoing a delete also takes the task back out of
+//! the trash and undoing a tag merge brings the merged definitions back.
+
+use std::collections::{BTreeMap, HashSet};
+
+use crate::models::{Project, Tag, Task, TasksFile, TrashEntry};
+
+/// Oldest entries are dropped past this many.
+pub const UNDO_LIMIT: usize = 50;
+
+trait Record: Clone + serde::Serialize {
+    fn id(&self) -> &str;
+}
+
+impl Record for Task {
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Record for Project {
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Record for Tag {
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl Record for TrashEntry {
+    fn id(&self) -> &str {
+        &self.task.id
+    }
+}
+
+/// One record on both sides of an action; `None` means it did not exist on that side.
+#[derive(Debug, Clone)]
+struct Change<T> {
+    before: Option<(usize, T)>,
+    after: Option<(usize, T)>,
+}
+
+fn same<T: serde::Serialize>(a: &T, b: &T) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+fn diff<T: Record>(before: &[T], after: &[T]) -> Vec<Change<T>> {
+    let old: BTreeMap<&str, (usize, &T)> = before
+        .iter()
+        .enumerate()
+        .map(|(index, record)| (record.id(), (index, record)))
+        .collect();
+    let mut seen = HashSet::new();
+    let mut changes = Vec::new();
+    for (index, record) in after.iter().enumerate() {
+        seen.insert(record.id());
+        match old.get(record.id()) {
+            Some((_, previous)) if same(*previous, record) => {}
+            previous => changes.push(Change {
+                before: previous.map(|(i, previous)| (*i, (*previous).clone())),
+                after: Some((index, record.clone())),
+            }),
+        }
+    }
+    for (index, record) in before.iter().enumerate() {
+        if !seen.contains(record.id()) {
+            changes.push(Change {
+                before: Some((index, record.clone())),
+                after: None,
+            });
+        }
+    }
+    changes
+}
+
+/// Pulls every record the action touched out of `records`, then reinserts the `pick`ed side in
+/// ascending position. The untouched records keep their relative order, so positions line up.
+fn apply<T: Record>(
+    records: &mut Vec<T>,
+    changes: &[Change<T>],
+    pick: impl Fn(&Change<T>) -> &Option<(usize, T)>,
+) {
+    let touched: HashSet<&str> = changes
+        .iter()
+        .flat_map(|change| [&change.before, &change.after])
+        .filter_map(|side| side.as_ref().map(|(_, record)| record.id()))
+        .collect();
+    records.retain(|record| !touched.contains(record.id()));
+    let mut restored: Vec<&(usize, T)> = changes.iter().filter_map(|c| pick(c).as_ref()).collect();
+    restored.sort_by_key(|(index, _)| *index);
+    for (index, record) in restored {
+        records.insert((*index).min(records.len()), record.clone());
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct UndoSummary {
+    /// Command that made the change, e.g. `delete_project`.
+    pub action: String,
+    pub at: i64,
+    pub tasks: usize,
+    pub projects: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct UndoEntry {
+    action: String,
+    at: i64,
+    tasks: Vec<Change<Task>>,
+    projects: Vec<Change<Project>>,
+    trash: Vec<Change<TrashEntry>>,
+    tags: Vec<Change<Tag>>,
+}
+
+impl UndoEntry {
+    /// `None` when nothing actually changed (e.g. deleting ids that no longer exist).
+    pub fn between(action: &str, at: i64, before: &TasksFile, after: &TasksFile) -> Option<Self> {
+        let entry = Self {
+            action: action.to_string(),
+            at,
+            tasks: diff(&before.tasks, &after.tasks),
+            projects: diff(&before.projects, &after.projects),
+            trash: diff(&before.trash, &after.trash),
+            tags: diff(&before.tags, &after.tags),
+        };
+        if entry.tasks.is_empty()
+            && entry.projects.is_empty()
+            && entry.trash.is_empty()
+            && entry.tags.is_empty()
+        {
+            return None;
+        }
+        Some(entry)
+    }
+
+    pub fn summary(&self) -> UndoSummary {
+        UndoSummary {
+            action: self.action.clone(),
+            at: self.at,
+            tasks: self.tasks.len(),
+            projects: self.projects.len(),
+        }
+    }
+
+    pub fn revert(
+        &self,
+        tasks: &mut Vec<Task>,
+        projects: &mut Vec<Project>,
+        trash: &mut Vec<TrashEntry>,
+        tags: &mut Vec<Tag>,
+    ) {
+        apply(projects, &self.projects, |change| &change.before);
+        apply(tasks, &self.tasks, |change| &change.before);
+        apply(trash, &self.trash, |change| &change.before);
+        apply(tags, &self.tags, |change| &change.before);
+    }
+
+    pub fn reapply(
+        &self,
+        tasks: &mut Vec<Task>,
+        projects: &mut Vec<Project>,
+        trash: &mut Vec<TrashEntry>,
+        tags: &mut Vec<Tag>,
+    ) {
+        apply(projects, &self.projects, |change| &change.after);
+        apply(tasks, &self.tasks, |change| &change.after);
+        apply(trash, &self.trash, |change| &change.after);
+        apply(tags, &self.tags, |change| &change.after);
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct UndoHistory {
+    undo: Vec<UndoEntry>,
+    redo: Vec<UndoEntry>,
+}
+
+impl UndoHistory {
+    /// A new action invalidates whatever was undone before it.
+    pub fn push(&mut self, entry: UndoEntry) {
+        self.redo.clear();
+        self.undo.push(entry);
+        if self.undo.len() > UNDO_LIMIT {
+            self.undo.remove(0);
+        }
+    }
+
+    pub fn pop_undo(&mut self) -> Option<UndoEntry> {
+        self.undo.pop()
+    }
+
+    pub fn pop_redo(&mut self) -> Option<UndoEntry> {
+        self.redo.pop()
+    }
+
+    pub fn push_undone(&mut self, entry: UndoEntry) {
+        self.redo.push(entry);
+    }
+
+    pub fn push_redone(&mut self, entry: UndoEntry) {
+        self.undo.push(entry);
+    }
+
+    pub fn status(&self) -> UndoStatus {
+        UndoStatus {
+            undo: self.undo.last().map(UndoEntry::summary),
+            redo: self.redo.last().map(UndoEntry::summary),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+/// What the next undo / redo would do, for the UI labels.
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct UndoStatus {
+    pub undo: Option<UndoSummary>,
+    pub redo: Option<UndoSummary>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+
+    fn task(id: &str, title: &str) -> Task {
+        test_support::task(id).title(title)
+    }
+
+    fn file(tasks: Vec<Task>, trash: Vec<TrashEntry>) -> TasksFile {
+        TasksFile {
+            schema_version: 1,
+            tasks,
+            projects: Vec::new(),
+            trash,
+            tags: Vec::new(),
+        }
+    }
+
+    fn ids(tasks: &[Task]) -> Vec<&str> {
+        tasks.iter().map(|task| task.id.as_str()).collect()
+    }
+
+    #[test]
+    fn revert_restores_positions_and_keeps_unrelated_edits() {
+        let before = vec![
+            task("a", "A"),
+            task("b", "B"),
+            task("c", "C"),
+            task("d", "D"),
+        ];
+        let mut after = before.clone();
+        after.remove(2);
+        after.remove(0);
+        after[0].title = "B edited".to_string();
+        after.push(task("e", "E"));
+        let entry = UndoEntry::between(
+            "bulk",
+            5,
+            &file(before, Vec::new()),
+            &file(after.clone(), Vec::new()),
+        )
+        .unwrap();
+        assert_eq!(entry.summary().tasks, 4);
+
+        // A later edit the action never touched survives the undo.
+        let mut current = after.clone();
+        current[1].title = "D later".to_string();
+        let mut projects = Vec::new();
+        let mut trash = Vec::new();
+        let mut tags = Vec::new();
+        entry.revert(&mut current, &mut projects, &mut trash, &mut tags);
+        assert_eq!(ids(&current), vec!["a", "b", "c", "d"]);
+        assert_eq!(current[1].title, "B");
+        assert_eq!(current[3].title, "D later");
+
+        entry.reapply(&mut current, &mut projects, &mut trash, &mut tags);
+        assert_eq!(ids(&current), vec!["b", "d", "e"]);
+        assert_eq!(current[0].title, "B edited");
+    }
+
+    #[test]
+    fn no_op_actions_are_not_recorded() {
+        let tasks = file(vec![task("a", "A")], Vec::new());
+        assert!(UndoEntry::between("delete_task", 1, &tasks, &tasks).is_none());
+    }
+
+    #[test]
+    fn undoing_a_trashed_delete_takes_the_task_back_out_of_the_trash() {
+        let before = file(vec![task("a", "A"), task("b", "B")], Vec::new());
+        let after = file(
+            vec![task("b", "B")],
+            vec![TrashEntry {
+                task: task("a", "A"),
+                deleted_at: 9,
+            }],
+        );
+        let entry = UndoEntry::between("delete_task", 9, &before, &after).unwrap();
+        let mut current = after.clone();
+        entry.revert(
+            &mut current.tasks,
+            &mut current.projects,
+            &mut current.trash,
+            &mut current.tags,
+        );
+        assert_eq!(ids(&current.tasks), vec!["a", "b"]);
+        assert!(current.trash.is_empty());
+        entry.reapply(
+            &mut current.tasks,
+            &mut current.projects,
+            &mut current.trash,
+            &mut current.tags,
+        );
+        assert_eq!(ids(&current.tasks), vec!["b"]);
+        assert_eq!(current.trash.len(), 1);
+    }
+
+    #[test]
+    fn history_caps_entries_and_new_actions_drop_redo() {
+        let before = file(vec![task("a", "A")], Vec::new());
+        let after = file(Vec::new(), Vec::new());
+        let entry = || UndoEntry::between("delete_task", 1, &before, &after).unwrap();
+        let mut history = UndoHistory::default();
+        for _ in 0..UNDO_LIMIT + 5 {
+            history.push(entry());
+        }
+        assert_eq!(history.undo.len(), UNDO_LIMIT);
+
+        let undone = history.pop_undo().unwrap();
+        history.push_undone(undone);
+        assert_eq!(history.status().redo.unwrap().action, "delete_task");
+        history.push(entry());
+        assert!(history.status().redo.is_none());
+        history.clear();
+        assert!(history.status().undo.is_none());
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::calendar::{format_datetime, DateLocale};
+use crate::focus::FocusSession;
+use crate::models::{DateFormat, Project, Task, Timestamp};
+use crate::stats::{completed_at, focus_stats, local_date, FocusStats};
+
+const REVIEW_DAYS: i64 = 7;
+/// Caps each task list in the prompt; the counts still cover everything.
+const MAX_PROMPT_TASKS: usize = 60;
+/// Caps each list in the parsed review.
+const MAX_ITEMS: usize = 8;
+/// Caps the project and estimate lines of the focus section in the prompt.
+const MAX_PROMPT_FOCUS_ROWS: usize = 5;
+
+/// A generated weekly review, stored as `reviews/weekly-<YYYY-MM-DD>.json` (the local day the
+/// week ended).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WeeklyReview {
+    pub generated_at: Timestamp,
+    /// `[from, to)`: the seven days before generation.
+    pub from: Timestamp,
+    pub to: Timestamp,
+    pub completed_count: usize,
+    pub overdue_count: usize,
+    pub summary: String,
+    pub wins: Vec<String>,
+    /// Overdue or postponed work, with a short reason when the model offers one.
+    pub slipped: Vec<String>,
+    pub next_week_focus: Vec<String>,
+    /// Tracked and focused time of the week; missing in reviews saved before it was added.
+    #[serde(default)]
+    pub focus: Option<FocusStats>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SavedWeeklyReview {
+    pub path: String,
+    pub review: WeeklyReview,
+}
+
+/// What goes to the AI provider, plus the counts the review keeps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReviewPrompt {
+    pub from: Timestamp,
+    pub to: Timestamp,
+    pub completed_count: usize,
+    pub overdue_count: usize,
+    pub focus: FocusStats,
+    pub system: String,
+    pub user: String,
+}
+
+fn task_line(task: &Task, projects: &HashMap<&str, &str>, when: Option<Timestamp>) -> String {
+    let project = projects
+        .get(task.project_id.as_str())
+        .copied()
+        .unwrap_or(task.project_id.as_str());
+    let mut line = format!("- [{project}] {}", task.title.trim());
+    if let Some(ts) = when {
+        line.push_str(&format!(" ({})", format_datetime(ts, DateFormat::Ymd)));
+    }
+    if task.carry_over_count > 0 {
+        line.push_str(&format!(" carried over {}x", task.carry_over_count));
+    }
+    line
+}
+
+fn hours(seconds: i64) -> String {
+    format!("{}h{:02}m", seconds / 3600, seconds % 3600 / 60)
+}
+
+/// Tasks completed during the past week, every open task whose due time has passed, and the
+/// week's tracked and focused time.
+pub fn review_prompt(
+    tasks: &[Task],
+    sessions: &[FocusSession],
+    projects: &[Project],
+    language: &str,
+    system_locale: &str,
+    now: Timestamp,
+) -> ReviewPrompt {
+    let from = now - REVIEW_DAYS * 86_400;
+    let names: HashMap<&str, &str> = projects
+        .iter()
+        .map(|project| (project.id.as_str(), project.name.as_str()))
+        .collect();
+
+    let mut completed: Vec<(&Task, Timestamp)> = tasks
+        .iter()
+        .filter_map(|task| completed_at(task).map(|at| (task, at)))
+        .filter(|(_, at)| (from..now).contains(at))
+        .collect();
+    completed.sort_by_key(|(_, at)| *at);
+    let mut overdue: Vec<(&Task, Timestamp)> = tasks
+        .iter()
+        .filter(|task| !task.completed)
+        .filter_map(|task| task.due_at.map(|due| (task, due)))
+        .filter(|(_, due)| *due < now)
+        .collect();
+    overdue.sort_by_key(|(_, due)| *due);
+
+    let reply_language = match DateLocale::resolve(language, system_locale) {
+        DateLocale::Zh => "Simplified Chinese",
+        DateLocale::En => "English",
+    };
+    let system = format!(
+        "You write a short weekly review for a personal to-do list. Reply with one JSON object and \
+         nothing else: {{\"summary\": string, \"wins\": [string], \"slipped\": [string], \
+         \"next_week_focus\": [string]}}. wins: what got done that mattered. slipped: overdue or \
+         repeatedly postponed work, with a likely reason when the data suggests one. \
+         next_week_focus: three to five concrete priorities. At most {MAX_ITEMS} items per list. \
+         Write in {reply_language}."
+    );
+
+    let mut user = format!(
+        "Week: {} to {}\n\nCompleted ({}):\n",
+        format_datetime(from, DateFormat::Ymd),
+        format_datetime(now, DateFormat::Ymd),
+        completed.len()
+    );
+    for (task, at) in completed.iter().rev().take(MAX_PROMPT_TASKS) {
+        user.push_str(&task_line(task, &names, Some(*at)));
+        user.push('\n');
+    }
+    user.push_str(&format!("\nOverdue ({}):\n", overdue.len()));
+    for (task, due) in overdue.iter().take(MAX_PROMPT_TASKS) {
+        user.push_str(&task_line(task, &names, Some(*due)));
+        user.push('\n');
+    }
+
+    let focus = focus_stats(tasks, sessions, from, now, now);
+    user.push_str(&format!(
+        "\nTime: {} tracked, {} in {} focus sessions\n",
+        hours(focus.tracked_seconds),
+        hours(focus.focus_seconds),
+        focus.focus_sessions
+    ));
+    for row in focus.projects.iter().take(MAX_PROMPT_FOCUS_ROWS) {
+        let project = names
+            .get(row.key.as_str())
+            .copied()
+            .unwrap_or(row.key.as_str());
+        user.push_str(&format!(
+            "- [{project}] {}\n",
+            hours(row.tracked_seconds + row.focus_seconds)
+        ));
+    }
+    for row in focus.estimates.iter().take(MAX_PROMPT_FOCUS_ROWS) {
+        user.push_str(&format!(
+            "- {}: estimated {}m, spent {}m\n",
+            row.title.trim(),
+            row.estimate_minutes,
+            row.actual_minutes
+        ));
+    }
+
+    ReviewPrompt {
+        from,
+        to: now,
+        completed_count: completed.len(),
+        overdue_count: overdue.len(),
+        focus,
+        system,
+        user,
+    }
+}
+
+fn string_list(value: &serde_json::Value) -> Vec<String> {
+    let mut items: Vec<String> = Vec::new();
+    for item in value.as_array().into_iter().flatten() {
+        let Some(text) = item.as_str().map(str::trim).filter(|text| !text.is_empty()) else {
+            continue;
+        };
+        if items.len() < MAX_ITEMS && !items.iter().any(|seen| seen == text) {
+            items.push(text.to_string());
+        }
+    }
+    items
+}
+
+/// The model's reply as a review; errors when it holds no JSON object or nothing usable.
+pub fn parse_review(
+    prompt: &ReviewPrompt,
+    text: &str,
+    now: Timestamp,
+) -> Result<WeeklyReview, String> {
+    let value = crate::ai::reply_json(text)
+        .ok_or_else(|| "failed to parse ai response as json".to_string())?;
+    let review = WeeklyReview {
+        generated_at: now,
+        from: prompt.from,
+        to: prompt.to,
+        completed_count: prompt.completed_count,
+        overdue_count: prompt.overdue_count,
+        summary: value["summary"].as_str().unwrap_or("").trim().to_string(),
+        wins: string_list(&value["wins"]),
+        slipped: string_list(&value["slipped"]),
+        next_week_focus: string_list(&value["next_week_focus"]),
+        focus: Some(prompt.focus.clone()),
+    };
+    if review.summary.is_empty()
+        && review.wins.is_empty()
+        && review.slipped.is_empty()
+        && review.next_week_focus.is_empty()
+    {
+        return Err("ai response has no review content".to_string());
+    }
+    Ok(review)
+}
+
+pub fn reviews_dir(root: &Path) -> PathBuf {
+    root.join("reviews")
+}
+
+/// One document per week-ending day; regenerating on the same day replaces it.
+pub fn review_path(root: &Path, review: &WeeklyReview) -> PathBuf {
+    let day = local_date(review.to)
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| review.to.to_string());
+    reviews_dir(root).join(format!("weekly-{day}.json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support;
+
+    const NOW: Timestamp = 1_700_000_000;
+
+    fn task(id: &str, completed_at: Option<Timestamp>, due_at: Option<Timestamp>) -> Task {
+        test_support::task(id)
+            .project("work")
+            .title(&format!("Task {id}"))
+            .done_at(completed_at)
+            .due(due_at)
+    }
+
+    #[test]
+    fn prompt_lists_last_weeks_completions_and_open_overdue_tasks() {
+        let tasks = vec![
+            task("done", Some(NOW - 86_400), None),
+            task("old", Some(NOW - 8 * 86_400), None),
+            task("late", None, Some(NOW - 3 * 86_400)),
+            task("later", None, Some(NOW + 86_400)),
+        ];
+        let projects: Vec<Project> =
+            serde_json::from_value(serde_json::json!([{ "id": "work", "name": "Job" }])).unwrap();
+        let sessions = vec![FocusSession {
+            task_id: "done".to_string(),
+            started_at: NOW - 2 * 86_400,
+            ended_at: NOW - 2 * 86_400 + 1500,
+            minutes: 25,
+            focused_seconds: 1500,
+            finished: true,
+        }];
+        let prompt = review_prompt(&tasks, &sessions, &projects, "en", "", NOW);
+        assert_eq!((prompt.completed_count, prompt.overdue_count), (1, 1));
+        assert_eq!(prompt.focus.focus_sessions, 1);
+        assert!(prompt
+            .user
+            .contains("Time: 0h00m tracked, 0h25m in 1 focus sessions"));
+        assert!(prompt.user.contains("- [Job] 0h25m"));
+        assert_eq!(prompt.from, NOW - 7 * 86_400);
+        assert!(prompt.user.contains("[Job] Task done"));
+        assert!(prompt.user.contains("[Job] Task late"));
+        assert!(!prompt.user.contains("Task old"));
+        assert!(!prompt.user.contains("Task later"));
+        assert!(prompt.system.contains("English"));
+        assert!(review_prompt(&tasks, &[], &projects, "zh", "", NOW)
+            .system
+            .contains("Chinese"));
+    }
+
+    #[test]
+    fn parse_review_cleans_lists_and_rejects_empty_replies() {
+        let prompt = review_prompt(&[], &[], &[], "en", "", NOW);
+        let text = "```json\n{\"summary\":\" Solid week \",\"wins\":[\"a\",\"a\",\" \",3],\
+                    \"slipped\":[\"b\"],\"next_week_focus\":[\"c\",\"d\"]}\n```";
+        let review = parse_review(&prompt, text, NOW + 5).unwrap();
+        assert_eq!(review.summary, "Solid week");
+        assert_eq!(review.wins, vec!["a"]);
+        assert_eq!(review.slipped, vec!["b"]);
+        assert_eq!(review.next_week_focus, vec!["c", "d"]);
+        assert_eq!((review.generated_at, review.to), (NOW + 5, NOW));
+        assert_eq!(review.focus, Some(prompt.focus.clone()));
+
+        // Reviews saved before focus time was added still load.
+        let mut old = serde_json::to_value(&review).unwrap();
+        old.as_object_mut().unwrap().remove("focus");
+        let old: WeeklyReview = serde_json::from_value(old).unwrap();
+        assert_eq!(old.focus, None);
+
+        assert!(parse_review(&prompt, "no json here", NOW).is_err());
+        assert!(parse_review(&prompt, "{\"wins\": []}", NOW).is_err());
+    }
+}
@@ -131,6 +131,13 @@ pub fn show_settings_window<R: Runtime>(app: &AppHandle<R>) -> Result<(), String
     }
 }
 
+/// True when any app window is on screen; used to detect a "headless" (tray-only) session.
+pub fn any_window_visible<R: Runtime>(app: &AppHandle<R>) -> bool {
+    app.webview_windows()
+        .values()
+        .any(|window| window.is_visible().unwrap_or(false))
+}
+
 pub fn hide_quick_window<R: Runtime>(app: &AppHandle<R>) -> bool {
     if let Some(window) = app.get_webview_window("quick") {
         if let Err(err) = window.hide() {
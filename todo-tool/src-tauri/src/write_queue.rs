@@ -0,0 +1,224 @@
+//! Write-behind for `persist`: commands change memory and notify the UI at once, and the
+//! disk write follows once edits have been quiet for [`DEBOUNCE`], so a burst (bulk edits,
+//! imports, typing in notes) costs one write of data.json instead of one per command.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Quiet period after the latest change before the write happens.
+pub const DEBOUNCE: Duration = Duration::from_millis(500);
+/// A steady stream of changes still gets written at least this often.
+pub const MAX_DELAY: Duration = Duration::from_secs(5);
+/// A failed write is tried again after this long (or by the next flush).
+pub const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// A write that is owed: the data directory it goes to and whether an auto backup was due.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingWrite {
+    pub root: PathBuf,
+    pub with_backup: bool,
+}
+
+/// What the UI is told about write-behind (`get_write_status`, `write_status` event).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WriteStatus {
+    /// Edits made in memory that are not on disk yet.
+    pub pending: bool,
+    /// Why the latest write failed; cleared by the next one that succeeds.
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Queue {
+    pending: Option<PendingWrite>,
+    first_at: Option<Instant>,
+    due_at: Option<Instant>,
+    last_error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct WriteQueue {
+    queue: Mutex<Queue>,
+    wake: Condvar,
+    // Held while a write runs, so a flush waits for one already in progress.
+    writing: Mutex<()>,
+}
+
+impl WriteQueue {
+    fn queue(&self) -> MutexGuard<'_, Queue> {
+        self.queue
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Marks the data dirty. The write is due [`DEBOUNCE`] after the latest call, but no later
+    /// than [`MAX_DELAY`] after the first unwritten one; a requested backup sticks until
+    /// written.
+    pub fn schedule(&self, root: PathBuf, with_backup: bool, now: Instant) {
+        let mut queue = self.queue();
+        let with_backup = with_backup
+            || queue
+                .pending
+                .as_ref()
+                .is_some_and(|pending| pending.with_backup);
+        let first_at = *queue.first_at.get_or_insert(now);
+        queue.due_at = Some((now + DEBOUNCE).min(first_at + MAX_DELAY));
+        queue.pending = Some(PendingWrite { root, with_backup });
+        self.wake.notify_all();
+    }
+
+    /// Puts back a write that failed. A newer request already covers it, except for its
+    /// backup flag.
+    pub fn retry(&self, write: PendingWrite, now: Instant) {
+        let mut queue = self.queue();
+        match &mut queue.pending {
+            Some(pending) => pending.with_backup |= write.with_backup,
+            None => {
+                queue.pending = Some(write);
+                queue.first_at = Some(now);
+                queue.due_at = Some(now + RETRY_DELAY);
+            }
+        }
+        self.wake.notify_all();
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.queue().pending.is_some()
+    }
+
+    /// Whether the latest write failed, so edits may only exist in memory.
+    pub fn has_failed(&self) -> bool {
+        self.queue().last_error.is_some()
+    }
+
+    pub fn status(&self) -> WriteStatus {
+        let queue = self.queue();
+        WriteStatus {
+            pending: queue.pending.is_some(),
+            last_error: queue.last_error.clone(),
+        }
+    }
+
+    fn take(&self) -> Option<PendingWrite> {
+        let mut queue = self.queue();
+        queue.first_at = None;
+        queue.due_at = None;
+        queue.pending.take()
+    }
+
+    /// Blocks until a write is due; the writer thread then runs [`WriteQueue::run`].
+    // Only the app has a writer thread, not `--no-default-features` builds.
+    #[allow(dead_code)]
+    pub fn wait_until_due(&self) {
+        let mut queue = self.queue();
+        loop {
+            let now = Instant::now();
+            queue = match queue.due_at {
+                Some(due_at) if due_at <= now => return,
+                Some(due_at) => {
+                    self.wake
+                        .wait_timeout(queue, due_at - now)
+                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                        .0
+                }
+                None => self
+                    .wake
+                    .wait(queue)
+                    .unwrap_or_else(|poisoned| poisoned.into_inner()),
+            };
+        }
+    }
+
+    /// Runs `write` for the pending write, due or not. A failed write is queued again and its
+    /// error kept for [`WriteQueue::status`]. Returns whether anything was written.
+    pub fn run<E: std::fmt::Display>(
+        &self,
+        write: impl FnOnce(&PendingWrite) -> Result<(), E>,
+    ) -> Result<bool, E> {
+        let _writing = self
+            .writing
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let Some(pending) = self.take() else {
+            return Ok(false);
+        };
+        match write(&pending) {
+            Ok(()) => {
+                self.queue().last_error = None;
+                Ok(true)
+            }
+            Err(err) => {
+                self.queue().last_error = Some(err.to_string());
+                self.retry(pending, Instant::now());
+                Err(err)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bursts_coalesce_into_one_write_that_is_due_after_the_quiet_period() {
+        let queue = WriteQueue::default();
+        let root = PathBuf::from("/data");
+        let start = Instant::now();
+        queue.schedule(root.clone(), false, start);
+        queue.schedule(root.clone(), true, start + Duration::from_millis(300));
+        queue.schedule(root.clone(), false, start + Duration::from_millis(600));
+        assert_eq!(
+            queue.queue().due_at,
+            Some(start + Duration::from_millis(1100))
+        );
+        assert_eq!(
+            queue.take(),
+            Some(PendingWrite {
+                root: root.clone(),
+                with_backup: true
+            })
+        );
+        assert!(!queue.is_pending());
+
+        // Changes every 300ms never go quiet; the write still happens after MAX_DELAY.
+        let mut at = start;
+        while at < start + MAX_DELAY {
+            queue.schedule(root.clone(), false, at);
+            at += Duration::from_millis(300);
+        }
+        assert_eq!(queue.queue().due_at, Some(start + MAX_DELAY));
+    }
+
+    #[test]
+    fn run_writes_on_demand_and_requeues_failed_writes() {
+        let queue = WriteQueue::default();
+        assert_eq!(queue.run(|_| Ok::<_, &str>(())), Ok(false));
+
+        let now = Instant::now();
+        queue.schedule(PathBuf::from("/data"), true, now);
+        assert_eq!(queue.run(|_| Err("disk full")), Err("disk full"));
+        assert!(queue.is_pending());
+        assert!(queue.queue().due_at.unwrap() >= now + RETRY_DELAY);
+        assert_eq!(
+            queue.status(),
+            WriteStatus {
+                pending: true,
+                last_error: Some("disk full".to_string())
+            }
+        );
+        let mut written = None;
+        assert_eq!(
+            queue.run(|write| {
+                written = Some(write.clone());
+                Ok::<_, &str>(())
+            }),
+            Ok(true)
+        );
+        assert!(written.unwrap().with_backup);
+        assert!(!queue.is_pending());
+        assert_eq!(queue.status(), WriteStatus::default());
+    }
+}
@@ -0,0 +1,444 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+use crate::calendar::week_start;
+use crate::models::{Project, Task, WeekStart};
+use crate::stats::{completed_at, local_date};
+
+const TOP_WEEKS: usize = 3;
+const TOP_PROJECTS: usize = 5;
+const TOP_TAGS: usize = 10;
+const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct MonthStat {
+    /// 1-12.
+    pub month: u32,
+    pub created: usize,
+    pub completed: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct WeekStat {
+    /// First day of the week (per `week_starts_on`), `YYYY-MM-DD`.
+    pub week_start: String,
+    pub completed: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct RankedCount {
+    /// Project id or tag; equals `label` for tags.
+    pub key: String,
+    pub label: String,
+    pub count: usize,
+}
+
+/// Longest run of consecutive days with at least one completion.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct Streak {
+    pub days: usize,
+    pub start: Option<String>,
+    pub end: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct YearReview {
+    pub year: i32,
+    pub total_completed: usize,
+    pub total_created: usize,
+    /// Always twelve entries, January first; the chart data for the report.
+    pub months: Vec<MonthStat>,
+    pub busiest_weeks: Vec<WeekStat>,
+    pub top_projects: Vec<RankedCount>,
+    /// Tags on tasks completed during the year.
+    pub top_tags: Vec<RankedCount>,
+    pub longest_streak: Streak,
+}
+
+/// Highest count first; ties keep key order so the report is stable between runs.
+fn ranked(counts: HashMap<String, usize>, limit: usize) -> Vec<(String, usize)> {
+    let mut list: Vec<(String, usize)> = counts.into_iter().collect();
+    list.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    list.truncate(limit);
+    list
+}
+
+pub fn longest_streak(days: &BTreeMap<NaiveDate, usize>) -> Streak {
+    let mut best = Streak::default();
+    let mut run: Option<(NaiveDate, NaiveDate, usize)> = None;
+    for day in days.keys() {
+        run = match run {
+            Some((start, end, len)) if end + Duration::days(1) == *day => {
+                Some((start, *day, len + 1))
+            }
+            _ => Some((*day, *day, 1)),
+        };
+        if let Some((start, end, len)) = run {
+            if len > best.days {
+                best = Streak {
+                    days: len,
+                    start: Some(start.format("%Y-%m-%d").to_string()),
+                    end: Some(end.format("%Y-%m-%d").to_string()),
+                };
+            }
+        }
+    }
+    best
+}
+
+/// Aggregates one calendar year (local time). Someday tasks count like any other: finishing
+/// one is still an accomplishment.
+pub fn year_review(
+    tasks: &[Task],
+    projects: &[Project],
+    year: i32,
+    week_starts_on: WeekStart,
+) -> YearReview {
+    let in_year = |date: &NaiveDate| date.year() == year;
+    let mut months: Vec<MonthStat> = (1..=12)
+        .map(|month| MonthStat {
+            month,
+            created: 0,
+            completed: 0,
+        })
+        .collect();
+    let mut days: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+    let mut weeks: HashMap<String, usize> = HashMap::new();
+    let mut by_project: HashMap<String, usize> = HashMap::new();
+    let mut by_tag: HashMap<String, usize> = HashMap::new();
+    let mut total_created = 0;
+
+    for task in tasks {
+        if let Some(created) = local_date(task.created_at).filter(in_year) {
+            total_created += 1;
+            months[created.month0() as usize].created += 1;
+        }
+        let Some(done) = completed_at(task).and_then(local_date).filter(in_year) else {
+            continue;
+        };
+        months[done.month0() as usize].completed += 1;
+        *days.entry(done).or_default() += 1;
+        let week = week_start(done, week_starts_on)
+            .format("%Y-%m-%d")
+            .to_string();
+        *weeks.entry(week).or_default() += 1;
+        *by_project.entry(task.project_id.clone()).or_default() += 1;
+        for tag in &task.tags {
+            *by_tag.entry(tag.clone()).or_default() += 1;
+        }
+    }
+
+    let names: HashMap<&str, &str> = projects
+        .iter()
+        .map(|project| (project.id.as_str(), project.name.as_str()))
+        .collect();
+    YearReview {
+        year,
+        total_completed: days.values().sum(),
+        total_created,
+        months,
+        busiest_weeks: ranked(weeks, TOP_WEEKS)
+            .into_iter()
+            .map(|(week_start, completed)| WeekStat {
+                week_start,
+                completed,
+            })
+            .collect(),
+        top_projects: ranked(by_project, TOP_PROJECTS)
+            .into_iter()
+            .map(|(key, count)| RankedCount {
+                // Completions in a deleted project still count; show the id instead.
+                label: names.get(key.as_str()).unwrap_or(&key.as_str()).to_string(),
+                key,
+                count,
+            })
+            .collect(),
+        top_tags: ranked(by_tag, TOP_TAGS)
+            .into_iter()
+            .map(|(key, count)| RankedCount {
+                label: key.clone(),
+                key,
+                count,
+            })
+            .collect(),
+        longest_streak: longest_streak(&days),
+    }
+}
+
+fn bar(count: usize, max: usize, width: usize) -> String {
+    if max == 0 {
+        return String::new();
+    }
+    "█".repeat((count * width).div_ceil(max))
+}
+
+pub fn render_markdown(review: &YearReview) -> String {
+    let mut out = format!("# {} in Review\n\n", review.year);
+    out.push_str(&format!(
+        "- Completed: **{}**\n- Created: **{}**\n",
+        review.total_completed, review.total_created
+    ));
+    let streak = &review.longest_streak;
+    if let (Some(start), Some(end)) = (&streak.start, &streak.end) {
+        out.push_str(&format!(
+            "- Longest streak: **{} days** ({start} – {end})\n",
+            streak.days
+        ));
+    }
+
+    out.push_str("\n## Month by Month\n\n| Month | Completed | Created | |\n|---|---:|---:|---|\n");
+    let max = review.months.iter().map(|m| m.completed).max().unwrap_or(0);
+    for month in &review.months {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            MONTH_NAMES[month.month as usize - 1],
+            month.completed,
+            month.created,
+            bar(month.completed, max, 20)
+        ));
+    }
+
+    let mut section = |title: &str, rows: Vec<(String, usize)>| {
+        out.push_str(&format!("\n## {title}\n\n"));
+        if rows.is_empty() {
+            out.push_str("_Nothing yet_\n");
+        }
+        for (index, (label, count)) in rows.iter().enumerate() {
+            out.push_str(&format!("{}. {label} — {count}\n", index + 1));
+        }
+    };
+    section(
+        "Busiest Weeks",
+        review
+            .busiest_weeks
+            .iter()
+            .map(|week| (format!("Week of {}", week.week_start), week.completed))
+            .collect(),
+    );
+    section(
+        "Biggest Projects",
+        review
+            .top_projects
+            .iter()
+            .map(|project| (project.label.clone(), project.count))
+            .collect(),
+    );
+    section(
+        "Most-used Tags",
+        review
+            .top_tags
+            .iter()
+            .map(|tag| (format!("#{}", tag.label), tag.count))
+            .collect(),
+    );
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Self-contained page (inline CSS, no scripts) so it opens anywhere and can be shared as is.
+pub fn render_html(review: &YearReview) -> String {
+    let mut body = format!(
+        "<h1>{} in Review</h1>\n<div class=\"totals\"><div><b>{}</b>completed</div><div><b>{}</b>created</div><div><b>{}</b>day streak</div></div>\n",
+        review.year, review.total_completed, review.total_created, review.longest_streak.days
+    );
+
+    let max = review.months.iter().map(|m| m.completed).max().unwrap_or(0);
+    body.push_str("<h2>Month by Month</h2>\n<div class=\"chart\">\n");
+    for month in &review.months {
+        let height = (month.completed * 100).checked_div(max).unwrap_or(0);
+        body.push_str(&format!(
+            "<div class=\"col\" title=\"{} completed\"><div class=\"bar\" style=\"height:{height}%\"></div><span>{}</span></div>\n",
+            month.completed,
+            MONTH_NAMES[month.month as usize - 1]
+        ));
+    }
+    body.push_str("</div>\n");
+
+    let mut list = |title: &str, rows: Vec<(String, usize)>| {
+        body.push_str(&format!("<h2>{title}</h2>\n<ol>\n"));
+        for (label, count) in rows {
+            body.push_str(&format!(
+                "<li>{} <em>{count}</em></li>\n",
+                escape_html(&label)
+            ));
+        }
+        body.push_str("</ol>\n");
+    };
+    list(
+        "Busiest Weeks",
+        review
+            .busiest_weeks
+            .iter()
+            .map(|week| (format!("Week of {}", week.week_start), week.completed))
+            .collect(),
+    );
+    list(
+        "Biggest Projects",
+        review
+            .top_projects
+            .iter()
+            .map(|project| (project.label.clone(), project.count))
+            .collect(),
+    );
+    list(
+        "Most-used Tags",
+        review
+            .top_tags
+            .iter()
+            .map(|tag| (format!("#{}", tag.label), tag.count))
+            .collect(),
+    );
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>MustDo {year} in Review</title>\n<style>\n{STYLE}</style>\n</head>\n<body>\n{body}</body>\n</html>\n",
+        year = review.year
+    )
+}
+
+const STYLE: &str = "body{font-family:system-ui,sans-serif;max-width:720px;margin:40px auto;padding:0 16px;color:#1f2933}
+.totals{display:flex;gap:16px}.totals div{flex:1;padding:16px;border-radius:12px;background:#f1f5f9;text-align:center}
+.totals b{display:block;font-size:32px}
+.chart{display:flex;align-items:flex-end;gap:6px;height:160px}
+.col{flex:1;display:flex;flex-direction:column;justify-content:flex-end;height:100%;text-align:center;font-size:12px}
+.bar{background:#3b82f6;border-radius:4px 4px 0 0;min-height:2px}
+em{color:#64748b;font-style:normal;margin-left:6px}
+";
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+
+    use super::*;
+    use crate::models::{test_support, Timestamp};
+
+    fn at(year: i32, month: u32, day: u32) -> Timestamp {
+        Local
+            .with_ymd_and_hms(year, month, day, 12, 0, 0)
+            .unwrap()
+            .timestamp()
+    }
+
+    fn task(id: &str, project_id: &str, created: Timestamp, done: Option<Timestamp>) -> Task {
+        test_support::task(id)
+            .project(project_id)
+            .created(created)
+            .done_at(done)
+    }
+
+    fn project(id: &str, name: &str) -> Project {
+        serde_json::from_str(&format!(r#"{{"id":"{id}","name":"{name}"}}"#)).unwrap()
+    }
+
+    fn sample() -> Vec<Task> {
+        let mut tasks = vec![
+            task("a", "work", at(2024, 12, 30), Some(at(2025, 1, 5))),
+            task("b", "work", at(2025, 1, 5), Some(at(2025, 1, 6))),
+            task("c", "home", at(2025, 1, 6), Some(at(2025, 1, 7))),
+            task("d", "gone", at(2025, 3, 1), Some(at(2025, 3, 3))),
+            task("e", "work", at(2025, 3, 2), None),
+            task("f", "work", at(2025, 12, 31), Some(at(2026, 1, 1))),
+        ];
+        tasks[0].tags = vec!["deep".to_string()];
+        tasks[1].tags = vec!["deep".to_string(), "quick".to_string()];
+        tasks[5].tags = vec!["next-year".to_string()];
+        tasks
+    }
+
+    #[test]
+    fn review_aggregates_one_calendar_year() {
+        let projects = vec![project("work", "Work"), project("home", "Home")];
+        let review = year_review(&sample(), &projects, 2025, WeekStart::Monday);
+        assert_eq!(review.total_completed, 4);
+        assert_eq!(review.total_created, 5);
+        assert_eq!(review.months.len(), 12);
+        assert_eq!(
+            (review.months[0].completed, review.months[0].created),
+            (3, 2)
+        );
+        assert_eq!(
+            (review.months[2].completed, review.months[2].created),
+            (1, 2)
+        );
+        assert_eq!(review.months[11].created, 1);
+
+        // Jan 5 2025 is a Sunday: with Monday weeks it belongs to the week of Dec 30.
+        let weeks: Vec<(&str, usize)> = review
+            .busiest_weeks
+            .iter()
+            .map(|w| (w.week_start.as_str(), w.completed))
+            .collect();
+        assert_eq!(
+            weeks,
+            [("2025-01-06", 2), ("2024-12-30", 1), ("2025-03-03", 1)]
+        );
+        let sunday = year_review(&sample(), &projects, 2025, WeekStart::Sunday);
+        assert_eq!(sunday.busiest_weeks[0].week_start, "2025-01-05");
+        assert_eq!(sunday.busiest_weeks[0].completed, 3);
+
+        let top: Vec<(&str, usize)> = review
+            .top_projects
+            .iter()
+            .map(|p| (p.label.as_str(), p.count))
+            .collect();
+        assert_eq!(top, [("Work", 2), ("gone", 1), ("Home", 1)]);
+        assert_eq!(review.top_tags[0].key, "deep");
+        assert_eq!(review.top_tags[0].count, 2);
+        assert!(review.top_tags.iter().all(|tag| tag.key != "next-year"));
+
+        assert_eq!(
+            review.longest_streak,
+            Streak {
+                days: 3,
+                start: Some("2025-01-05".to_string()),
+                end: Some("2025-01-07".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn empty_year_has_zeroed_months_and_no_streak() {
+        let review = year_review(&sample(), &[], 2019, WeekStart::Monday);
+        assert_eq!(review.total_completed, 0);
+        assert!(review
+            .months
+            .iter()
+            .all(|m| m.completed == 0 && m.created == 0));
+        assert_eq!(review.longest_streak, Streak::default());
+        let md = render_markdown(&review);
+        assert!(md.contains("_Nothing yet_"));
+        assert!(!md.contains("Longest streak"));
+        assert!(render_html(&review).contains("height:0%"));
+    }
+
+    #[test]
+    fn reports_render_the_review_in_markdown_and_html() {
+        let projects = vec![project("work", "R&D <core>")];
+        let review = year_review(&sample(), &projects, 2025, WeekStart::Monday);
+
+        let md = render_markdown(&review);
+        assert!(md.starts_with("# 2025 in Review\n"));
+        assert!(md.contains("- Completed: **4**"));
+        assert!(md.contains("- Longest streak: **3 days** (2025-01-05 – 2025-01-07)"));
+        assert!(md.contains(&format!("| Jan | 3 | 2 | {} |", "█".repeat(20))));
+        assert!(md.contains("| Feb | 0 | 0 |  |"));
+        assert!(md.contains("1. Week of 2025-01-06 — 2"));
+        assert!(md.contains("1. R&D <core> — 2"));
+        assert!(md.contains("1. #deep — 2"));
+
+        let html = render_html(&review);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>MustDo 2025 in Review</title>"));
+        assert!(html.contains("height:100%"));
+        assert!(html.contains("<li>R&amp;D &lt;core&gt; <em>2</em></li>"));
+        assert!(!html.contains("<script"));
+    }
+}
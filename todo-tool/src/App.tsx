@@ -26,6 +26,7 @@ import "./App.css";
 import type { TaskComposerDraft } from "./components/TaskComposer";
 import { ConfirmDialog } from "./components/ConfirmDialog";
 import { ForcedReminderOverlay } from "./components/ForcedReminderOverlay";
+import { ReminderDigestOverlay } from "./components/ReminderDigestOverlay";
 import { TaskEditModal } from "./components/TaskEditModal";
 import { useToast } from "./components/ToastProvider";
 import { useConfirmDialog } from "./components/useConfirmDialog";
@@ -46,11 +47,16 @@ import {
   deleteTask,
   deleteTasks,
   dismissForced,
+  getRecoveryStatus,
   loadState,
-  snoozeTask,
+  notificationAction,
+  promoteStepToTask,
+  redoLastAction,
+  snoozeTaskPreset,
   showSettingsWindow,
   updateProject,
   updateSettings,
+  undoLastAction,
   updateTask,
 } from "./api";
 import {
@@ -60,20 +66,30 @@ import {
 } from "./date";
 import { I18nProvider, makeTranslator, resolveAppLanguage } from "./i18n";
 import { newTask } from "./logic";
-import { TAURI_NAVIGATE } from "./events";
+import { QUICK_ADD_HASH, TAURI_NAVIGATE } from "./events";
 import { detectPlatform } from "./platform";
 import { buildReminderConfig, getReminderTargetTime } from "./reminder";
-import { computeSnoozeUntilSeconds, type SnoozePresetId } from "./snooze";
+import { playSound } from "./sound";
+import type { SnoozePresetId } from "./snooze";
 import { normalizeTheme } from "./theme";
-import type { Project, Settings, Task } from "./types";
+import type {
+  DailySummary,
+  FocusSession,
+  MarkdownSyncReport,
+  Project,
+  ReminderDigest,
+  Settings,
+  StateDelta,
+  StatePayload,
+  Task,
+  WriteStatus,
+} from "./types";
 import { TodayView } from "./views/TodayView";
 
 const NOTIFICATION_ACTION_TYPE = "todo-reminder";
-const NOTIFICATION_ACTION_SNOOZE_5 = "snooze5";
-const NOTIFICATION_ACTION_SNOOZE_15 = "snooze15";
-const NOTIFICATION_ACTION_SNOOZE_1H = "snooze1h";
-const NOTIFICATION_ACTION_SNOOZE_TOMORROW = "snoozeTomorrow";
+// Action ids are the backend's `NotificationAction` wire names.
 const NOTIFICATION_ACTION_COMPLETE = "complete";
+const NOTIFICATION_ACTION_SNOOZE_10M = "snooze_10m";
 
 function normalizeTask(task: Task) {
   const sort_order = task.sort_order || task.created_at * 1000;
@@ -99,10 +115,10 @@ function normalizeSettings(settings: Settings): Settings {
     ? settings.today_focus_ids.filter((id) => typeof id === "string")
     : [];
   const ai_enabled = Boolean(settings.ai_enabled);
-  const deepseek_api_key =
-    typeof settings.deepseek_api_key === "string"
-      ? settings.deepseek_api_key
-      : "";
+  const ai_api_key_set = Boolean(settings.ai_api_key_set);
+  const ai_provider = settings.ai_provider ?? "deepseek";
+  const ai_base_url =
+    typeof settings.ai_base_url === "string" ? settings.ai_base_url : "";
   const ai_model =
     typeof settings.ai_model === "string" && settings.ai_model.trim()
       ? settings.ai_model.trim()
@@ -137,7 +153,9 @@ function normalizeSettings(settings: Settings): Settings {
     ...settings,
     today_focus_ids,
     ai_enabled,
-    deepseek_api_key,
+    ai_api_key_set,
+    ai_provider,
+    ai_base_url,
     ai_model,
     ai_prompt,
     update_behavior,
@@ -178,6 +196,25 @@ function reconcileById<T extends { id: string }>(
   });
 }
 
+// Applies a delta: replaces or appends the upserted items and drops the removed
+// ids, keeping everything else (and its reference) as it was.
+function patchById<T extends { id: string }>(
+  prev: T[],
+  upserted: T[],
+  removed: string[],
+): T[] {
+  const removedIds = new Set(removed);
+  const pending = new Map(upserted.map((item) => [item.id, item]));
+  const next: T[] = [];
+  for (const item of prev) {
+    if (removedIds.has(item.id)) continue;
+    const replacement = pending.get(item.id);
+    pending.delete(item.id);
+    next.push(replacement ?? item);
+  }
+  return [...next, ...pending.values()];
+}
+
 function mergeUniqueIds(existing: string[], incoming: string[]) {
   if (incoming.length === 0) return existing;
   const next = [...existing];
@@ -191,24 +228,14 @@ function mergeUniqueIds(existing: string[], incoming: string[]) {
   return next;
 }
 
-function playBeep() {
-  try {
-    const context = new AudioContext();
-    const oscillator = context.createOscillator();
-    const gain = context.createGain();
-    oscillator.type = "sine";
-    oscillator.frequency.value = 880;
-    gain.gain.value = 0.15;
-    oscillator.connect(gain);
-    gain.connect(context.destination);
-    oscillator.start();
-    oscillator.stop(context.currentTime + 0.3);
-    oscillator.onended = () => {
-      context.close();
-    };
-  } catch {
-    // Ignore audio errors in restricted environments.
-  }
+// The sound of the first forced task's project; normal reminders keep the default beep.
+function reminderSoundId(tasks: Task[], projects: Project[]): string | null {
+  const forced = tasks.find((task) => task.reminder.kind === "forced");
+  if (!forced) return null;
+  return (
+    projects.find((project) => project.id === forced.project_id)
+      ?.notification_sound ?? null
+  );
 }
 
 function isActiveForcedReminder(task: Task, nowSeconds: number): boolean {
@@ -265,6 +292,7 @@ function App() {
   const [confirmDeleteBusy, setConfirmDeleteBusy] = useState(false);
 
   const [forcedQueueIds, setForcedQueueIds] = useState<string[]>([]);
+  const [digestIds, setDigestIds] = useState<string[]>([]);
   const [normalQueueIds, setNormalQueueIds] = useState<string[]>([]);
 
   const [pendingUpdate, setPendingUpdate] = useState<Update | null>(null);
@@ -294,10 +322,13 @@ function App() {
 
   // Keep a mutable pointer for async callbacks so we can read latest settings without stale closures.
   const settingsRef = useRef<Settings | null>(null);
+  const tasksRef = useRef<Task[]>([]);
+  const projectsRef = useRef<Project[]>([]);
   const startupUpdateCheckStartedRef = useRef(false);
   const updateBusyRef = useRef(false);
   const aiKeyMissingWarnedRef = useRef(false);
   const aiPromptPlaceholderWarnedRef = useRef(false);
+  const recoveryPromptShownRef = useRef(false);
 
   useEffect(() => {
     const onHash = () => {
@@ -308,6 +339,270 @@ function App() {
     return () => window.removeEventListener("hashchange", onHash);
   }, []);
 
+  // Edits picked up from the synced Markdown export (settings.markdown_sync_enabled).
+  useEffect(() => {
+    if (getCurrentWindow().label !== "main") return;
+    let disposed = false;
+    let unlisten: (() => void) | null = null;
+
+    void (async () => {
+      const listener = await listen<MarkdownSyncReport>(
+        "markdown_sync",
+        ({ payload }) => {
+          if (!payload) return;
+          const completed = payload.completed?.length ?? 0;
+          const conflicts = payload.conflicts?.length ?? 0;
+          if (completed > 0) {
+            toast.notify(t("markdownSync.completed", { count: completed }), {
+              tone: "success",
+            });
+          }
+          if (conflicts > 0) {
+            toast.notify(t("markdownSync.conflicts", { count: conflicts }), {
+              tone: "danger",
+              durationMs: 6000,
+            });
+          }
+        },
+      );
+      if (disposed) {
+        listener();
+        return;
+      }
+      unlisten = listener;
+    })().catch((err) => {
+      void frontendLog(
+        "error",
+        "frontend: failed to register markdown_sync listener",
+        {
+          window: getCurrentWindow().label,
+          err: describeError(err),
+        },
+      );
+    });
+
+    return () => {
+      disposed = true;
+      if (unlisten) unlisten();
+    };
+  }, [t, toast]);
+
+  // A focus session ran its full length (stopped sessions do not send the event).
+  useEffect(() => {
+    if (getCurrentWindow().label !== "main") return;
+    let disposed = false;
+    let unlisten: (() => void) | null = null;
+
+    void (async () => {
+      const listener = await listen<FocusSession>(
+        "focus_finished",
+        ({ payload }) => {
+          if (!payload) return;
+          const task = tasksRef.current.find(
+            (item) => item.id === payload.task_id,
+          );
+          toast.notify(
+            t("focus.finished", {
+              title: task?.title ?? payload.task_id,
+              minutes: payload.minutes,
+            }),
+            { tone: "success", durationMs: 6000 },
+          );
+        },
+      );
+      if (disposed) {
+        listener();
+        return;
+      }
+      unlisten = listener;
+    })().catch((err) => {
+      void frontendLog(
+        "error",
+        "frontend: failed to register focus_finished listener",
+        {
+          window: getCurrentWindow().label,
+          err: describeError(err),
+        },
+      );
+    });
+
+    return () => {
+      disposed = true;
+      if (unlisten) unlisten();
+    };
+  }, [t, toast]);
+
+  // Tasks whose start date was reached now show up in the today/week views.
+  useEffect(() => {
+    if (getCurrentWindow().label !== "main") return;
+    let disposed = false;
+    let unlisten: (() => void) | null = null;
+
+    void (async () => {
+      const listener = await listen<Task[]>("task_started", ({ payload }) => {
+        if (!payload || payload.length === 0) return;
+        toast.notify(
+          payload.length === 1
+            ? t("task.started", { title: payload[0].title })
+            : t("task.startedMany", { count: payload.length }),
+          { tone: "default", durationMs: 6000 },
+        );
+      });
+      if (disposed) {
+        listener();
+        return;
+      }
+      unlisten = listener;
+    })().catch((err) => {
+      void frontendLog("error", "frontend: failed to register task_started listener", {
+        window: getCurrentWindow().label,
+        err: describeError(err),
+      });
+    });
+
+    return () => {
+      disposed = true;
+      if (unlisten) unlisten();
+    };
+  }, [t, toast]);
+
+  // End-of-day counts (settings.daily_summary_enabled): a system notification plus a toast.
+  useEffect(() => {
+    if (getCurrentWindow().label !== "main") return;
+    let disposed = false;
+    let unlisten: (() => void) | null = null;
+
+    void (async () => {
+      const listener = await listen<DailySummary>(
+        "daily_summary",
+        ({ payload }) => {
+          if (!payload) return;
+          const body = t("dailySummary.body", {
+            completed: payload.completed,
+            overdue: payload.overdue,
+            remaining: payload.remaining,
+          });
+          toast.notify(`${t("dailySummary.title")}: ${body}`, {
+            tone: payload.overdue > 0 ? "danger" : "success",
+            durationMs: 8000,
+          });
+          void (async () => {
+            let granted = false;
+            try {
+              granted = await isPermissionGranted();
+            } catch {
+              granted = true;
+            }
+            if (!granted) return;
+            await Promise.resolve(
+              sendNotification({ title: t("dailySummary.title"), body }),
+            );
+          })().catch(() => {});
+        },
+      );
+      if (disposed) {
+        listener();
+        return;
+      }
+      unlisten = listener;
+    })().catch((err) => {
+      void frontendLog(
+        "error",
+        "frontend: failed to register daily_summary listener",
+        {
+          window: getCurrentWindow().label,
+          err: describeError(err),
+        },
+      );
+    });
+
+    return () => {
+      disposed = true;
+      if (unlisten) unlisten();
+    };
+  }, [t, toast]);
+
+  // Background saves (write-behind) failing or working again: edits may exist only in memory.
+  useEffect(() => {
+    if (getCurrentWindow().label !== "main") return;
+    let disposed = false;
+    let unlisten: (() => void) | null = null;
+
+    void (async () => {
+      const listener = await listen<WriteStatus>(
+        "write_status",
+        ({ payload }) => {
+          if (!payload) return;
+          if (payload.last_error) {
+            toast.notify(
+              t("storage.writeFailed", { error: payload.last_error }),
+              { tone: "danger", durationMs: 10000 },
+            );
+            return;
+          }
+          toast.notify(t("storage.writeRecovered"), { tone: "success" });
+        },
+      );
+      if (disposed) {
+        listener();
+        return;
+      }
+      unlisten = listener;
+    })().catch((err) => {
+      void frontendLog(
+        "error",
+        "frontend: failed to register write_status listener",
+        {
+          window: getCurrentWindow().label,
+          err: describeError(err),
+        },
+      );
+    });
+
+    return () => {
+      disposed = true;
+      if (unlisten) unlisten();
+    };
+  }, [t, toast]);
+
+  // data.json was unreadable at startup: explain what the backend recovered and offer the
+  // backup list in Settings so the user can pick another restore point.
+  useEffect(() => {
+    if (getCurrentWindow().label !== "main") return;
+    if (recoveryPromptShownRef.current) return;
+    recoveryPromptShownRef.current = true;
+
+    void (async () => {
+      const res = await getRecoveryStatus();
+      const status = res.ok ? res.data : null;
+      if (!status) return;
+      void frontendLog("warn", "frontend: data recovered at startup", {
+        quarantined_as: status.quarantined_as ?? "",
+        recovered_from: status.recovered_from ?? "",
+      });
+      const description = [
+        status.recovered_from
+          ? t("recovery.restored", { backup: status.recovered_from })
+          : t("recovery.empty"),
+        status.quarantined_as
+          ? t("recovery.quarantined", { file: status.quarantined_as })
+          : t("recovery.quarantineFailed"),
+      ].join(" ");
+      const openBackups = await requestConfirm({
+        title: t("recovery.title"),
+        description,
+        confirmText: t("recovery.openBackups"),
+        cancelText: t("recovery.keep"),
+        tone: "danger",
+      });
+      if (openBackups) await handleOpenSettingsWindow();
+    })().catch((err) => {
+      void frontendLog("error", "frontend: get_recovery_status failed", {
+        err: describeError(err),
+      });
+    });
+  }, []);
+
   // Backend-triggered navigation (tray menu, etc.). Keep it scoped to the main window.
   useEffect(() => {
     if (getCurrentWindow().label !== "main") return;
@@ -322,7 +617,8 @@ function App() {
         TAURI_NAVIGATE,
         ({ payload }) => {
           const next = typeof payload?.hash === "string" ? payload.hash : "";
-          if (!next) return;
+          // Quick window requests are broadcast too; they are not main routes.
+          if (!next || next === QUICK_ADD_HASH) return;
           window.location.hash = next;
         },
       );
@@ -349,6 +645,46 @@ function App() {
     };
   }, []);
 
+  // Quick-add global shortcut: the backend shows the quick window, we focus its add input.
+  useEffect(() => {
+    if (getCurrentWindow().label !== "quick") return;
+    let disposed = false;
+    let unlisten: (() => void) | null = null;
+
+    void (async () => {
+      const listener = await listen<{ hash: string }>(
+        TAURI_NAVIGATE,
+        ({ payload }) => {
+          if (payload?.hash !== QUICK_ADD_HASH) return;
+          window.requestAnimationFrame(() => {
+            document
+              .querySelector<HTMLInputElement>(".composer-input")
+              ?.focus();
+          });
+        },
+      );
+
+      if (disposed) {
+        listener();
+        return;
+      }
+      unlisten = listener;
+    })().catch((err) => {
+      void frontendLog(
+        "error",
+        "frontend: failed to register quick add listener",
+        {
+          err: describeError(err),
+        },
+      );
+    });
+
+    return () => {
+      disposed = true;
+      if (unlisten) unlisten();
+    };
+  }, []);
+
   // Check for app updates once per launch. Keep it scoped to the main window to avoid multi-window work.
   //
   // NOTE: We delay the actual check until after settings are loaded (so we can honor update_behavior),
@@ -470,9 +806,11 @@ function App() {
     };
   }, [settings, toast, t]);
 
-  // Notification actions (OS-level buttons) should only be handled by the quick window.
+  // Buttons on the native reminder notification. The main window sends those notifications,
+  // so it also takes the actions; the backend completes or snoozes the task without any window
+  // coming forward.
   useEffect(() => {
-    if (getViewFromHash() !== "quick") return;
+    if (getViewFromHash() !== "main") return;
     let disposed = false;
     let actionListener: PluginListener | null = null;
     const windowLabel = getCurrentWindow().label;
@@ -486,20 +824,14 @@ function App() {
           {
             id: NOTIFICATION_ACTION_TYPE,
             actions: [
-              { id: NOTIFICATION_ACTION_SNOOZE_5, title: t("banner.snooze5") },
               {
-                id: NOTIFICATION_ACTION_SNOOZE_15,
-                title: t("banner.snooze15"),
+                id: NOTIFICATION_ACTION_COMPLETE,
+                title: t("notification.action.done"),
               },
               {
-                id: NOTIFICATION_ACTION_SNOOZE_1H,
-                title: t("banner.snooze1h"),
+                id: NOTIFICATION_ACTION_SNOOZE_10M,
+                title: t("notification.action.snooze10m"),
               },
-              {
-                id: NOTIFICATION_ACTION_SNOOZE_TOMORROW,
-                title: t("banner.snoozeTomorrowMorning"),
-              },
-              { id: NOTIFICATION_ACTION_COMPLETE, title: t("banner.complete") },
             ],
           },
         ]);
@@ -522,47 +854,24 @@ function App() {
           typeof payload.extra?.taskId === "string"
             ? payload.extra.taskId
             : null;
-
-        if (taskId) {
-          const snoozePreset: SnoozePresetId | null =
-            actionId === NOTIFICATION_ACTION_SNOOZE_5
-              ? "m5"
-              : actionId === NOTIFICATION_ACTION_SNOOZE_15
-                ? "m15"
-                : actionId === NOTIFICATION_ACTION_SNOOZE_1H
-                  ? "h1"
-                  : actionId === NOTIFICATION_ACTION_SNOOZE_TOMORROW
-                    ? "tomorrow0900"
-                    : null;
-
-          if (snoozePreset) {
-            const until = computeSnoozeUntilSeconds(snoozePreset);
-            await snoozeTask(taskId, until);
-            setNormalQueueIds((prev) => prev.filter((id) => id !== taskId));
-          } else if (actionId === NOTIFICATION_ACTION_COMPLETE) {
-            await completeTask(taskId);
-            setNormalQueueIds((prev) => prev.filter((id) => id !== taskId));
-          }
+        if (
+          !taskId ||
+          (actionId !== NOTIFICATION_ACTION_COMPLETE &&
+            actionId !== NOTIFICATION_ACTION_SNOOZE_10M)
+        ) {
+          return;
         }
 
-        // Bring the quick window forward so the user sees the updated list immediately.
-        const window = getCurrentWindow();
-        void window.show().catch((err) => {
-          void frontendLog("warn", "window.show failed (notification action)", {
+        const result = await notificationAction(taskId, actionId);
+        if (result.ok) {
+          setNormalQueueIds((prev) => prev.filter((id) => id !== taskId));
+        } else {
+          void frontendLog("warn", "notification action failed", {
             window: windowLabel,
-            err: describeError(err),
+            actionId,
+            err: result.error,
           });
-        });
-        void window.setFocus().catch((err) => {
-          void frontendLog(
-            "warn",
-            "window.setFocus failed (notification action)",
-            {
-              window: windowLabel,
-              err: describeError(err),
-            },
-          );
-        });
+        }
       });
 
       if (disposed) {
@@ -590,23 +899,17 @@ function App() {
     let disposed = false;
     let unlistenState: (() => void) | null = null;
     let unlistenReminder: (() => void) | null = null;
-
-    void (async () => {
-      const windowLabel = getCurrentWindow().label;
-      void frontendLog("info", "frontend: load_state begin", {
-        window: windowLabel,
-        hash: window.location.hash,
-      });
-
-      const res = await loadState();
-      if (res.ok && res.data) {
-        void frontendLog("info", "frontend: load_state ok", {
-          window: windowLabel,
-          tasks: res.data.tasks?.length ?? 0,
-          projects: res.data.projects?.length ?? 0,
-        });
+    let unlistenDigest: (() => void) | null = null;
+    let unlistenDelta: (() => void) | null = null;
+
+    // Full payloads (load_state, state_updated) replace everything and tell
+    // which delta they already include.
+    let lastSeq = 0;
+    const applyState = (payload: Partial<StatePayload>) => {
+      if (typeof payload.seq === "number") lastSeq = payload.seq;
+      if (payload.tasks) {
         setTasks((prev) => {
-          const raw = res.data?.tasks ?? [];
+          const raw = payload.tasks ?? [];
           if (prev.length === raw.length) {
             let same = true;
             for (let i = 0; i < raw.length; i += 1) {
@@ -628,8 +931,10 @@ function App() {
             (a, b) => a.updated_at === b.updated_at,
           );
         });
+      }
+      if (payload.projects) {
         setProjects((prev) => {
-          const raw = res.data?.projects ?? [];
+          const raw = payload.projects ?? [];
           if (prev.length === raw.length) {
             let same = true;
             for (let i = 0; i < raw.length; i += 1) {
@@ -651,7 +956,46 @@ function App() {
             (a, b) => a.updated_at === b.updated_at,
           );
         });
-        setSettings(normalizeSettings(res.data.settings));
+      }
+      if (payload.settings) {
+        setSettings(normalizeSettings(payload.settings));
+      }
+    };
+    const applyDelta = (delta: StateDelta) => {
+      if (delta.tasks_upserted.length > 0 || delta.tasks_removed.length > 0) {
+        const upserted = delta.tasks_upserted.map(normalizeTask);
+        setTasks((prev) => patchById(prev, upserted, delta.tasks_removed));
+      }
+      if (delta.projects_changed) {
+        const nextProjects = delta.projects_changed.map(normalizeProject);
+        setProjects((prev) =>
+          reconcileById(
+            prev,
+            nextProjects,
+            (a, b) => a.updated_at === b.updated_at,
+          ),
+        );
+      }
+      if (delta.settings_changed) {
+        setSettings(normalizeSettings(delta.settings_changed));
+      }
+    };
+
+    void (async () => {
+      const windowLabel = getCurrentWindow().label;
+      void frontendLog("info", "frontend: load_state begin", {
+        window: windowLabel,
+        hash: window.location.hash,
+      });
+
+      const res = await loadState();
+      if (res.ok && res.data) {
+        void frontendLog("info", "frontend: load_state ok", {
+          window: windowLabel,
+          tasks: res.data.tasks?.length ?? 0,
+          projects: res.data.projects?.length ?? 0,
+        });
+        applyState(res.data);
       } else {
         void frontendLog("warn", "frontend: load_state failed", {
           window: windowLabel,
@@ -660,64 +1004,7 @@ function App() {
       }
 
       const stateListener = await listen("state_updated", (event) => {
-        const payload = event.payload as {
-          tasks?: Task[];
-          projects?: Project[];
-          settings?: Settings;
-        };
-        if (payload.tasks) {
-          setTasks((prev) => {
-            const raw = payload.tasks ?? [];
-            if (prev.length === raw.length) {
-              let same = true;
-              for (let i = 0; i < raw.length; i += 1) {
-                if (
-                  prev[i]?.id !== raw[i]?.id ||
-                  prev[i]?.updated_at !== raw[i]?.updated_at
-                ) {
-                  same = false;
-                  break;
-                }
-              }
-              if (same) return prev;
-            }
-
-            const nextTasks = raw.map(normalizeTask);
-            return reconcileById(
-              prev,
-              nextTasks,
-              (a, b) => a.updated_at === b.updated_at,
-            );
-          });
-        }
-        if (payload.projects) {
-          setProjects((prev) => {
-            const raw = payload.projects ?? [];
-            if (prev.length === raw.length) {
-              let same = true;
-              for (let i = 0; i < raw.length; i += 1) {
-                if (
-                  prev[i]?.id !== raw[i]?.id ||
-                  prev[i]?.updated_at !== raw[i]?.updated_at
-                ) {
-                  same = false;
-                  break;
-                }
-              }
-              if (same) return prev;
-            }
-
-            const nextProjects = raw.map(normalizeProject);
-            return reconcileById(
-              prev,
-              nextProjects,
-              (a, b) => a.updated_at === b.updated_at,
-            );
-          });
-        }
-        if (payload.settings) {
-          setSettings(normalizeSettings(payload.settings));
-        }
+        applyState(event.payload as Partial<StatePayload>);
       });
       if (disposed) {
         stateListener();
@@ -728,16 +1015,41 @@ function App() {
         window: windowLabel,
       });
 
+      const deltaListener = await listen("state_delta", (event) => {
+        const delta = event.payload as StateDelta;
+        // Already part of the last full payload.
+        if (delta.seq <= lastSeq) return;
+        if (delta.seq === lastSeq + 1) {
+          lastSeq = delta.seq;
+          applyDelta(delta);
+          return;
+        }
+        // A delta went missing: start over from the full state.
+        void frontendLog("warn", "frontend: state_delta gap, reloading", {
+          window: windowLabel,
+          expected: lastSeq + 1,
+          got: delta.seq,
+        });
+        void loadState().then((reload) => {
+          if (reload.ok && reload.data) applyState(reload.data);
+        });
+      });
+      if (disposed) {
+        deltaListener();
+        return;
+      }
+      unlistenDelta = deltaListener;
+
       const reminderListener = await listen("reminder_fired", async (event) => {
         const payload = event.payload as Task[];
         if (!Array.isArray(payload) || payload.length === 0) return;
 
-        // Beep only from the quick window instance to avoid duplicate sounds.
+        // Play only from the quick window instance to avoid duplicate sounds.
         if (
           settingsRef.current?.sound_enabled &&
           getViewFromHash() === "quick"
         ) {
-          playBeep();
+          void playSound(reminderSoundId(payload, projectsRef.current));
         }
 
         const forced = payload.filter(
@@ -803,6 +1115,67 @@ function App() {
       void frontendLog("info", "frontend: reminder_fired listener ready", {
         window: windowLabel,
       });
+
+      // Bursts at or above `reminder_digest_threshold` arrive as one digest: a single overlay
+      // (when any task is forced) and a single system notification instead of one per task.
+      const digestListener = await listen<ReminderDigest>(
+        "reminder_digest",
+        async (event) => {
+          const digest = event.payload;
+          if (!digest || !Array.isArray(digest.tasks)) return;
+          if (digest.tasks.length === 0) return;
+
+          if (
+            settingsRef.current?.sound_enabled &&
+            getViewFromHash() === "quick"
+          ) {
+            void playSound(reminderSoundId(digest.tasks, projectsRef.current));
+          }
+
+          if (digest.forced) {
+            setDigestIds((prev) =>
+              mergeUniqueIds(
+                prev,
+                digest.tasks.map((task) => task.id),
+              ),
+            );
+          } else {
+            setNormalQueueIds((prev) =>
+              mergeUniqueIds(
+                prev,
+                digest.tasks.map((task) => task.id),
+              ),
+            );
+          }
+
+          if (getViewFromHash() === "main") {
+            let granted = false;
+            try {
+              granted = await isPermissionGranted();
+            } catch {
+              granted = true;
+            }
+            if (granted) {
+              void Promise.resolve(
+                sendNotification({
+                  title: t("digest.notificationTitle", {
+                    count: digest.tasks.length,
+                  }),
+                  body: digest.tasks.map((task) => task.title).join("\n"),
+                  silent: settingsRef.current
+                    ? !settingsRef.current.sound_enabled
+                    : false,
+                }),
+              ).catch(() => {});
+            }
+          }
+        },
+      );
+      if (disposed) {
+        digestListener();
+        return;
+      }
+      unlistenDigest = digestListener;
     })().catch((err) => {
       void frontendLog("error", "frontend: initial state subscription failed", {
         window: getCurrentWindow().label,
@@ -814,9 +1187,19 @@ function App() {
       disposed = true;
       if (unlistenState) unlistenState();
       if (unlistenReminder) unlistenReminder();
+      if (unlistenDigest) unlistenDigest();
+      if (unlistenDelta) unlistenDelta();
     };
   }, [t]);
 
+  useEffect(() => {
+    tasksRef.current = tasks;
+  }, [tasks]);
+
+  useEffect(() => {
+    projectsRef.current = projects;
+  }, [projects]);
+
   useEffect(() => {
     settingsRef.current = settings;
     if (!settings) return;
@@ -884,6 +1267,37 @@ function App() {
     return () => window.removeEventListener("keydown", onKeyDown);
   }, [confirmDeleteTaskId, editingTaskId, view]);
 
+  // Mod+Z / Mod+Shift+Z undo and redo the last delete or bulk complete. Text fields keep their
+  // native undo; an empty history is not worth a toast.
+  useEffect(() => {
+    const onKeyDown = (event: KeyboardEvent) => {
+      if (event.defaultPrevented) return;
+      if (!(event.ctrlKey || event.metaKey)) return;
+      if (event.key.toLowerCase() !== "z") return;
+      const target = event.target as HTMLElement | null;
+      if (target?.closest("input, textarea, select, [contenteditable='true']")) {
+        return;
+      }
+      event.preventDefault();
+      const redo = event.shiftKey;
+      void (redo ? redoLastAction() : undoLastAction()).then((result) => {
+        if (!result.ok || !result.data) {
+          if (result.error && !result.error.startsWith("nothing to")) {
+            toast.notify(result.error, { tone: "danger", durationMs: 6000 });
+          }
+          return;
+        }
+        const action = t(`undo.action.${result.data.action}`);
+        toast.notify(t(redo ? "undo.redone" : "undo.undone", { action }), {
+          tone: "success",
+        });
+      });
+    };
+
+    window.addEventListener("keydown", onKeyDown);
+    return () => window.removeEventListener("keydown", onKeyDown);
+  }, [t, toast]);
+
   // Keep reminder queues in sync with current tasks (completed/dismissed tasks should drop out).
   useEffect(() => {
     const nowSeconds = Math.floor(Date.now() / 1000);
@@ -907,6 +1321,18 @@ function App() {
         return task && !task.completed && task.reminder.kind === "normal";
       }),
     );
+    setDigestIds((prev) =>
+      prev.filter((id) => {
+        const task = tasks.find((item) => item.id === id);
+        if (!task || task.completed || task.reminder.kind === "none") {
+          return false;
+        }
+        if (task.reminder.kind === "forced") {
+          return isActiveForcedReminder(task, nowSeconds);
+        }
+        return (task.reminder.snoozed_until ?? 0) <= nowSeconds;
+      }),
+    );
   }, [tasks]);
 
   // Fallback: the reminder window can be created after `reminder_fired` is emitted, so it may
@@ -918,6 +1344,11 @@ function App() {
       .filter((task) => isActiveForcedReminder(task, nowSeconds))
       .map((task) => task.id);
     if (forcedIds.length === 0) return;
+    const threshold = settingsRef.current?.reminder_digest_threshold ?? 0;
+    if (threshold > 0 && forcedIds.length >= threshold) {
+      setDigestIds((prev) => mergeUniqueIds(prev, forcedIds));
+      return;
+    }
     setForcedQueueIds((prev) => mergeUniqueIds(prev, forcedIds));
   }, [tasks, view]);
 
@@ -933,8 +1364,18 @@ function App() {
       .filter((task): task is Task => Boolean(task));
   }, [normalQueueIds, tasks]);
 
-  const reminderTask = forcedTasks[0] ?? null;
-  const reminderQueueTotal = forcedTasks.length;
+  const digestTasks = useMemo(() => {
+    return digestIds
+      .map((id) => tasks.find((task) => task.id === id))
+      .filter((task): task is Task => Boolean(task));
+  }, [digestIds, tasks]);
+
+  // The digest takes over the overlay; forced tasks it covers are not queued twice.
+  const queuedForcedTasks = forcedTasks.filter(
+    (task) => !digestIds.includes(task.id),
+  );
+  const reminderTask = queuedForcedTasks[0] ?? null;
+  const reminderQueueTotal = queuedForcedTasks.length;
   const reminderQueueIndex = reminderTask ? 1 : 0;
 
   const deleteCandidate = useMemo(() => {
@@ -963,12 +1404,12 @@ function App() {
 
   // Reminder window: if the queue is empty, hide it.
   useEffect(() => {
-    if (view === "reminder" && !reminderTask) {
+    if (view === "reminder" && !reminderTask && digestTasks.length === 0) {
       void getCurrentWindow()
         .hide()
         .catch(() => {});
     }
-  }, [view, reminderTask]);
+  }, [view, reminderTask, digestTasks.length]);
 
   // Reminder window: full-screen transparent overlay.
   useEffect(() => {
@@ -1127,18 +1568,25 @@ function App() {
     task.important = draft.important;
     task.tags = draft.tags;
     task.repeat = draft.repeat;
+    task.notes = draft.notes ?? undefined;
+    task.steps = draft.steps.map((title, index) => ({
+      id: crypto.randomUUID(),
+      title,
+      completed: false,
+      created_at: nowSec + index,
+    }));
     task.updated_at = nowSec;
 
     const aiSettings = settingsRef.current;
     const aiEnabled = Boolean(aiSettings?.ai_enabled);
-    const apiKey = aiSettings?.deepseek_api_key?.trim?.() ?? "";
+    const keyStored = Boolean(aiSettings?.ai_api_key_set);
     const model = aiSettings?.ai_model?.trim?.() ?? "";
-    const aiReady = aiEnabled && Boolean(apiKey) && Boolean(model);
+    const aiReady = aiEnabled && keyStored && Boolean(model);
     if (aiEnabled && !aiReady) {
       // Don't block task creation if AI is enabled but not configured yet.
       if (!aiKeyMissingWarnedRef.current) {
         aiKeyMissingWarnedRef.current = true;
-        const message = !apiKey
+        const message = !keyStored
           ? t("settings.ai.keyMissingFallback")
           : t("settings.ai.modelMissingFallback");
         toast.notify(message, {
@@ -1220,7 +1668,7 @@ function App() {
       }
 
       const aiNotes = typeof ai.notes === "string" ? ai.notes.trim() : "";
-      task.notes = aiNotes ? aiNotes : undefined;
+      if (aiNotes) task.notes = aiNotes;
 
       // Merge tags: keep user-entered hashtags, then append AI-suggested tags.
       const aiTags = Array.isArray(ai.tags) ? ai.tags : [];
@@ -1284,8 +1732,8 @@ function App() {
           const parsed = aiRemindAt
             ? parseLocalDateTimeString(aiRemindAt)
             : null;
-          const defaultTarget =
-            kind === "normal" ? task.due_at - 10 * 60 : task.due_at;
+          const dueAt = task.due_at ?? nowSec;
+          const defaultTarget = kind === "normal" ? dueAt - 10 * 60 : dueAt;
           const remindAt = Math.max(parsed ?? defaultTarget, nowSec);
           task.reminder = {
             kind,
@@ -1302,7 +1750,7 @@ function App() {
     if (draft.reminder_kind !== "none") {
       task.reminder = buildReminderConfig(
         draft.reminder_kind,
-        task.due_at,
+        task.due_at ?? nowSec,
         draft.reminder_offset_minutes,
         nowSec,
       );
@@ -1393,6 +1841,18 @@ function App() {
     await updateTask(next);
   }
 
+  async function handlePromoteStep(taskId: string, stepId: string): Promise<boolean> {
+    const result = await promoteStepToTask(taskId, stepId);
+    if (!result.ok) {
+      toast.notify(result.error ?? t("alert.operationFailed"), {
+        tone: "danger",
+        durationMs: 6000,
+      });
+      return false;
+    }
+    return true;
+  }
+
   async function handleBulkUpdate(nextTasks: Task[]): Promise<boolean> {
     if (nextTasks.length === 0) return true;
     const result = await bulkUpdateTasks(nextTasks);
@@ -1442,8 +1902,6 @@ function App() {
   async function handleReminderSnooze(preset: SnoozePresetId) {
     if (!reminderTask) return;
     const taskId = reminderTask.id;
-    const until = computeSnoozeUntilSeconds(preset);
-
     // Update UI first so the overlay reacts instantly (show next reminder or hide if none).
     setForcedQueueIds((prev) => {
       const next = prev.filter((id) => id !== taskId);
@@ -1454,7 +1912,7 @@ function App() {
       return next;
     });
 
-    await snoozeTask(taskId, until);
+    await snoozeTaskPreset(taskId, preset);
   }
 
   async function handleReminderDismiss() {
@@ -1489,9 +1947,28 @@ function App() {
     await completeTask(taskId);
   }
 
+  async function handleDigestSnooze(task: Task, preset: SnoozePresetId) {
+    setDigestIds((prev) => prev.filter((id) => id !== task.id));
+    await snoozeTaskPreset(task.id, preset);
+  }
+
+  async function handleDigestComplete(task: Task) {
+    setDigestIds((prev) => prev.filter((id) => id !== task.id));
+    await completeTask(task.id);
+  }
+
+  async function handleDigestDismissAll() {
+    const remaining = digestTasks;
+    setDigestIds([]);
+    await Promise.all(
+      remaining
+        .filter((task) => task.reminder.kind === "forced")
+        .map((task) => dismissForced(task.id)),
+    );
+  }
+
   async function handleNormalSnooze(task: Task, preset: SnoozePresetId) {
-    const until = computeSnoozeUntilSeconds(preset);
-    await snoozeTask(task.id, until);
+    await snoozeTaskPreset(task.id, preset);
     setNormalQueueIds((prev) => prev.filter((id) => id !== task.id));
   }
 
@@ -1606,7 +2083,7 @@ function App() {
     const parts = path.split("/").filter(Boolean);
 
     // Supported routes:
-    // - main window: #/main, #/main/today, #/main/calendar
+    // - main window: #/main, #/main/today, #/main/calendar, #/main/project/<id> (tray menu)
     // - settings window: #/settings
     // - legacy: #/main/settings (main window will redirect to settings window)
     if (parts[0] === "settings") return "settings";
@@ -1618,6 +2095,12 @@ function App() {
     return "home";
   })();
 
+  const routeProjectId = (() => {
+    const parts = locationHash.replace("#", "").split("/").filter(Boolean);
+    if (parts[0] !== "main" || parts[1] !== "project" || !parts[2]) return null;
+    return decodeURIComponent(parts[2]);
+  })();
+
   useEffect(() => {
     // When someone navigates the main window to the settings route (e.g. legacy link),
     // try opening the dedicated settings window. If that fails, keep the in-window settings
@@ -1821,10 +2304,21 @@ function App() {
             onOpenCalendar={handleOpenCalendar}
             onNormalSnooze={handleNormalSnooze}
             onNormalComplete={handleNormalComplete}
+            routeProjectId={routeProjectId}
+          />
+        )}
+
+        {view === "reminder" && settings && digestTasks.length > 0 && (
+          <ReminderDigestOverlay
+            tasks={digestTasks}
+            color={settings.forced_reminder_color}
+            onSnooze={handleDigestSnooze}
+            onComplete={handleDigestComplete}
+            onDismissAll={handleDigestDismissAll}
           />
         )}
 
-        {view === "reminder" && settings && (
+        {view === "reminder" && settings && digestTasks.length === 0 && (
           <ForcedReminderOverlay
             task={reminderTask}
             color={settings.forced_reminder_color}
@@ -1841,6 +2335,7 @@ function App() {
             task={editingTask}
             showNotes={view === "main"}
             onSave={handleUpdateTask}
+            onPromoteStep={(stepId) => handlePromoteStep(editingTask.id, stepId)}
             onClose={() => setEditingTaskId(null)}
           />
         )}
@@ -1,17 +1,76 @@
 import { invoke } from "@tauri-apps/api/core";
 import type {
+  ActivityEntry,
+  ArchiveEstimate,
+  Attachment,
+  AutomationRule,
+  Burndown,
+  BurndownRange,
+  CarryOverEntry,
+  ChangeSet,
   CommandResult,
+  Comment,
+  ContextSummary,
+  CreateOutcome,
+  EmailDeliveryEntry,
+  EventsManifest,
+  ExportContent,
+  ExportFilter,
+  ExportFormat,
+  ExternalImportReport,
+  MarkdownHeadings,
+  MergePlan,
+  MergePreview,
+  FocusSession,
+  FocusStats,
+  FocusStatus,
+  MaintenanceReport,
+  MarkdownSyncReport,
+  NoteVersion,
+  ObsidianExportReport,
+  Profile,
+  ProfileRegistry,
   Project,
+  ProjectNode,
+  RecoveryStatus,
+  ReminderAuditEntry,
   ReminderKind,
   RepeatRule,
+  SavedWeeklyReview,
+  SearchHit,
   Settings,
+  ShortcutCheck,
+  SmartList,
+  SoundClip,
+  SoundEntry,
+  Statistics,
   StatePayload,
+  Suggestion,
+  SyncReport,
+  Tag,
+  TagStat,
   Task,
+  TaskDraft,
+  TaskPage,
+  TaskQuerySpec,
+  TimeReportGroup,
+  TimeReportRow,
+  TrashEntry,
+  UnblockChain,
+  UndoStatus,
+  UndoSummary,
+  ViewPrefs,
+  WaitingForGroup,
+  WriteStatus,
+  YearReviewExport,
 } from "./types";
+import type { NotificationActionId, SnoozePresetId } from "./snooze";
 
 export interface BackupEntry {
   name: string;
   modified_at: number;
+  // Bytes on disk.
+  size: number;
 }
 
 export async function loadState() {
@@ -26,6 +85,14 @@ export async function updateProject(project: Project) {
   return invoke<CommandResult<Project>>("update_project", { project });
 }
 
+export async function muteProject(projectId: string, until: number | null) {
+  return invoke<CommandResult<Project>>("mute_project", { projectId, until });
+}
+
+export async function unmuteProject(projectId: string) {
+  return invoke<CommandResult<Project>>("unmute_project", { projectId });
+}
+
 export async function swapProjectSortOrder(firstId: string, secondId: string) {
   return invoke<CommandResult<boolean>>("swap_project_sort_order", {
     firstId,
@@ -37,10 +104,111 @@ export async function deleteProject(projectId: string) {
   return invoke<CommandResult<boolean>>("delete_project", { projectId });
 }
 
+export async function projectTree() {
+  return invoke<CommandResult<ProjectNode[]>>("project_tree");
+}
+
+export async function listSmartLists() {
+  return invoke<CommandResult<SmartList[]>>("list_smart_lists");
+}
+
+export async function createSmartList(list: SmartList) {
+  return invoke<CommandResult<SmartList>>("create_smart_list", { list });
+}
+
+export async function updateSmartList(list: SmartList) {
+  return invoke<CommandResult<SmartList>>("update_smart_list", { list });
+}
+
+export async function deleteSmartList(listId: string) {
+  return invoke<CommandResult<boolean>>("delete_smart_list", { listId });
+}
+
+// The list's tasks as of now, in its sort order.
+export async function evaluateSmartList(listId: string) {
+  return invoke<CommandResult<Task[]>>("evaluate_smart_list", { listId });
+}
+
+// Rules run after task saves (completed, tag added) and on the scheduler tick (overdue).
+export async function listAutomationRules() {
+  return invoke<CommandResult<AutomationRule[]>>("list_automation_rules");
+}
+
+export async function createAutomationRule(rule: AutomationRule) {
+  return invoke<CommandResult<AutomationRule>>("create_automation_rule", {
+    rule,
+  });
+}
+
+export async function updateAutomationRule(rule: AutomationRule) {
+  return invoke<CommandResult<AutomationRule>>("update_automation_rule", {
+    rule,
+  });
+}
+
+export async function deleteAutomationRule(ruleId: string) {
+  return invoke<CommandResult<boolean>>("delete_automation_rule", { ruleId });
+}
+
+export async function listTags() {
+  return invoke<CommandResult<TagStat[]>>("list_tags");
+}
+
+// Tags for the tag input's autocomplete, recently used first.
+export async function suggestTags(prefix?: string, limit?: number) {
+  return invoke<CommandResult<TagStat[]>>("suggest_tags", { prefix, limit });
+}
+
+// Both resolve to the number of tasks rewritten; one undo reverts the whole change.
+export async function renameTag(old: string, next: string) {
+  return invoke<CommandResult<number>>("rename_tag", { old, new: next });
+}
+
+export async function mergeTags(sources: string[], target: string) {
+  return invoke<CommandResult<number>>("merge_tags", { sources, target });
+}
+
+export async function listTagDefinitions() {
+  return invoke<CommandResult<Tag[]>>("list_tag_definitions");
+}
+
+export async function createTag(tag: Tag) {
+  return invoke<CommandResult<Tag>>("create_tag", { tag });
+}
+
+// A new name is applied to every task carrying the old one.
+export async function updateTag(tag: Tag) {
+  return invoke<CommandResult<Tag>>("update_tag", { tag });
+}
+
+export async function deleteTag(tagId: string) {
+  return invoke<CommandResult<boolean>>("delete_tag", { tagId });
+}
+
+export async function getViewPrefs(projectId: string) {
+  return invoke<CommandResult<ViewPrefs>>("get_view_prefs", { projectId });
+}
+
+export async function setViewPrefs(projectId: string, prefs: ViewPrefs) {
+  return invoke<CommandResult<ViewPrefs>>("set_view_prefs", {
+    projectId,
+    prefs,
+  });
+}
+
 export async function createTask(task: Task) {
   return invoke<CommandResult<Task>>("create_task", { task });
 }
 
+// Like createTask, but an open task with the same title in the same project on the same day
+// stops the creation and comes back as `{ duplicates }` ("create anyway" = createTask).
+export async function createTaskChecked(task: Task) {
+  return invoke<CommandResult<CreateOutcome>>("create_task", {
+    task,
+    checkDuplicates: true,
+  });
+}
+
 export async function updateTask(task: Task) {
   return invoke<CommandResult<Task>>("update_task", { task });
 }
@@ -49,6 +217,14 @@ export async function bulkUpdateTasks(tasks: Task[]) {
   return invoke<CommandResult<boolean>>("bulk_update_tasks", { tasks });
 }
 
+// The preview* helpers run the same command with `dryRun`: nothing is changed or saved.
+export async function previewBulkUpdateTasks(tasks: Task[]) {
+  return invoke<CommandResult<ChangeSet>>("bulk_update_tasks", {
+    tasks,
+    dryRun: true,
+  });
+}
+
 export async function swapSortOrder(firstId: string, secondId: string) {
   return invoke<CommandResult<boolean>>("swap_sort_order", {
     firstId,
@@ -56,6 +232,83 @@ export async function swapSortOrder(firstId: string, secondId: string) {
   });
 }
 
+export async function pinTask(taskId: string) {
+  return invoke<CommandResult<Task>>("pin_task", { taskId });
+}
+
+export async function unpinTask(taskId: string) {
+  return invoke<CommandResult<Task>>("unpin_task", { taskId });
+}
+
+export async function addComment(taskId: string, text: string) {
+  return invoke<CommandResult<Comment>>("add_comment", { taskId, text });
+}
+
+export async function deleteComment(taskId: string, commentId: string) {
+  return invoke<CommandResult<boolean>>("delete_comment", { taskId, commentId });
+}
+
+// The step and the steps nested under it become a new task; returns that task.
+export async function promoteStepToTask(taskId: string, stepId: string) {
+  return invoke<CommandResult<Task>>("promote_step_to_task", { taskId, stepId });
+}
+
+// Moves a task (with its steps) into another task as a step; returns the updated target.
+export async function convertTaskToStep(
+  taskId: string,
+  targetTaskId: string,
+  parentStepId?: string,
+) {
+  return invoke<CommandResult<Task>>("convert_task_to_step", {
+    taskId,
+    targetTaskId,
+    parentStepId: parentStepId ?? null,
+  });
+}
+
+// Only one timer runs at a time: starting one stops any other.
+export async function startTimer(taskId: string) {
+  return invoke<CommandResult<Task>>("start_timer", { taskId });
+}
+
+// Without a task id every running timer is stopped; returns the tasks that were changed.
+export async function stopTimer(taskId?: string) {
+  return invoke<CommandResult<Task[]>>("stop_timer", { taskId: taskId ?? null });
+}
+
+export async function reportTime(group?: TimeReportGroup, from?: number, to?: number) {
+  return invoke<CommandResult<TimeReportRow[]>>("report_time", {
+    group: group ?? null,
+    from: from ?? null,
+    to: to ?? null,
+  });
+}
+
+// What `taskId` is waiting on, blockers first.
+export async function unblockChain(taskId: string) {
+  return invoke<CommandResult<UnblockChain>>("unblock_chain", { taskId });
+}
+
+// `path` is the user's file; the backend copies it into the data directory.
+export async function addAttachment(taskId: string, path: string) {
+  return invoke<CommandResult<Attachment>>("add_attachment", { taskId, path });
+}
+
+export async function removeAttachment(taskId: string, attachmentId: string) {
+  return invoke<CommandResult<boolean>>("remove_attachment", {
+    taskId,
+    attachmentId,
+  });
+}
+
+// Opens the stored copy with the system's default app.
+export async function openAttachment(taskId: string, attachmentId: string) {
+  return invoke<CommandResult<boolean>>("open_attachment", {
+    taskId,
+    attachmentId,
+  });
+}
+
 export async function completeTask(taskId: string) {
   return invoke<CommandResult<Task>>("complete_task", { taskId });
 }
@@ -78,10 +331,36 @@ export async function setShortcutCaptureActive(active: boolean) {
   });
 }
 
+// `action` is the settings field being edited; its current binding is not a conflict.
+export async function validateShortcut(shortcut: string, action?: string) {
+  return invoke<CommandResult<ShortcutCheck>>("validate_shortcut", { shortcut, action });
+}
+
 export async function snoozeTask(taskId: string, until: number) {
   return invoke<CommandResult<boolean>>("snooze_task", { taskId, until });
 }
 
+export async function setDoNotDisturb(enabled: boolean) {
+  return invoke<CommandResult<Settings>>("set_do_not_disturb", { enabled });
+}
+
+// Resolves to the snooze time the backend picked for the preset.
+export async function snoozeTaskPreset(taskId: string, preset: SnoozePresetId) {
+  return invoke<CommandResult<number>>("snooze_task_preset", { taskId, preset });
+}
+
+export async function notificationAction(taskId: string, action: NotificationActionId) {
+  return invoke<CommandResult<boolean>>("notification_action", { taskId, action });
+}
+
+export async function listSounds() {
+  return invoke<CommandResult<SoundEntry[]>>("list_sounds");
+}
+
+export async function previewSound(soundId: string) {
+  return invoke<CommandResult<SoundClip>>("preview_sound", { soundId });
+}
+
 export async function dismissForced(taskId: string) {
   return invoke<CommandResult<boolean>>("dismiss_forced", { taskId });
 }
@@ -94,14 +373,281 @@ export async function deleteTasks(taskIds: string[]) {
   return invoke<CommandResult<boolean>>("delete_tasks", { taskIds });
 }
 
+export async function previewDeleteTasks(taskIds: string[]) {
+  return invoke<CommandResult<ChangeSet>>("delete_tasks", {
+    taskIds,
+    dryRun: true,
+  });
+}
+
+export async function listTrash() {
+  return invoke<CommandResult<TrashEntry[]>>("list_trash");
+}
+
+export async function restoreFromTrash(taskIds: string[]) {
+  return invoke<CommandResult<Task[]>>("restore_from_trash", { taskIds });
+}
+
+// Without ids the whole trash is emptied; either way it is permanent.
+export async function emptyTrash(taskIds?: string[]) {
+  return invoke<CommandResult<number>>("empty_trash", { taskIds: taskIds ?? null });
+}
+
+// Archived tasks of one year (or all years), most recently completed first.
+export async function listArchived(year?: number) {
+  return invoke<CommandResult<Task[]>>("list_archived", { year: year ?? null });
+}
+
+export async function searchArchive(query: string, limit?: number) {
+  return invoke<CommandResult<SearchHit[]>>("search_archive", {
+    query,
+    limit: limit ?? null,
+  });
+}
+
+// Moves an archived task back into the task list; not undoable.
+export async function unarchiveTask(taskId: string) {
+  return invoke<CommandResult<Task>>("unarchive_task", { taskId });
+}
+
+export async function queryTasks(spec: TaskQuerySpec) {
+  return invoke<CommandResult<TaskPage>>("query_tasks", { spec });
+}
+
+export async function listWaitingFor() {
+  return invoke<CommandResult<WaitingForGroup[]>>("list_waiting_for");
+}
+
+export async function resolveQuickDue(dueAt?: number) {
+  return invoke<CommandResult<number | null>>("resolve_quick_due", { dueAt: dueAt ?? null });
+}
+
+export async function listSomedayTasks() {
+  return invoke<CommandResult<Task[]>>("list_someday_tasks");
+}
+
+export async function listScheduledTasks() {
+  return invoke<CommandResult<Task[]>>("list_scheduled_tasks");
+}
+
+export async function moveToSomeday(taskId: string) {
+  return invoke<CommandResult<Task>>("move_to_someday", { taskId });
+}
+
+export async function moveFromSomeday(taskId: string, dueAt?: number) {
+  return invoke<CommandResult<Task>>("move_from_someday", { taskId, dueAt: dueAt ?? null });
+}
+
+export async function listContexts() {
+  return invoke<CommandResult<ContextSummary[]>>("list_contexts");
+}
+
+export async function getBurndown(projectId?: string | null, range?: BurndownRange) {
+  return invoke<CommandResult<Burndown>>("get_burndown", {
+    projectId: projectId ?? null,
+    range: range ?? null,
+  });
+}
+
+// Defaults to the last 30 local days, today included.
+export async function getStatistics(from?: number, to?: number) {
+  return invoke<CommandResult<Statistics>>("get_statistics", {
+    from: from ?? null,
+    to: to ?? null,
+  });
+}
+
+// Timer and focus session time; same default range as getStatistics.
+export async function getFocusStats(from?: number, to?: number) {
+  return invoke<CommandResult<FocusStats>>("get_focus_stats", {
+    from: from ?? null,
+    to: to ?? null,
+  });
+}
+
+export async function searchTasks(
+  query: string,
+  options?: { includeCompleted?: boolean; limit?: number },
+) {
+  return invoke<CommandResult<SearchHit[]>>("search_tasks", {
+    query,
+    includeCompleted: options?.includeCompleted ?? null,
+    limit: options?.limit ?? null,
+  });
+}
+
+export async function undoLastAction() {
+  return invoke<CommandResult<UndoSummary>>("undo_last_action");
+}
+
+export async function redoLastAction() {
+  return invoke<CommandResult<UndoSummary>>("redo_last_action");
+}
+
+export async function getUndoStatus() {
+  return invoke<CommandResult<UndoStatus>>("get_undo_status");
+}
+
+export async function suggestNextTask(limit?: number) {
+  return invoke<CommandResult<Suggestion[]>>("suggest_next_task", {
+    limit: limit ?? null,
+  });
+}
+
+export async function inferTaskFromText(text: string) {
+  return invoke<CommandResult<TaskDraft>>("infer_task_from_text", { text });
+}
+
+export async function eventsManifest() {
+  return invoke<CommandResult<EventsManifest>>("events_manifest");
+}
+
+export async function getRecoveryStatus() {
+  return invoke<CommandResult<RecoveryStatus | null>>("get_recovery_status");
+}
+
+export async function formatDates(timestamps: number[]) {
+  return invoke<CommandResult<string[]>>("format_dates", { timestamps });
+}
+
+export async function setSmtpPassword(password: string) {
+  return invoke<CommandResult<boolean>>("set_smtp_password", { password });
+}
+
+export async function setMqttPassword(password: string) {
+  return invoke<CommandResult<boolean>>("set_mqtt_password", { password });
+}
+
+export async function setCaldavPassword(password: string) {
+  return invoke<CommandResult<boolean>>("set_caldav_password", { password });
+}
+
+export type SecretName =
+  | "deepseek_api_key"
+  | "openai_api_key"
+  | "anthropic_api_key"
+  | "smtp_password"
+  | "mqtt_password"
+  | "caldav_password";
+
+export async function setSecret(name: SecretName, value: string) {
+  return invoke<CommandResult<Settings>>("set_secret", { name, value });
+}
+
+export async function clearSecret(name: SecretName) {
+  return invoke<CommandResult<Settings>>("clear_secret", { name });
+}
+
+export async function syncNow() {
+  return invoke<CommandResult<SyncReport>>("sync_now");
+}
+
+export async function sendTestEmail() {
+  return invoke<CommandResult<boolean>>("send_test_email");
+}
+
+export async function listEmailDeliveries(limit?: number) {
+  return invoke<CommandResult<EmailDeliveryEntry[]>>("list_email_deliveries", {
+    limit,
+  });
+}
+
+export async function listCarryOverLog(limit?: number) {
+  return invoke<CommandResult<CarryOverEntry[]>>("list_carry_over_log", {
+    limit,
+  });
+}
+
+export async function getNoteHistory(taskId: string) {
+  return invoke<CommandResult<NoteVersion[]>>("get_note_history", { taskId });
+}
+
+// The notes being replaced are saved as a new version, so a restore can be undone the same way.
+export async function restoreNoteVersion(taskId: string, version: number) {
+  return invoke<CommandResult<Task>>("restore_note_version", {
+    taskId,
+    version,
+  });
+}
+
+// Newest first; both default to the latest 50 entries.
+export async function getTaskActivity(taskId: string, limit?: number) {
+  return invoke<CommandResult<ActivityEntry[]>>("get_task_activity", {
+    taskId,
+    limit,
+  });
+}
+
+export async function getRecentActivity(limit?: number) {
+  return invoke<CommandResult<ActivityEntry[]>>("get_recent_activity", {
+    limit,
+  });
+}
+
+// One focus (pomodoro) session at a time; the scheduler emits `focus_tick` every second and
+// `focus_finished` when it runs out.
+export async function startFocusSession(taskId: string, minutes?: number) {
+  return invoke<CommandResult<FocusStatus>>("start_focus_session", {
+    taskId,
+    minutes: minutes ?? null,
+  });
+}
+
+export async function pauseFocus() {
+  return invoke<CommandResult<FocusStatus>>("pause_focus");
+}
+
+export async function resumeFocus() {
+  return invoke<CommandResult<FocusStatus>>("resume_focus");
+}
+
+export async function stopFocus() {
+  return invoke<CommandResult<FocusSession | null>>("stop_focus");
+}
+
+export async function getFocusStatus() {
+  return invoke<CommandResult<FocusStatus | null>>("get_focus_status");
+}
+
+export async function listFocusSessions(taskId?: string, limit?: number) {
+  return invoke<CommandResult<FocusSession[]>>("list_focus_sessions", {
+    taskId: taskId ?? null,
+    limit,
+  });
+}
+
+export async function listReminderAudit(limit?: number) {
+  return invoke<CommandResult<ReminderAuditEntry[]>>("list_reminder_audit", {
+    limit,
+  });
+}
+
 export async function listBackups() {
   return invoke<CommandResult<BackupEntry[]>>("list_backups");
 }
 
+export async function estimateBackupArchive() {
+  return invoke<CommandResult<ArchiveEstimate>>("estimate_backup_archive");
+}
+
+// `path` is the destination directory; resolves to the written archive path.
+export async function exportAllBackups(path: string) {
+  return invoke<CommandResult<string>>("export_all_backups", { path });
+}
+
 export async function deleteBackup(filename: string) {
   return invoke<CommandResult<boolean>>("delete_backup", { filename });
 }
 
+// Writes changes still waiting in the backend's write-behind queue; true if any were.
+export async function flushStorage() {
+  return invoke<CommandResult<boolean>>("flush_storage");
+}
+
+export async function getWriteStatus() {
+  return invoke<CommandResult<WriteStatus>>("get_write_status");
+}
+
 export async function createBackup() {
   return invoke<CommandResult<boolean>>("create_backup");
 }
@@ -114,16 +660,115 @@ export async function importBackup(path: string) {
   return invoke<CommandResult<Task[]>>("import_backup", { path });
 }
 
-export async function exportTasksJson() {
-  return invoke<CommandResult<string>>("export_tasks_json");
+export async function previewRestoreBackup(name: string) {
+  return invoke<CommandResult<ChangeSet>>("restore_backup", {
+    filename: name,
+    dryRun: true,
+  });
+}
+
+export async function previewImportBackup(path: string) {
+  return invoke<CommandResult<ChangeSet>>("import_backup", {
+    path,
+    dryRun: true,
+  });
+}
+
+// Backup tasks/projects that differ from the current data.
+export async function previewBackupMerge(name: string) {
+  return invoke<CommandResult<MergePreview>>("preview_backup_merge", {
+    filename: name,
+  });
 }
 
-export async function exportTasksCsv() {
-  return invoke<CommandResult<string>>("export_tasks_csv");
+// Restores only the given ids; the newer copy wins where both sides have one.
+export async function mergeBackup(
+  name: string,
+  taskIds: string[],
+  projectIds: string[] = [],
+) {
+  return invoke<CommandResult<MergePlan>>("merge_backup", {
+    filename: name,
+    taskIds,
+    projectIds,
+  });
 }
 
-export async function exportTasksMarkdown() {
-  return invoke<CommandResult<string>>("export_tasks_markdown");
+// Todoist CSV / TickTick JSON; adds to the current data instead of replacing it.
+export async function importExternal(path: string, dryRun = false) {
+  return invoke<CommandResult<ExternalImportReport>>("import_external", {
+    path,
+    dryRun,
+  });
+}
+
+// `- [ ]` / `- [x]` lists from a Markdown file path or pasted text.
+export async function importMarkdown(
+  source: string,
+  headings: MarkdownHeadings = "project",
+  dryRun = false,
+) {
+  return invoke<CommandResult<ExternalImportReport>>("import_markdown", {
+    source,
+    headings,
+    dryRun,
+  });
+}
+
+export async function exportTasksJson(filter?: ExportFilter) {
+  return invoke<CommandResult<string>>("export_tasks_json", {
+    filter: filter ?? null,
+  });
+}
+
+export async function exportTasksCsv(filter?: ExportFilter) {
+  return invoke<CommandResult<string>>("export_tasks_csv", {
+    filter: filter ?? null,
+  });
+}
+
+export async function exportTasksMarkdown(filter?: ExportFilter) {
+  return invoke<CommandResult<string>>("export_tasks_markdown", {
+    filter: filter ?? null,
+  });
+}
+
+export async function exportTasksIcs(filter?: ExportFilter) {
+  return invoke<CommandResult<string>>("export_tasks_ics", {
+    filter: filter ?? null,
+  });
+}
+
+// Writes exports/mustdo-sync.md; checkbox edits to it are synced back while
+// `markdown_sync_enabled` is on.
+export async function exportYearReview(year: number) {
+  return invoke<CommandResult<YearReviewExport>>("export_year_review", {
+    year,
+  });
+}
+
+export async function exportMarkdownSync() {
+  return invoke<CommandResult<string>>("export_markdown_sync");
+}
+
+export async function exportToObsidian(vaultPath: string) {
+  return invoke<CommandResult<ObsidianExportReport>>("export_to_obsidian", {
+    vaultPath,
+  });
+}
+
+export async function syncMarkdownExport() {
+  return invoke<CommandResult<MarkdownSyncReport>>("sync_markdown_export");
+}
+
+export async function exportTasksContent(
+  format: ExportFormat,
+  filter?: ExportFilter,
+) {
+  return invoke<CommandResult<ExportContent>>("export_tasks_content", {
+    format,
+    filter: filter ?? null,
+  });
 }
 
 export interface AiPlanRequest {
@@ -158,6 +803,11 @@ export interface AiPlan {
   repeat: RepeatRule | null;
 }
 
+// Sends last week's completed and overdue tasks to the configured AI provider.
+export async function aiWeeklyReview() {
+  return invoke<CommandResult<SavedWeeklyReview>>("ai_weekly_review");
+}
+
 export async function aiPlanTask(request: AiPlanRequest) {
   try {
     const data = await invoke<AiPlan>("ai_plan_task", { request });
@@ -167,3 +817,35 @@ export async function aiPlanTask(request: AiPlanRequest) {
     return { ok: false, error: message } satisfies CommandResult<AiPlan>;
   }
 }
+
+export async function runMaintenanceNow() {
+  return invoke<CommandResult<MaintenanceReport>>("run_maintenance_now");
+}
+
+export async function setDataDirectory(path: string) {
+  return invoke<CommandResult<string>>("set_data_directory", { path });
+}
+
+export async function testFireReminder(taskId: string) {
+  return invoke<CommandResult<Task>>("test_fire_reminder", { taskId });
+}
+
+export async function advanceClock(seconds: number) {
+  return invoke<CommandResult<number>>("advance_clock", { seconds });
+}
+
+export async function isGuestMode() {
+  return invoke<CommandResult<boolean>>("is_guest_mode");
+}
+
+export async function listProfiles() {
+  return invoke<CommandResult<ProfileRegistry>>("list_profiles");
+}
+
+export async function createProfile(name: string) {
+  return invoke<CommandResult<Profile>>("create_profile", { name });
+}
+
+export async function switchProfile(name: string) {
+  return invoke<CommandResult<StatePayload>>("switch_profile", { name });
+}
@@ -58,7 +58,7 @@ export function ForcedReminderOverlay({
   const [showMoreSnooze, setShowMoreSnooze] = useState(false);
 
   const relative = useMemo(() => {
-    if (!task) return "";
+    if (!task || task.due_at == null) return "";
     const delta = task.due_at - now;
     if (delta === 0) return t("forced.relative.now");
     if (delta > 0) {
@@ -79,7 +79,7 @@ export function ForcedReminderOverlay({
       if (event.key === "Escape") {
         // Safer default than “关闭提醒”: Esc = snooze and revisit soon.
         event.preventDefault();
-        onSnooze("m5");
+        onSnooze("in_5m");
       }
     };
     window.addEventListener("keydown", onKeyDown);
@@ -171,7 +171,7 @@ export function ForcedReminderOverlay({
             <button
               type="button"
               className="forced-btn secondary"
-              onClick={() => onSnooze("m5")}
+              onClick={() => onSnooze("in_5m")}
             >
               <Icons.Snooze />
               {t("forced.action.snooze5")}
@@ -193,7 +193,7 @@ export function ForcedReminderOverlay({
                   className="forced-btn secondary"
                   onClick={() => {
                     setShowMoreSnooze(false);
-                    onSnooze("m15");
+                    onSnooze("in_15m");
                   }}
                 >
                   <Icons.Snooze />
@@ -204,7 +204,7 @@ export function ForcedReminderOverlay({
                   className="forced-btn secondary"
                   onClick={() => {
                     setShowMoreSnooze(false);
-                    onSnooze("h1");
+                    onSnooze("in_1h");
                   }}
                 >
                   <Icons.Snooze />
@@ -215,7 +215,7 @@ export function ForcedReminderOverlay({
                   className="forced-btn secondary"
                   onClick={() => {
                     setShowMoreSnooze(false);
-                    onSnooze("tomorrow0900");
+                    onSnooze("tomorrow_morning");
                   }}
                 >
                   <Icons.Snooze />
@@ -23,28 +23,28 @@ export function NotificationBanner({
             <button
               type="button"
               className="pill"
-              onClick={() => onSnooze(task, "m5")}
+              onClick={() => onSnooze(task, "in_5m")}
             >
               {t("banner.snooze5")}
             </button>
             <button
               type="button"
               className="pill"
-              onClick={() => onSnooze(task, "m15")}
+              onClick={() => onSnooze(task, "in_15m")}
             >
               {t("banner.snooze15")}
             </button>
             <button
               type="button"
               className="pill"
-              onClick={() => onSnooze(task, "h1")}
+              onClick={() => onSnooze(task, "in_1h")}
             >
               {t("banner.snooze1h")}
             </button>
             <button
               type="button"
               className="pill"
-              onClick={() => onSnooze(task, "tomorrow0900")}
+              onClick={() => onSnooze(task, "tomorrow_morning")}
             >
               {t("banner.snoozeTomorrowMorning")}
             </button>
@@ -0,0 +1,109 @@
+import { formatDue } from "../date";
+import { useI18n } from "../i18n";
+import type { Task } from "../types";
+import type { SnoozePresetId } from "../snooze";
+
+import { Icons } from "./icons";
+
+// One overlay for a burst of reminders (see `settings.reminder_digest_threshold`), instead of
+// walking through a forced overlay per task.
+export function ReminderDigestOverlay({
+  tasks,
+  color,
+  onSnooze,
+  onComplete,
+  onDismissAll,
+}: {
+  tasks: Task[];
+  color: string;
+  onSnooze: (task: Task, preset: SnoozePresetId) => void;
+  onComplete: (task: Task) => void;
+  onDismissAll: () => void;
+}) {
+  const { t } = useI18n();
+
+  if (tasks.length === 0) return null;
+
+  return (
+    <div
+      className="forced-reminder"
+      style={{ ["--forced-color" as any]: color }}
+    >
+      <div className="forced-reminder-scrim" aria-hidden="true" />
+
+      <div
+        className="forced-reminder-sheet"
+        role="alertdialog"
+        aria-label={t("digest.title", { count: tasks.length })}
+      >
+        <div
+          className="forced-reminder-accent"
+          style={{ backgroundColor: color }}
+        />
+
+        <div className="reminder-digest">
+          <div className="forced-reminder-toprow">
+            <span className="forced-reminder-badge">
+              <span
+                className="forced-reminder-dot"
+                style={{ backgroundColor: color }}
+              />
+              {t("digest.title", { count: tasks.length })}
+            </span>
+          </div>
+
+          <ul className="reminder-digest-list">
+            {tasks.map((task) => (
+              <li key={task.id} className="reminder-digest-item">
+                <div className="reminder-digest-main">
+                  <div className="reminder-digest-title">
+                    {task.reminder.kind === "forced" && (
+                      <span
+                        className="forced-reminder-dot"
+                        style={{ backgroundColor: color }}
+                        title={t("forced.title")}
+                      />
+                    )}
+                    {task.title}
+                  </div>
+                  <span className="forced-reminder-chip">
+                    <Icons.Clock />
+                    {formatDue(task.due_at)}
+                  </span>
+                </div>
+                <div className="reminder-digest-actions">
+                  <button
+                    type="button"
+                    className="forced-btn secondary"
+                    onClick={() => onSnooze(task, "in_5m")}
+                  >
+                    <Icons.Snooze />
+                    {t("forced.action.snooze5")}
+                  </button>
+                  <button
+                    type="button"
+                    className="forced-btn primary"
+                    onClick={() => onComplete(task)}
+                  >
+                    <Icons.Check />
+                    {t("forced.action.complete")}
+                  </button>
+                </div>
+              </li>
+            ))}
+          </ul>
+
+          <div className="reminder-digest-footer">
+            <button
+              type="button"
+              className="forced-btn ghost"
+              onClick={onDismissAll}
+            >
+              {t("digest.dismissAll")}
+            </button>
+          </div>
+        </div>
+      </div>
+    </div>
+  );
+}
@@ -63,7 +63,7 @@ export function TaskCard({
         : "";
   const overdueFlag = overdue
     ? (() => {
-        const delta = Math.max(0, now - task.due_at);
+        const delta = Math.max(0, now - (task.due_at ?? now));
         const days = Math.floor(delta / 86400);
         if (days > 0) return t("task.overdueFlag.days", { days });
         const hours = Math.max(1, Math.floor(delta / 3600));
@@ -214,6 +214,9 @@ export function TaskCard({
   return (
     <div
       className={`task-card ${mode} q${task.quadrant} ${task.completed ? "completed" : ""} ${overdue ? "overdue" : ""}`}
+      style={
+        task.color ? { borderLeft: `3px solid ${task.color}` } : undefined
+      }
       draggable={draggable}
       onDragStart={onDragStart}
       ref={cardRef}
@@ -247,7 +250,10 @@ export function TaskCard({
         </button>
 
         <div className="task-content">
-          <span className="task-title">{task.title}</span>
+          <span className="task-title">
+            {task.emoji && <span className="task-emoji">{task.emoji} </span>}
+            {task.title}
+          </span>
           <div className="task-meta">
             <span
               className="task-time task-due-time"
@@ -3,6 +3,7 @@ import { useEffect, useMemo, useRef, useState } from "react";
 import { IconButton } from "./IconButton";
 import { Icons } from "./icons";
 
+import { inferTaskFromText } from "../api";
 import { fromDateTimeLocal, toDateTimeLocal } from "../date";
 import { useI18n } from "../i18n";
 import {
@@ -31,8 +32,14 @@ export type TaskComposerDraft = {
   repeat: RepeatRule;
   reminder_kind: ReminderKind;
   reminder_offset_minutes: number;
+  // Filled when a multi-line paste was turned into a draft.
+  steps: string[];
+  notes: string | null;
 };
 
+// Below this the parser is guessing; keep the composer's own default instead.
+const INFER_MIN_CONFIDENCE = 0.5;
+
 export function TaskComposer({
   placeholder,
   placeholderOptions,
@@ -66,6 +73,8 @@ export function TaskComposer({
   const [repeat, setRepeat] = useState<RepeatRule>({ type: "none" });
   const [reminderKind, setReminderKind] = useState<ReminderKind>("none");
   const [reminderOffset, setReminderOffset] = useState<number>(10);
+  const [pastedSteps, setPastedSteps] = useState<string[]>([]);
+  const [pastedNotes, setPastedNotes] = useState<string | null>(null);
 
   const initialDueAtRef = useRef<number>(dueAt);
   const placeholderItems = useMemo(
@@ -206,9 +215,13 @@ export function TaskComposer({
         repeat,
         reminder_kind: reminderKind,
         reminder_offset_minutes: reminderOffset,
+        steps: pastedSteps,
+        notes: pastedNotes,
       });
       if (result === false) return;
       setTitle("");
+      setPastedSteps([]);
+      setPastedNotes(null);
       setRepeat({ type: "none" });
       setImportant(false);
       setReminderKind("none");
@@ -222,6 +235,25 @@ export function TaskComposer({
     }
   }
 
+  // Multi-line pastes (an email, a checklist) become a draft: first line as title, bullets as
+  // steps, the rest as notes, plus any date the text mentions. Single lines paste as usual.
+  async function handlePaste(text: string) {
+    const res = await inferTaskFromText(text);
+    if (!res.ok || !res.data) return false;
+    const draft = res.data;
+    if (!draft.title) return false;
+    setTitle([draft.title, ...draft.tags.map((tag) => `#${tag}`)].join(" "));
+    if (draft.due_at && draft.confidence.due_at >= INFER_MIN_CONFIDENCE) {
+      setDueAt(draft.due_at);
+    }
+    if (draft.important && draft.confidence.important >= INFER_MIN_CONFIDENCE) {
+      setImportant(true);
+    }
+    setPastedSteps(draft.steps);
+    setPastedNotes(draft.notes ?? null);
+    return true;
+  }
+
   function togglePopup(next: typeof activePopup) {
     setActivePopup((prev) => (prev === next ? null : next));
   }
@@ -236,7 +268,21 @@ export function TaskComposer({
           value={title}
           disabled={busy}
           aria-busy={busy}
-          onChange={(e) => setTitle(e.currentTarget.value)}
+          onChange={(e) => {
+            setTitle(e.currentTarget.value);
+            if (!e.currentTarget.value) {
+              setPastedSteps([]);
+              setPastedNotes(null);
+            }
+          }}
+          onPaste={(e) => {
+            const text = e.clipboardData.getData("text/plain");
+            if (!text.includes("\n") || title.trim()) return;
+            e.preventDefault();
+            void handlePaste(text).then((applied) => {
+              if (!applied) setTitle(text.replace(/\s+/g, " ").trim());
+            });
+          }}
           onKeyDown={(e) => {
             if (e.key !== "Enter") return;
             // IME: don't submit while the user is still composing text.
@@ -264,6 +310,17 @@ export function TaskComposer({
         />
 
         <div className="composer-actions" aria-label={t("composer.options")}>
+          {(pastedSteps.length > 0 || pastedNotes) && (
+            <span
+              className="composer-paste-badge"
+              title={t("composer.pasted", {
+                steps: pastedSteps.length,
+                notes: pastedNotes ? 1 : 0,
+              })}
+            >
+              +{pastedSteps.length || t("composer.pastedNotes")}
+            </span>
+          )}
           <IconButton
             className={`composer-action-btn ${activePopup === "due" || isDueCustomized ? "active" : ""}`}
             onClick={() => togglePopup("due")}
@@ -2,8 +2,15 @@ import { useEffect, useMemo, useState } from "react";
 
 import { formatDue, fromDateTimeLocal, toDateTimeLocal } from "../date";
 import { useI18n } from "../i18n";
-import { buildReminderConfig, getReminderOffsetMinutes, buildReminderKindOptions, buildReminderOffsetPresets } from "../reminder";
+import {
+  buildReminderConfig,
+  getExtraReminderOffsets,
+  getReminderOffsetMinutes,
+  buildReminderKindOptions,
+  buildReminderOffsetPresets,
+} from "../reminder";
 import { defaultRepeatRule, buildRepeatTypeOptions, buildWeekdayOptions } from "../repeat";
+import { stepDepths, stepSubtreeIds } from "../steps";
 import { normalizeTag } from "../tags";
 import type { ReminderKind, RepeatRule, Task } from "../types";
 
@@ -16,18 +23,23 @@ export function TaskEditModal({
   task,
   showNotes,
   onSave,
+  onPromoteStep,
   onClose,
 }: {
   task: Task;
   showNotes: boolean;
   onSave: (next: Task) => Promise<void> | void;
+  /** Turns a saved step into its own task; resolves to false when the backend refused. */
+  onPromoteStep?: (stepId: string) => Promise<boolean>;
   onClose: () => void;
 }) {
   const { t } = useI18n();
   const [draftTitle, setDraftTitle] = useState(task.title);
-  const [draftDueAt, setDraftDueAt] = useState(task.due_at);
+  const [draftDueAt, setDraftDueAt] = useState<number | null>(task.due_at);
   const [draftReminderKind, setDraftReminderKind] = useState<ReminderKind>(task.reminder.kind);
   const [draftReminderOffset, setDraftReminderOffset] = useState<number>(getReminderOffsetMinutes(task));
+  const [draftExtraOffsets, setDraftExtraOffsets] = useState<number[]>(getExtraReminderOffsets(task));
+  const [draftEscalateAfter, setDraftEscalateAfter] = useState<number | null>(task.escalate_after ?? null);
   const [draftRepeat, setDraftRepeat] = useState<RepeatRule>(task.repeat);
   const [draftNotes, setDraftNotes] = useState(task.notes ?? "");
   const [draftSteps, setDraftSteps] = useState(task.steps);
@@ -40,12 +52,14 @@ export function TaskEditModal({
   const reminderOffsetPresets = useMemo(() => buildReminderOffsetPresets(t), [t]);
   const repeatTypeOptions = useMemo(() => buildRepeatTypeOptions(t), [t]);
   const weekdayOptions = useMemo(() => buildWeekdayOptions(t), [t]);
+  const draftStepDepths = useMemo(() => stepDepths(draftSteps), [draftSteps]);
 
   useEffect(() => {
     setDraftTitle(task.title);
     setDraftDueAt(task.due_at);
     setDraftReminderKind(task.reminder.kind);
     setDraftReminderOffset(getReminderOffsetMinutes(task));
+    setDraftEscalateAfter(task.escalate_after ?? null);
     setDraftRepeat(task.repeat);
     setDraftNotes(task.notes ?? "");
     setDraftSteps(task.steps);
@@ -60,6 +74,7 @@ export function TaskEditModal({
     setDraftDueAt(task.due_at);
     setDraftReminderKind(task.reminder.kind);
     setDraftReminderOffset(getReminderOffsetMinutes(task));
+    setDraftEscalateAfter(task.escalate_after ?? null);
     setDraftRepeat(task.repeat);
     setDraftNotes(task.notes ?? "");
     setDraftSteps(task.steps);
@@ -111,7 +126,23 @@ export function TaskEditModal({
   }
 
   function removeStep(stepId: string) {
-    setDraftSteps((prev) => prev.filter((step) => step.id !== stepId));
+    setDraftSteps((prev) => {
+      const removed = stepSubtreeIds(prev, stepId);
+      return prev.filter((step) => !removed.has(step.id));
+    });
+  }
+
+  async function promoteStep(stepId: string) {
+    if (!onPromoteStep) return;
+    setSaving(true);
+    try {
+      if (await onPromoteStep(stepId)) {
+        // The backend moved the step and its children out; keep the rest of the draft.
+        removeStep(stepId);
+      }
+    } finally {
+      setSaving(false);
+    }
   }
 
   async function handleSave() {
@@ -123,7 +154,11 @@ export function TaskEditModal({
       title,
       due_at: draftDueAt,
       repeat: draftRepeat,
-      reminder: buildReminderConfig(draftReminderKind, draftDueAt, draftReminderOffset),
+      reminder:
+        draftDueAt == null
+          ? task.reminder
+          : buildReminderConfig(draftReminderKind, draftDueAt, draftReminderOffset, now, draftExtraOffsets),
+      escalate_after: draftEscalateAfter,
       steps: draftSteps,
       tags: draftTags,
       notes: showNotes ? draftNotes.trim() || undefined : task.notes,
@@ -222,7 +257,59 @@ export function TaskEditModal({
                       disabled={saving}
                     />
                     <span>{t("reminder.offset.minutes")}</span>
+                    <button
+                      type="button"
+                      className="pill"
+                      onClick={() =>
+                        setDraftExtraOffsets((prev) =>
+                          prev.includes(draftReminderOffset) ? prev : [...prev, draftReminderOffset],
+                        )
+                      }
+                      disabled={saving}
+                    >
+                      {t("reminder.offset.add")}
+                    </button>
                   </div>
+                  {draftExtraOffsets.length > 0 && (
+                    <div className="inline-config-buttons">
+                      {draftExtraOffsets
+                        .filter((minutes) => minutes !== draftReminderOffset)
+                        .map((minutes) => (
+                          <button
+                            key={minutes}
+                            type="button"
+                            className="pill active"
+                            onClick={() => setDraftExtraOffsets((prev) => prev.filter((m) => m !== minutes))}
+                            disabled={saving}
+                            title={t("reminder.offset.remove")}
+                          >
+                            {t("reminder.offset.extra", { minutes })}
+                          </button>
+                        ))}
+                    </div>
+                  )}
+                  {draftReminderKind === "normal" && (
+                    <div className="inline-config-extra">
+                      <span>{t("reminder.escalate")}</span>
+                      <select
+                        className="inline-input"
+                        value={draftEscalateAfter ?? ""}
+                        onChange={(event) => {
+                          const value = event.currentTarget.value;
+                          setDraftEscalateAfter(value === "" ? null : Number(value));
+                        }}
+                        disabled={saving}
+                      >
+                        <option value="">{t("reminder.escalate.default")}</option>
+                        <option value={0}>{t("reminder.escalate.never")}</option>
+                        {[2, 3, 5].map((count) => (
+                          <option key={count} value={count}>
+                            {t("reminder.escalate.after", { count })}
+                          </option>
+                        ))}
+                      </select>
+                    </div>
+                  )}
                 </>
               )}
             </div>
@@ -427,7 +514,11 @@ export function TaskEditModal({
               <div className="steps-empty">{t("taskEdit.stepEmpty")}</div>
             ) : (
               draftSteps.map((step) => (
-                <div key={step.id} className={`step-item ${step.completed ? "completed" : ""}`}>
+                <div
+                  key={step.id}
+                  className={`step-item ${step.completed ? "completed" : ""}`}
+                  style={{ ["--step-depth" as any]: draftStepDepths.get(step.id) ?? 0 }}
+                >
                   <button
                     type="button"
                     className="step-checkbox"
@@ -439,6 +530,18 @@ export function TaskEditModal({
                     {step.completed && <Icons.Check />}
                   </button>
                   <span className="step-title">{step.title}</span>
+                  {onPromoteStep && (
+                    <button
+                      type="button"
+                      className="step-promote"
+                      onClick={() => void promoteStep(step.id)}
+                      title={t("taskEdit.stepPromote")}
+                      aria-label={t("taskEdit.stepPromote")}
+                      disabled={saving}
+                    >
+                      <Icons.ArrowUp />
+                    </button>
+                  )}
                   <button
                     type="button"
                     className="step-delete"
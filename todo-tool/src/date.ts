@@ -1,4 +1,6 @@
-export function formatDue(ts: number) {
+// Undated tasks (`due_at: null`) render as an empty string.
+export function formatDue(ts: number | null) {
+  if (ts == null) return "";
   const d = new Date(ts * 1000);
   const yyyy = d.getFullYear();
   const mm = String(d.getMonth() + 1).padStart(2, "0");
@@ -8,7 +10,8 @@ export function formatDue(ts: number) {
   return `${yyyy}-${mm}-${dd} ${hh}:${mi}`;
 }
 
-export function toDateTimeLocal(ts: number) {
+export function toDateTimeLocal(ts: number | null) {
+  if (ts == null) return "";
   const d = new Date(ts * 1000);
   const yyyy = d.getFullYear();
   const mm = String(d.getMonth() + 1).padStart(2, "0");
@@ -8,3 +8,7 @@ export const DOM_WINDOW_DRAG_START = "fk.todo:window-drag-start";
 // Tauri event emitted by the Rust backend to request frontend navigation (hash route).
 // Tauri v2 event names must be [A-Za-z0-9-/:_]. Avoid dots.
 export const TAURI_NAVIGATE = "mustdo:navigate";
+
+// Navigate hash the quick-add global shortcut sends to the quick window; it focuses the add
+// input instead of changing the route.
+export const QUICK_ADD_HASH = "#/quick/add";
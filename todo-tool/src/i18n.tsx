@@ -79,6 +79,10 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "project.menu.pin": "置顶",
     "project.menu.unpin": "取消置顶",
     "project.menu.rename": "改名",
+    "project.menu.mute": "静音提醒",
+    "project.menu.muteWeek": "静音提醒 7 天",
+    "project.menu.unmute": "恢复提醒",
+    "project.muted": "提醒已静音",
     "project.confirmDelete.title": "删除项目「{name}」？",
     "project.confirmDelete.description":
       "该项目下的任务将移动到默认项目，且此操作不可撤销。",
@@ -154,6 +158,8 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "composer.placeholderAiExample4": "示例：开会请提前 30 分钟提醒",
     "composer.aiPlanning": "AI 正在结合时间/项目生成…",
     "composer.options": "任务选项",
+    "composer.pasted": "粘贴内容已拆分：{steps} 个步骤，{notes} 段备注",
+    "composer.pastedNotes": "备注",
     "composer.due": "到期时间",
     "composer.reminder": "提醒",
     "composer.repeat": "循环",
@@ -189,6 +195,13 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "reminder.offset.titleBefore": "提前 {label} 提醒",
     "reminder.offset.before": "提前",
     "reminder.offset.minutes": "分钟",
+    "reminder.offset.add": "再加一个提醒",
+    "reminder.offset.extra": "提前 {minutes} 分钟",
+    "reminder.offset.remove": "点击移除这个提醒",
+    "reminder.escalate": "忽略后升级",
+    "reminder.escalate.default": "跟随设置",
+    "reminder.escalate.never": "从不",
+    "reminder.escalate.after": "忽略 {count} 次后",
 
     "repeat.none": "不循环",
     "repeat.daily": "每日",
@@ -222,6 +235,10 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "repeat.format.weekly": "每周({days})",
     "repeat.format.monthly": "每月({day}号)",
     "repeat.format.yearly": "每年({month}-{day})",
+    "repeat.format.everyNDays": "每 {n} 天",
+    "repeat.format.everyNWeeks": "每 {n} 周",
+    "repeat.format.everyNWeeksOn": "每 {n} 周({days})",
+    "repeat.format.everyNMonths": "每 {n} 个月({day}号)",
 
     "task.markComplete": "标记为完成",
     "task.markIncomplete": "标记为未完成",
@@ -259,6 +276,7 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "taskEdit.stepRequired": "请输入步骤内容",
     "taskEdit.stepEmpty": "无步骤",
     "taskEdit.stepDelete": "删除步骤",
+    "taskEdit.stepPromote": "转为独立任务",
     "taskEdit.stepMarkComplete": "标记步骤为完成",
     "taskEdit.stepMarkIncomplete": "标记步骤为未完成",
     "taskEdit.close": "关闭编辑",
@@ -269,6 +287,8 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "banner.snooze1h": "稍后 1 小时",
     "banner.snoozeTomorrowMorning": "明天早上",
     "banner.complete": "完成",
+    "notification.action.done": "完成",
+    "notification.action.snooze10m": "10 分钟后提醒",
 
     "forced.title": "强制提醒",
     "forced.queue": "提醒队列",
@@ -292,6 +312,25 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "forced.time.hoursMins": "{hours}小时 {mins}分",
     "forced.time.mins": "{mins}分",
     "forced.time.lessThanMin": "不到 1 分钟",
+    "digest.title": "{count} 个提醒同时到期",
+    "digest.dismissAll": "全部关闭",
+    "markdownSync.completed": "已从 Markdown 同步完成 {count} 个任务",
+    "dailySummary.title": "今日总结",
+    "storage.writeFailed": "改动未能保存到磁盘，目前只在内存中：{error}",
+    "storage.writeRecovered": "改动已重新保存到磁盘",
+    "focus.finished": "专注完成：{title}（{minutes} 分钟）",
+    "task.started": "已到开始时间：{title}",
+    "task.startedMany": "{count} 个任务已到开始时间",
+    "dailySummary.body": "已完成 {completed} · 逾期 {overdue} · 今日剩余 {remaining}",
+    "markdownSync.conflicts": "Markdown 中有 {count} 处修改无法同步",
+    "recovery.title": "数据文件已损坏",
+    "recovery.restored": "已从备份 {backup} 恢复任务。",
+    "recovery.empty": "没有可用的备份，已以空数据启动。",
+    "recovery.quarantined": "损坏的文件已保留为 {file}。",
+    "recovery.quarantineFailed": "损坏的文件未能移走，请检查数据目录。",
+    "recovery.openBackups": "查看备份",
+    "recovery.keep": "继续使用",
+    "digest.notificationTitle": "{count} 个提醒到期",
 
     "update.found": "发现新版本",
     "update.foundWithVersion": "发现新版本 v{version}",
@@ -311,6 +350,12 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
 
     "alert.deleteFailed": "删除失败",
     "alert.operationFailed": "操作失败",
+    "undo.undone": "已撤销：{action}",
+    "undo.redone": "已重做：{action}",
+    "undo.action.delete_task": "删除任务",
+    "undo.action.delete_tasks": "批量删除",
+    "undo.action.bulk_complete_tasks": "批量完成",
+    "undo.action.delete_project": "删除项目",
     "confirm.uncompleteRepeatTask.title": "取消完成循环任务？",
     "confirm.uncompleteRepeatTask":
       "该任务为循环任务，取消完成不会删除已经生成的下一期任务，仍要继续吗？",
@@ -323,6 +368,7 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "settings.section.notifications": "通知",
     "settings.section.backups": "备份",
     "settings.section.export": "导出",
+    "settings.section.sync": "同步",
     "settings.section.samples": "示例数据",
     "settings.openFailed": "打开设置失败",
     "settings.update": "更新",
@@ -348,6 +394,14 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "settings.shortcutHint": "按下组合键，按 Esc 取消",
     "settings.shortcutNeedModifier": "请包含 Ctrl/Command/Alt/Shift",
     "settings.shortcutNeedKey": "请再按一个非修饰键",
+    "settings.shortcut_quick_add": "快速添加快捷键",
+    "settings.shortcut_toggle_main": "显示/隐藏主窗口快捷键",
+    "settings.shortcut_complete_focus": "完成今日首个焦点任务快捷键",
+    "settings.shortcutUnbound": "未设置，点击录入",
+    "settings.shortcutClear": "清除",
+    "settings.shortcutInvalidKey": "无法识别该组合键",
+    "settings.shortcutConflictApp": "已被“{action}”使用",
+    "settings.shortcutConflictSystem": "系统或其他应用占用：{reason}",
     "settings.theme": "主题",
     "settings.theme.retro": "美式复古（当前）",
     "settings.theme.elegant": "淡雅（白+浅蓝）",
@@ -373,10 +427,19 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "settings.language.zh": "中文",
     "settings.language.en": "English",
     "settings.ai": "AI 自然语言输入",
-    "settings.ai.vendor": "仅支持 DeepSeek",
-    "settings.ai.apiKey": "DeepSeek API Key",
-    "settings.ai.apiKeyPlaceholder": "粘贴你的 DeepSeek API Key",
-    "settings.ai.keyRequired": "请先填写 DeepSeek API Key",
+    "settings.ai.provider": "服务商",
+    "settings.ai.providerDeepseek": "DeepSeek",
+    "settings.ai.providerOpenai": "OpenAI / 兼容接口",
+    "settings.ai.providerAnthropic": "Anthropic",
+    "settings.ai.providerOllama": "Ollama（本地）",
+    "settings.ai.baseUrl": "接口地址",
+    "settings.ai.modelPlaceholder": "留空使用默认模型",
+    "settings.ai.apiKey": "API Key",
+    "settings.ai.apiKeyPlaceholder": "粘贴所选服务商的 API Key",
+    "settings.ai.apiKeyStored": "已保存在系统钥匙串，输入新 Key 可替换",
+    "settings.ai.apiKeyClear": "清除",
+    "settings.ai.keySaveFailed": "API Key 保存失败",
+    "settings.ai.keyRequired": "请先填写 API Key",
     "settings.ai.model": "模型",
     "settings.ai.modelDeepseekChat": "deepseek-chat（默认）",
     "settings.ai.modelDeepseekReasoner": "deepseek-reasoner",
@@ -384,7 +447,7 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "settings.ai.modelMissingFallback":
       "AI 已开启但未选择模型，本次将按普通模式创建任务",
     "settings.ai.keyMissingFallback":
-      "AI 已开启但未填写 DeepSeek API Key，本次将按普通模式创建任务",
+      "AI 已开启但未填写 API Key，本次将按普通模式创建任务",
     "settings.ai.prompt": "系统提示词",
     "settings.ai.promptPlaceholder": "可选：自定义系统提示词（支持占位符）",
     "settings.ai.placeholderTitle": "占位符列表",
@@ -404,6 +467,13 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
       "系统提示词未检测到占位符：已在本次调用自动补齐（建议在设置中保留占位符）",
     "settings.quickBlur": "快捷界面毛玻璃",
     "settings.sound": "提示音",
+    "settings.sound.beep": "嘀",
+    "settings.sound.chime": "风铃",
+    "settings.sound.bell": "铃声",
+    "settings.sound.alarm": "闹钟",
+    "settings.projectSound": "强提醒声音 · {name}",
+    "settings.projectSound.default": "默认",
+    "settings.projectSound.preview": "试听",
     "settings.closeBehavior": "关闭行为",
     "settings.closeBehavior.hide": "隐藏到托盘",
     "settings.closeBehavior.exit": "退出应用",
@@ -423,6 +493,18 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "settings.reminderRepeatMaxTimes.3": "3 次",
     "settings.reminderRepeatMaxTimes.5": "5 次",
     "settings.reminderRepeatMaxTimes.10": "10 次",
+    "settings.reminderDigestThreshold": "合并提醒",
+    "settings.reminderDigestThreshold.never": "从不合并",
+    "settings.reminderDigestThreshold.count": "同时 {count} 个及以上",
+    "settings.reminderEscalateAfter": "忽略后升级为强提醒",
+    "settings.reminderEscalateAfter.never": "从不升级",
+    "settings.dnd": "勿扰模式（仍弹出强提醒）",
+    "settings.quietHours": "免打扰时段",
+    "settings.quietHours.start": "开始时间",
+    "settings.quietHours.end": "结束时间",
+    "settings.dailySummary": "每日总结",
+    "settings.focusEndForced": "专注结束时强提醒",
+    "settings.dailySummary.time": "总结时间",
     "settings.permission.granted": "已授权",
     "settings.permission.denied": "未授权",
     "settings.permission.unknown": "待检查",
@@ -435,7 +517,19 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "settings.backup.weekly": "每周",
     "settings.backup.monthly": "每月",
     "settings.backup.manual": "手动备份",
+    "settings.backup.retention": "保留备份数",
+    "settings.backup.retentionCount": "{count} 份",
+    "settings.backup.compress": "压缩备份（gzip）",
+    "settings.backup.directory": "同时备份到",
+    "settings.backup.directoryChoose": "选择文件夹",
+    "settings.backup.directoryClear": "不再同步",
+    "settings.backup.directoryNone":
+      "未设置（可选 Dropbox / OneDrive 等同步文件夹；不可用时只保存在本地）",
     "settings.backup.list": "备份列表",
+    "settings.storageEngine": "存储引擎",
+    "settings.storageEngine.json": "JSON 文件",
+    "settings.storageEngine.sqlite": "SQLite 数据库",
+    "settings.storageEngine.hint": "切换时迁移一次数据，旧数据保留为一份备份",
     "settings.backup.empty": "暂无备份",
     "settings.backup.restore": "恢复",
     "settings.backup.delete": "删除",
@@ -443,19 +537,78 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "settings.backup.refresh": "刷新",
     "settings.backup.restoreConfirm":
       "恢复将覆盖当前任务数据（不影响设置），确认继续？",
+    "settings.backup.merge": "选择恢复",
+    "settings.backup.mergeTitle": "从 {name} 选择要恢复的内容",
+    "settings.backup.mergeEmpty": "该备份与当前数据没有差异",
+    "settings.backup.mergeApply": "恢复所选（{count}）",
+    "settings.backup.mergeStatus.missing": "当前已删除",
+    "settings.backup.mergeStatus.backup_newer": "备份较新",
+    "settings.backup.mergeStatus.current_newer": "当前较新，将保留当前",
+    "settings.backup.mergeDone": "已恢复 {count} 项，{kept} 项保留当前版本",
     "settings.backup.import": "导入备份",
     "settings.backup.importPlaceholder": "选择备份文件",
     "settings.backup.importAction": "导入恢复",
     "settings.backup.importHintEmpty": "请选择备份文件",
+    "settings.backup.external": "从其他应用导入",
+    "settings.backup.externalAction": "选择 Todoist / TickTick / Markdown 文件",
+    "settings.backup.externalConfirm":
+      "将新增 {tasks} 个任务、{projects} 个清单（现有数据保留）。{warnings}确认导入？",
+    "settings.backup.externalWarnings": "{count} 项无法完整导入。",
+    "settings.backup.externalDone": "已导入 {tasks} 个任务",
+    "settings.backup.markdown": "粘贴 Markdown 清单",
+    "settings.backup.markdownPlaceholder":
+      "粘贴会议纪要，例如：\n## 项目\n- [ ] 任务\n  - [ ] 步骤",
+    "settings.backup.markdownHeadings": "标题用作",
+    "settings.backup.markdownHeadingsProject": "标题作为清单",
+    "settings.backup.markdownHeadingsTag": "标题作为标签",
+    "settings.backup.markdownAction": "导入任务",
+    "settings.trash.retention": "回收站保留",
+    "settings.trash.days": "{days} 天",
+    "settings.trash.keepForever": "不自动清理",
+    "settings.archive.after": "已完成任务归档",
+    "settings.archive.days": "完成 {days} 天后",
+    "settings.archive.never": "不归档",
 
     "settings.export": "导出",
     "settings.export.json": "导出 JSON",
     "settings.export.csv": "导出 CSV",
     "settings.export.md": "导出 Markdown",
+    "settings.export.ics": "导出 ICS 日历",
+    "settings.export.yearReview": "年度回顾",
+    "settings.export.project": "导出范围",
+    "settings.export.allProjects": "全部清单",
+    "settings.export.yearReviewDone": "{year} 年度回顾已生成：完成 {completed} 项任务",
     "settings.export.last": "最近导出",
     "settings.export.copy": "复制路径",
     "settings.export.copied": "已复制导出路径",
     "settings.export.failed": "导出失败：{error}",
+    "settings.export.mdSync": "同步 Markdown 勾选",
+    "settings.export.mdSyncHint":
+      "在导出的 mustdo-sync.md 中勾选任务即完成；结构性修改只提示不应用",
+    "settings.export.mdSyncRewrite": "重新生成同步文件",
+    "settings.export.obsidian": "Obsidian 日记",
+    "settings.export.obsidianFolder": "日记文件夹",
+    "settings.export.obsidianRun": "导出到 Obsidian",
+    "settings.export.obsidianVault": "更换仓库",
+    "settings.export.obsidianHint":
+      "按到期日写入每日笔记，只更新 MustDo 管理的区块",
+    "settings.export.obsidianDone":
+      "已更新 {written} 篇笔记，清理 {cleared} 篇",
+    "settings.sync.url": "CalDAV 地址",
+    "settings.sync.username": "用户名",
+    "settings.sync.password": "密码",
+    "settings.sync.passwordPlaceholder": "已保存时留空",
+    "settings.sync.passwordSaved": "CalDAV 密码已保存",
+    "settings.sync.auto": "自动同步",
+    "settings.sync.interval": "同步间隔",
+    "settings.sync.everyMinutes": "每 {minutes} 分钟",
+    "settings.sync.now": "立即同步",
+    "settings.sync.running": "同步中…",
+    "settings.sync.done":
+      "同步完成：上传 {pushed}，下载 {pulled}，冲突 {conflicts}",
+    "settings.sync.failed": "同步失败：{error}",
+    "settings.sync.last": "上次同步：{time}",
+    "settings.sync.hint": "填写任务列表（VTODO 日历）的地址后即可同步",
 
     "settings.samples": "示例数据",
     "settings.samples.add": "添加 AI 小说助手示例任务",
@@ -549,6 +702,10 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "project.menu.pin": "Pin",
     "project.menu.unpin": "Unpin",
     "project.menu.rename": "Rename",
+    "project.menu.mute": "Mute reminders",
+    "project.menu.muteWeek": "Mute reminders for 7 days",
+    "project.menu.unmute": "Unmute reminders",
+    "project.muted": "Reminders muted",
     "project.confirmDelete.title": 'Delete project "{name}"?',
     "project.confirmDelete.description":
       "Tasks in this project will be moved to Default. This action cannot be undone.",
@@ -626,6 +783,8 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
       "Example: meeting — remind me 30 min early",
     "composer.aiPlanning": "AI is preparing time/project details…",
     "composer.options": "Task options",
+    "composer.pasted": "Pasted text split into {steps} steps and {notes} note",
+    "composer.pastedNotes": "note",
     "composer.due": "Due",
     "composer.reminder": "Reminder",
     "composer.repeat": "Repeat",
@@ -661,6 +820,13 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "reminder.offset.titleBefore": "Remind {label} before due",
     "reminder.offset.before": "Before",
     "reminder.offset.minutes": "min",
+    "reminder.offset.add": "Add another reminder",
+    "reminder.offset.extra": "{minutes} min before",
+    "reminder.offset.remove": "Click to remove this reminder",
+    "reminder.escalate": "Escalate",
+    "reminder.escalate.default": "As in settings",
+    "reminder.escalate.never": "Never",
+    "reminder.escalate.after": "After {count} ignored",
 
     "repeat.none": "None",
     "repeat.daily": "Daily",
@@ -694,6 +860,10 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "repeat.format.weekly": "Weekly ({days})",
     "repeat.format.monthly": "Monthly ({day})",
     "repeat.format.yearly": "Yearly ({month}-{day})",
+    "repeat.format.everyNDays": "Every {n} days",
+    "repeat.format.everyNWeeks": "Every {n} weeks",
+    "repeat.format.everyNWeeksOn": "Every {n} weeks ({days})",
+    "repeat.format.everyNMonths": "Every {n} months ({day})",
 
     "task.markComplete": "Mark complete",
     "task.markIncomplete": "Mark incomplete",
@@ -731,6 +901,7 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "taskEdit.stepRequired": "Enter step text",
     "taskEdit.stepEmpty": "No steps",
     "taskEdit.stepDelete": "Delete step",
+    "taskEdit.stepPromote": "Make it a task",
     "taskEdit.stepMarkComplete": "Mark step complete",
     "taskEdit.stepMarkIncomplete": "Mark step incomplete",
     "taskEdit.close": "Close editor",
@@ -741,6 +912,8 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "banner.snooze1h": "Snooze 1h",
     "banner.snoozeTomorrowMorning": "Tomorrow morning",
     "banner.complete": "Complete",
+    "notification.action.done": "Done",
+    "notification.action.snooze10m": "Snooze 10m",
 
     "forced.title": "Forced alert",
     "forced.queue": "Queue",
@@ -764,6 +937,25 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "forced.time.hoursMins": "{hours}h {mins}m",
     "forced.time.mins": "{mins}m",
     "forced.time.lessThanMin": "< 1m",
+    "digest.title": "{count} reminders due",
+    "digest.dismissAll": "Dismiss all",
+    "markdownSync.completed": "Completed {count} task(s) from Markdown",
+    "dailySummary.title": "Today's summary",
+    "storage.writeFailed": "Changes could not be saved to disk and exist only in memory: {error}",
+    "storage.writeRecovered": "Changes are saved to disk again",
+    "focus.finished": "Focus session done: {title} ({minutes} min)",
+    "task.started": "Ready to start: {title}",
+    "task.startedMany": "{count} tasks are ready to start",
+    "dailySummary.body": "{completed} done · {overdue} overdue · {remaining} left today",
+    "markdownSync.conflicts": "{count} Markdown edit(s) could not be synced",
+    "recovery.title": "Data file was corrupt",
+    "recovery.restored": "Tasks were restored from backup {backup}.",
+    "recovery.empty": "No usable backup was found, so MustDo started empty.",
+    "recovery.quarantined": "The damaged file was kept as {file}.",
+    "recovery.quarantineFailed": "The damaged file could not be moved aside; check the data folder.",
+    "recovery.openBackups": "View backups",
+    "recovery.keep": "Continue",
+    "digest.notificationTitle": "{count} reminders due",
 
     "update.found": "Update available",
     "update.foundWithVersion": "Update available v{version}",
@@ -784,6 +976,12 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
 
     "alert.deleteFailed": "Delete failed",
     "alert.operationFailed": "Operation failed",
+    "undo.undone": "Undone: {action}",
+    "undo.redone": "Redone: {action}",
+    "undo.action.delete_task": "delete task",
+    "undo.action.delete_tasks": "delete tasks",
+    "undo.action.bulk_complete_tasks": "complete tasks",
+    "undo.action.delete_project": "delete project",
     "confirm.uncompleteRepeatTask.title": "Uncomplete repeating task?",
     "confirm.uncompleteRepeatTask":
       "This is a repeating task. Un-completing it won't remove the next instance already created. Continue?",
@@ -796,6 +994,7 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "settings.section.notifications": "Notifications",
     "settings.section.backups": "Backups",
     "settings.section.export": "Export",
+    "settings.section.sync": "Sync",
     "settings.section.samples": "Sample data",
     "settings.openFailed": "Failed to open settings",
     "settings.update": "Updates",
@@ -821,6 +1020,14 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "settings.shortcutHint": "Press a shortcut, Esc to cancel",
     "settings.shortcutNeedModifier": "Include Ctrl/Command/Alt/Shift",
     "settings.shortcutNeedKey": "Press a non-modifier key",
+    "settings.shortcut_quick_add": "Quick add shortcut",
+    "settings.shortcut_toggle_main": "Show/hide main window shortcut",
+    "settings.shortcut_complete_focus": "Complete top focus task shortcut",
+    "settings.shortcutUnbound": "Not set, click to record",
+    "settings.shortcutClear": "Clear",
+    "settings.shortcutInvalidKey": "This key combination is not supported",
+    "settings.shortcutConflictApp": "Already used by \"{action}\"",
+    "settings.shortcutConflictSystem": "Taken by the system or another app: {reason}",
     "settings.theme": "Theme",
     "settings.theme.retro": "American retro (current)",
     "settings.theme.elegant": "Elegant (White/Light blue)",
@@ -846,10 +1053,19 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "settings.language.zh": "Chinese",
     "settings.language.en": "English",
     "settings.ai": "AI (Natural Language)",
-    "settings.ai.vendor": "DeepSeek only",
-    "settings.ai.apiKey": "DeepSeek API Key",
-    "settings.ai.apiKeyPlaceholder": "Paste your DeepSeek API key",
-    "settings.ai.keyRequired": "Please enter your DeepSeek API key first",
+    "settings.ai.provider": "Provider",
+    "settings.ai.providerDeepseek": "DeepSeek",
+    "settings.ai.providerOpenai": "OpenAI / compatible",
+    "settings.ai.providerAnthropic": "Anthropic",
+    "settings.ai.providerOllama": "Ollama (local)",
+    "settings.ai.baseUrl": "Endpoint",
+    "settings.ai.modelPlaceholder": "Leave blank for the default model",
+    "settings.ai.apiKey": "API Key",
+    "settings.ai.apiKeyPlaceholder": "Paste the provider's API key",
+    "settings.ai.apiKeyStored": "Saved in the system keychain; type a new key to replace it",
+    "settings.ai.apiKeyClear": "Clear",
+    "settings.ai.keySaveFailed": "Could not save the API key",
+    "settings.ai.keyRequired": "Please enter your API key first",
     "settings.ai.model": "Model",
     "settings.ai.modelDeepseekChat": "deepseek-chat (default)",
     "settings.ai.modelDeepseekReasoner": "deepseek-reasoner",
@@ -857,7 +1073,7 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "settings.ai.modelMissingFallback":
       "AI is enabled but the model is missing. Falling back to normal task creation.",
     "settings.ai.keyMissingFallback":
-      "AI is enabled but the DeepSeek API key is missing. Falling back to normal task creation.",
+      "AI is enabled but the API key is missing. Falling back to normal task creation.",
     "settings.ai.prompt": "System prompt",
     "settings.ai.promptPlaceholder":
       "Optional: customize the system prompt (supports placeholders)",
@@ -878,6 +1094,13 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
       "No placeholders detected in the system prompt. They will be appended automatically for this run.",
     "settings.quickBlur": "Quick blur",
     "settings.sound": "Sound",
+    "settings.sound.beep": "Beep",
+    "settings.sound.chime": "Chime",
+    "settings.sound.bell": "Bell",
+    "settings.sound.alarm": "Alarm",
+    "settings.projectSound": "Forced reminder sound · {name}",
+    "settings.projectSound.default": "Default",
+    "settings.projectSound.preview": "Preview",
     "settings.closeBehavior": "Close behavior",
     "settings.closeBehavior.hide": "Hide to tray",
     "settings.closeBehavior.exit": "Exit app",
@@ -897,6 +1120,18 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "settings.reminderRepeatMaxTimes.3": "3 times",
     "settings.reminderRepeatMaxTimes.5": "5 times",
     "settings.reminderRepeatMaxTimes.10": "10 times",
+    "settings.reminderDigestThreshold": "Group reminders",
+    "settings.reminderDigestThreshold.never": "Never",
+    "settings.reminderDigestThreshold.count": "{count} or more at once",
+    "settings.reminderEscalateAfter": "Escalate ignored reminders",
+    "settings.reminderEscalateAfter.never": "Never",
+    "settings.dnd": "Do not disturb (forced reminders still show)",
+    "settings.quietHours": "Quiet hours",
+    "settings.quietHours.start": "Start time",
+    "settings.quietHours.end": "End time",
+    "settings.dailySummary": "End-of-day summary",
+    "settings.focusEndForced": "Forced reminder when focus ends",
+    "settings.dailySummary.time": "Summary time",
     "settings.permission.granted": "Granted",
     "settings.permission.denied": "Not granted",
     "settings.permission.unknown": "Checking",
@@ -909,7 +1144,19 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "settings.backup.weekly": "Weekly",
     "settings.backup.monthly": "Monthly",
     "settings.backup.manual": "Backup now",
+    "settings.backup.retention": "Backups to keep",
+    "settings.backup.retentionCount": "{count} backups",
+    "settings.backup.compress": "Compress backups (gzip)",
+    "settings.backup.directory": "Also back up to",
+    "settings.backup.directoryChoose": "Choose folder",
+    "settings.backup.directoryClear": "Stop copying",
+    "settings.backup.directoryNone":
+      "Not set (e.g. a Dropbox / OneDrive folder; backups stay local when it is unavailable)",
     "settings.backup.list": "Backups",
+    "settings.storageEngine": "Storage engine",
+    "settings.storageEngine.json": "JSON file",
+    "settings.storageEngine.sqlite": "SQLite database",
+    "settings.storageEngine.hint": "Switching migrates the data once and keeps the old data as a backup",
     "settings.backup.empty": "No backups",
     "settings.backup.restore": "Restore",
     "settings.backup.delete": "Delete",
@@ -917,19 +1164,79 @@ const STRINGS: Record<AppLanguage, Record<string, string>> = {
     "settings.backup.refresh": "Refresh",
     "settings.backup.restoreConfirm":
       "Restoring will overwrite task data (settings stay). Continue?",
+    "settings.backup.merge": "Pick items",
+    "settings.backup.mergeTitle": "Choose what to restore from {name}",
+    "settings.backup.mergeEmpty": "This backup matches the current data",
+    "settings.backup.mergeApply": "Restore selected ({count})",
+    "settings.backup.mergeStatus.missing": "Deleted since",
+    "settings.backup.mergeStatus.backup_newer": "Backup is newer",
+    "settings.backup.mergeStatus.current_newer": "Current is newer and is kept",
+    "settings.backup.mergeDone": "Restored {count} items, kept {kept} current versions",
     "settings.backup.import": "Import backup",
     "settings.backup.importPlaceholder": "Choose a backup file",
     "settings.backup.importAction": "Import & restore",
     "settings.backup.importHintEmpty": "Choose a backup file",
+    "settings.backup.external": "Import from other apps",
+    "settings.backup.externalAction": "Choose a Todoist / TickTick / Markdown file",
+    "settings.backup.externalConfirm":
+      "This adds {tasks} tasks and {projects} lists (existing data is kept). {warnings}Import?",
+    "settings.backup.externalWarnings": "{count} items could not be imported completely. ",
+    "settings.backup.externalDone": "Imported {tasks} tasks",
+    "settings.backup.markdown": "Paste a Markdown checklist",
+    "settings.backup.markdownPlaceholder":
+      "Paste meeting notes, e.g.\n## Project\n- [ ] Task\n  - [ ] Step",
+    "settings.backup.markdownHeadings": "Use headings as",
+    "settings.backup.markdownHeadingsProject": "Headings as lists",
+    "settings.backup.markdownHeadingsTag": "Headings as tags",
+    "settings.backup.markdownAction": "Import tasks",
+    "settings.trash.retention": "Keep deleted tasks",
+    "settings.trash.days": "{days} days",
+    "settings.trash.keepForever": "Until emptied",
+    "settings.archive.after": "Archive completed tasks",
+    "settings.archive.days": "After {days} days",
+    "settings.archive.never": "Never",
 
     "settings.export": "Export",
     "settings.export.json": "Export JSON",
     "settings.export.csv": "Export CSV",
     "settings.export.md": "Export Markdown",
+    "settings.export.ics": "Export ICS calendar",
+    "settings.export.yearReview": "Year in review",
+    "settings.export.project": "Export scope",
+    "settings.export.allProjects": "All lists",
+    "settings.export.yearReviewDone":
+      "{year} in review is ready: {completed} tasks completed",
     "settings.export.last": "Last export",
     "settings.export.copy": "Copy path",
     "settings.export.copied": "Export path copied",
     "settings.export.failed": "Export failed: {error}",
+    "settings.export.mdSync": "Sync Markdown checkboxes",
+    "settings.export.mdSyncHint":
+      "Checking a task in mustdo-sync.md completes it; structural edits are reported, not applied",
+    "settings.export.mdSyncRewrite": "Rewrite sync file",
+    "settings.export.obsidian": "Obsidian daily notes",
+    "settings.export.obsidianFolder": "Daily notes folder",
+    "settings.export.obsidianRun": "Export to Obsidian",
+    "settings.export.obsidianVault": "Change vault",
+    "settings.export.obsidianHint":
+      "Writes tasks into each due day's note; only the MustDo block is updated",
+    "settings.export.obsidianDone":
+      "Updated {written} notes, cleared {cleared}",
+    "settings.sync.url": "CalDAV URL",
+    "settings.sync.username": "Username",
+    "settings.sync.password": "Password",
+    "settings.sync.passwordPlaceholder": "Leave blank if saved",
+    "settings.sync.passwordSaved": "CalDAV password saved",
+    "settings.sync.auto": "Auto sync",
+    "settings.sync.interval": "Sync interval",
+    "settings.sync.everyMinutes": "Every {minutes} min",
+    "settings.sync.now": "Sync now",
+    "settings.sync.running": "Syncing…",
+    "settings.sync.done":
+      "Synced: {pushed} up, {pulled} down, {conflicts} conflicts",
+    "settings.sync.failed": "Sync failed: {error}",
+    "settings.sync.last": "Last synced: {time}",
+    "settings.sync.hint": "Enter the URL of a task list (VTODO calendar) to sync",
 
     "settings.samples": "Sample data",
     "settings.samples.add": "Add AI Novel sample tasks",
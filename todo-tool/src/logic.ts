@@ -1,4 +1,4 @@
-import { defaultDueAt, isDueToday, isOverdue } from "./scheduler";
+import { defaultDueAt, dueSortKey, isDueToday, isOverdue } from "./scheduler";
 import type { ReminderConfig, Task } from "./types";
 
 export type QuickTab = "todo" | "today" | "all" | "done";
@@ -75,7 +75,7 @@ export function visibleQuickTasks(
     const ao = isOverdue(a, ts) ? 1 : 0;
     const bo = isOverdue(b, ts) ? 1 : 0;
     if (ao !== bo) return bo - ao;
-    if (a.due_at !== b.due_at) return a.due_at - b.due_at;
+    if (a.due_at !== b.due_at) return dueSortKey(a) - dueSortKey(b);
     if (a.important !== b.important) return a.important ? -1 : 1;
     return a.created_at - b.created_at;
   });
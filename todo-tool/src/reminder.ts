@@ -1,5 +1,5 @@
 import type { Translator } from "./i18n";
-import type { ReminderKind, Task } from "./types";
+import type { ReminderKind, ReminderLeadTimes, Task } from "./types";
 
 // Reminder helpers are shared by composer + edit modal (and need to match backend semantics).
 export type ReminderKindOption = { id: ReminderKind; label: string };
@@ -9,15 +9,43 @@ export type ReminderOffsetPreset = {
   minutes: number;
 };
 
-// Must match backend scheduler semantics:
-// snoozed_until > remind_at > default_target (normal: due-10min; forced: due)
-export function getReminderTargetTime(task: Task): number | null {
+// Backend default for `settings.reminder_lead_minutes`.
+export const DEFAULT_REMINDER_LEAD_MINUTES: ReminderLeadTimes = { normal: 10, forced: 0 };
+
+// Every time the reminder should fire, earliest first: one per spec (dated tasks only) plus
+// `remind_at`, or the default target (due minus the lead time for the kind) when both are empty.
+export function getReminderTargets(
+  task: Task,
+  leads: ReminderLeadTimes = DEFAULT_REMINDER_LEAD_MINUTES,
+): number[] {
+  const reminder = task.reminder;
+  if (reminder.kind === "none") return [];
+
+  const targets =
+    task.due_at == null
+      ? []
+      : (reminder.specs ?? []).map((spec) => task.due_at! - spec.minutes_before * 60);
+  if (reminder.remind_at != null) targets.push(reminder.remind_at);
+  if (targets.length === 0 && task.due_at != null) {
+    targets.push(task.due_at - leads[reminder.kind] * 60);
+  }
+  return Array.from(new Set(targets)).sort((a, b) => a - b);
+}
+
+// Must match backend scheduler semantics: snoozed_until wins, otherwise the latest target that
+// has been reached, otherwise the first one still ahead.
+export function getReminderTargetTime(
+  task: Task,
+  leads: ReminderLeadTimes = DEFAULT_REMINDER_LEAD_MINUTES,
+  nowSeconds: number = Math.floor(Date.now() / 1000),
+): number | null {
   const reminder = task.reminder;
   if (reminder.kind === "none") return null;
+  if (reminder.snoozed_until != null) return reminder.snoozed_until;
 
-  const defaultTarget =
-    reminder.kind === "normal" ? task.due_at - 10 * 60 : task.due_at;
-  return reminder.snoozed_until ?? reminder.remind_at ?? defaultTarget;
+  const targets = getReminderTargets(task, leads);
+  const reached = targets.filter((target) => target <= nowSeconds);
+  return reached.length > 0 ? reached[reached.length - 1] : (targets[0] ?? null);
 }
 
 export function buildReminderKindOptions(t: Translator): ReminderKindOption[] {
@@ -43,32 +71,55 @@ export function buildReminderOffsetPresets(
   ];
 }
 
-export function getReminderOffsetMinutes(task: Task): number {
-  if (task.reminder.kind === "none") return 0;
+export function getReminderOffsetMinutes(
+  task: Task,
+  leads: ReminderLeadTimes = DEFAULT_REMINDER_LEAD_MINUTES,
+): number {
+  if (task.reminder.kind === "none" || task.due_at == null) return 0;
+
+  if (task.reminder.remind_at == null && task.reminder.specs?.length) {
+    // The spec closest to the due time is the one the editor shows.
+    return Math.min(...task.reminder.specs.map((spec) => spec.minutes_before));
+  }
 
-  // Default offset: normal = due-10min; forced = due.
-  const defaultRemindAt =
-    task.reminder.kind === "normal" ? task.due_at - 10 * 60 : task.due_at;
+  // Default offset: the configured lead time for the reminder kind.
+  const defaultRemindAt = task.due_at - leads[task.reminder.kind] * 60;
   const remindAt = task.reminder.remind_at ?? defaultRemindAt;
 
   const offset = Math.round((task.due_at - remindAt) / 60);
   return Math.max(0, offset);
 }
 
+// The task's other reminders (besides the one `getReminderOffsetMinutes` reports), in minutes
+// before due, so editing or rescheduling keeps them.
+export function getExtraReminderOffsets(task: Task): number[] {
+  if (task.reminder.kind === "none" || task.due_at == null) return [];
+  const primary = getReminderOffsetMinutes(task);
+  return (task.reminder.specs ?? [])
+    .map((spec) => spec.minutes_before)
+    .filter((minutes) => minutes !== primary);
+}
+
 export function buildReminderConfig(
   kind: ReminderKind,
   dueAtSeconds: number,
   offsetMinutes: number,
   nowSeconds: number = Math.floor(Date.now() / 1000),
+  extraOffsets: number[] = [],
 ): Task["reminder"] {
   if (kind === "none") {
     return { kind: "none", forced_dismissed: false };
   }
 
+  // The backend folds a whole-minute `remind_at` into `specs` on save.
   const remindAt = Math.max(dueAtSeconds - offsetMinutes * 60, nowSeconds);
+  const specs = Array.from(new Set(extraOffsets.filter((minutes) => minutes !== offsetMinutes)))
+    .sort((a, b) => b - a)
+    .map((minutes) => ({ minutes_before: minutes }));
   return {
     kind,
     remind_at: remindAt,
+    specs,
     snoozed_until: undefined,
     forced_dismissed: false,
     last_fired_at: undefined,
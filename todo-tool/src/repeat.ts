@@ -47,20 +47,31 @@ export function defaultRepeatRule(type: RepeatRule["type"]): RepeatRule {
 
 export function formatRepeatRule(rule: RepeatRule, t: Translator): string {
   const weekdayOptions = buildWeekdayOptions(t);
+  const formatDays = (days: number[]) =>
+    days
+      .map((day) => weekdayOptions.find((opt) => opt.id === day)?.label ?? String(day))
+      .join(", ");
   switch (rule.type) {
     case "none":
       return t("repeat.format.none");
     case "daily":
       return rule.workday_only ? t("repeat.format.dailyWorkday") : t("repeat.format.daily");
     case "weekly":
-      return t("repeat.format.weekly", {
-        days: rule.days
-          .map((day) => weekdayOptions.find((opt) => opt.id === day)?.label ?? String(day))
-          .join(", "),
-      });
+      return t("repeat.format.weekly", { days: formatDays(rule.days) });
     case "monthly":
       return t("repeat.format.monthly", { day: rule.day });
     case "yearly":
       return t("repeat.format.yearly", { month: rule.month, day: rule.day });
+    case "every_n_days":
+      return t("repeat.format.everyNDays", { n: Math.max(1, rule.n) });
+    case "every_n_weeks":
+      return rule.days.length > 0
+        ? t("repeat.format.everyNWeeksOn", {
+            n: Math.max(1, rule.n),
+            days: formatDays(rule.days),
+          })
+        : t("repeat.format.everyNWeeks", { n: Math.max(1, rule.n) });
+    case "every_n_months":
+      return t("repeat.format.everyNMonths", { n: Math.max(1, rule.n), day: rule.day });
   }
 }
@@ -1,6 +1,6 @@
 import type { Task } from "./types";
 
-import { buildReminderConfig, getReminderOffsetMinutes } from "./reminder";
+import { buildReminderConfig, getExtraReminderOffsets, getReminderOffsetMinutes } from "./reminder";
 import { nextWorkdayAtLocalTime, tomorrowAtLocalTime } from "./timePresets";
 
 export type ReschedulePresetId =
@@ -11,7 +11,7 @@ export type ReschedulePresetId =
 
 export function computeRescheduleDueAt(task: Task, preset: ReschedulePresetId, now: Date = new Date()): number {
   const nowSeconds = Math.floor(now.getTime() / 1000);
-  const base = Math.max(task.due_at, nowSeconds);
+  const base = Math.max(task.due_at ?? nowSeconds, nowSeconds);
 
   if (preset === "plus10m") return base + 10 * 60;
   if (preset === "plus1h") return base + 60 * 60;
@@ -22,7 +22,7 @@ export function computeRescheduleDueAt(task: Task, preset: ReschedulePresetId, n
 export function rescheduleTask(task: Task, nextDueAt: number, nowSeconds: number): Task {
   const kind = task.reminder.kind;
   const offset = getReminderOffsetMinutes(task);
-  const reminder = buildReminderConfig(kind, nextDueAt, offset, nowSeconds);
+  const reminder = buildReminderConfig(kind, nextDueAt, offset, nowSeconds, getExtraReminderOffsets(task));
 
   return {
     ...task,
@@ -1,14 +1,25 @@
 import type { Task } from "./types";
 
+// Undated tasks sort after every dated one (matches the backend's due ordering).
+export function dueSortKey(task: Task) {
+  return task.due_at ?? Number.MAX_SAFE_INTEGER;
+}
+
 export function sortByDue(tasks: Task[]) {
-  return [...tasks].sort((a, b) => a.due_at - b.due_at);
+  return [...tasks].sort((a, b) => dueSortKey(a) - dueSortKey(b));
 }
 
 export function isOverdue(task: Task, now: number) {
-  return !task.completed && task.due_at < now;
+  return !task.completed && !task.someday && task.due_at != null && task.due_at < now;
+}
+
+// Open and waiting for a start date that is still ahead (matches the backend's `is_scheduled`).
+export function isScheduled(task: Task, now: number) {
+  return !task.completed && task.start_at != null && task.start_at > now;
 }
 
 export function isDueToday(task: Task, now: Date) {
+  if (task.due_at == null) return false;
   const due = new Date(task.due_at * 1000);
   return (
     due.getFullYear() === now.getFullYear() &&
@@ -18,6 +29,7 @@ export function isDueToday(task: Task, now: Date) {
 }
 
 export function isDueTomorrow(task: Task, now: Date) {
+  if (task.due_at == null) return false;
   const due = new Date(task.due_at * 1000);
   const tomorrow = new Date(now);
   tomorrow.setDate(tomorrow.getDate() + 1);
@@ -29,6 +41,7 @@ export function isDueTomorrow(task: Task, now: Date) {
 }
 
 export function isDueThisWeek(task: Task, now: Date) {
+  if (task.due_at == null) return false;
   const due = new Date(task.due_at * 1000);
   const start = new Date(now);
   const day = start.getDay();
@@ -41,6 +54,7 @@ export function isDueThisWeek(task: Task, now: Date) {
 }
 
 export function isDueInFuture(task: Task, now: Date) {
+  if (task.due_at == null) return true;
   const due = new Date(task.due_at * 1000);
   const endTomorrow = new Date(now);
   endTomorrow.setDate(endTomorrow.getDate() + 1);
@@ -1,15 +1,13 @@
-import { tomorrowAtLocalTime } from "./timePresets";
-
-export type SnoozePresetId = "m5" | "m15" | "h1" | "tomorrow0900";
-
-export function computeSnoozeUntilSeconds(
-  preset: SnoozePresetId,
-  now: Date = new Date(),
-): number {
-  const nowSeconds = Math.floor(now.getTime() / 1000);
-  if (preset === "m5") return nowSeconds + 5 * 60;
-  if (preset === "m15") return nowSeconds + 15 * 60;
-  if (preset === "h1") return nowSeconds + 60 * 60;
-  return tomorrowAtLocalTime(now, 9, 0);
-}
+// Snooze times are worked out by the backend (`snooze_task_preset`) from the work-hour settings,
+// so every window lands on the same time for the same preset.
+export type SnoozePresetId =
+  | "in_5m"
+  | "in_10m"
+  | "in_15m"
+  | "in_1h"
+  | "this_evening"
+  | "tomorrow_morning"
+  | "next_monday";
 
+// Buttons on the native reminder notification (`notification_action`).
+export type NotificationActionId = "complete" | "snooze_10m";
@@ -0,0 +1,89 @@
+import { previewSound } from "./api";
+import type { SoundClip } from "./types";
+
+// The backend's bundled sounds (`BUNDLED_SOUNDS`), synthesized here as [hz, seconds] notes;
+// 0 Hz is a pause.
+const TONES: Record<string, Array<[number, number]>> = {
+  beep: [[880, 0.3]],
+  chime: [
+    [660, 0.15],
+    [880, 0.15],
+    [1320, 0.3],
+  ],
+  bell: [[1046, 0.6]],
+  alarm: [
+    [988, 0.12],
+    [0, 0.08],
+    [988, 0.12],
+    [0, 0.08],
+    [988, 0.12],
+  ],
+};
+
+export function playTone(tone: string) {
+  const notes = TONES[tone] ?? TONES.beep;
+  try {
+    const context = new AudioContext();
+    const gain = context.createGain();
+    gain.gain.value = 0.15;
+    gain.connect(context.destination);
+    let at = context.currentTime;
+    let last: OscillatorNode | null = null;
+    for (const [frequency, seconds] of notes) {
+      if (frequency > 0) {
+        const oscillator = context.createOscillator();
+        oscillator.type = "sine";
+        oscillator.frequency.value = frequency;
+        oscillator.connect(gain);
+        oscillator.start(at);
+        oscillator.stop(at + seconds);
+        last = oscillator;
+      }
+      at += seconds;
+    }
+    if (last) {
+      last.onended = () => {
+        context.close();
+      };
+    }
+  } catch {
+    // Ignore audio errors in restricted environments.
+  }
+}
+
+function playClip(clip: SoundClip) {
+  if (clip.tone) {
+    playTone(clip.tone);
+    return;
+  }
+  const url = URL.createObjectURL(
+    new Blob([new Uint8Array(clip.bytes)], {
+      type: clip.mime ?? "audio/wav",
+    }),
+  );
+  const audio = new Audio(url);
+  audio.onended = () => URL.revokeObjectURL(url);
+  void audio.play().catch(() => {
+    URL.revokeObjectURL(url);
+    playTone("beep");
+  });
+}
+
+// Plays a sound picked in settings (`preview_sound`); anything that cannot be loaded, such as
+// a user file that was removed, falls back to the default beep.
+export async function playSound(soundId?: string | null) {
+  if (!soundId) {
+    playTone("beep");
+    return;
+  }
+  try {
+    const res = await previewSound(soundId);
+    if (res.ok && res.data) {
+      playClip(res.data);
+      return;
+    }
+  } catch {
+    // Fall through to the default sound.
+  }
+  playTone("beep");
+}
@@ -0,0 +1,36 @@
+import type { Step } from "./types";
+
+// Nesting depth of every step (0 = top level). Unknown or cyclic parents count as top level,
+// matching how the backend normalizes them.
+export function stepDepths(steps: Step[]): Map<string, number> {
+  const parents = new Map(steps.map((step) => [step.id, step.parent_id ?? null]));
+  const depths = new Map<string, number>();
+  for (const step of steps) {
+    let depth = 0;
+    const seen = new Set([step.id]);
+    let parent = parents.get(step.id) ?? null;
+    while (parent && parents.has(parent) && !seen.has(parent)) {
+      seen.add(parent);
+      depth += 1;
+      parent = parents.get(parent) ?? null;
+    }
+    depths.set(step.id, depth);
+  }
+  return depths;
+}
+
+// `stepId` and every step nested under it.
+export function stepSubtreeIds(steps: Step[], stepId: string): Set<string> {
+  const ids = new Set([stepId]);
+  let grew = true;
+  while (grew) {
+    grew = false;
+    for (const step of steps) {
+      if (step.parent_id && ids.has(step.parent_id) && !ids.has(step.id)) {
+        ids.add(step.id);
+        grew = true;
+      }
+    }
+  }
+  return ids;
+}
@@ -5,22 +5,46 @@ export type RepeatRule =
   | { type: "daily"; workday_only: boolean }
   | { type: "weekly"; days: number[] }
   | { type: "monthly"; day: number }
-  | { type: "yearly"; month: number; day: number };
+  | { type: "yearly"; month: number; day: number }
+  | { type: "every_n_days"; n: number }
+  | { type: "every_n_weeks"; n: number; days: number[] }
+  | { type: "every_n_months"; n: number; day: number };
+
+export interface ReminderLeadTimes {
+  normal: number;
+  forced: number;
+}
+
+/** A reminder relative to the due time. */
+export interface ReminderSpec {
+  minutes_before: number;
+}
 
 export interface ReminderConfig {
   kind: ReminderKind;
+  /** One reminder per spec; only dated tasks use them. */
+  specs?: ReminderSpec[];
+  /** An absolute reminder time (undated tasks, or "as soon as possible"). */
   remind_at?: number;
   snoozed_until?: number;
   forced_dismissed: boolean;
   last_fired_at?: number;
   repeat_fired_count?: number;
+  email_sent_at?: number;
+  /** Normal firings since the last snooze. */
+  ignored_count?: number;
+  /** Raised from normal to forced after being ignored; the next repeat is normal again. */
+  escalated?: boolean;
 }
 
 export type CloseBehavior = "hide_to_tray" | "exit";
 export type MinimizeBehavior = "hide_to_tray" | "minimize";
 export type BackupSchedule = "none" | "daily" | "weekly" | "monthly";
+export type StorageEngine = "json" | "sqlite";
 export type UpdateBehavior = "auto" | "next_restart" | "disabled";
 
+export type AiProviderKind = "deepseek" | "openai" | "anthropic" | "ollama";
+
 export interface WindowBounds {
   x: number;
   y: number;
@@ -34,6 +58,64 @@ export interface Step {
   completed: boolean;
   created_at: number;
   completed_at?: number;
+  /** The step this one is nested under; absent for top-level steps. */
+  parent_id?: string | null;
+}
+
+export interface TaskProgress {
+  steps_completed: number;
+  steps_total: number;
+  percent_complete: number;
+}
+
+export interface Comment {
+  id: string;
+  text: string;
+  created_at: number;
+}
+
+// A file copied into the data directory's attachments/ folder.
+export interface Attachment {
+  id: string;
+  name: string;
+  file: string;
+  size: number;
+  added_at: number;
+}
+
+// One stretch of tracked time; `ended_at` is null while the timer runs.
+export interface TimeEntry {
+  started_at: number;
+  ended_at: number | null;
+}
+
+export type TimeReportGroup = "day" | "project" | "tag";
+
+export interface TimeReportRow {
+  /** Local `YYYY-MM-DD`, a project id, or a tag (`""` for untagged tasks). */
+  key: string;
+  seconds: number;
+  task_count: number;
+}
+
+export interface DependencyNode {
+  id: string;
+  title: string;
+  completed: boolean;
+  /** Still waiting on an open task of its own. */
+  blocked: boolean;
+  /** Fewest dependency hops from the queried task (0 for the task itself). */
+  depth: number;
+}
+
+export interface UnblockChain {
+  task_id: string;
+  /** Blockers before the tasks they block, ending with the queried task. */
+  nodes: DependencyNode[];
+  /** `from` blocks `to`. */
+  edges: { from: string; to: string }[];
+  /** Open blockers that can be worked on right now. */
+  actionable: string[];
 }
 
 export interface Project {
@@ -44,13 +126,57 @@ export interface Project {
   created_at: number;
   updated_at: number;
   sample_tag?: string;
+  reminders_muted?: boolean;
+  muted_until?: number | null;
+  /** Sound for forced reminders (`list_sounds` id); unset plays the default beep. */
+  notification_sound?: string | null;
+  /** `#rrggbb` accent for the sidebar dot. */
+  color?: string | null;
+  /** A built-in icon id (see `PROJECT_ICONS` in MainView) or an emoji. */
+  icon?: string | null;
+  /** Folder this project is nested in; unset/null is top-level. */
+  parent_id?: string | null;
+}
+
+/** One entry of `project_tree`; `open_tasks` counts only the project's own tasks. */
+export interface ProjectNode {
+  project: Project;
+  open_tasks: number;
+  children: ProjectNode[];
+}
+
+export interface SoundEntry {
+  id: string;
+  name: string;
+  bundled: boolean;
+}
+
+/** A bundled tone (`tone`) or a user file's bytes. */
+// Why a shortcut is refused by `validate_shortcut`: another action of this app
+// (its settings field) or the OS / another application.
+export type ShortcutConflict =
+  | { kind: "app"; action: string }
+  | { kind: "system"; reason: string };
+
+export interface ShortcutCheck {
+  shortcut: string;
+  valid: boolean;
+  error: string | null;
+  conflicts: ShortcutConflict[];
+}
+
+export interface SoundClip {
+  id: string;
+  tone: string | null;
+  mime: string | null;
+  bytes: number[];
 }
 
 export interface Task {
   id: string;
   project_id: string;
   title: string;
-  due_at: number;
+  due_at: number | null;
   important: boolean;
   completed: boolean;
   completed_at?: number;
@@ -64,19 +190,392 @@ export interface Task {
   sample_tag?: string;
   reminder: ReminderConfig;
   repeat: RepeatRule;
+  waiting_on?: string;
+  waiting_since?: number;
+  series_id?: string;
+  pinned?: boolean;
+  context?: string;
+  someday?: boolean;
+  spawn_ahead?: number;
+  /** Next occurrence counts from the completion day instead of the due date. */
+  repeat_from_completion?: boolean;
+  /** The series stops once this many occurrences exist. */
+  end_after_occurrences?: number | null;
+  /** No occurrence is created that would be due after this timestamp. */
+  end_by_date?: number | null;
+  /** 0-based position in the repeat series. */
+  occurrence_index?: number;
+  /** IANA zone repeats are computed in; unset means the system zone. */
+  timezone?: string | null;
+  /** Overrides `settings.reminder_escalate_after`; 0 never escalates. */
+  escalate_after?: number | null;
+  /** Kept out of the today/week views until this time. */
+  start_at?: number | null;
+  carry_over_count?: number;
+  skip_carry_over?: boolean;
+  /** `#rrggbb`, validated by the backend. */
+  color?: string | null;
+  emoji?: string | null;
+  estimate_minutes?: number | null;
+  comments?: Comment[];
+  attachments?: Attachment[];
+  time_entries?: TimeEntry[];
+  /** Ids of tasks that must be completed first; reminders wait until they are. */
+  blocked_by?: string[];
+  progress?: TaskProgress;
+}
+
+export interface WaitingForEntry {
+  task: Task;
+  waiting_since: number;
+  pending_secs: number;
+}
+
+export interface WaitingForGroup {
+  person: string;
+  oldest_pending_secs: number;
+  tasks: WaitingForEntry[];
+}
+
+export type BurndownRange = "week" | "month" | "quarter";
+
+export interface BurndownPoint {
+  date: string;
+  open: number;
+  completed: number;
+}
+
+export interface YearReview {
+  year: number;
+  total_completed: number;
+  total_created: number;
+  months: { month: number; created: number; completed: number }[];
+  busiest_weeks: { week_start: string; completed: number }[];
+  top_projects: { key: string; label: string; count: number }[];
+  top_tags: { key: string; label: string; count: number }[];
+  longest_streak: { days: number; start: string | null; end: string | null };
+}
+
+export interface YearReviewExport {
+  markdown_path: string;
+  html_path: string;
+  review: YearReview;
+}
+
+export interface Burndown {
+  project_id: string | null;
+  range: BurndownRange;
+  points: BurndownPoint[];
+  remaining: number;
+  completion_rate: number;
+  forecast_days: number | null;
+  forecast_date: string | null;
+}
+
+// Dashboard numbers over [from, to); see `get_statistics`.
+export interface Statistics {
+  from: number;
+  to: number;
+  created: number;
+  completed: number;
+  /** Share of the tasks due in the range that are completed; null when nothing was due. */
+  completion_rate: number | null;
+  current_streak: number;
+  longest_streak: { days: number; start: string | null; end: string | null };
+  projects: {
+    project_id: string;
+    name: string;
+    created: number;
+    completed: number;
+    open: number;
+  }[];
+  average_delay_minutes: number | null;
+  completed_late: number;
+  /** Seven entries, Monday (1) first. */
+  weekdays: { weekday: number; completed: number }[];
+  busiest_weekdays: number[];
+}
+
+export interface FocusTimeRow {
+  /** Local `YYYY-MM-DD`, a project id, or a tag (`""` for untagged tasks). */
+  key: string;
+  /** From task timers. */
+  tracked_seconds: number;
+  /** From focus sessions, pauses left out. */
+  focus_seconds: number;
+}
+
+// Timer and focus time over [from, to); see `get_focus_stats`.
+export interface FocusStats {
+  from: number;
+  to: number;
+  tracked_seconds: number;
+  focus_seconds: number;
+  focus_sessions: number;
+  finished_sessions: number;
+  days: FocusTimeRow[];
+  projects: FocusTimeRow[];
+  tags: FocusTimeRow[];
+  /** Estimated tasks worked on in the range, furthest over their estimate first. */
+  estimates: {
+    task_id: string;
+    title: string;
+    estimate_minutes: number;
+    /** All time spent on the task so far, not only in the range. */
+    actual_minutes: number;
+  }[];
+}
+
+// Stored under reviews/weekly-<date>.json; see `ai_weekly_review`.
+export interface WeeklyReview {
+  generated_at: number;
+  /** [from, to): the seven days before generation. */
+  from: number;
+  to: number;
+  completed_count: number;
+  overdue_count: number;
+  summary: string;
+  wins: string[];
+  slipped: string[];
+  next_week_focus: string[];
+  /** Missing in reviews saved before focus time was added. */
+  focus?: FocusStats | null;
+}
+
+export interface SavedWeeklyReview {
+  path: string;
+  review: WeeklyReview;
+}
+
+export type SuggestionReason =
+  | { kind: "overdue"; minutes: number }
+  | { kind: "due_soon"; minutes: number }
+  | { kind: "important" }
+  | { kind: "fits_today"; estimate_minutes: number; remaining_minutes: number }
+  | { kind: "quick_win"; estimate_minutes: number }
+  | {
+      kind: "too_long_for_today";
+      estimate_minutes: number;
+      remaining_minutes: number;
+    }
+  | { kind: "stale"; days: number };
+
+export interface Suggestion {
+  task: Task;
+  score: number;
+  reasons: SuggestionReason[];
+}
+
+export type SearchMatchKind = "prefix" | "word" | "substring";
+export type SearchField = "title" | "tag" | "step" | "notes";
+
+/** `[start, end)` in UTF-16 units of the field text, usable with `String.slice`. */
+export interface MatchSpan {
+  start: number;
+  end: number;
+}
+
+export interface FieldMatch {
+  field: SearchField;
+  /** Position in `tags` / `steps`; null for title and notes. */
+  index: number | null;
+  spans: MatchSpan[];
+}
+
+export interface SearchHit {
+  task: Task;
+  score: number;
+  kinds: SearchMatchKind[];
+  matches: FieldMatch[];
+}
+
+// A deleted task; purged after `trash_retention_days` unless restored.
+export interface TrashEntry {
+  task: Task;
+  deleted_at: number;
+}
+
+export interface UndoSummary {
+  /** Command that made the change, e.g. `delete_project`. */
+  action: string;
+  at: number;
+  tasks: number;
+  projects: number;
+}
+
+// What the next undo / redo would do; null when that stack is empty.
+export interface UndoStatus {
+  undo: UndoSummary | null;
+  redo: UndoSummary | null;
+}
+
+export interface TaskChange {
+  before: Task;
+  after: Task;
+  fields: string[];
 }
 
+// Returned by commands called with `dryRun: true`.
+export interface ChangeSet {
+  created: Task[];
+  updated: TaskChange[];
+  deleted: Task[];
+  projects_created: Project[];
+  projects_updated: Project[];
+  projects_deleted: Project[];
+}
+
+// How a backup record compares with the current one of the same id.
+export type MergeStatus = "missing" | "backup_newer" | "current_newer";
+
+export interface MergeTask {
+  task: Task;
+  current: Task | null;
+  status: MergeStatus;
+  fields: string[];
+}
+
+export interface MergeProject {
+  project: Project;
+  current: Project | null;
+  status: MergeStatus;
+}
+
+// `preview_backup_merge` result; records only in the current data are not listed.
+export interface MergePreview {
+  tasks: MergeTask[];
+  projects: MergeProject[];
+}
+
+// `merge_backup` result: what was written, and selected ids whose newer current copy was kept.
+export interface MergePlan {
+  tasks: Task[];
+  projects: Project[];
+  kept_current: string[];
+}
+
+export type ExternalFormat = "todoist_csv" | "tick_tick_json" | "markdown";
+
+// What the headings above a pasted checkbox list become.
+export type MarkdownHeadings = "project" | "tag";
+
+// `import_external` result; `changes` lists what was (or, with dryRun, would be) added.
+export interface ExternalImportReport {
+  format: ExternalFormat;
+  dry_run: boolean;
+  changes: ChangeSet;
+  warnings: string[];
+}
+
+export type SyncConflictKind =
+  | "added"
+  | "removed"
+  | "duplicated"
+  | "unknown_task"
+  | "text_changed"
+  | "unchecked"
+  | "task_missing";
+
+export interface SyncConflict {
+  kind: SyncConflictKind;
+  line: number | null;
+  task_id: string | null;
+  text: string;
+}
+
+export interface TaskDraft {
+  title: string;
+  due_at?: number | null;
+  important: boolean;
+  tags: string[];
+  steps: string[];
+  notes?: string | null;
+  // 0 when the field was not found in the text.
+  confidence: {
+    title: number;
+    due_at: number;
+    important: number;
+    tags: number;
+    steps: number;
+  };
+}
+
+export interface EventSpec {
+  name: string;
+  version: number;
+  payload: string;
+  description: string;
+}
+
+export interface EventsManifest {
+  manifest_version: number;
+  events: EventSpec[];
+  schemas: { name: string; fields: { name: string; type: string }[] }[];
+}
+
+export interface RecoveryStatus {
+  detected_at: number;
+  error: string;
+  quarantined_as?: string | null;
+  recovered_from?: string | null;
+  skipped_backups: string[];
+}
+
+// Payload of the `write_status` event and `get_write_status`: edits not on disk yet and why
+// the last background write failed (null once a write succeeds again).
+export interface WriteStatus {
+  pending: boolean;
+  last_error?: string | null;
+}
+
+// Payload of the `daily_summary` event (settings.daily_summary_enabled).
+export interface DailySummary {
+  date: string;
+  generated_at: number;
+  completed: number;
+  overdue: number;
+  remaining: number;
+}
+
+export interface MarkdownSyncReport {
+  completed: string[];
+  conflicts: SyncConflict[];
+}
+
+export interface ObsidianExportReport {
+  written: string[];
+  cleared: string[];
+  tasks: number;
+}
+
+export interface ContextSummary {
+  context: string;
+  open_count: number;
+  total_count: number;
+}
+
+export type NoDatePolicy = "today" | "tomorrow" | "someday";
+
 export interface Settings {
   shortcut: string;
+  shortcut_quick_add: string;
+  shortcut_toggle_main: string;
+  shortcut_complete_focus: string;
   theme: string;
   ui_radius: "theme" | "sharp" | "round";
   ui_border: "theme" | "thin" | "thick";
   ui_shadow: "theme" | "none" | "soft" | "strong";
   language: "auto" | "zh" | "en";
   ai_enabled: boolean;
-  deepseek_api_key: string;
+  ai_provider: AiProviderKind;
+  // Empty uses the provider's default endpoint.
+  ai_base_url: string;
+  // Whether the selected provider's key is stored in the OS keychain (`setSecret`); always true
+  // for providers without one.
+  ai_api_key_set: boolean;
   ai_model: string;
   ai_prompt: string;
+  ai_include_comments?: boolean;
   update_behavior: UpdateBehavior;
   sound_enabled: boolean;
   close_behavior: CloseBehavior;
@@ -86,20 +585,380 @@ export interface Settings {
   quick_bounds?: WindowBounds;
   quick_tab: string;
   quick_sort: string;
+  quick_default_due_time?: string;
+  quick_due_rounding_minutes?: number;
+  quick_no_date_policy?: NoDatePolicy;
   forced_reminder_color: string;
   backup_schedule: BackupSchedule;
+  // How many backups are kept (1-100, default 5).
+  backup_retention?: number;
+  // New backups are written as gzip-compressed `.json.gz` files.
+  backup_compress?: boolean;
+  // Extra folder (e.g. Dropbox) that also receives each backup; "" = none.
+  backup_directory?: string;
+  storage_engine?: StorageEngine;
   last_backup_at?: number;
   today_focus_date?: string;
   today_focus_ids: string[];
   today_prompted_date?: string;
   reminder_repeat_interval_sec: number;
   reminder_repeat_max_times: number;
+  reminder_digest_threshold?: number;
+  /** Ignored normal reminders become forced after this many firings; 0 is off. */
+  reminder_escalate_after?: number;
+  reminder_lead_minutes?: ReminderLeadTimes;
+  email_reminder_enabled?: boolean;
+  smtp_host?: string;
+  smtp_port?: number;
+  smtp_security?: SmtpSecurity;
+  smtp_username?: string;
+  email_from?: string;
+  email_to?: string;
+  email_unacked_minutes?: number;
+  email_when_headless?: boolean;
+  quadrant_config?: QuadrantConfig;
+  view_prefs?: Record<string, ViewPrefs>;
+  /** Saved filters; changed only through the smart list commands. */
+  smart_lists?: SmartList[];
+  /** If-this-then-that rules; changed only through the automation rule commands. */
+  automation_rules?: AutomationRule[];
+  week_starts_on?: WeekStart;
+  date_format?: DateFormat;
+  overdue_grace_minutes?: number;
+  work_day_start?: string;
+  work_day_end?: string;
+  /** Holds normal reminders until turned off; forced reminders still fire. */
+  dnd_enabled?: boolean;
+  quiet_hours_enabled?: boolean;
+  /** Local `HH:MM`; the range may wrap past midnight. */
+  quiet_hours_start?: string;
+  quiet_hours_end?: string;
+  last_maintenance_at?: number;
+  carry_over_enabled?: boolean;
+  last_carry_over_at?: number;
+  daily_summary_enabled?: boolean;
+  daily_summary_time?: string;
+  last_daily_summary_at?: number;
+  focus_end_forced_reminder?: boolean;
+  markdown_sync_enabled?: boolean;
+  obsidian_vault_path?: string;
+  obsidian_daily_folder?: string;
+  mqtt_enabled?: boolean;
+  mqtt_host?: string;
+  mqtt_port?: number;
+  mqtt_tls?: boolean;
+  mqtt_username?: string;
+  mqtt_topic_prefix?: string;
+  caldav_enabled?: boolean;
+  caldav_url?: string;
+  caldav_username?: string;
+  caldav_sync_interval_minutes?: number;
+  last_caldav_sync_at?: number | null;
+  trash_retention_days?: number;
+  // Completed tasks move to the yearly archive files after this many days; 0 = never.
+  archive_after_days?: number;
+}
+
+// Returned by `sync_now`; failed requests are listed in `errors` and retried next run.
+export interface SyncReport {
+  pushed: number;
+  pulled: number;
+  deleted_local: number;
+  deleted_remote: number;
+  conflicts: number;
+  errors: string[];
+}
+
+export interface MaintenanceReport {
+  ran_at: number;
+  removed_temp_files: number;
+  compacted_log_lines: number;
+  pruned_view_prefs: number;
+  archived_tasks: number;
+  errors: string[];
+}
+
+export type ExportFormat = "json" | "csv" | "markdown" | "ics";
+
+// Optional narrowing for the exporters. `from`/`to` range over completed_at for
+// completed tasks and due_at for open ones (`to` exclusive).
+export interface ExportFilter {
+  project_id?: string | null;
+  tag?: string | null;
+  status?: "all" | "open" | "completed";
+  from?: number | null;
+  to?: number | null;
+}
+
+export interface ExportContent {
+  format: ExportFormat;
+  mime_type: string;
+  content: string;
+}
+
+export interface DataDirProgress {
+  item: string;
+  done: number;
+  total: number;
+}
+
+export interface ArchiveEstimate {
+  files: number;
+  bytes: number;
+}
+
+export interface ArchiveProgress {
+  item: string;
+  done: number;
+  total: number;
+  bytes_done: number;
+  bytes_total: number;
+}
+
+export interface Profile {
+  id: string;
+  name: string;
+  created_at: number;
+}
+
+export interface ProfileRegistry {
+  active: string;
+  profiles: Profile[];
+}
+
+export type WeekStart = "monday" | "sunday" | "saturday";
+export type DateFormat = "ymd" | "mdy" | "dmy";
+
+export type TaskSortKey = "manual" | "due" | "priority" | "created";
+export type TaskGroupBy = "none" | "project" | "tag" | "date_bucket";
+
+export interface TaskFilter {
+  project_id?: string;
+  tags?: string[];
+  include_completed?: boolean;
+  /** Only completed tasks; implies include_completed. */
+  completed_only?: boolean;
+  important_only?: boolean;
+  due_after?: number;
+  due_before?: number;
+  text?: string;
+  context?: string;
+  include_someday?: boolean;
+  include_scheduled?: boolean;
+}
+
+export interface TaskQuerySpec {
+  filter?: TaskFilter;
+  sort?: TaskSortKey;
+  descending?: boolean;
+  group_by?: TaskGroupBy;
+  omit_steps?: boolean;
+  /** Rows to skip, counted across groups in display order. */
+  offset?: number;
+  /** Rows to return after `offset`; everything when unset. */
+  limit?: number;
+}
+
+export interface TaskGroup {
+  key: string;
+  label: string;
+  tasks: Task[];
+}
+
+/** One page of `query_tasks`; groups without rows on the page are left out. */
+export interface TaskPage {
+  groups: TaskGroup[];
+  total: number;
+  offset: number;
+  has_more: boolean;
+}
+
+/** `create_task` with `check_duplicates`: the created task, or the open tasks it repeats. */
+export type CreateOutcome = Task | { duplicates: Task[] };
+
+/** Due-date part of a smart list, relative to when it is evaluated. */
+export type DueFilter =
+  | { kind: "any" }
+  | { kind: "overdue" }
+  | { kind: "today" }
+  | { kind: "next_days"; days: number }
+  | { kind: "no_date" };
+
+/** A saved filter ("Next 7 days", "Waiting"); `evaluate_smart_list` computes its tasks. */
+export interface SmartList {
+  id: string;
+  name: string;
+  project_ids: string[];
+  tags: string[];
+  important_only: boolean;
+  waiting_only: boolean;
+  due: DueFilter;
+  include_completed: boolean;
+  include_someday: boolean;
+  sort: TaskSortKey;
+  created_at: number;
+  updated_at: number;
+}
+
+export type AutomationTrigger =
+  | { kind: "task_completed" }
+  // Fires once per due time, `minutes` after the task fell due.
+  | { kind: "task_overdue"; minutes: number }
+  | { kind: "tag_added"; tag: string };
+
+export type AutomationAction =
+  | { kind: "move_to_project"; project_id: string }
+  | { kind: "add_tag"; tag: string }
+  // Undated tasks are left alone.
+  | { kind: "set_reminder"; minutes_before: number }
+  | { kind: "run_export"; format: ExportFormat };
+
+/** Changes made by a rule's actions never set off other rules. */
+export interface AutomationRule {
+  id: string;
+  name: string;
+  enabled: boolean;
+  trigger: AutomationTrigger;
+  actions: AutomationAction[];
+  // Kept by the backend: task id -> due time an overdue rule already fired for.
+  overdue_fired?: Record<string, number>;
+  created_at: number;
+  updated_at: number;
+}
+
+export interface TagStat {
+  tag: string;
+  count: number;
+  open_count: number;
+  last_used_at: number;
+  // Set when the tag has a definition.
+  id: string | null;
+  color: string | null;
+  parent_id: string | null;
+}
+
+// Color and nesting for a tag; tasks still store tag names.
+export interface Tag {
+  id: string;
+  name: string;
+  color: string | null;
+  parent_id: string | null;
+  created_at: number;
+  updated_at: number;
+}
+
+export interface ViewPrefs {
+  sort: TaskSortKey;
+  group_by: TaskGroupBy;
+  show_completed: boolean;
+}
+
+export interface QuadrantDefinition {
+  id: 1 | 2 | 3 | 4;
+  name: string;
+  color: string;
+}
+
+export interface QuadrantConfig {
+  quadrants: QuadrantDefinition[];
+  auto_classify: boolean;
+  urgent_within_hours: number;
+  important_urgent: number;
+  important_not_urgent: number;
+  urgent_not_important: number;
+  neither: number;
+}
+
+export type SmtpSecurity = "start_tls" | "tls" | "none";
+
+export interface EmailDeliveryEntry {
+  at: number;
+  kind: "reminder" | "test";
+  to: string;
+  subject: string;
+  task_ids: string[];
+  ok: boolean;
+  error?: string;
+}
+
+export interface CarryOverEntry {
+  at: number;
+  task_id: string;
+  title: string;
+  from_due: number;
+  to_due: number;
+  count: number;
+}
+
+// Notes an edit replaced; newest version first from `get_note_history`.
+export interface NoteVersion {
+  task_id: string;
+  version: number;
+  notes: string;
+  saved_at: number;
+}
+
+export type ActivityKind =
+  | { kind: "created" }
+  | { kind: "edited"; fields: string[] }
+  | { kind: "completed" }
+  | { kind: "reopened" }
+  | { kind: "snoozed"; until: number };
+
+// `title` is the task's title when the entry was written.
+export type ActivityEntry = {
+  at: number;
+  task_id: string;
+  title: string;
+} & ActivityKind;
+
+export interface FocusStatus {
+  task_id: string;
+  minutes: number;
+  started_at: number;
+  focused_seconds: number;
+  remaining_seconds: number;
+  paused: boolean;
+}
+
+export interface FocusSession {
+  task_id: string;
+  started_at: number;
+  ended_at: number;
+  minutes: number;
+  focused_seconds: number;
+  finished: boolean;
+}
+
+export interface ReminderDigest {
+  fired_at: number;
+  forced: boolean;
+  tasks: Task[];
+}
+
+export interface ReminderAuditEntry {
+  at: number;
+  task_id: string;
+  title: string;
+  kind: ReminderKind;
+  grouped: boolean;
 }
 
 export interface StatePayload {
   tasks: Task[];
   projects: Project[];
   settings: Settings;
+  /** Latest `state_delta` number already included. */
+  seq: number;
+}
+
+/** `state_delta` event: what changed since delta `seq - 1`. */
+export interface StateDelta {
+  seq: number;
+  tasks_upserted: Task[];
+  tasks_removed: string[];
+  projects_changed: Project[] | null;
+  settings_changed: Settings | null;
 }
 
 export interface CommandResult<T> {
@@ -10,6 +10,7 @@ import { NotificationBanner } from "../components/NotificationBanner";
 import { TaskCard } from "../components/TaskCard";
 import { describeError, frontendLog } from "../frontendLog";
 import { useI18n } from "../i18n";
+import { dueSortKey } from "../scheduler";
 import type { SnoozePresetId } from "../snooze";
 import type { Project, Settings, Task } from "../types";
 
@@ -78,6 +79,7 @@ export function CalendarView({
     const nowSec = Math.floor(Date.now() / 1000);
     for (const task of tasks) {
       if (!showCompleted && task.completed) continue;
+      if (task.due_at == null) continue;
       const key = formatLocalDateKey(new Date(task.due_at * 1000));
       const cur = map.get(key) ?? { total: 0, important: 0, overdue: 0 };
       cur.total += 1;
@@ -96,10 +98,11 @@ export function CalendarView({
     const list = tasks
       .filter((task) => (showCompleted ? true : !task.completed))
       .filter((task) =>
+        task.due_at != null &&
         sameLocalDate(task.due_at, Math.floor(selectedDay.getTime() / 1000)),
       )
       .slice()
-      .sort((a, b) => a.due_at - b.due_at || a.created_at - b.created_at);
+      .sort((a, b) => dueSortKey(a) - dueSortKey(b) || a.created_at - b.created_at);
     return list;
   }, [tasks, selectedDay, showCompleted]);
 
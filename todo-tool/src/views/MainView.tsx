@@ -2,7 +2,7 @@ import { useEffect, useMemo, useRef, useState, type DragEvent } from "react";
 
 import { getCurrentWindow } from "@tauri-apps/api/window";
 
-import { swapSortOrder } from "../api";
+import { muteProject, swapSortOrder, unmuteProject } from "../api";
 import { ConfirmDialog } from "../components/ConfirmDialog";
 import { NotificationBanner } from "../components/NotificationBanner";
 import { IconButton } from "../components/IconButton";
@@ -41,6 +41,44 @@ function resolveDynamicQuadrant(task: Task, now: Date, nowSeconds: number) {
   return urgent ? 3 : 4;
 }
 
+// Built-in project icons; keep in sync with `PROJECT_ICONS` in src-tauri/src/flair.rs.
+const PROJECT_ICONS: Record<string, (typeof Icons)[keyof typeof Icons]> = {
+  star: Icons.Star,
+  calendar: Icons.Calendar,
+  bell: Icons.Bell,
+  list: Icons.List,
+  grid: Icons.Grid,
+  pin: Icons.Pin,
+  clock: Icons.Clock,
+  settings: Icons.Settings,
+  check: Icons.Check,
+  repeat: Icons.Repeat,
+  search: Icons.Search,
+  filter: Icons.Filter,
+};
+
+function ProjectMarker({ project }: { project: Project }) {
+  const BuiltIn = project.icon ? PROJECT_ICONS[project.icon] : undefined;
+  if (BuiltIn || project.icon) {
+    return (
+      <span
+        className="sidebar-project-icon"
+        style={project.color ? { color: project.color } : undefined}
+        aria-hidden="true"
+      >
+        {BuiltIn ? <BuiltIn /> : project.icon}
+      </span>
+    );
+  }
+  return (
+    <span
+      className="sidebar-project-dot"
+      style={project.color ? { background: project.color } : undefined}
+      aria-hidden="true"
+    />
+  );
+}
+
 export function MainView({
   tasks,
   projects,
@@ -65,6 +103,7 @@ export function MainView({
   onOpenCalendar,
   onNormalSnooze,
   onNormalComplete,
+  routeProjectId,
 }: {
   tasks: Task[];
   projects: Project[];
@@ -91,6 +130,8 @@ export function MainView({
   onOpenCalendar: () => void;
   onNormalSnooze: (task: Task, preset: SnoozePresetId) => Promise<void> | void;
   onNormalComplete: (task: Task) => Promise<void> | void;
+  /** Project picked from the tray menu (`#/main/project/<id>`). */
+  routeProjectId?: string | null;
 }) {
   const { t } = useI18n();
   const [mainView, setMainView] = useState<"quadrant" | "list">("list");
@@ -132,7 +173,7 @@ export function MainView({
 
   const aiReady =
     Boolean(settings?.ai_enabled) &&
-    Boolean((settings?.deepseek_api_key ?? "").trim()) &&
+    Boolean(settings?.ai_api_key_set) &&
     Boolean((settings?.ai_model ?? "").trim());
   const aiPlaceholderOptions = useMemo(() => {
     if (!aiReady) return [];
@@ -175,6 +216,17 @@ export function MainView({
     return () => window.removeEventListener("keydown", onKeyDown);
   }, [projectMenu]);
 
+  useEffect(() => {
+    if (!routeProjectId) return;
+    if (projects.some((project) => project.id === routeProjectId)) {
+      setSidebarSelection("project");
+      setSelectedProjectId(routeProjectId);
+      setMainView("list");
+    }
+    // Back to the plain route so picking the same project again navigates again.
+    window.location.hash = "#/main";
+  }, [routeProjectId, projects]);
+
   useEffect(() => {
     if (sidebarSelection !== "project") return;
     if (projects.some((project) => project.id === selectedProjectId)) return;
@@ -191,7 +243,20 @@ export function MainView({
       if (a.created_at !== b.created_at) return a.created_at - b.created_at;
       return a.id.localeCompare(b.id);
     });
-    return list;
+    // Subprojects follow their folder (depth-first), so the flat list reads as a tree.
+    const ids = new Set(list.map((project) => project.id));
+    const parentOf = (project: Project) =>
+      project.parent_id && ids.has(project.parent_id) ? project.parent_id : null;
+    const nested: { project: Project; depth: number }[] = [];
+    const visit = (parentId: string | null, depth: number) => {
+      for (const project of list) {
+        if (parentOf(project) !== parentId) continue;
+        nested.push({ project, depth });
+        visit(project.id, depth + 1);
+      }
+    };
+    visit(null, 0);
+    return nested;
   }, [projects]);
 
   const projectsById = useMemo(() => {
@@ -223,6 +288,42 @@ export function MainView({
     }
   }
 
+  function isProjectMuted(project: Project) {
+    if (!project.reminders_muted) return false;
+    const now = Math.floor(Date.now() / 1000);
+    return project.muted_until == null || now < project.muted_until;
+  }
+
+  async function handleSetProjectMute(project: Project, days: number | null) {
+    if (projectBusy) return;
+    setProjectBusy(true);
+    try {
+      const res =
+        days === 0
+          ? await unmuteProject(project.id)
+          : await muteProject(
+              project.id,
+              days === null
+                ? null
+                : Math.floor(Date.now() / 1000) + days * 24 * 60 * 60,
+            );
+      if (!res.ok) {
+        void frontendLog("warn", "frontend: project mute failed", {
+          projectId: project.id,
+          error: res.error ?? "",
+        });
+        await onRefreshState();
+      }
+    } catch (err) {
+      void frontendLog("error", "frontend: project mute threw", {
+        projectId: project.id,
+        err: describeError(err),
+      });
+    } finally {
+      setProjectBusy(false);
+    }
+  }
+
   async function handleRenameProject(project: Project) {
     if (projectBusy) return;
     if (project.id === "inbox") return;
@@ -761,7 +862,7 @@ export function MainView({
               )}
 
               <div className="sidebar-project-list">
-                {orderedProjects.map((project) => {
+                {orderedProjects.map(({ project, depth }) => {
                   const active =
                     sidebarSelection === "project" &&
                     project.id === selectedProjectId;
@@ -812,6 +913,11 @@ export function MainView({
                       key={project.id}
                       type="button"
                       className={`sidebar-item ${active ? "active" : ""}`}
+                      style={
+                        depth > 0
+                          ? { paddingLeft: `${10 + depth * 14}px` }
+                          : undefined
+                      }
                       onClick={() => {
                         setSidebarSelection("project");
                         setSelectedProjectId(project.id);
@@ -820,11 +926,6 @@ export function MainView({
                       }}
                       onContextMenu={(event) => {
                         event.preventDefault();
-                        // Inbox has no context actions; avoid showing an empty menu.
-                        if (project.id === "inbox") {
-                          setProjectMenu(null);
-                          return;
-                        }
                         setProjectMenu({
                           projectId: project.id,
                           x: event.clientX,
@@ -832,11 +933,16 @@ export function MainView({
                         });
                       }}
                     >
-                      <span
-                        className="sidebar-project-dot"
-                        aria-hidden="true"
-                      />
+                      <ProjectMarker project={project} />
                       <span className="sidebar-project-name">{label}</span>
+                      {isProjectMuted(project) && (
+                        <span
+                          className="sidebar-project-muted"
+                          title={t("project.muted")}
+                        >
+                          <Icons.Bell />
+                        </span>
+                      )}
                     </button>
                   );
                 })}
@@ -1229,6 +1335,48 @@ export function MainView({
                   </button>
                 )}
 
+                {isProjectMuted(project) ? (
+                  <button
+                    type="button"
+                    className="context-menu-item"
+                    disabled={projectBusy}
+                    onClick={() => {
+                      setProjectMenu(null);
+                      void handleSetProjectMute(project, 0);
+                    }}
+                  >
+                    <Icons.Bell />
+                    <span>{t("project.menu.unmute")}</span>
+                  </button>
+                ) : (
+                  <>
+                    <button
+                      type="button"
+                      className="context-menu-item"
+                      disabled={projectBusy}
+                      onClick={() => {
+                        setProjectMenu(null);
+                        void handleSetProjectMute(project, 7);
+                      }}
+                    >
+                      <Icons.Bell />
+                      <span>{t("project.menu.muteWeek")}</span>
+                    </button>
+                    <button
+                      type="button"
+                      className="context-menu-item"
+                      disabled={projectBusy}
+                      onClick={() => {
+                        setProjectMenu(null);
+                        void handleSetProjectMute(project, null);
+                      }}
+                    >
+                      <Icons.Bell />
+                      <span>{t("project.menu.mute")}</span>
+                    </button>
+                  </>
+                )}
+
                 {!isInbox && (
                   <button
                     type="button"
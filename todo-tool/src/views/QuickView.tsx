@@ -106,7 +106,7 @@ export function QuickView({
 
   const aiReady =
     Boolean(settings?.ai_enabled) &&
-    Boolean((settings?.deepseek_api_key ?? "").trim()) &&
+    Boolean(settings?.ai_api_key_set) &&
     Boolean((settings?.ai_model ?? "").trim());
   const aiPlaceholderOptions = useMemo(() => {
     if (!aiReady) return [];
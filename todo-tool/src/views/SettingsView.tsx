@@ -11,6 +11,7 @@ import { openUrl } from "@tauri-apps/plugin-opener";
 import { formatDue } from "../date";
 import { getAppVersion } from "../version";
 import { describeError, frontendLog } from "../frontendLog";
+import { playSound } from "../sound";
 import {
   createBackup,
   createProject,
@@ -19,12 +20,28 @@ import {
   deleteTasks,
   exportTasksCsv,
   exportTasksJson,
+  exportMarkdownSync,
+  exportToObsidian,
+  exportYearReview,
+  exportTasksIcs,
   exportTasksMarkdown,
   importBackup,
+  importExternal,
+  importMarkdown,
   listBackups,
+  listSounds,
+  mergeBackup,
+  previewBackupMerge,
   restoreBackup,
+  clearSecret,
+  setCaldavPassword,
+  setSecret,
   setShortcutCaptureActive,
+  syncNow,
+  updateProject,
+  validateShortcut,
   type BackupEntry,
+  type SecretName,
 } from "../api";
 import { useI18n } from "../i18n";
 import {
@@ -35,7 +52,19 @@ import {
 } from "../sampleData";
 import { captureShortcutFromEvent } from "../shortcut";
 import { normalizeTheme } from "../theme";
-import type { BackupSchedule, Project, Settings, Task } from "../types";
+import type {
+  AiProviderKind,
+  BackupSchedule,
+  CommandResult,
+  ExternalImportReport,
+  MarkdownHeadings,
+  MergePreview,
+  Project,
+  Settings,
+  SoundEntry,
+  StorageEngine,
+  Task,
+} from "../types";
 
 import { WindowTitlebar } from "../components/WindowTitlebar";
 import { useToast } from "../components/ToastProvider";
@@ -45,6 +74,58 @@ import { Switch } from "../components/Switch";
 import { detectPlatform } from "../platform";
 
 type PermissionStatus = "unknown" | "granted" | "denied";
+
+const BACKUP_RETENTION_OPTIONS = [3, 5, 10, 20, 50, 100];
+
+function formatBytes(bytes: number) {
+  if (bytes < 1024) return `${bytes} B`;
+  if (bytes < 1024 * 1024) return `${(bytes / 1024).toFixed(1)} KB`;
+  return `${(bytes / 1024 / 1024).toFixed(1)} MB`;
+}
+
+// Mirrors `ai::providers` in the backend.
+const AI_PROVIDERS: Record<
+  AiProviderKind,
+  { labelKey: string; secret: SecretName | null; baseUrl: string; model: string }
+> = {
+  deepseek: {
+    labelKey: "settings.ai.providerDeepseek",
+    secret: "deepseek_api_key",
+    baseUrl: "https://api.deepseek.com/v1",
+    model: "deepseek-chat",
+  },
+  openai: {
+    labelKey: "settings.ai.providerOpenai",
+    secret: "openai_api_key",
+    baseUrl: "https://api.openai.com/v1",
+    model: "gpt-4o-mini",
+  },
+  anthropic: {
+    labelKey: "settings.ai.providerAnthropic",
+    secret: "anthropic_api_key",
+    baseUrl: "https://api.anthropic.com/v1",
+    model: "claude-3-5-haiku-latest",
+  },
+  ollama: {
+    labelKey: "settings.ai.providerOllama",
+    secret: null,
+    baseUrl: "http://localhost:11434",
+    model: "llama3.1",
+  },
+};
+type ShortcutField =
+  | "shortcut"
+  | "shortcut_quick_add"
+  | "shortcut_toggle_main"
+  | "shortcut_complete_focus";
+
+// Optional per-action global shortcuts; blank means unbound.
+const ACTION_SHORTCUT_FIELDS: Exclude<ShortcutField, "shortcut">[] = [
+  "shortcut_quick_add",
+  "shortcut_toggle_main",
+  "shortcut_complete_focus",
+];
+
 type ManualUpdateCheckResult =
   | { status: "update"; version: string }
   | { status: "none" }
@@ -77,9 +158,24 @@ export function SettingsView({
     useState<PermissionStatus>("unknown");
   const [backups, setBackups] = useState<BackupEntry[]>([]);
   const [importPath, setImportPath] = useState<string | null>(null);
+  const [mergeSource, setMergeSource] = useState<{
+    name: string;
+    preview: MergePreview;
+  } | null>(null);
+  // `task:<id>` / `project:<id>` keys of the rows picked for a merge.
+  const [mergeSelection, setMergeSelection] = useState<Set<string>>(
+    () => new Set(),
+  );
+  const [markdownDraft, setMarkdownDraft] = useState("");
+  const [markdownHeadings, setMarkdownHeadings] =
+    useState<MarkdownHeadings>("project");
   const [shortcutDraft, setShortcutDraft] = useState("");
-  const [shortcutCapturing, setShortcutCapturing] = useState(false);
+  const [shortcutCapturing, setShortcutCapturing] =
+    useState<ShortcutField | null>(null);
   const [shortcutHint, setShortcutHint] = useState<string | null>(null);
+  // Which row the hint belongs to; it outlives capture when explaining a conflict.
+  const [shortcutHintField, setShortcutHintField] =
+    useState<ShortcutField>("shortcut");
   const [seedBusy, setSeedBusy] = useState(false);
   const [sampleDeleteBusy, setSampleDeleteBusy] = useState(false);
   const [appVersion, setAppVersion] = useState<string | null>(null);
@@ -89,17 +185,24 @@ export function SettingsView({
   const [exportBusy, setExportBusy] = useState(false);
   const [exportPath, setExportPath] = useState<string | null>(null);
   const [exportError, setExportError] = useState<string | null>(null);
-  const [deepseekKeyDraft, setDeepseekKeyDraft] = useState("");
+  // "" exports every project.
+  const [exportProjectId, setExportProjectId] = useState("");
+  const [sounds, setSounds] = useState<SoundEntry[]>([]);
+  const [caldavPasswordDraft, setCaldavPasswordDraft] = useState("");
+  const [syncBusy, setSyncBusy] = useState(false);
+  const [syncError, setSyncError] = useState<string | null>(null);
+  const [apiKeyDraft, setApiKeyDraft] = useState("");
   const [aiModelDraft, setAiModelDraft] = useState("deepseek-chat");
+  const [aiBaseUrlDraft, setAiBaseUrlDraft] = useState("");
   const [aiPromptDraft, setAiPromptDraft] = useState("");
   const lastAiSettingsRef = useRef<{
-    deepseekKey: string;
     aiModel: string;
+    aiBaseUrl: string;
     aiPrompt: string;
   } | null>(null);
 
   async function handleBack() {
-    setShortcutCapturing(false);
+    setShortcutCapturing(null);
     setShortcutHint(null);
     if (settings) {
       const nextShortcut = shortcutDraft.trim();
@@ -158,6 +261,26 @@ export function SettingsView({
     }
   }
 
+  useEffect(() => {
+    void listSounds().then((res) => {
+      if (res.ok && res.data) setSounds(res.data);
+    });
+  }, []);
+
+  async function handleProjectSound(project: Project, soundId: string) {
+    const res = await updateProject({
+      ...project,
+      notification_sound: soundId || null,
+    });
+    if (!res.ok) {
+      toast.notify(res.error ?? t("common.unknownError"), {
+        tone: "danger",
+      });
+      return;
+    }
+    if (settings?.sound_enabled) void playSound(soundId || null);
+  }
+
   async function refreshBackups() {
     const res = await listBackups();
     if (res.ok && res.data) {
@@ -181,6 +304,76 @@ export function SettingsView({
     await restoreBackup(name);
   }
 
+  function notifyError(message: string | null | undefined) {
+    toast.notify(message || t("common.unknownError"), {
+      tone: "danger",
+      durationMs: 6000,
+    });
+  }
+
+  async function handleOpenMerge(name: string) {
+    try {
+      const res = await previewBackupMerge(name);
+      if (!res.ok || !res.data) {
+        notifyError(res.error);
+        return;
+      }
+      const preview = res.data;
+      // Rows whose current copy is newer would be kept anyway; leave them unticked.
+      setMergeSelection(
+        new Set([
+          ...preview.projects
+            .filter((row) => row.status !== "current_newer")
+            .map((row) => `project:${row.project.id}`),
+          ...preview.tasks
+            .filter((row) => row.status !== "current_newer")
+            .map((row) => `task:${row.task.id}`),
+        ]),
+      );
+      setMergeSource({ name, preview });
+    } catch (error) {
+      notifyError(error instanceof Error ? error.message : String(error));
+    }
+  }
+
+  function toggleMergeRow(key: string) {
+    setMergeSelection((prev) => {
+      const next = new Set(prev);
+      if (next.has(key)) next.delete(key);
+      else next.add(key);
+      return next;
+    });
+  }
+
+  async function handleApplyMerge() {
+    if (!mergeSource || mergeSelection.size === 0) return;
+    const ids = (prefix: string) =>
+      [...mergeSelection]
+        .filter((key) => key.startsWith(prefix))
+        .map((key) => key.slice(prefix.length));
+    try {
+      const res = await mergeBackup(
+        mergeSource.name,
+        ids("task:"),
+        ids("project:"),
+      );
+      if (!res.ok || !res.data) {
+        notifyError(res.error);
+        return;
+      }
+      toast.notify(
+        t("settings.backup.mergeDone", {
+          count: res.data.tasks.length + res.data.projects.length,
+          kept: res.data.kept_current.length,
+        }),
+        { tone: "success" },
+      );
+      setMergeSource(null);
+    } catch (error) {
+      notifyError(error instanceof Error ? error.message : String(error));
+    }
+  }
+
   async function handleDeleteBackup(name: string) {
     const ok = await requestConfirm({
       title: t("settings.backup.delete"),
@@ -199,7 +392,7 @@ export function SettingsView({
       const selected = await open({
         multiple: false,
         directory: false,
-        filters: [{ name: "Backup", extensions: ["json"] }],
+        filters: [{ name: "Backup", extensions: ["json", "gz"] }],
       });
       if (!selected || Array.isArray(selected)) return;
       const ok = await requestConfirm({
@@ -220,17 +413,159 @@ export function SettingsView({
     }
   }
 
-  async function handleExport(kind: "json" | "csv" | "md") {
+  // Previews `run`, asks for confirmation, then imports; false when nothing was added.
+  async function confirmExternalImport(
+    run: (dryRun: boolean) => Promise<CommandResult<ExternalImportReport>>,
+  ) {
+    try {
+      const preview = await run(true);
+      if (!preview.ok || !preview.data) {
+        throw new Error(preview.error ?? "");
+      }
+      const { changes, warnings } = preview.data;
+      const ok = await requestConfirm({
+        title: t("settings.backup.external"),
+        description: t("settings.backup.externalConfirm", {
+          tasks: changes.created.length,
+          projects: changes.projects_created.length,
+          warnings: warnings.length
+            ? t("settings.backup.externalWarnings", { count: warnings.length })
+            : "",
+        }),
+        confirmText: t("common.confirm"),
+        cancelText: t("common.cancel"),
+      });
+      if (!ok) return false;
+      const res = await run(false);
+      if (!res.ok || !res.data) {
+        throw new Error(res.error ?? "");
+      }
+      toast.notify(
+        t("settings.backup.externalDone", {
+          tasks: res.data.changes.created.length,
+        }),
+        { tone: "success" },
+      );
+      return true;
+    } catch (error) {
+      const message = error instanceof Error ? error.message : String(error);
+      toast.notify(message || t("common.unknownError"), {
+        tone: "danger",
+        durationMs: 6000,
+      });
+      return false;
+    }
+  }
+
+  async function handleChooseBackupDirectory() {
+    if (!settings) return;
+    try {
+      const selected = await open({ multiple: false, directory: true });
+      if (!selected || Array.isArray(selected)) return;
+      await onUpdateSettings({ ...settings, backup_directory: selected });
+    } catch (error) {
+      const message = error instanceof Error ? error.message : String(error);
+      toast.notify(message || t("common.unknownError"), {
+        tone: "danger",
+        durationMs: 6000,
+      });
+    }
+  }
+
+  async function handleImportExternal() {
+    try {
+      const selected = await open({
+        multiple: false,
+        directory: false,
+        filters: [
+          {
+            name: "Todoist / TickTick / Markdown",
+            extensions: ["csv", "json", "md", "markdown"],
+          },
+        ],
+      });
+      if (!selected || Array.isArray(selected)) return;
+      await confirmExternalImport((dryRun) =>
+        importExternal(selected, dryRun),
+      );
+    } catch (error) {
+      const message = error instanceof Error ? error.message : String(error);
+      toast.notify(message || t("common.unknownError"), {
+        tone: "danger",
+        durationMs: 6000,
+      });
+    }
+  }
+
+  async function handleImportMarkdown() {
+    const source = markdownDraft;
+    if (!source.trim()) return;
+    const imported = await confirmExternalImport((dryRun) =>
+      importMarkdown(source, markdownHeadings, dryRun),
+    );
+    if (imported) setMarkdownDraft("");
+  }
+
+  async function handleExport(kind: "json" | "csv" | "md" | "ics") {
     if (exportBusy) return;
     setExportBusy(true);
     setExportError(null);
+    const filter = exportProjectId ? { project_id: exportProjectId } : undefined;
     try {
       const res =
         kind === "json"
-          ? await exportTasksJson()
+          ? await exportTasksJson(filter)
           : kind === "csv"
-            ? await exportTasksCsv()
-            : await exportTasksMarkdown();
+            ? await exportTasksCsv(filter)
+            : kind === "md"
+              ? await exportTasksMarkdown(filter)
+              : await exportTasksIcs(filter);
+      if (res.ok && res.data) {
+        setExportPath(res.data);
+      } else {
+        setExportError(res.error ?? "unknown error");
+      }
+    } catch (err) {
+      setExportError(err instanceof Error ? err.message : String(err));
+    } finally {
+      setExportBusy(false);
+    }
+  }
+
+  async function handleYearReviewExport() {
+    if (exportBusy) return;
+    // In January the year worth reviewing is the one that just ended.
+    const now = new Date();
+    const year = now.getMonth() === 0 ? now.getFullYear() - 1 : now.getFullYear();
+    setExportBusy(true);
+    setExportError(null);
+    try {
+      const res = await exportYearReview(year);
+      if (res.ok && res.data) {
+        setExportPath(res.data.html_path);
+        toast.notify(
+          t("settings.export.yearReviewDone", {
+            year,
+            completed: res.data.review.total_completed,
+          }),
+          { tone: "success" },
+        );
+      } else {
+        setExportError(res.error ?? "unknown error");
+      }
+    } catch (err) {
+      setExportError(err instanceof Error ? err.message : String(err));
+    } finally {
+      setExportBusy(false);
+    }
+  }
+
+  async function handleMarkdownSyncExport() {
+    if (exportBusy) return;
+    setExportBusy(true);
+    setExportError(null);
+    try {
+      const res = await exportMarkdownSync();
       if (res.ok && res.data) {
         setExportPath(res.data);
       } else {
@@ -243,6 +578,92 @@ export function SettingsView({
     }
   }
 
+  async function handleObsidianExport(chooseVault: boolean) {
+    if (!settings || exportBusy) return;
+    let vault = settings.obsidian_vault_path ?? "";
+    if (chooseVault || !vault) {
+      const selected = await open({ multiple: false, directory: true });
+      if (!selected || Array.isArray(selected)) return;
+      vault = selected;
+    }
+    setExportBusy(true);
+    setExportError(null);
+    try {
+      const res = await exportToObsidian(vault);
+      if (res.ok && res.data) {
+        toast.notify(
+          t("settings.export.obsidianDone", {
+            written: res.data.written.length,
+            cleared: res.data.cleared.length,
+          }),
+          { tone: "success" },
+        );
+      } else {
+        setExportError(res.error ?? "unknown error");
+      }
+    } catch (err) {
+      setExportError(err instanceof Error ? err.message : String(err));
+    } finally {
+      setExportBusy(false);
+    }
+  }
+
+  // The key goes to the OS keychain; settings only learn whether one is stored.
+  async function saveApiKey(nextKey: string) {
+    const secret = settings ? AI_PROVIDERS[settings.ai_provider].secret : null;
+    if (!secret) return true;
+    const res = nextKey
+      ? await setSecret(secret, nextKey)
+      : await clearSecret(secret);
+    if (!res.ok || !res.data) {
+      toast.notify(res.error ?? t("settings.ai.keySaveFailed"), {
+        tone: "danger",
+      });
+      return false;
+    }
+    setApiKeyDraft("");
+    return onUpdateSettings(res.data);
+  }
+
+  async function handleCaldavPasswordSave() {
+    const password = caldavPasswordDraft;
+    if (!password) return;
+    const res = await setCaldavPassword(password);
+    if (res.ok) {
+      setCaldavPasswordDraft("");
+      toast.notify(t("settings.sync.passwordSaved"), { tone: "success" });
+    } else {
+      setSyncError(res.error ?? "unknown error");
+    }
+  }
+
+  async function handleSyncNow() {
+    if (syncBusy) return;
+    setSyncBusy(true);
+    setSyncError(null);
+    try {
+      const res = await syncNow();
+      if (res.ok && res.data) {
+        const report = res.data;
+        toast.notify(
+          t("settings.sync.done", {
+            pushed: report.pushed + report.deleted_remote,
+            pulled: report.pulled + report.deleted_local,
+            conflicts: report.conflicts,
+          }),
+          { tone: report.errors.length > 0 ? "danger" : "success" },
+        );
+        if (report.errors.length > 0) setSyncError(report.errors[0]);
+      } else {
+        setSyncError(res.error ?? "unknown error");
+      }
+    } catch (err) {
+      setSyncError(err instanceof Error ? err.message : String(err));
+    } finally {
+      setSyncBusy(false);
+    }
+  }
+
   async function handleCopyExportPath() {
     if (!exportPath) return;
     try {
@@ -382,6 +803,33 @@ export function SettingsView({
     [onUpdateSettings, settings, shortcutDraft],
   );
 
+  // Null when the shortcut can be saved for `field`, otherwise a message for the hint.
+  const describeShortcutProblem = useCallback(
+    async (field: ShortcutField, shortcut: string) => {
+      // Leave it to update_settings when the check itself is unavailable.
+      const res = await validateShortcut(shortcut, field).catch(() => null);
+      if (!res?.ok || !res.data) return null;
+      const check = res.data;
+      if (check.error) return t("settings.shortcutInvalidKey");
+      const conflict = check.conflicts[0];
+      if (!conflict) return null;
+      return conflict.kind === "app"
+        ? t("settings.shortcutConflictApp", {
+            action: t(`settings.${conflict.action}`),
+          })
+        : t("settings.shortcutConflictSystem", { reason: conflict.reason });
+    },
+    [t],
+  );
+
+  const applyActionShortcut = useCallback(
+    async (field: Exclude<ShortcutField, "shortcut">, next: string) => {
+      if (!settings || next === settings[field]) return;
+      await onUpdateSettings({ ...settings, [field]: next });
+    },
+    [onUpdateSettings, settings],
+  );
+
   // When settings load, sync drafts and refresh side-effecty data (permission, backups).
   useEffect(() => {
     if (!settings) return;
@@ -392,48 +840,50 @@ export function SettingsView({
   useEffect(() => {
     if (!settings) {
       lastAiSettingsRef.current = null;
-      setDeepseekKeyDraft("");
+      setApiKeyDraft("");
       setAiModelDraft("deepseek-chat");
+      setAiBaseUrlDraft("");
       setAiPromptDraft("");
       return;
     }
 
-    const nextKey = settings.deepseek_api_key ?? "";
     const nextModel = settings.ai_model ?? "deepseek-chat";
+    const nextBaseUrl = settings.ai_base_url ?? "";
     const nextPrompt = settings.ai_prompt ?? "";
 
     const prev = lastAiSettingsRef.current;
     if (!prev) {
       lastAiSettingsRef.current = {
-        deepseekKey: nextKey,
         aiModel: nextModel,
+        aiBaseUrl: nextBaseUrl,
         aiPrompt: nextPrompt,
       };
-      setDeepseekKeyDraft(nextKey);
       setAiModelDraft(nextModel);
+      setAiBaseUrlDraft(nextBaseUrl);
       setAiPromptDraft(nextPrompt);
       return;
     }
 
-    if (deepseekKeyDraft === prev.deepseekKey && nextKey !== prev.deepseekKey) {
-      setDeepseekKeyDraft(nextKey);
-    }
     if (aiModelDraft === prev.aiModel && nextModel !== prev.aiModel) {
       setAiModelDraft(nextModel);
     }
+    if (aiBaseUrlDraft === prev.aiBaseUrl && nextBaseUrl !== prev.aiBaseUrl) {
+      setAiBaseUrlDraft(nextBaseUrl);
+    }
     if (aiPromptDraft === prev.aiPrompt && nextPrompt !== prev.aiPrompt) {
       setAiPromptDraft(nextPrompt);
     }
 
     lastAiSettingsRef.current = {
-      deepseekKey: nextKey,
       aiModel: nextModel,
+      aiBaseUrl: nextBaseUrl,
       aiPrompt: nextPrompt,
     };
   }, [settings]);
 
   useEffect(() => {
     if (!shortcutCapturing) return;
+    setShortcutHintField(shortcutCapturing);
     setShortcutHint(t("settings.shortcutHint"));
     void setShortcutCaptureActive(true).catch(() => {});
 
@@ -442,7 +892,7 @@ export function SettingsView({
       event.stopPropagation();
 
       if (event.key === "Escape") {
-        setShortcutCapturing(false);
+        setShortcutCapturing(null);
         setShortcutHint(null);
         return;
       }
@@ -457,14 +907,28 @@ export function SettingsView({
         return;
       }
 
-      setShortcutCapturing(false);
+      const field = shortcutCapturing;
+      setShortcutCapturing(null);
       setShortcutHint(null);
-      setShortcutDraft(result.shortcut);
-      void applyShortcutDraft(result.shortcut);
+      void (async () => {
+        // Check before saving so conflicts are explained instead of failing to register.
+        const problem = await describeShortcutProblem(field, result.shortcut);
+        if (problem) {
+          setShortcutHintField(field);
+          setShortcutHint(problem);
+          return;
+        }
+        if (field === "shortcut") {
+          setShortcutDraft(result.shortcut);
+          await applyShortcutDraft(result.shortcut);
+        } else {
+          await applyActionShortcut(field, result.shortcut);
+        }
+      })();
     };
 
     const handleBlur = () => {
-      setShortcutCapturing(false);
+      setShortcutCapturing(null);
       setShortcutHint(null);
     };
 
@@ -475,7 +939,13 @@ export function SettingsView({
       window.removeEventListener("keydown", handleKeyDown, { capture: true });
       window.removeEventListener("blur", handleBlur);
     };
-  }, [shortcutCapturing, t, applyShortcutDraft]);
+  }, [
+    shortcutCapturing,
+    t,
+    applyShortcutDraft,
+    applyActionShortcut,
+    describeShortcutProblem,
+  ]);
 
   // Refresh side-effecty data when the settings page mounts.
   useEffect(() => {
@@ -577,6 +1047,13 @@ export function SettingsView({
     () => (settings ? normalizeTheme(settings.theme) : "retro"),
     [settings?.theme],
   );
+  // A retention set elsewhere (e.g. by hand in settings.json) stays selectable.
+  const backupRetentionOptions = useMemo(() => {
+    const current = settings?.backup_retention ?? 5;
+    return BACKUP_RETENTION_OPTIONS.includes(current)
+      ? BACKUP_RETENTION_OPTIONS
+      : [...BACKUP_RETENTION_OPTIONS, current].sort((a, b) => a - b);
+  }, [settings?.backup_retention]);
   const permissionLabel = useMemo(() => {
     if (permissionStatus === "granted") return t("settings.permission.granted");
     if (permissionStatus === "denied") return t("settings.permission.denied");
@@ -686,6 +1163,13 @@ export function SettingsView({
               >
                 {t("settings.section.export")}
               </button>
+              <button
+                type="button"
+                className="settings-nav-btn"
+                onClick={() => scrollToSection("settings-sync")}
+              >
+                {t("settings.section.sync")}
+              </button>
               <button
                 type="button"
                 className="settings-nav-btn"
@@ -785,25 +1269,59 @@ export function SettingsView({
                     <label>{t("settings.shortcut")}</label>
                     <button
                       type="button"
-                      className={`shortcut-capture ${shortcutCapturing ? "capturing" : ""}`}
+                      className={`shortcut-capture ${shortcutCapturing === "shortcut" ? "capturing" : ""}`}
                       onClick={() => {
                         if (shortcutCapturing) {
-                          setShortcutCapturing(false);
+                          setShortcutCapturing(null);
                           setShortcutHint(null);
                           return;
                         }
-                        setShortcutCapturing(true);
+                        setShortcutCapturing("shortcut");
                       }}
                       title={t("settings.shortcutHint")}
                     >
-                      {shortcutCapturing
+                      {shortcutCapturing === "shortcut"
                         ? t("settings.shortcutCapturing")
                         : shortcutDraft || t("settings.shortcutCapture")}
                     </button>
-                    {shortcutHint && (
+                    {shortcutHint && shortcutHintField === "shortcut" && (
                       <span className="settings-status">{shortcutHint}</span>
                     )}
                   </div>
+                  {ACTION_SHORTCUT_FIELDS.map((field) => (
+                    <div key={field} className="settings-row">
+                      <label>{t(`settings.${field}`)}</label>
+                      <button
+                        type="button"
+                        className={`shortcut-capture ${shortcutCapturing === field ? "capturing" : ""}`}
+                        onClick={() => {
+                          if (shortcutCapturing) {
+                            setShortcutCapturing(null);
+                            setShortcutHint(null);
+                            return;
+                          }
+                          setShortcutCapturing(field);
+                        }}
+                        title={t("settings.shortcutHint")}
+                      >
+                        {shortcutCapturing === field
+                          ? t("settings.shortcutCapturing")
+                          : settings[field] || t("settings.shortcutUnbound")}
+                      </button>
+                      {settings[field] && (
+                        <button
+                          type="button"
+                          className="pill"
+                          onClick={() => void applyActionShortcut(field, "")}
+                        >
+                          {t("settings.shortcutClear")}
+                        </button>
+                      )}
+                      {shortcutHint && shortcutHintField === field && (
+                        <span className="settings-status">{shortcutHint}</span>
+                      )}
+                    </div>
+                  ))}
                   <div className="settings-row">
                     <label>{t("settings.theme")}</label>
                     <select
@@ -947,6 +1465,42 @@ export function SettingsView({
                       }
                     />
                   </div>
+                  {projects.map((project) => (
+                    <div className="settings-row" key={project.id}>
+                      <label>
+                        {t("settings.projectSound", { name: project.name })}
+                      </label>
+                      <select
+                        value={project.notification_sound ?? ""}
+                        onChange={(event) =>
+                          void handleProjectSound(
+                            project,
+                            event.currentTarget.value,
+                          )
+                        }
+                      >
+                        <option value="">
+                          {t("settings.projectSound.default")}
+                        </option>
+                        {sounds.map((sound) => (
+                          <option key={sound.id} value={sound.id}>
+                            {sound.bundled
+                              ? t(`settings.sound.${sound.id}`)
+                              : sound.name}
+                          </option>
+                        ))}
+                      </select>
+                      <button
+                        type="button"
+                        className="pill"
+                        onClick={() =>
+                          void playSound(project.notification_sound)
+                        }
+                      >
+                        {t("settings.projectSound.preview")}
+                      </button>
+                    </div>
+                  ))}
                   <div className="settings-row">
                     <label>{t("settings.closeBehavior")}</label>
                     <select
@@ -996,10 +1550,15 @@ export function SettingsView({
                       checked={settings.ai_enabled}
                       ariaLabel={t("settings.ai")}
                       onChange={async (nextEnabled) => {
-                        const nextKey = deepseekKeyDraft.trim();
+                        const nextKey = apiKeyDraft.trim();
                         const nextModel = aiModelDraft.trim();
+                        const provider = AI_PROVIDERS[settings.ai_provider];
                         if (nextEnabled) {
-                          if (!nextKey) {
+                          if (
+                            provider.secret &&
+                            !nextKey &&
+                            !settings.ai_api_key_set
+                          ) {
                             toast.notify(t("settings.ai.keyRequired"), {
                               tone: "danger",
                             });
@@ -1012,77 +1571,153 @@ export function SettingsView({
                             return;
                           }
                         }
+                        if (nextKey && !(await saveApiKey(nextKey))) {
+                          return;
+                        }
                         const ok = await onUpdateSettings({
                           ...settings,
                           ai_enabled: nextEnabled,
-                          deepseek_api_key: nextKey,
+                          ai_api_key_set:
+                            settings.ai_api_key_set || Boolean(nextKey),
                           ai_model: nextModel,
                           ai_prompt: aiPromptDraft,
                         });
                         if (!ok) return;
-                        setDeepseekKeyDraft(nextKey);
-                        setAiModelDraft(nextModel || "deepseek-chat");
+                        setAiModelDraft(nextModel || provider.model);
                       }}
                     />
-                    <span className="settings-status">
-                      {t("settings.ai.vendor")}
-                    </span>
                   </div>
                   {settings.ai_enabled && (
                     <>
                       <div className="settings-row">
-                        <label>{t("settings.ai.model")}</label>
+                        <label>{t("settings.ai.provider")}</label>
                         <select
-                          value={aiModelDraft}
+                          value={settings.ai_provider}
                           onChange={(event) => {
-                            const nextModel = event.currentTarget.value;
-                            const fallbackModel = settings.ai_model;
-                            setAiModelDraft(nextModel);
+                            const nextProvider = event.currentTarget
+                              .value as AiProviderKind;
+                            setApiKeyDraft("");
+                            // Blank model and endpoint: the backend fills in the new defaults.
                             void onUpdateSettings({
                               ...settings,
-                              ai_model: nextModel,
-                            }).then((ok) => {
-                              if (ok) return;
-                              setAiModelDraft(fallbackModel);
+                              ai_provider: nextProvider,
+                              ai_model: "",
+                              ai_base_url: "",
                             });
                           }}
                         >
-                          <option value="deepseek-chat">
-                            {t("settings.ai.modelDeepseekChat")}
-                          </option>
-                          <option value="deepseek-reasoner">
-                            {t("settings.ai.modelDeepseekReasoner")}
-                          </option>
+                          {(Object.keys(AI_PROVIDERS) as AiProviderKind[]).map(
+                            (kind) => (
+                              <option key={kind} value={kind}>
+                                {t(AI_PROVIDERS[kind].labelKey)}
+                              </option>
+                            ),
+                          )}
                         </select>
                       </div>
                       <div className="settings-row">
-                        <label>{t("settings.ai.apiKey")}</label>
+                        <label>{t("settings.ai.baseUrl")}</label>
                         <input
-                          type="password"
-                          value={deepseekKeyDraft}
-                          placeholder={t("settings.ai.apiKeyPlaceholder")}
+                          type="url"
+                          value={aiBaseUrlDraft}
+                          placeholder={AI_PROVIDERS[settings.ai_provider].baseUrl}
                           onChange={(event) =>
-                            setDeepseekKeyDraft(event.target.value)
+                            setAiBaseUrlDraft(event.target.value)
                           }
                           onBlur={() => {
-                            const nextKey = deepseekKeyDraft.trim();
-                            const fallbackKey = settings.deepseek_api_key;
-                            if (nextKey === settings.deepseek_api_key) return;
+                            const nextUrl = aiBaseUrlDraft.trim();
+                            if (nextUrl === settings.ai_base_url) return;
                             void onUpdateSettings({
                               ...settings,
-                              deepseek_api_key: nextKey,
+                              ai_base_url: nextUrl,
                             }).then((ok) => {
-                              if (ok) {
-                                setDeepseekKeyDraft(nextKey);
-                                return;
-                              }
-                              setDeepseekKeyDraft(fallbackKey);
+                              if (!ok) setAiBaseUrlDraft(settings.ai_base_url);
                             });
                           }}
                           autoComplete="off"
                           spellCheck={false}
                         />
                       </div>
+                      <div className="settings-row">
+                        <label>{t("settings.ai.model")}</label>
+                        {settings.ai_provider !== "deepseek" ? (
+                          <input
+                            value={aiModelDraft}
+                            placeholder={t("settings.ai.modelPlaceholder")}
+                            onChange={(event) =>
+                              setAiModelDraft(event.target.value)
+                            }
+                            onBlur={() => {
+                              const nextModel = aiModelDraft.trim();
+                              if (nextModel === settings.ai_model) return;
+                              void onUpdateSettings({
+                                ...settings,
+                                ai_model: nextModel,
+                              }).then((ok) => {
+                                if (!ok) setAiModelDraft(settings.ai_model);
+                              });
+                            }}
+                            autoComplete="off"
+                            spellCheck={false}
+                          />
+                        ) : (
+                          <select
+                            value={aiModelDraft}
+                            onChange={(event) => {
+                              const nextModel = event.currentTarget.value;
+                              const fallbackModel = settings.ai_model;
+                              setAiModelDraft(nextModel);
+                              void onUpdateSettings({
+                                ...settings,
+                                ai_model: nextModel,
+                              }).then((ok) => {
+                                if (ok) return;
+                                setAiModelDraft(fallbackModel);
+                              });
+                            }}
+                          >
+                            <option value="deepseek-chat">
+                              {t("settings.ai.modelDeepseekChat")}
+                            </option>
+                            <option value="deepseek-reasoner">
+                              {t("settings.ai.modelDeepseekReasoner")}
+                            </option>
+                          </select>
+                        )}
+                      </div>
+                      {AI_PROVIDERS[settings.ai_provider].secret && (
+                        <div className="settings-row">
+                          <label>{t("settings.ai.apiKey")}</label>
+                          <input
+                            type="password"
+                            value={apiKeyDraft}
+                            placeholder={
+                              settings.ai_api_key_set
+                                ? t("settings.ai.apiKeyStored")
+                                : t("settings.ai.apiKeyPlaceholder")
+                            }
+                            onChange={(event) =>
+                              setApiKeyDraft(event.target.value)
+                            }
+                            onBlur={() => {
+                              const nextKey = apiKeyDraft.trim();
+                              if (!nextKey) return;
+                              void saveApiKey(nextKey);
+                            }}
+                            autoComplete="off"
+                            spellCheck={false}
+                          />
+                          {settings.ai_api_key_set && (
+                            <button
+                              type="button"
+                              className="pill"
+                              onClick={() => void saveApiKey("")}
+                            >
+                              {t("settings.ai.apiKeyClear")}
+                            </button>
+                          )}
+                        </div>
+                      )}
 
                       <div className="settings-row settings-row-multiline">
                         <label>{t("settings.ai.prompt")}</label>
@@ -1212,34 +1847,176 @@ export function SettingsView({
                       </option>
                     </select>
                   </div>
-                </div>
-              </section>
-
-              <section id="settings-backups" className="settings-card">
-                <div className="settings-card-header">
-                  <h2 className="settings-card-title">
-                    {t("settings.section.backups")}
-                  </h2>
-                </div>
-                <div className="settings-card-body">
                   <div className="settings-row">
-                    <label>{t("settings.backup")}</label>
+                    <label>{t("settings.reminderDigestThreshold")}</label>
                     <select
-                      value={settings.backup_schedule}
+                      value={settings.reminder_digest_threshold ?? 5}
                       onChange={(event) =>
                         void onUpdateSettings({
                           ...settings,
-                          backup_schedule: event.currentTarget
-                            .value as BackupSchedule,
+                          reminder_digest_threshold: Number(
+                            event.currentTarget.value,
+                          ),
                         })
                       }
                     >
-                      <option value="none">{t("settings.backup.none")}</option>
-                      <option value="daily">
-                        {t("settings.backup.daily")}
+                      <option value={0}>
+                        {t("settings.reminderDigestThreshold.never")}
                       </option>
-                      <option value="weekly">
-                        {t("settings.backup.weekly")}
+                      {[3, 5, 10].map((count) => (
+                        <option key={count} value={count}>
+                          {t("settings.reminderDigestThreshold.count", {
+                            count,
+                          })}
+                        </option>
+                      ))}
+                    </select>
+                  </div>
+                  <div className="settings-row">
+                    <label>{t("settings.reminderEscalateAfter")}</label>
+                    <select
+                      value={settings.reminder_escalate_after ?? 0}
+                      onChange={(event) =>
+                        void onUpdateSettings({
+                          ...settings,
+                          reminder_escalate_after: Number(
+                            event.currentTarget.value,
+                          ),
+                        })
+                      }
+                    >
+                      <option value={0}>
+                        {t("settings.reminderEscalateAfter.never")}
+                      </option>
+                      {[2, 3, 5].map((count) => (
+                        <option key={count} value={count}>
+                          {t("reminder.escalate.after", { count })}
+                        </option>
+                      ))}
+                    </select>
+                  </div>
+                  <div className="settings-row">
+                    <label>{t("settings.focusEndForced")}</label>
+                    <Switch
+                      checked={settings.focus_end_forced_reminder ?? false}
+                      ariaLabel={t("settings.focusEndForced")}
+                      onChange={(nextEnabled) =>
+                        void onUpdateSettings({
+                          ...settings,
+                          focus_end_forced_reminder: nextEnabled,
+                        })
+                      }
+                    />
+                  </div>
+                  <div className="settings-row">
+                    <label>{t("settings.dnd")}</label>
+                    <Switch
+                      checked={settings.dnd_enabled ?? false}
+                      ariaLabel={t("settings.dnd")}
+                      onChange={(nextEnabled) =>
+                        void onUpdateSettings({
+                          ...settings,
+                          dnd_enabled: nextEnabled,
+                        })
+                      }
+                    />
+                  </div>
+                  <div className="settings-row">
+                    <label>{t("settings.quietHours")}</label>
+                    <Switch
+                      checked={settings.quiet_hours_enabled ?? false}
+                      ariaLabel={t("settings.quietHours")}
+                      onChange={(nextEnabled) =>
+                        void onUpdateSettings({
+                          ...settings,
+                          quiet_hours_enabled: nextEnabled,
+                        })
+                      }
+                    />
+                    <input
+                      type="time"
+                      value={settings.quiet_hours_start ?? "23:00"}
+                      aria-label={t("settings.quietHours.start")}
+                      disabled={!settings.quiet_hours_enabled}
+                      onChange={(event) => {
+                        const value = event.currentTarget.value;
+                        if (!value) return;
+                        void onUpdateSettings({
+                          ...settings,
+                          quiet_hours_start: value,
+                        });
+                      }}
+                    />
+                    <input
+                      type="time"
+                      value={settings.quiet_hours_end ?? "08:00"}
+                      aria-label={t("settings.quietHours.end")}
+                      disabled={!settings.quiet_hours_enabled}
+                      onChange={(event) => {
+                        const value = event.currentTarget.value;
+                        if (!value) return;
+                        void onUpdateSettings({
+                          ...settings,
+                          quiet_hours_end: value,
+                        });
+                      }}
+                    />
+                  </div>
+                  <div className="settings-row">
+                    <label>{t("settings.dailySummary")}</label>
+                    <Switch
+                      checked={settings.daily_summary_enabled ?? false}
+                      ariaLabel={t("settings.dailySummary")}
+                      onChange={(nextEnabled) =>
+                        void onUpdateSettings({
+                          ...settings,
+                          daily_summary_enabled: nextEnabled,
+                        })
+                      }
+                    />
+                    <input
+                      type="time"
+                      value={settings.daily_summary_time ?? "18:00"}
+                      aria-label={t("settings.dailySummary.time")}
+                      disabled={!settings.daily_summary_enabled}
+                      onChange={(event) => {
+                        const value = event.currentTarget.value;
+                        if (!value) return;
+                        void onUpdateSettings({
+                          ...settings,
+                          daily_summary_time: value,
+                        });
+                      }}
+                    />
+                  </div>
+                </div>
+              </section>
+
+              <section id="settings-backups" className="settings-card">
+                <div className="settings-card-header">
+                  <h2 className="settings-card-title">
+                    {t("settings.section.backups")}
+                  </h2>
+                </div>
+                <div className="settings-card-body">
+                  <div className="settings-row">
+                    <label>{t("settings.backup")}</label>
+                    <select
+                      value={settings.backup_schedule}
+                      onChange={(event) =>
+                        void onUpdateSettings({
+                          ...settings,
+                          backup_schedule: event.currentTarget
+                            .value as BackupSchedule,
+                        })
+                      }
+                    >
+                      <option value="none">{t("settings.backup.none")}</option>
+                      <option value="daily">
+                        {t("settings.backup.daily")}
+                      </option>
+                      <option value="weekly">
+                        {t("settings.backup.weekly")}
                       </option>
                       <option value="monthly">
                         {t("settings.backup.monthly")}
@@ -1253,6 +2030,95 @@ export function SettingsView({
                       {t("settings.backup.manual")}
                     </button>
                   </div>
+                  <div className="settings-row">
+                    <label>{t("settings.backup.retention")}</label>
+                    <select
+                      value={settings.backup_retention ?? 5}
+                      aria-label={t("settings.backup.retention")}
+                      onChange={(event) =>
+                        void onUpdateSettings({
+                          ...settings,
+                          backup_retention: Number(event.currentTarget.value),
+                        })
+                      }
+                    >
+                      {backupRetentionOptions.map((count) => (
+                        <option key={count} value={count}>
+                          {t("settings.backup.retentionCount", { count })}
+                        </option>
+                      ))}
+                    </select>
+                  </div>
+                  <div className="settings-row settings-row-multiline">
+                    <label>{t("settings.backup.directory")}</label>
+                    <div className="backup-import">
+                      <button
+                        type="button"
+                        className="pill"
+                        onClick={() => void handleChooseBackupDirectory()}
+                      >
+                        {t("settings.backup.directoryChoose")}
+                      </button>
+                      {settings.backup_directory ? (
+                        <button
+                          type="button"
+                          className="pill"
+                          onClick={() =>
+                            void onUpdateSettings({
+                              ...settings,
+                              backup_directory: "",
+                            })
+                          }
+                        >
+                          {t("settings.backup.directoryClear")}
+                        </button>
+                      ) : null}
+                      <span className="settings-status">
+                        {settings.backup_directory ||
+                          t("settings.backup.directoryNone")}
+                      </span>
+                    </div>
+                  </div>
+                  <div className="settings-row">
+                    <label>{t("settings.backup.compress")}</label>
+                    <Switch
+                      checked={settings.backup_compress ?? false}
+                      ariaLabel={t("settings.backup.compress")}
+                      onChange={(nextEnabled) =>
+                        void onUpdateSettings({
+                          ...settings,
+                          backup_compress: nextEnabled,
+                        })
+                      }
+                    />
+                  </div>
+                  <div className="settings-row">
+                    <label>{t("settings.storageEngine")}</label>
+                    <select
+                      value={settings.storage_engine ?? "json"}
+                      onChange={(event) => {
+                        const storage_engine = event.currentTarget
+                          .value as StorageEngine;
+                        void onUpdateSettings({
+                          ...settings,
+                          storage_engine,
+                        }).then((ok) => {
+                          // The old data is kept as a backup when switching.
+                          if (ok) void refreshBackups();
+                        });
+                      }}
+                    >
+                      <option value="json">
+                        {t("settings.storageEngine.json")}
+                      </option>
+                      <option value="sqlite">
+                        {t("settings.storageEngine.sqlite")}
+                      </option>
+                    </select>
+                    <span className="settings-status">
+                      {t("settings.storageEngine.hint")}
+                    </span>
+                  </div>
                   <div className="settings-row">
                     <label>{t("settings.backup.list")}</label>
                     <button
@@ -1274,7 +2140,8 @@ export function SettingsView({
                           <div className="backup-info">
                             <div className="backup-name">{backup.name}</div>
                             <div className="backup-meta">
-                              {formatDue(backup.modified_at)}
+                              {formatDue(backup.modified_at)} ·{" "}
+                              {formatBytes(backup.size)}
                             </div>
                           </div>
                           <div className="backup-actions">
@@ -1287,6 +2154,13 @@ export function SettingsView({
                             >
                               {t("settings.backup.restore")}
                             </button>
+                            <button
+                              type="button"
+                              className="pill"
+                              onClick={() => void handleOpenMerge(backup.name)}
+                            >
+                              {t("settings.backup.merge")}
+                            </button>
                             <button
                               type="button"
                               className="pill"
@@ -1301,6 +2175,67 @@ export function SettingsView({
                       ))
                     )}
                   </div>
+                  {mergeSource && (
+                    <div className="backup-merge">
+                      <div className="backup-name">
+                        {t("settings.backup.mergeTitle", {
+                          name: mergeSource.name,
+                        })}
+                      </div>
+                      {mergeSource.preview.tasks.length === 0 &&
+                      mergeSource.preview.projects.length === 0 ? (
+                        <div className="backup-empty">
+                          {t("settings.backup.mergeEmpty")}
+                        </div>
+                      ) : (
+                        [
+                          ...mergeSource.preview.projects.map((row) => ({
+                            key: `project:${row.project.id}`,
+                            label: row.project.name,
+                            status: row.status,
+                          })),
+                          ...mergeSource.preview.tasks.map((row) => ({
+                            key: `task:${row.task.id}`,
+                            label: row.task.title,
+                            status: row.status,
+                          })),
+                        ].map((row) => (
+                          <label key={row.key} className="backup-merge-row">
+                            <input
+                              type="checkbox"
+                              checked={mergeSelection.has(row.key)}
+                              onChange={() => toggleMergeRow(row.key)}
+                            />
+                            <span className="backup-merge-label">
+                              {row.label}
+                            </span>
+                            <span className="backup-meta">
+                              {t(`settings.backup.mergeStatus.${row.status}`)}
+                            </span>
+                          </label>
+                        ))
+                      )}
+                      <div className="backup-actions">
+                        <button
+                          type="button"
+                          className="pill"
+                          disabled={mergeSelection.size === 0}
+                          onClick={() => void handleApplyMerge()}
+                        >
+                          {t("settings.backup.mergeApply", {
+                            count: mergeSelection.size,
+                          })}
+                        </button>
+                        <button
+                          type="button"
+                          className="pill"
+                          onClick={() => setMergeSource(null)}
+                        >
+                          {t("common.cancel")}
+                        </button>
+                      </div>
+                    </div>
+                  )}
                   <div className="settings-row settings-row-multiline">
                     <label>{t("settings.backup.import")}</label>
                     <div className="backup-import">
@@ -1316,6 +2251,99 @@ export function SettingsView({
                       </span>
                     </div>
                   </div>
+                  <div className="settings-row">
+                    <label>{t("settings.backup.external")}</label>
+                    <button
+                      type="button"
+                      className="pill"
+                      onClick={() => void handleImportExternal()}
+                    >
+                      {t("settings.backup.externalAction")}
+                    </button>
+                  </div>
+                  <div className="settings-row settings-row-multiline">
+                    <label>{t("settings.backup.markdown")}</label>
+                    <div>
+                      <textarea
+                        className="settings-textarea"
+                        value={markdownDraft}
+                        placeholder={t("settings.backup.markdownPlaceholder")}
+                        aria-label={t("settings.backup.markdown")}
+                        onChange={(event) =>
+                          setMarkdownDraft(event.target.value)
+                        }
+                        rows={6}
+                      />
+                      <div className="backup-import">
+                        <select
+                          value={markdownHeadings}
+                          aria-label={t("settings.backup.markdownHeadings")}
+                          onChange={(event) =>
+                            setMarkdownHeadings(
+                              event.currentTarget.value as MarkdownHeadings,
+                            )
+                          }
+                        >
+                          <option value="project">
+                            {t("settings.backup.markdownHeadingsProject")}
+                          </option>
+                          <option value="tag">
+                            {t("settings.backup.markdownHeadingsTag")}
+                          </option>
+                        </select>
+                        <button
+                          type="button"
+                          className="pill"
+                          disabled={!markdownDraft.trim()}
+                          onClick={() => void handleImportMarkdown()}
+                        >
+                          {t("settings.backup.markdownAction")}
+                        </button>
+                      </div>
+                    </div>
+                  </div>
+                  <div className="settings-row">
+                    <label>{t("settings.trash.retention")}</label>
+                    <select
+                      value={settings.trash_retention_days ?? 30}
+                      aria-label={t("settings.trash.retention")}
+                      onChange={(event) =>
+                        void onUpdateSettings({
+                          ...settings,
+                          trash_retention_days: Number(event.currentTarget.value),
+                        })
+                      }
+                    >
+                      {[7, 30, 90, 0].map((days) => (
+                        <option key={days} value={days}>
+                          {days === 0
+                            ? t("settings.trash.keepForever")
+                            : t("settings.trash.days", { days })}
+                        </option>
+                      ))}
+                    </select>
+                  </div>
+                  <div className="settings-row">
+                    <label>{t("settings.archive.after")}</label>
+                    <select
+                      value={settings.archive_after_days ?? 90}
+                      aria-label={t("settings.archive.after")}
+                      onChange={(event) =>
+                        void onUpdateSettings({
+                          ...settings,
+                          archive_after_days: Number(event.currentTarget.value),
+                        })
+                      }
+                    >
+                      {[30, 90, 365, 0].map((days) => (
+                        <option key={days} value={days}>
+                          {days === 0
+                            ? t("settings.archive.never")
+                            : t("settings.archive.days", { days })}
+                        </option>
+                      ))}
+                    </select>
+                  </div>
                 </div>
               </section>
 
@@ -1328,6 +2356,20 @@ export function SettingsView({
                 <div className="settings-card-body">
                   <div className="settings-row">
                     <label>{t("settings.export")}</label>
+                    <select
+                      value={exportProjectId}
+                      aria-label={t("settings.export.project")}
+                      onChange={(event) =>
+                        setExportProjectId(event.currentTarget.value)
+                      }
+                    >
+                      <option value="">{t("settings.export.allProjects")}</option>
+                      {projects.map((project) => (
+                        <option key={project.id} value={project.id}>
+                          {project.name}
+                        </option>
+                      ))}
+                    </select>
                     <button
                       type="button"
                       className="pill"
@@ -1352,12 +2394,90 @@ export function SettingsView({
                     >
                       {t("settings.export.md")}
                     </button>
+                    <button
+                      type="button"
+                      className="pill"
+                      onClick={() => void handleExport("ics")}
+                      disabled={exportBusy}
+                    >
+                      {t("settings.export.ics")}
+                    </button>
+                    <button
+                      type="button"
+                      className="pill"
+                      onClick={() => void handleYearReviewExport()}
+                      disabled={exportBusy}
+                    >
+                      {t("settings.export.yearReview")}
+                    </button>
                     {exportError && (
                       <span className="settings-status danger">
                         {t("settings.export.failed", { error: exportError })}
                       </span>
                     )}
                   </div>
+                  <div className="settings-row">
+                    <label>{t("settings.export.mdSync")}</label>
+                    <Switch
+                      checked={settings.markdown_sync_enabled ?? false}
+                      ariaLabel={t("settings.export.mdSync")}
+                      onChange={(nextEnabled) => {
+                        void onUpdateSettings({
+                          ...settings,
+                          markdown_sync_enabled: nextEnabled,
+                        });
+                        if (nextEnabled) void handleMarkdownSyncExport();
+                      }}
+                    />
+                    <button
+                      type="button"
+                      className="pill"
+                      onClick={() => void handleMarkdownSyncExport()}
+                      disabled={exportBusy || !settings.markdown_sync_enabled}
+                    >
+                      {t("settings.export.mdSyncRewrite")}
+                    </button>
+                    <span className="settings-status">
+                      {t("settings.export.mdSyncHint")}
+                    </span>
+                  </div>
+                  <div className="settings-row">
+                    <label>{t("settings.export.obsidian")}</label>
+                    <input
+                      type="text"
+                      value={settings.obsidian_daily_folder ?? "Daily"}
+                      aria-label={t("settings.export.obsidianFolder")}
+                      placeholder={t("settings.export.obsidianFolder")}
+                      onChange={(event) =>
+                        void onUpdateSettings({
+                          ...settings,
+                          obsidian_daily_folder: event.currentTarget.value,
+                        })
+                      }
+                    />
+                    <button
+                      type="button"
+                      className="pill"
+                      onClick={() => void handleObsidianExport(false)}
+                      disabled={exportBusy}
+                    >
+                      {t("settings.export.obsidianRun")}
+                    </button>
+                    {settings.obsidian_vault_path && (
+                      <button
+                        type="button"
+                        className="pill"
+                        onClick={() => void handleObsidianExport(true)}
+                        disabled={exportBusy}
+                      >
+                        {t("settings.export.obsidianVault")}
+                      </button>
+                    )}
+                    <span className="settings-status">
+                      {settings.obsidian_vault_path ||
+                        t("settings.export.obsidianHint")}
+                    </span>
+                  </div>
                   {exportPath && (
                     <div className="settings-row">
                       <label>{t("settings.export.last")}</label>
@@ -1374,6 +2494,116 @@ export function SettingsView({
                 </div>
               </section>
 
+              <section id="settings-sync" className="settings-card">
+                <div className="settings-card-header">
+                  <h2 className="settings-card-title">
+                    {t("settings.section.sync")}
+                  </h2>
+                </div>
+                <div className="settings-card-body">
+                  <div className="settings-row">
+                    <label>{t("settings.sync.url")}</label>
+                    <input
+                      type="url"
+                      value={settings.caldav_url ?? ""}
+                      aria-label={t("settings.sync.url")}
+                      placeholder="https://example.com/dav/calendars/me/tasks/"
+                      onChange={(event) =>
+                        void onUpdateSettings({
+                          ...settings,
+                          caldav_url: event.currentTarget.value,
+                        })
+                      }
+                    />
+                  </div>
+                  <div className="settings-row">
+                    <label>{t("settings.sync.username")}</label>
+                    <input
+                      type="text"
+                      value={settings.caldav_username ?? ""}
+                      aria-label={t("settings.sync.username")}
+                      onChange={(event) =>
+                        void onUpdateSettings({
+                          ...settings,
+                          caldav_username: event.currentTarget.value,
+                        })
+                      }
+                    />
+                  </div>
+                  <div className="settings-row">
+                    <label>{t("settings.sync.password")}</label>
+                    <input
+                      type="password"
+                      value={caldavPasswordDraft}
+                      aria-label={t("settings.sync.password")}
+                      placeholder={t("settings.sync.passwordPlaceholder")}
+                      onChange={(event) =>
+                        setCaldavPasswordDraft(event.currentTarget.value)
+                      }
+                      onBlur={() => void handleCaldavPasswordSave()}
+                    />
+                  </div>
+                  <div className="settings-row">
+                    <label>{t("settings.sync.auto")}</label>
+                    <Switch
+                      checked={settings.caldav_enabled ?? false}
+                      ariaLabel={t("settings.sync.auto")}
+                      onChange={(nextEnabled) =>
+                        void onUpdateSettings({
+                          ...settings,
+                          caldav_enabled: nextEnabled,
+                        })
+                      }
+                    />
+                    <select
+                      value={settings.caldav_sync_interval_minutes ?? 30}
+                      aria-label={t("settings.sync.interval")}
+                      disabled={!settings.caldav_enabled}
+                      onChange={(event) =>
+                        void onUpdateSettings({
+                          ...settings,
+                          caldav_sync_interval_minutes: Number(
+                            event.currentTarget.value,
+                          ),
+                        })
+                      }
+                    >
+                      {[5, 15, 30, 60, 240, 1440].map((minutes) => (
+                        <option key={minutes} value={minutes}>
+                          {t("settings.sync.everyMinutes", { minutes })}
+                        </option>
+                      ))}
+                    </select>
+                  </div>
+                  <div className="settings-row">
+                    <label>{t("settings.sync.now")}</label>
+                    <button
+                      type="button"
+                      className="pill"
+                      onClick={() => void handleSyncNow()}
+                      disabled={syncBusy || !settings.caldav_url}
+                    >
+                      {syncBusy
+                        ? t("settings.sync.running")
+                        : t("settings.sync.now")}
+                    </button>
+                    {syncError ? (
+                      <span className="settings-status danger">
+                        {t("settings.sync.failed", { error: syncError })}
+                      </span>
+                    ) : (
+                      <span className="settings-status">
+                        {settings.last_caldav_sync_at
+                          ? t("settings.sync.last", {
+                              time: formatDue(settings.last_caldav_sync_at),
+                            })
+                          : t("settings.sync.hint")}
+                      </span>
+                    )}
+                  </div>
+                </div>
+              </section>
+
               <section id="settings-samples" className="settings-card">
                 <div className="settings-card-header">
                   <h2 className="settings-card-title">
@@ -7,6 +7,7 @@ import { WindowTitlebar } from "../components/WindowTitlebar";
 import { Icons } from "../components/icons";
 import { describeError, frontendLog } from "../frontendLog";
 import { useI18n } from "../i18n";
+import { dueSortKey } from "../scheduler";
 import { taskMatchesQuery } from "../search";
 import type { Settings, Task } from "../types";
 
@@ -56,7 +57,7 @@ export function TodayView({
     const list = tasks
       .filter((task) => !task.completed)
       .slice()
-      .sort((a, b) => a.due_at - b.due_at || a.created_at - b.created_at);
+      .sort((a, b) => dueSortKey(a) - dueSortKey(b) || a.created_at - b.created_at);
     return list.filter((task) => taskMatchesQuery(task, focusSearch));
   }, [tasks, focusSearch]);
 
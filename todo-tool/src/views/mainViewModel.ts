@@ -1,4 +1,4 @@
-import { isDueThisWeek, isDueToday, isOverdue } from "../scheduler";
+import { dueSortKey, isDueThisWeek, isDueToday, isOverdue, isScheduled } from "../scheduler";
 import { taskMatchesQuery } from "../search";
 import type { Task } from "../types";
 
@@ -49,14 +49,15 @@ export function filterTasksByScope(
   scope: MainScope,
   now: Date,
 ): Task[] {
+  const nowSeconds = Math.floor(now.getTime() / 1000);
   if (scope.kind === "today") {
-    const nowSeconds = Math.floor(now.getTime() / 1000);
     return tasks.filter(
-      (task) => isDueToday(task, now) || isOverdue(task, nowSeconds),
+      (task) =>
+        !isScheduled(task, nowSeconds) && (isDueToday(task, now) || isOverdue(task, nowSeconds)),
     );
   }
   if (scope.kind === "week") {
-    return tasks.filter((task) => isDueThisWeek(task, now));
+    return tasks.filter((task) => !isScheduled(task, nowSeconds) && isDueThisWeek(task, now));
   }
   if (scope.kind === "important") {
     return tasks.filter((task) => task.important);
@@ -80,18 +81,18 @@ export function sortTasksWithPinnedImportant(
 
     if (sort === "created") {
       if (a.created_at !== b.created_at) return a.created_at - b.created_at;
-      if (a.due_at !== b.due_at) return a.due_at - b.due_at;
+      if (a.due_at !== b.due_at) return dueSortKey(a) - dueSortKey(b);
       return a.id.localeCompare(b.id);
     }
 
     if (sort === "manual") {
       if (a.sort_order !== b.sort_order) return a.sort_order - b.sort_order;
-      if (a.due_at !== b.due_at) return a.due_at - b.due_at;
+      if (a.due_at !== b.due_at) return dueSortKey(a) - dueSortKey(b);
       return a.id.localeCompare(b.id);
     }
 
     // "due" (default)
-    if (a.due_at !== b.due_at) return a.due_at - b.due_at;
+    if (a.due_at !== b.due_at) return dueSortKey(a) - dueSortKey(b);
     if (a.created_at !== b.created_at) return a.created_at - b.created_at;
     return a.id.localeCompare(b.id);
   });